@@ -1,3 +1,5 @@
 fn main() {
+    // The Tauri build step is only needed for the desktop app
+    #[cfg(feature = "gui")]
     tauri_build::build()
 }
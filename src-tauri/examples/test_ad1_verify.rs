@@ -3,6 +3,7 @@
 //! Usage: cargo run --example test_ad1_verify -- /path/to/file.ad1
 
 use ffx_check_lib::ad1;
+use ffx_check_lib::containers::VerifyStatus;
 use std::env;
 use tracing_subscriber::EnvFilter;
 
@@ -61,9 +62,9 @@ fn main() -> Result<(), String> {
         Ok(results) => {
             println!("\nVerification complete. {} items checked.", results.len());
             
-            let passed: Vec<_> = results.iter().filter(|r| r.status == "ok").collect();
-            let failed: Vec<_> = results.iter().filter(|r| r.status == "mismatch").collect();
-            let skipped: Vec<_> = results.iter().filter(|r| r.status == "skipped" || r.status == "no_hash").collect();
+            let passed: Vec<_> = results.iter().filter(|r| r.status == VerifyStatus::Ok).collect();
+            let failed: Vec<_> = results.iter().filter(|r| r.status == VerifyStatus::Mismatch).collect();
+            let skipped: Vec<_> = results.iter().filter(|r| r.status == VerifyStatus::Skipped).collect();
             
             println!("\nSummary:");
             println!("  Passed: {}", passed.len());
@@ -73,9 +74,9 @@ fn main() -> Result<(), String> {
             if !failed.is_empty() {
                 println!("\nFailed items:");
                 for r in &failed {
-                    println!("  - {}", r.path);
+                    println!("  - {}", r.target_path().unwrap_or_default());
                     println!("    Algorithm: {:?}", r.algorithm);
-                    println!("    Stored:   {:?}", r.stored);
+                    println!("    Stored:   {:?}", r.expected);
                     println!("    Computed: {:?}", r.computed);
                     println!("    Size: {:?} bytes", r.size);
                 }
@@ -85,7 +86,7 @@ fn main() -> Result<(), String> {
             if !passed.is_empty() && std::env::var("SHOW_PASSED").is_ok() {
                 println!("\nPassed items (first 5):");
                 for r in passed.iter().take(5) {
-                    println!("  - {}", r.path);
+                    println!("  - {}", r.target_path().unwrap_or_default());
                     println!("    Computed: {:?}", r.computed);
                 }
             }
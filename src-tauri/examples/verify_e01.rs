@@ -0,0 +1,52 @@
+// Command-line E01 verifier built on the library API (no Tauri needed)
+// Run with: cargo run --no-default-features --example verify_e01 -- <image.E01> [algorithm]
+use ffx_check_lib::{common::hash::hashes_match, ewf, verification};
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let Some(path) = args.next() else {
+        eprintln!("Usage: verify_e01 <image.E01> [algorithm]");
+        std::process::exit(2);
+    };
+    let algorithm = args.next().unwrap_or_else(|| "md5".to_string());
+
+    let info = match ewf::info(&path) {
+        Ok(info) => info,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    };
+    println!("Image:     {}", path);
+    println!("Segments:  {}", info.segment_count);
+    println!("Size:      {} bytes", info.total_size);
+
+    let mut last_percent = 0;
+    let result = verification::verify_auto(&path, "E01", &algorithm, |current, total| {
+        let percent = if total > 0 { current * 100 / total } else { 0 };
+        if percent >= last_percent + 10 {
+            eprintln!("  {:>3}%", percent);
+            last_percent = percent;
+        }
+    });
+
+    let computed = match result {
+        Ok(hash) => hash,
+        Err(e) => {
+            eprintln!("Verification failed: {}", e);
+            std::process::exit(1);
+        }
+    };
+    println!("{}: {}", algorithm.to_uppercase(), computed);
+
+    let stored = info.stored_hashes.iter()
+        .find(|h| h.algorithm.replace('-', "").eq_ignore_ascii_case(&algorithm.replace('-', "")));
+    match stored {
+        Some(stored) if hashes_match(&stored.hash, &computed) => println!("Matches stored hash"),
+        Some(stored) => {
+            println!("MISMATCH - stored hash is {}", stored.hash);
+            std::process::exit(1);
+        }
+        None => println!("No stored {} hash to compare against", algorithm.to_uppercase()),
+    }
+}
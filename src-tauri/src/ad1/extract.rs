@@ -0,0 +1,776 @@
+//! Parallel AD1 extraction
+//!
+//! The calling thread walks the parsed item tree, applies the filter and
+//! creates every output directory up front, so workers never race on
+//! directory creation. File items are queued in batches (small files are
+//! grouped so millions of tiny items don't cost one queue round-trip each)
+//! and a pool of workers reads their chunks through one shared `FileIoPool`,
+//! inflates and writes them. Manifest rows are stored by plan index, so the
+//! manifest lists files in tree order whatever order they finish in.
+//! Alternate streams written into native NTFS streams go last, on the calling
+//! thread, once the files holding them exist. With a deduplication index,
+//! files whose content is already in the output are skipped or linked.
+
+use std::fs::{self, File};
+use std::io::{SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Mutex};
+use tracing::{debug, trace, warn};
+
+use super::parser::{read_item_data, unreadable_file, Session};
+use super::types::*;
+use super::utils::*;
+use crate::common::atomic_file::AtomicFile;
+use crate::common::extract_filter::{ExtractFilter, ExtractMatcher, ExtractProgress, ExtractStats, UnreadableFile};
+use crate::common::dedupe::DedupeIndex;
+use crate::common::extract_manifest::{ExtractedFile, HashingWriter};
+use crate::common::io_pool::FileIoPool;
+use crate::common::safe_path::{join_sanitized, sanitize_component, PathChange, SanitizedPath};
+use crate::common::volume::volume_info;
+
+/// Worker threads used when the caller does not choose
+pub const DEFAULT_EXTRACT_WORKERS: usize = 4;
+/// Upper bound on worker threads (more only contend for the segment pool)
+pub const MAX_EXTRACT_WORKERS: usize = 16;
+/// Small files are batched until a batch holds this many bytes...
+const BATCH_BYTES: u64 = 8 * 1024 * 1024;
+/// ...or this many files
+const BATCH_FILES: usize = 256;
+
+/// One file item to extract
+struct FileJob {
+    /// Position in tree order, used to order the manifest
+    index: usize,
+    item: Item,
+    logical_path: String,
+    output_path: PathBuf,
+    changes: Vec<PathChange>,
+    /// An alternate stream written into `output_path`, a native NTFS stream
+    native_stream: bool,
+}
+
+/// Everything the tree walk decided before any file is written
+#[derive(Default)]
+struct ExtractPlan {
+    files: Vec<FileJob>,
+    /// Created folders and their metadata, in tree (pre-)order
+    folders: Vec<(PathBuf, Vec<Metadata>)>,
+    bytes_total: u64,
+    /// Write alternate streams into native streams rather than sibling files
+    native_streams: bool,
+}
+
+/// What became of one queued file
+enum Outcome {
+    Written(ExtractedFile),
+    /// Best-effort extraction only: the data could not be read, nothing was written
+    Unreadable(UnreadableFile),
+}
+
+/// Segment access shared by the extraction workers
+struct SharedSegments {
+    pool: Mutex<FileIoPool>,
+    file_sizes: Vec<u64>,
+    seg_span: u64,
+    logical_size: u64,
+}
+
+impl SharedSegments {
+    fn new(path: &str, session: &Session) -> Self {
+        let paths = (1..=session.segment_header.segment_number)
+            .map(|index| PathBuf::from(build_segment_path(path, index)))
+            .collect();
+        Self {
+            pool: Mutex::new(FileIoPool::with_default_limit(paths)),
+            file_sizes: session.file_sizes.clone(),
+            seg_span: segment_span(session.segment_header.fragments_size),
+            logical_size: session.logical_size(),
+        }
+    }
+
+    /// Read bytes at a logical offset, holding the pool only for the read
+    fn read_bytes(&self, offset: u64, length: usize) -> Result<Vec<u8>, String> {
+        if offset.saturating_add(length as u64) > self.logical_size {
+            return Err(format!("Corrupt AD1 structure: {length} bytes at offset {offset} run past the end of the container"));
+        }
+        let mut buf = vec![0u8; length];
+        let reads = segment_reads(offset, length, self.seg_span, &self.file_sizes)?;
+        let mut pool = self.pool.lock().map_err(|_| "AD1 segment pool poisoned".to_string())?;
+        for (file_index, data_offset, range) in reads {
+            let file = pool.get_file(file_index)?;
+            file.seek(SeekFrom::Start(data_offset + AD1_LOGICAL_MARGIN))
+                .map_err(|e| format!("Failed to seek segment data: {e}"))?;
+            file.read_exact(&mut buf[range])
+                .map_err(|e| format!("Failed to read segment data: {e}"))?;
+        }
+        Ok(buf)
+    }
+}
+
+/// Extract the container on `workers` threads, keeping only files selected by `filter`
+///
+/// Produces the same files, stats and (tree-ordered) manifest rows as
+/// `extract_with_progress`. Progress is reported from the calling thread
+/// after each finished file. With `best_effort`, files whose data cannot be
+/// read (corrupt zlib stream, truncated segment) are listed in
+/// `stats.unreadable` and the others are still extracted; without it the
+/// first one aborts the extraction. With `native_streams`, alternate streams
+/// go into native streams of their files when the output is on NTFS.
+/// With `dedupe`, a file whose content is in the index is handled by its
+/// strategy, and every file written is added to it.
+#[allow(clippy::too_many_arguments)]
+pub fn extract_parallel<F>(
+    path: &str,
+    output_dir: &str,
+    filter: &ExtractFilter,
+    workers: usize,
+    best_effort: bool,
+    native_streams: bool,
+    dedupe: Option<&DedupeIndex>,
+    mut progress_callback: F,
+) -> Result<ExtractStats, String>
+where
+    F: FnMut(ExtractProgress),
+{
+    let matcher = filter.compile()?;
+    let session = Session::open(path)?;
+    let output_path = Path::new(output_dir);
+    let mut stats = ExtractStats::default();
+    let mut plan = ExtractPlan { native_streams: native_streams && ntfs_output(output_path), ..Default::default() };
+    for item in &session.root_items {
+        plan_item(item, "", output_path, &matcher, true, &mut stats, &mut plan)?;
+    }
+
+    let workers = workers.clamp(1, MAX_EXTRACT_WORKERS);
+    let chunk_size = session.logical_header.zlib_chunk_size as usize;
+    let segments = SharedSegments::new(path, &session);
+    drop(session);
+
+    let mut progress = ExtractProgress {
+        files_total: plan.files.len(),
+        bytes_total: plan.bytes_total,
+        ..Default::default()
+    };
+    debug!(files = progress.files_total, bytes = progress.bytes_total, workers, "Extracting AD1 in parallel");
+    progress_callback(progress);
+
+    let mut rows: Vec<Option<Outcome>> = Vec::with_capacity(plan.files.len());
+    rows.resize_with(plan.files.len(), || None);
+    let (streams, files): (Vec<FileJob>, Vec<FileJob>) = plan.files.into_iter().partition(|job| job.native_stream);
+    let queue = Mutex::new(batches(files).into_iter());
+    let abort = AtomicBool::new(false);
+    let mut first_error = None;
+
+    std::thread::scope(|scope| {
+        let (tx, rx) = mpsc::channel::<(usize, Result<Outcome, String>)>();
+        for _ in 0..workers {
+            let tx = tx.clone();
+            let (queue, abort, segments) = (&queue, &abort, &segments);
+            scope.spawn(move || {
+                while !abort.load(Ordering::Relaxed) {
+                    let Some(batch) = queue.lock().ok().and_then(|mut queue| queue.next()) else { break };
+                    for job in batch {
+                        if abort.load(Ordering::Relaxed) {
+                            return;
+                        }
+                        let result = write_file(&job, chunk_size, segments, best_effort, dedupe);
+                        if tx.send((job.index, result)).is_err() {
+                            return;
+                        }
+                    }
+                }
+            });
+        }
+        drop(tx);
+
+        for (index, result) in rx {
+            match result {
+                Ok(outcome) => {
+                    progress.files_done += 1;
+                    if let Outcome::Written(file) = &outcome {
+                        progress.bytes_done += file.size;
+                    }
+                    progress_callback(progress);
+                    rows[index] = Some(outcome);
+                }
+                Err(e) => {
+                    abort.store(true, Ordering::Relaxed);
+                    if first_error.is_none() {
+                        warn!(error = %e, "AD1 extraction worker failed");
+                        first_error = Some(e);
+                    }
+                }
+            }
+        }
+    });
+    if let Some(e) = first_error {
+        return Err(e);
+    }
+    for job in &streams {
+        let outcome = write_file(job, chunk_size, &segments, best_effort, None)?;
+        progress.files_done += 1;
+        if let Outcome::Written(file) = &outcome {
+            progress.bytes_done += file.size;
+        }
+        progress_callback(progress);
+        rows[job.index] = Some(outcome);
+    }
+
+    // Folders get their times once their contents exist, deepest first
+    for (folder, metadata) in plan.folders.iter().rev() {
+        if folder.exists() {
+            apply_metadata(folder, metadata)?;
+        }
+    }
+
+    for outcome in rows.into_iter().flatten() {
+        match outcome {
+            Outcome::Written(file) => stats.files.push(file),
+            Outcome::Unreadable(file) => stats.unreadable.push(file),
+        }
+    }
+    stats.extracted = stats.files.len();
+    stats.total_bytes = stats.files.iter().map(|file| file.size).sum();
+    for file in &stats.files {
+        if let Some(duplicate) = &file.duplicate {
+            stats.deduplicated += 1;
+            stats.deduplicated_bytes += duplicate.saved_bytes(file.size);
+        }
+    }
+    debug!(
+        matched = stats.matched,
+        skipped = stats.skipped,
+        pruned_folders = stats.pruned_folders,
+        unreadable = stats.unreadable.len(),
+        deduplicated = stats.deduplicated,
+        total_bytes = stats.total_bytes,
+        "AD1 parallel extraction complete"
+    );
+    Ok(stats)
+}
+
+/// What `extract_parallel` would write, without creating or writing anything
+///
+/// The stats match the real extraction's; `files` lists the planned output
+/// paths, sizes and name changes, with empty hashes.
+pub fn plan_extraction(path: &str, output_dir: &str, filter: &ExtractFilter, native_streams: bool) -> Result<ExtractStats, String> {
+    let matcher = filter.compile()?;
+    let session = Session::open(path)?;
+    let mut stats = ExtractStats::default();
+    let mut plan = ExtractPlan { native_streams: native_streams && ntfs_output(Path::new(output_dir)), ..Default::default() };
+    for item in &session.root_items {
+        plan_item(item, "", Path::new(output_dir), &matcher, false, &mut stats, &mut plan)?;
+    }
+    stats.files = plan.files.into_iter()
+        .map(|job| ExtractedFile::planned(&job.logical_path, &job.output_path, job.item.decompressed_size)
+            .with_path_changes(job.changes))
+        .collect();
+    stats.extracted = stats.files.len();
+    stats.total_bytes = plan.bytes_total;
+    Ok(stats)
+}
+
+/// Walk one item for the plan, mirroring `Session::extract_item_with_progress`;
+/// output directories are only created with `create_dirs`
+fn plan_item(
+    item: &Item,
+    parent_path: &str,
+    output_dir: &Path,
+    filter: &ExtractMatcher,
+    create_dirs: bool,
+    stats: &mut ExtractStats,
+    plan: &mut ExtractPlan,
+) -> Result<(), String> {
+    let logical_path = child_path(parent_path, item);
+    let (SanitizedPath { path: item_path, changes }, native_stream) =
+        output_target(output_dir, parent_path, item, plan.native_streams);
+    if item.item_type == AD1_FOLDER_SIGNATURE {
+        if filter.prunes_folder(&logical_path) {
+            trace!(path = %logical_path, "Folder excluded by filter");
+            stats.pruned_folders += 1;
+            return Ok(());
+        }
+        // Filtered extractions only create folders that end up holding files
+        if create_dirs && filter.selects_all() {
+            fs::create_dir_all(&item_path)
+                .map_err(|e| format!("Failed to create directory {:?}: {e}", item_path))?;
+        }
+        plan.folders.push((item_path.clone(), item.metadata.clone()));
+    } else if filter.matches_file(&logical_path, item.decompressed_size) {
+        stats.matched += 1;
+        if let Some(parent) = item_path.parent().filter(|_| create_dirs) {
+            fs::create_dir_all(parent).map_err(|e| {
+                format!("Failed to create directory {:?}: {e}", parent)
+            })?;
+        }
+        plan.bytes_total += item.decompressed_size;
+        plan.files.push(FileJob {
+            index: plan.files.len(),
+            item: Item { children: Vec::new(), ..item.clone() },
+            logical_path: logical_path.clone(),
+            output_path: item_path,
+            changes,
+            native_stream,
+        });
+    } else {
+        stats.skipped += 1;
+    }
+
+    for child in &item.children {
+        plan_item(child, &logical_path, output_dir, filter, create_dirs, stats, plan)?;
+    }
+    Ok(())
+}
+
+/// Output path of `item` under `output_dir`, and whether it is a native stream
+///
+/// Files and folders keep their container path. Alternate streams and
+/// attachments belong to a file, which most filesystems can't give children:
+/// they are written next to it as `file__ADS_stream` or `message__ATT_name`,
+/// with the manifest recording the change. With `native_streams` a stream is
+/// written into the NTFS stream of the same name of its extracted file.
+pub(crate) fn output_target(output_dir: &Path, parent_path: &str, item: &Item, native_streams: bool) -> (SanitizedPath, bool) {
+    let (suffix, name, what) = match item.kind {
+        ItemKind::AlternateStream => ("__ADS_", child_name(item), "alternate data stream"),
+        ItemKind::Attachment => ("__ATT_", item.name.as_str(), "attachment"),
+        ItemKind::File | ItemKind::Folder => return (join_sanitized(output_dir, &join_path(parent_path, &item.name)), false),
+    };
+    let (name, name_change) = sanitize_component(name);
+    if native_streams && item.kind == ItemKind::AlternateStream {
+        let SanitizedPath { path, mut changes } = join_sanitized(output_dir, parent_path);
+        let file_name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+        changes.extend(name_change);
+        return (SanitizedPath { path: path.with_file_name(format!("{file_name}:{name}")), changes }, true);
+    }
+
+    let mut sibling = join_sanitized(output_dir, &format!("{parent_path}{suffix}{name}"));
+    sibling.changes.extend(name_change);
+    let original = child_path(parent_path, item);
+    sibling.changes.push(PathChange {
+        original: original.rsplit('/').next().unwrap_or_default().to_string(),
+        sanitized: sibling.path.file_name().unwrap_or_default().to_string_lossy().to_string(),
+        reason: format!("{what} written as a sibling file"),
+    });
+    (sibling, false)
+}
+
+/// Whether native streams can be written under `output_dir`: Windows, NTFS
+fn ntfs_output(output_dir: &Path) -> bool {
+    cfg!(windows) && volume_info(output_dir).is_ok_and(|volume| volume.filesystem.as_deref() == Some("NTFS"))
+}
+
+/// Group consecutive small files; a file of `BATCH_BYTES` or more goes alone
+fn batches(files: Vec<FileJob>) -> Vec<Vec<FileJob>> {
+    let mut batches = Vec::new();
+    let mut current = Vec::new();
+    let mut current_bytes = 0u64;
+    for job in files {
+        let size = job.item.decompressed_size;
+        if !current.is_empty() && (current_bytes + size > BATCH_BYTES || current.len() >= BATCH_FILES) {
+            batches.push(std::mem::take(&mut current));
+            current_bytes = 0;
+        }
+        current_bytes += size;
+        current.push(job);
+    }
+    if !current.is_empty() {
+        batches.push(current);
+    }
+    batches
+}
+
+/// Inflate one file item and write it, hashing the written bytes
+fn write_file(
+    job: &FileJob,
+    chunk_size: usize,
+    segments: &SharedSegments,
+    best_effort: bool,
+    dedupe: Option<&DedupeIndex>,
+) -> Result<Outcome, String> {
+    let read = read_item_data(&job.item, chunk_size, segments.logical_size, &mut |offset, length| {
+        segments.read_bytes(offset, length)
+    });
+    let data = match read {
+        Ok(data) => data,
+        Err(e) if best_effort => {
+            warn!(path = %job.logical_path, error = %e, "Item data unreadable, not extracted");
+            return Ok(Outcome::Unreadable(unreadable_file(&job.logical_path, &e)));
+        }
+        Err(e) => return Err(format!("{}: {e}", job.logical_path)),
+    };
+    if job.native_stream {
+        // Renaming onto a stream is not possible, so it is written in place
+        let file = File::create(&job.output_path)
+            .map_err(|e| format!("Failed to create stream {:?}: {e}", job.output_path))?;
+        let mut writer = HashingWriter::new(file);
+        writer.write_all(&data)
+            .map_err(|e| format!("Failed to write stream {:?}: {e}", job.output_path))?;
+        return Ok(Outcome::Written(writer.finish(&job.logical_path, &job.output_path)?
+            .with_path_changes(job.changes.clone())));
+    }
+    let file = AtomicFile::create(&job.output_path)
+        .map_err(|e| format!("Failed to create file {:?}: {e}", job.output_path))?;
+    let mut writer = HashingWriter::new(file);
+    writer.write_all(&data)
+        .map_err(|e| format!("Failed to write file {:?}: {e}", job.output_path))?;
+    let (accessed, modified) = item_file_times(&job.item.metadata);
+    let written = match dedupe {
+        Some(index) => writer.commit_deduped(&job.logical_path, index)?,
+        None => writer.commit(&job.logical_path)?,
+    };
+    Ok(Outcome::Written(written
+        .with_original_times(accessed, modified)
+        .with_path_changes(job.changes.clone())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::ZlibEncoder;
+    use flate2::Compression;
+
+    enum Node {
+        Folder(&'static str, Vec<Node>),
+        File(String, Vec<u8>),
+        /// Item of the given type code with data and child items
+        Owner(u32, String, Vec<u8>, Vec<Node>),
+    }
+
+    /// Append `nodes` as a sibling chain, returning the first item's address
+    fn write_items(logical: &mut Vec<u8>, nodes: &[Node], chunk_size: usize) -> u64 {
+        let mut addresses = Vec::new();
+        for node in nodes {
+            let address = logical.len();
+            addresses.push(address);
+            let (name, item_type) = match node {
+                Node::Folder(name, _) => (name.as_bytes(), AD1_FOLDER_SIGNATURE),
+                Node::File(name, _) => (name.as_bytes(), 0),
+                Node::Owner(item_type, name, _, _) => (name.as_bytes(), *item_type),
+            };
+            logical.resize(address + 0x30 + name.len(), 0);
+            logical[address + 0x28..address + 0x2c].copy_from_slice(&item_type.to_le_bytes());
+            logical[address + 0x2c..address + 0x30].copy_from_slice(&(name.len() as u32).to_le_bytes());
+            logical[address + 0x30..].copy_from_slice(name);
+
+            match node {
+                Node::Folder(_, children) => {
+                    let child = write_items(logical, children, chunk_size);
+                    logical[address + 0x08..address + 0x10].copy_from_slice(&child.to_le_bytes());
+                }
+                Node::File(_, data) | Node::Owner(_, _, data, _) => {
+                    let chunks: Vec<Vec<u8>> = data.chunks(chunk_size).map(|chunk| {
+                        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::fast());
+                        encoder.write_all(chunk).unwrap();
+                        encoder.finish().unwrap()
+                    }).collect();
+                    let table = logical.len() as u64;
+                    logical[address + 0x18..address + 0x20].copy_from_slice(&table.to_le_bytes());
+                    logical[address + 0x20..address + 0x28].copy_from_slice(&(data.len() as u64).to_le_bytes());
+                    logical.extend_from_slice(&(chunks.len() as u64).to_le_bytes());
+                    let mut chunk_address = table + 8 + (chunks.len() as u64 + 1) * 8;
+                    for chunk in &chunks {
+                        logical.extend_from_slice(&chunk_address.to_le_bytes());
+                        chunk_address += chunk.len() as u64;
+                    }
+                    logical.extend_from_slice(&chunk_address.to_le_bytes());
+                    for chunk in &chunks {
+                        logical.extend_from_slice(chunk);
+                    }
+                    if let Node::Owner(_, _, _, children) = node {
+                        let child = write_items(logical, children, chunk_size);
+                        logical[address + 0x08..address + 0x10].copy_from_slice(&child.to_le_bytes());
+                    }
+                }
+            }
+        }
+        for pair in addresses.windows(2) {
+            logical[pair[0]..pair[0] + 8].copy_from_slice(&(pair[1] as u64).to_le_bytes());
+        }
+        addresses.first().map_or(0, |address| *address as u64)
+    }
+
+    /// AD1 split into one-fragment segments, so items straddle segment files
+    fn write_tree_ad1(dir: &Path, nodes: &[Node]) -> String {
+        let chunk_size = 4096;
+        let mut logical = vec![0u8; 0x200];
+        logical[0..15].copy_from_slice(b"ADLOGICALIMAGE\0");
+        logical[0x10..0x14].copy_from_slice(&3u32.to_le_bytes());
+        logical[0x18..0x1c].copy_from_slice(&(chunk_size as u32).to_le_bytes());
+        let first = write_items(&mut logical, nodes, chunk_size);
+        logical[0x24..0x2c].copy_from_slice(&first.to_le_bytes());
+
+        let span = segment_span(1) as usize;
+        let pieces: Vec<&[u8]> = logical.chunks(span).collect();
+        for (index, piece) in pieces.iter().enumerate() {
+            let mut file = vec![0u8; AD1_LOGICAL_MARGIN as usize];
+            file[..15].copy_from_slice(AD1_SIGNATURE);
+            file[0x18..0x1c].copy_from_slice(&(index as u32 + 1).to_le_bytes());
+            file[0x1c..0x20].copy_from_slice(&(pieces.len() as u32).to_le_bytes());
+            file[0x22..0x26].copy_from_slice(&1u32.to_le_bytes());
+            file[0x28..0x2c].copy_from_slice(&512u32.to_le_bytes());
+            file.extend_from_slice(piece);
+            fs::write(dir.join(format!("image.ad{}", index + 1)), file).unwrap();
+        }
+        dir.join("image.ad1").to_string_lossy().to_string()
+    }
+
+    fn fixture(dir: &Path) -> String {
+        // Pseudo-random, so the data doesn't compress below one segment
+        let data = |seed: u64, len: usize| {
+            let mut state = seed.wrapping_mul(0x9e37_79b9_7f4a_7c15) | 1;
+            (0..len).map(|_| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                state as u8
+            }).collect::<Vec<u8>>()
+        };
+        let tiny: Vec<Node> = (0..40).map(|n| Node::File(format!("note{n}.txt"), data(n as u64, 50 + n * 13))).collect();
+        write_tree_ad1(dir, &[
+            Node::Folder("docs", vec![
+                Node::File("big.bin".to_string(), data(1, 70_000)),
+                Node::Folder("tiny", tiny),
+                Node::File("empty.txt".to_string(), Vec::new()),
+            ]),
+            Node::File("readme.md".to_string(), data(2, 9_000)),
+        ])
+    }
+
+    #[test]
+    fn test_parallel_matches_sequential_extraction() {
+        let evidence = tempfile::tempdir().unwrap();
+        let path = fixture(evidence.path());
+        assert!(fs::metadata(evidence.path().join("image.ad2")).is_ok());
+
+        let out = tempfile::tempdir().unwrap();
+        let sequential_dir = out.path().join("sequential");
+        let sequential = crate::ad1::extract_with_progress(
+            &path, sequential_dir.to_str().unwrap(), &ExtractFilter::default(), |_, _| {},
+        ).unwrap();
+
+        for workers in [1, 3, 8] {
+            let parallel_dir = out.path().join(format!("parallel{workers}"));
+            let mut last = ExtractProgress::default();
+            let parallel = extract_parallel(
+                &path, parallel_dir.to_str().unwrap(), &ExtractFilter::default(), workers, false, false, None, |p| last = p,
+            ).unwrap();
+
+            assert_eq!(parallel.extracted, 43);
+            assert_eq!(parallel.extracted, sequential.extracted);
+            assert_eq!(parallel.total_bytes, sequential.total_bytes);
+            assert_eq!(last.files_done, last.files_total);
+            assert_eq!(last.bytes_done, sequential.total_bytes);
+            // Same rows in the same (tree) order, with the same hashes
+            let rows = |stats: &ExtractStats| stats.files.iter()
+                .map(|f| (f.internal_path.clone(), f.size, f.md5.clone(), f.sha256.clone()))
+                .collect::<Vec<_>>();
+            assert_eq!(rows(&parallel), rows(&sequential));
+            assert_eq!(
+                fs::read(parallel_dir.join("docs/big.bin")).unwrap(),
+                fs::read(sequential_dir.join("docs/big.bin")).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn test_filtered_parallel_extraction_and_batching() {
+        let evidence = tempfile::tempdir().unwrap();
+        let path = fixture(evidence.path());
+        let out = tempfile::tempdir().unwrap();
+        let filter = ExtractFilter { extensions: vec!["txt".to_string()], exclude_globs: vec!["tiny".to_string()], ..Default::default() };
+
+        let stats = extract_parallel(&path, out.path().to_str().unwrap(), &filter, 4, false, false, None, |_| {}).unwrap();
+        assert_eq!(stats.pruned_folders, 1);
+        let paths: Vec<&str> = stats.files.iter().map(|f| f.internal_path.as_str()).collect();
+        assert_eq!(paths, vec!["docs/empty.txt"]);
+        assert!(!out.path().join("docs/tiny").exists());
+
+        let job = |size: u64| FileJob {
+            index: 0,
+            item: Item { id: 0, name: String::new(), item_type: 0, kind: ItemKind::File, decompressed_size: size, zlib_metadata_addr: 0, metadata: Vec::new(), children: Vec::new() },
+            logical_path: String::new(),
+            output_path: PathBuf::new(),
+            changes: Vec::new(),
+            native_stream: false,
+        };
+        let mut files: Vec<FileJob> = (0..600).map(|_| job(10)).collect();
+        files.insert(300, job(BATCH_BYTES));
+        let sizes: Vec<usize> = batches(files).iter().map(Vec::len).collect();
+        assert_eq!(sizes, vec![256, 44, 1, 256, 44]);
+    }
+
+    #[test]
+    fn test_best_effort_extraction_skips_corrupt_files() {
+        let evidence = tempfile::tempdir().unwrap();
+        let bad: Vec<u8> = (0..6000u32).map(|i| (i % 97) as u8).collect();
+        let path = write_tree_ad1(evidence.path(), &[
+            Node::File("bad.bin".to_string(), bad.clone()),
+            Node::File("good.txt".to_string(), vec![b'g'; 6000]),
+        ]);
+        // Garble the zlib header of bad.bin's second chunk
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::fast());
+        encoder.write_all(&bad[4096..]).unwrap();
+        let chunk = encoder.finish().unwrap();
+        let mut bytes = fs::read(&path).unwrap();
+        let at = bytes.windows(chunk.len()).position(|window| window == chunk).unwrap();
+        bytes[at..at + 2].copy_from_slice(&[0xff, 0xff]);
+        fs::write(&path, bytes).unwrap();
+
+        let out = tempfile::tempdir().unwrap();
+        let error = extract_parallel(&path, out.path().to_str().unwrap(), &ExtractFilter::default(), 2, false, false, None, |_| {}).err().unwrap();
+        assert!(error.starts_with("bad.bin: Zlib inflate error in chunk 1"), "{error}");
+
+        let out = tempfile::tempdir().unwrap();
+        let mut last = ExtractProgress::default();
+        let stats = extract_parallel(&path, out.path().to_str().unwrap(), &ExtractFilter::default(), 2, true, false, None, |p| last = p).unwrap();
+        assert_eq!((stats.matched, stats.extracted, stats.total_bytes), (2, 1, 6000));
+        assert_eq!(stats.unreadable.len(), 1);
+        let unreadable = &stats.unreadable[0];
+        assert_eq!((unreadable.path.as_str(), unreadable.status.as_str(), unreadable.chunk), ("bad.bin", "corrupt", Some(1)));
+        assert_eq!(last.files_done, 2);
+        assert!(out.path().join("good.txt").exists());
+        assert!(!out.path().join("bad.bin").exists());
+    }
+
+    #[test]
+    fn test_plan_extraction_writes_nothing() {
+        let evidence = tempfile::tempdir().unwrap();
+        let path = fixture(evidence.path());
+        let out = tempfile::tempdir().unwrap();
+        let output_dir = out.path().join("plan");
+
+        let plan = plan_extraction(&path, output_dir.to_str().unwrap(), &ExtractFilter::default(), false).unwrap();
+        assert!(!output_dir.exists());
+
+        let real = extract_parallel(&path, output_dir.to_str().unwrap(), &ExtractFilter::default(), 2, false, false, None, |_| {}).unwrap();
+        assert_eq!((plan.matched, plan.extracted, plan.total_bytes), (real.matched, real.extracted, real.total_bytes));
+        let paths = |stats: &ExtractStats| stats.files.iter()
+            .map(|f| (f.internal_path.clone(), f.output_path.clone(), f.size))
+            .collect::<Vec<_>>();
+        assert_eq!(paths(&plan), paths(&real));
+        assert!(plan.files.iter().all(|f| f.sha256.is_empty()));
+    }
+
+    #[test]
+    fn test_streams_and_attachments_extract_as_siblings() {
+        let evidence = tempfile::tempdir().unwrap();
+        let path = write_tree_ad1(evidence.path(), &[
+            Node::Folder("docs", vec![
+                Node::Owner(0, "report.docx".to_string(), b"report".to_vec(), vec![
+                    Node::Owner(AD1_ALTERNATE_STREAM_TYPE, "Zone.Identifier".to_string(), b"[ZoneTransfer]\r\nZoneId=3".to_vec(), Vec::new()),
+                    // Unknown code under a file, stored with the file's name in front
+                    Node::File("report.docx:thumb".to_string(), b"thumb".to_vec()),
+                ]),
+                Node::Owner(0, "mail.msg".to_string(), b"message".to_vec(), vec![
+                    Node::Owner(AD1_ATTACHMENT_TYPE, "invoice.pdf".to_string(), b"%PDF".to_vec(), Vec::new()),
+                ]),
+            ]),
+        ]);
+
+        let tree = crate::ad1::info(&path, true).unwrap().tree.unwrap();
+        let kinds: Vec<(&str, ItemKind)> = tree.iter().map(|entry| (entry.path.as_str(), entry.item_kind)).collect();
+        assert_eq!(kinds, vec![
+            ("docs", ItemKind::Folder),
+            ("docs/report.docx", ItemKind::File),
+            ("docs/report.docx:Zone.Identifier", ItemKind::AlternateStream),
+            ("docs/report.docx:thumb", ItemKind::AlternateStream),
+            ("docs/mail.msg", ItemKind::File),
+            ("docs/mail.msg/invoice.pdf", ItemKind::Attachment),
+        ]);
+        let verified: Vec<String> = crate::ad1::verify(&path, "md5").unwrap().into_iter()
+            .map(|entry| match entry.target {
+                crate::containers::VerifyTarget::Path { path, .. } => path,
+                other => panic!("unexpected target {other:?}"),
+            })
+            .collect();
+        assert_eq!(verified, kinds[1..].iter().map(|(path, _)| path.to_string()).collect::<Vec<_>>());
+
+        // Native streams are only written on NTFS, so these stay siblings here
+        let out = tempfile::tempdir().unwrap();
+        let stats = extract_parallel(&path, out.path().to_str().unwrap(), &ExtractFilter::default(), 2, false, !cfg!(windows), None, |_| {}).unwrap();
+        assert_eq!(stats.extracted, 5);
+        let docs = out.path().join("docs");
+        assert_eq!(fs::read(docs.join("report.docx")).unwrap(), b"report");
+        assert_eq!(fs::read(docs.join("report.docx__ADS_Zone.Identifier")).unwrap(), b"[ZoneTransfer]\r\nZoneId=3");
+        assert_eq!(fs::read(docs.join("report.docx__ADS_thumb")).unwrap(), b"thumb");
+        assert_eq!(fs::read(docs.join("mail.msg__ATT_invoice.pdf")).unwrap(), b"%PDF");
+        let stream = stats.files.iter().find(|file| file.internal_path == "docs/report.docx:Zone.Identifier").unwrap();
+        assert_eq!(stream.path_changes, vec![PathChange {
+            original: "report.docx:Zone.Identifier".to_string(),
+            sanitized: "report.docx__ADS_Zone.Identifier".to_string(),
+            reason: "alternate data stream written as a sibling file".to_string(),
+        }]);
+
+        // The sequential extraction writes the same files
+        let sequential_dir = out.path().join("sequential");
+        let sequential = crate::ad1::extract_with_progress(
+            &path, sequential_dir.to_str().unwrap(), &ExtractFilter::default(), |_, _| {},
+        ).unwrap();
+        let rows = |stats: &ExtractStats| stats.files.iter()
+            .map(|f| (f.internal_path.clone(), f.md5.clone(), f.path_changes.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(rows(&sequential), rows(&stats));
+    }
+
+    #[test]
+    fn test_deduplicated_extraction_of_overlapping_containers() {
+        use crate::common::dedupe::DedupeStrategy;
+        use crate::common::extract_manifest::{ExtractionManifest, ManifestFormat};
+
+        let shared: Vec<u8> = (0..30_000u32).map(|i| (i % 251) as u8).collect();
+        let (first, second) = (tempfile::tempdir().unwrap(), tempfile::tempdir().unwrap());
+        let first = write_tree_ad1(first.path(), &[
+            Node::Folder("docs", vec![
+                Node::File("report.pdf".to_string(), shared.clone()),
+                Node::File("copy.pdf".to_string(), shared.clone()),
+            ]),
+            Node::File("first.txt".to_string(), b"first".to_vec()),
+        ]);
+        let second = write_tree_ad1(second.path(), &[
+            Node::Folder("mail", vec![Node::File("attachment.pdf".to_string(), shared.clone())]),
+            Node::File("second.txt".to_string(), b"second".to_vec()),
+        ]);
+
+        let out = tempfile::tempdir().unwrap();
+        let output_dir = out.path().to_str().unwrap();
+        let mut runs = Vec::new();
+        for path in [&first, &second] {
+            // Each run starts from the index the previous manifest saved
+            let index = DedupeIndex::load(out.path(), DedupeStrategy::Skip);
+            let stats = extract_parallel(path, output_dir, &ExtractFilter::default(), 2, false, false, Some(&index), |_| {}).unwrap();
+            ExtractionManifest::new(path, output_dir, stats.files.clone())
+                .with_dedupe_index(index.entries())
+                .write(ManifestFormat::Json)
+                .unwrap();
+            runs.push(stats);
+        }
+        let counts: Vec<(usize, usize, u64)> = runs.iter().map(|stats| (stats.extracted, stats.deduplicated, stats.deduplicated_bytes)).collect();
+        assert_eq!(counts, vec![(3, 1, 30_000), (2, 1, 30_000)]);
+
+        // The shared content is on disk once
+        fn file_bytes(dir: &Path) -> u64 {
+            fs::read_dir(dir).unwrap().flatten().map(|entry| match entry.file_type().unwrap().is_dir() {
+                true => file_bytes(&entry.path()),
+                false if entry.file_name().to_string_lossy().starts_with("extraction_manifest") => 0,
+                false => entry.metadata().unwrap().len(),
+            }).sum()
+        }
+        assert_eq!(file_bytes(out.path()), 30_000 + 5 + 6);
+        assert!(!out.path().join("mail/attachment.pdf").exists());
+
+        // Every file of the second container has a row; the skipped one
+        // names the first container's output holding its content
+        let manifest: serde_json::Value = serde_json::from_str(
+            &fs::read_to_string(out.path().join("extraction_manifest.json")).unwrap(),
+        ).unwrap();
+        let rows = manifest["files"].as_array().unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0]["internal_path"], "mail/attachment.pdf");
+        assert_eq!(rows[0]["sha256"], runs[0].files[0].sha256.as_str());
+        assert_eq!(rows[0]["duplicate"]["action"], "skipped");
+        assert_eq!(rows[0]["duplicate"]["original_path"], runs[0].files[0].output_path.as_str());
+        assert!(rows[1].get("duplicate").is_none());
+        assert_eq!(manifest["dedupe_index"].as_array().unwrap().len(), 3);
+    }
+}
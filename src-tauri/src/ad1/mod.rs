@@ -26,7 +26,8 @@
 //! ├──────────────────────────────────────────────────────────────┤
 //! │ Item Chain (linked list structure)                           │
 //! │  - Each item: next_addr, child_addr, metadata_addr           │
-//! │  - Item type: 0x05 = folder, others = files                 │
+//! │  - Item type: 0x05 = folder, 0x02 = alternate stream,       │
+//! │    0x03 = attachment, others = files                        │
 //! │  - Zlib-compressed data at zlib_metadata_addr                │
 //! └──────────────────────────────────────────────────────────────┘
 //! ```
@@ -44,32 +45,48 @@
 //! // Get container info with file tree
 //! let info = ad1::info("/path/to/evidence.ad1", true)?;
 //!
+//! // Stream a large tree in batches instead of one Vec
+//! let summary = ad1::tree_stream("/path/to/evidence.ad1", 1000, |entries| { /* ... */ })?;
+//!
 //! // Fast info (headers only, no tree parsing)
 //! let info_fast = ad1::info_fast("/path/to/evidence.ad1")?;
 //!
-//! // Verify file hashes
+//! // Verify file hashes; damaged files are listed as "corrupt"/"missing"
 //! let results = ad1::verify("/path/to/evidence.ad1", "sha1")?;
 //!
+//! // ... with ok/nok/corrupt/missing counts, or stopping at the first damaged file
+//! let report = ad1::verify_report("/path/to/evidence.ad1", "sha1", &Default::default(), |current, total| { /* ... */ })?;
+//!
+//! // Image hash comparable to the FTK companion log (.ad1.txt)
+//! let hash = ad1::hash_image_stream("/path/to/evidence.ad1", "md5")?;
+//!
 //! // Extract to output directory
 //! ad1::extract("/path/to/evidence.ad1", "/output/dir")?;
+//!
+//! // Extract on 4 worker threads, reporting files and bytes done
+//! let stats = ad1::extract_parallel("/path/to/evidence.ad1", "/output/dir", &filter, 4, false, false, None, |progress| { /* ... */ })?;
 //! ```
 
 mod types;
 mod parser;
 mod operations;
+mod extract;
 mod utils;
 
 // Re-export public types
 #[allow(unused_imports)]
 pub use types::{
-    Ad1Info, SegmentHeaderInfo, LogicalHeaderInfo, 
-    TreeEntry, VerifyEntry,
+    Ad1Info, Ad1VerifyReport, VersionedAd1VerifyReport, ItemDataError, ItemLinkError, SegmentHeaderInfo, SegmentSetError, LogicalHeaderInfo,
+    ItemKind, TreeEntry, TreeSummary, VerifyEntry, VerifySummary,
 };
 
 // Re-export public functions
 #[allow(unused_imports)]
 pub use operations::{
-    info, info_fast, verify, verify_with_progress,
-    extract, extract_with_progress, is_ad1,
+    capabilities, info, info_fast, info_with_tree_limit, info_with_options, tree_stream, DEFAULT_TREE_LIMIT,
+    verify, verify_with_progress, verify_report,
+    extract, extract_with_progress, is_ad1, quick_check,
     hash_segments, hash_segments_with_progress,
+    hash_image_stream, hash_image_stream_with_progress,
 };
+pub use extract::{extract_parallel, plan_extraction, DEFAULT_EXTRACT_WORKERS, MAX_EXTRACT_WORKERS};
@@ -6,28 +6,51 @@ use std::path::Path;
 use tracing::{debug, trace, instrument};
 
 use super::types::{
-    Ad1Info, VerifyEntry, AD1_SIGNATURE,
+    Ad1Info, Ad1VerifyReport, SegmentSetError, TreeEntry, TreeSummary, VerifyEntry, VerifySummary, AD1_FOLDER_SIGNATURE,
+    AD1_SIGNATURE,
 };
 use super::parser::Session;
 use super::utils::*;
 use crate::common::hash::{HashAlgorithm, StreamingHasher};
+use crate::containers::VerifyOptions;
+use crate::common::capabilities::FormatCapabilities;
+use crate::common::inflate::check_chunk_size;
+use crate::common::quick_check::StructureCheck;
+use crate::common::extract_filter::{ExtractFilter, ExtractStats};
+
+/// Everything is supported: AD1 carries an item tree with per-item hashes
+pub const fn capabilities() -> FormatCapabilities {
+    FormatCapabilities {
+        can_info: true,
+        can_info_tree: true,
+        can_verify_whole: true,
+        can_verify_items: true,
+        can_verify_segments: true,
+        can_extract: true,
+        can_extract_item: true,
+        notes: &["Encrypted AD1 containers are not supported"],
+    }
+}
 
 /// Fast info - only reads headers, doesn't parse full item tree
-/// Use this for quick container detection/display
-/// This uses lenient validation - will return info even with missing segments
+///
+/// Exactly two bounded reads of the first segment: its segment header and
+/// the 512 bytes after it (logical header, data
+/// source name, volume details). The item chain is never touched, and the
+/// other segments are only looked up, not opened - missing ones are listed
+/// in `segment_issues`. Use this for quick container detection/display.
 #[instrument]
 pub fn info_fast(path: &str) -> Result<Ad1Info, String> {
     debug!("Getting fast AD1 info (headers only)");
-    validate_format(path)?;  // Only validate format, not segments
-    
     let mut file = File::open(path)
         .map_err(|e| format!("Failed to open AD1 file '{path}': {e}"))?;
     
     let segment_header = read_segment_header(&mut file)?;
-    let logical_header = read_logical_header(&mut file)?;
-    
-    // Parse volume info from header
-    let volume = parse_volume_info(&mut file);
+    if segment_header.segment_number == 0 {
+        return Err("Invalid AD1 segment count".to_string());
+    }
+    let window = read_header_window(&mut file)?;
+    let logical_header = parse_logical_header_window(&window)?;
     
     // Parse companion log file for case metadata
     let companion_log = parse_companion_log(path);
@@ -35,6 +58,15 @@ pub fn info_fast(path: &str) -> Result<Ad1Info, String> {
     // Get segment files with sizes (includes missing segments)
     let (segment_files, segment_sizes, total_size, missing_segments) = 
         get_segment_files_with_sizes(path, segment_header.segment_number);
+    let segment_issues: Vec<SegmentSetError> = (2..=segment_header.segment_number)
+        .filter_map(|index| {
+            let file = Path::new(&build_segment_path(path, index)).file_name()?.to_string_lossy().to_string();
+            missing_segments.contains(&file).then_some(SegmentSetError::Missing { index, total: segment_header.segment_number, file })
+        })
+        .collect();
+    if !segment_issues.is_empty() {
+        debug!(path, missing = segment_issues.len(), "AD1 segment set is incomplete");
+    }
     
     let missing = if missing_segments.is_empty() {
         None
@@ -47,29 +79,65 @@ pub fn info_fast(path: &str) -> Result<Ad1Info, String> {
         logical: logical_header_info(&logical_header),
         item_count: 0, // Not parsed in fast mode
         tree: None,
+        tree_truncated: false,
         segment_files: Some(segment_files),
         segment_sizes: Some(segment_sizes),
         total_size: Some(total_size),
+        logical_size: None,
         missing_segments: missing,
-        volume,
+        segments: vec![segment_header_info(&segment_header)],
+        segment_issues,
+        volume: parse_volume_info(&window),
+        creator_tool: find_creator_tool(&window),
         companion_log,
+        parse_warnings: Vec::new(),
     })
 }
 
+/// Default cap on the entries `info` returns with `include_tree`
+///
+/// Larger trees are reported as truncated; use `tree_stream` to get them whole.
+pub const DEFAULT_TREE_LIMIT: usize = 200_000;
+
 /// Get full AD1 container information
-/// Note: This still requires all segments to be present (strict validation via Session::open)
-#[instrument]
+/// Note: This still requires all segments to be present (strict validation via Session::open_headers)
 pub fn info(path: &str, include_tree: bool) -> Result<Ad1Info, String> {
-    debug!("Getting AD1 info, include_tree={}", include_tree);
-    let session = Session::open(path)?;
+    info_with_tree_limit(path, include_tree, DEFAULT_TREE_LIMIT)
+}
+
+/// Full info with at most `tree_limit` tree entries
+///
+/// The whole item chain is still walked so `item_count` is exact, but only
+/// the first `tree_limit` entries are kept.
+pub fn info_with_tree_limit(path: &str, include_tree: bool, tree_limit: usize) -> Result<Ad1Info, String> {
+    info_with_options(path, include_tree, tree_limit, false)
+}
+
+/// Full info; with `best_effort`, unreadable items are skipped and listed in
+/// `parse_warnings` instead of failing the whole walk
+#[instrument]
+pub fn info_with_options(path: &str, include_tree: bool, tree_limit: usize, best_effort: bool) -> Result<Ad1Info, String> {
+    debug!("Getting AD1 info, include_tree={}, best_effort={}", include_tree, best_effort);
+    let mut session = Session::open_headers(path)?;
+    session.best_effort = best_effort;
     
-    let tree = if include_tree {
-        let mut entries = Vec::new();
-        collect_tree(&session.root_items, "", &mut entries);
-        Some(entries)
-    } else {
-        None
-    };
+    let mut tree = include_tree.then(Vec::new);
+    let mut tree_truncated = false;
+    let mut logical_size = 0u64;
+    for walked in session.walk_items(false) {
+        let walked = walked?;
+        if walked.item.item_type != AD1_FOLDER_SIGNATURE {
+            logical_size += walked.item.decompressed_size;
+        }
+        match tree.as_mut() {
+            Some(entries) if entries.len() < tree_limit => entries.push(tree_entry(&walked)),
+            Some(_) => tree_truncated = true,
+            None => {}
+        }
+    }
+    if tree_truncated {
+        debug!(item_count = session.item_counter, tree_limit, "AD1 tree truncated");
+    }
     
     // Get segment files with sizes
     let (segment_files, segment_sizes, total_size, missing_segments) = 
@@ -81,12 +149,10 @@ pub fn info(path: &str, include_tree: bool) -> Result<Ad1Info, String> {
         Some(missing_segments)
     };
     
-    // Parse volume info from the first segment file
-    let volume = {
-        let mut file = File::open(path)
-            .map_err(|e| format!("Failed to open AD1 file for volume info: {e}"))?;
-        parse_volume_info(&mut file)
-    };
+    // Volume info and creator from the first segment file's header window
+    let window = File::open(path)
+        .map_err(|e| format!("Failed to open AD1 file for volume info: {e}"))
+        .and_then(|mut file| read_header_window(&mut file))?;
     
     // Parse companion log file for case metadata
     let companion_log = parse_companion_log(path);
@@ -96,26 +162,87 @@ pub fn info(path: &str, include_tree: bool) -> Result<Ad1Info, String> {
         logical: logical_header_info(&session.logical_header),
         item_count: session.item_counter,
         tree,
+        tree_truncated,
         segment_files: Some(segment_files),
         segment_sizes: Some(segment_sizes),
         total_size: Some(total_size),
+        logical_size: session.parse_warnings.is_empty().then_some(logical_size),
         missing_segments: missing,
-        volume,
+        segments: session.segment_headers.iter().map(segment_header_info).collect(),
+        segment_issues: Vec::new(),
+        volume: parse_volume_info(&window),
+        creator_tool: find_creator_tool(&window),
         companion_log,
+        parse_warnings: std::mem::take(&mut session.parse_warnings),
     })
 }
 
+/// Walk the item tree, passing entries to `on_batch` in batches of `batch_size`
+///
+/// Entries arrive in depth-first order, each folder before its contents, and
+/// reference their parent by `parent_address`. Memory use is bounded by one
+/// batch, however many items the container holds.
+#[instrument(skip(on_batch))]
+pub fn tree_stream<F>(path: &str, batch_size: usize, mut on_batch: F) -> Result<TreeSummary, String>
+where
+    F: FnMut(Vec<TreeEntry>),
+{
+    let batch_size = batch_size.max(1);
+    let mut session = Session::open_headers(path)?;
+    let mut summary = TreeSummary::default();
+    let mut batch = Vec::with_capacity(batch_size);
+    
+    for walked in session.walk_items(false) {
+        let entry = tree_entry(&walked?);
+        summary.item_count += 1;
+        if entry.is_dir {
+            summary.folder_count += 1;
+        } else {
+            summary.file_count += 1;
+            summary.total_size += entry.size;
+        }
+        batch.push(entry);
+        if batch.len() == batch_size {
+            on_batch(std::mem::replace(&mut batch, Vec::with_capacity(batch_size)));
+        }
+    }
+    if !batch.is_empty() {
+        on_batch(batch);
+    }
+    
+    debug!(item_count = summary.item_count, "AD1 tree streamed");
+    Ok(summary)
+}
+
 /// Verify file hashes in the container
+///
+/// Files whose data cannot be read are recorded as "corrupt" or "missing"
+/// and the rest are still verified.
 pub fn verify(path: &str, algorithm: &str) -> Result<Vec<VerifyEntry>, String> {
     verify_with_progress(path, algorithm, |_, _| {})
 }
 
 /// Verify with progress callback
-pub fn verify_with_progress<F>(path: &str, algorithm: &str, mut progress_callback: F) -> Result<Vec<VerifyEntry>, String>
+pub fn verify_with_progress<F>(path: &str, algorithm: &str, progress_callback: F) -> Result<Vec<VerifyEntry>, String>
+where
+    F: FnMut(usize, usize)
+{
+    verify_report(path, algorithm, &VerifyOptions::default(), progress_callback).map(|report| report.entries)
+}
+
+/// Verify every file, with per-status counts
+///
+/// A file whose chunk table or zlib stream is damaged is recorded as
+/// "corrupt", one whose data is past the end of a truncated segment as
+/// "missing" - both with the failing chunk and the error - and verification
+/// continues, so a damaged container still yields the full list of affected
+/// files. With `options.fail_fast` the first such file aborts the verification.
+pub fn verify_report<F>(path: &str, algorithm: &str, options: &VerifyOptions, mut progress_callback: F) -> Result<Ad1VerifyReport, String>
 where
     F: FnMut(usize, usize)
 {
     let mut session = Session::open(path)?;
+    session.best_effort = !options.fail_fast;
     let algo = HashAlgorithm::from_str(algorithm)?;
     let mut results = Vec::new();
     
@@ -130,34 +257,65 @@ where
         session.verify_item_with_progress(item, "", algo, &mut results, &mut current, total, &mut progress_callback)?;
     }
     
-    Ok(results)
+    let summary = VerifySummary::from_entries(&results);
+    debug!(
+        path,
+        ok = summary.ok,
+        nok = summary.nok,
+        corrupt = summary.corrupt,
+        missing = summary.missing,
+        "AD1 verification complete"
+    );
+    Ok(Ad1VerifyReport {
+        algorithm: algo.name().to_string(),
+        entries: results,
+        summary,
+    })
 }
 
 /// Extract container contents to output directory
 pub fn extract(path: &str, output_dir: &str) -> Result<(), String> {
-    extract_with_progress(path, output_dir, |_, _| {})
+    extract_with_progress(path, output_dir, &ExtractFilter::default(), |_, _| {}).map(|_| ())
 }
 
-/// Extract with progress callback
-pub fn extract_with_progress<F>(path: &str, output_dir: &str, mut progress_callback: F) -> Result<(), String>
+/// Extract with progress callback, keeping only files selected by `filter`
+///
+/// Progress counts every file item seen; files inside pruned folders are
+/// never visited, so the final callback reports completion explicitly.
+pub fn extract_with_progress<F>(
+    path: &str,
+    output_dir: &str,
+    filter: &ExtractFilter,
+    mut progress_callback: F,
+) -> Result<ExtractStats, String>
 where
     F: FnMut(usize, usize)
 {
+    let matcher = filter.compile()?;
     let mut session = Session::open(path)?;
     let output_path = Path::new(output_dir);
     
     // Count total files for progress
     let total = count_files(&session.root_items);
     let mut current = 0;
+    let mut stats = ExtractStats::default();
     
     // Clone root_items to avoid borrow checker issues
     let root_items = session.root_items.clone();
     
     for item in &root_items {
-        session.extract_item_with_progress(item, output_path, &mut current, total, &mut progress_callback)?;
+        session.extract_item_with_progress(item, "", output_path, &matcher, &mut stats, &mut current, total, &mut progress_callback)?;
     }
+    progress_callback(total, total);
     
-    Ok(())
+    debug!(
+        matched = stats.matched,
+        skipped = stats.skipped,
+        pruned_folders = stats.pruned_folders,
+        total_bytes = stats.total_bytes,
+        "AD1 extraction complete"
+    );
+    Ok(stats)
 }
 
 /// Check if file is an AD1 container
@@ -172,9 +330,119 @@ pub fn is_ad1(path: &str) -> Result<bool, String> {
     Ok(is_ad1)
 }
 
-/// Hash AD1 segment files (image-level hash)
+/// Header-only structure check: every segment header, the logical header,
+/// and the first and last item records
+///
+/// Reads a few kilobytes however large the container is; item data is
+/// never decompressed.
+pub fn quick_check(path: &str) -> Result<Vec<StructureCheck>, String> {
+    validate_format(path)?;
+    let mut file = File::open(path)
+        .map_err(|e| format!("Failed to open AD1 file '{path}': {e}"))?;
+    let segment_header = read_segment_header(&mut file)?;
+    let (headers, issues) = read_segment_set(path, &segment_header);
+    let segments_ok = issues.is_empty();
+    let mut checks = vec![if segments_ok {
+        StructureCheck::pass("Segment headers", format!("{} segment(s) with matching signatures and indexes", headers.len()))
+    } else {
+        StructureCheck::fail("Segment headers", issues.iter().map(ToString::to_string).collect::<Vec<_>>().join("; "))
+    }];
+
+    let logical = read_logical_header(&mut file).and_then(|header| {
+        if !header.signature.starts_with(b"ADLOGICALIMAGE") {
+            return Err("No ADLOGICALIMAGE signature after the segment header".to_string());
+        }
+        check_chunk_size(header.zlib_chunk_size as u64, "AD1 zlib chunk size")?;
+        Ok(format!("Version {}, {} byte chunks, source '{}'", header.image_version, header.zlib_chunk_size, header.data_source_name))
+    });
+    let logical_ok = logical.is_ok();
+    checks.push(StructureCheck::from_result("Logical header", logical));
+
+    if !segments_ok || !logical_ok {
+        for name in ["First item", "Last item"] {
+            checks.push(StructureCheck::fail(name, "Not checked: the headers above are damaged"));
+        }
+        return Ok(checks);
+    }
+    let mut session = match Session::open_headers(path) {
+        Ok(session) => session,
+        Err(error) => {
+            for name in ["First item", "Last item"] {
+                checks.push(StructureCheck::fail(name, error.clone()));
+            }
+            return Ok(checks);
+        }
+    };
+    let first = session.logical_header.first_item_addr;
+    if first == 0 {
+        for name in ["First item", "Last item"] {
+            checks.push(StructureCheck::pass(name, "The container holds no items"));
+        }
+        return Ok(checks);
+    }
+    let describe = |session: &mut Session, address: u64| {
+        session.read_item(address, false)
+            .map(|(item, _, _)| format!("'{}' at offset {}", item.name, address))
+    };
+    checks.push(StructureCheck::from_result("First item", describe(&mut session, first)));
+    let last = session.last_item_address().and_then(|address| describe(&mut session, address));
+    checks.push(StructureCheck::from_result("Last item", last));
+    debug!(path, passed = checks.iter().all(|check| check.passed), "AD1 quick check finished");
+    Ok(checks)
+}
+
+/// Hash the logical image data stream (FTK's image hash)
+///
+/// FTK Imager's companion log records the hash of the uncompressed item
+/// data concatenated in stored order, not of the segment files, so this is
+/// the value to compare against the log's MD5/SHA1.
+pub fn hash_image_stream(path: &str, algorithm: &str) -> Result<String, String> {
+    hash_image_stream_with_progress(path, algorithm, |_, _| {})
+}
+
+/// Hash the logical image data stream with progress callback (in bytes)
+pub fn hash_image_stream_with_progress<F>(path: &str, algorithm: &str, mut progress_callback: F) -> Result<String, String>
+where
+    F: FnMut(u64, u64)
+{
+    let algo = HashAlgorithm::from_str(algorithm)?;
+    let mut session = Session::open_headers(path)?;
+    let mut total = 0u64;
+    for walked in session.walk_items(false) {
+        let item = walked?.item;
+        if item.item_type != AD1_FOLDER_SIGNATURE {
+            total += item.decompressed_size;
+        }
+    }
+    debug!(total, "Hashing AD1 image stream");
+
+    // Items in stored (pre-order) order, folders contributing no bytes, each
+    // file fed to the hasher a chunk at a time
+    let mut hasher = StreamingHasher::new(algo);
+    let mut current = 0u64;
+    let mut walker = session.walk_items(false);
+    while let Some(walked) = walker.next() {
+        let item = walked?.item;
+        if item.item_type == AD1_FOLDER_SIGNATURE || item.decompressed_size == 0 {
+            continue;
+        }
+        walker.session().stream_file_data(&item, |chunk| {
+            hasher.update(chunk);
+            current += chunk.len() as u64;
+            progress_callback(current, total);
+        })?;
+    }
+    progress_callback(total, total);
+
+    let hash = hasher.finalize();
+    debug!(hash = %hash, "AD1 image stream hash complete");
+    Ok(hash)
+}
+
+/// Hash AD1 segment files (file-level hash)
 /// This hashes all segment files sequentially to produce a single hash
-/// that can be compared against the stored hash in the companion log
+/// of the files on disk, e.g. to confirm a copy of the segment set.
+/// Use `hash_image_stream` to compare against the companion log.
 pub fn hash_segments(path: &str, algorithm: &str) -> Result<String, String> {
     hash_segments_with_progress(path, algorithm, |_, _| {})
 }
@@ -1,17 +1,27 @@
 //! AD1 parser implementation with Session management
 
-use flate2::read::ZlibDecoder;
 use rayon::prelude::*;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs::{self, File};
 use std::io::{Read, Seek, SeekFrom, Write};
 use std::path::Path;
 use std::sync::Arc;
-use tracing::{trace, debug, instrument};
+use tracing::{trace, debug, instrument, warn};
 
+use super::extract::output_target;
 use super::types::*;
 use super::utils::*;
-use crate::common::hash::{HashAlgorithm, compute_hash};
+use crate::common::atomic_file::AtomicFile;
+use crate::common::binary::ByteReader;
+use crate::common::hash::{HashAlgorithm, compute_hash, hashes_match};
+use crate::common::extract_filter::{ExtractMatcher, ExtractStats, UnreadableFile};
+use crate::common::extract_manifest::HashingWriter;
+use crate::common::inflate::{check_chunk_size, inflate_bounded, INFLATE_SLACK};
+use crate::common::parse_warning::ParseWarning;
+use crate::common::resources;
+use crate::common::safe_path::SanitizedPath;
+use crate::containers::{VerifyReason, VerifyStatus};
+use crate::logging::perf;
 
 /// LRU cache entry with access counter
 #[derive(Clone)]
@@ -23,25 +33,37 @@ pub(crate) struct CacheEntry {
 /// AD1 parsing session - manages file handles, caching, and parsing state
 pub(crate) struct Session {
     pub segment_header: SegmentHeader,
+    /// Header of every segment, checked to form one complete set
+    pub segment_headers: Vec<SegmentHeader>,
     pub logical_header: LogicalHeader,
     pub files: Vec<File>,
     pub file_sizes: Vec<u64>,
     pub item_counter: u64,
     pub root_items: Vec<Item>,
+    /// Skip unreadable items (and their subtrees) instead of failing the
+    /// walk; verification and extraction record files whose data can't be
+    /// read and go on
+    pub best_effort: bool,
+    /// Items skipped by a best-effort walk
+    pub parse_warnings: Vec<ParseWarning>,
     cache: HashMap<u64, CacheEntry>,
     cache_order: Vec<u64>,
+    /// Most file data entries kept in `cache`
+    cache_limit: usize,
 }
 
 impl Session {
-    /// Open an AD1 container and parse its structure
+    /// Open an AD1 container's segments and headers without reading any items
     #[instrument(skip_all, fields(path))]
-    pub fn open(path: &str) -> Result<Self, String> {
+    pub fn open_headers(path: &str) -> Result<Self, String> {
         debug!(path, "Opening AD1 session");
-        validate_input(path)?;
+        validate_format(path)?;
         let mut header_file = File::open(path)
             .map_err(|e| format!("Failed to open AD1 file '{path}': {e}"))?;
         let segment_header = read_segment_header(&mut header_file)?;
+        let segment_headers = check_segment_set(path, &segment_header)?;
         let logical_header = read_logical_header(&mut header_file)?;
+        check_chunk_size(logical_header.zlib_chunk_size as u64, "AD1 zlib chunk size")?;
         
         debug!(
             segment_count = segment_header.segment_number,
@@ -66,73 +88,113 @@ impl Session {
             file_sizes.push(data_size);
         }
 
-        let mut session = Session {
+        let cache_limit = if resources::low_memory() { LOW_MEMORY_CACHE_SIZE } else { CACHE_SIZE };
+        Ok(Session {
             segment_header,
+            segment_headers,
             logical_header,
             files,
             file_sizes,
             item_counter: 0,
             root_items: Vec::new(),
-            cache: HashMap::with_capacity(CACHE_SIZE),
-            cache_order: Vec::with_capacity(CACHE_SIZE),
-        };
+            best_effort: false,
+            parse_warnings: Vec::new(),
+            cache: HashMap::with_capacity(cache_limit),
+            cache_order: Vec::with_capacity(cache_limit),
+            cache_limit,
+        })
+    }
 
-        let root_items = session.read_item_chain(session.logical_header.first_item_addr)?;
+    /// Open an AD1 container and parse its whole item tree into `root_items`
+    pub fn open(path: &str) -> Result<Self, String> {
+        let mut session = Self::open_headers(path)?;
+        let first_item = session.logical_header.first_item_addr;
+        let root_items = perf::phase(perf::PARSING).in_scope(|| session.read_item_chain(first_item))?;
         debug!(root_item_count = root_items.len(), "Parsed root items");
         session.root_items = root_items;
-
         Ok(session)
     }
 
-    /// Read a chain of items starting at the given offset
+    /// Walk the item chain from the first root item without building the tree
+    pub fn walk_items(&mut self, with_metadata: bool) -> ItemWalker<'_> {
+        let first = self.logical_header.first_item_addr;
+        self.walk_from(first, with_metadata)
+    }
+
+    fn walk_from(&mut self, offset: u64, with_metadata: bool) -> ItemWalker<'_> {
+        let pending = if offset != 0 {
+            vec![PendingItem { address: offset, linked_from: 0, parent_address: 0, parent_type: None, parent_path: String::new(), depth: 0 }]
+        } else {
+            Vec::new()
+        };
+        ItemWalker { session: self, pending, with_metadata, visited: 0 }
+    }
+
+    /// Read a chain of items (and their children) starting at the given offset
     pub fn read_item_chain(&mut self, offset: u64) -> Result<Vec<Item>, String> {
-        let mut items = Vec::new();
-        let mut next_addr = offset;
-        while next_addr != 0 {
-            let (item, next) = self.read_item(next_addr)?;
-            items.push(item);
-            next_addr = next;
+        assemble_tree(self.walk_from(offset, true).map(|walked| walked.map(|w| (w.depth, w.item))))
+    }
+
+    /// Address of the last item record in stored (pre-order) order: the
+    /// last root item, then its last child, and so on down
+    ///
+    /// Reads only the two link fields of each item on the way.
+    pub(crate) fn last_item_address(&mut self) -> Result<u64, String> {
+        let mut address = self.logical_header.first_item_addr;
+        let mut from = 0;
+        let mut seen = HashSet::new();
+        loop {
+            if !seen.insert(address) {
+                return Err(format!("Corrupt AD1 structure: item links loop back to offset {address}"));
+            }
+            check_item_link(from, address, self.logical_size())?;
+            from = address;
+            let next_item_addr = self.read_u64(address)?;
+            let first_child_addr = self.read_u64(address + 0x08)?;
+            match (next_item_addr, first_child_addr) {
+                (0, 0) => return Ok(address),
+                (0, child) => address = child,
+                (next, _) => address = next,
+            }
         }
-        Ok(items)
-    }
-
-    /// Read a single item at the given offset
-    fn read_item(&mut self, offset: u64) -> Result<(Item, u64), String> {
-        let next_item_addr = self.read_u64(offset)?;
-        let first_child_addr = self.read_u64(offset + 0x08)?;
-        let first_metadata_addr = self.read_u64(offset + 0x10)?;
-        let zlib_metadata_addr = self.read_u64(offset + 0x18)?;
-        let decompressed_size = self.read_u64(offset + 0x20)?;
-        let item_type = self.read_u32(offset + 0x28)?;
-        let name_length = self.read_u32(offset + 0x2c)? as usize;
+    }
+
+    /// Read a single item at the given offset, without its children
+    ///
+    /// Returns the item with the addresses of its next sibling and first child.
+    pub(crate) fn read_item(&mut self, offset: u64, with_metadata: bool) -> Result<(Item, u64, u64), String> {
+        let fields = self.read_bytes(offset, 0x30)?;
+        let mut reader = ByteReader::at(&fields, offset);
+        let next_item_addr = reader.u64_le()?;
+        let first_child_addr = reader.u64_le()?;
+        let first_metadata_addr = reader.u64_le()?;
+        let zlib_metadata_addr = reader.u64_le()?;
+        let decompressed_size = reader.u64_le()?;
+        let item_type = reader.u32_le()?;
+        let name_length = reader.u32_le()? as usize;
         let name_bytes = self.read_bytes(offset + 0x30, name_length)?;
         let mut name = bytes_to_string(&name_bytes);
         name = name.replace('/', "_");
 
-        let metadata = if first_metadata_addr != 0 {
+        let metadata = if with_metadata && first_metadata_addr != 0 {
             self.read_metadata_list(first_metadata_addr)?
         } else {
             Vec::new()
         };
 
-        let children = if first_child_addr != 0 {
-            self.read_item_chain(first_child_addr)?
-        } else {
-            Vec::new()
-        };
-
         self.item_counter += 1;
         let item = Item {
             id: self.item_counter,
             name,
             item_type,
+            kind: ItemKind::of(item_type, None),
             decompressed_size,
             zlib_metadata_addr,
             metadata,
-            children,
+            children: Vec::new(),
         };
 
-        Ok((item, next_item_addr))
+        Ok((item, next_item_addr, first_child_addr))
     }
 
     /// Read metadata list starting at the given offset
@@ -140,6 +202,9 @@ impl Session {
         let mut list = Vec::new();
         let mut next_addr = offset;
         while next_addr != 0 {
+            if list.len() >= MAX_METADATA_ENTRIES {
+                return Err(format!("Corrupt AD1 metadata list at {offset}: more than {MAX_METADATA_ENTRIES} entries"));
+            }
             let meta = self.read_metadata(next_addr)?;
             next_addr = meta.next_metadata_addr;
             list.push(meta);
@@ -149,10 +214,12 @@ impl Session {
 
     /// Read a single metadata entry
     fn read_metadata(&mut self, offset: u64) -> Result<Metadata, String> {
-        let next_metadata_addr = self.read_u64(offset)?;
-        let category = self.read_u32(offset + 0x08)?;
-        let key = self.read_u32(offset + 0x0c)?;
-        let data_length = self.read_u32(offset + 0x10)? as usize;
+        let fields = self.read_bytes(offset, 0x14)?;
+        let mut reader = ByteReader::at(&fields, offset);
+        let next_metadata_addr = reader.u64_le()?;
+        let category = reader.u32_le()?;
+        let key = reader.u32_le()?;
+        let data_length = reader.u32_le()? as usize;
         let data = self.read_bytes(offset + 0x14, data_length)?;
 
         Ok(Metadata {
@@ -163,18 +230,9 @@ impl Session {
         })
     }
 
-    /// Read u32 at offset
-    pub fn read_u32(&mut self, offset: u64) -> Result<u32, String> {
-        let bytes = self.read_bytes(offset, 4)?;
-        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
-    }
-
     /// Read u64 at offset
     pub fn read_u64(&mut self, offset: u64) -> Result<u64, String> {
-        let bytes = self.read_bytes(offset, 8)?;
-        Ok(u64::from_le_bytes([
-            bytes[0], bytes[1], bytes[2], bytes[3], bytes[4], bytes[5], bytes[6], bytes[7],
-        ]))
+        Ok(ByteReader::at(&self.read_bytes(offset, 8)?, offset).u64_le()?)
     }
 
     /// Read bytes at offset
@@ -182,11 +240,24 @@ impl Session {
         if length == 0 {
             return Ok(Vec::new());
         }
+        // Lengths come from the container - check before allocating
+        if offset.saturating_add(length as u64) > self.logical_size() {
+            return Err(format!("Corrupt AD1 structure: {length} bytes at offset {offset} run past the end of the container"));
+        }
         let mut buf = vec![0u8; length];
         self.read_into(offset, &mut buf)?;
         Ok(buf)
     }
 
+    /// Size of the logical address space spanned by all segments
+    pub(crate) fn logical_size(&self) -> u64 {
+        let seg_span = segment_span(self.segment_header.fragments_size);
+        match self.file_sizes.split_last() {
+            Some((last, rest)) => seg_span * rest.len() as u64 + last,
+            None => 0,
+        }
+    }
+
     /// Read into buffer at offset (handles multi-segment reads)
     fn read_into(&mut self, offset: u64, buf: &mut [u8]) -> Result<(), String> {
         if buf.is_empty() {
@@ -194,155 +265,39 @@ impl Session {
         }
 
         let seg_span = segment_span(self.segment_header.fragments_size);
-        if seg_span == 0 {
-            return Err("Invalid AD1 fragment size".to_string());
-        }
-        let mut remaining = buf.len() as u64;
-        let mut buf_cursor = 0usize;
-        let mut file_cursor = (offset / seg_span) as usize;
-        let mut data_cursor = offset - (seg_span * file_cursor as u64);
-
-        while remaining > 0 {
-            let file_size = self
-                .file_sizes
-                .get(file_cursor)
-                .copied()
-                .ok_or_else(|| "AD1 offset out of range".to_string())?;
-            let mut to_read = remaining;
-            if data_cursor + to_read > file_size {
-                to_read = file_size.saturating_sub(data_cursor);
-            }
-            if to_read == 0 {
-                return Err("AD1 offset out of range".to_string());
-            }
-
+        for (file_index, data_offset, range) in segment_reads(offset, buf.len(), seg_span, &self.file_sizes)? {
             let file = self
                 .files
-                .get_mut(file_cursor)
+                .get_mut(file_index)
                 .ok_or_else(|| "AD1 segment index out of range".to_string())?;
-            file.seek(SeekFrom::Start(data_cursor + AD1_LOGICAL_MARGIN))
+            file.seek(SeekFrom::Start(data_offset + AD1_LOGICAL_MARGIN))
                 .map_err(|e| format!("Failed to seek segment data: {e}"))?;
-            file.read_exact(&mut buf[buf_cursor..buf_cursor + to_read as usize])
+            file.read_exact(&mut buf[range])
                 .map_err(|e| format!("Failed to read segment data: {e}"))?;
-
-            buf_cursor += to_read as usize;
-            remaining -= to_read;
-            data_cursor = 0;
-            file_cursor += 1;
         }
 
         Ok(())
     }
 
-    /// Read and decompress file data for an item
-    pub fn read_file_data(&mut self, item: &Item) -> Result<Arc<Vec<u8>>, String> {
+    /// Read and decompress file data for an item, keeping why and in which
+    /// chunk reading failed
+    pub fn try_read_file_data(&mut self, item: &Item) -> Result<Arc<Vec<u8>>, ItemDataError> {
         if item.decompressed_size == 0 {
             return Ok(Arc::new(Vec::new()));
         }
         if let Some(data) = self.search_cache(item.id) {
             return Ok(data);
         }
-        if item.zlib_metadata_addr == 0 {
-            return Err("Missing zlib metadata address".to_string());
-        }
 
-        let chunk_count = self.read_u64(item.zlib_metadata_addr)?;
-        let mut addresses = Vec::with_capacity(chunk_count as usize + 1);
-        for index in 0..=chunk_count {
-            let addr = self.read_u64(item.zlib_metadata_addr + ((index + 1) * 0x08))?;
-            addresses.push(addr);
-        }
-
-        // For small files (< 4 chunks), use sequential decompression
-        // For larger files, use parallel decompression
-        let data = if chunk_count < 4 {
-            self.decompress_sequential(&addresses, item.decompressed_size as usize)?
-        } else {
-            self.decompress_parallel(&addresses, item.decompressed_size as usize)?
-        };
+        let chunk_size = self.logical_header.zlib_chunk_size as usize;
+        let logical_size = self.logical_size();
+        let data = read_item_data(item, chunk_size, logical_size, &mut |offset, length| self.read_bytes(offset, length))?;
 
         let data = Arc::new(data);
         self.cache_data(item.id, data.clone());
         Ok(data)
     }
 
-    /// Sequential decompression for small files
-    fn decompress_sequential(&mut self, addresses: &[u64], decompressed_size: usize) -> Result<Vec<u8>, String> {
-        let chunk_count = addresses.len() - 1;
-        let mut output = vec![0u8; decompressed_size];
-        let mut data_index = 0usize;
-        
-        for index in 0..chunk_count {
-            let start = addresses[index];
-            let end = addresses[index + 1];
-            let compressed_len = end.saturating_sub(start) as usize;
-            if compressed_len == 0 {
-                continue;
-            }
-            let compressed = self.read_bytes(start, compressed_len)?;
-            let mut decoder = ZlibDecoder::new(&compressed[..]);
-            let mut chunk = Vec::new();
-            decoder
-                .read_to_end(&mut chunk)
-                .map_err(|e| format!("Zlib inflate error: {e}"))?;
-            let end_index = (data_index + chunk.len()).min(output.len());
-            output[data_index..end_index].copy_from_slice(&chunk[..end_index - data_index]);
-            data_index = end_index;
-        }
-        
-        Ok(output)
-    }
-
-    /// Parallel decompression for large files
-    fn decompress_parallel(&mut self, addresses: &[u64], decompressed_size: usize) -> Result<Vec<u8>, String> {
-        let chunk_count = addresses.len() - 1;
-        
-        // Pre-read all compressed chunks sequentially (I/O bound)
-        let mut compressed_chunks: Vec<(usize, Vec<u8>)> = Vec::with_capacity(chunk_count);
-        for index in 0..chunk_count {
-            let start = addresses[index];
-            let end = addresses[index + 1];
-            let compressed_len = end.saturating_sub(start) as usize;
-            if compressed_len == 0 {
-                continue;
-            }
-            let compressed = self.read_bytes(start, compressed_len)?;
-            compressed_chunks.push((index, compressed));
-        }
-        
-        // Decompress in parallel (CPU bound)
-        let decompressed_chunks: Vec<Result<(usize, Vec<u8>), String>> = compressed_chunks
-            .par_iter()
-            .map(|(index, compressed)| {
-                let mut decoder = ZlibDecoder::new(&compressed[..]);
-                let mut chunk = Vec::new();
-                decoder
-                    .read_to_end(&mut chunk)
-                    .map_err(|e| format!("Zlib inflate error: {e}"))?;
-                Ok((*index, chunk))
-            })
-            .collect();
-        
-        // Assemble output in order
-        let mut output = vec![0u8; decompressed_size];
-        let mut data_index = 0usize;
-        
-        // Sort by index to maintain order
-        let mut sorted_chunks: Vec<(usize, Vec<u8>)> = Vec::with_capacity(decompressed_chunks.len());
-        for result in decompressed_chunks {
-            sorted_chunks.push(result?);
-        }
-        sorted_chunks.sort_by_key(|(idx, _)| *idx);
-        
-        for (_, chunk) in sorted_chunks {
-            let end_index = (data_index + chunk.len()).min(output.len());
-            output[data_index..end_index].copy_from_slice(&chunk[..end_index - data_index]);
-            data_index = end_index;
-        }
-        
-        Ok(output)
-    }
-
     /// O(1) cache lookup using HashMap
     fn search_cache(&mut self, item_id: u64) -> Option<Arc<Vec<u8>>> {
         if let Some(entry) = self.cache.get_mut(&item_id) {
@@ -359,7 +314,7 @@ impl Session {
         }
         
         // Evict oldest entry if cache is full
-        if self.cache.len() >= CACHE_SIZE {
+        if self.cache.len() >= self.cache_limit {
             if let Some(oldest_id) = self.cache_order.first().copied() {
                 self.cache.remove(&oldest_id);
                 self.cache_order.remove(0);
@@ -373,7 +328,24 @@ impl Session {
         self.cache_order.push(item_id);
     }
 
+    /// Feed the decompressed data of `item` to `sink` one chunk at a time
+    ///
+    /// Holds one chunk in memory however large the item is, and bypasses the
+    /// cache. Yields the same bytes as `try_read_file_data`.
+    pub fn stream_file_data<S>(&mut self, item: &Item, mut sink: S) -> Result<(), String>
+    where
+        S: FnMut(&[u8])
+    {
+        let chunk_size = self.logical_header.zlib_chunk_size as usize;
+        let logical_size = self.logical_size();
+        Ok(stream_item_data(item, chunk_size, logical_size, &mut |offset, length| self.read_bytes(offset, length), &mut sink)?)
+    }
+
     /// Verify item hash with progress callback
+    ///
+    /// Items whose data cannot be read fail the walk, unless the session is
+    /// `best_effort`: then they are recorded as "corrupt" or "missing" (with
+    /// the failing chunk) and the walk goes on.
     pub fn verify_item_with_progress<F>(
         &mut self,
         item: &Item,
@@ -387,50 +359,53 @@ impl Session {
     where
         F: FnMut(usize, usize)
     {
-        let path = join_path(parent_path, &item.name);
+        let path = child_path(parent_path, item);
         if item.item_type != AD1_FOLDER_SIGNATURE {
             let stored = match algorithm {
                 HashAlgorithm::Md5 => find_hash(&item.metadata, MD5_HASH),
                 HashAlgorithm::Sha1 => find_hash(&item.metadata, SHA1_HASH),
-                HashAlgorithm::Sha256 | HashAlgorithm::Sha512 | 
+                HashAlgorithm::Sha256 | HashAlgorithm::Sha512 | HashAlgorithm::Sha3_256 |
                 HashAlgorithm::Blake3 | HashAlgorithm::Blake2 |
-                HashAlgorithm::Xxh3 | HashAlgorithm::Xxh64 | HashAlgorithm::Crc32 => None,
+                HashAlgorithm::Xxh3 | HashAlgorithm::Xxh128 | HashAlgorithm::Xxh64 |
+                HashAlgorithm::Crc32 => None,
             };
             
-            let data = self.read_file_data(item)?;
-            let computed = compute_hash(&data, algorithm);
-            
-            let (status, stored_for_output) = match &stored {
-                Some(stored_hash) => {
-                    // Compare hashes case-insensitively (both should be lowercase, but be safe)
-                    let matches = stored_hash.eq_ignore_ascii_case(&computed);
-                    if matches {
-                        ("ok", Some(stored_hash.clone()))
-                    } else {
-                        debug!(
-                            path = %path,
-                            stored = %stored_hash,
-                            computed = %computed,
-                            size = item.decompressed_size,
-                            "Hash mismatch"
-                        );
-                        ("nok", Some(stored_hash.clone()))
-                    }
+            let entry = match self.try_read_file_data(item) {
+                Ok(data) => {
+                    let computed = compute_hash(&data, algorithm);
+                    let (status, reason) = match &stored {
+                        Some(stored_hash) if hashes_match(stored_hash, &computed) => (VerifyStatus::Ok, None),
+                        Some(stored_hash) => {
+                            debug!(
+                                path = %path,
+                                stored = %stored_hash,
+                                computed = %computed,
+                                size = item.decompressed_size,
+                                "Hash mismatch"
+                            );
+                            (VerifyStatus::Mismatch, None)
+                        }
+                        None => {
+                            trace!(path = %path, "No stored hash, computed only");
+                            (VerifyStatus::Skipped, Some(VerifyReason::NoExpectedHash))
+                        }
+                    };
+                    let mut entry = VerifyEntry::path(path.clone(), status)
+                        .hashes(algorithm.name(), stored.as_deref(), Some(&computed))
+                        .size(item.decompressed_size);
+                    entry.reason = reason;
+                    entry
                 }
-                None => {
-                    trace!(path = %path, "No stored hash, computed only");
-                    ("computed", None)
+                Err(e) if self.best_effort => {
+                    warn!(path = %path, error = %e, "Item data unreadable, recorded and skipped");
+                    let mut entry = e.verify_entry(&path).size(item.decompressed_size);
+                    entry.algorithm = Some(algorithm.name().to_string());
+                    entry.expected = stored.as_deref().and_then(|hash| hash.parse().ok());
+                    entry
                 }
+                Err(e) => return Err(format!("{path}: {e}")),
             };
-
-            out.push(VerifyEntry {
-                path: path.clone(),
-                status: status.to_string(),
-                algorithm: Some(algorithm.name().to_string()),
-                computed: Some(computed),
-                stored: stored_for_output,
-                size: Some(item.decompressed_size),
-            });
+            out.push(entry);
             
             *current += 1;
             progress_callback(*current, total);
@@ -444,10 +419,23 @@ impl Session {
     }
 
     /// Extract item with progress callback
+    ///
+    /// `parent_path` is the item's folder relative to the container root,
+    /// used for filter matching; output paths are built from it under
+    /// `output_dir` with `output_target`. Folders pruned by the filter are skipped
+    /// without walking their children.
+    /// With a `best_effort` session, files whose data cannot be read are
+    /// listed in `stats.unreadable` instead of failing the extraction.
+    /// Alternate streams and attachments are written as sibling files (see
+    /// `output_target`), never into native streams.
+    #[allow(clippy::too_many_arguments)]
     pub fn extract_item_with_progress<F>(
         &mut self,
         item: &Item,
+        parent_path: &str,
         output_dir: &Path,
+        filter: &ExtractMatcher,
+        stats: &mut ExtractStats,
         current: &mut usize,
         total: usize,
         progress_callback: &mut F,
@@ -455,31 +443,842 @@ impl Session {
     where
         F: FnMut(usize, usize)
     {
-        let item_path = output_dir.join(&item.name);
+        let logical_path = child_path(parent_path, item);
+        let (SanitizedPath { path: item_path, changes }, _) = output_target(output_dir, parent_path, item, false);
         if item.item_type == AD1_FOLDER_SIGNATURE {
-            fs::create_dir_all(&item_path)
-                .map_err(|e| format!("Failed to create directory {:?}: {e}", item_path))?;
-        } else if item.item_type == 0 {
-            if let Some(parent) = item_path.parent() {
-                fs::create_dir_all(parent).map_err(|e| {
-                    format!("Failed to create directory {:?}: {e}", parent)
-                })?;
+            if filter.prunes_folder(&logical_path) {
+                trace!(path = %logical_path, "Folder excluded by filter");
+                stats.pruned_folders += 1;
+                return Ok(());
             }
-            let data = self.read_file_data(item)?;
-            let mut file = File::create(&item_path)
-                .map_err(|e| format!("Failed to create file {:?}: {e}", item_path))?;
-            file.write_all(&data)
-                .map_err(|e| format!("Failed to write file {:?}: {e}", item_path))?;
-            
+            // Filtered extractions only create folders that end up holding files
+            if filter.selects_all() {
+                fs::create_dir_all(&item_path)
+                    .map_err(|e| format!("Failed to create directory {:?}: {e}", item_path))?;
+            }
+        } else {
+            if filter.matches_file(&logical_path, item.decompressed_size) {
+                stats.matched += 1;
+                if let Some(parent) = item_path.parent() {
+                    fs::create_dir_all(parent).map_err(|e| {
+                        format!("Failed to create directory {:?}: {e}", parent)
+                    })?;
+                }
+                let decompression = perf::phase(perf::DECOMPRESSION);
+                let data = decompression.in_scope(|| self.try_read_file_data(item));
+                match data {
+                    Ok(data) => {
+                        decompression.record("bytes", data.len() as u64);
+                        drop(decompression);
+                        let writing = perf::phase(perf::WRITING).entered();
+                        writing.record("bytes", data.len() as u64);
+                        let file = AtomicFile::create(&item_path)
+                            .map_err(|e| format!("Failed to create file {:?}: {e}", item_path))?;
+                        let mut writer = HashingWriter::new(file);
+                        writer.write_all(&data)
+                            .map_err(|e| format!("Failed to write file {:?}: {e}", item_path))?;
+                        let (accessed, modified) = item_file_times(&item.metadata);
+                        if !changes.is_empty() {
+                            debug!(path = %logical_path, output = ?item_path, "Container path sanitized for extraction");
+                        }
+                        let extracted = writer.commit(&logical_path)?
+                            .with_original_times(accessed, modified)
+                            .with_path_changes(changes);
+                        stats.extracted += 1;
+                        stats.total_bytes += extracted.size;
+                        stats.files.push(extracted);
+                    }
+                    Err(e) if self.best_effort => {
+                        warn!(path = %logical_path, error = %e, "Item data unreadable, not extracted");
+                        stats.unreadable.push(unreadable_file(&logical_path, &e));
+                    }
+                    Err(e) => return Err(format!("{logical_path}: {e}")),
+                }
+            } else {
+                stats.skipped += 1;
+            }
+
             *current += 1;
             progress_callback(*current, total);
         }
 
         for child in &item.children {
-            self.extract_item_with_progress(child, &item_path, current, total, progress_callback)?;
+            self.extract_item_with_progress(child, &logical_path, output_dir, filter, stats, current, total, progress_callback)?;
         }
 
-        apply_metadata(&item_path, &item.metadata)?;
+        // Files got their times when written; folders once their contents exist
+        if item.item_type == AD1_FOLDER_SIGNATURE && item_path.exists() {
+            apply_metadata(&item_path, &item.metadata)?;
+        }
         Ok(())
     }
 }
+
+/// Stats entry for a file a best-effort extraction could not read
+pub(crate) fn unreadable_file(path: &str, error: &ItemDataError) -> UnreadableFile {
+    UnreadableFile {
+        path: path.to_string(),
+        status: error.status().to_string(),
+        chunk: error.chunk(),
+        message: error.to_string(),
+    }
+}
+
+/// Read and decompress an item's data through `read(offset, length)`
+///
+/// `read` must refuse ranges past `logical_size`. Shared by `Session` and
+/// the parallel extraction workers, which read through a shared segment pool.
+pub(crate) fn read_item_data<R>(item: &Item, chunk_size: usize, logical_size: u64, read: &mut R) -> Result<Vec<u8>, ItemDataError>
+where
+    R: FnMut(u64, usize) -> Result<Vec<u8>, String>,
+{
+    if item.decompressed_size == 0 {
+        return Ok(Vec::new());
+    }
+    if item.zlib_metadata_addr == 0 {
+        return Err(ItemDataError::Corrupt { chunk: None, error: "Missing zlib metadata address".to_string() });
+    }
+
+    let table_error = |error| ItemDataError::Missing { chunk: None, error };
+    let chunk_count = read_chunk_count(item, chunk_size, logical_size, read)?;
+    let corrupt = |error: String| ItemDataError::Corrupt { chunk: None, error: format!("Corrupt AD1 item '{}': {}", item.name, error) };
+    // 32-bit builds: never let a size or count wrap into a small, valid-looking one
+    let decompressed_size = usize::try_from(item.decompressed_size)
+        .map_err(|_| corrupt(format!("{} bytes do not fit in memory on this platform", item.decompressed_size)))?;
+    let capacity = usize::try_from(chunk_count).ok().and_then(|count| count.checked_add(1))
+        .ok_or_else(|| corrupt(format!("{} chunks do not fit in memory on this platform", chunk_count)))?;
+    let mut addresses = Vec::with_capacity(capacity);
+    for index in 0..=chunk_count {
+        addresses.push(read_u64_via(read, item.zlib_metadata_addr + ((index + 1) * 0x08)).map_err(table_error)?);
+    }
+    // Chunk data runs forward through the container
+    if let Some(index) = addresses.windows(2).position(|pair| pair[1] < pair[0]) {
+        return Err(corrupt(format!(
+            "chunk {} ends at offset {} before it starts at offset {}",
+            index, addresses[index + 1], addresses[index]
+        )));
+    }
+
+    // For small files (< 4 chunks), use sequential decompression
+    // For larger files, use parallel decompression
+    if chunk_count < 4 {
+        decompress_sequential(&addresses, chunk_size, decompressed_size, read)
+    } else {
+        decompress_parallel(&addresses, chunk_size, decompressed_size, read)
+    }
+}
+
+/// Inflate an item's data through `read(offset, length)` and pass it to
+/// `sink` chunk by chunk
+///
+/// Same checks and bytes as [`read_item_data`], but the address table is
+/// read as the chunks are, so memory stays at one chunk whatever the item's
+/// size.
+pub(crate) fn stream_item_data<R, S>(item: &Item, chunk_size: usize, logical_size: u64, read: &mut R, sink: &mut S) -> Result<(), ItemDataError>
+where
+    R: FnMut(u64, usize) -> Result<Vec<u8>, String>,
+    S: FnMut(&[u8]),
+{
+    if item.decompressed_size == 0 {
+        return Ok(());
+    }
+    if item.zlib_metadata_addr == 0 {
+        return Err(ItemDataError::Corrupt { chunk: None, error: "Missing zlib metadata address".to_string() });
+    }
+
+    let chunk_count = read_chunk_count(item, chunk_size, logical_size, read)?;
+    let address = |read: &mut R, index: u64| {
+        read_u64_via(read, item.zlib_metadata_addr + ((index + 1) * 0x08))
+            .map_err(|error| ItemDataError::Missing { chunk: None, error })
+    };
+    let mut remaining = item.decompressed_size;
+    let mut start = address(read, 0)?;
+    for index in 0..chunk_count {
+        let end = address(read, index + 1)?;
+        // Chunk data runs forward through the container
+        if end < start {
+            return Err(ItemDataError::Corrupt {
+                chunk: None,
+                error: format!(
+                    "Corrupt AD1 item '{}': chunk {} ends at offset {} before it starts at offset {}",
+                    item.name, index, end, start
+                ),
+            });
+        }
+        let compressed_len = compressed_length(start, end, index as usize)?;
+        let chunk_start = std::mem::replace(&mut start, end);
+        if compressed_len == 0 {
+            continue;
+        }
+        let compressed = read(chunk_start, compressed_len)
+            .map_err(|error| ItemDataError::Missing { chunk: Some(index), error })?;
+        let chunk = inflate_bounded(&compressed[..], chunk_size, chunk_size + INFLATE_SLACK)
+            .map_err(|e| ItemDataError::Corrupt { chunk: Some(index), error: e.to_string() })?;
+        let take = (chunk.len() as u64).min(remaining) as usize;
+        sink(&chunk[..take]);
+        remaining -= take as u64;
+    }
+
+    // Like `read_item_data`, chunks short of the stored size leave zeros
+    let zeros = vec![0u8; chunk_size.min(usize::try_from(remaining).unwrap_or(chunk_size))];
+    while remaining > 0 {
+        let take = (zeros.len() as u64).min(remaining) as usize;
+        sink(&zeros[..take]);
+        remaining -= take as u64;
+    }
+    Ok(())
+}
+
+/// Read an item's chunk count and check its address table fits in the
+/// container and covers the item's size
+fn read_chunk_count<R>(item: &Item, chunk_size: usize, logical_size: u64, read: &mut R) -> Result<u64, ItemDataError>
+where
+    R: FnMut(u64, usize) -> Result<Vec<u8>, String>,
+{
+    let chunk_count = read_u64_via(read, item.zlib_metadata_addr)
+        .map_err(|error| ItemDataError::Missing { chunk: None, error })?;
+    let table_fits = chunk_count.saturating_add(1).saturating_mul(8) <= logical_size;
+    if !table_fits || item.decompressed_size > chunk_count.saturating_mul(chunk_size as u64) {
+        return Err(ItemDataError::Corrupt {
+            chunk: None,
+            error: format!(
+                "Corrupt AD1 item '{}': {} chunks of {} bytes cannot hold {} bytes",
+                item.name, chunk_count, chunk_size, item.decompressed_size
+            ),
+        });
+    }
+    Ok(chunk_count)
+}
+
+fn read_u64_via<R>(read: &mut R, offset: u64) -> Result<u64, String>
+where
+    R: FnMut(u64, usize) -> Result<Vec<u8>, String>,
+{
+    Ok(ByteReader::at(&read(offset, 8)?, offset).u64_le()?)
+}
+
+/// Stored size of the chunk at `start..end` (the table is checked to run forward)
+fn compressed_length(start: u64, end: u64, index: usize) -> Result<usize, ItemDataError> {
+    usize::try_from(end - start).map_err(|_| ItemDataError::Missing {
+        chunk: Some(index as u64),
+        error: format!("{} stored bytes do not fit in memory on this platform", end - start),
+    })
+}
+
+/// Sequential decompression for small files
+fn decompress_sequential<R>(addresses: &[u64], chunk_size: usize, decompressed_size: usize, read: &mut R) -> Result<Vec<u8>, ItemDataError>
+where
+    R: FnMut(u64, usize) -> Result<Vec<u8>, String>,
+{
+    let chunk_count = addresses.len() - 1;
+    let mut output = vec![0u8; decompressed_size];
+    let mut data_index = 0usize;
+    
+    for index in 0..chunk_count {
+        let start = addresses[index];
+        let end = addresses[index + 1];
+        let compressed_len = compressed_length(start, end, index)?;
+        if compressed_len == 0 {
+            continue;
+        }
+        let compressed = read(start, compressed_len)
+            .map_err(|error| ItemDataError::Missing { chunk: Some(index as u64), error })?;
+        let chunk = inflate_bounded(&compressed[..], chunk_size, chunk_size + INFLATE_SLACK)
+            .map_err(|e| ItemDataError::Corrupt { chunk: Some(index as u64), error: e.to_string() })?;
+        let end_index = (data_index + chunk.len()).min(output.len());
+        output[data_index..end_index].copy_from_slice(&chunk[..end_index - data_index]);
+        data_index = end_index;
+    }
+    
+    Ok(output)
+}
+
+/// Parallel decompression for large files
+fn decompress_parallel<R>(addresses: &[u64], chunk_size: usize, decompressed_size: usize, read: &mut R) -> Result<Vec<u8>, ItemDataError>
+where
+    R: FnMut(u64, usize) -> Result<Vec<u8>, String>,
+{
+    let chunk_count = addresses.len() - 1;
+    
+    // Pre-read all compressed chunks sequentially (I/O bound)
+    let mut compressed_chunks: Vec<(usize, Vec<u8>)> = Vec::with_capacity(chunk_count);
+    for index in 0..chunk_count {
+        let start = addresses[index];
+        let end = addresses[index + 1];
+        let compressed_len = compressed_length(start, end, index)?;
+        if compressed_len == 0 {
+            continue;
+        }
+        let compressed = read(start, compressed_len)
+            .map_err(|error| ItemDataError::Missing { chunk: Some(index as u64), error })?;
+        compressed_chunks.push((index, compressed));
+    }
+    
+    // Decompress in parallel (CPU bound)
+    let decompressed_chunks: Vec<Result<(usize, Vec<u8>), ItemDataError>> = compressed_chunks
+        .par_iter()
+        .map(|(index, compressed)| {
+            let chunk = inflate_bounded(&compressed[..], chunk_size, chunk_size + INFLATE_SLACK)
+                .map_err(|e| ItemDataError::Corrupt { chunk: Some(*index as u64), error: e.to_string() })?;
+            Ok((*index, chunk))
+        })
+        .collect();
+    
+    // Assemble output in order
+    let mut output = vec![0u8; decompressed_size];
+    let mut data_index = 0usize;
+    
+    // Sort by index to maintain order
+    let mut sorted_chunks: Vec<(usize, Vec<u8>)> = Vec::with_capacity(decompressed_chunks.len());
+    for result in decompressed_chunks {
+        sorted_chunks.push(result?);
+    }
+    sorted_chunks.sort_by_key(|(idx, _)| *idx);
+    
+    for (_, chunk) in sorted_chunks {
+        let end_index = (data_index + chunk.len()).min(output.len());
+        output[data_index..end_index].copy_from_slice(&chunk[..end_index - data_index]);
+        data_index = end_index;
+    }
+    
+    Ok(output)
+}
+
+/// Item address still to be read by an `ItemWalker`
+struct PendingItem {
+    address: u64,
+    /// Item whose link led here (0 for the first root item)
+    linked_from: u64,
+    parent_address: u64,
+    /// Type code of the containing item (`None` for root items)
+    parent_type: Option<u32>,
+    parent_path: String,
+    depth: usize,
+}
+
+/// One item yielded by `ItemWalker`
+pub(crate) struct WalkedItem {
+    /// Offset of the item in the logical image - unique within the container
+    pub address: u64,
+    /// Address of the containing folder (0 for root items)
+    pub parent_address: u64,
+    pub depth: usize,
+    pub path: String,
+    /// The item itself; `children` is always empty
+    pub item: Item,
+}
+
+/// Depth-first (pre-order) iterator over the item chain
+///
+/// Reads one item per step, so walking a container with millions of items
+/// only keeps the current path's unvisited siblings in memory. Stops after
+/// the first read error, unless the session is `best_effort`: then the
+/// unreadable item and everything only linked from it are skipped (recorded
+/// in `parse_warnings`) and the walk continues with the siblings of items
+/// already read.
+pub(crate) struct ItemWalker<'a> {
+    session: &'a mut Session,
+    pending: Vec<PendingItem>,
+    with_metadata: bool,
+    /// Items yielded so far, bounded by `MAX_ITEMS`
+    visited: usize,
+}
+
+impl ItemWalker<'_> {
+    /// The session being walked, e.g. to read a yielded item's data
+    pub(crate) fn session(&mut self) -> &mut Session {
+        self.session
+    }
+
+    /// Check the link that led to `pending`, then read the item there
+    fn read_linked(&mut self, pending: &PendingItem) -> Result<(Item, u64, u64), String> {
+        let (from, address) = (pending.linked_from, pending.address);
+        check_item_link(from, address, self.session.logical_size())?;
+        let read = self.session.read_item(address, self.with_metadata)?;
+        if read.0.name.is_empty() {
+            return Err(ItemLinkError::EmptyRecord { from, address }.into());
+        }
+        Ok(read)
+    }
+}
+
+/// A sibling or child link must point forward and stay inside the container
+fn check_item_link(from: u64, address: u64, logical_size: u64) -> Result<(), ItemLinkError> {
+    if address >= logical_size {
+        return Err(ItemLinkError::PastEnd { from, address, logical_size });
+    }
+    if address <= from {
+        return Err(ItemLinkError::Backward { from, address });
+    }
+    Ok(())
+}
+
+impl Iterator for ItemWalker<'_> {
+    type Item = Result<WalkedItem, String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (current, (mut item, next_addr, child_addr)) = loop {
+            let current = self.pending.pop()?;
+            // A corrupt chain can link back on itself - stop instead of looping
+            self.visited += 1;
+            if self.visited > MAX_ITEMS {
+                self.pending.clear();
+                return Some(Err(format!("Corrupt AD1 item chain: more than {MAX_ITEMS} items")));
+            }
+            match self.read_linked(&current) {
+                Ok(read) => break (current, read),
+                Err(e) if self.session.best_effort => {
+                    warn!(address = current.address, error = %e, "Skipping unreadable AD1 item");
+                    self.session.parse_warnings.push(ParseWarning { address: current.address, error: e });
+                }
+                Err(e) => {
+                    self.pending.clear();
+                    return Some(Err(e));
+                }
+            }
+        };
+        item.kind = ItemKind::of(item.item_type, current.parent_type);
+        let path = child_path(&current.parent_path, &item);
+
+        if next_addr != 0 {
+            self.pending.push(PendingItem { address: next_addr, linked_from: current.address, ..current });
+        }
+        // Pushed last so the children are visited before the next sibling
+        if child_addr != 0 {
+            self.pending.push(PendingItem {
+                address: child_addr,
+                linked_from: current.address,
+                parent_address: current.address,
+                parent_type: Some(item.item_type),
+                parent_path: path.clone(),
+                depth: current.depth + 1,
+            });
+        }
+
+        Some(Ok(WalkedItem {
+            address: current.address,
+            parent_address: current.parent_address,
+            depth: current.depth,
+            path,
+            item,
+        }))
+    }
+}
+
+/// Nest a pre-order sequence of (depth, item) into a tree
+fn assemble_tree<I>(walked: I) -> Result<Vec<Item>, String>
+where
+    I: IntoIterator<Item = Result<(usize, Item), String>>,
+{
+    // Items whose children may still follow, innermost last
+    let mut open: Vec<(usize, Item)> = Vec::new();
+    let mut roots = Vec::new();
+    for entry in walked {
+        let (depth, item) = entry?;
+        close_items(&mut open, &mut roots, depth);
+        open.push((depth, item));
+    }
+    close_items(&mut open, &mut roots, 0);
+    Ok(roots)
+}
+
+/// Attach every open item at `depth` or deeper to its parent
+fn close_items(open: &mut Vec<(usize, Item)>, roots: &mut Vec<Item>, depth: usize) {
+    while open.last().is_some_and(|(item_depth, _)| *item_depth >= depth) {
+        let Some((_, item)) = open.pop() else { break };
+        match open.last_mut() {
+            Some((_, parent)) => parent.children.push(item),
+            None => roots.push(item),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::ZlibEncoder;
+    use flate2::Compression;
+    use std::fs::OpenOptions;
+
+    fn item(name: &str) -> Item {
+        Item {
+            id: 0,
+            name: name.to_string(),
+            item_type: 0,
+            kind: ItemKind::File,
+            decompressed_size: 0,
+            zlib_metadata_addr: 0,
+            metadata: Vec::new(),
+            children: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_assemble_tree_from_preorder() {
+        let walked = [(0, "a"), (1, "a1"), (2, "a1x"), (1, "a2"), (0, "b"), (0, "c"), (1, "c1")]
+            .into_iter()
+            .map(|(depth, name)| Ok((depth, item(name))));
+        let roots = assemble_tree(walked).unwrap();
+
+        let names = |items: &[Item]| items.iter().map(|i| i.name.clone()).collect::<Vec<_>>();
+        assert_eq!(names(&roots), vec!["a", "b", "c"]);
+        assert_eq!(names(&roots[0].children), vec!["a1", "a2"]);
+        assert_eq!(names(&roots[0].children[0].children), vec!["a1x"]);
+        assert!(roots[1].children.is_empty());
+        assert_eq!(names(&roots[2].children), vec!["c1"]);
+    }
+
+    fn deflate(data: &[u8]) -> Vec<u8> {
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::best());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    /// Single-segment AD1 with one file item "f.txt" stored as `chunks`
+    ///
+    /// Logical layout: logical header at 0, item at 0x100, zlib address
+    /// table at 0x200 followed by the chunk data.
+    fn write_ad1(dir: &Path, chunk_size: u32, chunk_count: u64, decompressed_size: u64, chunks: &[&[u8]]) -> String {
+        let mut logical = vec![0u8; 0x200];
+        logical[0..15].copy_from_slice(b"ADLOGICALIMAGE\0");
+        logical[0x10..0x14].copy_from_slice(&3u32.to_le_bytes());
+        logical[0x18..0x1c].copy_from_slice(&chunk_size.to_le_bytes());
+        logical[0x24..0x2c].copy_from_slice(&0x100u64.to_le_bytes());
+
+        let item = 0x100;
+        logical[item + 0x18..item + 0x20].copy_from_slice(&0x200u64.to_le_bytes());
+        logical[item + 0x20..item + 0x28].copy_from_slice(&decompressed_size.to_le_bytes());
+        logical[item + 0x2c..item + 0x30].copy_from_slice(&5u32.to_le_bytes());
+        logical[item + 0x30..item + 0x35].copy_from_slice(b"f.txt");
+
+        logical.extend_from_slice(&chunk_count.to_le_bytes());
+        let mut address = 0x200 + 8 + (chunks.len() as u64 + 1) * 8;
+        for chunk in chunks {
+            logical.extend_from_slice(&address.to_le_bytes());
+            address += chunk.len() as u64;
+        }
+        logical.extend_from_slice(&address.to_le_bytes());
+        for chunk in chunks {
+            logical.extend_from_slice(chunk);
+        }
+
+        let mut file = vec![0u8; AD1_LOGICAL_MARGIN as usize];
+        file[..15].copy_from_slice(AD1_SIGNATURE);
+        file[0x18..0x1c].copy_from_slice(&1u32.to_le_bytes());
+        file[0x1c..0x20].copy_from_slice(&1u32.to_le_bytes());
+        file[0x22..0x26].copy_from_slice(&4u32.to_le_bytes());
+        file[0x28..0x2c].copy_from_slice(&512u32.to_le_bytes());
+        file.extend_from_slice(&logical);
+        // Segments carry a trailing margin past the logical data
+        file.extend_from_slice(&[0u8; AD1_LOGICAL_MARGIN as usize]);
+
+        let path = dir.join("image.ad1");
+        fs::write(&path, file).unwrap();
+        path.to_string_lossy().to_string()
+    }
+
+    fn read_only_file(path: &str) -> Result<Arc<Vec<u8>>, String> {
+        let mut session = Session::open(path)?;
+        let file = session.root_items[0].clone();
+        Ok(session.try_read_file_data(&file)?)
+    }
+
+    #[test]
+    fn test_image_stream_hash_concatenates_file_data_in_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let data: Vec<u8> = (0..5000u32).map(|i| (i % 251) as u8).collect();
+        let path = write_ad1(dir.path(), 0x10000, 1, data.len() as u64, &[&deflate(&data)]);
+        assert_eq!(
+            crate::ad1::hash_image_stream(&path, "md5").unwrap(),
+            compute_hash(&data, HashAlgorithm::Md5)
+        );
+
+        // A folder holding two files: the folder adds no bytes, the files
+        // are hashed back to back in stored order
+        let mut bytes = fs::read(&path).unwrap();
+        let at = |logical: usize| AD1_LOGICAL_MARGIN as usize + logical;
+        let record = bytes[at(0x100)..at(0x130)].to_vec();
+        for (address, name, next) in [(0x180, b"g.txt", 0x1c0u64), (0x1c0, b"h.txt", 0)] {
+            bytes[at(address)..at(address + 0x30)].copy_from_slice(&record);
+            bytes[at(address)..at(address + 0x08)].copy_from_slice(&next.to_le_bytes());
+            bytes[at(address + 0x30)..at(address + 0x35)].copy_from_slice(name);
+        }
+        bytes[at(0x108)..at(0x110)].copy_from_slice(&0x180u64.to_le_bytes());
+        bytes[at(0x128)..at(0x12c)].copy_from_slice(&AD1_FOLDER_SIGNATURE.to_le_bytes());
+        fs::write(&path, bytes).unwrap();
+
+        let mut last = (0u64, 0u64);
+        let hash = crate::ad1::hash_image_stream_with_progress(&path, "sha1", |c, t| last = (c, t)).unwrap();
+        assert_eq!(last, (10_000, 10_000));
+        assert_eq!(hash, compute_hash(&[data.clone(), data].concat(), HashAlgorithm::Sha1));
+    }
+
+    #[test]
+    fn test_stream_file_data_matches_whole_item_read() {
+        let streamed = |path: &str| {
+            let mut session = Session::open(path).unwrap();
+            let file = session.root_items[0].clone();
+            let mut chunks = Vec::new();
+            session.stream_file_data(&file, |chunk| chunks.push(chunk.to_vec())).unwrap();
+            (chunks, session.try_read_file_data(&file).unwrap())
+        };
+
+        // Five chunks, the last one partial: one chunk at a time
+        let dir = tempfile::tempdir().unwrap();
+        let data: Vec<u8> = (0..5 * 0x1000 - 100u32).map(|i| (i % 251) as u8).collect();
+        let compressed: Vec<Vec<u8>> = data.chunks(0x1000).map(deflate).collect();
+        let refs: Vec<&[u8]> = compressed.iter().map(Vec::as_slice).collect();
+        let path = write_ad1(dir.path(), 0x1000, 5, data.len() as u64, &refs);
+        let (chunks, whole) = streamed(&path);
+        assert_eq!(chunks.len(), 5);
+        assert!(chunks.iter().all(|chunk| chunk.len() <= 0x1000));
+        assert_eq!(chunks.concat(), *whole);
+        assert_eq!(*whole, data);
+
+        // A stored size past the chunk data reads as trailing zeros either way
+        let dir = tempfile::tempdir().unwrap();
+        let path = write_ad1(dir.path(), 0x1000, 1, 4000, &[&deflate(&data[..3000])]);
+        let (chunks, whole) = streamed(&path);
+        assert_eq!(chunks.concat(), *whole);
+        assert_eq!(whole.len(), 4000);
+    }
+
+    #[test]
+    fn test_best_effort_walk_skips_broken_links() {
+        let dir = tempfile::tempdir().unwrap();
+        let data = b"hello".to_vec();
+        let path = write_ad1(dir.path(), 0x10000, 1, data.len() as u64, &[&deflate(&data)]);
+
+        // f.txt (0x100) -> next sibling g.txt (0x180), child link into nowhere
+        let mut bytes = fs::read(&path).unwrap();
+        let at = |logical: usize| AD1_LOGICAL_MARGIN as usize + logical;
+        bytes[at(0x100)..at(0x108)].copy_from_slice(&0x180u64.to_le_bytes());
+        bytes[at(0x108)..at(0x110)].copy_from_slice(&0xdead_beefu64.to_le_bytes());
+        bytes[at(0x180 + 0x2c)..at(0x180 + 0x30)].copy_from_slice(&5u32.to_le_bytes());
+        bytes[at(0x180 + 0x30)..at(0x180 + 0x35)].copy_from_slice(b"g.txt");
+        fs::write(&path, bytes).unwrap();
+
+        assert!(crate::ad1::info_with_tree_limit(&path, true, 100).is_err());
+
+        let info = crate::ad1::info_with_options(&path, true, 100, true).unwrap();
+        let paths: Vec<_> = info.tree.unwrap().iter().map(|e| e.path.clone()).collect();
+        assert_eq!(paths, vec!["f.txt", "g.txt"]);
+        assert_eq!(info.item_count, 2);
+        // Items were skipped, so their total is unknown
+        assert_eq!(info.logical_size, None);
+        assert_eq!(info.parse_warnings.len(), 1);
+        assert_eq!(info.parse_warnings[0].address, 0xdead_beef);
+        assert!(info.parse_warnings[0].error.contains("Corrupt AD1 structure"), "{}", info.parse_warnings[0].error);
+    }
+
+    #[test]
+    fn test_info_fast_reads_headers_only() {
+        let dir = tempfile::tempdir().unwrap();
+        let data = b"hello".to_vec();
+        let path = write_ad1(dir.path(), 0x10000, 1, data.len() as u64, &[&deflate(&data)]);
+
+        let mut bytes = fs::read(&path).unwrap();
+        let at = |logical: usize| AD1_LOGICAL_MARGIN as usize + logical;
+        bytes[at(0x2c)..at(0x30)].copy_from_slice(&3u32.to_le_bytes());
+        bytes[at(0x5c)..at(0x5f)].copy_from_slice(b"C:\\");
+        bytes[at(0x70)..at(0x8d)].copy_from_slice(b"AccessData FTK Imager 4.7.1.2");
+        // Cut off in the middle of the first item
+        bytes.truncate(at(0x110));
+        fs::write(&path, bytes).unwrap();
+
+        assert!(crate::ad1::info(&path, false).is_err());
+        let info = crate::ad1::info_fast(&path).unwrap();
+        assert_eq!((info.logical.image_version, info.logical.zlib_chunk_size), (3, 0x10000));
+        assert_eq!(info.logical.data_source_name, "C:\\");
+        assert_eq!(info.segment.segment_number, 1);
+        assert_eq!(info.creator_tool.as_deref(), Some("AccessData FTK Imager 4.7.1.2"));
+        assert!(info.segment_issues.is_empty() && info.item_count == 0);
+        assert_eq!(info.logical_size, None);
+
+        // A data source name longer than the window is cut, not read on
+        let mut window = vec![0u8; LOGICAL_HEADER_WINDOW];
+        window[..15].copy_from_slice(b"ADLOGICALIMAGE\0");
+        window[0x2c..0x30].copy_from_slice(&4096u32.to_le_bytes());
+        window[LOGICAL_HEADER_SIZE..].fill(b'x');
+        let header = parse_logical_header_window(&window).unwrap();
+        assert_eq!(header.data_source_name.len(), LOGICAL_HEADER_WINDOW - LOGICAL_HEADER_SIZE);
+    }
+
+    #[test]
+    fn test_quick_check_reads_headers_and_item_ends() {
+        let dir = tempfile::tempdir().unwrap();
+        let data = b"hello".to_vec();
+        let path = write_ad1(dir.path(), 0x10000, 1, data.len() as u64, &[&deflate(&data)]);
+        let checks = crate::ad1::quick_check(&path).unwrap();
+        let names: Vec<_> = checks.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, ["Segment headers", "Logical header", "First item", "Last item"]);
+        assert!(checks.iter().all(|c| c.passed), "{checks:?}");
+        assert_eq!(checks[3].detail, "'f.txt' at offset 256");
+
+        // f.txt links to a sibling past the end: the first item still parses
+        let mut bytes = fs::read(&path).unwrap();
+        let at = |logical: usize| AD1_LOGICAL_MARGIN as usize + logical;
+        bytes[at(0x100)..at(0x108)].copy_from_slice(&0xdead_beefu64.to_le_bytes());
+        fs::write(&path, &bytes).unwrap();
+        let checks = crate::ad1::quick_check(&path).unwrap();
+        assert!(checks[2].passed && !checks[3].passed, "{checks:?}");
+        assert!(checks[3].detail.contains("Corrupt AD1 structure"), "{}", checks[3].detail);
+
+        // A garbled logical header leaves the items unchecked
+        bytes[at(0)..at(4)].copy_from_slice(b"XXXX");
+        fs::write(&path, &bytes).unwrap();
+        let checks = crate::ad1::quick_check(&path).unwrap();
+        assert_eq!(checks.iter().filter(|c| !c.passed).count(), 3);
+        assert!(checks[2].detail.starts_with("Not checked"));
+    }
+
+    #[test]
+    fn test_verify_records_corrupt_and_missing_items() {
+        let dir = tempfile::tempdir().unwrap();
+        let data = b"hello".to_vec();
+        let path = write_ad1(dir.path(), 0x10000, 1, data.len() as u64, &[&deflate(&data)]);
+        let report = crate::ad1::verify_report(&path, "md5", &Default::default(), |_, _| {}).unwrap();
+        assert_eq!(report.summary, VerifySummary { computed: 1, ..Default::default() });
+
+        // Garble the zlib header of f.txt's only chunk (chunk data follows the
+        // two-entry address table at 0x200)
+        let mut bytes = fs::read(&path).unwrap();
+        let chunk = AD1_LOGICAL_MARGIN as usize + 0x200 + 8 + 2 * 8;
+        bytes[chunk..chunk + 2].copy_from_slice(&[0xff, 0xff]);
+        fs::write(&path, &bytes).unwrap();
+
+        let report = crate::ad1::verify_report(&path, "md5", &Default::default(), |_, _| {}).unwrap();
+        assert_eq!(report.summary, VerifySummary { corrupt: 1, ..Default::default() });
+        let entry = &report.entries[0];
+        assert_eq!(entry.target, crate::containers::VerifyTarget::Path { path: "f.txt".to_string(), chunk: Some(0) });
+        assert_eq!((entry.status, entry.legacy_status()), (VerifyStatus::Corrupt, "corrupt"));
+        assert!(entry.computed.is_none());
+        assert!(entry.message.as_deref().unwrap().starts_with("Zlib inflate error in chunk 0"), "{:?}", entry.message);
+
+        let error = crate::ad1::verify_report(&path, "md5", &crate::containers::VerifyOptions { fail_fast: true, ..Default::default() }, |_, _| {}).err().unwrap();
+        assert!(error.starts_with("f.txt: Zlib inflate error in chunk 0"), "{error}");
+
+        // A segment cut off inside the chunk data: the item is missing, not corrupt
+        bytes.truncate(chunk + 4);
+        fs::write(&path, &bytes).unwrap();
+        let entries = crate::ad1::verify(&path, "md5").unwrap();
+        assert_eq!((entries[0].status, entries[0].reason, entries[0].legacy_status()), (VerifyStatus::Error, Some(VerifyReason::MissingData), "missing"));
+        assert!(entries[0].message.as_deref().unwrap().contains("run past the end"), "{:?}", entries[0].message);
+    }
+
+    /// Two-segment AD1 whose first segment spans more than 4 GiB (sparse):
+    /// folder "big" at 0x100 holds "a.txt" just below the segment boundary,
+    /// whose one chunk straddles it, and "b.txt" in the second segment.
+    /// Returns the path and the logical addresses of a.txt and b.txt.
+    fn write_ad1_past_4gib(dir: &Path, data: &[u8]) -> (String, u64, u64) {
+        let fragments = 65_537u32;
+        let span = segment_span(fragments);
+        assert!(span > u32::MAX as u64);
+        let (a, b, table, chunk_start) = (span - 0x100, span + 0x1400, span + 0x1000, span - 0x20);
+        let chunk = deflate(data);
+
+        let mut segments: Vec<File> = (1..=2u32).map(|index| {
+            let mut header = vec![0u8; AD1_LOGICAL_MARGIN as usize];
+            header[..15].copy_from_slice(AD1_SIGNATURE);
+            header[0x18..0x1c].copy_from_slice(&index.to_le_bytes());
+            header[0x1c..0x20].copy_from_slice(&2u32.to_le_bytes());
+            header[0x22..0x26].copy_from_slice(&fragments.to_le_bytes());
+            header[0x28..0x2c].copy_from_slice(&512u32.to_le_bytes());
+            let mut file = File::create(dir.join(format!("big.ad{index}"))).unwrap();
+            file.write_all(&header).unwrap();
+            file.set_len(AD1_LOGICAL_MARGIN + if index == 1 { span } else { 0x2000 }).unwrap();
+            file
+        }).collect();
+        let mut put = |mut logical: u64, mut bytes: &[u8]| {
+            while !bytes.is_empty() {
+                let index = (logical / span) as usize;
+                let within = logical % span;
+                let len = bytes.len().min((span - within) as usize);
+                let file = &mut segments[index];
+                file.seek(SeekFrom::Start(AD1_LOGICAL_MARGIN + within)).unwrap();
+                file.write_all(&bytes[..len]).unwrap();
+                (logical, bytes) = (logical + len as u64, &bytes[len..]);
+            }
+        };
+        let record = |links: [u64; 4], size: u64, item_type: u32, name: &str| {
+            let mut record: Vec<u8> = links.iter().flat_map(|link| link.to_le_bytes()).collect();
+            record.extend_from_slice(&size.to_le_bytes());
+            record.extend_from_slice(&item_type.to_le_bytes());
+            record.extend_from_slice(&(name.len() as u32).to_le_bytes());
+            record.extend_from_slice(name.as_bytes());
+            record
+        };
+
+        let mut logical = vec![0u8; 0x100];
+        logical[0..15].copy_from_slice(b"ADLOGICALIMAGE\0");
+        logical[0x10..0x14].copy_from_slice(&3u32.to_le_bytes());
+        logical[0x18..0x1c].copy_from_slice(&0x10000u32.to_le_bytes());
+        logical[0x24..0x2c].copy_from_slice(&0x100u64.to_le_bytes());
+        put(0, &logical);
+        put(0x100, &record([0, a, 0, 0], 0, AD1_FOLDER_SIGNATURE, "big"));
+        put(a, &record([b, 0, 0, table], data.len() as u64, 0, "a.txt"));
+        put(b, &record([0; 4], 0, 0, "b.txt"));
+        let addresses: Vec<u8> = [1, chunk_start, chunk_start + chunk.len() as u64].iter().flat_map(|v| v.to_le_bytes()).collect();
+        put(table, &addresses);
+        put(chunk_start, &chunk);
+        (dir.join("big.ad1").to_string_lossy().to_string(), a, b)
+    }
+
+    #[test]
+    fn test_items_and_chunks_past_4gib() {
+        let dir = tempfile::tempdir().unwrap();
+        let data: Vec<u8> = (0..3000u32).map(|i| (i * 7 % 251) as u8).collect();
+        let (path, a, b) = write_ad1_past_4gib(dir.path(), &data);
+
+        let mut session = Session::open(&path).unwrap();
+        let folder = &session.root_items[0];
+        let names: Vec<_> = folder.children.iter().map(|item| item.name.as_str()).collect();
+        assert_eq!((folder.name.as_str(), names), ("big", vec!["a.txt", "b.txt"]));
+        let file = folder.children[0].clone();
+        assert_eq!(*session.try_read_file_data(&file).unwrap(), data);
+        assert_eq!(session.last_item_address().unwrap(), b);
+
+        // Links cut to 32 bits land on empty space or point back: a
+        // structured error instead of garbage items
+        let mut first = OpenOptions::new().write(true).open(dir.path().join("big.ad1")).unwrap();
+        first.seek(SeekFrom::Start(AD1_LOGICAL_MARGIN + 0x108)).unwrap();
+        first.write_all(&(a as u32 as u64).to_le_bytes()).unwrap();
+        let error = Session::open(&path).err().unwrap();
+        assert_eq!(error, ItemLinkError::EmptyRecord { from: 0x100, address: a as u32 as u64 }.to_string());
+
+        first.seek(SeekFrom::Start(AD1_LOGICAL_MARGIN + 0x108)).unwrap();
+        first.write_all(&a.to_le_bytes()).unwrap();
+        first.seek(SeekFrom::Start(AD1_LOGICAL_MARGIN + a)).unwrap();
+        first.write_all(&(b as u32 as u64).to_le_bytes()).unwrap();
+        let error = Session::open(&path).err().unwrap();
+        assert_eq!(error, ItemLinkError::Backward { from: a, address: b as u32 as u64 }.to_string());
+    }
+
+    #[test]
+    fn test_corrupt_chunks_fail_cleanly() {
+        let dir = tempfile::tempdir().unwrap();
+        let data = vec![0x41u8; 40_000];
+        let chunk = deflate(&data);
+
+        let path = write_ad1(dir.path(), 0x10000, 1, data.len() as u64, &[&chunk]);
+        assert_eq!(*read_only_file(&path).unwrap(), data);
+
+        // Declared 40 KB, but the chunk inflates to 16 MiB
+        let bomb = deflate(&vec![0u8; 16 * 1024 * 1024]);
+        let path = write_ad1(dir.path(), 0x10000, 1, data.len() as u64, &[&bomb]);
+        let err = read_only_file(&path).unwrap_err();
+        assert!(err.contains("corrupt structure"), "{err}");
+
+        // Truncated chunk data and an absurd chunk count are errors, not panics or OOM
+        let path = write_ad1(dir.path(), 0x10000, 1, data.len() as u64, &[&chunk[..chunk.len() / 2]]);
+        assert!(read_only_file(&path).is_err());
+        let path = write_ad1(dir.path(), 0x10000, u64::MAX / 16, data.len() as u64, &[&chunk]);
+        assert!(read_only_file(&path).unwrap_err().contains("Corrupt AD1 item"));
+        let path = write_ad1(dir.path(), 0x10000, 1, u64::MAX, &[&chunk]);
+        assert!(read_only_file(&path).unwrap_err().contains("Corrupt AD1 item"));
+
+        // The chunk size itself is validated when the container is opened
+        let path = write_ad1(dir.path(), u32::MAX, 1, data.len() as u64, &[&chunk]);
+        assert!(Session::open_headers(&path).is_err());
+    }
+}
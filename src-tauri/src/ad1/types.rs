@@ -1,9 +1,15 @@
 //! Type definitions for AD1 container format
 
+use std::fmt;
+
 use serde::Serialize;
 
+use crate::common::parse_warning::ParseWarning;
+use crate::common::time::Timestamp;
+use crate::containers::{VerifyEntries, VerifyReason, VerifyStatus, VerifyTarget, VERIFY_ENTRY_VERSION};
+
 /// Segment header information (public view)
-#[derive(Serialize)]
+#[derive(Serialize, Clone)]
 pub struct SegmentHeaderInfo {
     pub signature: String,
     pub segment_index: u32,
@@ -12,8 +18,146 @@ pub struct SegmentHeaderInfo {
     pub header_size: u32,
 }
 
+/// Why the segment files of an AD1 set do not add up
+///
+/// Every segment header repeats the set's segment count and carries its own
+/// position, so a short copy or a stray segment from another set is caught
+/// at open time instead of as a zlib error deep in the item tree.
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SegmentSetError {
+    /// A segment the first header counts is not on disk
+    Missing { index: u32, total: u32, file: String },
+    /// The segment exists but its header could not be read
+    Unreadable { index: u32, file: String, error: String },
+    /// The header names a different position than the file's extension
+    IndexMismatch { file: String, expected: u32, found: u32 },
+    /// The header disagrees with the first segment on the segment count
+    CountMismatch { file: String, expected: u32, found: u32 },
+}
+
+impl fmt::Display for SegmentSetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SegmentSetError::Missing { index, total, file } => {
+                write!(f, "segment {index} of {total} is missing ({file})")
+            }
+            SegmentSetError::Unreadable { index, file, error } => {
+                write!(f, "segment {index} ({file}) has an unreadable header: {error}")
+            }
+            SegmentSetError::IndexMismatch { file, expected, found } => {
+                write!(f, "{file} is segment {found} by its header, expected segment {expected}")
+            }
+            SegmentSetError::CountMismatch { file, expected, found } => {
+                write!(f, "{file} declares {found} segments, the first segment declares {expected}")
+            }
+        }
+    }
+}
+
+/// Why the data of one file item could not be read
+///
+/// `chunk` is the zlib chunk within the item (0-based), or `None` when the
+/// item's chunk table itself is unusable. Verification records these
+/// against the item (status "corrupt" or "missing") and moves on.
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ItemDataError {
+    /// The item record, its chunk table or a chunk's zlib stream is damaged
+    Corrupt { chunk: Option<u64>, error: String },
+    /// Bytes the item needs could not be read (truncated segment, I/O error)
+    Missing { chunk: Option<u64>, error: String },
+}
+
+impl ItemDataError {
+    /// Verification entry recorded for the unreadable item at `path`
+    pub fn verify_entry(&self, path: &str) -> VerifyEntry {
+        let (status, reason) = match self {
+            ItemDataError::Corrupt { .. } => (VerifyStatus::Corrupt, None),
+            ItemDataError::Missing { .. } => (VerifyStatus::Error, Some(VerifyReason::MissingData)),
+        };
+        let mut entry = VerifyEntry::new(VerifyTarget::Path { path: path.to_string(), chunk: self.chunk() }, status)
+            .message(self.to_string());
+        entry.reason = reason;
+        entry
+    }
+
+    /// Status recorded for the item in extraction stats
+    pub fn status(&self) -> &'static str {
+        match self {
+            ItemDataError::Corrupt { .. } => "corrupt",
+            ItemDataError::Missing { .. } => "missing",
+        }
+    }
+
+    pub fn chunk(&self) -> Option<u64> {
+        match self {
+            ItemDataError::Corrupt { chunk, .. } | ItemDataError::Missing { chunk, .. } => *chunk,
+        }
+    }
+}
+
+impl fmt::Display for ItemDataError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ItemDataError::Corrupt { chunk: Some(chunk), error } => write!(f, "Zlib inflate error in chunk {chunk}: {error}"),
+            ItemDataError::Corrupt { chunk: None, error } => write!(f, "{error}"),
+            ItemDataError::Missing { chunk: Some(chunk), error } => write!(f, "Chunk {chunk} could not be read: {error}"),
+            ItemDataError::Missing { chunk: None, error } => write!(f, "Chunk table could not be read: {error}"),
+        }
+    }
+}
+
+impl From<ItemDataError> for String {
+    fn from(error: ItemDataError) -> String {
+        error.to_string()
+    }
+}
+
+/// Why an item link could not be followed
+///
+/// FTK writes item records in tree order, so sibling and child links always
+/// point forward, into the container. A link that points back, or lands on
+/// a record with no name, is what an address cut to 32 bits looks like past
+/// 4 GiB: the walk stops there instead of listing garbage items.
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ItemLinkError {
+    /// The link points past the end of the container
+    PastEnd { from: u64, address: u64, logical_size: u64 },
+    /// The link points back to or before the item holding it
+    Backward { from: u64, address: u64 },
+    /// The link lands on an empty record
+    EmptyRecord { from: u64, address: u64 },
+}
+
+impl fmt::Display for ItemLinkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ItemLinkError::PastEnd { from, address, logical_size } => write!(
+                f,
+                "Corrupt AD1 structure: item link at offset {from} points to offset {address}, past the end of the container ({logical_size} bytes)"
+            ),
+            ItemLinkError::Backward { from, address } => write!(
+                f,
+                "Corrupt AD1 structure: item link at offset {from} points back to offset {address} (truncated address?)"
+            ),
+            ItemLinkError::EmptyRecord { from, address } => write!(
+                f,
+                "Corrupt AD1 structure: item link at offset {from} points to an empty record at offset {address} (truncated address?)"
+            ),
+        }
+    }
+}
+
+impl From<ItemLinkError> for String {
+    fn from(error: ItemLinkError) -> String {
+        error.to_string()
+    }
+}
+
 /// Logical header information (public view)
-#[derive(Serialize)]
+#[derive(Serialize, Clone)]
 pub struct LogicalHeaderInfo {
     pub signature: String,
     pub image_version: u32,
@@ -47,53 +191,162 @@ pub struct CompanionLogInfo {
     pub notes: Option<String>,
     pub md5_hash: Option<String>,
     pub sha1_hash: Option<String>,
-    pub acquisition_date: Option<String>,
+    pub acquisition_date: Option<Timestamp>,
+}
+
+/// What an AD1 item holds
+#[derive(Serialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ItemKind {
+    #[default]
+    File,
+    Folder,
+    /// NTFS alternate data stream, a child item of its file
+    AlternateStream,
+    /// Email attachment, a child item of its message
+    Attachment,
+}
+
+impl ItemKind {
+    /// Kind of an item from its type code and that of the item holding it
+    /// (`None` for root items)
+    ///
+    /// Unknown codes on children of files are taken for alternate streams,
+    /// the only thing files hold on NTFS volumes.
+    pub(crate) fn of(item_type: u32, parent_type: Option<u32>) -> Self {
+        match item_type {
+            AD1_FOLDER_SIGNATURE => ItemKind::Folder,
+            AD1_ALTERNATE_STREAM_TYPE => ItemKind::AlternateStream,
+            AD1_ATTACHMENT_TYPE => ItemKind::Attachment,
+            _ if parent_type.is_some_and(|parent| parent != AD1_FOLDER_SIGNATURE) => ItemKind::AlternateStream,
+            _ => ItemKind::File,
+        }
+    }
 }
 
 /// File/folder entry in the AD1 tree
-#[derive(Serialize)]
+#[derive(Serialize, Clone)]
 pub struct TreeEntry {
+    /// Path in the container; an alternate stream is `file:stream`
     pub path: String,
     pub is_dir: bool,
     pub size: u64,
     pub item_type: u32,
+    pub item_kind: ItemKind,
+    /// Offset of the item in the logical image (unique per container)
+    pub address: u64,
+    /// Address of the parent folder item, 0 for root items
+    pub parent_address: u64,
+}
+
+/// Totals sent after the last batch of a streamed tree
+#[derive(Serialize, Clone, Default, Debug, PartialEq, Eq)]
+pub struct TreeSummary {
+    pub item_count: u64,
+    pub file_count: u64,
+    pub folder_count: u64,
+    /// Combined size of all files
+    pub total_size: u64,
+}
+
+/// Verification result entry - shared by every container format
+pub use crate::containers::VerifyEntry;
+
+/// Per-status counts of a verification
+#[derive(Serialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct VerifySummary {
+    /// Computed hash matches the stored one
+    pub ok: usize,
+    /// Computed hash differs from the stored one
+    pub nok: usize,
+    /// No stored hash to compare against
+    pub computed: usize,
+    /// Data damaged (bad chunk table or zlib stream)
+    pub corrupt: usize,
+    /// Data not in the container (truncated segment, read error)
+    pub missing: usize,
+}
+
+impl VerifySummary {
+    pub fn from_entries(entries: &[VerifyEntry]) -> Self {
+        let mut summary = Self::default();
+        for entry in entries {
+            match entry.status {
+                VerifyStatus::Ok => summary.ok += 1,
+                VerifyStatus::Mismatch => summary.nok += 1,
+                VerifyStatus::Skipped => summary.computed += 1,
+                VerifyStatus::Corrupt => summary.corrupt += 1,
+                VerifyStatus::Error => summary.missing += 1,
+            }
+        }
+        summary
+    }
 }
 
-/// Verification result entry
+/// Per-file verification results with their counts
 #[derive(Serialize)]
-pub struct VerifyEntry {
-    pub path: String,
-    pub status: String,
-    /// The hash algorithm used (e.g., "md5", "sha1")
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub algorithm: Option<String>,
-    /// The computed hash value
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub computed: Option<String>,
-    /// The stored hash value from AD1 metadata (if available)
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub stored: Option<String>,
-    /// File size in bytes
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub size: Option<u64>,
+pub struct Ad1VerifyReport {
+    pub algorithm: String,
+    pub entries: Vec<VerifyEntry>,
+    pub summary: VerifySummary,
 }
 
-/// Complete AD1 container information
+impl Ad1VerifyReport {
+    /// The report with its entries in the JSON shape of `version` (see
+    /// [`VerifyEntries::versioned`])
+    pub fn versioned(self, version: Option<u32>) -> VersionedAd1VerifyReport {
+        let entries = VerifyEntries::versioned(self.entries, version);
+        VersionedAd1VerifyReport {
+            version: if matches!(entries, VerifyEntries::Current(_)) { VERIFY_ENTRY_VERSION } else { 1 },
+            algorithm: self.algorithm,
+            entries,
+            summary: self.summary,
+        }
+    }
+}
+
+/// [`Ad1VerifyReport`] as sent to a caller, `version` naming the entry shape
 #[derive(Serialize)]
+pub struct VersionedAd1VerifyReport {
+    pub version: u32,
+    pub algorithm: String,
+    pub entries: VerifyEntries,
+    pub summary: VerifySummary,
+}
+
+/// Complete AD1 container information
+#[derive(Serialize, Clone)]
 pub struct Ad1Info {
     pub segment: SegmentHeaderInfo,
     pub logical: LogicalHeaderInfo,
     pub item_count: u64,
     pub tree: Option<Vec<TreeEntry>>,
+    /// `tree` stops at the entry limit - stream the tree to get all items
+    pub tree_truncated: bool,
     pub segment_files: Option<Vec<String>>,
     /// Size of each segment file in bytes
     pub segment_sizes: Option<Vec<u64>>,
     /// Total size of all segment files combined
     pub total_size: Option<u64>,
+    /// Combined size of all file items (None from `info_fast`, which doesn't
+    /// walk the items, and when a best-effort parse skipped some)
+    pub logical_size: Option<u64>,
     /// Missing segment files (incomplete container)
     pub missing_segments: Option<Vec<String>>,
+    /// Header of each segment file present, in segment order
+    pub segments: Vec<SegmentHeaderInfo>,
+    /// Missing segment files (`info_fast` only - the full parse refuses
+    /// incomplete sets; `quick_check` also reads the other segments' headers)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub segment_issues: Vec<SegmentSetError>,
     pub volume: Option<VolumeInfo>,
+    /// Imaging tool named in the logical header area, when present
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub creator_tool: Option<String>,
     pub companion_log: Option<CompanionLogInfo>,
+    /// Items a best-effort parse skipped; `tree` and `item_count` are partial
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub parse_warnings: Vec<ParseWarning>,
 }
 
 // =============================================================================
@@ -141,6 +394,8 @@ pub(crate) struct Item {
     pub id: u64,
     pub name: String,
     pub item_type: u32,
+    /// Decoded from `item_type` and the parent's type while walking
+    pub kind: ItemKind,
     pub decompressed_size: u64,
     pub zlib_metadata_addr: u64,
     pub metadata: Vec<Metadata>,
@@ -153,8 +408,26 @@ pub(crate) struct Item {
 
 pub(crate) const AD1_SIGNATURE: &[u8; 15] = b"ADSEGMENTEDFILE";
 pub(crate) const AD1_LOGICAL_MARGIN: u64 = 512;
+/// Segment header fields read from the start of every segment
+pub(crate) const SEGMENT_HEADER_SIZE: usize = 0x2c;
+/// Fixed logical header fields, followed by the data source name
+pub(crate) const LOGICAL_HEADER_SIZE: usize = 0x5c;
+/// Upper bound on the data source name in the logical header
+pub(crate) const MAX_DATA_SOURCE_NAME_LENGTH: usize = 64 * 1024;
+/// Bytes read after the segment header by `info_fast`: the logical header,
+/// data source name and volume details
+pub(crate) const LOGICAL_HEADER_WINDOW: usize = 512;
+// Item type codes; plain files are 0
 pub(crate) const AD1_FOLDER_SIGNATURE: u32 = 0x05;
+pub(crate) const AD1_ALTERNATE_STREAM_TYPE: u32 = 0x02;
+pub(crate) const AD1_ATTACHMENT_TYPE: u32 = 0x03;
 pub(crate) const CACHE_SIZE: usize = 100;
+/// File data cache entries in low-memory mode
+pub(crate) const LOW_MEMORY_CACHE_SIZE: usize = 8;
+/// Upper bound on items walked in one container (corrupt/cyclic item chains)
+pub(crate) const MAX_ITEMS: usize = 50_000_000;
+/// Upper bound on metadata entries attached to one item
+pub(crate) const MAX_METADATA_ENTRIES: usize = 4096;
 pub(crate) const SEGMENT_BLOCK_SIZE: u64 = 65_536;
 
 // Metadata categories
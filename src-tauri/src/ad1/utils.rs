@@ -2,14 +2,16 @@
 
 use std::path::Path;
 use std::fs::File;
-use std::io::{Read, Seek, SeekFrom};
-use std::time::SystemTime;
+use std::io::Read;
 use chrono::{Local, NaiveDateTime, TimeZone};
 use filetime::FileTime;
 use tracing::trace;
 
+use super::parser::WalkedItem;
 use super::types::*;
-use crate::common::binary::{read_u32_at, read_u64_at, read_string_at};
+use crate::common::extract_manifest::set_original_times;
+use crate::common::binary::{read_u32_at, read_up_to, ByteReader};
+use crate::common::time::Timestamp;
 
 /// Get segment files with their sizes and track missing segments
 /// Returns (segment_names, segment_sizes, total_size, missing_segments)
@@ -92,14 +94,60 @@ pub fn validate_input(path: &str) -> Result<(), String> {
         return Err("Invalid AD1 segment count".to_string());
     }
 
-    for index in 1..=segment_count {
+    let header = read_segment_header(&mut file)?;
+    check_segment_set(path, &header).map(|_| ())
+}
+
+/// Read the header of every segment the first segment's header counts
+///
+/// Returns the headers found, in segment order, and every way the set is
+/// inconsistent: missing files, unreadable headers, or headers whose index
+/// or segment count contradicts the set.
+pub(crate) fn read_segment_set(path: &str, first: &SegmentHeader) -> (Vec<SegmentHeader>, Vec<SegmentSetError>) {
+    let total = first.segment_number;
+    let mut headers = Vec::with_capacity(total as usize);
+    let mut issues = Vec::new();
+
+    for index in 1..=total {
         let segment_path = build_segment_path(path, index);
-        if !Path::new(&segment_path).exists() {
-            return Err(format!("Missing AD1 segment: {segment_path}"));
+        let file = Path::new(&segment_path).file_name()
+            .map_or_else(|| segment_path.clone(), |name| name.to_string_lossy().to_string());
+        let header = match File::open(&segment_path) {
+            Ok(mut handle) => read_segment_header(&mut handle),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                issues.push(SegmentSetError::Missing { index, total, file });
+                continue;
+            }
+            Err(e) => Err(e.to_string()),
+        };
+        let header = match header {
+            Ok(header) => header,
+            Err(error) => {
+                issues.push(SegmentSetError::Unreadable { index, file, error });
+                continue;
+            }
+        };
+        if header.segment_index != index {
+            issues.push(SegmentSetError::IndexMismatch { file: file.clone(), expected: index, found: header.segment_index });
         }
+        if header.segment_number != total {
+            issues.push(SegmentSetError::CountMismatch { file, expected: total, found: header.segment_number });
+        }
+        headers.push(header);
     }
 
-    Ok(())
+    (headers, issues)
+}
+
+/// Headers of all segments, or an error naming every missing or
+/// contradicting segment
+pub(crate) fn check_segment_set(path: &str, first: &SegmentHeader) -> Result<Vec<SegmentHeader>, String> {
+    let (headers, issues) = read_segment_set(path, first);
+    if issues.is_empty() {
+        return Ok(headers);
+    }
+    let details: Vec<String> = issues.iter().map(ToString::to_string).collect();
+    Err(format!("Inconsistent AD1 segment set: {}", details.join("; ")))
 }
 
 /// Build segment file path from base path and segment index
@@ -115,37 +163,102 @@ pub fn build_segment_path(base: &str, index: u32) -> String {
 
 /// Read segment header from file
 pub fn read_segment_header(file: &mut File) -> Result<SegmentHeader, String> {
-    file.seek(SeekFrom::Start(0))
-        .map_err(|e| format!("Failed to seek segment header: {e}"))?;
-    let mut signature = [0u8; 16];
-    file.read_exact(&mut signature)
-        .map_err(|e| format!("Failed to read segment signature: {e}"))?;
+    let data = read_up_to(file, 0, SEGMENT_HEADER_SIZE)
+        .map_err(|e| format!("Failed to read segment header: {e}"))?;
+    parse_segment_header(&data)
+}
+
+/// Decode the segment header at the start of an AD1 segment
+pub(crate) fn parse_segment_header(data: &[u8]) -> Result<SegmentHeader, String> {
+    let mut reader = ByteReader::new(data);
+    let signature: [u8; 16] = reader.array()
+        .map_err(|_| "File is not of AD1 format".to_string())?;
     if &signature[..15] != AD1_SIGNATURE {
         return Err("File is not of AD1 format".to_string());
     }
 
-    Ok(SegmentHeader {
-        signature,
-        segment_index: read_u32_at(file, 0x18)?,
-        segment_number: read_u32_at(file, 0x1c)?,
-        fragments_size: read_u32_at(file, 0x22)?,
-        header_size: read_u32_at(file, 0x28)?,
-    })
+    let corrupt = |e| format!("Corrupt AD1 segment header: {e}");
+    reader.seek(0x18).map_err(corrupt)?;
+    let segment_index = reader.u32_le().map_err(corrupt)?;
+    let segment_number = reader.u32_le().map_err(corrupt)?;
+    reader.seek(0x22).map_err(corrupt)?;
+    let fragments_size = reader.u32_le().map_err(corrupt)?;
+    reader.seek(0x28).map_err(corrupt)?;
+    let header_size = reader.u32_le().map_err(corrupt)?;
+
+    Ok(SegmentHeader { signature, segment_index, segment_number, fragments_size, header_size })
 }
 
 /// Read logical header from file
 pub fn read_logical_header(file: &mut File) -> Result<LogicalHeader, String> {
-    let signature = read_string_at(file, AD1_LOGICAL_MARGIN, 15)?;
-    let image_version = read_u32_at(file, 0x210)?;
-    let zlib_chunk_size = read_u32_at(file, 0x218)?;
-    let logical_metadata_addr = read_u64_at(file, 0x21c)?;
-    let first_item_addr = read_u64_at(file, 0x224)?;
-    let data_source_name_length = read_u32_at(file, 0x22c)?;
-    let ad_signature = read_string_at(file, 0x230, 3)?;
-    let data_source_name_addr = read_u64_at(file, 0x234)?;
-    let attrguid_footer_addr = read_u64_at(file, 0x23c)?;
-    let locsguid_footer_addr = read_u64_at(file, 0x24c)?;
-    let data_source_name = read_string_at(file, 0x25c, data_source_name_length as usize)?;
+    let mut data = read_up_to(file, AD1_LOGICAL_MARGIN, LOGICAL_HEADER_SIZE)
+        .map_err(|e| format!("Failed to read logical header: {e}"))?;
+    // The data source name follows the fixed fields, its length is the u32
+    // at 0x2c - a garbage length is refused by the parser, not read
+    let mut fields = ByteReader::new(&data);
+    let name_length = fields.seek(0x2c).and_then(|_| fields.u32_le());
+    if let Ok(length) = name_length {
+        let length = (length as usize).min(MAX_DATA_SOURCE_NAME_LENGTH);
+        data.extend(read_up_to(file, AD1_LOGICAL_MARGIN + LOGICAL_HEADER_SIZE as u64, length)
+            .map_err(|e| format!("Failed to read data source name: {e}"))?);
+    }
+    parse_logical_header(&data)
+}
+
+/// The [`LOGICAL_HEADER_WINDOW`] bytes after the segment header, in one read
+pub fn read_header_window(file: &mut File) -> Result<Vec<u8>, String> {
+    read_up_to(file, AD1_LOGICAL_MARGIN, LOGICAL_HEADER_WINDOW)
+        .map_err(|e| format!("Failed to read logical header: {e}"))
+}
+
+/// Decode the logical header from a [`read_header_window`] window
+///
+/// A data source name running past the window is cut at its end rather
+/// than read.
+pub(crate) fn parse_logical_header_window(window: &[u8]) -> Result<LogicalHeader, String> {
+    let name_end = LOGICAL_HEADER_SIZE + data_source_name_length(window);
+    let cut_short = window.len() >= LOGICAL_HEADER_SIZE && window.len() < name_end;
+    if cut_short && name_end - LOGICAL_HEADER_SIZE <= MAX_DATA_SOURCE_NAME_LENGTH {
+        // Zero padding ends the name where the window does
+        let mut data = window.to_vec();
+        data.resize(name_end, 0);
+        return parse_logical_header(&data);
+    }
+    parse_logical_header(window)
+}
+
+/// The u32 at 0x2c of the logical header (0 if the header is cut short)
+fn data_source_name_length(logical: &[u8]) -> usize {
+    let mut fields = ByteReader::new(logical);
+    fields.seek(0x2c).and_then(|_| fields.u32_le()).unwrap_or(0) as usize
+}
+
+/// Decode the logical header (`data` starts at [`AD1_LOGICAL_MARGIN`])
+pub(crate) fn parse_logical_header(data: &[u8]) -> Result<LogicalHeader, String> {
+    let corrupt = |e| format!("Corrupt AD1 logical header: {e}");
+    let mut reader = ByteReader::at(data, AD1_LOGICAL_MARGIN);
+    let signature = bytes_to_string(reader.bytes(15).map_err(corrupt)?);
+    reader.seek(0x10).map_err(corrupt)?;
+    let image_version = reader.u32_le().map_err(corrupt)?;
+    reader.seek(0x18).map_err(corrupt)?;
+    let zlib_chunk_size = reader.u32_le().map_err(corrupt)?;
+    let logical_metadata_addr = reader.u64_le().map_err(corrupt)?;
+    let first_item_addr = reader.u64_le().map_err(corrupt)?;
+    let data_source_name_length = reader.u32_le().map_err(corrupt)?;
+    let ad_signature = bytes_to_string(reader.bytes(3).map_err(corrupt)?);
+    reader.seek(0x34).map_err(corrupt)?;
+    let data_source_name_addr = reader.u64_le().map_err(corrupt)?;
+    let attrguid_footer_addr = reader.u64_le().map_err(corrupt)?;
+    reader.seek(0x4c).map_err(corrupt)?;
+    let locsguid_footer_addr = reader.u64_le().map_err(corrupt)?;
+    if data_source_name_length as usize > MAX_DATA_SOURCE_NAME_LENGTH {
+        return Err(format!(
+            "Corrupt AD1 logical header: data source name of {} bytes (limit {})",
+            data_source_name_length, MAX_DATA_SOURCE_NAME_LENGTH
+        ));
+    }
+    reader.seek(LOGICAL_HEADER_SIZE).map_err(corrupt)?;
+    let data_source_name = bytes_to_string(reader.bytes(data_source_name_length as usize).map_err(corrupt)?);
 
     Ok(LogicalHeader {
         signature: copy_into_array(&signature, 16)?,
@@ -176,6 +289,43 @@ pub fn segment_span(fragments_size: u32) -> u64 {
     (fragments_size as u64 * SEGMENT_BLOCK_SIZE).saturating_sub(AD1_LOGICAL_MARGIN)
 }
 
+/// One piece of a logical read: (segment index, data offset in that segment, buffer range)
+pub(crate) type SegmentRange = (usize, u64, std::ops::Range<usize>);
+
+/// Split a read of `length` bytes at logical `offset` into per-segment reads
+///
+/// `file_sizes` are the data sizes of the segments, each spanning `seg_span`
+/// logical bytes except possibly the last.
+pub(crate) fn segment_reads(offset: u64, length: usize, seg_span: u64, file_sizes: &[u64]) -> Result<Vec<SegmentRange>, String> {
+    if seg_span == 0 {
+        return Err("Invalid AD1 fragment size".to_string());
+    }
+    let mut reads = Vec::new();
+    let mut remaining = length as u64;
+    let mut buf_cursor = 0usize;
+    let mut file_cursor = usize::try_from(offset / seg_span).map_err(|_| "AD1 offset out of range".to_string())?;
+    let mut data_cursor = offset - (seg_span * file_cursor as u64);
+
+    while remaining > 0 {
+        let file_size = file_sizes
+            .get(file_cursor)
+            .copied()
+            .ok_or_else(|| "AD1 offset out of range".to_string())?;
+        let to_read = remaining.min(file_size.saturating_sub(data_cursor));
+        if to_read == 0 {
+            return Err("AD1 offset out of range".to_string());
+        }
+        reads.push((file_cursor, data_cursor, buf_cursor..buf_cursor + to_read as usize));
+
+        buf_cursor += to_read as usize;
+        remaining -= to_read;
+        data_cursor = 0;
+        file_cursor += 1;
+    }
+
+    Ok(reads)
+}
+
 /// Convert bytes to string (stops at null terminator)
 pub fn bytes_to_string(bytes: &[u8]) -> String {
     let end = bytes.iter().position(|b| *b == 0).unwrap_or(bytes.len());
@@ -229,6 +379,12 @@ pub fn logical_header_info(header: &LogicalHeader) -> LogicalHeaderInfo {
 
 /// Apply metadata timestamps to extracted file
 pub fn apply_metadata(path: &Path, metadata: &[Metadata]) -> Result<(), String> {
+    let (access_time, modified_time) = item_file_times(metadata);
+    set_original_times(path, access_time, modified_time)
+}
+
+/// Access and modification times stored in item metadata
+pub fn item_file_times(metadata: &[Metadata]) -> (Option<FileTime>, Option<FileTime>) {
     let mut access_time = None;
     let mut modified_time = None;
 
@@ -243,17 +399,7 @@ pub fn apply_metadata(path: &Path, metadata: &[Metadata]) -> Result<(), String>
             _ => {}
         }
     }
-
-    if access_time.is_none() && modified_time.is_none() {
-        return Ok(());
-    }
-
-    let now = FileTime::from_system_time(SystemTime::now());
-    let atime = access_time.unwrap_or(now);
-    let mtime = modified_time.unwrap_or(atime);
-    filetime::set_file_times(path, atime, mtime)
-        .map_err(|e| format!("Failed to set file times for {:?}: {e}", path))?;
-    Ok(())
+    (access_time, modified_time)
 }
 
 /// Parse AD1 timestamp string to FileTime
@@ -299,19 +445,36 @@ pub fn find_hash(metadata: &[Metadata], key: u32) -> Option<String> {
         })
 }
 
-/// Collect tree entries recursively
-pub fn collect_tree(items: &[Item], parent_path: &str, out: &mut Vec<TreeEntry>) {
-    for item in items {
-        let path = join_path(parent_path, &item.name);
-        let is_dir = item.item_type == AD1_FOLDER_SIGNATURE;
-        let size = if is_dir { 0 } else { item.decompressed_size };
-        out.push(TreeEntry {
-            path: path.clone(),
-            is_dir,
-            size,
-            item_type: item.item_type,
-        });
-        collect_tree(&item.children, &path, out);
+/// Container path of `item` under `parent_path`
+///
+/// An alternate stream reads `file:stream`, the way Windows names it.
+pub(crate) fn child_path(parent_path: &str, item: &Item) -> String {
+    match item.kind {
+        ItemKind::AlternateStream => format!("{parent_path}:{}", child_name(item)),
+        _ => join_path(parent_path, &item.name),
+    }
+}
+
+/// Name of an alternate stream or attachment without its file's name
+///
+/// FTK may store a stream as `file.txt:stream` or `file.txt:stream:$DATA`;
+/// this returns `stream`.
+pub(crate) fn child_name(item: &Item) -> &str {
+    let name = item.name.strip_suffix(":$DATA").unwrap_or(&item.name);
+    name.rsplit_once(':').map_or(name, |(_, stream)| stream)
+}
+
+/// Tree entry for an item yielded by the item walker
+pub(crate) fn tree_entry(walked: &WalkedItem) -> TreeEntry {
+    let is_dir = walked.item.item_type == AD1_FOLDER_SIGNATURE;
+    TreeEntry {
+        path: walked.path.clone(),
+        is_dir,
+        size: if is_dir { 0 } else { walked.item.decompressed_size },
+        item_type: walked.item.item_type,
+        item_kind: walked.item.kind,
+        address: walked.address,
+        parent_address: walked.parent_address,
     }
 }
 
@@ -323,15 +486,19 @@ pub fn count_files(items: &[Item]) -> usize {
     }).sum()
 }
 
-/// Parse volume info from AD1 header region
-pub fn parse_volume_info(file: &mut File) -> Option<VolumeInfo> {
+/// Parse volume info from the header window (see [`read_header_window`])
+pub fn parse_volume_info(window: &[u8]) -> Option<VolumeInfo> {
     // Volume info is typically at offset 0x2A0+ in the logical header
     // Format: "C:\:NONAME [NTFS]" followed by OS info like "Windows XP (NTFS 3.1)"
-    
+    let field = |offset: u64, length: usize| {
+        let start = (offset - AD1_LOGICAL_MARGIN) as usize;
+        window.get(start..(start + length).min(window.len())).map(bytes_to_string)
+    };
+
     let mut info = VolumeInfo::default();
     
     // Read volume label region (around 0x2A0-0x2C0)
-    if let Ok(volume_str) = read_string_at(file, 0x2A8, 64) {
+    if let Some(volume_str) = field(0x2A8, 64) {
         let volume_trimmed = volume_str.trim_matches(char::from(0)).trim();
         if !volume_trimmed.is_empty() && volume_trimmed.contains(':') {
             // Parse "C:\:NONAME [NTFS]" format
@@ -347,7 +514,7 @@ pub fn parse_volume_info(file: &mut File) -> Option<VolumeInfo> {
     }
     
     // Read OS info region (around 0x370-0x3A0)
-    if let Ok(os_str) = read_string_at(file, 0x370, 64) {
+    if let Some(os_str) = field(0x370, 64) {
         let os_trimmed = os_str.trim_matches(char::from(0)).trim();
         if !os_trimmed.is_empty() && (os_trimmed.contains("Windows") || os_trimmed.contains("NTFS") || os_trimmed.contains("Linux")) {
             info.os_info = Some(os_trimmed.to_string());
@@ -355,7 +522,7 @@ pub fn parse_volume_info(file: &mut File) -> Option<VolumeInfo> {
     }
     
     // Read block size (typically at 0x2E8)
-    if let Ok(block_size_str) = read_string_at(file, 0x2E8, 8) {
+    if let Some(block_size_str) = field(0x2E8, 8) {
         let block_trimmed = block_size_str.trim_matches(char::from(0)).trim();
         if let Ok(block_size) = block_trimmed.parse::<u32>() {
             if block_size > 0 && block_size <= 65536 {
@@ -372,6 +539,18 @@ pub fn parse_volume_info(file: &mut File) -> Option<VolumeInfo> {
     }
 }
 
+/// Name and version of the imaging tool, if the header window carries one
+///
+/// Looks for a printable run naming FTK Imager or its vendors past the
+/// fixed logical header fields and the data source name.
+pub fn find_creator_tool(window: &[u8]) -> Option<String> {
+    const MARKERS: [&str; 3] = ["FTK", "AccessData", "Exterro"];
+    window.get(LOGICAL_HEADER_SIZE + data_source_name_length(window)..)?
+        .split(|b| !(0x20..0x7f).contains(b))
+        .map(|run| String::from_utf8_lossy(run).trim().to_string())
+        .find(|run| run.len() >= 6 && MARKERS.iter().any(|marker| run.contains(marker)))
+}
+
 /// Parse companion log file (.ad1.txt) for case metadata
 pub fn parse_companion_log(ad1_path: &str) -> Option<CompanionLogInfo> {
     use std::io::BufRead;
@@ -426,7 +605,7 @@ pub fn parse_companion_log(ad1_path: &str) -> Option<CompanionLogInfo> {
                             info.sha1_hash = Some(value);
                         }
                         "acquisition date" | "acquired" | "date" => {
-                            info.acquisition_date = Some(value);
+                            info.acquisition_date = Some(Timestamp::parse(&value));
                         }
                         "notes" => {
                             if !value.is_empty() {
@@ -524,4 +703,82 @@ mod tests {
         assert_eq!(&result[..2], b"hi");
         assert_eq!(&result[2..], &[0, 0, 0, 0, 0, 0]);
     }
+
+    /// Header-only segment claiming position `index` of `count`
+    fn write_segment(dir: &Path, index: u32, header_index: u32, count: u32) {
+        let mut file = vec![0u8; 512];
+        file[..15].copy_from_slice(AD1_SIGNATURE);
+        file[0x18..0x1c].copy_from_slice(&header_index.to_le_bytes());
+        file[0x1c..0x20].copy_from_slice(&count.to_le_bytes());
+        file[0x22..0x26].copy_from_slice(&4u32.to_le_bytes());
+        file[0x28..0x2c].copy_from_slice(&512u32.to_le_bytes());
+        std::fs::write(dir.join(format!("image.ad{index}")), file).unwrap();
+    }
+
+    fn first_header(path: &str) -> SegmentHeader {
+        read_segment_header(&mut File::open(path).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_segment_set_complete_and_missing() {
+        let dir = tempfile::tempdir().unwrap();
+        for index in 1..=3 {
+            write_segment(dir.path(), index, index, 3);
+        }
+        let path = dir.path().join("image.ad1").to_string_lossy().to_string();
+        let headers = check_segment_set(&path, &first_header(&path)).unwrap();
+        assert_eq!(headers.iter().map(|h| h.segment_index).collect::<Vec<_>>(), vec![1, 2, 3]);
+        assert!(validate_input(&path).is_ok());
+
+        std::fs::remove_file(dir.path().join("image.ad2")).unwrap();
+        let (headers, issues) = read_segment_set(&path, &first_header(&path));
+        assert_eq!(headers.len(), 2);
+        assert_eq!(issues, vec![SegmentSetError::Missing { index: 2, total: 3, file: "image.ad2".to_string() }]);
+        let error = validate_input(&path).unwrap_err();
+        assert!(error.contains("segment 2 of 3 is missing (image.ad2)"), "{error}");
+    }
+
+    #[test]
+    fn test_segment_set_contradicting_headers() {
+        let dir = tempfile::tempdir().unwrap();
+        write_segment(dir.path(), 1, 1, 3);
+        // A renamed .ad3 in place of .ad2, and an .ad3 from a 4-segment set
+        write_segment(dir.path(), 2, 3, 3);
+        write_segment(dir.path(), 3, 3, 4);
+        let path = dir.path().join("image.ad1").to_string_lossy().to_string();
+
+        let (headers, issues) = read_segment_set(&path, &first_header(&path));
+        assert_eq!(headers.len(), 3);
+        assert_eq!(issues, vec![
+            SegmentSetError::IndexMismatch { file: "image.ad2".to_string(), expected: 2, found: 3 },
+            SegmentSetError::CountMismatch { file: "image.ad3".to_string(), expected: 3, found: 4 },
+        ]);
+        let error = check_segment_set(&path, &first_header(&path)).err().unwrap();
+        assert!(error.contains("image.ad2 is segment 3 by its header"), "{error}");
+        assert!(error.contains("image.ad3 declares 4 segments"), "{error}");
+    }
+
+    #[test]
+    fn test_short_headers_are_truncation_errors() {
+        let mut segment = vec![0u8; SEGMENT_HEADER_SIZE];
+        segment[..15].copy_from_slice(AD1_SIGNATURE);
+        segment[0x1c..0x20].copy_from_slice(&2u32.to_le_bytes());
+        assert_eq!(parse_segment_header(&segment).unwrap().segment_number, 2);
+        let error = parse_segment_header(&segment[..0x24]).err().unwrap();
+        assert_eq!(error, "Corrupt AD1 segment header: truncated structure at offset 34: 4 bytes needed, 2 left");
+        assert_eq!(parse_segment_header(&segment[..8]).err().unwrap(), "File is not of AD1 format");
+
+        let mut logical = vec![0u8; LOGICAL_HEADER_SIZE];
+        logical[..15].copy_from_slice(b"ADLOGICALIMAGE\0");
+        logical[0x2c..0x30].copy_from_slice(&8u32.to_le_bytes());
+        logical.extend_from_slice(b"C:\\");
+        let error = parse_logical_header(&logical).err().unwrap();
+        assert_eq!(error, "Corrupt AD1 logical header: truncated structure at offset 604: 8 bytes needed, 3 left");
+        logical.extend_from_slice(b"Users");
+        assert_eq!(parse_logical_header(&logical).unwrap().data_source_name, "C:\\Users");
+        assert!(parse_logical_header(&logical[..0x20]).is_err());
+
+        logical[0x2c..0x30].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(parse_logical_header(&logical).err().unwrap().contains("data source name of 4294967295 bytes"));
+    }
 }
@@ -379,7 +379,8 @@ fn check_aff4(file: &mut File) -> Result<bool, String> {
     
     if let Ok(mut archive) = zip::ZipArchive::new(reader) {
         for i in 0..archive.len().min(20) { // Check first 20 entries
-            if let Ok(entry) = archive.by_index(i) {
+            // Raw: names are readable without the password of encrypted entries
+            if let Ok(entry) = archive.by_index_raw(i) {
                 let name = entry.name().to_lowercase();
                 if name == "container.description" || name.contains("aff4") {
                     return Ok(true);
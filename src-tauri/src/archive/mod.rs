@@ -27,11 +27,11 @@
 //! ```text
 //! archive/
 //! ├── mod.rs        - Main entry point, info() function
-//! ├── types.rs      - ArchiveFormat, ArchiveInfo
+//! ├── types.rs      - ArchiveFormat, ArchiveInfo, ArchiveSetReport
 //! ├── detection.rs  - Magic signatures, format detection
-//! ├── sevenz.rs     - 7-Zip header parsing, CRC
-//! ├── zip.rs        - ZIP/ZIP64 EOCD parsing
-//! └── segments.rs   - Multi-part archive discovery
+//! ├── sevenz.rs     - 7-Zip header parsing, Start/Next Header CRC checks
+//! ├── zip.rs        - ZIP/ZIP64 EOCD parsing, encrypted entry reading
+//! └── segments.rs   - Multi-part archive discovery and set validation
 //! ```
 //!
 //! Note: UFED detection in ZIPs is handled by `ufed::archive_scan`
@@ -43,7 +43,7 @@ pub mod zip;
 pub mod segments;
 
 // Re-exports for convenience
-pub use types::{ArchiveFormat, ArchiveInfo};
+pub use types::{ArchiveFormat, ArchiveInfo, ArchiveSetReport, EncryptedZipEntry, ZipEncryption, ZipEncryptionSummary, ZipEntryError};
 pub use detection::{is_archive, detect_archive_format, is_7z_segment};
 // Note: is_first_segment, is_continuation_segment are in containers::segments
 // which provides unified handling for all container types
@@ -51,6 +51,16 @@ pub use detection::{is_archive, detect_archive_format, is_7z_segment};
 use std::path::Path;
 use tracing::debug;
 
+use crate::common::capabilities::FormatCapabilities;
+
+/// Archive metadata and segment discovery only
+pub const fn capabilities() -> FormatCapabilities {
+    FormatCapabilities {
+        notes: &["Archive contents are not listed, verified or extracted - use standard archive tools"],
+        ..FormatCapabilities::INFO_ONLY
+    }
+}
+
 /// Get archive information including segment discovery
 pub fn info(path: &str) -> Result<ArchiveInfo, String> {
     debug!(path = %path, "Getting archive info");
@@ -74,37 +84,49 @@ pub fn info(path: &str) -> Result<ArchiveInfo, String> {
     let last_segment = segment_names.last().cloned().unwrap_or_default();
     let is_multipart = segment_count > 1;
     
+    // Check the set is whole before trusting metadata read from its parts
+    let set_report = match segments::validate_set(path, format) {
+        Ok(report) => Some(report),
+        Err(e) => {
+            debug!(path = %path, error = %e, "Archive set validation failed");
+            None
+        }
+    };
+    
     // Parse format-specific metadata
-    let (entry_count, central_dir_offset, central_dir_size, mut encrypted_headers, aes_encrypted) = 
+    let (entry_count, uncompressed_size, central_dir_offset, central_dir_size, mut encrypted_headers, aes_encrypted, zip_encryption) = 
         match format {
             ArchiveFormat::Zip | ArchiveFormat::Zip64 => {
                 let meta = zip::parse_metadata(path).unwrap_or_default();
-                (meta.entry_count, meta.central_dir_offset, meta.central_dir_size, 
-                 meta.encrypted_headers, meta.aes_encrypted)
+                (meta.entry_count, meta.uncompressed_size, meta.central_dir_offset, meta.central_dir_size, 
+                 meta.encrypted_headers, meta.aes_encrypted, meta.encryption)
             }
-            _ => (None, None, None, false, false),
+            _ => (None, None, None, None, false, false, None),
         };
     
     // Parse 7z-specific metadata with full Start Header details
-    let (next_header_offset, next_header_size, version, start_header_crc_valid, next_header_crc, sevenz_encrypted) = 
-        match format {
-            ArchiveFormat::SevenZip => {
-                let meta = sevenz::parse_metadata(path).unwrap_or_default();
-                (meta.next_header_offset, meta.next_header_size, meta.version,
-                 meta.start_header_crc_valid, meta.next_header_crc, meta.encrypted)
-            }
-            _ => (None, None, None, None, None, false),
-        };
+    // (read across all parts - the Next Header is usually in the last one)
+    let sevenz_meta = match format {
+        ArchiveFormat::SevenZip => sevenz::parse_set_metadata(&segment_names).unwrap_or_default(),
+        _ => sevenz::SevenZipMetadata::default(),
+    };
     
     // Set encrypted_headers for 7z if detected
-    if sevenz_encrypted {
+    if sevenz_meta.encrypted {
         encrypted_headers = true;
     }
     
+    // ZIP never hides names; 7z does once its header is encrypted
+    let filenames_visible = match format {
+        ArchiveFormat::Zip | ArchiveFormat::Zip64 => Some(true),
+        ArchiveFormat::SevenZip => Some(!encrypted_headers),
+        _ => None,
+    };
+    
     // Detect UFED files (UFDR/UFDX/UFD) inside the archive
     let (ufed_detected, ufed_files) = match format {
         ArchiveFormat::Zip | ArchiveFormat::Zip64 => {
-            crate::ufed::detect_in_zip(path).unwrap_or((false, vec![]))
+            crate::ufed::detect_in_zip(path, None).unwrap_or((false, vec![]))
         }
         _ => (false, vec![]),
     };
@@ -116,6 +138,8 @@ pub fn info(path: &str) -> Result<ArchiveInfo, String> {
         total_size = total_size,
         entry_count = ?entry_count,
         ufed_detected = ufed_detected,
+        set_complete = ?set_report.as_ref().map(|r| r.complete),
+        truncated = sevenz_meta.truncated,
         "Archive info loaded"
     );
     
@@ -129,16 +153,22 @@ pub fn info(path: &str) -> Result<ArchiveInfo, String> {
         last_segment,
         is_multipart,
         entry_count,
+        uncompressed_size,
         encrypted_headers,
         aes_encrypted,
+        filenames_visible,
+        zip_encryption,
         central_dir_offset,
         central_dir_size,
-        next_header_offset,
-        next_header_size,
-        version,
-        start_header_crc_valid,
-        next_header_crc,
+        next_header_offset: sevenz_meta.next_header_offset,
+        next_header_size: sevenz_meta.next_header_size,
+        version: sevenz_meta.version,
+        start_header_crc_valid: sevenz_meta.start_header_crc_valid,
+        next_header_crc: sevenz_meta.next_header_crc,
+        next_header_crc_valid: sevenz_meta.next_header_crc_valid,
+        truncated: sevenz_meta.truncated,
         ufed_detected,
         ufed_files,
+        set_report,
     })
 }
@@ -5,12 +5,22 @@
 //! - ZIP: .zip.001, .z01, .z02, etc.
 //! - RAR: .rar, .r00, .r01, etc.
 //!
+//! [`validate_set`] checks a discovered set for gaps, stray parts and parts
+//! whose own headers disagree with their file names.
+//!
 //! Note: For unified segment classification (is_first_segment, is_continuation_segment),
 //! use `containers::segments` which handles all format types.
 
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
 use std::path::Path;
 
-use super::types::ArchiveFormat;
+use tracing::debug;
+
+use super::detection::{RAR4_MAGIC, RAR5_MAGIC, SEVEN_ZIP_MAGIC, ZIP_EOCD_SIG, ZIP_LOCAL_HEADER_SIG};
+use super::sevenz::crc32;
+use super::types::{ArchiveFormat, ArchiveSetReport};
 
 // =============================================================================
 // Segment Pattern Types
@@ -30,12 +40,23 @@ pub enum SegmentPatternType {
     Single,
 }
 
+/// Segment number given to the `.zip` part of a `.z01` split set
+const ZIP_SPLIT_LAST: u32 = 999;
+
 // =============================================================================
 // Segment Discovery
 // =============================================================================
 
-/// Discover all segments of a multi-part archive
-pub fn discover_segments(path: &str, format: ArchiveFormat) -> Result<(Vec<String>, Vec<u64>), String> {
+/// Segments of a set found next to an archive part
+struct FoundSegments {
+    /// Base name in the casing of the examined part
+    base: String,
+    pattern: SegmentPatternType,
+    /// (full path, size, segment number), sorted by segment number
+    segments: Vec<(String, u64, u32)>,
+}
+
+fn find_segments(path: &str, format: ArchiveFormat) -> Result<FoundSegments, String> {
     let path_obj = Path::new(path);
     let dir = path_obj.parent()
         .ok_or_else(|| "Cannot determine parent directory".to_string())?;
@@ -46,7 +67,11 @@ pub fn discover_segments(path: &str, format: ArchiveFormat) -> Result<(Vec<Strin
     let lower = filename.to_lowercase();
     
     // Get the base name for segment matching
-    let (base_name, pattern_type) = get_segment_pattern(&lower, format);
+    let (base_name, pattern) = get_segment_pattern(&lower, format);
+    let base = filename.get(..base_name.len())
+        .filter(|b| b.to_lowercase() == base_name)
+        .unwrap_or(&base_name)
+        .to_string();
     
     let mut segments: Vec<(String, u64, u32)> = Vec::new();
     
@@ -56,7 +81,7 @@ pub fn discover_segments(path: &str, format: ArchiveFormat) -> Result<(Vec<Strin
             let entry_name = entry.file_name().to_string_lossy().to_string();
             let entry_lower = entry_name.to_lowercase();
             
-            if let Some(seg_num) = match_segment(&entry_lower, &base_name, pattern_type) {
+            if let Some(seg_num) = match_segment(&entry_lower, &base_name, pattern) {
                 if let Ok(meta) = entry.metadata() {
                     if meta.is_file() {
                         let full_path = entry.path().to_string_lossy().to_string();
@@ -70,16 +95,23 @@ pub fn discover_segments(path: &str, format: ArchiveFormat) -> Result<(Vec<Strin
     // Sort by segment number
     segments.sort_by_key(|(_, _, num)| *num);
     
+    Ok(FoundSegments { base, pattern, segments })
+}
+
+/// Discover all segments of a multi-part archive
+pub fn discover_segments(path: &str, format: ArchiveFormat) -> Result<(Vec<String>, Vec<u64>), String> {
+    let found = find_segments(path, format)?;
+    
     // If no segments found, just use the original file
-    if segments.is_empty() {
+    if found.segments.is_empty() {
         let size = std::fs::metadata(path)
             .map(|m| m.len())
             .unwrap_or(0);
         return Ok((vec![path.to_string()], vec![size]));
     }
     
-    let names: Vec<String> = segments.iter().map(|(n, _, _)| n.clone()).collect();
-    let sizes: Vec<u64> = segments.iter().map(|(_, s, _)| *s).collect();
+    let names: Vec<String> = found.segments.iter().map(|(n, _, _)| n.clone()).collect();
+    let sizes: Vec<u64> = found.segments.iter().map(|(_, s, _)| *s).collect();
     
     Ok((names, sizes))
 }
@@ -188,7 +220,7 @@ pub fn match_segment(entry_lower: &str, base_name: &str, pattern: SegmentPattern
         SegmentPatternType::ZipSplit => {
             // Match base.zip (last segment) or base.zNN
             if entry_lower == format!("{}.zip", base_name) {
-                return Some(999); // ZIP file is always last in split archives
+                return Some(ZIP_SPLIT_LAST); // ZIP file is always last in split archives
             }
             let prefix = format!("{}.", base_name);
             if entry_lower.starts_with(&prefix) {
@@ -210,3 +242,524 @@ pub fn match_segment(entry_lower: &str, base_name: &str, pattern: SegmentPattern
         }
     }
 }
+
+// =============================================================================
+// Set Validation
+// =============================================================================
+
+/// ZIP split marker at the start of the first part of a split archive
+const ZIP_SPLIT_SIG: &[u8] = &[0x50, 0x4B, 0x07, 0x08];
+/// Marker PKZIP leaves when a split archive needed only one part
+const ZIP_SPLIT_TEMP_SIG: &[u8] = &[0x50, 0x4B, 0x30, 0x30];
+/// End of central directory record plus the longest possible comment
+const ZIP_EOCD_SEARCH: u64 = 22 + 0xFFFF;
+/// Enough of a RAR volume's start for the marker and main header
+const RAR_HEAD_READ: usize = 64;
+/// Enough of a RAR volume's tail for the end of archive header
+const RAR_TAIL_READ: u64 = 64;
+/// Upper bound on trailing parts reported from a 7z start header
+const MAX_REPORTED_TRAILING: u64 = 1000;
+
+// RAR4 headers
+const RAR4_MAIN_HEAD: u8 = 0x73;
+const RAR4_END_HEAD: u8 = 0x7B;
+const RAR4_MHD_VOLUME: u16 = 0x0001;
+const RAR4_MHD_FIRSTVOLUME: u16 = 0x0100;
+const RAR4_EARC_NEXT_VOLUME: u16 = 0x0001;
+const RAR4_EARC_DATACRC: u16 = 0x0002;
+const RAR4_EARC_VOLNUMBER: u16 = 0x0008;
+
+// RAR5 headers
+const RAR5_MAIN_HEAD: u64 = 1;
+const RAR5_END_HEAD: u8 = 5;
+const RAR5_HFL_EXTRA: u64 = 0x0001;
+const RAR5_HFL_DATA: u64 = 0x0002;
+const RAR5_MHFL_VOLUME: u64 = 0x0001;
+const RAR5_MHFL_VOLNUMBER: u64 = 0x0002;
+const RAR5_EHFL_NEXTVOLUME: u8 = 0x01;
+
+/// Check that a multi-part archive set is complete and consistent
+///
+/// Besides gaps in the part numbering, this checks what the parts record
+/// about themselves:
+/// - 7z/ZIP splits: only the first part carries the format signature, so a
+///   continuation starting with one is a separately created archive. The 7z
+///   start header and the ZIP end of central directory also reveal missing
+///   trailing parts.
+/// - RAR volumes: each volume's headers must agree with the volume number
+///   its file name implies, and the last volume must not announce another.
+///
+/// Only part headers and tails are read, so this is cheap enough to run
+/// before metadata parsing.
+pub fn validate_set(path: &str, format: ArchiveFormat) -> Result<ArchiveSetReport, String> {
+    let found = find_segments(path, format)?;
+    let mut report = ArchiveSetReport::default();
+    
+    // `.partN.rar` volumes are not discovered as a set, so there is nothing to compare
+    if !found.segments.is_empty() && !uses_rar_part_naming(&found) {
+        check_numbering(&found, &mut report);
+        match found.pattern {
+            SegmentPatternType::DotNumeric => check_7z_split(&found, &mut report),
+            SegmentPatternType::ZipNumeric => check_zip_numeric(&found, &mut report),
+            SegmentPatternType::ZipSplit => check_zip_split(&found, &mut report),
+            SegmentPatternType::RarStyle => check_rar_volumes(&found, &mut report),
+            SegmentPatternType::Single => {}
+        }
+    }
+    
+    report.complete = report.missing.is_empty() && report.unexpected.is_empty();
+    debug!(
+        path = %path,
+        complete = report.complete,
+        missing = report.missing.len(),
+        unexpected = report.unexpected.len(),
+        "Archive set validated"
+    );
+    Ok(report)
+}
+
+fn uses_rar_part_naming(found: &FoundSegments) -> bool {
+    matches!(found.pattern, SegmentPatternType::RarStyle)
+        && found.base.rsplit('.').next()
+            .and_then(|last| last.to_lowercase().strip_prefix("part").map(str::to_string))
+            .is_some_and(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()))
+}
+
+/// File name of a part with the given segment number
+fn part_name(found: &FoundSegments, num: u32) -> String {
+    let base = &found.base;
+    match found.pattern {
+        SegmentPatternType::DotNumeric | SegmentPatternType::ZipNumeric => format!("{base}.{num:03}"),
+        SegmentPatternType::RarStyle if num == 0 => format!("{base}.rar"),
+        SegmentPatternType::RarStyle => format!("{base}.r{:02}", num - 1),
+        SegmentPatternType::ZipSplit if num == ZIP_SPLIT_LAST => format!("{base}.zip"),
+        SegmentPatternType::ZipSplit => format!("{base}.z{num:02}"),
+        SegmentPatternType::Single => base.clone(),
+    }
+}
+
+fn file_name(path: &str) -> String {
+    Path::new(path).file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.to_string())
+}
+
+fn add_missing(report: &mut ArchiveSetReport, name: String, issue: String) {
+    if !report.missing.contains(&name) {
+        report.missing.push(name);
+        report.issues.push(issue);
+    }
+}
+
+fn add_unexpected(report: &mut ArchiveSetReport, name: String, issue: String) {
+    if !report.unexpected.contains(&name) {
+        report.unexpected.push(name);
+        report.issues.push(issue);
+    }
+}
+
+fn read_head(path: &str, len: usize) -> Vec<u8> {
+    let mut buf = Vec::with_capacity(len);
+    if let Ok(file) = File::open(path) {
+        let _ = file.take(len as u64).read_to_end(&mut buf);
+    }
+    buf
+}
+
+fn read_tail(path: &str, size: u64, len: u64) -> Vec<u8> {
+    let mut buf = Vec::new();
+    if let Ok(mut file) = File::open(path) {
+        if file.seek(SeekFrom::Start(size.saturating_sub(len))).is_ok() {
+            let _ = file.take(len).read_to_end(&mut buf);
+        }
+    }
+    buf
+}
+
+fn le_u16(data: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes([data[offset], data[offset + 1]])
+}
+
+fn le_u32(data: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap())
+}
+
+fn le_u64(data: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap())
+}
+
+/// Gaps between the first part and the highest numbered part present
+fn check_numbering(found: &FoundSegments, report: &mut ArchiveSetReport) {
+    let present: HashSet<u32> = found.segments.iter().map(|(_, _, num)| *num).collect();
+    let zip_split = matches!(found.pattern, SegmentPatternType::ZipSplit);
+    let first = if matches!(found.pattern, SegmentPatternType::RarStyle) { 0 } else { 1 };
+    let last = present.iter().copied()
+        .filter(|num| !zip_split || *num != ZIP_SPLIT_LAST)
+        .max()
+        .unwrap_or(0);
+    
+    for num in first..=last {
+        if !present.contains(&num) {
+            let name = part_name(found, num);
+            add_missing(report, name.clone(), format!("{name} is missing from the part numbering"));
+        }
+    }
+    if zip_split && !present.contains(&ZIP_SPLIT_LAST) {
+        let name = part_name(found, ZIP_SPLIT_LAST);
+        add_missing(report, name.clone(), format!("{name} (the last part, holding the central directory) is missing"));
+    }
+}
+
+/// Signatures of `.7z.NNN` parts, and the set length recorded in the start header
+fn check_7z_split(found: &FoundSegments, report: &mut ArchiveSetReport) {
+    let mut start_header = None;
+    for (path, _, num) in &found.segments {
+        let head = read_head(path, 32);
+        let signed = head.starts_with(SEVEN_ZIP_MAGIC);
+        let name = file_name(path);
+        if *num == 1 {
+            if !signed {
+                add_unexpected(report, name.clone(), format!("{name} is the first part but does not start with the 7-Zip signature"));
+            } else if head.len() == 32 && crc32(&head[12..32]) == le_u32(&head, 8) {
+                start_header = Some(head);
+            }
+        } else if signed {
+            add_unexpected(report, name.clone(), format!("{name} starts with the 7-Zip signature, so it is a separate archive rather than a continuation"));
+        }
+    }
+    
+    // The archive ends after the next header: 32 + offset + size bytes into the set
+    let Some(header) = start_header else { return };
+    let Some(archive_size) = 32u64.checked_add(le_u64(&header, 12))
+        .and_then(|n| n.checked_add(le_u64(&header, 20))) else { return };
+    let volume_size = found.segments[0].1;
+    if volume_size == 0 {
+        return;
+    }
+    let gaps = report.missing.len() as u64;
+    let covered = found.segments.iter().map(|(_, size, _)| *size).sum::<u64>()
+        .saturating_add(gaps.saturating_mul(volume_size));
+    
+    let last = found.segments.last().map(|(_, _, num)| *num).unwrap_or(1);
+    if covered < archive_size {
+        let short = archive_size - covered;
+        let trailing = short.div_ceil(volume_size).min(MAX_REPORTED_TRAILING);
+        for n in 1..=trailing as u32 {
+            let name = part_name(found, last + n);
+            add_missing(report, name.clone(), format!("{name} is needed: the start header records {archive_size} bytes but the parts end {short} bytes short"));
+        }
+    } else if gaps == 0 {
+        // Parts starting past the recorded end belong to something else
+        let mut offset = 0u64;
+        for (path, size, _) in &found.segments {
+            if offset >= archive_size {
+                let name = file_name(path);
+                add_unexpected(report, name.clone(), format!("{name} lies beyond the {archive_size} bytes recorded in the start header"));
+            }
+            offset += size;
+        }
+    }
+}
+
+fn has_zip_start(head: &[u8]) -> bool {
+    head.starts_with(ZIP_LOCAL_HEADER_SIG) || head.starts_with(ZIP_SPLIT_SIG) || head.starts_with(ZIP_SPLIT_TEMP_SIG)
+}
+
+/// Offset of the last end of central directory signature in a part's tail
+fn find_eocd(tail: &[u8]) -> Option<usize> {
+    if tail.len() < 22 {
+        return None;
+    }
+    (0..=tail.len() - 22).rev().find(|&i| &tail[i..i + 4] == ZIP_EOCD_SIG)
+}
+
+/// Signatures of `.zip.NNN` parts, and the end of central directory in the last one
+fn check_zip_numeric(found: &FoundSegments, report: &mut ArchiveSetReport) {
+    for (path, _, num) in &found.segments {
+        let head = read_head(path, 4);
+        let name = file_name(path);
+        if *num == 1 {
+            if !has_zip_start(&head) {
+                add_unexpected(report, name.clone(), format!("{name} is the first part but does not start with a ZIP signature"));
+            }
+        } else if has_zip_start(&head) {
+            add_unexpected(report, name.clone(), format!("{name} starts with a ZIP signature, so it is a separate archive rather than a continuation"));
+        }
+    }
+    
+    if let Some((path, size, num)) = found.segments.last() {
+        if find_eocd(&read_tail(path, *size, ZIP_EOCD_SEARCH)).is_none() {
+            let name = part_name(found, num + 1);
+            add_missing(report, name.clone(), format!("{name} is needed: {} has no end of central directory record", file_name(path)));
+        }
+    }
+}
+
+/// Signatures of `.zNN` parts, and the disk count recorded in the `.zip`
+fn check_zip_split(found: &FoundSegments, report: &mut ArchiveSetReport) {
+    let mut recorded_disk = None;
+    for (path, size, num) in &found.segments {
+        let head = read_head(path, 4);
+        let name = file_name(path);
+        if *num == 1 {
+            if !head.starts_with(ZIP_SPLIT_SIG) && !head.starts_with(ZIP_LOCAL_HEADER_SIG) {
+                add_unexpected(report, name.clone(), format!("{name} is the first part but does not start with a ZIP signature"));
+            }
+        } else if head.starts_with(ZIP_SPLIT_SIG) {
+            add_unexpected(report, name.clone(), format!("{name} starts with the split marker of a first part, so it belongs to another archive"));
+        }
+        
+        if *num == ZIP_SPLIT_LAST {
+            let tail = read_tail(path, *size, ZIP_EOCD_SEARCH);
+            match find_eocd(&tail) {
+                // 0xFFFF defers to the ZIP64 record
+                Some(pos) => recorded_disk = Some(le_u16(&tail, pos + 4)).filter(|disk| *disk != 0xFFFF),
+                None => add_unexpected(report, name.clone(), format!("{name} has no end of central directory record")),
+            }
+        }
+    }
+    
+    // The .zip is disk N (0-based), so .z01 through .zN precede it
+    let Some(disk) = recorded_disk.map(u32::from) else { return };
+    let last_z = found.segments.iter().map(|(_, _, num)| *num)
+        .filter(|num| *num != ZIP_SPLIT_LAST)
+        .max()
+        .unwrap_or(0);
+    for num in last_z + 1..=disk {
+        let name = part_name(found, num);
+        add_missing(report, name.clone(), format!("{name} is needed: the end of central directory records {} parts", disk + 1));
+    }
+    for (path, _, num) in &found.segments {
+        if *num != ZIP_SPLIT_LAST && *num > disk {
+            let name = file_name(path);
+            add_unexpected(report, name.clone(), format!("{name} is beyond the {} parts recorded in the end of central directory", disk + 1));
+        }
+    }
+}
+
+/// What a RAR volume's own headers say about its place in the set
+#[derive(Default)]
+struct RarVolume {
+    /// The main header marks the archive as a volume of a set
+    is_volume: bool,
+    /// 0-based volume number, when recorded
+    number: Option<u64>,
+    /// The end header announces a following volume (None without an end header)
+    has_next: Option<bool>,
+}
+
+/// RAR5 variable-length integer
+fn read_vint(data: &[u8], pos: &mut usize) -> Option<u64> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let byte = *data.get(*pos)?;
+        *pos += 1;
+        value |= u64::from(byte & 0x7F) << shift;
+        if byte & 0x80 == 0 {
+            return Some(value);
+        }
+    }
+    None
+}
+
+fn parse_rar5_volume(head: &[u8], tail: &[u8]) -> Option<RarVolume> {
+    let data = head.get(RAR5_MAGIC.len()..)?;
+    let mut pos = 4; // header CRC32
+    let header_size = read_vint(data, &mut pos)?;
+    let header_end = (pos as u64).checked_add(header_size)?;
+    if header_end <= data.len() as u64 && crc32(&data[4..header_end as usize]) != le_u32(data, 0) {
+        return None;
+    }
+    if read_vint(data, &mut pos)? != RAR5_MAIN_HEAD {
+        return None;
+    }
+    let header_flags = read_vint(data, &mut pos)?;
+    if header_flags & RAR5_HFL_EXTRA != 0 {
+        read_vint(data, &mut pos)?;
+    }
+    if header_flags & RAR5_HFL_DATA != 0 {
+        read_vint(data, &mut pos)?;
+    }
+    let archive_flags = read_vint(data, &mut pos)?;
+    let is_volume = archive_flags & RAR5_MHFL_VOLUME != 0;
+    // Every volume but the first records its number
+    let number = if archive_flags & RAR5_MHFL_VOLNUMBER != 0 {
+        Some(read_vint(data, &mut pos)?)
+    } else if is_volume {
+        Some(0)
+    } else {
+        None
+    };
+    
+    // End of archive header: CRC32, size 3, type, header flags, end flags
+    let has_next = tail.len().checked_sub(8)
+        .map(|start| &tail[start..])
+        .filter(|end| end[4] == 3 && end[5] == RAR5_END_HEAD && crc32(&end[4..8]) == le_u32(end, 0))
+        .map(|end| end[7] & RAR5_EHFL_NEXTVOLUME != 0);
+    
+    Some(RarVolume { is_volume, number, has_next })
+}
+
+fn parse_rar4_volume(head: &[u8], tail: &[u8]) -> Option<RarVolume> {
+    let main = head.get(RAR4_MAGIC.len()..RAR4_MAGIC.len() + 7)?;
+    if main[2] != RAR4_MAIN_HEAD {
+        return None;
+    }
+    let flags = le_u16(main, 3);
+    let mut volume = RarVolume {
+        is_volume: flags & RAR4_MHD_VOLUME != 0,
+        // RAR 3.0+ marks the first volume; later ones record their number in the end header
+        number: (flags & RAR4_MHD_FIRSTVOLUME != 0).then_some(0),
+        has_next: None,
+    };
+    
+    // End of archive header: CRC16, type, flags, size, [data CRC32], [volume number]
+    let end = (0..tail.len().saturating_sub(6)).rev().find(|&p| {
+        let size = le_u16(tail, p + 5) as usize;
+        tail[p + 2] == RAR4_END_HEAD
+            && size >= 7
+            && p + size <= tail.len()
+            && crc32(&tail[p + 2..p + size]) as u16 == le_u16(tail, p)
+    });
+    if let Some(p) = end {
+        let end_flags = le_u16(tail, p + 3);
+        let size = le_u16(tail, p + 5) as usize;
+        volume.has_next = Some(end_flags & RAR4_EARC_NEXT_VOLUME != 0);
+        let mut field = p + 7;
+        if end_flags & RAR4_EARC_DATACRC != 0 {
+            field += 4;
+        }
+        if end_flags & RAR4_EARC_VOLNUMBER != 0 && field + 2 <= p + size {
+            volume.number = Some(u64::from(le_u16(tail, field)));
+        }
+    }
+    Some(volume)
+}
+
+/// Volume numbers recorded in `.rar`/`.rNN` headers against the numbering of the names
+fn check_rar_volumes(found: &FoundSegments, report: &mut ArchiveSetReport) {
+    let multipart = found.segments.len() > 1;
+    let last = found.segments.last().map(|(_, _, num)| *num);
+    
+    for (path, size, num) in &found.segments {
+        let name = file_name(path);
+        let head = read_head(path, RAR_HEAD_READ);
+        let tail = read_tail(path, *size, RAR_TAIL_READ);
+        let volume = if head.starts_with(RAR5_MAGIC) {
+            parse_rar5_volume(&head, &tail)
+        } else if head.starts_with(RAR4_MAGIC) {
+            parse_rar4_volume(&head, &tail)
+        } else {
+            add_unexpected(report, name.clone(), format!("{name} does not start with a RAR signature"));
+            continue;
+        };
+        let Some(volume) = volume else { continue };
+        
+        let expected = u64::from(*num);
+        if !volume.is_volume && (multipart || expected > 0) {
+            add_unexpected(report, name.clone(), format!("{name} is a standalone RAR archive, not a volume of this set"));
+            continue;
+        }
+        if let Some(recorded) = volume.number.filter(|n| *n != expected) {
+            add_unexpected(report, name.clone(), format!(
+                "{name} records itself as volume {} but its name places it at volume {}",
+                recorded + 1, expected + 1
+            ));
+        }
+        if Some(*num) == last && volume.has_next == Some(true) {
+            let next = part_name(found, num + 1);
+            add_missing(report, next.clone(), format!("{next} is needed: {name} announces a following volume"));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn write(dir: &Path, name: &str, data: &[u8]) -> String {
+        let path = dir.join(name);
+        std::fs::write(&path, data).unwrap();
+        path.to_string_lossy().to_string()
+    }
+    
+    #[test]
+    fn test_7z_split_gaps_trailing_parts_and_stray_signature() {
+        let dir = tempfile::tempdir().unwrap();
+        // 64-byte volumes; the start header places the end of the archive in part 4
+        let mut first = vec![0u8; 64];
+        first[..6].copy_from_slice(SEVEN_ZIP_MAGIC);
+        first[12..20].copy_from_slice(&200u64.to_le_bytes());
+        first[20..28].copy_from_slice(&24u64.to_le_bytes());
+        let crc = crc32(&first[12..32]);
+        first[8..12].copy_from_slice(&crc.to_le_bytes());
+        let path = write(dir.path(), "Evidence.7z.001", &first);
+        write(dir.path(), "Evidence.7z.003", &first);
+        
+        let report = validate_set(&path, ArchiveFormat::SevenZip).unwrap();
+        assert!(!report.complete);
+        assert_eq!(report.missing, vec!["Evidence.7z.002", "Evidence.7z.004"]);
+        assert_eq!(report.unexpected, vec!["Evidence.7z.003"]);
+        assert_eq!(report.issues.len(), 3);
+        
+        let single = write(dir.path(), "other.7z", &first);
+        assert!(validate_set(&single, ArchiveFormat::SevenZip).unwrap().complete);
+    }
+    
+    fn rar4_volume(main_flags: u16, end_flags: u16, volume: u16) -> Vec<u8> {
+        let mut data = RAR4_MAGIC.to_vec();
+        let mut main = vec![RAR4_MAIN_HEAD];
+        main.extend_from_slice(&main_flags.to_le_bytes());
+        main.extend_from_slice(&13u16.to_le_bytes());
+        main.extend_from_slice(&[0; 6]);
+        data.extend_from_slice(&(crc32(&main) as u16).to_le_bytes());
+        data.extend_from_slice(&main);
+        data.extend_from_slice(&[0xAA; 100]); // packed data
+        let mut end = vec![RAR4_END_HEAD];
+        end.extend_from_slice(&(end_flags | RAR4_EARC_VOLNUMBER).to_le_bytes());
+        end.extend_from_slice(&9u16.to_le_bytes());
+        end.extend_from_slice(&volume.to_le_bytes());
+        data.extend_from_slice(&(crc32(&end) as u16).to_le_bytes());
+        data.extend_from_slice(&end);
+        data
+    }
+    
+    #[test]
+    fn test_rar4_volume_numbers_and_next_volume() {
+        let dir = tempfile::tempdir().unwrap();
+        let first = RAR4_MHD_VOLUME | RAR4_MHD_FIRSTVOLUME;
+        let path = write(dir.path(), "case.rar", &rar4_volume(first, RAR4_EARC_NEXT_VOLUME, 0));
+        write(dir.path(), "case.r00", &rar4_volume(RAR4_MHD_VOLUME, RAR4_EARC_NEXT_VOLUME, 1));
+        let report = validate_set(&path, ArchiveFormat::Rar4).unwrap();
+        assert_eq!(report.missing, vec!["case.r01"]);
+        assert!(report.unexpected.is_empty());
+        
+        // A volume from a different position in the set
+        write(dir.path(), "case.r01", &rar4_volume(RAR4_MHD_VOLUME, 0, 3));
+        let report = validate_set(&path, ArchiveFormat::Rar4).unwrap();
+        assert!(report.missing.is_empty());
+        assert_eq!(report.unexpected, vec!["case.r01"]);
+        
+        write(dir.path(), "case.r01", &rar4_volume(RAR4_MHD_VOLUME, 0, 2));
+        assert!(validate_set(&path, ArchiveFormat::Rar4).unwrap().complete);
+    }
+    
+    #[test]
+    fn test_zip_split_disk_count_from_end_of_central_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write(dir.path(), "export.z01", ZIP_SPLIT_SIG);
+        let mut last = vec![0u8; 40];
+        let mut eocd = ZIP_EOCD_SIG.to_vec();
+        eocd.extend_from_slice(&2u16.to_le_bytes()); // this is disk 3 of 3
+        eocd.resize(22, 0);
+        last.extend_from_slice(&eocd);
+        write(dir.path(), "export.zip", &last);
+        
+        let report = validate_set(&path, ArchiveFormat::Zip).unwrap();
+        assert_eq!(report.missing, vec!["export.z02"]);
+        assert!(report.unexpected.is_empty());
+        
+        write(dir.path(), "export.z02", &[0u8; 16]);
+        assert!(validate_set(&path, ArchiveFormat::Zip).unwrap().complete);
+    }
+}
@@ -15,7 +15,7 @@
 
 use std::fs::File;
 use std::io::{Read, Seek, SeekFrom};
-use tracing::debug;
+use tracing::{debug, warn};
 
 use super::detection::SEVEN_ZIP_MAGIC;
 
@@ -39,38 +39,42 @@ pub mod header_types {
 // Metadata Parsing
 // =============================================================================
 
+/// Largest Next Header whose CRC is checked (header of a very large archive)
+pub const MAX_CHECKED_NEXT_HEADER_SIZE: u64 = 64 * 1024 * 1024;
+
 /// 7z metadata result
+#[derive(Default)]
 pub struct SevenZipMetadata {
     pub next_header_offset: Option<u64>,
     pub next_header_size: Option<u64>,
     pub version: Option<String>,
     pub start_header_crc_valid: Option<bool>,
     pub next_header_crc: Option<u32>,
+    /// Next Header bytes match `next_header_crc` (None if not checked: bad
+    /// Start Header, truncated archive or header above the size limit)
+    pub next_header_crc_valid: Option<bool>,
+    /// The Next Header lies (partly) beyond the end of the archive data
+    pub truncated: bool,
     pub encrypted: bool,
 }
 
-impl Default for SevenZipMetadata {
-    fn default() -> Self {
-        Self {
-            next_header_offset: None,
-            next_header_size: None,
-            version: None,
-            start_header_crc_valid: None,
-            next_header_crc: None,
-            encrypted: false,
-        }
-    }
-}
-
 /// Parse 7-Zip Start Header and Next Header metadata
 /// 
 /// Returns metadata structure with header offsets, version, CRC validation, and encryption status.
 pub fn parse_metadata(path: &str) -> Result<SevenZipMetadata, String> {
-    let mut file = File::open(path)
-        .map_err(|e| format!("Failed to open 7z: {e}"))?;
+    parse_set_metadata(&[path.to_string()])
+}
+
+/// Parse the headers of a 7z archive split into `parts` (in order)
+///
+/// The Next Header offset is relative to the whole stream, so it is usually
+/// found in the last part of a split archive.
+pub fn parse_set_metadata(parts: &[String]) -> Result<SevenZipMetadata, String> {
+    let path = parts.first().ok_or_else(|| "No 7z parts given".to_string())?;
+    let mut stream = PartStream::open(parts)?;
     
     let mut header = [0u8; 32];
-    file.read_exact(&mut header)
+    stream.read_exact_at(0, &mut header)
         .map_err(|e| format!("Failed to read 7z header: {e}"))?;
     
     // Verify signature (6 bytes at offset 0)
@@ -87,7 +91,7 @@ pub fn parse_metadata(path: &str) -> Result<SevenZipMetadata, String> {
     // This CRC covers bytes 0x0C to 0x1F (20 bytes: next header offset, size, and CRC)
     let stored_start_crc = u32::from_le_bytes(header[8..12].try_into().unwrap());
     let computed_start_crc = crc32(&header[12..32]);
-    let start_header_crc_valid = Some(stored_start_crc == computed_start_crc);
+    let start_header_valid = stored_start_crc == computed_start_crc;
     
     // Parse Next Header Offset (8 bytes at offset 0x0C)
     // This is relative to byte 0x20 (end of signature header)
@@ -97,36 +101,58 @@ pub fn parse_metadata(path: &str) -> Result<SevenZipMetadata, String> {
     let next_size = u64::from_le_bytes(header[20..28].try_into().unwrap());
     
     // Parse Next Header CRC (4 bytes at offset 0x1C)
-    let next_header_crc = Some(u32::from_le_bytes(header[28..32].try_into().unwrap()));
+    let next_header_crc = u32::from_le_bytes(header[28..32].try_into().unwrap());
     
     // Calculate absolute offset: 0x20 (32) + relative offset
-    let absolute_offset = 32 + next_offset_relative;
+    let absolute_offset = 32u64.saturating_add(next_offset_relative);
     
-    // Check if headers are encrypted by reading first byte of Next Header
+    // A damaged Start Header gives meaningless offsets - check nothing further
+    let truncated = start_header_valid && absolute_offset.saturating_add(next_size) > stream.len;
+    let mut next_header_crc_valid = None;
     let mut encrypted = false;
-    if next_size > 0 {
-        if file.seek(SeekFrom::Start(absolute_offset)).is_ok() {
-            let mut next_header_byte = [0u8; 1];
-            if file.read_exact(&mut next_header_byte).is_ok() {
-                // 0x17 = EncodedHeader - metadata is compressed and/or encrypted
-                if next_header_byte[0] == header_types::ENCODED_HEADER {
-                    debug!(
-                        path = %path,
-                        "7z has EncodedHeader - metadata may be encrypted"
-                    );
-                    // Try to detect AES in the encoded header stream info
-                    encrypted = detect_encryption(&mut file, absolute_offset).unwrap_or(false);
-                }
+    if start_header_valid && !truncated {
+        if next_size <= MAX_CHECKED_NEXT_HEADER_SIZE {
+            let computed = stream.crc32_range(absolute_offset, next_size)
+                .map_err(|e| format!("Failed to read 7z Next Header: {e}"))?;
+            next_header_crc_valid = Some(computed == next_header_crc);
+        } else {
+            debug!(path = %path, next_size, "7z Next Header too large to check its CRC");
+        }
+        
+        // Check if headers are encrypted by reading the start of the Next Header
+        let mut start = vec![0u8; next_size.min(256) as usize];
+        if !start.is_empty() && stream.read_exact_at(absolute_offset, &mut start).is_ok() {
+            // 0x17 = EncodedHeader - metadata is compressed and/or encrypted
+            if start[0] == header_types::ENCODED_HEADER {
+                debug!(
+                    path = %path,
+                    "7z has EncodedHeader - metadata may be encrypted"
+                );
+                // Try to detect AES in the encoded header stream info
+                encrypted = detect_encryption(&start);
             }
         }
     }
     
+    if truncated {
+        warn!(
+            path = %path,
+            next_header_end = absolute_offset.saturating_add(next_size),
+            archive_size = stream.len,
+            "7z archive is truncated - Next Header lies beyond the end of the data"
+        );
+    } else if !start_header_valid || next_header_crc_valid == Some(false) {
+        warn!(path = %path, start_header_valid, next_header_crc_valid = ?next_header_crc_valid, "7z header CRC mismatch");
+    }
+    
     debug!(
         path = %path,
         version = ?version,
         next_header_offset = absolute_offset,
         next_header_size = next_size,
-        crc_valid = ?start_header_crc_valid,
+        crc_valid = start_header_valid,
+        next_crc_valid = ?next_header_crc_valid,
+        truncated = truncated,
         encrypted = encrypted,
         "7z metadata parsed"
     );
@@ -135,46 +161,84 @@ pub fn parse_metadata(path: &str) -> Result<SevenZipMetadata, String> {
         next_header_offset: Some(absolute_offset),
         next_header_size: Some(next_size),
         version,
-        start_header_crc_valid,
-        next_header_crc,
+        start_header_crc_valid: Some(start_header_valid),
+        next_header_crc: Some(next_header_crc),
+        next_header_crc_valid,
+        truncated,
         encrypted,
     })
 }
 
+/// The parts of a (possibly split) archive read as one stream
+struct PartStream {
+    parts: Vec<(File, u64)>,
+    len: u64,
+}
+
+impl PartStream {
+    fn open(paths: &[String]) -> Result<Self, String> {
+        let parts = paths.iter()
+            .map(|path| {
+                let file = File::open(path).map_err(|e| format!("Failed to open 7z: {e}"))?;
+                let len = file.metadata().map_err(|e| format!("Failed to stat 7z: {e}"))?.len();
+                Ok((file, len))
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        let len = parts.iter().map(|(_, len)| len).sum();
+        Ok(Self { parts, len })
+    }
+
+    fn read_exact_at(&mut self, mut offset: u64, mut buf: &mut [u8]) -> std::io::Result<()> {
+        for (file, len) in &mut self.parts {
+            if buf.is_empty() {
+                break;
+            }
+            if offset >= *len {
+                offset -= *len;
+                continue;
+            }
+            let take = buf.len().min((*len - offset) as usize);
+            file.seek(SeekFrom::Start(offset))?;
+            file.read_exact(&mut buf[..take])?;
+            buf = &mut buf[take..];
+            offset = 0;
+        }
+        if buf.is_empty() {
+            Ok(())
+        } else {
+            Err(std::io::ErrorKind::UnexpectedEof.into())
+        }
+    }
+
+    fn crc32_range(&mut self, offset: u64, len: u64) -> std::io::Result<u32> {
+        let mut crc = Crc32::new();
+        let mut buf = vec![0u8; len.min(64 * 1024) as usize];
+        let mut done = 0u64;
+        while done < len {
+            let take = buf.len().min((len - done) as usize);
+            self.read_exact_at(offset + done, &mut buf[..take])?;
+            crc.update(&buf[..take]);
+            done += take as u64;
+        }
+        Ok(crc.finish())
+    }
+}
+
 /// Detect if 7z encoded header contains AES encryption
 /// 
 /// When Next Header starts with 0x17 (EncodedHeader), we need to parse
 /// the StreamsInfo to check if AES codec is in the decode pipeline.
 /// AES codec ID: 06 F1 07 01 (or variations)
-fn detect_encryption(file: &mut File, next_header_offset: u64) -> Result<bool, String> {
-    file.seek(SeekFrom::Start(next_header_offset))
-        .map_err(|e| format!("Failed to seek to Next Header: {e}"))?;
-    
-    // Read first chunk of encoded header to look for AES codec markers
-    let mut buf = [0u8; 256];
-    let bytes_read = file.read(&mut buf).unwrap_or(0);
-    
-    if bytes_read == 0 {
-        return Ok(false);
-    }
-    
+fn detect_encryption(buf: &[u8]) -> bool {
     // Look for AES codec signature patterns in the encoded header
     // 7z AES codec IDs typically start with 06 F1 07
-    for i in 0..bytes_read.saturating_sub(3) {
-        if buf[i] == 0x06 && buf[i + 1] == 0xF1 && buf[i + 2] == 0x07 {
-            return Ok(true);
-        }
+    if buf.windows(3).any(|w| w == [0x06, 0xF1, 0x07]) {
+        return true;
     }
     
     // Also check for 7zAES marker (alternative pattern)
     // 07 (codec ID length) followed by specific bytes
-    for i in 0..bytes_read.saturating_sub(4) {
-        if buf[i] == 0x07 && buf[i + 1] == 0x06 && buf[i + 2] == 0xF1 {
-            return Ok(true);
-        }
-    }
-    
-    Ok(false)
+    buf.windows(3).any(|w| w == [0x07, 0x06, 0xF1])
 }
 
 // =============================================================================
@@ -183,12 +247,29 @@ fn detect_encryption(file: &mut File, next_header_offset: u64) -> Result<bool, S
 
 /// CRC32 calculation for 7z (ISO 3309 polynomial, same as used in PNG/GZIP)
 pub fn crc32(data: &[u8]) -> u32 {
-    let mut crc: u32 = 0xFFFFFFFF;
-    for byte in data {
-        let index = ((crc ^ (*byte as u32)) & 0xFF) as usize;
-        crc = CRC32_TABLE[index] ^ (crc >> 8);
+    let mut crc = Crc32::new();
+    crc.update(data);
+    crc.finish()
+}
+
+/// Incremental CRC32 for data read in chunks
+struct Crc32(u32);
+
+impl Crc32 {
+    fn new() -> Self {
+        Self(0xFFFFFFFF)
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        for byte in data {
+            let index = ((self.0 ^ (*byte as u32)) & 0xFF) as usize;
+            self.0 = CRC32_TABLE[index] ^ (self.0 >> 8);
+        }
+    }
+
+    fn finish(&self) -> u32 {
+        !self.0
     }
-    !crc
 }
 
 /// CRC32 lookup table (ISO 3309 polynomial: 0xEDB88320)
@@ -211,3 +292,72 @@ static CRC32_TABLE: [u32; 256] = {
     }
     table
 };
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Signature header followed by `packed` bytes and a minimal Next Header
+    fn build_7z(packed: &[u8]) -> Vec<u8> {
+        let next_header = [header_types::HEADER, header_types::END];
+        let mut start = Vec::new();
+        start.extend_from_slice(&(packed.len() as u64).to_le_bytes());
+        start.extend_from_slice(&(next_header.len() as u64).to_le_bytes());
+        start.extend_from_slice(&crc32(&next_header).to_le_bytes());
+
+        let mut data = SEVEN_ZIP_MAGIC.to_vec();
+        data.extend_from_slice(&[0, 4]);
+        data.extend_from_slice(&crc32(&start).to_le_bytes());
+        data.extend_from_slice(&start);
+        data.extend_from_slice(packed);
+        data.extend_from_slice(&next_header);
+        data
+    }
+
+    fn parse(data: &[u8]) -> SevenZipMetadata {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("archive.7z");
+        std::fs::write(&path, data).unwrap();
+        parse_metadata(path.to_str().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_valid_archive_passes_both_crcs() {
+        let meta = parse(&build_7z(&[0xAB; 100]));
+        assert_eq!(meta.start_header_crc_valid, Some(true));
+        assert_eq!(meta.next_header_crc_valid, Some(true));
+        assert!(!meta.truncated);
+        assert_eq!(meta.next_header_offset, Some(132));
+    }
+
+    #[test]
+    fn test_damaged_next_header_fails_its_crc() {
+        let mut data = build_7z(&[0xAB; 100]);
+        let last = data.len() - 1;
+        data[last] ^= 0xFF;
+        let meta = parse(&data);
+        assert_eq!(meta.start_header_crc_valid, Some(true));
+        assert_eq!(meta.next_header_crc_valid, Some(false));
+        assert!(!meta.truncated);
+    }
+
+    #[test]
+    fn test_truncated_archive_is_reported() {
+        let data = build_7z(&[0xAB; 100]);
+        let meta = parse(&data[..80]);
+        assert!(meta.truncated);
+        assert_eq!(meta.start_header_crc_valid, Some(true));
+        assert_eq!(meta.next_header_crc_valid, None);
+
+        // A split archive carries its Next Header in the last part
+        let dir = tempfile::tempdir().unwrap();
+        let parts: Vec<String> = [&data[..80], &data[80..]].iter().enumerate().map(|(i, part)| {
+            let path = dir.path().join(format!("archive.7z.{:03}", i + 1));
+            std::fs::write(&path, part).unwrap();
+            path.to_string_lossy().to_string()
+        }).collect();
+        let meta = parse_set_metadata(&parts).unwrap();
+        assert!(!meta.truncated);
+        assert_eq!(meta.next_header_crc_valid, Some(true));
+    }
+}
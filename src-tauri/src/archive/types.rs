@@ -12,6 +12,8 @@
 //! | Chain of Custody | No | Yes |
 //! | Examples | ZIP, 7z, RAR | E01, AD1, AFF |
 
+use std::fmt;
+
 use serde::Serialize;
 
 /// Archive format type
@@ -121,10 +123,19 @@ pub struct ArchiveInfo {
     pub is_multipart: bool,
     /// Number of entries in the archive (from Central Directory for ZIP)
     pub entry_count: Option<u32>,
+    /// Total uncompressed size of the entries (ZIP, when the whole Central
+    /// Directory could be read)
+    pub uncompressed_size: Option<u64>,
     /// Whether archive has encrypted headers (filenames hidden)
     pub encrypted_headers: bool,
     /// Whether archive uses AES encryption
     pub aes_encrypted: bool,
+    /// Whether entry names can be listed without the password: always for
+    /// ZIP (names are stored in the clear even for encrypted entries), not
+    /// for 7z with header encryption; None for other formats
+    pub filenames_visible: Option<bool>,
+    /// ZIP-specific: entries counted by encryption method
+    pub zip_encryption: Option<ZipEncryptionSummary>,
     /// ZIP-specific: Central Directory offset
     pub central_dir_offset: Option<u64>,
     /// ZIP-specific: Central Directory size
@@ -139,10 +150,17 @@ pub struct ArchiveInfo {
     pub start_header_crc_valid: Option<bool>,
     /// 7z-specific: Next Header CRC (for reference)
     pub next_header_crc: Option<u32>,
+    /// 7z-specific: Next Header bytes match their CRC (None if not checked)
+    pub next_header_crc_valid: Option<bool>,
+    /// 7z-specific: the Next Header lies beyond the end of the archive data
+    /// (incomplete copy or download)
+    pub truncated: bool,
     /// UFED extraction detected (UFDR/UFDX/UFD)
     pub ufed_detected: bool,
     /// UFED file paths found inside archive
     pub ufed_files: Vec<String>,
+    /// Consistency of the multi-part set (None if it could not be checked)
+    pub set_report: Option<ArchiveSetReport>,
 }
 
 impl Default for ArchiveInfo {
@@ -157,8 +175,11 @@ impl Default for ArchiveInfo {
             last_segment: String::new(),
             is_multipart: false,
             entry_count: None,
+            uncompressed_size: None,
             encrypted_headers: false,
             aes_encrypted: false,
+            filenames_visible: None,
+            zip_encryption: None,
             central_dir_offset: None,
             central_dir_size: None,
             next_header_offset: None,
@@ -166,8 +187,113 @@ impl Default for ArchiveInfo {
             version: None,
             start_header_crc_valid: None,
             next_header_crc: None,
+            next_header_crc_valid: None,
+            truncated: false,
             ufed_detected: false,
             ufed_files: Vec::new(),
+            set_report: None,
+        }
+    }
+}
+
+/// How a single ZIP entry is encrypted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ZipEncryption {
+    None,
+    /// Traditional PKWARE encryption - weak, usually recoverable
+    ZipCrypto,
+    /// WinZip AE-1/AE-2
+    Aes128,
+    Aes192,
+    Aes256,
+    /// PKWARE strong encryption, or an AES field of unknown strength
+    Other,
+}
+
+/// An encrypted ZIP entry, by name
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct EncryptedZipEntry {
+    pub name: String,
+    pub encryption: ZipEncryption,
+}
+
+/// Why a ZIP entry could not be read
+///
+/// A wrong password is reported on its own: it says nothing about the
+/// archive, while `Corrupt` (bad checksum, failed AES authentication code,
+/// broken compressed stream) means the data itself is damaged.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ZipEntryError {
+    /// The entry is encrypted and no password was given
+    PasswordRequired { entry: String },
+    /// The password does not decrypt the entry
+    WrongPassword { entry: String },
+    /// The entry's data is damaged
+    Corrupt { entry: String, error: String },
+    /// Compression or encryption method this build cannot read
+    Unsupported { entry: String, error: String },
+}
+
+impl fmt::Display for ZipEntryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ZipEntryError::PasswordRequired { entry } => {
+                write!(f, "ZIP is encrypted ({entry}): a password is required to read its contents")
+            }
+            ZipEntryError::WrongPassword { entry } => write!(f, "Wrong password for ZIP entry {entry}"),
+            ZipEntryError::Corrupt { entry, error } => write!(f, "ZIP entry {entry} is corrupt: {error}"),
+            ZipEntryError::Unsupported { entry, error } => write!(f, "ZIP entry {entry} cannot be read: {error}"),
         }
     }
 }
+
+impl From<ZipEntryError> for String {
+    fn from(error: ZipEntryError) -> String {
+        error.to_string()
+    }
+}
+
+/// ZIP entries counted by encryption method (from the central directory)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ZipEncryptionSummary {
+    pub unencrypted: u32,
+    pub zip_crypto: u32,
+    pub aes128: u32,
+    pub aes192: u32,
+    pub aes256: u32,
+    pub other: u32,
+    /// The first encrypted entries, in central directory order
+    pub encrypted_entries: Vec<EncryptedZipEntry>,
+    /// Every central directory entry was read (false if it is truncated,
+    /// corrupt or too large to scan)
+    pub complete: bool,
+}
+
+impl ZipEncryptionSummary {
+    /// Entries with any kind of encryption
+    pub fn encrypted_count(&self) -> u32 {
+        self.zip_crypto + self.aes128 + self.aes192 + self.aes256 + self.other
+    }
+
+    pub fn has_aes(&self) -> bool {
+        self.aes128 + self.aes192 + self.aes256 > 0
+    }
+}
+
+/// Consistency check of a multi-part archive set
+///
+/// Part names are file names (not paths), in the casing of the examined
+/// part. Single-file archives are always complete.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ArchiveSetReport {
+    /// All expected parts present and consistent with each other
+    pub complete: bool,
+    /// Expected parts that are not on disk (gaps and missing trailing parts)
+    pub missing: Vec<String>,
+    /// Parts on disk that do not belong at their position in the set
+    pub unexpected: Vec<String>,
+    /// Human-readable reason for each missing or unexpected part
+    pub issues: Vec<String>,
+}
@@ -1,6 +1,8 @@
 //! ZIP format parsing
 //!
-//! Provides metadata extraction for ZIP and ZIP64 archives.
+//! Provides metadata extraction for ZIP and ZIP64 archives, and opens
+//! entries for reading with an optional password: ZipCrypto and WinZip AES
+//! (AE-1/AE-2) entries are decrypted as they are read.
 //!
 //! ## ZIP Format Layout
 //! ```text
@@ -24,9 +26,30 @@
 //! | 0x10   | 4    | Central Dir Offset |
 
 use std::fs::File;
-use std::io::{Read, Seek, SeekFrom};
+use std::io::{self, Read, Seek, SeekFrom};
 
-use super::detection::ZIP_EOCD_SIG;
+use ::zip::read::ZipFile;
+use ::zip::result::ZipError;
+use ::zip::ZipArchive;
+
+use crate::common::binary::{ByteReader, Truncated};
+use super::detection::{ZIP_CENTRAL_DIR_SIG, ZIP_EOCD_SIG};
+use super::types::{EncryptedZipEntry, ZipEncryption, ZipEncryptionSummary, ZipEntryError};
+
+/// Encrypted entry names listed in [`ZipEncryptionSummary`]
+pub const MAX_LISTED_ENCRYPTED: usize = 50;
+
+/// Largest central directory scanned for the encryption inventory (64MB)
+const MAX_CENTRAL_DIR_SCAN: u64 = 64 * 1024 * 1024;
+
+/// Extra field holding ZIP64 sizes (uncompressed size first)
+const ZIP64_EXTRA_ID: u16 = 0x0001;
+
+/// Extra field of WinZip AES entries
+const AES_EXTRA_ID: u16 = 0x9901;
+
+/// Compression method recorded for WinZip AES entries
+const AES_METHOD: u16 = 99;
 
 // =============================================================================
 // ZIP Metadata
@@ -39,6 +62,10 @@ pub struct ZipMetadata {
     pub central_dir_size: Option<u32>,
     pub encrypted_headers: bool,
     pub aes_encrypted: bool,
+    pub encryption: Option<ZipEncryptionSummary>,
+    /// Sum of the entries' uncompressed sizes, when the whole Central
+    /// Directory was read
+    pub uncompressed_size: Option<u64>,
 }
 
 impl Default for ZipMetadata {
@@ -49,6 +76,8 @@ impl Default for ZipMetadata {
             central_dir_size: None,
             encrypted_headers: false,
             aes_encrypted: false,
+            encryption: None,
+            uncompressed_size: None,
         }
     }
 }
@@ -86,75 +115,439 @@ pub fn parse_metadata(path: &str) -> Result<ZipMetadata, String> {
         return Ok(ZipMetadata::default());
     };
     
-    // Parse EOCD fields
-    // Offset 0x0A: Total entries (2 bytes)
-    let entry_count = if eocd_pos + 12 <= buf.len() {
-        Some(u16::from_le_bytes([buf[eocd_pos + 10], buf[eocd_pos + 11]]) as u32)
-    } else {
-        None
-    };
+    let eocd_file_offset = size - search_size as u64 + eocd_pos as u64;
+    let (entry_count, cd_size, cd_offset) = parse_eocd(&buf[eocd_pos..], eocd_file_offset)
+        .map_err(|e| format!("Corrupt ZIP end of central directory: {e}"))?;
     
-    // Offset 0x0C: Central Directory Size (4 bytes)
-    let cd_size = if eocd_pos + 16 <= buf.len() {
-        Some(u32::from_le_bytes([
-            buf[eocd_pos + 12],
-            buf[eocd_pos + 13],
-            buf[eocd_pos + 14],
-            buf[eocd_pos + 15],
-        ]))
-    } else {
-        None
-    };
-    
-    // Offset 0x10: Central Directory Offset (4 bytes)
-    let cd_offset = if eocd_pos + 20 <= buf.len() {
-        let offset = u32::from_le_bytes([
-            buf[eocd_pos + 16],
-            buf[eocd_pos + 17],
-            buf[eocd_pos + 18],
-            buf[eocd_pos + 19],
-        ]);
-        // Check for ZIP64 marker (0xFFFFFFFF)
-        if offset == 0xFFFFFFFF {
-            None // Would need to parse ZIP64 EOCD
-        } else {
-            Some(offset as u64)
-        }
-    } else {
-        None
-    };
-    
-    // Check for AES encryption by scanning Central Directory for extra field 0x9901
-    let aes_encrypted = if let (Some(offset), Some(size)) = (cd_offset, cd_size) {
-        check_aes(&mut file, offset, size).unwrap_or(false)
-    } else {
-        false
-    };
+    // Count entries by encryption method (and add up their sizes) from the
+    // Central Directory
+    let inventory = cd_offset.and_then(|offset| inventory_central_dir(&mut file, offset, cd_size).ok());
+    let uncompressed_size = inventory.as_ref()
+        .filter(|(summary, _)| summary.complete)
+        .and_then(|(_, size)| *size);
+    let encryption = inventory.map(|(summary, _)| summary);
     
     Ok(ZipMetadata {
-        entry_count,
+        entry_count: Some(entry_count),
         central_dir_offset: cd_offset,
-        central_dir_size: cd_size,
+        central_dir_size: Some(cd_size),
         encrypted_headers: false,
-        aes_encrypted,
+        aes_encrypted: encryption.as_ref().is_some_and(ZipEncryptionSummary::has_aes),
+        encryption,
+        uncompressed_size,
     })
 }
 
-/// Check if ZIP uses AES encryption (Extra Field Header ID 0x9901)
-fn check_aes(file: &mut File, cd_offset: u64, cd_size: u32) -> Result<bool, String> {
+/// Total entries, Central Directory size and offset from the EOCD record
+/// at the start of `eocd` (found at file offset `offset`)
+///
+/// The offset is `None` for ZIP64 archives, which keep it in the ZIP64 EOCD.
+fn parse_eocd(eocd: &[u8], offset: u64) -> Result<(u32, u32, Option<u64>), Truncated> {
+    let mut reader = ByteReader::at(eocd, offset);
+    reader.seek(0x0A)?;
+    let entry_count = reader.u16_le()? as u32;
+    let cd_size = reader.u32_le()?;
+    let cd_offset = reader.u32_le()?;
+    Ok((entry_count, cd_size, (cd_offset != 0xFFFFFFFF).then_some(cd_offset as u64)))
+}
+
+/// Walk the Central Directory, classifying every entry's encryption and
+/// adding up the entries' uncompressed sizes
+///
+/// Central Directory File Header (fixed part, 46 bytes):
+/// | Offset | Size | Field              |
+/// |--------|------|--------------------|
+/// | 0x08   | 2    | Flags (bit 0 encrypted, bit 6 strong encryption) |
+/// | 0x0A   | 2    | Compression method (99 = AES) |
+/// | 0x18   | 4    | Uncompressed size (0xFFFFFFFF: in the ZIP64 extra field) |
+/// | 0x1C   | 2    | File name length   |
+/// | 0x1E   | 2    | Extra field length |
+/// | 0x20   | 2    | Comment length     |
+fn inventory_central_dir(file: &mut File, cd_offset: u64, cd_size: u32) -> Result<(ZipEncryptionSummary, Option<u64>), String> {
     file.seek(SeekFrom::Start(cd_offset))
         .map_err(|e| format!("Failed to seek to Central Directory: {e}"))?;
-    
-    let mut buf = vec![0u8; cd_size.min(4096) as usize];
-    let bytes_read = file.read(&mut buf)
+    let mut buf = Vec::new();
+    file.take((cd_size as u64).min(MAX_CENTRAL_DIR_SCAN))
+        .read_to_end(&mut buf)
         .map_err(|e| format!("Failed to read Central Directory: {e}"))?;
     
-    // Look for AES extra field header (0x9901)
-    for i in 0..bytes_read.saturating_sub(2) {
-        if buf[i] == 0x01 && buf[i + 1] == 0x99 {
-            return Ok(true);
+    let mut summary = ZipEncryptionSummary::default();
+    let mut uncompressed_size = Some(0u64);
+    let mut pos = 0;
+    while pos + 46 <= buf.len() && &buf[pos..pos + 4] == ZIP_CENTRAL_DIR_SIG {
+        let mut header = ByteReader::at(&buf[pos..pos + 46], cd_offset + pos as u64);
+        header.seek(0x08)?;
+        let flags = header.u16_le()?;
+        let method = header.u16_le()?;
+        header.seek(0x18)?;
+        let size = header.u32_le()?;
+        header.seek(0x1C)?;
+        let name_len = header.u16_le()? as usize;
+        let extra_len = header.u16_le()? as usize;
+        let comment_len = header.u16_le()? as usize;
+        let name_start = pos + 46;
+        let extra_start = name_start + name_len;
+        let end = extra_start + extra_len + comment_len;
+        if end > buf.len() {
+            break;
+        }
+        
+        let extra = &buf[extra_start..extra_start + extra_len];
+        let entry_size = match size {
+            u32::MAX => extra_field(extra, ZIP64_EXTRA_ID)
+                .and_then(|data| data.get(..8))
+                .map(|data| u64::from_le_bytes(data.try_into().unwrap())),
+            size => Some(size as u64),
+        };
+        uncompressed_size = uncompressed_size.zip(entry_size).map(|(total, size)| total + size);
+        let encryption = entry_encryption(flags, method, extra);
+        match encryption {
+            ZipEncryption::None => summary.unencrypted += 1,
+            ZipEncryption::ZipCrypto => summary.zip_crypto += 1,
+            ZipEncryption::Aes128 => summary.aes128 += 1,
+            ZipEncryption::Aes192 => summary.aes192 += 1,
+            ZipEncryption::Aes256 => summary.aes256 += 1,
+            ZipEncryption::Other => summary.other += 1,
+        }
+        if encryption != ZipEncryption::None && summary.encrypted_entries.len() < MAX_LISTED_ENCRYPTED {
+            summary.encrypted_entries.push(EncryptedZipEntry {
+                name: String::from_utf8_lossy(&buf[name_start..extra_start]).to_string(),
+                encryption,
+            });
         }
+        pos = end;
     }
+    summary.complete = pos == cd_size as usize;
     
-    Ok(false)
+    Ok((summary, uncompressed_size))
+}
+
+/// Encryption of one entry from its flags, method and extra fields
+///
+/// AES Extra Field (0x9901) data: vendor version (2), vendor ID "AE" (2),
+/// strength (1: 1 = 128, 2 = 192, 3 = 256 bits), actual method (2).
+fn entry_encryption(flags: u16, method: u16, extra: &[u8]) -> ZipEncryption {
+    if flags & 0x0001 == 0 {
+        return ZipEncryption::None;
+    }
+    if method == AES_METHOD {
+        return match extra_field(extra, AES_EXTRA_ID).and_then(|data| data.get(4)) {
+            Some(1) => ZipEncryption::Aes128,
+            Some(2) => ZipEncryption::Aes192,
+            Some(3) => ZipEncryption::Aes256,
+            _ => ZipEncryption::Other,
+        };
+    }
+    if flags & 0x0040 != 0 {
+        return ZipEncryption::Other;
+    }
+    ZipEncryption::ZipCrypto
+}
+
+/// Data of the extra field with header `id`
+fn extra_field(extra: &[u8], id: u16) -> Option<&[u8]> {
+    let mut reader = ByteReader::new(extra);
+    while reader.remaining() >= 4 {
+        let header = reader.u16_le().ok()?;
+        let size = reader.u16_le().ok()? as usize;
+        let data = reader.bytes(size).ok()?;
+        if header == id {
+            return Some(data);
+        }
+    }
+    None
+}
+
+// =============================================================================
+// Entry Reading
+// =============================================================================
+
+/// Open entry `index` for reading, decrypting it with `password` if it is
+/// encrypted (the password is ignored for plain entries)
+///
+/// The password is checked against the entry's verifier here, so a wrong
+/// one fails before any data is read. AES entries are authenticated only at
+/// their end: the reader then fails, and [`read_error`] reports that as
+/// corruption.
+pub fn open_entry<'a, R: Read + Seek>(
+    archive: &'a mut ZipArchive<R>,
+    index: usize,
+    password: Option<&str>,
+) -> Result<ZipFile<'a>, ZipEntryError> {
+    let entry = archive.name_for_index(index).unwrap_or_default().to_string();
+    let opened = match password {
+        Some(password) => archive.by_index_decrypt(index, password.as_bytes()),
+        None => archive.by_index(index),
+    };
+    opened.map_err(|error| match error {
+        ZipError::UnsupportedArchive(ZipError::PASSWORD_REQUIRED) => ZipEntryError::PasswordRequired { entry },
+        ZipError::InvalidPassword => ZipEntryError::WrongPassword { entry },
+        ZipError::UnsupportedArchive(reason) => ZipEntryError::Unsupported { entry, error: reason.to_string() },
+        error => ZipEntryError::Corrupt { entry, error: error.to_string() },
+    })
+}
+
+/// Classify an error reading the data of `entry`
+///
+/// Failed CRC and AES authentication code checks mean the data was altered.
+/// A ZipCrypto password that happens to pass the one-byte verifier also
+/// ends up here, as a checksum mismatch.
+pub fn read_error(entry: &str, error: io::Error) -> ZipEntryError {
+    let message = error.to_string();
+    let error = if message.contains("authentication code") {
+        "AES authentication code does not match the data".to_string()
+    } else {
+        message
+    };
+    ZipEntryError::Corrupt { entry: entry.to_string(), error }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    /// (name, flags, method, extra field)
+    type Entry<'a> = (&'a str, u16, u16, Vec<u8>);
+
+    fn aes_extra(strength: u8) -> Vec<u8> {
+        let mut extra = Vec::new();
+        extra.extend_from_slice(&AES_EXTRA_ID.to_le_bytes());
+        extra.extend_from_slice(&7u16.to_le_bytes());
+        extra.extend_from_slice(&2u16.to_le_bytes()); // AE-2
+        extra.extend_from_slice(b"AE");
+        extra.push(strength);
+        extra.extend_from_slice(&8u16.to_le_bytes()); // deflate underneath
+        extra
+    }
+
+    /// Minimal ZIP with the given entries; the payloads are placeholders,
+    /// only the headers matter for the inventory
+    fn write_zip(path: &Path, entries: &[Entry]) {
+        let mut data = Vec::new();
+        let mut central = Vec::new();
+        for (name, flags, method, extra) in entries {
+            let offset = data.len() as u32;
+            let payload = b"ciphertext";
+            data.extend_from_slice(b"PK\x03\x04");
+            data.extend_from_slice(&20u16.to_le_bytes());
+            data.extend_from_slice(&flags.to_le_bytes());
+            data.extend_from_slice(&method.to_le_bytes());
+            data.extend_from_slice(&[0u8; 8]); // time, date, crc
+            data.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+            data.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+            data.extend_from_slice(&(name.len() as u16).to_le_bytes());
+            data.extend_from_slice(&(extra.len() as u16).to_le_bytes());
+            data.extend_from_slice(name.as_bytes());
+            data.extend_from_slice(extra);
+            data.extend_from_slice(payload);
+
+            central.extend_from_slice(b"PK\x01\x02");
+            central.extend_from_slice(&20u16.to_le_bytes());
+            central.extend_from_slice(&20u16.to_le_bytes());
+            central.extend_from_slice(&flags.to_le_bytes());
+            central.extend_from_slice(&method.to_le_bytes());
+            central.extend_from_slice(&[0u8; 8]);
+            central.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+            central.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+            central.extend_from_slice(&(name.len() as u16).to_le_bytes());
+            central.extend_from_slice(&(extra.len() as u16).to_le_bytes());
+            central.extend_from_slice(&[0u8; 10]); // comment len, disk, attributes
+            central.extend_from_slice(&offset.to_le_bytes());
+            central.extend_from_slice(name.as_bytes());
+            central.extend_from_slice(extra);
+        }
+        let cd_offset = data.len() as u32;
+        data.extend_from_slice(&central);
+        data.extend_from_slice(ZIP_EOCD_SIG);
+        data.extend_from_slice(&[0u8; 4]);
+        data.extend_from_slice(&(entries.len() as u16).to_le_bytes());
+        data.extend_from_slice(&(entries.len() as u16).to_le_bytes());
+        data.extend_from_slice(&(central.len() as u32).to_le_bytes());
+        data.extend_from_slice(&cd_offset.to_le_bytes());
+        data.extend_from_slice(&0u16.to_le_bytes());
+        std::fs::write(path, data).unwrap();
+    }
+
+    #[test]
+    fn test_mixed_archive_encryption_inventory() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("mixed.zip");
+        write_zip(&path, &[
+            ("readme.txt", 0, 0, Vec::new()),
+            ("photos/", 0, 0, Vec::new()),
+            ("ledger.xlsx", 0x0001, 8, Vec::new()),
+            ("vault/keys.kdbx", 0x0001, AES_METHOD, aes_extra(3)),
+            ("vault/notes.txt", 0x0001, AES_METHOD, aes_extra(1)),
+            ("pkware.bin", 0x0041, 8, Vec::new()),
+        ]);
+
+        let meta = parse_metadata(path.to_str().unwrap()).unwrap();
+        assert_eq!(meta.entry_count, Some(6));
+        assert_eq!(meta.uncompressed_size, Some(6 * 10));
+        assert!(meta.aes_encrypted);
+        let summary = meta.encryption.unwrap();
+        assert!(summary.complete);
+        assert_eq!(
+            (summary.unencrypted, summary.zip_crypto, summary.aes128, summary.aes192, summary.aes256, summary.other),
+            (2, 1, 1, 0, 1, 1)
+        );
+        assert_eq!(summary.encrypted_count(), 4);
+        let listed: Vec<(&str, ZipEncryption)> = summary.encrypted_entries.iter()
+            .map(|entry| (entry.name.as_str(), entry.encryption))
+            .collect();
+        assert_eq!(listed, vec![
+            ("ledger.xlsx", ZipEncryption::ZipCrypto),
+            ("vault/keys.kdbx", ZipEncryption::Aes256),
+            ("vault/notes.txt", ZipEncryption::Aes128),
+            ("pkware.bin", ZipEncryption::Other),
+        ]);
+
+        let info = super::super::info(path.to_str().unwrap()).unwrap();
+        assert_eq!(info.filenames_visible, Some(true));
+        assert_eq!(info.zip_encryption.map(|s| s.aes256), Some(1));
+        assert_eq!(info.uncompressed_size, Some(60));
+    }
+
+    #[test]
+    fn test_zipcrypto_only_archive_lists_first_names() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("locked.zip");
+        let names: Vec<String> = (0..MAX_LISTED_ENCRYPTED + 5).map(|n| format!("doc{n}.pdf")).collect();
+        let entries: Vec<Entry> = names.iter().map(|name| (name.as_str(), 0x0001, 8, Vec::new())).collect();
+        write_zip(&path, &entries);
+
+        let meta = parse_metadata(path.to_str().unwrap()).unwrap();
+        assert!(!meta.aes_encrypted);
+        let summary = meta.encryption.unwrap();
+        assert_eq!(summary.zip_crypto as usize, names.len());
+        assert_eq!(summary.unencrypted, 0);
+        assert_eq!(summary.encrypted_entries.len(), MAX_LISTED_ENCRYPTED);
+        assert_eq!(summary.encrypted_entries[0].name, "doc0.pdf");
+    }
+
+    #[test]
+    fn test_truncated_eocd_is_an_error() {
+        let mut eocd = b"PK\x05\x06".to_vec();
+        eocd.extend_from_slice(&[0u8; 6]);
+        eocd.extend_from_slice(&2u16.to_le_bytes());
+        eocd.extend_from_slice(&[0x2e, 0]);
+        assert_eq!(parse_eocd(&eocd, 500).unwrap_err(), Truncated { offset: 512, needed: 4, available: 2 });
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cut.zip");
+        std::fs::write(&path, &eocd).unwrap();
+        let error = parse_metadata(path.to_str().unwrap()).err().unwrap();
+        assert_eq!(error, "Corrupt ZIP end of central directory: truncated structure at offset 12: 4 bytes needed, 2 left");
+
+        eocd.extend_from_slice(&[0, 0, 0xff, 0xff, 0xff, 0xff]);
+        assert_eq!(parse_eocd(&eocd, 0).unwrap(), (2, 0x2e, None));
+    }
+
+    /// ZipCrypto-encrypted, stored single-entry ZIP built by hand (the zip
+    /// crate only decrypts this scheme)
+    fn zip_crypto_archive(name: &str, data: &[u8], password: &str) -> Vec<u8> {
+        fn crc_byte(crc: u32, byte: u8) -> u32 {
+            let mut c = (crc ^ byte as u32) & 0xff;
+            for _ in 0..8 {
+                c = if c & 1 != 0 { 0xEDB8_8320 ^ (c >> 1) } else { c >> 1 };
+            }
+            c ^ (crc >> 8)
+        }
+        let mut keys = [0x1234_5678u32, 0x2345_6789, 0x3456_7890];
+        let update = |keys: &mut [u32; 3], byte: u8| {
+            keys[0] = crc_byte(keys[0], byte);
+            keys[1] = keys[1].wrapping_add(keys[0] & 0xff).wrapping_mul(134_775_813).wrapping_add(1);
+            keys[2] = crc_byte(keys[2], (keys[1] >> 24) as u8);
+        };
+        password.bytes().for_each(|b| update(&mut keys, b));
+        let crc = crc32fast::hash(data);
+        let mut plain = vec![0x5au8; 11];
+        plain.push((crc >> 24) as u8);
+        plain.extend_from_slice(data);
+        let encrypted: Vec<u8> = plain.iter().map(|&p| {
+            let temp = (keys[2] | 2) as u16;
+            let cipher = p ^ (temp.wrapping_mul(temp ^ 1) >> 8) as u8;
+            update(&mut keys, p);
+            cipher
+        }).collect();
+
+        let fields = |out: &mut Vec<u8>| {
+            out.extend_from_slice(&1u16.to_le_bytes()); // encrypted
+            out.extend_from_slice(&0u16.to_le_bytes()); // stored
+            out.extend_from_slice(&[0u8; 4]); // time, date
+            out.extend_from_slice(&crc.to_le_bytes());
+            out.extend_from_slice(&(encrypted.len() as u32).to_le_bytes());
+            out.extend_from_slice(&(data.len() as u32).to_le_bytes());
+            out.extend_from_slice(&(name.len() as u16).to_le_bytes());
+            out.extend_from_slice(&0u16.to_le_bytes()); // extra
+        };
+        let mut zip = b"PK\x03\x04\x14\x00".to_vec();
+        fields(&mut zip);
+        zip.extend_from_slice(name.as_bytes());
+        zip.extend_from_slice(&encrypted);
+        let cd_offset = zip.len() as u32;
+        zip.extend_from_slice(b"PK\x01\x02\x14\x00\x14\x00");
+        fields(&mut zip);
+        zip.extend_from_slice(&[0u8; 14]); // comment, disk, attributes, local header offset 0
+        zip.extend_from_slice(name.as_bytes());
+        let cd_size = zip.len() as u32 - cd_offset;
+        zip.extend_from_slice(b"PK\x05\x06\0\0\0\0\x01\0\x01\0");
+        zip.extend_from_slice(&cd_size.to_le_bytes());
+        zip.extend_from_slice(&cd_offset.to_le_bytes());
+        zip.extend_from_slice(&[0, 0]);
+        zip
+    }
+
+    fn aes_archive(entries: &[(&str, &[u8])], password: &str) -> Vec<u8> {
+        use std::io::Write;
+        let mut writer = ::zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+        for (name, data) in entries {
+            let options = ::zip::write::SimpleFileOptions::default().with_aes_encryption(::zip::AesMode::Aes256, password);
+            writer.start_file(*name, options).unwrap();
+            writer.write_all(data).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    fn read_entry(bytes: &[u8], index: usize, password: Option<&str>) -> Result<Vec<u8>, ZipEntryError> {
+        let mut archive = ZipArchive::new(std::io::Cursor::new(bytes)).unwrap();
+        let mut entry = open_entry(&mut archive, index, password)?;
+        let name = entry.name().to_string();
+        let mut data = Vec::new();
+        entry.read_to_end(&mut data).map_err(|e| read_error(&name, e))?;
+        Ok(data)
+    }
+
+    #[test]
+    fn test_encrypted_entries_need_the_right_password() {
+        let zip_crypto = zip_crypto_archive("notes.txt", b"hello zipcrypto", "secret");
+        assert_eq!(read_entry(&zip_crypto, 0, Some("secret")).unwrap(), b"hello zipcrypto");
+        assert_eq!(read_entry(&zip_crypto, 0, Some("wrong")), Err(ZipEntryError::WrongPassword { entry: "notes.txt".to_string() }));
+        assert_eq!(read_entry(&zip_crypto, 0, None), Err(ZipEntryError::PasswordRequired { entry: "notes.txt".to_string() }));
+
+        // Under 20 bytes the writer uses AE-2 (no CRC), above it AE-1
+        let long = vec![7u8; 4096];
+        let aes = aes_archive(&[("ae2.txt", b"short"), ("ae1.bin", &long)], "secret");
+        assert_eq!(read_entry(&aes, 0, Some("secret")).unwrap(), b"short");
+        assert_eq!(read_entry(&aes, 1, Some("secret")).unwrap(), long);
+        let error = read_entry(&aes, 1, Some("Secret")).unwrap_err();
+        assert_eq!(error.to_string(), "Wrong password for ZIP entry ae1.bin");
+    }
+
+    #[test]
+    fn test_tampered_aes_entry_fails_authentication_not_the_password() {
+        let mut aes = aes_archive(&[("ae2.txt", b"short")], "secret");
+        // Local header, name, AES extra field, then salt (16) and verifier (2)
+        let data = 30 + u16::from_le_bytes([aes[26], aes[27]]) as usize + u16::from_le_bytes([aes[28], aes[29]]) as usize;
+        aes[data + 18] ^= 0x01;
+
+        match read_entry(&aes, 0, Some("secret")) {
+            Err(ZipEntryError::Corrupt { entry, error }) => {
+                assert_eq!(entry, "ae2.txt");
+                assert!(error.contains("authentication code"), "{error}");
+            }
+            other => panic!("expected a corrupt entry, got {other:?}"),
+        }
+    }
 }
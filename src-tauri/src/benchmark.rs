@@ -0,0 +1,416 @@
+//! Hash throughput benchmark and per-device tuning
+//!
+//! Hashing speed depends on the storage path (USB bridge, NAS, NVMe) as much
+//! as on the CPU, and the fixed [`BUFFER_SIZE`] isn't the best read size
+//! everywhere. [`benchmark_hash`] times every algorithm x buffer size
+//! combination over a sample and reports MB/s for each. The sample is either
+//! the first bytes of an existing file, opened read-only, or a temporary file
+//! in the system temp directory - the benchmark never writes next to the
+//! evidence.
+//!
+//! When the file is large enough, each combination reads its own region of
+//! it so none of them is served from the OS cache. Otherwise all
+//! combinations share one region after a warm-up pass (`cached_reads`), and
+//! the figures compare algorithms and buffer sizes rather than the device.
+//!
+//! The last result per storage device (see [`device_id`]) is kept in the app
+//! data directory. [`BenchmarkStore::tuning_for`] turns it into the
+//! [`HashTuning`] that `verify_auto` applies to raw images on that device.
+
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use chrono::{SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info, warn};
+
+use crate::common::atomic_file::write_atomic;
+use crate::common::hash::{HashAlgorithm, HashTuning, StreamingHasher};
+use crate::common::{device, BUFFER_SIZE};
+
+/// Sample read from a file when no limit is given
+pub const DEFAULT_FILE_SAMPLE: u64 = 1024 * 1024 * 1024; // 1GB
+/// Largest temporary sample the benchmark will write
+pub const MAX_TEMP_SAMPLE: u64 = 8 * 1024 * 1024 * 1024; // 8GB
+/// Buffer sizes compared when the caller gives none
+pub const DEFAULT_BUFFER_SIZES: [usize; 5] = [
+    256 * 1024,
+    1024 * 1024,
+    4 * 1024 * 1024,
+    BUFFER_SIZE,
+    64 * 1024 * 1024,
+];
+
+// =============================================================================
+// Benchmark Types
+// =============================================================================
+
+/// What to read during the benchmark
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum BenchmarkTarget {
+    /// The first `limit_bytes` (default [`DEFAULT_FILE_SAMPLE`]) of an
+    /// existing file, opened read-only
+    File {
+        path: String,
+        #[serde(default, rename = "limitBytes")]
+        limit_bytes: Option<u64>,
+    },
+    /// A temporary file of this size in the system temp directory
+    TempFile {
+        #[serde(rename = "sizeBytes")]
+        size_bytes: u64,
+    },
+}
+
+/// Throughput of one algorithm with one buffer size
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BenchmarkMeasurement {
+    /// Canonical algorithm name (e.g. "SHA-256")
+    pub algorithm: String,
+    pub buffer_size: usize,
+    pub bytes: u64,
+    pub duration_ms: u64,
+    pub mb_per_sec: f64,
+}
+
+/// Result of one benchmark run
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BenchmarkResult {
+    /// Storage device the sample was read from (see [`device_id`])
+    pub device: String,
+    /// File that was read; None for a temporary sample
+    pub source_path: Option<String>,
+    /// Bytes hashed per combination
+    pub sample_bytes: u64,
+    /// All combinations read the same (cached) region of the sample
+    pub cached_reads: bool,
+    /// One entry per algorithm and buffer size, in the order run
+    pub measurements: Vec<BenchmarkMeasurement>,
+    /// Fastest buffer size for each algorithm
+    pub best: Vec<BenchmarkMeasurement>,
+    /// RFC 3339, UTC
+    pub measured_at: String,
+}
+
+// =============================================================================
+// Device Identity
+// =============================================================================
+
+/// Mount point (Unix) or volume prefix (Windows) of the device holding `path`
+///
+/// Mount points stay the same when removable media is reconnected, unlike
+/// raw device numbers, so a saved benchmark keeps applying to it.
+pub fn device_id(path: &Path) -> Result<String, String> {
+    let mount = device::mount_point(path)?.to_string_lossy().to_string();
+    // Drive letters are case-insensitive
+    if cfg!(unix) { Ok(mount) } else { Ok(mount.to_uppercase()) }
+}
+
+// =============================================================================
+// Benchmark
+// =============================================================================
+
+/// Temporary sample file, deleted when dropped
+struct TempSample(PathBuf);
+
+impl TempSample {
+    /// Write `size` bytes of pseudo-random (incompressible) data
+    fn create(size: u64) -> Result<Self, String> {
+        let path = std::env::temp_dir().join(format!("ffx-hash-benchmark-{}.bin", uuid::Uuid::new_v4()));
+        let sample = TempSample(path);
+        let file = File::create(&sample.0)
+            .map_err(|e| format!("Failed to create benchmark sample: {e}"))?;
+        let mut writer = BufWriter::new(file);
+
+        // xorshift64* - fast, and defeats transparent compression
+        let mut state = 0x9E37_79B9_7F4A_7C15u64;
+        let mut block = vec![0u8; 1024 * 1024];
+        let mut remaining = size;
+        while remaining > 0 {
+            for word in block.chunks_exact_mut(8) {
+                state ^= state >> 12;
+                state ^= state << 25;
+                state ^= state >> 27;
+                word.copy_from_slice(&state.wrapping_mul(0x2545_F491_4F6C_DD1D).to_le_bytes());
+            }
+            let len = remaining.min(block.len() as u64) as usize;
+            writer.write_all(&block[..len])
+                .map_err(|e| format!("Failed to write benchmark sample: {e}"))?;
+            remaining -= len as u64;
+        }
+        writer.flush()
+            .map_err(|e| format!("Failed to write benchmark sample: {e}"))?;
+        Ok(sample)
+    }
+}
+
+impl Drop for TempSample {
+    fn drop(&mut self) {
+        if let Err(e) = fs::remove_file(&self.0) {
+            warn!(path = %self.0.display(), "Failed to remove benchmark sample: {}", e);
+        }
+    }
+}
+
+/// Hash `len` bytes from `offset` with reads of `buffer_size`
+fn time_region(path: &Path, offset: u64, len: u64, algorithm: HashAlgorithm, buffer_size: usize) -> Result<(u64, Duration), String> {
+    let mut file = File::open(path)
+        .map_err(|e| format!("Failed to open benchmark sample: {e}"))?;
+    file.seek(SeekFrom::Start(offset))
+        .map_err(|e| format!("Failed to seek benchmark sample: {e}"))?;
+    let mut reader = file.take(len);
+    let mut buf = vec![0u8; buffer_size];
+    let mut hasher = StreamingHasher::new(algorithm);
+    let mut bytes = 0u64;
+
+    let start = Instant::now();
+    loop {
+        let n = reader.read(&mut buf)
+            .map_err(|e| format!("Read error: {e}"))?;
+        if n == 0 {
+            break;
+        }
+        // Same parallel BLAKE3 path as verification
+        hasher.update_parallel(&buf[..n]);
+        bytes += n as u64;
+    }
+    hasher.finalize();
+    Ok((bytes, start.elapsed()))
+}
+
+/// Measure hashing throughput for every algorithm x buffer size combination
+///
+/// `buffer_sizes` are clamped like [`HashTuning::buffer_size`]; an empty
+/// list compares [`DEFAULT_BUFFER_SIZES`]. Progress is reported as
+/// (combinations done, combinations total).
+pub fn benchmark_hash<F>(
+    target: &BenchmarkTarget,
+    algorithms: &[String],
+    buffer_sizes: &[usize],
+    mut on_progress: F,
+) -> Result<BenchmarkResult, String>
+where
+    F: FnMut(u64, u64),
+{
+    if algorithms.is_empty() {
+        return Err("No hash algorithm specified".to_string());
+    }
+    let algorithms = algorithms.iter()
+        .map(|a| HashAlgorithm::from_str(a))
+        .collect::<Result<Vec<_>, _>>()?;
+    let requested = if buffer_sizes.is_empty() { &DEFAULT_BUFFER_SIZES[..] } else { buffer_sizes };
+    let mut sizes: Vec<usize> = requested.iter()
+        .map(|size| HashTuning { buffer_size: Some(*size), blake3_threads: None }.buffer_size())
+        .collect();
+    sizes.sort_unstable();
+    sizes.dedup();
+
+    let (sample_path, source_path, sample_bytes, _temp) = match target {
+        BenchmarkTarget::File { path, limit_bytes } => {
+            let meta = fs::metadata(path)
+                .map_err(|e| format!("Failed to get file metadata: {e}"))?;
+            if !meta.is_file() {
+                return Err(format!("Not a regular file: {path}"));
+            }
+            let sample = limit_bytes.unwrap_or(DEFAULT_FILE_SAMPLE).min(meta.len());
+            (PathBuf::from(path), Some(path.clone()), sample, None)
+        }
+        BenchmarkTarget::TempFile { size_bytes } => {
+            if *size_bytes == 0 || *size_bytes > MAX_TEMP_SAMPLE {
+                return Err(format!("Benchmark sample size must be between 1 byte and {MAX_TEMP_SAMPLE} bytes"));
+            }
+            let temp = TempSample::create(*size_bytes)?;
+            (temp.0.clone(), None, *size_bytes, Some(temp))
+        }
+    };
+    if sample_bytes == 0 {
+        return Err("Benchmark sample is empty".to_string());
+    }
+
+    let device = device_id(&sample_path)?;
+    let combinations: Vec<(HashAlgorithm, usize)> = algorithms.iter()
+        .flat_map(|algo| sizes.iter().map(move |size| (*algo, *size)))
+        .collect();
+    let total = combinations.len() as u64;
+
+    // A region per combination keeps every read uncached, if the file is big enough
+    let file_size = fs::metadata(&sample_path).map(|m| m.len()).unwrap_or(0);
+    let cached_reads = source_path.is_none() || file_size / sample_bytes < total;
+    if cached_reads {
+        time_region(&sample_path, 0, sample_bytes, HashAlgorithm::Crc32, BUFFER_SIZE)?;
+    }
+    info!(device = %device, sample_bytes, combinations = total, cached_reads, "Starting hash benchmark");
+
+    let mut measurements = Vec::with_capacity(combinations.len());
+    for (i, (algorithm, buffer_size)) in combinations.into_iter().enumerate() {
+        let offset = if cached_reads { 0 } else { i as u64 * sample_bytes };
+        let (bytes, elapsed) = time_region(&sample_path, offset, sample_bytes, algorithm, buffer_size)?;
+        let secs = elapsed.as_secs_f64();
+        let mb_per_sec = if secs > 0.0 { (bytes as f64 / (1024.0 * 1024.0)) / secs } else { 0.0 };
+        debug!(algorithm = algorithm.name(), buffer_size, mb_per_sec, "Benchmark combination done");
+        measurements.push(BenchmarkMeasurement {
+            algorithm: algorithm.name().to_string(),
+            buffer_size,
+            bytes,
+            duration_ms: elapsed.as_millis() as u64,
+            mb_per_sec,
+        });
+        on_progress(i as u64 + 1, total);
+    }
+
+    let mut best: Vec<BenchmarkMeasurement> = Vec::new();
+    for m in &measurements {
+        match best.iter_mut().find(|b| b.algorithm == m.algorithm) {
+            Some(b) if m.mb_per_sec > b.mb_per_sec => *b = m.clone(),
+            Some(_) => {}
+            None => best.push(m.clone()),
+        }
+    }
+
+    Ok(BenchmarkResult {
+        device,
+        source_path,
+        sample_bytes,
+        cached_reads,
+        measurements,
+        best,
+        measured_at: Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true),
+    })
+}
+
+// =============================================================================
+// Benchmark Store
+// =============================================================================
+
+/// Last benchmark result per device, kept in one JSON file
+#[derive(Clone, Debug)]
+pub struct BenchmarkStore {
+    file: PathBuf,
+}
+
+impl BenchmarkStore {
+    pub fn new(file: impl Into<PathBuf>) -> Self {
+        Self { file: file.into() }
+    }
+
+    /// Results under the app data directory (next to the database)
+    pub fn default_location() -> Self {
+        let file = dirs::data_local_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("com.ffxcheck.app")
+            .join("hash_benchmarks.json");
+        Self::new(file)
+    }
+
+    /// All saved results, keyed by device
+    pub fn load_all(&self) -> BTreeMap<String, BenchmarkResult> {
+        let Ok(json) = fs::read(&self.file) else {
+            return BTreeMap::new();
+        };
+        serde_json::from_slice(&json)
+            .map_err(|e| warn!(path = %self.file.display(), "Ignoring unreadable benchmark results: {}", e))
+            .unwrap_or_default()
+    }
+
+    pub fn get(&self, device: &str) -> Option<BenchmarkResult> {
+        self.load_all().remove(device)
+    }
+
+    /// Save a result, replacing the previous one for its device
+    pub fn save(&self, result: &BenchmarkResult) -> Result<(), String> {
+        let mut all = self.load_all();
+        all.insert(result.device.clone(), result.clone());
+        if let Some(dir) = self.file.parent() {
+            fs::create_dir_all(dir)
+                .map_err(|e| format!("Failed to create benchmark directory: {e}"))?;
+        }
+        let json = serde_json::to_vec_pretty(&all)
+            .map_err(|e| format!("Failed to serialize benchmark results: {e}"))?;
+        write_atomic(&self.file, json)
+            .map_err(|e| format!("Failed to write benchmark results: {e}"))?;
+        debug!(device = %result.device, "Benchmark result saved");
+        Ok(())
+    }
+
+    /// Tuning for hashing `path` with `algorithm`, from the benchmark of its device
+    ///
+    /// Default tuning when the device or algorithm hasn't been benchmarked.
+    pub fn tuning_for(&self, path: &str, algorithm: &str) -> HashTuning {
+        let Ok(algorithm) = HashAlgorithm::from_str(algorithm) else {
+            return HashTuning::default();
+        };
+        let Some(result) = device_id(Path::new(path)).ok().and_then(|device| self.get(&device)) else {
+            return HashTuning::default();
+        };
+        result.best.iter()
+            .find(|m| HashAlgorithm::from_str(&m.algorithm).ok() == Some(algorithm))
+            .map(|m| HashTuning { buffer_size: Some(m.buffer_size), blake3_threads: None })
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_benchmark_is_read_only_and_tunes_its_device() {
+        let evidence = tempfile::tempdir().unwrap();
+        let path = evidence.path().join("disk.dd");
+        let data: Vec<u8> = (0..4 * 256 * 1024u32).map(|i| (i % 251) as u8).collect();
+        fs::write(&path, &data).unwrap();
+        let path_str = path.to_string_lossy().to_string();
+
+        let target = BenchmarkTarget::File { path: path_str.clone(), limit_bytes: Some(256 * 1024) };
+        let algorithms = vec!["md5".to_string(), "blake3".to_string()];
+        let mut progress = Vec::new();
+        let result = benchmark_hash(&target, &algorithms, &[128 * 1024, 64 * 1024], |done, total| progress.push((done, total))).unwrap();
+
+        assert_eq!(result.measurements.len(), 4);
+        assert_eq!(progress.last(), Some(&(4, 4)));
+        assert!(!result.cached_reads, "four 256KB regions fit in the 1MB file");
+        assert!(result.measurements.iter().all(|m| m.bytes == 256 * 1024));
+        assert_eq!(result.best.iter().map(|m| m.algorithm.as_str()).collect::<Vec<_>>(), vec!["MD5", "BLAKE3"]);
+        assert_eq!(fs::read(&path).unwrap(), data);
+        assert_eq!(fs::read_dir(evidence.path()).unwrap().count(), 1);
+
+        let app_data = tempfile::tempdir().unwrap();
+        let store = BenchmarkStore::new(app_data.path().join("hash_benchmarks.json"));
+        assert_eq!(store.tuning_for(&path_str, "md5"), HashTuning::default());
+        store.save(&result).unwrap();
+        let best_md5 = result.best[0].buffer_size;
+        assert_eq!(store.tuning_for(&path_str, "MD5").buffer_size, Some(best_md5));
+        assert_eq!(store.tuning_for(&path_str, "sha256"), HashTuning::default());
+    }
+
+    #[test]
+    fn test_temp_benchmark_cleans_up_and_validates_input() {
+        let target = BenchmarkTarget::TempFile { size_bytes: 200 * 1024 };
+        let result = benchmark_hash(&target, &["sha256".to_string()], &[1], |_, _| {}).unwrap();
+        assert!(result.cached_reads);
+        assert_eq!(result.source_path, None);
+        assert_eq!(result.measurements.len(), 1);
+        assert_eq!(result.measurements[0].buffer_size, crate::common::hash::MIN_TUNED_BUFFER_SIZE);
+
+        let leftovers = fs::read_dir(std::env::temp_dir()).unwrap()
+            .flatten()
+            .filter(|e| e.file_name().to_string_lossy().starts_with("ffx-hash-benchmark-"))
+            .count();
+        assert_eq!(leftovers, 0);
+
+        assert!(benchmark_hash(&BenchmarkTarget::TempFile { size_bytes: 0 }, &["md5".to_string()], &[], |_, _| {}).is_err());
+        assert!(benchmark_hash(&target, &[], &[], |_, _| {}).is_err());
+        assert!(benchmark_hash(&target, &["nope".to_string()], &[], |_, _| {}).is_err());
+
+        let target: BenchmarkTarget = serde_json::from_str(r#"{"sizeBytes": 1024}"#).unwrap();
+        assert!(matches!(target, BenchmarkTarget::TempFile { size_bytes: 1024 }));
+        let target: BenchmarkTarget = serde_json::from_str(r#"{"path": "/evidence/disk.dd", "limitBytes": 10}"#).unwrap();
+        assert!(matches!(target, BenchmarkTarget::File { limit_bytes: Some(10), .. }));
+    }
+}
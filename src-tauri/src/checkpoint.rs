@@ -0,0 +1,461 @@
+//! Checkpoint/resume for long raw image verifications
+//!
+//! A multi-terabyte verification that is interrupted (crash, reboot, user
+//! cancel) would otherwise have to start again from byte 0. Checkpointed
+//! verification periodically writes a small JSON file to the app data
+//! directory - never next to the evidence - containing:
+//!
+//! - the identity of every input segment (path, size, modification time)
+//! - the algorithm and the byte offset reached
+//! - the serialized hasher state (see `common::resumable_hash`)
+//!
+//! `resume_raw_verify` refuses to continue if any segment changed since the
+//! checkpoint was written, then seeks to the saved offset and carries on.
+//! The checkpoint is deleted once the final hash has been computed.
+//!
+//! Exact resume is available for MD5, SHA-1, SHA-256, SHA-512 and CRC32.
+//! BLAKE2/BLAKE3, SHA3 and the XXH family don't allow their state to be
+//! exported, so their checkpoints only record the inputs and a resume hashes
+//! again from the start of the image (still validating that the evidence
+//! is unchanged). `VerifyCheckpoint::exact_resume` tells the two apart.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::UNIX_EPOCH;
+
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info, warn};
+
+use crate::common::atomic_file::write_atomic;
+use crate::common::path_security::check_output_location;
+use crate::common::resumable_hash::{HasherSnapshot, ResumableHasher};
+use crate::common::{resources, HashAlgorithm, StreamingHasher, BUFFER_SIZE};
+use crate::raw;
+
+/// Write a checkpoint after this many bytes have been hashed
+pub const DEFAULT_CHECKPOINT_INTERVAL: u64 = 4 * 1024 * 1024 * 1024; // 4GB
+
+// =============================================================================
+// Checkpoint Types
+// =============================================================================
+
+/// Identity of one input file at the time a checkpoint was written
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InputFingerprint {
+    pub path: String,
+    pub size: u64,
+    /// Modification time in milliseconds since the Unix epoch
+    pub modified_ms: Option<u64>,
+}
+
+impl InputFingerprint {
+    fn of(path: &Path) -> Result<Self, String> {
+        let meta = fs::metadata(path)
+            .map_err(|e| format!("Failed to stat {}: {}", path.display(), e))?;
+        let modified_ms = meta.modified().ok()
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map(|d| d.as_millis() as u64);
+        Ok(Self {
+            path: path.to_string_lossy().to_string(),
+            size: meta.len(),
+            modified_ms,
+        })
+    }
+}
+
+/// Saved state of an interrupted verification
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VerifyCheckpoint {
+    pub job_id: String,
+    /// Path the verification was started with (first segment)
+    pub path: String,
+    pub algorithm: String,
+    pub inputs: Vec<InputFingerprint>,
+    pub total_size: u64,
+    /// Bytes covered by `hasher` (always 0 when exact resume isn't possible)
+    pub bytes_hashed: u64,
+    pub hasher: Option<HasherSnapshot>,
+    pub exact_resume: bool,
+    pub updated_at: String,
+}
+
+/// Outcome of a checkpointed verification run
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResumableVerifyResult {
+    pub job_id: String,
+    /// Final hash, or None if the run was cancelled (checkpoint kept)
+    pub hash: Option<String>,
+    pub bytes_hashed: u64,
+    pub total_size: u64,
+    /// Offset the run started from (0 for a fresh or non-exact run)
+    pub resumed_from: u64,
+}
+
+// =============================================================================
+// Checkpoint Store
+// =============================================================================
+
+/// Directory of checkpoint files, one `<job_id>.json` per job
+#[derive(Clone, Debug)]
+pub struct CheckpointStore {
+    dir: PathBuf,
+}
+
+impl CheckpointStore {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Checkpoints under the app data directory (next to the database)
+    pub fn default_location() -> Self {
+        let dir = dirs::data_local_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("com.ffxcheck.app")
+            .join("checkpoints");
+        Self::new(dir)
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn file_for(&self, job_id: &str) -> Result<PathBuf, String> {
+        // Job ids become file names - only allow uuid-like ids
+        if job_id.is_empty() || !job_id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+            return Err(format!("Invalid job id: {job_id}"));
+        }
+        Ok(self.dir.join(format!("{job_id}.json")))
+    }
+
+    /// Write a checkpoint atomically (temp file + rename)
+    pub fn save(&self, checkpoint: &VerifyCheckpoint) -> Result<(), String> {
+        let path = self.file_for(&checkpoint.job_id)?;
+        fs::create_dir_all(&self.dir)
+            .map_err(|e| format!("Failed to create checkpoint directory: {e}"))?;
+
+        let json = serde_json::to_vec_pretty(checkpoint)
+            .map_err(|e| format!("Failed to serialize checkpoint: {e}"))?;
+        write_atomic(&path, json)
+            .map_err(|e| format!("Failed to write checkpoint: {e}"))?;
+
+        debug!(job_id = %checkpoint.job_id, bytes_hashed = checkpoint.bytes_hashed, "Checkpoint saved");
+        Ok(())
+    }
+
+    pub fn load(&self, job_id: &str) -> Result<VerifyCheckpoint, String> {
+        let path = self.file_for(job_id)?;
+        let json = fs::read(&path)
+            .map_err(|e| format!("No checkpoint for job {job_id}: {e}"))?;
+        serde_json::from_slice(&json)
+            .map_err(|e| format!("Corrupt checkpoint for job {job_id}: {e}"))
+    }
+
+    /// All readable checkpoints, most recently updated first
+    pub fn list(&self) -> Vec<VerifyCheckpoint> {
+        let Ok(entries) = fs::read_dir(&self.dir) else {
+            return Vec::new();
+        };
+        let mut checkpoints: Vec<VerifyCheckpoint> = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .filter_map(|path| {
+                let json = fs::read(&path).ok()?;
+                serde_json::from_slice(&json)
+                    .map_err(|e| warn!(path = %path.display(), "Skipping unreadable checkpoint: {}", e))
+                    .ok()
+            })
+            .collect();
+        checkpoints.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
+        checkpoints
+    }
+
+    /// Delete a checkpoint; returns false if it did not exist
+    pub fn remove(&self, job_id: &str) -> Result<bool, String> {
+        let path = self.file_for(job_id)?;
+        match fs::remove_file(&path) {
+            Ok(()) => Ok(true),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(false),
+            Err(e) => Err(format!("Failed to delete checkpoint {job_id}: {e}")),
+        }
+    }
+}
+
+// =============================================================================
+// Checkpointed Raw Verification
+// =============================================================================
+
+/// Hasher used by a checkpointed run
+enum JobHasher {
+    Resumable(ResumableHasher),
+    /// State can't be exported - checkpoints only record the inputs
+    Streaming(Box<StreamingHasher>),
+}
+
+impl JobHasher {
+    fn update(&mut self, data: &[u8]) {
+        match self {
+            JobHasher::Resumable(h) => h.update(data),
+            JobHasher::Streaming(h) => h.update(data),
+        }
+    }
+
+    fn finalize(self) -> String {
+        match self {
+            JobHasher::Resumable(h) => h.finalize(),
+            JobHasher::Streaming(h) => h.finalize(),
+        }
+    }
+}
+
+fn fingerprint_inputs(path: &str) -> Result<Vec<InputFingerprint>, String> {
+    raw::get_segment_paths(path)?
+        .iter()
+        .map(|segment| InputFingerprint::of(segment))
+        .collect()
+}
+
+/// Start a checkpointed verification of a raw image (single or multi-segment)
+///
+/// A checkpoint is written every `interval` bytes and when `cancel` is set;
+/// a cancelled run returns with `hash: None` and can be continued with
+/// `resume_raw_verify`. Progress is reported as (bytes hashed, total bytes).
+pub fn start_raw_verify<F>(
+    store: &CheckpointStore,
+    job_id: &str,
+    path: &str,
+    algorithm: &str,
+    interval: u64,
+    cancel: &AtomicBool,
+    progress: F,
+) -> Result<ResumableVerifyResult, String>
+where
+    F: FnMut(u64, u64),
+{
+    let algo = HashAlgorithm::from_str(algorithm)?;
+    let inputs = fingerprint_inputs(path)?;
+
+    let segment_paths: Vec<PathBuf> = inputs.iter().map(|input| PathBuf::from(&input.path)).collect();
+    check_output_location(&segment_paths, store.dir())
+        .map_err(|e| format!("Checkpoint location rejected: {e}"))?;
+
+    let exact_resume = ResumableHasher::supports(algo);
+    let checkpoint = VerifyCheckpoint {
+        job_id: job_id.to_string(),
+        path: path.to_string(),
+        algorithm: algo.name().to_string(),
+        total_size: inputs.iter().map(|input| input.size).sum(),
+        inputs,
+        bytes_hashed: 0,
+        hasher: None,
+        exact_resume,
+        updated_at: chrono::Utc::now().to_rfc3339(),
+    };
+    store.save(&checkpoint)?;
+
+    info!(job_id, path, algorithm = algo.name(), exact_resume, "Starting checkpointed verification");
+    run_raw_verify(store, checkpoint, interval, cancel, progress)
+}
+
+/// Continue a verification from its checkpoint
+///
+/// Fails without hashing anything if any input segment was added, removed,
+/// resized or modified since the checkpoint was written.
+pub fn resume_raw_verify<F>(
+    store: &CheckpointStore,
+    job_id: &str,
+    interval: u64,
+    cancel: &AtomicBool,
+    progress: F,
+) -> Result<ResumableVerifyResult, String>
+where
+    F: FnMut(u64, u64),
+{
+    let checkpoint = store.load(job_id)?;
+
+    let current = fingerprint_inputs(&checkpoint.path)?;
+    if current != checkpoint.inputs {
+        warn!(target: "security", job_id, path = %checkpoint.path, "Evidence changed since checkpoint");
+        return Err(format!(
+            "Evidence changed since the checkpoint was written ({}) - start a new verification",
+            checkpoint.path
+        ));
+    }
+
+    info!(job_id, bytes_hashed = checkpoint.bytes_hashed, "Resuming checkpointed verification");
+    run_raw_verify(store, checkpoint, interval, cancel, progress)
+}
+
+fn run_raw_verify<F>(
+    store: &CheckpointStore,
+    mut checkpoint: VerifyCheckpoint,
+    interval: u64,
+    cancel: &AtomicBool,
+    mut progress: F,
+) -> Result<ResumableVerifyResult, String>
+where
+    F: FnMut(u64, u64),
+{
+    let algo = HashAlgorithm::from_str(&checkpoint.algorithm)?;
+    let mut handle = raw::RawHandle::open(&checkpoint.path)?;
+    let total_size = handle.total_size();
+
+    let mut hasher = match (checkpoint.hasher.take(), ResumableHasher::new(algo)) {
+        (Some(snapshot), _) => {
+            let restored = ResumableHasher::restore(snapshot)?;
+            if restored.length() != checkpoint.bytes_hashed || checkpoint.bytes_hashed > total_size {
+                return Err("Corrupt checkpoint: hasher state does not match offset".to_string());
+            }
+            JobHasher::Resumable(restored)
+        }
+        (None, Some(fresh)) => {
+            checkpoint.bytes_hashed = 0;
+            JobHasher::Resumable(fresh)
+        }
+        (None, None) => {
+            checkpoint.bytes_hashed = 0;
+            JobHasher::Streaming(Box::new(StreamingHasher::new(algo)))
+        }
+    };
+
+    let resumed_from = checkpoint.bytes_hashed;
+    let mut bytes_hashed = resumed_from;
+    let mut last_checkpoint = resumed_from;
+    handle.seek(resumed_from);
+    progress(bytes_hashed, total_size);
+
+    let save = |checkpoint: &mut VerifyCheckpoint, hasher: &JobHasher, bytes_hashed: u64| {
+        if let JobHasher::Resumable(h) = hasher {
+            checkpoint.bytes_hashed = bytes_hashed;
+            checkpoint.hasher = Some(h.snapshot());
+        }
+        checkpoint.updated_at = chrono::Utc::now().to_rfc3339();
+        store.save(checkpoint)
+    };
+
+    let mut buf = vec![0u8; resources::buffer_size(BUFFER_SIZE)];
+    loop {
+        if cancel.load(Ordering::Relaxed) {
+            save(&mut checkpoint, &hasher, bytes_hashed)?;
+            info!(job_id = %checkpoint.job_id, bytes_hashed, "Verification cancelled, checkpoint kept");
+            return Ok(ResumableVerifyResult {
+                job_id: checkpoint.job_id,
+                hash: None,
+                bytes_hashed,
+                total_size,
+                resumed_from,
+            });
+        }
+
+        let n = handle.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+        bytes_hashed += n as u64;
+        progress(bytes_hashed, total_size);
+
+        if bytes_hashed - last_checkpoint >= interval {
+            save(&mut checkpoint, &hasher, bytes_hashed)?;
+            last_checkpoint = bytes_hashed;
+        }
+    }
+
+    if bytes_hashed != total_size {
+        return Err(format!(
+            "Image ended early: read {} of {} bytes - checkpoint kept",
+            bytes_hashed, total_size
+        ));
+    }
+
+    let hash = hasher.finalize();
+    store.remove(&checkpoint.job_id)?;
+    info!(job_id = %checkpoint.job_id, hash = %hash, "Checkpointed verification complete");
+
+    Ok(ResumableVerifyResult {
+        job_id: checkpoint.job_id,
+        hash: Some(hash),
+        bytes_hashed,
+        total_size,
+        resumed_from,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Two-segment raw image plus a checkpoint directory outside it
+    fn fixture() -> (tempfile::TempDir, tempfile::TempDir, String) {
+        let evidence = tempfile::tempdir().unwrap();
+        let data: Vec<u8> = (0..(BUFFER_SIZE as u32 * 3)).map(|i| (i % 251) as u8).collect();
+        let (first, second) = data.split_at(BUFFER_SIZE + BUFFER_SIZE / 2);
+        fs::write(evidence.path().join("disk.001"), first).unwrap();
+        fs::write(evidence.path().join("disk.002"), second).unwrap();
+        let path = evidence.path().join("disk.001").to_string_lossy().to_string();
+        (evidence, tempfile::tempdir().unwrap(), path)
+    }
+
+    #[test]
+    fn test_interrupted_verify_resumes_to_same_hash() {
+        let (_evidence, checkpoints, path) = fixture();
+        let store = CheckpointStore::new(checkpoints.path());
+
+        for algorithm in ["sha256", "md5", "blake3"] {
+            let expected = raw::verify(&path, algorithm).unwrap();
+
+            // Cancel after the first buffer has been hashed
+            let cancel = AtomicBool::new(false);
+            let first = start_raw_verify(&store, "job-1", &path, algorithm, 1, &cancel, |current, _| {
+                if current > 0 {
+                    cancel.store(true, Ordering::Relaxed);
+                }
+            })
+            .unwrap();
+            assert!(first.hash.is_none());
+            assert_eq!(store.list().len(), 1);
+
+            let not_cancelled = AtomicBool::new(false);
+            let mut first_progress = None;
+            let resumed = resume_raw_verify(&store, "job-1", 1, &not_cancelled, |current, _| {
+                first_progress.get_or_insert(current);
+            })
+            .unwrap();
+
+            assert_eq!(resumed.hash.as_deref(), Some(expected.as_str()), "{algorithm}");
+            if ResumableHasher::supports(HashAlgorithm::from_str(algorithm).unwrap()) {
+                assert_eq!(resumed.resumed_from, BUFFER_SIZE as u64);
+                assert_eq!(first_progress, Some(BUFFER_SIZE as u64));
+            } else {
+                assert_eq!(resumed.resumed_from, 0);
+            }
+            assert!(store.list().is_empty());
+        }
+    }
+
+    #[test]
+    fn test_resume_refuses_changed_evidence() {
+        let (evidence, checkpoints, path) = fixture();
+        let store = CheckpointStore::new(checkpoints.path());
+
+        let cancel = AtomicBool::new(true);
+        start_raw_verify(&store, "job-2", &path, "sha1", DEFAULT_CHECKPOINT_INTERVAL, &cancel, |_, _| {}).unwrap();
+
+        fs::write(evidence.path().join("disk.002"), b"tampered").unwrap();
+        let not_cancelled = AtomicBool::new(false);
+        let err = resume_raw_verify(&store, "job-2", DEFAULT_CHECKPOINT_INTERVAL, &not_cancelled, |_, _| {}).unwrap_err();
+        assert!(err.contains("Evidence changed"));
+    }
+
+    #[test]
+    fn test_checkpoint_inside_evidence_folder_rejected() {
+        let (evidence, _checkpoints, path) = fixture();
+        let store = CheckpointStore::new(evidence.path().join("checkpoints"));
+        let cancel = AtomicBool::new(false);
+        assert!(start_raw_verify(&store, "job-3", &path, "md5", DEFAULT_CHECKPOINT_INTERVAL, &cancel, |_, _| {}).is_err());
+        assert!(store.file_for("../escape").is_err());
+    }
+}
@@ -0,0 +1,397 @@
+// Crash-safe output files
+//
+// Every file the tool produces - extracted files and images, reports,
+// manifests, exports - is written to `<name>.partial` beside its final path,
+// synced, and only then renamed into place (a rename within one folder is
+// atomic). A crash or a failed write never leaves a truncated file under the
+// final name, so a file with its final name is complete.
+//
+// Large outputs also keep a progress marker, `<name>.partial.progress`,
+// recording how many bytes of the partial file are synced to disk, so resume
+// logic can tell an interrupted output from a complete one. What crashed
+// runs leave behind is removed by `clean_stale_partials`.
+
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufWriter, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use tracing::{debug, info, warn};
+
+use super::device::is_block_device_path;
+
+/// Suffix of an output while it is being written
+pub const PARTIAL_SUFFIX: &str = ".partial";
+
+/// Suffix of the progress marker beside a partial output
+pub const PROGRESS_SUFFIX: &str = ".partial.progress";
+
+/// Partial files untouched for this long are left over from a crashed run
+pub const STALE_PARTIAL_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Bytes written between progress markers
+pub const PROGRESS_INTERVAL: u64 = 256 * 1024 * 1024;
+
+/// Write buffer of [`AtomicFile::create`] (the `BufWriter` default)
+const DEFAULT_BUFFER: usize = 8 * 1024;
+
+/// Where the output `path` is written before it is complete
+pub fn partial_path(path: &Path) -> PathBuf {
+    with_suffix(path, PARTIAL_SUFFIX)
+}
+
+fn progress_path(path: &Path) -> PathBuf {
+    with_suffix(path, PROGRESS_SUFFIX)
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(suffix);
+    path.with_file_name(name)
+}
+
+/// Contents of a progress marker
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ProgressMarker {
+    /// Bytes of the partial file synced to disk
+    pub bytes_written: u64,
+    /// Final size of the output, when known
+    pub expected_bytes: Option<u64>,
+}
+
+/// What exists of an output
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum OutputState {
+    Missing,
+    /// The file exists under its final name
+    Complete,
+    /// Only the partial file exists; `bytes_written` is what its progress
+    /// marker vouches for (the file's size without a marker)
+    Interrupted {
+        bytes_written: u64,
+        expected_bytes: Option<u64>,
+    },
+}
+
+/// Whether the output `path` is complete, interrupted or not started
+pub fn output_state(path: &Path) -> OutputState {
+    if path.exists() {
+        return OutputState::Complete;
+    }
+    let Ok(partial) = fs::metadata(partial_path(path)) else {
+        return OutputState::Missing;
+    };
+    let marker: Option<ProgressMarker> = fs::read(progress_path(path)).ok()
+        .and_then(|json| serde_json::from_slice(&json).ok());
+    match marker {
+        // Bytes written after the last marker may not have reached the disk
+        Some(marker) => OutputState::Interrupted {
+            bytes_written: marker.bytes_written.min(partial.len()),
+            expected_bytes: marker.expected_bytes,
+        },
+        None => OutputState::Interrupted { bytes_written: partial.len(), expected_bytes: None },
+    }
+}
+
+/// An output file written under its partial name until [`commit`](Self::commit)
+///
+/// Dropping it uncommitted (an error on the way) removes the partial file,
+/// unless [`keep_partial`](Self::keep_partial) asked for it to be resumable.
+pub struct AtomicFile {
+    /// Taken on commit and drop - Windows can't rename or remove an open file
+    writer: Option<BufWriter<File>>,
+    path: PathBuf,
+    partial: PathBuf,
+    written: u64,
+    /// Expected size and the byte count of the next marker, when markers are kept
+    progress: Option<(Option<u64>, u64)>,
+    keep_partial: bool,
+    committed: bool,
+}
+
+impl AtomicFile {
+    /// Start writing `path`, replacing a partial file left for it
+    pub fn create(path: &Path) -> io::Result<Self> {
+        Self::create_with_capacity(path, DEFAULT_BUFFER)
+    }
+
+    /// [`create`](Self::create) with a write buffer of `capacity` bytes
+    ///
+    /// Drives (`/dev/sdb`, `\\.\PhysicalDrive2`) are refused as outputs.
+    pub fn create_with_capacity(path: &Path, capacity: usize) -> io::Result<Self> {
+        if is_block_device_path(&path.to_string_lossy()) {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("{} is a drive, not a file to write", path.display())));
+        }
+        let partial = partial_path(path);
+        let file = File::create(&partial)?;
+        Ok(Self::new(path, partial, file, 0, capacity))
+    }
+
+    /// Continue the partial file of `path` after its first `len` bytes,
+    /// dropping anything behind them (see [`output_state`])
+    pub fn resume(path: &Path, len: u64) -> io::Result<Self> {
+        let partial = partial_path(path);
+        let mut file = OpenOptions::new().write(true).open(&partial)?;
+        file.set_len(len)?;
+        file.seek(SeekFrom::End(0))?;
+        Ok(Self::new(path, partial, file, len, DEFAULT_BUFFER))
+    }
+
+    fn new(path: &Path, partial: PathBuf, file: File, written: u64, capacity: usize) -> Self {
+        Self {
+            writer: Some(BufWriter::with_capacity(capacity, file)),
+            path: path.to_path_buf(),
+            partial,
+            written,
+            progress: None,
+            keep_partial: false,
+            committed: false,
+        }
+    }
+
+    /// Keep a progress marker beside the partial file, updated every
+    /// [`PROGRESS_INTERVAL`] bytes, for outputs of several gigabytes
+    pub fn with_progress_markers(mut self, expected_bytes: Option<u64>) -> io::Result<Self> {
+        self.progress = Some((expected_bytes, self.written + PROGRESS_INTERVAL));
+        self.record_progress()?;
+        Ok(self)
+    }
+
+    /// Leave the partial file in place if dropped uncommitted, to resume it
+    pub fn keep_partial(mut self) -> Self {
+        self.keep_partial = true;
+        self
+    }
+
+    /// The final path
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Bytes in the partial file, including those before a resume
+    pub fn written(&self) -> u64 {
+        self.written
+    }
+
+    fn writer(&mut self) -> &mut BufWriter<File> {
+        self.writer.as_mut().expect("writer is only taken by commit and drop")
+    }
+
+    /// Sync what was written so far and record it in the progress marker
+    /// (nothing without [`with_progress_markers`](Self::with_progress_markers))
+    pub fn record_progress(&mut self) -> io::Result<()> {
+        let Some((expected_bytes, next)) = self.progress.as_mut() else { return Ok(()) };
+        *next = self.written + PROGRESS_INTERVAL;
+        let marker = ProgressMarker { bytes_written: self.written, expected_bytes: *expected_bytes };
+        let writer = self.writer();
+        writer.flush()?;
+        writer.get_ref().sync_data()?;
+        fs::write(progress_path(&self.path), serde_json::to_vec(&marker)?)
+    }
+
+    /// Sync the partial file to disk and rename it to its final name
+    pub fn commit(mut self) -> io::Result<()> {
+        let writer = self.writer.take().expect("writer is only taken by commit and drop");
+        let file = writer.into_inner().map_err(|e| e.into_error())?;
+        file.sync_all()?;
+        drop(file);
+        fs::rename(&self.partial, &self.path)?;
+        self.committed = true;
+        sync_parent(&self.path);
+        if self.progress.is_some() {
+            let _ = fs::remove_file(progress_path(&self.path));
+        }
+        debug!(path = %self.path.display(), bytes = self.written, "Output committed");
+        Ok(())
+    }
+}
+
+impl Write for AtomicFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.writer().write(buf)?;
+        self.written += n as u64;
+        if self.progress.is_some_and(|(_, next)| self.written >= next) {
+            self.record_progress()?;
+        }
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer().flush()
+    }
+}
+
+impl Drop for AtomicFile {
+    fn drop(&mut self) {
+        // Close the file before removing it
+        drop(self.writer.take());
+        if self.committed || self.keep_partial {
+            return;
+        }
+        if let Err(e) = fs::remove_file(&self.partial) {
+            warn!(path = %self.partial.display(), "Failed to remove partial output: {}", e);
+        }
+        let _ = fs::remove_file(progress_path(&self.path));
+    }
+}
+
+/// Make a rename durable by syncing the folder holding it
+fn sync_parent(path: &Path) {
+    #[cfg(unix)]
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        if let Err(e) = File::open(parent).and_then(|dir| dir.sync_all()) {
+            debug!(path = %parent.display(), "Failed to sync folder: {}", e);
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = path;
+}
+
+/// Write `contents` to `path` through its partial file
+pub fn write_atomic(path: &Path, contents: impl AsRef<[u8]>) -> io::Result<()> {
+    let mut file = AtomicFile::create(path)?;
+    file.write_all(contents.as_ref())?;
+    file.commit()
+}
+
+/// Partial files removed by [`clean_stale_partials`]
+#[derive(Serialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct PartialCleanup {
+    /// Removed partial files and progress markers
+    pub removed: Vec<String>,
+    pub bytes_freed: u64,
+    /// Stale partial files that could not be removed
+    pub failed: Vec<String>,
+}
+
+/// Remove partial files and progress markers under `dir` that were last
+/// written more than `max_age` ago
+///
+/// Younger ones may belong to a run still in progress and are kept. Links
+/// to other folders are not followed.
+pub fn clean_stale_partials(dir: &Path, max_age: Duration) -> PartialCleanup {
+    let mut cleanup = PartialCleanup::default();
+    let now = SystemTime::now();
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(folder) = pending.pop() {
+        let Ok(entries) = fs::read_dir(&folder) else { continue };
+        for entry in entries.flatten() {
+            let Ok(file_type) = entry.file_type() else { continue };
+            if file_type.is_dir() {
+                pending.push(entry.path());
+                continue;
+            }
+            let name = entry.file_name().to_string_lossy().to_string();
+            if !file_type.is_file() || !(name.ends_with(PARTIAL_SUFFIX) || name.ends_with(PROGRESS_SUFFIX)) {
+                continue;
+            }
+            let Ok(metadata) = entry.metadata() else { continue };
+            let age = metadata.modified().ok().and_then(|modified| now.duration_since(modified).ok());
+            if age.is_none_or(|age| age < max_age) {
+                continue;
+            }
+            let path = entry.path().to_string_lossy().to_string();
+            match fs::remove_file(entry.path()) {
+                Ok(()) => {
+                    cleanup.bytes_freed += metadata.len();
+                    cleanup.removed.push(path);
+                }
+                Err(e) => {
+                    warn!(path, "Failed to remove stale partial output: {}", e);
+                    cleanup.failed.push(path);
+                }
+            }
+        }
+    }
+    cleanup.removed.sort();
+    if !cleanup.removed.is_empty() {
+        info!(dir = %dir.display(), files = cleanup.removed.len(), bytes = cleanup.bytes_freed, "Removed stale partial outputs");
+    }
+    cleanup
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_failure_before_rename_leaves_no_final_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let report = dir.path().join("report.pdf");
+
+        // An error between write and rename drops the file uncommitted
+        let mut file = AtomicFile::create(&report).unwrap();
+        file.write_all(b"%PDF-1.7 half a report").unwrap();
+        file.flush().unwrap();
+        assert!(partial_path(&report).exists() && !report.exists());
+        drop(file);
+        assert!(!report.exists() && !partial_path(&report).exists());
+        assert_eq!(output_state(&report), OutputState::Missing);
+
+        // A crash skips the drop as well: only the partial name exists
+        let mut file = AtomicFile::create(&report).unwrap();
+        file.write_all(b"%PDF-1.7").unwrap();
+        file.flush().unwrap();
+        std::mem::forget(file);
+        assert!(!report.exists());
+        assert_eq!(output_state(&report), OutputState::Interrupted { bytes_written: 8, expected_bytes: None });
+
+        write_atomic(&report, b"%PDF-1.7 complete").unwrap();
+        assert_eq!(fs::read(&report).unwrap(), b"%PDF-1.7 complete");
+        assert_eq!(output_state(&report), OutputState::Complete);
+        assert!(!partial_path(&report).exists());
+
+        // Never a drive, not even through its partial name
+        let error = write_atomic(Path::new("/dev/sdb"), b"x").unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn test_progress_marker_and_resume() {
+        let dir = tempfile::tempdir().unwrap();
+        let image = dir.path().join("disk.raw");
+
+        let mut file = AtomicFile::create(&image).unwrap()
+            .with_progress_markers(Some(10))
+            .unwrap()
+            .keep_partial();
+        file.write_all(b"01234").unwrap();
+        file.record_progress().unwrap();
+        // Written after the last marker, so not vouched for
+        file.write_all(b"56").unwrap();
+        file.flush().unwrap();
+        drop(file);
+        assert_eq!(output_state(&image), OutputState::Interrupted { bytes_written: 5, expected_bytes: Some(10) });
+
+        let mut file = AtomicFile::resume(&image, 5).unwrap().with_progress_markers(Some(10)).unwrap();
+        assert_eq!(file.written(), 5);
+        file.write_all(b"56789").unwrap();
+        file.commit().unwrap();
+        assert_eq!(fs::read(&image).unwrap(), b"0123456789");
+        assert!(!progress_path(&image).exists());
+    }
+
+    #[test]
+    fn test_clean_stale_partials_reports_what_it_removed() {
+        let dir = tempfile::tempdir().unwrap();
+        let nested = dir.path().join("Users/bob");
+        fs::create_dir_all(&nested).unwrap();
+        fs::write(nested.join("a.txt.partial"), b"half").unwrap();
+        fs::write(dir.path().join("disk.raw.partial"), b"image").unwrap();
+        fs::write(dir.path().join("disk.raw.partial.progress"), b"{}").unwrap();
+        fs::write(dir.path().join("notes.txt"), b"kept").unwrap();
+
+        // Everything is fresh, so a run could still be writing it
+        let kept = clean_stale_partials(dir.path(), STALE_PARTIAL_AGE);
+        assert!(kept.removed.is_empty());
+
+        let cleanup = clean_stale_partials(dir.path(), Duration::ZERO);
+        assert_eq!(cleanup.removed.len(), 3, "{:?}", cleanup.removed);
+        assert_eq!(cleanup.bytes_freed, 11);
+        assert!(cleanup.failed.is_empty());
+        assert!(dir.path().join("notes.txt").exists() && !nested.join("a.txt.partial").exists());
+    }
+}
@@ -2,6 +2,7 @@
 //
 // Provides consistent little-endian binary reading across all formats
 
+use std::fmt;
 use std::fs::File;
 use std::io::{Read, Seek, SeekFrom};
 use tracing::trace;
@@ -11,7 +12,7 @@ use tracing::trace;
 // =============================================================================
 
 /// Read a single byte from file at current position
-pub fn read_u8(file: &mut File) -> Result<u8, String> {
+pub fn read_u8<R: Read + ?Sized>(file: &mut R) -> Result<u8, String> {
     let mut buf = [0u8; 1];
     file.read_exact(&mut buf)
         .map_err(|e| format!("Failed to read u8: {}", e))?;
@@ -19,7 +20,7 @@ pub fn read_u8(file: &mut File) -> Result<u8, String> {
 }
 
 /// Read u16 little-endian from file at current position
-pub fn read_u16_le(file: &mut File) -> Result<u16, String> {
+pub fn read_u16_le<R: Read + ?Sized>(file: &mut R) -> Result<u16, String> {
     let mut buf = [0u8; 2];
     file.read_exact(&mut buf)
         .map_err(|e| format!("Failed to read u16: {}", e))?;
@@ -27,7 +28,7 @@ pub fn read_u16_le(file: &mut File) -> Result<u16, String> {
 }
 
 /// Read u32 little-endian from file at current position
-pub fn read_u32_le(file: &mut File) -> Result<u32, String> {
+pub fn read_u32_le<R: Read + ?Sized>(file: &mut R) -> Result<u32, String> {
     let mut buf = [0u8; 4];
     file.read_exact(&mut buf)
         .map_err(|e| format!("Failed to read u32: {}", e))?;
@@ -35,7 +36,7 @@ pub fn read_u32_le(file: &mut File) -> Result<u32, String> {
 }
 
 /// Read u64 little-endian from file at current position
-pub fn read_u64_le(file: &mut File) -> Result<u64, String> {
+pub fn read_u64_le<R: Read + ?Sized>(file: &mut R) -> Result<u64, String> {
     let mut buf = [0u8; 8];
     file.read_exact(&mut buf)
         .map_err(|e| format!("Failed to read u64: {}", e))?;
@@ -44,7 +45,7 @@ pub fn read_u64_le(file: &mut File) -> Result<u64, String> {
 
 /// Read u32 big-endian from file at current position
 #[allow(dead_code)]
-pub fn read_u32_be(file: &mut File) -> Result<u32, String> {
+pub fn read_u32_be<R: Read + ?Sized>(file: &mut R) -> Result<u32, String> {
     let mut buf = [0u8; 4];
     file.read_exact(&mut buf)
         .map_err(|e| format!("Failed to read u32 BE: {}", e))?;
@@ -53,7 +54,7 @@ pub fn read_u32_be(file: &mut File) -> Result<u32, String> {
 
 /// Read u64 big-endian from file at current position
 #[allow(dead_code)]
-pub fn read_u64_be(file: &mut File) -> Result<u64, String> {
+pub fn read_u64_be<R: Read + ?Sized>(file: &mut R) -> Result<u64, String> {
     let mut buf = [0u8; 8];
     file.read_exact(&mut buf)
         .map_err(|e| format!("Failed to read u64 BE: {}", e))?;
@@ -155,6 +156,150 @@ pub fn read_bytes(file: &mut File, length: usize) -> Result<Vec<u8>, String> {
     Ok(buf)
 }
 
+/// Read up to `length` bytes at `offset` - fewer if the file ends first
+///
+/// For structures parsed with [`ByteReader`]: a short read then surfaces as a
+/// [`Truncated`] error naming the field, not as an I/O error.
+pub fn read_up_to<R: Read + Seek + ?Sized>(file: &mut R, offset: u64, length: usize) -> Result<Vec<u8>, String> {
+    file.seek(SeekFrom::Start(offset))
+        .map_err(|e| format!("Failed to seek to offset {}: {}", offset, e))?;
+    let mut buf = Vec::new();
+    file.take(length as u64).read_to_end(&mut buf)
+        .map_err(|e| format!("Failed to read {} bytes at offset {}: {}", length, offset, e))?;
+    Ok(buf)
+}
+
+// =============================================================================
+// Bounds-checked Slice Reading
+// =============================================================================
+
+/// A structure ended before one of its fields
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Truncated {
+    /// File offset of the field that did not fit
+    pub offset: u64,
+    /// Bytes the field needs
+    pub needed: usize,
+    /// Bytes left in the structure
+    pub available: usize,
+}
+
+impl fmt::Display for Truncated {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "truncated structure at offset {}: {} bytes needed, {} left",
+            self.offset, self.needed, self.available
+        )
+    }
+}
+
+impl std::error::Error for Truncated {}
+
+impl From<Truncated> for String {
+    fn from(error: Truncated) -> String {
+        error.to_string()
+    }
+}
+
+/// Cursor over an in-memory structure with bounds-checked reads
+///
+/// Every read checks the remaining length and fails with [`Truncated`]
+/// instead of panicking on a short slice. `base` is the file offset the
+/// slice was read from, so errors point into the file.
+#[derive(Debug, Clone)]
+pub struct ByteReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+    base: u64,
+}
+
+impl<'a> ByteReader<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Self::at(data, 0)
+    }
+
+    /// Reader over `data` that was read from file offset `base`
+    pub fn at(data: &'a [u8], base: u64) -> Self {
+        Self { data, pos: 0, base }
+    }
+
+    /// Position within the slice
+    pub fn position(&self) -> usize {
+        self.pos
+    }
+
+    /// File offset of the next read
+    pub fn offset(&self) -> u64 {
+        self.base + self.pos as u64
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.data.len() - self.pos
+    }
+
+    /// Move to `pos` within the slice (the end of the slice is allowed)
+    pub fn seek(&mut self, pos: usize) -> Result<(), Truncated> {
+        if pos > self.data.len() {
+            return Err(Truncated {
+                offset: self.base + pos as u64,
+                needed: pos - self.data.len(),
+                available: 0,
+            });
+        }
+        self.pos = pos;
+        Ok(())
+    }
+
+    pub fn skip(&mut self, count: usize) -> Result<(), Truncated> {
+        self.bytes(count).map(|_| ())
+    }
+
+    /// The next `count` bytes
+    pub fn bytes(&mut self, count: usize) -> Result<&'a [u8], Truncated> {
+        if count > self.remaining() {
+            return Err(Truncated { offset: self.offset(), needed: count, available: self.remaining() });
+        }
+        let bytes = &self.data[self.pos..self.pos + count];
+        self.pos += count;
+        Ok(bytes)
+    }
+
+    pub fn array<const N: usize>(&mut self) -> Result<[u8; N], Truncated> {
+        let mut array = [0u8; N];
+        array.copy_from_slice(self.bytes(N)?);
+        Ok(array)
+    }
+
+    pub fn u8(&mut self) -> Result<u8, Truncated> {
+        Ok(self.array::<1>()?[0])
+    }
+
+    pub fn u16_le(&mut self) -> Result<u16, Truncated> {
+        self.array().map(u16::from_le_bytes)
+    }
+
+    pub fn u32_le(&mut self) -> Result<u32, Truncated> {
+        self.array().map(u32::from_le_bytes)
+    }
+
+    pub fn u64_le(&mut self) -> Result<u64, Truncated> {
+        self.array().map(u64::from_le_bytes)
+    }
+
+    pub fn u16_be(&mut self) -> Result<u16, Truncated> {
+        self.array().map(u16::from_be_bytes)
+    }
+
+    pub fn u32_be(&mut self) -> Result<u32, Truncated> {
+        self.array().map(u32::from_be_bytes)
+    }
+
+    pub fn u64_be(&mut self) -> Result<u64, Truncated> {
+        self.array().map(u64::from_be_bytes)
+    }
+}
+
 // =============================================================================
 // Utility Functions
 // =============================================================================
@@ -224,4 +369,32 @@ mod tests {
         assert_eq!(bytes_to_string(b"hello\x00world"), "hello");
         assert_eq!(bytes_to_string(b"no null"), "no null");
     }
+
+    #[test]
+    fn test_byte_reader_reads_both_endians_and_stops_at_the_end() {
+        let data = [0x34, 0x12, 0x12, 0x34, 0x78, 0x56, 0x34, 0x12, 0xAA];
+        let mut reader = ByteReader::at(&data, 0x100);
+        assert_eq!(reader.u16_le().unwrap(), 0x1234);
+        assert_eq!(reader.u16_be().unwrap(), 0x1234);
+        assert_eq!(reader.u32_le().unwrap(), 0x12345678);
+
+        // A failed read leaves the cursor where it was
+        let error = reader.u32_le().unwrap_err();
+        assert_eq!(error, Truncated { offset: 0x108, needed: 4, available: 1 });
+        assert_eq!(String::from(error), "truncated structure at offset 264: 4 bytes needed, 1 left");
+        assert_eq!(reader.u8().unwrap(), 0xAA);
+        assert_eq!(reader.remaining(), 0);
+
+        reader.seek(2).unwrap();
+        assert_eq!(reader.array::<2>().unwrap(), [0x12, 0x34]);
+        assert_eq!(reader.seek(10).unwrap_err().offset, 0x10A);
+        assert!(reader.u64_be().is_err());
+    }
+
+    #[test]
+    fn test_read_up_to_stops_at_the_end_of_the_file() {
+        let mut data = std::io::Cursor::new(vec![1u8, 2, 3, 4]);
+        assert_eq!(read_up_to(&mut data, 1, 2).unwrap(), [2, 3]);
+        assert_eq!(read_up_to(&mut data, 2, 16).unwrap(), [3, 4]);
+    }
 }
@@ -0,0 +1,42 @@
+// Capability declarations for container formats
+//
+// Each format module declares what it can do with a `capabilities()` const fn
+// sitting next to the code that does it; the containers layer composes
+// them for `get_supported_formats` so the UI never has to guess.
+
+use serde::Serialize;
+
+/// Operations a container format supports in this build
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct FormatCapabilities {
+    /// Container metadata (`logical_info`)
+    pub can_info: bool,
+    /// Item tree listing
+    pub can_info_tree: bool,
+    /// One hash over the whole image or data stream
+    pub can_verify_whole: bool,
+    /// Per-item hashes checked against stored ones
+    pub can_verify_items: bool,
+    /// Each segment file hashed on its own
+    pub can_verify_segments: bool,
+    /// Export the image or its contents (`logical_extract`)
+    pub can_extract: bool,
+    /// Export selected items only
+    pub can_extract_item: bool,
+    /// Known limitations, shown next to disabled actions
+    pub notes: &'static [&'static str],
+}
+
+impl FormatCapabilities {
+    /// Metadata only - the starting point for formats still being built out
+    pub const INFO_ONLY: Self = Self {
+        can_info: true,
+        can_info_tree: false,
+        can_verify_whole: false,
+        can_verify_items: false,
+        can_verify_segments: false,
+        can_extract: false,
+        can_extract_item: false,
+        notes: &[],
+    };
+}
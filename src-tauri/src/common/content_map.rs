@@ -0,0 +1,207 @@
+//! Zero and entropy map of an image, collected while it is verified
+//!
+//! "Was this drive wiped?" is answered by how much of it holds zeros or
+//! random-looking data. Verification already reads every byte, so a byte
+//! histogram per fixed window of logical data costs one counter increment
+//! per byte and no allocation; each window is reduced to its zero fraction
+//! and Shannon entropy when it fills. The UI draws the windows as a heat
+//! strip, and [`summarize_map`] gives the numbers a report states.
+
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use super::atomic_file::write_atomic;
+use super::integrity;
+
+/// Logical bytes per map window
+pub const DEFAULT_MAP_WINDOW: u64 = 64 * 1024 * 1024;
+/// Entropy (bits per byte) from which a window counts as high-entropy:
+/// encrypted, random or wiped with random data
+pub const HIGH_ENTROPY_BITS: f32 = 7.5;
+
+/// One window of the map
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct MapWindow {
+    /// Fraction of the window's bytes that are zero (0.0 - 1.0)
+    pub zero_fraction: f32,
+    /// Shannon entropy of the window (0.0 - 8.0 bits per byte)
+    pub entropy: f32,
+}
+
+/// Per-window statistics of an image's logical data
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ContentMap {
+    pub window_size: u64,
+    pub total_bytes: u64,
+    /// Windows in image order; only the last may be shorter than `window_size`
+    pub windows: Vec<MapWindow>,
+}
+
+impl ContentMap {
+    /// Bytes covered by window `index`
+    pub fn window_len(&self, index: usize) -> u64 {
+        let start = index as u64 * self.window_size;
+        self.total_bytes.saturating_sub(start).min(self.window_size)
+    }
+
+    /// Write the map as JSON
+    pub fn export_json(&self, path: &Path) -> Result<(), String> {
+        let json = serde_json::to_string(self)
+            .map_err(|e| format!("Failed to serialize content map: {e}"))?;
+        let json = integrity::tag_json_if_enabled(json)?;
+        write_atomic(path, json)
+            .map_err(|e| format!("Failed to write content map {}: {e}", path.display()))
+    }
+}
+
+/// Builds a [`ContentMap`] from data fed in image order
+pub struct ContentMapBuilder {
+    counts: [u64; 256],
+    /// Bytes counted in the open window
+    filled: u64,
+    map: ContentMap,
+}
+
+impl ContentMapBuilder {
+    pub fn new(window_size: u64) -> Self {
+        Self {
+            counts: [0; 256],
+            filled: 0,
+            map: ContentMap { window_size: window_size.max(1), total_bytes: 0, windows: Vec::new() },
+        }
+    }
+
+    /// Count the next bytes of the image
+    pub fn update(&mut self, mut data: &[u8]) {
+        while !data.is_empty() {
+            let room = (self.map.window_size - self.filled).min(data.len() as u64) as usize;
+            for &byte in &data[..room] {
+                self.counts[byte as usize] += 1;
+            }
+            self.filled += room as u64;
+            self.map.total_bytes += room as u64;
+            data = &data[room..];
+            if self.filled == self.map.window_size {
+                self.close_window();
+            }
+        }
+    }
+
+    /// The map, with the last partial window closed
+    pub fn finish(mut self) -> ContentMap {
+        if self.filled > 0 {
+            self.close_window();
+        }
+        self.map
+    }
+
+    fn close_window(&mut self) {
+        let len = self.filled as f64;
+        let entropy = self.counts.iter()
+            .filter(|&&count| count > 0)
+            .map(|&count| {
+                let probability = count as f64 / len;
+                -probability * probability.log2()
+            })
+            .sum::<f64>();
+        self.map.windows.push(MapWindow {
+            zero_fraction: (self.counts[0] as f64 / len) as f32,
+            entropy: entropy as f32,
+        });
+        self.counts = [0; 256];
+        self.filled = 0;
+    }
+}
+
+/// What a report says about a map
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct MapSummary {
+    pub total_bytes: u64,
+    /// Percentage of all bytes that are zero
+    pub percent_zero: f64,
+    /// Percentage of the bytes in windows at or above the entropy threshold
+    pub percent_high_entropy: f64,
+    /// Windows holding nothing but zeros
+    pub zero_windows: usize,
+    pub window_count: usize,
+}
+
+impl MapSummary {
+    /// One line for a report, e.g. "99.7% of the image contains zeros, 0.1% high-entropy data"
+    pub fn describe(&self) -> String {
+        format!(
+            "{:.1}% of the image contains zeros, {:.1}% high-entropy data",
+            self.percent_zero, self.percent_high_entropy
+        )
+    }
+}
+
+/// Summarize a map, counting windows with an entropy of at least
+/// `high_entropy_bits` (see [`HIGH_ENTROPY_BITS`]) as high-entropy
+pub fn summarize_map(map: &ContentMap, high_entropy_bits: f32) -> MapSummary {
+    let (mut zero_bytes, mut high_entropy_bytes, mut zero_windows) = (0f64, 0u64, 0);
+    for (index, window) in map.windows.iter().enumerate() {
+        let len = map.window_len(index);
+        zero_bytes += window.zero_fraction as f64 * len as f64;
+        if window.entropy >= high_entropy_bits {
+            high_entropy_bytes += len;
+        }
+        if window.zero_fraction >= 1.0 {
+            zero_windows += 1;
+        }
+    }
+    let percent = |bytes: f64| if map.total_bytes == 0 { 0.0 } else { bytes * 100.0 / map.total_bytes as f64 };
+    MapSummary {
+        total_bytes: map.total_bytes,
+        percent_zero: percent(zero_bytes),
+        percent_high_entropy: percent(high_entropy_bytes as f64),
+        zero_windows,
+        window_count: map.windows.len(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_windows_split_across_updates() {
+        let mut builder = ContentMapBuilder::new(256);
+        // 300 zeros, then every byte value twice: the third window holds
+        // each value once
+        builder.update(&[0u8; 200]);
+        builder.update(&[0u8; 100]);
+        let uniform: Vec<u8> = (0..=255u8).chain(0..=255u8).collect();
+        builder.update(&uniform);
+        let map = builder.finish();
+
+        assert_eq!(map.total_bytes, 812);
+        assert_eq!(map.windows.len(), 4);
+        assert_eq!(map.windows[0], MapWindow { zero_fraction: 1.0, entropy: 0.0 });
+        // 44 zeros, then bytes 0..=211
+        assert_eq!(map.windows[1].zero_fraction, 45.0 / 256.0);
+        assert!((map.windows[2].entropy - 8.0).abs() < 1e-6, "{:?}", map.windows[2]);
+        assert_eq!(map.window_len(3), 812 - 768);
+    }
+
+    #[test]
+    fn test_summary_of_wiped_image() {
+        let mut builder = ContentMapBuilder::new(1000);
+        builder.update(&vec![0u8; 9970]);
+        builder.update(&(1..=30).collect::<Vec<u8>>());
+        let map = builder.finish();
+
+        let summary = summarize_map(&map, HIGH_ENTROPY_BITS);
+        assert_eq!(summary.window_count, 10);
+        assert_eq!(summary.zero_windows, 9);
+        assert!((summary.percent_zero - 99.7).abs() < 0.01, "{summary:?}");
+        assert_eq!(summary.percent_high_entropy, 0.0);
+        assert_eq!(summary.describe(), "99.7% of the image contains zeros, 0.0% high-entropy data");
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("map.json");
+        map.export_json(&path).unwrap();
+        let read: ContentMap = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(read, map);
+    }
+}
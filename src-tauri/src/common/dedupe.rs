@@ -0,0 +1,348 @@
+// Deduplicated extraction - each distinct file content is written once
+//
+// Containers from the same custodian mostly hold the same files. With
+// deduplication on, every file is still streamed through the manifest's
+// hashing writer into its partial file; once the SHA-256 is known the index
+// of outputs already written is consulted, and a duplicate is dropped,
+// hard-linked or cloned from the earlier output instead of being moved into
+// place. The manifest row records what was done and which output holds the
+// content, and the index is saved with the manifest so the next extraction
+// into the same folder starts from it.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tracing::debug;
+
+use super::atomic_file::{partial_path, AtomicFile};
+use super::extract_manifest::{ExtractedFile, MANIFEST_NAME};
+
+/// Prefix of the index lines in a CSV manifest
+const CSV_INDEX_PREFIX: &str = "# dedupe_index: ";
+
+/// What is done with a file whose content is already in the output
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DedupeStrategy {
+    /// Write nothing; the manifest points at the output holding the content
+    #[default]
+    Skip,
+    /// Hard link to the output holding the content
+    Hardlink,
+    /// Copy-on-write clone of it (Btrfs, XFS, APFS)
+    Reflink,
+}
+
+/// What was done with a duplicate
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DedupeAction {
+    Skipped,
+    Hardlinked,
+    Reflinked,
+    /// The link could not be made, so the file was written after all
+    Copied,
+}
+
+impl DedupeAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DedupeAction::Skipped => "skipped",
+            DedupeAction::Hardlinked => "hardlinked",
+            DedupeAction::Reflinked => "reflinked",
+            DedupeAction::Copied => "copied",
+        }
+    }
+}
+
+/// Manifest record of a file whose content an earlier output already held
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Duplicate {
+    /// Output holding the same content
+    pub original_path: String,
+    pub action: DedupeAction,
+    /// Why the link failed, for `Copied`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub link_error: Option<String>,
+}
+
+impl Duplicate {
+    /// Whether the output path has an inode of its own (not skipped or
+    /// hard-linked), so the item's timestamps can be applied to it
+    pub fn has_own_file(&self) -> bool {
+        matches!(self.action, DedupeAction::Reflinked | DedupeAction::Copied)
+    }
+
+    /// Bytes not written for this file
+    pub fn saved_bytes(&self, size: u64) -> u64 {
+        if self.action == DedupeAction::Copied { 0 } else { size }
+    }
+}
+
+/// An output in the index, as saved in the manifest
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct IndexedFile {
+    pub sha256: String,
+    pub size: u64,
+    pub output_path: String,
+}
+
+/// Outputs by content, shared by the workers of one extraction
+pub struct DedupeIndex {
+    strategy: DedupeStrategy,
+    files: Mutex<HashMap<(String, u64), String>>,
+}
+
+impl DedupeIndex {
+    pub fn new(strategy: DedupeStrategy) -> Self {
+        Self { strategy, files: Mutex::new(HashMap::new()) }
+    }
+
+    /// Index of what earlier extractions left in `output_dir`, from their
+    /// manifests: the saved index plus the files written. Outputs that are
+    /// gone or changed size since are left out.
+    pub fn load(output_dir: &Path, strategy: DedupeStrategy) -> Self {
+        let index = Self::new(strategy);
+        let mut entries = Vec::new();
+        if let Ok(json) = fs::read_to_string(output_dir.join(format!("{MANIFEST_NAME}.json"))) {
+            entries.extend(json_entries(&json));
+        }
+        if let Ok(csv) = fs::read_to_string(output_dir.join(format!("{MANIFEST_NAME}.csv"))) {
+            entries.extend(csv.lines().filter_map(csv_entry));
+        }
+        if let Ok(mut files) = index.files.lock() {
+            for entry in entries {
+                let present = fs::metadata(&entry.output_path)
+                    .is_ok_and(|metadata| metadata.is_file() && metadata.len() == entry.size);
+                if present && entry.size > 0 && !entry.sha256.is_empty() {
+                    files.entry((entry.sha256, entry.size)).or_insert(entry.output_path);
+                }
+            }
+            debug!(dir = %output_dir.display(), files = files.len(), "Loaded deduplication index");
+        }
+        index
+    }
+
+    /// Every indexed output, by path, for the manifest
+    pub fn entries(&self) -> Vec<IndexedFile> {
+        let Ok(files) = self.files.lock() else { return Vec::new() };
+        let mut entries: Vec<IndexedFile> = files.iter()
+            .map(|((sha256, size), output_path)| IndexedFile { sha256: sha256.clone(), size: *size, output_path: output_path.clone() })
+            .collect();
+        entries.sort_by(|a, b| a.output_path.cmp(&b.output_path));
+        entries
+    }
+
+    /// Move `file`, described by `written`, into place unless an indexed
+    /// output has the same content; then apply the strategy instead
+    ///
+    /// A link that fails falls back to committing the written copy, so the
+    /// file is never lost. Empty files are always written.
+    pub fn place(&self, file: AtomicFile, written: &ExtractedFile) -> Result<Option<Duplicate>, String> {
+        let key = (written.sha256.clone(), written.size);
+        let original = self.files.lock()
+            .map_err(|_| "Deduplication index poisoned".to_string())?
+            .get(&key)
+            .filter(|original| Path::new(original.as_str()) != file.path())
+            .cloned();
+        let Some(original_path) = original.filter(|_| written.size > 0) else {
+            commit(file)?;
+            if written.size > 0 {
+                // Two workers writing the same new content both keep theirs;
+                // the first to finish is the one indexed
+                self.files.lock()
+                    .map_err(|_| "Deduplication index poisoned".to_string())?
+                    .entry(key)
+                    .or_insert_with(|| written.output_path.clone());
+            }
+            return Ok(None);
+        };
+
+        let mut duplicate = Duplicate { original_path, action: DedupeAction::Skipped, link_error: None };
+        if self.strategy == DedupeStrategy::Skip {
+            drop(file);
+        } else {
+            let target = file.path().to_path_buf();
+            let staging = staging_path(&target);
+            let _ = fs::remove_file(&staging);
+            match link(self.strategy, Path::new(&duplicate.original_path), &staging) {
+                Ok(action) => {
+                    drop(file);
+                    fs::rename(&staging, &target)
+                        .map_err(|e| format!("Failed to write {:?}: {e}", target))?;
+                    duplicate.action = action;
+                }
+                Err(e) => {
+                    debug!(path = %target.display(), error = %e, "Link failed, writing the duplicate");
+                    let _ = fs::remove_file(&staging);
+                    commit(file)?;
+                    duplicate.action = DedupeAction::Copied;
+                    duplicate.link_error = Some(e.to_string());
+                }
+            }
+        }
+        debug!(path = %written.output_path, original = %duplicate.original_path, action = duplicate.action.as_str(), "Duplicate output");
+        Ok(Some(duplicate))
+    }
+}
+
+fn commit(file: AtomicFile) -> Result<(), String> {
+    let path = file.path().to_path_buf();
+    file.commit().map_err(|e| format!("Failed to write {:?}: {e}", path))
+}
+
+/// Where a link is made before being renamed over `target`; a partial name,
+/// so a crash leaves nothing `clean_stale_partials` misses
+fn staging_path(target: &Path) -> PathBuf {
+    let mut name = target.file_name().unwrap_or_default().to_os_string();
+    name.push(".dedupe");
+    partial_path(&target.with_file_name(name))
+}
+
+fn link(strategy: DedupeStrategy, original: &Path, staging: &Path) -> io::Result<DedupeAction> {
+    match strategy {
+        DedupeStrategy::Skip => Ok(DedupeAction::Skipped),
+        DedupeStrategy::Hardlink => fs::hard_link(original, staging).map(|_| DedupeAction::Hardlinked),
+        DedupeStrategy::Reflink => os::clone_file(original, staging).map(|_| DedupeAction::Reflinked),
+    }
+}
+
+/// Index entries of a JSON manifest: its saved index and its written files
+fn json_entries(json: &str) -> Vec<IndexedFile> {
+    let Ok(manifest) = serde_json::from_str::<serde_json::Value>(json) else { return Vec::new() };
+    let saved = manifest.get("dedupe_index").and_then(|index| index.as_array()).into_iter().flatten();
+    let written = manifest.get("files").and_then(|files| files.as_array()).into_iter().flatten()
+        .filter(|file| file.get("duplicate").is_none_or(|duplicate| duplicate.is_null()));
+    saved.chain(written)
+        .filter_map(|entry| serde_json::from_value(entry.clone()).ok())
+        .collect()
+}
+
+/// An index line of a CSV manifest: `# dedupe_index: <sha256> <size> <path>`
+fn csv_entry(line: &str) -> Option<IndexedFile> {
+    let mut fields = line.strip_prefix(CSV_INDEX_PREFIX)?.splitn(3, ' ');
+    Some(IndexedFile {
+        sha256: fields.next()?.to_string(),
+        size: fields.next()?.parse().ok()?,
+        output_path: fields.next()?.to_string(),
+    })
+}
+
+/// The index as comment lines of a CSV manifest
+pub fn csv_index_lines(entries: &[IndexedFile]) -> String {
+    entries.iter()
+        .map(|entry| format!("{CSV_INDEX_PREFIX}{} {} {}\n", entry.sha256, entry.size, entry.output_path))
+        .collect()
+}
+
+#[cfg(target_os = "linux")]
+mod os {
+    use std::fs::{File, OpenOptions};
+    use std::io;
+    use std::os::fd::AsRawFd;
+    use std::path::Path;
+
+    /// FICLONE the whole of `original` into a new file `target`
+    pub fn clone_file(original: &Path, target: &Path) -> io::Result<()> {
+        let source = File::open(original)?;
+        let clone = OpenOptions::new().write(true).create_new(true).open(target)?;
+        // SAFETY: FICLONE only reads the two descriptors, open for the call
+        if unsafe { libc::ioctl(clone.as_raw_fd(), libc::FICLONE, source.as_raw_fd()) } == 0 {
+            return Ok(());
+        }
+        let error = io::Error::last_os_error();
+        drop(clone);
+        let _ = std::fs::remove_file(target);
+        Err(error)
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod os {
+    use std::ffi::CString;
+    use std::io;
+    use std::os::unix::ffi::OsStrExt;
+    use std::path::Path;
+
+    /// clonefile(2), which creates `target`
+    pub fn clone_file(original: &Path, target: &Path) -> io::Result<()> {
+        let original = CString::new(original.as_os_str().as_bytes())?;
+        let target = CString::new(target.as_os_str().as_bytes())?;
+        // SAFETY: both paths are NUL-terminated and outlive the call
+        if unsafe { libc::clonefile(original.as_ptr(), target.as_ptr(), 0) } == 0 {
+            Ok(())
+        } else {
+            Err(io::Error::last_os_error())
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+mod os {
+    use std::io;
+    use std::path::Path;
+
+    pub fn clone_file(_original: &Path, _target: &Path) -> io::Result<()> {
+        Err(io::Error::new(io::ErrorKind::Unsupported, "copy-on-write clones are not supported on this platform"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::extract_manifest::HashingWriter;
+    use std::io::Write;
+
+    fn extract(index: &DedupeIndex, path: &Path, data: &[u8]) -> ExtractedFile {
+        let mut writer = HashingWriter::new(AtomicFile::create(path).unwrap());
+        writer.write_all(data).unwrap();
+        writer.commit_deduped("item", index).unwrap()
+    }
+
+    #[test]
+    fn test_links_duplicates_and_falls_back_to_copying() {
+        let dir = tempfile::tempdir().unwrap();
+        let index = DedupeIndex::new(DedupeStrategy::Hardlink);
+        let first = extract(&index, &dir.path().join("a.txt"), b"same content");
+        assert_eq!(first.duplicate, None);
+
+        let second = extract(&index, &dir.path().join("b.txt"), b"same content");
+        let duplicate = second.duplicate.unwrap();
+        assert_eq!((duplicate.action, duplicate.original_path.as_str()), (DedupeAction::Hardlinked, first.output_path.as_str()));
+        assert_eq!(fs::read(dir.path().join("b.txt")).unwrap(), b"same content");
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::MetadataExt;
+            let inode = |name: &str| fs::metadata(dir.path().join(name)).unwrap().ino();
+            assert_eq!(inode("a.txt"), inode("b.txt"));
+        }
+
+        // Whether or not the filesystem clones, the file ends up complete
+        let index = DedupeIndex::new(DedupeStrategy::Reflink);
+        extract(&index, &dir.path().join("c.txt"), b"cloned");
+        let clone = extract(&index, &dir.path().join("d.txt"), b"cloned").duplicate.unwrap();
+        assert!(matches!(clone.action, DedupeAction::Reflinked | DedupeAction::Copied));
+        assert_eq!(clone.link_error.is_some(), clone.action == DedupeAction::Copied);
+        assert_eq!(fs::read(dir.path().join("d.txt")).unwrap(), b"cloned");
+        assert!(!staging_path(&dir.path().join("d.txt")).exists());
+    }
+
+    #[test]
+    fn test_index_is_reloaded_from_manifests() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("kept.bin"), b"12345").unwrap();
+        fs::write(dir.path().join("changed.bin"), b"1").unwrap();
+        let output = |name: &str| dir.path().join(name).to_string_lossy().to_string();
+        let entry = |sha256: &str, size: u64, name: &str| IndexedFile { sha256: sha256.to_string(), size, output_path: output(name) };
+        fs::write(
+            dir.path().join(format!("{MANIFEST_NAME}.csv")),
+            csv_index_lines(&[entry("aa", 5, "kept.bin"), entry("bb", 3, "changed.bin"), entry("cc", 3, "gone.bin")]),
+        ).unwrap();
+
+        let index = DedupeIndex::load(dir.path(), DedupeStrategy::Skip);
+        assert_eq!(index.entries(), vec![entry("aa", 5, "kept.bin")]);
+    }
+}
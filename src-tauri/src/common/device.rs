@@ -0,0 +1,158 @@
+// Detection of evidence devices that disappear mid-operation
+//
+// When a USB dock or card reader drops, every further read of the evidence
+// fails. Read paths classify such errors with `is_device_loss` and abort with
+// a single `device_disconnected` error instead of reporting one failure per
+// chunk. The message prefix lets the job registry tell a lost device from an
+// ordinary failure, so the job can be retried once the media is back.
+//
+// `is_block_device_path` recognises drives named directly (`/dev/sdb`,
+// `\\.\PhysicalDrive2`), which are hashed in place but never written to.
+
+use std::fmt::Display;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Prefix of every device-loss error message
+pub const DEVICE_DISCONNECTED_PREFIX: &str = "Device disconnected";
+
+/// Whether an I/O error means the device holding the file is gone
+///
+/// Unix: EIO, ENXIO and ENODEV (the errors a yanked USB mass-storage device
+/// produces). Windows: ERROR_NOT_READY, ERROR_DEV_NOT_EXIST and
+/// ERROR_DEVICE_NOT_CONNECTED.
+pub fn is_device_loss(err: &io::Error) -> bool {
+    #[cfg(unix)]
+    const DEVICE_LOSS_CODES: &[i32] = &[5, 6, 19];
+    #[cfg(windows)]
+    const DEVICE_LOSS_CODES: &[i32] = &[21, 55, 1167];
+    #[cfg(not(any(unix, windows)))]
+    const DEVICE_LOSS_CODES: &[i32] = &[];
+
+    err.raw_os_error().is_some_and(|code| DEVICE_LOSS_CODES.contains(&code))
+}
+
+/// The error reported when the device holding `path` went away
+pub fn device_disconnected(path: &Path, detail: impl Display) -> String {
+    format!("{}: {} ({})", DEVICE_DISCONNECTED_PREFIX, path.display(), detail)
+}
+
+/// Whether an error message was produced by [`device_disconnected`]
+pub fn is_device_disconnected(error: &str) -> bool {
+    error.starts_with(DEVICE_DISCONNECTED_PREFIX)
+}
+
+/// Describe an I/O error on `path`, as a device-loss error when it is one
+pub fn describe_io_error(path: &Path, context: &str, err: &io::Error) -> String {
+    if is_device_loss(err) {
+        device_disconnected(path, err)
+    } else {
+        format!("{}: {}", context, err)
+    }
+}
+
+/// Whether `path` names a drive or volume device rather than a file
+///
+/// Unix: anything under `/dev/`. Windows (either slash style): the device
+/// namespace forms `\\.\PhysicalDrive2`, `\\.\CdRom0`, `\\.\D:`,
+/// `\\.\Harddisk0Partition1` and `\\?\Volume{guid}` - but not a file
+/// reached through them, such as `\\?\C:\evidence\disk.dd`.
+pub fn is_block_device_path(path: &str) -> bool {
+    if path.starts_with("/dev/") {
+        return path.len() > "/dev/".len();
+    }
+    let path = path.replace('/', "\\");
+    if let Some(name) = path.strip_prefix(r"\\.\") {
+        let name = name.to_ascii_lowercase();
+        let drive_letter = name.len() == 2 && name.as_bytes()[0].is_ascii_alphabetic() && name.ends_with(':');
+        let partition = name.strip_prefix("harddisk")
+            .and_then(|rest| rest.split_once("partition"))
+            .is_some_and(|(disk, partition)| is_number(disk) && is_number(partition));
+        name.strip_prefix("physicaldrive").is_some_and(is_number)
+            || name.strip_prefix("cdrom").is_some_and(is_number)
+            || drive_letter || partition || is_volume_guid(&name)
+    } else if let Some(name) = path.strip_prefix(r"\\?\") {
+        let name = name.to_ascii_lowercase();
+        is_volume_guid(&name) || name.starts_with(r"globalroot\device\")
+    } else {
+        false
+    }
+}
+
+fn is_number(text: &str) -> bool {
+    !text.is_empty() && text.bytes().all(|b| b.is_ascii_digit())
+}
+
+/// `volume{guid}` with nothing after it
+fn is_volume_guid(name: &str) -> bool {
+    name.strip_prefix("volume{").is_some_and(|rest| rest.ends_with('}') && !rest.contains('\\'))
+}
+
+/// Mount point (Unix) or volume prefix (elsewhere) of an existing path
+pub fn mount_point(path: &Path) -> Result<PathBuf, String> {
+    let path = path.canonicalize()
+        .map_err(|e| format!("Failed to resolve {}: {}", path.display(), e))?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        let device_of = |p: &Path| std::fs::metadata(p).map(|m| m.dev()).ok();
+        let device = device_of(&path);
+        let mut mount = path.as_path();
+        while let Some(parent) = mount.parent() {
+            if device_of(parent) != device {
+                break;
+            }
+            mount = parent;
+        }
+        Ok(mount.to_path_buf())
+    }
+
+    #[cfg(not(unix))]
+    {
+        use std::path::Component;
+        match path.components().next() {
+            Some(Component::Prefix(prefix)) => Ok(PathBuf::from(prefix.as_os_str())),
+            _ => Ok(path),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_device_loss_classification() {
+        #[cfg(unix)]
+        {
+            assert!(is_device_loss(&io::Error::from_raw_os_error(5)));
+            assert!(is_device_loss(&io::Error::from_raw_os_error(19)));
+        }
+        assert!(!is_device_loss(&io::Error::new(io::ErrorKind::UnexpectedEof, "short read")));
+        assert!(!is_device_loss(&io::Error::from(io::ErrorKind::PermissionDenied)));
+
+        let error = device_disconnected(Path::new("/Volumes/USB/disk.E01"), "Input/output error");
+        assert!(is_device_disconnected(&error));
+        assert!(!is_device_disconnected("Read error: unexpected end of file"));
+    }
+
+    #[test]
+    fn test_block_device_path_forms() {
+        for device in [
+            "/dev/sdb", "/dev/nvme0n1", "/dev/rdisk3", "/dev/disk/by-id/usb-Tableau_T8u-0:0",
+            r"\\.\PhysicalDrive2", r"\\.\physicaldrive12", "//./PhysicalDrive0", r"\\.\CdRom0",
+            r"\\.\E:", r"\\.\Harddisk1Partition2", r"\\?\Volume{3f2504e0-4f89-11d3-9a0c-0305e82c3301}",
+            r"\\?\GLOBALROOT\Device\Harddisk1\Partition0",
+        ] {
+            assert!(is_block_device_path(device), "{device}");
+        }
+        for file in [
+            "/dev/", "/evidence/dev/sdb", "disk.dd", r"C:\evidence\disk.E01", r"\\?\C:\evidence\disk.dd",
+            r"\\.\PhysicalDrive", r"\\.\PhysicalDrive2x", r"\\.\E:\evidence.E01", r"\\server\share\disk.dd",
+            r"\\?\Volume{3f2504e0-4f89-11d3-9a0c-0305e82c3301}\disk.dd",
+        ] {
+            assert!(!is_block_device_path(file), "{file}");
+        }
+    }
+}
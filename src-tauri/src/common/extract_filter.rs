@@ -0,0 +1,245 @@
+// Extraction filters - select files by path glob, extension and size
+//
+// Globs follow gitignore conventions: a pattern without '/' ("*.plist")
+// matches the file or folder name at any depth, a pattern with '/'
+// ("Users/*/Library/Messages/**") matches the whole path from the container
+// root. `*` never crosses a path separator, `**` does. Matching is
+// case-insensitive on Windows; extensions are always compared ignoring case.
+//
+// A folder matched by an exclude glob (or by the prefix of an exclude glob
+// ending in "/**") is pruned: its subtree is never walked.
+
+use globset::{Glob, GlobBuilder, GlobSet, GlobSetBuilder};
+use serde::{Deserialize, Serialize};
+
+use super::extract_manifest::ExtractedFile;
+
+/// Which files to extract (all fields optional; the default extracts everything)
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ExtractFilter {
+    /// Extract only files matching at least one of these (empty = all)
+    pub include_globs: Vec<String>,
+    /// Skip files and prune folders matching any of these
+    pub exclude_globs: Vec<String>,
+    pub min_size: Option<u64>,
+    pub max_size: Option<u64>,
+    /// Extensions without the dot, e.g. ["plist", "db"] (empty = all)
+    pub extensions: Vec<String>,
+}
+
+impl ExtractFilter {
+    /// True if the filter selects everything
+    pub fn is_empty(&self) -> bool {
+        self.include_globs.is_empty()
+            && self.exclude_globs.is_empty()
+            && self.min_size.is_none()
+            && self.max_size.is_none()
+            && self.extensions.is_empty()
+    }
+
+    /// Compile the globs; fails on an invalid pattern
+    pub fn compile(&self) -> Result<ExtractMatcher, String> {
+        let mut folder_excludes = self.exclude_globs.clone();
+        folder_excludes.extend(
+            self.exclude_globs.iter()
+                .filter_map(|pattern| pattern.strip_suffix("/**"))
+                .filter(|prefix| !prefix.is_empty())
+                .map(str::to_string),
+        );
+
+        Ok(ExtractMatcher {
+            select_all: self.is_empty(),
+            include: PatternSet::new(&self.include_globs)?,
+            exclude: PatternSet::new(&self.exclude_globs)?,
+            folder_exclude: PatternSet::new(&folder_excludes)?,
+            min_size: self.min_size,
+            max_size: self.max_size,
+            extensions: self.extensions.iter()
+                .map(|ext| ext.trim_start_matches('.').to_lowercase())
+                .filter(|ext| !ext.is_empty())
+                .collect(),
+        })
+    }
+}
+
+/// Counts reported by a filtered extraction
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ExtractStats {
+    /// Files that passed the filter
+    pub matched: usize,
+    /// Files seen and rejected by the filter
+    pub skipped: usize,
+    /// Files written to the output directory
+    pub extracted: usize,
+    /// Folders excluded without walking their contents
+    pub pruned_folders: usize,
+    pub total_bytes: u64,
+    /// Files a best-effort extraction could not read and left out
+    pub unreadable: Vec<UnreadableFile>,
+    /// Files whose content was already in the output (deduplicated extraction)
+    pub deduplicated: usize,
+    /// Bytes those files did not take up again
+    pub deduplicated_bytes: u64,
+    /// Files written, for the extraction manifest
+    #[serde(skip)]
+    pub files: Vec<ExtractedFile>,
+}
+
+/// A matched file whose data could not be read, so nothing was written
+#[derive(Debug, Clone, Serialize)]
+pub struct UnreadableFile {
+    pub path: String,
+    /// "corrupt" or "missing", as in verification results
+    pub status: String,
+    /// Chunk within the file where reading failed, when known
+    pub chunk: Option<u64>,
+    pub message: String,
+}
+
+/// Extraction progress in files and bytes
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExtractProgress {
+    pub files_done: usize,
+    pub files_total: usize,
+    pub bytes_done: u64,
+    pub bytes_total: u64,
+}
+
+/// Globs split by whether they match the name or the full path
+struct PatternSet {
+    name: GlobSet,
+    path: GlobSet,
+    is_empty: bool,
+}
+
+impl PatternSet {
+    fn new(patterns: &[String]) -> Result<Self, String> {
+        let mut name = GlobSetBuilder::new();
+        let mut path = GlobSetBuilder::new();
+        for pattern in patterns {
+            let normalized = pattern.replace('\\', "/");
+            let normalized = normalized.trim_start_matches('/');
+            if normalized.contains('/') {
+                path.add(build_glob(normalized)?);
+            } else {
+                name.add(build_glob(normalized)?);
+            }
+        }
+        Ok(Self {
+            name: name.build().map_err(|e| format!("Invalid filter pattern: {e}"))?,
+            path: path.build().map_err(|e| format!("Invalid filter pattern: {e}"))?,
+            is_empty: patterns.is_empty(),
+        })
+    }
+
+    fn is_match(&self, path: &str) -> bool {
+        let name = path.rsplit('/').next().unwrap_or(path);
+        self.name.is_match(name) || self.path.is_match(path)
+    }
+}
+
+fn build_glob(pattern: &str) -> Result<Glob, String> {
+    GlobBuilder::new(pattern)
+        .literal_separator(true)
+        .case_insensitive(cfg!(windows))
+        .build()
+        .map_err(|e| format!("Invalid filter pattern '{pattern}': {e}"))
+}
+
+/// Compiled form of `ExtractFilter`
+pub struct ExtractMatcher {
+    select_all: bool,
+    include: PatternSet,
+    exclude: PatternSet,
+    folder_exclude: PatternSet,
+    min_size: Option<u64>,
+    max_size: Option<u64>,
+    extensions: Vec<String>,
+}
+
+impl ExtractMatcher {
+    /// True if the filter selects everything (full extraction)
+    pub fn selects_all(&self) -> bool {
+        self.select_all
+    }
+
+    /// Whether a folder's whole subtree should be skipped
+    ///
+    /// `path` is relative to the container root, '/'-separated.
+    pub fn prunes_folder(&self, path: &str) -> bool {
+        !self.folder_exclude.is_empty && self.folder_exclude.is_match(path)
+    }
+
+    /// Whether a file at `path` (relative, '/'-separated) should be extracted
+    pub fn matches_file(&self, path: &str, size: u64) -> bool {
+        if self.select_all {
+            return true;
+        }
+        if !self.exclude.is_empty && self.exclude.is_match(path) {
+            return false;
+        }
+        if self.min_size.is_some_and(|min| size < min) || self.max_size.is_some_and(|max| size > max) {
+            return false;
+        }
+        if !self.extensions.is_empty() {
+            let name = path.rsplit('/').next().unwrap_or(path);
+            let ext = name.rsplit_once('.').map(|(_, ext)| ext.to_lowercase());
+            if !ext.is_some_and(|ext| self.extensions.contains(&ext)) {
+                return false;
+            }
+        }
+        self.include.is_empty || self.include.is_match(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filter(include: &[&str], exclude: &[&str]) -> ExtractFilter {
+        ExtractFilter {
+            include_globs: include.iter().map(|s| s.to_string()).collect(),
+            exclude_globs: exclude.iter().map(|s| s.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_name_and_path_globs() {
+        let matcher = filter(&["*.plist", "Users/*/Library/Messages/**"], &[]).compile().unwrap();
+        assert!(matcher.matches_file("Users/bob/Library/Preferences/com.apple.dock.plist", 10));
+        assert!(matcher.matches_file("Users/bob/Library/Messages/chat.db", 10));
+        assert!(!matcher.matches_file("Users/bob/x/Library/Messages/chat.db", 10));
+        assert!(!matcher.matches_file("Users/bob/notes.txt", 10));
+    }
+
+    #[test]
+    fn test_exclude_prunes_folder() {
+        let matcher = filter(&[], &["Users/*/AppData/**", "node_modules"]).compile().unwrap();
+        assert!(matcher.prunes_folder("Users/bob/AppData"));
+        assert!(matcher.prunes_folder("src/node_modules"));
+        assert!(!matcher.prunes_folder("Users/bob/Documents"));
+        assert!(!matcher.matches_file("Users/bob/AppData/Local/x.db", 1));
+        assert!(matcher.matches_file("Users/bob/Documents/x.db", 1));
+    }
+
+    #[test]
+    fn test_size_and_extension() {
+        let matcher = ExtractFilter {
+            min_size: Some(10),
+            max_size: Some(100),
+            extensions: vec![".DB".to_string()],
+            ..Default::default()
+        }
+        .compile()
+        .unwrap();
+        assert!(matcher.matches_file("a/chat.db", 50));
+        assert!(matcher.matches_file("a/CHAT.DB", 50));
+        assert!(!matcher.matches_file("a/chat.db", 5));
+        assert!(!matcher.matches_file("a/chat.db", 500));
+        assert!(!matcher.matches_file("a/db", 50));
+        assert!(ExtractFilter::default().compile().unwrap().selects_all());
+    }
+}
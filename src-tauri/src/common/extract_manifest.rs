@@ -0,0 +1,322 @@
+// Extraction manifests - a record of every file an extraction wrote
+//
+// Each extraction leaves extraction_manifest.json (or .csv) in its output
+// directory: one row per written file with the MD5/SHA-256 of the bytes
+// actually written, computed while writing, and the timestamps the container
+// recorded for the item. Original timestamps are applied to the output files
+// where the platform allows; a failure is noted on the row instead of
+// aborting the extraction. With deduplication (see `dedupe`) a file whose
+// content an earlier output holds records that output and what was done.
+
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use filetime::FileTime;
+use serde::{Deserialize, Serialize};
+
+use super::atomic_file::{write_atomic, AtomicFile};
+use super::dedupe::{csv_index_lines, DedupeIndex, Duplicate, IndexedFile};
+use super::hash::{HashAlgorithm, StreamingHasher};
+use super::integrity;
+use super::safe_path::PathChange;
+
+/// Manifest file name without extension
+pub const MANIFEST_NAME: &str = "extraction_manifest";
+
+/// Manifest file format
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ManifestFormat {
+    #[default]
+    Json,
+    Csv,
+}
+
+impl ManifestFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ManifestFormat::Json => "json",
+            ManifestFormat::Csv => "csv",
+        }
+    }
+}
+
+/// One file written by an extraction
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ExtractedFile {
+    /// Path inside the container (the image name for whole-image extraction)
+    pub internal_path: String,
+    pub output_path: String,
+    pub size: u64,
+    pub md5: String,
+    pub sha256: String,
+    /// Original timestamps stored in the container (RFC 3339, UTC)
+    pub modified: Option<String>,
+    pub accessed: Option<String>,
+    /// Why the original timestamps could not be applied to the output file
+    pub timestamp_error: Option<String>,
+    /// Changes made to `internal_path` to build a safe `output_path`
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub path_changes: Vec<PathChange>,
+    /// Set when an earlier output already held this content
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duplicate: Option<Duplicate>,
+}
+
+impl ExtractedFile {
+    /// Record the container's timestamps and apply them to the written file
+    pub fn with_original_times(mut self, accessed: Option<FileTime>, modified: Option<FileTime>) -> Self {
+        self.accessed = accessed.map(format_file_time);
+        self.modified = modified.map(format_file_time);
+        // A skipped or hard-linked duplicate has no file of its own to stamp
+        if self.duplicate.as_ref().is_some_and(|duplicate| !duplicate.has_own_file()) {
+            return self;
+        }
+        self.timestamp_error = set_original_times(Path::new(&self.output_path), accessed, modified).err();
+        self
+    }
+
+    /// Record how the container path was sanitized into the output path
+    pub fn with_path_changes(mut self, changes: Vec<PathChange>) -> Self {
+        self.path_changes = changes;
+        self
+    }
+
+    /// A file a dry run would write (no hashes, nothing on disk)
+    pub fn planned(internal_path: &str, output_path: &Path, size: u64) -> Self {
+        Self {
+            internal_path: internal_path.to_string(),
+            output_path: output_path.to_string_lossy().to_string(),
+            size,
+            ..Default::default()
+        }
+    }
+}
+
+/// Summary header plus one entry per extracted file
+#[derive(Debug, Clone, Serialize)]
+pub struct ExtractionManifest {
+    pub tool: String,
+    pub tool_version: String,
+    /// When the extraction finished (RFC 3339, UTC)
+    pub extracted_at: String,
+    pub source_container: String,
+    pub output_dir: String,
+    pub files: Vec<ExtractedFile>,
+    /// Outputs by content after a deduplicated extraction, read back by the
+    /// next one into the same folder
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub dedupe_index: Vec<IndexedFile>,
+}
+
+impl ExtractionManifest {
+    pub fn new(source_container: &str, output_dir: &str, files: Vec<ExtractedFile>) -> Self {
+        Self {
+            tool: env!("CARGO_PKG_NAME").to_string(),
+            tool_version: env!("CARGO_PKG_VERSION").to_string(),
+            extracted_at: Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true),
+            source_container: source_container.to_string(),
+            output_dir: output_dir.to_string(),
+            files,
+            dedupe_index: Vec::new(),
+        }
+    }
+
+    /// Save the deduplication index with the manifest
+    pub fn with_dedupe_index(mut self, entries: Vec<IndexedFile>) -> Self {
+        self.dedupe_index = entries;
+        self
+    }
+
+    /// Write the manifest into the output directory, returning its path
+    pub fn write(&self, format: ManifestFormat) -> Result<PathBuf, String> {
+        let path = Path::new(&self.output_dir).join(format!("{}.{}", MANIFEST_NAME, format.extension()));
+        let content = match format {
+            ManifestFormat::Json => integrity::tag_json_if_enabled(serde_json::to_string_pretty(self)
+                .map_err(|e| format!("Failed to serialize extraction manifest: {e}"))?)?,
+            ManifestFormat::Csv => integrity::tag_text_if_enabled(self.to_csv())?,
+        };
+        write_atomic(&path, content)
+            .map_err(|e| format!("Failed to write extraction manifest {:?}: {e}", path))?;
+        Ok(path)
+    }
+
+    /// CSV with the summary as leading `#` comment lines
+    fn to_csv(&self) -> String {
+        let mut out = String::new();
+        for (key, value) in [
+            ("tool", &self.tool),
+            ("tool_version", &self.tool_version),
+            ("extracted_at", &self.extracted_at),
+            ("source_container", &self.source_container),
+            ("output_dir", &self.output_dir),
+        ] {
+            out.push_str(&format!("# {}: {}\n", key, value));
+        }
+        out.push_str(&csv_index_lines(&self.dedupe_index));
+        out.push_str("source_container,internal_path,output_path,size,md5,sha256,modified,accessed,timestamp_error,path_changes,duplicate_of,dedupe_action,dedupe_error\n");
+        for file in &self.files {
+            let fields = [
+                csv_field(&self.source_container),
+                csv_field(&file.internal_path),
+                csv_field(&file.output_path),
+                file.size.to_string(),
+                file.md5.clone(),
+                file.sha256.clone(),
+                csv_field(file.modified.as_deref().unwrap_or("")),
+                csv_field(file.accessed.as_deref().unwrap_or("")),
+                csv_field(file.timestamp_error.as_deref().unwrap_or("")),
+                csv_field(&file.path_changes.iter().map(|change| change.to_string()).collect::<Vec<_>>().join("; ")),
+                csv_field(file.duplicate.as_ref().map_or("", |duplicate| duplicate.original_path.as_str())),
+                file.duplicate.as_ref().map_or("", |duplicate| duplicate.action.as_str()).to_string(),
+                csv_field(file.duplicate.as_ref().and_then(|duplicate| duplicate.link_error.as_deref()).unwrap_or("")),
+            ];
+            out.push_str(&fields.join(","));
+            out.push('\n');
+        }
+        out
+    }
+}
+
+/// Quote a CSV field if it contains a delimiter, quote or line break
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Writer that hashes everything written through it with MD5 and SHA-256
+pub struct HashingWriter<W: Write> {
+    inner: W,
+    md5: StreamingHasher,
+    sha256: StreamingHasher,
+    written: u64,
+}
+
+impl<W: Write> HashingWriter<W> {
+    pub fn new(inner: W) -> Self {
+        Self {
+            inner,
+            md5: StreamingHasher::new(HashAlgorithm::Md5),
+            sha256: StreamingHasher::new(HashAlgorithm::Sha256),
+            written: 0,
+        }
+    }
+
+    /// Flush the writer and describe what was written
+    pub fn finish(mut self, internal_path: &str, output_path: &Path) -> Result<ExtractedFile, String> {
+        self.inner.flush()
+            .map_err(|e| format!("Failed to flush {:?}: {e}", output_path))?;
+        Ok(self.describe(internal_path, output_path).1)
+    }
+
+    fn describe(self, internal_path: &str, output_path: &Path) -> (W, ExtractedFile) {
+        let extracted = ExtractedFile {
+            internal_path: internal_path.to_string(),
+            output_path: output_path.to_string_lossy().to_string(),
+            size: self.written,
+            md5: self.md5.finalize(),
+            sha256: self.sha256.finalize(),
+            ..Default::default()
+        };
+        (self.inner, extracted)
+    }
+}
+
+impl HashingWriter<AtomicFile> {
+    /// Move the output into place and describe what was written
+    pub fn commit(self, internal_path: &str) -> Result<ExtractedFile, String> {
+        let output_path = self.inner.path().to_path_buf();
+        let (file, extracted) = self.describe(internal_path, &output_path);
+        file.commit()
+            .map_err(|e| format!("Failed to write {:?}: {e}", output_path))?;
+        Ok(extracted)
+    }
+
+    /// [`commit`](Self::commit), or handle the file as a duplicate when
+    /// `index` holds an output with the same content
+    pub fn commit_deduped(self, internal_path: &str, index: &DedupeIndex) -> Result<ExtractedFile, String> {
+        let output_path = self.inner.path().to_path_buf();
+        let (file, mut extracted) = self.describe(internal_path, &output_path);
+        extracted.duplicate = index.place(file, &extracted)?;
+        Ok(extracted)
+    }
+}
+
+impl<W: Write> Write for HashingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.md5.update(&buf[..n]);
+        self.sha256.update(&buf[..n]);
+        self.written += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Apply original access/modification times to an extracted path
+///
+/// A missing access time falls back to now, a missing modification time to
+/// the access time. Does nothing when the container stored neither.
+pub fn set_original_times(path: &Path, accessed: Option<FileTime>, modified: Option<FileTime>) -> Result<(), String> {
+    if accessed.is_none() && modified.is_none() {
+        return Ok(());
+    }
+    let atime = accessed.unwrap_or_else(|| FileTime::from_system_time(SystemTime::now()));
+    let mtime = modified.unwrap_or(atime);
+    filetime::set_file_times(path, atime, mtime)
+        .map_err(|e| format!("Failed to set file times for {:?}: {e}", path))
+}
+
+fn format_file_time(time: FileTime) -> String {
+    DateTime::<Utc>::from_timestamp(time.unix_seconds(), time.nanoseconds())
+        .map(|dt| dt.to_rfc3339_opts(SecondsFormat::Secs, true))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_manifest_records_written_bytes() {
+        let dir = tempfile::tempdir().unwrap();
+        let output = dir.path().join("notes, draft.txt");
+        let mut writer = HashingWriter::new(fs::File::create(&output).unwrap());
+        writer.write_all(b"hello world").unwrap();
+        let modified = FileTime::from_unix_time(1_546_344_000, 0);
+        let changes = crate::common::safe_path::join_sanitized(dir.path(), "../notes, draft.txt").changes;
+        let file = writer.finish("../notes, draft.txt", &output).unwrap()
+            .with_original_times(None, Some(modified))
+            .with_path_changes(changes);
+
+        assert_eq!(file.size, 11);
+        assert_eq!(file.md5, "5eb63bbbe01eeed093cb22bb8f5acdc3");
+        assert_eq!(file.sha256, "b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9");
+        assert_eq!(file.modified.as_deref(), Some("2019-01-01T12:00:00Z"));
+        assert_eq!(file.timestamp_error, None);
+        assert_eq!(FileTime::from_last_modification_time(&fs::metadata(&output).unwrap()), modified);
+
+        let output_dir = dir.path().to_string_lossy().to_string();
+        let manifest = ExtractionManifest::new("/evidence/image.ad1", &output_dir, vec![file]);
+        let csv_path = manifest.write(ManifestFormat::Csv).unwrap();
+        let csv = fs::read_to_string(&csv_path).unwrap();
+        assert!(csv.starts_with("# tool: "));
+        assert!(csv.contains("/evidence/image.ad1,\"../notes, draft.txt\","));
+        assert!(csv.contains("'..' -> '' (parent directory reference removed)"));
+
+        let json_path = manifest.write(ManifestFormat::Json).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&fs::read_to_string(json_path).unwrap()).unwrap();
+        assert_eq!(json["files"][0]["size"], 11);
+        assert_eq!(json["files"][0]["path_changes"][0]["original"], "..");
+        assert_eq!(json["source_container"], "/evidence/image.ad1");
+    }
+}
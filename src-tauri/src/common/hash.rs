@@ -1,7 +1,8 @@
 // Shared hash utilities for forensic container verification
 //
 // Provides unified hashing across all container formats (AD1, E01, RAW, L01)
-// with support for MD5, SHA-1, SHA-256, SHA-512, BLAKE2b, BLAKE3, XXH3, XXH64, CRC32
+// with support for MD5, SHA-1, SHA-256, SHA-512, SHA3-256, BLAKE2b, BLAKE3,
+// XXH3, XXH128, XXH64, CRC32
 
 use std::fs::File;
 use std::io::{BufRead, BufReader};
@@ -9,15 +10,14 @@ use std::path::Path;
 use md5::Md5;
 use sha1::{Sha1, Digest};
 use sha2::{Sha256, Sha512};
+use sha3::Sha3_256;
 use blake2::Blake2b512;
 use blake3::Hasher as Blake3Hasher;
 use xxhash_rust::xxh3::Xxh3;
 use xxhash_rust::xxh64::Xxh64;
 use crc32fast::Hasher as Crc32Hasher;
-use serde::Serialize;
-use tracing::{debug, trace, instrument};
-
-use super::BUFFER_SIZE;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, trace, warn, instrument};
 
 // =============================================================================
 // Hash Algorithm Enum
@@ -26,9 +26,11 @@ use super::BUFFER_SIZE;
 /// Supported hash algorithms for forensic verification
 /// - MD5/SHA1: Legacy algorithms for AD1 metadata comparison
 /// - SHA256/SHA512: NIST approved, court-accepted forensic standards
+/// - SHA3-256: NIST FIPS 202 (Keccak), required by some partner agencies
 /// - BLAKE3: Modern, extremely fast cryptographic hash
 /// - BLAKE2b: Fast cryptographic hash (used in many security applications)
 /// - XXH3/XXH64: Ultra-fast non-cryptographic hashes for integrity checks
+///   (XXH3 has always emitted the 128-bit digest here; XXH128 names it explicitly)
 /// - CRC32: Fast checksum (non-cryptographic)
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum HashAlgorithm {
@@ -36,9 +38,11 @@ pub enum HashAlgorithm {
     Sha1,
     Sha256,
     Sha512,
+    Sha3_256,
     Blake3,
     Blake2,
     Xxh3,
+    Xxh128,
     Xxh64,
     Crc32,
 }
@@ -51,13 +55,15 @@ impl HashAlgorithm {
             "sha1" | "sha-1" => Ok(HashAlgorithm::Sha1),
             "sha256" | "sha-256" => Ok(HashAlgorithm::Sha256),
             "sha512" | "sha-512" => Ok(HashAlgorithm::Sha512),
+            "sha3-256" | "sha3_256" | "sha3256" => Ok(HashAlgorithm::Sha3_256),
             "blake3" => Ok(HashAlgorithm::Blake3),
             "blake2" | "blake2b" => Ok(HashAlgorithm::Blake2),
             "xxh3" | "xxhash3" => Ok(HashAlgorithm::Xxh3),
+            "xxh128" | "xxhash128" => Ok(HashAlgorithm::Xxh128),
             "xxh64" | "xxhash64" => Ok(HashAlgorithm::Xxh64),
             "crc32" | "crc-32" => Ok(HashAlgorithm::Crc32),
             _ => Err(format!(
-                "Unsupported hash algorithm: '{}'. Supported: md5, sha1, sha256, sha512, blake3, blake2, xxh3, xxh64, crc32",
+                "Unsupported hash algorithm: '{}'. Supported: md5, sha1, sha256, sha512, sha3-256, blake3, blake2, xxh3, xxh128, xxh64, crc32",
                 algorithm
             )),
         }
@@ -70,9 +76,11 @@ impl HashAlgorithm {
             HashAlgorithm::Sha1 => "SHA-1",
             HashAlgorithm::Sha256 => "SHA-256",
             HashAlgorithm::Sha512 => "SHA-512",
+            HashAlgorithm::Sha3_256 => "SHA3-256",
             HashAlgorithm::Blake3 => "BLAKE3",
             HashAlgorithm::Blake2 => "BLAKE2b",
             HashAlgorithm::Xxh3 => "XXH3",
+            HashAlgorithm::Xxh128 => "XXH128",
             HashAlgorithm::Xxh64 => "XXH64",
             HashAlgorithm::Crc32 => "CRC32",
         }
@@ -85,9 +93,11 @@ impl HashAlgorithm {
             HashAlgorithm::Sha1 => 40,
             HashAlgorithm::Sha256 => 64,
             HashAlgorithm::Sha512 => 128,
+            HashAlgorithm::Sha3_256 => 64,
             HashAlgorithm::Blake3 => 64,
             HashAlgorithm::Blake2 => 128,
             HashAlgorithm::Xxh3 => 32,  // 128-bit = 32 hex chars
+            HashAlgorithm::Xxh128 => 32,
             HashAlgorithm::Xxh64 => 16, // 64-bit = 16 hex chars
             HashAlgorithm::Crc32 => 8,  // 32-bit = 8 hex chars
         }
@@ -111,9 +121,11 @@ pub enum StreamingHasher {
     Sha1(Sha1),
     Sha256(Sha256),
     Sha512(Sha512),
+    Sha3_256(Sha3_256),
     Blake3(Box<Blake3Hasher>),  // Boxed to reduce enum size (~1920 bytes -> 8 byte pointer)
     Blake2(Blake2b512),
     Xxh3(Xxh3),
+    Xxh128(Xxh3),
     Xxh64(Xxh64),
     Crc32(Crc32Hasher),
 }
@@ -126,9 +138,11 @@ impl StreamingHasher {
             HashAlgorithm::Sha1 => StreamingHasher::Sha1(Sha1::new()),
             HashAlgorithm::Sha256 => StreamingHasher::Sha256(Sha256::new()),
             HashAlgorithm::Sha512 => StreamingHasher::Sha512(Sha512::new()),
+            HashAlgorithm::Sha3_256 => StreamingHasher::Sha3_256(Sha3_256::new()),
             HashAlgorithm::Blake3 => StreamingHasher::Blake3(Box::new(Blake3Hasher::new())),
             HashAlgorithm::Blake2 => StreamingHasher::Blake2(Blake2b512::new()),
             HashAlgorithm::Xxh3 => StreamingHasher::Xxh3(Xxh3::new()),
+            HashAlgorithm::Xxh128 => StreamingHasher::Xxh128(Xxh3::new()),
             HashAlgorithm::Xxh64 => StreamingHasher::Xxh64(Xxh64::new(0)),
             HashAlgorithm::Crc32 => StreamingHasher::Crc32(Crc32Hasher::new()),
         }
@@ -146,9 +160,10 @@ impl StreamingHasher {
             StreamingHasher::Sha1(h) => Digest::update(h, data),
             StreamingHasher::Sha256(h) => Digest::update(h, data),
             StreamingHasher::Sha512(h) => Digest::update(h, data),
+            StreamingHasher::Sha3_256(h) => Digest::update(h, data),
             StreamingHasher::Blake3(h) => { h.update(data); }
             StreamingHasher::Blake2(h) => Digest::update(h, data),
-            StreamingHasher::Xxh3(h) => h.update(data),
+            StreamingHasher::Xxh3(h) | StreamingHasher::Xxh128(h) => h.update(data),
             StreamingHasher::Xxh64(h) => h.update(data),
             StreamingHasher::Crc32(h) => h.update(data),
         }
@@ -170,15 +185,70 @@ impl StreamingHasher {
             StreamingHasher::Sha1(h) => hex::encode(h.finalize()),
             StreamingHasher::Sha256(h) => hex::encode(h.finalize()),
             StreamingHasher::Sha512(h) => hex::encode(h.finalize()),
+            StreamingHasher::Sha3_256(h) => hex::encode(h.finalize()),
             StreamingHasher::Blake3(h) => h.finalize().to_hex().to_string(),
             StreamingHasher::Blake2(h) => hex::encode(h.finalize()),
-            StreamingHasher::Xxh3(h) => format!("{:032x}", h.digest128()),
+            StreamingHasher::Xxh3(h) | StreamingHasher::Xxh128(h) => format!("{:032x}", h.digest128()),
             StreamingHasher::Xxh64(h) => format!("{:016x}", h.digest()),
             StreamingHasher::Crc32(h) => format!("{:08x}", h.finalize()),
         }
     }
 }
 
+// =============================================================================
+// Throughput Tuning
+// =============================================================================
+
+/// Smallest read buffer accepted by [`HashTuning`]
+pub const MIN_TUNED_BUFFER_SIZE: usize = 64 * 1024; // 64KB
+/// Largest read buffer accepted by [`HashTuning`]
+pub const MAX_TUNED_BUFFER_SIZE: usize = 256 * 1024 * 1024; // 256MB
+
+/// Read buffer size and BLAKE3 thread count for a hashing pass
+///
+/// `None` keeps the built-in behaviour: reads of the settings' buffer size
+/// (by default [`super::BUFFER_SIZE`]) and BLAKE3
+/// on rayon's global pool (all cores). Values usually come from a hash
+/// benchmark of the device holding the evidence.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HashTuning {
+    pub buffer_size: Option<usize>,
+    pub blake3_threads: Option<usize>,
+}
+
+impl HashTuning {
+    /// Read buffer size to use, clamped to a sane range (and to
+    /// [`super::resources::LOW_MEMORY_BUFFER_SIZE`] in low-memory mode);
+    /// the settings' buffer size when none is set
+    pub fn buffer_size(&self) -> usize {
+        match self.buffer_size {
+            Some(size) => super::resources::buffer_size(size.clamp(MIN_TUNED_BUFFER_SIZE, MAX_TUNED_BUFFER_SIZE)),
+            None => super::resources::io_buffer_size(),
+        }
+    }
+
+    /// Dedicated rayon pool for BLAKE3 when a thread count is set
+    ///
+    /// Falls back to the global pool (None) if the pool can't be built.
+    pub fn blake3_pool(&self) -> Option<rayon::ThreadPool> {
+        let threads = self.blake3_threads.filter(|n| *n > 0)?;
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()
+            .map_err(|e| warn!(threads, "Failed to build BLAKE3 thread pool: {}", e))
+            .ok()
+    }
+}
+
+/// BLAKE3 `update_rayon` on `pool`, or on the global pool
+pub fn blake3_update(hasher: &mut Blake3Hasher, data: &[u8], pool: Option<&rayon::ThreadPool>) {
+    match pool {
+        Some(pool) => pool.install(|| { hasher.update_rayon(data); }),
+        None => { hasher.update_rayon(data); }
+    }
+}
+
 // =============================================================================
 // One-shot Hash Computation
 // =============================================================================
@@ -206,6 +276,11 @@ pub fn compute_hash(data: &[u8], algorithm: HashAlgorithm) -> String {
             hasher.update(data);
             hex::encode(hasher.finalize())
         }
+        HashAlgorithm::Sha3_256 => {
+            let mut hasher = Sha3_256::new();
+            hasher.update(data);
+            hex::encode(hasher.finalize())
+        }
         HashAlgorithm::Blake3 => {
             let mut hasher = Blake3Hasher::new();
             hasher.update(data);
@@ -216,7 +291,7 @@ pub fn compute_hash(data: &[u8], algorithm: HashAlgorithm) -> String {
             hasher.update(data);
             hex::encode(hasher.finalize())
         }
-        HashAlgorithm::Xxh3 => {
+        HashAlgorithm::Xxh3 | HashAlgorithm::Xxh128 => {
             let mut hasher = Xxh3::new();
             hasher.update(data);
             format!("{:032x}", hasher.digest128())
@@ -233,12 +308,65 @@ pub fn compute_hash(data: &[u8], algorithm: HashAlgorithm) -> String {
     }
 }
 
+/// Compute hash of data, hashing BLAKE3 on `tuning.blake3_threads` threads
+///
+/// The buffer size doesn't apply to in-memory data; without a thread count
+/// this is [`compute_hash`].
+pub fn compute_hash_with_tuning(data: &[u8], algorithm: HashAlgorithm, tuning: &HashTuning) -> String {
+    match (algorithm, tuning.blake3_pool()) {
+        (HashAlgorithm::Blake3, Some(pool)) => {
+            let mut hasher = Blake3Hasher::new();
+            blake3_update(&mut hasher, data, Some(&pool));
+            hasher.finalize().to_hex().to_string()
+        }
+        _ => compute_hash(data, algorithm),
+    }
+}
+
 /// Compute hash from algorithm string (convenience wrapper)
 pub fn compute_hash_str(data: &[u8], algorithm: &str) -> Result<String, String> {
     let algo = HashAlgorithm::from_str(algorithm)?;
     Ok(compute_hash(data, algo))
 }
 
+// =============================================================================
+// Keyed BLAKE3 (integrity tags)
+// =============================================================================
+
+/// Keyed BLAKE3 - a MAC over our own exports (see `common::integrity`)
+///
+/// Only for tags that show an export wasn't edited after this installation
+/// wrote it. Never use it for evidentiary hashes: those stay the standard
+/// unkeyed algorithms anyone can recompute.
+pub struct KeyedBlake3(Box<Blake3Hasher>);
+
+impl KeyedBlake3 {
+    pub fn new(key: &[u8; 32]) -> Self {
+        Self(Box::new(Blake3Hasher::new_keyed(key)))
+    }
+
+    /// Keyed with the key BLAKE3 derives for `context` from `key_material`,
+    /// so one secret yields independent keys per purpose
+    pub fn derived(context: &str, key_material: &[u8]) -> Self {
+        Self::new(&blake3::derive_key(context, key_material))
+    }
+
+    pub fn update(&mut self, data: &[u8]) -> &mut Self {
+        self.0.update(data);
+        self
+    }
+
+    /// The tag as lowercase hex
+    pub fn finalize_hex(&self) -> String {
+        self.0.finalize().to_hex().to_string()
+    }
+
+    /// Whether the tag equals `hex` (compared in constant time)
+    pub fn matches_hex(&self, hex: &str) -> bool {
+        blake3::Hash::from_hex(hex).is_ok_and(|expected| expected == self.0.finalize())
+    }
+}
+
 // =============================================================================
 // File Hashing with Progress
 // =============================================================================
@@ -252,10 +380,24 @@ pub fn compute_hash_str(data: &[u8], algorithm: &str) -> Result<String, String>
 /// 
 /// # Returns
 /// The hex-encoded hash string
-#[instrument(skip(progress_callback), fields(path = %path.display()))]
 pub fn hash_file_with_progress<F>(
     path: &Path,
     algorithm: &str,
+    progress_callback: F,
+) -> Result<String, String>
+where
+    F: FnMut(u64, u64),
+{
+    hash_file_with_tuning(path, algorithm, &HashTuning::default(), progress_callback)
+}
+
+/// Hash a file with progress reporting, using the given buffer size and
+/// BLAKE3 thread count
+#[instrument(skip(progress_callback), fields(path = %path.display()))]
+pub fn hash_file_with_tuning<F>(
+    path: &Path,
+    algorithm: &str,
+    tuning: &HashTuning,
     mut progress_callback: F,
 ) -> Result<String, String>
 where
@@ -268,26 +410,28 @@ where
     let metadata = std::fs::metadata(path)
         .map_err(|e| format!("Failed to get file metadata: {}", e))?;
     let total_size = metadata.len();
+    let buffer_size = tuning.buffer_size();
     
-    debug!(algorithm, total_size, "Starting file hash");
+    debug!(algorithm, total_size, buffer_size, "Starting file hash");
 
     let file = File::open(path)
         .map_err(|e| format!("Failed to open file: {}", e))?;
-    let mut reader = BufReader::with_capacity(BUFFER_SIZE, file);
+    let mut reader = BufReader::with_capacity(buffer_size, file);
 
-    let algorithm_lower = algorithm.to_lowercase();
+    let algo = HashAlgorithm::from_str(algorithm)?;
 
     // For BLAKE3, use parallel hashing for best performance
-    if algorithm_lower == "blake3" {
+    if algo == HashAlgorithm::Blake3 {
         trace!("Using BLAKE3 parallel hashing");
-        return hash_file_blake3_parallel(&mut reader, total_size, &mut progress_callback);
+        let pool = tuning.blake3_pool();
+        return hash_file_blake3_parallel(&mut reader, total_size, pool.as_ref(), &mut progress_callback);
     }
 
     // For other algorithms, use streaming hasher
-    trace!("Using streaming hasher for {}", algorithm);
-    let mut hasher = StreamingHasher::from_str(algorithm)?;
+    trace!("Using streaming hasher for {}", algo.name());
+    let mut hasher = StreamingHasher::new(algo);
     let mut bytes_read_total = 0u64;
-    let report_interval = (total_size / 20).max(BUFFER_SIZE as u64);
+    let report_interval = (total_size / 20).max(buffer_size as u64);
     let mut last_report = 0u64;
 
     loop {
@@ -318,6 +462,7 @@ where
 fn hash_file_blake3_parallel<R, F>(
     reader: &mut BufReader<R>,
     total_size: u64,
+    pool: Option<&rayon::ThreadPool>,
     progress_callback: &mut F,
 ) -> Result<String, String>
 where
@@ -326,7 +471,7 @@ where
 {
     let mut hasher = Blake3Hasher::new();
     let mut bytes_read_total = 0u64;
-    let report_interval = (total_size / 20).max(BUFFER_SIZE as u64);
+    let report_interval = (total_size / 20).max(reader.capacity() as u64);
     let mut last_report = 0u64;
 
     loop {
@@ -337,8 +482,8 @@ where
             break;
         }
 
-        // BLAKE3 update_rayon uses all cores for parallel hashing
-        hasher.update_rayon(buf);
+        // BLAKE3 update_rayon uses all cores (or the tuned pool) for parallel hashing
+        blake3_update(&mut hasher, buf, pool);
         reader.consume(len);
 
         bytes_read_total += len as u64;
@@ -357,6 +502,93 @@ pub fn hash_file(path: &Path, algorithm: &str) -> Result<String, String> {
     hash_file_with_progress(path, algorithm, |_, _| {})
 }
 
+// =============================================================================
+// Hash Values
+// =============================================================================
+
+/// A hex digest in canonical form: lowercase, without separators
+///
+/// Stored hashes come from logs and manifests written by other tools, in
+/// uppercase (EnCase), grouped in blocks of 8 (Forensic MD5 segment lists),
+/// colon separated or with a trailing CR from a Windows log. All of these
+/// parse to the same value, so comparing `HashValue`s is case- and
+/// format-insensitive.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct HashValue(String);
+
+impl HashValue {
+    /// Parse a hash, checking its length against `algorithm`
+    pub fn parse(value: &str, algorithm: HashAlgorithm) -> Result<Self, String> {
+        let hash: HashValue = value.parse()?;
+        if hash.0.len() != algorithm.hash_length() {
+            return Err(format!(
+                "{} hash must be {} hex characters, got {}: {}",
+                algorithm.name(), algorithm.hash_length(), hash.0.len(), hash
+            ));
+        }
+        Ok(hash)
+    }
+
+    /// Parse a hash labelled with an algorithm name ("MD5", "SHA-1", ...)
+    ///
+    /// The length is only checked for algorithms we know.
+    pub fn parse_named(value: &str, algorithm: &str) -> Result<Self, String> {
+        match HashAlgorithm::from_str(algorithm) {
+            Ok(algorithm) => Self::parse(value, algorithm),
+            Err(_) => value.parse(),
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Whether `other` parses to this hash
+    pub fn matches(&self, other: &str) -> bool {
+        other.parse::<HashValue>().is_ok_and(|other| other == *self)
+    }
+}
+
+impl std::str::FromStr for HashValue {
+    type Err = String;
+
+    /// Parse a hex digest of any length
+    ///
+    /// Surrounding whitespace is trimmed and spaces, tabs and colons used as
+    /// group separators are removed; anything else that is not hex is an error.
+    fn from_str(value: &str) -> Result<Self, String> {
+        let hex: String = value.trim()
+            .chars()
+            .filter(|c| !matches!(c, ' ' | '\t' | ':'))
+            .collect();
+        if hex.is_empty() {
+            return Err("Empty hash value".to_string());
+        }
+        if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(format!("Invalid hash value (not hexadecimal): {}", value.trim()));
+        }
+        Ok(HashValue(hex.to_ascii_lowercase()))
+    }
+}
+
+impl std::fmt::Display for HashValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl From<HashValue> for String {
+    fn from(hash: HashValue) -> Self {
+        hash.0
+    }
+}
+
+impl Serialize for HashValue {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0)
+    }
+}
+
 // =============================================================================
 // Hash Validation Utilities
 // =============================================================================
@@ -378,9 +610,18 @@ pub fn guess_algorithm_from_hash(hash: &str) -> Option<HashAlgorithm> {
     }
 }
 
-/// Compare two hashes (case-insensitive)
+/// Compare two hashes in any [`HashValue`] format; unparseable hashes never match
 pub fn hashes_match(hash1: &str, hash2: &str) -> bool {
-    hash1.to_lowercase() == hash2.to_lowercase()
+    hash1.parse::<HashValue>().is_ok_and(|hash| hash.matches(hash2))
+}
+
+/// Whether two algorithm labels ("SHA-1", "sha1") name the same algorithm;
+/// labels we don't know are compared case-insensitively
+pub fn same_algorithm(name1: &str, name2: &str) -> bool {
+    match (HashAlgorithm::from_str(name1), HashAlgorithm::from_str(name2)) {
+        (Ok(algo1), Ok(algo2)) => algo1 == algo2,
+        _ => name1.trim().eq_ignore_ascii_case(name2.trim()),
+    }
 }
 
 // =============================================================================
@@ -392,7 +633,7 @@ pub fn hashes_match(hash1: &str, hash2: &str) -> bool {
 pub enum HashMatchResult {
     /// Exact match (same case)
     Exact,
-    /// Match but different case (e.g., "abc" vs "ABC")
+    /// Match after normalization (e.g., "abc" vs "ABC" or "AB C")
     CaseInsensitive,
     /// Hashes do not match
     Mismatch,
@@ -408,32 +649,21 @@ impl HashMatchResult {
 }
 
 /// Compare two hash strings with detailed result
+///
+/// Hashes that only match after normalization (case, grouping, stray
+/// whitespace) are reported as `CaseInsensitive`.
 pub fn compare_hashes(computed: &str, expected: &str) -> HashMatchResult {
-    let computed = computed.trim();
-    let expected = expected.trim();
-    
-    // Check for invalid characters
-    if !computed.chars().all(|c| c.is_ascii_hexdigit()) 
-        || !expected.chars().all(|c| c.is_ascii_hexdigit()) {
+    let (Ok(computed_value), Ok(expected_value)) = (computed.parse::<HashValue>(), expected.parse::<HashValue>()) else {
         return HashMatchResult::Invalid;
-    }
-    
-    // Length mismatch
-    if computed.len() != expected.len() {
-        return HashMatchResult::Mismatch;
-    }
+    };
     
-    // Exact match
-    if computed == expected {
-        return HashMatchResult::Exact;
+    if computed_value != expected_value {
+        HashMatchResult::Mismatch
+    } else if computed.trim() == expected.trim() {
+        HashMatchResult::Exact
+    } else {
+        HashMatchResult::CaseInsensitive
     }
-    
-    // Case-insensitive match
-    if computed.eq_ignore_ascii_case(expected) {
-        return HashMatchResult::CaseInsensitive;
-    }
-    
-    HashMatchResult::Mismatch
 }
 
 /// Detailed hash verification result
@@ -552,9 +782,140 @@ pub fn verify_file_hash(
         .with_data_size(file_size))
 }
 
+// =============================================================================
+// Arbitrary File Hashing
+// =============================================================================
+
+/// Single digest computed for a file
+#[derive(Debug, Clone, Serialize)]
+pub struct FileDigest {
+    pub algorithm: String,
+    pub hash: String,
+}
+
+/// Result of hashing a regular (non-container) file with one or more algorithms
+#[derive(Debug, Clone, Serialize)]
+pub struct FileHashResult {
+    pub path: String,
+    pub size: u64,
+    /// One digest per requested algorithm, in request order
+    pub digests: Vec<FileDigest>,
+    pub duration_ms: u64,
+    /// File size or mtime changed while hashing - digests may not be reproducible
+    pub unstable: bool,
+    /// Expected hash supplied by the caller
+    pub expected: Option<String>,
+    /// Comparison of `expected` against every digest of matching length
+    /// (SHA-256, SHA3-256 and BLAKE3 share one) - a match with any of them wins
+    pub match_result: Option<HashMatchResult>,
+}
+
+/// Hash any regular file with several algorithms
+///
+/// The file is read once and each buffer is fed to every algorithm's
+/// hasher. The file's size and mtime are re-checked after that pass and the
+/// result is flagged `unstable` if they changed.
+#[instrument(skip(progress_callback))]
+pub fn hash_regular_file<F>(
+    path: &Path,
+    algorithms: &[String],
+    expected: Option<&str>,
+    mut progress_callback: F,
+) -> Result<FileHashResult, String>
+where
+    F: FnMut(u64, u64),
+{
+    if algorithms.is_empty() {
+        return Err("No hash algorithm specified".to_string());
+    }
+    let before = std::fs::metadata(path)
+        .map_err(|e| format!("Failed to get file metadata: {}", e))?;
+    if before.is_dir() {
+        return Err(format!("Path is a directory, not a file: {}", path.display()));
+    }
+    if !before.is_file() {
+        return Err(format!("Not a regular file: {}", path.display()));
+    }
+    // Validate all algorithms up front so we don't fail after a long pass
+    let algos = algorithms.iter()
+        .map(|algorithm| HashAlgorithm::from_str(algorithm))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let size = before.len();
+    let start = std::time::Instant::now();
+
+    let buffer_size = HashTuning::default().buffer_size();
+    let file = File::open(path)
+        .map_err(|e| format!("Failed to open file: {}", e))?;
+    let mut reader = BufReader::with_capacity(buffer_size, file);
+    let mut hashers: Vec<StreamingHasher> = algos.iter().map(|algo| StreamingHasher::new(*algo)).collect();
+    let report_interval = (size / 20).max(buffer_size as u64);
+    let mut bytes_read_total = 0u64;
+    let mut last_report = 0u64;
+
+    loop {
+        let buf = reader.fill_buf()
+            .map_err(|e| format!("Read error: {}", e))?;
+        let len = buf.len();
+        if len == 0 {
+            break;
+        }
+
+        for hasher in &mut hashers {
+            hasher.update_parallel(buf);
+        }
+        reader.consume(len);
+
+        bytes_read_total += len as u64;
+        if bytes_read_total - last_report >= report_interval {
+            progress_callback(bytes_read_total, size);
+            last_report = bytes_read_total;
+        }
+    }
+    progress_callback(size, size);
+
+    let digests: Vec<FileDigest> = algos.iter().zip(hashers)
+        .map(|(algo, hasher)| FileDigest {
+            algorithm: algo.name().to_string(),
+            hash: hasher.finalize(),
+        })
+        .collect();
+
+    let after = std::fs::metadata(path).ok();
+    let unstable = match after {
+        Some(m) => m.len() != size || m.modified().ok() != before.modified().ok(),
+        None => true,
+    };
+    if unstable {
+        debug!(path = %path.display(), "File changed while hashing");
+    }
+
+    let match_result = expected.map(|exp| {
+        if exp.parse::<HashValue>().is_err() {
+            return HashMatchResult::Invalid;
+        }
+        digests.iter().zip(&algos)
+            .filter(|(_, algo)| HashValue::parse(exp, **algo).is_ok())
+            .map(|(d, _)| compare_hashes(&d.hash, exp))
+            .find(HashMatchResult::is_match)
+            .unwrap_or(HashMatchResult::Mismatch)
+    });
+
+    Ok(FileHashResult {
+        path: path.to_string_lossy().to_string(),
+        size,
+        digests,
+        duration_ms: start.elapsed().as_millis() as u64,
+        unstable,
+        expected: expected.map(|e| e.trim().to_string()),
+        match_result,
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::common::BUFFER_SIZE;
 
     #[test]
     fn test_algorithm_parsing() {
@@ -579,6 +940,41 @@ mod tests {
         assert_eq!(sha1, "2aae6c35c94fcfb415dbe95f408b9ce91ee846ed");
     }
 
+    /// Known digests of "abc" for every supported algorithm
+    const ABC_VECTORS: &[(HashAlgorithm, &str)] = &[
+        (HashAlgorithm::Md5, "900150983cd24fb0d6963f7d28e17f72"),
+        (HashAlgorithm::Sha1, "a9993e364706816aba3e25717850c26c9cd0d89d"),
+        (HashAlgorithm::Sha256, "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"),
+        (HashAlgorithm::Sha512, "ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f"),
+        (HashAlgorithm::Sha3_256, "3a985da74fe225b2045c172d6bd390bd855f086e3e9d525b46bfe24511431532"),
+        (HashAlgorithm::Blake3, "6437b3ac38465133ffb63b75273a8db548c558465d79db03fd359c6cd5bd9d85"),
+        (HashAlgorithm::Blake2, "ba80a53f981c4d0d6a2797b69f12f6e94c212f14685ac4b74b12bb6fdbffa2d17d87c5392aab792dc252d5de4533cc9518d38aa8dbf1925ab92386edd4009923"),
+        (HashAlgorithm::Xxh3, "06b05ab6733a618578af5f94892f3950"),
+        (HashAlgorithm::Xxh128, "06b05ab6733a618578af5f94892f3950"),
+        (HashAlgorithm::Xxh64, "44bc2cf5ad770999"),
+        (HashAlgorithm::Crc32, "352441c2"),
+    ];
+
+    #[test]
+    fn test_known_vectors_all_algorithms() {
+        for &(algorithm, expected) in ABC_VECTORS {
+            assert_eq!(compute_hash(b"abc", algorithm), expected, "{}", algorithm.name());
+            assert_eq!(expected.len(), algorithm.hash_length(), "{}", algorithm.name());
+
+            let mut hasher = StreamingHasher::new(algorithm);
+            hasher.update(b"a");
+            hasher.update_parallel(b"bc");
+            assert_eq!(hasher.finalize(), expected, "{} (streaming)", algorithm.name());
+        }
+    }
+
+    #[test]
+    fn test_new_algorithm_aliases() {
+        assert_eq!(HashAlgorithm::from_str("sha3-256").unwrap(), HashAlgorithm::Sha3_256);
+        assert_eq!(HashAlgorithm::from_str("SHA3_256").unwrap(), HashAlgorithm::Sha3_256);
+        assert_eq!(HashAlgorithm::from_str("xxh128").unwrap(), HashAlgorithm::Xxh128);
+    }
+
     #[test]
     fn test_streaming_hasher() {
         let mut hasher = StreamingHasher::new(HashAlgorithm::Md5);
@@ -600,4 +996,136 @@ mod tests {
         assert_eq!(guess_algorithm_from_hash("5eb63bbbe01eeed093cb22bb8f5acdc3"), Some(HashAlgorithm::Md5));
         assert_eq!(guess_algorithm_from_hash("2aae6c35c94fcfb415dbe95f408b9ce91ee846ed"), Some(HashAlgorithm::Sha1));
     }
+
+    #[test]
+    fn test_hash_regular_file_multiple_algorithms() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("keywords.txt");
+        std::fs::write(&path, b"hello world").unwrap();
+
+        let algorithms = vec!["md5".to_string(), "sha256".to_string()];
+        let expected = "5eb63bbbe01eeed093cb22bb8f5acdc3";
+        let result = hash_regular_file(&path, &algorithms, Some(expected), |_, _| {}).unwrap();
+
+        assert_eq!(result.size, 11);
+        assert_eq!(result.digests.len(), 2);
+        assert_eq!(result.digests[0].hash, expected);
+        assert_eq!(result.digests[1].algorithm, "SHA-256");
+        assert!(!result.unstable);
+        assert_eq!(result.match_result, Some(HashMatchResult::Exact));
+    }
+
+    #[test]
+    fn test_hash_regular_file_reads_once_for_all_algorithms() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("sample.bin");
+        let data: Vec<u8> = (0..300_000u32).map(|i| (i % 251) as u8).collect();
+        std::fs::write(&path, &data).unwrap();
+
+        let algorithms = vec!["md5".to_string(), "sha1".to_string(), "blake3".to_string()];
+        let mut reports = Vec::new();
+        let result = hash_regular_file(&path, &algorithms, None, |current, total| reports.push((current, total))).unwrap();
+
+        // Progress covers a single pass over the file
+        assert!(reports.iter().all(|&(current, total)| total == data.len() as u64 && current <= total));
+        assert_eq!(reports.last(), Some(&(data.len() as u64, data.len() as u64)));
+        for (digest, algorithm) in result.digests.iter().zip([HashAlgorithm::Md5, HashAlgorithm::Sha1, HashAlgorithm::Blake3]) {
+            assert_eq!(digest.hash, compute_hash(&data, algorithm));
+        }
+    }
+
+    #[test]
+    fn test_hash_regular_file_matches_expected_among_same_length_digests() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("keywords.txt");
+        std::fs::write(&path, b"hello world").unwrap();
+
+        // SHA-256 and BLAKE3 are both 64 hex characters
+        let algorithms = vec!["sha256".to_string(), "blake3".to_string()];
+        let blake3 = compute_hash(b"hello world", HashAlgorithm::Blake3);
+        let result = hash_regular_file(&path, &algorithms, Some(&blake3), |_, _| {}).unwrap();
+        assert_eq!(result.match_result, Some(HashMatchResult::Exact));
+
+        let other = compute_hash(b"hello there", HashAlgorithm::Blake3);
+        let result = hash_regular_file(&path, &algorithms, Some(&other), |_, _| {}).unwrap();
+        assert_eq!(result.match_result, Some(HashMatchResult::Mismatch));
+
+        // Grouped and uppercased as in a log, still the same hash
+        let grouped: Vec<String> = blake3.to_uppercase().as_bytes().chunks(8)
+            .map(|group| String::from_utf8(group.to_vec()).unwrap())
+            .collect();
+        let result = hash_regular_file(&path, &algorithms, Some(&grouped.join(" ")), |_, _| {}).unwrap();
+        assert_eq!(result.match_result, Some(HashMatchResult::CaseInsensitive));
+
+        let result = hash_regular_file(&path, &algorithms, Some("not a hash"), |_, _| {}).unwrap();
+        assert_eq!(result.match_result, Some(HashMatchResult::Invalid));
+    }
+
+    #[test]
+    fn test_same_algorithm() {
+        assert!(same_algorithm("SHA-1", "sha1"));
+        assert!(same_algorithm("sha3_256", "SHA3-256"));
+        assert!(!same_algorithm("SHA-256", "BLAKE3"));
+        assert!(same_algorithm("Tiger", "tiger "));
+    }
+
+    #[test]
+    fn test_hash_regular_file_rejects_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        let err = hash_regular_file(dir.path(), &["md5".to_string()], None, |_, _| {}).unwrap_err();
+        assert!(err.contains("directory"));
+    }
+
+    #[test]
+    fn test_tuned_hashing_matches_default() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("sample.bin");
+        let data: Vec<u8> = (0..300_000u32).map(|i| (i % 251) as u8).collect();
+        std::fs::write(&path, &data).unwrap();
+
+        let tuning = HashTuning { buffer_size: Some(1), blake3_threads: Some(2) };
+        assert_eq!(tuning.buffer_size(), MIN_TUNED_BUFFER_SIZE);
+        assert_eq!(HashTuning::default().buffer_size(), BUFFER_SIZE);
+        for algorithm in ["blake3", "sha256"] {
+            let mut updates = 0;
+            let tuned = hash_file_with_tuning(&path, algorithm, &tuning, |_, _| updates += 1).unwrap();
+            assert_eq!(tuned, hash_file(&path, algorithm).unwrap(), "{algorithm}");
+            assert!(updates > 1, "{algorithm} reported progress per tuned buffer");
+        }
+        assert_eq!(compute_hash_with_tuning(&data, HashAlgorithm::Blake3, &tuning),
+            compute_hash(&data, HashAlgorithm::Blake3));
+    }
+
+    #[test]
+    fn test_hash_value_normalizes_tool_formats() {
+        let md5 = "28a9b613d6eefe8a0515ef0a675bdebd";
+        // Forensic MD5 groups segment hashes in blocks of 8
+        let grouped = HashValue::parse("28A9B613 D6EEFE8A 0515EF0A 675BDEBD", HashAlgorithm::Md5).unwrap();
+        assert_eq!(grouped.as_str(), md5);
+        // EnCase reports uppercase, Windows logs leave a trailing CR
+        let encase = HashValue::parse("28A9B613D6EEFE8A0515EF0A675BDEBD", HashAlgorithm::Md5).unwrap();
+        let stray = HashValue::parse_named(" 28a9b613d6eefe8a0515ef0a675bdebd\r\n", "MD5").unwrap();
+        assert_eq!(grouped, encase);
+        assert_eq!(encase, stray);
+        assert_eq!(HashValue::parse("28:a9:b6:13:d6:ee:fe:8a:05:15:ef:0a:67:5b:de:bd", HashAlgorithm::Md5).unwrap(), encase);
+        assert_eq!(serde_json::to_string(&encase).unwrap(), format!("\"{md5}\""));
+
+        assert!(hashes_match("28A9B613 D6EEFE8A 0515EF0A 675BDEBD\r", md5));
+        assert_eq!(compare_hashes(md5, md5), HashMatchResult::Exact);
+        assert_eq!(compare_hashes(md5, "28A9B613 D6EEFE8A 0515EF0A 675BDEBD"), HashMatchResult::CaseInsensitive);
+        assert_eq!(compare_hashes(md5, "28a9b613"), HashMatchResult::Mismatch);
+    }
+
+    #[test]
+    fn test_hash_value_rejects_malformed_values() {
+        let md5 = "28a9b613d6eefe8a0515ef0a675bdebd";
+        assert!(HashValue::parse(md5, HashAlgorithm::Sha1).unwrap_err().contains("40 hex characters"));
+        assert!(HashValue::parse("", HashAlgorithm::Md5).is_err());
+        assert!("\r\n".parse::<HashValue>().is_err());
+        assert!("28a9b613-d6eefe8a".parse::<HashValue>().is_err());
+        // Unknown algorithm names skip the length check only
+        assert!(HashValue::parse_named("abcd", "custom").is_ok());
+        assert!(!hashes_match("not a hash", "not a hash"));
+        assert_eq!(compare_hashes(md5, "zz"), HashMatchResult::Invalid);
+    }
 }
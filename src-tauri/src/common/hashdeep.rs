@@ -0,0 +1,444 @@
+// hashdeep audit files - export and audit in hashdeep's own format
+//
+// hashdeep writes a short header followed by one row per file:
+//
+//   %%%% HASHDEEP-1.0
+//   %%%% size,md5,sha256,filename
+//   ## Invoked from: /home/examiner
+//   ## $ hashdeep -c md5,sha256 -r evidence
+//   ##
+//   11,5eb63bbbe01eeed093cb22bb8f5acdc3,b94d27b9...,/home/examiner/evidence/hello.txt
+//
+// The hash columns always come in hashdeep's own order (md5, sha1, sha256),
+// whatever order they were requested in. The filename is the last column and
+// is written as-is, so a comma in a name needs no escaping: readers split off
+// the fixed columns and keep the rest of the line. A line break would end the
+// row, so files whose names contain one are left out of an export and listed
+// instead.
+//
+// An audit hashes a directory with the algorithms named in the file's header
+// and sorts every file into hashdeep's categories: matched (known hashes at
+// the known path), moved (known hashes at another path), changed (a known
+// path with other hashes) and new, plus the known files that were found
+// neither by path nor by hashes (missing). Known paths match either the
+// file's full path or its path relative to the audited directory, so a set
+// hashed on another machine can still be matched by location.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use rayon::prelude::*;
+use serde::Serialize;
+use tracing::{debug, info};
+
+use super::atomic_file::write_atomic;
+use super::integrity;
+use super::hash::{hash_regular_file, HashAlgorithm};
+
+/// First line of every hashdeep file
+pub const HASHDEEP_HEADER: &str = "%%%% HASHDEEP-1.0";
+
+/// Hash columns hashdeep files can hold that we compute, in hashdeep's order
+const COLUMNS: [(&str, HashAlgorithm); 3] = [
+    ("md5", HashAlgorithm::Md5),
+    ("sha1", HashAlgorithm::Sha1),
+    ("sha256", HashAlgorithm::Sha256),
+];
+
+/// One row of a hashdeep file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HashdeepEntry {
+    pub size: u64,
+    /// Lowercase hex, one per column of the file
+    pub hashes: Vec<String>,
+    pub path: String,
+}
+
+/// A parsed (or to be written) hashdeep file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HashdeepFile {
+    /// Hash column names (`md5`, `sha1`, `sha256`), in file order
+    pub columns: Vec<String>,
+    pub entries: Vec<HashdeepEntry>,
+}
+
+/// A file left out of an export or audit
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SkippedFile {
+    pub path: String,
+    pub reason: String,
+}
+
+/// Outcome of [`export`]
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HashdeepExport {
+    pub output_path: String,
+    pub columns: Vec<String>,
+    pub files_written: usize,
+    pub skipped: Vec<SkippedFile>,
+}
+
+/// A known file found at another path
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MovedFile {
+    pub path: String,
+    /// Where the audit file recorded it
+    pub known_path: String,
+}
+
+/// Outcome of [`audit`], in hashdeep's categories
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HashdeepAudit {
+    pub matched: Vec<String>,
+    pub moved: Vec<MovedFile>,
+    pub changed: Vec<String>,
+    pub new: Vec<String>,
+    /// Known paths found neither by path nor by hashes
+    pub missing: Vec<String>,
+    /// Files of the directory that could not be hashed
+    pub unreadable: Vec<SkippedFile>,
+    /// Every file matched and every known file was found
+    pub passed: bool,
+}
+
+/// Column names for `algorithms`, deduplicated and in hashdeep's order
+pub fn columns_for(algorithms: &[String]) -> Result<Vec<String>, String> {
+    if algorithms.is_empty() {
+        return Err("No hash algorithm specified".to_string());
+    }
+    let mut wanted = Vec::new();
+    for algorithm in algorithms {
+        let algo = HashAlgorithm::from_str(algorithm)?;
+        if !COLUMNS.iter().any(|(_, column)| *column == algo) {
+            return Err(format!("hashdeep files have no {} column (md5, sha1 and sha256 only)", algo.name()));
+        }
+        wanted.push(algo);
+    }
+    Ok(COLUMNS.iter()
+        .filter(|(_, algo)| wanted.contains(algo))
+        .map(|(name, _)| name.to_string())
+        .collect())
+}
+
+impl HashdeepFile {
+    /// Parse a hashdeep file; Windows line endings and repeated headers
+    /// (concatenated files) are accepted
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut lines = text.lines().map(|line| line.trim_end_matches('\r')).enumerate();
+        match lines.by_ref().find(|(_, line)| !line.is_empty()) {
+            Some((_, HASHDEEP_HEADER)) => {}
+            _ => return Err(format!("Not a hashdeep file (no {HASHDEEP_HEADER} line)")),
+        }
+
+        let mut columns: Option<Vec<String>> = None;
+        let mut entries = Vec::new();
+        for (idx, line) in lines {
+            let line_no = idx + 1;
+            if line.is_empty() || line.starts_with("##") || line == HASHDEEP_HEADER {
+                continue;
+            }
+            if let Some(header) = line.strip_prefix("%%%% ") {
+                let parsed = parse_columns(header).map_err(|e| format!("Line {line_no}: {e}"))?;
+                if columns.as_ref().is_some_and(|known| *known != parsed) {
+                    return Err(format!("Line {line_no}: the columns change part-way through the file"));
+                }
+                columns = Some(parsed);
+                continue;
+            }
+            let Some(columns) = &columns else {
+                return Err(format!("Line {line_no}: row before the %%%% size,...,filename header"));
+            };
+            let mut fields = line.splitn(columns.len() + 2, ',');
+            let size = fields.next()
+                .and_then(|size| size.parse().ok())
+                .ok_or_else(|| format!("Line {line_no}: invalid file size"))?;
+            let hashes: Vec<String> = fields.by_ref().take(columns.len()).map(str::to_lowercase).collect();
+            let path = fields.next().filter(|path| !path.is_empty())
+                .ok_or_else(|| format!("Line {line_no}: expected {} columns", columns.len() + 2))?;
+            entries.push(HashdeepEntry { size, hashes, path: path.to_string() });
+        }
+
+        let columns = columns.ok_or("hashdeep file has no %%%% size,...,filename header")?;
+        Ok(Self { columns, entries })
+    }
+
+    /// The file as hashdeep writes it: header, `## Invoked from:` and
+    /// `## $` command lines, then one row per entry
+    pub fn to_text(&self, invoked_from: &str, command: &str) -> String {
+        let mut text = format!(
+            "{HASHDEEP_HEADER}\n%%%% size,{},filename\n## Invoked from: {invoked_from}\n## $ {command}\n##\n",
+            self.columns.join(","),
+        );
+        for entry in &self.entries {
+            text.push_str(&format!("{},{},{}\n", entry.size, entry.hashes.join(","), entry.path));
+        }
+        text
+    }
+}
+
+/// Hash column names of a `%%%% size,...,filename` header line
+fn parse_columns(header: &str) -> Result<Vec<String>, String> {
+    let fields: Vec<&str> = header.split(',').map(str::trim).collect();
+    let (Some(&"size"), Some(&"filename")) = (fields.first(), fields.last()) else {
+        return Err(format!("unexpected header '{header}' (expected size,...,filename)"));
+    };
+    let hashes = &fields[1..fields.len() - 1];
+    if hashes.is_empty() {
+        return Err("header names no hash column".to_string());
+    }
+    hashes.iter()
+        .map(|name| match COLUMNS.iter().find(|(column, _)| column.eq_ignore_ascii_case(name)) {
+            Some((column, _)) => Ok(column.to_string()),
+            None => Err(format!("{name} columns are not supported (md5, sha1 and sha256 only)")),
+        })
+        .collect()
+}
+
+/// Regular files under `paths` (directories recursively, symlinks not
+/// followed), sorted within each directory
+pub fn collect_files(paths: &[String]) -> Result<Vec<PathBuf>, String> {
+    fn walk(dir: &Path, files: &mut Vec<PathBuf>) -> Result<(), String> {
+        let mut children: Vec<PathBuf> = std::fs::read_dir(dir)
+            .map_err(|e| format!("Failed to read directory {}: {}", dir.display(), e))?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .collect();
+        children.sort();
+        for child in children {
+            let Ok(meta) = std::fs::symlink_metadata(&child) else { continue };
+            if meta.is_dir() {
+                walk(&child, files)?;
+            } else if meta.is_file() {
+                files.push(child);
+            }
+        }
+        Ok(())
+    }
+
+    let mut files = Vec::new();
+    for path in paths {
+        let path = Path::new(path);
+        let meta = std::fs::metadata(path)
+            .map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+        if meta.is_dir() {
+            walk(path, &mut files)?;
+        } else {
+            files.push(path.to_path_buf());
+        }
+    }
+    Ok(files)
+}
+
+/// Hash `files` in parallel with the algorithms of `columns`, keeping their
+/// order; `progress` gets (files done, files total)
+fn hash_entries<F>(files: &[PathBuf], columns: &[String], progress: F) -> (Vec<HashdeepEntry>, Vec<SkippedFile>)
+where
+    F: Fn(usize, usize) + Sync,
+{
+    let done = AtomicUsize::new(0);
+    let results: Vec<Result<HashdeepEntry, SkippedFile>> = files.par_iter()
+        .map(|file| {
+            let result = hash_regular_file(file, columns, None, |_, _| {});
+            progress(done.fetch_add(1, Ordering::Relaxed) + 1, files.len());
+            let path = file.to_string_lossy().to_string();
+            match result {
+                Ok(hashed) => Ok(HashdeepEntry {
+                    size: hashed.size,
+                    hashes: hashed.digests.into_iter().map(|digest| digest.hash.to_lowercase()).collect(),
+                    path,
+                }),
+                Err(reason) => Err(SkippedFile { path, reason }),
+            }
+        })
+        .collect();
+
+    let mut entries = Vec::with_capacity(results.len());
+    let mut skipped = Vec::new();
+    for result in results {
+        match result {
+            Ok(entry) => entries.push(entry),
+            Err(file) => skipped.push(file),
+        }
+    }
+    (entries, skipped)
+}
+
+/// Hash the files under `paths` and write them to `output` as a hashdeep
+/// file with the `algorithms` columns (md5, sha1, sha256)
+///
+/// Unreadable files and names containing a line break are listed as
+/// skipped; `output` itself is never hashed.
+pub fn export<F>(paths: &[String], algorithms: &[String], output: &Path, progress: F) -> Result<HashdeepExport, String>
+where
+    F: Fn(usize, usize) + Sync,
+{
+    let columns = columns_for(algorithms)?;
+    let mut files = collect_files(paths)?;
+    files.retain(|file| file != output);
+
+    let mut skipped = Vec::new();
+    files.retain(|file| {
+        let name = file.to_string_lossy();
+        let writable = !name.contains(['\n', '\r']);
+        if !writable {
+            skipped.push(SkippedFile {
+                path: name.to_string(),
+                reason: "name contains a line break, which a hashdeep row can't hold".to_string(),
+            });
+        }
+        writable
+    });
+
+    info!(files = files.len(), columns = ?columns, output = %output.display(), "Exporting hashdeep file");
+    let (entries, unreadable) = hash_entries(&files, &columns, progress);
+    skipped.extend(unreadable);
+
+    let invoked_from = std::env::current_dir()
+        .map(|dir| dir.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let command = format!("core-ffx export_hashdeep -c {} {}", columns.join(","), paths.join(" "));
+    let file = HashdeepFile { columns, entries };
+    write_atomic(output, integrity::tag_text_if_enabled(file.to_text(&invoked_from, &command))?)
+        .map_err(|e| format!("Failed to write {}: {}", output.display(), e))?;
+
+    Ok(HashdeepExport {
+        output_path: output.to_string_lossy().to_string(),
+        columns: file.columns,
+        files_written: file.entries.len(),
+        skipped,
+    })
+}
+
+/// Forward slashes, so paths recorded on Windows compare with ours
+fn path_key(path: &str) -> String {
+    path.replace('\\', "/")
+}
+
+/// Audit the files under `dir` against the hashdeep file `audit_file`
+pub fn audit<F>(dir: &Path, audit_file: &Path, progress: F) -> Result<HashdeepAudit, String>
+where
+    F: Fn(usize, usize) + Sync,
+{
+    let text = std::fs::read_to_string(audit_file)
+        .map_err(|e| format!("Failed to read {}: {}", audit_file.display(), e))?;
+    let known = HashdeepFile::parse(&text)?;
+    if !dir.is_dir() {
+        return Err(format!("{} is not a directory", dir.display()));
+    }
+    let mut files = collect_files(&[dir.to_string_lossy().to_string()])?;
+    files.retain(|file| file != audit_file);
+    info!(files = files.len(), known = known.entries.len(), dir = %dir.display(), "Auditing against hashdeep file");
+    let (entries, unreadable) = hash_entries(&files, &known.columns, progress);
+
+    let by_path: HashMap<String, usize> = known.entries.iter().enumerate()
+        .map(|(idx, entry)| (path_key(&entry.path), idx))
+        .collect();
+    let mut by_hashes: HashMap<(u64, &[String]), Vec<usize>> = HashMap::new();
+    for (idx, entry) in known.entries.iter().enumerate() {
+        by_hashes.entry((entry.size, entry.hashes.as_slice())).or_default().push(idx);
+    }
+
+    let mut report = HashdeepAudit { unreadable, ..Default::default() };
+    let mut seen = vec![false; known.entries.len()];
+    for entry in &entries {
+        let relative = Path::new(&entry.path).strip_prefix(dir).ok()
+            .map(|relative| path_key(&relative.to_string_lossy()));
+        let at_path = by_path.get(&path_key(&entry.path)).copied()
+            .or_else(|| relative.and_then(|relative| by_path.get(&relative).copied()));
+        let same_hashes = by_hashes.get(&(entry.size, entry.hashes.as_slice()));
+
+        if let Some(idx) = at_path {
+            seen[idx] = true;
+            if known.entries[idx].size == entry.size && known.entries[idx].hashes == entry.hashes {
+                report.matched.push(entry.path.clone());
+                continue;
+            }
+        }
+        match (same_hashes, at_path) {
+            (Some(candidates), _) => {
+                // Prefer a known file not accounted for yet
+                let idx = candidates.iter().copied().find(|idx| !seen[*idx]).unwrap_or(candidates[0]);
+                seen[idx] = true;
+                report.moved.push(MovedFile { path: entry.path.clone(), known_path: known.entries[idx].path.clone() });
+            }
+            (None, Some(_)) => report.changed.push(entry.path.clone()),
+            (None, None) => report.new.push(entry.path.clone()),
+        }
+    }
+    report.missing = known.entries.iter().zip(&seen)
+        .filter(|(_, seen)| !**seen)
+        .map(|(entry, _)| entry.path.clone())
+        .collect();
+    report.passed = report.moved.is_empty() && report.changed.is_empty() && report.new.is_empty()
+        && report.missing.is_empty() && report.unreadable.is_empty();
+    debug!(matched = report.matched.len(), moved = report.moved.len(), changed = report.changed.len(),
+        new = report.new.len(), missing = report.missing.len(), "hashdeep audit finished");
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Audit file in hashdeep's layout, with a comma in one name
+    const FIXTURE: &str = include_str!("testdata/evidence.hashdeep");
+
+    #[test]
+    fn test_fixture_round_trips_byte_for_byte() {
+        let file = HashdeepFile::parse(FIXTURE).unwrap();
+        assert_eq!(file.columns, ["md5", "sha256"]);
+        assert_eq!(file.entries.len(), 3);
+        assert_eq!(file.entries[1].path, "/home/examiner/evidence/notes, draft.txt");
+        assert_eq!(file.entries[1].size, 13);
+        assert_eq!(file.to_text("/home/examiner", "hashdeep -c md5,sha256 -r evidence"), FIXTURE);
+
+        // Windows line endings read the same
+        assert_eq!(HashdeepFile::parse(&FIXTURE.replace('\n', "\r\n")).unwrap(), file);
+        assert!(HashdeepFile::parse("size,md5,filename\n").is_err());
+        assert_eq!(columns_for(&["SHA-256".to_string(), "md5".to_string()]).unwrap(), ["md5", "sha256"]);
+        assert!(columns_for(&["blake3".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_export_then_audit_sorts_changes_into_categories() {
+        let dir = tempfile::tempdir().unwrap();
+        let evidence = dir.path().join("evidence");
+        std::fs::create_dir_all(evidence.join("sub")).unwrap();
+        std::fs::write(evidence.join("hello.txt"), b"hello world").unwrap();
+        std::fs::write(evidence.join("notes, draft.txt"), b"Hello, world!").unwrap();
+        std::fs::write(evidence.join("sub").join("moved.bin"), b"moves").unwrap();
+        std::fs::write(evidence.join("changed.bin"), b"before").unwrap();
+        std::fs::write(evidence.join("deleted.bin"), b"deleted").unwrap();
+        let audit_file = dir.path().join("evidence.hashdeep");
+
+        let paths = [evidence.to_string_lossy().to_string()];
+        let exported = export(&paths, &["sha256".to_string(), "md5".to_string()], &audit_file, |_, _| {}).unwrap();
+        assert_eq!(exported.files_written, 5);
+        assert!(exported.skipped.is_empty());
+        let written = std::fs::read_to_string(&audit_file).unwrap();
+        assert!(written.starts_with("%%%% HASHDEEP-1.0\n%%%% size,md5,sha256,filename\n## Invoked from: "));
+        assert!(written.contains(&format!("13,6cd3556deb0da54bca060b4c39479839,315f5bdb76d078c43b8ac0064e4a0164612b1fce77c869345bfc94c75894edd3,{}\n",
+            evidence.join("notes, draft.txt").display())));
+
+        std::fs::rename(evidence.join("sub").join("moved.bin"), evidence.join("moved.bin")).unwrap();
+        std::fs::write(evidence.join("changed.bin"), b"after").unwrap();
+        std::fs::remove_file(evidence.join("deleted.bin")).unwrap();
+        std::fs::write(evidence.join("new.bin"), b"new").unwrap();
+
+        let report = audit(&evidence, &audit_file, |_, _| {}).unwrap();
+        let name = |path: &str| Path::new(path).file_name().unwrap().to_string_lossy().to_string();
+        assert_eq!(report.matched.iter().map(|p| name(p)).collect::<Vec<_>>(), ["hello.txt", "notes, draft.txt"]);
+        assert_eq!(report.moved.len(), 1);
+        assert!(report.moved[0].known_path.ends_with("moved.bin") && report.moved[0].known_path.contains("sub"));
+        assert_eq!(report.changed.iter().map(|p| name(p)).collect::<Vec<_>>(), ["changed.bin"]);
+        assert_eq!(report.new.iter().map(|p| name(p)).collect::<Vec<_>>(), ["new.bin"]);
+        assert_eq!(report.missing.iter().map(|p| name(p)).collect::<Vec<_>>(), ["deleted.bin"]);
+        assert!(!report.passed);
+    }
+}
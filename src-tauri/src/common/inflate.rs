@@ -0,0 +1,113 @@
+// Bounded zlib inflation for container chunks
+//
+// Chunk and section sizes come from the container itself, so a corrupt or
+// crafted image can pair a tiny compressed chunk with a multi-gigabyte
+// expansion. Chunk decompression goes through `inflate_bounded`, which stops
+// one byte past the caller's limit instead of reading the stream to its end.
+
+use std::fmt;
+use std::io::{self, Read};
+
+use flate2::read::ZlibDecoder;
+
+/// Largest chunk size accepted from a container header (64 MiB)
+pub const MAX_CHUNK_SIZE: u64 = 64 * 1024 * 1024;
+
+/// Allowance over the expected chunk size before a chunk counts as inflated
+pub const INFLATE_SLACK: usize = 4096;
+
+/// Why a bounded inflate failed
+#[derive(Debug)]
+pub enum InflateError {
+    /// Corrupt or truncated zlib stream, or the underlying read failed
+    Io(io::Error),
+    /// The stream expands past the limit - the container structure is corrupt
+    Oversized { limit: usize },
+}
+
+impl fmt::Display for InflateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InflateError::Io(e) => write!(f, "{}", e),
+            InflateError::Oversized { limit } => {
+                write!(f, "decompressed data exceeds {} bytes (corrupt structure)", limit)
+            }
+        }
+    }
+}
+
+/// Inflate a zlib stream, refusing to produce more than `limit` bytes
+///
+/// `expected` only sizes the initial buffer; pass the declared chunk size.
+pub fn inflate_bounded<R: Read>(compressed: R, expected: usize, limit: usize) -> Result<Vec<u8>, InflateError> {
+    inflate_bounded_counted(compressed, expected, limit).map(|(output, _)| output)
+}
+
+/// [`inflate_bounded`], also returning how many compressed bytes the
+/// stream took up
+pub fn inflate_bounded_counted<R: Read>(compressed: R, expected: usize, limit: usize) -> Result<(Vec<u8>, u64), InflateError> {
+    let mut output = Vec::with_capacity(expected.min(limit));
+    let mut decoder = ZlibDecoder::new(compressed);
+    (&mut decoder)
+        .take(limit as u64 + 1)
+        .read_to_end(&mut output)
+        .map_err(InflateError::Io)?;
+    if output.len() > limit {
+        return Err(InflateError::Oversized { limit });
+    }
+    Ok((output, decoder.total_in()))
+}
+
+/// Validate a chunk size declared by a container header
+pub fn check_chunk_size(chunk_size: u64, what: &str) -> Result<usize, String> {
+    if chunk_size == 0 || chunk_size > MAX_CHUNK_SIZE {
+        return Err(format!(
+            "Corrupt structure: {} of {} bytes is outside 1..={} bytes",
+            what, chunk_size, MAX_CHUNK_SIZE
+        ));
+    }
+    Ok(chunk_size as usize)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::ZlibEncoder;
+    use flate2::Compression;
+    use std::io::Write;
+
+    fn deflate(data: &[u8]) -> Vec<u8> {
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::best());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    #[test]
+    fn test_inflate_bounded_rejects_bombs_and_truncation() {
+        let chunk = vec![0x41u8; 32 * 1024];
+        let compressed = deflate(&chunk);
+        assert_eq!(inflate_bounded(&compressed[..], chunk.len(), chunk.len() + INFLATE_SLACK).unwrap(), chunk);
+        // Stored chunks are followed by other data; only the stream is counted
+        let stored = [&compressed[..], &[0xAA; 64][..]].concat();
+        let (output, consumed) = inflate_bounded_counted(&stored[..], chunk.len(), chunk.len() + INFLATE_SLACK).unwrap();
+        assert_eq!((output.len(), consumed), (chunk.len(), compressed.len() as u64));
+
+        // 16 MiB of zeros deflates to less than one uncompressed chunk
+        let bomb = deflate(&vec![0u8; 16 * 1024 * 1024]);
+        assert!(bomb.len() < chunk.len());
+        let err = inflate_bounded(&bomb[..], chunk.len(), chunk.len() + INFLATE_SLACK).unwrap_err();
+        assert!(matches!(err, InflateError::Oversized { limit } if limit == chunk.len() + INFLATE_SLACK));
+
+        // Truncated and garbage streams fail instead of returning short data
+        let truncated = &compressed[..compressed.len() / 2];
+        assert!(matches!(inflate_bounded(truncated, chunk.len(), chunk.len()), Err(InflateError::Io(_))));
+        assert!(matches!(inflate_bounded(&[0x78, 0x9c, 0xff, 0xff][..], 16, 16), Err(InflateError::Io(_))));
+    }
+
+    #[test]
+    fn test_check_chunk_size() {
+        assert_eq!(check_chunk_size(32 * 1024, "chunk size").unwrap(), 32 * 1024);
+        assert!(check_chunk_size(0, "chunk size").is_err());
+        assert!(check_chunk_size(u32::MAX as u64 * 512, "chunk size").is_err());
+    }
+}
@@ -0,0 +1,314 @@
+// Integrity tags on exports that travel between our own systems
+//
+// With `integrityTags` on in the settings, extraction manifests, audit log
+// exports, exported hash lists and content maps, and report JSON carry a
+// keyed BLAKE3 tag (see `hash::KeyedBlake3`) made with a secret kept in
+// the settings folder. Whoever edits such a file can recompute a SHA-256
+// for it, but not the tag without that secret; `verify_file` checks it.
+//
+// A tag is not an evidentiary hash. Evidence is hashed with the standard
+// algorithms so anyone can recompute the value; a tag only says "written,
+// unchanged since, by an installation holding this key".
+//
+// Text exports (CSV, hash lists, hashdeep, JSONL) end with a tag line
+// covering every byte before it:
+//
+//   ## integrity_tag: blake3-keyed:<64 hex digits>
+//
+// The leading `#` keeps hash-list, hashdeep and CSV comment handling
+// skipping it. JSON objects get a last "integrity_tag" field instead,
+// covering the text of the object before it.
+
+use std::fs::{self, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use tracing::info;
+
+use super::hash::KeyedBlake3;
+use crate::settings;
+
+/// File name of the secret in the settings folder
+pub const KEY_FILE_NAME: &str = "integrity.key";
+
+/// Name of the tag line and JSON field
+pub const TAG_FIELD: &str = "integrity_tag";
+
+/// Prefix of a tag's value, naming how it was made
+const TAG_SCHEME: &str = "blake3-keyed:";
+
+/// BLAKE3 `derive_key` context of the tag key; a new scheme gets a new context
+const TAG_CONTEXT: &str = "ffx-check export integrity tag v1";
+
+/// Secret of one installation, from which tag keys are derived
+pub struct IntegrityKey([u8; 32]);
+
+impl IntegrityKey {
+    pub fn from_bytes(secret: [u8; 32]) -> Self {
+        Self(secret)
+    }
+
+    /// This installation's secret, created on first use
+    pub fn installation() -> Result<Self, String> {
+        Self::load_or_create(&key_path())
+    }
+
+    /// Read the 32-byte secret at `path`, or create it from the OS random
+    /// source; on Unix the file is readable by its owner only
+    pub fn load_or_create(path: &Path) -> Result<Self, String> {
+        match fs::read(path) {
+            Ok(bytes) => return Self::parse(path, &bytes),
+            Err(e) if e.kind() == ErrorKind::NotFound => {}
+            Err(e) => return Err(format!("Failed to read integrity key {}: {}", path.display(), e)),
+        }
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+        let mut secret = [0u8; 32];
+        getrandom::getrandom(&mut secret).map_err(|e| format!("No random source for the integrity key: {}", e))?;
+
+        let mut options = OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let mut file = match options.open(path) {
+            Ok(file) => file,
+            // Created meanwhile by another call: use that one
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => {
+                let bytes = fs::read(path).map_err(|e| format!("Failed to read integrity key {}: {}", path.display(), e))?;
+                return Self::parse(path, &bytes);
+            }
+            Err(e) => return Err(format!("Failed to create integrity key {}: {}", path.display(), e)),
+        };
+        file.write_all(&secret)
+            .and_then(|_| file.sync_all())
+            .map_err(|e| format!("Failed to write integrity key {}: {}", path.display(), e))?;
+        info!(path = %path.display(), "Created integrity key");
+        Ok(Self(secret))
+    }
+
+    fn parse(path: &Path, bytes: &[u8]) -> Result<Self, String> {
+        <[u8; 32]>::try_from(bytes)
+            .map(Self)
+            .map_err(|_| format!("Integrity key {} is damaged ({} bytes, expected 32)", path.display(), bytes.len()))
+    }
+
+    fn hasher(&self) -> KeyedBlake3 {
+        KeyedBlake3::derived(TAG_CONTEXT, &self.0)
+    }
+
+    /// Tag value of `data` ("blake3-keyed:<hex>")
+    pub fn tag(&self, data: &[u8]) -> String {
+        format!("{}{}", TAG_SCHEME, self.hasher().update(data).finalize_hex())
+    }
+
+    /// Tags text written piece by piece, for a tag line at its end
+    pub fn text_tagger(&self) -> TextTagger {
+        TextTagger(self.hasher())
+    }
+
+    fn matches(&self, data: &[u8], tag: &str) -> bool {
+        tag.strip_prefix(TAG_SCHEME).is_some_and(|hex| self.hasher().update(data).matches_hex(hex))
+    }
+}
+
+/// Running tag of text being written; see [`IntegrityKey::text_tagger`]
+pub struct TextTagger(KeyedBlake3);
+
+impl TextTagger {
+    pub fn update(&mut self, data: &[u8]) {
+        self.0.update(data);
+    }
+
+    /// The tag line to write after everything passed to `update`, which
+    /// must end with a line break
+    pub fn tag_line(&self) -> String {
+        tag_line(&format!("{}{}", TAG_SCHEME, self.0.finalize_hex()))
+    }
+}
+
+fn tag_line(tag: &str) -> String {
+    format!("## {}: {}\n", TAG_FIELD, tag)
+}
+
+/// Whether `line` is a tag line (readers of tagged text skip it)
+pub fn is_tag_line(line: &str) -> bool {
+    line.trim_end().starts_with(&format!("## {}: ", TAG_FIELD))
+}
+
+/// Key file in the settings folder
+pub fn key_path() -> PathBuf {
+    let settings_path = settings::store().map(|store| store.path().to_path_buf()).unwrap_or_else(settings::default_path);
+    settings_path.parent().unwrap_or(Path::new(".")).join(KEY_FILE_NAME)
+}
+
+/// The installation's key when tags are on in the settings
+pub fn enabled_key() -> Result<Option<IntegrityKey>, String> {
+    if !settings::current().integrity_tags {
+        return Ok(None);
+    }
+    IntegrityKey::installation().map(Some)
+}
+
+/// `text` followed by a tag line
+pub fn tag_text(key: &IntegrityKey, text: &str) -> String {
+    let mut tagger = key.text_tagger();
+    let mut out = text.to_string();
+    if !out.is_empty() && !out.ends_with('\n') {
+        out.push('\n');
+    }
+    tagger.update(out.as_bytes());
+    out.push_str(&tagger.tag_line());
+    out
+}
+
+/// A JSON object's text with a last `integrity_tag` field
+pub fn tag_json(key: &IntegrityKey, json: &str) -> Result<String, String> {
+    let body = json.trim_end().strip_suffix('}').map(str::trim_end)
+        .ok_or("Only a JSON object can carry an integrity tag")?;
+    let separator = if body.ends_with('{') { "" } else { "," };
+    Ok(format!("{}{}\n  \"{}\": \"{}\"\n}}\n", body, separator, TAG_FIELD, key.tag(body.as_bytes())))
+}
+
+/// [`tag_text`] with the installation's key when tags are on, else `text`
+pub fn tag_text_if_enabled(text: String) -> Result<String, String> {
+    Ok(match enabled_key()? {
+        Some(key) => tag_text(&key, &text),
+        None => text,
+    })
+}
+
+/// [`tag_json`] with the installation's key when tags are on, else `json`
+pub fn tag_json_if_enabled(json: String) -> Result<String, String> {
+    match enabled_key()? {
+        Some(key) => tag_json(&key, &json),
+        None => Ok(json),
+    }
+}
+
+/// Outcome of [`verify_file`]
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct IntegrityCheck {
+    pub path: String,
+    /// The file ends with a tag line or `integrity_tag` field
+    pub tagged: bool,
+    /// The tag matches the content under this installation's key
+    pub valid: bool,
+    pub error: Option<String>,
+}
+
+/// Check the tag of an export written with [`tag_text`] or [`tag_json`]
+pub fn verify_file(path: &Path, key: &IntegrityKey) -> Result<IntegrityCheck, String> {
+    let bytes = fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let (tagged, error) = match std::str::from_utf8(&bytes) {
+        Ok(text) => match verify_text(text, key) {
+            Some(Ok(())) => (true, None),
+            Some(Err(e)) => (true, Some(e)),
+            None => (false, Some("The file carries no integrity tag".to_string())),
+        },
+        Err(_) => (false, Some("Not a text export (invalid UTF-8)".to_string())),
+    };
+    Ok(IntegrityCheck { path: path.display().to_string(), tagged, valid: tagged && error.is_none(), error })
+}
+
+/// None if `text` has no tag
+fn verify_text(text: &str, key: &IntegrityKey) -> Option<Result<(), String>> {
+    let (covered, tag) = split_tag_line(text).or_else(|| split_json_tag(text))?;
+    Some(if key.matches(covered.as_bytes(), tag) {
+        Ok(())
+    } else {
+        Err("The tag does not match: the file was changed after tagging, or tagged with another installation's key".to_string())
+    })
+}
+
+/// (text before the tag line, tag) of tagged text
+fn split_tag_line(text: &str) -> Option<(&str, &str)> {
+    let body = text.strip_suffix('\n').unwrap_or(text);
+    let start = body.rfind('\n').map_or(0, |pos| pos + 1);
+    let tag = body[start..].trim_end().strip_prefix(&format!("## {}: ", TAG_FIELD))?;
+    Some((&text[..start], tag))
+}
+
+/// (object text before the tag field, tag) of a tagged JSON object
+fn split_json_tag(text: &str) -> Option<(&str, &str)> {
+    let marker = format!("\"{}\": \"", TAG_FIELD);
+    let pos = text.rfind(&marker)?;
+    let (tag, rest) = text[pos + marker.len()..].split_once('"')?;
+    if rest.trim() != "}" {
+        return None;
+    }
+    let before = text[..pos].trim_end();
+    Some((before.strip_suffix(',').unwrap_or(before), tag))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key() -> IntegrityKey {
+        IntegrityKey::from_bytes([7; 32])
+    }
+
+    /// The byte at `index` of `path` changed by one bit
+    fn flip_byte(path: &Path, index: usize) {
+        let mut bytes = fs::read(path).unwrap();
+        bytes[index] ^= 0x01;
+        fs::write(path, bytes).unwrap();
+    }
+
+    #[test]
+    fn test_tagged_text_and_json_verify_and_detect_one_changed_byte() {
+        let dir = tempfile::tempdir().unwrap();
+        let csv = dir.path().join("hashes.csv");
+        let json = dir.path().join("manifest.json");
+        let text = "FileName,FileSize,Algorithm,Hash\ndisk.001,1024,MD5,9e107d9d372bb6826bd81d3542a419d6";
+        fs::write(&csv, tag_text(&key(), text)).unwrap();
+        fs::write(&json, tag_json(&key(), "{\n  \"tool\": \"ffx-check\",\n  \"files\": []\n}").unwrap()).unwrap();
+
+        let tagged = fs::read_to_string(&csv).unwrap();
+        let line = tagged.lines().last().unwrap();
+        assert!(is_tag_line(line) && line.len() == "## integrity_tag: blake3-keyed:".len() + 64, "{line}");
+        assert_eq!(tag_text(&key(), text), tagged, "tags are deterministic");
+        let json_text = fs::read_to_string(&json).unwrap();
+        assert!(serde_json::from_str::<serde_json::Value>(&json_text).unwrap()[TAG_FIELD].as_str().unwrap().starts_with(TAG_SCHEME));
+
+        for path in [&csv, &json] {
+            let check = verify_file(path, &key()).unwrap();
+            assert!(check.tagged && check.valid, "{:?}", check.error);
+            // Another installation can't vouch for it
+            assert!(!verify_file(path, &IntegrityKey::from_bytes([8; 32])).unwrap().valid);
+
+            flip_byte(path, 12);
+            let check = verify_file(path, &key()).unwrap();
+            assert!(check.tagged && !check.valid);
+            assert!(check.error.unwrap().contains("does not match"));
+        }
+
+        fs::write(&csv, text).unwrap();
+        let untagged = verify_file(&csv, &key()).unwrap();
+        assert!(!untagged.tagged && !untagged.valid);
+    }
+
+    #[test]
+    fn test_installation_key_is_created_once_and_private() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config").join(KEY_FILE_NAME);
+        let first = IntegrityKey::load_or_create(&path).unwrap();
+        let again = IntegrityKey::load_or_create(&path).unwrap();
+        assert_eq!(first.0, again.0);
+        assert_ne!(first.0, [0; 32]);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+        }
+
+        fs::write(&path, b"short").unwrap();
+        let Err(error) = IntegrityKey::load_or_create(&path) else { panic!("accepted a short key file") };
+        assert!(error.contains("damaged"), "{error}");
+    }
+}
@@ -2,15 +2,41 @@
 //
 // Provides LRU caching for file handles when working with multi-segment
 // forensic images (E01, RAW, etc.) to avoid exceeding OS file descriptor limits.
+// Handles are `SegmentRead` trait objects so tests can put a failing reader
+// behind the pool (see `FileIoPool::with_opener`). Files opened by the
+// default opener retry transient network errors (see `retry`).
 
 use std::collections::{HashMap, VecDeque};
 use std::fs::File;
-use std::path::PathBuf;
-use tracing::{debug, trace};
+use std::io::{self, Read, Seek};
+use std::path::{Path, PathBuf};
+use tracing::{debug, trace, warn};
+
+use super::device;
+use super::retry::RetryingReader;
 
 /// Default maximum number of simultaneously open file handles
 pub const DEFAULT_MAX_OPEN_FILES: usize = 32;
 
+// =============================================================================
+// Segment Readers
+// =============================================================================
+
+/// Read access to one open segment file
+pub trait SegmentRead: Read + Seek + Send {
+    /// Size of the segment in bytes
+    fn size(&self) -> io::Result<u64>;
+}
+
+impl SegmentRead for File {
+    fn size(&self) -> io::Result<u64> {
+        Ok(self.metadata()?.len())
+    }
+}
+
+/// Opens segment files for a pool (defaults to a retrying `File::open`)
+pub type SegmentOpener = Box<dyn Fn(&Path) -> io::Result<Box<dyn SegmentRead>> + Send + Sync>;
+
 // =============================================================================
 // File I/O Pool - Like libbfio_pool
 // =============================================================================
@@ -20,12 +46,15 @@ pub const DEFAULT_MAX_OPEN_FILES: usize = 32;
 pub struct FileIoPool {
     /// Paths to all segment files in order
     file_paths: Vec<PathBuf>,
-    /// Currently open file handles (file_index -> File)
-    open_handles: HashMap<usize, File>,
+    /// Currently open file handles (file_index -> reader)
+    open_handles: HashMap<usize, Box<dyn SegmentRead>>,
     /// LRU queue for file handle management
     lru_queue: VecDeque<usize>,
     /// Maximum number of simultaneously open files
     max_open: usize,
+    /// Custom segment opener (tests); a [`RetryingReader`] over
+    /// `File::open` when unset
+    opener: Option<SegmentOpener>,
 }
 
 impl FileIoPool {
@@ -36,6 +65,15 @@ impl FileIoPool {
             open_handles: HashMap::new(),
             lru_queue: VecDeque::new(),
             max_open,
+            opener: None,
+        }
+    }
+
+    /// Create a pool that opens its files through `opener`
+    pub fn with_opener(file_paths: Vec<PathBuf>, max_open: usize, opener: SegmentOpener) -> Self {
+        Self {
+            opener: Some(opener),
+            ..Self::new(file_paths, max_open)
         }
     }
 
@@ -45,7 +83,10 @@ impl FileIoPool {
     }
 
     /// Get a file handle, opening it if necessary and managing LRU cache
-    pub fn get_file(&mut self, file_index: usize) -> Result<&mut File, String> {
+    ///
+    /// Failing to open a file on a device that is gone yields a
+    /// [`device::device_disconnected`] error.
+    pub fn get_file(&mut self, file_index: usize) -> Result<&mut dyn SegmentRead, String> {
         if file_index >= self.file_paths.len() {
             return Err(format!(
                 "File index {} out of range (have {} files)",
@@ -61,7 +102,7 @@ impl FileIoPool {
             // Add to front
             self.lru_queue.push_front(file_index);
             trace!(file_index, "File handle cache hit");
-            return Ok(self.open_handles.get_mut(&file_index).unwrap());
+            return Ok(self.open_handles.get_mut(&file_index).unwrap().as_mut());
         }
 
         // Need to open the file - check if we need to close one first
@@ -76,13 +117,18 @@ impl FileIoPool {
         // Open the new file
         let file_path = &self.file_paths[file_index];
         debug!(file_index, ?file_path, "Opening file handle");
-        let file = File::open(file_path)
-            .map_err(|e| format!("Failed to open segment {}: {}", file_index, e))?;
+        let opened = match &self.opener {
+            Some(opener) => opener(file_path),
+            None => RetryingReader::open(file_path).map(|file| Box::new(file) as Box<dyn SegmentRead>),
+        };
+        let file = opened.map_err(|e| {
+            device::describe_io_error(file_path, &format!("Failed to open segment {}", file_index), &e)
+        })?;
 
         self.open_handles.insert(file_index, file);
         self.lru_queue.push_front(file_index);
 
-        Ok(self.open_handles.get_mut(&file_index).unwrap())
+        Ok(self.open_handles.get_mut(&file_index).unwrap().as_mut())
     }
 
     /// Get the number of files in the pool
@@ -110,6 +156,23 @@ impl FileIoPool {
         self.open_handles.clear();
         self.lru_queue.clear();
     }
+
+    /// Close the handles of every file under `prefix` (e.g. the mount point
+    /// of a device that went away), so the OS can release the device
+    ///
+    /// Returns the number of handles closed. Files are reopened on next use.
+    pub fn invalidate_prefix(&mut self, prefix: &Path) -> usize {
+        let file_paths = &self.file_paths;
+        let before = self.open_handles.len();
+        self.open_handles.retain(|&index, _| !file_paths[index].starts_with(prefix));
+        let open_handles = &self.open_handles;
+        self.lru_queue.retain(|index| open_handles.contains_key(index));
+        let closed = before - self.open_handles.len();
+        if closed > 0 {
+            warn!(prefix = %prefix.display(), closed, "Closed file handles under lost device");
+        }
+        closed
+    }
 }
 
 #[cfg(test)]
@@ -151,6 +214,31 @@ mod tests {
         assert_eq!(pool.open_count(), 3);
     }
 
+    #[test]
+    fn test_invalidate_prefix_closes_only_matching_handles() {
+        let usb = TempDir::new().unwrap();
+        let local = TempDir::new().unwrap();
+        let paths: Vec<PathBuf> = [usb.path(), usb.path(), local.path()].iter().enumerate()
+            .map(|(i, dir)| {
+                let path = dir.join(format!("image.{:03}", i + 1));
+                std::fs::write(&path, [i as u8; 16]).unwrap();
+                path
+            })
+            .collect();
+
+        let mut pool = FileIoPool::new(paths, 3);
+        for i in 0..3 {
+            pool.get_file(i).unwrap();
+        }
+        assert_eq!(pool.invalidate_prefix(usb.path()), 2);
+        assert_eq!(pool.open_count(), 1);
+        assert_eq!(pool.invalidate_prefix(usb.path()), 0);
+
+        // Reopened on next use
+        pool.get_file(0).unwrap();
+        assert_eq!(pool.open_count(), 2);
+    }
+
     #[test]
     fn test_file_pool_out_of_range() {
         let pool_paths: Vec<PathBuf> = vec![];
@@ -7,19 +7,57 @@ pub mod io_pool;
 pub mod hex;
 pub mod magic;
 pub mod entropy;
+pub mod content_map;
 pub mod path_security;
+pub mod safe_path;
 pub mod audit;
+pub mod resumable_hash;
+pub mod extract_filter;
+pub mod extract_manifest;
+pub mod inflate;
+pub mod progress;
+pub mod device;
+pub mod resources;
+pub mod parse_warning;
+pub mod time;
+pub mod capabilities;
+pub mod quick_check;
+pub mod retry;
+pub mod volume;
+pub mod atomic_file;
+pub mod hashdeep;
+pub mod integrity;
+pub mod dedupe;
 
 // Re-exports for convenience
 pub use hash::{HashAlgorithm, StreamingHasher, compute_hash, hash_file_with_progress};
-pub use hash::{compare_hashes, HashMatchResult, HashVerificationResult, verify_hash};
-pub use binary::{read_u8, read_u16_le, read_u32_le, read_u64_le, read_u32_be};
-pub use segments::{discover_numbered_segments, discover_e01_segments, get_segment_basename, is_numbered_segment};
-pub use io_pool::{FileIoPool, DEFAULT_MAX_OPEN_FILES};
+pub use hash::{HashTuning, compute_hash_with_tuning, hash_file_with_tuning};
+pub use hash::{compare_hashes, hashes_match, HashMatchResult, HashValue, HashVerificationResult, verify_hash};
+pub use hash::{hash_regular_file, FileHashResult, FileDigest};
+pub use binary::{read_u8, read_u16_le, read_u32_le, read_u64_le, read_u32_be, ByteReader, Truncated};
+pub use segments::{discover_numbered_segments, discover_e01_segments, discover_ewf_segments, get_segment_basename, is_numbered_segment, SplitName};
+pub use segments::{EwfNaming, EwfSegmentName, EwfSegmentSet};
+pub use io_pool::{FileIoPool, SegmentRead, SegmentOpener, DEFAULT_MAX_OPEN_FILES};
 pub use hex::{format_hex_dump, format_hex_inline, format_hex_string, HexDumpOptions, HexDumpResult};
 pub use magic::{detect_file_type, FileType, FileCategory, is_image, is_archive, is_executable};
 pub use entropy::{calculate_entropy, classify_entropy, EntropyClass, EntropyResult, is_likely_encrypted};
-pub use path_security::{safe_join, sanitize_filename, is_safe_path, contains_traversal_pattern};
+pub use content_map::{summarize_map, ContentMap, ContentMapBuilder, MapSummary, MapWindow, DEFAULT_MAP_WINDOW, HIGH_ENTROPY_BITS};
+pub use path_security::{safe_join, sanitize_filename, is_safe_path, contains_traversal_pattern, check_output_location, available_space, OutputLocationCheck};
+pub use safe_path::{sanitize_component, join_sanitized, PathChange, SanitizedPath};
+pub use extract_filter::{ExtractFilter, ExtractMatcher, ExtractProgress, ExtractStats};
+pub use extract_manifest::{ExtractedFile, ExtractionManifest, HashingWriter, ManifestFormat};
+pub use resumable_hash::{ResumableHasher, HasherSnapshot};
+pub use resources::{IoPriority, JobResources};
+pub use parse_warning::ParseWarning;
+pub use time::Timestamp;
+pub use capabilities::FormatCapabilities;
+pub use quick_check::StructureCheck;
+pub use retry::{RetryPolicy, RetryingReader};
+pub use volume::{volume_info, StorageVolume};
+pub use atomic_file::{write_atomic, AtomicFile, OutputState, PartialCleanup};
+pub use hashdeep::{HashdeepAudit, HashdeepEntry, HashdeepExport, HashdeepFile};
+pub use integrity::{IntegrityCheck, IntegrityKey};
+pub use dedupe::{DedupeAction, DedupeIndex, DedupeStrategy};
 pub use audit::{log_evidence_access, log_hash_verification, log_container_opened, log_report_generation, log_security_event};
 
 // Shared constants - tuned for high throughput sequential I/O
@@ -0,0 +1,14 @@
+// Structure errors skipped by best-effort container parsing
+
+use serde::Serialize;
+
+/// A damaged structure that best-effort parsing skipped
+///
+/// Everything reachable without it is still reported, so a container with
+/// one unreadable link shows a partial tree instead of nothing.
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct ParseWarning {
+    /// Item address (AD1) or global section offset (EWF) that failed
+    pub address: u64,
+    pub error: String,
+}
@@ -3,9 +3,11 @@
 //! Provides functions for validating and sanitizing file paths
 //! to prevent path traversal attacks and other security issues.
 
-use std::path::{Path, PathBuf};
+use std::path::{Component, Path, PathBuf};
 use tracing::{warn, info};
 
+use super::device::is_block_device_path;
+
 /// Error type for path security operations
 #[derive(Debug, Clone)]
 pub enum PathSecurityError {
@@ -140,8 +142,9 @@ pub fn contains_traversal_pattern(filename: &str) -> bool {
         return true;
     }
     
-    // Check for parent directory references
-    if filename.contains("..") {
+    // Check for parent directory references (a whole `..` component, not
+    // a name like "file..name.txt")
+    if filename.split(['/', '\\']).any(|component| component == "..") {
         return true;
     }
     
@@ -226,6 +229,124 @@ pub fn is_safe_path(path: &Path) -> bool {
     true
 }
 
+/// Result of checking an extraction output location against its evidence inputs
+#[derive(Debug, Clone, Default, serde::Serialize)]
+pub struct OutputLocationCheck {
+    /// Output directory lives on the same volume/device as an input segment
+    pub same_volume: bool,
+}
+
+/// Check that an extraction output directory is forensically safe.
+///
+/// Errors if the (canonical) output directory is inside - or equal to - the
+/// directory containing any input segment, so extracted files can never be
+/// mingled with original evidence, and if it is a drive (`/dev/sdb`,
+/// `\\.\PhysicalDrive2`). Reports whether output and input share a
+/// volume so callers can warn or refuse.
+pub fn check_output_location(inputs: &[PathBuf], output_dir: &Path) -> Result<OutputLocationCheck, String> {
+    if is_block_device_path(&output_dir.to_string_lossy()) {
+        return Err(format!("{} is a drive - extraction never writes onto a device directly", output_dir.display()));
+    }
+    let canonical_output = canonicalize_lenient(output_dir)
+        .map_err(|e| format!("Failed to resolve output directory {}: {}", output_dir.display(), e))?;
+
+    let mut check = OutputLocationCheck::default();
+    for input in inputs {
+        let canonical_input = input.canonicalize()
+            .map_err(|e| format!("Failed to resolve input {}: {}", input.display(), e))?;
+        let evidence_dir = canonical_input.parent().unwrap_or(&canonical_input);
+
+        if canonical_output.starts_with(evidence_dir) {
+            warn!(
+                target: "security",
+                input = %canonical_input.display(),
+                output = %canonical_output.display(),
+                "Extraction output inside evidence directory"
+            );
+            return Err(format!(
+                "Output directory {} is inside the evidence directory {} - choose a location outside the evidence folder",
+                canonical_output.display(),
+                evidence_dir.display()
+            ));
+        }
+
+        if !check.same_volume && same_volume(evidence_dir, &canonical_output) {
+            check.same_volume = true;
+        }
+    }
+
+    Ok(check)
+}
+
+/// Canonicalize a path that may not exist yet by resolving its nearest
+/// existing ancestor and re-appending the remaining components
+///
+/// A `..` in the missing part is applied lexically, the way
+/// `create_dir_all` will resolve it.
+fn canonicalize_lenient(path: &Path) -> std::io::Result<PathBuf> {
+    if let Ok(canonical) = path.canonicalize() {
+        return Ok(canonical);
+    }
+    let mut existing = path;
+    let mut rest = Vec::new();
+    loop {
+        match existing.parent() {
+            Some(parent) => {
+                if let Some(last) = existing.components().next_back() {
+                    rest.push(last);
+                }
+                existing = parent;
+                if existing.as_os_str().is_empty() {
+                    existing = Path::new(".");
+                }
+                if let Ok(canonical) = existing.canonicalize() {
+                    let mut resolved = canonical;
+                    for component in rest.iter().rev() {
+                        match component {
+                            Component::ParentDir => {
+                                resolved.pop();
+                            }
+                            Component::Normal(name) => resolved.push(name),
+                            _ => {}
+                        }
+                    }
+                    return Ok(resolved);
+                }
+            }
+            None => return path.canonicalize(),
+        }
+    }
+}
+
+/// Best-effort check whether two existing paths are on the same volume
+#[cfg(unix)]
+fn same_volume(a: &Path, b: &Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
+    let dev = |p: &Path| {
+        p.ancestors().find_map(|ancestor| std::fs::metadata(ancestor).ok()).map(|m| m.dev())
+    };
+    matches!((dev(a), dev(b)), (Some(x), Some(y)) if x == y)
+}
+
+/// Best-effort check whether two existing paths are on the same volume
+#[cfg(not(unix))]
+fn same_volume(a: &Path, b: &Path) -> bool {
+    use std::path::Component;
+    let prefix = |p: &Path| match p.components().next() {
+        Some(Component::Prefix(prefix)) => Some(prefix.as_os_str().to_ascii_lowercase()),
+        _ => None,
+    };
+    prefix(a).is_some() && prefix(a) == prefix(b)
+}
+
+/// Free bytes available to this process on the volume holding `path`
+///
+/// `path` need not exist yet; its nearest existing ancestor is queried.
+/// `None` when the platform or filesystem can't tell.
+pub fn available_space(path: &Path) -> Option<u64> {
+    super::volume::volume_info(path).ok()?.available_bytes
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -265,4 +386,49 @@ mod tests {
         assert!(!is_safe_path(Path::new("../secret.txt")));
         assert!(!is_safe_path(Path::new("/etc/passwd")));
     }
+
+    #[test]
+    fn test_output_inside_evidence_dir_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let evidence = dir.path().join("evidence");
+        std::fs::create_dir(&evidence).unwrap();
+        let image = evidence.join("disk.E01");
+        std::fs::write(&image, b"x").unwrap();
+
+        // Same folder and a not-yet-created subfolder are both rejected
+        assert!(check_output_location(std::slice::from_ref(&image), &evidence).is_err());
+        assert!(check_output_location(std::slice::from_ref(&image), &evidence.join("export/new")).is_err());
+
+        // A `..` after a folder that doesn't exist yet still lands in the evidence folder
+        let sneaky = dir.path().join("new/../evidence/export");
+        assert_eq!(canonicalize_lenient(&sneaky).unwrap(), evidence.canonicalize().unwrap().join("export"));
+        assert!(check_output_location(std::slice::from_ref(&image), &sneaky).is_err());
+
+        // ... and so are drives, whatever the evidence
+        for device in ["/dev/sdb", r"\\.\PhysicalDrive1"] {
+            let error = check_output_location(std::slice::from_ref(&image), Path::new(device)).unwrap_err();
+            assert!(error.contains("never writes onto a device"), "{error}");
+        }
+    }
+
+    #[test]
+    fn test_output_on_same_volume_flagged() {
+        let dir = tempfile::tempdir().unwrap();
+        let evidence = dir.path().join("evidence");
+        let output = dir.path().join("output");
+        std::fs::create_dir(&evidence).unwrap();
+        let image = evidence.join("disk.E01");
+        std::fs::write(&image, b"x").unwrap();
+
+        let check = check_output_location(&[image], &output).unwrap();
+        assert!(check.same_volume);
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_available_space_of_missing_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        let free = available_space(&dir.path().join("not/created/yet"));
+        assert!(free.is_some_and(|bytes| bytes > 0));
+    }
 }
@@ -0,0 +1,297 @@
+// Lock-free progress tracking with sampled reporting
+//
+// Hashers call their progress callback once per buffer, which with many
+// parallel jobs means thousands of calls per second. Instead of forwarding
+// each call as a UI event, workers store their counters in atomics and a
+// single sampler thread reads all jobs at a fixed cadence, reporting only
+// when something changed. The event rate then depends on the interval, not
+// on how fast (or how parallel) the hashing is.
+//
+// Pausing works the same way: a job's workers check its [`PauseGate`] at
+// buffer boundaries with one atomic load, and only take a lock to park
+// while the job is paused. Hasher state stays on the parked thread's
+// stack, so a resumed job carries on from the buffer it stopped at.
+
+use std::cell::RefCell;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicU8, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::Duration;
+
+/// Default sampling cadence for progress events
+pub const PROGRESS_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Decides when the sampler takes its next snapshot
+pub trait SampleClock: Send {
+    /// Block until the next sample is due or `stop` fires; true once stopped
+    fn wait(&mut self, stop: &mpsc::Receiver<()>) -> bool;
+}
+
+/// Wall-clock sampling every `interval`
+impl SampleClock for Duration {
+    fn wait(&mut self, stop: &mpsc::Receiver<()>) -> bool {
+        // Woken early (disconnected) when the work is done
+        !matches!(stop.recv_timeout(*self), Err(RecvTimeoutError::Timeout))
+    }
+}
+
+/// Sampling driven by explicit ticks, so tests don't depend on timing
+#[cfg(test)]
+pub(crate) struct ManualClock(pub mpsc::Receiver<()>);
+
+#[cfg(test)]
+impl SampleClock for ManualClock {
+    fn wait(&mut self, stop: &mpsc::Receiver<()>) -> bool {
+        loop {
+            if !matches!(stop.try_recv(), Err(mpsc::TryRecvError::Empty)) {
+                return true;
+            }
+            match self.0.recv_timeout(Duration::from_millis(1)) {
+                Ok(()) => return false,
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => {
+                    let _ = stop.recv();
+                    return true;
+                }
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum JobState {
+    #[default]
+    Pending,
+    Running,
+    Finished,
+}
+
+impl JobState {
+    fn from_u8(value: u8) -> Self {
+        match value {
+            1 => JobState::Running,
+            2 => JobState::Finished,
+            _ => JobState::Pending,
+        }
+    }
+}
+
+/// Point-in-time view of one job
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct JobSnapshot {
+    pub done: u64,
+    pub total: u64,
+    pub state: JobState,
+}
+
+impl JobSnapshot {
+    pub fn percent(&self) -> f64 {
+        match self.state {
+            JobState::Finished => 100.0,
+            _ if self.total == 0 => 0.0,
+            _ => (self.done as f64 / self.total as f64 * 100.0).min(100.0),
+        }
+    }
+}
+
+/// Progress counters of one job, updated by its worker without locking
+#[derive(Default)]
+pub struct JobProgress {
+    done: AtomicU64,
+    total: AtomicU64,
+    state: AtomicU8,
+}
+
+impl JobProgress {
+    /// Set the expected total before the job starts (e.g. the file size)
+    pub fn set_total(&self, total: u64) {
+        self.total.store(total, Ordering::Relaxed);
+    }
+
+    pub fn start(&self) {
+        self.state.store(JobState::Running as u8, Ordering::Release);
+    }
+
+    /// Record progress - cheap enough to call once per buffer
+    pub fn update(&self, done: u64, total: u64) {
+        self.total.store(total, Ordering::Relaxed);
+        self.done.store(done, Ordering::Relaxed);
+    }
+
+    pub fn finish(&self) {
+        self.done.store(self.total.load(Ordering::Relaxed), Ordering::Relaxed);
+        self.state.store(JobState::Finished as u8, Ordering::Release);
+    }
+
+    pub fn snapshot(&self) -> JobSnapshot {
+        JobSnapshot {
+            state: JobState::from_u8(self.state.load(Ordering::Acquire)),
+            done: self.done.load(Ordering::Relaxed),
+            total: self.total.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Pause switch of one job, checked by its workers between buffers
+#[derive(Default)]
+pub struct PauseGate {
+    paused: AtomicBool,
+    lock: Mutex<()>,
+    resumed: Condvar,
+}
+
+impl PauseGate {
+    fn lock(&self) -> std::sync::MutexGuard<'_, ()> {
+        self.lock.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Returns false if the job was already paused
+    pub fn pause(&self) -> bool {
+        let _guard = self.lock();
+        !self.paused.swap(true, Ordering::AcqRel)
+    }
+
+    /// Wake parked workers; returns false if the job was not paused
+    pub fn resume(&self) -> bool {
+        let _guard = self.lock();
+        let was_paused = self.paused.swap(false, Ordering::AcqRel);
+        self.resumed.notify_all();
+        was_paused
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::Acquire)
+    }
+
+    /// Block while the job is paused
+    ///
+    /// `before_parking` runs once before blocking (e.g. to close file
+    /// handles). Returns whether the caller was parked.
+    pub fn wait(&self, before_parking: impl FnOnce()) -> bool {
+        if !self.is_paused() {
+            return false;
+        }
+        before_parking();
+        let mut guard = self.lock();
+        while self.is_paused() {
+            guard = self.resumed.wait(guard).unwrap_or_else(|poisoned| poisoned.into_inner());
+        }
+        true
+    }
+}
+
+thread_local! {
+    /// Pause gate of the job running on this thread, if it can be paused
+    static CURRENT_PAUSE_GATE: RefCell<Option<Arc<PauseGate>>> = const { RefCell::new(None) };
+}
+
+/// Run `work` with `gate` as this thread's pause gate, restoring the
+/// previous one afterwards
+pub fn with_pause_gate<T>(gate: Arc<PauseGate>, work: impl FnOnce() -> T) -> T {
+    struct Restore(Option<Arc<PauseGate>>);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            set_current_thread_pause_gate(self.0.take());
+        }
+    }
+    let _restore = Restore(CURRENT_PAUSE_GATE.with(|current| current.replace(Some(gate))));
+    work()
+}
+
+/// Pause gate of the job running on this thread, for handing on to the
+/// threads it spawns
+pub fn current_pause_gate() -> Option<Arc<PauseGate>> {
+    CURRENT_PAUSE_GATE.with(|current| current.borrow().clone())
+}
+
+pub fn set_current_thread_pause_gate(gate: Option<Arc<PauseGate>>) {
+    CURRENT_PAUSE_GATE.with(|current| *current.borrow_mut() = gate);
+}
+
+/// Park while the current thread's job is paused (no-op for jobs that
+/// can't be paused); see [`PauseGate::wait`]
+pub fn pause_point(before_parking: impl FnOnce()) -> bool {
+    CURRENT_PAUSE_GATE.with(|current| current.borrow().as_ref().is_some_and(|gate| gate.wait(before_parking)))
+}
+
+/// Fixed set of jobs whose progress is sampled together
+pub struct ProgressTracker {
+    jobs: Vec<JobProgress>,
+}
+
+impl ProgressTracker {
+    pub fn new(job_count: usize) -> Self {
+        Self {
+            jobs: (0..job_count).map(|_| JobProgress::default()).collect(),
+        }
+    }
+
+    pub fn job(&self, idx: usize) -> &JobProgress {
+        &self.jobs[idx]
+    }
+
+    pub fn snapshot(&self) -> Vec<JobSnapshot> {
+        self.jobs.iter().map(JobProgress::snapshot).collect()
+    }
+
+    /// Run `work` while a sampler thread reports this tracker on every tick
+    /// of `clock` (usually a `Duration` interval)
+    ///
+    /// `report` receives the previous and current snapshots and is only
+    /// called when something changed. A final sample is always taken after
+    /// `work` returns, so the last report reflects the finished state.
+    pub fn sample_while<R, W, E, C>(&self, mut clock: C, mut report: E, work: W) -> R
+    where
+        C: SampleClock,
+        W: FnOnce() -> R,
+        E: FnMut(&[JobSnapshot], &[JobSnapshot]) + Send,
+    {
+        let (stop_tx, stop_rx) = mpsc::channel::<()>();
+        // Taken before `work` starts so fast work can't race the baseline
+        let mut last = self.snapshot();
+        std::thread::scope(|s| {
+            s.spawn(move || {
+                loop {
+                    let stopped = clock.wait(&stop_rx);
+                    let current = self.snapshot();
+                    if current != last {
+                        report(&last, &current);
+                        last = current;
+                    }
+                    if stopped {
+                        break;
+                    }
+                }
+            });
+
+            let result = work();
+            drop(stop_tx);
+            result
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sampler_reports_final_state_once_per_change() {
+        let tracker = ProgressTracker::new(2);
+        let mut reports = Vec::new();
+        tracker.sample_while(Duration::from_secs(60), |_, current| reports.push(current.to_vec()), || {
+            for (idx, total) in [(0, 10), (1, 20)] {
+                let job = tracker.job(idx);
+                job.start();
+                for done in 0..=total {
+                    job.update(done, total);
+                }
+                job.finish();
+            }
+        });
+
+        // The interval never elapsed - only the final sample is reported
+        assert_eq!(reports.len(), 1);
+        assert!(reports[0].iter().all(|job| job.state == JobState::Finished && job.percent() == 100.0));
+    }
+}
@@ -0,0 +1,36 @@
+// Header-only structure checks
+//
+// Each format module walks its own headers (`ewf::quick_check`,
+// `ad1::quick_check`, ...) and reports one `StructureCheck` per property it
+// looked at; `containers::quick_check` adds the path and timing. Nothing
+// here reads chunk or file data, so a check takes seconds on any size.
+
+use serde::Serialize;
+
+/// One structural property of a container and whether it holds
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct StructureCheck {
+    /// What was checked, e.g. "Section chain"
+    pub name: String,
+    pub passed: bool,
+    /// What was found, or where it went wrong
+    pub detail: String,
+}
+
+impl StructureCheck {
+    pub fn pass(name: &str, detail: impl Into<String>) -> Self {
+        Self { name: name.to_string(), passed: true, detail: detail.into() }
+    }
+
+    pub fn fail(name: &str, detail: impl Into<String>) -> Self {
+        Self { name: name.to_string(), passed: false, detail: detail.into() }
+    }
+
+    /// Pass or fail depending on `result`, with its message as the detail
+    pub fn from_result(name: &str, result: Result<String, String>) -> Self {
+        match result {
+            Ok(detail) => Self::pass(name, detail),
+            Err(detail) => Self::fail(name, detail),
+        }
+    }
+}
@@ -0,0 +1,289 @@
+// Per-job CPU and I/O limits
+//
+// A job can cap the rayon workers it uses (E01 decompression and hashing,
+// parallel segment and batch hashing) and lower its I/O priority, so a
+// long verification on a shared workstation doesn't starve everything else.
+// Unset options fall back to app-wide defaults changed from the settings.
+//
+// Low I/O priority uses ioprio_set (idle class) on Linux and background
+// thread mode on Windows. Elsewhere it is silently ignored.
+//
+// Low-memory mode caps I/O buffers at 4MB, never memory-maps evidence and
+// shrinks chunk caches and E01 read-ahead, for machines (and 32-bit builds,
+// where it is always on) that can't spare hundreds of megabytes of address
+// space. Hashes and output are identical either way.
+
+use std::cell::Cell;
+use std::sync::RwLock;
+
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
+
+/// I/O scheduling class for a job's threads
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IoPriority {
+    #[default]
+    Normal,
+    /// Only gets the disk when nothing else wants it
+    Low,
+}
+
+/// Thread cap and I/O priority of a job
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JobResources {
+    /// Worker threads; `None` uses every core
+    pub threads: Option<usize>,
+    #[serde(default)]
+    pub io_priority: IoPriority,
+    /// Small buffers, no mmap, small caches
+    #[serde(default)]
+    pub low_memory: bool,
+}
+
+static DEFAULTS: RwLock<JobResources> = RwLock::new(JobResources { threads: None, io_priority: IoPriority::Normal, low_memory: false });
+
+thread_local! {
+    /// Priority applied to this thread, handed on to threads it starts
+    static CURRENT_IO_PRIORITY: Cell<IoPriority> = const { Cell::new(IoPriority::Normal) };
+    /// Low-memory mode of this thread, handed on like the I/O priority
+    static CURRENT_LOW_MEMORY: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Largest I/O buffer in low-memory mode
+pub const LOW_MEMORY_BUFFER_SIZE: usize = 4 * 1024 * 1024; // 4MB
+
+/// Settings used by jobs that don't choose their own
+pub fn defaults() -> JobResources {
+    *DEFAULTS.read().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Change the defaults for jobs started from now on
+pub fn set_defaults(resources: JobResources) {
+    *DEFAULTS.write().unwrap_or_else(|poisoned| poisoned.into_inner()) = resources.normalized();
+}
+
+impl JobResources {
+    /// Per-job options, with unset ones taken from [`defaults`]
+    pub fn resolve(threads: Option<usize>, io_priority: Option<IoPriority>, low_memory: Option<bool>) -> Self {
+        let defaults = defaults();
+        Self {
+            threads: threads.or(defaults.threads),
+            io_priority: io_priority.unwrap_or(defaults.io_priority),
+            low_memory: low_memory.unwrap_or(defaults.low_memory),
+        }
+        .normalized()
+    }
+
+    /// A thread cap of 0 means "no cap"
+    fn normalized(self) -> Self {
+        Self { threads: self.threads.filter(|n| *n > 0), ..self }
+    }
+
+    /// Run `work` on a dedicated pool with this thread cap, I/O priority and
+    /// memory mode
+    ///
+    /// Unlimited settings run `work` directly on the calling thread. Code
+    /// inside sizes itself with `rayon::current_num_threads()`, so the cap
+    /// also applies to nested parallel loops and BLAKE3.
+    pub fn run<R, W>(&self, work: W) -> R
+    where
+        R: Send,
+        W: FnOnce() -> R + Send,
+    {
+        if *self == Self::default() {
+            return work();
+        }
+        let io_priority = self.io_priority;
+        let low_memory = self.low_memory;
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(self.threads.unwrap_or(0))
+            .start_handler(move |_| {
+                set_current_thread_io_priority(io_priority);
+                set_current_thread_low_memory(low_memory);
+            })
+            .build();
+        match pool {
+            Ok(pool) => pool.install(work),
+            Err(e) => {
+                warn!(threads = ?self.threads, "Failed to build job thread pool, running unlimited: {}", e);
+                work()
+            }
+        }
+    }
+}
+
+/// Priority applied to the calling thread (`Normal` unless set)
+pub fn current_io_priority() -> IoPriority {
+    CURRENT_IO_PRIORITY.with(Cell::get)
+}
+
+/// Pool of `num_threads` workers sharing the calling thread's I/O priority
+///
+/// For pools started inside a job; new threads don't inherit the priority
+/// on every platform.
+pub fn inherited_thread_pool(num_threads: usize) -> Result<rayon::ThreadPool, rayon::ThreadPoolBuildError> {
+    let io_priority = current_io_priority();
+    let low_memory = CURRENT_LOW_MEMORY.with(Cell::get);
+    rayon::ThreadPoolBuilder::new()
+        .num_threads(num_threads)
+        .start_handler(move |_| {
+            set_current_thread_io_priority(io_priority);
+            set_current_thread_low_memory(low_memory);
+        })
+        .build()
+}
+
+/// Whether the calling thread runs in low-memory mode
+///
+/// Always true on 32-bit targets, where mapping a multi-gigabyte file or a
+/// few large batches exhausts the address space.
+pub fn low_memory() -> bool {
+    cfg!(target_pointer_width = "32") || CURRENT_LOW_MEMORY.with(Cell::get)
+}
+
+/// Switch low-memory mode for the calling thread (and pools it starts
+/// through [`inherited_thread_pool`])
+pub fn set_current_thread_low_memory(enabled: bool) {
+    CURRENT_LOW_MEMORY.with(|current| current.set(enabled));
+}
+
+/// `size`, capped at [`LOW_MEMORY_BUFFER_SIZE`] in low-memory mode
+pub fn buffer_size(size: usize) -> usize {
+    if low_memory() { size.min(LOW_MEMORY_BUFFER_SIZE) } else { size }
+}
+
+/// I/O buffer size from the settings, capped in low-memory mode
+pub fn io_buffer_size() -> usize {
+    buffer_size(crate::settings::current().buffer_size)
+}
+
+/// Whether a file of `size` bytes should be memory-mapped for hashing
+pub fn use_mmap(size: u64) -> bool {
+    size >= super::MMAP_THRESHOLD && !low_memory()
+}
+
+/// Apply `priority` to the calling thread
+///
+/// Returns false where the platform has no per-thread I/O priority or the
+/// call failed; the thread then keeps running at normal priority.
+pub fn set_current_thread_io_priority(priority: IoPriority) -> bool {
+    CURRENT_IO_PRIORITY.with(|current| current.set(priority));
+    let applied = os::set_io_priority(priority);
+    if !applied {
+        debug!(?priority, "I/O priority not supported here, ignoring");
+    }
+    applied
+}
+
+#[cfg(target_os = "linux")]
+mod os {
+    use super::IoPriority;
+
+    const IOPRIO_WHO_PROCESS: libc::c_long = 1;
+    const IOPRIO_CLASS_SHIFT: libc::c_long = 13;
+    const IOPRIO_CLASS_NONE: libc::c_long = 0;
+    const IOPRIO_CLASS_IDLE: libc::c_long = 3;
+
+    /// ioprio_set on the calling thread (pid 0 is the current task)
+    pub fn set_io_priority(priority: IoPriority) -> bool {
+        let class = match priority {
+            IoPriority::Normal => IOPRIO_CLASS_NONE,
+            IoPriority::Low => IOPRIO_CLASS_IDLE,
+        };
+        // SAFETY: ioprio_set takes plain integers and touches no memory
+        unsafe { libc::syscall(libc::SYS_ioprio_set, IOPRIO_WHO_PROCESS, 0, class << IOPRIO_CLASS_SHIFT) == 0 }
+    }
+}
+
+#[cfg(windows)]
+mod os {
+    use super::IoPriority;
+    use windows_sys::Win32::System::Threading::{
+        GetCurrentThread, SetThreadPriority, THREAD_MODE_BACKGROUND_BEGIN, THREAD_MODE_BACKGROUND_END,
+    };
+
+    /// Background mode lowers both CPU and I/O priority of the thread
+    pub fn set_io_priority(priority: IoPriority) -> bool {
+        let mode = match priority {
+            IoPriority::Normal => THREAD_MODE_BACKGROUND_END,
+            IoPriority::Low => THREAD_MODE_BACKGROUND_BEGIN,
+        };
+        // SAFETY: the pseudo handle of the current thread is always valid
+        unsafe { SetThreadPriority(GetCurrentThread(), mode) != 0 }
+    }
+}
+
+#[cfg(not(any(target_os = "linux", windows)))]
+mod os {
+    use super::IoPriority;
+
+    pub fn set_io_priority(_priority: IoPriority) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rayon::prelude::*;
+    use std::collections::HashSet;
+    use std::time::Duration;
+
+    #[test]
+    fn test_thread_cap_is_honored() {
+        let resources = JobResources { threads: Some(2), ..Default::default() };
+        let (reported, workers) = resources.run(|| {
+            let workers: HashSet<_> = (0..64).into_par_iter()
+                .map(|_| {
+                    std::thread::sleep(Duration::from_millis(2));
+                    std::thread::current().id()
+                })
+                .collect();
+            (rayon::current_num_threads(), workers)
+        });
+        assert_eq!(reported, 2);
+        assert!(!workers.is_empty() && workers.len() <= 2, "{} worker threads used", workers.len());
+    }
+
+    #[test]
+    fn test_low_priority_reaches_worker_threads() {
+        let resources = JobResources { threads: Some(2), io_priority: IoPriority::Low, low_memory: false };
+        let priorities: Vec<IoPriority> = resources.run(|| {
+            let nested = inherited_thread_pool(2).unwrap();
+            let mut seen = vec![current_io_priority()];
+            seen.extend(nested.install(|| (0..8).into_par_iter().map(|_| current_io_priority()).collect::<Vec<_>>()));
+            seen
+        });
+        assert!(priorities.iter().all(|p| *p == IoPriority::Low));
+        assert_eq!(current_io_priority(), IoPriority::Normal);
+    }
+
+    #[test]
+    fn test_low_memory_reaches_worker_threads() {
+        let resources = JobResources { low_memory: true, ..Default::default() };
+        let (modes, buffer) = resources.run(|| {
+            let nested = inherited_thread_pool(2).unwrap();
+            let mut seen = vec![low_memory()];
+            seen.extend(nested.install(|| (0..8).into_par_iter().map(|_| low_memory()).collect::<Vec<_>>()));
+            (seen, buffer_size(crate::common::BUFFER_SIZE))
+        });
+        assert!(modes.iter().all(|m| *m));
+        assert_eq!(buffer, LOW_MEMORY_BUFFER_SIZE);
+        assert!(resources.run(|| !use_mmap(u64::MAX)));
+        assert_eq!(low_memory(), cfg!(target_pointer_width = "32"));
+    }
+
+    #[test]
+    fn test_unset_options_use_defaults() {
+        assert_eq!(JobResources::resolve(Some(0), Some(IoPriority::Low), None).threads, None);
+        let previous = defaults();
+        set_defaults(JobResources { threads: Some(3), io_priority: IoPriority::Low, low_memory: true });
+        let resolved = JobResources::resolve(None, None, None);
+        let overridden = JobResources::resolve(Some(1), Some(IoPriority::Normal), Some(false));
+        set_defaults(previous);
+        assert_eq!(resolved, JobResources { threads: Some(3), io_priority: IoPriority::Low, low_memory: true });
+        assert_eq!(overridden, JobResources { threads: Some(1), io_priority: IoPriority::Normal, low_memory: false });
+    }
+}
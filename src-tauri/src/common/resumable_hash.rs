@@ -0,0 +1,302 @@
+// Resumable hashing - hasher state that can be written to disk and restored
+//
+// The RustCrypto hashers used by StreamingHasher do not expose their internal
+// state, so checkpointed verification drives the raw block compression
+// functions itself and keeps the chaining state, the partial block and the
+// message length in a serializable snapshot.
+//
+// Exportable: MD5, SHA-1, SHA-256, SHA-512, CRC32.
+// Not exportable (BLAKE2/3, SHA3, XXH*): `ResumableHasher::new` returns None
+// and callers must hash from the start.
+
+use serde::{Deserialize, Serialize};
+use sha1::digest::generic_array::GenericArray;
+
+use super::hash::HashAlgorithm;
+
+const MD5_INIT: [u32; 4] = [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476];
+const SHA1_INIT: [u32; 5] = [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476, 0xc3d2e1f0];
+const SHA256_INIT: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a,
+    0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+const SHA512_INIT: [u64; 8] = [
+    0x6a09e667f3bcc908, 0xbb67ae8584caa73b, 0x3c6ef372fe94f82b, 0xa54ff53a5f1d36f1,
+    0x510e527fade682d1, 0x9b05688c2b3e6c1f, 0x1f83d9abfb41bd6b, 0x5be0cd19137e2179,
+];
+
+/// Chaining state of a resumable hasher
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "algorithm", content = "state", rename_all = "lowercase")]
+pub enum ChainState {
+    Md5([u32; 4]),
+    Sha1([u32; 5]),
+    Sha256([u32; 8]),
+    Sha512([u64; 8]),
+    Crc32(u32),
+}
+
+/// Serializable snapshot of a `ResumableHasher`
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HasherSnapshot {
+    pub chain: ChainState,
+    /// Bytes of the current, not yet compressed block
+    pub pending: Vec<u8>,
+    /// Total message length in bytes
+    pub length: u64,
+}
+
+/// Hasher whose state can be snapshotted and restored mid-stream
+#[derive(Clone)]
+pub struct ResumableHasher {
+    chain: ChainState,
+    pending: Vec<u8>,
+    length: u64,
+}
+
+impl ResumableHasher {
+    /// Create a hasher for `algorithm`, or None if its state can't be exported
+    pub fn new(algorithm: HashAlgorithm) -> Option<Self> {
+        let chain = match algorithm {
+            HashAlgorithm::Md5 => ChainState::Md5(MD5_INIT),
+            HashAlgorithm::Sha1 => ChainState::Sha1(SHA1_INIT),
+            HashAlgorithm::Sha256 => ChainState::Sha256(SHA256_INIT),
+            HashAlgorithm::Sha512 => ChainState::Sha512(SHA512_INIT),
+            HashAlgorithm::Crc32 => ChainState::Crc32(0),
+            _ => return None,
+        };
+        Some(Self { chain, pending: Vec::new(), length: 0 })
+    }
+
+    /// Whether `algorithm` supports exact checkpoint/resume
+    pub fn supports(algorithm: HashAlgorithm) -> bool {
+        Self::new(algorithm).is_some()
+    }
+
+    /// Restore a hasher from a snapshot taken with `snapshot`
+    pub fn restore(snapshot: HasherSnapshot) -> Result<Self, String> {
+        let block = Self::block_len(&snapshot.chain);
+        // CRC32 has no block buffer; the block hashes always keep less than a block
+        if snapshot.pending.len() >= block.max(1) {
+            return Err("Corrupt hasher snapshot: pending data exceeds block size".to_string());
+        }
+        Ok(Self { chain: snapshot.chain, pending: snapshot.pending, length: snapshot.length })
+    }
+
+    pub fn snapshot(&self) -> HasherSnapshot {
+        HasherSnapshot {
+            chain: self.chain.clone(),
+            pending: self.pending.clone(),
+            length: self.length,
+        }
+    }
+
+    /// Number of bytes hashed so far
+    pub fn length(&self) -> u64 {
+        self.length
+    }
+
+    fn block_len(chain: &ChainState) -> usize {
+        match chain {
+            ChainState::Md5(_) | ChainState::Sha1(_) | ChainState::Sha256(_) => 64,
+            ChainState::Sha512(_) => 128,
+            ChainState::Crc32(_) => 0,
+        }
+    }
+
+    pub fn update(&mut self, mut data: &[u8]) {
+        self.length += data.len() as u64;
+
+        if let ChainState::Crc32(crc) = &mut self.chain {
+            let mut hasher = crc32fast::Hasher::new_with_initial(*crc);
+            hasher.update(data);
+            *crc = hasher.finalize();
+            return;
+        }
+
+        let block = Self::block_len(&self.chain);
+
+        // Top up a partial block first
+        if !self.pending.is_empty() {
+            let take = (block - self.pending.len()).min(data.len());
+            self.pending.extend_from_slice(&data[..take]);
+            data = &data[take..];
+            if self.pending.len() < block {
+                return;
+            }
+            let full = std::mem::take(&mut self.pending);
+            compress(&mut self.chain, &full);
+        }
+
+        // Whole blocks straight from the input
+        let whole = data.len() - data.len() % block;
+        if whole > 0 {
+            compress(&mut self.chain, &data[..whole]);
+        }
+        self.pending.extend_from_slice(&data[whole..]);
+    }
+
+    /// Finalize and return the hash as a lowercase hex string
+    pub fn finalize(mut self) -> String {
+        if let ChainState::Crc32(crc) = self.chain {
+            return format!("{:08x}", crc);
+        }
+
+        let bit_length = self.length.wrapping_mul(8);
+        match Self::block_len(&self.chain) {
+            // SHA-512 has a 128-bit length field; lengths here never exceed 64 bits
+            128 => {
+                let mut length_field = [0u8; 16];
+                length_field[8..].copy_from_slice(&bit_length.to_be_bytes());
+                self.pad(128, &length_field);
+            }
+            _ if matches!(self.chain, ChainState::Md5(_)) => self.pad(64, &bit_length.to_le_bytes()),
+            _ => self.pad(64, &bit_length.to_be_bytes()),
+        }
+
+        let digest: Vec<u8> = match &self.chain {
+            ChainState::Md5(state) => state.iter().flat_map(|w| w.to_le_bytes()).collect(),
+            ChainState::Sha1(state) => state.iter().flat_map(|w| w.to_be_bytes()).collect(),
+            ChainState::Sha256(state) => state.iter().flat_map(|w| w.to_be_bytes()).collect(),
+            ChainState::Sha512(state) => state.iter().flat_map(|w| w.to_be_bytes()).collect(),
+            ChainState::Crc32(_) => unreachable!(),
+        };
+        hex::encode(digest)
+    }
+
+    /// Merkle-Damgard padding: 0x80, zeros, then the length field
+    fn pad(&mut self, block: usize, length_field: &[u8]) {
+        let mut tail = std::mem::take(&mut self.pending);
+        tail.push(0x80);
+        while tail.len() % block != block - length_field.len() {
+            tail.push(0);
+        }
+        tail.extend_from_slice(length_field);
+        compress(&mut self.chain, &tail);
+    }
+}
+
+/// Run the block function over `blocks` (a whole number of blocks)
+fn compress(chain: &mut ChainState, blocks: &[u8]) {
+    match chain {
+        ChainState::Md5(state) => {
+            for block in blocks.chunks_exact(64) {
+                md5_compress(state, block);
+            }
+        }
+        ChainState::Sha1(state) => {
+            let blocks: Vec<_> = blocks.chunks_exact(64).map(GenericArray::clone_from_slice).collect();
+            sha1::compress(state, &blocks);
+        }
+        ChainState::Sha256(state) => {
+            let blocks: Vec<_> = blocks.chunks_exact(64).map(GenericArray::clone_from_slice).collect();
+            sha2::compress256(state, &blocks);
+        }
+        ChainState::Sha512(state) => {
+            let blocks: Vec<_> = blocks.chunks_exact(128).map(GenericArray::clone_from_slice).collect();
+            sha2::compress512(state, &blocks);
+        }
+        ChainState::Crc32(_) => {}
+    }
+}
+
+const MD5_SHIFTS: [u32; 64] = [
+    7, 12, 17, 22, 7, 12, 17, 22, 7, 12, 17, 22, 7, 12, 17, 22,
+    5, 9, 14, 20, 5, 9, 14, 20, 5, 9, 14, 20, 5, 9, 14, 20,
+    4, 11, 16, 23, 4, 11, 16, 23, 4, 11, 16, 23, 4, 11, 16, 23,
+    6, 10, 15, 21, 6, 10, 15, 21, 6, 10, 15, 21, 6, 10, 15, 21,
+];
+
+const MD5_K: [u32; 64] = [
+    0xd76aa478, 0xe8c7b756, 0x242070db, 0xc1bdceee,
+    0xf57c0faf, 0x4787c62a, 0xa8304613, 0xfd469501,
+    0x698098d8, 0x8b44f7af, 0xffff5bb1, 0x895cd7be,
+    0x6b901122, 0xfd987193, 0xa679438e, 0x49b40821,
+    0xf61e2562, 0xc040b340, 0x265e5a51, 0xe9b6c7aa,
+    0xd62f105d, 0x02441453, 0xd8a1e681, 0xe7d3fbc8,
+    0x21e1cde6, 0xc33707d6, 0xf4d50d87, 0x455a14ed,
+    0xa9e3e905, 0xfcefa3f8, 0x676f02d9, 0x8d2a4c8a,
+    0xfffa3942, 0x8771f681, 0x6d9d6122, 0xfde5380c,
+    0xa4beea44, 0x4bdecfa9, 0xf6bb4b60, 0xbebfbc70,
+    0x289b7ec6, 0xeaa127fa, 0xd4ef3085, 0x04881d05,
+    0xd9d4d039, 0xe6db99e5, 0x1fa27cf8, 0xc4ac5665,
+    0xf4292244, 0x432aff97, 0xab9423a7, 0xfc93a039,
+    0x655b59c3, 0x8f0ccc92, 0xffeff47d, 0x85845dd1,
+    0x6fa87e4f, 0xfe2ce6e0, 0xa3014314, 0x4e0811a1,
+    0xf7537e82, 0xbd3af235, 0x2ad7d2bb, 0xeb86d391,
+];
+
+/// MD5 block function (RFC 1321) - md-5 does not export its compressor
+fn md5_compress(state: &mut [u32; 4], block: &[u8]) {
+    let mut m = [0u32; 16];
+    for (word, bytes) in m.iter_mut().zip(block.chunks_exact(4)) {
+        *word = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+    }
+
+    let [mut a, mut b, mut c, mut d] = *state;
+    for i in 0..64 {
+        let (f, g) = match i / 16 {
+            0 => ((b & c) | (!b & d), i),
+            1 => ((d & b) | (!d & c), (5 * i + 1) % 16),
+            2 => (b ^ c ^ d, (3 * i + 5) % 16),
+            _ => (c ^ (b | !d), (7 * i) % 16),
+        };
+        let f = f.wrapping_add(a).wrapping_add(MD5_K[i]).wrapping_add(m[g]);
+        a = d;
+        d = c;
+        c = b;
+        b = b.wrapping_add(f.rotate_left(MD5_SHIFTS[i]));
+    }
+
+    state[0] = state[0].wrapping_add(a);
+    state[1] = state[1].wrapping_add(b);
+    state[2] = state[2].wrapping_add(c);
+    state[3] = state[3].wrapping_add(d);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::hash::compute_hash;
+
+    const RESUMABLE: [HashAlgorithm; 5] = [
+        HashAlgorithm::Md5,
+        HashAlgorithm::Sha1,
+        HashAlgorithm::Sha256,
+        HashAlgorithm::Sha512,
+        HashAlgorithm::Crc32,
+    ];
+
+    #[test]
+    fn test_matches_streaming_hashers() {
+        // Cover empty input, padding edge cases around the block size, and multi-block data
+        let data: Vec<u8> = (0..1000u32).map(|i| (i * 31 % 251) as u8).collect();
+        for algorithm in RESUMABLE {
+            for len in [0, 1, 55, 56, 63, 64, 111, 112, 127, 128, 1000] {
+                let mut hasher = ResumableHasher::new(algorithm).unwrap();
+                hasher.update(&data[..len]);
+                assert_eq!(hasher.finalize(), compute_hash(&data[..len], algorithm), "{} len {}", algorithm.name(), len);
+            }
+        }
+    }
+
+    #[test]
+    fn test_snapshot_round_trip() {
+        let data: Vec<u8> = (0..5000u32).map(|i| (i % 256) as u8).collect();
+        for algorithm in RESUMABLE {
+            let mut first = ResumableHasher::new(algorithm).unwrap();
+            first.update(&data[..1777]);
+            let json = serde_json::to_string(&first.snapshot()).unwrap();
+
+            let mut resumed = ResumableHasher::restore(serde_json::from_str(&json).unwrap()).unwrap();
+            resumed.update(&data[1777..]);
+            assert_eq!(resumed.finalize(), compute_hash(&data, algorithm), "{}", algorithm.name());
+        }
+    }
+
+    #[test]
+    fn test_unsupported_algorithms() {
+        assert!(!ResumableHasher::supports(HashAlgorithm::Blake3));
+        assert!(!ResumableHasher::supports(HashAlgorithm::Xxh3));
+    }
+}
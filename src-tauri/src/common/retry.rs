@@ -0,0 +1,316 @@
+// Retry of transient read errors on network shares
+//
+// Verifying evidence straight from an SMB or NFS share runs into the odd
+// dropped session ("The specified network name is no longer available")
+// that a second attempt survives. `RetryingReader` wraps a segment file:
+// a read or seek failing with a transient network error is retried with
+// exponential backoff on a freshly opened handle, positioned where the
+// failed one was. Permanent errors (not found, permission denied) and
+// device loss (see `device`) fail at once, as before.
+//
+// Every retry is logged and passed to the observers registered with
+// `add_observer`: the job registry counts it against the jobs reading that
+// evidence and the GUI turns it into an "io-retry" warning event.
+
+use std::io::{self, ErrorKind, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use super::io_pool::SegmentRead;
+
+/// How often and how patiently a failed read is retried
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RetryPolicy {
+    /// Retries after the first failed attempt; 0 disables retrying
+    pub max_retries: u32,
+    /// Wait before the first retry, doubled for each further one
+    pub initial_backoff_ms: u64,
+    /// Upper bound of the wait between two attempts
+    pub max_backoff_ms: u64,
+}
+
+impl RetryPolicy {
+    pub const DEFAULT: RetryPolicy = RetryPolicy { max_retries: 3, initial_backoff_ms: 500, max_backoff_ms: 8_000 };
+
+    /// Never retry
+    pub const fn none() -> Self {
+        RetryPolicy { max_retries: 0, initial_backoff_ms: 0, max_backoff_ms: 0 }
+    }
+
+    /// Wait before retry number `retry` (0-based)
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let factor = 1u64.checked_shl(retry).unwrap_or(u64::MAX);
+        Duration::from_millis(self.initial_backoff_ms.saturating_mul(factor).min(self.max_backoff_ms))
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+static POLICY: RwLock<RetryPolicy> = RwLock::new(RetryPolicy::DEFAULT);
+
+/// Policy used by readers opened from now on
+pub fn policy() -> RetryPolicy {
+    *POLICY.read().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+pub fn set_policy(policy: RetryPolicy) {
+    *POLICY.write().unwrap_or_else(|poisoned| poisoned.into_inner()) = policy;
+}
+
+/// One retried read, as passed to the observers
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RetryEvent {
+    /// Segment file whose read failed
+    pub path: String,
+    /// 1 for the first retry
+    pub attempt: u32,
+    pub max_retries: u32,
+    /// Wait before this retry
+    pub delay_ms: u64,
+    pub error: String,
+}
+
+/// Called for every retry, on the thread that retries
+pub type RetryObserver = Box<dyn Fn(&RetryEvent) + Send + Sync>;
+
+static OBSERVERS: RwLock<Vec<RetryObserver>> = RwLock::new(Vec::new());
+
+/// Get told about every retry from now on
+pub fn add_observer(observer: RetryObserver) {
+    OBSERVERS.write().unwrap_or_else(|poisoned| poisoned.into_inner()).push(observer);
+}
+
+fn report(event: &RetryEvent) {
+    warn!(path = %event.path, attempt = event.attempt, max_retries = event.max_retries,
+        delay_ms = event.delay_ms, error = %event.error, "Retrying read after transient I/O error");
+    for observer in OBSERVERS.read().unwrap_or_else(|poisoned| poisoned.into_inner()).iter() {
+        observer(event);
+    }
+}
+
+/// Whether an I/O error is a network hiccup worth another attempt
+///
+/// Timeouts, reset or aborted connections and unreachable networks or
+/// hosts. Windows reports a dropped SMB session through raw codes:
+/// ERROR_NETNAME_DELETED (64), ERROR_UNEXP_NET_ERR (59) and
+/// ERROR_SEM_TIMEOUT (121). Not found, permission denied and device loss
+/// are never transient.
+pub fn is_transient(err: &io::Error) -> bool {
+    #[cfg(windows)]
+    const TRANSIENT_CODES: &[i32] = &[59, 64, 121];
+    #[cfg(not(windows))]
+    const TRANSIENT_CODES: &[i32] = &[];
+
+    if err.raw_os_error().is_some_and(|code| TRANSIENT_CODES.contains(&code)) {
+        return true;
+    }
+    matches!(
+        err.kind(),
+        ErrorKind::TimedOut
+            | ErrorKind::ConnectionReset
+            | ErrorKind::ConnectionAborted
+            | ErrorKind::NotConnected
+            | ErrorKind::BrokenPipe
+            | ErrorKind::NetworkDown
+            | ErrorKind::NetworkUnreachable
+            | ErrorKind::HostUnreachable
+            | ErrorKind::StaleNetworkFileHandle
+    )
+}
+
+/// Opens (and reopens) the file behind a [`RetryingReader`]
+pub type Reopen<R> = Box<dyn Fn(&Path) -> io::Result<R> + Send + Sync>;
+
+/// A segment reader that retries transient errors on a reopened handle
+pub struct RetryingReader<R> {
+    path: PathBuf,
+    reopen: Reopen<R>,
+    /// `None` after a failed attempt, until the next one reopens the file
+    inner: Option<R>,
+    /// Position of the reader, restored on a reopened handle
+    position: u64,
+    policy: RetryPolicy,
+}
+
+impl RetryingReader<std::fs::File> {
+    /// Open `path` under the current [`policy`]
+    pub fn open(path: &Path) -> io::Result<Self> {
+        Self::with_reopen(path, policy(), Box::new(|path: &Path| std::fs::File::open(path)))
+    }
+}
+
+impl<R: Read + Seek> RetryingReader<R> {
+    /// Open `path` through `reopen`, which is also used for every retry
+    pub fn with_reopen(path: &Path, policy: RetryPolicy, reopen: Reopen<R>) -> io::Result<Self> {
+        let mut reader = RetryingReader { path: path.to_path_buf(), reopen, inner: None, position: 0, policy };
+        reader.attempt(|_| Ok(()))?;
+        Ok(reader)
+    }
+
+    fn handle(&mut self) -> io::Result<&mut R> {
+        if self.inner.is_none() {
+            let mut reopened = (self.reopen)(&self.path)?;
+            if self.position > 0 {
+                reopened.seek(SeekFrom::Start(self.position))?;
+            }
+            self.inner = Some(reopened);
+        }
+        Ok(self.inner.as_mut().expect("handle was just opened"))
+    }
+
+    /// Run `op` on the handle, reopening it and trying again after a
+    /// transient failure
+    fn attempt<T>(&mut self, mut op: impl FnMut(&mut R) -> io::Result<T>) -> io::Result<T> {
+        let mut retries = 0;
+        loop {
+            match self.handle().and_then(&mut op) {
+                Err(e) if retries < self.policy.max_retries && is_transient(&e) => {
+                    self.inner = None;
+                    let delay = self.policy.backoff(retries);
+                    retries += 1;
+                    report(&RetryEvent {
+                        path: self.path.to_string_lossy().to_string(),
+                        attempt: retries,
+                        max_retries: self.policy.max_retries,
+                        delay_ms: delay.as_millis() as u64,
+                        error: e.to_string(),
+                    });
+                    std::thread::sleep(delay);
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+impl<R: Read + Seek> Read for RetryingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.attempt(|inner| inner.read(buf))?;
+        self.position += read as u64;
+        Ok(read)
+    }
+}
+
+impl<R: Read + Seek> Seek for RetryingReader<R> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.position = self.attempt(|inner| inner.seek(pos))?;
+        Ok(self.position)
+    }
+}
+
+impl<R: SegmentRead> SegmentRead for RetryingReader<R> {
+    fn size(&self) -> io::Result<u64> {
+        match &self.inner {
+            Some(inner) => inner.size(),
+            None => (self.reopen)(&self.path)?.size(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::hash::{HashAlgorithm, StreamingHasher};
+    use std::io::Cursor;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Arc;
+
+    /// Reader over `data` whose every `every`th read fails with `error`
+    /// while `failures` (shared by every handle) is above zero
+    struct FlakyReader {
+        data: Cursor<Vec<u8>>,
+        failures: Arc<AtomicU32>,
+        error: ErrorKind,
+        reads: u32,
+        every: u32,
+    }
+
+    impl Read for FlakyReader {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            self.reads += 1;
+            if self.reads.is_multiple_of(self.every) && self.failures.load(Ordering::SeqCst) > 0 {
+                self.failures.fetch_sub(1, Ordering::SeqCst);
+                return Err(io::Error::from(self.error));
+            }
+            // Short reads, so a stream takes many of them
+            let len = buf.len().min(1000);
+            self.data.read(&mut buf[..len])
+        }
+    }
+
+    impl Seek for FlakyReader {
+        fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+            self.data.seek(pos)
+        }
+    }
+
+    fn flaky(data: &[u8], failures: u32, error: ErrorKind, every: u32, opens: Arc<AtomicU32>) -> (RetryingReader<FlakyReader>, Arc<AtomicU32>) {
+        let failures = Arc::new(AtomicU32::new(failures));
+        let shared = failures.clone();
+        let data = data.to_vec();
+        let reopen: Reopen<FlakyReader> = Box::new(move |_: &Path| {
+            opens.fetch_add(1, Ordering::SeqCst);
+            Ok(FlakyReader { data: Cursor::new(data.clone()), failures: shared.clone(), error, reads: 0, every })
+        });
+        let policy = RetryPolicy { max_retries: 3, initial_backoff_ms: 0, max_backoff_ms: 0 };
+        (RetryingReader::with_reopen(Path::new("//server/share/disk.001"), policy, reopen).unwrap(), failures)
+    }
+
+    fn sha256(reader: &mut impl Read) -> io::Result<String> {
+        let mut hasher = StreamingHasher::new(HashAlgorithm::Sha256);
+        let mut buf = [0u8; 4096];
+        loop {
+            let read = reader.read(&mut buf)?;
+            if read == 0 {
+                return Ok(hasher.finalize());
+            }
+            hasher.update(&buf[..read]);
+        }
+    }
+
+    #[test]
+    fn test_transient_errors_are_retried_on_a_reopened_handle() {
+        let data: Vec<u8> = (0..50_000u32).map(|i| (i * 7 % 251) as u8).collect();
+        let expected = sha256(&mut Cursor::new(data.clone())).unwrap();
+
+        // Each reopened handle fails again on its third read, so every retry
+        // resumes mid-stream from the recorded position
+        let opens = Arc::new(AtomicU32::new(0));
+        let (mut reader, failures) = flaky(&data, 5, ErrorKind::ConnectionReset, 3, opens.clone());
+        assert_eq!(sha256(&mut reader).unwrap(), expected);
+        assert_eq!(failures.load(Ordering::SeqCst), 0);
+        assert_eq!(opens.load(Ordering::SeqCst), 6);
+
+        // More consecutive failures than the policy allows
+        let mut every_read = flaky(&data, 10, ErrorKind::TimedOut, 1, Arc::default()).0;
+        let error = sha256(&mut every_read).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::TimedOut);
+    }
+
+    #[test]
+    fn test_permanent_errors_fail_at_once() {
+        let opens = Arc::new(AtomicU32::new(0));
+        let (mut reader, failures) = flaky(&[0u8; 10_000], 1, ErrorKind::PermissionDenied, 1, opens.clone());
+        assert_eq!(sha256(&mut reader).unwrap_err().kind(), ErrorKind::PermissionDenied);
+        assert_eq!(failures.load(Ordering::SeqCst), 0);
+        assert_eq!(opens.load(Ordering::SeqCst), 1);
+
+        assert!(!is_transient(&io::Error::from(ErrorKind::NotFound)));
+        assert!(!is_transient(&io::Error::from_raw_os_error(5)));
+        assert!(is_transient(&io::Error::from(ErrorKind::StaleNetworkFileHandle)));
+        let policy = RetryPolicy::default();
+        assert_eq!(policy.backoff(0), Duration::from_millis(500));
+        assert_eq!(policy.backoff(2), Duration::from_millis(2_000));
+        assert_eq!(policy.backoff(40), Duration::from_millis(8_000));
+    }
+}
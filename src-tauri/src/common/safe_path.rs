@@ -0,0 +1,216 @@
+// Sanitizing container entry names into extraction output paths
+//
+// Names inside AD1/L01/ZIP containers are attacker-controlled: they can hold
+// `..`, absolute paths, drive letters, reserved Windows device names and
+// characters the output filesystem rejects. Every extraction builds its output
+// paths with `join_sanitized`, which always yields a path under the output
+// root and reports each change it made so the manifest keeps the original name.
+
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+/// Windows device names that cannot be used as a file name, with or without extension
+const RESERVED_NAMES: &[&str] = &[
+    "CON", "PRN", "AUX", "NUL",
+    "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8", "COM9",
+    "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// Replacement for characters and names that cannot be kept
+const REPLACEMENT: char = '_';
+
+/// Filesystem rules a name is sanitized for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TargetOs {
+    Unix,
+    Windows,
+}
+
+impl TargetOs {
+    /// Rules of the platform we are running on
+    pub const fn current() -> Self {
+        if cfg!(windows) { TargetOs::Windows } else { TargetOs::Unix }
+    }
+}
+
+/// One change made to a container path to make it safe to create
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PathChange {
+    /// Component as stored in the container
+    pub original: String,
+    /// Component as written (empty when it was dropped)
+    pub sanitized: String,
+    pub reason: String,
+}
+
+impl std::fmt::Display for PathChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "'{}' -> '{}' ({})", self.original, self.sanitized, self.reason)
+    }
+}
+
+/// Output path for a container entry, with the changes made to its name
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SanitizedPath {
+    pub path: PathBuf,
+    pub changes: Vec<PathChange>,
+}
+
+/// Make a single name safe to create on this platform
+///
+/// Returns the name to use and, if it differs, what was changed.
+pub fn sanitize_component(name: &str) -> (String, Option<PathChange>) {
+    sanitize_component_for(name, TargetOs::current())
+}
+
+/// Make a single name safe to create under the rules of `target`
+///
+/// Separators, NUL and `.`/`..` are replaced everywhere. For Windows, control
+/// and reserved characters are replaced, trailing dots and spaces stripped and
+/// device names (`PRN.txt`) prefixed with `_`.
+pub fn sanitize_component_for(name: &str, target: TargetOs) -> (String, Option<PathChange>) {
+    let mut reasons = Vec::new();
+    let windows = target == TargetOs::Windows;
+
+    let mut sanitized: String = name.chars().map(|c| {
+        let illegal = match c {
+            '/' | '\\' | '\0' => true,
+            '\x01'..='\x1f' | '<' | '>' | ':' | '"' | '|' | '?' | '*' => windows,
+            _ => false,
+        };
+        if illegal { REPLACEMENT } else { c }
+    }).collect();
+    if sanitized != name {
+        reasons.push("illegal characters replaced");
+    }
+
+    if windows {
+        let trimmed = sanitized.trim_end_matches(['.', ' ']);
+        if trimmed.len() != sanitized.len() && !trimmed.is_empty() {
+            sanitized.truncate(trimmed.len());
+            reasons.push("trailing dots or spaces removed");
+        }
+        let stem = sanitized.split('.').next().unwrap_or_default().trim_end();
+        if RESERVED_NAMES.iter().any(|reserved| stem.eq_ignore_ascii_case(reserved)) {
+            sanitized.insert(0, REPLACEMENT);
+            reasons.push("reserved device name");
+        }
+    }
+
+    if sanitized.is_empty() || sanitized.chars().all(|c| c == '.' || (windows && c == ' ')) {
+        sanitized = REPLACEMENT.to_string();
+        reasons.push("name not allowed");
+    }
+
+    let change = (!reasons.is_empty()).then(|| PathChange {
+        original: name.to_string(),
+        sanitized: sanitized.clone(),
+        reason: reasons.join(", "),
+    });
+    (sanitized, change)
+}
+
+/// Join a container path onto `output_root` on this platform
+pub fn join_sanitized(output_root: &Path, internal_path: &str) -> SanitizedPath {
+    join_sanitized_for(output_root, internal_path, TargetOs::current())
+}
+
+/// Join a container path onto `output_root`, keeping the result under it
+///
+/// `/` and `\` both separate components. Root, drive and `.`/`..` components
+/// are dropped rather than resolved, so no entry can climb out of the root;
+/// the remaining components go through [`sanitize_component_for`].
+pub fn join_sanitized_for(output_root: &Path, internal_path: &str, target: TargetOs) -> SanitizedPath {
+    let mut path = output_root.to_path_buf();
+    let mut changes = Vec::new();
+    let dropped = |original: &str, reason: &str| PathChange {
+        original: original.to_string(),
+        sanitized: String::new(),
+        reason: reason.to_string(),
+    };
+
+    if internal_path.starts_with(['/', '\\']) {
+        changes.push(dropped(internal_path, "absolute path made relative"));
+    }
+    let mut kept = 0;
+    for (index, component) in internal_path.split(['/', '\\']).enumerate() {
+        match component {
+            "" | "." => {}
+            ".." => changes.push(dropped(component, "parent directory reference removed")),
+            drive if index == 0 && is_drive_prefix(drive) => changes.push(dropped(drive, "drive letter removed")),
+            name => {
+                let (sanitized, change) = sanitize_component_for(name, target);
+                changes.extend(change);
+                path.push(sanitized);
+                kept += 1;
+            }
+        }
+    }
+    if kept == 0 {
+        path.push(REPLACEMENT.to_string());
+        changes.push(PathChange {
+            original: internal_path.to_string(),
+            sanitized: REPLACEMENT.to_string(),
+            reason: "no usable path components".to_string(),
+        });
+    }
+
+    SanitizedPath { path, changes }
+}
+
+/// `C:` style drive designator
+fn is_drive_prefix(component: &str) -> bool {
+    let bytes = component.as_bytes();
+    bytes.len() == 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn join(internal_path: &str, target: TargetOs) -> SanitizedPath {
+        join_sanitized_for(Path::new("/out"), internal_path, target)
+    }
+
+    #[test]
+    fn test_hostile_paths_stay_under_root() {
+        for target in [TargetOs::Unix, TargetOs::Windows] {
+            let joined = join("../../etc/passwd", target);
+            assert_eq!(joined.path, Path::new("/out/etc/passwd"));
+            assert_eq!(joined.changes.len(), 2);
+
+            let joined = join("C:\\Windows\\System32\\..\\..\\config", target);
+            assert_eq!(joined.path, Path::new("/out/Windows/System32/config"));
+            assert_eq!(joined.changes[0].reason, "drive letter removed");
+            assert_eq!(joined.changes.len(), 3);
+
+            let joined = join("/etc/shadow", target);
+            assert_eq!(joined.path, Path::new("/out/etc/shadow"));
+            assert_eq!(joined.changes.len(), 1);
+
+            assert_eq!(join("../..", target).path, Path::new("/out/_"));
+            assert!(join("docs/report.pdf", target).changes.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_windows_names_renamed_only_for_windows() {
+        let (name, change) = sanitize_component_for("PRN.txt", TargetOs::Windows);
+        assert_eq!(name, "_PRN.txt");
+        assert_eq!(change.unwrap().reason, "reserved device name");
+        assert_eq!(sanitize_component_for("con", TargetOs::Windows).0, "_con");
+        assert_eq!(sanitize_component_for("CONSOLE.log", TargetOs::Windows).0, "CONSOLE.log");
+        assert_eq!(sanitize_component_for("PRN.txt", TargetOs::Unix), ("PRN.txt".to_string(), None));
+
+        let (name, change) = sanitize_component_for("notes.txt  ", TargetOs::Windows);
+        assert_eq!(name, "notes.txt");
+        assert_eq!(change.unwrap().original, "notes.txt  ");
+        assert_eq!(sanitize_component_for("notes.txt  ", TargetOs::Unix).0, "notes.txt  ");
+        assert_eq!(sanitize_component_for("   ", TargetOs::Windows).0, "_");
+
+        assert_eq!(sanitize_component_for("a<b>:c?.txt", TargetOs::Windows).0, "a_b__c_.txt");
+        assert_eq!(sanitize_component_for("a<b>:c?.txt", TargetOs::Unix).0, "a<b>:c?.txt");
+        assert_eq!(sanitize_component_for("nul\0byte", TargetOs::Unix).0, "nul_byte");
+    }
+}
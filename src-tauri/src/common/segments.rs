@@ -1,22 +1,255 @@
 // Shared segment discovery utilities for forensic container parsers
 //
 // Handles multi-segment forensic images in various formats:
-// - Numbered segments: .001, .002, .003, etc.
-// - E01 segments: .E01, .E02, ..., .E99, then .Ex00, .Ex01, etc.
+// - Split segments: .001/.0001/.01, .aa/.ab (split -b), .part1 (see SPLIT_SCHEMES)
+// - EWF segments: .E01-.E99 then .EAA-.ZZZ, .Ex01-.Ex99 then .ExAA-.EzZZ,
+//   .s01-.s99 then .saa-.szz, and the logical .L01/.Lx01 forms (EwfNaming)
 // - AD1 segments: .ad1, .ad2, .ad3, etc.
+// - Listed segments: a set given file by file (segments spread over several
+//   drives), which replaces discovery for the files it names
 
+use std::cell::RefCell;
 use std::path::{Path, PathBuf};
 use std::fs;
-use tracing::{debug, trace};
+use std::sync::Arc;
+use tracing::{debug, trace, warn};
+
+use crate::logging::perf;
+
+// =============================================================================
+// Split Image Suffix Schemes (.001, .0001, .aa, .part1)
+// =============================================================================
+
+/// Counter alphabet of a split suffix
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum SuffixDigits {
+    /// 0-9, zero-padded to the suffix width
+    Decimal,
+    /// a-z counting from "aa" = 0 (split -b)
+    Letters,
+}
+
+impl SuffixDigits {
+    fn parse(self, counter: &str) -> Option<u32> {
+        match self {
+            SuffixDigits::Decimal if counter.chars().all(|c| c.is_ascii_digit()) => counter.parse().ok(),
+            SuffixDigits::Letters if counter.chars().all(|c| c.is_ascii_lowercase()) => Some(
+                counter.bytes().fold(0, |index, c| index * 26 + (c - b'a') as u32),
+            ),
+            _ => None,
+        }
+    }
+
+    fn format(self, index: u32, width: usize) -> Option<String> {
+        match self {
+            SuffixDigits::Decimal => Some(format!("{:0width$}", index, width = width)),
+            SuffixDigits::Letters => {
+                let mut rest = index;
+                let mut letters = vec![b'a'; width];
+                for letter in letters.iter_mut().rev() {
+                    *letter = b'a' + (rest % 26) as u8;
+                    rest /= 26;
+                }
+                (rest == 0).then(|| String::from_utf8_lossy(&letters).into_owned())
+            }
+        }
+    }
+}
+
+/// One way of naming the segments of a split image
+struct SplitScheme {
+    /// Fixed text before the counter, matched case-insensitively
+    prefix: &'static str,
+    digits: SuffixDigits,
+    min_width: usize,
+    max_width: usize,
+    /// Padding may differ within a set (part9, part10)
+    variable_width: bool,
+    /// Index of the first segment (split -d also starts a decimal set at 0)
+    first: u32,
+    /// Only recognised after a raw image extension (image.dd.aa) - short
+    /// letter suffixes would otherwise match ordinary extensions
+    needs_raw_base: bool,
+}
+
+/// Suffix schemes of split images, tried in order
+const SPLIT_SCHEMES: &[SplitScheme] = &[
+    // image.001, image.0001, image.01 (FTK Imager, dd, split -d)
+    SplitScheme { prefix: "", digits: SuffixDigits::Decimal, min_width: 2, max_width: 4, variable_width: false, first: 1, needs_raw_base: false },
+    // image.dd.aa, image.dd.ab, ... (split -b)
+    SplitScheme { prefix: "", digits: SuffixDigits::Letters, min_width: 2, max_width: 2, variable_width: false, first: 0, needs_raw_base: true },
+    // image.dd.part1, image.dd.part02
+    SplitScheme { prefix: "part", digits: SuffixDigits::Decimal, min_width: 1, max_width: 4, variable_width: true, first: 1, needs_raw_base: false },
+];
+
+/// Image extensions that may carry a letter split suffix
+const RAW_SPLIT_BASES: &[&str] = &["dd", "raw", "img", "bin"];
+
+/// A file name recognised as one segment of a split image
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SplitName {
+    /// File name without the segment suffix ("image.dd" for image.dd.aa)
+    pub base: String,
+    /// Position in the set (.001 = 1, .aa = 0, .part1 = 1)
+    pub index: u32,
+    scheme: usize,
+    width: usize,
+    /// Suffix written in upper case (IMAGE.DD.AB)
+    upper: bool,
+}
+
+impl SplitName {
+    /// Parse a file name against the split suffix schemes
+    pub fn parse(filename: &str) -> Option<Self> {
+        let (base, suffix) = filename.rsplit_once('.')?;
+        if base.is_empty() {
+            return None;
+        }
+        let lower = suffix.to_ascii_lowercase();
+        SPLIT_SCHEMES.iter().enumerate().find_map(|(scheme_idx, scheme)| {
+            let counter = lower.strip_prefix(scheme.prefix)?;
+            if counter.len() < scheme.min_width || counter.len() > scheme.max_width {
+                return None;
+            }
+            if scheme.needs_raw_base {
+                let base_ext = base.rsplit_once('.').map(|(_, ext)| ext.to_ascii_lowercase());
+                if !base_ext.is_some_and(|ext| RAW_SPLIT_BASES.contains(&ext.as_str())) {
+                    return None;
+                }
+            }
+            Some(Self {
+                base: base.to_string(),
+                index: scheme.digits.parse(counter)?,
+                scheme: scheme_idx,
+                width: counter.len(),
+                upper: suffix.chars().any(|c| c.is_ascii_uppercase()),
+            })
+        })
+    }
+
+    /// Index the set's first segment has
+    pub fn first_index(&self) -> u32 {
+        SPLIT_SCHEMES[self.scheme].first
+    }
+
+    /// Whether this is the first segment of its set
+    pub fn is_first(&self) -> bool {
+        self.index <= self.first_index()
+    }
+
+    /// Whether `other` names a segment of the same set (case-insensitive)
+    pub fn same_set(&self, other: &SplitName) -> bool {
+        self.scheme == other.scheme
+            && (self.width == other.width || SPLIT_SCHEMES[self.scheme].variable_width)
+            && self.base.eq_ignore_ascii_case(&other.base)
+    }
+
+    /// File name of segment `index` of this set, in this name's case
+    pub fn sibling(&self, index: u32) -> Option<String> {
+        let scheme = &SPLIT_SCHEMES[self.scheme];
+        let width = if scheme.variable_width && !self.padded() { 0 } else { self.width };
+        let suffix = format!("{}{}", scheme.prefix, scheme.digits.format(index, width)?);
+        let suffix = if self.upper { suffix.to_uppercase() } else { suffix };
+        Some(format!("{}.{}", self.base, suffix))
+    }
+
+    fn padded(&self) -> bool {
+        self.width > 1 && self.index < 10u32.pow(self.width as u32 - 1)
+    }
+}
+
+/// List the segments of `split`'s set in `dir` as (index, path, size), sorted
+///
+/// Matching is case-insensitive, so sets copied through case-insensitive
+/// filesystems (IMAGE.001, image.002) are still found whole.
+pub fn list_split_segments(dir: &Path, split: &SplitName) -> Vec<(u32, PathBuf, u64)> {
+    let mut found: Vec<(u32, PathBuf, u64)> = match fs::read_dir(dir) {
+        Ok(entries) => entries.flatten()
+            .filter_map(|entry| {
+                let other = SplitName::parse(&entry.file_name().to_string_lossy())?;
+                if !split.same_set(&other) {
+                    return None;
+                }
+                let meta = entry.metadata().ok().filter(|m| m.is_file())?;
+                Some((other.index, entry.path(), meta.len()))
+            })
+            .collect(),
+        Err(e) => {
+            debug!(?dir, error = %e, "Failed to list segment directory");
+            Vec::new()
+        }
+    };
+    found.sort_by_key(|(index, _, _)| *index);
+    found.dedup_by_key(|(index, _, _)| *index);
+    found
+}
+
+// =============================================================================
+// Listed Segments (sets split across directories)
+// =============================================================================
+
+thread_local! {
+    /// Segment list in effect on this thread, handed on to threads it starts
+    static LISTED_SEGMENTS: RefCell<Option<Arc<[PathBuf]>>> = const { RefCell::new(None) };
+}
+
+/// Run `work` with `segments` as the segment set of any of its files
+///
+/// Discovery of a path in the list returns the list as given instead of
+/// looking beside the file, so a set whose segments live on two drives
+/// opens like one in a single directory. The caller checks the order (see
+/// `ewf::with_segment_list` and `raw::with_segment_list`).
+pub fn with_listed_segments<T>(segments: Vec<PathBuf>, work: impl FnOnce() -> T) -> T {
+    struct Restore(Option<Arc<[PathBuf]>>);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            set_current_thread_listed_segments(self.0.take());
+        }
+    }
+    let _restore = Restore(LISTED_SEGMENTS.with(|listed| listed.replace(Some(segments.into()))));
+    work()
+}
+
+/// Segment list in effect on the calling thread
+pub fn current_listed_segments() -> Option<Arc<[PathBuf]>> {
+    LISTED_SEGMENTS.with(|listed| listed.borrow().clone())
+}
+
+/// Put a list taken with [`current_listed_segments`] in effect on the calling
+/// thread (for worker threads of a listed set)
+pub fn set_current_thread_listed_segments(segments: Option<Arc<[PathBuf]>>) {
+    LISTED_SEGMENTS.with(|listed| *listed.borrow_mut() = segments);
+}
+
+/// The listed set `path` belongs to, if any
+fn listed_set_of(path: &str) -> Option<Arc<[PathBuf]>> {
+    current_listed_segments().filter(|segments| segments.iter().any(|segment| segment == Path::new(path)))
+}
+
+/// Whether `path` is a segment of the list in effect on this thread
+pub fn is_listed_segment(path: &str) -> bool {
+    listed_set_of(path).is_some()
+}
 
 // =============================================================================
-// Numbered Segment Discovery (.001, .002, etc.)
+// Numbered Segment Discovery (.001, .aa, .part1, etc.)
 // =============================================================================
 
-/// Discover numbered segments (.001, .002, etc.) starting from any segment
+/// Discover split image segments (.001, .0001, .aa, .part1, etc.) starting from any segment
 /// 
-/// Returns (paths, sizes) sorted by segment number
+/// Returns (paths, sizes) sorted by segment number. A segment missing
+/// before the last one found is an error: reading across the gap would
+/// produce a wrong image hash.
 pub fn discover_numbered_segments(path: &str) -> Result<(Vec<PathBuf>, Vec<u64>), String> {
+    let _phase = perf::phase(perf::SEGMENT_DISCOVERY).entered();
+    if let Some(listed) = listed_set_of(path) {
+        debug!(path, segment_count = listed.len(), "Using listed segments");
+        let sizes = listed.iter()
+            .map(|segment| fs::metadata(segment).map(|meta| meta.len())
+                .map_err(|e| format!("Failed to get size of {}: {}", segment.display(), e)))
+            .collect::<Result<_, _>>()?;
+        return Ok((listed.to_vec(), sizes));
+    }
     debug!(path, "Discovering numbered segments");
     let path_obj = Path::new(path);
     let parent = path_obj.parent().unwrap_or(Path::new("."));
@@ -24,17 +257,20 @@ pub fn discover_numbered_segments(path: &str) -> Result<(Vec<PathBuf>, Vec<u64>)
         .ok_or("Invalid filename")?
         .to_string_lossy();
 
-    let lower = filename.to_lowercase();
-    
-    // Check if this is a numbered segment (.001, .002, etc.)
-    if let Some(ext_start) = lower.rfind('.') {
-        let ext = &lower[ext_start + 1..];
-        if ext.len() == 3 && ext.chars().all(|c| c.is_ascii_digit()) {
-            // Multi-segment numbered format
-            let base = &filename[..filename.len() - 4]; // Remove .XXX
-            trace!(base, "Detected numbered segment format");
-            return discover_numbered_segments_by_base(parent, base);
+    if let Some(split) = SplitName::parse(&filename) {
+        trace!(base = %split.base, "Detected split segment format");
+        let found = list_split_segments(parent, &split);
+        let Some(start) = found.first().map(|(index, _, _)| (*index).min(split.first_index())) else {
+            return Err("No segments found".to_string());
+        };
+        for (expected, (index, _, _)) in (start..).zip(&found) {
+            if *index != expected {
+                let name = split.sibling(expected).unwrap_or_else(|| format!("#{}", expected));
+                return Err(format!("Missing segment {} ({} other segments found)", name, found.len()));
+            }
         }
+        debug!(segment_count = found.len(), "Found split segments");
+        return Ok(found.into_iter().map(|(_, path, size)| (path, size)).unzip());
     }
 
     // Single file or other format - just use the one file
@@ -46,184 +282,299 @@ pub fn discover_numbered_segments(path: &str) -> Result<(Vec<PathBuf>, Vec<u64>)
     Ok((vec![path_obj.to_path_buf()], vec![size]))
 }
 
-/// Discover numbered segments by base name
-fn discover_numbered_segments_by_base(dir: &Path, base: &str) -> Result<(Vec<PathBuf>, Vec<u64>), String> {
-    trace!(?dir, base, "Discovering segments by base name");
-    // First try direct path construction
-    let result = discover_numbered_segments_direct(dir, base);
-    if let Ok((segs, _)) = &result {
-        if !segs.is_empty() {
-            debug!(segment_count = segs.len(), "Found segments via direct path");
-            return result;
-        }
-    }
-    
-    // Fall back to directory scan for case-insensitive matching
-    trace!("Falling back to directory scan for case-insensitive matching");
-    discover_numbered_segments_scan(dir, base)
+// =============================================================================
+// EWF Segment Discovery (.E01-.E99, .EAA..., .Ex01..., .s01..., .L01..., .Lx01...)
+// =============================================================================
+
+/// Naming scheme of an EWF segment set
+///
+/// Counting goes from 01 to 99, then continues with letters: EnCase writes
+/// image.E99, image.EAA ... image.EZZ, image.FAA; EWF2 goes from .Ex99 to
+/// .ExAA ... .ExZZ, .EyAA. A set uses one scheme throughout, so discovery
+/// never mixes them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EwfNaming {
+    /// EnCase / EWF1 physical images (.E01)
+    E01,
+    /// EnCase 7+ EWF2 physical images (.Ex01)
+    Ex01,
+    /// SMART images (.s01 ... .s99, .saa ... .szz)
+    S01,
+    /// EWF1 logical evidence (.L01)
+    L01,
+    /// EWF2 logical evidence (.Lx01)
+    Lx01,
 }
 
-/// Try to find segments by constructing paths directly
-fn discover_numbered_segments_direct(dir: &Path, base: &str) -> Result<(Vec<PathBuf>, Vec<u64>), String> {
-    let mut segments = Vec::new();
-    let mut sizes = Vec::new();
-    let mut found_any = false;
-    let mut consecutive_missing = 0;
+impl EwfNaming {
+    const ALL: [EwfNaming; 5] = [EwfNaming::E01, EwfNaming::Ex01, EwfNaming::S01, EwfNaming::L01, EwfNaming::Lx01];
 
-    for num in 1..=999 {
-        let segment_name = format!("{}.{:03}", base, num);
-        let segment_path = dir.join(&segment_name);
-        
-        // Try original case first
-        if segment_path.exists() {
-            let size = fs::metadata(&segment_path)
-                .map_err(|e| format!("Failed to get segment size: {}", e))?
-                .len();
-            segments.push(segment_path);
-            sizes.push(size);
-            found_any = true;
-            consecutive_missing = 0;
-            continue;
+    /// (letter, whether this is the four-letter EWF2 form)
+    fn shape(self) -> (u8, bool) {
+        match self {
+            EwfNaming::E01 => (b'E', false),
+            EwfNaming::Ex01 => (b'E', true),
+            EwfNaming::S01 => (b'S', false),
+            EwfNaming::L01 => (b'L', false),
+            EwfNaming::Lx01 => (b'L', true),
         }
-        
-        // Try lowercase
-        let segment_name_lower = segment_name.to_lowercase();
-        let segment_path_lower = dir.join(&segment_name_lower);
-        if segment_path_lower.exists() {
-            let size = fs::metadata(&segment_path_lower)
-                .map_err(|e| format!("Failed to get segment size: {}", e))?
-                .len();
-            segments.push(segment_path_lower);
-            sizes.push(size);
-            found_any = true;
-            consecutive_missing = 0;
-            continue;
+    }
+
+    /// Scheme name for messages, e.g. "Ex01"
+    pub fn label(self) -> &'static str {
+        match self {
+            EwfNaming::E01 => "E01",
+            EwfNaming::Ex01 => "Ex01",
+            EwfNaming::S01 => "s01",
+            EwfNaming::L01 => "L01",
+            EwfNaming::Lx01 => "Lx01",
         }
-        
-        // Try uppercase
-        let segment_name_upper = segment_name.to_uppercase();
-        let segment_path_upper = dir.join(&segment_name_upper);
-        if segment_path_upper.exists() {
-            let size = fs::metadata(&segment_path_upper)
-                .map_err(|e| format!("Failed to get segment size: {}", e))?
-                .len();
-            segments.push(segment_path_upper);
-            sizes.push(size);
-            found_any = true;
-            consecutive_missing = 0;
-            continue;
+    }
+
+    /// Physical and logical sets of one case may share a stem
+    fn logical(self) -> bool {
+        matches!(self, EwfNaming::L01 | EwfNaming::Lx01)
+    }
+
+    /// Letters the first counter position runs through once the numbers
+    /// are used up (upper case)
+    fn lead_range(self) -> (u8, u8) {
+        match self.shape() {
+            (_, true) => (b'X', b'Z'),
+            (b'S', _) => (b'S', b'S'),
+            (letter, _) => (letter, b'Z'),
         }
-        
-        // Segment not found
-        consecutive_missing += 1;
-        
-        // If we've found segments before and now have a gap, stop
-        if found_any {
-            break; // End of sequence
-        } else if consecutive_missing > 10 {
-            // Haven't found any and checked first 10 numbers - give up
-            break;
+    }
+
+    /// Extension of segment `number` (1-based), in the case the imaging
+    /// tools write it; None past the last name the scheme has
+    pub fn extension(self, number: u32) -> Option<String> {
+        let (letter, ewf2) = self.shape();
+        let letter = letter as char;
+        let ext = match number {
+            0 => return None,
+            1..=99 if ewf2 => format!("{letter}x{number:02}"),
+            1..=99 => format!("{letter}{number:02}"),
+            _ => {
+                let index = number - 100;
+                let (lead_base, lead_last) = self.lead_range();
+                let lead = u8::try_from(index / (26 * 26)).ok()?.checked_add(lead_base).filter(|lead| *lead <= lead_last)?;
+                let pair: String = [index / 26 % 26, index % 26].iter().map(|&i| (b'A' + i as u8) as char).collect();
+                if ewf2 {
+                    format!("{letter}{}{pair}", lead.to_ascii_lowercase() as char)
+                } else {
+                    format!("{}{pair}", lead as char)
+                }
+            }
+        };
+        Some(if self == EwfNaming::S01 { ext.to_lowercase() } else { ext })
+    }
+
+    /// Segment number of `ext` in this scheme (case-insensitive)
+    pub fn number(self, ext: &str) -> Option<u32> {
+        let (letter, ewf2) = self.shape();
+        let ext = ext.to_ascii_uppercase();
+        let (lead, counter) = match (ewf2, ext.as_bytes()) {
+            (true, [first, lead, counter @ ..]) if *first == letter && counter.len() == 2 => (*lead, counter),
+            (false, [lead, counter @ ..]) if counter.len() == 2 => (*lead, counter),
+            _ => return None,
+        };
+        let (lead_base, lead_last) = self.lead_range();
+        if counter.iter().all(u8::is_ascii_digit) {
+            let number = ((counter[0] - b'0') * 10 + counter[1] - b'0') as u32;
+            let numbered_lead = if ewf2 { b'X' } else { letter };
+            return (lead == numbered_lead && number >= 1).then_some(number);
+        }
+        if !counter.iter().all(u8::is_ascii_uppercase) || !(lead_base..=lead_last).contains(&lead) {
+            return None;
         }
+        let index = ((lead - lead_base) as u32 * 26 + (counter[0] - b'A') as u32) * 26 + (counter[1] - b'A') as u32;
+        Some(100 + index)
     }
 
-    Ok((segments, sizes))
+    /// Whether `ext` is a letter continuation written the way the tools
+    /// write it (.EAA, .ExAA, .LAA - not .sql or .log)
+    fn written_continuation(self, ext: &str) -> bool {
+        let (letter, ewf2) = self.shape();
+        let prefix = if ewf2 { format!("{}x", letter as char) } else { (letter as char).to_string() };
+        self != EwfNaming::S01
+            && ext.strip_prefix(&prefix).is_some_and(|pair| pair.bytes().all(|b| b.is_ascii_uppercase()))
+    }
 }
 
-/// Scan directory to find segments with case-insensitive matching
-fn discover_numbered_segments_scan(dir: &Path, base: &str) -> Result<(Vec<PathBuf>, Vec<u64>), String> {
-    let base_lower = base.to_lowercase();
-    let mut found_segments: Vec<(u32, PathBuf, u64)> = Vec::new();
-    
-    if let Ok(entries) = fs::read_dir(dir) {
-        for entry in entries.flatten() {
-            let filename = entry.file_name().to_string_lossy().to_string();
-            let filename_lower = filename.to_lowercase();
-            
-            // Check if filename matches our pattern (base.XXX where XXX is numeric)
-            if let Some(dot_pos) = filename_lower.rfind('.') {
-                let file_base = &filename_lower[..dot_pos];
-                let ext = &filename_lower[dot_pos + 1..];
-                
-                if file_base == base_lower && ext.len() == 3 && ext.chars().all(|c| c.is_ascii_digit()) {
-                    if let Ok(num) = ext.parse::<u32>() {
-                        if let Ok(meta) = entry.metadata() {
-                            found_segments.push((num, entry.path(), meta.len()));
-                        }
-                    }
-                }
-            }
+/// A file name recognised as one segment of an EWF set
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EwfSegmentName {
+    /// File name without the extension
+    pub stem: String,
+    pub naming: EwfNaming,
+    pub number: u32,
+    /// Extension written in lower case (image.e02)
+    lower: bool,
+}
+
+impl EwfSegmentName {
+    /// Parse a file name against the EWF naming schemes
+    ///
+    /// Letter continuations are only recognised as the tools write them
+    /// (.EAA, .ExAA, .LAA) so that ordinary extensions such as .log or .sql
+    /// are not taken for segments; discovery of a known set accepts any case.
+    pub fn parse(filename: &str) -> Option<Self> {
+        let (stem, ext) = filename.rsplit_once('.')?;
+        if stem.is_empty() {
+            return None;
         }
+        EwfNaming::ALL.iter().find_map(|&naming| {
+            let number = naming.number(ext)?;
+            if number >= 100 && !naming.written_continuation(ext) {
+                return None;
+            }
+            Some(Self {
+                stem: stem.to_string(),
+                naming,
+                number,
+                lower: ext == ext.to_ascii_lowercase(),
+            })
+        })
     }
-    
-    if found_segments.is_empty() {
-        return Err("No segments found".to_string());
+
+    /// File name of segment `number` of this set, in this name's case
+    pub fn sibling(&self, number: u32) -> Option<String> {
+        let ext = self.naming.extension(number)?;
+        Some(format!("{}.{}", self.stem, if self.lower { ext.to_lowercase() } else { ext }))
     }
-    
-    // Sort by segment number
-    found_segments.sort_by_key(|(num, _, _)| *num);
-    
-    let segments: Vec<PathBuf> = found_segments.iter().map(|(_, p, _)| p.clone()).collect();
-    let sizes: Vec<u64> = found_segments.iter().map(|(_, _, s)| *s).collect();
-    
-    Ok((segments, sizes))
 }
 
-// =============================================================================
-// E01 Segment Discovery (.E01, .E02, ..., .Ex01, etc.)
-// =============================================================================
+/// Segment number of a later EWF segment (.E02, .EAA, .Ex02, .s02, .L02, ...)
+/// and the path its set's first segment would have (same extension case)
+///
+/// Returns None for first segments and other names. The first segment is
+/// not checked for existence.
+pub fn ewf_first_segment(path: &Path) -> Option<(u32, PathBuf)> {
+    let name = EwfSegmentName::parse(&path.file_name()?.to_string_lossy())?;
+    let first = name.sibling(1).filter(|_| name.number >= 2)?;
+    Some((name.number, path.with_file_name(first)))
+}
 
-/// Discover E01 segments (.E01, .E02, ..., .E99, .Ex00, .Ex01, etc.)
-pub fn discover_e01_segments(base_path: &str) -> Result<Vec<PathBuf>, String> {
-    debug!(base_path, "Discovering E01 segments");
+/// The existing file at `path`, or at its all-lowercase name
+pub fn existing_case_variant(path: &Path) -> Option<PathBuf> {
+    if path.exists() {
+        return Some(path.to_path_buf());
+    }
+    let lower = path.with_file_name(path.file_name()?.to_string_lossy().to_lowercase());
+    lower.exists().then_some(lower)
+}
+
+/// The segments of one EWF set, found by its naming scheme
+#[derive(Clone, Debug, Default)]
+pub struct EwfSegmentSet {
+    /// Scheme of the set, None for a file not named like a segment
+    pub naming: Option<EwfNaming>,
+    /// Segment files in order, up to the first missing number
+    pub paths: Vec<PathBuf>,
+    /// Files next to the set that share its stem but are not part of it:
+    /// other naming schemes, or segments after a gap
+    pub conflicts: Vec<String>,
+}
+
+/// Discover the segments of an EWF set in the naming scheme of `path`
+///
+/// When a later segment is given, discovery starts from the set's first
+/// segment if it is present, otherwise from the given segment onwards.
+/// Segments of other schemes sharing the stem (an .Ex01 copy next to an
+/// .E01 set) are never joined to the set; they and any segments past a gap
+/// are reported in `conflicts`.
+pub fn discover_ewf_segments(base_path: &str) -> Result<EwfSegmentSet, String> {
+    let _phase = perf::phase(perf::SEGMENT_DISCOVERY).entered();
+    if let Some(listed) = listed_set_of(base_path) {
+        debug!(base_path, segment_count = listed.len(), "Using listed EWF segments");
+        let naming = listed[0].file_name()
+            .and_then(|name| EwfSegmentName::parse(&name.to_string_lossy()))
+            .map(|name| name.naming);
+        return Ok(EwfSegmentSet { naming, paths: listed.to_vec(), conflicts: Vec::new() });
+    }
+    debug!(base_path, "Discovering EWF segments");
     let path = Path::new(base_path);
     let parent = path.parent().ok_or("Invalid path")?;
-    let stem = path.file_stem().ok_or("No filename")?.to_string_lossy();
-    
-    let mut paths = vec![path.to_path_buf()];
-    
-    for i in 2..=999 {
-        let segment_name = if i <= 99 {
-            format!("{}.E{:02}", stem, i)
-        } else {
-            // After E99 comes Ex00, Ex01, etc.
-            format!("{}.Ex{:02}", stem, i - 99)
-        };
-        
-        let segment_path = parent.join(&segment_name);
-        if segment_path.exists() {
-            trace!(segment = i, ?segment_path, "Found E01 segment");
-            paths.push(segment_path);
-        } else {
-            // Also try lowercase
-            let segment_name_lower = segment_name.to_lowercase();
-            let segment_path_lower = parent.join(&segment_name_lower);
-            if segment_path_lower.exists() {
-                trace!(segment = i, ?segment_path_lower, "Found E01 segment (lowercase)");
-                paths.push(segment_path_lower);
-            } else {
-                break;
+    let filename = path.file_name().ok_or("No filename")?.to_string_lossy().to_string();
+    let Some(name) = EwfSegmentName::parse(&filename) else {
+        return Ok(EwfSegmentSet { naming: None, paths: vec![path.to_path_buf()], conflicts: Vec::new() });
+    };
+
+    let (mut number, mut paths) = (name.number, vec![path.to_path_buf()]);
+    if let Some(first) = name.sibling(1).filter(|_| name.number > 1).and_then(|first| existing_case_variant(&parent.join(first))) {
+        debug!(?first, "Anchoring EWF discovery on the first segment");
+        (number, paths) = (1, vec![first]);
+    }
+    let start = number;
+    while let Some(next) = name.sibling(number + 1) {
+        match existing_case_variant(&parent.join(&next)) {
+            Some(segment) => {
+                trace!(segment = number + 1, ?segment, "Found EWF segment");
+                paths.push(segment);
+                number += 1;
             }
+            None => break,
         }
     }
-    
-    debug!(segment_count = paths.len(), "E01 segments discovered");
-    Ok(paths)
+
+    let conflicts = naming_conflicts(parent, &name, start, number);
+    if !conflicts.is_empty() {
+        warn!(base_path, ?conflicts, "Files next to the EWF set do not belong to it");
+    }
+    debug!(segment_count = paths.len(), naming = name.naming.label(), "EWF segments discovered");
+    Ok(EwfSegmentSet { naming: Some(name.naming), paths, conflicts })
+}
+
+/// Files in `dir` sharing the set's stem that discovery left out: segments
+/// of another scheme for the same kind of evidence, and segments numbered
+/// past `last` (a gap)
+fn naming_conflicts(dir: &Path, name: &EwfSegmentName, first: u32, last: u32) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(dir) else { return Vec::new() };
+    let mut conflicts: Vec<(u32, String)> = entries.flatten()
+        .filter_map(|entry| {
+            let filename = entry.file_name().to_string_lossy().to_string();
+            let other = EwfSegmentName::parse(&filename)
+                .filter(|other| other.stem.eq_ignore_ascii_case(&name.stem))?;
+            if other.naming == name.naming {
+                return (other.number > last).then(|| (other.number, format!(
+                    "{} follows a missing segment ({} not found)",
+                    filename,
+                    name.sibling(last + 1).unwrap_or_default(),
+                )));
+            }
+            (other.naming.logical() == name.naming.logical()).then(|| (0, format!(
+                "{} uses {} naming but the set is named {}, so it is not part of the set",
+                filename,
+                other.naming.label(),
+                name.naming.label(),
+            )))
+        })
+        .collect();
+    conflicts.sort();
+    trace!(first, last, conflicts = conflicts.len(), "Checked EWF naming");
+    conflicts.into_iter().map(|(_, message)| message).collect()
+}
+
+/// Discover EWF segment paths (see [`discover_ewf_segments`])
+pub fn discover_e01_segments(base_path: &str) -> Result<Vec<PathBuf>, String> {
+    discover_ewf_segments(base_path).map(|set| set.paths)
+}
+
+/// SMART segment extension for a 1-based segment number:
+/// s01..s99, then saa..szz (like libewf)
+#[cfg(test)]
+fn smart_segment_extension(segment: usize) -> Option<String> {
+    EwfNaming::S01.extension(segment as u32)
 }
 
 // =============================================================================
 // Utility Functions
 // =============================================================================
 
-/// Check if filename is a numbered segment (.001, .002, etc.)
+/// Check if filename is a split image segment (.001, .0001, .aa, .part1, etc.)
 pub fn is_numbered_segment(filename: &str) -> bool {
-    let lower = filename.to_lowercase();
-    if let Some(ext_start) = lower.rfind('.') {
-        let ext = &lower[ext_start + 1..];
-        if ext.len() == 3 && ext.chars().all(|c| c.is_ascii_digit()) {
-            return true;
-        }
-    }
-    false
+    SplitName::parse(filename).is_some()
 }
 
 /// Check if file is part of a segmented series (E01 or numbered)
@@ -236,56 +587,28 @@ pub fn is_segmented_file(filename: &str) -> bool {
 }
 
 /// Get the base name without segment number for grouping
-/// Example: "image.001" -> "image", "image.E01" -> "image"
+/// Example: "image.001" -> "image", "image.E01" -> "image", "image.dd.aa" -> "image.dd"
 pub fn get_segment_basename(filename: &str) -> String {
-    let lower = filename.to_lowercase();
-    
-    // Handle .E01, .E02, etc.
-    if lower.ends_with(".e01") {
-        return filename[..filename.len() - 4].to_string();
+    // Handle .E01, .E02, .EAA, .Ex01, SMART .s01 and logical .L01
+    if let Some(ewf) = EwfSegmentName::parse(filename) {
+        return ewf.stem;
     }
     
-    // Handle .001, .002, etc.
-    if let Some(dot_pos) = filename.rfind('.') {
-        let ext = &filename[dot_pos + 1..];
-        if ext.len() == 3 && ext.chars().all(|c| c.is_ascii_digit()) {
-            return filename[..dot_pos].to_string();
-        }
+    // Handle .001, .aa, .part1, etc.
+    if let Some(split) = SplitName::parse(filename) {
+        return split.base;
     }
     
     filename.to_string()
 }
 
 /// Get the path to the first available segment given any segment path
-/// Tries .001 first, then scans for the lowest numbered segment
 pub fn get_first_segment_path(path: &str) -> String {
-    let path_obj = Path::new(path);
-    if let Some(parent) = path_obj.parent() {
-        if let Some(filename) = path_obj.file_name() {
-            let filename_str = filename.to_string_lossy();
-            if let Some(dot_pos) = filename_str.rfind('.') {
-                let base = &filename_str[..dot_pos];
-                
-                // Try .001 first (most common)
-                let first_seg = format!("{}.001", base);
-                let first_path = parent.join(&first_seg);
-                if first_path.exists() {
-                    return first_path.to_string_lossy().to_string();
-                }
-                
-                // If .001 doesn't exist, find the lowest numbered segment
-                for num in 2..=999 {
-                    let seg_name = format!("{}.{:03}", base, num);
-                    let seg_path = parent.join(&seg_name);
-                    if seg_path.exists() {
-                        return seg_path.to_string_lossy().to_string();
-                    }
-                }
-            }
-        }
-    }
-    // Return original path if we can't find any lower segment
-    path.to_string()
+    discover_numbered_segments(path).ok()
+        .and_then(|(segments, _)| segments.into_iter().next())
+        .map(|first| first.to_string_lossy().to_string())
+        // Return original path if we can't find any lower segment
+        .unwrap_or_else(|| path.to_string())
 }
 
 /// Calculate total size of all segments in a series
@@ -362,7 +685,66 @@ mod tests {
         assert!(is_numbered_segment("IMAGE.001"));
         assert!(!is_numbered_segment("image.dd"));
         assert!(!is_numbered_segment("image.e01"));
-        assert!(!is_numbered_segment("image.01")); // Only 2 digits
+        assert!(is_numbered_segment("image.01"));
+        assert!(is_numbered_segment("image.0001"));
+        assert!(!is_numbered_segment("image.1")); // Only 1 digit
+        assert!(is_numbered_segment("disk.dd.aa"));
+        assert!(is_numbered_segment("disk.RAW.AB"));
+        assert!(!is_numbered_segment("archive.gz")); // Letters need a raw base
+        assert!(is_numbered_segment("disk.dd.part1"));
+        assert!(is_numbered_segment("disk.img.part12"));
+    }
+
+    fn discover_names(dir: &Path, start: &str) -> Result<Vec<String>, String> {
+        let (paths, _) = discover_numbered_segments(dir.join(start).to_str().unwrap())?;
+        Ok(paths.iter().map(|p| p.file_name().unwrap().to_string_lossy().to_string()).collect())
+    }
+
+    #[test]
+    fn test_discover_split_schemes() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in [
+            "disk.dd.aa", "disk.dd.AB", "DISK.DD.ac",
+            "four.0001", "four.0002", "four.001",
+            "two.01", "two.02",
+            "set.dd.part9", "set.dd.part10", "set.dd.part1",
+        ] {
+            std::fs::write(dir.path().join(name), b"x").unwrap();
+        }
+        for n in 2..=8 {
+            std::fs::write(dir.path().join(format!("set.dd.part{}", n)), b"x").unwrap();
+        }
+
+        // Mixed case, started from the middle
+        assert_eq!(discover_names(dir.path(), "disk.dd.AB").unwrap(), vec!["disk.dd.aa", "disk.dd.AB", "DISK.DD.ac"]);
+        // Width is part of the scheme: four.001 is a different set
+        assert_eq!(discover_names(dir.path(), "four.0002").unwrap(), vec!["four.0001", "four.0002"]);
+        assert_eq!(discover_names(dir.path(), "two.02").unwrap(), vec!["two.01", "two.02"]);
+        let parts = discover_names(dir.path(), "set.dd.part9").unwrap();
+        assert_eq!(parts.len(), 10);
+        assert_eq!(parts[0], "set.dd.part1");
+        assert_eq!(parts[9], "set.dd.part10");
+    }
+
+    #[test]
+    fn test_discover_split_gap_is_error() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["disk.dd.aa", "disk.dd.ac"] {
+            std::fs::write(dir.path().join(name), b"x").unwrap();
+        }
+        let err = discover_names(dir.path(), "disk.dd.aa").unwrap_err();
+        assert!(err.contains("disk.dd.ab"), "{}", err);
+    }
+
+    #[test]
+    fn test_split_sibling_names() {
+        let split = SplitName::parse("IMAGE.DD.AB").unwrap();
+        assert_eq!(split.index, 1);
+        assert!(!split.is_first());
+        assert_eq!(split.sibling(0).as_deref(), Some("IMAGE.DD.AA"));
+        assert_eq!(split.sibling(26).as_deref(), Some("IMAGE.DD.BA"));
+        assert_eq!(split.sibling(26 * 26), None);
+        assert_eq!(SplitName::parse("x.part02").unwrap().sibling(10).as_deref(), Some("x.part10"));
     }
 
     #[test]
@@ -371,6 +753,99 @@ mod tests {
         assert_eq!(get_segment_basename("image.E01"), "image");
         assert_eq!(get_segment_basename("image.dd"), "image.dd");
         assert_eq!(get_segment_basename("SCHARDT.001"), "SCHARDT");
+        assert_eq!(get_segment_basename("legacy.s01"), "legacy");
+    }
+
+    #[test]
+    fn test_smart_segment_extension() {
+        assert_eq!(smart_segment_extension(2).as_deref(), Some("s02"));
+        assert_eq!(smart_segment_extension(99).as_deref(), Some("s99"));
+        assert_eq!(smart_segment_extension(100).as_deref(), Some("saa"));
+        assert_eq!(smart_segment_extension(101).as_deref(), Some("sab"));
+        assert_eq!(smart_segment_extension(100 + 26 * 26), None);
+    }
+
+    #[test]
+    fn test_discover_smart_segments() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["legacy.s01", "legacy.s02", "legacy.s03", "legacy.E02"] {
+            std::fs::write(dir.path().join(name), b"x").unwrap();
+        }
+        let first = dir.path().join("legacy.s01");
+        let segments = discover_e01_segments(first.to_str().unwrap()).unwrap();
+        let names: Vec<_> = segments.iter()
+            .map(|p| p.file_name().unwrap().to_string_lossy().to_string())
+            .collect();
+        assert_eq!(names, vec!["legacy.s01", "legacy.s02", "legacy.s03"]);
+    }
+
+    #[test]
+    fn test_discovery_anchors_on_first_segment() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["case.E01", "case.E02", "case.E03"] {
+            std::fs::write(dir.path().join(name), b"x").unwrap();
+        }
+        let third = dir.path().join("case.E03");
+        assert_eq!(ewf_first_segment(&third), Some((3, dir.path().join("case.E01"))));
+        assert_eq!(ewf_first_segment(&dir.path().join("case.E01")), None);
+        assert_eq!(ewf_first_segment(Path::new("legacy.s12")), Some((12, PathBuf::from("legacy.s01"))));
+
+        let segments = discover_e01_segments(third.to_str().unwrap()).unwrap();
+        assert_eq!(segments.first(), Some(&dir.path().join("case.E01")));
+        assert_eq!(segments.len(), 3);
+
+        // Without the first segment, discovery starts where it was asked to
+        std::fs::remove_file(dir.path().join("case.E01")).unwrap();
+        let segments = discover_e01_segments(third.to_str().unwrap()).unwrap();
+        assert_eq!(segments, vec![third]);
+    }
+
+    #[test]
+    fn test_ewf_naming_round_trips() {
+        for (naming, number, ext) in [
+            (EwfNaming::E01, 99, "E99"), (EwfNaming::E01, 100, "EAA"), (EwfNaming::E01, 101, "EAB"),
+            (EwfNaming::E01, 100 + 26 * 26, "FAA"), (EwfNaming::Ex01, 100, "ExAA"), (EwfNaming::Ex01, 100 + 26 * 26, "EyAA"),
+            (EwfNaming::S01, 100, "saa"), (EwfNaming::L01, 2, "L02"), (EwfNaming::Lx01, 100, "LxAA"),
+        ] {
+            assert_eq!(naming.extension(number).as_deref(), Some(ext), "{naming:?} {number}");
+            assert_eq!(naming.number(ext), Some(number), "{ext}");
+        }
+        assert_eq!(EwfNaming::E01.number("Ex01"), None);
+        assert_eq!(EwfNaming::Ex01.number("E01"), None);
+
+        let name = EwfSegmentName::parse("image.ex03").unwrap();
+        assert_eq!((name.naming, name.number), (EwfNaming::Ex01, 3));
+        assert_eq!(name.sibling(1).as_deref(), Some("image.ex01"));
+        assert!(EwfSegmentName::parse("image.exe").is_none());
+    }
+
+    #[test]
+    fn test_mixed_naming_schemes_are_reported_not_joined() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut names: Vec<String> = (1..=99).map(|i| format!("mixed.E{i:02}")).collect();
+        names.extend(["mixed.EAA", "mixed.EAB", "mixed.Ex01", "mixed.Ex02", "mixed.L01"].map(String::from));
+        for name in &names {
+            std::fs::write(dir.path().join(name), b"x").unwrap();
+        }
+
+        let set = discover_ewf_segments(dir.path().join("mixed.E01").to_str().unwrap()).unwrap();
+        assert_eq!(set.naming, Some(EwfNaming::E01));
+        assert_eq!(set.paths.len(), 101);
+        assert_eq!(set.paths[99], dir.path().join("mixed.EAA"));
+        assert_eq!(set.conflicts.len(), 2, "{:?}", set.conflicts);
+        assert!(set.conflicts[0].starts_with("mixed.Ex0"), "{}", set.conflicts[0]);
+        assert!(set.conflicts[0].contains("uses Ex01 naming but the set is named E01"));
+
+        // The Ex01 copy is its own set, with the E01 files as the conflicts
+        let set = discover_ewf_segments(dir.path().join("mixed.Ex01").to_str().unwrap()).unwrap();
+        assert_eq!(set.paths.len(), 2);
+        assert_eq!(set.conflicts.len(), 101);
+
+        // A segment past a gap is named rather than silently dropped
+        std::fs::remove_file(dir.path().join("mixed.E50")).unwrap();
+        let set = discover_ewf_segments(dir.path().join("mixed.E01").to_str().unwrap()).unwrap();
+        assert_eq!(set.paths.len(), 49);
+        assert!(set.conflicts.iter().any(|c| c == "mixed.E51 follows a missing segment (mixed.E50 not found)"), "{:?}", set.conflicts);
     }
 
     #[test]
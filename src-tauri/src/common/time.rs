@@ -0,0 +1,207 @@
+// Timestamp normalization for container metadata
+//
+// Every metadata source writes dates its own way:
+// - FTK companion logs: "Mon Jan 13 14:02:01 2020"
+// - E01 header: "2020 1 13 14 2 1" (header2 uses POSIX seconds)
+// - UFD INI: "2020-01-13 14:02:01" or "2020-01-13T14:02:01", local time
+// - Files on disk: SystemTime
+//
+// All of them become a `Timestamp` holding an ISO 8601 UTC form for display
+// and sorting next to the untouched source text. Unrecognized text is kept
+// as-is with an empty `iso_utc`, never rejected.
+
+use std::fmt;
+use std::time::SystemTime;
+
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, SecondsFormat, Utc};
+use serde::{Deserialize, Deserializer, Serialize};
+
+/// Naive layouts tried in order; whitespace is collapsed before matching
+const NAIVE_FORMATS: &[&str] = &[
+    "%Y-%m-%d %H:%M:%S%.f",
+    "%Y-%m-%dT%H:%M:%S%.f",
+    "%Y-%m-%d %H:%M",
+    "%Y/%m/%d %H:%M:%S%.f",
+    // FTK Imager ("Mon Jan 13 14:02:01 2020"), ctime style
+    "%a %b %d %H:%M:%S %Y",
+    "%b %d %H:%M:%S %Y",
+];
+
+/// Layouts carrying a UTC offset
+const OFFSET_FORMATS: &[&str] = &[
+    "%Y-%m-%d %H:%M:%S%.f %z",
+    "%Y-%m-%d %H:%M:%S%.f%z",
+    "%a %b %d %H:%M:%S %Y %z",
+];
+
+/// Suffixes naming UTC explicitly
+const UTC_SUFFIXES: &[&str] = &["UTC", "GMT", "Z"];
+
+/// A metadata date in canonical form, with the source text preserved
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct Timestamp {
+    /// "YYYY-MM-DDTHH:MM:SSZ", empty when `original` wasn't recognized.
+    /// Without `timezone_known` the source's wall-clock time is taken as UTC.
+    pub iso_utc: String,
+    /// Text exactly as the source wrote it
+    pub original: String,
+    /// The source stated its offset (or is an absolute instant)
+    pub timezone_known: bool,
+}
+
+impl Timestamp {
+    /// Parse `original` in any of the known source formats
+    ///
+    /// Unrecognized values come back with an empty `iso_utc`.
+    pub fn parse(original: &str) -> Self {
+        let text = original.split_whitespace().collect::<Vec<_>>().join(" ");
+        match parse_text(&text) {
+            Some((utc, timezone_known)) => Self { iso_utc: iso(&utc), original: original.to_string(), timezone_known },
+            None => Self::unparsed(original),
+        }
+    }
+
+    /// A known instant, e.g. POSIX seconds from an E01 header2
+    pub fn from_utc(utc: DateTime<Utc>, original: impl Into<String>) -> Self {
+        Self { iso_utc: iso(&utc), original: original.into(), timezone_known: true }
+    }
+
+    /// File system time; `original` is the local time with its offset
+    pub fn from_system_time(time: SystemTime) -> Self {
+        let local: DateTime<Local> = time.into();
+        Self::from_utc(local.with_timezone(&Utc), local.format("%Y-%m-%d %H:%M:%S %:z").to_string())
+    }
+
+    fn unparsed(original: &str) -> Self {
+        Self { iso_utc: String::new(), original: original.to_string(), timezone_known: false }
+    }
+
+    /// Whether `original` was recognized
+    pub fn is_parsed(&self) -> bool {
+        !self.iso_utc.is_empty()
+    }
+
+    /// The normalized instant, if recognized
+    pub fn to_utc(&self) -> Option<DateTime<Utc>> {
+        DateTime::parse_from_rfc3339(&self.iso_utc).ok().map(|dt| dt.with_timezone(&Utc))
+    }
+}
+
+/// Normalized form when recognized, otherwise the source text
+impl fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(if self.is_parsed() { &self.iso_utc } else { &self.original })
+    }
+}
+
+/// Accepts the struct or a bare date string (data saved before normalization)
+impl<'de> Deserialize<'de> for Timestamp {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        struct Fields {
+            #[serde(default)]
+            iso_utc: String,
+            original: String,
+            #[serde(default)]
+            timezone_known: bool,
+        }
+
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Repr {
+            Fields(Fields),
+            Text(String),
+        }
+
+        Ok(match Repr::deserialize(deserializer)? {
+            Repr::Fields(f) => Self { iso_utc: f.iso_utc, original: f.original, timezone_known: f.timezone_known },
+            Repr::Text(text) => Self::parse(&text),
+        })
+    }
+}
+
+fn iso(utc: &DateTime<Utc>) -> String {
+    utc.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+/// UTC instant and whether the text stated its zone
+fn parse_text(text: &str) -> Option<(DateTime<Utc>, bool)> {
+    if text.is_empty() {
+        return None;
+    }
+    if let Ok(dt) = DateTime::parse_from_rfc3339(text) {
+        return Some((dt.with_timezone(&Utc), true));
+    }
+    if let Some(dt) = OFFSET_FORMATS.iter().find_map(|f| DateTime::parse_from_str(text, f).ok()) {
+        return Some((dt.with_timezone(&Utc), true));
+    }
+    // "... UTC" / "...Z" - naive text in a stated zone
+    let stripped = UTC_SUFFIXES.iter().find_map(|suffix| text.strip_suffix(suffix)).map(str::trim_end);
+    if let Some(naive) = stripped.and_then(parse_naive) {
+        return Some((naive.and_utc(), true));
+    }
+    parse_naive(text).map(|naive| (naive.and_utc(), false))
+}
+
+fn parse_naive(text: &str) -> Option<NaiveDateTime> {
+    NAIVE_FORMATS.iter()
+        .find_map(|f| NaiveDateTime::parse_from_str(text, f).ok())
+        .or_else(|| parse_numeric_fields(text))
+}
+
+/// E01 header layout: "YYYY M D H M S", fields unpadded
+fn parse_numeric_fields(text: &str) -> Option<NaiveDateTime> {
+    let fields: Vec<u32> = text.split(' ').map(|part| part.parse().ok()).collect::<Option<_>>()?;
+    let [year, month, day, hour, minute, second] = fields[..] else { return None };
+    NaiveDate::from_ymd_opt(year as i32, month, day)?.and_hms_opt(hour, minute, second)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_source_formats() {
+        let ftk = Timestamp::parse("Mon Jan 13 14:02:01 2020");
+        assert_eq!((ftk.iso_utc.as_str(), ftk.timezone_known), ("2020-01-13T14:02:01Z", false));
+        assert_eq!(Timestamp::parse("Fri Jun  7 09:05:00 2019").iso_utc, "2019-06-07T09:05:00Z");
+
+        let e01 = Timestamp::parse("2020 1 13 14 2 1");
+        assert_eq!((e01.iso_utc.as_str(), e01.timezone_known), ("2020-01-13T14:02:01Z", false));
+        assert_eq!(e01.original, "2020 1 13 14 2 1");
+
+        assert_eq!(Timestamp::parse("2020-01-13 14:02:01").iso_utc, "2020-01-13T14:02:01Z");
+        assert_eq!(Timestamp::parse("2020-01-13T14:02:01.250").iso_utc, "2020-01-13T14:02:01Z");
+
+        let offset = Timestamp::parse("2020-01-13T14:02:01+02:00");
+        assert_eq!((offset.iso_utc.as_str(), offset.timezone_known), ("2020-01-13T12:02:01Z", true));
+        let utc = Timestamp::parse("2020-01-13 14:02:01 UTC");
+        assert_eq!((utc.iso_utc.as_str(), utc.timezone_known), ("2020-01-13T14:02:01Z", true));
+    }
+
+    #[test]
+    fn test_absolute_times() {
+        let header2 = Timestamp::from_utc(DateTime::from_timestamp(1_578_924_121, 0).unwrap(), "1578924121");
+        assert_eq!(header2.iso_utc, "2020-01-13T14:02:01Z");
+        assert!(header2.timezone_known);
+
+        let mtime = SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(1_578_924_121);
+        let file = Timestamp::from_system_time(mtime);
+        assert_eq!(file.iso_utc, "2020-01-13T14:02:01Z");
+        assert_eq!(file.to_utc(), DateTime::from_timestamp(1_578_924_121, 0));
+        assert_eq!(Timestamp::parse(&file.original).iso_utc, file.iso_utc);
+    }
+
+    #[test]
+    fn test_unparseable_value_passes_through() {
+        let ts = Timestamp::parse("sometime last week");
+        assert_eq!(ts, Timestamp { iso_utc: String::new(), original: "sometime last week".into(), timezone_known: false });
+        assert_eq!(ts.to_string(), "sometime last week");
+        assert!(!Timestamp::parse("2020 13 45 99 0 0").is_parsed());
+
+        let old: Timestamp = serde_json::from_str("\"2020-01-13 14:02:01\"").unwrap();
+        assert_eq!(old.iso_utc, "2020-01-13T14:02:01Z");
+        let json = serde_json::to_string(&old).unwrap();
+        assert_eq!(serde_json::from_str::<Timestamp>(&json).unwrap(), old);
+    }
+}
@@ -0,0 +1,258 @@
+// Size and kind of the volume an output path lives on
+//
+// Before an extraction the UI shows whether the destination can hold it
+// ("120 GB free, extraction needs ~310 GB") and warns about writing to a
+// removable drive or a network share. Linux reads statvfs and /proc/mounts
+// (plus sysfs for removable disks), macOS statfs, Windows
+// GetDiskFreeSpaceEx, GetDriveType and GetVolumeInformation. Anything the
+// platform can't tell is left `None`.
+
+use serde::Serialize;
+use std::path::Path;
+
+/// The volume holding a path
+#[derive(Serialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct StorageVolume {
+    /// The path asked about
+    pub path: String,
+    /// Mount point (Unix) or volume root such as `D:\` (Windows)
+    pub mount_point: Option<String>,
+    /// Filesystem type, e.g. "ext4", "apfs", "NTFS"
+    pub filesystem: Option<String>,
+    pub total_bytes: Option<u64>,
+    /// Free bytes, including any reserved for the administrator
+    pub free_bytes: Option<u64>,
+    /// Free bytes this process may write
+    pub available_bytes: Option<u64>,
+    /// USB drive, card reader or optical drive
+    pub removable: Option<bool>,
+    /// Network share (NFS, SMB, ...)
+    pub network: Option<bool>,
+}
+
+/// Filesystem types of network shares (as Linux and macOS name them)
+const NETWORK_FILESYSTEMS: &[&str] = &[
+    "nfs", "nfs4", "cifs", "smb3", "smbfs", "afpfs", "webdav", "davfs", "ncpfs",
+    "afs", "9p", "ceph", "glusterfs", "lustre", "fuse.sshfs", "sshfs", "fuse.rclone",
+];
+
+/// Describe the volume holding `path`
+///
+/// `path` need not exist yet; its nearest existing ancestor is queried.
+pub fn volume_info(path: &Path) -> Result<StorageVolume, String> {
+    let existing = path.ancestors()
+        .find(|ancestor| ancestor.exists())
+        .ok_or_else(|| format!("No existing folder on the path {}", path.display()))?;
+    let mut volume = StorageVolume { path: path.display().to_string(), ..Default::default() };
+    os::describe(existing, &mut volume);
+    Ok(volume)
+}
+
+fn is_network_filesystem(filesystem: &str) -> bool {
+    NETWORK_FILESYSTEMS.contains(&filesystem.to_lowercase().as_str())
+}
+
+/// Source device and filesystem type of `mount_point` in /proc/mounts format
+///
+/// The last entry wins: a later mount hides earlier ones on the same point.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+pub(crate) fn find_mount(mounts: &str, mount_point: &str) -> Option<(String, String)> {
+    // Spaces, tabs and backslashes in paths are written as octal escapes
+    let unescape = |field: &str| {
+        field.replace("\\040", " ").replace("\\011", "\t").replace("\\012", "\n").replace("\\134", "\\")
+    };
+    mounts.lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let (source, target, filesystem) = (fields.next()?, fields.next()?, fields.next()?);
+            (unescape(target) == mount_point).then(|| (unescape(source), filesystem.to_string()))
+        })
+        .next_back()
+}
+
+#[cfg(target_os = "linux")]
+mod os {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+    use std::path::Path;
+
+    use super::{find_mount, is_network_filesystem, StorageVolume};
+    use crate::common::device::mount_point;
+
+    pub fn describe(path: &Path, volume: &mut StorageVolume) {
+        if let Ok(c_path) = CString::new(path.as_os_str().as_bytes()) {
+            // SAFETY: statvfs only writes into the zeroed struct we pass it
+            let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+            if unsafe { libc::statvfs(c_path.as_ptr(), &mut stat) } == 0 {
+                let fragment = stat.f_frsize as u64;
+                volume.total_bytes = Some(stat.f_blocks as u64 * fragment);
+                volume.free_bytes = Some(stat.f_bfree as u64 * fragment);
+                volume.available_bytes = Some(stat.f_bavail as u64 * fragment);
+            }
+        }
+
+        let Ok(mount) = mount_point(path) else { return };
+        let mount = mount.to_string_lossy().to_string();
+        let entry = std::fs::read_to_string("/proc/mounts").ok().and_then(|mounts| find_mount(&mounts, &mount));
+        volume.mount_point = Some(mount);
+        let Some((source, filesystem)) = entry else { return };
+        let network = is_network_filesystem(&filesystem);
+        volume.network = Some(network);
+        volume.removable = if network { Some(false) } else { removable(&source) };
+        volume.filesystem = Some(filesystem);
+    }
+
+    /// Whether the block device `source` (e.g. /dev/sdb1) sits on USB or is
+    /// flagged removable; `None` for sources that aren't block devices
+    fn removable(source: &str) -> Option<bool> {
+        let device = Path::new(source).canonicalize().ok()?;
+        let name = device.strip_prefix("/dev").ok()?.to_str()?.to_string();
+        // /sys/class/block/<name> links into the device tree, through the
+        // USB controller for USB disks
+        let sys = Path::new("/sys/class/block").join(&name).canonicalize().ok()?;
+        if sys.to_string_lossy().contains("/usb") {
+            return Some(true);
+        }
+        // Partitions carry no flag of their own; their disk is the parent
+        [sys.join("removable"), sys.parent()?.join("removable")].iter()
+            .find_map(|flag| std::fs::read_to_string(flag).ok())
+            .map(|flag| flag.trim() == "1")
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod os {
+    use std::ffi::{CStr, CString};
+    use std::os::unix::ffi::OsStrExt;
+    use std::path::Path;
+
+    use super::{is_network_filesystem, StorageVolume};
+
+    /// statfs flag of volumes on a locally attached device (sys/mount.h)
+    const MNT_LOCAL: u32 = 0x1000;
+
+    pub fn describe(path: &Path, volume: &mut StorageVolume) {
+        let Ok(c_path) = CString::new(path.as_os_str().as_bytes()) else { return };
+        // SAFETY: statfs only writes into the zeroed struct we pass it
+        let mut stat: libc::statfs = unsafe { std::mem::zeroed() };
+        if unsafe { libc::statfs(c_path.as_ptr(), &mut stat) } != 0 {
+            return;
+        }
+        let block = stat.f_bsize as u64;
+        volume.total_bytes = Some(stat.f_blocks * block);
+        volume.free_bytes = Some(stat.f_bfree * block);
+        volume.available_bytes = Some(stat.f_bavail * block);
+        // SAFETY: the kernel NUL-terminates both names within their arrays
+        let text = |chars: &[libc::c_char]| unsafe { CStr::from_ptr(chars.as_ptr()) }.to_string_lossy().to_string();
+        let filesystem = text(&stat.f_fstypename);
+        let mount = text(&stat.f_mntonname);
+        let network = is_network_filesystem(&filesystem);
+        volume.network = Some(network);
+        // External drives (and opened disk images) mount under /Volumes; the
+        // startup disk's volumes don't
+        volume.removable = Some(!network && mount.starts_with("/Volumes/") && stat.f_flags & MNT_LOCAL != 0);
+        volume.filesystem = Some(filesystem);
+        volume.mount_point = Some(mount);
+    }
+}
+
+#[cfg(windows)]
+mod os {
+    use std::os::windows::ffi::OsStrExt;
+    use std::path::Path;
+    use windows_sys::Win32::Storage::FileSystem::{
+        GetDiskFreeSpaceExW, GetDriveTypeW, GetVolumeInformationW, GetVolumePathNameW,
+    };
+
+    use super::StorageVolume;
+
+    // GetDriveType results
+    const DRIVE_REMOVABLE: u32 = 2;
+    const DRIVE_REMOTE: u32 = 4;
+    const DRIVE_CDROM: u32 = 5;
+
+    /// Buffer for a volume root or filesystem name (MAX_PATH + 1)
+    const NAME_CHARS: usize = 261;
+
+    pub fn describe(path: &Path, volume: &mut StorageVolume) {
+        let wide: Vec<u16> = path.as_os_str().encode_wide().chain(Some(0)).collect();
+        let (mut available, mut total, mut free) = (0u64, 0u64, 0u64);
+        // SAFETY: `wide` is NUL-terminated and outlives the call
+        if unsafe { GetDiskFreeSpaceExW(wide.as_ptr(), &mut available, &mut total, &mut free) } != 0 {
+            volume.total_bytes = Some(total);
+            volume.free_bytes = Some(free);
+            volume.available_bytes = Some(available);
+        }
+
+        let mut root = [0u16; NAME_CHARS];
+        // SAFETY: the buffer length is passed along; the result is NUL-terminated
+        if unsafe { GetVolumePathNameW(wide.as_ptr(), root.as_mut_ptr(), NAME_CHARS as u32) } == 0 {
+            return;
+        }
+        let text = |chars: &[u16]| String::from_utf16_lossy(&chars[..chars.iter().position(|&c| c == 0).unwrap_or(chars.len())]);
+        volume.mount_point = Some(text(&root));
+
+        // SAFETY: `root` is NUL-terminated
+        let drive_type = unsafe { GetDriveTypeW(root.as_ptr()) };
+        volume.removable = Some(matches!(drive_type, DRIVE_REMOVABLE | DRIVE_CDROM));
+        volume.network = Some(drive_type == DRIVE_REMOTE);
+
+        let mut filesystem = [0u16; NAME_CHARS];
+        // SAFETY: unused outputs may be null; the name buffer length is passed along
+        let ok = unsafe {
+            GetVolumeInformationW(
+                root.as_ptr(),
+                std::ptr::null_mut(), 0,
+                std::ptr::null_mut(), std::ptr::null_mut(), std::ptr::null_mut(),
+                filesystem.as_mut_ptr(), NAME_CHARS as u32,
+            )
+        };
+        if ok != 0 {
+            volume.filesystem = Some(text(&filesystem));
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+mod os {
+    use std::path::Path;
+
+    use super::StorageVolume;
+
+    pub fn describe(_path: &Path, _volume: &mut StorageVolume) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_temp_dir_volume_is_sane() {
+        let dir = tempfile::tempdir().unwrap();
+        // A destination that doesn't exist yet is looked up through its parent
+        let volume = volume_info(&dir.path().join("not/yet/created")).unwrap();
+        if cfg!(any(target_os = "linux", target_os = "macos", windows)) {
+            let (total, free, available) = (volume.total_bytes.unwrap(), volume.free_bytes.unwrap(), volume.available_bytes.unwrap());
+            assert!(total > 0 && free <= total && available <= free, "{volume:?}");
+            let mount = volume.mount_point.clone().unwrap();
+            let resolved = dir.path().canonicalize().unwrap();
+            assert!(resolved.starts_with(&mount), "{mount} does not hold {}", resolved.display());
+            assert!(volume.filesystem.as_deref().is_some_and(|fs| !fs.is_empty()), "{volume:?}");
+        }
+        assert_eq!(crate::common::available_space(dir.path()), volume.available_bytes);
+    }
+
+    #[test]
+    fn test_find_mount_reads_proc_mounts_lines() {
+        let mounts = "/dev/sda2 / ext4 rw,relatime 0 0\n\
+            //nas/cases /mnt/case\\040files cifs rw,vers=3.0 0 0\n\
+            /dev/sdb1 /media/usb vfat rw 0 0\n\
+            /dev/sdc1 /media/usb exfat rw 0 0\n";
+        assert_eq!(find_mount(mounts, "/"), Some(("/dev/sda2".to_string(), "ext4".to_string())));
+        assert_eq!(find_mount(mounts, "/mnt/case files"), Some(("//nas/cases".to_string(), "cifs".to_string())));
+        // The later mount hides the earlier one
+        assert_eq!(find_mount(mounts, "/media/usb"), Some(("/dev/sdc1".to_string(), "exfat".to_string())));
+        assert_eq!(find_mount(mounts, "/srv"), None);
+        assert!(is_network_filesystem("cifs") && is_network_filesystem("NFS4") && !is_network_filesystem("ext4"));
+    }
+}
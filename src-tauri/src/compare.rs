@@ -0,0 +1,365 @@
+//! Byte-for-byte comparison of two disk images
+//!
+//! `compare_images` proves that a conversion or a re-acquisition holds the
+//! same data as the original, or says where it does not. Both images are
+//! read through their decoded view - a raw image with its split segments
+//! stitched together, an E01 through its chunks - as [`triage::open_image`]
+//! opens them, so an E01 compares equal to the dd of the same disk.
+//!
+//! Each image is read on its own thread, a few large blocks ahead of the
+//! comparison, so the two reads and the comparison overlap. Differing bytes
+//! are gathered into ranges of consecutive differing bytes; the report lists
+//! the first `max_ranges` of them and counts the rest. When the lengths
+//! differ the common prefix is compared and the difference reported.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, SyncSender};
+use std::thread;
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info};
+
+use crate::common::resources;
+use crate::triage;
+
+/// Differing ranges listed by default
+pub const DEFAULT_MAX_RANGES: usize = 1000;
+
+/// Blocks each reader may be ahead of the comparison
+const READ_AHEAD_BLOCKS: usize = 2;
+
+/// Bytes compared at once before looking for the differing ones
+const COMPARE_SLICE: usize = 4096;
+
+/// Options for [`compare_images`]
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CompareOptions {
+    /// Differing ranges listed in the report; later ones are only counted
+    pub max_ranges: usize,
+    /// Bytes read from each image at a time (default
+    /// `resources::io_buffer_size`)
+    pub buffer_size: Option<usize>,
+}
+
+impl Default for CompareOptions {
+    fn default() -> Self {
+        Self { max_ranges: DEFAULT_MAX_RANGES, buffer_size: None }
+    }
+}
+
+/// Consecutive differing bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct DiffRange {
+    pub offset: u64,
+    pub length: u64,
+}
+
+/// Progress of a running comparison
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompareProgress {
+    pub bytes_done: u64,
+    /// Bytes to compare: the size of the shorter image
+    pub bytes_total: u64,
+    pub differing_bytes: u64,
+}
+
+/// Result of [`compare_images`]
+#[derive(Debug, Clone, Serialize)]
+pub struct ImageComparison {
+    pub path_a: String,
+    pub path_b: String,
+    /// Decoded sizes
+    pub size_a: u64,
+    pub size_b: u64,
+    /// Same size and the same bytes
+    pub identical: bool,
+    /// `size_b - size_a`; only the first `compared_bytes` are compared
+    pub length_difference: i64,
+    pub compared_bytes: u64,
+    /// First differing byte, or the end of the shorter image when only the
+    /// lengths differ
+    pub first_difference: Option<u64>,
+    /// Differing bytes within `compared_bytes`
+    pub differing_bytes: u64,
+    /// All differing ranges, listed or not
+    pub range_count: u64,
+    /// The first `max_ranges` differing ranges, in offset order
+    pub ranges: Vec<DiffRange>,
+    /// More ranges differ than are listed
+    pub ranges_truncated: bool,
+    pub duration_secs: f64,
+}
+
+/// What a reader thread sends
+enum Block {
+    /// The image opened, with its decoded size
+    Opened(u64),
+    Data(Vec<u8>),
+}
+
+/// Compare two E01 (any EWF variant) or raw images byte for byte
+///
+/// Stops with an error when `cancel` is set; `progress` gets the bytes
+/// compared so far after each block.
+pub fn compare_images<F>(
+    path_a: &str,
+    path_b: &str,
+    options: &CompareOptions,
+    cancel: &AtomicBool,
+    mut progress: F,
+) -> Result<ImageComparison, String>
+where
+    F: FnMut(CompareProgress),
+{
+    let started = Instant::now();
+    let buffer_size = options.buffer_size.unwrap_or_else(resources::io_buffer_size).max(COMPARE_SLICE);
+    thread::scope(|scope| {
+        let (tx_a, rx_a) = mpsc::sync_channel(READ_AHEAD_BLOCKS);
+        let (tx_b, rx_b) = mpsc::sync_channel(READ_AHEAD_BLOCKS);
+        scope.spawn(move || read_image(path_a, buffer_size, cancel, tx_a));
+        scope.spawn(move || read_image(path_b, buffer_size, cancel, tx_b));
+        let mut side_a = Side::open(path_a, rx_a)?;
+        let mut side_b = Side::open(path_b, rx_b)?;
+
+        let total = side_a.size.min(side_b.size);
+        debug!(path_a, path_b, size_a = side_a.size, size_b = side_b.size, buffer_size, "Comparing images");
+        let mut diff = Differences::new(options.max_ranges);
+        let mut done = 0u64;
+        progress(CompareProgress { bytes_done: 0, bytes_total: total, differing_bytes: 0 });
+        while done < total {
+            if cancel.load(Ordering::Relaxed) {
+                return Err(format!("Comparison cancelled at byte {done} of {total}"));
+            }
+            let (a, b) = (side_a.next_bytes()?, side_b.next_bytes()?);
+            let len = a.len().min(b.len()).min((total - done) as usize);
+            diff.compare(done, &a[..len], &b[..len]);
+            side_a.consume(len);
+            side_b.consume(len);
+            done += len as u64;
+            progress(CompareProgress { bytes_done: done, bytes_total: total, differing_bytes: diff.bytes });
+        }
+        diff.close(total);
+
+        let (size_a, size_b) = (side_a.size, side_b.size);
+        let first_difference = diff.first.or((size_a != size_b).then_some(total));
+        let comparison = ImageComparison {
+            path_a: path_a.to_string(),
+            path_b: path_b.to_string(),
+            size_a,
+            size_b,
+            identical: first_difference.is_none(),
+            length_difference: size_b as i64 - size_a as i64,
+            compared_bytes: total,
+            first_difference,
+            differing_bytes: diff.bytes,
+            range_count: diff.count,
+            ranges_truncated: diff.count > diff.ranges.len() as u64,
+            ranges: diff.ranges,
+            duration_secs: started.elapsed().as_secs_f64(),
+        };
+        info!(
+            path_a,
+            path_b,
+            identical = comparison.identical,
+            differing_bytes = comparison.differing_bytes,
+            ranges = comparison.range_count,
+            "Image comparison complete"
+        );
+        Ok(comparison)
+    })
+}
+
+/// Reader thread: open the image, then send its blocks in order until the
+/// end, an error, cancellation or the comparison hanging up
+fn read_image(path: &str, buffer_size: usize, cancel: &AtomicBool, tx: SyncSender<Result<Block, String>>) {
+    let mut image = match triage::open_image(path) {
+        Ok(image) => image,
+        Err(e) => {
+            let _ = tx.send(Err(format!("{path}: {e}")));
+            return;
+        }
+    };
+    let size = image.size();
+    if tx.send(Ok(Block::Opened(size))).is_err() {
+        return;
+    }
+    let mut offset = 0u64;
+    while offset < size && !cancel.load(Ordering::Relaxed) {
+        let block = match image.read_at(offset, (size - offset).min(buffer_size as u64) as usize) {
+            Ok(data) if data.is_empty() => Err(format!("{path}: image ended at byte {offset} of {size}")),
+            Ok(data) => {
+                offset += data.len() as u64;
+                Ok(Block::Data(data))
+            }
+            Err(e) => Err(format!("{path}: read failed at byte {offset}: {e}")),
+        };
+        let failed = block.is_err();
+        if tx.send(block).is_err() || failed {
+            return;
+        }
+    }
+}
+
+/// The comparison's end of one reader, with the unconsumed rest of a block
+struct Side<'a> {
+    path: &'a str,
+    size: u64,
+    rx: Receiver<Result<Block, String>>,
+    block: Vec<u8>,
+    position: usize,
+}
+
+impl<'a> Side<'a> {
+    fn open(path: &'a str, rx: Receiver<Result<Block, String>>) -> Result<Self, String> {
+        match rx.recv() {
+            Ok(Ok(Block::Opened(size))) => Ok(Self { path, size, rx, block: Vec::new(), position: 0 }),
+            Ok(Err(e)) => Err(e),
+            Ok(Ok(Block::Data(_))) | Err(_) => Err(format!("{path}: reader stopped before opening the image")),
+        }
+    }
+
+    /// Unconsumed bytes, waiting for the next block when there are none
+    fn next_bytes(&mut self) -> Result<&[u8], String> {
+        if self.position == self.block.len() {
+            self.block = match self.rx.recv() {
+                Ok(Ok(Block::Data(data))) => data,
+                Ok(Err(e)) => return Err(e),
+                Ok(Ok(Block::Opened(_))) | Err(_) => return Err(format!("{}: reader stopped early", self.path)),
+            };
+            self.position = 0;
+        }
+        Ok(&self.block[self.position..])
+    }
+
+    fn consume(&mut self, len: usize) {
+        self.position += len;
+    }
+}
+
+/// Differing ranges found so far
+struct Differences {
+    max_ranges: usize,
+    ranges: Vec<DiffRange>,
+    /// Start of the range still being extended
+    open: Option<u64>,
+    first: Option<u64>,
+    bytes: u64,
+    count: u64,
+}
+
+impl Differences {
+    fn new(max_ranges: usize) -> Self {
+        Self { max_ranges, ranges: Vec::new(), open: None, first: None, bytes: 0, count: 0 }
+    }
+
+    /// Compare `a` and `b`, which start at image offset `offset`
+    fn compare(&mut self, offset: u64, a: &[u8], b: &[u8]) {
+        for (index, (a, b)) in a.chunks(COMPARE_SLICE).zip(b.chunks(COMPARE_SLICE)).enumerate() {
+            let slice_offset = offset + (index * COMPARE_SLICE) as u64;
+            if a == b {
+                self.close(slice_offset);
+                continue;
+            }
+            for (position, (x, y)) in a.iter().zip(b).enumerate() {
+                let at = slice_offset + position as u64;
+                if x == y {
+                    self.close(at);
+                } else {
+                    self.bytes += 1;
+                    self.first.get_or_insert(at);
+                    self.open.get_or_insert(at);
+                }
+            }
+        }
+    }
+
+    /// End the open range, if any, before `end`
+    fn close(&mut self, end: u64) {
+        let Some(start) = self.open.take() else { return };
+        self.count += 1;
+        if self.ranges.len() < self.max_ranges {
+            self.ranges.push(DiffRange { offset: start, length: end - start });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn data(len: usize) -> Vec<u8> {
+        let mut state = 0x2545_f491_4f6c_dd1du64;
+        (0..len).map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state as u8
+        }).collect()
+    }
+
+    fn options(max_ranges: usize) -> CompareOptions {
+        CompareOptions { max_ranges, buffer_size: Some(64 * 1024) }
+    }
+
+    #[test]
+    fn test_reports_the_differing_ranges_of_split_and_single_images() {
+        let dir = tempfile::tempdir().unwrap();
+        let original = data(1_000_000);
+        // A is split in two; B is one file with two damaged ranges, the
+        // second across a read block and the split point
+        fs::write(dir.path().join("a.001"), &original[..600_000]).unwrap();
+        fs::write(dir.path().join("a.002"), &original[600_000..]).unwrap();
+        let mut changed = original.clone();
+        for byte in &mut changed[1_000..1_016] {
+            *byte = !*byte;
+        }
+        for byte in &mut changed[590_000..610_000] {
+            *byte = !*byte;
+        }
+        fs::write(dir.path().join("b.dd"), &changed).unwrap();
+        let (a, b) = (dir.path().join("a.001"), dir.path().join("b.dd"));
+        let (a, b) = (a.to_str().unwrap(), b.to_str().unwrap());
+
+        let mut last = CompareProgress::default();
+        let result = compare_images(a, b, &options(10), &AtomicBool::new(false), |p| last = p).unwrap();
+        assert!(!result.identical);
+        assert_eq!((result.size_a, result.size_b, result.length_difference), (1_000_000, 1_000_000, 0));
+        assert_eq!(result.first_difference, Some(1_000));
+        assert_eq!(result.differing_bytes, 16 + 20_000);
+        assert_eq!(result.ranges, vec![
+            DiffRange { offset: 1_000, length: 16 },
+            DiffRange { offset: 590_000, length: 20_000 },
+        ]);
+        assert!(!result.ranges_truncated);
+        assert_eq!((last.bytes_done, last.bytes_total, last.differing_bytes), (1_000_000, 1_000_000, 20_016));
+
+        let capped = compare_images(a, b, &options(1), &AtomicBool::new(false), |_| {}).unwrap();
+        assert_eq!((capped.ranges.len(), capped.range_count, capped.ranges_truncated), (1, 2, true));
+
+        let cancelled = compare_images(a, b, &options(10), &AtomicBool::new(true), |_| {});
+        assert!(cancelled.unwrap_err().contains("cancelled"));
+    }
+
+    #[test]
+    fn test_unequal_lengths_compare_the_common_prefix() {
+        let dir = tempfile::tempdir().unwrap();
+        let original = data(200_000);
+        fs::write(dir.path().join("short.dd"), &original[..150_000]).unwrap();
+        fs::write(dir.path().join("long.dd"), &original).unwrap();
+        let path = |name: &str| dir.path().join(name).to_string_lossy().to_string();
+
+        let result = compare_images(&path("long.dd"), &path("short.dd"), &CompareOptions::default(), &AtomicBool::new(false), |_| {}).unwrap();
+        assert!(!result.identical);
+        assert_eq!((result.compared_bytes, result.length_difference), (150_000, -50_000));
+        assert_eq!((result.first_difference, result.differing_bytes), (Some(150_000), 0));
+        assert!(result.ranges.is_empty());
+
+        let result = compare_images(&path("long.dd"), &path("long.dd"), &CompareOptions::default(), &AtomicBool::new(false), |_| {}).unwrap();
+        assert!(result.identical);
+        assert_eq!(result.first_difference, None);
+    }
+}
@@ -0,0 +1,162 @@
+//! Per-container access registry
+//!
+//! Every operation opens its own handles (`EwfHandle`, AD1 sessions and raw
+//! handles are `Send` but not shared between threads), so any number of
+//! readers - info, verification, hex reads, copies - can work on the same
+//! container at once. What must not overlap is writing: two extractions
+//! into the same output, or an extraction into a path another operation is
+//! reading. Operations take a guard from [`registry`] for each path they
+//! touch; conflicting requests fail at once with the operation holding the
+//! path, instead of interleaving their writes.
+//!
+//! Paths are compared after canonicalization, so `./a/../image.E01` and the
+//! absolute path lock the same entry. The registry is in-process only.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard, OnceLock};
+
+use tracing::debug;
+
+/// Readers and writer of one path
+#[derive(Default)]
+struct Entry {
+    readers: usize,
+    /// Operation writing the path, if any
+    writer: Option<String>,
+}
+
+/// Shared/exclusive access to container and output paths
+#[derive(Default)]
+pub struct AccessRegistry {
+    entries: Mutex<HashMap<PathBuf, Entry>>,
+}
+
+/// Held access to a path; released on drop
+#[must_use = "access is released as soon as the guard is dropped"]
+pub struct AccessGuard<'a> {
+    registry: &'a AccessRegistry,
+    key: PathBuf,
+    write: bool,
+}
+
+/// The process-wide registry
+pub fn registry() -> &'static AccessRegistry {
+    static REGISTRY: OnceLock<AccessRegistry> = OnceLock::new();
+    REGISTRY.get_or_init(AccessRegistry::default)
+}
+
+impl AccessRegistry {
+    fn lock(&self) -> MutexGuard<'_, HashMap<PathBuf, Entry>> {
+        // Entries are updated in single statements, so a panic elsewhere
+        // can't leave one half-written
+        self.entries.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Shared access for reading `path`; fails while it is being written
+    pub fn read(&self, path: &str) -> Result<AccessGuard<'_>, String> {
+        let key = access_key(path);
+        let mut entries = self.lock();
+        let entry = entries.entry(key.clone()).or_default();
+        if let Some(writer) = &entry.writer {
+            return Err(format!("{} is being written by {}; try again when it finishes", path, writer));
+        }
+        entry.readers += 1;
+        Ok(AccessGuard { registry: self, key, write: false })
+    }
+
+    /// Exclusive access for `operation` to write `path`; fails while any
+    /// other operation reads or writes it
+    pub fn write(&self, path: &str, operation: &str) -> Result<AccessGuard<'_>, String> {
+        let key = access_key(path);
+        let mut entries = self.lock();
+        let entry = entries.entry(key.clone()).or_default();
+        if let Some(writer) = &entry.writer {
+            return Err(format!("{} is already being written by {}", path, writer));
+        }
+        if entry.readers > 0 {
+            return Err(format!("{} is being read by {} other operation(s)", path, entry.readers));
+        }
+        entry.writer = Some(operation.to_string());
+        debug!(path, operation, "Acquired exclusive access");
+        Ok(AccessGuard { registry: self, key, write: true })
+    }
+
+    fn release(&self, key: &Path, write: bool) {
+        let mut entries = self.lock();
+        let Some(entry) = entries.get_mut(key) else { return };
+        if write {
+            entry.writer = None;
+        } else {
+            entry.readers = entry.readers.saturating_sub(1);
+        }
+        if entry.readers == 0 && entry.writer.is_none() {
+            entries.remove(key);
+        }
+    }
+}
+
+impl Drop for AccessGuard<'_> {
+    fn drop(&mut self) {
+        self.registry.release(&self.key, self.write);
+    }
+}
+
+/// Canonical form of `path`; outputs that don't exist yet are resolved
+/// through their closest existing ancestor
+fn access_key(path: &str) -> PathBuf {
+    let path = Path::new(path);
+    if let Ok(canonical) = path.canonicalize() {
+        return canonical;
+    }
+    let absolute = std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+    let mut missing = Vec::new();
+    let mut current = absolute.as_path();
+    while let Some(parent) = current.parent() {
+        missing.push(current.file_name().unwrap_or_default().to_os_string());
+        if let Ok(canonical) = parent.canonicalize() {
+            return missing.iter().rev().fold(canonical, |acc, name| acc.join(name));
+        }
+        current = parent;
+    }
+    absolute
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_readers_share_and_writers_exclude() {
+        let dir = tempfile::tempdir().unwrap();
+        let image = dir.path().join("image.E01");
+        std::fs::write(&image, b"x").unwrap();
+        let image = image.to_str().unwrap();
+        let registry = AccessRegistry::default();
+
+        let first = registry.read(image).unwrap();
+        let second = registry.read(image).unwrap();
+        assert!(registry.write(image, "extract").err().unwrap().contains("being read by 2"));
+        drop((first, second));
+
+        let writer = registry.write(image, "extract").unwrap();
+        assert!(registry.read(image).err().unwrap().contains("being written by extract"));
+        drop(writer);
+        assert!(registry.read(image).is_ok());
+        assert!(registry.lock().is_empty());
+    }
+
+    #[test]
+    fn test_same_output_through_different_spellings_conflicts() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("cases")).unwrap();
+        let output = dir.path().join("cases").join("out");
+        let roundabout = dir.path().join("cases").join("..").join("cases").join("out");
+        let registry = AccessRegistry::default();
+
+        let _extracting = registry.write(output.to_str().unwrap(), "extract").unwrap();
+        let error = registry.write(roundabout.to_str().unwrap(), "extract").err().unwrap();
+        assert!(error.contains("already being written by extract"), "{error}");
+        assert!(registry.write(dir.path().join("cases").join("other").to_str().unwrap(), "extract").is_ok());
+    }
+}
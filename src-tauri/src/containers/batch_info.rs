@@ -0,0 +1,317 @@
+//! Fast container info for many files at once
+//!
+//! After a scan the UI needs the header info of every row. Asking for it
+//! one file at a time leaves a NAS idle between round trips, so
+//! [`info_batch`] reads several files at once - at most
+//! [`DEFAULT_WORKERS_PER_DEVICE`] per storage device, so one slow share is
+//! not flooded while files on other devices wait - and reports each result
+//! as soon as it's ready. Results go through the info cache: running the
+//! same batch again is answered from memory.
+//!
+//! A file that does not answer within the timeout is reported as timed out
+//! and its worker moves on. The hung read itself can't be interrupted; it
+//! finishes (or not) on a thread of its own.
+
+use std::collections::VecDeque;
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use tracing::{debug, info, warn};
+
+use super::cache::info_fast_cached;
+use super::types::{BatchInfoItem, BatchInfoOptions, BatchInfoSummary, ContainerInfo};
+
+/// Seconds one file may take when [`BatchInfoOptions::timeout_secs`] is not set
+pub const DEFAULT_INFO_TIMEOUT_SECS: u64 = 30;
+
+/// Files read at once when neither [`BatchInfoOptions::workers`] nor the
+/// settings set it
+pub const DEFAULT_BATCH_WORKERS: usize = 8;
+
+/// Upper bound on files read at once
+pub const MAX_BATCH_WORKERS: usize = 32;
+
+/// Files read at once from one device when neither
+/// [`BatchInfoOptions::workers_per_device`] nor the settings set it
+pub const DEFAULT_WORKERS_PER_DEVICE: usize = 4;
+
+type Loader = dyn Fn(&str) -> Result<ContainerInfo, String> + Send + Sync;
+
+/// Fast info ([`info_fast_cached`]) of every file in `paths`
+///
+/// `on_item` is called once per file, from the worker that read it, in
+/// completion order. Errors and timeouts are per file - the batch itself
+/// always completes. Once `cancel` is set no further file is started; the
+/// files not reached are counted as `skipped`.
+pub fn info_batch<F>(paths: &[String], options: &BatchInfoOptions, cancel: &AtomicBool, on_item: F) -> BatchInfoSummary
+where
+    F: Fn(BatchInfoItem) + Sync,
+{
+    let refresh = options.refresh;
+    let load: Arc<Loader> = Arc::new(move |path: &str| info_fast_cached(path, refresh));
+    let timeout = Duration::from_secs(options.timeout_secs.unwrap_or(DEFAULT_INFO_TIMEOUT_SECS));
+    let settings = crate::settings::current();
+    let workers = options.workers.unwrap_or(settings.batch_workers).clamp(1, MAX_BATCH_WORKERS);
+    let per_device = options.workers_per_device.unwrap_or(settings.workers_per_device).max(1);
+    run_batch(paths, workers, per_device, timeout, cancel, load, on_item)
+}
+
+/// Pending files per device and how many of each device's are being read
+struct Queues {
+    pending: Vec<VecDeque<usize>>,
+    running: Vec<usize>,
+}
+
+impl Queues {
+    /// Next file from the least busy device that is under its limit
+    fn next(&mut self, per_device: usize) -> Option<(usize, usize)> {
+        let device = (0..self.pending.len())
+            .filter(|&d| !self.pending[d].is_empty() && self.running[d] < per_device)
+            .min_by_key(|&d| self.running[d])?;
+        self.running[device] += 1;
+        Some((device, self.pending[device].pop_front()?))
+    }
+
+    fn is_empty(&self) -> bool {
+        self.pending.iter().all(VecDeque::is_empty)
+    }
+
+    fn remaining(&self) -> usize {
+        self.pending.iter().map(VecDeque::len).sum()
+    }
+}
+
+fn run_batch<F>(
+    paths: &[String],
+    workers: usize,
+    per_device: usize,
+    timeout: Duration,
+    cancel: &AtomicBool,
+    load: Arc<Loader>,
+    on_item: F,
+) -> BatchInfoSummary
+where
+    F: Fn(BatchInfoItem) + Sync,
+{
+    let started = Instant::now();
+    let mut devices: Vec<String> = Vec::new();
+    let mut pending: Vec<VecDeque<usize>> = Vec::new();
+    for (index, path) in paths.iter().enumerate() {
+        let device = device_key(path);
+        let slot = devices.iter().position(|d| *d == device).unwrap_or_else(|| {
+            devices.push(device);
+            pending.push(VecDeque::new());
+            devices.len() - 1
+        });
+        pending[slot].push_back(index);
+    }
+    let workers = workers.min(paths.len());
+    info!(files = paths.len(), devices = devices.len(), workers, "Starting info batch");
+
+    let queues = Mutex::new(Queues { running: vec![0; pending.len()], pending });
+    let ready = Condvar::new();
+    let summary = Mutex::new(BatchInfoSummary { total: paths.len(), ..Default::default() });
+
+    thread::scope(|scope| {
+        for _ in 0..workers {
+            scope.spawn(|| loop {
+                let (device, index) = {
+                    let mut state = queues.lock().unwrap_or_else(|e| e.into_inner());
+                    loop {
+                        if state.is_empty() || cancel.load(Ordering::Relaxed) {
+                            return;
+                        }
+                        if let Some(next) = state.next(per_device) {
+                            break next;
+                        }
+                        state = ready.wait(state).unwrap_or_else(|e| e.into_inner());
+                    }
+                };
+
+                let item = load_item(&paths[index], timeout, &load);
+                {
+                    let mut summary = summary.lock().unwrap_or_else(|e| e.into_inner());
+                    match (&item.error, item.timed_out) {
+                        (None, _) => summary.succeeded += 1,
+                        (Some(_), timed_out) => {
+                            summary.failed += 1;
+                            summary.timed_out += timed_out as usize;
+                        }
+                    }
+                }
+                on_item(item);
+
+                queues.lock().unwrap_or_else(|e| e.into_inner()).running[device] -= 1;
+                ready.notify_all();
+            });
+        }
+    });
+
+    let mut summary = summary.into_inner().unwrap_or_else(|e| e.into_inner());
+    summary.skipped = queues.into_inner().unwrap_or_else(|e| e.into_inner()).remaining();
+    summary.elapsed_ms = started.elapsed().as_millis() as u64;
+    info!(?summary, "Info batch finished");
+    summary
+}
+
+/// Storage device of `path` for scheduling, from the path text alone
+///
+/// Resolving the real mount point stats the path, which blocks on a hung
+/// share before any timeout applies. The drive letter or UNC share, or
+/// elsewhere the first two directories (`/mnt/nas`, `/Volumes/Evidence`),
+/// tell devices apart well enough to spread the reads.
+fn device_key(path: &str) -> String {
+    let Some(parent) = Path::new(path).parent() else {
+        return String::new();
+    };
+    let mut key = PathBuf::new();
+    let mut directories = 0;
+    for component in parent.components() {
+        match component {
+            Component::Prefix(prefix) => return prefix.as_os_str().to_string_lossy().to_uppercase(),
+            Component::Normal(_) if directories == 2 => break,
+            Component::Normal(_) => directories += 1,
+            _ => {}
+        }
+        key.push(component);
+    }
+    key.to_string_lossy().to_string()
+}
+
+/// Info of one file on a thread of its own, abandoned after `timeout`
+fn load_item(path: &str, timeout: Duration, load: &Arc<Loader>) -> BatchInfoItem {
+    let started = Instant::now();
+    let (tx, rx) = mpsc::channel();
+    let worker_load = Arc::clone(load);
+    let worker_path = path.to_string();
+    let spawned = thread::Builder::new()
+        .name("info-batch-item".to_string())
+        .spawn(move || {
+            let _ = tx.send(worker_load(&worker_path));
+        });
+
+    let (result, timed_out) = match spawned {
+        Err(e) => (Err(format!("Failed to start a reader thread: {}", e)), false),
+        Ok(_) => match rx.recv_timeout(timeout) {
+            Ok(result) => (result, false),
+            Err(RecvTimeoutError::Timeout) => {
+                warn!(path, timeout_secs = timeout.as_secs(), "Container info timed out");
+                (Err(format!("No response within {} seconds (unreachable network share?)", timeout.as_secs())), true)
+            }
+            Err(RecvTimeoutError::Disconnected) => (Err("Reading the container info panicked".to_string()), false),
+        },
+    };
+    if let Err(error) = &result {
+        debug!(path, error = %error, "Container info failed");
+    }
+    let (info, error) = match result {
+        Ok(info) => (Some(info), None),
+        Err(error) => (None, Some(error)),
+    };
+    BatchInfoItem {
+        path: path.to_string(),
+        info,
+        error,
+        timed_out,
+        elapsed_ms: started.elapsed().as_millis() as u64,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicUsize;
+
+    fn stub_info(path: &str) -> ContainerInfo {
+        ContainerInfo {
+            container: path.to_string(),
+            ad1: None,
+            e01: None,
+            l01: None,
+            raw: None,
+            archive: None,
+            ufed: None,
+            iso: None,
+            note: None,
+            companion_log: None,
+            segments: None,
+            manifest: None,
+            triage: None,
+            physical_size: None,
+            logical_size: None,
+            compression_ratio: None,
+        }
+    }
+
+    #[test]
+    fn test_errors_and_timeouts_stay_per_file() {
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let (counter, max) = (Arc::clone(&in_flight), Arc::clone(&peak));
+        let load: Arc<Loader> = Arc::new(move |path: &str| {
+            let now = counter.fetch_add(1, Ordering::SeqCst) + 1;
+            max.fetch_max(now, Ordering::SeqCst);
+            let result = match path {
+                "hung.E01" => {
+                    thread::sleep(Duration::from_millis(500));
+                    Ok(stub_info(path))
+                }
+                "bad.E01" => Err("Not an EWF file".to_string()),
+                _ => {
+                    thread::sleep(Duration::from_millis(20));
+                    Ok(stub_info(path))
+                }
+            };
+            counter.fetch_sub(1, Ordering::SeqCst);
+            result
+        });
+
+        let paths: Vec<String> = ["hung.E01", "bad.E01", "a.E01", "b.E01", "c.E01", "d.E01"]
+            .iter().map(|p| p.to_string()).collect();
+        let reported = Mutex::new(Vec::new());
+        let summary = run_batch(&paths, 8, 2, Duration::from_millis(100), &AtomicBool::new(false), load, |item| {
+            reported.lock().unwrap().push((item.path, item.error.is_some(), item.timed_out));
+        });
+
+        assert_eq!((summary.total, summary.succeeded, summary.failed, summary.timed_out, summary.skipped), (6, 4, 2, 1, 0));
+        let mut reported = reported.into_inner().unwrap();
+        reported.sort();
+        assert_eq!(reported.len(), 6);
+        assert!(reported.contains(&("hung.E01".to_string(), true, true)));
+        assert!(reported.contains(&("bad.E01".to_string(), true, false)));
+        // All relative paths share one device, so at most two reads at once
+        // (plus the abandoned one still sleeping)
+        assert!(peak.load(Ordering::SeqCst) <= 3, "peak {}", peak.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_device_key_uses_path_prefix_only() {
+        assert_eq!(device_key("/mnt/nas/case-17/disk.E01"), "/mnt/nas");
+        assert_eq!(device_key("/mnt/nas/other/disk.E01"), device_key("/mnt/nas/case-17/disk.E02"));
+        assert_eq!(device_key("/data/disk.E01"), "/data");
+        assert_eq!(device_key("disk.E01"), "");
+        // Never touches the file system: a path that can't exist still has a key
+        assert_eq!(device_key("/unreachable/share/disk.E01"), "/unreachable/share");
+        #[cfg(windows)]
+        {
+            assert_eq!(device_key(r"e:\case\disk.E01"), "E:");
+            assert_eq!(device_key(r"\\nas\evidence\case\disk.E01"), r"\\NAS\EVIDENCE");
+        }
+    }
+
+    #[test]
+    fn test_cancel_skips_files_not_started() {
+        let cancel = Arc::new(AtomicBool::new(false));
+        let flag = Arc::clone(&cancel);
+        let load: Arc<Loader> = Arc::new(move |path: &str| {
+            flag.store(true, Ordering::SeqCst);
+            Ok(stub_info(path))
+        });
+        let paths: Vec<String> = (0..5).map(|i| format!("{i}.dd")).collect();
+        let summary = run_batch(&paths, 1, 1, Duration::from_secs(5), &cancel, load, |_| {});
+        assert_eq!((summary.succeeded, summary.skipped), (1, 4));
+    }
+}
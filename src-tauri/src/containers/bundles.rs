@@ -0,0 +1,277 @@
+//! Grouping scan results into evidence bundles
+//!
+//! An acquisition folder usually holds more than the image: image.E01 to
+//! image.E12, the imager's image.E01.txt log, an image.csv file listing and
+//! sometimes an image.raw exported from it. [`group_evidence`] folds the scan
+//! results sharing a stem into one bundle per container, with every file
+//! labeled by its role. Logs and manifests are found by the companion lookups
+//! `info` uses, since the scanner itself skips them.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use tracing::debug;
+
+use super::companion::{find_companion_log, find_companion_manifest};
+use super::scanning::detect_container_type_by_extension;
+use super::segments::{get_segment_basename, segment_set_info};
+use super::types::{BundleMember, BundleRole, DiscoveredFile, EvidenceBundle, LogConfidence};
+
+/// Container type of raw images, the format containers get exported to
+const RAW_IMAGE_TYPE: &str = "Raw Image";
+
+/// Group scan results into evidence bundles
+///
+/// Files in the same folder sharing a stem form one bundle. Its primary is
+/// the first of them that is not a raw image; raw images with the stem are
+/// its derived exports. A second non-raw container with the same stem gets a
+/// bundle of its own, as does every raw image of a stem without one.
+/// Bundles come in the order their stems were first seen.
+pub fn group_evidence(files: &[DiscoveredFile]) -> Vec<EvidenceBundle> {
+    let mut grouper = BundleGrouper::default();
+    for file in files {
+        grouper.add(file.clone());
+    }
+    grouper.bundles()
+}
+
+/// Incremental [`group_evidence`] for streaming scans
+#[derive(Default)]
+pub struct BundleGrouper {
+    /// Scan results per folder and lowercase stem, in arrival order
+    groups: Vec<(BundleKey, Vec<DiscoveredFile>)>,
+    positions: HashMap<BundleKey, usize>,
+    /// Segment and companion members per primary path - the companion
+    /// lookups read the folder's logs, so they run once per container
+    primary_members: HashMap<String, Vec<BundleMember>>,
+}
+
+/// Folder and lowercase stem shared by a bundle's files
+type BundleKey = (String, String);
+
+impl BundleGrouper {
+    /// Add a scan result, returning the bundle it belongs to now
+    ///
+    /// A raw image that arrives before its container first comes back as a
+    /// bundle of its own; the container's arrival returns the same bundle
+    /// `id` with the raw image moved to a derived export.
+    pub fn add(&mut self, file: DiscoveredFile) -> Option<EvidenceBundle> {
+        let path = file.path.clone();
+        let key = bundle_key(&file);
+        let groups = &mut self.groups;
+        let position = *self.positions.entry(key.clone()).or_insert_with(|| {
+            groups.push((key, Vec::new()));
+            groups.len() - 1
+        });
+        self.groups[position].1.push(file);
+
+        let (key, files) = &self.groups[position];
+        build_bundles(key, files, &mut self.primary_members)
+            .into_iter()
+            .find(|bundle| bundle.members.iter().any(|member| member.path == path))
+    }
+
+    /// Bundles of everything added so far
+    pub fn bundles(&mut self) -> Vec<EvidenceBundle> {
+        let members = &mut self.primary_members;
+        self.groups.iter()
+            .flat_map(|(key, files)| build_bundles(key, files, members))
+            .collect()
+    }
+}
+
+fn bundle_key(file: &DiscoveredFile) -> BundleKey {
+    let folder = Path::new(&file.path).parent()
+        .map(|parent| parent.to_string_lossy().to_string())
+        .unwrap_or_default();
+    (folder, evidence_stem(&file.filename).to_lowercase())
+}
+
+/// Name of a container without segment numbers and container extensions
+///
+/// "image" for image.E01, image.001, image.7z.001, image.raw and image.dd.gz.
+/// Other dots are kept: case.v2.E01 has the stem "case.v2".
+fn evidence_stem(filename: &str) -> String {
+    let mut stem = get_segment_basename(filename);
+    // Unsegmented names still carry their extension
+    let mut strip = stem == filename;
+    while strip || detect_container_type_by_extension(&stem.to_lowercase()).is_some() {
+        match stem.rsplit_once('.') {
+            Some((rest, _)) if !rest.is_empty() => stem = rest.to_string(),
+            _ => break,
+        }
+        strip = false;
+    }
+    stem
+}
+
+/// Bundles of the files sharing one folder and stem
+fn build_bundles(
+    (folder, stem): &BundleKey,
+    files: &[DiscoveredFile],
+    primary_members: &mut HashMap<String, Vec<BundleMember>>,
+) -> Vec<EvidenceBundle> {
+    let (raw, containers): (Vec<&DiscoveredFile>, Vec<&DiscoveredFile>) = files.iter()
+        .partition(|file| file.container_type == RAW_IMAGE_TYPE);
+    let (primaries, exports) = if containers.is_empty() { (raw, Vec::new()) } else { (containers, raw) };
+
+    primaries.into_iter().enumerate().map(|(index, primary)| {
+        let mut members = primary_members.entry(primary.path.clone())
+            .or_insert_with(|| container_members(primary))
+            .clone();
+        // Exports go with the first container of the stem
+        if index == 0 {
+            members.extend(exports.iter().map(|export| BundleMember {
+                path: export.path.clone(),
+                filename: export.filename.clone(),
+                role: BundleRole::DerivedExport,
+                size: export.total_segment_size.unwrap_or(export.size),
+            }));
+        }
+        let id = if index == 0 {
+            Path::new(folder).join(stem).to_string_lossy().to_string()
+        } else {
+            primary.path.clone()
+        };
+        EvidenceBundle {
+            id,
+            stem: evidence_stem(&primary.filename),
+            primary: primary.clone(),
+            members,
+        }
+    }).collect()
+}
+
+/// The container, its further segments and its companion log and manifest
+fn container_members(file: &DiscoveredFile) -> Vec<BundleMember> {
+    let folder = Path::new(&file.path).parent().unwrap_or(Path::new(""));
+    let member = |filename: &str, role, size| BundleMember {
+        path: folder.join(filename).to_string_lossy().to_string(),
+        filename: filename.to_string(),
+        role,
+        size,
+    };
+    let mut members = vec![BundleMember {
+        path: file.path.clone(),
+        filename: file.filename.clone(),
+        role: BundleRole::Primary,
+        size: file.size,
+    }];
+
+    // UFED extraction entries come with their member list; other sets are
+    // statted, keeping only names of the same set (the listing also matches
+    // image.raw for image.E01)
+    let basename = get_segment_basename(&file.filename).to_lowercase();
+    let segments: Vec<(String, u64)> = match (&file.segment_files, &file.segment_sizes) {
+        (Some(names), Some(sizes)) => names.iter().cloned().zip(sizes.iter().copied()).collect(),
+        _ => segment_set_info(&file.path, None)
+            .map(|set| set.files.into_iter()
+                .filter(|segment| get_segment_basename(&segment.name).to_lowercase() == basename)
+                .map(|segment| (segment.name, segment.size))
+                .collect())
+            .unwrap_or_default(),
+    };
+    for (name, size) in segments {
+        if name == file.filename {
+            members[0].size = size;
+        } else {
+            members.push(member(&name, BundleRole::Segment, size));
+        }
+    }
+
+    // Logs and manifests that could as well belong to another image in the
+    // folder stay out
+    let log = find_companion_log(&file.path)
+        .filter(|log| log.confidence != LogConfidence::Ambiguous)
+        .map(|log| log.log_path);
+    let stem = evidence_stem(&file.filename).to_lowercase();
+    let manifest = find_companion_manifest(&file.path)
+        .map(|manifest| manifest.manifest_path)
+        .filter(|path| {
+            Path::new(path).file_name().is_some_and(|name| name.to_string_lossy().to_lowercase().starts_with(&stem))
+        });
+    for (path, role) in [(log, BundleRole::Log), (manifest, BundleRole::Manifest)] {
+        let Some(path) = path else { continue };
+        let path = Path::new(&path);
+        let filename = path.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+        let size = fs::metadata(path).map(|metadata| metadata.len()).unwrap_or(0);
+        members.push(member(&filename, role, size));
+    }
+    debug!(path = %file.path, members = members.len(), "Bundled container");
+    members
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::containers::scan_directory;
+
+    /// An FTK Imager acquisition folder: a two-segment E01 with its log,
+    /// file listing and a raw export, plus an unrelated image
+    fn acquisition_folder() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["image.E01", "image.E02", "image.raw", "other.dd"] {
+            fs::write(dir.path().join(name), b"EVF").unwrap();
+        }
+        fs::write(
+            dir.path().join("image.E01.txt"),
+            "Created By AccessData FTK Imager 4.7.1.2\n\nCase Information:\nCase Number: 2024-17\n\n\
+             [Computed Hashes]\n MD5 checksum:    11111111111111111111111111111111\n\n\
+             Segment list:\n E:\\Evidence\\image.E01\n E:\\Evidence\\image.E02\n",
+        ).unwrap();
+        fs::write(
+            dir.path().join("image.csv"),
+            "Filename,Full Path,Size (bytes),MD5\na.txt,Users/bob/a.txt,3,d41d8cd98f00b204e9800998ecf8427e\n",
+        ).unwrap();
+        dir
+    }
+
+    fn roles(bundle: &EvidenceBundle) -> Vec<(&str, BundleRole)> {
+        bundle.members.iter().map(|member| (member.filename.as_str(), member.role)).collect()
+    }
+
+    #[test]
+    fn test_acquisition_folder_groups_into_one_bundle() {
+        let dir = acquisition_folder();
+        let files = scan_directory(dir.path().to_str().unwrap()).unwrap();
+        let bundles = group_evidence(&files);
+
+        let image: Vec<&EvidenceBundle> = bundles.iter().filter(|bundle| bundle.stem == "image").collect();
+        assert_eq!(image.len(), 1, "{:?}", bundles.iter().map(|b| &b.id).collect::<Vec<_>>());
+        let image = image[0];
+        assert_eq!(image.primary.filename, "image.E01");
+        assert_eq!(roles(image), vec![
+            ("image.E01", BundleRole::Primary),
+            ("image.E02", BundleRole::Segment),
+            ("image.E01.txt", BundleRole::Log),
+            ("image.csv", BundleRole::Manifest),
+            ("image.raw", BundleRole::DerivedExport),
+        ]);
+        // The unrelated image keeps a row of its own, without the folder's companions
+        let other: Vec<&EvidenceBundle> = bundles.iter().filter(|bundle| bundle.stem == "other").collect();
+        assert_eq!(bundles.len(), 2);
+        assert_eq!(roles(other[0]), vec![("other.dd", BundleRole::Primary)]);
+    }
+
+    #[test]
+    fn test_streamed_export_moves_into_container_bundle() {
+        let dir = acquisition_folder();
+        let mut files = scan_directory(dir.path().to_str().unwrap()).unwrap();
+        files.retain(|file| file.filename.starts_with("image"));
+        // The raw export arrives first
+        files.sort_by_key(|file| file.container_type != RAW_IMAGE_TYPE);
+
+        let mut grouper = BundleGrouper::default();
+        let first = grouper.add(files[0].clone()).unwrap();
+        assert_eq!(roles(&first)[0], ("image.raw", BundleRole::Primary));
+        let second = grouper.add(files[1].clone()).unwrap();
+        assert_eq!(second.id, first.id);
+        assert_eq!(second.primary.filename, "image.E01");
+        assert_eq!(roles(&second).last(), Some(&("image.raw", BundleRole::DerivedExport)));
+        assert_eq!(grouper.bundles().len(), 1);
+
+        assert_eq!(evidence_stem("image.7z.001"), "image");
+        assert_eq!(evidence_stem("image.dd.gz"), "image");
+        assert_eq!(evidence_stem("case.v2.E01"), "case.v2");
+    }
+}
@@ -0,0 +1,450 @@
+//! In-memory cache for container info results
+//!
+//! Parsing a multi-segment E01 on a network share can take several seconds, and
+//! the UI requests the same container repeatedly. Results are cached in a small
+//! LRU keyed by canonical path + size + mtime of the first segment, so any
+//! change to the file on disk invalidates the entry automatically.
+//!
+//! Companion logs are cached alongside, keyed by the size and mtime of every
+//! candidate log next to the image, so adding or editing a `.txt` log does not
+//! force a re-parse of the image itself. Those candidates are listed and logs
+//! parsed outside the cache lock.
+
+use std::collections::{HashMap, VecDeque};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::SystemTime;
+use tracing::{debug, trace};
+
+use crate::ad1;
+use super::companion::{companion_log_candidates, find_companion_log};
+use super::operations;
+use super::types::{CompanionLogInfo, ContainerInfo};
+
+/// Maximum number of cached container info results
+pub const INFO_CACHE_CAPACITY: usize = 64;
+
+/// Maximum number of cached fast (header-only) info results - small enough
+/// to keep a whole scan's worth, so a repeated info batch is answered from
+/// memory
+pub const FAST_INFO_CACHE_CAPACITY: usize = 2048;
+
+/// Which info variant was requested (each is cached separately)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub(crate) enum InfoMode {
+    Fast,
+    Full,
+    /// Full info with up to this many tree entries
+    FullWithTree(usize),
+    /// Best-effort full info, with the tree limit when a tree was requested
+    BestEffort(Option<usize>),
+}
+
+/// Identity of a file on disk at a point in time
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct FileStamp {
+    pub size: u64,
+    pub modified: Option<SystemTime>,
+}
+
+impl FileStamp {
+    pub fn of(path: &Path) -> Option<Self> {
+        let meta = fs::metadata(path).ok()?;
+        Some(Self {
+            size: meta.len(),
+            modified: meta.modified().ok(),
+        })
+    }
+}
+
+struct CachedInfo {
+    stamp: FileStamp,
+    info: ContainerInfo,
+}
+
+/// Stamps of the candidate companion logs next to an image
+type LogStamps = Vec<(PathBuf, Option<FileStamp>)>;
+
+/// Stat every file [`find_companion_log`] would consider for `path`, without
+/// parsing any of them
+fn companion_log_stamps(path: &str) -> LogStamps {
+    companion_log_candidates(path)
+        .into_iter()
+        .map(|log| {
+            let stamp = FileStamp::of(&log);
+            (log, stamp)
+        })
+        .collect()
+}
+
+struct CachedCompanion {
+    stamp: FileStamp,
+    /// Candidate logs at parse time - a log appearing, vanishing or changing
+    /// invalidates the entry
+    logs: LogStamps,
+    log: Option<CompanionLogInfo>,
+}
+
+/// LRU cache of container info and companion log results
+pub(crate) struct InfoCache {
+    infos: HashMap<(PathBuf, InfoMode), CachedInfo>,
+    lru_queue: VecDeque<(PathBuf, InfoMode)>,
+    companions: HashMap<PathBuf, CachedCompanion>,
+    max_entries: usize,
+    /// Fast results are capped separately from (much larger) full ones
+    max_fast_entries: usize,
+}
+
+impl InfoCache {
+    pub fn new(max_entries: usize) -> Self {
+        Self {
+            infos: HashMap::new(),
+            lru_queue: VecDeque::new(),
+            companions: HashMap::new(),
+            max_entries,
+            max_fast_entries: max_entries,
+        }
+    }
+
+    pub fn with_fast_capacity(mut self, max_fast_entries: usize) -> Self {
+        self.max_fast_entries = max_fast_entries;
+        self
+    }
+
+    /// Get a cached info if the file stamp still matches
+    pub fn get_info(&mut self, path: &Path, mode: InfoMode, stamp: &FileStamp) -> Option<ContainerInfo> {
+        let key = (path.to_path_buf(), mode);
+        let hit = match self.infos.get(&key) {
+            Some(entry) if entry.stamp == *stamp => entry.info.clone(),
+            Some(_) => {
+                trace!(?path, "Cached info is stale");
+                self.remove_info(&key);
+                return None;
+            }
+            None => return None,
+        };
+        self.touch(&key);
+        Some(hit)
+    }
+
+    pub fn insert_info(&mut self, path: &Path, mode: InfoMode, stamp: FileStamp, info: ContainerInfo) {
+        let key = (path.to_path_buf(), mode);
+        if self.infos.contains_key(&key) {
+            self.remove_info(&key);
+        }
+        // Remove the oldest entry of the same kind if at capacity
+        let fast = mode == InfoMode::Fast;
+        let capacity = if fast { self.max_fast_entries } else { self.max_entries };
+        while self.infos.keys().filter(|(_, m)| (*m == InfoMode::Fast) == fast).count() >= capacity {
+            let Some(oldest) = self.lru_queue.iter().rposition(|(_, m)| (*m == InfoMode::Fast) == fast) else {
+                break;
+            };
+            let Some(old) = self.lru_queue.remove(oldest) else {
+                break;
+            };
+            self.infos.remove(&old);
+            if !self.infos.keys().any(|(p, _)| *p == old.0) {
+                self.companions.remove(&old.0);
+            }
+        }
+        self.infos.insert(key.clone(), CachedInfo { stamp, info });
+        self.lru_queue.push_front(key);
+    }
+
+    /// Get a cached companion log if neither the image nor its candidate logs changed
+    pub fn get_companion(&self, path: &Path, stamp: &FileStamp, logs: &LogStamps) -> Option<Option<CompanionLogInfo>> {
+        let entry = self.companions.get(path)?;
+        if entry.stamp != *stamp {
+            return None;
+        }
+        if entry.logs != *logs {
+            trace!(?path, "Cached companion log is stale");
+            return None;
+        }
+        Some(entry.log.clone())
+    }
+
+    pub fn insert_companion(&mut self, path: &Path, stamp: FileStamp, logs: LogStamps, log: Option<CompanionLogInfo>) {
+        self.companions.insert(path.to_path_buf(), CachedCompanion { stamp, logs, log });
+    }
+
+    fn touch(&mut self, key: &(PathBuf, InfoMode)) {
+        self.lru_queue.retain(|k| k != key);
+        self.lru_queue.push_front(key.clone());
+    }
+
+    fn remove_info(&mut self, key: &(PathBuf, InfoMode)) {
+        self.infos.remove(key);
+        self.lru_queue.retain(|k| k != key);
+    }
+}
+
+fn global_cache() -> &'static Mutex<InfoCache> {
+    static CACHE: OnceLock<Mutex<InfoCache>> = OnceLock::new();
+    CACHE.get_or_init(|| Mutex::new(InfoCache::new(INFO_CACHE_CAPACITY).with_fast_capacity(FAST_INFO_CACHE_CAPACITY)))
+}
+
+/// Look up `path` in `cache`, falling back to `load` on a miss.
+///
+/// The companion log is resolved through its own cache entry and attached to
+/// the returned info. `load` is only invoked when the image itself changed
+/// (or `refresh` is set).
+pub(crate) fn cached_with<F>(
+    cache: &Mutex<InfoCache>,
+    path: &str,
+    mode: InfoMode,
+    refresh: bool,
+    load: F,
+) -> Result<ContainerInfo, String>
+where
+    F: FnOnce(&str) -> Result<ContainerInfo, String>,
+{
+    let canonical = fs::canonicalize(path).unwrap_or_else(|_| PathBuf::from(path));
+    let stamp = match FileStamp::of(&canonical) {
+        Some(s) => s,
+        // Not a plain file (e.g. UFED folder) or unreadable - don't cache
+        None => return load(path),
+    };
+
+    let lock = || cache.lock().map_err(|e| format!("Info cache poisoned: {e}"));
+
+    if !refresh {
+        let hit = lock()?.get_info(&canonical, mode, &stamp);
+        if let Some(mut info) = hit {
+            // Directory listing and log parsing happen without the lock held
+            let logs = companion_log_stamps(path);
+            let cached = lock()?.get_companion(&canonical, &stamp, &logs);
+            info.companion_log = match cached {
+                Some(log) => log,
+                None => {
+                    let log = find_companion_log(path);
+                    lock()?.insert_companion(&canonical, stamp, logs, log.clone());
+                    log
+                }
+            };
+            debug!(path, ?mode, "Container info cache hit");
+            return Ok(info);
+        }
+    }
+
+    // Stamped before loading so a log written meanwhile is picked up next time
+    let logs = companion_log_stamps(path);
+    let mut info = load(path)?;
+    let log = info.companion_log.take();
+
+    let mut guard = lock()?;
+    guard.insert_info(&canonical, mode, stamp.clone(), info.clone());
+    guard.insert_companion(&canonical, stamp, logs, log.clone());
+    drop(guard);
+
+    info.companion_log = log;
+    Ok(info)
+}
+
+/// Cached variant of [`operations::info_fast`]
+pub fn info_fast_cached(path: &str, refresh: bool) -> Result<ContainerInfo, String> {
+    cached_with(global_cache(), path, InfoMode::Fast, refresh, operations::info_fast)
+}
+
+/// Cached variant of [`operations::info_with_options`]
+///
+/// `tree_limit` defaults to [`ad1::DEFAULT_TREE_LIMIT`].
+pub fn info_cached(path: &str, include_tree: bool, tree_limit: Option<usize>, best_effort: bool, refresh: bool) -> Result<ContainerInfo, String> {
+    let tree_limit = tree_limit.unwrap_or(ad1::DEFAULT_TREE_LIMIT);
+    let mode = match (best_effort, include_tree) {
+        (true, _) => InfoMode::BestEffort(include_tree.then_some(tree_limit)),
+        (false, true) => InfoMode::FullWithTree(tree_limit),
+        (false, false) => InfoMode::Full,
+    };
+    cached_with(global_cache(), path, mode, refresh, |p| operations::info_with_options(p, include_tree, tree_limit, best_effort))
+}
+
+/// Drop all cached container info (e.g. when a project is closed)
+pub fn clear_info_cache() {
+    if let Ok(mut guard) = global_cache().lock() {
+        *guard = InfoCache::new(INFO_CACHE_CAPACITY).with_fast_capacity(FAST_INFO_CACHE_CAPACITY);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use std::time::Duration;
+    use crate::containers::LogConfidence;
+
+    fn stub_info(label: &str) -> ContainerInfo {
+        ContainerInfo {
+            container: label.to_string(),
+            ad1: None,
+            e01: None,
+            l01: None,
+            raw: None,
+            archive: None,
+            ufed: None,
+            iso: None,
+            note: None,
+            companion_log: None,
+            segments: None,
+            manifest: None,
+            triage: None,
+            physical_size: None,
+            logical_size: None,
+            compression_ratio: None,
+        }
+    }
+
+    fn bump_mtime(path: &Path) {
+        let meta = fs::metadata(path).unwrap();
+        let later = meta.modified().unwrap() + Duration::from_secs(60);
+        filetime::set_file_mtime(path, filetime::FileTime::from_system_time(later)).unwrap();
+    }
+
+    #[test]
+    fn test_second_call_hits_cache() {
+        let dir = tempfile::tempdir().unwrap();
+        let image = dir.path().join("disk.dd");
+        fs::write(&image, b"image data").unwrap();
+        let image = image.to_str().unwrap();
+
+        let cache = Mutex::new(InfoCache::new(4));
+        let opens = Cell::new(0);
+        let load = |_: &str| {
+            opens.set(opens.get() + 1);
+            Ok(stub_info("RAW"))
+        };
+
+        cached_with(&cache, image, InfoMode::Fast, false, load).unwrap();
+        cached_with(&cache, image, InfoMode::Fast, false, load).unwrap();
+        assert_eq!(opens.get(), 1);
+
+        // Refresh bypasses the cache
+        cached_with(&cache, image, InfoMode::Fast, true, load).unwrap();
+        assert_eq!(opens.get(), 2);
+
+        // Different mode is a separate entry
+        cached_with(&cache, image, InfoMode::Full, false, load).unwrap();
+        assert_eq!(opens.get(), 3);
+    }
+
+    #[test]
+    fn test_touching_file_invalidates_cache() {
+        let dir = tempfile::tempdir().unwrap();
+        let image_path = dir.path().join("disk.dd");
+        fs::write(&image_path, b"image data").unwrap();
+        let image = image_path.to_str().unwrap();
+
+        let cache = Mutex::new(InfoCache::new(4));
+        let opens = Cell::new(0);
+        let load = |_: &str| {
+            opens.set(opens.get() + 1);
+            Ok(stub_info("RAW"))
+        };
+
+        cached_with(&cache, image, InfoMode::Fast, false, load).unwrap();
+        bump_mtime(&image_path);
+        cached_with(&cache, image, InfoMode::Fast, false, load).unwrap();
+        assert_eq!(opens.get(), 2);
+    }
+
+    #[test]
+    fn test_lru_eviction() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut cache = InfoCache::new(2);
+        for name in ["a.dd", "b.dd", "c.dd"] {
+            let p = dir.path().join(name);
+            fs::write(&p, name).unwrap();
+            let stamp = FileStamp::of(&p).unwrap();
+            cache.insert_info(&p, InfoMode::Fast, stamp, stub_info(name));
+        }
+        assert_eq!(cache.infos.len(), 2);
+        let a = dir.path().join("a.dd");
+        assert!(cache.get_info(&a, InfoMode::Fast, &FileStamp::of(&a).unwrap()).is_none());
+
+        // Fast results have their own cap and don't push out full ones
+        let mut cache = InfoCache::new(1).with_fast_capacity(2);
+        cache.insert_info(&a, InfoMode::Full, FileStamp::of(&a).unwrap(), stub_info("full"));
+        for name in ["a.dd", "b.dd", "c.dd"] {
+            let p = dir.path().join(name);
+            cache.insert_info(&p, InfoMode::Fast, FileStamp::of(&p).unwrap(), stub_info(name));
+        }
+        assert!(cache.get_info(&a, InfoMode::Full, &FileStamp::of(&a).unwrap()).is_some());
+        assert!(cache.get_info(&a, InfoMode::Fast, &FileStamp::of(&a).unwrap()).is_none());
+        assert_eq!(cache.infos.len(), 3);
+    }
+
+    #[test]
+    fn test_companion_log_invalidated_independently() {
+        let dir = tempfile::tempdir().unwrap();
+        let image = dir.path().join("disk.dd");
+        let log_path = dir.path().join("disk.dd.txt");
+        fs::write(&image, b"image data").unwrap();
+        fs::write(&log_path, b"log").unwrap();
+
+        let mut cache = InfoCache::new(4);
+        let stamp = FileStamp::of(&image).unwrap();
+        let image = image.to_str().unwrap();
+        let logs = companion_log_stamps(image);
+        assert_eq!(logs.len(), 1);
+        let mut log = stub_companion();
+        log.log_path = log_path.to_string_lossy().to_string();
+        cache.insert_companion(Path::new(image), stamp.clone(), logs.clone(), Some(log));
+        assert!(cache.get_companion(Path::new(image), &stamp, &logs).is_some());
+
+        bump_mtime(&log_path);
+        assert!(cache.get_companion(Path::new(image), &stamp, &companion_log_stamps(image)).is_none());
+    }
+
+    #[test]
+    fn test_companion_log_appearing_after_first_lookup_is_found() {
+        let dir = tempfile::tempdir().unwrap();
+        let image_path = dir.path().join("disk.dd");
+        fs::write(&image_path, b"image data").unwrap();
+        let image = image_path.to_str().unwrap();
+
+        let cache = Mutex::new(InfoCache::new(4));
+        let opens = Cell::new(0);
+        let load = |_: &str| {
+            opens.set(opens.get() + 1);
+            Ok(stub_info("RAW"))
+        };
+
+        let info = cached_with(&cache, image, InfoMode::Fast, false, load).unwrap();
+        assert!(info.companion_log.is_none());
+
+        fs::write(dir.path().join("disk.md5"), "55555555555555555555555555555555\n").unwrap();
+        let info = cached_with(&cache, image, InfoMode::Fast, false, load).unwrap();
+        let log = info.companion_log.expect("new log should be picked up");
+        assert_eq!(log.stored_hashes[0].hash, "55555555555555555555555555555555");
+        // Only the log was re-read, not the image
+        assert_eq!(opens.get(), 1);
+
+        // Unchanged folder is answered from the cache
+        let info = cached_with(&cache, image, InfoMode::Fast, false, load).unwrap();
+        assert!(info.companion_log.is_some());
+        assert_eq!(opens.get(), 1);
+    }
+
+    fn stub_companion() -> CompanionLogInfo {
+        CompanionLogInfo {
+            log_path: String::new(),
+            created_by: None,
+            case_number: None,
+            evidence_number: None,
+            unique_description: None,
+            examiner: None,
+            notes: None,
+            acquisition_started: None,
+            acquisition_finished: None,
+            verification_started: None,
+            verification_finished: None,
+            stored_hashes: Vec::new(),
+            segment_list: Vec::new(),
+            segment_hashes: Vec::new(),
+            confidence: LogConfidence::Exact,
+            other_candidates: Vec::new(),
+            source_device: None,
+        }
+    }
+}
@@ -0,0 +1,107 @@
+//! Supported formats and what each can do
+//!
+//! The capability records come from the format modules themselves
+//! (`ad1::capabilities()`, `ewf::l01_capabilities()`, ...); this module only
+//! maps every `ContainerKind` to its record, so adding a kind without
+//! declaring its capabilities does not compile.
+
+use serde::Serialize;
+
+use crate::ad1;
+use crate::archive;
+use crate::common::capabilities::FormatCapabilities;
+use crate::ewf;
+use crate::iso;
+use crate::raw;
+use crate::ufed;
+
+use super::types::ContainerKind;
+
+/// One container format the backend can detect, with its capabilities
+#[derive(Debug, Clone, Serialize)]
+pub struct SupportedFormat {
+    /// Stable identifier, e.g. "e01"
+    pub id: &'static str,
+    /// Display name, e.g. "E01 / Ex01 / SMART"
+    pub label: &'static str,
+    pub capabilities: FormatCapabilities,
+}
+
+impl ContainerKind {
+    /// Every kind, in the order `supported_formats` lists them
+    pub(crate) const ALL: [ContainerKind; 8] = [
+        ContainerKind::Ad1,
+        ContainerKind::E01,
+        ContainerKind::L01,
+        ContainerKind::Lx01,
+        ContainerKind::Raw,
+        ContainerKind::Iso,
+        ContainerKind::Archive,
+        ContainerKind::Ufed,
+    ];
+
+    pub(crate) fn capabilities(self) -> FormatCapabilities {
+        match self {
+            ContainerKind::Ad1 => ad1::capabilities(),
+            ContainerKind::E01 => ewf::capabilities(),
+            ContainerKind::L01 => ewf::l01_capabilities(),
+            ContainerKind::Lx01 => ewf::lx01_capabilities(),
+            ContainerKind::Raw => raw::capabilities(),
+            ContainerKind::Iso => iso::capabilities(),
+            ContainerKind::Archive => archive::capabilities(),
+            ContainerKind::Ufed => ufed::capabilities(),
+        }
+    }
+
+    fn id_and_label(self) -> (&'static str, &'static str) {
+        match self {
+            ContainerKind::Ad1 => ("ad1", "AD1"),
+            ContainerKind::E01 => ("e01", "E01 / Ex01 / SMART"),
+            ContainerKind::L01 => ("l01", "L01"),
+            ContainerKind::Lx01 => ("lx01", "Lx01"),
+            ContainerKind::Raw => ("raw", "Raw image"),
+            ContainerKind::Iso => ("iso", "ISO 9660 / UDF"),
+            ContainerKind::Archive => ("archive", "Archive"),
+            ContainerKind::Ufed => ("ufed", "UFED"),
+        }
+    }
+}
+
+/// Capability matrix of every format the backend detects
+pub fn supported_formats() -> Vec<SupportedFormat> {
+    ContainerKind::ALL.iter().map(|&kind| {
+        let (id, label) = kind.id_and_label();
+        SupportedFormat { id, label, capabilities: kind.capabilities() }
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_every_kind_declares_capabilities() {
+        // Exhaustive on purpose: a new variant fails to compile here until
+        // it is given a position in `ContainerKind::ALL`
+        let position = |kind: ContainerKind| match kind {
+            ContainerKind::Ad1 => 0,
+            ContainerKind::E01 => 1,
+            ContainerKind::L01 => 2,
+            ContainerKind::Lx01 => 3,
+            ContainerKind::Raw => 4,
+            ContainerKind::Iso => 5,
+            ContainerKind::Archive => 6,
+            ContainerKind::Ufed => 7,
+        };
+        let positions: Vec<usize> = ContainerKind::ALL.iter().map(|&kind| position(kind)).collect();
+        assert_eq!(positions, (0..ContainerKind::ALL.len()).collect::<Vec<_>>());
+
+        let formats = supported_formats();
+        assert_eq!(formats.len(), ContainerKind::ALL.len());
+        let mut ids: Vec<&str> = formats.iter().map(|f| f.id).collect();
+        ids.sort_unstable();
+        ids.dedup();
+        assert_eq!(ids.len(), formats.len(), "format ids must be unique");
+        assert!(formats.iter().all(|f| f.capabilities.can_info));
+    }
+}
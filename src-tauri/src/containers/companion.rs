@@ -6,14 +6,28 @@
 //! - Guymager
 //! - Forensic MD5
 //! - Various hash files (.md5, .sha1, .sha256)
+//!
+//! It also reads file manifests (FTK Imager CSV/TSV file listings with
+//! per-file MD5/SHA1) exported next to AD1/L01 containers.
 
+use std::collections::HashMap;
 use std::fs;
-use std::path::Path;
+use std::io::Read;
+use std::path::{Path, PathBuf};
 use std::sync::OnceLock;
 use regex::Regex;
 use tracing::debug;
 
-use super::types::{CompanionLogInfo, StoredHash, SegmentHash};
+use super::scanning::detect_container_type_by_extension;
+use super::segments::{get_segment_basename, is_numbered_segment, segment_set_info};
+use super::types::{
+    CompanionLogInfo, DriveGeometry, LogConfidence, SourceDeviceInfo, ManifestInfo, StoredHash, SegmentHash, VerifyEntry, VerifyReason,
+    VerifyStatus,
+};
+use crate::common::device::is_block_device_path;
+use crate::common::hash::{hashes_match, same_algorithm, HashAlgorithm, HashValue};
+use crate::common::segments::SplitName;
+use crate::common::time::Timestamp;
 
 /// Pre-compiled regex for matching hex hash values (32-128 chars)
 /// Compiled once on first use via OnceLock
@@ -24,10 +38,194 @@ fn hash_regex() -> &'static Regex {
     })
 }
 
+/// First hex hash in `text`, in canonical form
+fn find_hash(text: &str) -> Option<String> {
+    let hash: HashValue = hash_regex().find(text)?.as_str().parse().ok()?;
+    Some(hash.into())
+}
+
+/// Extensions of files considered as companion logs
+const LOG_EXTENSIONS: &[&str] = &["txt", "log", "info", "hash", "md5", "sha1", "sha256"];
+
+/// Larger text files are exports or reports, not acquisition logs
+const MAX_LOG_SIZE: u64 = 4 * 1024 * 1024;
+
+fn is_log_name(lower: &str) -> bool {
+    lower.rsplit_once('.').is_some_and(|(_, ext)| LOG_EXTENSIONS.contains(&ext))
+}
+
+fn is_log_file(entry: &fs::DirEntry) -> bool {
+    entry.metadata().is_ok_and(|m| m.is_file() && m.len() <= MAX_LOG_SIZE)
+}
+
+/// Files in the image's folder [`find_companion_log`] would consider, in
+/// path order, found without reading any of them
+pub(crate) fn companion_log_candidates(image_path: &str) -> Vec<PathBuf> {
+    let Some(parent) = Path::new(image_path).parent().filter(|_| !is_block_device_path(image_path)) else {
+        return Vec::new();
+    };
+    let Ok(entries) = fs::read_dir(parent) else {
+        return Vec::new();
+    };
+    let mut logs: Vec<PathBuf> = entries.flatten()
+        .filter(|entry| is_log_name(&entry.file_name().to_string_lossy().to_lowercase()) && is_log_file(entry))
+        .map(|entry| entry.path())
+        .collect();
+    logs.sort();
+    logs
+}
+
 /// Find and parse companion log file (e.g., .txt file created by FTK Imager, dc3dd, etc.)
+///
+/// Evidence folders often hold several images and several logs, so every
+/// log in the image's folder is parsed and scored rather than taking the
+/// first one that parses:
+/// - a segment list or per-segment hashes naming the image's segments
+/// - the image's filename appearing in the log text
+/// - a log filename derived from the image's name (image.E01.txt, image.log, ...)
+///
+/// Logs that only reference other images in the folder are dropped. The
+/// best scoring log is returned with its `confidence` and the paths of the
+/// other viable candidates.
 pub fn find_companion_log(image_path: &str) -> Option<CompanionLogInfo> {
     debug!("Looking for companion log for: {}", image_path);
+    // A drive has no folder of acquisition files around it
+    if is_block_device_path(image_path) {
+        return None;
+    }
     let path = Path::new(image_path);
+    let parent = path.parent()?;
+    let image = ImageNames::new(path)?;
+    let named: Vec<String> = named_candidates(path)?.iter()
+        .filter_map(|p| p.file_name())
+        .map(|n| n.to_string_lossy().to_lowercase())
+        .collect();
+
+    let mut logs = Vec::new();
+    let mut other_images = Vec::new();
+    for entry in fs::read_dir(parent).ok()?.flatten() {
+        let lower = entry.file_name().to_string_lossy().to_lowercase();
+        if is_log_name(&lower) {
+            if is_log_file(&entry) {
+                logs.push((entry.path(), named.contains(&lower)));
+            }
+        } else if (detect_container_type_by_extension(&lower).is_some() || is_numbered_segment(&lower))
+            && get_segment_basename(&lower) != image.key
+            && !image.names.contains(&lower)
+        {
+            other_images.push(lower);
+        }
+    }
+    // Deterministic order for ties
+    logs.sort();
+
+    let mut candidates: Vec<(CompanionLogInfo, u32)> = Vec::new();
+    for (log_path, is_named) in logs {
+        let Ok(content) = fs::read_to_string(&log_path) else { continue };
+        let Ok(info) = parse_companion_text(&content, &log_path) else { continue };
+        if let Some(score) = score_candidate(&info, &content, is_named, &image, &other_images) {
+            debug!(log = ?log_path, score, "Companion log candidate");
+            candidates.push((info, score));
+        }
+    }
+
+    // Stable sort keeps path order among equal scores
+    candidates.sort_by_key(|(_, score)| std::cmp::Reverse(*score));
+    let mut candidates = candidates.into_iter();
+    let Some((mut best, best_score)) = candidates.next() else {
+        debug!("No companion log found for: {}", image_path);
+        return None;
+    };
+    let others: Vec<(CompanionLogInfo, u32)> = candidates.collect();
+    let tied = others.first().is_some_and(|(_, score)| *score == best_score);
+
+    best.confidence = if tied || best_score == 0 {
+        LogConfidence::Ambiguous
+    } else if best_score >= SCORE_MENTIONED {
+        LogConfidence::Exact
+    } else {
+        LogConfidence::Probable
+    };
+    best.other_candidates = others.into_iter().map(|(info, _)| info.log_path).collect();
+    debug!(log = %best.log_path, confidence = ?best.confidence, "Selected companion log");
+    Some(best)
+}
+
+/// Score for a segment list or per-segment hashes naming the image
+const SCORE_SEGMENTS: u32 = 4;
+/// Score for the image's filename appearing in the log text
+const SCORE_MENTIONED: u32 = 2;
+/// Score for a log filename derived from the image's name
+const SCORE_NAMED: u32 = 1;
+
+/// Names that identify an image in log text
+struct ImageNames {
+    /// Lowercased segment basename ("image1" for image1.E01)
+    key: String,
+    /// Lowercased filenames of the image's segments
+    names: Vec<String>,
+}
+
+impl ImageNames {
+    fn new(path: &Path) -> Option<Self> {
+        let filename = path.file_name()?.to_string_lossy().to_lowercase();
+        let mut names: Vec<String> = segment_set_info(&path.to_string_lossy(), None)
+            .map(|set| set.files.into_iter().map(|f| f.name.to_lowercase()).collect())
+            .unwrap_or_default();
+        if !names.contains(&filename) {
+            names.push(filename.clone());
+        }
+        Some(Self { key: get_segment_basename(&filename), names })
+    }
+
+    /// Whether a segment list entry or segment name refers to this image
+    fn is_segment(&self, entry: &str) -> bool {
+        let name = entry.trim().rsplit(['/', '\\']).next().unwrap_or(entry).to_lowercase();
+        self.names.contains(&name) || get_segment_basename(&name) == self.key
+    }
+}
+
+/// Score a parsed log against an image, None if it belongs to another image
+fn score_candidate(info: &CompanionLogInfo, content: &str, named: bool, image: &ImageNames, other_images: &[String]) -> Option<u32> {
+    let mut score = 0;
+
+    let segment_names: Vec<&str> = info.segment_list.iter().map(String::as_str)
+        .chain(info.segment_hashes.iter().map(|sh| sh.segment_name.as_str()))
+        .filter(|name| !name.is_empty())
+        .collect();
+    if !segment_names.is_empty() {
+        if !segment_names.iter().any(|name| image.is_segment(name)) {
+            // The log lists the segments of some other image
+            return None;
+        }
+        score += SCORE_SEGMENTS;
+    }
+
+    let lower = content.to_lowercase();
+    if image.names.iter().any(|name| mentions(&lower, name)) {
+        score += SCORE_MENTIONED;
+    } else if other_images.iter().any(|name| mentions(&lower, name)) {
+        return None;
+    }
+
+    if named {
+        score += SCORE_NAMED;
+    }
+    Some(score)
+}
+
+/// Whether `text` contains `name` as a whole filename (not inside a longer one)
+fn mentions(text: &str, name: &str) -> bool {
+    text.match_indices(name).any(|(pos, _)| {
+        let before = text[..pos].chars().next_back();
+        let after = text[pos + name.len()..].chars().next();
+        !before.is_some_and(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '.'))
+            && !after.is_some_and(char::is_alphanumeric)
+    })
+}
+
+/// Log paths derived from the image's filename by the tools we know about
+fn named_candidates(path: &Path) -> Option<Vec<PathBuf>> {
     let parent = path.parent()?;
     let stem = path.file_stem()?.to_str()?;
     let filename = path.file_name()?.to_str()?;
@@ -108,25 +306,11 @@ pub fn find_companion_log(image_path: &str) -> Option<CompanionLogInfo> {
         candidate_paths.push(parent.join(format!("{}_info.txt", base_stem)));
     }
     
-    for log_path in candidate_paths {
-        if log_path.exists() {
-            debug!("Found companion log candidate: {:?}", log_path);
-            if let Ok(info) = parse_companion_log(&log_path) {
-                debug!("Successfully parsed companion log: {:?}", log_path);
-                return Some(info);
-            }
-        }
-    }
-    
-    debug!("No companion log found for: {}", image_path);
-    None
+    Some(candidate_paths)
 }
 
-/// Parse companion log file from various forensic tools (FTK Imager, dc3dd, dcfldd, Guymager, etc.)
-fn parse_companion_log(log_path: &Path) -> Result<CompanionLogInfo, String> {
-    let content = fs::read_to_string(log_path)
-        .map_err(|e| format!("Failed to read log file: {}", e))?;
-    
+/// Parse companion log text from various forensic tools (FTK Imager, dc3dd, dcfldd, Guymager, etc.)
+fn parse_companion_text(content: &str, log_path: &Path) -> Result<CompanionLogInfo, String> {
     let mut info = CompanionLogInfo {
         log_path: log_path.to_string_lossy().to_string(),
         created_by: None,
@@ -142,6 +326,9 @@ fn parse_companion_log(log_path: &Path) -> Result<CompanionLogInfo, String> {
         stored_hashes: Vec::new(),
         segment_list: Vec::new(),
         segment_hashes: Vec::new(),
+        confidence: LogConfidence::default(),
+        other_candidates: Vec::new(),
+        source_device: parse_source_device(content),
     };
     
     // Detect file format based on content
@@ -157,7 +344,7 @@ fn parse_companion_log(log_path: &Path) -> Result<CompanionLogInfo, String> {
     
     // Handle hash-only files (just hash value, maybe with filename)
     if is_hash_only {
-        if let Some(hash_info) = parse_simple_hash_file(&content, log_path) {
+        if let Some(hash_info) = parse_simple_hash_file(content, log_path) {
             info.stored_hashes.push(hash_info);
             return Ok(info);
         }
@@ -165,12 +352,19 @@ fn parse_companion_log(log_path: &Path) -> Result<CompanionLogInfo, String> {
     
     // Handle Forensic MD5 per-segment hash format
     if is_forensic_md5 {
-        if let Some(segment_hashes) = parse_forensic_md5_segments(&content) {
+        if let Some(segment_hashes) = parse_forensic_md5_segments(content) {
             info.segment_hashes = segment_hashes;
             info.created_by = Some("Forensic MD5".to_string());
         }
     }
     
+    // dc3dd piecewise hashes, one per output split (hash= with ofsz=)
+    if is_dc3dd {
+        if let Some(segment_hashes) = parse_dc3dd_segments(content) {
+            info.segment_hashes = segment_hashes;
+        }
+    }
+    
     // Parse line by line
     let mut in_segment_list = false;
     let mut in_computed_hashes = false;
@@ -253,7 +447,7 @@ fn parse_companion_log(log_path: &Path) -> Result<CompanionLogInfo, String> {
             if let Some(hash_info) = parse_hash_line(line, in_verification_results) {
                 // Check if we already have this algorithm - update with verification status
                 if let Some(existing) = info.stored_hashes.iter_mut()
-                    .find(|h| h.algorithm.to_lowercase() == hash_info.algorithm.to_lowercase()) 
+                    .find(|h| same_algorithm(&h.algorithm, &hash_info.algorithm))
                 {
                     if hash_info.verified.is_some() {
                         existing.verified = hash_info.verified;
@@ -273,10 +467,10 @@ fn parse_companion_log(log_path: &Path) -> Result<CompanionLogInfo, String> {
                 "unique description" | "description" => info.unique_description = Some(value),
                 "examiner" | "examiner name" => info.examiner = Some(value),
                 "notes" | "note" | "comments" => info.notes = Some(value),
-                "acquisition started" | "start time" | "started" => info.acquisition_started = Some(value),
-                "acquisition finished" | "end time" | "finished" | "completed" => info.acquisition_finished = Some(value),
-                "verification started" => info.verification_started = Some(value),
-                "verification finished" => info.verification_finished = Some(value),
+                "acquisition started" | "start time" | "started" => info.acquisition_started = Some(Timestamp::parse(&value)),
+                "acquisition finished" | "end time" | "finished" | "completed" => info.acquisition_finished = Some(Timestamp::parse(&value)),
+                "verification started" => info.verification_started = Some(Timestamp::parse(&value)),
+                "verification finished" => info.verification_finished = Some(Timestamp::parse(&value)),
                 "source" | "source device" | "input" => {
                     if info.unique_description.is_none() {
                         info.unique_description = Some(value);
@@ -306,6 +500,7 @@ fn parse_companion_log(log_path: &Path) -> Result<CompanionLogInfo, String> {
         && info.unique_description.is_none()
         && info.segment_list.is_empty()
         && info.segment_hashes.is_empty()
+        && info.source_device.is_none()
     {
         return Err("No useful information found in log file".to_string());
     }
@@ -326,7 +521,7 @@ fn parse_simple_hash_file(content: &str, log_path: &Path) -> Option<StoredHash>
     };
     
     // Extract hash from content (might be "hash  filename" or just "hash")
-    let hash = hash_regex().find(content.trim())?.as_str().to_lowercase();
+    let hash = find_hash(content.trim())?;
     
     // Get file modification time as timestamp
     let timestamp = log_path.metadata().ok()
@@ -349,7 +544,7 @@ fn parse_simple_hash_file(content: &str, log_path: &Path) -> Option<StoredHash>
 
 /// Parse hash from generic hash file content
 fn parse_hash_from_content(content: &str, log_path: &Path) -> Option<StoredHash> {
-    let hash = hash_regex().find(content.trim())?.as_str().to_lowercase();
+    let hash = find_hash(content.trim())?;
     
     // Guess algorithm from hash length
     let algorithm = match hash.len() {
@@ -400,10 +595,10 @@ fn parse_dc3dd_hash_line(line: &str) -> Option<StoredHash> {
     
     for (pattern, algo_name) in &algorithms {
         if line_lower.contains(pattern) {
-            if let Some(m) = hash_regex().find(line) {
+            if let Some(hash) = find_hash(line) {
                 return Some(StoredHash {
                     algorithm: algo_name.to_string(),
-                    hash: m.as_str().to_lowercase(),
+                    hash,
                     verified: None,
                     timestamp: None,  // Will be set by caller from log file context
                     source: Some("companion".to_string()),
@@ -440,8 +635,7 @@ fn parse_hash_line(line: &str, check_verified: bool) -> Option<StoredHash> {
     for alg in &algorithms {
         if line_lower.contains(alg) {
             // Try to extract the hash value using pre-compiled regex
-            if let Some(m) = hash_regex().find(line) {
-                let hash = m.as_str().to_lowercase();
+            if let Some(hash) = find_hash(line) {
                 
                 // Check for verification status
                 let verified = if check_verified {
@@ -493,6 +687,97 @@ fn parse_key_value(line: &str) -> Option<(String, String)> {
     None
 }
 
+/// Source drive details from the drive information of a log
+///
+/// Reads FTK Imager's "[Drive Geometry]" and "[Physical Drive Information]"
+/// blocks and the device details Guymager records (its "Linux device" /
+/// "Device size" lines and the hdparm/smartctl output it captures). The
+/// capacity comes from an exact byte count if the log has one, otherwise
+/// from sector count × sector size.
+fn parse_source_device(content: &str) -> Option<SourceDeviceInfo> {
+    let mut device = SourceDeviceInfo::default();
+    let (mut cylinders, mut heads, mut sectors_per_track) = (None, None, None);
+    let mut sector_count = None;
+
+    for line in content.lines() {
+        let Some((key, value)) = parse_key_value(line.trim()) else {
+            continue;
+        };
+        let key = key.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase();
+        let text = || Some(value.clone());
+        match key.as_str() {
+            "drive model" | "model" | "model number" | "device model" => device.model = device.model.take().or_else(text),
+            "drive serial number" | "serial number" | "serial nr." | "serial nr" | "serial no" => {
+                device.serial = device.serial.take().or_else(text)
+            }
+            "drive interface type" | "interface" | "transport" | "sata version is" | "transport protocol" => {
+                device.interface = device.interface.take().or_else(text)
+            }
+            "device size" | "user capacity" | "drive size" => {
+                device.capacity_bytes = device.capacity_bytes.or_else(|| parse_byte_count(&value))
+            }
+            "bytes per sector" | "sector size" | "logical sector size" | "sector sizes" => {
+                device.sector_size = device.sector_size.or_else(|| parse_byte_count(&value)?.try_into().ok())
+            }
+            "sector count" | "lba48 user addressable sectors" => sector_count = sector_count.or_else(|| parse_byte_count(&value)),
+            "cylinders" => cylinders = cylinders.or_else(|| parse_byte_count(&value)),
+            "tracks per cylinder" | "heads" => heads = heads.or_else(|| parse_byte_count(&value)?.try_into().ok()),
+            "sectors per track" => sectors_per_track = sectors_per_track.or_else(|| parse_byte_count(&value)?.try_into().ok()),
+            _ => {}
+        }
+    }
+
+    if let (Some(cylinders), Some(heads), Some(sectors_per_track)) = (cylinders, heads, sectors_per_track) {
+        device.geometry = Some(DriveGeometry { cylinders, heads, sectors_per_track });
+    }
+    if device.capacity_bytes.is_none() {
+        device.capacity_bytes = sector_count.zip(device.sector_size).map(|(count, size)| count * size as u64);
+    }
+    (device != SourceDeviceInfo::default()).then_some(device)
+}
+
+/// Leading number of a size value, thousands separators allowed and scaled
+/// by a following binary unit: "500,107,862,016 bytes" → 500107862016,
+/// "476940 MB" → 476940 × 2^20
+fn parse_byte_count(value: &str) -> Option<u64> {
+    let value = value.trim();
+    let end = value.find(|c: char| !c.is_ascii_digit() && c != ',').unwrap_or(value.len());
+    let number: u64 = value[..end].replace(',', "").parse().ok()?;
+    let unit = value[end..].split_whitespace().next().unwrap_or("").to_lowercase();
+    let shift = match unit.trim_end_matches(|c: char| !c.is_ascii_alphabetic()) {
+        "kb" | "kib" => 10,
+        "mb" | "mib" | "mbytes" => 20,
+        "gb" | "gib" => 30,
+        "tb" | "tib" => 40,
+        _ => 0,
+    };
+    number.checked_mul(1u64 << shift)
+}
+
+/// Model and serial differences between a log's source device and the
+/// ones recorded in an image's header
+///
+/// Tools decorate models differently (FTK appends " ATA Device", " USB
+/// Device"), so models match when one contains the other once reduced to
+/// letters and digits. Serials have to match exactly in that form.
+pub fn source_device_discrepancies(device: &SourceDeviceInfo, model: Option<&str>, serial: Option<&str>) -> Vec<String> {
+    let reduce = |text: &str| text.chars().filter(char::is_ascii_alphanumeric).collect::<String>().to_uppercase();
+    let mut issues = Vec::new();
+    if let (Some(logged), Some(header)) = (device.model.as_deref(), model) {
+        let (a, b) = (reduce(logged), reduce(header));
+        if !a.is_empty() && !b.is_empty() && !a.contains(&b) && !b.contains(&a) {
+            issues.push(format!("Drive model differs: log \"{}\", image header \"{}\"", logged, header));
+        }
+    }
+    if let (Some(logged), Some(header)) = (device.serial.as_deref(), serial) {
+        let (a, b) = (reduce(logged), reduce(header));
+        if !a.is_empty() && !b.is_empty() && a != b {
+            issues.push(format!("Drive serial number differs: log \"{}\", image header \"{}\"", logged, header));
+        }
+    }
+    issues
+}
+
 /// Parse "Forensic MD5" style per-segment hash log files
 /// Format:
 /// * SCHARDT.001: From: 0, To: 1389747, Size: 1301248, MD5 Value:
@@ -550,18 +835,11 @@ fn parse_forensic_md5_segments(content: &str) -> Option<Vec<SegmentHash>> {
         if line.starts_with("* ...") && current_segment.is_some() {
             // Extract hex hash (may be space-separated)
             let hash_part = &line[5..]; // Skip "* ..."
-            let hash_part = hash_part.trim_end_matches("...");
-            
-            // Remove spaces and convert to lowercase
-            let hash: String = hash_part
-                .chars()
-                .filter(|c| c.is_ascii_hexdigit())
-                .collect::<String>()
-                .to_lowercase();
+            let hash_part = hash_part.trim_end().trim_end_matches("...");
             
-            if hash.len() >= 32 {
-                if let Some(seg) = current_segment.as_mut() {
-                    seg.hash = hash;
+            if let Some(seg) = current_segment.as_mut() {
+                if let Ok(hash) = HashValue::parse_named(hash_part, &seg.algorithm) {
+                    seg.hash = hash.into();
                 }
             }
         }
@@ -581,8 +859,103 @@ fn parse_forensic_md5_segments(content: &str) -> Option<Vec<SegmentHash>> {
     }
 }
 
-/// Extract segment number from segment name (e.g., "SCHARDT.001" -> 1)
-fn extract_segment_number(name: &str) -> Option<u32> {
+/// dc3dd total hash line: "a1b2c3... (md5)"
+fn dc3dd_total_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    REGEX.get_or_init(|| {
+        Regex::new(r"(?i)^(?:\[\w+\]\s+)?[a-f0-9]{32,128}\s+\((md5|sha-?1|sha-?256|sha-?512)\)").expect("Invalid dc3dd regex")
+    })
+}
+
+/// dc3dd piecewise hash line: "[ok] a1b2c3..., sectors 0 - 2097151, `image.000'"
+fn dc3dd_piece_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    REGEX.get_or_init(|| {
+        Regex::new(r"(?i)^(?:\[(\w+)\]\s+)?([a-f0-9]{32,128}),\s*(sectors|bytes)\s+(\d+)\s*-\s*(\d+)(?:,\s*`([^']+)')?")
+            .expect("Invalid dc3dd regex")
+    })
+}
+
+/// Parse dc3dd piecewise hashes, listed under the total hash of each
+/// algorithm ("a1b2c3... (md5)")
+///
+/// Ranges are inclusive and in sectors of the logged sector size (512 when
+/// the log has none) or in bytes; both come back as byte offsets, so a
+/// piece without a file name (left empty) can be matched to its split by
+/// byte range. A named piece (output results) replaces the unnamed one of the same range
+/// (input results).
+fn parse_dc3dd_segments(content: &str) -> Option<Vec<SegmentHash>> {
+    let sector_size = content.lines()
+        .find_map(|line| {
+            let lower = line.trim().to_lowercase();
+            let value = lower.strip_prefix("sector size:")?.split_whitespace().next()?.replace(',', "");
+            value.parse::<u64>().ok()
+        })
+        .filter(|size| *size > 0)
+        .unwrap_or(512);
+
+    let mut segments: Vec<SegmentHash> = Vec::new();
+    let mut algorithm: Option<&'static str> = None;
+    for line in content.lines() {
+        let line = line.trim();
+        if let Some(caps) = dc3dd_total_regex().captures(line) {
+            algorithm = HashAlgorithm::from_str(&caps[1]).ok().map(|algo| algo.name());
+            continue;
+        }
+        let (Some(caps), Some(algorithm)) = (dc3dd_piece_regex().captures(line), algorithm) else {
+            continue;
+        };
+        let (Ok(from), Ok(to)) = (caps[4].parse::<u64>(), caps[5].parse::<u64>()) else {
+            continue;
+        };
+        let unit = if caps[3].eq_ignore_ascii_case("sectors") { sector_size } else { 1 };
+        let Some(end) = to.checked_add(1).and_then(|end| end.checked_mul(unit)) else {
+            continue;
+        };
+        let Ok(hash) = HashValue::parse_named(&caps[2], algorithm) else {
+            continue;
+        };
+        let name = caps.get(6).map(|name| name.as_str().to_string());
+        let verified = caps.get(1).map(|status| status.as_str().eq_ignore_ascii_case("ok"));
+        let offset_from = from * unit;
+
+        let same_range = segments.iter().position(|seg| {
+            seg.algorithm == algorithm && seg.offset_from == Some(offset_from) && seg.offset_to == Some(end - 1)
+        });
+        if let Some(index) = same_range {
+            if name.is_none() {
+                continue;
+            }
+            segments.remove(index);
+        }
+        let piece = segments.iter().filter(|seg| seg.algorithm == algorithm).count() as u32 + 1;
+        segments.push(SegmentHash {
+            segment_number: name.as_deref().and_then(extract_segment_number).unwrap_or(piece),
+            segment_name: name.unwrap_or_default(),
+            algorithm: algorithm.to_string(),
+            hash: hash.into(),
+            offset_from: Some(offset_from),
+            offset_to: Some(end - 1),
+            size: Some(end - offset_from),
+            verified,
+        });
+    }
+
+    if segments.is_empty() {
+        None
+    } else {
+        segments.sort_by_key(|seg| seg.offset_from);
+        Some(segments)
+    }
+}
+
+/// Extract segment number from segment name (e.g., "SCHARDT.001" -> 1,
+/// "image.0100" -> 100, "image.dd.ab" -> 1)
+pub(super) fn extract_segment_number(name: &str) -> Option<u32> {
+    let file_name = name.rsplit(['/', '\\']).next().unwrap_or(name);
+    if let Some(split) = SplitName::parse(file_name) {
+        return Some(split.index);
+    }
     // Try to find numeric extension
     if let Some(dot_pos) = name.rfind('.') {
         let ext = &name[dot_pos + 1..];
@@ -609,3 +982,430 @@ fn extract_numeric_value(line: &str, key: &str) -> Option<u64> {
     }
     None
 }
+
+// =============================================================================
+// File Manifests (FTK Imager CSV file listings)
+// =============================================================================
+
+/// Extensions of files checked for a manifest header
+const MANIFEST_EXTENSIONS: &[&str] = &["csv", "tsv", "txt"];
+
+/// One file listed in a manifest
+#[derive(Debug, Clone)]
+pub struct ManifestEntry {
+    /// Path as written in the manifest
+    pub path: String,
+    pub size: Option<u64>,
+    pub md5: Option<String>,
+    pub sha1: Option<String>,
+}
+
+/// Column layout read from a manifest header row
+struct ManifestColumns {
+    delimiter: char,
+    path: usize,
+    size: Option<usize>,
+    md5: Option<usize>,
+    sha1: Option<usize>,
+}
+
+impl ManifestColumns {
+    /// Parse a header row, None unless it has a file name and an MD5 or SHA1 column
+    fn from_header(line: &str) -> Option<Self> {
+        let delimiter = if line.contains('\t') { '\t' } else { ',' };
+        let names: Vec<String> = split_delimited(line, delimiter).iter()
+            .map(|name| name.trim().to_lowercase())
+            .collect();
+        // First listed name wins, so "Full Path" is preferred over "Filename"
+        let find = |candidates: &[&str]| candidates.iter().find_map(|c| names.iter().position(|name| name == c));
+
+        let md5 = find(&["md5", "md5 hash"]);
+        let sha1 = find(&["sha1", "sha-1", "sha1 hash"]);
+        if md5.is_none() && sha1.is_none() {
+            return None;
+        }
+        Some(Self {
+            delimiter,
+            path: find(&["full path", "path", "filenames", "filename", "file name", "name"])?,
+            size: find(&["size", "size (bytes)", "file size", "logical size"]),
+            md5,
+            sha1,
+        })
+    }
+
+    fn entry(&self, line: &str) -> Option<ManifestEntry> {
+        let fields = split_delimited(line, self.delimiter);
+        let path = fields.get(self.path)?.trim();
+        if path.is_empty() {
+            return None;
+        }
+        let hash = |column: Option<usize>, algorithm: HashAlgorithm| {
+            HashValue::parse(fields.get(column?)?, algorithm).ok().map(String::from)
+        };
+        Some(ManifestEntry {
+            path: path.to_string(),
+            size: self.size
+                .and_then(|column| fields.get(column))
+                .and_then(|size| size.trim().replace(',', "").parse().ok()),
+            md5: hash(self.md5, HashAlgorithm::Md5),
+            sha1: hash(self.sha1, HashAlgorithm::Sha1),
+        })
+    }
+}
+
+/// Split one delimited line, honouring double-quoted fields ("" escapes a quote)
+pub(super) fn split_delimited(line: &str, delimiter: char) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            c if c == delimiter && !quoted => fields.push(std::mem::take(&mut field)),
+            c => field.push(c),
+        }
+    }
+    fields.push(field);
+    fields
+}
+
+/// Decode manifest bytes - FTK writes UTF-8 (with or without BOM) or UTF-16 LE
+pub(super) fn decode_text(bytes: &[u8]) -> String {
+    if let Some(utf16) = bytes.strip_prefix(&[0xFF, 0xFE]) {
+        let units: Vec<u16> = utf16.chunks_exact(2).map(|pair| u16::from_le_bytes([pair[0], pair[1]])).collect();
+        return String::from_utf16_lossy(&units);
+    }
+    let bytes = bytes.strip_prefix(&[0xEF, 0xBB, 0xBF]).unwrap_or(bytes);
+    String::from_utf8_lossy(bytes).into_owned()
+}
+
+/// Read only the header row of a possible manifest
+fn read_manifest_header(path: &Path) -> Option<ManifestColumns> {
+    let mut head = Vec::new();
+    fs::File::open(path).ok()?.take(8192).read_to_end(&mut head).ok()?;
+    let text = decode_text(&head);
+    ManifestColumns::from_header(text.lines().find(|line| !line.trim().is_empty())?)
+}
+
+/// Parse a file manifest (CSV or tab-delimited, header row first)
+pub fn parse_manifest(path: &Path) -> Result<Vec<ManifestEntry>, String> {
+    let bytes = fs::read(path).map_err(|e| format!("Failed to read manifest: {}", e))?;
+    let text = decode_text(&bytes);
+    let mut lines = text.lines().filter(|line| !line.trim().is_empty());
+    let columns = lines.next()
+        .and_then(ManifestColumns::from_header)
+        .ok_or_else(|| format!("{} is not a file manifest (no Filename and MD5/SHA1 header)", path.display()))?;
+    Ok(lines.filter_map(|line| columns.entry(line)).collect())
+}
+
+/// Find a file manifest exported next to a container
+///
+/// Manifests named after the container (image.csv, image_files.csv ...)
+/// are preferred; a manifest with another name is only used when it is the
+/// only one in the folder.
+pub fn find_companion_manifest(image_path: &str) -> Option<ManifestInfo> {
+    let path = Path::new(image_path);
+    let image = ImageNames::new(path)?;
+    let mut manifests: Vec<(PathBuf, ManifestColumns, bool)> = fs::read_dir(path.parent()?).ok()?
+        .flatten()
+        .filter_map(|entry| {
+            let lower = entry.file_name().to_string_lossy().to_lowercase();
+            let (_, ext) = lower.rsplit_once('.')?;
+            if !MANIFEST_EXTENSIONS.contains(&ext) || !entry.metadata().is_ok_and(|m| m.is_file()) {
+                return None;
+            }
+            let columns = read_manifest_header(&entry.path())?;
+            Some((entry.path(), columns, lower.starts_with(&image.key)))
+        })
+        .collect();
+    manifests.sort_by(|a, b| b.2.cmp(&a.2).then_with(|| a.0.cmp(&b.0)));
+
+    let (manifest_path, columns, named) = manifests.first()?;
+    if !named && manifests.len() > 1 {
+        debug!(image_path, count = manifests.len(), "Several unnamed manifests, none associated");
+        return None;
+    }
+    Some(ManifestInfo {
+        manifest_path: manifest_path.to_string_lossy().to_string(),
+        has_md5: columns.md5.is_some(),
+        has_sha1: columns.sha1.is_some(),
+    })
+}
+
+/// Normalize a manifest or container path for matching
+///
+/// Backslashes become '/', a leading drive letter ("C:") and FTK's "[root]"
+/// marker are dropped and case is folded, since FTK lists Windows sources.
+fn normalize_manifest_path(path: &str) -> String {
+    path.replace('\\', "/")
+        .split('/')
+        .enumerate()
+        .filter(|(idx, part)| {
+            let drive = *idx == 0 && part.len() == 2 && part.ends_with(':') && part.starts_with(|c: char| c.is_ascii_alphabetic());
+            !part.is_empty() && *part != "." && !drive && !part.eq_ignore_ascii_case("[root]")
+        })
+        .map(|(_, part)| part.to_lowercase())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Compare manifest hashes against hashes computed from the container
+///
+/// `computed` yields (container path, hash) pairs for `algorithm`. Manifest
+/// paths that don't match a container path exactly are retried without
+/// their leading folders, since FTK may prefix the evidence name or the
+/// source's parent folders.
+pub(crate) fn compare_manifest<I>(manifest: &[ManifestEntry], algorithm: &str, computed: I) -> Vec<VerifyEntry>
+where
+    I: IntoIterator<Item = (String, String)>,
+{
+    let mut container: HashMap<String, (String, String, bool)> = computed.into_iter()
+        .map(|(path, hash)| (normalize_manifest_path(&path), (path, hash, false)))
+        .collect();
+    let use_md5 = algorithm.eq_ignore_ascii_case("md5");
+    let label = algorithm.to_uppercase();
+
+    let mut results = Vec::with_capacity(manifest.len());
+    for entry in manifest {
+        let normalized = normalize_manifest_path(&entry.path);
+        let key = std::iter::successors(Some(normalized.as_str()), |p| p.split_once('/').map(|(_, rest)| rest))
+            .find(|candidate| container.contains_key(*candidate))
+            .map(str::to_string);
+        let Some((path, hash, seen)) = key.and_then(|key| container.get_mut(&key)) else {
+            results.push(
+                VerifyEntry::path(entry.path.clone(), VerifyStatus::Error)
+                    .reason(VerifyReason::MissingFromContainer)
+                    .message("Listed in manifest but not found in container"),
+            );
+            continue;
+        };
+        *seen = true;
+
+        let expected = if use_md5 { entry.md5.as_ref() } else { entry.sha1.as_ref() };
+        let result = match expected {
+            Some(expected) if hashes_match(expected, hash) => {
+                VerifyEntry::path(path.clone(), VerifyStatus::Ok).message(format!("{}: {}", label, hash))
+            }
+            Some(expected) => VerifyEntry::path(path.clone(), VerifyStatus::Mismatch)
+                .message(format!("{} mismatch: manifest {}, computed {}", label, expected, hash)),
+            None => VerifyEntry::path(path.clone(), VerifyStatus::Skipped)
+                .reason(VerifyReason::NoExpectedHash)
+                .message(format!("{}: {} (no {} in manifest)", label, hash, label)),
+        };
+        results.push(result.hashes(&label, expected.map(String::as_str), Some(hash)));
+    }
+
+    let mut unlisted: Vec<&String> = container.values()
+        .filter(|(_, _, seen)| !seen)
+        .map(|(path, _, _)| path)
+        .collect();
+    unlisted.sort();
+    results.extend(unlisted.into_iter().map(|path| {
+        VerifyEntry::path(path.clone(), VerifyStatus::Skipped)
+            .reason(VerifyReason::MissingFromManifest)
+            .message("Found in container but not listed in manifest")
+    }));
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ftk_log(md5: &str, segments: &[&str]) -> String {
+        let mut log = format!(
+            "Created By AccessData FTK Imager 4.7.1.2\n\nCase Information:\nCase Number: 2024-17\nExaminer: J. Doe\n\n\
+             [Computed Hashes]\n MD5 checksum:    {md5}\n\nSegment list:\n"
+        );
+        for segment in segments {
+            log.push_str(&format!(" E:\\Evidence\\{segment}\n"));
+        }
+        log.push_str("\nImage Verification Results:\n MD5 checksum:    ");
+        log.push_str(md5);
+        log.push_str(" : verified\n");
+        log
+    }
+
+    #[test]
+    fn test_pairs_logs_with_images_in_shared_folder() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["image1.E01", "image1.E02", "image2.E01"] {
+            fs::write(dir.path().join(name), b"EVF").unwrap();
+        }
+        let md5_1 = "11111111111111111111111111111111";
+        let md5_2 = "22222222222222222222222222222222";
+        // Sorted first, so a first-that-parses lookup would pick it for both images
+        fs::write(dir.path().join("acquisition1.txt"), ftk_log(md5_2, &["image2.E01"])).unwrap();
+        fs::write(dir.path().join("acquisition2.txt"), ftk_log(md5_1, &["image1.E01", "image1.E02"])).unwrap();
+
+        for (image, log, md5) in [("image1.E01", "acquisition2.txt", md5_1), ("image2.E01", "acquisition1.txt", md5_2)] {
+            let info = find_companion_log(dir.path().join(image).to_str().unwrap()).unwrap();
+            assert!(info.log_path.ends_with(log), "{image} paired with {}", info.log_path);
+            assert_eq!(info.stored_hashes[0].hash, md5);
+            assert_eq!(info.confidence, LogConfidence::Exact);
+            assert!(info.other_candidates.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_unreferenced_logs_are_ambiguous() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("disk.dd"), b"raw").unwrap();
+        fs::write(dir.path().join("a.md5"), "33333333333333333333333333333333\n").unwrap();
+        fs::write(dir.path().join("b.md5"), "44444444444444444444444444444444\n").unwrap();
+
+        let info = find_companion_log(dir.path().join("disk.dd").to_str().unwrap()).unwrap();
+        assert_eq!(info.confidence, LogConfidence::Ambiguous);
+        assert_eq!(info.other_candidates.len(), 1);
+
+        // A log named after the image wins over the unreferenced ones
+        fs::write(dir.path().join("disk.md5"), "55555555555555555555555555555555\n").unwrap();
+        let info = find_companion_log(dir.path().join("disk.dd").to_str().unwrap()).unwrap();
+        assert!(info.log_path.ends_with("disk.md5"));
+        assert_eq!(info.confidence, LogConfidence::Probable);
+        assert_eq!(info.other_candidates.len(), 2);
+    }
+
+    const FTK_DRIVE_LOG: &str = "Created By AccessData® FTK® Imager 4.7.1.2\n\n\
+        Case Information:\nCase Number: 2024-17\n\n\
+        Physical Evidentiary Item (Source) Information:\n\
+        [Device Info]\n Source Type: Physical\n\
+        [Drive Geometry]\n Cylinders: 60,801\n Tracks per Cylinder: 255\n Sectors per Track: 63\n \
+        Bytes per Sector: 512\n Sector Count: 976,773,168\n\
+        [Physical Drive Information]\n Drive Model: WDC WD5000AAKX-00ERMA0 ATA Device\n \
+        Drive Serial Number: WD-WCC2EKJ12345\n Drive Interface Type: IDE\n Removable drive: False\n \
+        Source data size: 476940 MB\n Sector count:    976773168\n\
+        [Computed Hashes]\n MD5 checksum:    0123456789abcdef0123456789abcdef\n";
+
+    const GUYMAGER_INFO: &str = "GUYMAGER ACQUISITION INFO FILE\n==============================\n\n\
+        Guymager\n========\nVersion              : 0.8.12-1\n\n\
+        Device information\n==================\n\
+        Command executed: bash -c \"smartctl -a /dev/sdb\"\nInformation returned:\n   \
+        Device Model:     Samsung SSD 860 EVO 250GB\n   \
+        Serial Number:    S3YHNX0K123456A\n   \
+        User Capacity:    250,059,350,016 bytes [250 GB]\n   \
+        Sector Size:      512 bytes logical/physical\n   \
+        SATA Version is:  SATA 3.2, 6.0 Gb/s (current: 6.0 Gb/s)\n\n\
+        Acquisition\n===========\n\
+        Linux device         : /dev/sdb\n\
+        Device size          : 250059350016 (250.1GB)\n";
+
+    #[test]
+    fn test_dc3dd_named_pieces_replace_unnamed_ranges() {
+        let md5_a = "0f343b0931126a20f133d67c2b018a3b";
+        let md5_b = "54ac58cc1e2711a1a3d88bce15bb152d";
+        let log = format!(
+            "dc3dd 7.2.646 started at 2024-03-02 10:14:07 +0000\n\
+             input results for device `/dev/sdc':\n   c68df444559e55e96cd91d6ddc3fe835 (md5)\n\
+             \x20     {md5_a}, bytes 0 - 1023\n      {md5_b}, bytes 1024 - 2047\n\
+             output results for files `usb.000':\n   [ok] c68df444559e55e96cd91d6ddc3fe835 (md5)\n\
+             \x20     [ok] {md5_b}, bytes 1024 - 2047, `usb.001'\n"
+        );
+        let segments = parse_dc3dd_segments(&log).unwrap();
+        let rows: Vec<_> = segments.iter()
+            .map(|seg| (seg.segment_name.as_str(), seg.segment_number, seg.offset_from, seg.size, seg.verified))
+            .collect();
+        assert_eq!(rows, [
+            ("", 1, Some(0), Some(1024), None),
+            ("usb.001", 1, Some(1024), Some(1024), Some(true)),
+        ]);
+        assert!(segments.iter().all(|seg| seg.algorithm == "MD5"));
+    }
+
+    #[test]
+    fn test_source_device_from_ftk_and_guymager_logs() {
+        let ftk = parse_companion_text(FTK_DRIVE_LOG, Path::new("disk.E01.txt")).unwrap();
+        assert_eq!(ftk.source_device, Some(SourceDeviceInfo {
+            model: Some("WDC WD5000AAKX-00ERMA0 ATA Device".to_string()),
+            serial: Some("WD-WCC2EKJ12345".to_string()),
+            interface: Some("IDE".to_string()),
+            capacity_bytes: Some(500_107_862_016),
+            sector_size: Some(512),
+            geometry: Some(DriveGeometry { cylinders: 60_801, heads: 255, sectors_per_track: 63 }),
+        }));
+
+        let guymager = parse_companion_text(GUYMAGER_INFO, Path::new("disk.info")).unwrap();
+        let device = guymager.source_device.unwrap();
+        assert_eq!(device.model.as_deref(), Some("Samsung SSD 860 EVO 250GB"));
+        assert_eq!(device.serial.as_deref(), Some("S3YHNX0K123456A"));
+        assert_eq!(device.interface.as_deref(), Some("SATA 3.2, 6.0 Gb/s (current: 6.0 Gb/s)"));
+        assert_eq!((device.capacity_bytes, device.sector_size, device.geometry), (Some(250_059_350_016), Some(512), None));
+
+        assert_eq!(parse_byte_count("476940 MB"), Some(476_940 << 20));
+        assert_eq!(parse_byte_count("unknown"), None);
+    }
+
+    #[test]
+    fn test_source_device_discrepancies() {
+        let ftk = parse_companion_text(FTK_DRIVE_LOG, Path::new("disk.E01.txt")).unwrap().source_device.unwrap();
+        // EnCase records the bare model; FTK's " ATA Device" suffix is no difference
+        assert!(source_device_discrepancies(&ftk, Some("WDC WD5000AAKX-00ERMA0"), Some("WD-WCC2EKJ12345 ")).is_empty());
+        assert!(source_device_discrepancies(&ftk, None, None).is_empty());
+        let issues = source_device_discrepancies(&ftk, Some("ST1000DM003-1SB102"), Some("Z9A1B2C3"));
+        assert_eq!(issues.len(), 2);
+        assert!(issues[1].contains("WD-WCC2EKJ12345") && issues[1].contains("Z9A1B2C3"), "{issues:?}");
+    }
+
+    #[test]
+    fn test_manifest_csv_and_tsv_parsing() {
+        let dir = tempfile::tempdir().unwrap();
+        let csv = dir.path().join("evidence_files.csv");
+        fs::write(&csv, "\u{feff}MD5,SHA1,FileNames\r\n\
+            d41d8cd98f00b204e9800998ecf8427e,da39a3ee5e6b4b0d3255bfef95601890afd80709,\"C:\\Users\\bob\\a, b.txt\"\r\n").unwrap();
+        let entries = parse_manifest(&csv).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].path, "C:\\Users\\bob\\a, b.txt");
+        assert_eq!(entries[0].md5.as_deref(), Some("d41d8cd98f00b204e9800998ecf8427e"));
+
+        let tsv = dir.path().join("listing.txt");
+        fs::write(&tsv, "Filename\tFull Path\tSize (bytes)\tMD5\n\
+            a.txt\tUsers/bob/a.txt\t1,024\tn/a\n").unwrap();
+        let entries = parse_manifest(&tsv).unwrap();
+        assert_eq!((entries[0].path.as_str(), entries[0].size, entries[0].md5.as_deref()), ("Users/bob/a.txt", Some(1024), None));
+
+        // An acquisition log is not a manifest
+        fs::write(dir.path().join("evidence.txt"), ftk_log("11111111111111111111111111111111", &[])).unwrap();
+        assert!(parse_manifest(&dir.path().join("evidence.txt")).is_err());
+
+        // Two manifests: the one named after the container is associated
+        fs::write(dir.path().join("evidence.ad1"), b"ADSEGMENTEDFILE").unwrap();
+        let info = find_companion_manifest(dir.path().join("evidence.ad1").to_str().unwrap()).unwrap();
+        assert!(info.manifest_path.ends_with("evidence_files.csv"));
+        assert!(info.has_md5 && info.has_sha1);
+    }
+
+    #[test]
+    fn test_compare_manifest_statuses() {
+        let entry = |path: &str, md5: &str| ManifestEntry {
+            path: path.to_string(),
+            size: None,
+            md5: Some(md5.to_string()),
+            sha1: None,
+        };
+        let manifest = [
+            entry("C:\\Users\\Bob\\a.txt", &"a".repeat(32)),
+            entry("evidence.ad1\\[root]\\Users\\bob\\b.txt", &"b".repeat(32)),
+            entry("C:\\Users\\bob\\gone.txt", &"c".repeat(32)),
+        ];
+        let computed = [
+            ("Users/bob/a.txt".to_string(), "a".repeat(32)),
+            ("Users/bob/b.txt".to_string(), "f".repeat(32)),
+            ("Users/bob/extra.txt".to_string(), "e".repeat(32)),
+        ];
+        let results = compare_manifest(&manifest, "md5", computed);
+        let status: Vec<(&str, &str)> = results.iter()
+            .map(|r| (r.target_path().unwrap(), r.legacy_status()))
+            .collect();
+        assert_eq!(status, vec![
+            ("Users/bob/a.txt", "ok"),
+            ("Users/bob/b.txt", "nok"),
+            ("C:\\Users\\bob\\gone.txt", "missing_from_container"),
+            ("Users/bob/extra.txt", "missing_from_manifest"),
+        ]);
+    }
+}
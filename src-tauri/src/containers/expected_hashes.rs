@@ -0,0 +1,459 @@
+//! Expected-hash lists exchanged with other agencies
+//!
+//! Partner agencies send expected hashes in a few loose formats:
+//! - one hash per line, optionally followed by a file name (md5sum style)
+//! - CSV, either NSRL-style with one column per algorithm ("SHA-1", "MD5",
+//!   "FileName", "FileSize") or with explicitly configured columns
+//! - tab-separated `filename<TAB>hash`
+//!
+//! Every format is normalized into [`SegmentHash`] entries (hashes naming a
+//! file) and [`StoredHash`] entries (bare hashes, e.g. of a whole image).
+//! Hashes are canonicalized through [`HashValue`], so uppercase hashes and
+//! Windows line endings read the same as anything else. When no algorithm
+//! is given, it is taken from the hash length (MD5, SHA-1, SHA-256, SHA-512).
+
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+use super::companion::{decode_text, extract_segment_number, split_delimited};
+use super::types::{SegmentHash, StoredHash};
+use crate::common::atomic_file::write_atomic;
+use crate::common::integrity;
+use crate::common::hash::{HashAlgorithm, HashValue};
+
+/// Layout of an expected-hash list
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HashListFormat {
+    /// `hash [filename]` per line
+    Lines,
+    /// Comma separated, header row or configured columns
+    Csv,
+    /// `filename<TAB>hash` per line
+    Tsv,
+}
+
+/// Explicit CSV layout, for lists whose header we don't recognize
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CsvColumns {
+    /// 0-based column of the file name; none for bare hashes
+    pub filename: Option<usize>,
+    pub hash: usize,
+    /// Algorithm of the hash column; guessed from the hash length when unset
+    pub algorithm: Option<String>,
+    pub size: Option<usize>,
+    /// Skip the first row
+    #[serde(default)]
+    pub has_header: bool,
+}
+
+/// An imported expected-hash list
+#[derive(Serialize, Clone)]
+pub struct ExpectedHashList {
+    pub format: HashListFormat,
+    /// Hashes naming a file or segment
+    pub segment_hashes: Vec<SegmentHash>,
+    /// Hashes without a file name
+    pub hashes: Vec<StoredHash>,
+    /// 1-based numbers of non-empty lines without a usable hash
+    pub skipped_lines: Vec<usize>,
+}
+
+impl ExpectedHashList {
+    /// Segment hashes of `algorithm` - a list may carry several per file
+    pub fn for_algorithm(&self, algorithm: &str) -> Vec<SegmentHash> {
+        let Ok(wanted) = HashAlgorithm::from_str(algorithm) else {
+            return Vec::new();
+        };
+        self.segment_hashes.iter()
+            .filter(|h| HashAlgorithm::from_str(&h.algorithm).is_ok_and(|algo| algo == wanted))
+            .cloned()
+            .collect()
+    }
+}
+
+/// Column names recognized in a CSV header
+const FILENAME_COLUMNS: &[&str] = &["filename", "file name", "name", "full path", "path", "segment", "segment name"];
+const SIZE_COLUMNS: &[&str] = &["filesize", "file size", "size", "size (bytes)"];
+const ALGORITHM_COLUMNS: &[&str] = &["algorithm", "hash type", "hashtype"];
+const HASH_COLUMNS: &[&str] = &["hash", "hash value", "digest", "expected hash"];
+
+/// Algorithm of a hash whose type isn't stated
+fn algorithm_for_length(hash: &HashValue) -> Option<HashAlgorithm> {
+    match hash.as_str().len() {
+        32 => Some(HashAlgorithm::Md5),
+        40 => Some(HashAlgorithm::Sha1),
+        64 => Some(HashAlgorithm::Sha256),
+        128 => Some(HashAlgorithm::Sha512),
+        _ => None,
+    }
+}
+
+/// A hash with its algorithm name, validated against each other
+fn parse_hash(value: &str, algorithm: Option<&str>) -> Option<(String, HashValue)> {
+    let value = value.trim().trim_matches('"');
+    match algorithm {
+        Some(name) => {
+            let hash = HashValue::parse_named(value, name).ok()?;
+            let name = HashAlgorithm::from_str(name).map(|a| a.name().to_string()).unwrap_or_else(|_| name.trim().to_uppercase());
+            Some((name, hash))
+        }
+        None => {
+            let hash: HashValue = value.parse().ok()?;
+            Some((algorithm_for_length(&hash)?.name().to_string(), hash))
+        }
+    }
+}
+
+/// Accumulates entries while parsing
+struct ListBuilder {
+    list: ExpectedHashList,
+}
+
+impl ListBuilder {
+    fn new(format: HashListFormat) -> Self {
+        Self {
+            list: ExpectedHashList { format, segment_hashes: Vec::new(), hashes: Vec::new(), skipped_lines: Vec::new() },
+        }
+    }
+
+    fn push(&mut self, filename: Option<&str>, algorithm: String, hash: HashValue, size: Option<u64>) {
+        match filename.map(str::trim).filter(|name| !name.is_empty()) {
+            Some(name) => {
+                let segment_number = extract_segment_number(name)
+                    .unwrap_or(self.list.segment_hashes.len() as u32 + 1);
+                self.list.segment_hashes.push(SegmentHash {
+                    segment_name: name.to_string(),
+                    segment_number,
+                    algorithm,
+                    hash: hash.into(),
+                    offset_from: None,
+                    offset_to: None,
+                    size,
+                    verified: None,
+                });
+            }
+            None => self.list.hashes.push(StoredHash {
+                algorithm,
+                hash: hash.into(),
+                verified: None,
+                timestamp: None,
+                source: Some("imported".to_string()),
+                offset: None,
+                size,
+            }),
+        }
+    }
+}
+
+/// Non-empty, non-comment lines with their 1-based numbers
+fn content_lines(text: &str) -> impl Iterator<Item = (usize, &str)> {
+    text.lines()
+        .enumerate()
+        .map(|(idx, line)| (idx + 1, line.trim_end_matches('\r')))
+        .filter(|(_, line)| !line.trim().is_empty() && !line.trim_start().starts_with('#'))
+}
+
+fn parse_lines(text: &str) -> ExpectedHashList {
+    let mut builder = ListBuilder::new(HashListFormat::Lines);
+    for (number, line) in content_lines(text) {
+        let line = line.trim();
+        let (hash, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        // md5sum marks binary-mode entries with '*'
+        let filename = rest.trim().trim_start_matches('*');
+        match parse_hash(hash, None) {
+            Some((algorithm, hash)) => builder.push(Some(filename), algorithm, hash, None),
+            None => builder.list.skipped_lines.push(number),
+        }
+    }
+    builder.list
+}
+
+fn parse_tsv(text: &str) -> ExpectedHashList {
+    let mut builder = ListBuilder::new(HashListFormat::Tsv);
+    for (number, line) in content_lines(text) {
+        let parsed = line.rsplit_once('\t')
+            .and_then(|(filename, hash)| Some((filename, parse_hash(hash, None)?)));
+        match parsed {
+            Some((filename, (algorithm, hash))) => builder.push(Some(filename), algorithm, hash, None),
+            // A header row is skipped like any other line without a hash
+            None => builder.list.skipped_lines.push(number),
+        }
+    }
+    builder.list
+}
+
+/// Hash columns of a CSV row: (column, fixed algorithm or algorithm column)
+enum HashColumns {
+    /// One column per algorithm, NSRL style
+    Named(Vec<(usize, String)>),
+    /// A hash column, with its algorithm in another column or guessed
+    Single { hash: usize, algorithm: Option<usize>, fixed: Option<String> },
+}
+
+struct CsvLayout {
+    filename: Option<usize>,
+    size: Option<usize>,
+    hashes: HashColumns,
+}
+
+impl CsvLayout {
+    fn from_columns(columns: &CsvColumns) -> Self {
+        Self {
+            filename: columns.filename,
+            size: columns.size,
+            hashes: HashColumns::Single { hash: columns.hash, algorithm: None, fixed: columns.algorithm.clone() },
+        }
+    }
+
+    /// Layout from a header row, None unless it names a hash column
+    fn from_header(line: &str) -> Option<Self> {
+        let names: Vec<String> = split_delimited(line, ',').iter()
+            .map(|name| name.trim().to_lowercase())
+            .collect();
+        let find = |candidates: &[&str]| candidates.iter().find_map(|c| names.iter().position(|name| name == c));
+
+        let hashes = match find(HASH_COLUMNS) {
+            Some(hash) => HashColumns::Single { hash, algorithm: find(ALGORITHM_COLUMNS), fixed: None },
+            None => {
+                let named: Vec<(usize, String)> = names.iter().enumerate()
+                    .filter_map(|(idx, name)| Some((idx, HashAlgorithm::from_str(name).ok()?.name().to_string())))
+                    .collect();
+                if named.is_empty() {
+                    return None;
+                }
+                HashColumns::Named(named)
+            }
+        };
+        Some(Self { filename: find(FILENAME_COLUMNS), size: find(SIZE_COLUMNS), hashes })
+    }
+
+    /// Add the hashes of one row; false if it had none
+    fn add_row(&self, line: &str, builder: &mut ListBuilder) -> bool {
+        let fields = split_delimited(line, ',');
+        let field = |column: Option<usize>| column.and_then(|c| fields.get(c)).map(|f| f.trim());
+        let filename = field(self.filename);
+        let size = field(self.size).and_then(|size| size.replace(',', "").parse().ok());
+
+        let mut found = false;
+        let mut add = |value: Option<&str>, algorithm: Option<&str>| {
+            if let Some((algorithm, hash)) = value.and_then(|value| parse_hash(value, algorithm.filter(|a| !a.is_empty()))) {
+                builder.push(filename, algorithm, hash, size);
+                found = true;
+            }
+        };
+        match &self.hashes {
+            HashColumns::Named(columns) => {
+                for (column, algorithm) in columns {
+                    add(field(Some(*column)), Some(algorithm));
+                }
+            }
+            HashColumns::Single { hash, algorithm, fixed } => {
+                add(field(Some(*hash)), fixed.as_deref().or(field(*algorithm)));
+            }
+        }
+        found
+    }
+}
+
+fn parse_csv(text: &str, columns: Option<&CsvColumns>) -> Result<ExpectedHashList, String> {
+    let mut builder = ListBuilder::new(HashListFormat::Csv);
+    let mut lines = content_lines(text);
+    let layout = match columns {
+        Some(columns) => {
+            if columns.has_header {
+                lines.next();
+            }
+            CsvLayout::from_columns(columns)
+        }
+        None => lines.next()
+            .and_then(|(_, header)| CsvLayout::from_header(header))
+            .ok_or("CSV hash list has no recognizable header (needs a Hash or algorithm-named column); configure the columns")?,
+    };
+    for (number, line) in lines {
+        if !layout.add_row(line, &mut builder) {
+            builder.list.skipped_lines.push(number);
+        }
+    }
+    Ok(builder.list)
+}
+
+/// Parse an expected-hash list from text
+///
+/// `columns` only applies to CSV; without it the header row is used.
+pub fn parse_expected_hashes(text: &str, format: HashListFormat, columns: Option<&CsvColumns>) -> Result<ExpectedHashList, String> {
+    let list = match format {
+        HashListFormat::Lines => parse_lines(text),
+        HashListFormat::Tsv => parse_tsv(text),
+        HashListFormat::Csv => parse_csv(text, columns)?,
+    };
+    if list.segment_hashes.is_empty() && list.hashes.is_empty() {
+        return Err("No hashes found in the list".to_string());
+    }
+    Ok(list)
+}
+
+/// Format of a list file, from its extension or its first line
+fn detect_format(path: &Path, text: &str) -> HashListFormat {
+    match path.extension().and_then(|e| e.to_str()).map(str::to_lowercase).as_deref() {
+        Some("csv") => return HashListFormat::Csv,
+        Some("tsv") => return HashListFormat::Tsv,
+        _ => {}
+    }
+    match content_lines(text).next() {
+        Some((_, line)) if line.contains('\t') => HashListFormat::Tsv,
+        Some((_, line)) if line.contains(',') => HashListFormat::Csv,
+        _ => HashListFormat::Lines,
+    }
+}
+
+/// Read an expected-hash list, detecting the format unless given
+pub fn import_expected_hashes(path: &Path, format: Option<HashListFormat>, columns: Option<&CsvColumns>) -> Result<ExpectedHashList, String> {
+    let bytes = fs::read(path).map_err(|e| format!("Failed to read hash list {}: {}", path.display(), e))?;
+    let text = decode_text(&bytes);
+    let format = format.unwrap_or_else(|| detect_format(path, &text));
+    let list = parse_expected_hashes(&text, format, columns)
+        .map_err(|e| format!("{}: {}", path.display(), e))?;
+    debug!(
+        path = %path.display(), ?format, segments = list.segment_hashes.len(),
+        hashes = list.hashes.len(), skipped = list.skipped_lines.len(), "Imported expected hashes"
+    );
+    Ok(list)
+}
+
+/// Quote a CSV field when it needs it
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Write `hashes` in `format` (CSV gets a FileName,FileSize,Algorithm,Hash header)
+pub fn format_expected_hashes(hashes: &[SegmentHash], format: HashListFormat) -> String {
+    let mut out = String::new();
+    if format == HashListFormat::Csv {
+        out.push_str("FileName,FileSize,Algorithm,Hash\n");
+    }
+    for entry in hashes {
+        let line = match format {
+            HashListFormat::Lines => format!("{}  {}", entry.hash, entry.segment_name),
+            HashListFormat::Tsv => format!("{}\t{}", entry.segment_name, entry.hash),
+            HashListFormat::Csv => format!(
+                "{},{},{},{}",
+                csv_field(&entry.segment_name),
+                entry.size.map(|size| size.to_string()).unwrap_or_default(),
+                csv_field(&entry.algorithm),
+                entry.hash
+            ),
+        };
+        out.push_str(&line);
+        out.push('\n');
+    }
+    out
+}
+
+/// Export `hashes` to `path`; returns the number of entries written
+pub fn export_expected_hashes(hashes: &[SegmentHash], path: &Path, format: HashListFormat) -> Result<usize, String> {
+    write_atomic(path, integrity::tag_text_if_enabled(format_expected_hashes(hashes, format))?)
+        .map_err(|e| format!("Failed to write hash list {}: {}", path.display(), e))?;
+    Ok(hashes.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MD5: &str = "9e107d9d372bb6826bd81d3542a419d6";
+    const SHA1: &str = "2fd4e1c67a2d28fced849ee1bb76e7391b93eb12";
+
+    fn segments() -> Vec<SegmentHash> {
+        ["disk.001", "disk.002", "disk, copy.003"].iter().enumerate()
+            .map(|(idx, name)| SegmentHash {
+                segment_name: name.to_string(),
+                segment_number: idx as u32 + 1,
+                algorithm: "MD5".to_string(),
+                hash: format!("{:032x}", idx + 0xabc),
+                offset_from: None,
+                offset_to: None,
+                size: Some(1024 * (idx as u64 + 1)),
+                verified: None,
+            })
+            .collect()
+    }
+
+    /// Exported lists read back the same, also after a trip through Windows
+    fn assert_round_trip(format: HashListFormat) {
+        let original = segments();
+        let text = format_expected_hashes(&original, format);
+        let windows = text.replace('\n', "\r\n").to_uppercase().replace("FILENAME,FILESIZE,ALGORITHM,HASH", "FileName,FileSize,Algorithm,Hash");
+        for text in [text, windows] {
+            let list = parse_expected_hashes(&text, format, None).unwrap();
+            assert!(list.skipped_lines.is_empty(), "{format:?}: {:?}", list.skipped_lines);
+            assert_eq!(list.segment_hashes.len(), original.len(), "{format:?}");
+            for (read, written) in list.segment_hashes.iter().zip(&original) {
+                assert_eq!(read.segment_name.to_lowercase(), written.segment_name, "{format:?}");
+                assert_eq!(read.segment_number, written.segment_number);
+                assert_eq!(read.algorithm, "MD5");
+                assert_eq!(read.hash, written.hash, "{format:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_round_trip_each_format() {
+        assert_round_trip(HashListFormat::Lines);
+        assert_round_trip(HashListFormat::Tsv);
+        assert_round_trip(HashListFormat::Csv);
+
+        let csv = parse_expected_hashes(&format_expected_hashes(&segments(), HashListFormat::Csv), HashListFormat::Csv, None).unwrap();
+        assert_eq!(csv.segment_hashes[2].size, Some(3072));
+    }
+
+    #[test]
+    fn test_loose_and_nsrl_lists() {
+        let text = format!("# from agency X\r\n{}\r\n{} *disk.E01\r\nnot a hash\r\n", MD5.to_uppercase(), SHA1);
+        let list = parse_expected_hashes(&text, HashListFormat::Lines, None).unwrap();
+        assert_eq!(list.hashes.len(), 1);
+        assert_eq!(list.hashes[0].hash, MD5);
+        assert_eq!(list.segment_hashes[0].segment_name, "disk.E01");
+        assert_eq!(list.segment_hashes[0].algorithm, "SHA-1");
+        assert_eq!(list.skipped_lines, vec![4]);
+
+        let nsrl = format!(
+            "\"SHA-1\",\"MD5\",\"CRC32\",\"FileName\",\"FileSize\"\r\n\"{}\",\"{}\",\"414FA339\",\"disk.001\",4096\r\n",
+            SHA1.to_uppercase(), MD5.to_uppercase()
+        );
+        let list = parse_expected_hashes(&nsrl, HashListFormat::Csv, None).unwrap();
+        assert_eq!(list.segment_hashes.len(), 3);
+        assert_eq!(list.for_algorithm("md5")[0].hash, MD5);
+        assert_eq!(list.for_algorithm("sha1")[0].size, Some(4096));
+
+        let columns = CsvColumns { filename: Some(2), hash: 0, algorithm: Some("md5".to_string()), size: None, has_header: true };
+        let custom = format!("Value,Case,Item\n{},2024-001,disk.002\n", MD5.to_uppercase());
+        let list = parse_expected_hashes(&custom, HashListFormat::Csv, Some(&columns)).unwrap();
+        assert_eq!((list.segment_hashes[0].segment_name.as_str(), list.segment_hashes[0].segment_number), ("disk.002", 2));
+        assert!(parse_expected_hashes(&custom, HashListFormat::Csv, None).is_err());
+    }
+
+    #[test]
+    fn test_import_detects_format_from_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let tsv = dir.path().join("expected.txt");
+        fs::write(&tsv, format!("disk.001\t{}\r\n", MD5.to_uppercase())).unwrap();
+        let list = import_expected_hashes(&tsv, None, None).unwrap();
+        assert_eq!(list.format, HashListFormat::Tsv);
+        assert_eq!(list.segment_hashes[0].hash, MD5);
+
+        let out = dir.path().join("export.csv");
+        assert_eq!(export_expected_hashes(&list.segment_hashes, &out, HashListFormat::Csv).unwrap(), 1);
+        let back = import_expected_hashes(&out, None, None).unwrap();
+        assert_eq!(back.format, HashListFormat::Csv);
+        assert_eq!(back.segment_hashes[0].segment_name, "disk.001");
+    }
+}
@@ -0,0 +1,183 @@
+//! Explainable container detection
+//!
+//! [`identify`] runs the format checks against a file and records what each
+//! one found, so a misdetection can be traced to the check that decided it.
+//! `detect_container` takes the first match of the same report: the order
+//! of the checks below is the detection order.
+
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use tracing::debug;
+
+use crate::ad1;
+use crate::archive;
+use crate::common::format_hex_inline;
+use crate::ewf;
+use crate::iso;
+use crate::raw;
+use crate::ufed;
+use super::scanning::detect_container_type_by_extension;
+use super::types::{ContainerKind, DetectionCandidate, DetectionConfidence, DetectionReport};
+
+/// Bytes shown in [`DetectionReport::signature_bytes_hex`]
+const SIGNATURE_BYTES: u64 = 16;
+
+/// Run every detection check against `path`
+///
+/// Only a missing or unreadable path is an error. An empty file (or a
+/// folder) is reported as unrecognized without running the checks.
+pub fn identify(path: &str) -> Result<DetectionReport, String> {
+    let path_obj = Path::new(path);
+    if !path_obj.exists() {
+        return Err(format!("Input file not found: {path}"));
+    }
+    let metadata = path_obj.metadata().map_err(|e| format!("Failed to read metadata of {path}: {e}"))?;
+    let lower = path.to_lowercase();
+    let mut head = Vec::new();
+    if metadata.is_file() {
+        File::open(path_obj)
+            .and_then(|file| file.take(SIGNATURE_BYTES).read_to_end(&mut head))
+            .map_err(|e| format!("Failed to read {path}: {e}"))?;
+    }
+    let mut report = DetectionReport {
+        path: path.to_string(),
+        file_size: metadata.len(),
+        detected: None,
+        candidates: Vec::new(),
+        signature_bytes_hex: format_hex_inline(&head, true),
+        extension_hint: detect_container_type_by_extension(&lower).map(str::to_string),
+    };
+    if head.is_empty() {
+        debug!(path, "Nothing to identify: empty file or folder");
+        return Ok(report);
+    }
+
+    let checks = &mut report.candidates;
+    let mut record = |kind, confidence, reason: String| checks.push(DetectionCandidate { kind, confidence, reason });
+    let by_name = |named: bool| if named { DetectionConfidence::High } else { DetectionConfidence::Medium };
+
+    // Cellebrite UFED is recognized by name (or a ZIP beside its .ufd)
+    if ufed::is_ufed(path) {
+        record(ContainerKind::Ufed, DetectionConfidence::High, "UFED file name, or a ZIP next to a .ufd".to_string());
+    } else {
+        record(ContainerKind::Ufed, DetectionConfidence::None, "Not a UFED file name".to_string());
+    }
+
+    // EWF by signature whatever the name, so renamed segments are found.
+    // `is_e01` accepts the L01 signatures too, hence the order.
+    let lx01 = ewf::is_lx01_file(path).unwrap_or(false);
+    let l01 = !lx01 && ewf::is_l01_file(path).unwrap_or(false);
+    let e01 = !lx01 && !l01 && ewf::is_e01(path).unwrap_or(false);
+    let e01_named = lower.ends_with(".ewf") || lower.contains(".e0") || lower.contains(".ex") || lower.ends_with(".s01");
+    let l01_named = lower.contains(".l0") || lower.contains(".lx");
+    for (kind, found, named, signature) in [
+        (ContainerKind::E01, e01, e01_named, "EVF/EVF2"),
+        (ContainerKind::L01, l01, l01_named, "LVF"),
+        (ContainerKind::Lx01, lx01, l01_named, "LVF2/LEF2"),
+    ] {
+        match (found, named) {
+            (true, true) => record(kind, DetectionConfidence::High, format!("{signature} signature and segment name")),
+            (true, false) => record(kind, DetectionConfidence::Medium, format!("{signature} signature, but not an EWF segment name (renamed?)")),
+            (false, true) => record(kind, DetectionConfidence::None, format!("EWF segment name, but no {signature} signature")),
+            (false, false) => record(kind, DetectionConfidence::None, format!("No {signature} signature")),
+        }
+    }
+
+    let ad1_named = lower.ends_with(".ad1");
+    if ad1::is_ad1(path).unwrap_or(false) {
+        record(ContainerKind::Ad1, by_name(ad1_named), "ADSEGMENTEDFILE signature".to_string());
+    } else {
+        record(ContainerKind::Ad1, DetectionConfidence::None, "No ADSEGMENTEDFILE signature".to_string());
+    }
+
+    // Gzip-compressed disk images are raw images, not archives
+    if lower.ends_with(".gz") {
+        if raw::gzip::is_gzip_image(path_obj) {
+            record(ContainerKind::Raw, DetectionConfidence::High, "Gzip stream holding a disk image".to_string());
+        } else {
+            record(ContainerKind::Raw, DetectionConfidence::None, "Gzip file that does not hold a disk image".to_string());
+        }
+    }
+
+    // Archives before raw, so a 7z part named .001 is not taken for a split image
+    match (archive::detect_archive_format(path).ok().flatten(), archive::is_archive(path).unwrap_or(false)) {
+        (Some(format), _) => record(ContainerKind::Archive, DetectionConfidence::High, format!("{format:?} signature")),
+        (None, true) => record(ContainerKind::Archive, DetectionConfidence::Medium, "Archive extension, no archive signature".to_string()),
+        (None, false) => record(ContainerKind::Archive, DetectionConfidence::None, "No archive signature or extension".to_string()),
+    }
+
+    // Optical images - before raw, since .img files may be ISO 9660 too
+    if lower.ends_with(".iso") || lower.ends_with(".udf") || lower.ends_with(".img") {
+        if iso::is_iso(path).unwrap_or(false) {
+            record(ContainerKind::Iso, DetectionConfidence::High, "ISO 9660/UDF descriptor at sector 16".to_string());
+        } else {
+            record(ContainerKind::Iso, DetectionConfidence::None, "No ISO 9660/UDF descriptor at sector 16".to_string());
+        }
+    }
+
+    if raw::is_raw(path).unwrap_or(false) {
+        record(ContainerKind::Raw, DetectionConfidence::Low, "Raw image extension or split segment suffix (raw images have no signature)".to_string());
+    } else {
+        record(ContainerKind::Raw, DetectionConfidence::None, "No raw image extension or split segment suffix".to_string());
+    }
+
+    report.detected = report.candidates.iter()
+        .find(|candidate| candidate.confidence > DetectionConfidence::None)
+        .map(|candidate| candidate.kind);
+    debug!(path, detected = ?report.detected, "Container identified");
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn confidence(report: &DetectionReport, kind: ContainerKind) -> DetectionConfidence {
+        report.candidates.iter()
+            .filter(|candidate| candidate.kind == kind)
+            .map(|candidate| candidate.confidence)
+            .max()
+            .unwrap_or(DetectionConfidence::None)
+    }
+
+    #[test]
+    fn test_renamed_e01_is_found_by_signature() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("evidence.bin");
+        let mut bytes = b"EVF\x09\x0d\x0a\xff\x00\x01\x01\x00\x00\x00".to_vec();
+        bytes.resize(4096, 0);
+        std::fs::write(&path, bytes).unwrap();
+
+        let report = identify(path.to_str().unwrap()).unwrap();
+        assert_eq!(report.detected, Some(ContainerKind::E01));
+        assert_eq!(confidence(&report, ContainerKind::E01), DetectionConfidence::Medium);
+        // The .bin name alone would have made it an optical archive
+        assert_eq!(confidence(&report, ContainerKind::Archive), DetectionConfidence::Medium);
+        assert!(report.signature_bytes_hex.starts_with("45 56 46 09 0D 0A FF 00"), "{}", report.signature_bytes_hex);
+        assert_eq!(super::super::operations::detect_container(path.to_str().unwrap()).ok(), Some(ContainerKind::E01));
+    }
+
+    #[test]
+    fn test_7z_part_named_001_and_empty_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let part = dir.path().join("disk.001");
+        let mut bytes = archive::detection::SEVEN_ZIP_MAGIC.to_vec();
+        bytes.resize(4096, 0);
+        std::fs::write(&part, bytes).unwrap();
+
+        let report = identify(part.to_str().unwrap()).unwrap();
+        assert_eq!(report.detected, Some(ContainerKind::Archive));
+        assert_eq!(confidence(&report, ContainerKind::Archive), DetectionConfidence::High);
+        // The split suffix still shows up as the weaker alternative
+        assert_eq!(confidence(&report, ContainerKind::Raw), DetectionConfidence::Low);
+
+        let empty = dir.path().join("empty.dd");
+        std::fs::write(&empty, b"").unwrap();
+        let report = identify(empty.to_str().unwrap()).unwrap();
+        assert_eq!((report.detected, report.file_size, report.signature_bytes_hex.as_str()), (None, 0, ""));
+        assert!(report.candidates.is_empty());
+        let error = super::super::operations::detect_container(empty.to_str().unwrap()).unwrap_err();
+        assert!(error.starts_with("File is empty"), "{error}");
+    }
+}
@@ -1,19 +1,73 @@
 //! Container abstraction layer for forensic image formats
 //! 
 //! This module provides a unified interface for working with various forensic
-//! container formats including AD1, E01, L01, Raw, Archive, and UFED.
+//! container formats including AD1, E01, L01, Raw, ISO, Archive, and UFED.
 
 mod types;
 mod operations;
 mod scanning;
 mod segments;
 mod companion;
+mod cache;
+mod watcher;
+mod expected_hashes;
+mod capabilities;
+mod access;
+mod quick_check;
+mod batch_info;
+mod identify;
+mod bundles;
 
 // Re-export all public types
 pub use types::*;
 
 // Re-export main operations
-pub use operations::{info, info_fast, verify, extract};
+pub use operations::{info, info_with_tree_limit, info_with_options, info_fast, verify, verify_with_options, verify_against_manifest, extract, extract_with_options, extract_with_progress};
+
+// Re-export cached info lookups
+pub use cache::{info_cached, info_fast_cached, clear_info_cache, INFO_CACHE_CAPACITY, FAST_INFO_CACHE_CAPACITY};
 
 // Re-export scanning functions
-pub use scanning::{scan_directory, scan_directory_recursive, scan_directory_streaming};
+pub use scanning::{
+    scan_directory, scan_directory_recursive, scan_directory_streaming, scan_directory_parallel,
+    scan_directory_with_options,
+    discover_file, DEFAULT_SCAN_WORKERS, MAX_SCAN_WORKERS, UFED_EXTRACTION_TYPE, ORPHANED_E01_TYPE,
+};
+
+// Re-export file manifest parsing
+pub use companion::{parse_manifest, find_companion_manifest, ManifestEntry};
+
+// Re-export companion log lookup
+pub use companion::{find_companion_log, source_device_discrepancies};
+
+// Re-export expected-hash list exchange
+pub use expected_hashes::{
+    import_expected_hashes, export_expected_hashes, parse_expected_hashes, format_expected_hashes,
+    CsvColumns, ExpectedHashList, HashListFormat,
+};
+
+// Re-export the format capability matrix
+pub use capabilities::{supported_formats, SupportedFormat};
+
+// Re-export per-path access control
+pub use access::{registry as access_registry, AccessGuard, AccessRegistry};
+
+// Re-export segment set discovery
+pub use segments::segment_set_info;
+
+// Re-export the header-only structure check
+pub use quick_check::{quick_check, QuickCheckReport};
+
+// Re-export explainable container detection
+pub use identify::identify;
+
+// Re-export evidence bundle grouping
+pub use bundles::{group_evidence, BundleGrouper};
+
+// Re-export batched info lookups
+pub use batch_info::{
+    info_batch, DEFAULT_INFO_TIMEOUT_SECS, DEFAULT_BATCH_WORKERS, MAX_BATCH_WORKERS, DEFAULT_WORKERS_PER_DEVICE,
+};
+
+// Re-export intake folder watching
+pub use watcher::{watch_directory, unwatch_directory, DirectoryWatcher, DEFAULT_SETTLE_TIME};
@@ -2,18 +2,35 @@
 //!
 //! This module provides the main entry points for working with forensic containers.
 
-use tracing::debug;
-use std::path::Path;
+use tracing::{debug, warn};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
 
 use crate::ad1;
 use crate::archive;
+use crate::common::atomic_file::{clean_stale_partials, PartialCleanup, STALE_PARTIAL_AGE};
 use crate::common::audit::{log_evidence_access, log_data_export};
+use crate::common::dedupe::DedupeIndex;
+use crate::common::extract_filter::{ExtractFilter, ExtractProgress, ExtractStats};
+use crate::common::extract_manifest::{ExtractedFile, ExtractionManifest};
+use crate::common::path_security::{available_space, check_output_location};
+use crate::common::safe_path::sanitize_component;
 use crate::ewf;
+use crate::iso;
 use crate::raw;
 use crate::ufed;
 
-use super::types::{ContainerInfo, ContainerKind, VerifyEntry};
-use super::companion::find_companion_log;
+use super::types::{
+    ContainerInfo, ContainerKind, ExtractOptions, ExtractResult, SanitizedEntry, VerifyEntry, VerifyOptions, VerifyReason, VerifyStatus,
+    VerifyTarget,
+};
+use super::companion::{compare_manifest, find_companion_log, find_companion_manifest, parse_manifest};
+use super::segments::segment_set_info;
+use super::identify::identify;
+
+/// Shown with Android sparse images, whose container bytes hash differently
+/// from the partition they hold
+const SPARSE_HASH_NOTE: &str = "Android sparse image: the container hash will not match a dd image of the partition; compare the logical image hash instead";
 
 /// Fast info - only reads headers, doesn't parse full item trees
 /// Use this for quick container listing/display
@@ -27,9 +44,10 @@ pub fn info_fast(path: &str) -> Result<ContainerInfo, String> {
     })?;
     let companion_log = find_companion_log(path);
     
-    match kind {
+    let info = match kind {
         ContainerKind::Ad1 => {
             let info = ad1::info_fast(path)?;
+            let segment_count = info.segment.segment_number;
             Ok(ContainerInfo {
                 container: "AD1".to_string(),
                 ad1: Some(info),
@@ -38,22 +56,36 @@ pub fn info_fast(path: &str) -> Result<ContainerInfo, String> {
                 raw: None,
                 archive: None,
                 ufed: None,
+                iso: None,
                 note: None,
                 companion_log,
+                segments: segment_set_info(path, Some(segment_count)),
+                manifest: find_companion_manifest(path),
+                triage: None,
+                physical_size: None,
+                logical_size: None,
+                compression_ratio: None,
             })
         }
         ContainerKind::E01 => {
             let info = ewf::info(path)?;
             Ok(ContainerInfo {
-                container: "E01".to_string(),
+                container: e01_container_label(&info),
                 ad1: None,
                 e01: Some(info),
                 l01: None,
                 raw: None,
                 archive: None,
                 ufed: None,
+                iso: None,
                 note: None,
                 companion_log,
+                segments: segment_set_info(path, None),
+                manifest: None,
+                triage: None,
+                physical_size: None,
+                logical_size: None,
+                compression_ratio: None,
             })
         }
         ContainerKind::L01 => {
@@ -67,12 +99,42 @@ pub fn info_fast(path: &str) -> Result<ContainerInfo, String> {
                 raw: None,
                 archive: None,
                 ufed: None,
+                iso: None,
                 note: None,
                 companion_log,
+                segments: segment_set_info(path, None),
+                manifest: find_companion_manifest(path),
+                triage: None,
+                physical_size: None,
+                logical_size: None,
+                compression_ratio: None,
+            })
+        }
+        ContainerKind::Lx01 => {
+            // EWF2 sections need their own parser; only metadata is read for now
+            let info = ewf::lx01_info(path)?;
+            Ok(ContainerInfo {
+                container: "Lx01".to_string(),
+                ad1: None,
+                e01: None,
+                l01: Some(info),
+                raw: None,
+                archive: None,
+                ufed: None,
+                iso: None,
+                note: None,
+                companion_log,
+                segments: segment_set_info(path, None),
+                manifest: find_companion_manifest(path),
+                triage: None,
+                physical_size: None,
+                logical_size: None,
+                compression_ratio: None,
             })
         }
         ContainerKind::Raw => {
             let info = raw::info(path)?;
+            let note = info.sparse.as_ref().map(|_| SPARSE_HASH_NOTE.to_string());
             Ok(ContainerInfo {
                 container: "RAW".to_string(),
                 ad1: None,
@@ -81,8 +143,36 @@ pub fn info_fast(path: &str) -> Result<ContainerInfo, String> {
                 raw: Some(info),
                 archive: None,
                 ufed: None,
+                iso: None,
+                note,
+                companion_log,
+                segments: segment_set_info(path, None),
+                manifest: None,
+                triage: None,
+                physical_size: None,
+                logical_size: None,
+                compression_ratio: None,
+            })
+        }
+        ContainerKind::Iso => {
+            let info = iso::info(path)?;
+            Ok(ContainerInfo {
+                container: iso_container_label(&info),
+                ad1: None,
+                e01: None,
+                l01: None,
+                raw: None,
+                archive: None,
+                ufed: None,
+                iso: Some(info),
                 note: None,
                 companion_log,
+                segments: segment_set_info(path, None),
+                manifest: None,
+                triage: None,
+                physical_size: None,
+                logical_size: None,
+                compression_ratio: None,
             })
         }
         ContainerKind::Archive => {
@@ -95,8 +185,15 @@ pub fn info_fast(path: &str) -> Result<ContainerInfo, String> {
                 raw: None,
                 archive: Some(info),
                 ufed: None,
+                iso: None,
                 note: None,
                 companion_log,
+                segments: segment_set_info(path, None),
+                manifest: None,
+                triage: None,
+                physical_size: None,
+                logical_size: None,
+                compression_ratio: None,
             })
         }
         ContainerKind::Ufed => {
@@ -109,24 +206,91 @@ pub fn info_fast(path: &str) -> Result<ContainerInfo, String> {
                 raw: None,
                 archive: None,
                 ufed: Some(info),
+                iso: None,
                 note: None,
                 companion_log,
+                segments: None,
+                manifest: None,
+                triage: None,
+                physical_size: None,
+                logical_size: None,
+                compression_ratio: None,
             })
         }
+    };
+    info.map(|info| with_sizes(info, path))
+}
+
+/// Fill in the physical and logical sizes of `info` from whichever format
+/// it holds, and the compression ratio when both are known
+fn with_sizes(mut info: ContainerInfo, path: &str) -> ContainerInfo {
+    let on_disk = || std::fs::metadata(path).ok().filter(|meta| meta.is_file()).map(|meta| meta.len());
+    info.physical_size = info.segments.as_ref()
+        .map(|segments| segments.total_size)
+        .filter(|size| *size > 0)
+        .or_else(on_disk);
+    info.logical_size = if let Some(ad1) = &info.ad1 {
+        ad1.logical_size
+    } else if let Some(ewf) = info.e01.as_ref().or(info.l01.as_ref()) {
+        // Lx01 info doesn't read the media size
+        Some(ewf.sector_count * ewf.bytes_per_sector as u64).filter(|size| *size > 0)
+    } else if let Some(raw) = &info.raw {
+        Some(raw.logical_size)
+    } else if let Some(archive) = &info.archive {
+        archive.uncompressed_size
+    } else {
+        info.iso.as_ref().map(|iso| iso.image_size)
+    };
+    info.compression_ratio = match (info.logical_size, info.physical_size) {
+        (Some(logical), Some(physical)) if physical > 0 => Some(logical as f64 / physical as f64),
+        _ => None,
+    };
+    info
+}
+
+/// Label for EWF physical images - SMART images share the E01 code path
+fn e01_container_label(info: &ewf::EwfInfo) -> String {
+    if info.format_version == ewf::SMART_FORMAT_VERSION {
+        "SMART".to_string()
+    } else {
+        "E01".to_string()
+    }
+}
+
+/// Label for optical images - discs without an ISO 9660 tree are plain UDF
+fn iso_container_label(info: &iso::IsoInfo) -> String {
+    if info.has_iso9660 {
+        "ISO 9660".to_string()
+    } else {
+        "UDF".to_string()
     }
 }
 
 /// Full info - reads headers and optionally parses item trees
 pub fn info(path: &str, include_tree: bool) -> Result<ContainerInfo, String> {
+    info_with_tree_limit(path, include_tree, ad1::DEFAULT_TREE_LIMIT)
+}
+
+/// Full info, keeping at most `tree_limit` AD1 tree entries
+/// (`ad1.tree_truncated` is set when the container holds more)
+pub fn info_with_tree_limit(path: &str, include_tree: bool, tree_limit: usize) -> Result<ContainerInfo, String> {
+    info_with_options(path, include_tree, tree_limit, false)
+}
+
+/// Full info; with `best_effort`, AD1 item chains and E01/L01 section
+/// chains that break off mid-way return what was readable, listing the
+/// damage in the format's `parse_warnings`
+pub fn info_with_options(path: &str, include_tree: bool, tree_limit: usize, best_effort: bool) -> Result<ContainerInfo, String> {
     // Audit log: evidence container access (full info)
     log_evidence_access("info", Path::new(path), None, None);
     
     let kind = detect_container(path)?;
     let companion_log = find_companion_log(path);
     
-    match kind {
+    let info = match kind {
         ContainerKind::Ad1 => {
-            let info = ad1::info(path, include_tree)?;
+            let info = ad1::info_with_options(path, include_tree, tree_limit, best_effort)?;
+            let segment_count = info.segment.segment_number;
             Ok(ContainerInfo {
                 container: "AD1".to_string(),
                 ad1: Some(info),
@@ -135,27 +299,41 @@ pub fn info(path: &str, include_tree: bool) -> Result<ContainerInfo, String> {
                 raw: None,
                 archive: None,
                 ufed: None,
+                iso: None,
                 note: None,
                 companion_log,
+                segments: segment_set_info(path, Some(segment_count)),
+                manifest: find_companion_manifest(path),
+                triage: None,
+                physical_size: None,
+                logical_size: None,
+                compression_ratio: None,
             })
         }
         ContainerKind::E01 => {
-            let info = ewf::info(path)?;
+            let info = ewf::info_with_options(path, best_effort)?;
             Ok(ContainerInfo {
-                container: "E01".to_string(),
+                container: e01_container_label(&info),
                 ad1: None,
                 e01: Some(info),
                 l01: None,
                 raw: None,
                 archive: None,
                 ufed: None,
+                iso: None,
                 note: None,
                 companion_log,
+                segments: segment_set_info(path, None),
+                manifest: None,
+                triage: None,
+                physical_size: None,
+                logical_size: None,
+                compression_ratio: None,
             })
         }
         ContainerKind::L01 => {
             // L01 uses the same EWF format as E01 (logical evidence vs physical)
-            let info = ewf::info(path)?;
+            let info = ewf::info_with_options(path, best_effort)?;
             Ok(ContainerInfo {
                 container: "L01".to_string(),
                 ad1: None,
@@ -164,12 +342,42 @@ pub fn info(path: &str, include_tree: bool) -> Result<ContainerInfo, String> {
                 raw: None,
                 archive: None,
                 ufed: None,
+                iso: None,
                 note: None,
                 companion_log,
+                segments: segment_set_info(path, None),
+                manifest: find_companion_manifest(path),
+                triage: None,
+                physical_size: None,
+                logical_size: None,
+                compression_ratio: None,
+            })
+        }
+        ContainerKind::Lx01 => {
+            // EWF2 sections need their own parser; only metadata is read for now
+            let info = ewf::lx01_info(path)?;
+            Ok(ContainerInfo {
+                container: "Lx01".to_string(),
+                ad1: None,
+                e01: None,
+                l01: Some(info),
+                raw: None,
+                archive: None,
+                ufed: None,
+                iso: None,
+                note: None,
+                companion_log,
+                segments: segment_set_info(path, None),
+                manifest: find_companion_manifest(path),
+                triage: None,
+                physical_size: None,
+                logical_size: None,
+                compression_ratio: None,
             })
         }
         ContainerKind::Raw => {
             let info = raw::info(path)?;
+            let note = info.sparse.as_ref().map(|_| SPARSE_HASH_NOTE.to_string());
             Ok(ContainerInfo {
                 container: "RAW".to_string(),
                 ad1: None,
@@ -178,8 +386,36 @@ pub fn info(path: &str, include_tree: bool) -> Result<ContainerInfo, String> {
                 raw: Some(info),
                 archive: None,
                 ufed: None,
+                iso: None,
+                note,
+                companion_log,
+                segments: segment_set_info(path, None),
+                manifest: None,
+                triage: None,
+                physical_size: None,
+                logical_size: None,
+                compression_ratio: None,
+            })
+        }
+        ContainerKind::Iso => {
+            let info = iso::info(path)?;
+            Ok(ContainerInfo {
+                container: iso_container_label(&info),
+                ad1: None,
+                e01: None,
+                l01: None,
+                raw: None,
+                archive: None,
+                ufed: None,
+                iso: Some(info),
                 note: None,
                 companion_log,
+                segments: segment_set_info(path, None),
+                manifest: None,
+                triage: None,
+                physical_size: None,
+                logical_size: None,
+                compression_ratio: None,
             })
         }
         ContainerKind::Archive => {
@@ -192,8 +428,15 @@ pub fn info(path: &str, include_tree: bool) -> Result<ContainerInfo, String> {
                 raw: None,
                 archive: Some(info),
                 ufed: None,
+                iso: None,
                 note: None,
                 companion_log,
+                segments: segment_set_info(path, None),
+                manifest: None,
+                triage: None,
+                physical_size: None,
+                logical_size: None,
+                compression_ratio: None,
             })
         }
         ContainerKind::Ufed => {
@@ -206,130 +449,470 @@ pub fn info(path: &str, include_tree: bool) -> Result<ContainerInfo, String> {
                 raw: None,
                 archive: None,
                 ufed: Some(info),
+                iso: None,
                 note: None,
                 companion_log,
+                segments: None,
+                manifest: None,
+                triage: None,
+                physical_size: None,
+                logical_size: None,
+                compression_ratio: None,
             })
         }
-    }
+    };
+    info.map(|info| with_sizes(info, path))
 }
 
 /// Verify container integrity using the specified hash algorithm
 pub fn verify(path: &str, algorithm: &str) -> Result<Vec<VerifyEntry>, String> {
+    verify_with_options(path, algorithm, &VerifyOptions::default())
+}
+
+/// Verify container integrity; AD1 files whose data cannot be read are
+/// reported as "corrupt"/"missing" entries unless `options.fail_fast` makes
+/// the first one an error. An unreadable E01/L01 chunk is an error unless
+/// `options.best_effort` hashes it as zeros and reports a partial image hash.
+pub fn verify_with_options(path: &str, algorithm: &str, options: &VerifyOptions) -> Result<Vec<VerifyEntry>, String> {
     // Audit log: verification operation
     log_evidence_access("verify", Path::new(path), Some(algorithm), None);
     
     match detect_container(path)? {
-        ContainerKind::Ad1 => {
-            let ad1_results = ad1::verify(path, algorithm)?;
-            Ok(ad1_results.into_iter().map(|entry| VerifyEntry {
-                path: Some(entry.path),
-                chunk_index: None,
-                status: entry.status,
-                message: None,
-            }).collect())
-        }
-        ContainerKind::E01 => {
-            let ewf_results = ewf::verify_chunks(path, algorithm)?;
-            Ok(ewf_results.into_iter().map(|entry| VerifyEntry {
-                path: None,
-                chunk_index: Some(entry.chunk_index),
-                status: entry.status,
-                message: entry.message,
-            }).collect())
-        }
-        ContainerKind::L01 => {
-            // L01 uses the same EWF format - use ewf::verify_chunks
-            let ewf_results = ewf::verify_chunks(path, algorithm)?;
-            Ok(ewf_results.into_iter().map(|entry| VerifyEntry {
-                path: None,
-                chunk_index: Some(entry.chunk_index),
-                status: entry.status,
-                message: entry.message,
-            }).collect())
-        }
+        ContainerKind::Ad1 => Ok(ad1::verify_report(path, algorithm, options, |_, _| {})?.entries),
+        // L01 uses the same EWF format
+        ContainerKind::E01 | ContainerKind::L01 => ewf::verify_chunks(path, algorithm, options.best_effort),
+        ContainerKind::Lx01 => Err("Lx01 verification is not implemented yet (EWF2 chunk tables are not parsed).".to_string()),
         ContainerKind::Raw => {
+            let started = Instant::now();
             let computed_hash = raw::verify(path, algorithm)?;
-            Ok(vec![VerifyEntry {
-                path: None,
-                chunk_index: None,
-                status: "computed".to_string(),
-                message: Some(format!("{}: {}", algorithm.to_uppercase(), computed_hash)),
-            }])
+            if !raw::is_sparse(path) {
+                return Ok(vec![computed_image_entry(None, algorithm, &computed_hash, started)]);
+            }
+            // Sparse images get both: only the logical hash matches a dd image
+            let started_logical = Instant::now();
+            let logical_hash = raw::verify_logical_with_progress(path, algorithm, |_, _| {})?;
+            Ok(vec![
+                computed_image_entry(Some("Container"), algorithm, &computed_hash, started),
+                computed_image_entry(Some("Logical image"), algorithm, &logical_hash, started_logical),
+            ])
+        }
+        ContainerKind::Iso => {
+            // Optical images are verified as one whole-file hash, like raw images
+            let started = Instant::now();
+            let computed_hash = raw::verify(path, algorithm)?;
+            Ok(vec![computed_image_entry(None, algorithm, &computed_hash, started)])
         }
         ContainerKind::Archive => Err("Archive verification is not implemented yet. Use standard archive tools.".to_string()),
         ContainerKind::Ufed => Err("UFED verification is not implemented yet.".to_string()),
     }
 }
 
-/// Extract container contents to the specified output directory
-pub fn extract(path: &str, output_dir: &str) -> Result<(), String> {
-    // Audit log: extraction operation (sensitive - exports evidence)
-    // Note: bytes_exported is 0 here as we don't know total size yet
-    log_data_export(
-        Path::new(path),
-        Path::new(output_dir),
-        0, // Size determined during extraction
+/// Whole-image hash with nothing stored to compare it against
+fn computed_image_entry(label: Option<&str>, algorithm: &str, hash: &str, started: Instant) -> VerifyEntry {
+    let algorithm = algorithm.to_uppercase();
+    let message = match label {
+        Some(label) => format!("{} {}: {}", label, algorithm, hash),
+        None => format!("{}: {}", algorithm, hash),
+    };
+    VerifyEntry::new(VerifyTarget::WholeImage { label: label.map(str::to_string) }, VerifyStatus::Skipped)
+        .reason(VerifyReason::NoExpectedHash)
+        .hashes(&algorithm, None, Some(hash))
+        .message(message)
+        .duration(started.elapsed())
+}
+
+/// Verify the files of a container against an exported file manifest
+///
+/// Per-file hashes are computed with MD5 if the manifest lists MD5 values,
+/// SHA1 otherwise. Entries report "ok", "nok", "computed" (no manifest hash
+/// for that algorithm), "missing_from_container" or "missing_from_manifest".
+pub fn verify_against_manifest(container_path: &str, manifest_path: &str) -> Result<Vec<VerifyEntry>, String> {
+    let manifest = parse_manifest(Path::new(manifest_path))?;
+    let algorithm = if manifest.iter().any(|entry| entry.md5.is_some()) { "md5" } else { "sha1" };
+
+    // Audit log: verification operation
+    log_evidence_access("verify_manifest", Path::new(container_path), Some(algorithm), None);
+
+    let computed = match detect_container(container_path)? {
+        // Unreadable files have no hash to compare, so they stop the comparison
+        // instead of showing up as missing from the container
+        ContainerKind::Ad1 => ad1::verify_report(container_path, algorithm, &VerifyOptions { fail_fast: true, ..Default::default() }, |_, _| {})?.entries,
+        ContainerKind::L01 | ContainerKind::Lx01 => return Err("L01 manifest verification is not implemented yet (no per-file hashing for L01).".to_string()),
+        _ => return Err("Manifest verification needs a logical container (AD1).".to_string()),
+    };
+    let results = compare_manifest(
+        &manifest,
+        algorithm,
+        computed.into_iter().filter_map(|entry| Some((entry.target_path()?.to_string(), entry.computed?.to_string()))),
     );
-    
-    match detect_container(path)? {
-        ContainerKind::Ad1 => ad1::extract(path, output_dir),
-        ContainerKind::E01 => ewf::extract(path, output_dir),
-        ContainerKind::L01 => ewf::extract(path, output_dir),  // L01 uses same EWF extraction
-        ContainerKind::Raw => raw::extract(path, output_dir),
-        ContainerKind::Archive => Err("Archive extraction is not implemented yet. Use standard archive tools (7z, unzip).".to_string()),
-        ContainerKind::Ufed => Err("UFED extraction is not implemented yet. The UFED container is typically already extracted.".to_string()),
-    }
+    debug!(
+        container_path,
+        manifest_path,
+        entries = results.len(),
+        failed = results.iter().filter(|entry| entry.status != VerifyStatus::Ok).count(),
+        "Manifest verification complete"
+    );
+    Ok(results)
 }
 
-/// Detect the container type from the file path and magic bytes
-pub(crate) fn detect_container(path: &str) -> Result<ContainerKind, String> {
-    let path_obj = Path::new(path);
-    if !path_obj.exists() {
-        return Err(format!("Input file not found: {path}"));
+/// Extract container contents to the specified output directory
+pub fn extract(path: &str, output_dir: &str) -> Result<ExtractResult, String> {
+    extract_with_options(path, output_dir, &ExtractOptions::default())
+}
+
+/// Extract container contents with explicit safety options
+pub fn extract_with_options(path: &str, output_dir: &str, options: &ExtractOptions) -> Result<ExtractResult, String> {
+    extract_with_progress(path, output_dir, options, |_| {})
+}
+
+/// Extract with progress in files and bytes (AD1 only; images report nothing)
+pub fn extract_with_progress<F>(path: &str, output_dir: &str, options: &ExtractOptions, mut progress: F) -> Result<ExtractResult, String>
+where
+    F: FnMut(ExtractProgress),
+{
+    let started = std::time::Instant::now();
+    let kind = detect_container(path)?;
+    let warnings = check_extract_output(kind, path, output_dir, options)?;
+    
+    // Held until extraction returns; a dry run writes nothing
+    let _access = if options.dry_run {
+        None
+    } else {
+        let access = super::access::registry();
+        Some((access.read(path)?, access.write(output_dir, "extraction")?))
+    };
+    
+    if !options.dry_run {
+        // Audit log: extraction operation (sensitive - exports evidence)
+        // Note: bytes_exported is 0 here as we don't know total size yet
+        log_data_export(
+            Path::new(path),
+            Path::new(output_dir),
+            0, // Size determined during extraction
+        );
     }
 
-    let lower = path.to_lowercase();
+    // Partial files this old were left by an earlier run that crashed
+    let cleanup = if options.dry_run {
+        PartialCleanup::default()
+    } else {
+        clean_stale_partials(Path::new(output_dir), STALE_PARTIAL_AGE)
+    };
+    
+    let filter = options.filter.clone().unwrap_or_default();
+    // Seeded from the manifests of earlier extractions into the same folder
+    let dedupe = (options.dedupe && kind == ContainerKind::Ad1 && !options.dry_run)
+        .then(|| DedupeIndex::load(Path::new(output_dir), options.dedupe_strategy));
+    let (stats, files) = match kind {
+        ContainerKind::Ad1 => {
+            let mut stats = if options.dry_run {
+                ad1::plan_extraction(path, output_dir, &filter, options.native_streams)?
+            } else {
+                let workers = options.workers.unwrap_or(ad1::DEFAULT_EXTRACT_WORKERS);
+                ad1::extract_parallel(path, output_dir, &filter, workers, options.best_effort, options.native_streams, dedupe.as_ref(), &mut progress)?
+            };
+            let files = std::mem::take(&mut stats.files);
+            (Some(stats), files)
+        }
+        ContainerKind::E01 | ContainerKind::Raw => {
+            (None, vec![extract_image_files(kind, path, output_dir, &filter, options, &mut progress)?])
+        }
+        ContainerKind::L01 => return Err("L01 extraction is not implemented yet (file entries in the ltree section are not parsed).".to_string()),
+        ContainerKind::Lx01 => return Err("Lx01 extraction is not implemented yet (EWF2 chunk tables are not parsed).".to_string()),
+        ContainerKind::Iso => return Err("ISO file extraction is not implemented yet. Mount the image or use standard tools (7z, xorriso).".to_string()),
+        ContainerKind::Archive => return Err("Archive extraction is not implemented yet. Use standard archive tools (7z, unzip).".to_string()),
+        ContainerKind::Ufed => return Err("UFED extraction is not implemented yet. The UFED container is typically already extracted.".to_string()),
+    };
     
-    // Check Cellebrite UFED formats first (UFD, UFDR, UFDX)
-    if ufed::is_ufed(path) {
-        return Ok(ContainerKind::Ufed);
+    let mut result = summarize_extraction(output_dir, warnings, stats, &files, options.dry_run);
+    if !cleanup.failed.is_empty() {
+        result.warnings.push(format!(
+            "{} stale partial file(s) from an earlier interrupted run could not be removed: {}",
+            cleanup.failed.len(),
+            cleanup.failed.join(", ")
+        ));
+    }
+    result.cleaned_partials = cleanup.removed;
+    if options.dedupe && kind != ContainerKind::Ad1 {
+        result.warnings.push("Deduplication applies to AD1 extraction only; every file was written".to_string());
+    }
+    let unreadable = result.stats.as_ref().map_or(0, |stats| stats.unreadable.len());
+    if unreadable > 0 {
+        result.warnings.push(format!(
+            "{} file(s) could not be read (corrupt or missing data) and were not extracted; see stats.unreadable",
+            unreadable
+        ));
+    }
+    if options.dry_run {
+        debug!(
+            path,
+            files = result.file_count,
+            bytes = result.total_bytes,
+            collisions = result.collisions.len(),
+            shortfall_bytes = result.shortfall_bytes,
+            "Extraction dry run complete"
+        );
+        return Ok(result);
     }
     
-    // Check E01/EWF first (before L01 to avoid .lx01 confusion)
-    // Support .e01, .ex01, .e02, .e03, etc., and .ewf extensions
-    if lower.ends_with(".e01") || lower.ends_with(".ex01") || lower.ends_with(".ewf") 
-        || lower.contains(".e0") || lower.contains(".ex")
-    {
-        debug!("Checking E01 signature for: {}", path);
-        if ewf::is_e01(path).unwrap_or(false) {
-            return Ok(ContainerKind::E01);
-        } else {
-            debug!("E01 signature check failed for: {}", path);
+    result.duration_secs = started.elapsed().as_secs_f64();
+    if result.duration_secs > 0.0 {
+        result.bytes_per_sec = result.total_bytes as f64 / result.duration_secs;
+    }
+    debug!(path, bytes = result.total_bytes, bytes_per_sec = result.bytes_per_sec, "Extraction complete");
+
+    let mut manifest = ExtractionManifest::new(path, output_dir, files);
+    if let Some(index) = &dedupe {
+        manifest = manifest.with_dedupe_index(index.entries());
+    }
+    let manifest_path = manifest.write(options.manifest_format)?;
+    debug!(
+        path,
+        files = manifest.files.len(),
+        manifest = %manifest_path.display(),
+        "Extraction manifest written"
+    );
+    result.manifest_path = manifest_path.to_string_lossy().to_string();
+    Ok(result)
+}
+
+/// Build the result shared by real extractions and dry runs from the
+/// written (or planned) files; dry runs also check for existing output
+/// files and free space
+fn summarize_extraction(
+    output_dir: &str,
+    warnings: Vec<String>,
+    stats: Option<ExtractStats>,
+    files: &[ExtractedFile],
+    dry_run: bool,
+) -> ExtractResult {
+    let total_bytes = files.iter().map(|file| file.size).sum();
+    let longest_output_path = files.iter()
+        .map(|file| &file.output_path)
+        .max_by_key(|path| path.chars().count())
+        .cloned();
+    let sanitized_entries = files.iter()
+        .filter(|file| !file.path_changes.is_empty())
+        .map(|file| SanitizedEntry {
+            internal_path: file.internal_path.clone(),
+            output_path: file.output_path.clone(),
+            changes: file.path_changes.clone(),
+        })
+        .collect();
+    let (collisions, free_bytes) = if dry_run {
+        let collisions = files.iter()
+            .filter(|file| Path::new(&file.output_path).exists())
+            .map(|file| file.output_path.clone())
+            .collect();
+        (collisions, available_space(Path::new(output_dir)))
+    } else {
+        (Vec::new(), None)
+    };
+    
+    ExtractResult {
+        output_dir: output_dir.to_string(),
+        dry_run,
+        warnings,
+        stats,
+        manifest_path: String::new(),
+        file_count: files.len(),
+        total_bytes,
+        longest_output_path,
+        sanitized_entries,
+        collisions,
+        insufficient_space: free_bytes.is_some_and(|free| total_bytes > free),
+        shortfall_bytes: free_bytes.map(|free| total_bytes.saturating_sub(free)).filter(|&missing| missing > 0),
+        free_bytes,
+        cleaned_partials: Vec::new(),
+        duration_secs: 0.0,
+        bytes_per_sec: 0.0,
+    }
+}
+
+/// File-level extraction for disk images
+///
+/// E01/raw extraction writes the whole media stream; there is no parsed
+/// file listing to filter yet. Until there is, a non-empty filter is refused
+/// instead of silently exporting the entire image.
+fn extract_image_files<F>(
+    kind: ContainerKind,
+    path: &str,
+    output_dir: &str,
+    filter: &ExtractFilter,
+    options: &ExtractOptions,
+    progress: &mut F,
+) -> Result<ExtractedFile, String>
+where
+    F: FnMut(ExtractProgress),
+{
+    let dry_run = options.dry_run;
+    if !filter.is_empty() {
+        return Err("File filters need a file listing and are only supported for AD1 containers - E01/raw images can only be extracted whole".to_string());
+    }
+    match kind {
+        ContainerKind::E01 if dry_run => ewf::plan_extract(path, output_dir),
+        ContainerKind::E01 => {
+            let in_flight = options.in_flight_chunks.unwrap_or(ewf::DEFAULT_EXTRACT_IN_FLIGHT_CHUNKS);
+            ewf::extract_with_progress(path, output_dir, in_flight, |bytes_done, bytes_total| {
+                progress(ExtractProgress { files_done: 0, files_total: 1, bytes_done, bytes_total });
+            })
         }
+        _ => {
+            // raw::extract takes the output file, not a directory
+            let stem = Path::new(path).file_stem()
+                .map(|s| s.to_string_lossy().to_string())
+                .unwrap_or_else(|| "image".to_string());
+            let (file_name, _) = sanitize_component(&format!("{}.raw", stem));
+            let output_path = Path::new(output_dir).join(file_name);
+            if dry_run {
+                let image_name = Path::new(path).file_name()
+                    .map(|s| s.to_string_lossy().to_string())
+                    .unwrap_or_default();
+                return Ok(ExtractedFile::planned(&image_name, &output_path, raw::info(path)?.logical_size));
+            }
+            raw::extract(path, &output_path.to_string_lossy())
+        }
+    }
+}
+
+/// Refuse output inside the evidence folder; warn (or fail) on a shared volume
+fn check_extract_output(
+    kind: ContainerKind,
+    path: &str,
+    output_dir: &str,
+    options: &ExtractOptions,
+) -> Result<Vec<String>, String> {
+    // All segments of a set normally sit together, but check each one
+    let mut inputs = match kind {
+        ContainerKind::E01 | ContainerKind::L01 => ewf::get_segment_paths(path).unwrap_or_default(),
+        ContainerKind::Lx01 => ewf::lx01_segment_paths(path),
+        ContainerKind::Raw => raw::get_segment_paths(path).unwrap_or_default(),
+        _ => Vec::new(),
+    };
+    if inputs.is_empty() {
+        inputs.push(PathBuf::from(path));
     }
     
-    // Check L01/Lx01 - use ewf::is_l01_file for proper LVF signature detection
-    if (lower.ends_with(".l01") || lower.ends_with(".lx01") 
-        || lower.contains(".l0") || lower.contains(".lx"))
-        && ewf::is_l01_file(path).unwrap_or(false) 
-    {
-        return Ok(ContainerKind::L01);
+    let check = check_output_location(&inputs, Path::new(output_dir))?;
+    
+    let mut warnings = Vec::new();
+    if check.same_volume {
+        let message = format!("Output directory {output_dir} is on the same volume as the evidence");
+        if options.fail_on_same_volume {
+            return Err(message);
+        }
+        warn!("{}", message);
+        warnings.push(message);
     }
+    Ok(warnings)
+}
 
-    // Check AD1
-    if ad1::is_ad1(path)? {
-        return Ok(ContainerKind::Ad1);
+/// Detect the container type from the file path and magic bytes
+///
+/// The first match of [`identify`]; see its report for why.
+pub(crate) fn detect_container(path: &str) -> Result<ContainerKind, String> {
+    let report = identify(path)?;
+    if let Some(kind) = report.detected {
+        return Ok(kind);
+    }
+    if report.file_size == 0 {
+        return Err(format!("File is empty: {path}"));
     }
+    Err(format!("Unsupported or unrecognized logical container: {}\nSupported formats: AD1, E01/EWF, L01/Lx01, RAW (.dd, .raw, .img, .001), ISO 9660/UDF (.iso), Archives (7z, ZIP, RAR), UFED (UFD, UFDR, UFDX)", path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    /// Single-segment E01 of `chunks` deflated chunks of 64 zeroed sectors
+    fn build_zeroed_e01(chunks: u32) -> Vec<u8> {
+        fn section(image: &mut Vec<u8>, kind: &str, data: &[u8]) {
+            let start = image.len() as u64;
+            let size = 76 + data.len() as u64;
+            let next = if kind == "done" { start } else { start + size };
+            let mut descriptor = [0u8; 76];
+            descriptor[..kind.len()].copy_from_slice(kind.as_bytes());
+            descriptor[16..24].copy_from_slice(&next.to_le_bytes());
+            descriptor[24..32].copy_from_slice(&size.to_le_bytes());
+            image.extend_from_slice(&descriptor);
+            image.extend_from_slice(data);
+        }
+
+        let mut image = b"EVF\x09\x0d\x0a\xff\x00\x01\x01\x00\x00\x00".to_vec();
+        let mut volume = vec![0u8; 1052];
+        volume[0] = 0x01; // fixed disk
+        volume[4..8].copy_from_slice(&chunks.to_le_bytes());
+        volume[8..12].copy_from_slice(&64u32.to_le_bytes());
+        volume[12..16].copy_from_slice(&512u32.to_le_bytes());
+        volume[16..24].copy_from_slice(&(chunks as u64 * 64).to_le_bytes());
+        section(&mut image, "volume", &volume);
+
+        let sectors_start = image.len() as u32 + 76;
+        let mut sectors = Vec::new();
+        let mut offsets = Vec::new();
+        for _ in 0..chunks {
+            offsets.push((sectors_start + sectors.len() as u32) | 0x8000_0000);
+            let mut encoder = flate2::write::ZlibEncoder::new(&mut sectors, flate2::Compression::default());
+            encoder.write_all(&[0u8; 64 * 512]).unwrap();
+            encoder.finish().unwrap();
+        }
+        section(&mut image, "sectors", &sectors);
 
-    // Check archive formats (7z, ZIP, RAR, etc.) - before raw to catch .7z.001 properly
-    if archive::is_archive(path).unwrap_or(false) {
-        return Ok(ContainerKind::Archive);
+        let mut table = vec![0u8; 24];
+        table[0..4].copy_from_slice(&chunks.to_le_bytes());
+        for offset in offsets {
+            table.extend_from_slice(&offset.to_le_bytes());
+        }
+        table.extend_from_slice(&[0u8; 4]);
+        section(&mut image, "table", &table);
+        section(&mut image, "done", &[]);
+        image
     }
 
-    // Check raw disk images (.dd, .raw, .img, .001, .002, etc.)
-    if raw::is_raw(path).unwrap_or(false) {
-        return Ok(ContainerKind::Raw);
+    #[test]
+    fn test_compressed_e01_reports_logical_and_physical_size() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("disk.E01");
+        let image = build_zeroed_e01(16);
+        std::fs::write(&path, &image).unwrap();
+        let path = path.to_str().unwrap();
+
+        for info in [info_fast(path).unwrap(), info(path, false).unwrap()] {
+            assert_eq!(info.physical_size, Some(image.len() as u64));
+            assert_eq!(info.logical_size, Some(16 * 64 * 512));
+            let ratio = info.compression_ratio.unwrap();
+            assert!(ratio > 10.0, "zeroed chunks should compress well, got {ratio}");
+        }
+
+        // A raw image is its own logical image
+        let raw = dir.path().join("disk.dd");
+        std::fs::write(&raw, vec![7u8; 4096]).unwrap();
+        let info = info_fast(raw.to_str().unwrap()).unwrap();
+        assert_eq!((info.physical_size, info.logical_size, info.compression_ratio), (Some(4096), Some(4096), Some(1.0)));
     }
 
-    Err(format!("Unsupported or unrecognized logical container: {}\nSupported formats: AD1, E01/EWF, L01, RAW (.dd, .raw, .img, .001), Archives (7z, ZIP, RAR), UFED (UFD, UFDR, UFDX)", path))
+    #[test]
+    fn test_raw_verify_reports_computed_whole_image() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("disk.dd");
+        let data: Vec<u8> = (0..64 * 1024u32).map(|i| (i % 251) as u8 + 1).collect();
+        std::fs::write(&path, &data).unwrap();
+
+        let entries = verify(path.to_str().unwrap(), "sha256").unwrap();
+        assert_eq!(entries.len(), 1);
+        let image = &entries[0];
+        assert_eq!(image.target, VerifyTarget::WholeImage { label: None });
+        assert_eq!((image.status, image.reason), (VerifyStatus::Skipped, Some(VerifyReason::NoExpectedHash)));
+        assert_eq!(image.legacy_status(), "computed");
+        let expected = crate::common::hash::compute_hash_str(&data, "sha256").unwrap();
+        assert_eq!(image.computed.as_ref().map(ToString::to_string), Some(expected.clone()));
+
+        // Callers that don't pass a version still get the old shape
+        let legacy = serde_json::to_value(crate::containers::VerifyEntries::versioned(entries, None)).unwrap();
+        assert_eq!(legacy[0]["status"], "computed");
+        assert_eq!(legacy[0]["message"], format!("SHA256: {}", expected));
+        assert!(legacy[0]["path"].is_null() && legacy[0]["chunk_index"].is_null());
+    }
 }
@@ -0,0 +1,85 @@
+//! Quick structural check of a container before committing to a full hash
+//!
+//! Runs the format's header-only check (`ewf::quick_check`,
+//! `ad1::quick_check`, `raw::quick_check`) and wraps its results with the
+//! path and timing. A passing report means the container is structurally
+//! intact - every header, section chain and segment is where it should be -
+//! not that its data hashes correctly.
+
+use serde::Serialize;
+use std::path::Path;
+use std::time::Instant;
+use tracing::debug;
+
+use crate::ad1;
+use crate::common::audit::log_evidence_access;
+use crate::common::quick_check::StructureCheck;
+use crate::ewf;
+use crate::raw;
+
+use super::operations::detect_container;
+use super::types::ContainerKind;
+
+/// Result of [`quick_check`]
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuickCheckReport {
+    pub path: String,
+    /// Format id as in `get_supported_formats`, e.g. "e01"
+    pub format: String,
+    /// Every check passed
+    pub passed: bool,
+    pub checks: Vec<StructureCheck>,
+    pub elapsed_ms: u64,
+}
+
+/// Check the headers and segment layout of the container at `path` without
+/// reading its data
+///
+/// E01/s01/L01: section chain of every segment, the done section and the
+/// chunk table counts. AD1: segment headers, logical header, first and last
+/// item records. Raw and ISO: segment continuity and sizes.
+pub fn quick_check(path: &str) -> Result<QuickCheckReport, String> {
+    log_evidence_access("quick_check", Path::new(path), None, None);
+    let started = Instant::now();
+    let (format, checks) = match detect_container(path)? {
+        ContainerKind::Ad1 => ("ad1", ad1::quick_check(path)?),
+        ContainerKind::E01 => ("e01", ewf::quick_check(path)?),
+        ContainerKind::L01 => ("l01", ewf::quick_check(path)?),
+        ContainerKind::Raw => ("raw", raw::quick_check(path)?),
+        ContainerKind::Iso => ("iso", raw::quick_check(path)?),
+        ContainerKind::Lx01 => return Err("Quick check is not available for Lx01 containers (EWF2 sections are not walked)".to_string()),
+        ContainerKind::Archive => return Err("Quick check is not available for archives. Use standard archive tools to test them.".to_string()),
+        ContainerKind::Ufed => return Err("Quick check is not available for UFED extractions.".to_string()),
+    };
+    let report = QuickCheckReport {
+        path: path.to_string(),
+        format: format.to_string(),
+        passed: checks.iter().all(|check| check.passed),
+        checks,
+        elapsed_ms: started.elapsed().as_millis() as u64,
+    };
+    debug!(path, format, passed = report.passed, elapsed_ms = report.elapsed_ms, "Quick check finished");
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_dispatches_by_format() {
+        let dir = tempfile::tempdir().unwrap();
+        let image = dir.path().join("disk.dd");
+        std::fs::write(&image, vec![0u8; 4096]).unwrap();
+        let report = quick_check(image.to_str().unwrap()).unwrap();
+        assert_eq!(report.format, "raw");
+        assert!(report.passed, "{:?}", report.checks);
+        assert_eq!(report.checks.len(), 2);
+
+        let zip = dir.path().join("files.zip");
+        std::fs::write(&zip, [b"PK\x03\x04".as_slice(), &[0u8; 60]].concat()).unwrap();
+        let error = quick_check(zip.to_str().unwrap()).unwrap_err();
+        assert!(error.contains("not available for archives"), "{error}");
+    }
+}
@@ -2,33 +2,146 @@
 //!
 //! This module provides functions for discovering forensic container files
 //! in directories, with support for streaming results and recursive scanning.
+//! Recursive walks list subdirectories on a small pool of workers, which
+//! matters on network shares where every readdir is a round trip.
+//!
+//! Symlinked directories are not followed unless asked for, and no
+//! directory is entered twice - bind mounts, junctions pointing up the tree
+//! and followed symlink cycles would otherwise keep a walk going forever.
+//! Directories skipped that way, or for lying below `max_depth`, are counted
+//! in the [`ScanSummary`].
 
 use std::collections::HashSet;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
 use tracing::debug;
 
-use super::types::DiscoveredFile;
+use super::types::{DiscoveredFile, ScanOptions, ScanSummary};
+use crate::common::segments::{existing_case_variant, EwfNaming, EwfSegmentName};
+use crate::common::time::Timestamp;
+use crate::ufed::collection::find_associated_files;
 use super::segments::{
     is_first_segment, is_numbered_segment, is_archive_segment,
     get_segment_basename, get_first_segment_path_fast,
 };
 
+/// Directory workers used when [`ScanOptions::workers`] is not set
+pub const DEFAULT_SCAN_WORKERS: usize = 4;
+
+/// Upper bound on directory workers - more mostly adds contention on shares
+pub const MAX_SCAN_WORKERS: usize = 8;
+
+/// Container type of a UFED extraction folder reported as one entry
+pub const UFED_EXTRACTION_TYPE: &str = "UFED Extraction";
+
+/// Container type of an E01 set whose first segment is not in the folder
+pub const ORPHANED_E01_TYPE: &str = "EnCase (E01) – incomplete (missing .E01)";
+
+/// Collection index Cellebrite writes next to the extractions it lists
+const UFED_COLLECTION_INDEX: &str = "evidencecollection.ufdx";
+
+/// Extensions of the files a UFED extraction folder is made of
+const UFED_MEMBER_EXTENSIONS: &[&str] = &[".ufd", ".ufdx", ".ufdr", ".zip", ".pdf", ".xml", ".xlsx"];
+
 /// Scan a directory for forensic container files (non-recursive)
 pub fn scan_directory(dir_path: &str) -> Result<Vec<DiscoveredFile>, String> {
-    scan_directory_impl(dir_path, false)
+    scan_directory_with_options(dir_path, false, &ScanOptions::default())
 }
 
 /// Scan a directory recursively for forensic container files
 pub fn scan_directory_recursive(dir_path: &str) -> Result<Vec<DiscoveredFile>, String> {
-    scan_directory_impl(dir_path, true)
+    scan_directory_with_options(dir_path, true, &ScanOptions::default())
 }
 
 /// Streaming scan that calls callback for each file found (for real-time UI updates)
+///
+/// Walks subdirectories in parallel with the default [`ScanOptions`]; see
+/// [`scan_directory_parallel`].
 pub fn scan_directory_streaming<F>(dir_path: &str, recursive: bool, on_file_found: F) -> Result<usize, String>
 where
-    F: Fn(&DiscoveredFile),
+    F: Fn(&DiscoveredFile) + Sync,
 {
+    scan_directory_parallel(dir_path, recursive, &ScanOptions::default(), on_file_found)
+        .map(|summary| summary.count)
+}
+
+/// Streaming scan over a bounded pool of directory workers
+///
+/// Directories are queued on a work-stealing pool. Files of one directory are
+/// reported together and in filename order; directories may be reported in
+/// any order. When `timeout_ms` elapses, directories not yet listed are
+/// skipped and the summary is marked `timed_out`.
+pub fn scan_directory_parallel<F>(
+    dir_path: &str,
+    recursive: bool,
+    options: &ScanOptions,
+    on_file_found: F,
+) -> Result<ScanSummary, String>
+where
+    F: Fn(&DiscoveredFile) + Sync,
+{
+    let root = validate_scan_root(dir_path)?;
+    // An unreadable root fails the scan; unreadable subdirectories are skipped
+    let listing = list_dir(root, recursive, options.follow_symlinks)?;
+
+    let workers = options.workers.unwrap_or(DEFAULT_SCAN_WORKERS).clamp(1, MAX_SCAN_WORKERS);
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(workers)
+        .build()
+        .map_err(|e| format!("Failed to create thread pool: {e}"))?;
+
+    let walk = ParallelWalk {
+        recursive,
+        deadline: options.timeout_ms.map(|ms| Instant::now() + Duration::from_millis(ms)),
+        seen: Mutex::new(HashSet::new()),
+        count: AtomicUsize::new(0),
+        directories: AtomicUsize::new(0),
+        timed_out: AtomicBool::new(false),
+        descent: Descent::new(root, options),
+        on_file_found: &on_file_found,
+    };
+    pool.scope(|scope| walk.process(scope, root.to_path_buf(), listing, 0));
+
+    let (loops_skipped, depth_skipped) = walk.descent.skipped();
+    if loops_skipped > 0 || depth_skipped > 0 {
+        debug!(loops_skipped, depth_skipped, "Directories left out of the scan");
+    }
+    Ok(ScanSummary {
+        count: walk.count.into_inner(),
+        directories: walk.directories.into_inner(),
+        timed_out: walk.timed_out.into_inner(),
+        loops_skipped,
+        depth_skipped,
+    })
+}
+
+/// Collecting scan with explicit depth and symlink options, sorted by path
+pub fn scan_directory_with_options(dir_path: &str, recursive: bool, options: &ScanOptions) -> Result<Vec<DiscoveredFile>, String> {
+    let path = validate_scan_root(dir_path)?;
+
+    let mut discovered = if recursive {
+        let found = Mutex::new(Vec::new());
+        scan_directory_parallel(dir_path, true, options, |file| {
+            found.lock().unwrap_or_else(|e| e.into_inner()).push(file.clone());
+        })?;
+        found.into_inner().unwrap_or_else(|e| e.into_inner())
+    } else {
+        let mut discovered = Vec::new();
+        let descent = Descent::new(path, options);
+        scan_dir_internal(path, &mut HashSet::new(), false, &descent, 0, &mut |file| discovered.push(file))?;
+        discovered
+    };
+
+    // read_dir order is filesystem-dependent - sort so results are deterministic
+    discovered.sort_by(|a, b| a.path.cmp(&b.path));
+
+    Ok(discovered)
+}
+
+fn validate_scan_root(dir_path: &str) -> Result<&Path, String> {
     let path = Path::new(dir_path);
     if !path.exists() {
         return Err(format!("Directory not found: {dir_path}"));
@@ -36,228 +149,177 @@ where
     if !path.is_dir() {
         return Err(format!("Path is not a directory: {dir_path}"));
     }
+    Ok(path)
+}
+
+/// Key of the dedup set: (parent directory, segment basename), so that
+/// identically named segment sets in different folders are reported separately
+type SeenKey = (PathBuf, String);
 
-    let mut seen_basenames = HashSet::new();
-    let mut count = 0;
+/// One directory's entries after the first pass
+struct DirListing {
+    /// (entry, filename, lowercase filename), sorted by filename
+    files: Vec<(fs::DirEntry, String, String)>,
+    /// Lowercase stems of UFD files (to identify UFED extraction sets)
+    ufd_basenames: HashSet<String>,
+    /// Sorted; empty unless the scan is recursive
+    subdirs: Vec<PathBuf>,
+}
 
-    scan_dir_streaming_internal(path, &mut seen_basenames, recursive, &on_file_found, &mut count)?;
+/// Identity of a directory, to notice the walk reaching it a second time
+///
+/// (device, inode) on Unix; elsewhere the canonical path, which resolves
+/// junctions and symlinks to the directory they point at.
+#[cfg(unix)]
+type DirId = (u64, u64);
+#[cfg(not(unix))]
+type DirId = PathBuf;
 
-    Ok(count)
+#[cfg(unix)]
+fn dir_id(path: &Path) -> Option<DirId> {
+    use std::os::unix::fs::MetadataExt;
+    fs::metadata(path).ok().map(|m| (m.dev(), m.ino()))
 }
 
-fn scan_dir_streaming_internal<F>(
-    path: &Path,
-    seen_basenames: &mut HashSet<String>,
-    recursive: bool,
-    on_file_found: &F,
-    count: &mut usize,
-) -> Result<(), String>
-where
-    F: Fn(&DiscoveredFile),
-{
-    let entries = fs::read_dir(path)
-        .map_err(|e| format!("Failed to read directory: {e}"))?;
+#[cfg(not(unix))]
+fn dir_id(path: &Path) -> Option<DirId> {
+    fs::canonicalize(path).ok()
+}
 
-    // First pass: collect all entries and find UFD files (to identify UFED extraction sets)
-    let mut file_entries = Vec::new();
-    let mut ufd_basenames: HashSet<String> = HashSet::new();
-    let mut ufd_paths: std::collections::HashMap<String, std::path::PathBuf> = std::collections::HashMap::new();
-    let mut subdirs = Vec::new();
-    
-    for entry in entries {
-        let entry = match entry {
-            Ok(e) => e,
-            Err(e) => {
-                tracing::warn!("Failed to read directory entry: {}", e);
-                continue;
-            }
-        };
+/// Loop and depth limits of one walk
+struct Descent {
+    max_depth: Option<usize>,
+    follow_symlinks: bool,
+    visited: Mutex<HashSet<DirId>>,
+    loops_skipped: AtomicUsize,
+    depth_skipped: AtomicUsize,
+}
 
-        let entry_path = entry.path();
-        
-        let file_type = match entry.file_type() {
-            Ok(ft) => ft,
-            Err(e) => {
-                tracing::warn!("Failed to get file type for {:?}: {}", entry_path, e);
-                continue;
-            }
-        };
-        
-        if file_type.is_dir() {
-            if recursive {
-                subdirs.push(entry_path);
-            }
-            continue;
-        }
-        
-        if !file_type.is_file() {
-            continue;
+impl Descent {
+    fn new(root: &Path, options: &ScanOptions) -> Self {
+        Self {
+            max_depth: options.max_depth,
+            follow_symlinks: options.follow_symlinks,
+            visited: Mutex::new(dir_id(root).into_iter().collect()),
+            loops_skipped: AtomicUsize::new(0),
+            depth_skipped: AtomicUsize::new(0),
         }
+    }
 
-        let filename = entry.file_name().to_string_lossy().to_string();
-        let lower = filename.to_lowercase();
-        
-        // Track UFD files to identify UFED extraction sets
-        if lower.ends_with(".ufd") {
-            // Extract basename without extension
-            if let Some(stem) = Path::new(&filename).file_stem() {
-                let stem_lower = stem.to_string_lossy().to_lowercase();
-                ufd_basenames.insert(stem_lower.clone());
-                ufd_paths.insert(stem_lower, entry_path.clone());
-            }
+    /// Whether to list `path`, a directory `depth` levels below the root
+    fn enter(&self, path: &Path, depth: usize) -> bool {
+        if self.max_depth.is_some_and(|max| depth > max) {
+            self.depth_skipped.fetch_add(1, Ordering::Relaxed);
+            return false;
         }
-        
-        file_entries.push((entry, filename, lower));
+        // Unreadable directories fail in list_dir and are skipped there
+        let Some(id) = dir_id(path) else { return true };
+        if !self.visited.lock().unwrap_or_else(|e| e.into_inner()).insert(id) {
+            debug!("Skipping already visited directory: {}", path.display());
+            self.loops_skipped.fetch_add(1, Ordering::Relaxed);
+            return false;
+        }
+        true
     }
-    
-    // Recurse into subdirectories
-    for subdir in subdirs {
-        let _ = scan_dir_streaming_internal(&subdir, seen_basenames, recursive, on_file_found, count);
+
+    /// (loops_skipped, depth_skipped)
+    fn skipped(&self) -> (usize, usize) {
+        (self.loops_skipped.load(Ordering::Relaxed), self.depth_skipped.load(Ordering::Relaxed))
     }
-    
-    // Second pass: process files
-    // - UFD files are skipped (metadata only, not evidence containers)
-    // - UFDX files are skipped (collection index)
-    // - ZIP files with matching UFD are detected as "UFED" type containers
-    for (entry, filename, lower) in file_entries {
-        let entry_path = entry.path();
-        
-        let path_str = match entry_path.to_str() {
-            Some(s) => s,
-            None => continue,
-        };
+}
 
-        // Skip macOS resource fork files (._filename)
-        if filename.starts_with("._") {
-            continue;
-        }
-        
-        // Skip non-first segments entirely - we only want to show one entry per container
-        if !is_first_segment(&lower) {
-            continue;
-        }
-        
-        // Skip UFDX files - these are collection indexes/pointers, not evidence containers
-        // They point to actual evidence but contain no evidence data themselves
-        if lower.ends_with(".ufdx") {
-            debug!("Skipping UFED collection index: {} (metadata pointer, not evidence)", filename);
-            continue;
-        }
-        
-        // Skip UFD files when they exist alongside matching ZIP (metadata only)
-        if !ufd_basenames.is_empty() && lower.ends_with(".ufd") {
-            debug!("Skipping UFED metadata file: {} (metadata only)", filename);
-            continue;
-        }
-        
-        // Check for forensic container files by extension only (fast, no file I/O)
-        // Special case: ZIP files with sibling UFD are UFED extraction containers
-        let container_type = if lower.ends_with(".zip") {
-            if let Some(stem) = Path::new(&filename).file_stem() {
-                let stem_lower = stem.to_string_lossy().to_lowercase();
-                if ufd_paths.contains_key(&stem_lower) {
-                    Some("UFED")
-                } else {
-                    detect_container_type_by_extension(&lower)
-                }
-            } else {
-                detect_container_type_by_extension(&lower)
-            }
-        } else {
-            detect_container_type_by_extension(&lower)
-        };
+/// Sequential directory walker, used by non-recursive scans
+fn scan_dir_internal<F>(
+    path: &Path,
+    seen: &mut HashSet<SeenKey>,
+    recursive: bool,
+    descent: &Descent,
+    depth: usize,
+    on_file_found: &mut F,
+) -> Result<(), String>
+where
+    F: FnMut(DiscoveredFile),
+{
+    let listing = list_dir(path, recursive, descent.follow_symlinks)?;
 
-        if let Some(ctype) = container_type {
-            // For multi-segment files (like .E01, .001), only show the first segment
-            let basename = get_segment_basename(&filename);
-            if seen_basenames.insert(basename.clone()) {
-                // For numbered segments, construct .001 path without checking existence (fast)
-                let display_path = if is_numbered_segment(&lower) {
-                    get_first_segment_path_fast(path_str)
-                } else {
-                    path_str.to_string()
-                };
-                
-                let display_filename = Path::new(&display_path)
-                    .file_name()
-                    .map(|f| f.to_string_lossy().to_string())
-                    .unwrap_or(filename.clone());
-                
-                // Use DirEntry metadata (cached from readdir syscall) - fast
-                let metadata = entry.metadata().ok();
-                let file_size = metadata.as_ref().map(|m| m.len()).unwrap_or(0);
-                
-                // Extract timestamps from metadata
-                let created = metadata.as_ref()
-                    .and_then(|m| m.created().ok())
-                    .map(|t| {
-                        let dt: chrono::DateTime<chrono::Local> = t.into();
-                        dt.format("%Y-%m-%d %H:%M:%S").to_string()
-                    });
-                let modified = metadata.as_ref()
-                    .and_then(|m| m.modified().ok())
-                    .map(|t| {
-                        let dt: chrono::DateTime<chrono::Local> = t.into();
-                        dt.format("%Y-%m-%d %H:%M:%S").to_string()
-                    });
-                
-                // FAST PATH: Skip segment calculation during scan - it's slow on external drives
-                // Segment details will be calculated on-demand when user selects a file
-                
-                let file = DiscoveredFile {
-                    path: display_path,
-                    filename: display_filename,
-                    container_type: ctype.to_string(),
-                    size: file_size, // Just first segment size - full size calculated on-demand
-                    segment_count: None,
-                    segment_files: None,
-                    segment_sizes: None,
-                    total_segment_size: None,
-                    created,
-                    modified,
-                };
-                
-                on_file_found(&file);
-                *count += 1;
-            } else {
-                debug!("Skipping duplicate basename: {}", filename);
-            }
-        } else {
-            debug!("Skipping file with unrecognized container type: {}", filename);
+    for subdir in &listing.subdirs {
+        if descent.enter(subdir, depth + 1) {
+            let _ = scan_dir_internal(subdir, seen, recursive, descent, depth + 1, on_file_found);
         }
     }
 
+    for file in dir_files(path, listing.files, &listing.ufd_basenames, |key| seen.insert(key)) {
+        on_file_found(file);
+    }
+
     Ok(())
 }
 
-fn scan_directory_impl(dir_path: &str, recursive: bool) -> Result<Vec<DiscoveredFile>, String> {
-    let path = Path::new(dir_path);
-    if !path.exists() {
-        return Err(format!("Directory not found: {dir_path}"));
-    }
-    if !path.is_dir() {
-        return Err(format!("Path is not a directory: {dir_path}"));
+/// Shared state of a parallel walk
+struct ParallelWalk<'a, F> {
+    recursive: bool,
+    deadline: Option<Instant>,
+    seen: Mutex<HashSet<SeenKey>>,
+    count: AtomicUsize,
+    directories: AtomicUsize,
+    timed_out: AtomicBool,
+    descent: Descent,
+    on_file_found: &'a F,
+}
+
+impl<F> ParallelWalk<'_, F>
+where
+    F: Fn(&DiscoveredFile) + Sync,
+{
+    fn should_stop(&self) -> bool {
+        if self.timed_out.load(Ordering::Relaxed) {
+            return true;
+        }
+        if self.deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            self.timed_out.store(true, Ordering::Relaxed);
+            return true;
+        }
+        false
     }
 
-    let mut discovered = Vec::new();
-    let mut seen_basenames = HashSet::new();
+    /// Queue the subdirectories of an already listed directory, then report its files
+    fn process<'s>(&'s self, scope: &rayon::Scope<'s>, path: PathBuf, listing: DirListing, depth: usize) {
+        if self.should_stop() {
+            return;
+        }
+        self.directories.fetch_add(1, Ordering::Relaxed);
 
-    scan_dir_internal(path, &mut discovered, &mut seen_basenames, recursive)?;
+        for subdir in listing.subdirs {
+            scope.spawn(move |scope| {
+                if self.should_stop() || !self.descent.enter(&subdir, depth + 1) {
+                    return;
+                }
+                match list_dir(&subdir, self.recursive, self.descent.follow_symlinks) {
+                    Ok(listing) => self.process(scope, subdir, listing, depth + 1),
+                    Err(e) => debug!("Skipping {}: {}", subdir.display(), e),
+                }
+            });
+        }
 
-    Ok(discovered)
+        let found = dir_files(&path, listing.files, &listing.ufd_basenames, |key| {
+            self.seen.lock().unwrap_or_else(|e| e.into_inner()).insert(key)
+        });
+        for file in &found {
+            (self.on_file_found)(file);
+        }
+        self.count.fetch_add(found.len(), Ordering::Relaxed);
+    }
 }
 
-fn scan_dir_internal(
-    path: &Path,
-    discovered: &mut Vec<DiscoveredFile>,
-    seen_basenames: &mut HashSet<String>,
-    recursive: bool,
-) -> Result<(), String> {
+/// First pass over a directory: collect files, UFD stems and subdirectories
+///
+/// Symlinked directories (and junctions) count as subdirectories only with
+/// `follow_symlinks`.
+fn list_dir(path: &Path, recursive: bool, follow_symlinks: bool) -> Result<DirListing, String> {
     let entries = fs::read_dir(path)
         .map_err(|e| format!("Failed to read directory: {e}"))?;
 
-    // First pass: collect all entries and find UFD files (to identify UFED extraction sets)
-    let mut file_entries = Vec::new();
+    let mut files = Vec::new();
     let mut ufd_basenames: HashSet<String> = HashSet::new();
     let mut subdirs = Vec::new();
 
@@ -280,7 +342,7 @@ fn scan_dir_internal(
             }
         };
         
-        if file_type.is_dir() {
+        if file_type.is_dir() || (follow_symlinks && file_type.is_symlink() && entry_path.is_dir()) {
             if recursive {
                 subdirs.push(entry_path);
             }
@@ -302,19 +364,46 @@ fn scan_dir_internal(
             }
         }
         
-        file_entries.push((entry, filename, lower));
-    }
-    
-    // Recurse into subdirectories
-    for subdir in subdirs {
-        let _ = scan_dir_internal(&subdir, discovered, seen_basenames, recursive);
+        files.push((entry, filename, lower));
     }
 
-    // Second pass: process files
-    // - UFD files are skipped (metadata only, not evidence containers)
-    // - UFDX files are skipped (collection index)
-    // - ZIP files with matching UFD are detected as "UFED" type containers
-    for (entry, filename, lower) in file_entries {
+    // Sorted so streaming order within a directory is stable
+    subdirs.sort();
+    files.sort_by(|a, b| a.1.cmp(&b.1));
+
+    Ok(DirListing { files, ufd_basenames, subdirs })
+}
+
+/// Second pass over a directory: the files to report, in filename order
+///
+/// - A UFED extraction folder is reported once, in place of its UFD (see
+///   [`ufed_extraction`]); its ZIP/PDF/UFDR members are not listed separately
+/// - UFD files are skipped (metadata only, not evidence containers)
+/// - UFDX files are skipped (collection index)
+/// - ZIP files with matching UFD are detected as "UFED" type containers
+/// - An E01 set without its .E01 is reported once, at its lowest segment, as
+///   [`ORPHANED_E01_TYPE`]
+///
+/// `first_of_set` inserts into the dedup set and returns false for a
+/// segment set that was already reported.
+fn dir_files(
+    path: &Path,
+    files: Vec<(fs::DirEntry, String, String)>,
+    ufd_basenames: &HashSet<String>,
+    mut first_of_set: impl FnMut(SeenKey) -> bool,
+) -> Vec<DiscoveredFile> {
+    let ufed = ufed_extraction(&files);
+    let names: HashSet<String> = files.iter().map(|(_, _, lower)| lower.clone()).collect();
+    let mut found = Vec::new();
+    for (entry, filename, lower) in files {
+        if let Some(ufed) = ufed.as_ref().filter(|ufed| ufed.covers(&lower)) {
+            if lower == ufed.anchor {
+                found.push(ufed.entry.clone());
+            } else {
+                debug!("Skipping UFED extraction member: {}", filename);
+            }
+            continue;
+        }
         let entry_path = entry.path();
         
         let path_str = match entry_path.to_str() {
@@ -325,106 +414,242 @@ fn scan_dir_internal(
             }
         };
 
-        // Skip macOS resource fork files (._filename)
-        if filename.starts_with("._") {
+        if let Some(stem) = orphaned_e01_stem(&filename, |sibling| names.contains(&sibling.to_lowercase())) {
+            if first_of_set((path.to_path_buf(), stem)) {
+                let metadata = entry.metadata().ok();
+                found.push(build_discovered_file(path_str, &filename, &lower, ORPHANED_E01_TYPE, metadata.as_ref()));
+            }
             continue;
         }
-        
-        // Skip non-first segments entirely - we only want to show one entry per container
-        if !is_first_segment(&lower) {
+
+        let Some(ctype) = classify_file(&filename, &lower, ufd_basenames) else {
             continue;
-        }
-        
-        // Skip UFDX files - these are collection indexes/pointers, not evidence containers
-        // They point to actual evidence but contain no evidence data themselves
-        if lower.ends_with(".ufdx") {
-            debug!("Skipping UFED collection index: {} (metadata pointer, not evidence)", filename);
+        };
+
+        // For multi-segment files (like .E01, .001), only show the first segment
+        let basename = get_segment_basename(&filename);
+        if !first_of_set((path.to_path_buf(), basename)) {
+            debug!("Skipping duplicate basename: {}", filename);
             continue;
         }
-        
-        // Skip UFD files when they exist alongside matching ZIP (metadata only)
-        if !ufd_basenames.is_empty() && lower.ends_with(".ufd") {
-            debug!("Skipping UFED metadata file: {} (metadata only)", filename);
-            continue;
+
+        // Use DirEntry metadata (cached from readdir syscall) - fast
+        let metadata = entry.metadata().ok();
+        found.push(build_discovered_file(path_str, &filename, &lower, ctype, metadata.as_ref()));
+    }
+    found
+}
+
+/// A UFED extraction folder reported as a single entry
+struct UfedExtraction {
+    /// Lowercase name of the UFD (or collection index) the entry points at
+    anchor: String,
+    entry: DiscoveredFile,
+    /// Lowercase names of the other member files
+    members: HashSet<String>,
+}
+
+impl UfedExtraction {
+    fn covers(&self, lower: &str) -> bool {
+        lower == self.anchor || self.members.contains(lower)
+    }
+}
+
+/// Group a folder holding a UFED extraction (a .ufd or EvidenceCollection.ufdx)
+///
+/// The entry points at the UFD (or the collection index when there is no
+/// UFD) so it opens as a UFED container. Its segment list holds that file and
+/// the UFED pieces `find_associated_files` reports for it - ZIP, UFDR, PDF,
+/// XML/XLSX reports and the collection index - and its size is their sum.
+/// Other files in the folder (e.g. an E01 stored alongside) are still listed
+/// on their own.
+fn ufed_extraction(files: &[(fs::DirEntry, String, String)]) -> Option<UfedExtraction> {
+    let (anchor, filename, lower) = files.iter()
+        .find(|(_, _, lower)| lower.ends_with(".ufd"))
+        .or_else(|| files.iter().find(|(_, _, lower)| lower == UFED_COLLECTION_INDEX))?;
+    let anchor_path = anchor.path();
+    let path_str = anchor_path.to_str()?;
+
+    let members: Vec<_> = find_associated_files(&anchor_path, None).into_iter()
+        .filter(|member| member.file_type != "Other")
+        .collect();
+    let anchor_size = anchor.metadata().map(|m| m.len()).unwrap_or(0);
+    let segment_files: Vec<String> = std::iter::once(filename.clone())
+        .chain(members.iter().map(|member| member.filename.clone()))
+        .collect();
+    let segment_sizes: Vec<u64> = std::iter::once(anchor_size)
+        .chain(members.iter().map(|member| member.size))
+        .collect();
+    let total: u64 = segment_sizes.iter().sum();
+
+    let metadata = anchor.metadata().ok();
+    let mut entry = build_discovered_file(path_str, filename, lower, UFED_EXTRACTION_TYPE, metadata.as_ref());
+    entry.size = total;
+    entry.segment_count = Some(segment_files.len() as u32);
+    entry.segment_files = Some(segment_files);
+    entry.segment_sizes = Some(segment_sizes);
+    entry.total_segment_size = Some(total);
+    debug!(path = path_str, members = members.len(), total, "Grouped UFED extraction folder");
+
+    Some(UfedExtraction {
+        anchor: lower.clone(),
+        entry,
+        members: members.iter().map(|member| member.filename.to_lowercase()).collect(),
+    })
+}
+
+/// Scan-filter a single file outside of a directory walk (used by the watcher)
+///
+/// Applies the same first-segment and container-type rules as the directory
+/// scans. Only the file's metadata is read; no handle is kept open. A file
+/// in a UFED extraction folder resolves to the folder's grouped entry.
+pub fn discover_file(path: &Path) -> Option<DiscoveredFile> {
+    let path_str = path.to_str()?;
+    let filename = path.file_name()?.to_string_lossy().to_string();
+    let lower = filename.to_lowercase();
+
+    // Only UFED pieces can belong to an extraction folder - skip the listing otherwise
+    if UFED_MEMBER_EXTENSIONS.iter().any(|ext| lower.ends_with(ext)) {
+        let ufed = path.parent()
+            .and_then(|dir| list_dir(dir, false, false).ok())
+            .and_then(|listing| ufed_extraction(&listing.files));
+        if let Some(ufed) = ufed.filter(|ufed| ufed.covers(&lower)) {
+            return Some(ufed.entry);
         }
-        
-        // Check for forensic container files by extension only (fast, no file I/O)
-        // Special case: ZIP files with sibling UFD are UFED extraction containers
-        let container_type = if lower.ends_with(".zip") {
-            if let Some(stem) = Path::new(&filename).file_stem() {
-                let stem_lower = stem.to_string_lossy().to_lowercase();
-                if ufd_basenames.contains(&stem_lower) {
-                    Some("UFED")
-                } else {
-                    detect_container_type_by_extension(&lower)
-                }
-            } else {
-                detect_container_type_by_extension(&lower)
-            }
-        } else {
-            detect_container_type_by_extension(&lower)
-        };
+    }
 
-        if let Some(ctype) = container_type {
-            // For multi-segment files (like .E01, .001), only show the first segment
-            let basename = get_segment_basename(&filename);
-            if seen_basenames.insert(basename.clone()) {
-                // For numbered segments, always use the first segment path (.001)
-                let display_path = if is_numbered_segment(&lower) {
-                    get_first_segment_path_fast(path_str)
-                } else {
-                    path_str.to_string()
-                };
-                
-                let display_filename = Path::new(&display_path)
-                    .file_name()
-                    .map(|f| f.to_string_lossy().to_string())
-                    .unwrap_or(filename.clone());
-                
-                // Use DirEntry metadata (cached from readdir syscall) - fast
-                let metadata = entry.metadata().ok();
-                let file_size = metadata.as_ref().map(|m| m.len()).unwrap_or(0);
-                
-                // Extract timestamps from metadata
-                let created = metadata.as_ref()
-                    .and_then(|m| m.created().ok())
-                    .map(|t| {
-                        let dt: chrono::DateTime<chrono::Local> = t.into();
-                        dt.format("%Y-%m-%d %H:%M:%S").to_string()
-                    });
-                let modified = metadata.as_ref()
-                    .and_then(|m| m.modified().ok())
-                    .map(|t| {
-                        let dt: chrono::DateTime<chrono::Local> = t.into();
-                        dt.format("%Y-%m-%d %H:%M:%S").to_string()
-                    });
-                
-                discovered.push(DiscoveredFile {
-                    path: display_path,
-                    filename: display_filename,
-                    container_type: ctype.to_string(),
-                    size: file_size, // Just first segment size - full size calculated on-demand
-                    segment_count: None,
-                    segment_files: None,
-                    segment_sizes: None,
-                    total_segment_size: None,
-                    created,
-                    modified,
-                });
+    let has_sibling = |sibling: &str| existing_case_variant(&path.with_file_name(sibling)).is_some();
+    if orphaned_e01_stem(&filename, has_sibling).is_some() {
+        let metadata = fs::metadata(path).ok();
+        return Some(build_discovered_file(path_str, &filename, &lower, ORPHANED_E01_TYPE, metadata.as_ref()));
+    }
+
+    // The walker knows about sibling UFD files from its first pass; look them up directly here
+    let mut ufd_basenames = HashSet::new();
+    if let Some(stem) = Path::new(&filename).file_stem() {
+        let stem = stem.to_string_lossy().to_string();
+        let has_sibling_ufd = lower.ends_with(".ufd")
+            || (lower.ends_with(".zip")
+                && ["ufd", "UFD"].iter().any(|ext| path.with_file_name(format!("{stem}.{ext}")).is_file()));
+        if has_sibling_ufd {
+            ufd_basenames.insert(stem.to_lowercase());
+        }
+    }
+
+    let ctype = classify_file(&filename, &lower, &ufd_basenames)?;
+    let metadata = fs::metadata(path).ok();
+    Some(build_discovered_file(path_str, &filename, &lower, ctype, metadata.as_ref()))
+}
+
+/// Stem of an E01 set whose first segment is missing, if `filename` is the
+/// lowest segment of that set that exists
+///
+/// Goes by filenames only (`has_sibling` checks another name in the same
+/// folder); opening the set reads the segment number from the header.
+fn orphaned_e01_stem(filename: &str, has_sibling: impl Fn(&str) -> bool) -> Option<String> {
+    let name = EwfSegmentName::parse(filename)
+        .filter(|name| name.naming == EwfNaming::E01 && name.number >= 2)?;
+    if (1..name.number).any(|i| name.sibling(i).is_some_and(|sibling| has_sibling(&sibling))) {
+        return None;
+    }
+    Some(name.stem)
+}
+
+/// Decide whether a file should be listed and with which container type
+///
+/// `ufd_basenames` holds the lowercase stems of UFD files in the same folder.
+fn classify_file(filename: &str, lower: &str, ufd_basenames: &HashSet<String>) -> Option<&'static str> {
+    // Skip macOS resource fork files (._filename)
+    if filename.starts_with("._") {
+        return None;
+    }
+    
+    // Skip non-first segments entirely - we only want to show one entry per container
+    if !is_first_segment(lower) {
+        return None;
+    }
+    
+    // Skip UFDX files - these are collection indexes/pointers, not evidence containers
+    // They point to actual evidence but contain no evidence data themselves
+    if lower.ends_with(".ufdx") {
+        debug!("Skipping UFED collection index: {} (metadata pointer, not evidence)", filename);
+        return None;
+    }
+    
+    // Skip UFD files when they exist alongside matching ZIP (metadata only)
+    if !ufd_basenames.is_empty() && lower.ends_with(".ufd") {
+        debug!("Skipping UFED metadata file: {} (metadata only)", filename);
+        return None;
+    }
+    
+    // Check for forensic container files by extension only (fast, no file I/O)
+    // Special case: ZIP files with sibling UFD are UFED extraction containers
+    let container_type = if lower.ends_with(".zip") {
+        if let Some(stem) = Path::new(filename).file_stem() {
+            let stem_lower = stem.to_string_lossy().to_lowercase();
+            if ufd_basenames.contains(&stem_lower) {
+                Some("UFED")
             } else {
-                debug!("Skipping duplicate basename: {}", filename);
+                detect_container_type_by_extension(lower)
             }
         } else {
-            debug!("Skipping file with unrecognized container type: {}", filename);
+            detect_container_type_by_extension(lower)
         }
+    } else {
+        detect_container_type_by_extension(lower)
+    };
+
+    if container_type.is_none() {
+        debug!("Skipping file with unrecognized container type: {}", filename);
     }
+    container_type
+}
 
-    Ok(())
+/// Build the scan result entry for a file that passed `classify_file`
+fn build_discovered_file(
+    path_str: &str,
+    filename: &str,
+    lower: &str,
+    ctype: &str,
+    metadata: Option<&fs::Metadata>,
+) -> DiscoveredFile {
+    // For numbered segments, construct .001 path without checking existence (fast)
+    let display_path = if is_numbered_segment(lower) {
+        get_first_segment_path_fast(path_str)
+    } else {
+        path_str.to_string()
+    };
+    
+    let display_filename = Path::new(&display_path)
+        .file_name()
+        .map(|f| f.to_string_lossy().to_string())
+        .unwrap_or_else(|| filename.to_string());
+    
+    let file_size = metadata.map(|m| m.len()).unwrap_or(0);
+    
+    // Extract timestamps from metadata
+    let created = metadata.and_then(|m| m.created().ok()).map(Timestamp::from_system_time);
+    let modified = metadata.and_then(|m| m.modified().ok()).map(Timestamp::from_system_time);
+    
+    // FAST PATH: Skip segment calculation during scan - it's slow on external drives
+    // Segment details will be calculated on-demand when user selects a file
+    DiscoveredFile {
+        path: display_path,
+        filename: display_filename,
+        container_type: ctype.to_string(),
+        size: file_size, // Just first segment size - full size calculated on-demand
+        segment_count: None,
+        segment_files: None,
+        segment_sizes: None,
+        total_segment_size: None,
+        created,
+        modified,
+    }
 }
 
 /// Detect container type by file extension only (fast, no file I/O)
 /// Returns None for unrecognized extensions
-fn detect_container_type_by_extension(lower: &str) -> Option<&'static str> {
+pub(super) fn detect_container_type_by_extension(lower: &str) -> Option<&'static str> {
     // =========================================================================
     // Forensic Containers (evidence preservation formats)
     // =========================================================================
@@ -442,7 +667,7 @@ fn detect_container_type_by_extension(lower: &str) -> Option<&'static str> {
         Some("AFF")
     } else if lower.ends_with(".aff4") {
         Some("AFF4")
-    } else if lower.ends_with(".s01") || lower.ends_with(".s02") {
+    } else if lower.ends_with(".s01") {
         Some("SMART")
     // =========================================================================
     // UFED Mobile Forensics
@@ -464,6 +689,8 @@ fn detect_container_type_by_extension(lower: &str) -> Option<&'static str> {
         Some("ZIP")
     } else if lower.ends_with(".rar") || lower.ends_with(".r00") {
         Some("RAR")
+    } else if lower.ends_with(".dd.gz") || lower.ends_with(".raw.gz") || lower.ends_with(".img.gz") || lower.ends_with(".bin.gz") {
+        Some("RAW (gzip)")
     } else if lower.ends_with(".tar.gz") || lower.ends_with(".tgz") {
         Some("TAR.GZ")
     } else if lower.ends_with(".tar.xz") || lower.ends_with(".txz") {
@@ -515,6 +742,8 @@ fn detect_container_type_by_extension(lower: &str) -> Option<&'static str> {
     // =========================================================================
     } else if lower.ends_with(".iso") {
         Some("ISO 9660")
+    } else if lower.ends_with(".udf") {
+        Some("UDF")
     } else if lower.ends_with(".bin") || lower.ends_with(".cue") {
         Some("BIN/CUE")
     // =========================================================================
@@ -529,3 +758,210 @@ fn detect_container_type_by_extension(lower: &str) -> Option<&'static str> {
         None
     }
 }
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    fn make_fixture() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        for sub in ["case_a", "case_b"] {
+            let sub_dir = dir.path().join(sub);
+            fs::create_dir(&sub_dir).unwrap();
+            fs::write(sub_dir.join("image.001"), b"seg1").unwrap();
+            fs::write(sub_dir.join("image.002"), b"seg2").unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn test_recursive_scan_keeps_same_named_sets_in_different_folders() {
+        let dir = make_fixture();
+        let root = dir.path().to_str().unwrap();
+
+        let found = scan_directory_recursive(root).unwrap();
+        assert_eq!(found.len(), 2);
+        assert!(found[0].path.contains("case_a"));
+        assert!(found[1].path.contains("case_b"));
+        assert!(found.iter().all(|f| f.filename == "image.001"));
+    }
+
+    #[test]
+    fn test_streaming_scan_matches_collecting_scan() {
+        let dir = make_fixture();
+        let root = dir.path().to_str().unwrap();
+
+        let streamed = Mutex::new(Vec::new());
+        let count = scan_directory_streaming(root, true, |f| {
+            streamed.lock().unwrap().push(f.path.clone());
+        }).unwrap();
+        let mut streamed = streamed.into_inner().unwrap();
+        streamed.sort();
+
+        let collected: Vec<String> = scan_directory_recursive(root)
+            .unwrap()
+            .into_iter()
+            .map(|f| f.path)
+            .collect();
+
+        assert_eq!(count, 2);
+        assert_eq!(streamed, collected);
+    }
+
+    #[test]
+    fn test_non_recursive_scan_dedups_within_folder() {
+        let dir = make_fixture();
+        let sub = dir.path().join("case_a");
+
+        let found = scan_directory(sub.to_str().unwrap()).unwrap();
+        assert_eq!(found.len(), 1);
+        assert!(scan_directory(dir.path().to_str().unwrap()).unwrap().is_empty());
+    }
+
+    /// Nested tree mixing segment sets, UFED extractions and non-evidence files
+    fn make_nested_fixture() -> tempfile::TempDir {
+        let dir = make_fixture();
+        let files = [
+            "top.E01", "top.E02", "notes.txt",
+            "case_a/deep/disk.E01", "case_a/deep/disk.E02", "case_a/deep/disk.E03",
+            "case_a/deep/deeper/image.001", "case_a/deep/deeper/image.002",
+            "case_a/deep/deeper/phone.zip", "case_a/deep/deeper/phone.ufd",
+            "case_b/archive.7z.001", "case_b/archive.7z.002", "case_b/readme.md",
+            "case_c/empty/.keep", "case_c/x/y/z/evidence.ad1", "case_c/x/y/z/evidence.ad2",
+        ];
+        for file in files {
+            let path = dir.path().join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, file.as_bytes()).unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn test_parallel_walk_matches_sequential_walk() {
+        let dir = make_nested_fixture();
+        let root = dir.path().to_str().unwrap();
+
+        let mut sequential = Vec::new();
+        let descent = Descent::new(dir.path(), &ScanOptions::default());
+        scan_dir_internal(dir.path(), &mut HashSet::new(), true, &descent, 0, &mut |f| {
+            sequential.push((f.path, f.container_type));
+        }).unwrap();
+        sequential.sort();
+        assert_eq!(sequential.len(), 8);
+
+        for workers in [1, 4, 8] {
+            let found = Mutex::new(Vec::new());
+            let options = ScanOptions { workers: Some(workers), ..Default::default() };
+            let summary = scan_directory_parallel(root, true, &options, |f| {
+                found.lock().unwrap().push((f.path.clone(), f.container_type.clone()));
+            }).unwrap();
+            let mut found = found.into_inner().unwrap();
+            found.sort();
+            assert_eq!(found, sequential, "workers = {workers}");
+            assert_eq!(summary.count, 8);
+            assert_eq!(summary.directories, 10);
+            assert!(!summary.timed_out);
+        }
+    }
+
+    #[test]
+    fn test_ufed_extraction_folder_is_one_entry() {
+        let dir = tempfile::tempdir().unwrap();
+        let files: [(&str, &[u8]); 5] = [
+            ("Apple_iPhone.ufd", b"[General]"),
+            ("Apple_iPhone.zip", b"PK extraction"),
+            ("Apple_iPhone Report.pdf", b"%PDF"),
+            ("EvidenceCollection.ufdx", b"<ufdx/>"),
+            ("laptop.E01", b"EVF"),
+        ];
+        for (name, data) in files {
+            fs::write(dir.path().join(name), data).unwrap();
+        }
+
+        let found = scan_directory(dir.path().to_str().unwrap()).unwrap();
+        assert_eq!(found.len(), 2);
+        let ufed = &found[0];
+        assert_eq!(ufed.container_type, UFED_EXTRACTION_TYPE);
+        assert_eq!(ufed.filename, "Apple_iPhone.ufd");
+        assert_eq!(ufed.segment_files.as_ref().unwrap(), &[
+            "Apple_iPhone.ufd", "Apple_iPhone Report.pdf", "EvidenceCollection.ufdx", "Apple_iPhone.zip",
+        ]);
+        assert_eq!(ufed.size, 9 + 13 + 4 + 7);
+        assert_eq!(ufed.total_segment_size, Some(ufed.size));
+        assert_eq!(found[1].filename, "laptop.E01");
+
+        // The watcher resolves any member to the same grouped entry
+        let member = discover_file(&dir.path().join("Apple_iPhone.zip")).unwrap();
+        assert_eq!(member.path, ufed.path);
+        assert_eq!(discover_file(&dir.path().join("laptop.E01")).unwrap().container_type, "EnCase (E01)");
+    }
+
+    #[test]
+    fn test_e01_set_without_first_segment_is_flagged() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["orphan.E03", "orphan.E04", "orphan.E05", "whole.E01", "whole.E02"] {
+            fs::write(dir.path().join(name), b"EVF").unwrap();
+        }
+
+        let found = scan_directory(dir.path().to_str().unwrap()).unwrap();
+        let types: Vec<_> = found.iter().map(|f| (f.filename.as_str(), f.container_type.as_str())).collect();
+        assert_eq!(types, [("orphan.E03", ORPHANED_E01_TYPE), ("whole.E01", "EnCase (E01)")]);
+
+        // The watcher agrees: only the lowest remaining segment is reported
+        assert_eq!(discover_file(&dir.path().join("orphan.E03")).unwrap().container_type, ORPHANED_E01_TYPE);
+        assert!(discover_file(&dir.path().join("orphan.E04")).is_none());
+        assert!(discover_file(&dir.path().join("whole.E02")).is_none());
+    }
+
+    #[test]
+    fn test_parallel_walk_stops_at_timeout() {
+        let dir = make_nested_fixture();
+        let options = ScanOptions { timeout_ms: Some(0), ..Default::default() };
+        let summary = scan_directory_parallel(dir.path().to_str().unwrap(), true, &options, |_| {
+            panic!("nothing is reported after the deadline");
+        }).unwrap();
+        assert!(summary.timed_out);
+        assert_eq!(summary.count, 0);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlink_cycle_terminates_and_is_counted() {
+        let dir = make_fixture();
+        std::os::unix::fs::symlink(dir.path(), dir.path().join("case_a").join("up")).unwrap();
+        let root = dir.path().to_str().unwrap();
+
+        // Not followed by default
+        let summary = scan_directory_parallel(root, true, &ScanOptions::default(), |_| {}).unwrap();
+        assert_eq!((summary.count, summary.directories, summary.loops_skipped), (2, 3, 0));
+
+        let options = ScanOptions { follow_symlinks: true, ..Default::default() };
+        let summary = scan_directory_parallel(root, true, &options, |_| {}).unwrap();
+        assert_eq!((summary.count, summary.directories), (2, 3));
+        assert!(summary.loops_skipped > 0);
+
+        let mut found = 0;
+        let descent = Descent::new(dir.path(), &options);
+        scan_dir_internal(dir.path(), &mut HashSet::new(), true, &descent, 0, &mut |_| found += 1).unwrap();
+        assert_eq!(found, 2);
+        assert_eq!(descent.skipped(), (1, 0));
+    }
+
+    #[test]
+    fn test_max_depth_limits_descent() {
+        let dir = make_nested_fixture();
+        let root = dir.path().to_str().unwrap();
+
+        let options = ScanOptions { max_depth: Some(0), ..Default::default() };
+        let summary = scan_directory_parallel(root, true, &options, |_| {}).unwrap();
+        assert_eq!((summary.count, summary.directories, summary.depth_skipped), (1, 1, 3));
+
+        let options = ScanOptions { max_depth: Some(1), ..Default::default() };
+        let found = scan_directory_with_options(root, true, &options).unwrap();
+        let names: Vec<_> = found.iter().map(|f| f.filename.as_str()).collect();
+        assert_eq!(names, ["image.001", "archive.7z.001", "image.001", "top.E01"]);
+    }
+}
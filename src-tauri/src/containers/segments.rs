@@ -1,71 +1,41 @@
 //! Segment handling utilities for multi-part forensic containers
 //!
 //! This module provides functions for detecting and handling segmented container files
-//! like .E01/.E02, .ad1/.ad2, .001/.002, and various archive split formats.
+//! like .E01/.E02, .ad1/.ad2, .001/.002 (and the other split suffixes in
+//! `common::segments`), and various archive split formats.
 
+use std::fs;
 use std::path::Path;
 use tracing::debug;
 
-/// Check if filename is a numbered segment (.001, .002, etc.)
+use crate::common::segments::{self, list_split_segments, EwfSegmentName, SplitName};
+
+use super::types::{SegmentFile, SegmentSetInfo};
+
+/// Check if filename is a split image segment (.001, .0001, .aa, .part1, etc.)
 pub fn is_numbered_segment(lower: &str) -> bool {
-    if let Some(ext_start) = lower.rfind('.') {
-        let ext = &lower[ext_start + 1..];
-        if ext.len() == 3 && ext.chars().all(|c| c.is_ascii_digit()) {
-            return true;
-        }
-    }
-    false
+    SplitName::parse(lower).is_some()
 }
 
 /// Get the path to the first available segment given any segment path
-/// Tries .001 first, then scans for the lowest numbered segment
-/// NOTE: This does file I/O to check existence - use get_first_segment_path_fast for scanning
+/// NOTE: This does file I/O to list the folder - use get_first_segment_path_fast for scanning
 #[allow(dead_code)]
 pub fn get_first_segment_path(path: &str) -> String {
-    let path_obj = Path::new(path);
-    if let Some(parent) = path_obj.parent() {
-        if let Some(filename) = path_obj.file_name() {
-            let filename_str = filename.to_string_lossy();
-            if let Some(dot_pos) = filename_str.rfind('.') {
-                let base = &filename_str[..dot_pos];
-                
-                // Try .001 first (most common)
-                let first_seg = format!("{}.001", base);
-                let first_path = parent.join(&first_seg);
-                if first_path.exists() {
-                    return first_path.to_string_lossy().to_string();
-                }
-                
-                // If .001 doesn't exist, find the lowest numbered segment
-                for num in 2..=999 {
-                    let seg_name = format!("{}.{:03}", base, num);
-                    let seg_path = parent.join(&seg_name);
-                    if seg_path.exists() {
-                        return seg_path.to_string_lossy().to_string();
-                    }
-                }
-            }
-        }
-    }
-    // Return original path if we can't find any lower segment
-    path.to_string()
+    segments::get_first_segment_path(path)
 }
 
-/// Fast version - just constructs .001 path without checking existence
+/// Fast version - just constructs the first segment's path without checking existence
 /// Used during directory scan to avoid slow file I/O
 pub fn get_first_segment_path_fast(path: &str) -> String {
     let path_obj = Path::new(path);
-    if let Some(parent) = path_obj.parent() {
-        if let Some(filename) = path_obj.file_name() {
-            let filename_str = filename.to_string_lossy();
-            if let Some(dot_pos) = filename_str.rfind('.') {
-                let base = &filename_str[..dot_pos];
-                let first_seg = format!("{}.001", base);
-                return parent.join(&first_seg).to_string_lossy().to_string();
-            }
-        }
+    let split = path_obj.file_name().and_then(|name| SplitName::parse(&name.to_string_lossy()));
+    match (path_obj.parent(), split) {
+        // A decimal set may start at .000/.00 - keep those as they are
+        (Some(parent), Some(split)) if !split.is_first() || split.index != 0 => split.sibling(split.first_index())
+            .map(|first| parent.join(first).to_string_lossy().to_string())
+            .unwrap_or_else(|| path.to_string()),
+        _ => path.to_string(),
     }
-    path.to_string()
 }
 
 /// Check if file is part of a segmented series
@@ -95,12 +65,9 @@ pub fn is_first_segment(lower: &str) -> bool {
     // AD1 segments but not first: .ad2, .ad3, etc.
     if is_ad1_segment(lower) && !lower.ends_with(".ad1") { return false; }
     
-    // E01 files: .E01 is first
-    if lower.ends_with(".e01") { return true; }
-    // E01 segments but not first
-    if lower.ends_with(".e02") { return false; }
-    for i in 3..=99 {
-        if lower.ends_with(&format!(".e{:02}", i)) { return false; }
+    // EWF files: .E01, .Ex01, .s01 and .L01 are first, .E02, .EAA etc. are not
+    if let Some(ewf) = EwfSegmentName::parse(lower) {
+        return ewf.number == 1;
     }
     
     // Archive formats - first segments
@@ -129,12 +96,9 @@ pub fn is_first_segment(lower: &str) -> bool {
     // GZIP
     if lower.ends_with(".gz") { return true; }
     
-    // Numbered segments: .001 is first (for non-archive files)
-    if let Some(dot_pos) = lower.rfind('.') {
-        let ext = &lower[dot_pos + 1..];
-        if ext.len() == 3 && ext.chars().all(|c| c.is_ascii_digit()) {
-            return ext == "001";
-        }
+    // Split segments: .001, .aa, .part1 are first (for non-archive files)
+    if let Some(split) = SplitName::parse(lower) {
+        return split.is_first();
     }
     
     // Not a segment file, treat as first
@@ -193,9 +157,9 @@ pub fn is_archive_segment(lower: &str) -> bool {
 pub fn get_segment_basename(filename: &str) -> String {
     let lower = filename.to_lowercase();
     
-    // Handle .E01, .E02, etc.
-    if lower.ends_with(".e01") {
-        return filename[..filename.len() - 4].to_string();
+    // Handle .E01, .EAA, .Ex01, SMART .s01 and logical .L01
+    if let Some(ewf) = EwfSegmentName::parse(filename) {
+        return ewf.stem;
     }
     
     // Handle .ad1, .ad2, .ad3, etc.
@@ -231,31 +195,18 @@ pub fn get_segment_basename(filename: &str) -> String {
         }
     }
     
-    // Handle .001, .002, etc.
-    if let Some(dot_pos) = filename.rfind('.') {
-        let ext = &filename[dot_pos + 1..];
-        if ext.len() == 3 && ext.chars().all(|c| c.is_ascii_digit()) {
-            return filename[..dot_pos].to_string();
-        }
+    // Handle .001, .aa, .part1, etc.
+    if let Some(split) = SplitName::parse(filename) {
+        return split.base;
     }
     
     filename.to_string()
 }
 
-/// Detailed segment information
-#[allow(dead_code)]
-pub struct SegmentInfo {
-    pub total_size: u64,
-    pub count: u32,
-    pub files: Vec<String>,
-    pub sizes: Vec<u64>,
-}
-
 /// Calculate total size of all segments in a series
 #[allow(dead_code)]
-pub fn calculate_total_segment_info(dir: &Path, basename: &str) -> Option<SegmentInfo> {
+pub fn calculate_total_segment_info(dir: &Path, basename: &str) -> Option<SegmentSetInfo> {
     let mut total = 0u64;
-    let mut count = 0u32;
     let mut files = Vec::new();
     let mut sizes = Vec::new();
     
@@ -266,7 +217,6 @@ pub fn calculate_total_segment_info(dir: &Path, basename: &str) -> Option<Segmen
         if let Ok(metadata) = segment_path.metadata() {
             let size = metadata.len();
             total += size;
-            count += 1;
             files.push(segment_name);
             sizes.push(size);
         } else {
@@ -275,8 +225,8 @@ pub fn calculate_total_segment_info(dir: &Path, basename: &str) -> Option<Segmen
     }
     
     if total > 0 {
-        debug!("Found {} AD1 segments, total size: {}", count, total);
-        return Some(SegmentInfo { total_size: total, count, files, sizes });
+        debug!("Found {} AD1 segments, total size: {}", files.len(), total);
+        return Some(segment_set(files, sizes, Vec::new()));
     }
     
     // Try E01 segments (.E01, .E02, ...)
@@ -291,7 +241,6 @@ pub fn calculate_total_segment_info(dir: &Path, basename: &str) -> Option<Segmen
         if let Ok(metadata) = segment_path.metadata() {
             let size = metadata.len();
             total += size;
-            count += 1;
             files.push(segment_name);
             sizes.push(size);
         } else {
@@ -300,8 +249,8 @@ pub fn calculate_total_segment_info(dir: &Path, basename: &str) -> Option<Segmen
     }
     
     if total > 0 {
-        debug!("Found {} E01 segments, total size: {}", count, total);
-        return Some(SegmentInfo { total_size: total, count, files, sizes });
+        debug!("Found {} E01 segments, total size: {}", files.len(), total);
+        return Some(segment_set(files, sizes, Vec::new()));
     }
     
     // Try numbered segments (.001, .002, ...)
@@ -311,7 +260,6 @@ pub fn calculate_total_segment_info(dir: &Path, basename: &str) -> Option<Segmen
         if let Ok(metadata) = segment_path.metadata() {
             let size = metadata.len();
             total += size;
-            count += 1;
             files.push(segment_name);
             sizes.push(size);
         } else {
@@ -320,9 +268,189 @@ pub fn calculate_total_segment_info(dir: &Path, basename: &str) -> Option<Segmen
     }
     
     if total > 0 {
-        debug!("Found {} numbered segments, total size: {}", count, total);
-        return Some(SegmentInfo { total_size: total, count, files, sizes });
+        debug!("Found {} numbered segments, total size: {}", files.len(), total);
+        return Some(segment_set(files, sizes, Vec::new()));
     }
     
     None
 }
+
+fn segment_set(names: Vec<String>, sizes: Vec<u64>, missing: Vec<String>) -> SegmentSetInfo {
+    let files: Vec<SegmentFile> = names.into_iter().zip(sizes)
+        .map(|(name, size)| SegmentFile { name, size })
+        .collect();
+    SegmentSetInfo {
+        count: files.len() as u32,
+        total_size: files.iter().map(|f| f.size).sum(),
+        files,
+        missing,
+    }
+}
+
+/// Segment extension pattern: letters, then an unpadded number (AD1; EWF
+/// sets go through `EwfSegmentName`)
+struct SegmentScheme {
+    /// Letter prefix as written on the given segment ("ad")
+    prefix: String,
+}
+
+impl SegmentScheme {
+    /// Parse a segment extension into its scheme and segment number
+    fn parse(ext: &str) -> Option<(Self, u32)> {
+        let digits_at = ext.find(|c: char| c.is_ascii_digit())?;
+        let (prefix, digits) = ext.split_at(digits_at);
+        if !digits.chars().all(|c| c.is_ascii_digit()) || !prefix.eq_ignore_ascii_case("ad") {
+            return None;
+        }
+        let number = digits.parse().ok()?;
+        Some((Self { prefix: prefix.to_string() }, number))
+    }
+
+    fn matches(&self, ext: &str) -> Option<u32> {
+        let (other, number) = Self::parse(ext)?;
+        other.prefix.eq_ignore_ascii_case(&self.prefix).then_some(number)
+    }
+
+    fn name(&self, stem: &str, number: u32) -> String {
+        format!("{}.{}{}", stem, self.prefix, number)
+    }
+}
+
+/// Stat the segment set `path` belongs to, reporting gaps in the numbering
+///
+/// Any segment of the set may be given. `expected_count` is the segment
+/// count recorded by the format (AD1 headers), used to report missing
+/// segments after the last one present. Files are only listed and statted,
+/// so this is cheap enough for `info_fast`. Non-segmented files come back
+/// as a one-file set.
+pub fn segment_set_info(path: &str, expected_count: Option<u32>) -> Option<SegmentSetInfo> {
+    let path_obj = Path::new(path);
+    let dir = path_obj.parent()?;
+    let filename = path_obj.file_name()?.to_string_lossy().to_string();
+
+    // Raw split sets (.001, .aa, .part1) may start at index 0
+    if let Some(split) = SplitName::parse(&filename) {
+        let found: Vec<(u32, String, u64)> = list_split_segments(dir, &split).into_iter()
+            .map(|(index, segment, size)| (index, segment.file_name().unwrap_or_default().to_string_lossy().to_string(), size))
+            .collect();
+        let first = found.first().map_or(split.first_index(), |(index, _, _)| (*index).min(split.first_index()));
+        return Some(finish_segment_set(path, found, first, expected_count, |n| split.sibling(n)));
+    }
+
+    // EWF sets, in their own naming scheme (.E99 is followed by .EAA)
+    if let Some(ewf) = EwfSegmentName::parse(&filename) {
+        let mut found: Vec<(u32, String, u64)> = fs::read_dir(dir).ok()?
+            .flatten()
+            .filter_map(|entry| {
+                let name = entry.file_name().to_string_lossy().to_string();
+                let (entry_stem, ext) = name.rsplit_once('.')?;
+                if !entry_stem.eq_ignore_ascii_case(&ewf.stem) {
+                    return None;
+                }
+                let number = ewf.naming.number(ext)?;
+                let meta = entry.metadata().ok().filter(|m| m.is_file())?;
+                Some((number, name, meta.len()))
+            })
+            .collect();
+        found.sort_by_key(|(number, _, _)| *number);
+        found.dedup_by_key(|(number, _, _)| *number);
+        return Some(finish_segment_set(path, found, 1, expected_count, |n| ewf.sibling(n)));
+    }
+
+    let scheme = filename.rsplit_once('.')
+        .and_then(|(stem, ext)| SegmentScheme::parse(ext).map(|(scheme, _)| (stem.to_string(), scheme)));
+    let Some((stem, scheme)) = scheme else {
+        let size = fs::metadata(path_obj).ok()?.len();
+        return Some(segment_set(vec![filename], vec![size], Vec::new()));
+    };
+
+    let mut found: Vec<(u32, String, u64)> = fs::read_dir(dir).ok()?
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let (entry_stem, ext) = name.rsplit_once('.')?;
+            if !entry_stem.eq_ignore_ascii_case(&stem) {
+                return None;
+            }
+            let number = scheme.matches(ext)?;
+            let meta = entry.metadata().ok().filter(|m| m.is_file())?;
+            Some((number, name, meta.len()))
+        })
+        .collect();
+    found.sort_by_key(|(number, _, _)| *number);
+    found.dedup_by_key(|(number, _, _)| *number);
+
+    Some(finish_segment_set(path, found, 1, expected_count, |n| Some(scheme.name(&stem, n))))
+}
+
+/// Build the set from sorted (number, name, size) entries, naming the gaps
+/// between `first` and the last segment found or expected
+fn finish_segment_set(
+    path: &str,
+    found: Vec<(u32, String, u64)>,
+    first: u32,
+    expected_count: Option<u32>,
+    name: impl Fn(u32) -> Option<String>,
+) -> SegmentSetInfo {
+    let expected_last = expected_count.filter(|count| *count > 0).map(|count| first + count - 1);
+    let last = found.last().map(|(number, _, _)| *number).max(expected_last).unwrap_or(0);
+    let missing: Vec<String> = (first..=last)
+        .filter(|n| found.binary_search_by_key(n, |(number, _, _)| *number).is_err())
+        .filter_map(&name)
+        .collect();
+    if !missing.is_empty() {
+        debug!(path, missing = missing.len(), "Segment set has gaps");
+    }
+
+    let (names, sizes) = found.into_iter().map(|(_, name, size)| (name, size)).unzip();
+    segment_set(names, sizes, missing)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_segment_set_reports_gaps_and_total() {
+        let dir = tempfile::tempdir().unwrap();
+        for (name, size) in [("disk.E01", 100), ("disk.E02", 50), ("disk.E04", 25), ("other.E03", 1)] {
+            fs::write(dir.path().join(name), vec![0u8; size]).unwrap();
+        }
+        let set = segment_set_info(dir.path().join("disk.E02").to_str().unwrap(), None).unwrap();
+        assert_eq!(set.count, 3);
+        assert_eq!(set.total_size, 175);
+        assert_eq!(set.files[0].name, "disk.E01");
+        assert_eq!(set.missing, vec!["disk.E03"]);
+
+        // AD1 headers record the segment count, so a short set is detected too
+        fs::write(dir.path().join("logical.ad1"), b"x").unwrap();
+        fs::write(dir.path().join("logical.ad2"), b"x").unwrap();
+        let set = segment_set_info(dir.path().join("logical.ad1").to_str().unwrap(), Some(3)).unwrap();
+        assert_eq!(set.count, 2);
+        assert_eq!(set.missing, vec!["logical.ad3"]);
+
+        fs::write(dir.path().join("single.dd"), b"abc").unwrap();
+        let set = segment_set_info(dir.path().join("single.dd").to_str().unwrap(), None).unwrap();
+        assert_eq!((set.count, set.total_size), (1, 3));
+    }
+
+    #[test]
+    fn test_ewf_letter_continuations_group_with_their_set() {
+        for (lower, first) in [("image.e01", true), ("image.e02", false), ("image.ex01", true), ("image.ex02", false), ("image.l02", false), ("notes.log", true)] {
+            assert_eq!(is_first_segment(lower), first, "{lower}");
+        }
+        assert_eq!(get_segment_basename("image.EAA"), "image");
+        assert_eq!(get_segment_basename("image.Ex01"), "image");
+        assert_eq!(get_segment_basename("notes.sql"), "notes.sql");
+
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["long.E98", "long.E99", "long.EAA", "long.EAC", "long.Ex01"] {
+            fs::write(dir.path().join(name), b"x").unwrap();
+        }
+        let set = segment_set_info(dir.path().join("long.EAA").to_str().unwrap(), None).unwrap();
+        assert_eq!(set.count, 4);
+        assert_eq!(set.files[2].name, "long.EAA");
+        assert_eq!(set.missing.len(), 97 + 1);
+        assert_eq!(set.missing.last().map(String::as_str), Some("long.EAB"));
+    }
+}
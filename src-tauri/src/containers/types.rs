@@ -6,8 +6,16 @@ use serde::{Serialize, Deserialize};
 
 use crate::ad1;
 use crate::archive;
+use crate::common::dedupe::DedupeStrategy;
+use crate::common::extract_filter::{ExtractFilter, ExtractStats};
+use crate::common::extract_manifest::ManifestFormat;
+use crate::common::hash::HashValue;
+use crate::common::safe_path::PathChange;
+use crate::common::time::Timestamp;
 use crate::ewf;
+use crate::iso;
 use crate::raw;
+use crate::triage::TriageSummary;
 use crate::ufed;
 
 /// Stored hash from container metadata or companion log files
@@ -38,9 +46,9 @@ pub struct SegmentHash {
     pub algorithm: String,
     /// The hash value
     pub hash: String,
-    /// Starting byte/sector offset
+    /// Starting offset (bytes for dc3dd logs, whose sector ranges are converted)
     pub offset_from: Option<u64>,
-    /// Ending byte/sector offset
+    /// Ending offset, inclusive for dc3dd logs
     pub offset_to: Option<u64>,
     /// Segment size
     pub size: Option<u64>,
@@ -49,7 +57,7 @@ pub struct SegmentHash {
 }
 
 /// Information parsed from companion log files (e.g., FTK logs, Guymager logs)
-#[derive(Serialize, Clone)]
+#[derive(Serialize, Clone, Default)]
 pub struct CompanionLogInfo {
     pub log_path: String,
     pub created_by: Option<String>,
@@ -58,19 +66,60 @@ pub struct CompanionLogInfo {
     pub unique_description: Option<String>,
     pub examiner: Option<String>,
     pub notes: Option<String>,
-    pub acquisition_started: Option<String>,
-    pub acquisition_finished: Option<String>,
-    pub verification_started: Option<String>,
-    pub verification_finished: Option<String>,
+    pub acquisition_started: Option<Timestamp>,
+    pub acquisition_finished: Option<Timestamp>,
+    pub verification_started: Option<Timestamp>,
+    pub verification_finished: Option<Timestamp>,
     pub stored_hashes: Vec<StoredHash>,
     pub segment_list: Vec<String>,
     /// Per-segment hashes
     pub segment_hashes: Vec<SegmentHash>,
+    /// How sure we are this log belongs to the image
+    pub confidence: LogConfidence,
+    /// Other logs in the folder that could also belong to the image
+    pub other_candidates: Vec<String>,
+    /// The drive that was imaged, from the log's drive information
+    pub source_device: Option<SourceDeviceInfo>,
+}
+
+/// Acquisition source drive, as described by an imaging log
+#[derive(Serialize, Clone, Debug, Default, PartialEq)]
+pub struct SourceDeviceInfo {
+    pub model: Option<String>,
+    pub serial: Option<String>,
+    /// Bus the drive was attached by (e.g. "IDE", "USB", "SATA 3.0")
+    pub interface: Option<String>,
+    pub capacity_bytes: Option<u64>,
+    /// Logical sector size in bytes
+    pub sector_size: Option<u32>,
+    pub geometry: Option<DriveGeometry>,
+}
+
+/// CHS geometry reported for a drive
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DriveGeometry {
+    pub cylinders: u64,
+    /// Tracks per cylinder
+    pub heads: u32,
+    pub sectors_per_track: u32,
+}
+
+/// Confidence of a companion log to image association
+#[derive(Serialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum LogConfidence {
+    /// The log's segment list, per-segment hashes or text name the image
+    Exact,
+    /// Only the log's filename follows the image's name
+    #[default]
+    Probable,
+    /// Nothing singles this log out - don't compare without confirmation
+    Ambiguous,
 }
 
 /// Unified container information structure
 /// Holds format-specific info in the appropriate field
-#[derive(Serialize)]
+#[derive(Serialize, Clone)]
 pub struct ContainerInfo {
     pub container: String,
     pub ad1: Option<ad1::Ad1Info>,
@@ -81,8 +130,54 @@ pub struct ContainerInfo {
     pub raw: Option<raw::RawInfo>,
     pub archive: Option<archive::ArchiveInfo>,
     pub ufed: Option<ufed::UfedInfo>,
+    /// ISO 9660 / UDF optical disc image
+    pub iso: Option<iso::IsoInfo>,
     pub note: Option<String>,
     pub companion_log: Option<CompanionLogInfo>,
+    /// All files of a segmented container with their combined size
+    pub segments: Option<SegmentSetInfo>,
+    /// File listing with per-file hashes exported next to the container
+    pub manifest: Option<ManifestInfo>,
+    /// Partition/OS triage of E01/raw images, only filled in on request
+    pub triage: Option<TriageSummary>,
+    /// Bytes on disk across all segments
+    pub physical_size: Option<u64>,
+    /// Bytes of the evidence inside: sectors × sector size for E01, the
+    /// file items for AD1 (not known from `info_fast`), the uncompressed
+    /// entries for archives. None when it would take a full parse.
+    pub logical_size: Option<u64>,
+    /// `logical_size / physical_size` when both are known (2.0 = the
+    /// evidence takes half its size on disk)
+    pub compression_ratio: Option<f64>,
+}
+
+/// File manifest (e.g. FTK Imager CSV file listing) found next to a container
+#[derive(Serialize, Clone, Debug)]
+pub struct ManifestInfo {
+    pub manifest_path: String,
+    pub has_md5: bool,
+    pub has_sha1: bool,
+}
+
+/// One file of a segment set
+#[derive(Serialize, Clone, Debug)]
+pub struct SegmentFile {
+    pub name: String,
+    pub size: u64,
+}
+
+/// Segment files making up a container (E01/raw/AD1 sets) - found by
+/// statting the folder, never by opening the segments
+#[derive(Serialize, Clone, Debug, Default)]
+pub struct SegmentSetInfo {
+    pub count: u32,
+    /// Combined size of all present segments
+    pub total_size: u64,
+    /// Present segments in numbering order
+    pub files: Vec<SegmentFile>,
+    /// Expected segment names that were not found (gaps in the numbering,
+    /// or a short set where the format records the segment count)
+    pub missing: Vec<String>,
 }
 
 /// Represents a discovered forensic container file during directory scanning
@@ -96,26 +191,502 @@ pub struct DiscoveredFile {
     pub segment_files: Option<Vec<String>>,
     pub segment_sizes: Option<Vec<u64>>,
     pub total_segment_size: Option<u64>,
-    pub created: Option<String>,
-    pub modified: Option<String>,
+    pub created: Option<Timestamp>,
+    pub modified: Option<Timestamp>,
+}
+
+/// Options for parallel directory scans
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ScanOptions {
+    /// Directory workers (default 4, at most 8)
+    pub workers: Option<usize>,
+    /// Stop walking after this long; files found so far are still reported
+    pub timeout_ms: Option<u64>,
+    /// Levels below the root to descend into (unlimited when unset)
+    pub max_depth: Option<usize>,
+    /// Descend into symlinked directories and junctions (loops are still cut)
+    pub follow_symlinks: bool,
+}
+
+/// Outcome of a parallel directory scan
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScanSummary {
+    /// Files reported through the callback
+    pub count: usize,
+    /// Directories whose listing was processed
+    pub directories: usize,
+    /// The timeout hit before the walk finished
+    pub timed_out: bool,
+    /// Directories not entered because the walk had already been there
+    pub loops_skipped: usize,
+    /// Directories not entered because they lie deeper than `max_depth`
+    pub depth_skipped: usize,
+}
+
+/// Role of a file within an [`EvidenceBundle`]
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BundleRole {
+    /// The container the bundle opens as
+    Primary,
+    /// Further segment of the primary container
+    Segment,
+    /// Acquisition log of the primary container
+    Log,
+    /// File listing exported with the primary container
+    Manifest,
+    /// Raw image with the same stem, exported from the primary container
+    DerivedExport,
+}
+
+/// One file of an [`EvidenceBundle`]
+#[derive(Serialize, Clone, Debug)]
+pub struct BundleMember {
+    pub path: String,
+    pub filename: String,
+    pub role: BundleRole,
+    pub size: u64,
+}
+
+/// A container together with its segments, companion files and derived
+/// exports, shown as one row of the scan results
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct EvidenceBundle {
+    /// Stays the same while members arrive during a streaming scan
+    pub id: String,
+    /// Basename shared by the members ("image" for image.E01)
+    pub stem: String,
+    /// Scan result of the primary container
+    pub primary: DiscoveredFile,
+    /// Every file of the bundle, the primary first
+    pub members: Vec<BundleMember>,
+}
+
+/// Options for [`info_batch`](super::info_batch)
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct BatchInfoOptions {
+    /// Seconds one file may take before it is reported as timed out (default 30)
+    pub timeout_secs: Option<u64>,
+    /// Files read at once (default 8, at most 32)
+    pub workers: Option<usize>,
+    /// Files read at once from one storage device (default 4)
+    pub workers_per_device: Option<usize>,
+    /// Bypass the info cache
+    pub refresh: bool,
+}
+
+/// Fast info of one file of a batch, sent as soon as it is ready
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchInfoItem {
+    pub path: String,
+    pub info: Option<ContainerInfo>,
+    pub error: Option<String>,
+    /// No answer within the timeout (e.g. a hung network share)
+    pub timed_out: bool,
+    pub elapsed_ms: u64,
+}
+
+/// Totals of an info batch
+#[derive(Debug, Clone, Default, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchInfoSummary {
+    pub total: usize,
+    pub succeeded: usize,
+    /// Files with an error, including those that timed out
+    pub failed: usize,
+    pub timed_out: usize,
+    /// Files not started because the batch was cancelled
+    pub skipped: usize,
+    pub elapsed_ms: u64,
+}
+
+// =============================================================================
+// Verification Results
+// =============================================================================
+
+/// JSON shape of [`VerifyEntry`]; version 1 is [`LegacyVerifyEntry`]
+pub const VERIFY_ENTRY_VERSION: u32 = 2;
+
+/// What a verification entry is about
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum VerifyTarget {
+    /// A file inside a logical container; `chunk` is the zlib chunk within
+    /// the file that could not be read
+    Path { path: String, chunk: Option<u64> },
+    /// A chunk of an EWF image
+    Chunk { index: usize },
+    /// One segment file of a set
+    Segment { name: String },
+    /// The whole image, or one view of it (`label`, e.g. "Logical image")
+    WholeImage { label: Option<String> },
+}
+
+/// Outcome of one verification entry
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum VerifyStatus {
+    /// Computed hash matches the expected one
+    Ok,
+    /// Computed hash differs from the expected one
+    Mismatch,
+    /// Data damaged (bad chunk table or zlib stream)
+    Corrupt,
+    /// Nothing to compare: the hash was only computed, or the entry was not checked
+    Skipped,
+    /// Data could not be read (truncated segment, unreadable chunk, I/O error)
+    Error,
 }
 
-/// Result entry from container verification
-#[derive(Serialize)]
+/// How much a status matters to the examiner
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum VerifySeverity {
+    Info,
+    /// Not verified, but nothing is known to be wrong
+    Warning,
+    /// The evidence does not verify
+    Failure,
+}
+
+impl VerifyStatus {
+    pub fn severity(self) -> VerifySeverity {
+        match self {
+            VerifyStatus::Ok => VerifySeverity::Info,
+            VerifyStatus::Skipped => VerifySeverity::Warning,
+            VerifyStatus::Mismatch | VerifyStatus::Corrupt | VerifyStatus::Error => VerifySeverity::Failure,
+        }
+    }
+}
+
+/// Why an entry got its status, where the status alone doesn't say
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum VerifyReason {
+    /// Skipped: there is no stored or listed hash to compare against
+    NoExpectedHash,
+    /// Error: the data lies past the end of a truncated segment
+    MissingData,
+    /// Error: the image hash covers only the chunks that could be read
+    PartialImage,
+    /// Error: listed in the manifest but not in the container
+    MissingFromContainer,
+    /// Skipped: in the container but not listed in the manifest
+    MissingFromManifest,
+    /// Skipped: an image chunk holding sectors the acquiring tool could not
+    /// read from the source and stored as zeros
+    AcquisitionReadError,
+}
+
+/// One result of a container verification, the same for every format
+#[derive(Serialize, Clone, Debug, PartialEq)]
 pub struct VerifyEntry {
+    pub target: VerifyTarget,
+    pub status: VerifyStatus,
+    /// Always `status.severity()`
+    pub severity: VerifySeverity,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<VerifyReason>,
+    /// Hash algorithm of `expected` and `computed` (e.g. "MD5")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub algorithm: Option<String>,
+    /// Hash stored in the container or listed in a manifest
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expected: Option<HashValue>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub computed: Option<HashValue>,
+    /// Size of the data hashed, in bytes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration_secs: Option<f64>,
+}
+
+impl VerifyEntry {
+    pub fn new(target: VerifyTarget, status: VerifyStatus) -> Self {
+        Self {
+            target,
+            status,
+            severity: status.severity(),
+            reason: None,
+            algorithm: None,
+            expected: None,
+            computed: None,
+            size: None,
+            message: None,
+            duration_secs: None,
+        }
+    }
+
+    pub fn path(path: impl Into<String>, status: VerifyStatus) -> Self {
+        Self::new(VerifyTarget::Path { path: path.into(), chunk: None }, status)
+    }
+
+    pub fn reason(mut self, reason: VerifyReason) -> Self {
+        self.reason = Some(reason);
+        self
+    }
+
+    /// Set the algorithm and both hashes; a hash that doesn't parse as hex is left out
+    pub fn hashes(mut self, algorithm: &str, expected: Option<&str>, computed: Option<&str>) -> Self {
+        self.algorithm = Some(algorithm.to_string());
+        self.expected = expected.and_then(|hash| hash.parse().ok());
+        self.computed = computed.and_then(|hash| hash.parse().ok());
+        self
+    }
+
+    pub fn size(mut self, size: u64) -> Self {
+        self.size = Some(size);
+        self
+    }
+
+    pub fn message(mut self, message: impl Into<String>) -> Self {
+        self.message = Some(message.into());
+        self
+    }
+
+    pub fn duration(mut self, duration: std::time::Duration) -> Self {
+        self.duration_secs = Some(duration.as_secs_f64());
+        self
+    }
+
+    /// File path of a `Path` entry
+    pub fn target_path(&self) -> Option<&str> {
+        match &self.target {
+            VerifyTarget::Path { path, .. } => Some(path),
+            _ => None,
+        }
+    }
+
+    /// Status string of the version 1 JSON shape
+    pub fn legacy_status(&self) -> &'static str {
+        match (self.status, self.reason, &self.target) {
+            (VerifyStatus::Ok, ..) => "ok",
+            (VerifyStatus::Mismatch, ..) => "nok",
+            (VerifyStatus::Corrupt, ..) => "corrupt",
+            (VerifyStatus::Skipped, Some(VerifyReason::MissingFromManifest), _) => "missing_from_manifest",
+            (VerifyStatus::Skipped, Some(VerifyReason::NoExpectedHash), _) => "computed",
+            (VerifyStatus::Skipped, Some(VerifyReason::AcquisitionReadError), _) => "acquisition_error",
+            (VerifyStatus::Skipped, ..) => "skipped",
+            (VerifyStatus::Error, Some(VerifyReason::MissingData), _) => "missing",
+            (VerifyStatus::Error, Some(VerifyReason::PartialImage), _) => "partial",
+            (VerifyStatus::Error, Some(VerifyReason::MissingFromContainer), _) => "missing_from_container",
+            (VerifyStatus::Error, ..) => "error",
+        }
+    }
+}
+
+/// Version 1 JSON shape of a verification entry, kept for callers that
+/// have not moved to [`VerifyEntry`] (see [`VerifyEntries::versioned`])
+///
+/// The AD1-only fields are left out when empty, as they were.
+#[derive(Serialize, Clone, Debug)]
+pub struct LegacyVerifyEntry {
     pub path: Option<String>,
     pub chunk_index: Option<usize>,
     pub status: String,
     pub message: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub algorithm: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub computed: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stored: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chunk: Option<u64>,
 }
 
-/// Internal enum for container type detection
-#[derive(Clone, Copy, Debug)]
-pub(crate) enum ContainerKind {
+impl From<&VerifyEntry> for LegacyVerifyEntry {
+    fn from(entry: &VerifyEntry) -> Self {
+        let (path, chunk_index, chunk) = match &entry.target {
+            VerifyTarget::Path { path, chunk } => (Some(path.clone()), None, *chunk),
+            VerifyTarget::Chunk { index } => (None, Some(*index), None),
+            VerifyTarget::Segment { name } => (Some(name.clone()), None, None),
+            VerifyTarget::WholeImage { .. } => (None, None, None),
+        };
+        Self {
+            path,
+            chunk_index,
+            status: entry.legacy_status().to_string(),
+            message: entry.message.clone(),
+            algorithm: entry.algorithm.clone(),
+            computed: entry.computed.as_ref().map(ToString::to_string),
+            stored: entry.expected.as_ref().map(ToString::to_string),
+            size: entry.size,
+            chunk,
+        }
+    }
+}
+
+/// Verification entries in the JSON shape a caller asked for
+#[derive(Serialize, Clone, Debug)]
+#[serde(untagged)]
+pub enum VerifyEntries {
+    Current(Vec<VerifyEntry>),
+    Legacy(Vec<LegacyVerifyEntry>),
+}
+
+impl VerifyEntries {
+    /// [`VERIFY_ENTRY_VERSION`] gives the current shape; anything older
+    /// (or no version, for callers that don't know about versions) the
+    /// version 1 shape
+    pub fn versioned(entries: Vec<VerifyEntry>, version: Option<u32>) -> Self {
+        if version.unwrap_or(1) >= VERIFY_ENTRY_VERSION {
+            VerifyEntries::Current(entries)
+        } else {
+            VerifyEntries::Legacy(entries.iter().map(LegacyVerifyEntry::from).collect())
+        }
+    }
+}
+
+/// Options controlling verification; each applies only to the formats named
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct VerifyOptions {
+    /// Stop at the first file whose data cannot be read (AD1)
+    pub fail_fast: bool,
+    /// Keep hashing past unreadable chunks, as zeros, and list them (E01)
+    pub best_effort: bool,
+    /// Hash a set whose segment headers contradict the filename order (E01)
+    pub force: bool,
+    /// Add chunk storage statistics to the report (E01)
+    pub collect_stats: bool,
+    /// Add a zero/entropy map of the image to the report (E01, raw)
+    pub collect_map: bool,
+}
+
+/// Options controlling container extraction
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ExtractOptions {
+    /// Refuse to extract when output shares a volume with the evidence
+    pub fail_on_same_volume: bool,
+    /// Extract only matching files (containers with a file listing only)
+    pub filter: Option<ExtractFilter>,
+    /// Format of the extraction manifest written to the output directory
+    pub manifest_format: ManifestFormat,
+    /// Worker threads for AD1 extraction (default `ad1::DEFAULT_EXTRACT_WORKERS`)
+    pub workers: Option<usize>,
+    /// Chunks decompressed ahead of the writer for E01 extraction
+    /// (default `ewf::DEFAULT_EXTRACT_IN_FLIGHT_CHUNKS`)
+    pub in_flight_chunks: Option<usize>,
+    /// Report what would be written without creating or writing anything
+    pub dry_run: bool,
+    /// Skip AD1 files whose data cannot be read (listed in
+    /// `stats.unreadable`) instead of stopping at the first one
+    pub best_effort: bool,
+    /// Write AD1 alternate data streams into native streams when the output
+    /// is on NTFS (Windows only); otherwise they become `file__ADS_stream`
+    pub native_streams: bool,
+    /// Write each file content once (AD1 only): a file whose SHA-256 matches
+    /// an output of this or an earlier extraction into the same folder is
+    /// handled by `dedupe_strategy`, and its manifest row says so
+    pub dedupe: bool,
+    pub dedupe_strategy: DedupeStrategy,
+}
+
+/// Result of a container extraction, or of a dry run of one
+#[derive(Debug, Clone, Serialize)]
+pub struct ExtractResult {
+    pub output_dir: String,
+    /// Nothing was written; the rest describes what would have been
+    pub dry_run: bool,
+    /// Non-fatal issues (e.g. output on the same volume as the evidence)
+    pub warnings: Vec<String>,
+    /// Matched/skipped/extracted counts (logical containers only)
+    pub stats: Option<ExtractStats>,
+    /// Extraction manifest listing every written file with its hashes
+    /// (empty for a dry run)
+    pub manifest_path: String,
+    pub file_count: usize,
+    pub total_bytes: u64,
+    /// Longest output path written, to spot paths too long for the destination
+    pub longest_output_path: Option<String>,
+    /// Files whose names were changed to be created safely
+    pub sanitized_entries: Vec<SanitizedEntry>,
+    /// Output files that already exist (dry run only)
+    pub collisions: Vec<String>,
+    /// Free space on the destination volume, when known (dry run only)
+    pub free_bytes: Option<u64>,
+    /// `total_bytes` exceeds `free_bytes`
+    pub insufficient_space: bool,
+    /// Bytes missing on the destination when `insufficient_space`
+    pub shortfall_bytes: Option<u64>,
+    /// Partial files of an earlier, interrupted run removed from the output
+    /// folder before extracting
+    pub cleaned_partials: Vec<String>,
+    /// Wall-clock time of the extraction (0 for a dry run)
+    pub duration_secs: f64,
+    /// `total_bytes` over `duration_secs`
+    pub bytes_per_sec: f64,
+}
+
+/// A container entry written (or to be written) under a changed name
+#[derive(Debug, Clone, Serialize)]
+pub struct SanitizedEntry {
+    pub internal_path: String,
+    pub output_path: String,
+    pub changes: Vec<PathChange>,
+}
+
+/// Container type detection result
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ContainerKind {
     Ad1,
     E01,
     L01,
+    Lx01,
     Raw,
+    Iso,
     Archive,
     Ufed,
 }
+
+/// How strongly one detection check points at a container kind
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum DetectionConfidence {
+    /// The check ruled the kind out
+    None,
+    /// Matched by name only (raw images have no signature)
+    Low,
+    /// Signature matched but the name suggests something else, or the name
+    /// matched without a signature to confirm it
+    Medium,
+    /// Signature and name agree
+    High,
+}
+
+/// One check [`identify`](super::identify) performed
+#[derive(Serialize, Clone, Debug)]
+pub struct DetectionCandidate {
+    pub kind: ContainerKind,
+    pub confidence: DetectionConfidence,
+    pub reason: String,
+}
+
+/// Why a file was (or wasn't) detected as a container kind
+#[derive(Serialize, Clone, Debug)]
+pub struct DetectionReport {
+    pub path: String,
+    pub file_size: u64,
+    /// First matching candidate in detection order; `None` for empty or
+    /// unrecognized files
+    pub detected: Option<ContainerKind>,
+    /// Every check, in the order detection runs them
+    pub candidates: Vec<DetectionCandidate>,
+    /// First 16 bytes, e.g. "45 56 46 09 0D 0A FF 00 ..."
+    pub signature_bytes_hex: String,
+    /// Format the file name alone suggests
+    pub extension_hint: Option<String>,
+}
@@ -0,0 +1,218 @@
+//! Directory watcher for evidence intake folders
+//!
+//! Watches a directory for new or renamed files and reports each forensic
+//! container once its size has stopped changing (images are usually still
+//! being copied in when the first event fires). Files are only ever stat'ed,
+//! never opened, so copy tools that need exclusive access are not disturbed.
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use tracing::{debug, info, warn};
+
+use super::scanning::discover_file;
+use super::types::DiscoveredFile;
+
+/// How long a file's size must stay unchanged before it is reported
+pub const DEFAULT_SETTLE_TIME: Duration = Duration::from_secs(5);
+
+/// How often pending files are re-checked
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// A file seen by the watcher that has not settled yet
+struct PendingFile {
+    size: u64,
+    changed_at: Instant,
+}
+
+/// An active directory watch; dropping it stops watching
+pub struct DirectoryWatcher {
+    dir_path: String,
+    // Kept alive for the lifetime of the watch - dropping it unregisters the OS watch
+    _watcher: RecommendedWatcher,
+    stop: Arc<AtomicBool>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl DirectoryWatcher {
+    /// Start watching `dir_path`, calling `on_stable` for each new container
+    pub fn start<F>(dir_path: &str, recursive: bool, settle_time: Duration, on_stable: F) -> Result<Self, String>
+    where
+        F: Fn(DiscoveredFile) + Send + 'static,
+    {
+        let root = Path::new(dir_path);
+        if !root.is_dir() {
+            return Err(format!("Path is not a directory: {dir_path}"));
+        }
+
+        let (tx, rx) = mpsc::channel::<PathBuf>();
+        let mut watcher = notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+            match res {
+                Ok(event) => {
+                    // Creates and renames bring new files in; data modifications keep
+                    // the settle timer of a file that is still being copied fresh
+                    if matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) {
+                        for path in event.paths {
+                            let _ = tx.send(path);
+                        }
+                    }
+                }
+                Err(e) => warn!("Directory watch error: {}", e),
+            }
+        })
+        .map_err(|e| format!("Failed to create directory watcher: {e}"))?;
+
+        let mode = if recursive { RecursiveMode::Recursive } else { RecursiveMode::NonRecursive };
+        watcher.watch(root, mode)
+            .map_err(|e| format!("Failed to watch {dir_path}: {e}"))?;
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let stop_worker = Arc::clone(&stop);
+        let worker = thread::spawn(move || {
+            settle_loop(rx, &stop_worker, settle_time, on_stable);
+        });
+
+        info!(dir_path, recursive, "Directory watch started");
+        Ok(Self {
+            dir_path: dir_path.to_string(),
+            _watcher: watcher,
+            stop,
+            worker: Some(worker),
+        })
+    }
+
+    pub fn dir_path(&self) -> &str {
+        &self.dir_path
+    }
+}
+
+impl Drop for DirectoryWatcher {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+        info!(dir_path = %self.dir_path, "Directory watch stopped");
+    }
+}
+
+/// Debounce raw filesystem events until files have a stable size
+fn settle_loop<F>(rx: mpsc::Receiver<PathBuf>, stop: &AtomicBool, settle_time: Duration, on_stable: F)
+where
+    F: Fn(DiscoveredFile),
+{
+    let mut pending: HashMap<PathBuf, PendingFile> = HashMap::new();
+    let mut reported: HashSet<String> = HashSet::new();
+
+    while !stop.load(Ordering::Relaxed) {
+        match rx.recv_timeout(POLL_INTERVAL) {
+            Ok(path) => {
+                pending.insert(path, PendingFile { size: u64::MAX, changed_at: Instant::now() });
+                // Drain the burst of events a copy produces before checking sizes
+                while let Ok(path) = rx.try_recv() {
+                    pending.insert(path, PendingFile { size: u64::MAX, changed_at: Instant::now() });
+                }
+            }
+            Err(mpsc::RecvTimeoutError::Timeout) => {}
+            Err(mpsc::RecvTimeoutError::Disconnected) => break,
+        }
+
+        let now = Instant::now();
+        let mut settled = Vec::new();
+        pending.retain(|path, file| {
+            // Only stat the file - opening it would break copy tools on Windows
+            let size = match fs::metadata(path) {
+                Ok(meta) if meta.is_file() => meta.len(),
+                // Removed, renamed away, or a directory
+                _ => return false,
+            };
+            if size != file.size {
+                file.size = size;
+                file.changed_at = now;
+                return true;
+            }
+            if now.duration_since(file.changed_at) >= settle_time {
+                settled.push(path.clone());
+                return false;
+            }
+            true
+        });
+
+        settled.sort();
+        for path in settled {
+            let Some(found) = discover_file(&path) else {
+                continue;
+            };
+            if reported.insert(found.path.clone()) {
+                debug!(path = %found.path, "Watched file is stable");
+                on_stable(found);
+            }
+        }
+    }
+}
+
+// =============================================================================
+// Watch registry - lets several watches coexist, addressed by id
+// =============================================================================
+
+fn watches() -> &'static Mutex<HashMap<String, DirectoryWatcher>> {
+    static WATCHES: OnceLock<Mutex<HashMap<String, DirectoryWatcher>>> = OnceLock::new();
+    WATCHES.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Start a registered watch and return its id (for `unwatch_directory`)
+pub fn watch_directory<F>(dir_path: &str, recursive: bool, on_stable: F) -> Result<String, String>
+where
+    F: Fn(DiscoveredFile) + Send + 'static,
+{
+    let watcher = DirectoryWatcher::start(dir_path, recursive, DEFAULT_SETTLE_TIME, on_stable)?;
+    let id = uuid::Uuid::new_v4().to_string();
+    watches()
+        .lock()
+        .map_err(|_| "Watch registry poisoned".to_string())?
+        .insert(id.clone(), watcher);
+    Ok(id)
+}
+
+/// Stop a registered watch; returns false if the id is unknown
+pub fn unwatch_directory(watch_id: &str) -> Result<bool, String> {
+    let watcher = watches()
+        .lock()
+        .map_err(|_| "Watch registry poisoned".to_string())?
+        .remove(watch_id);
+    // Dropped outside the lock - joining the worker can take up to POLL_INTERVAL
+    Ok(watcher.is_some())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_watcher_reports_stable_first_segment_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let (tx, rx) = mpsc::channel();
+        let _watcher = DirectoryWatcher::start(
+            dir.path().to_str().unwrap(),
+            false,
+            Duration::from_millis(300),
+            move |file| { let _ = tx.send(file); },
+        )
+        .unwrap();
+
+        fs::write(dir.path().join("disk.E01"), b"EVF").unwrap();
+        fs::write(dir.path().join("disk.E02"), b"EVF").unwrap();
+        fs::write(dir.path().join("notes.txt"), b"not evidence").unwrap();
+
+        let found = rx.recv_timeout(Duration::from_secs(10)).unwrap();
+        assert_eq!(found.filename, "disk.E01");
+        assert!(rx.recv_timeout(Duration::from_secs(2)).is_err());
+    }
+}
@@ -0,0 +1,542 @@
+//! Evidence duplication with hash verification
+//!
+//! `copy_evidence` turns the copy-then-hash-both-sides routine into one
+//! audited operation: every segment of a container is streamed to the
+//! destination while its hash is computed, the copy is optionally read back
+//! and hashed again, and a manifest with the source and destination hashes
+//! and the times of the copy is written next to the copied files.
+//!
+//! Sources are only ever opened read-only. An existing destination file is
+//! reused only when it is a byte-identical prefix of its source - what an
+//! interrupted copy leaves behind - which is checked by hashing both; the
+//! copy then continues from its end. Any other existing file is a collision
+//! and is only replaced with `overwrite`.
+
+use std::fs::{self, File};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Instant;
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use filetime::FileTime;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info, warn};
+
+use crate::common::atomic_file::{partial_path, output_state, write_atomic, AtomicFile, OutputState};
+use crate::common::extract_manifest::set_original_times;
+use crate::common::{check_output_location, hashes_match, resources, StreamingHasher, BUFFER_SIZE};
+use crate::containers::{access_registry, segment_set_info};
+
+/// Suffix of the manifest written to the destination directory
+pub const MANIFEST_SUFFIX: &str = ".copy-manifest.json";
+
+// =============================================================================
+// Copy Types
+// =============================================================================
+
+/// Options for [`copy_evidence`]
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CopyOptions {
+    /// Hash algorithm for source and destination hashes
+    pub algorithm: String,
+    /// Read back and hash every destination file after copying it
+    pub verify_destination: bool,
+    /// Replace destination files that are not a prefix of their source
+    pub overwrite: bool,
+}
+
+impl Default for CopyOptions {
+    fn default() -> Self {
+        Self {
+            algorithm: "sha256".to_string(),
+            verify_destination: true,
+            overwrite: false,
+        }
+    }
+}
+
+/// What a copy is doing with the current file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CopyPhase {
+    /// Comparing an existing destination file with its source
+    Check,
+    Copy,
+    /// Hashing the written destination file
+    Verify,
+}
+
+/// Progress of a running copy
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CopyProgress {
+    pub file: String,
+    pub file_index: usize,
+    pub file_count: usize,
+    pub phase: CopyPhase,
+    /// Work done over all files (copied plus verified bytes)
+    pub bytes_done: u64,
+    pub bytes_total: u64,
+    /// Bytes read per second since the copy started
+    pub bytes_per_second: f64,
+}
+
+/// How a destination file came to hold its source's bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CopyAction {
+    Copied,
+    /// Continued from an interrupted copy
+    Resumed,
+    /// A complete copy was already there
+    AlreadyPresent,
+    /// A different file was replaced (`overwrite`)
+    Overwritten,
+}
+
+/// One copied file in the manifest
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CopiedFile {
+    pub source: String,
+    pub destination: String,
+    pub size: u64,
+    /// Source modification time (RFC 3339, UTC), also applied to the copy
+    pub source_modified: Option<String>,
+    /// Hash of the source, computed while copying
+    pub source_hash: String,
+    /// Hash of the destination read back after copying
+    pub destination_hash: Option<String>,
+    /// Whether the hashes match; None when the destination was not verified
+    pub verified: Option<bool>,
+    pub action: CopyAction,
+    /// Bytes already present from an interrupted copy
+    pub resumed_from: u64,
+    pub started_at: String,
+    pub finished_at: String,
+}
+
+/// Record of a copy, written as `<first segment>.copy-manifest.json`
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CopyManifest {
+    pub tool: String,
+    pub tool_version: String,
+    pub algorithm: String,
+    pub source_path: String,
+    pub dest_dir: String,
+    pub started_at: String,
+    pub finished_at: String,
+    pub files: Vec<CopiedFile>,
+    /// Every file was read back and matched its source
+    pub verified: bool,
+}
+
+/// Outcome of [`copy_evidence`]
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CopyResult {
+    pub manifest_path: String,
+    #[serde(flatten)]
+    pub manifest: CopyManifest,
+}
+
+// =============================================================================
+// Copy
+// =============================================================================
+
+/// Copy the container at `source_path` and all its segments into `dest_dir`
+///
+/// The destination must be outside the evidence folder. Stops between
+/// buffers when `cancel` is set; running the copy again resumes it.
+pub fn copy_evidence<F>(
+    source_path: &str,
+    dest_dir: &str,
+    options: &CopyOptions,
+    cancel: &AtomicBool,
+    progress: F,
+) -> Result<CopyResult, String>
+where
+    F: FnMut(&CopyProgress),
+{
+    StreamingHasher::from_str(&options.algorithm)?;
+    let source = Path::new(source_path);
+    let set = segment_set_info(source_path, None)
+        .ok_or_else(|| format!("Cannot read source {}", source_path))?;
+    if !set.missing.is_empty() {
+        return Err(format!("Segment set is incomplete - missing {}", set.missing.join(", ")));
+    }
+    let source_dir = source.parent().unwrap_or(Path::new("."));
+    let sources: Vec<PathBuf> = set.files.iter().map(|file| source_dir.join(&file.name)).collect();
+
+    let dest = Path::new(dest_dir);
+    check_output_location(&sources, dest)?;
+    // Held until the copy returns
+    let access = access_registry();
+    let _access = (access.read(source_path)?, access.write(dest_dir, "evidence copy")?);
+    fs::create_dir_all(dest)
+        .map_err(|e| format!("Failed to create destination {}: {}", dest.display(), e))?;
+
+    let passes = if options.verify_destination { 2 } else { 1 };
+    let mut copier = Copier {
+        options,
+        cancel,
+        progress,
+        started: Instant::now(),
+        bytes_read: 0,
+        bytes_done: 0,
+        bytes_total: set.total_size * passes,
+        file: String::new(),
+        file_index: 0,
+        file_count: sources.len(),
+    };
+    info!(source_path, dest_dir, files = sources.len(), total = set.total_size, "Starting evidence copy");
+
+    let started_at = now();
+    let mut files = Vec::with_capacity(sources.len());
+    for (index, segment) in sources.iter().enumerate() {
+        let name = segment.file_name().unwrap_or_default();
+        copier.file = name.to_string_lossy().to_string();
+        copier.file_index = index;
+        files.push(copier.copy_file(segment, &dest.join(name))?);
+    }
+
+    let manifest = CopyManifest {
+        tool: env!("CARGO_PKG_NAME").to_string(),
+        tool_version: env!("CARGO_PKG_VERSION").to_string(),
+        algorithm: options.algorithm.clone(),
+        source_path: source_path.to_string(),
+        dest_dir: dest_dir.to_string(),
+        started_at,
+        finished_at: now(),
+        verified: files.iter().all(|file| file.verified == Some(true)),
+        files,
+    };
+    let manifest_path = write_manifest(&manifest, &dest.join(format!("{}{}", set.files[0].name, MANIFEST_SUFFIX)))?;
+    info!(source_path, manifest = %manifest_path.display(), verified = manifest.verified, "Evidence copy finished");
+    Ok(CopyResult { manifest_path: manifest_path.to_string_lossy().to_string(), manifest })
+}
+
+/// State shared by the files of one copy
+struct Copier<'a, F> {
+    options: &'a CopyOptions,
+    cancel: &'a AtomicBool,
+    progress: F,
+    started: Instant,
+    /// Bytes read from either side, for the throughput
+    bytes_read: u64,
+    bytes_done: u64,
+    bytes_total: u64,
+    file: String,
+    file_index: usize,
+    file_count: usize,
+}
+
+impl<F: FnMut(&CopyProgress)> Copier<'_, F> {
+    fn copy_file(&mut self, source: &Path, dest: &Path) -> Result<CopiedFile, String> {
+        let started_at = now();
+        let meta = fs::metadata(source)
+            .map_err(|e| format!("Failed to stat {}: {}", source.display(), e))?;
+        let size = meta.len();
+        let mut input = File::open(source)
+            .map_err(|e| format!("Failed to open {}: {}", source.display(), e))?;
+        let mut hasher = StreamingHasher::from_str(&self.options.algorithm)?;
+
+        let partial = partial_path(dest);
+        let (resumed_from, action) = match fs::metadata(dest) {
+            // An interrupted copy continues from its partial file, reusing
+            // only the bytes its progress marker vouches for
+            Err(_) => {
+                let interrupted = match output_state(dest) {
+                    OutputState::Interrupted { bytes_written, .. } => bytes_written.min(size),
+                    _ => 0,
+                };
+                if interrupted > 0 && self.is_prefix_copy(&mut input, &mut hasher, &partial, interrupted)? {
+                    (interrupted, CopyAction::Resumed)
+                } else {
+                    input.seek(SeekFrom::Start(0)).map_err(|e| format!("Failed to seek {}: {}", source.display(), e))?;
+                    hasher = StreamingHasher::from_str(&self.options.algorithm)?;
+                    (0, CopyAction::Copied)
+                }
+            }
+            Ok(existing) if existing.is_dir() => {
+                return Err(format!("Destination {} is a directory", dest.display()));
+            }
+            Ok(existing) => {
+                let present = existing.len();
+                if present <= size && self.is_prefix_copy(&mut input, &mut hasher, dest, present)? {
+                    if present == size {
+                        (present, CopyAction::AlreadyPresent)
+                    } else {
+                        // Left by a copy that wrote under the final name
+                        fs::rename(dest, &partial)
+                            .map_err(|e| format!("Failed to move {} aside: {}", dest.display(), e))?;
+                        (present, CopyAction::Resumed)
+                    }
+                } else if self.options.overwrite {
+                    input.seek(SeekFrom::Start(0)).map_err(|e| format!("Failed to seek {}: {}", source.display(), e))?;
+                    hasher = StreamingHasher::from_str(&self.options.algorithm)?;
+                    (0, CopyAction::Overwritten)
+                } else {
+                    return Err(format!(
+                        "Destination {} already exists and differs from its source - set overwrite to replace it",
+                        dest.display()
+                    ));
+                }
+            }
+        };
+        debug!(source = %source.display(), resumed_from, ?action, "Copying evidence file");
+
+        if action != CopyAction::AlreadyPresent {
+            // The partial file stays when the copy fails or is cancelled, so
+            // running it again resumes
+            let mut output = if resumed_from > 0 { AtomicFile::resume(dest, resumed_from) } else { AtomicFile::create(dest) }
+                .and_then(|output| output.with_progress_markers(Some(size)))
+                .map(AtomicFile::keep_partial)
+                .map_err(|e| format!("Failed to open {} for writing: {}", dest.display(), e))?;
+            let copied = self.stream(&mut input, size - resumed_from, CopyPhase::Copy, true, |data| {
+                hasher.update(data);
+                output.write_all(data).map_err(|e| format!("Failed to write {}: {}", dest.display(), e))
+            });
+            if let Err(e) = copied {
+                if let Err(marker) = output.record_progress() {
+                    warn!(dest = %dest.display(), "Failed to record copy progress: {}", marker);
+                }
+                return Err(e);
+            }
+            output.commit().map_err(|e| format!("Failed to write {}: {}", dest.display(), e))?;
+        }
+        let source_hash = hasher.finalize();
+
+        let modified = FileTime::from_last_modification_time(&meta);
+        if let Err(e) = set_original_times(dest, None, Some(modified)) {
+            warn!("{}", e);
+        }
+
+        let destination_hash = if self.options.verify_destination {
+            let mut written = File::open(dest)
+                .map_err(|e| format!("Failed to reopen {}: {}", dest.display(), e))?;
+            let mut hasher = StreamingHasher::from_str(&self.options.algorithm)?;
+            self.stream(&mut written, size, CopyPhase::Verify, true, |data| {
+                hasher.update(data);
+                Ok(())
+            })?;
+            Some(hasher.finalize())
+        } else {
+            None
+        };
+        let verified = destination_hash.as_deref().map(|hash| hashes_match(hash, &source_hash));
+        if verified == Some(false) {
+            warn!(source = %source.display(), dest = %dest.display(), "Copied file does not match its source");
+        }
+
+        Ok(CopiedFile {
+            source: source.to_string_lossy().to_string(),
+            destination: dest.to_string_lossy().to_string(),
+            size,
+            source_modified: DateTime::<Utc>::from_timestamp(modified.unix_seconds(), modified.nanoseconds())
+                .map(|dt| dt.to_rfc3339_opts(SecondsFormat::Secs, true)),
+            source_hash,
+            destination_hash,
+            verified,
+            action,
+            resumed_from,
+            started_at,
+            finished_at: now(),
+        })
+    }
+
+    /// Whether the `len` bytes at `dest` are the start of the source
+    ///
+    /// The source prefix is fed to `hasher` as well, so on a match the copy
+    /// continues from `len` without reading it again.
+    fn is_prefix_copy(&mut self, input: &mut File, hasher: &mut StreamingHasher, dest: &Path, len: u64) -> Result<bool, String> {
+        let algorithm = &self.options.algorithm;
+        let mut existing = File::open(dest)
+            .map_err(|e| format!("Failed to open {}: {}", dest.display(), e))?;
+        let mut dest_hasher = StreamingHasher::from_str(algorithm)?;
+        self.stream(&mut existing, len, CopyPhase::Check, false, |data| {
+            dest_hasher.update(data);
+            Ok(())
+        })?;
+        let mut prefix_hasher = StreamingHasher::from_str(algorithm)?;
+        self.stream(input, len, CopyPhase::Check, true, |data| {
+            prefix_hasher.update(data);
+            hasher.update(data);
+            Ok(())
+        })?;
+        Ok(dest_hasher.finalize() == prefix_hasher.finalize())
+    }
+
+    /// Read `len` bytes from `reader` in buffers, reporting progress; `counts`
+    /// says whether the bytes count towards the job's progress
+    fn stream(
+        &mut self,
+        reader: &mut File,
+        len: u64,
+        phase: CopyPhase,
+        counts: bool,
+        mut each: impl FnMut(&[u8]) -> Result<(), String>,
+    ) -> Result<(), String> {
+        let mut buffer = vec![0u8; resources::buffer_size(BUFFER_SIZE).min(len.max(1) as usize)];
+        let mut remaining = len;
+        while remaining > 0 {
+            if self.cancel.load(Ordering::Relaxed) {
+                return Err(format!("Copy cancelled at {} - run it again to resume", self.file));
+            }
+            let want = buffer.len().min(remaining as usize);
+            let read = reader.read(&mut buffer[..want])
+                .map_err(|e| format!("Failed to read {}: {}", self.file, e))?;
+            if read == 0 {
+                return Err(format!("{} ended {} bytes early", self.file, remaining));
+            }
+            each(&buffer[..read])?;
+            remaining -= read as u64;
+            self.bytes_read += read as u64;
+            if counts {
+                self.bytes_done += read as u64;
+            }
+            self.report(phase);
+        }
+        Ok(())
+    }
+
+    fn report(&mut self, phase: CopyPhase) {
+        let elapsed = self.started.elapsed().as_secs_f64();
+        let progress = CopyProgress {
+            file: self.file.clone(),
+            file_index: self.file_index,
+            file_count: self.file_count,
+            phase,
+            bytes_done: self.bytes_done,
+            bytes_total: self.bytes_total,
+            bytes_per_second: if elapsed > 0.0 { self.bytes_read as f64 / elapsed } else { 0.0 },
+        };
+        (self.progress)(&progress);
+    }
+}
+
+fn write_manifest(manifest: &CopyManifest, path: &Path) -> Result<PathBuf, String> {
+    let json = serde_json::to_vec_pretty(manifest)
+        .map_err(|e| format!("Failed to serialize copy manifest: {e}"))?;
+    write_atomic(path, json)
+        .map_err(|e| format!("Failed to write copy manifest: {e}"))?;
+    Ok(path.to_path_buf())
+}
+
+fn now() -> String {
+    Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn evidence() -> (tempfile::TempDir, PathBuf) {
+        let root = tempfile::tempdir().unwrap();
+        let evidence = root.path().join("evidence");
+        fs::create_dir(&evidence).unwrap();
+        fs::write(evidence.join("disk.001"), vec![0xA5; 3000]).unwrap();
+        fs::write(evidence.join("disk.002"), vec![0x5A; 1000]).unwrap();
+        (root, evidence)
+    }
+
+    #[test]
+    fn test_copy_hashes_and_verifies_every_segment() {
+        let (root, evidence) = evidence();
+        let dest = root.path().join("copy");
+        let mut last = None;
+        let result = copy_evidence(
+            evidence.join("disk.001").to_str().unwrap(),
+            dest.to_str().unwrap(),
+            &CopyOptions::default(),
+            &AtomicBool::new(false),
+            |progress| last = Some(progress.clone()),
+        ).unwrap();
+
+        let files = &result.manifest.files;
+        assert_eq!(files.len(), 2);
+        assert!(result.manifest.verified);
+        assert!(files.iter().all(|f| f.action == CopyAction::Copied && f.destination_hash.as_ref() == Some(&f.source_hash)));
+        assert_eq!(fs::read(dest.join("disk.002")).unwrap(), vec![0x5A; 1000]);
+        assert!(Path::new(&result.manifest_path).ends_with("disk.001.copy-manifest.json"));
+        let last = last.unwrap();
+        assert_eq!((last.bytes_done, last.bytes_total, last.phase), (8000, 8000, CopyPhase::Verify));
+
+        // The evidence folder itself is never a valid destination
+        let inside = evidence.join("copy");
+        assert!(copy_evidence(evidence.join("disk.001").to_str().unwrap(), inside.to_str().unwrap(),
+            &CopyOptions::default(), &AtomicBool::new(false), |_| {}).is_err());
+    }
+
+    #[test]
+    fn test_crashed_copy_resumes_from_its_marker() {
+        let (root, evidence) = evidence();
+        let dest = root.path().join("copy");
+        fs::create_dir(&dest).unwrap();
+        // A crash after the marker at 1000 bytes, with 200 more unsynced bytes
+        fs::write(dest.join("disk.001.partial"), vec![0xA5; 1200]).unwrap();
+        fs::write(dest.join("disk.001.partial.progress"), r#"{"bytesWritten":1000,"expectedBytes":3000}"#).unwrap();
+        assert!(!dest.join("disk.001").exists());
+
+        let result = copy_evidence(evidence.join("disk.001").to_str().unwrap(), dest.to_str().unwrap(),
+            &CopyOptions::default(), &AtomicBool::new(false), |_| {}).unwrap();
+        let first = &result.manifest.files[0];
+        assert_eq!((first.action, first.resumed_from, first.verified), (CopyAction::Resumed, 1000, Some(true)));
+        assert_eq!(fs::read(dest.join("disk.001")).unwrap(), vec![0xA5; 3000]);
+        assert!(!dest.join("disk.001.partial").exists() && !dest.join("disk.001.partial.progress").exists());
+
+        // A cancelled copy leaves its partial file, never the final name
+        fs::remove_file(dest.join("disk.001")).unwrap();
+        let error = copy_evidence(evidence.join("disk.001").to_str().unwrap(), dest.to_str().unwrap(),
+            &CopyOptions::default(), &AtomicBool::new(true), |_| {}).unwrap_err();
+        assert!(error.contains("cancelled"), "{error}");
+        assert!(!dest.join("disk.001").exists() && dest.join("disk.001.partial").exists());
+    }
+
+    #[test]
+    fn test_interrupted_copy_resumes_and_collisions_need_overwrite() {
+        let (root, evidence) = evidence();
+        let dest = root.path().join("copy");
+        fs::create_dir(&dest).unwrap();
+        fs::write(dest.join("disk.001"), vec![0xA5; 1200]).unwrap();
+        let source = evidence.join("disk.001");
+        let copy = |options: &CopyOptions| copy_evidence(
+            source.to_str().unwrap(), dest.to_str().unwrap(), options, &AtomicBool::new(false), |_| {},
+        );
+
+        let result = copy(&CopyOptions::default()).unwrap();
+        let first = &result.manifest.files[0];
+        assert_eq!((first.action, first.resumed_from, first.verified), (CopyAction::Resumed, 1200, Some(true)));
+        assert_eq!(fs::read(dest.join("disk.001")).unwrap(), vec![0xA5; 3000]);
+        let again = copy(&CopyOptions::default()).unwrap();
+        assert!(again.manifest.files.iter().all(|f| f.action == CopyAction::AlreadyPresent));
+        assert_eq!(again.manifest.files[0].source_hash, first.source_hash);
+
+        fs::write(dest.join("disk.002"), b"something else").unwrap();
+        assert!(copy(&CopyOptions::default()).unwrap_err().contains("overwrite"));
+        let options = CopyOptions { overwrite: true, ..CopyOptions::default() };
+        let replaced = copy(&options).unwrap();
+        assert_eq!(replaced.manifest.files[1].action, CopyAction::Overwritten);
+        assert_eq!(fs::read(dest.join("disk.002")).unwrap(), vec![0x5A; 1000]);
+        assert_eq!(fs::read(evidence.join("disk.002")).unwrap(), vec![0x5A; 1000]);
+    }
+
+    #[test]
+    fn test_copy_refuses_a_destination_being_written() {
+        let (root, evidence) = evidence();
+        let dest = root.path().join("copy");
+        let source = evidence.join("disk.001");
+        let copy = || copy_evidence(source.to_str().unwrap(), dest.to_str().unwrap(),
+            &CopyOptions::default(), &AtomicBool::new(false), |_| {});
+
+        let extraction = access_registry().write(dest.to_str().unwrap(), "extraction").unwrap();
+        let err = copy().unwrap_err();
+        assert!(err.contains("already being written by extraction"), "{err}");
+        assert!(!dest.exists());
+        drop(extraction);
+        copy().unwrap();
+    }
+}
@@ -5,6 +5,7 @@
 //! - Files (discovered evidence containers)
 //! - Hashes (computed hash records with timestamps)
 //! - Verifications (verification audit trail)
+//! - Verification status (latest background verification outcome per path)
 //! - UI state (open tabs, settings)
 
 use rusqlite::{Connection, params, Result as SqlResult};
@@ -64,6 +65,21 @@ pub struct VerificationRecord {
     pub actual_hash: String,
 }
 
+/// Latest background verification outcome of a file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VerificationStatusRecord {
+    pub path: String,
+    pub algorithm: String,
+    pub status: String,  // 'verified', 'mismatch', 'no-expected', 'error'
+    pub computed_hash: Option<String>,
+    pub expected_hash: Option<String>,
+    pub expected_source: Option<String>,  // 'embedded', 'companion'
+    pub error: Option<String>,
+    pub file_size: i64,  // size and mtime (Unix seconds) when verified, to detect changes
+    pub file_modified: Option<i64>,
+    pub verified_at: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OpenTabRecord {
     pub id: String,
@@ -151,6 +167,20 @@ impl Database {
                 FOREIGN KEY (hash_id) REFERENCES hashes(id) ON DELETE CASCADE
             );
             
+            -- Verification status (latest background verification per path)
+            CREATE TABLE IF NOT EXISTS verification_status (
+                path TEXT PRIMARY KEY,
+                algorithm TEXT NOT NULL,
+                status TEXT NOT NULL,
+                computed_hash TEXT,
+                expected_hash TEXT,
+                expected_source TEXT,
+                error TEXT,
+                file_size INTEGER NOT NULL,
+                file_modified INTEGER,
+                verified_at TEXT NOT NULL
+            );
+            
             -- Open tabs (UI state per session)
             CREATE TABLE IF NOT EXISTS open_tabs (
                 id TEXT PRIMARY KEY,
@@ -463,6 +493,57 @@ impl Database {
         rows.collect()
     }
     
+    pub fn upsert_verification_status(&self, status: &VerificationStatusRecord) -> SqlResult<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO verification_status (path, algorithm, status, computed_hash, expected_hash,
+                expected_source, error, file_size, file_modified, verified_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
+             ON CONFLICT(path) DO UPDATE SET
+                algorithm = excluded.algorithm,
+                status = excluded.status,
+                computed_hash = excluded.computed_hash,
+                expected_hash = excluded.expected_hash,
+                expected_source = excluded.expected_source,
+                error = excluded.error,
+                file_size = excluded.file_size,
+                file_modified = excluded.file_modified,
+                verified_at = excluded.verified_at",
+            params![
+                status.path, status.algorithm, status.status, status.computed_hash, status.expected_hash,
+                status.expected_source, status.error, status.file_size, status.file_modified, status.verified_at
+            ],
+        )?;
+        Ok(())
+    }
+    
+    pub fn get_verification_status(&self, path: &str) -> SqlResult<Option<VerificationStatusRecord>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT path, algorithm, status, computed_hash, expected_hash, expected_source, error,
+                    file_size, file_modified, verified_at
+             FROM verification_status WHERE path = ?1"
+        )?;
+        
+        let mut rows = stmt.query(params![path])?;
+        if let Some(row) = rows.next()? {
+            Ok(Some(VerificationStatusRecord {
+                path: row.get(0)?,
+                algorithm: row.get(1)?,
+                status: row.get(2)?,
+                computed_hash: row.get(3)?,
+                expected_hash: row.get(4)?,
+                expected_source: row.get(5)?,
+                error: row.get(6)?,
+                file_size: row.get(7)?,
+                file_modified: row.get(8)?,
+                verified_at: row.get(9)?,
+            }))
+        } else {
+            Ok(None)
+        }
+    }
+    
     // ========================================================================
     // Open Tabs Operations
     // ========================================================================
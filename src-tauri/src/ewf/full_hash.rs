@@ -0,0 +1,384 @@
+//! Logical image hashes and physical segment hashes in one pass
+//!
+//! Chain-of-custody forms ask for both the hash of the decompressed media
+//! and the hash of every .E01/.E02 file. Hashing them separately reads a
+//! multi-terabyte set twice; [`full_hash`] reads each segment file once,
+//! front to back. Every buffer goes to the segment's hashers, and the chunks
+//! stored in it are decoded from the same buffer as soon as their stored
+//! bytes are in. Chunks come out in storage order, which need not be chunk
+//! order, so decoded chunks wait until the image hashers reach them.
+
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::time::Instant;
+
+use rayon::prelude::*;
+use tracing::{debug, info};
+
+use crate::common::{
+    device,
+    hash::{HashAlgorithm, StreamingHasher},
+    inflate::{inflate_bounded_counted, InflateError, INFLATE_SLACK},
+    resources,
+};
+
+use super::handle::EwfHandle;
+use super::operations::segment_headers;
+use super::types::*;
+
+/// Bytes read between two progress callbacks
+const PROGRESS_BYTES: u64 = 64 * 1024 * 1024;
+
+/// A chunk stored in the segment file being read
+#[derive(Clone, Copy)]
+struct StoredChunk {
+    index: usize,
+    offset: u64,
+    /// Offset past the last byte the chunk may take up: a full chunk when
+    /// stored as is, twice that (capped at the file end) when compressed, as
+    /// the chunk reader allows
+    end: u64,
+    compressed: bool,
+}
+
+/// Hash the media of the E01 at `path` and each of its segment files with
+/// every algorithm in `algorithms`, reading the segment files once
+///
+/// `progress` receives (bytes read, bytes in all segment files). A set whose
+/// segment headers contradict the filename order is refused, and the first
+/// unreadable chunk aborts - use the best-effort verification to get past
+/// damaged chunks.
+pub fn full_hash<F>(path: &str, algorithms: &[String], progress: F) -> Result<EwfFullHashes, String>
+where
+    F: FnMut(u64, u64),
+{
+    full_hash_buffered(path, algorithms, resources::io_buffer_size(), progress)
+}
+
+/// [`full_hash`] reading the segment files `buffer_size` bytes at a time
+fn full_hash_buffered<F>(path: &str, algorithms: &[String], buffer_size: usize, mut progress: F) -> Result<EwfFullHashes, String>
+where
+    F: FnMut(u64, u64),
+{
+    if algorithms.is_empty() {
+        return Err("No hash algorithm given".to_string());
+    }
+    let algos = algorithms.iter()
+        .map(|algorithm| HashAlgorithm::from_str(algorithm))
+        .collect::<Result<Vec<_>, _>>()?;
+    if let Some(check) = segment_headers(path).ok().filter(|check| !check.is_consistent()) {
+        return Err(format!(
+            "Segment files do not form one consistent set, so the image would be hashed wrong: {}",
+            check.issues.join("; ")
+        ));
+    }
+
+    let handle = EwfHandle::open(path)?;
+    let chunk_count = handle.get_chunk_count();
+    let chunk_size = handle.get_chunk_size() as usize;
+    // Stored whole like the others, but only partly media
+    let last_chunk = (handle.get_media_size().div_ceil(chunk_size as u64) as usize).saturating_sub(1);
+
+    // The stored chunks of each segment file, in file order
+    let mut stored: Vec<Vec<StoredChunk>> = vec![Vec::new(); handle.segments.len()];
+    for index in 0..chunk_count {
+        if let ChunkStorage::Stored { segment, offset, compressed } = handle.chunk_storage(index)? {
+            let span = if compressed { chunk_size as u64 * 2 } else { chunk_size as u64 };
+            let end = if compressed { (offset + span).min(handle.segments[segment].file_size) } else { offset + span };
+            stored[segment].push(StoredChunk { index, offset, end, compressed });
+        }
+    }
+    for chunks in &mut stored {
+        chunks.sort_by_key(|chunk| chunk.offset);
+    }
+
+    let bytes_total: u64 = handle.segments.iter().map(|segment| segment.file_size).sum();
+    info!(path, chunk_count, segments = stored.len(), algorithms = ?algorithms, "Starting combined image and segment hashing");
+
+    let mut image = ImageStream::new(&algos);
+    image.advance(&handle)?;
+    let mut segments = Vec::with_capacity(stored.len());
+    let mut bytes_done = 0u64;
+    let mut last_report = 0u64;
+    for (segment, chunks) in stored.iter().enumerate() {
+        let started = Instant::now();
+        let segment_path = handle.segment_path(segment).cloned().unwrap_or_default();
+        let file = File::open(&segment_path)
+            .map_err(|e| format!("Failed to open segment {}: {}", segment_path.display(), e))?;
+        let mut reader = BufReader::with_capacity(buffer_size, file);
+        let mut hashers: Vec<StreamingHasher> = algos.iter().map(|&algo| StreamingHasher::new(algo)).collect();
+
+        // Stored bytes of the chunks not yet decoded, starting at `window_start`
+        let mut window: Vec<u8> = Vec::new();
+        let mut window_start = 0u64;
+        let mut position = 0u64;
+        let mut next = 0;
+        loop {
+            let block = reader.fill_buf().map_err(|e| {
+                if device::is_device_loss(&e) {
+                    device::device_disconnected(&segment_path, &e)
+                } else {
+                    format!("Read error in {}: {}", segment_path.display(), e)
+                }
+            })?;
+            let len = block.len();
+            for hasher in &mut hashers {
+                hasher.update(block);
+            }
+            if let Some(pending) = chunks.get(next) {
+                if window.is_empty() {
+                    window_start = pending.offset.max(position);
+                }
+                if window_start < position + len as u64 {
+                    window.extend_from_slice(&block[(window_start.max(position) - position) as usize..]);
+                }
+            }
+            reader.consume(len);
+            position += len as u64;
+            let eof = len == 0;
+
+            // Decode the chunks whose stored bytes are all in
+            let ready = chunks[next..].iter()
+                .take_while(|chunk| eof || chunk.end <= position)
+                .count();
+            let decoded: Vec<_> = chunks[next..next + ready].par_iter()
+                .map(|chunk| {
+                    let from = chunk.offset.saturating_sub(window_start) as usize;
+                    let to = (chunk.end.min(position).saturating_sub(window_start) as usize).min(window.len());
+                    decode_chunk(window.get(from..to).unwrap_or_default(), chunk.compressed, chunk_size)
+                })
+                .collect();
+            for (chunk, data) in chunks[next..next + ready].iter().zip(decoded) {
+                let mut data = data.map_err(|(kind, message)| {
+                    handle.chunk_error(chunk.index, kind, message, Some((segment, chunk.offset)), chunk.compressed).to_string()
+                })?;
+                if chunk.index == last_chunk {
+                    data.truncate(handle.chunk_data_size(chunk.index));
+                }
+                image.push(chunk.index, data, &handle)?;
+            }
+            next += ready;
+
+            match chunks.get(next) {
+                Some(pending) if pending.offset < window_start + window.len() as u64 => {
+                    window.drain(..(pending.offset - window_start) as usize);
+                    window_start = pending.offset;
+                }
+                _ => window.clear(),
+            }
+
+            bytes_done += len as u64;
+            if bytes_done - last_report >= PROGRESS_BYTES {
+                progress(bytes_done, bytes_total);
+                last_report = bytes_done;
+            }
+            if eof {
+                break;
+            }
+        }
+
+        let segment_name = segment_path.file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| format!("segment_{}", segment + 1));
+        debug!(segment = %segment_name, bytes = position, "Segment hashed");
+        segments.push(SegmentFileHashes {
+            segment_name,
+            segment_number: segment as u32 + 1,
+            segment_path: segment_path.to_string_lossy().to_string(),
+            size: position,
+            hashes: hashers.into_iter().map(StreamingHasher::finalize).collect(),
+            duration_secs: started.elapsed().as_secs_f64(),
+        });
+    }
+    progress(bytes_total, bytes_total);
+
+    if image.next < chunk_count {
+        return Err(format!("Chunk {} was never read from the segment files", image.next));
+    }
+    Ok(EwfFullHashes {
+        algorithms: algos.iter().map(|algo| algo.name().to_string()).collect(),
+        image_hashes: image.hashers.into_iter().map(StreamingHasher::finalize).collect(),
+        segments,
+        chunk_count,
+        stored_hashes: handle.stored_hashes.clone(),
+    })
+}
+
+/// Decoded chunks on their way to the image hashers, which take them in
+/// chunk order
+///
+/// A chunk stored ahead of its predecessors waits in `pending`; with the
+/// usual layout that is a handful of chunks, at worst the chunks of the
+/// segments read before the one holding the missing chunk.
+struct ImageStream {
+    hashers: Vec<StreamingHasher>,
+    /// First chunk not hashed yet
+    next: usize,
+    pending: BTreeMap<usize, Vec<u8>>,
+}
+
+impl ImageStream {
+    fn new(algos: &[HashAlgorithm]) -> Self {
+        Self {
+            hashers: algos.iter().map(|&algo| StreamingHasher::new(algo)).collect(),
+            next: 0,
+            pending: BTreeMap::new(),
+        }
+    }
+
+    fn push(&mut self, index: usize, data: Vec<u8>, handle: &EwfHandle) -> Result<(), String> {
+        self.pending.insert(index, data);
+        self.advance(handle)
+    }
+
+    /// Hash chunks for as long as the next one is at hand - decoded, or not
+    /// stored and so all zeros
+    fn advance(&mut self, handle: &EwfHandle) -> Result<(), String> {
+        while self.next < handle.get_chunk_count() {
+            let data = match handle.chunk_storage(self.next)? {
+                ChunkStorage::Zeros(len) => vec![0u8; len],
+                ChunkStorage::Stored { .. } => match self.pending.remove(&self.next) {
+                    Some(data) => data,
+                    None => break,
+                },
+            };
+            for hasher in &mut self.hashers {
+                hasher.update(&data);
+            }
+            self.next += 1;
+        }
+        Ok(())
+    }
+}
+
+/// Chunk data from its stored bytes, classified like the chunk reader's errors
+fn decode_chunk(stored: &[u8], compressed: bool, chunk_size: usize) -> Result<Vec<u8>, (ChunkErrorKind, String)> {
+    if !compressed {
+        return stored.get(..chunk_size)
+            .map(<[u8]>::to_vec)
+            .ok_or_else(|| (ChunkErrorKind::ShortRead, format!("segment ends {} bytes into the chunk", stored.len())));
+    }
+    inflate_bounded_counted(stored, chunk_size, chunk_size + INFLATE_SLACK)
+        .map(|(data, _)| data)
+        .map_err(|e| {
+            let kind = match &e {
+                InflateError::Oversized { .. } => ChunkErrorKind::CorruptStructure,
+                InflateError::Io(io) if io.kind() == std::io::ErrorKind::UnexpectedEof => ChunkErrorKind::ShortRead,
+                InflateError::Io(_) => ChunkErrorKind::Decompression,
+            };
+            (kind, format!("decompression failed: {}", e))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use crate::common::hash::compute_hash_str;
+    use crate::ewf::{hash_single_segment, verify};
+
+    const SECTOR: usize = 512;
+    /// Two sectors per chunk
+    const CHUNK: usize = 2 * SECTOR;
+    /// Eleven sectors: six chunks, the last one half media
+    const SECTORS: usize = 11;
+
+    /// Append a section of type `kind`; "next" and "done" link to themselves
+    fn section(image: &mut Vec<u8>, kind: &str, data: &[u8]) {
+        let start = image.len() as u64;
+        let size = 76 + data.len() as u64;
+        let next = if kind == "next" || kind == "done" { start } else { start + size };
+        let mut descriptor = [0u8; 76];
+        descriptor[..kind.len()].copy_from_slice(kind.as_bytes());
+        descriptor[16..24].copy_from_slice(&next.to_le_bytes());
+        descriptor[24..32].copy_from_slice(&size.to_le_bytes());
+        image.extend_from_slice(&descriptor);
+        image.extend_from_slice(data);
+    }
+
+    /// One segment file holding the chunks `stored` (chunk, compressed) in
+    /// that order; its table lists them in chunk order
+    fn segment(number: u16, media: &[u8], stored: &[(usize, bool)], last: bool) -> Vec<u8> {
+        let mut image = b"EVF\x09\x0d\x0a\xff\x00\x01".to_vec();
+        image.extend_from_slice(&number.to_le_bytes());
+        image.extend_from_slice(&[0, 0]);
+        if number == 1 {
+            let mut volume = vec![0u8; 1052];
+            volume[4..8].copy_from_slice(&(SECTORS.div_ceil(2) as u32).to_le_bytes());
+            volume[8..12].copy_from_slice(&2u32.to_le_bytes());
+            volume[12..16].copy_from_slice(&(SECTOR as u32).to_le_bytes());
+            volume[16..24].copy_from_slice(&(SECTORS as u64).to_le_bytes());
+            section(&mut image, "volume", &volume);
+        }
+
+        let sectors_start = image.len() as u32 + 76;
+        let mut sectors = Vec::new();
+        let mut offsets = Vec::new();
+        for &(chunk, compressed) in stored {
+            let data = &media[chunk * CHUNK..((chunk + 1) * CHUNK).min(media.len())];
+            let offset = sectors_start + sectors.len() as u32;
+            if compressed {
+                let mut encoder = flate2::write::ZlibEncoder::new(&mut sectors, flate2::Compression::default());
+                encoder.write_all(data).unwrap();
+                encoder.finish().unwrap();
+                offsets.push((chunk, offset | 0x8000_0000));
+            } else {
+                sectors.extend_from_slice(data);
+                offsets.push((chunk, offset));
+            }
+        }
+        section(&mut image, "sectors", &sectors);
+
+        offsets.sort();
+        let mut table = vec![0u8; 24];
+        table[0..4].copy_from_slice(&(offsets.len() as u32).to_le_bytes());
+        for (_, offset) in offsets {
+            table.extend_from_slice(&offset.to_le_bytes());
+        }
+        table.extend_from_slice(&[0u8; 4]);
+        section(&mut image, "table", &table);
+        if last {
+            let md5 = hex::decode(compute_hash_str(media, "md5").unwrap()).unwrap();
+            section(&mut image, "hash", &[md5, vec![0u8; 20]].concat());
+            section(&mut image, "done", &[]);
+        } else {
+            section(&mut image, "next", &[]);
+        }
+        image
+    }
+
+    #[test]
+    fn test_one_pass_matches_separate_image_and_segment_hashes() {
+        let media: Vec<u8> = (0..SECTORS * SECTOR).map(|i| (i * 7 + i / CHUNK) as u8).collect();
+        let dir = tempfile::tempdir().unwrap();
+        let (first, second) = (dir.path().join("disk.E01"), dir.path().join("disk.E02"));
+        // Chunks stored out of chunk order in both segments
+        std::fs::write(&first, segment(1, &media, &[(1, true), (0, false), (2, true)], false)).unwrap();
+        std::fs::write(&second, segment(2, &media, &[(4, false), (5, true), (3, true)], true)).unwrap();
+        let path = first.to_str().unwrap();
+        let algorithms = vec!["md5".to_string(), "sha256".to_string()];
+
+        // Buffers cutting through stored chunks, and one holding a whole segment
+        for buffer_size in [700, 1 << 20] {
+            let hashes = full_hash_buffered(path, &algorithms, buffer_size, |_, _| {}).unwrap();
+            assert_eq!(hashes.algorithms, ["MD5", "SHA-256"]);
+            for (algorithm, hash) in algorithms.iter().zip(&hashes.image_hashes) {
+                assert_eq!(hash, &verify(path, algorithm).unwrap(), "{algorithm} with {buffer_size} byte buffers");
+                assert_eq!(hash, &compute_hash_str(&media, algorithm).unwrap());
+            }
+            assert_eq!(hashes.segments.len(), 2);
+            for segment in &hashes.segments {
+                for (algorithm, hash) in algorithms.iter().zip(&segment.hashes) {
+                    assert_eq!(hash, &hash_single_segment(&segment.segment_path, algorithm, |_, _| {}).unwrap());
+                }
+            }
+        }
+
+        let report = crate::verification::verify_e01_full(path, &algorithms, |_, _| {}).unwrap();
+        let checks: Vec<_> = report.image_hashes.iter().map(|check| (check.verified, check.expected_source.as_deref())).collect();
+        assert_eq!(checks, [(Some(true), Some("embedded")), (None, None)]);
+        let segments: Vec<_> = report.segments.iter().map(|result| (result.segment_name.as_str(), result.algorithm.as_str())).collect();
+        assert_eq!(segments, [("disk.E01", "MD5"), ("disk.E01", "SHA-256"), ("disk.E02", "MD5"), ("disk.E02", "SHA-256")]);
+    }
+}
@@ -1,26 +1,42 @@
 //! EwfHandle - Main interface for EWF file access (E01/L01/Ex01/Lx01)
 //! Similar to libewf_handle in libewf
 
-use std::fs::File;
-use std::io::{Read, Seek, SeekFrom};
+use std::io::{Read, SeekFrom};
+use std::path::PathBuf;
 use std::sync::Arc;
-use flate2::read::ZlibDecoder;
-use tracing::{trace, debug, instrument};
+use tracing::{trace, debug, instrument, warn};
 
 use crate::common::{
-    FileIoPool,
-    binary::{read_u32_le, read_u64_le},
+    FileIoPool, SegmentOpener, SegmentRead,
+    binary::{read_up_to, ByteReader, Truncated},
+    device,
+    inflate::{check_chunk_size, inflate_bounded, inflate_bounded_counted, InflateError, INFLATE_SLACK},
+    parse_warning::ParseWarning,
+    resources,
     segments::discover_e01_segments,
 };
+use crate::logging::perf;
 
 use super::types::*;
+use super::header::HeaderValues;
 use super::cache::ChunkCache;
 
+/// Decompressed chunks kept per handle
+const CHUNK_CACHE: usize = 256;
+/// Cached chunks in low-memory mode
+const LOW_MEMORY_CHUNK_CACHE: usize = 16;
+
 // =============================================================================
 // EWF Handle - Main Interface (like libewf_handle)
 // =============================================================================
 
 /// Handle for EWF format files (E01, L01, Ex01, Lx01)
+///
+/// A handle is `Send` but not `Sync`: its chunk cache and segment file pool
+/// are unsynchronized and every read takes `&mut self`, so it belongs to one
+/// thread at a time. Concurrent work on the same image opens a handle per
+/// thread, as the parallel verifier does; handles share nothing but the
+/// read-only files on disk.
 pub struct EwfHandle {
     /// File I/O pool managing all segment files
     pub(crate) file_pool: FileIoPool,
@@ -36,30 +52,78 @@ pub struct EwfHandle {
     pub(crate) stored_hashes: Vec<StoredImageHash>,
     /// Header metadata (case info, examiner, dates, etc.)
     pub(crate) header_info: HeaderInfo,
+    /// Optical-media sessions from the session section (empty for disks)
+    pub(crate) sessions: Vec<SessionInfo>,
+    /// Sectors the acquisition could not read, from the error2 section
+    pub(crate) acquisition_errors: Vec<SectorRange>,
+    /// Where a best-effort open stopped walking a damaged section chain
+    pub(crate) parse_warnings: Vec<ParseWarning>,
     /// Pre-computed segment boundary cumulative offsets for fast lookup
     segment_cumulative_sizes: Vec<u64>,
+    /// Mount point of the device holding the segments; its handles are
+    /// released when the device goes away
+    evidence_mount: PathBuf,
+    /// Storage counters of uncached chunk reads, once enabled
+    chunk_stats: Option<ChunkStats>,
 }
 
 impl EwfHandle {
     /// Open EWF file set (like libewf_handle_open)
     #[instrument(skip_all, fields(path))]
     pub fn open(path: &str) -> Result<Self, String> {
+        Self::open_with(path, None, false)
+    }
+
+    /// Open a set from its segment files in order, wherever they are (see
+    /// [`super::with_segment_list`])
+    pub fn open_with_segments(paths: Vec<String>) -> Result<Self, String> {
+        let first = paths.first().cloned().unwrap_or_default();
+        super::with_segment_list(&first, &paths, || Self::open(&first))
+    }
+
+    /// Open even if the section chain breaks off, keeping the sections read
+    /// before the damage (recorded in `parse_warnings`)
+    ///
+    /// Opening still fails if no volume section was reached.
+    #[instrument(skip_all, fields(path))]
+    pub fn open_best_effort(path: &str) -> Result<Self, String> {
+        Self::open_with(path, None, true)
+    }
+
+    /// Open with segment files read through `opener` (fault injection in tests)
+    #[cfg(test)]
+    pub(crate) fn open_with_opener(path: &str, opener: SegmentOpener) -> Result<Self, String> {
+        Self::open_with(path, Some(opener), false)
+    }
+
+    fn open_with(path: &str, opener: Option<SegmentOpener>, best_effort: bool) -> Result<Self, String> {
         debug!(path, "Opening EWF handle");
         
+        // A later segment is only readable together with the set's first one
+        if let Some(orphan) = super::operations::find_orphaned_segment(path) {
+            return Err(orphan.to_string());
+        }
+        
         // Step 1: Discover all segment files (like libewf_glob)
         let segment_paths = discover_e01_segments(path)?;
         debug!(segment_count = segment_paths.len(), "Discovered EWF segments");
+        let evidence_mount = segment_paths.first()
+            .and_then(|first| device::mount_point(first).ok()
+                .or_else(|| first.parent().map(PathBuf::from)))
+            .unwrap_or_default();
         
         // Step 2: Create file I/O pool
-        let mut file_pool = FileIoPool::new(segment_paths, MAX_OPEN_FILES);
+        let mut file_pool = match opener {
+            Some(opener) => FileIoPool::with_opener(segment_paths, MAX_OPEN_FILES, opener),
+            None => FileIoPool::new(segment_paths, MAX_OPEN_FILES),
+        };
         
         // Step 3: Get segment file sizes for global offset conversion
         let mut segment_sizes = Vec::new();
         for i in 0..file_pool.get_file_count() {
             let file = file_pool.get_file(i)?;
-            let size = file.metadata()
-                .map_err(|e| format!("Failed to get metadata: {}", e))?
-                .len();
+            let size = file.size()
+                .map_err(|e| format!("Failed to get metadata: {}", e))?;
             segment_sizes.push(size);
         }
         
@@ -72,9 +136,19 @@ impl EwfHandle {
         }
         
         // Step 4: Parse sections globally (not per-segment!)
-        let (segments, volume_info, chunk_table, stored_hashes, header_info) = Self::parse_sections_globally(&mut file_pool, &segment_sizes)?;
-        
-        let volume = volume_info.ok_or("No volume section found")?;
+        let (segments, volume_info, chunk_table, stored_hashes, header_info, parse_warnings) = perf::phase(perf::PARSING)
+            .in_scope(|| Self::parse_sections_globally(&mut file_pool, &segment_sizes, best_effort))?;
+        
+        let mut volume = volume_info.ok_or("No volume section found")?;
+        // Every chunk buffer is sized from this - reject absurd values up front
+        check_chunk_size(
+            volume.sectors_per_chunk as u64 * volume.bytes_per_sector as u64,
+            "volume chunk size (sectors per chunk x bytes per sector)",
+        )?;
+        // Some SMART writers emit an EnCase-sized volume section - trust the extension too
+        if !volume.is_smart && is_smart_extension(path) {
+            volume.is_smart = true;
+        }
         debug!(
             chunk_count = volume.chunk_count,
             sectors_per_chunk = volume.sectors_per_chunk,
@@ -82,8 +156,29 @@ impl EwfHandle {
             "EWF volume info parsed"
         );
         
+        // Step 4b: Optical media describes its track layout in a session section
+        let sessions = match Self::read_sessions(&mut file_pool, &segments, volume.sector_count) {
+            Ok(sessions) => sessions,
+            Err(e) => {
+                debug!("Ignoring session section: {}", e);
+                Vec::new()
+            }
+        };
+        
+        // Step 4c: Sectors the source drive failed to deliver were zero-filled
+        let acquisition_errors = match Self::read_acquisition_errors(&mut file_pool, &segments) {
+            Ok(errors) => errors,
+            Err(e) => {
+                warn!("Ignoring error2 section: {}", e);
+                Vec::new()
+            }
+        };
+        if !acquisition_errors.is_empty() {
+            debug!(ranges = acquisition_errors.len(), "Image records acquisition read errors");
+        }
+        
         // Step 5: Create chunk cache
-        let chunk_cache = ChunkCache::new(256); // Cache last 256 chunks
+        let chunk_cache = ChunkCache::new(if resources::low_memory() { LOW_MEMORY_CHUNK_CACHE } else { CHUNK_CACHE }); // Cache the last chunks read
         
         debug!(
             chunk_table_size = chunk_table.len(),
@@ -99,15 +194,25 @@ impl EwfHandle {
             chunk_cache,
             stored_hashes,
             header_info,
+            sessions,
+            acquisition_errors,
+            parse_warnings,
             segment_cumulative_sizes,
+            evidence_mount,
+            chunk_stats: None,
         })
     }
 
     /// Parse sections globally across all segments (next_offset is global!)
+    ///
+    /// With `best_effort`, a structural error ends the walk with a warning
+    /// instead of failing it.
+    #[allow(clippy::type_complexity)]
     fn parse_sections_globally(
         file_pool: &mut FileIoPool,
         segment_sizes: &[u64],
-    ) -> Result<(Vec<SegmentFile>, Option<VolumeSection>, Vec<ChunkLocation>, Vec<StoredImageHash>, HeaderInfo), String> {
+        best_effort: bool,
+    ) -> Result<(Vec<SegmentFile>, Option<VolumeSection>, Vec<ChunkLocation>, Vec<StoredImageHash>, HeaderInfo, Vec<ParseWarning>), String> {
         // Initialize segment file structures
         let mut segments: Vec<SegmentFile> = (0..file_pool.get_file_count())
             .map(|i| SegmentFile {
@@ -121,7 +226,9 @@ impl EwfHandle {
         let mut volume_info: Option<VolumeSection> = None;
         let mut chunk_locations = Vec::new();
         let mut stored_hashes: Vec<StoredImageHash> = Vec::new();
-        let mut header_info = HeaderInfo::default();
+        // header2 (EnCase 6+) supersedes header; keep the first of each
+        let mut header_values: Option<HeaderValues> = None;
+        let mut header2_values: Option<HeaderValues> = None;
         
         // Track sectors section for delta chunk scanning
         let mut sectors_data_offset: Option<u64> = None;
@@ -131,171 +238,210 @@ impl EwfHandle {
         let mut current_global_offset = 13u64;
         let mut last_sectors_offset: Option<u64> = None;
         let mut section_count = 0;
-        const MAX_SECTIONS: u32 = 10000;
         
         trace!("Starting global section walk...");
         
-        loop {
-            if section_count >= MAX_SECTIONS {
-                trace!("Reached max sections limit");
-                break;
-            }
-            section_count += 1;
-            
-            // Convert global offset to (segment_index, offset_in_segment)
-            let (mut seg_idx, offset_in_seg) = Self::global_to_segment_offset(current_global_offset, segment_sizes)?;
+        // Run as a closure so a structural error can end the walk without
+        // discarding what was parsed before it (best effort)
+        let walked = (|| -> Result<(), String> {
+            loop {
+                if section_count >= MAX_SECTIONS {
+                    return Err(format!("Corrupt section chain: more than {} sections", MAX_SECTIONS));
+                }
+                section_count += 1;
             
-            // Check if we have enough space for section descriptor
-            if offset_in_seg + 32 > segment_sizes[seg_idx] {
-                trace!("Not enough space for section descriptor at global offset {}", current_global_offset);
-                break;
-            }
+                // Convert global offset to (segment_index, offset_in_segment)
+                let (mut seg_idx, offset_in_seg) = Self::global_to_segment_offset(current_global_offset, segment_sizes)?;
             
-            // Read section descriptor
-            let file = file_pool.get_file(seg_idx)?;
-            let section_desc = match Self::read_section_descriptor(file, offset_in_seg) {
-                Ok(desc) => desc,
-                Err(e) => {
-                    trace!("Failed to read section at global offset {}: {}", current_global_offset, e);
+                // Check if we have enough space for section descriptor
+                if offset_in_seg + 32 > segment_sizes[seg_idx] {
+                    trace!("Not enough space for section descriptor at global offset {}", current_global_offset);
                     break;
                 }
-            };
             
-            let section_type = String::from_utf8_lossy(&section_desc.section_type)
-                .trim_matches('\0')
-                .to_string();
+                // Read section descriptor
+                let file = file_pool.get_file(seg_idx)?;
+                let section_desc = match Self::read_section_descriptor(file, offset_in_seg) {
+                    Ok(desc) => desc,
+                    Err(e) => {
+                        trace!("Failed to read section at global offset {}: {}", current_global_offset, e);
+                        break;
+                    }
+                };
             
-            trace!("Section '{}' at global offset {} (seg {}, offset {})", 
-                     section_type, current_global_offset, seg_idx, offset_in_seg);
+                let section_type = String::from_utf8_lossy(&section_desc.section_type)
+                    .trim_matches('\0')
+                    .to_string();
             
-            // Create section entry
-            let mut seg_section = SegmentSection {
-                section_type: section_type.clone(),
-                offset_in_segment: offset_in_seg,
-                size: section_desc.size,
-                data_offset: None,
-                table_data: None,
-            };
+                trace!("Section '{}' at global offset {} (seg {}, offset {})", 
+                         section_type, current_global_offset, seg_idx, offset_in_seg);
+            
+                // Create section entry
+                let mut seg_section = SegmentSection {
+                    section_type: section_type.clone(),
+                    offset_in_segment: offset_in_seg,
+                    size: section_desc.size,
+                    data_offset: None,
+                    table_data: None,
+                };
             
-            // Handle different section types
-            match section_type.as_str() {
-                "header" => {
-                    // Header section contains zlib-compressed case metadata
-                    let data_global_offset = current_global_offset + 76;
-                    let (data_seg_idx, data_offset_in_seg) = Self::global_to_segment_offset(data_global_offset, segment_sizes)?;
-                    seg_section.data_offset = Some(data_global_offset);
+                // Handle different section types
+                match section_type.as_str() {
+                    "header" | "header2" => {
+                        // Header sections contain zlib-compressed case metadata
+                        let data_global_offset = current_global_offset + 76;
+                        let (data_seg_idx, data_offset_in_seg) = Self::global_to_segment_offset(data_global_offset, segment_sizes)?;
+                        seg_section.data_offset = Some(data_global_offset);
                     
-                    // Only parse first header section
-                    if header_info.acquiry_date.is_none() {
-                        if let Ok(parsed_header) = Self::read_header_section(file_pool, data_seg_idx, data_offset_in_seg, section_desc.size.saturating_sub(76)) {
-                            trace!("  Parsed header: case={:?} examiner={:?} acquiry_date={:?}", 
-                                     parsed_header.case_number, parsed_header.examiner_name, parsed_header.acquiry_date);
-                            header_info = parsed_header;
+                        let is_header2 = section_type == "header2";
+                        let slot = if is_header2 { &mut header2_values } else { &mut header_values };
+                        if slot.is_none() {
+                            if let Ok(values) = Self::read_header_section(file_pool, data_seg_idx, data_offset_in_seg, section_desc.size.saturating_sub(76), is_header2) {
+                                for (name, value) in values.named() {
+                                    trace!("  {}: {} = {}", section_type, name, value);
+                                }
+                                *slot = Some(values);
+                            }
                         }
                     }
-                }
-                "volume" | "disk" => {
-                    let data_global_offset = current_global_offset + 76;
-                    let (data_seg_idx, data_offset_in_seg) = Self::global_to_segment_offset(data_global_offset, segment_sizes)?;
-                    seg_section.data_offset = Some(data_global_offset);
+                    "volume" | "disk" => {
+                        let data_global_offset = current_global_offset + 76;
+                        let (data_seg_idx, data_offset_in_seg) = Self::global_to_segment_offset(data_global_offset, segment_sizes)?;
+                        seg_section.data_offset = Some(data_global_offset);
                     
-                    if volume_info.is_none() {
-                        volume_info = Some(Self::read_volume_section(file_pool, data_seg_idx, data_offset_in_seg)?);
+                        if volume_info.is_none() {
+                            let data_size = section_desc.size.saturating_sub(76);
+                            volume_info = Some(Self::read_volume_section(file_pool, data_seg_idx, data_offset_in_seg, data_size)?);
+                        }
                     }
-                }
-                "sectors" => {
-                    let data_global_offset = current_global_offset + 76;
-                    seg_section.data_offset = Some(data_global_offset);
-                    last_sectors_offset = Some(data_global_offset);
+                    "sectors" => {
+                        let data_global_offset = current_global_offset + 76;
+                        seg_section.data_offset = Some(data_global_offset);
+                        last_sectors_offset = Some(data_global_offset);
                     
-                    sectors_data_offset = Some(data_global_offset);
-                    sectors_data_size = Some(section_desc.size.saturating_sub(76));
-                }
-                "table" => {
-                    let data_global_offset = current_global_offset + 76;
-                    let (data_seg_idx, data_offset_in_seg) = Self::global_to_segment_offset(data_global_offset, segment_sizes)?;
-                    seg_section.data_offset = Some(data_global_offset);
+                        sectors_data_offset = Some(data_global_offset);
+                        sectors_data_size = Some(section_desc.size.saturating_sub(76));
+                    }
+                    "table" => {
+                        let data_global_offset = current_global_offset + 76;
+                        let (data_seg_idx, data_offset_in_seg) = Self::global_to_segment_offset(data_global_offset, segment_sizes)?;
+                        seg_section.data_offset = Some(data_global_offset);
+                    
+                        // SMART (EWF-S01) has no "sectors" section: chunk data follows the
+                        // offset array inside the table section, and the offsets count from
+                        // the start of the table data instead of the segment file
+                        let is_smart = volume_info.as_ref().is_some_and(|v| v.is_smart);
+                        let (sectors_base, offsets_base) = if is_smart {
+                            (Some(data_global_offset), data_offset_in_seg)
+                        } else {
+                            (last_sectors_offset, 0)
+                        };
                     
-                    if let Some(sectors_base) = last_sectors_offset {
-                        trace!("  Reading {} at seg {} offset {}, sectors_base={}", section_type, data_seg_idx, data_offset_in_seg, sectors_base);
-                        let file = file_pool.get_file(data_seg_idx)?;
-                        if let Ok(table) = Self::read_table_section(file, data_offset_in_seg, section_desc.size, sectors_base) {
-                            trace!("  Table has {} chunk offsets, base_offset={}", table.offsets.len(), table.base_offset);
-                            for (chunk_in_table, &offset) in table.offsets.iter().enumerate() {
-                                chunk_locations.push(ChunkLocation {
-                                    segment_index: seg_idx,
-                                    section_index: segments[seg_idx].sections.len(),
-                                    chunk_in_table,
-                                    offset,
-                                    base_offset: table.base_offset,
-                                    sectors_base,
-                                    is_delta_chunk: false,
-                                });
+                        if let Some(sectors_base) = sectors_base {
+                            trace!("  Reading {} at seg {} offset {}, sectors_base={}", section_type, data_seg_idx, data_offset_in_seg, sectors_base);
+                            let file = file_pool.get_file(data_seg_idx)?;
+                            if let Ok(table) = Self::read_table_section(file, data_offset_in_seg, section_desc.size, offsets_base) {
+                                trace!("  Table has {} chunk offsets, base_offset={}", table.offsets.len(), table.base_offset);
+                                for (chunk_in_table, &offset) in table.offsets.iter().enumerate() {
+                                    chunk_locations.push(ChunkLocation {
+                                        segment_index: seg_idx,
+                                        section_index: segments[seg_idx].sections.len(),
+                                        chunk_in_table,
+                                        offset,
+                                        base_offset: table.base_offset,
+                                        sectors_base,
+                                        is_delta_chunk: false,
+                                    });
+                                }
+                                seg_section.table_data = Some(table);
+                            } else {
+                                trace!("  Failed to read table section");
                             }
-                            seg_section.table_data = Some(table);
                         } else {
-                            trace!("  Failed to read table section");
+                            trace!("  Skipping {} - no sectors_base set", section_type);
                         }
-                    } else {
-                        trace!("  Skipping {} - no sectors_base set", section_type);
                     }
-                }
-                "table2" => {
-                    trace!("  Skipping table2 section (contains checksums)");
-                }
-                "hash" => {
-                    let data_global_offset = current_global_offset + 76;
-                    let (data_seg_idx, data_offset_in_seg) = Self::global_to_segment_offset(data_global_offset, segment_sizes)?;
+                    "table2" => {
+                        trace!("  Skipping table2 section (contains checksums)");
+                    }
+                    "hash" => {
+                        let data_global_offset = current_global_offset + 76;
+                        let (data_seg_idx, data_offset_in_seg) = Self::global_to_segment_offset(data_global_offset, segment_sizes)?;
                     
-                    if let Ok(hashes) = Self::read_hash_section(file_pool, data_seg_idx, data_offset_in_seg) {
-                        trace!("  Found {} hashes in hash section", hashes.len());
-                        stored_hashes.extend(hashes);
+                        if let Ok(hashes) = Self::read_hash_section(file_pool, data_seg_idx, data_offset_in_seg) {
+                            trace!("  Found {} hashes in hash section", hashes.len());
+                            stored_hashes.extend(hashes);
+                        }
                     }
-                }
-                "digest" => {
-                    let data_global_offset = current_global_offset + 76;
-                    let (data_seg_idx, data_offset_in_seg) = Self::global_to_segment_offset(data_global_offset, segment_sizes)?;
+                    "digest" => {
+                        let data_global_offset = current_global_offset + 76;
+                        let (data_seg_idx, data_offset_in_seg) = Self::global_to_segment_offset(data_global_offset, segment_sizes)?;
                     
-                    if let Ok(hashes) = Self::read_digest_section(file_pool, data_seg_idx, data_offset_in_seg, section_desc.size) {
-                        trace!("  Found {} hashes in digest section", hashes.len());
-                        stored_hashes.extend(hashes);
+                        if let Ok(hashes) = Self::read_digest_section(file_pool, data_seg_idx, data_offset_in_seg, section_desc.size) {
+                            trace!("  Found {} hashes in digest section", hashes.len());
+                            stored_hashes.extend(hashes);
+                        }
                     }
-                }
-                "done" => {
-                    segments[seg_idx].sections.push(seg_section);
-                    trace!("Reached 'done' section, stopping");
-                    break;
-                }
-                "next" => {
-                    if section_desc.next_offset == current_global_offset {
-                        if seg_idx + 1 < segments.len() {
-                            seg_idx += 1;
-                            let next_segment_start: u64 = segment_sizes.iter().take(seg_idx).sum();
-                            current_global_offset = next_segment_start + 13;
-                            trace!("Moving to segment {} at global offset {}", seg_idx, current_global_offset);
-                            continue;
-                        } else {
-                            trace!("No more segments, stopping");
-                            break;
+                    "done" => {
+                        segments[seg_idx].sections.push(seg_section);
+                        trace!("Reached 'done' section, stopping");
+                        break;
+                    }
+                    "next" => {
+                        // next_offset is segment-relative; "next" points to itself
+                        let segment_start: u64 = segment_sizes.iter().take(seg_idx).sum();
+                        if segment_start + section_desc.next_offset == current_global_offset {
+                            if seg_idx + 1 < segments.len() {
+                                seg_idx += 1;
+                                let next_segment_start: u64 = segment_sizes.iter().take(seg_idx).sum();
+                                current_global_offset = next_segment_start + 13;
+                                trace!("Moving to segment {} at global offset {}", seg_idx, current_global_offset);
+                                continue;
+                            } else {
+                                trace!("No more segments, stopping");
+                                break;
+                            }
                         }
                     }
+                    _ => {}
                 }
-                _ => {}
-            }
             
-            segments[seg_idx].sections.push(seg_section);
+                segments[seg_idx].sections.push(seg_section);
             
-            if section_desc.next_offset == 0 || section_desc.next_offset == current_global_offset {
-                trace!("Section chain ended");
-                break;
+                let segment_start: u64 = segment_sizes.iter().take(seg_idx).sum();
+                let next_global_offset = segment_start + section_desc.next_offset;
+                if section_desc.next_offset == 0 || next_global_offset == current_global_offset {
+                    trace!("Section chain ended");
+                    break;
+                }
+                // Sections only ever point forward - a backward link would loop
+                if next_global_offset < current_global_offset {
+                    return Err(format!(
+                        "Corrupt section chain: '{}' section at offset {} links back to {}",
+                        section_type, current_global_offset, next_global_offset
+                    ));
+                }
+                current_global_offset = next_global_offset;
             }
-            
-            let segment_start: u64 = segment_sizes.iter().take(seg_idx).sum();
-            current_global_offset = segment_start + section_desc.next_offset;
+            Ok(())
+        })();
+        let mut parse_warnings = Vec::new();
+        if let Err(error) = walked {
+            if !best_effort {
+                return Err(error);
+            }
+            warn!(offset = current_global_offset, error = %error, "Section walk stopped early, keeping sections parsed so far");
+            parse_warnings.push(ParseWarning { address: current_global_offset, error });
         }
         
         trace!("Parsed {} sections, {} chunk locations", section_count, chunk_locations.len());
         
+        let header_info = match (header_values, header2_values) {
+            (Some(header), Some(header2)) => HeaderInfo::from_values(&header.merge(header2)),
+            (Some(values), None) | (None, Some(values)) => HeaderInfo::from_values(&values),
+            (None, None) => HeaderInfo::default(),
+        };
+        
         // If no chunk locations were found, try delta chunk scanning
         if chunk_locations.is_empty() {
             if let (Some(vol), Some(sectors_offset), Some(sectors_size)) = 
@@ -319,7 +465,7 @@ impl EwfHandle {
             }
         }
         
-        Ok((segments, volume_info, chunk_locations, stored_hashes, header_info))
+        Ok((segments, volume_info, chunk_locations, stored_hashes, header_info, parse_warnings))
     }
     
     /// Scan for delta/inline chunks in sectors section
@@ -547,11 +693,8 @@ impl EwfHandle {
                 
                 let mut chunk_data = if is_compressed {
                     let buffered = std::io::BufReader::with_capacity(65536, file.take(chunk_size as u64 * 2));
-                    let mut decoder = ZlibDecoder::new(buffered);
-                    let mut decompressed = Vec::with_capacity(chunk_size);
-                    decoder.read_to_end(&mut decompressed)
-                        .map_err(|e| format!("Decompression failed: {}", e))?;
-                    decompressed
+                    inflate_bounded(buffered, chunk_size, chunk_size + INFLATE_SLACK)
+                        .map_err(|e| format!("Decompression of chunk {} failed: {}", chunk_idx, e))?
                 } else {
                     let mut uncompressed = vec![0u8; chunk_size];
                     file.read_exact(&mut uncompressed)
@@ -658,14 +801,103 @@ impl EwfHandle {
     /// Read a chunk by global index (like libewf_handle_read_buffer)
     pub fn read_chunk(&mut self, chunk_index: usize) -> Result<Vec<u8>, String> {
         self.read_chunk_internal(chunk_index, true)
+            .map_err(|e| e.to_string())
     }
     
     /// Read chunk without caching - optimized for sequential access patterns
     pub fn read_chunk_no_cache(&mut self, chunk_index: usize) -> Result<Vec<u8>, String> {
         self.read_chunk_internal(chunk_index, false)
+            .map_err(|e| e.to_string())
+    }
+    
+    /// Count how uncached chunk reads are stored from now on
+    pub fn collect_chunk_stats(&mut self) {
+        self.chunk_stats.get_or_insert_with(ChunkStats::default);
+    }
+    
+    /// Counters gathered since the last call, if collection is enabled
+    pub fn take_chunk_stats(&mut self) -> Option<ChunkStats> {
+        self.chunk_stats.as_mut().map(std::mem::take)
+    }
+    
+    /// Read chunk without caching, keeping the structured error on failure
+    pub fn try_read_chunk(&mut self, chunk_index: usize) -> Result<Vec<u8>, ChunkReadError> {
+        self.read_chunk_internal(chunk_index, false)
+    }
+    
+    /// Size in bytes of the given chunk once decompressed (the last chunk may be short)
+    pub fn chunk_data_size(&self, chunk_index: usize) -> usize {
+        let chunk_size = self.get_chunk_size() as u64;
+        let media_size = self.get_media_size();
+        let start = chunk_index as u64 * chunk_size;
+        (start + chunk_size).min(media_size).saturating_sub(start) as usize
     }
     
-    fn read_chunk_internal(&mut self, chunk_index: usize, use_cache: bool) -> Result<Vec<u8>, String> {
+    /// Where a chunk is stored, without reading it
+    ///
+    /// Chunks missing from the table and table entries without an offset
+    /// are zeros, as [`read_chunk`](Self::read_chunk) returns them.
+    pub(crate) fn chunk_storage(&self, chunk_index: usize) -> Result<ChunkStorage, String> {
+        let chunk_size = self.get_chunk_size() as usize;
+        let Some(location) = self.chunk_table.get(chunk_index) else {
+            return Ok(ChunkStorage::Zeros(self.chunk_data_size(chunk_index)));
+        };
+        if location.offset == 0 && location.sectors_base == 0 {
+            return Ok(ChunkStorage::Zeros(chunk_size));
+        }
+        let compressed = (location.offset & 0x80000000) != 0;
+        let global_offset = if location.is_delta_chunk {
+            location.sectors_base + 4
+        } else {
+            self.segment_cumulative_sizes[location.segment_index] + location.base_offset + (location.offset & 0x7FFFFFFF)
+        };
+        let (segment, offset) = self.global_to_segment_fast(global_offset)
+            .map_err(|e| format!("Chunk {}: {}", chunk_index, e))?;
+        Ok(ChunkStorage::Stored { segment, offset, compressed })
+    }
+    
+    /// Path of the segment file at `segment` (an index into the set)
+    pub(crate) fn segment_path(&self, segment: usize) -> Option<&PathBuf> {
+        self.segments.get(segment).and_then(|seg| self.file_pool.get_path(seg.file_index))
+    }
+    
+    /// Build a chunk error annotated with the media range the chunk covers
+    pub(super) fn chunk_error(
+        &self,
+        chunk_index: usize,
+        kind: ChunkErrorKind,
+        message: String,
+        stored_at: Option<(usize, u64)>,
+        compressed: bool,
+    ) -> ChunkReadError {
+        let bytes_per_sector = self.volume.bytes_per_sector.max(1) as u64;
+        let byte_start = chunk_index as u64 * self.get_chunk_size() as u64;
+        let byte_end = byte_start + self.chunk_data_size(chunk_index).max(1) as u64;
+        let (segment_path, segment_offset) = match stored_at {
+            Some((seg_idx, offset)) => (
+                self.segments.get(seg_idx)
+                    .and_then(|seg| self.file_pool.get_path(seg.file_index))
+                    .map(|p| p.to_string_lossy().to_string()),
+                Some(offset),
+            ),
+            None => (None, None),
+        };
+        
+        ChunkReadError {
+            chunk_index,
+            byte_start,
+            byte_end,
+            sector_start: byte_start / bytes_per_sector,
+            sector_end: (byte_end - 1) / bytes_per_sector,
+            segment_path,
+            segment_offset,
+            compressed,
+            kind,
+            message,
+        }
+    }
+    
+    fn read_chunk_internal(&mut self, chunk_index: usize, use_cache: bool) -> Result<Vec<u8>, ChunkReadError> {
         // Check cache first
         if use_cache {
             if let Some(cached_data) = self.chunk_cache.get(chunk_index) {
@@ -680,7 +912,13 @@ impl EwfHandle {
             None => {
                 let expected_chunks = self.volume.chunk_count as usize;
                 if chunk_index >= expected_chunks {
-                    return Err(format!("Chunk {} beyond expected count {}", chunk_index, expected_chunks));
+                    return Err(self.chunk_error(
+                        chunk_index,
+                        ChunkErrorKind::Location,
+                        format!("beyond expected count {}", expected_chunks),
+                        None,
+                        false,
+                    ));
                 }
                 
                 let final_chunk_size = if chunk_index == expected_chunks - 1 {
@@ -694,30 +932,42 @@ impl EwfHandle {
                     chunk_size
                 };
                 
+                if let (false, Some(stats)) = (use_cache, &mut self.chunk_stats) {
+                    stats.record_sparse(final_chunk_size);
+                }
                 return Ok(vec![0u8; final_chunk_size]);
             }
         };
         
         if location.offset == 0 && location.sectors_base == 0 {
+            if let (false, Some(stats)) = (use_cache, &mut self.chunk_stats) {
+                stats.record_sparse(chunk_size);
+            }
             return Ok(vec![0u8; chunk_size]);
         }
         
+        let is_compressed = (location.offset & 0x80000000) != 0;
+        
         // Use fast lookup with pre-computed cumulative sizes
-        let (seg_idx, offset_in_segment, is_compressed) = if location.is_delta_chunk {
-            let is_compressed = (location.offset & 0x80000000) != 0;
+        let (seg_idx, offset_in_segment) = if location.is_delta_chunk {
             let data_offset = location.sectors_base + 4;
             
             let (seg_idx, offset_in_seg) = self.global_to_segment_fast(data_offset)
-                .map_err(|e| format!("Delta chunk {}: offset {} error: {}", chunk_index, data_offset, e))?;
+                .map_err(|e| self.chunk_error(
+                    chunk_index,
+                    ChunkErrorKind::Location,
+                    format!("delta chunk offset {} error: {}", data_offset, e),
+                    None,
+                    is_compressed,
+                ))?;
             
             if chunk_index < 3 {
                 trace!("Delta chunk {}: sectors_base={} data_offset={} compressed={} seg={} local={}", 
                          chunk_index, location.sectors_base, data_offset, is_compressed, seg_idx, offset_in_seg);
             }
             
-            (seg_idx, offset_in_seg, is_compressed)
+            (seg_idx, offset_in_seg)
         } else {
-            let is_compressed = (location.offset & 0x80000000) != 0;
             let offset_value = (location.offset & 0x7FFFFFFF) as u64;
             
             let segment_local_offset = if location.base_offset > 0 {
@@ -735,7 +985,14 @@ impl EwfHandle {
                 Err(e) => {
                     trace!("Chunk {}: offset={:#x} compressed={} offset_value={} segment_local={} absolute={} ERROR: {}", 
                              chunk_index, location.offset, is_compressed, offset_value, segment_local_offset, absolute_offset, e);
-                    return Err(e);
+                    // The table says the data lives past the end of its segment: truncated segment
+                    return Err(self.chunk_error(
+                        chunk_index,
+                        ChunkErrorKind::ShortRead,
+                        e,
+                        Some((location.segment_index, segment_local_offset)),
+                        is_compressed,
+                    ));
                 }
             };
             
@@ -744,27 +1001,18 @@ impl EwfHandle {
                          chunk_index, location.offset, is_compressed, offset_value, location.base_offset, location.sectors_base, absolute_offset, seg_idx, offset_in_segment);
             }
             
-            (seg_idx, offset_in_segment, is_compressed)
+            (seg_idx, offset_in_segment)
         };
         
-        let file = self.file_pool.get_file(self.segments[seg_idx].file_index)?;
-        
-        file.seek(SeekFrom::Start(offset_in_segment))
-            .map_err(|e| format!("Seek to chunk {} at offset {} failed: {}", chunk_index, offset_in_segment, e))?;
-        
-        let mut chunk_data = if is_compressed {
-            let buffered = std::io::BufReader::with_capacity(65536, file.take(chunk_size as u64 * 2));
-            let mut decoder = ZlibDecoder::new(buffered);
-            let mut decompressed = Vec::with_capacity(chunk_size);
-            decoder.read_to_end(&mut decompressed)
-                .map_err(|e| format!("Chunk {} decompression failed at offset {}: {}", chunk_index, offset_in_segment, e))?;
-            decompressed
-        } else {
-            let mut uncompressed = vec![0u8; chunk_size];
-            file.read_exact(&mut uncompressed)
-                .map_err(|e| format!("Read uncompressed chunk failed: {}", e))?;
-            uncompressed
-        };
+        let stored_at = Some((seg_idx, offset_in_segment));
+        let read_result = self.read_stored_chunk(seg_idx, offset_in_segment, is_compressed, chunk_size);
+        if let Err((ChunkErrorKind::DeviceDisconnected, _)) = &read_result {
+            // Let the OS release the device so it can be remounted
+            self.file_pool.invalidate_prefix(&self.evidence_mount);
+        }
+        let (mut chunk_data, stored_bytes) = read_result.map_err(|(kind, message)| {
+            self.chunk_error(chunk_index, kind, message, stored_at, is_compressed)
+        })?;
         
         // Truncate last chunk if needed
         let expected_chunks = self.volume.sector_count.div_ceil(self.volume.sectors_per_chunk as u64);
@@ -782,15 +1030,80 @@ impl EwfHandle {
         
         if use_cache {
             self.chunk_cache.insert(chunk_index, chunk_data.clone());
+        } else if let Some(stats) = &mut self.chunk_stats {
+            stats.record_stored(is_compressed, stored_bytes, chunk_data.len());
         }
         
         Ok(chunk_data)
     }
+    
+    /// Read (and inflate) the stored data of one chunk from its segment
+    /// file, with the number of bytes it took up there
+    fn read_stored_chunk(
+        &mut self,
+        seg_idx: usize,
+        offset_in_segment: u64,
+        is_compressed: bool,
+        chunk_size: usize,
+    ) -> Result<(Vec<u8>, u64), (ChunkErrorKind, String)> {
+        let file_index = self.segments[seg_idx].file_index;
+        let segment_path = self.file_pool.get_path(file_index).cloned().unwrap_or_default();
+        // A lost device is reported as such, not as a per-chunk read error
+        let io_error = |kind: ChunkErrorKind, context: &str, e: &std::io::Error| {
+            if device::is_device_loss(e) {
+                (ChunkErrorKind::DeviceDisconnected, device::device_disconnected(&segment_path, e))
+            } else {
+                (kind, format!("{}: {}", context, e))
+            }
+        };
+        
+        let file = self.file_pool.get_file(file_index)
+            .map_err(|e| {
+                let kind = if device::is_device_disconnected(&e) { ChunkErrorKind::DeviceDisconnected } else { ChunkErrorKind::Io };
+                (kind, e)
+            })?;
+        
+        file.seek(SeekFrom::Start(offset_in_segment))
+            .map_err(|e| io_error(ChunkErrorKind::Io, "seek failed", &e))?;
+        
+        if is_compressed {
+            let buffered = std::io::BufReader::with_capacity(65536, file.take(chunk_size as u64 * 2));
+            inflate_bounded_counted(buffered, chunk_size, chunk_size + INFLATE_SLACK)
+                .map_err(|e| match &e {
+                    InflateError::Oversized { .. } => (ChunkErrorKind::CorruptStructure, format!("decompression failed: {}", e)),
+                    InflateError::Io(io) => {
+                        let kind = match io.kind() {
+                            std::io::ErrorKind::UnexpectedEof => ChunkErrorKind::ShortRead,
+                            std::io::ErrorKind::InvalidInput | std::io::ErrorKind::InvalidData => ChunkErrorKind::Decompression,
+                            _ => ChunkErrorKind::Io,
+                        };
+                        io_error(kind, "decompression failed", io)
+                    }
+                })
+        } else {
+            let mut uncompressed = vec![0u8; chunk_size];
+            file.read_exact(&mut uncompressed)
+                .map_err(|e| {
+                    let kind = if e.kind() == std::io::ErrorKind::UnexpectedEof {
+                        ChunkErrorKind::ShortRead
+                    } else {
+                        ChunkErrorKind::Io
+                    };
+                    io_error(kind, "read uncompressed chunk failed", &e)
+                })?;
+            Ok((uncompressed, chunk_size as u64))
+        }
+    }
 
     pub fn get_volume_info(&self) -> &VolumeSection {
         &self.volume
     }
 
+    /// Whether this image is a legacy SMART (EWF-S01) image
+    pub fn is_smart(&self) -> bool {
+        self.volume.is_smart
+    }
+
     pub fn get_chunk_count(&self) -> usize {
         self.volume.chunk_count as usize
     }
@@ -804,101 +1117,95 @@ impl EwfHandle {
     // Section Reading Helper Methods
     // =========================================================================
 
-    fn read_section_descriptor(file: &mut File, offset: u64) -> Result<SectionDescriptor, String> {
-        file.seek(SeekFrom::Start(offset))
-            .map_err(|e| format!("Seek failed: {}", e))?;
-        
-        let mut section_type = [0u8; 16];
-        file.read_exact(&mut section_type)
-            .map_err(|e| format!("Read section type failed: {}", e))?;
-        
-        let next_offset = read_u64_le(file)?;
-        let size = read_u64_le(file)?;
-        
-        Ok(SectionDescriptor {
-            section_type,
-            next_offset,
-            size,
-        })
+    fn read_section_descriptor(file: &mut dyn SegmentRead, offset: u64) -> Result<SectionDescriptor, String> {
+        let bytes = read_up_to(file, offset, 32)?;
+        Ok(parse_section_descriptor(&bytes, offset)?)
     }
 
-    fn read_volume_section(file_pool: &mut FileIoPool, file_index: usize, offset: u64) -> Result<VolumeSection, String> {
+    fn read_volume_section(file_pool: &mut FileIoPool, file_index: usize, offset: u64, data_size: u64) -> Result<VolumeSection, String> {
         trace!("read_volume_section: file_index={}, offset={}", file_index, offset);
         
         let file = file_pool.get_file(file_index)?;
-        
-        file.seek(SeekFrom::Start(offset))
-            .map_err(|e| format!("Seek failed: {}", e))?;
-        
-        let mut raw_bytes = [0u8; 20];
-        file.read_exact(&mut raw_bytes)
-            .map_err(|e| format!("Read raw bytes failed: {}", e))?;
-        trace!("Raw volume bytes: {:02x?}", &raw_bytes);
-        
-        file.seek(SeekFrom::Start(offset))
-            .map_err(|e| format!("Re-seek failed: {}", e))?;
-        
-        let _media_and_padding = read_u32_le(file)?;
-        let chunk_count = read_u32_le(file)?;
-        let sectors_per_chunk = read_u32_le(file)?;
-        let bytes_per_sector = read_u32_le(file)?;
-        let sector_count = read_u64_le(file)?;
-        
-        trace!("Volume: chunk_count={}, sectors_per_chunk={}, bytes_per_sector={}, sector_count={}", 
-                 chunk_count, sectors_per_chunk, bytes_per_sector, sector_count);
-        
-        Ok(VolumeSection {
-            chunk_count,
-            sectors_per_chunk,
-            bytes_per_sector,
-            sector_count,
-            compression_level: 1,
-        })
+        // Enough for the SMART marker when the section is large enough to
+        // carry one, otherwise for the 64-bit sector count
+        let length = if data_size >= SMART_VOLUME_DATA_SIZE { SMART_VOLUME_DATA_SIZE as usize } else { 24 };
+        let data = read_up_to(file, offset, length)?;
+        let volume = parse_volume_data(&data, offset, data_size)?;
+        
+        trace!("Volume: chunk_count={}, sectors_per_chunk={}, bytes_per_sector={}, sector_count={}, smart={}", 
+                 volume.chunk_count, volume.sectors_per_chunk, volume.bytes_per_sector, volume.sector_count, volume.is_smart);
+        Ok(volume)
     }
 
-    fn read_table_section(file: &mut File, offset: u64, size: u64, _sectors_base: u64) -> Result<TableSection, String> {
-        file.seek(SeekFrom::Start(offset))
-            .map_err(|e| format!("Seek failed: {}", e))?;
-        
-        let mut header_bytes = [0u8; 24];
-        file.read_exact(&mut header_bytes)
-            .map_err(|e| format!("Read header failed: {}", e))?;
-        
-        trace!("    Raw table header bytes: {:02x?}", &header_bytes);
-        
-        let entry_count = u32::from_le_bytes([header_bytes[0], header_bytes[1], header_bytes[2], header_bytes[3]]);
-        let base_offset = u64::from_le_bytes([
-            header_bytes[8], header_bytes[9], header_bytes[10], header_bytes[11],
-            header_bytes[12], header_bytes[13], header_bytes[14], header_bytes[15]
-        ]);
-        
-        let chunk_count = if entry_count > 0 {
-            entry_count
-        } else {
-            ((size.saturating_sub(24 + 4)) / 4) as u32
+    /// Read the table section data at `offset`; `offsets_base` is where in
+    /// the segment file its chunk offsets count from beyond the table's own
+    /// base offset (0 when they are segment-relative)
+    fn read_table_section(file: &mut dyn SegmentRead, offset: u64, size: u64, offsets_base: u64) -> Result<TableSection, String> {
+        let mut data = read_up_to(file, offset, TABLE_HEADER_SIZE)?;
+        let entry_count = ByteReader::at(&data, offset).u32_le()?;
+        // The entries live inside the section: a count that runs past it is
+        // corrupt and must not size the read
+        let length = (table_chunk_count(entry_count, size) as u64 * 4).min(size) as usize;
+        data.extend(read_up_to(file, offset + TABLE_HEADER_SIZE as u64, length)?);
+        
+        let mut table = parse_table_data(&data, offset, size)?;
+        table.base_offset += offsets_base;
+        trace!("    Table: entry_count={}, base_offset={}, using_count={}, first offsets={:#x?}", 
+                 entry_count, table.base_offset, table.chunk_count, &table.offsets[..table.offsets.len().min(5)]);
+        Ok(table)
+    }
+
+    /// Data of the first section of type `kind` in the image, if any
+    fn read_section_data(file_pool: &mut FileIoPool, segments: &[SegmentFile], kind: &str, max_size: u64) -> Result<Option<Vec<u8>>, String> {
+        let Some((file_index, section)) = segments.iter().find_map(|segment| {
+            segment.sections.iter()
+                .find(|s| s.section_type == kind)
+                .map(|s| (segment.file_index, s))
+        }) else {
+            return Ok(None);
         };
         
-        trace!("    Table: entry_count={}, base_offset={}, using_count={}", 
-                 entry_count, base_offset, chunk_count);
-        
-        let mut offsets = Vec::with_capacity(chunk_count as usize);
-        for i in 0..chunk_count {
-            let raw_offset = read_u32_le(file)? as u64;
-            offsets.push(raw_offset);
-            
-            if i < 5 {
-                let is_compressed = (raw_offset & 0x80000000) != 0;
-                let offset_value = raw_offset & 0x7FFFFFFF;
-                let current_pos = file.stream_position().unwrap_or(0);
-                trace!("      Offset[{}] at file_pos={}: raw={:#x} compressed={} value={}", 
-                         i, current_pos - 4, raw_offset, is_compressed, offset_value);
-            }
+        let data_size = section.size.saturating_sub(76);
+        if data_size > max_size {
+            return Err(format!("Corrupt {} section: {} bytes exceeds {} bytes", kind, data_size, max_size));
         }
-        
-        Ok(TableSection {
-            chunk_count,
-            base_offset,
-            offsets,
+        let file = file_pool.get_file(file_index)?;
+        file.seek(SeekFrom::Start(section.offset_in_segment + 76))
+            .map_err(|e| format!("Failed to seek to {} section: {}", kind, e))?;
+        let mut data = vec![0u8; data_size as usize];
+        file.read_exact(&mut data)
+            .map_err(|e| format!("Failed to read {} section: {}", kind, e))?;
+        Ok(Some(data))
+    }
+
+    /// Read the first session section of the image, if any
+    fn read_sessions(file_pool: &mut FileIoPool, segments: &[SegmentFile], sector_count: u64) -> Result<Vec<SessionInfo>, String> {
+        match Self::read_section_data(file_pool, segments, "session", MAX_SESSION_SECTION_SIZE)? {
+            Some(data) => parse_session_data(&data, sector_count),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Read the error2 section of the image, if any
+    fn read_acquisition_errors(file_pool: &mut FileIoPool, segments: &[SegmentFile]) -> Result<Vec<SectorRange>, String> {
+        match Self::read_section_data(file_pool, segments, "error2", MAX_ERROR2_SECTION_SIZE)? {
+            Some(data) => parse_error2_data(&data),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Chunks holding sectors the acquisition could not read, in chunk order
+    pub fn acquisition_error_chunks(&self) -> impl Iterator<Item = usize> + '_ {
+        let sectors_per_chunk = u64::from(self.volume.sectors_per_chunk.max(1));
+        let chunk_count = self.get_chunk_count();
+        // Ranges are sorted by first sector but may overlap
+        let mut next = 0usize;
+        self.acquisition_errors.iter().flat_map(move |range| {
+            let first = usize::try_from(range.first_sector / sectors_per_chunk).unwrap_or(usize::MAX);
+            let last = usize::try_from((range.end_sector() - 1) / sectors_per_chunk).unwrap_or(usize::MAX);
+            let (start, end) = (first.max(next), last.saturating_add(1).min(chunk_count));
+            next = next.max(end);
+            start..end
         })
     }
 
@@ -987,87 +1294,164 @@ impl EwfHandle {
         Ok(hashes)
     }
 
-    /// Read header section containing case metadata (zlib-compressed)
+    /// Read and decode a header/header2 section payload
     fn read_header_section(
         file_pool: &mut FileIoPool,
         file_index: usize,
         offset: u64,
         size: u64,
-    ) -> Result<HeaderInfo, String> {
+        is_header2: bool,
+    ) -> Result<HeaderValues, String> {
+        if size > MAX_HEADER_SECTION_SIZE {
+            return Err(format!("Corrupt header section: {} bytes exceeds {} bytes", size, MAX_HEADER_SECTION_SIZE));
+        }
         let file = file_pool.get_file(file_index)?;
         file.seek(SeekFrom::Start(offset))
             .map_err(|e| format!("Failed to seek to header section: {}", e))?;
         
-        // Read compressed data
         let mut compressed = vec![0u8; size as usize];
         file.read_exact(&mut compressed)
             .map_err(|e| format!("Failed to read header section: {}", e))?;
         
-        // Find zlib magic (78 9c) and decompress
-        let zlib_start = compressed.windows(2)
-            .position(|w| w == [0x78, 0x9c])
-            .unwrap_or(0);
-        
-        let mut decoder = ZlibDecoder::new(&compressed[zlib_start..]);
-        let mut decompressed = String::new();
-        decoder.read_to_string(&mut decompressed)
-            .map_err(|e| format!("Failed to decompress header: {}", e))?;
-        
-        // Parse tab-separated header format:
-        // Line 0: version (e.g., "1")
-        // Line 1: "main" or category
-        // Line 2: column headers (tab-separated): c, n, a, e, t, av, ov, m, u, p, r
-        // Line 3: values (tab-separated)
-        let lines: Vec<&str> = decompressed.lines().collect();
-        
-        let mut header = HeaderInfo::default();
-        
-        if lines.len() >= 4 {
-            let headers: Vec<&str> = lines[2].split('\t').collect();
-            let values: Vec<&str> = lines[3].split('\t').collect();
-            
-            for (i, &col) in headers.iter().enumerate() {
-                if i < values.len() {
-                    let val = values[i].trim();
-                    if !val.is_empty() {
-                        match col {
-                            "c" => header.case_number = Some(val.to_string()),
-                            "n" => header.evidence_number = Some(val.to_string()),
-                            "a" => header.description = Some(val.to_string()),
-                            "e" => header.examiner_name = Some(val.to_string()),
-                            "t" => header.notes = Some(val.to_string()),
-                            "av" => header.acquiry_sw_version = Some(val.to_string()),
-                            "ov" => header.acquiry_os = Some(val.to_string()),
-                            "m" => {
-                                // Parse date format "2017 12 14 11 52 41" -> "2017-12-14 11:52:41"
-                                if let Some(formatted) = Self::parse_ewf_date(val) {
-                                    header.acquiry_date = Some(formatted);
-                                }
-                            }
-                            "u" => {
-                                if let Some(formatted) = Self::parse_ewf_date(val) {
-                                    header.system_date = Some(formatted);
-                                }
-                            }
-                            _ => {}
-                        }
-                    }
-                }
-            }
-        }
-        
-        Ok(header)
+        HeaderValues::from_section(&compressed, is_header2)
     }
+}
 
-    /// Parse EWF date format "YYYY MM DD HH MM SS" to "YYYY-MM-DD HH:MM:SS"
-    fn parse_ewf_date(s: &str) -> Option<String> {
-        let parts: Vec<&str> = s.split_whitespace().collect();
-        if parts.len() >= 6 {
-            Some(format!("{}-{}-{} {}:{}:{}", 
-                parts[0], parts[1], parts[2],
-                parts[3], parts[4], parts[5]))
-        } else {
-            None
+/// Decode a section descriptor: type, next offset and size
+pub(crate) fn parse_section_descriptor(bytes: &[u8], offset: u64) -> Result<SectionDescriptor, Truncated> {
+    let mut reader = ByteReader::at(bytes, offset);
+    Ok(SectionDescriptor {
+        section_type: reader.array()?,
+        next_offset: reader.u64_le()?,
+        size: reader.u64_le()?,
+    })
+}
+
+/// Decode the start of volume/disk section data
+///
+/// `data_size` is the size the descriptor declares: a SMART volume is 94
+/// bytes (or carries "SMART" at offset 85) and has a 32-bit sector count.
+pub(crate) fn parse_volume_data(data: &[u8], offset: u64, data_size: u64) -> Result<VolumeSection, Truncated> {
+    let mut reader = ByteReader::at(data, offset);
+    // Media type byte followed by 3 bytes of padding
+    let media_type = reader.u8()?;
+    reader.skip(3)?;
+    let chunk_count = reader.u32_le()?;
+    let sectors_per_chunk = reader.u32_le()?;
+    let bytes_per_sector = reader.u32_le()?;
+    
+    let mut is_smart = data_size == SMART_VOLUME_DATA_SIZE;
+    if !is_smart && data_size >= SMART_VOLUME_DATA_SIZE {
+        let mut marker = reader.clone();
+        marker.seek(85)?;
+        is_smart = marker.bytes(SMART_VOLUME_SIGNATURE.len())? == SMART_VOLUME_SIGNATURE;
+    }
+    let sector_count = if is_smart { reader.u32_le()? as u64 } else { reader.u64_le()? };
+    
+    Ok(VolumeSection {
+        media_type,
+        chunk_count,
+        sectors_per_chunk,
+        bytes_per_sector,
+        sector_count,
+        compression_level: 1,
+        is_smart,
+    })
+}
+
+/// Entries a table section holds: its own count, or what fits in a section
+/// of `size` bytes when the count is zero
+fn table_chunk_count(entry_count: u32, size: u64) -> u32 {
+    if entry_count > 0 {
+        entry_count
+    } else {
+        ((size.saturating_sub(24 + 4)) / 4) as u32
+    }
+}
+
+/// Decode table section data: the 24-byte header and the chunk offsets
+pub(crate) fn parse_table_data(data: &[u8], offset: u64, size: u64) -> Result<TableSection, Truncated> {
+    let mut reader = ByteReader::at(data, offset);
+    let entry_count = reader.u32_le()?;
+    reader.skip(4)?;
+    let base_offset = reader.u64_le()?;
+    reader.seek(TABLE_HEADER_SIZE)?;
+    
+    let chunk_count = table_chunk_count(entry_count, size);
+    let mut offsets = Vec::with_capacity((chunk_count as usize).min(reader.remaining() / 4));
+    for _ in 0..chunk_count {
+        offsets.push(reader.u32_le()? as u64);
+    }
+    
+    Ok(TableSection {
+        chunk_count,
+        base_offset,
+        offsets,
+    })
+}
+
+/// Decode session section data into sessions ordered by first sector
+///
+/// Entries only record where each session starts; a session runs up to the
+/// next one, the last one up to the end of the media.
+pub(crate) fn parse_session_data(data: &[u8], sector_count: u64) -> Result<Vec<SessionInfo>, String> {
+    if data.len() < SESSION_HEADER_SIZE {
+        return Err(format!("Corrupt session section: {} bytes is shorter than its header", data.len()));
+    }
+    let count = u32::from_le_bytes([data[0], data[1], data[2], data[3]]) as usize;
+    let room = (data.len() - SESSION_HEADER_SIZE) / SESSION_ENTRY_SIZE;
+    if count > room {
+        return Err(format!("Corrupt session section: {} sessions but room for {}", count, room));
+    }
+    
+    let mut starts: Vec<u64> = data[SESSION_HEADER_SIZE..]
+        .chunks_exact(SESSION_ENTRY_SIZE)
+        .take(count)
+        .map(|entry| u32::from_le_bytes([entry[4], entry[5], entry[6], entry[7]]) as u64)
+        .collect();
+    starts.sort_unstable();
+    starts.dedup();
+    
+    Ok(starts.iter().enumerate().map(|(i, &first_sector)| {
+        let end = starts.get(i + 1).copied().unwrap_or(sector_count).max(first_sector);
+        SessionInfo { first_sector, sector_count: end - first_sector }
+    }).collect())
+}
+
+/// Decode error2 section data into sector ranges ordered by first sector
+///
+/// Entries with no sectors are dropped.
+pub(crate) fn parse_error2_data(data: &[u8]) -> Result<Vec<SectorRange>, String> {
+    if data.len() < ERROR2_HEADER_SIZE {
+        return Err(format!("Corrupt error2 section: {} bytes is shorter than its header", data.len()));
+    }
+    let count = u32::from_le_bytes([data[0], data[1], data[2], data[3]]) as usize;
+    let room = (data.len() - ERROR2_HEADER_SIZE) / ERROR2_ENTRY_SIZE;
+    if count > room {
+        return Err(format!("Corrupt error2 section: {} entries but room for {}", count, room));
+    }
+    
+    let mut ranges: Vec<SectorRange> = data[ERROR2_HEADER_SIZE..]
+        .chunks_exact(ERROR2_ENTRY_SIZE)
+        .take(count)
+        .map(|entry| SectorRange {
+            first_sector: u32::from_le_bytes([entry[0], entry[1], entry[2], entry[3]]) as u64,
+            sector_count: u32::from_le_bytes([entry[4], entry[5], entry[6], entry[7]]) as u64,
+        })
+        .filter(|range| range.sector_count > 0)
+        .collect();
+    ranges.sort_unstable_by_key(|range| range.first_sector);
+    Ok(ranges)
+}
+
+/// Check for the SMART segment extension (.s01, .s02, ...)
+pub(crate) fn is_smart_extension(path: &str) -> bool {
+    let lower = path.to_lowercase();
+    match lower.rfind('.') {
+        Some(pos) => {
+            let ext = &lower[pos + 1..];
+            ext.len() == 3 && ext.starts_with('s') && ext[1..].chars().all(|c| c.is_ascii_digit())
         }
+        None => false,
     }
 }
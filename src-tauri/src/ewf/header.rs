@@ -0,0 +1,239 @@
+//! EWF header/header2 section decoding
+//!
+//! Both sections carry the case metadata as a zlib-compressed text block:
+//!
+//! ```text
+//! 3                      <- number of categories
+//! main                   <- category name
+//! a  c  n  e  t  av ...  <- tab-separated field codes
+//! .. .. .. .. .. ..  ... <- tab-separated values, same order
+//! ```
+//!
+//! `header` is ASCII, while `header2` (EnCase 6+) is UTF-16LE with a byte
+//! order mark. Images written by EnCase 6+ contain both, in which case the
+//! header2 values take precedence.
+
+use std::collections::HashMap;
+
+use super::types::MAX_HEADER_SECTION_SIZE;
+use crate::common::inflate::inflate_bounded;
+use crate::common::time::Timestamp;
+
+/// Field codes of the "main" category and their display names
+pub const HEADER_FIELDS: &[(&str, &str)] = &[
+    ("c", "Case Number"),
+    ("n", "Evidence Number"),
+    ("a", "Description"),
+    ("e", "Examiner Name"),
+    ("t", "Notes"),
+    ("av", "Acquisition Software Version"),
+    ("ov", "Acquisition OS"),
+    ("m", "Acquisition Date"),
+    ("u", "System Date"),
+    ("p", "Password Hash"),
+    ("r", "Compression"),
+    ("md", "Model"),
+    ("sn", "Serial Number"),
+    ("l", "Device Label"),
+    ("tb", "Total Bytes"),
+];
+
+/// UTF-16LE byte order mark written at the start of header2
+const UTF16_LE_BOM: [u8; 2] = [0xff, 0xfe];
+
+/// Display name of a header field code (e.g. "c" -> "Case Number")
+pub fn header_field_name(code: &str) -> Option<&'static str> {
+    HEADER_FIELDS
+        .iter()
+        .find(|(c, _)| *c == code)
+        .map(|(_, name)| *name)
+}
+
+/// Values of the "main" category keyed by field code
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct HeaderValues {
+    values: HashMap<String, String>,
+}
+
+impl HeaderValues {
+    /// Decompress and parse a header or header2 section payload
+    pub fn from_section(data: &[u8], is_header2: bool) -> Result<Self, String> {
+        let text = decode_section_text(data, is_header2)?;
+        Ok(Self::parse(&text))
+    }
+
+    /// Parse the decoded text of a header section
+    ///
+    /// The field code line follows the "main" category name and the value
+    /// line follows the codes. Values are positional, so empty columns are
+    /// kept while splitting and only dropped afterwards.
+    pub fn parse(text: &str) -> Self {
+        let lines: Vec<&str> = text.lines().map(|l| l.trim_end_matches('\r')).collect();
+
+        let codes_idx = lines
+            .iter()
+            .position(|l| l.trim() == "main")
+            .map(|i| i + 1)
+            .or_else(|| lines.iter().position(|l| l.contains('\t')));
+
+        let mut values = HashMap::new();
+        if let Some(idx) = codes_idx {
+            if let (Some(codes), Some(row)) = (lines.get(idx), lines.get(idx + 1)) {
+                for (code, value) in codes.split('\t').zip(row.split('\t')) {
+                    let (code, value) = (code.trim(), value.trim());
+                    if !code.is_empty() && !value.is_empty() {
+                        values.insert(code.to_string(), value.to_string());
+                    }
+                }
+            }
+        }
+
+        Self { values }
+    }
+
+    pub fn get(&self, code: &str) -> Option<&str> {
+        self.values.get(code).map(String::as_str)
+    }
+
+    /// Owned value of a field, for filling `Option<String>` metadata fields
+    pub fn value(&self, code: &str) -> Option<String> {
+        self.values.get(code).cloned()
+    }
+
+    /// Acquisition (`m`) or system (`u`) date
+    pub fn date(&self, code: &str) -> Option<Timestamp> {
+        self.get(code).map(parse_header_date)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Overlay `preferred` on top of these values (header2 over header)
+    pub fn merge(mut self, preferred: HeaderValues) -> Self {
+        self.values.extend(preferred.values);
+        self
+    }
+
+    /// Known fields as (display name, value) in the order of [`HEADER_FIELDS`]
+    pub fn named(&self) -> Vec<(&'static str, &str)> {
+        HEADER_FIELDS
+            .iter()
+            .filter_map(|(code, name)| self.get(code).map(|value| (*name, value)))
+            .collect()
+    }
+}
+
+/// Decompress a header section and decode its text
+///
+/// Some writers pad the section before the zlib stream, so the stream is
+/// located by its magic rather than assumed to start at offset 0. The text is
+/// UTF-16LE for header2 and whenever a UTF-16 byte order mark is present.
+pub fn decode_section_text(data: &[u8], is_header2: bool) -> Result<String, String> {
+    let start = find_zlib_stream(data).ok_or("Header section has no zlib stream")?;
+
+    let decompressed = inflate_bounded(&data[start..], data.len() * 4, MAX_HEADER_SECTION_SIZE as usize)
+        .map_err(|e| format!("Failed to decompress header: {}", e))?;
+
+    if is_header2 || decompressed.starts_with(&UTF16_LE_BOM) {
+        Ok(decode_utf16_le(&decompressed))
+    } else {
+        Ok(String::from_utf8_lossy(&decompressed).into_owned())
+    }
+}
+
+/// Offset of the first valid zlib header (CMF 0x78 with a matching FLG check)
+fn find_zlib_stream(data: &[u8]) -> Option<usize> {
+    data.windows(2)
+        .position(|w| w[0] == 0x78 && (u16::from(w[0]) << 8 | u16::from(w[1])) % 31 == 0)
+}
+
+/// Decode UTF-16LE bytes, dropping the byte order mark if present
+pub(crate) fn decode_utf16_le(data: &[u8]) -> String {
+    let data = data.strip_prefix(&UTF16_LE_BOM[..]).unwrap_or(data);
+    let units: Vec<u16> = data
+        .chunks_exact(2)
+        .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+        .collect();
+    String::from_utf16_lossy(&units)
+}
+
+/// Normalize a header date
+///
+/// header stores dates as "YYYY MM DD HH MM SS" in the examiner's local time
+/// while header2 stores them as POSIX seconds.
+pub fn parse_header_date(value: &str) -> Timestamp {
+    let posix = value.trim().parse::<i64>().ok().and_then(|secs| chrono::DateTime::from_timestamp(secs, 0));
+    match posix {
+        Some(date) => Timestamp::from_utc(date, value),
+        None => Timestamp::parse(value),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// header2 section payload captured from an EnCase 6.19 image
+    /// (case values replaced with sanitized placeholders)
+    const HEADER2_ENCASE6: &[u8] = &[
+        0x78, 0xda, 0x75, 0x90, 0xc1, 0x4e, 0xc3, 0x40, 0x0c, 0x44, 0xe7, 0xba, 0xf9, 0x8a, 0xfd, 0x81,
+        0x56, 0x29, 0x6d, 0x83, 0x38, 0x22, 0xd4, 0x0b, 0x57, 0x90, 0x38, 0x2f, 0x4d, 0x5a, 0x22, 0x9a,
+        0xa4, 0x94, 0x36, 0x20, 0x3e, 0x1e, 0x78, 0x76, 0x22, 0x0e, 0x55, 0x91, 0x35, 0xa3, 0x5d, 0xaf,
+        0x3d, 0xe3, 0xf5, 0xcf, 0xf7, 0x5c, 0x99, 0x1a, 0x25, 0xd5, 0x6a, 0x39, 0x25, 0x05, 0xad, 0x41,
+        0x0b, 0x2a, 0x70, 0x04, 0x49, 0x3d, 0xdc, 0x39, 0x37, 0xe0, 0x04, 0xf6, 0xd4, 0x3e, 0x50, 0x91,
+        0xb4, 0x05, 0x47, 0x4e, 0x91, 0xfb, 0xa3, 0x96, 0xca, 0x89, 0xa8, 0x1d, 0xd9, 0x3d, 0xf9, 0x0e,
+        0x8e, 0x2a, 0x75, 0x40, 0xbf, 0x77, 0xc5, 0x3b, 0xdd, 0x52, 0xb9, 0xd2, 0x44, 0x57, 0x54, 0xce,
+        0x74, 0xc3, 0xc9, 0x7a, 0x16, 0xdc, 0x03, 0xf9, 0xdc, 0xb3, 0x41, 0xf7, 0x9a, 0xd2, 0xb9, 0xd2,
+        0x27, 0x4a, 0x8d, 0x4f, 0x57, 0xa1, 0x12, 0xdc, 0xb5, 0xd6, 0x17, 0x5c, 0xf2, 0xbe, 0x21, 0xd7,
+        0xf1, 0x1e, 0xe1, 0x0d, 0x51, 0x33, 0x7d, 0xe5, 0x8e, 0x95, 0xde, 0xf5, 0x4a, 0x7d, 0x81, 0x8e,
+        0xb9, 0x4c, 0x75, 0x0d, 0xcc, 0xe3, 0xc9, 0xd5, 0x4a, 0x3a, 0x3e, 0xa8, 0x89, 0xe4, 0x03, 0x15,
+        0x4b, 0x26, 0x28, 0x34, 0x87, 0x17, 0x3e, 0x43, 0x7e, 0x21, 0x5b, 0xf8, 0x64, 0x39, 0x7f, 0xcf,
+        0xe8, 0x3c, 0xb8, 0x57, 0x36, 0x56, 0x66, 0xfc, 0x74, 0xd8, 0x5b, 0x8d, 0xb6, 0x6d, 0xaf, 0xf7,
+        0xfd, 0x3d, 0xc3, 0x3b, 0xbc, 0x6c, 0x67, 0x9d, 0x6f, 0xf3, 0x05, 0xde, 0xfa, 0x16, 0x93, 0xde,
+        0xc6, 0x7e, 0xd3, 0x0c, 0x7f, 0x31, 0x71, 0x9f, 0x81, 0x2d, 0x06, 0xbf, 0x13, 0x5a, 0xff, 0xb9,
+        0xb5, 0xae, 0xb7, 0x76, 0x07, 0xd3, 0x3e, 0x57, 0x9d, 0x8d, 0x30, 0xa5, 0x5f, 0x1c, 0x8b, 0x3e,
+        0xf7,
+    ];
+
+    #[test]
+    fn test_header2_utf16_decoding() {
+        let values = HeaderValues::from_section(HEADER2_ENCASE6, true).unwrap();
+
+        assert_eq!(values.get("c"), Some("CASE-2019-0042"));
+        assert_eq!(values.get("n"), Some("E001"));
+        assert_eq!(values.get("a"), Some("Seagate ST500 laptop drive"));
+        assert_eq!(values.get("e"), Some("J. Examiner"));
+        assert_eq!(values.get("av"), Some("6.19.7.2"));
+        assert_eq!(values.get("ov"), Some("Windows 7"));
+        let acquired = values.date("m").unwrap();
+        assert_eq!((acquired.iso_utc.as_str(), acquired.timezone_known), ("2019-01-01T12:00:00Z", true));
+        assert_eq!(values.date("u").unwrap().iso_utc, "2019-01-01T12:01:01Z");
+
+        // Only the "main" category is read, not the srce/sub tables after it
+        assert_eq!(values.get("id"), None);
+        assert_eq!(values.named()[0], ("Case Number", "CASE-2019-0042"));
+
+        // Even when the section name is unknown the BOM gives UTF-16 away
+        assert_eq!(decode_section_text(HEADER2_ENCASE6, false).unwrap(), decode_section_text(HEADER2_ENCASE6, true).unwrap());
+    }
+
+    #[test]
+    fn test_header2_values_take_precedence() {
+        let header = HeaderValues::parse(
+            "1\r\nmain\r\nc\tn\ta\te\tt\tav\tov\tm\tu\tp\tr\r\nCASE-OLD\tE001\t\tJ. Examiner\t\t6.19\tWindows 7\t2019 1 1 12 0 0\t2019 1 1 12 1 1\t0\tf\r\n\r\n",
+        );
+        assert_eq!(header.get("a"), None);
+        assert_eq!(header.get("r"), Some("f"));
+        let acquired = header.date("m").unwrap();
+        assert_eq!((acquired.iso_utc.as_str(), acquired.original.as_str()), ("2019-01-01T12:00:00Z", "2019 1 1 12 0 0"));
+        assert!(!acquired.timezone_known);
+
+        let merged = header.merge(HeaderValues::from_section(HEADER2_ENCASE6, true).unwrap());
+        assert_eq!(merged.get("c"), Some("CASE-2019-0042"));
+        assert_eq!(merged.get("a"), Some("Seagate ST500 laptop drive"));
+        // Fields only present in header survive the merge
+        assert_eq!(merged.get("r"), Some("f"));
+        assert_eq!(header_field_name("av"), Some("Acquisition Software Version"));
+    }
+}
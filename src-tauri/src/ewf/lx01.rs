@@ -0,0 +1,412 @@
+//! Lx01 (EWF2 logical evidence) metadata parsing
+//!
+//! EWF2 differs from the v1 layout `EwfHandle` reads: after a 32-byte file
+//! header, each section starts with a 64-byte descriptor whose
+//! `previous_offset` points back to the descriptor of the section before it.
+//! A segment is therefore walked backwards from its last descriptor (the
+//! "next" or "done" section at the end of the file).
+//!
+//! ```text
+//! File header (32 bytes)         Section descriptor (64 bytes)
+//! 0x00 signature    [8]          0x00 section type     u32
+//! 0x08 major/minor  u8, u8       0x04 data flags       u32
+//! 0x0a compression  u16          0x08 previous offset  u64
+//! 0x0c segment      u16          0x10 data size        u64
+//! 0x0e set GUID     [16]         0x18 descriptor size  u32
+//!                                0x1c padding size     u32
+//!                                0x20 data MD5         [16]
+//!                                0x3c checksum         u32
+//! ```
+//!
+//! Info only needs the device information, case data, hash and single files
+//! data sections. Device information and case data hold the same
+//! tab-separated "main" table as v1 header2, with EWF2 field codes. Chunk
+//! tables are not parsed yet, so Lx01 cannot be verified or extracted here.
+
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use tracing::{debug, trace, warn};
+
+use super::header::{decode_section_text, decode_utf16_le, HeaderValues};
+use crate::common::capabilities::FormatCapabilities;
+use crate::common::time::Timestamp;
+use super::types::*;
+
+/// Metadata only until the EWF2 chunk tables are parsed
+pub const fn capabilities() -> FormatCapabilities {
+    FormatCapabilities {
+        notes: &["EWF2 chunk tables are not parsed yet: metadata only"],
+        ..FormatCapabilities::INFO_ONLY
+    }
+}
+
+/// EWF2 file header size
+const FILE_HEADER_SIZE: u64 = 32;
+/// EWF2 section descriptor size
+const SECTION_DESCRIPTOR_SIZE: u64 = 64;
+
+// EWF2 section types
+const SECTION_DEVICE_INFORMATION: u32 = 0x01;
+const SECTION_CASE_DATA: u32 = 0x02;
+const SECTION_MD5_HASH: u32 = 0x08;
+const SECTION_SHA1_HASH: u32 = 0x09;
+const SECTION_SINGLE_FILES_DATA: u32 = 0x20;
+
+/// One section of an EWF2 segment
+#[derive(Clone, Debug)]
+struct Ewf2Section {
+    section_type: u32,
+    /// Offset of the section data (right after its descriptor)
+    data_offset: u64,
+    /// Data size without padding
+    data_size: u64,
+}
+
+/// Check if a file starts with an Lx01 (EWF2 logical) signature
+pub fn is_lx01_file(path: &str) -> Result<bool, String> {
+    let mut file = File::open(path)
+        .map_err(|e| format!("Failed to open file: {}", e))?;
+    let mut sig = [0u8; 8];
+    if file.read_exact(&mut sig).is_err() {
+        return Ok(false);
+    }
+    Ok(&sig == LEF2_SIGNATURE || &sig == LVF2_SIGNATURE)
+}
+
+/// Segment files of an Lx01 set (.Lx01, .Lx02, ...) that exist on disk
+pub fn lx01_segment_paths(path: &str) -> Vec<PathBuf> {
+    let first = Path::new(path);
+    let mut paths = vec![first.to_path_buf()];
+    let (Some(parent), Some(stem), Some(ext)) = (first.parent(), first.file_stem(), first.extension()) else {
+        return paths;
+    };
+    let ext = ext.to_string_lossy();
+    let prefix = ext.trim_end_matches(|c: char| c.is_ascii_digit());
+    for number in 2..=99 {
+        let name = format!("{}.{}{:02}", stem.to_string_lossy(), prefix, number);
+        let candidates = [parent.join(&name), parent.join(name.to_lowercase())];
+        match candidates.into_iter().find(|candidate| candidate.exists()) {
+            Some(segment) => paths.push(segment),
+            None => break,
+        }
+    }
+    paths
+}
+
+/// Read the case, device and file entry metadata of an Lx01 set
+///
+/// `total_size` is the logical size of the files in the container and
+/// `logical_entries` their counts. Chunk and sector fields stay 0 until the
+/// sector tables are parsed.
+pub fn info(path: &str) -> Result<EwfInfo, String> {
+    let segments = lx01_segment_paths(path);
+    let mut file = File::open(path)
+        .map_err(|e| format!("Failed to open Lx01 file '{}': {}", path, e))?;
+    let mut header = [0u8; FILE_HEADER_SIZE as usize];
+    file.read_exact(&mut header)
+        .map_err(|e| format!("Failed to read Lx01 file header: {}", e))?;
+    if &header[0..8] != LEF2_SIGNATURE && &header[0..8] != LVF2_SIGNATURE {
+        return Err(format!("Not an Lx01 file: {}", path));
+    }
+    let compression_method = u16::from_le_bytes([header[10], header[11]]);
+    debug!(path, segments = segments.len(), compression_method, "Reading Lx01 metadata");
+
+    let mut case = HeaderValues::default();
+    let mut device = HeaderValues::default();
+    let mut entries = None;
+    let mut stored_hashes = Vec::new();
+
+    for segment_path in &segments {
+        let mut segment = File::open(segment_path)
+            .map_err(|e| format!("Failed to open Lx01 segment {:?}: {}", segment_path, e))?;
+        for section in read_sections(&mut segment)? {
+            match section.section_type {
+                SECTION_DEVICE_INFORMATION | SECTION_CASE_DATA | SECTION_SINGLE_FILES_DATA => {
+                    let data = read_section_data(&mut segment, &section, MAX_HEADER_SECTION_SIZE)?;
+                    let text = match section_text(&data, compression_method) {
+                        Ok(text) => text,
+                        Err(e) => {
+                            warn!(section_type = section.section_type, error = %e, "Skipping undecodable Lx01 section");
+                            continue;
+                        }
+                    };
+                    match section.section_type {
+                        SECTION_DEVICE_INFORMATION if device.is_empty() => device = HeaderValues::parse(&text),
+                        SECTION_CASE_DATA if case.is_empty() => case = HeaderValues::parse(&text),
+                        SECTION_SINGLE_FILES_DATA => entries = Some(parse_file_entries(&text)),
+                        _ => {}
+                    }
+                }
+                SECTION_MD5_HASH | SECTION_SHA1_HASH => {
+                    let (algorithm, size) = if section.section_type == SECTION_MD5_HASH { ("MD5", 16) } else { ("SHA1", 20) };
+                    let data = read_section_data(&mut segment, &section, size)?;
+                    if data.len() == size as usize && data.iter().any(|b| *b != 0) {
+                        stored_hashes.push(StoredImageHash {
+                            algorithm: algorithm.to_string(),
+                            hash: hex::encode(&data),
+                            verified: None,
+                            timestamp: None,
+                            source: Some("container".to_string()),
+                            offset: Some(section.data_offset),
+                            size: Some(size),
+                        });
+                    }
+                }
+                other => trace!(section_type = other, "Skipping Lx01 section"),
+            }
+        }
+    }
+
+    let entries = entries.unwrap_or_default();
+    let acquiry_date = case.date("at");
+    for hash in &mut stored_hashes {
+        hash.timestamp = acquiry_date.as_ref().map(Timestamp::to_string);
+    }
+    let number = |values: &HeaderValues, code: &str| values.get(code).and_then(|v| v.parse::<u64>().ok());
+    let segment_files = (segments.len() > 1).then(|| {
+        segments.iter()
+            .filter_map(|p| p.file_name())
+            .map(|f| f.to_string_lossy().to_string())
+            .collect()
+    });
+
+    Ok(EwfInfo {
+        format_version: "EWF2 (Lx01)".to_string(),
+        segment_count: segments.len() as u32,
+        chunk_count: 0,
+        sector_count: number(&device, "ts").unwrap_or(0),
+        bytes_per_sector: number(&device, "bp").unwrap_or(SECTOR_SIZE) as u32,
+        sectors_per_chunk: number(&case, "sb").unwrap_or(0) as u32,
+        total_size: entries.total_bytes,
+        compression: compression_name(compression_method).to_string(),
+        case_number: case.value("cn"),
+        description: case.value("nm"),
+        examiner_name: case.value("ex"),
+        evidence_number: case.value("en"),
+        notes: case.value("nt"),
+        acquiry_date,
+        system_date: case.date("tt"),
+        model: device.value("md"),
+        serial_number: device.value("sn"),
+        media_type: media_type_name(MEDIA_TYPE_LOGICAL).map(str::to_string),
+        sessions: Vec::new(),
+        acquisition_errors: Vec::new(),
+        logical_entries: Some(entries),
+        stored_hashes,
+        segment_files,
+        header_section_offset: None,
+        volume_section_offset: None,
+        hash_section_offset: None,
+        digest_section_offset: None,
+        segment_consistency: None,
+        parse_warnings: Vec::new(),
+    })
+}
+
+/// Walk a segment's section chain from its last descriptor, returning the
+/// sections in file order
+fn read_sections(file: &mut File) -> Result<Vec<Ewf2Section>, String> {
+    let file_size = file.seek(SeekFrom::End(0))
+        .map_err(|e| format!("Failed to seek Lx01 segment: {}", e))?;
+    if file_size < FILE_HEADER_SIZE + SECTION_DESCRIPTOR_SIZE {
+        return Err("Lx01 segment too small to hold a section".to_string());
+    }
+
+    let mut sections = Vec::new();
+    let mut offset = file_size - SECTION_DESCRIPTOR_SIZE;
+    loop {
+        if sections.len() as u32 >= MAX_SECTIONS {
+            return Err(format!("Corrupt Lx01 segment: more than {} sections", MAX_SECTIONS));
+        }
+        let mut descriptor = [0u8; SECTION_DESCRIPTOR_SIZE as usize];
+        file.seek(SeekFrom::Start(offset))
+            .and_then(|_| file.read_exact(&mut descriptor))
+            .map_err(|e| format!("Failed to read Lx01 section descriptor at {}: {}", offset, e))?;
+        let u32_at = |at: usize| u32::from_le_bytes(descriptor[at..at + 4].try_into().unwrap());
+        let u64_at = |at: usize| u64::from_le_bytes(descriptor[at..at + 8].try_into().unwrap());
+
+        let data_offset = offset + SECTION_DESCRIPTOR_SIZE;
+        let data_size = u64_at(0x10).saturating_sub(u32_at(0x1c) as u64);
+        if data_offset.saturating_add(data_size) > file_size {
+            return Err(format!("Corrupt Lx01 section at {}: {} bytes of data past the end of the segment", offset, data_size));
+        }
+        sections.push(Ewf2Section { section_type: u32_at(0x00), data_offset, data_size });
+
+        // The chain runs strictly backwards and ends at the first section
+        let previous = u64_at(0x08);
+        if previous == 0 {
+            break;
+        }
+        if previous >= offset || previous < FILE_HEADER_SIZE {
+            return Err(format!("Corrupt Lx01 section chain: section at {} points to {}", offset, previous));
+        }
+        offset = previous;
+    }
+
+    sections.reverse();
+    Ok(sections)
+}
+
+/// Read up to `max` bytes of a section's data
+fn read_section_data(file: &mut File, section: &Ewf2Section, max: u64) -> Result<Vec<u8>, String> {
+    if section.data_size > max {
+        return Err(format!(
+            "Corrupt Lx01 section {:#x}: {} bytes exceeds the {} byte limit",
+            section.section_type, section.data_size, max
+        ));
+    }
+    let mut data = vec![0u8; section.data_size as usize];
+    file.seek(SeekFrom::Start(section.data_offset))
+        .and_then(|_| file.read_exact(&mut data))
+        .map_err(|e| format!("Failed to read Lx01 section data: {}", e))?;
+    Ok(data)
+}
+
+/// Decode a text section (UTF-16LE, zlib-compressed unless the set is uncompressed)
+fn section_text(data: &[u8], compression_method: u16) -> Result<String, String> {
+    match compression_method {
+        0 => Ok(decode_utf16_le(data)),
+        1 => decode_section_text(data, true),
+        other => Err(format!("{} compression is not supported", compression_name(other))),
+    }
+}
+
+fn compression_name(method: u16) -> &'static str {
+    match method {
+        0 => "None",
+        1 => "Deflate",
+        2 => "Bzip2",
+        _ => "Unknown",
+    }
+}
+
+/// Count the file entries of the single files data ("ltree") text
+///
+/// Rows of the "entry" table follow its tab-separated field codes; count
+/// lines between them (which give the tree nesting) have a different column
+/// count and are skipped. `p` = 1 marks a folder, `ls` is the logical size.
+fn parse_file_entries(text: &str) -> LogicalEntries {
+    let mut entries = LogicalEntries::default();
+    let mut lines = text.lines().map(|l| l.trim_end_matches('\r'));
+    if !lines.any(|l| l.trim() == "entry") {
+        return entries;
+    }
+    let Some(codes) = lines.find(|l| l.split('\t').any(|f| f.chars().any(|c| c.is_ascii_alphabetic()))) else {
+        return entries;
+    };
+    let codes: Vec<&str> = codes.split('\t').map(str::trim).collect();
+    let column = |code: &str| codes.iter().position(|c| *c == code);
+    let (is_folder, size) = (column("p"), column("ls"));
+
+    for line in lines {
+        let fields: Vec<&str> = line.split('\t').collect();
+        if fields.len() != codes.len() {
+            continue;
+        }
+        if is_folder.is_some_and(|i| fields[i].trim() == "1") {
+            entries.folder_count += 1;
+        } else {
+            entries.file_count += 1;
+            entries.total_bytes += size.and_then(|i| fields[i].trim().parse::<u64>().ok()).unwrap_or(0);
+        }
+    }
+    entries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::ZlibEncoder;
+    use flate2::Compression;
+    use std::io::Write;
+
+    fn utf16_zlib(text: &str) -> Vec<u8> {
+        let mut utf16 = vec![0xff, 0xfe];
+        utf16.extend(text.encode_utf16().flat_map(u16::to_le_bytes));
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&utf16).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    /// Segment with the given (type, data) sections and a closing done section
+    fn write_segment(path: &Path, segment_number: u16, sections: &[(u32, Vec<u8>)]) {
+        let mut file = LEF2_SIGNATURE.to_vec();
+        file.extend_from_slice(&[2, 1]);
+        file.extend_from_slice(&1u16.to_le_bytes());
+        file.extend_from_slice(&segment_number.to_le_bytes());
+        file.resize(FILE_HEADER_SIZE as usize, 0xab);
+
+        let mut previous = 0u64;
+        let done = (0x0f, Vec::new());
+        for (section_type, data) in sections.iter().chain(std::iter::once(&done)) {
+            let padding = (16 - data.len() % 16) % 16;
+            let offset = file.len() as u64;
+            let mut descriptor = [0u8; SECTION_DESCRIPTOR_SIZE as usize];
+            descriptor[0x00..0x04].copy_from_slice(&section_type.to_le_bytes());
+            descriptor[0x08..0x10].copy_from_slice(&previous.to_le_bytes());
+            descriptor[0x10..0x18].copy_from_slice(&((data.len() + padding) as u64).to_le_bytes());
+            descriptor[0x18..0x1c].copy_from_slice(&(SECTION_DESCRIPTOR_SIZE as u32).to_le_bytes());
+            descriptor[0x1c..0x20].copy_from_slice(&(padding as u32).to_le_bytes());
+            file.extend_from_slice(&descriptor);
+            file.extend_from_slice(data);
+            file.extend(std::iter::repeat_n(0u8, padding));
+            previous = offset;
+        }
+        std::fs::write(path, file).unwrap();
+    }
+
+    const CASE_DATA: &str = "1\nmain\nnm\tcn\ten\tex\tnt\tav\tos\ttt\tat\tsb\n\
+        Suspect laptop user folder\tCASE-2024-017\tL001\tJ. Examiner\tCollected on site\t20.4.0.4\tWindows 10\t1704110460\t1704110400\t64\n\n";
+    const DEVICE_INFORMATION: &str = "1\nmain\nsn\tmd\tlb\tts\tbp\tdt\n\t\tLogical Evidence\t0\t512\tl\n\n";
+    const FILE_ENTRIES: &str = "5\nrec\ntb\tcl\n7168\t1\n\nperm\n0\t0\n\nsrce\n0\t1\n\nsub\n0\t1\n\nentry\n0\t1\n\
+        p\tn\tid\tls\tcid\n1\tUsers\t1\t\t0\n0\t3\n\
+        0\tnotes.txt\t2\t1024\t0\n0\tmail.pst\t3\t4096\t0\n0\tphoto.jpg\t4\t2048\t0\n";
+
+    #[test]
+    fn test_lx01_info_reads_case_device_and_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let first = dir.path().join("collection.Lx01");
+        write_segment(&first, 1, &[
+            (SECTION_DEVICE_INFORMATION, utf16_zlib(DEVICE_INFORMATION)),
+            (SECTION_CASE_DATA, utf16_zlib(CASE_DATA)),
+        ]);
+        let md5 = (0u8..16).collect::<Vec<u8>>();
+        write_segment(&dir.path().join("collection.Lx02"), 2, &[
+            (SECTION_SINGLE_FILES_DATA, utf16_zlib(FILE_ENTRIES)),
+            (SECTION_MD5_HASH, md5),
+        ]);
+        let path = first.to_string_lossy().to_string();
+
+        assert!(is_lx01_file(&path).unwrap());
+        let info = info(&path).unwrap();
+        assert_eq!(info.segment_count, 2);
+        assert_eq!(info.case_number.as_deref(), Some("CASE-2024-017"));
+        assert_eq!(info.evidence_number.as_deref(), Some("L001"));
+        assert_eq!(info.examiner_name.as_deref(), Some("J. Examiner"));
+        assert_eq!(info.description.as_deref(), Some("Suspect laptop user folder"));
+        assert_eq!(info.acquiry_date.map(|d| d.iso_utc).as_deref(), Some("2024-01-01T12:00:00Z"));
+        assert_eq!(info.compression, "Deflate");
+        assert_eq!(info.media_type.as_deref(), Some("logical"));
+        assert_eq!(info.logical_entries, Some(LogicalEntries { file_count: 3, folder_count: 1, total_bytes: 7168 }));
+        assert_eq!(info.total_size, 7168);
+        assert_eq!(info.stored_hashes.len(), 1);
+        assert_eq!(info.stored_hashes[0].hash, "000102030405060708090a0b0c0d0e0f");
+    }
+
+    #[test]
+    fn test_corrupt_section_chain_is_an_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("loop.Lx01");
+        write_segment(&path, 1, &[(SECTION_CASE_DATA, utf16_zlib(CASE_DATA))]);
+
+        // Point the done section back at itself
+        let mut data = std::fs::read(&path).unwrap();
+        let done = data.len() - SECTION_DESCRIPTOR_SIZE as usize;
+        data[done + 0x08..done + 0x10].copy_from_slice(&(done as u64).to_le_bytes());
+        std::fs::write(&path, data).unwrap();
+
+        let err = info(path.to_str().unwrap()).err().unwrap();
+        assert!(err.contains("Corrupt Lx01 section chain"), "{err}");
+    }
+}
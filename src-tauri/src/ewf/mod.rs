@@ -7,6 +7,7 @@
 //! - **L01**: Logical evidence files (EWF v1)
 //! - **Ex01**: Physical disk images (EWF v2)
 //! - **Lx01**: Logical evidence files (EWF v2)
+//! - **S01**: Legacy ASR Data SMART images (EWF-S01)
 //!
 //! ## EWF Format Overview
 //!
@@ -26,11 +27,12 @@
 //! │  - E01:  "EVF\x09\x0d\x0a\xff\x00"                           │
 //! │  - L01:  "LVF\x09\x0d\x0a\xff\x00"                           │
 //! │  - Ex01: "EVF2\x0d\x0a\x81\x00"                              │
-//! │  - Lx01: "LVF2\x0d\x0a\x81\x00"                              │
+//! │  - Lx01: "LVF2\x0d\x0a\x81\x00" or "LEF2\x0d\x0a\x81\x00"    │
+//! │  - S01:  same as E01, "SMART" marker in the volume section   │
 //! ├──────────────────────────────────────────────────────────────┤
 //! │ Section Chain (linked list of sections)                      │
 //! │  ┌─────────────────────────────────────────────────────────┐ │
-//! │  │ header: Case info, examiner, etc.                       │ │
+//! │  │ header/header2: Case info, examiner, etc.               │ │
 //! │  │ volume: Chunk count, sector info, compression           │ │
 //! │  │ sectors: Compressed chunk data                          │ │
 //! │  │ table: Chunk offset table                               │ │
@@ -47,6 +49,11 @@
 //! - Subsequent segments (.E02, .E03, etc.) contain additional chunk data
 //! - "next" sections indicate continuation to next segment
 //!
+//! Segments are found beside the given file. A set spread over several
+//! directories (one disk filled mid-acquisition) is opened from an explicit
+//! list with [`EwfHandle::open_with_segments`] or [`with_segment_list`],
+//! which check the order against the segment numbers in the file headers.
+//!
 //! ## Usage
 //!
 //! ```rust,ignore
@@ -74,14 +81,25 @@
 mod types;
 mod cache;
 mod handle;
+mod header;
 mod operations;
+mod lx01;
+mod structure;
+mod sample;
+mod full_hash;
 pub mod parser;  // Hex viewer parser for detailed metadata
 
 // Re-export public types
 pub use types::{
-    StoredImageHash, VolumeSection, EwfInfo, VerifyResult, HeaderInfo,
+    StoredImageHash, VolumeSection, EwfInfo, HeaderInfo,
+    ChunkErrorKind, ChunkReadError, EwfVerifyReport, EwfFullHashes, SegmentFileHashes, EwfSampleReport, SampleSize, SAMPLE_NOTICE, LogicalEntries, OrphanedSegment, SectorRange,
+    SegmentConsistency, SegmentHeader, ChunkStats,
+    SMART_FORMAT_VERSION, MAX_REPORTED_CHUNK_ERRORS, CHUNK_RATIO_BUCKETS,
 };
 
+// Re-export header section decoding
+pub use header::{HeaderValues, HEADER_FIELDS, header_field_name};
+
 // Re-export parser types for hex viewer
 pub use parser::{
     EwfVariant, EwfDetailedInfo, EwfSectionHeader, EwfVolumeInfo, 
@@ -95,7 +113,22 @@ pub use handle::EwfHandle;
 
 // Re-export public functions
 pub use operations::{
-    info, is_e01, is_ewf, get_segment_paths, hash_single_segment,
-    verify, verify_with_progress, verify_chunks,
-    extract,
+    info, info_with_options, is_e01, is_ewf, is_smart, get_segment_paths, segment_number, find_orphaned_segment,
+    segment_headers, validate_segment_list, with_segment_list, verify_ignoring_segment_order,
+    hash_single_segment,
+    verify, verify_with_progress, verify_best_effort_with_progress, verify_report, verify_chunks,
+    extract, extract_with_progress, plan_extract, DEFAULT_EXTRACT_IN_FLIGHT_CHUNKS,
+    capabilities, l01_capabilities,
 };
+
+// Re-export the header-only structure check
+pub use structure::{quick_check, verify_preflight};
+
+// Re-export the sampled chunk check
+pub use sample::sample_verify;
+
+// Re-export the combined image and segment hashing
+pub use full_hash::full_hash;
+
+// Re-export Lx01 (EWF2 logical) metadata parsing
+pub use lx01::{info as lx01_info, is_lx01_file, lx01_segment_paths, capabilities as lx01_capabilities};
@@ -2,35 +2,76 @@
 
 use std::fs::File;
 use std::io::{Read, Write};
+use std::collections::HashSet;
 use std::path::{Path, PathBuf};
+use std::time::Instant;
 
-use md5::Md5;
-use sha1::{Sha1, Digest};
-use sha2::{Sha256, Sha512};
-use blake2::Blake2b512;
-use blake3::Hasher as Blake3Hasher;
-use xxhash_rust::xxh3::Xxh3;
-use xxhash_rust::xxh64::Xxh64;
 use rayon::prelude::*;
-use tracing::{debug, instrument};
+use tracing::{debug, info, instrument, warn};
 
 use crate::common::{
-    BUFFER_SIZE, MMAP_THRESHOLD,
-    hash::StreamingHasher,
-    segments::discover_e01_segments,
+    BUFFER_SIZE,
+    atomic_file::AtomicFile,
+    capabilities::FormatCapabilities,
+    content_map::{ContentMapBuilder, DEFAULT_MAP_WINDOW},
+    extract_manifest::{ExtractedFile, HashingWriter},
+    hash::{HashAlgorithm, StreamingHasher},
+    progress,
+    resources::{self, IoPriority},
+    safe_path::sanitize_component,
+    segments::{self, discover_e01_segments, discover_ewf_segments, ewf_first_segment, existing_case_variant},
+    time::Timestamp,
 };
+use crate::containers::{VerifyEntry, VerifyOptions, VerifyReason, VerifyStatus, VerifyTarget};
+use crate::logging::perf;
 
 use super::types::*;
 use super::handle::EwfHandle;
 
+/// E01/Ex01/SMART physical images: whole-image hash, per-segment hashes and
+/// export of the media as a raw image
+pub const fn capabilities() -> FormatCapabilities {
+    FormatCapabilities {
+        can_info: true,
+        can_info_tree: false,
+        can_verify_whole: true,
+        can_verify_items: false,
+        can_verify_segments: true,
+        can_extract: true,
+        can_extract_item: false,
+        notes: &["File systems inside the image are not parsed - extraction exports the whole media"],
+    }
+}
+
+/// L01 logical evidence: the chunk data is verified, but the file entries
+/// in the ltree section are not parsed, so there is nothing to extract
+pub const fn l01_capabilities() -> FormatCapabilities {
+    FormatCapabilities {
+        can_info: true,
+        can_info_tree: false,
+        can_verify_whole: true,
+        can_verify_items: false,
+        can_verify_segments: false,
+        can_extract: false,
+        can_extract_item: false,
+        notes: &["L01 file entries are not parsed yet: no tree, per-file hashes or extraction"],
+    }
+}
+
 // =============================================================================
 // Info Operations
 // =============================================================================
 
-#[instrument]
 pub fn info(path: &str) -> Result<EwfInfo, String> {
-    debug!("Getting EWF info");
-    let handle = EwfHandle::open(path)?;
+    info_with_options(path, false)
+}
+
+/// Info; with `best_effort`, a damaged section chain keeps the metadata
+/// parsed before the damage and is reported in `parse_warnings`
+#[instrument]
+pub fn info_with_options(path: &str, best_effort: bool) -> Result<EwfInfo, String> {
+    debug!(best_effort, "Getting EWF info");
+    let handle = if best_effort { EwfHandle::open_best_effort(path)? } else { EwfHandle::open(path)? };
     let volume = handle.get_volume_info();
     let total_size = volume.sector_count * volume.bytes_per_sector as u64;
     
@@ -55,12 +96,9 @@ pub fn info(path: &str) -> Result<EwfInfo, String> {
     };
     
     // Get file modification time as fallback timestamp
-    let file_timestamp: Option<String> = Path::new(path).metadata().ok()
+    let file_timestamp = Path::new(path).metadata().ok()
         .and_then(|m| m.modified().ok())
-        .map(|t| {
-            let datetime: chrono::DateTime<chrono::Utc> = t.into();
-            datetime.format("%Y-%m-%d %H:%M:%S").to_string()
-        });
+        .map(Timestamp::from_system_time);
     
     // Use acquiry_date from header if available, otherwise fall back to file timestamp
     let acquiry_date = handle.header_info.acquiry_date.clone()
@@ -72,7 +110,7 @@ pub fn info(path: &str) -> Result<EwfInfo, String> {
             algorithm: h.algorithm.clone(),
             hash: h.hash.clone(),
             verified: h.verified,
-            timestamp: h.timestamp.clone().or_else(|| acquiry_date.clone()),
+            timestamp: h.timestamp.clone().or_else(|| acquiry_date.as_ref().map(Timestamp::to_string)),
             source: h.source.clone(),
             offset: h.offset,
             size: h.size,
@@ -88,7 +126,7 @@ pub fn info(path: &str) -> Result<EwfInfo, String> {
     if let Some(first_segment) = handle.segments.first() {
         for section in &first_segment.sections {
             match section.section_type.as_str() {
-                "header" => {
+                "header" | "header2" => {
                     if header_section_offset.is_none() {
                         header_section_offset = Some(section.offset_in_segment);
                     }
@@ -122,8 +160,24 @@ pub fn info(path: &str) -> Result<EwfInfo, String> {
         "EWF info complete"
     );
     
+    let segment_consistency = segment_headers(path)
+        .inspect_err(|e| debug!(path, error = %e, "Segment headers not checked"))
+        .ok();
+    if let Some(check) = segment_consistency.as_ref().filter(|check| !check.is_consistent()) {
+        warn!(path, issues = ?check.issues, "EWF segment files do not match their headers");
+    }
+    
+    // SMART volumes have no media type byte
+    let media_type = if !handle.sessions.is_empty() {
+        media_type_name(MEDIA_TYPE_OPTICAL)
+    } else if handle.is_smart() {
+        None
+    } else {
+        media_type_name(volume.media_type)
+    };
+    
     Ok(EwfInfo {
-        format_version: "EWF1".to_string(),
+        format_version: if handle.is_smart() { SMART_FORMAT_VERSION.to_string() } else { "EWF1".to_string() },
         segment_count,
         chunk_count: handle.get_chunk_count() as u32,
         sector_count: volume.sector_count,
@@ -140,12 +194,18 @@ pub fn info(path: &str) -> Result<EwfInfo, String> {
         system_date: handle.header_info.system_date.clone(),
         model: None,
         serial_number: None,
+        media_type: media_type.map(str::to_string),
+        sessions: handle.sessions.clone(),
+        acquisition_errors: handle.acquisition_errors.clone(),
+        logical_entries: None,
         stored_hashes,
         segment_files,
         header_section_offset,
         volume_section_offset,
         hash_section_offset,
         digest_section_offset,
+        segment_consistency,
+        parse_warnings: handle.parse_warnings.clone(),
     })
 }
 
@@ -167,16 +227,76 @@ pub fn is_e01(path: &str) -> Result<bool, String> {
         return Ok(false);
     }
     
-    // Check all EWF variants: E01 (EVF), Ex01 (EVF2), L01 (LVF), Lx01 (LVF2)
+    // Check all EWF variants: E01 (EVF), Ex01 (EVF2), L01 (LVF), Lx01 (LVF2/LEF2)
     let is_ewf1 = &sig == EWF_SIGNATURE;
     let is_ewf2 = &sig == EWF2_SIGNATURE;
     let is_lvf1 = &sig == LVF_SIGNATURE;
-    let is_lvf2 = &sig == LVF2_SIGNATURE;
+    let is_lvf2 = &sig == LVF2_SIGNATURE || &sig == LEF2_SIGNATURE;
     debug!("is_e01: {} -> sig={:02x?} ewf1={} ewf2={} lvf1={} lvf2={}", 
            path, &sig, is_ewf1, is_ewf2, is_lvf1, is_lvf2);
     Ok(is_ewf1 || is_ewf2 || is_lvf1 || is_lvf2)
 }
 
+/// Segment number from the EWF file header (1 for the first segment of a set)
+pub fn segment_number(path: &str) -> Result<u32, String> {
+    let mut file = File::open(path)
+        .map_err(|e| format!("Failed to open file: {}", e))?;
+    let mut header = [0u8; 16];
+    file.read_exact(&mut header)
+        .map_err(|e| format!("Failed to read EWF file header: {}", e))?;
+    let signature = &header[..8];
+    if signature == EWF_SIGNATURE || signature == LVF_SIGNATURE {
+        // EWF1: fields start byte, then a 16-bit segment number
+        Ok(u16::from_le_bytes([header[9], header[10]]) as u32)
+    } else if signature == EWF2_SIGNATURE || signature == LVF2_SIGNATURE || signature == LEF2_SIGNATURE {
+        // EWF2: major/minor version and compression method come first
+        Ok(u32::from_le_bytes([header[12], header[13], header[14], header[15]]))
+    } else {
+        Err(format!("Not an EWF file: {}", path))
+    }
+}
+
+/// The segment `path` is, if it is a later segment whose set has no first
+/// segment next to it
+///
+/// A later segment with its first segment present is fine - segment
+/// discovery starts from the first one.
+pub fn find_orphaned_segment(path: &str) -> Option<OrphanedSegment> {
+    // A listed set has been checked to start with its first segment
+    if segments::is_listed_segment(path) {
+        return None;
+    }
+    let segment_number = segment_number(path).ok()?;
+    if segment_number <= 1 {
+        return None;
+    }
+    let path_obj = Path::new(path);
+    let first = ewf_first_segment(path_obj).map(|(_, first)| first).unwrap_or_else(|| {
+        // Other naming (.Ex02, .L02): the digits of the extension restart at 01
+        let ext = path_obj.extension().map(|e| e.to_string_lossy().to_string()).unwrap_or_default();
+        let prefix = ext.trim_end_matches(|c: char| c.is_ascii_digit());
+        path_obj.with_extension(format!("{}01", if prefix.is_empty() { "E" } else { prefix }))
+    });
+    if existing_case_variant(&first).is_some() {
+        return None;
+    }
+    let orphan = OrphanedSegment {
+        path: path.to_string(),
+        segment_number,
+        expected_first: first.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default(),
+    };
+    debug!(path, segment_number, expected_first = %orphan.expected_first, "EWF set is missing its first segment");
+    Some(orphan)
+}
+
+/// Check if file is a SMART (EWF-S01) image
+///
+/// SMART images carry the EVF signature, so this combines the signature
+/// check with the `.s01` naming used by ASR Data's tools.
+pub fn is_smart(path: &str) -> Result<bool, String> {
+    Ok(super::handle::is_smart_extension(path) && is_e01(path)?)
+}
+
 /// Alias for is_e01 - check if file is any EWF variant
 #[inline]
 pub fn is_ewf(path: &str) -> Result<bool, String> {
@@ -188,6 +308,152 @@ pub fn get_segment_paths(path: &str) -> Result<Vec<PathBuf>, String> {
     discover_e01_segments(path)
 }
 
+/// Check that `segments` are the segments of one EWF set in order, by the
+/// segment numbers and set identifiers in their headers
+pub fn validate_segment_list(segments: &[String]) -> Result<Vec<PathBuf>, String> {
+    if segments.is_empty() {
+        return Err("No segment files listed".to_string());
+    }
+    let paths: Vec<PathBuf> = segments.iter().map(PathBuf::from).collect();
+    let mut reference: Option<String> = None;
+    for (index, path) in paths.iter().enumerate() {
+        let header = read_segment_header(path);
+        match header.segment_number {
+            Some(number) if number as usize == index + 1 => {}
+            Some(number) => return Err(format!(
+                "{} is segment {} by its header but listed as segment {}", path.display(), number, index + 1
+            )),
+            None => return Err(format!("{} has no readable EWF segment header", path.display())),
+        }
+        match (header.set_identifier, &reference) {
+            (Some(id), Some(reference)) if &id != reference => return Err(format!(
+                "{} belongs to a different acquisition (set {} instead of {})", path.display(), id, reference
+            )),
+            (Some(id), None) => reference = Some(id),
+            _ => {}
+        }
+    }
+    Ok(paths)
+}
+
+/// Run `work` on the image at `path` with its segments taken from
+/// `segments` instead of the directory of `path`
+///
+/// The list is checked with [`validate_segment_list`] first; `path` must be
+/// one of its files.
+pub fn with_segment_list<T>(path: &str, segments: &[String], work: impl FnOnce() -> Result<T, String>) -> Result<T, String> {
+    let paths = validate_segment_list(segments)?;
+    if !paths.iter().any(|segment| segment == Path::new(path)) {
+        return Err(format!("{} is not one of the listed segment files", path));
+    }
+    debug!(path, segment_count = paths.len(), "EWF set given as a segment list");
+    segments::with_listed_segments(paths, work)
+}
+
+/// Read every segment's header number and set identifier and check them
+/// against the filename order
+pub fn segment_headers(path: &str) -> Result<SegmentConsistency, String> {
+    let set = discover_ewf_segments(path)?;
+    let segments: Vec<SegmentHeader> = set.paths.iter()
+        .map(|segment| read_segment_header(segment))
+        .collect();
+    
+    let mut issues = Vec::new();
+    for (index, segment) in segments.iter().enumerate() {
+        let expected = index as u32 + 1;
+        match segment.segment_number {
+            Some(number) if number != expected => issues.push(format!(
+                "{} is segment {} by its header but segment {} by its name",
+                segment.filename, number, expected
+            )),
+            None => issues.push(format!("{} has no readable segment header", segment.filename)),
+            _ => {}
+        }
+    }
+    let order_matches = issues.is_empty();
+    
+    let reference = segments.iter().find_map(|segment| segment.set_identifier.as_deref());
+    let mut same_set = true;
+    for segment in &segments {
+        if let (Some(id), Some(reference)) = (segment.set_identifier.as_deref(), reference) {
+            if id != reference {
+                same_set = false;
+                issues.push(format!("{} belongs to a different acquisition (set {} instead of {})", segment.filename, id, reference));
+            }
+        }
+    }
+    
+    let single_naming = set.conflicts.is_empty();
+    issues.extend(set.conflicts);
+    
+    Ok(SegmentConsistency { segments, order_matches, same_set, single_naming, issues })
+}
+
+/// Segment number and set identifier of one segment file
+///
+/// EWF2 keeps both in the file header; EWF1 keeps the set identifier in the
+/// volume/disk section of the first segment and the data section of later
+/// ones.
+fn read_segment_header(path: &Path) -> SegmentHeader {
+    let filename = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let read = || -> std::io::Result<(Option<u32>, Option<String>)> {
+        let mut file = File::open(path)?;
+        let mut header = [0u8; 32];
+        file.read_exact(&mut header[..EWF_FILE_HEADER_SIZE])?;
+        let signature = &header[..8];
+        if signature == EWF_SIGNATURE || signature == LVF_SIGNATURE {
+            let number = u16::from_le_bytes([header[9], header[10]]) as u32;
+            return Ok((Some(number), ewf1_set_identifier(&mut file)?));
+        }
+        if signature == EWF2_SIGNATURE || signature == LVF2_SIGNATURE || signature == LEF2_SIGNATURE {
+            file.read_exact(&mut header[EWF_FILE_HEADER_SIZE..])?;
+            let number = u32::from_le_bytes([header[12], header[13], header[14], header[15]]);
+            return Ok((Some(number), set_identifier(&header[16..32])));
+        }
+        Ok((None, None))
+    };
+    let (segment_number, set_identifier) = read().unwrap_or_else(|e| {
+        debug!(path = %path.display(), error = %e, "Failed to read EWF segment header");
+        (None, None)
+    });
+    SegmentHeader { filename, segment_number, set_identifier }
+}
+
+/// Walk the first sections of an EWF1 segment to its volume/disk/data section
+fn ewf1_set_identifier(file: &mut File) -> std::io::Result<Option<String>> {
+    use std::io::{Seek, SeekFrom};
+    
+    let file_size = file.metadata()?.len();
+    let mut offset = EWF_FILE_HEADER_SIZE as u64;
+    // The section sits right after the header (and header2) sections
+    for _ in 0..8 {
+        if offset + SECTION_DESCRIPTOR_SIZE as u64 > file_size {
+            break;
+        }
+        let mut descriptor = [0u8; SECTION_DESCRIPTOR_SIZE];
+        file.seek(SeekFrom::Start(offset))?;
+        file.read_exact(&mut descriptor)?;
+        let kind = String::from_utf8_lossy(&descriptor[..16]).trim_matches('\0').to_string();
+        if matches!(kind.as_str(), "volume" | "disk" | "data") {
+            let mut guid = [0u8; 16];
+            file.seek(SeekFrom::Start(offset + (SECTION_DESCRIPTOR_SIZE + VOLUME_SET_IDENTIFIER_OFFSET) as u64))?;
+            file.read_exact(&mut guid)?;
+            return Ok(set_identifier(&guid));
+        }
+        let next = u64::from_le_bytes(descriptor[16..24].try_into().unwrap());
+        if next <= offset || kind == "sectors" || kind == "done" {
+            break;
+        }
+        offset = next;
+    }
+    Ok(None)
+}
+
+/// Format a set identifier GUID (all zeros means none was recorded)
+fn set_identifier(bytes: &[u8]) -> Option<String> {
+    bytes.iter().any(|&b| b != 0).then(|| super::parser::format_guid(bytes))
+}
+
 // =============================================================================
 // Segment Hashing
 // =============================================================================
@@ -212,10 +478,10 @@ where
     let file = File::open(path)
         .map_err(|e| format!("Failed to open segment: {}", e))?;
     
-    let algorithm_lower = algorithm.to_lowercase();
+    let algo = HashAlgorithm::from_str(algorithm)?;
     
     // For BLAKE3 with large files, use mmap + parallel hashing
-    if algorithm_lower == "blake3" && total_size >= MMAP_THRESHOLD {
+    if algo == HashAlgorithm::Blake3 && resources::use_mmap(total_size) {
         let mmap = unsafe { Mmap::map(&file) }
             .map_err(|e| format!("Failed to mmap file: {}", e))?;
         
@@ -233,11 +499,11 @@ where
     }
     
     // For large files, use mmap for better I/O
-    if total_size >= MMAP_THRESHOLD {
+    if resources::use_mmap(total_size) {
         let mmap = unsafe { Mmap::map(&file) }
             .map_err(|e| format!("Failed to mmap file: {}", e))?;
         
-        let mut hasher = StreamingHasher::from_str(algorithm)?;
+        let mut hasher = StreamingHasher::new(algo);
         let chunk_size = 64 * 1024 * 1024;
         let mut bytes_processed = 0u64;
         
@@ -251,10 +517,10 @@ where
     }
     
     // Standard BufReader path for smaller files
-    let mut reader = std::io::BufReader::with_capacity(BUFFER_SIZE, file);
+    let mut reader = std::io::BufReader::with_capacity(resources::io_buffer_size(), file);
     
     // For BLAKE3 without mmap, still use parallel hashing
-    if algorithm_lower == "blake3" {
+    if algo == HashAlgorithm::Blake3 {
         let mut hasher = blake3::Hasher::new();
         let mut bytes_read_total = 0u64;
         let report_interval = (total_size / 20).max(BUFFER_SIZE as u64);
@@ -281,7 +547,7 @@ where
     }
     
     // For other algorithms, use StreamingHasher
-    let mut hasher = StreamingHasher::from_str(algorithm)?;
+    let mut hasher = StreamingHasher::new(algo);
     
     let mut bytes_read_total = 0u64;
     let report_interval = (total_size / 20).max(BUFFER_SIZE as u64);
@@ -312,66 +578,281 @@ where
 // =============================================================================
 
 /// Verify image and return detailed results for each chunk (used by containers.rs)
-pub fn verify_chunks(path: &str, algorithm: &str) -> Result<Vec<VerifyResult>, String> {
-    let hash = verify_with_progress(path, algorithm, |_, _| {})?;
-    
-    Ok(vec![VerifyResult {
-        chunk_index: 0,
-        status: "ok".to_string(),
-        message: Some(hash),
-    }])
+///
+/// The first entry carries the image hash, followed by one skipped entry
+/// with reason "acquisition_read_error" per chunk the acquisition
+/// zero-filled. An unreadable chunk fails the verification unless
+/// `best_effort` is set: then the image entry is an error with reason
+/// "partial_image" (its hash covers zeros for the failed chunks) and each
+/// failed chunk gets an error entry.
+pub fn verify_chunks(path: &str, algorithm: &str, best_effort: bool) -> Result<Vec<VerifyEntry>, String> {
+    let started = Instant::now();
+    let report = verify_report(path, algorithm, &VerifyOptions { best_effort, ..Default::default() }, |_, _| {})?;
+
+    let mut results = Vec::with_capacity(1 + report.failed_chunks.len());
+    let image = if report.complete {
+        VerifyEntry::new(VerifyTarget::WholeImage { label: None }, VerifyStatus::Ok)
+    } else {
+        VerifyEntry::new(VerifyTarget::WholeImage { label: None }, VerifyStatus::Error).reason(VerifyReason::PartialImage)
+    };
+    results.push(image
+        .hashes(&algorithm.to_uppercase(), None, Some(&report.hash))
+        .message(report.hash.clone())
+        .duration(started.elapsed()));
+    let acquisition_errors = &report.acquisition_error_chunks;
+    results.extend(report.failed_chunks.iter().map(|failure| {
+        let mut message = failure.to_string();
+        if acquisition_errors.binary_search(&failure.chunk_index).is_ok() {
+            message.push_str(" (chunk also contains acquisition-time read errors)");
+        }
+        VerifyEntry::new(VerifyTarget::Chunk { index: failure.chunk_index }, VerifyStatus::Error).message(message)
+    }));
+    // Chunks that read fine but hold sectors zero-filled at acquisition
+    let failed: HashSet<usize> = report.failed_chunks.iter().map(|failure| failure.chunk_index).collect();
+    results.extend(acquisition_errors.iter().filter(|index| !failed.contains(index)).map(|&index| {
+        VerifyEntry::new(VerifyTarget::Chunk { index }, VerifyStatus::Skipped)
+            .reason(VerifyReason::AcquisitionReadError)
+            .message(format!("Chunk {} contains acquisition-time read errors (zero-filled)", index))
+    }));
+
+    Ok(results)
 }
 
-/// Extract image contents to a raw file
-pub fn extract(path: &str, output_dir: &str) -> Result<(), String> {
-    let mut handle = EwfHandle::open(path)?;
-    let volume = handle.get_volume_info();
-    let chunk_count = handle.get_chunk_count();
-    
-    // Create output filename based on input path
-    let input_path = Path::new(path);
-    let stem = input_path.file_stem()
+/// Raw file `extract` writes for `path`: `<output_dir>/<stem>.raw`
+fn extract_output_path(path: &str, output_dir: &str) -> PathBuf {
+    let stem = Path::new(path).file_stem()
         .map(|s| s.to_string_lossy().to_string())
         .unwrap_or_else(|| "image".to_string());
-    
-    let output_path = Path::new(output_dir).join(format!("{}.raw", stem));
-    let mut output = File::create(&output_path)
-        .map_err(|e| format!("Failed to create output file: {}", e))?;
-    
+    let (file_name, _) = sanitize_component(&format!("{}.raw", stem));
+    Path::new(output_dir).join(file_name)
+}
+
+/// The raw file `extract` would write, without writing it
+pub fn plan_extract(path: &str, output_dir: &str) -> Result<ExtractedFile, String> {
+    let handle = EwfHandle::open(path)?;
+    let volume = handle.get_volume_info();
+    let image_name = Path::new(path).file_name()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    Ok(ExtractedFile::planned(
+        &image_name,
+        &extract_output_path(path, output_dir),
+        volume.sector_count * volume.bytes_per_sector as u64,
+    ))
+}
+
+/// Chunks decompressed ahead of the writer during extraction (~16MB of
+/// 32KB chunks)
+pub const DEFAULT_EXTRACT_IN_FLIGHT_CHUNKS: usize = 512;
+
+/// Extract image contents to a raw file, hashing the bytes as they are written
+pub fn extract(path: &str, output_dir: &str) -> Result<ExtractedFile, String> {
+    extract_with_progress(path, output_dir, DEFAULT_EXTRACT_IN_FLIGHT_CHUNKS, |_, _| {})
+}
+
+/// Extract image contents to a raw file with pipelined reading
+///
+/// Worker threads (one segment handle each) read and decompress batches of
+/// chunks in parallel while this thread writes them strictly in order, so
+/// the output is written sequentially and hashed on the way out. At most
+/// `in_flight_chunks` chunks are read ahead of the writer. The output is
+/// `sector_count × bytes_per_sector` bytes, the last chunk truncated to fit.
+/// Progress is reported in bytes written.
+#[instrument(skip(progress_callback))]
+pub fn extract_with_progress<F>(path: &str, output_dir: &str, in_flight_chunks: usize, mut progress_callback: F) -> Result<ExtractedFile, String>
+where
+    F: FnMut(u64, u64)
+{
+    use std::collections::BTreeMap;
+    use std::sync::{mpsc, Arc, Mutex};
+    use std::thread;
+
+    let handle = EwfHandle::open(path)?;
+    let volume = handle.get_volume_info();
+    let chunk_count = handle.get_chunk_count();
     let total_bytes = volume.sector_count * volume.bytes_per_sector as u64;
+    let chunk_size = handle.get_chunk_size() as usize;
+    drop(handle);
+
+    let output_path = extract_output_path(path, output_dir);
+    let output = AtomicFile::create_with_capacity(&output_path, resources::io_buffer_size())
+        .and_then(|output| output.with_progress_markers(Some(total_bytes)))
+        .map_err(|e| format!("Failed to create output file: {}", e))?;
+    let mut output = HashingWriter::new(output);
+
+    // Enough batches in flight to keep every worker busy, each small enough
+    // that the read-ahead stays within `in_flight_chunks`
+    let in_flight_chunks = in_flight_chunks.min(chunk_budget(chunk_size)).max(1);
+    let workers = rayon::current_num_threads().clamp(1, in_flight_chunks);
+    let batch_size = (in_flight_chunks / (workers * 2)).clamp(1, 64);
+    let max_batches = (in_flight_chunks / batch_size).max(1);
+    let batch_count = chunk_count.div_ceil(batch_size);
+    debug!(chunk_count, workers, batch_size, max_batches, "Pipelined EWF extraction");
+
+    // The writer hands out batch numbers as credits: a new one only after
+    // a batch is written, which bounds the chunks held in memory
+    let (work_tx, work_rx) = mpsc::channel::<usize>();
+    let work_rx = Arc::new(Mutex::new(work_rx));
+    let (result_tx, result_rx) = mpsc::channel::<(usize, Result<Batch, String>)>();
+    let io_priority = resources::current_io_priority();
+    let low_memory = resources::low_memory();
+    let worker_handles: Vec<_> = (0..workers).map(|_| {
+        let work_rx = Arc::clone(&work_rx);
+        let result_tx = result_tx.clone();
+        let path = path.to_string();
+        let listed = segments::current_listed_segments();
+        let job_span = tracing::Span::current();
+        thread::spawn(move || {
+            let _job = job_span.enter();
+            segments::set_current_thread_listed_segments(listed);
+            if io_priority != IoPriority::Normal {
+                resources::set_current_thread_io_priority(io_priority);
+            }
+            resources::set_current_thread_low_memory(low_memory);
+            let mut handle = match EwfHandle::open(&path) {
+                Ok(handle) => handle,
+                Err(e) => {
+                    let _ = result_tx.send((usize::MAX, Err(e)));
+                    return;
+                }
+            };
+            loop {
+                let next = work_rx.lock().map(|rx| rx.recv());
+                let Ok(Ok(batch)) = next else { return };
+                let start = batch * batch_size;
+                let chunks = read_batch(&mut handle, start..(start + batch_size).min(chunk_count), false);
+                if result_tx.send((batch, chunks)).is_err() {
+                    return;
+                }
+            }
+        })
+    }).collect();
+    drop(result_tx);
+
+    let mut next_credit = 0;
+    while next_credit < batch_count.min(max_batches) {
+        let _ = work_tx.send(next_credit);
+        next_credit += 1;
+    }
+
+    let mut pending: BTreeMap<usize, Vec<Vec<u8>>> = BTreeMap::new();
+    let mut next_batch = 0;
     let mut bytes_written = 0u64;
-    
-    for i in 0..chunk_count {
-        let chunk_data = handle.read_chunk_no_cache(i)?;
-        
-        let bytes_to_write = if bytes_written + chunk_data.len() as u64 > total_bytes {
-            (total_bytes - bytes_written) as usize
-        } else {
-            chunk_data.len()
-        };
-        
-        output.write_all(&chunk_data[..bytes_to_write])
-            .map_err(|e| format!("Failed to write to output: {}", e))?;
-        
-        bytes_written += bytes_to_write as u64;
-        
-        if bytes_written >= total_bytes {
-            break;
+    let written = (|| -> Result<(), String> {
+        while next_batch < batch_count && bytes_written < total_bytes {
+            let (batch, chunks) = recv_from_workers(&result_rx, &worker_handles)?;
+            pending.insert(batch, chunks?.0);
+            while let Some(chunks) = pending.remove(&next_batch) {
+                let writing = perf::phase(perf::WRITING).entered();
+                let batch_start = bytes_written;
+                for chunk_data in chunks {
+                    let bytes_to_write = (chunk_data.len() as u64).min(total_bytes - bytes_written) as usize;
+                    output.write_all(&chunk_data[..bytes_to_write])
+                        .map_err(|e| format!("Failed to write to output: {}", e))?;
+                    bytes_written += bytes_to_write as u64;
+                    if bytes_written >= total_bytes {
+                        break;
+                    }
+                }
+                writing.record("bytes", bytes_written - batch_start);
+                drop(writing);
+                progress_callback(bytes_written, total_bytes);
+                next_batch += 1;
+                if next_credit < batch_count {
+                    let _ = work_tx.send(next_credit);
+                    next_credit += 1;
+                }
+            }
+        }
+        Ok(())
+    })();
+
+    // Closing the work queue lets idle workers exit; busy ones finish their batch
+    drop(work_tx);
+    drop(result_rx);
+    for worker in worker_handles {
+        worker.join().map_err(|_| "Extraction worker panicked".to_string())?;
+    }
+    written?;
+
+    // The media stream has no file timestamps of its own
+    let image_name = Path::new(path).file_name()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    output.commit(&image_name)
+}
+
+/// How often a writer waiting on extraction workers checks they are alive
+const WORKER_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(200);
+
+/// Next result from the extraction workers
+///
+/// Workers only exit while batches are outstanding by panicking, and a
+/// panicked worker takes its batch with it: the others keep their senders
+/// open, so a plain `recv` would wait forever for the missing batch.
+fn recv_from_workers<T>(results: &std::sync::mpsc::Receiver<T>, workers: &[std::thread::JoinHandle<()>]) -> Result<T, String> {
+    use std::sync::mpsc::RecvTimeoutError;
+    loop {
+        match results.recv_timeout(WORKER_POLL_INTERVAL) {
+            Ok(result) => return Ok(result),
+            Err(RecvTimeoutError::Timeout) => {
+                if workers.iter().any(|worker| worker.is_finished()) {
+                    return Err("Extraction worker panicked; its batch was never read".to_string());
+                }
+            }
+            Err(RecvTimeoutError::Disconnected) => return Err("Extraction workers stopped unexpectedly".to_string()),
         }
     }
-    
-    Ok(())
 }
 
 pub fn verify(path: &str, algorithm: &str) -> Result<String, String> {
     verify_with_progress(path, algorithm, |_current, _total| {})
 }
 
+/// Verify the image; refuses sets whose segment headers contradict the
+/// filename order (see [`segment_headers`])
 pub fn verify_with_progress<F>(path: &str, algorithm: &str, progress_callback: F) -> Result<String, String> 
 where
     F: FnMut(usize, usize)
 {
-    verify_with_progress_optimized(path, algorithm, progress_callback)
+    verify_with_progress_optimized(path, algorithm, &VerifyOptions::default(), progress_callback)
+        .map(|report| report.hash)
+}
+
+/// Best-effort verification: keeps hashing past unreadable chunks
+///
+/// Each failed chunk is hashed as zeros and recorded (with the sector range it
+/// covers) in the returned report, so the examiner can see which part of the
+/// disk is affected instead of a bare "verification failed".
+pub fn verify_best_effort_with_progress<F>(path: &str, algorithm: &str, progress_callback: F) -> Result<EwfVerifyReport, String>
+where
+    F: FnMut(usize, usize)
+{
+    verify_with_progress_optimized(path, algorithm, &VerifyOptions { best_effort: true, ..Default::default() }, progress_callback)
+}
+
+/// Verify in filename order even when segment headers disagree with it
+///
+/// The problems are logged and returned in `segment_issues`; the hash is
+/// of the files as named, which is what a forced run is for (e.g. to show
+/// the mismatch is caused by the renamed parts).
+pub fn verify_ignoring_segment_order<F>(path: &str, algorithm: &str, best_effort: bool, progress_callback: F) -> Result<EwfVerifyReport, String>
+where
+    F: FnMut(usize, usize)
+{
+    verify_with_progress_optimized(path, algorithm, &VerifyOptions { best_effort, force: true, ..Default::default() }, progress_callback)
+}
+
+/// Verify with every option explicit: `best_effort` and `force` as in
+/// [`verify_best_effort_with_progress`] and [`verify_ignoring_segment_order`];
+/// `collect_stats` adds chunk storage statistics to the report at the cost
+/// of a few counters per chunk; `collect_map` adds a zero/entropy map of the
+/// image at the cost of a counter per byte
+pub fn verify_report<F>(path: &str, algorithm: &str, options: &VerifyOptions, progress_callback: F) -> Result<EwfVerifyReport, String>
+where
+    F: FnMut(usize, usize)
+{
+    verify_with_progress_optimized(path, algorithm, options, progress_callback)
 }
 
 /// Optimized E01 verification with batched I/O and parallel decompression
@@ -381,44 +862,100 @@ where
 /// 2. Single file handle per segment (no handle pool contention)
 /// 3. Parallel decompression using rayon
 /// 4. Pipelined I/O: read next batch while hashing current batch
-fn verify_with_progress_optimized<F>(path: &str, algorithm: &str, mut progress_callback: F) -> Result<String, String> 
+///
+/// Without `best_effort` the first chunk error aborts verification, and a set
+/// that fails [`verify_preflight`](super::verify_preflight) (a truncated segment,
+/// no done section) is refused before any chunk is read. Without `force` a set
+/// whose segment headers contradict the filename order is refused.
+/// With `collect_stats` the reader's chunk counters travel with each batch;
+/// with `collect_map` the hashing thread also counts the bytes it hashes.
+fn verify_with_progress_optimized<F>(
+    path: &str,
+    algorithm: &str,
+    options: &VerifyOptions,
+    mut progress_callback: F,
+) -> Result<EwfVerifyReport, String> 
 where
     F: FnMut(usize, usize)
 {
+    let VerifyOptions { best_effort, force, collect_stats, collect_map, .. } = *options;
     use std::sync::mpsc;
     use std::thread;
     use std::sync::atomic::{AtomicUsize, Ordering};
     use std::sync::Arc;
     
-    debug!(path = %path, "Starting optimized EWF verification");
+    debug!(path = %path, best_effort, "Starting optimized EWF verification");
+    
+    // Hashing a shuffled set gives a wrong hash that looks like any other
+    let mut segment_issues = Vec::new();
+    if let Some(check) = segment_headers(path).ok().filter(|check| !check.is_consistent()) {
+        if !force {
+            return Err(format!(
+                "Segment files do not form one consistent set, so the image would be hashed wrong: {}",
+                check.issues.join("; ")
+            ));
+        }
+        warn!(path = %path, issues = ?check.issues, "Verifying despite segment set problems");
+        segment_issues = check.issues;
+    }
+    
+    // A truncated copy would otherwise end in a seek error part way through
+    if let Some(problem) = super::structure::verify_preflight(path) {
+        if !best_effort {
+            return Err(problem);
+        }
+        warn!(path = %path, problem = %problem, "Verifying a damaged segment set");
+        segment_issues.push(problem);
+    }
     
     let handle = EwfHandle::open(path)?;
     let chunk_count = handle.get_chunk_count();
     let chunk_size = (handle.get_volume_info().sectors_per_chunk as usize) 
                    * (handle.get_volume_info().bytes_per_sector as usize);
     
+    // Zero-filled at acquisition: a mismatch against the source drive is
+    // expected there, not a sign of damage in storage
+    let acquisition_errors = handle.acquisition_errors.clone();
+    let unreadable_sector_count: u64 = acquisition_errors.iter().map(|range| range.sector_count).sum();
+    let acquisition_error_chunks: Vec<usize> = handle.acquisition_error_chunks().take(MAX_REPORTED_CHUNK_ERRORS).collect();
+    if unreadable_sector_count > 0 {
+        info!(path = %path, unreadable_sector_count, "Image holds sectors the acquisition could not read");
+    }
+    
     debug!(chunk_count, chunk_size, "EWF info for verification");
     
-    // Algorithm selection
-    let algorithm_lower = algorithm.to_lowercase();
+    // Algorithm selection (validated before any I/O is started)
+    let algo = HashAlgorithm::from_str(algorithm)?;
     let path_str = path.to_string();
     
     // Larger batch sizes for better I/O efficiency
     // Each batch reads ~16MB of compressed data (512 chunks * ~32KB)
     let num_threads = rayon::current_num_threads();
-    let batch_size = 512.max(num_threads * 32); // At least 512 chunks per batch
+    let low_memory = resources::low_memory();
+    let batch_size = 512.max(num_threads * 32).min(chunk_budget(chunk_size)); // At least 512 chunks per batch
     
-    debug!(batch_size, num_threads, "Optimized batch configuration");
+    debug!(batch_size, num_threads, low_memory, "Optimized batch configuration");
     
     // Progress tracking
     let chunks_processed = Arc::new(AtomicUsize::new(0));
     let chunks_processed_clone = chunks_processed.clone();
     
     // Channel for batches - allow some pipelining
-    let (tx, rx) = mpsc::sync_channel::<Result<Vec<Vec<u8>>, String>>(4);
+    let (tx, rx) = mpsc::sync_channel::<Result<Batch, String>>(if low_memory { 1 } else { 4 });
     
-    // I/O + Decompression thread
+    // I/O + Decompression thread, at the job's I/O priority
+    let io_priority = resources::current_io_priority();
+    let listed = segments::current_listed_segments();
+    let pause_gate = progress::current_pause_gate();
+    let job_span = tracing::Span::current();
     let io_handle = thread::spawn(move || {
+        let _job = job_span.enter();
+        segments::set_current_thread_listed_segments(listed);
+        progress::set_current_thread_pause_gate(pause_gate);
+        if io_priority != IoPriority::Normal {
+            resources::set_current_thread_io_priority(io_priority);
+        }
+        resources::set_current_thread_low_memory(low_memory);
         let mut handle = match EwfHandle::open(&path_str) {
             Ok(h) => h,
             Err(e) => {
@@ -426,51 +963,31 @@ where
                 return;
             }
         };
+        if collect_stats {
+            handle.collect_chunk_stats();
+        }
         
         for batch_start in (0..chunk_count).step_by(batch_size) {
+            // Paused jobs release their segment handles; the pool reopens
+            // them on the next read
+            progress::pause_point(|| handle.file_pool.close_all());
             let batch_end = (batch_start + batch_size).min(chunk_count);
-            let actual_batch_size = batch_end - batch_start;
+            let batch = read_batch(&mut handle, batch_start..batch_end, best_effort);
+            let failed = batch.is_err();
             
-            // Read chunks sequentially (minimizes seeks within segment)
-            let batch_chunks: Result<Vec<Vec<u8>>, String> = (batch_start..batch_end)
-                .map(|i| handle.read_chunk_no_cache(i))
-                .collect();
-            
-            match batch_chunks {
-                Ok(chunks) => {
-                    chunks_processed_clone.fetch_add(actual_batch_size, Ordering::Relaxed);
-                    if tx.send(Ok(chunks)).is_err() {
-                        return;
-                    }
-                }
-                Err(e) => {
-                    let _ = tx.send(Err(e));
-                    return;
-                }
+            chunks_processed_clone.fetch_add(batch_end - batch_start, Ordering::Relaxed);
+            if tx.send(batch).is_err() || failed {
+                return;
             }
         }
     });
     
-    // Hashing on main thread with algorithm-specific hashers
-    let use_sha1 = algorithm_lower == "sha1" || algorithm_lower == "sha-1";
-    let use_sha256 = algorithm_lower == "sha256" || algorithm_lower == "sha-256";
-    let use_sha512 = algorithm_lower == "sha512" || algorithm_lower == "sha-512";
-    let use_blake3 = algorithm_lower == "blake3";
-    let use_blake2 = algorithm_lower == "blake2" || algorithm_lower == "blake2b";
-    let use_xxh3 = algorithm_lower == "xxh3" || algorithm_lower == "xxhash3";
-    let use_xxh64 = algorithm_lower == "xxh64" || algorithm_lower == "xxhash64";
-    let use_crc32 = algorithm_lower == "crc32";
-    
-    let is_known_algo = use_sha1 || use_sha256 || use_sha512 || use_blake3 || use_blake2 || use_xxh3 || use_xxh64 || use_crc32;
-    let mut md5_hasher: Option<Md5> = if !is_known_algo { Some(Md5::new()) } else { None };
-    let mut sha1_hasher = if use_sha1 { Some(Sha1::new()) } else { None };
-    let mut sha256_hasher = if use_sha256 { Some(Sha256::new()) } else { None };
-    let mut sha512_hasher = if use_sha512 { Some(Sha512::new()) } else { None };
-    let mut blake3_hasher = if use_blake3 { Some(Blake3Hasher::new()) } else { None };
-    let mut blake2_hasher = if use_blake2 { Some(Blake2b512::new()) } else { None };
-    let mut xxh3_hasher = if use_xxh3 { Some(Xxh3::new()) } else { None };
-    let mut xxh64_hasher = if use_xxh64 { Some(Xxh64::new(0)) } else { None };
-    let mut crc32_hasher = if use_crc32 { Some(crc32fast::Hasher::new()) } else { None };
+    // Hashing on main thread
+    let mut hasher = StreamingHasher::new(algo);
+    let mut failed_chunks: Vec<ChunkReadError> = Vec::new();
+    let mut failed_chunk_count = 0usize;
+    let mut chunk_stats = collect_stats.then(ChunkStats::default);
+    let mut content_map = collect_map.then(|| ContentMapBuilder::new(DEFAULT_MAP_WINDOW));
     
     // Process batches as they arrive
     while let Ok(batch_result) = rx.recv() {
@@ -478,38 +995,34 @@ where
         progress_callback(processed, chunk_count);
         
         match batch_result {
-            Ok(batch_chunks) => {
-                // For BLAKE3, use parallel hashing on large batches
-                if let Some(ref mut hasher) = blake3_hasher {
+            Ok((batch_chunks, failures, batch_stats)) => {
+                if let (Some(total), Some(batch_stats)) = (&mut chunk_stats, &batch_stats) {
+                    total.merge(batch_stats);
+                }
+                failed_chunk_count += failures.len();
+                let room = MAX_REPORTED_CHUNK_ERRORS.saturating_sub(failed_chunks.len());
+                failed_chunks.extend(failures.into_iter().take(room));
+                if let Some(map) = &mut content_map {
+                    for chunk_data in &batch_chunks {
+                        map.update(chunk_data);
+                    }
+                }
+                
+                let hashing = perf::phase(perf::HASHING).entered();
+                let total_size: usize = batch_chunks.iter().map(|c| c.len()).sum();
+                if algo == HashAlgorithm::Blake3 && !low_memory {
                     // Concatenate batch into single buffer for parallel hashing
-                    let total_size: usize = batch_chunks.iter().map(|c| c.len()).sum();
                     let mut combined = Vec::with_capacity(total_size);
                     for chunk in &batch_chunks {
                         combined.extend_from_slice(chunk);
                     }
-                    hasher.update_rayon(&combined);
+                    hasher.update_parallel(&combined);
                 } else {
-                    // Sequential hashing for other algorithms
                     for chunk_data in &batch_chunks {
-                        if let Some(ref mut hasher) = md5_hasher {
-                            Digest::update(hasher, chunk_data);
-                        } else if let Some(ref mut hasher) = sha1_hasher {
-                            hasher.update(chunk_data);
-                        } else if let Some(ref mut hasher) = sha256_hasher {
-                            hasher.update(chunk_data);
-                        } else if let Some(ref mut hasher) = sha512_hasher {
-                            hasher.update(chunk_data);
-                        } else if let Some(ref mut hasher) = blake2_hasher {
-                            hasher.update(chunk_data);
-                        } else if let Some(ref mut hasher) = xxh3_hasher {
-                            hasher.update(chunk_data);
-                        } else if let Some(ref mut hasher) = xxh64_hasher {
-                            hasher.update(chunk_data);
-                        } else if let Some(ref mut hasher) = crc32_hasher {
-                            hasher.update(chunk_data);
-                        }
+                        hasher.update(chunk_data);
                     }
                 }
+                hashing.record("bytes", total_size as u64);
             }
             Err(e) => {
                 let _ = io_handle.join();
@@ -522,28 +1035,61 @@ where
     
     io_handle.join().map_err(|_| "I/O thread panicked".to_string())?;
     
-    // Return hash result
-    if let Some(hasher) = md5_hasher {
-        Ok(hex::encode(hasher.finalize()))
-    } else if let Some(hasher) = sha1_hasher {
-        Ok(hex::encode(hasher.finalize()))
-    } else if let Some(hasher) = sha256_hasher {
-        Ok(hex::encode(hasher.finalize()))
-    } else if let Some(hasher) = sha512_hasher {
-        Ok(hex::encode(hasher.finalize()))
-    } else if let Some(hasher) = blake3_hasher {
-        Ok(format!("{}", hasher.finalize().to_hex()))
-    } else if let Some(hasher) = blake2_hasher {
-        Ok(hex::encode(hasher.finalize()))
-    } else if let Some(hasher) = xxh3_hasher {
-        Ok(format!("{:016x}", hasher.digest128()))
-    } else if let Some(hasher) = xxh64_hasher {
-        Ok(format!("{:016x}", hasher.digest()))
-    } else if let Some(hasher) = crc32_hasher {
-        Ok(format!("{:08x}", hasher.finalize()))
+    Ok(EwfVerifyReport {
+        algorithm: algo.name().to_string(),
+        hash: hasher.finalize(),
+        complete: failed_chunk_count == 0,
+        chunk_count,
+        failed_chunk_count,
+        failed_chunks,
+        segment_issues,
+        acquisition_errors,
+        unreadable_sector_count,
+        acquisition_error_chunks,
+        chunk_stats,
+        content_map: content_map.map(ContentMapBuilder::finish),
+    })
+}
+
+/// Chunks a pipeline may hold at once: unlimited, or what fits in
+/// [`resources::LOW_MEMORY_BUFFER_SIZE`] in low-memory mode
+fn chunk_budget(chunk_size: usize) -> usize {
+    if resources::low_memory() {
+        (resources::LOW_MEMORY_BUFFER_SIZE / chunk_size.max(1)).max(1)
     } else {
-        Err("Unknown hash algorithm".to_string())
+        usize::MAX
+    }
+}
+
+/// Chunk data of a verification batch, the chunks that failed and, when
+/// collected, how the batch's chunks were stored
+type Batch = (Vec<Vec<u8>>, Vec<ChunkReadError>, Option<ChunkStats>);
+
+/// Read a range of chunks sequentially (minimizes seeks within segment)
+///
+/// With `best_effort`, failed chunks are replaced by zeros and recorded;
+/// otherwise the first failure aborts. A lost device always aborts with a
+/// single device-disconnected error - every later chunk would fail too.
+fn read_batch(handle: &mut EwfHandle, chunks: std::ops::Range<usize>, best_effort: bool) -> Result<Batch, String> {
+    let phase = perf::phase(perf::DECOMPRESSION).entered();
+    let mut data = Vec::with_capacity(chunks.len());
+    let mut failures = Vec::new();
+    for i in chunks {
+        match handle.try_read_chunk(i) {
+            Ok(chunk) => data.push(chunk),
+            Err(e) if e.kind == ChunkErrorKind::DeviceDisconnected => return Err(e.message),
+            Err(e) if best_effort => {
+                debug!(chunk = i, error = %e, "Chunk failed, substituting zeros");
+                data.push(vec![0u8; handle.chunk_data_size(i)]);
+                failures.push(e);
+            }
+            Err(e) => return Err(e.to_string()),
+        }
+    }
+    if !phase.is_disabled() {
+        phase.record("bytes", data.iter().map(|chunk| chunk.len() as u64).sum::<u64>());
     }
+    Ok((data, failures, handle.take_chunk_stats()))
 }
 
 /// Legacy parallel verification (kept for reference/fallback)
@@ -562,15 +1108,7 @@ where
     debug!(chunk_count, "EWF chunk count");
     
     // Create hasher based on algorithm
-    let algorithm_lower = algorithm.to_lowercase();
-    let use_sha1 = algorithm_lower == "sha1" || algorithm_lower == "sha-1";
-    let use_sha256 = algorithm_lower == "sha256" || algorithm_lower == "sha-256";
-    let use_sha512 = algorithm_lower == "sha512" || algorithm_lower == "sha-512";
-    let use_blake3 = algorithm_lower == "blake3";
-    let use_blake2 = algorithm_lower == "blake2" || algorithm_lower == "blake2b";
-    let use_xxh3 = algorithm_lower == "xxh3" || algorithm_lower == "xxhash3";
-    let use_xxh64 = algorithm_lower == "xxh64" || algorithm_lower == "xxhash64";
-    let use_crc32 = algorithm_lower == "crc32";
+    let mut hasher = StreamingHasher::from_str(algorithm)?;
     
     let path_str = path.to_string();
     
@@ -594,11 +1132,6 @@ where
     let channel_depth = num_threads.max(16);
     let (tx, rx) = mpsc::sync_channel::<Result<(usize, Vec<Vec<u8>>), String>>(channel_depth);
     
-    rayon::ThreadPoolBuilder::new()
-        .num_threads(num_threads)
-        .build_global()
-        .ok();
-    
     // Spawn decompression thread pool
     let decompression_handle = thread::spawn(move || {
         let handles_result: Result<Vec<EwfHandle>, String> = (0..num_threads)
@@ -658,18 +1191,6 @@ where
         }
     });
     
-    // Hash on main thread
-    let is_known_algo = use_sha1 || use_sha256 || use_sha512 || use_blake3 || use_blake2 || use_xxh3 || use_xxh64 || use_crc32;
-    let mut md5_hasher: Option<Md5> = if !is_known_algo { Some(Md5::new()) } else { None };
-    let mut sha1_hasher = if use_sha1 { Some(Sha1::new()) } else { None };
-    let mut sha256_hasher = if use_sha256 { Some(Sha256::new()) } else { None };
-    let mut sha512_hasher = if use_sha512 { Some(Sha512::new()) } else { None };
-    let mut blake3_hasher = if use_blake3 { Some(Blake3Hasher::new()) } else { None };
-    let mut blake2_hasher = if use_blake2 { Some(Blake2b512::new()) } else { None };
-    let mut xxh3_hasher = if use_xxh3 { Some(Xxh3::new()) } else { None };
-    let mut xxh64_hasher = if use_xxh64 { Some(Xxh64::new(0)) } else { None };
-    let mut crc32_hasher = if use_crc32 { Some(crc32fast::Hasher::new()) } else { None };
-    
     while let Ok(batch_result) = rx.recv() {
         let decompressed = decompressed_chunks.load(std::sync::atomic::Ordering::Relaxed);
         progress_callback(decompressed, chunk_count);
@@ -678,26 +1199,7 @@ where
             Ok((batch_start, batch_chunks)) => {
                 for (relative_idx, chunk_data) in batch_chunks.iter().enumerate() {
                     let _chunk_idx = batch_start + relative_idx;
-                    
-                    if let Some(ref mut hasher) = md5_hasher {
-                        Digest::update(hasher, chunk_data);
-                    } else if let Some(ref mut hasher) = sha1_hasher {
-                        hasher.update(chunk_data);
-                    } else if let Some(ref mut hasher) = sha256_hasher {
-                        hasher.update(chunk_data);
-                    } else if let Some(ref mut hasher) = sha512_hasher {
-                        hasher.update(chunk_data);
-                    } else if let Some(ref mut hasher) = blake3_hasher {
-                        hasher.update_rayon(chunk_data);
-                    } else if let Some(ref mut hasher) = blake2_hasher {
-                        hasher.update(chunk_data);
-                    } else if let Some(ref mut hasher) = xxh3_hasher {
-                        hasher.update(chunk_data);
-                    } else if let Some(ref mut hasher) = xxh64_hasher {
-                        hasher.update(chunk_data);
-                    } else if let Some(ref mut hasher) = crc32_hasher {
-                        hasher.update(chunk_data);
-                    }
+                    hasher.update_parallel(chunk_data);
                 }
             }
             Err(e) => {
@@ -711,26 +1213,722 @@ where
     
     decompression_handle.join().map_err(|_| "Decompression thread panicked".to_string())?;
     
-    // Return hash result
-    if let Some(hasher) = md5_hasher {
-        Ok(hex::encode(hasher.finalize()))
-    } else if let Some(hasher) = sha1_hasher {
-        Ok(hex::encode(hasher.finalize()))
-    } else if let Some(hasher) = sha256_hasher {
-        Ok(hex::encode(hasher.finalize()))
-    } else if let Some(hasher) = sha512_hasher {
-        Ok(hex::encode(hasher.finalize()))
-    } else if let Some(hasher) = blake3_hasher {
-        Ok(format!("{}", hasher.finalize().to_hex()))
-    } else if let Some(hasher) = blake2_hasher {
-        Ok(hex::encode(hasher.finalize()))
-    } else if let Some(hasher) = xxh3_hasher {
-        Ok(format!("{:016x}", hasher.digest128()))
-    } else if let Some(hasher) = xxh64_hasher {
-        Ok(format!("{:016x}", hasher.digest()))
-    } else if let Some(hasher) = crc32_hasher {
-        Ok(format!("{:08x}", hasher.finalize()))
-    } else {
-        Err("Unknown hash algorithm".to_string())
+    Ok(hasher.finalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::handle::{parse_error2_data, parse_section_descriptor, parse_session_data, parse_table_data, parse_volume_data};
+
+    const SECTOR: usize = 2048;
+
+    fn session_data(starts: &[u32]) -> Vec<u8> {
+        let mut data = vec![0u8; SESSION_HEADER_SIZE];
+        data[0..4].copy_from_slice(&(starts.len() as u32).to_le_bytes());
+        for start in starts {
+            let mut entry = [0u8; SESSION_ENTRY_SIZE];
+            entry[4..8].copy_from_slice(&start.to_le_bytes());
+            data.extend_from_slice(&entry);
+        }
+        data.extend_from_slice(&[0u8; 4]);
+        data
+    }
+
+    /// Append a section of type `kind`; "done" links to itself
+    fn section(image: &mut Vec<u8>, kind: &str, data: &[u8]) {
+        let start = image.len() as u64;
+        let size = 76 + data.len() as u64;
+        let next = if kind == "done" { start } else { start + size };
+        let mut descriptor = [0u8; 76];
+        descriptor[..kind.len()].copy_from_slice(kind.as_bytes());
+        descriptor[16..24].copy_from_slice(&next.to_le_bytes());
+        descriptor[24..32].copy_from_slice(&size.to_le_bytes());
+        image.extend_from_slice(&descriptor);
+        image.extend_from_slice(data);
+    }
+
+    /// Single-segment optical E01: four uncompressed one-sector chunks and a
+    /// session section with sessions starting at sectors 0 and 3
+    fn build_optical_e01() -> (Vec<u8>, Vec<u8>) {
+        build_optical_e01_compressing(&[false; 4])
+    }
+
+    /// [`build_optical_e01`] with the chunks flagged in `compressed` deflated
+    fn build_optical_e01_compressing(compressed: &[bool; 4]) -> (Vec<u8>, Vec<u8>) {
+        let media: Vec<u8> = (0..4 * SECTOR).map(|i| (i / SECTOR * 37 + i % 251) as u8).collect();
+        let mut image = b"EVF\x09\x0d\x0a\xff\x00\x01\x01\x00\x00\x00".to_vec();
+
+        let mut volume = vec![0u8; 1052];
+        volume[0] = MEDIA_TYPE_OPTICAL;
+        volume[4..8].copy_from_slice(&4u32.to_le_bytes());
+        volume[8..12].copy_from_slice(&1u32.to_le_bytes());
+        volume[12..16].copy_from_slice(&(SECTOR as u32).to_le_bytes());
+        volume[16..24].copy_from_slice(&4u64.to_le_bytes());
+        section(&mut image, "volume", &volume);
+
+        let sectors_start = image.len() as u32 + 76;
+        let mut sectors = Vec::new();
+        let mut offsets = Vec::new();
+        for (chunk, &deflate) in media.chunks(SECTOR).zip(compressed) {
+            let offset = sectors_start + sectors.len() as u32;
+            if deflate {
+                let mut encoder = flate2::write::ZlibEncoder::new(&mut sectors, flate2::Compression::default());
+                encoder.write_all(chunk).unwrap();
+                encoder.finish().unwrap();
+                offsets.push(offset | 0x8000_0000);
+            } else {
+                sectors.extend_from_slice(chunk);
+                offsets.push(offset);
+            }
+        }
+        section(&mut image, "sectors", &sectors);
+
+        let mut table = vec![0u8; 24];
+        table[0..4].copy_from_slice(&4u32.to_le_bytes());
+        for offset in offsets {
+            table.extend_from_slice(&offset.to_le_bytes());
+        }
+        table.extend_from_slice(&[0u8; 4]);
+        section(&mut image, "table", &table);
+        section(&mut image, "session", &session_data(&[3, 0]));
+        section(&mut image, "done", &[]);
+        (image, media)
+    }
+
+    #[test]
+    fn test_optical_image_reports_sessions_and_hashes_full_media() {
+        let (image, media) = build_optical_e01();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("disc.E01");
+        std::fs::write(&path, image).unwrap();
+        let path = path.to_str().unwrap();
+
+        let info = info(path).unwrap();
+        assert_eq!(info.media_type.as_deref(), Some("optical"));
+        assert_eq!(info.sessions, vec![
+            SessionInfo { first_sector: 0, sector_count: 3 },
+            SessionInfo { first_sector: 3, sector_count: 1 },
+        ]);
+        assert_eq!(info.total_size, media.len() as u64);
+
+        let expected = crate::common::hash::compute_hash_str(&media, "md5").unwrap();
+        assert_eq!(verify(path, "md5").unwrap(), expected);
+    }
+
+    /// Single-segment SMART (EWF-S01) image of three two-sector chunks, the
+    /// last one deflated, stored after the offset array inside the table
+    /// section with offsets counting from the start of the table data
+    fn build_smart_s01() -> (Vec<u8>, Vec<u8>) {
+        const CHUNK: usize = 2 * 512;
+        let media: Vec<u8> = (0..3 * CHUNK).map(|i| (i / CHUNK * 53 + i % 241) as u8).collect();
+        let mut image = b"EVF\x09\x0d\x0a\xff\x00\x01\x01\x00\x00\x00".to_vec();
+
+        let mut volume = vec![0u8; SMART_VOLUME_DATA_SIZE as usize];
+        volume[4..8].copy_from_slice(&3u32.to_le_bytes());
+        volume[8..12].copy_from_slice(&2u32.to_le_bytes());
+        volume[12..16].copy_from_slice(&512u32.to_le_bytes());
+        volume[16..20].copy_from_slice(&6u32.to_le_bytes());
+        volume[85..90].copy_from_slice(SMART_VOLUME_SIGNATURE);
+        section(&mut image, "volume", &volume);
+
+        let mut chunks = Vec::new();
+        let mut offsets = Vec::new();
+        let data_start = (TABLE_HEADER_SIZE + 3 * 4 + 4) as u32;
+        for (index, chunk) in media.chunks(CHUNK).enumerate() {
+            let offset = data_start + chunks.len() as u32;
+            if index == 2 {
+                let mut encoder = flate2::write::ZlibEncoder::new(&mut chunks, flate2::Compression::default());
+                encoder.write_all(chunk).unwrap();
+                encoder.finish().unwrap();
+                offsets.push(offset | 0x8000_0000);
+            } else {
+                chunks.extend_from_slice(chunk);
+                offsets.push(offset);
+            }
+        }
+        let mut table = vec![0u8; TABLE_HEADER_SIZE];
+        table[0..4].copy_from_slice(&3u32.to_le_bytes());
+        for offset in offsets {
+            table.extend_from_slice(&offset.to_le_bytes());
+        }
+        table.extend_from_slice(&[0u8; 4]);
+        table.extend_from_slice(&chunks);
+        section(&mut image, "table", &table);
+        section(&mut image, "done", &[]);
+        (image, media)
+    }
+
+    #[test]
+    fn test_smart_image_hashes_media_from_table_relative_offsets() {
+        let (image, media) = build_smart_s01();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("drive.s01");
+        std::fs::write(&path, image).unwrap();
+        let path = path.to_str().unwrap();
+
+        let mut handle = EwfHandle::open(path).unwrap();
+        assert!(handle.is_smart());
+        assert_eq!(handle.read_chunk(1).unwrap(), media[1024..2048]);
+        assert_eq!(handle.read_chunk(2).unwrap(), media[2048..]);
+
+        let expected = crate::common::hash::compute_hash_str(&media, "md5").unwrap();
+        assert_eq!(verify(path, "md5").unwrap(), expected);
+    }
+
+    #[test]
+    fn test_extraction_writer_notices_a_panicked_worker() {
+        use std::sync::mpsc;
+        use std::thread;
+
+        let (result_tx, result_rx) = mpsc::channel::<usize>();
+        let (stop_tx, stop_rx) = mpsc::channel::<()>();
+        // One worker idles with its sender open, the other dies mid-batch
+        let idle_tx = result_tx.clone();
+        let idle = thread::spawn(move || {
+            let _ = stop_rx.recv();
+            drop(idle_tx);
+        });
+        let dying = thread::spawn(move || {
+            let _result_tx = result_tx;
+            panic!("worker died mid-batch");
+        });
+        let workers = vec![idle, dying];
+
+        let error = recv_from_workers(&result_rx, &workers).unwrap_err();
+        assert!(error.contains("panicked"), "{}", error);
+        drop(stop_tx);
+        let panicked = workers.into_iter().map(|worker| worker.join().is_err()).collect::<Vec<_>>();
+        assert_eq!(panicked, vec![false, true]);
+    }
+
+    #[test]
+    fn test_corrupt_chunk_fails_unless_best_effort() {
+        let (mut image, media) = build_smart_s01();
+        // Chunk 2 (sectors 4-5) is the deflated one, after two raw chunks
+        let stored_at = 13 + 76 + SMART_VOLUME_DATA_SIZE as usize + 76 + TABLE_HEADER_SIZE + 3 * 4 + 4 + 2 * 1024;
+        image[stored_at..stored_at + 2].copy_from_slice(&[0xff, 0xff]);
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("drive.s01");
+        std::fs::write(&path, image).unwrap();
+        let path = path.to_str().unwrap();
+
+        let error = verify_chunks(path, "md5", false).unwrap_err();
+        assert!(error.contains("Chunk 2"), "{}", error);
+        assert!(verify_report(path, "md5", &VerifyOptions::default(), |_, _| {}).is_err());
+
+        let report = verify_report(path, "md5", &VerifyOptions { best_effort: true, ..Default::default() }, |_, _| {}).unwrap();
+        assert!(!report.complete);
+        assert_eq!((report.chunk_count, report.failed_chunk_count), (3, 1));
+        let mut zero_filled = media.clone();
+        zero_filled[2048..].fill(0);
+        assert_eq!(report.hash, crate::common::hash::compute_hash_str(&zero_filled, "md5").unwrap());
+
+        let failure = &report.failed_chunks[0];
+        assert_eq!(failure.chunk_index, 2);
+        assert_eq!((failure.byte_start, failure.byte_end), (2048, 3072));
+        assert_eq!((failure.sector_start, failure.sector_end), (4, 5));
+        assert_eq!(failure.segment_path.as_deref(), Some(path));
+        assert_eq!(failure.segment_offset, Some(stored_at as u64));
+        assert!(failure.compressed);
+        assert_eq!(failure.kind, ChunkErrorKind::Decompression);
+
+        let entries = verify_chunks(path, "md5", true).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!((entries[0].status, entries[0].reason), (VerifyStatus::Error, Some(VerifyReason::PartialImage)));
+        assert_eq!(entries[1].target, VerifyTarget::Chunk { index: 2 });
+        assert_eq!(entries[1].status, VerifyStatus::Error);
+        assert_eq!(entries[1].message.as_deref(), Some(failure.to_string().as_str()));
+    }
+
+    /// [`build_optical_e01`] with an error2 section recording `ranges`
+    /// (first sector, sector count) before its done section
+    fn build_optical_e01_with_errors(ranges: &[(u32, u32)]) -> (Vec<u8>, Vec<u8>) {
+        let (mut image, media) = build_optical_e01();
+        image.truncate(image.len() - 76);
+        let mut error2 = vec![0u8; ERROR2_HEADER_SIZE];
+        error2[0..4].copy_from_slice(&(ranges.len() as u32).to_le_bytes());
+        for (first_sector, sector_count) in ranges {
+            error2.extend_from_slice(&first_sector.to_le_bytes());
+            error2.extend_from_slice(&sector_count.to_le_bytes());
+        }
+        error2.extend_from_slice(&[0u8; 4]);
+        section(&mut image, "error2", &error2);
+        section(&mut image, "done", &[]);
+        (image, media)
+    }
+
+    #[test]
+    fn test_acquisition_errors_annotate_chunks() {
+        // Unsorted and overlapping, as a retrying imager may record them
+        let (image, media) = build_optical_e01_with_errors(&[(2, 1), (1, 2), (3, 0)]);
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("dying.E01");
+        std::fs::write(&path, image).unwrap();
+        let path = path.to_str().unwrap();
+
+        let info = info(path).unwrap();
+        assert_eq!(info.acquisition_errors, vec![
+            SectorRange { first_sector: 1, sector_count: 2 },
+            SectorRange { first_sector: 2, sector_count: 1 },
+        ]);
+
+        // The zero-filled data is what was stored, so the image still verifies
+        let report = verify_report(path, "md5", &VerifyOptions { best_effort: true, ..Default::default() }, |_, _| {}).unwrap();
+        assert!(report.complete);
+        assert_eq!(report.hash, crate::common::hash::compute_hash_str(&media, "md5").unwrap());
+        assert_eq!((report.unreadable_sector_count, report.acquisition_error_chunks.as_slice()), (3, [1, 2].as_slice()));
+
+        let entries = verify_chunks(path, "md5", false).unwrap();
+        assert_eq!(entries[0].status, VerifyStatus::Ok);
+        let annotated: Vec<_> = entries[1..].iter().map(|entry| (&entry.target, entry.status, entry.reason, entry.legacy_status())).collect();
+        assert_eq!(annotated, vec![
+            (&VerifyTarget::Chunk { index: 1 }, VerifyStatus::Skipped, Some(VerifyReason::AcquisitionReadError), "acquisition_error"),
+            (&VerifyTarget::Chunk { index: 2 }, VerifyStatus::Skipped, Some(VerifyReason::AcquisitionReadError), "acquisition_error"),
+        ]);
+        assert_eq!(entries[1].message.as_deref(), Some("Chunk 1 contains acquisition-time read errors (zero-filled)"));
+    }
+
+    #[test]
+    fn test_verify_chunks_reports_whole_image_entry() {
+        let (image, media) = build_optical_e01();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("disc.E01");
+        std::fs::write(&path, image).unwrap();
+
+        let entries = verify_chunks(path.to_str().unwrap(), "md5", false).unwrap();
+        assert_eq!(entries.len(), 1);
+        let image = &entries[0];
+        assert_eq!(image.target, VerifyTarget::WholeImage { label: None });
+        assert_eq!((image.status, image.legacy_status()), (VerifyStatus::Ok, "ok"));
+        assert_eq!(image.algorithm.as_deref(), Some("MD5"));
+        let expected = crate::common::hash::compute_hash_str(&media, "md5").unwrap();
+        assert_eq!(image.computed.as_ref().map(ToString::to_string), Some(expected));
+        assert!(image.expected.is_none() && image.duration_secs.is_some());
+    }
+
+    #[test]
+    fn test_best_effort_keeps_sections_before_a_broken_link() {
+        let (mut image, media) = build_optical_e01();
+        // The session section's next offset now loops back to the file header
+        let session = image.windows(7).position(|w| w == b"session").unwrap();
+        image[session + 16..session + 24].copy_from_slice(&13u64.to_le_bytes());
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("disc.E01");
+        std::fs::write(&path, image).unwrap();
+        let path = path.to_str().unwrap();
+
+        let Err(error) = info(path) else { panic!("strict parse accepted a looping section chain") };
+        assert!(error.contains("links back"), "{error}");
+
+        let info = info_with_options(path, true).unwrap();
+        assert_eq!(info.total_size, media.len() as u64);
+        assert_eq!(info.chunk_count, 4);
+        assert_eq!(info.parse_warnings.len(), 1);
+        assert_eq!(info.parse_warnings[0].address, session as u64);
+        assert!(info.parse_warnings[0].error.contains("links back"));
+    }
+
+    /// The serial read-one-write-one extraction the pipeline replaced
+    fn extract_sequential(path: &str) -> Vec<u8> {
+        let mut handle = EwfHandle::open(path).unwrap();
+        let volume = handle.get_volume_info();
+        let total_bytes = (volume.sector_count * volume.bytes_per_sector as u64) as usize;
+        let mut output = Vec::new();
+        for i in 0..handle.get_chunk_count() {
+            let chunk = handle.read_chunk_no_cache(i).unwrap();
+            let n = chunk.len().min(total_bytes - output.len());
+            output.extend_from_slice(&chunk[..n]);
+            if output.len() >= total_bytes {
+                break;
+            }
+        }
+        output
+    }
+
+    #[test]
+    fn test_pipelined_extract_matches_sequential() {
+        let (image, media) = build_optical_e01();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("disc.E01");
+        std::fs::write(&path, image).unwrap();
+        let path = path.to_str().unwrap();
+        let expected = extract_sequential(path);
+        assert_eq!(expected, media);
+
+        for in_flight in [1, 3, DEFAULT_EXTRACT_IN_FLIGHT_CHUNKS] {
+            let out = tempfile::tempdir().unwrap();
+            let mut last = (0, 0);
+            let file = extract_with_progress(path, out.path().to_str().unwrap(), in_flight, |done, total| last = (done, total)).unwrap();
+            assert_eq!(std::fs::read(&file.output_path).unwrap(), expected, "in_flight {in_flight}");
+            assert_eq!(file.size, media.len() as u64);
+            assert_eq!(file.md5, crate::common::hash::compute_hash_str(&media, "md5").unwrap());
+            assert_eq!(last, (media.len() as u64, media.len() as u64));
+        }
+
+        // Low-memory mode shrinks the read-ahead, not the output
+        resources::set_current_thread_low_memory(true);
+        let out = tempfile::tempdir().unwrap();
+        let file = extract_with_progress(path, out.path().to_str().unwrap(), DEFAULT_EXTRACT_IN_FLIGHT_CHUNKS, |_, _| {});
+        let hash = verify(path, "sha1");
+        resources::set_current_thread_low_memory(false);
+        assert_eq!(std::fs::read(&file.unwrap().output_path).unwrap(), expected);
+        assert_eq!(hash.unwrap(), crate::common::hash::compute_hash_str(&media, "sha1").unwrap());
+    }
+
+    #[test]
+    fn test_concurrent_info_verify_and_reads_are_stable() {
+        fn assert_send<T: Send>() {}
+        assert_send::<EwfHandle>();
+
+        let (image, media) = build_optical_e01();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("disc.E01");
+        std::fs::write(&path, image).unwrap();
+        let path = path.to_str().unwrap();
+        let sha1 = crate::common::hash::compute_hash_str(&media, "sha1").unwrap();
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(3);
+
+        std::thread::scope(|scope| {
+            for worker in 0..6usize {
+                let (media, sha1) = (&media, &sha1);
+                scope.spawn(move || {
+                    let mut handle = EwfHandle::open(path).unwrap();
+                    let mut round = 0usize;
+                    while std::time::Instant::now() < deadline {
+                        match (worker + round) % 3 {
+                            0 => {
+                                let refresh = round.is_multiple_of(4);
+                                let info = crate::containers::info_cached(path, false, None, false, refresh).unwrap();
+                                assert_eq!(info.e01.unwrap().total_size, media.len() as u64);
+                            }
+                            1 => assert_eq!(&verify(path, "sha1").unwrap(), sha1),
+                            _ => {
+                                // Ranges straddling chunk boundaries exercise the cache
+                                let offset = (round * 997 + worker * 131) % (media.len() - SECTOR);
+                                let bytes = handle.read_at(offset as u64, SECTOR).unwrap();
+                                assert_eq!(bytes, &media[offset..offset + SECTOR]);
+                            }
+                        }
+                        round += 1;
+                    }
+                });
+            }
+        });
+    }
+
+    #[test]
+    fn test_verification_collects_chunk_stats() {
+        let (image, media) = build_optical_e01_compressing(&[true, false, true, false]);
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("disc.E01");
+        std::fs::write(&path, image).unwrap();
+        let path = path.to_str().unwrap();
+        let sha1 = crate::common::hash::compute_hash_str(&media, "sha1").unwrap();
+
+        let plain = verify_report(path, "sha1", &VerifyOptions::default(), |_, _| {}).unwrap();
+        assert_eq!(plain.hash, sha1);
+        assert!(plain.chunk_stats.is_none() && plain.content_map.is_none());
+
+        let report = verify_report(path, "sha1", &VerifyOptions { collect_stats: true, collect_map: true, ..Default::default() }, |_, _| {}).unwrap();
+        assert_eq!(report.hash, sha1);
+        let stats = report.chunk_stats.unwrap();
+        assert_eq!((stats.compressed_chunks, stats.uncompressed_chunks, stats.sparse_chunks), (2, 2, 0));
+        assert_eq!(stats.logical_bytes, media.len() as u64);
+        assert!(stats.stored_bytes < stats.logical_bytes);
+        let (min, mean, max) = (stats.min_ratio.unwrap(), stats.mean_ratio.unwrap(), stats.max_ratio.unwrap());
+        assert!(min <= mean && mean <= max && max < 1.0, "{min} {mean} {max}");
+        assert_eq!(stats.ratio_histogram.iter().sum::<u64>(), 2);
+        // The map is of the media, whatever the chunks' storage
+        let mut expected = ContentMapBuilder::new(DEFAULT_MAP_WINDOW);
+        expected.update(&media);
+        assert_eq!(report.content_map, Some(expected.finish()));
+
+        // Batches of one chunk merge to the same totals
+        let mut handle = EwfHandle::open(path).unwrap();
+        handle.collect_chunk_stats();
+        let mut merged = ChunkStats::default();
+        for chunk in 0..4 {
+            let (_, _, batch) = read_batch(&mut handle, chunk..chunk + 1, false).unwrap();
+            merged.merge(&batch.unwrap());
+        }
+        assert_eq!(merged, stats);
+    }
+
+    #[test]
+    fn test_chunk_stats_histogram_and_sparse_chunks() {
+        let mut stats = ChunkStats::default();
+        stats.record_stored(true, 50, 1000);
+        stats.record_stored(true, 900, 1000);
+        stats.record_stored(false, 1000, 1000);
+        stats.record_sparse(1000);
+        assert_eq!(stats.ratio_histogram, [1, 0, 0, 0, 1]);
+        assert_eq!((stats.min_ratio, stats.max_ratio, stats.mean_ratio), (Some(0.05), Some(0.9), Some(0.475)));
+        assert_eq!((stats.stored_bytes, stats.logical_bytes, stats.sparse_chunks), (1950, 4000, 1));
+    }
+
+    /// Segment file holding only its header, a volume (first segment) or
+    /// data section carrying `set_identifier`, and "done"
+    fn identity_segment(number: u16, set_identifier: [u8; 16]) -> Vec<u8> {
+        let mut image = b"EVF\x09\x0d\x0a\xff\x00\x01".to_vec();
+        image.extend_from_slice(&number.to_le_bytes());
+        image.extend_from_slice(&[0, 0]);
+        let mut data = vec![0u8; 1052];
+        data[VOLUME_SET_IDENTIFIER_OFFSET..VOLUME_SET_IDENTIFIER_OFFSET + 16].copy_from_slice(&set_identifier);
+        for (kind, data) in [(if number == 1 { "volume" } else { "data" }, data), ("done", Vec::new())] {
+            let start = image.len() as u64;
+            let size = (SECTION_DESCRIPTOR_SIZE + data.len()) as u64;
+            let mut descriptor = [0u8; SECTION_DESCRIPTOR_SIZE];
+            descriptor[..kind.len()].copy_from_slice(kind.as_bytes());
+            descriptor[16..24].copy_from_slice(&(if kind == "done" { start } else { start + size }).to_le_bytes());
+            descriptor[24..32].copy_from_slice(&size.to_le_bytes());
+            image.extend_from_slice(&descriptor);
+            image.extend_from_slice(&data);
+        }
+        image
+    }
+
+    #[test]
+    fn test_renamed_segments_are_detected() {
+        let dir = tempfile::tempdir().unwrap();
+        let (first, second) = (dir.path().join("disk.E01"), dir.path().join("disk.E02"));
+        let set = [0x5A; 16];
+        std::fs::write(&first, identity_segment(1, set)).unwrap();
+        std::fs::write(&second, identity_segment(2, set)).unwrap();
+        let first = first.to_str().unwrap();
+
+        let check = segment_headers(first).unwrap();
+        assert!(check.is_consistent(), "{:?}", check.issues);
+        assert_eq!(check.segments[1].segment_number, Some(2));
+        assert_eq!(check.segments[0].set_identifier, check.segments[1].set_identifier);
+        assert!(check.segments[0].set_identifier.is_some());
+
+        // A botched copy swapped the names, and E02 came from another acquisition
+        std::fs::write(first, identity_segment(2, set)).unwrap();
+        std::fs::write(&second, identity_segment(1, [0x11; 16])).unwrap();
+        let check = segment_headers(first).unwrap();
+        assert!(!check.order_matches && !check.same_set);
+        assert_eq!(check.issues.len(), 3);
+        assert!(check.issues[0].starts_with("disk.E01 is segment 2"), "{}", check.issues[0]);
+
+        let error = verify(first, "md5").unwrap_err();
+        assert!(error.contains("disk.E02 is segment 1 by its header"), "{error}");
+    }
+
+    #[test]
+    fn test_mixed_naming_set_is_refused_not_hashed() {
+        let dir = tempfile::tempdir().unwrap();
+        let set = [0x5A; 16];
+        for (name, number) in [("disk.E01", 1), ("disk.E02", 2), ("disk.Ex01", 1)] {
+            std::fs::write(dir.path().join(name), identity_segment(number, set)).unwrap();
+        }
+        let first = dir.path().join("disk.E01");
+        let first = first.to_str().unwrap();
+
+        let check = segment_headers(first).unwrap();
+        assert_eq!(check.segments.len(), 2);
+        assert!(check.order_matches && check.same_set && !check.single_naming);
+        assert_eq!(check.issues, ["disk.Ex01 uses Ex01 naming but the set is named E01, so it is not part of the set"]);
+
+        let error = verify(first, "md5").unwrap_err();
+        assert!(error.contains("disk.Ex01 uses Ex01 naming"), "{error}");
+    }
+
+    /// Segment reader whose device can be "unplugged" mid-read
+    struct UnpluggableReader {
+        inner: File,
+        unplugged: std::sync::Arc<std::sync::atomic::AtomicBool>,
+    }
+
+    impl UnpluggableReader {
+        fn check(&self) -> std::io::Result<()> {
+            if self.unplugged.load(std::sync::atomic::Ordering::Relaxed) {
+                // EIO / ERROR_DEVICE_NOT_CONNECTED
+                Err(std::io::Error::from_raw_os_error(if cfg!(windows) { 1167 } else { 5 }))
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    impl Read for UnpluggableReader {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            self.check()?;
+            self.inner.read(buf)
+        }
+    }
+
+    impl std::io::Seek for UnpluggableReader {
+        fn seek(&mut self, pos: std::io::SeekFrom) -> std::io::Result<u64> {
+            self.check()?;
+            self.inner.seek(pos)
+        }
+    }
+
+    impl crate::common::SegmentRead for UnpluggableReader {
+        fn size(&self) -> std::io::Result<u64> {
+            self.inner.size()
+        }
+    }
+
+    /// Two-segment set of four one-sector chunks, two in each segment
+    fn build_two_segment_e01() -> (Vec<u8>, Vec<u8>, Vec<u8>) {
+        let media: Vec<u8> = (0..4 * SECTOR).map(|i| (i / SECTOR * 53 + i % 241) as u8).collect();
+        let mut segments = Vec::new();
+        for (number, chunks) in [(1u16, &media[..2 * SECTOR]), (2, &media[2 * SECTOR..])] {
+            let mut segment = b"EVF\x09\x0d\x0a\xff\x00\x01".to_vec();
+            segment.extend_from_slice(&number.to_le_bytes());
+            segment.extend_from_slice(&[0, 0]);
+            if number == 1 {
+                let mut volume = vec![0u8; 1052];
+                volume[4..8].copy_from_slice(&4u32.to_le_bytes());
+                volume[8..12].copy_from_slice(&1u32.to_le_bytes());
+                volume[12..16].copy_from_slice(&(SECTOR as u32).to_le_bytes());
+                volume[16..24].copy_from_slice(&4u64.to_le_bytes());
+                section(&mut segment, "volume", &volume);
+            }
+            let sectors_start = segment.len() as u32 + 76;
+            section(&mut segment, "sectors", chunks);
+            let mut table = vec![0u8; 24];
+            table[0..4].copy_from_slice(&2u32.to_le_bytes());
+            for chunk in 0..2 {
+                table.extend_from_slice(&(sectors_start + chunk * SECTOR as u32).to_le_bytes());
+            }
+            table.extend_from_slice(&[0u8; 4]);
+            section(&mut segment, "table", &table);
+            if number == 1 {
+                // "next" links to itself
+                let start = segment.len() as u64;
+                section(&mut segment, "next", &[]);
+                segment[start as usize + 16..start as usize + 24].copy_from_slice(&start.to_le_bytes());
+            } else {
+                section(&mut segment, "done", &[]);
+            }
+            segments.push(segment);
+        }
+        let second = segments.pop().unwrap();
+        (segments.pop().unwrap(), second, media)
+    }
+
+    #[test]
+    fn test_segments_listed_across_directories_match_one_directory() {
+        let (first, second, media) = build_two_segment_e01();
+        let together = tempfile::tempdir().unwrap();
+        std::fs::write(together.path().join("disk.E01"), &first).unwrap();
+        std::fs::write(together.path().join("disk.E02"), &second).unwrap();
+        let together = together.path().join("disk.E01").to_string_lossy().to_string();
+        // One disk filled up part way through the acquisition
+        let (drive_a, drive_b) = (tempfile::tempdir().unwrap(), tempfile::tempdir().unwrap());
+        std::fs::write(drive_a.path().join("disk.E01"), &first).unwrap();
+        std::fs::write(drive_b.path().join("disk.E02"), &second).unwrap();
+        let listed: Vec<String> = [drive_a.path().join("disk.E01"), drive_b.path().join("disk.E02")].iter()
+            .map(|path| path.to_string_lossy().to_string())
+            .collect();
+
+        let report = |path: &str| verify_report(path, "md5", &VerifyOptions { collect_stats: true, collect_map: true, ..Default::default() }, |_, _| {}).map(|r| serde_json::to_value(r).unwrap());
+        let expected = report(&together).unwrap();
+        assert_eq!(expected["hash"], crate::common::hash::compute_hash_str(&media, "md5").unwrap().as_str());
+        assert!(report(&listed[0]).unwrap_err().contains("next segment"));
+        assert_eq!(with_segment_list(&listed[0], &listed, || report(&listed[0])).unwrap(), expected);
+
+        let info_of = |path: &str| info(path).map(|i| serde_json::to_value(i).unwrap());
+        assert_eq!(with_segment_list(&listed[0], &listed, || info_of(&listed[0])).unwrap(), info_of(&together).unwrap());
+        assert_eq!(EwfHandle::open_with_segments(listed.clone()).unwrap().get_chunk_count(), 4);
+
+        let swapped = [listed[1].clone(), listed[0].clone()];
+        let error = with_segment_list(&listed[0], &swapped, || report(&listed[0])).unwrap_err();
+        assert!(error.ends_with("disk.E02 is segment 2 by its header but listed as segment 1"), "{error}");
+    }
+
+    #[test]
+    fn test_device_loss_aborts_with_single_error_and_releases_handles() {
+        use std::sync::atomic::{AtomicBool, Ordering};
+        use std::sync::Arc;
+
+        let (image, _) = build_optical_e01();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("disc.E01");
+        std::fs::write(&path, image).unwrap();
+
+        let unplugged = Arc::new(AtomicBool::new(false));
+        let flag = unplugged.clone();
+        let mut handle = EwfHandle::open_with_opener(path.to_str().unwrap(), Box::new(move |path| {
+            Ok(Box::new(UnpluggableReader { inner: File::open(path)?, unplugged: flag.clone() }))
+        })).unwrap();
+
+        assert_eq!(read_batch(&mut handle, 0..2, true).unwrap().1.len(), 0);
+        assert_eq!(handle.file_pool.open_count(), 1);
+
+        unplugged.store(true, Ordering::Relaxed);
+        let error = read_batch(&mut handle, 2..4, true).unwrap_err();
+        assert!(crate::common::device::is_device_disconnected(&error), "{error}");
+        assert_eq!(handle.file_pool.open_count(), 0);
+    }
+
+    #[test]
+    fn test_later_segment_without_first_is_reported() {
+        let (first, _) = build_optical_e01();
+        let mut third = first.clone();
+        third[9..11].copy_from_slice(&3u16.to_le_bytes());
+        let dir = tempfile::tempdir().unwrap();
+        let third_path = dir.path().join("disc.E03");
+        std::fs::write(&third_path, &third).unwrap();
+        let third_path = third_path.to_str().unwrap();
+
+        assert_eq!(segment_number(third_path).unwrap(), 3);
+        let orphan = find_orphaned_segment(third_path).unwrap();
+        assert_eq!((orphan.segment_number, orphan.expected_first.as_str()), (3, "disc.E01"));
+        let error = info(third_path).err().unwrap();
+        assert!(error.contains("segment 3") && error.contains("disc.E01"), "{error}");
+
+        // With the first segment present, opening a later one reads the whole set
+        std::fs::write(dir.path().join("disc.e01"), &first).unwrap();
+        assert!(find_orphaned_segment(third_path).is_none());
+        let info = info(third_path).unwrap();
+        assert_eq!(info.total_size, 4 * SECTOR as u64);
+    }
+
+    #[test]
+    fn test_session_data_bounds() {
+        let sessions = parse_session_data(&session_data(&[0, 16, 16]), 10).unwrap();
+        assert_eq!(sessions, vec![
+            SessionInfo { first_sector: 0, sector_count: 16 },
+            SessionInfo { first_sector: 16, sector_count: 0 },
+        ]);
+
+        let mut truncated = session_data(&[0, 100]);
+        truncated[0..4].copy_from_slice(&1000u32.to_le_bytes());
+        assert!(parse_session_data(&truncated, 200).is_err());
+        assert!(parse_session_data(&[0u8; 8], 200).is_err());
+
+        let mut error2 = vec![0u8; ERROR2_HEADER_SIZE + ERROR2_ENTRY_SIZE];
+        error2[0..4].copy_from_slice(&2u32.to_le_bytes());
+        assert!(parse_error2_data(&error2).unwrap_err().contains("2 entries but room for 1"));
+        assert!(parse_error2_data(&error2[..ERROR2_HEADER_SIZE - 1]).is_err());
+    }
+
+    #[test]
+    fn test_short_section_structures_are_truncation_errors() {
+        let error = parse_section_descriptor(b"volume\0\0\0\0\0\0\0\0\0\0\x4c\0\0\0", 13).unwrap_err();
+        assert_eq!(error.to_string(), "truncated structure at offset 29: 8 bytes needed, 4 left");
+
+        let mut volume = vec![0u8; 20];
+        volume[0] = MEDIA_TYPE_OPTICAL;
+        volume[4..8].copy_from_slice(&3u32.to_le_bytes());
+        volume[16..20].copy_from_slice(&96u32.to_le_bytes());
+        // A SMART volume has a 32-bit sector count, an EnCase one a 64-bit one
+        let smart = parse_volume_data(&volume, 89, SMART_VOLUME_DATA_SIZE).unwrap();
+        assert_eq!((smart.media_type, smart.chunk_count, smart.sector_count, smart.is_smart), (MEDIA_TYPE_OPTICAL, 3, 96, true));
+        assert_eq!(parse_volume_data(&volume, 89, 80).unwrap_err().offset, 89 + 16);
+        assert_eq!(parse_volume_data(&volume[..10], 89, 80).unwrap_err().offset, 89 + 8);
+
+        let mut table = vec![0u8; TABLE_HEADER_SIZE + 8];
+        table[..4].copy_from_slice(&3u32.to_le_bytes());
+        table[TABLE_HEADER_SIZE..TABLE_HEADER_SIZE + 4].copy_from_slice(&0x8000_0010u32.to_le_bytes());
+        let error = parse_table_data(&table, 1000, 4096).err().unwrap();
+        assert_eq!((error.offset, error.available), (1000 + TABLE_HEADER_SIZE as u64 + 8, 0));
+        table.extend_from_slice(&[0u8; 4]);
+        assert_eq!(parse_table_data(&table, 1000, 4096).unwrap().offsets, [0x8000_0010, 0, 0]);
+        assert!(parse_table_data(&table[..20], 1000, 4096).is_err_and(|e| e.offset == 1024));
     }
 }
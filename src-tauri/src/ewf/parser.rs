@@ -10,14 +10,17 @@
 //! - Lx01: Logical evidence file (EWF v2)
 
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
 use std::fs::File;
 use std::io::{Read, Seek, SeekFrom};
 
 use crate::viewer::{HeaderRegion, MetadataField, ParsedMetadata};
 
 // Import all signatures from types module (single source of truth)
-use super::types::{EWF_SIGNATURE, EWF2_SIGNATURE, LVF_SIGNATURE, LVF2_SIGNATURE};
+use super::header::HeaderValues;
+use crate::common::time::Timestamp;
+use super::types::{EWF_SIGNATURE, EWF2_SIGNATURE, LVF_SIGNATURE, LVF2_SIGNATURE, LEF2_SIGNATURE, MAX_ERROR2_SECTION_SIZE};
+use super::handle::parse_error2_data;
+use crate::common::binary::{read_up_to, ByteReader, Truncated};
 
 // ============================================================================
 // Constants
@@ -119,8 +122,8 @@ pub struct EwfCaseInfo {
     pub evidence_number: Option<String>,
     pub examiner: Option<String>,
     pub notes: Option<String>,
-    pub acquisition_date: Option<String>,
-    pub system_date: Option<String>,
+    pub acquisition_date: Option<Timestamp>,
+    pub system_date: Option<Timestamp>,
     pub acquisition_software: Option<String>,
     pub acquisition_os: Option<String>,
     pub device_model: Option<String>,
@@ -241,7 +244,7 @@ pub fn detect_ewf_variant(header: &[u8]) -> Result<(EwfVariant, u8), String> {
         return Ok((EwfVariant::Ex01, 2));
     }
     // Check for Lx01 (logical, v2)
-    if &header[0..8] == LVF2_SIGNATURE {
+    if &header[0..8] == LVF2_SIGNATURE || &header[0..8] == LEF2_SIGNATURE {
         return Ok((EwfVariant::Lx01, 2));
     }
     
@@ -267,7 +270,8 @@ pub fn is_ewf_file(path: &str) -> Result<bool, String> {
     Ok(&sig == EWF_SIGNATURE || 
        &sig == LVF_SIGNATURE || 
        &sig == EWF2_SIGNATURE || 
-       &sig == LVF2_SIGNATURE)
+       &sig == LVF2_SIGNATURE ||
+       &sig == LEF2_SIGNATURE)
 }
 
 /// Check if a file is L01 format specifically
@@ -278,7 +282,7 @@ pub fn is_l01_file(path: &str) -> Result<bool, String> {
         return Ok(false);
     }
     
-    Ok(&sig == LVF_SIGNATURE || &sig == LVF2_SIGNATURE)
+    Ok(&sig == LVF_SIGNATURE || &sig == LVF2_SIGNATURE || &sig == LEF2_SIGNATURE)
 }
 
 /// Parse all section headers in the file
@@ -303,8 +307,9 @@ fn parse_all_sections(file: &mut File, start_offset: u64, file_size: u64) -> Res
             break;
         }
         
-        // Parse section type (first 16 bytes, null-terminated)
-        let type_bytes = &header[0..16];
+        let mut reader = ByteReader::at(&header, offset);
+        // Section type (first 16 bytes, null-terminated), next offset, size
+        let type_bytes: [u8; 16] = reader.array()?;
         let section_type = type_bytes
             .iter()
             .take_while(|&&b| b != 0)
@@ -317,21 +322,12 @@ fn parse_all_sections(file: &mut File, start_offset: u64, file_size: u64) -> Res
             break;
         }
         
-        // Parse offsets and size
-        let next_offset = u64::from_le_bytes([
-            header[16], header[17], header[18], header[19],
-            header[20], header[21], header[22], header[23],
-        ]);
+        let next_offset = reader.u64_le()?;
+        let section_size = reader.u64_le()?;
         
-        let section_size = u64::from_le_bytes([
-            header[24], header[25], header[26], header[27],
-            header[28], header[29], header[30], header[31],
-        ]);
-        
-        // Checksum at offset 72
-        let checksum = u32::from_le_bytes([
-            header[72], header[73], header[74], header[75],
-        ]);
+        // Checksum at offset 72, after 40 bytes of padding
+        reader.seek(72)?;
+        let checksum = reader.u32_le()?;
         
         let section = EwfSectionHeader {
             section_type: section_type.clone(),
@@ -372,174 +368,98 @@ fn parse_volume_section(file: &mut File, sections: &[EwfSectionHeader]) -> Resul
     
     if let Some(section) = volume_section {
         let data_offset = section.file_offset + SECTION_HEADER_SIZE as u64;
-        
-        file.seek(SeekFrom::Start(data_offset))
-            .map_err(|e| format!("Seek failed: {}", e))?;
-        
-        let mut data = [0u8; 80];
-        if file.read_exact(&mut data).is_err() {
-            return Ok(None);
-        }
-        
-        // Parse volume fields
-        let chunk_count = u32::from_le_bytes([data[4], data[5], data[6], data[7]]);
-        let sectors_per_chunk = u32::from_le_bytes([data[8], data[9], data[10], data[11]]);
-        let bytes_per_sector = u32::from_le_bytes([data[12], data[13], data[14], data[15]]);
-        let sector_count = u64::from_le_bytes([
-            data[16], data[17], data[18], data[19],
-            data[20], data[21], data[22], data[23],
-        ]);
-        
-        let chs_cylinders = u32::from_le_bytes([data[24], data[25], data[26], data[27]]);
-        let chs_heads = u32::from_le_bytes([data[28], data[29], data[30], data[31]]);
-        let chs_sectors = u32::from_le_bytes([data[32], data[33], data[34], data[35]]);
-        let media_type = u32::from_le_bytes([data[36], data[37], data[38], data[39]]);
-        let compression_level = data[56];
-        
-        // GUID at offset 60 (16 bytes)
-        let guid = if data.len() > 76 {
-            let guid_bytes = &data[60..76];
-            if guid_bytes.iter().any(|&b| b != 0) {
-                Some(format_guid(guid_bytes))
-            } else {
-                None
-            }
-        } else {
-            None
-        };
-        
-        return Ok(Some(EwfVolumeInfo {
-            chunk_count,
-            sectors_per_chunk,
-            bytes_per_sector,
-            sector_count,
-            chs_cylinders,
-            chs_heads,
-            chs_sectors,
-            media_type,
-            compression_level,
-            guid,
-        }));
+        // A volume section cut short is reported as no volume information
+        let data = read_up_to(file, data_offset, 80)?;
+        return Ok(decode_volume(&data, data_offset).ok());
     }
     
     Ok(None)
 }
 
+/// Decode the first 80 bytes of volume section data
+fn decode_volume(data: &[u8], offset: u64) -> Result<EwfVolumeInfo, Truncated> {
+    let mut reader = ByteReader::at(data, offset);
+    reader.skip(4)?;
+    let chunk_count = reader.u32_le()?;
+    let sectors_per_chunk = reader.u32_le()?;
+    let bytes_per_sector = reader.u32_le()?;
+    let sector_count = reader.u64_le()?;
+    let chs_cylinders = reader.u32_le()?;
+    let chs_heads = reader.u32_le()?;
+    let chs_sectors = reader.u32_le()?;
+    let media_type = reader.u32_le()?;
+    reader.seek(56)?;
+    let compression_level = reader.u8()?;
+    
+    // GUID at offset 60 (16 bytes)
+    reader.seek(60)?;
+    let guid_bytes = reader.bytes(16)?;
+    let guid = guid_bytes.iter().any(|&b| b != 0).then(|| format_guid(guid_bytes));
+    
+    Ok(EwfVolumeInfo {
+        chunk_count,
+        sectors_per_chunk,
+        bytes_per_sector,
+        sector_count,
+        chs_cylinders,
+        chs_heads,
+        chs_sectors,
+        media_type,
+        compression_level,
+        guid,
+    })
+}
+
 /// Parse case information from header/header2 sections
 fn parse_case_info(file: &mut File, sections: &[EwfSectionHeader]) -> Result<EwfCaseInfo, String> {
-    let mut case_info = EwfCaseInfo::default();
-    
-    // Prefer header2 (UTF-16) over header (ASCII)
-    let header_section = sections
-        .iter()
-        .find(|s| s.section_type == "header2")
-        .or_else(|| sections.iter().find(|s| s.section_type == "header"));
-    
-    if let Some(section) = header_section {
-        let data_offset = section.file_offset + SECTION_HEADER_SIZE as u64;
-        let data_size = section.section_size.saturating_sub(SECTION_HEADER_SIZE as u64) as usize;
-        
-        // Limit to reasonable size
-        let read_size = data_size.min(65536);
-        
-        file.seek(SeekFrom::Start(data_offset))
-            .map_err(|e| format!("Seek failed: {}", e))?;
-        
-        let mut data = vec![0u8; read_size];
-        if file.read_exact(&mut data).is_err() {
-            return Ok(case_info);
-        }
-        
-        // Check if data is zlib compressed (starts with 0x78)
-        if data.len() >= 2 && (data[0] == 0x78 || (data[0] == 0x78 && data[1] == 0x9c)) {
-            // Try to decompress
-            if let Ok(decompressed) = decompress_zlib(&data) {
-                case_info = parse_header_content(&decompressed, section.section_type == "header2");
-            }
-        } else {
-            // Try parsing as-is
-            case_info = parse_header_content(&data, section.section_type == "header2");
-        }
-    }
+    let header = read_header_values(file, sections, "header")?;
+    let header2 = read_header_values(file, sections, "header2")?;
+    
+    // Prefer header2 (UTF-16) values over header (ASCII)
+    let values = match (header, header2) {
+        (Some(header), Some(header2)) => header.merge(header2),
+        (Some(values), None) | (None, Some(values)) => values,
+        (None, None) => return Ok(EwfCaseInfo::default()),
+    };
     
-    Ok(case_info)
+    Ok(EwfCaseInfo {
+        description: values.value("a"),
+        case_number: values.value("c"),
+        evidence_number: values.value("n"),
+        examiner: values.value("e"),
+        notes: values.value("t"),
+        acquisition_date: values.date("m"),
+        system_date: values.date("u"),
+        acquisition_software: values.value("av"),
+        acquisition_os: values.value("ov"),
+        device_model: values.value("md"),
+        device_serial: values.value("sn"),
+        device_label: values.value("l"),
+        device_total_bytes: values.get("tb").and_then(|tb| tb.parse().ok()),
+    })
 }
 
-/// Parse decompressed header content
-fn parse_header_content(data: &[u8], is_utf16: bool) -> EwfCaseInfo {
-    let mut case_info = EwfCaseInfo::default();
-    
-    // Convert to string
-    let content = if is_utf16 {
-        // UTF-16 LE decoding
-        let utf16_data: Vec<u16> = data
-            .chunks(2)
-            .filter_map(|chunk| {
-                if chunk.len() == 2 {
-                    Some(u16::from_le_bytes([chunk[0], chunk[1]]))
-                } else {
-                    None
-                }
-            })
-            .collect();
-        String::from_utf16_lossy(&utf16_data)
-    } else {
-        String::from_utf8_lossy(data).to_string()
+/// Read and decode the first section of the given header type
+fn read_header_values(file: &mut File, sections: &[EwfSectionHeader], section_type: &str) -> Result<Option<HeaderValues>, String> {
+    let Some(section) = sections.iter().find(|s| s.section_type == section_type) else {
+        return Ok(None);
     };
     
-    // Parse key=value pairs
-    let fields = parse_ewf_header_fields(&content);
-    
-    // Map known fields
-    case_info.description = fields.get("a").cloned();
-    case_info.case_number = fields.get("c").cloned();
-    case_info.evidence_number = fields.get("n").cloned();
-    case_info.examiner = fields.get("e").cloned();
-    case_info.notes = fields.get("t").cloned();
-    case_info.acquisition_date = fields.get("m").cloned();
-    case_info.system_date = fields.get("u").cloned();
-    case_info.acquisition_software = fields.get("av").cloned();
-    case_info.acquisition_os = fields.get("ov").cloned();
-    case_info.device_model = fields.get("md").cloned();
-    case_info.device_serial = fields.get("sn").cloned();
-    case_info.device_label = fields.get("l").cloned();
-    
-    // Parse total bytes if present
-    if let Some(tb) = fields.get("tb") {
-        case_info.device_total_bytes = tb.parse().ok();
-    }
+    let data_offset = section.file_offset + SECTION_HEADER_SIZE as u64;
+    let data_size = section.section_size.saturating_sub(SECTION_HEADER_SIZE as u64) as usize;
     
-    case_info
-}
-
-/// Parse EWF header key=value fields
-fn parse_ewf_header_fields(content: &str) -> HashMap<String, String> {
-    let mut fields = HashMap::new();
-    
-    // Split by tabs and newlines
-    for line in content.split(|c| c == '\n' || c == '\r') {
-        let line = line.trim();
-        if line.is_empty() {
-            continue;
-        }
-        
-        // Look for key=value or key\tvalue patterns
-        if let Some(eq_pos) = line.find('=') {
-            let key = line[..eq_pos].trim();
-            let value = line[eq_pos + 1..].trim();
-            if !key.is_empty() && !value.is_empty() {
-                fields.insert(key.to_string(), value.to_string());
-            }
-        } else if let Some(tab_pos) = line.find('\t') {
-            let key = line[..tab_pos].trim();
-            let value = line[tab_pos + 1..].trim();
-            if !key.is_empty() && !value.is_empty() {
-                fields.insert(key.to_string(), value.to_string());
-            }
-        }
+    // Limit to reasonable size
+    let read_size = data_size.min(65536);
+    
+    file.seek(SeekFrom::Start(data_offset))
+        .map_err(|e| format!("Seek failed: {}", e))?;
+    
+    let mut data = vec![0u8; read_size];
+    if file.read_exact(&mut data).is_err() {
+        return Ok(None);
     }
     
-    fields
+    Ok(HeaderValues::from_section(&data, section_type == "header2").ok())
 }
 
 /// Parse hash information from hash/digest sections
@@ -587,53 +507,29 @@ fn parse_hash_info(file: &mut File, sections: &[EwfSectionHeader]) -> Result<Ewf
 
 /// Parse error information from error2 section
 fn parse_error_info(file: &mut File, sections: &[EwfSectionHeader]) -> Result<Vec<EwfErrorEntry>, String> {
-    let mut errors = Vec::new();
+    let Some(section) = sections.iter().find(|s| s.section_type == "error2") else {
+        return Ok(Vec::new());
+    };
+    let data_size = section.section_size.saturating_sub(SECTION_HEADER_SIZE as u64).min(MAX_ERROR2_SECTION_SIZE);
     
-    if let Some(section) = sections.iter().find(|s| s.section_type == "error2") {
-        let data_offset = section.file_offset + SECTION_HEADER_SIZE as u64;
-        
-        file.seek(SeekFrom::Start(data_offset))
-            .map_err(|e| format!("Seek failed: {}", e))?;
-        
-        let mut header = [0u8; 12];
-        if file.read_exact(&mut header).is_err() {
-            return Ok(errors);
-        }
-        
-        let error_count = u32::from_le_bytes([header[0], header[1], header[2], header[3]]) as usize;
-        
-        // Limit to reasonable number
-        let max_errors = error_count.min(10000);
-        
-        for _ in 0..max_errors {
-            let mut entry = [0u8; 8];
-            if file.read_exact(&mut entry).is_err() {
-                break;
-            }
-            
-            let first_sector = u32::from_le_bytes([entry[0], entry[1], entry[2], entry[3]]);
-            let sector_count = u32::from_le_bytes([entry[4], entry[5], entry[6], entry[7]]);
-            
-            errors.push(EwfErrorEntry {
-                first_sector,
-                sector_count,
-            });
-        }
+    file.seek(SeekFrom::Start(section.file_offset + SECTION_HEADER_SIZE as u64))
+        .map_err(|e| format!("Seek failed: {}", e))?;
+    
+    let mut data = vec![0u8; data_size as usize];
+    if file.read_exact(&mut data).is_err() {
+        return Ok(Vec::new());
     }
     
-    Ok(errors)
-}
-
-/// Simple zlib decompression using flate2
-fn decompress_zlib(data: &[u8]) -> Result<Vec<u8>, String> {
-    use flate2::read::ZlibDecoder;
-    use std::io::Read;
-    
-    let mut decoder = ZlibDecoder::new(data);
-    let mut decompressed = Vec::new();
-    decoder.read_to_end(&mut decompressed)
-        .map_err(|e| format!("Decompression failed: {}", e))?;
-    Ok(decompressed)
+    // A damaged section shows as no entries rather than failing the viewer
+    Ok(parse_error2_data(&data)
+        .unwrap_or_default()
+        .into_iter()
+        .take(10000)
+        .map(|range| EwfErrorEntry {
+            first_sector: range.first_sector as u32,
+            sector_count: range.sector_count as u32,
+        })
+        .collect())
 }
 
 // ============================================================================
@@ -646,7 +542,7 @@ fn bytes_to_hex(bytes: &[u8]) -> String {
 }
 
 /// Format GUID bytes
-fn format_guid(bytes: &[u8]) -> String {
+pub(crate) fn format_guid(bytes: &[u8]) -> String {
     if bytes.len() < 16 {
         return bytes_to_hex(bytes);
     }
@@ -914,7 +810,7 @@ pub fn ewf_detailed_info_to_metadata(info: &EwfDetailedInfo) -> ParsedMetadata {
     if let Some(ref acq_date) = info.case_info.acquisition_date {
         fields.push(MetadataField {
             key: "Acquisition Date".to_string(),
-            value: acq_date.clone(),
+            value: acq_date.original.clone(),
             category: "Acquisition".to_string(),
             linked_region: Some("header".to_string()),
             source_offset: header_offset,
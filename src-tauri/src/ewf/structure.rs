@@ -0,0 +1,429 @@
+//! Header-only structure check of EWF1 segment sets (E01, s01, L01)
+//!
+//! Walks the section descriptors of every segment without reading chunk
+//! data: a copy that was cut short, garbled or is missing its last segment
+//! shows up in seconds instead of after a full verification. Each segment's
+//! chain is walked on its own (descriptor offsets are segment-relative) and
+//! must end in "next", or "done" for the last segment.
+//!
+//! A segment shorter than its sections say is reported as truncated, naming
+//! the section that runs past the end; [`verify_preflight`] runs the same
+//! walk so a full verification says so up front instead of failing on a
+//! seek deep into the set.
+
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+use tracing::{debug, trace};
+
+use crate::common::quick_check::StructureCheck;
+use crate::common::segments::discover_ewf_segments;
+use super::handle::parse_section_descriptor;
+use super::operations::find_orphaned_segment;
+use super::types::*;
+
+/// What the walk of one segment's section chain found
+#[derive(Default)]
+struct SegmentWalk {
+    sections: u32,
+    /// Type of the section that ended the chain ("done" or "next")
+    end: Option<String>,
+    /// Where the chain broke, if it did
+    error: Option<String>,
+    /// Chunk count from the volume/disk section
+    volume_chunks: Option<u32>,
+    /// Entries of the segment's table sections (table2 mirrors them)
+    table_entries: u64,
+    /// First section found to extend past the end of the file
+    truncation: Option<SegmentTruncation>,
+}
+
+/// A section of a segment that claims more of the file than there is
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct SegmentTruncation {
+    segment_number: usize,
+    segment_name: String,
+    /// Section type, or "file header" when not even the first descriptor fits
+    section_type: String,
+    section_offset: u64,
+    /// Offset the section's size, next link or chunk table reaches
+    claimed_end: u64,
+    file_size: u64,
+}
+
+impl std::fmt::Display for SegmentTruncation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Segment {} ({}) truncated: '{}' section at offset {} claims to extend to offset {} but the file ends at {}",
+            self.segment_number, self.segment_name, self.section_type, self.section_offset, self.claimed_end, self.file_size
+        )
+    }
+}
+
+/// Segment file names, section walks and header problems of an EWF1 set
+struct SetWalk {
+    names: Vec<String>,
+    walks: Vec<SegmentWalk>,
+    header_issues: Vec<String>,
+}
+
+/// Check the segment headers, section chains, done section and chunk
+/// tables of the EWF1 set at `path`
+///
+/// EWF2 sets (Ex01/Lx01) keep their section descriptors at the end of each
+/// section and are not walked here.
+pub fn quick_check(path: &str) -> Result<Vec<StructureCheck>, String> {
+    let SetWalk { names, walks, header_issues } = walk_set(path)?;
+    let count = names.len();
+    let mut checks = Vec::with_capacity(5);
+
+    // A truncated copy fails the other checks too; this one says why
+    let truncated: Vec<String> = walks.iter()
+        .filter_map(|walk| walk.truncation.as_ref().map(|truncation| truncation.to_string()))
+        .collect();
+    checks.push(if truncated.is_empty() {
+        StructureCheck::pass("Segment sizes", format!("Every section lies within its segment file ({} segment(s))", count))
+    } else {
+        StructureCheck::fail("Segment sizes", truncated.join("; "))
+    });
+
+    checks.push(if header_issues.is_empty() {
+        StructureCheck::pass("Segment headers", format!("{} segment(s) in order", count))
+    } else {
+        StructureCheck::fail("Segment headers", header_issues.join("; "))
+    });
+
+    let broken: Vec<String> = names.iter().zip(&walks)
+        .filter_map(|(name, walk)| walk.error.as_ref().map(|error| format!("{}: {}", name, error)))
+        .collect();
+    let sections: u32 = walks.iter().map(|walk| walk.sections).sum();
+    checks.push(if broken.is_empty() {
+        StructureCheck::pass("Section chain", format!("{} section(s) across {} segment(s)", sections, count))
+    } else {
+        StructureCheck::fail("Section chain", broken.join("; "))
+    });
+
+    checks.push(StructureCheck::from_result("Done section", check_done(&names, &walks)));
+
+    let volume_chunks = walks.iter().find_map(|walk| walk.volume_chunks);
+    let table_entries: u64 = walks.iter().map(|walk| walk.table_entries).sum();
+    checks.push(match volume_chunks {
+        None => StructureCheck::fail("Chunk tables", "No volume section found"),
+        Some(chunks) if chunks as u64 == table_entries => {
+            StructureCheck::pass("Chunk tables", format!("{} chunk(s), as the volume section declares", table_entries))
+        }
+        Some(chunks) => StructureCheck::fail(
+            "Chunk tables",
+            format!("Tables list {} chunk(s) but the volume section declares {}", table_entries, chunks),
+        ),
+    });
+    Ok(checks)
+}
+
+/// Why a full verification of the EWF1 set at `path` would stop part way:
+/// the first truncated segment, or a last segment without its done section
+///
+/// `None` when the set looks whole or cannot be walked here (EWF2, unreadable
+/// headers) - opening it for verification reports those.
+pub fn verify_preflight(path: &str) -> Option<String> {
+    let SetWalk { names, walks, .. } = walk_set(path).ok()?;
+    if let Some(truncation) = walks.iter().find_map(|walk| walk.truncation.as_ref()) {
+        return Some(truncation.to_string());
+    }
+    check_done(&names, &walks).err()
+}
+
+/// Read every segment's file header and walk its section chain
+fn walk_set(path: &str) -> Result<SetWalk, String> {
+    if let Some(orphan) = find_orphaned_segment(path) {
+        return Err(orphan.to_string());
+    }
+    let set = discover_ewf_segments(path)?;
+    debug!(path, segments = set.paths.len(), "Quick check of EWF set");
+
+    let mut header_issues = set.conflicts.clone();
+    let mut walks = Vec::with_capacity(set.paths.len());
+    for (index, segment) in set.paths.iter().enumerate() {
+        let name = file_name(segment);
+        let mut file = File::open(segment).map_err(|e| format!("Failed to open {}: {}", name, e))?;
+        let mut header = [0u8; EWF_FILE_HEADER_SIZE];
+        file.read_exact(&mut header).map_err(|e| format!("Failed to read the file header of {}: {}", name, e))?;
+        let signature = &header[..8];
+        if signature == EWF2_SIGNATURE || signature == LVF2_SIGNATURE || signature == LEF2_SIGNATURE {
+            return Err("Quick check of EWF2 (Ex01/Lx01) segments is not supported".to_string());
+        }
+        if signature != EWF_SIGNATURE && signature != LVF_SIGNATURE {
+            header_issues.push(format!("{} has no EWF signature", name));
+            walks.push(SegmentWalk { error: Some("no EWF signature".to_string()), ..Default::default() });
+            continue;
+        }
+        let number = u16::from_le_bytes([header[9], header[10]]) as usize;
+        if number != index + 1 {
+            header_issues.push(format!("{} is segment {} by its header but segment {} by its name", name, number, index + 1));
+        }
+        walks.push(walk_segment(&mut file, index + 1, &name)?);
+    }
+    let names = set.paths.iter().map(|p| file_name(p)).collect();
+    Ok(SetWalk { names, walks, header_issues })
+}
+
+/// Every segment but the last hands over with "next"; the last one ends
+/// with "done"
+fn check_done(names: &[String], walks: &[SegmentWalk]) -> Result<String, String> {
+    let (last, earlier) = walks.split_last().ok_or("No segments")?;
+    if let Some(index) = earlier.iter().position(|walk| walk.end.as_deref() == Some("done")) {
+        return Err(format!("{} ends with a done section but is not the last segment", names[index]));
+    }
+    let last_name = &names[names.len() - 1];
+    match last.end.as_deref() {
+        Some("done") => Ok(format!("{} ends with a done section", last_name)),
+        Some(_) => Err(format!("{} hands over to a next segment that was not found", last_name)),
+        None => Err(format!("Not reached: the section chain of {} is broken", last_name)),
+    }
+}
+
+/// Walk one segment's descriptors from the end of the file header
+///
+/// Each section must fit in the file: its descriptor, its size, the
+/// descriptor its next link points at and the chunks its table lists.
+fn walk_segment(file: &mut File, number: usize, name: &str) -> Result<SegmentWalk, String> {
+    let size = file.metadata().map_err(|e| format!("Failed to get metadata: {}", e))?.len();
+    let truncated = |section_type: &str, section_offset: u64, claimed_end: u64| Some(SegmentTruncation {
+        segment_number: number,
+        segment_name: name.to_string(),
+        section_type: section_type.to_string(),
+        section_offset,
+        claimed_end,
+        file_size: size,
+    });
+    let mut walk = SegmentWalk::default();
+    let mut offset = EWF_FILE_HEADER_SIZE as u64;
+    // The section whose next link led to `offset`
+    let mut previous = ("file header".to_string(), 0u64);
+    loop {
+        if walk.sections >= MAX_SECTIONS {
+            walk.error = Some(format!("more than {} sections", MAX_SECTIONS));
+            break;
+        }
+        if offset + SECTION_DESCRIPTOR_SIZE as u64 > size {
+            walk.error = Some(format!("chain runs past the end of the file at offset {} ({} bytes)", offset, size));
+            walk.truncation = truncated(&previous.0, previous.1, offset + SECTION_DESCRIPTOR_SIZE as u64);
+            break;
+        }
+        let mut descriptor = [0u8; SECTION_DESCRIPTOR_SIZE];
+        file.seek(SeekFrom::Start(offset)).and_then(|_| file.read_exact(&mut descriptor))
+            .map_err(|e| format!("Failed to read section descriptor at offset {}: {}", offset, e))?;
+        let SectionDescriptor { section_type, next_offset: next, size: section_size } = parse_section_descriptor(&descriptor, offset)?;
+        let kind = String::from_utf8_lossy(&section_type).trim_end_matches('\0').to_string();
+        if kind.is_empty() || !kind.bytes().all(|b| b.is_ascii_alphanumeric()) {
+            walk.error = Some(format!("unreadable section descriptor at offset {}", offset));
+            break;
+        }
+        walk.sections += 1;
+        trace!(kind, offset, next, section_size, "Section");
+
+        if offset.saturating_add(section_size) > size {
+            walk.error = Some(format!("'{}' section at offset {} is {} bytes, past the end of the file", kind, offset, section_size));
+            walk.truncation = truncated(&kind, offset, offset.saturating_add(section_size));
+            break;
+        }
+
+        let data = offset + SECTION_DESCRIPTOR_SIZE as u64;
+        match kind.as_str() {
+            "done" | "next" => {
+                walk.end = Some(kind);
+                break;
+            }
+            "volume" | "disk" if walk.volume_chunks.is_none() => {
+                // Media type and padding, then the chunk count
+                walk.volume_chunks = Some(read_u32(file, data + 4)?);
+            }
+            "table" => {
+                let entries = read_u32(file, data)?;
+                walk.table_entries += entries as u64;
+                let chunk_end = furthest_chunk(file, data, entries, section_size)?;
+                if chunk_end > size {
+                    walk.error = Some(format!("'table' section at offset {} lists chunk data up to offset {}, past the end of the file", offset, chunk_end));
+                    walk.truncation = truncated(&kind, offset, chunk_end);
+                    break;
+                }
+            }
+            _ => {}
+        }
+
+        if next <= offset || next > size {
+            walk.error = Some(format!("'{}' section at offset {} links to {}, outside the rest of the file", kind, offset, next));
+            if next > size {
+                walk.truncation = truncated(&kind, offset, next);
+            }
+            break;
+        }
+        previous = (kind, offset);
+        offset = next;
+    }
+    Ok(walk)
+}
+
+/// One past the start of the furthest chunk a table section lists (0 for an
+/// empty table): the least the segment must hold for the table to be valid
+///
+/// Offsets are relative to the table's base offset, with the top bit
+/// flagging compression.
+fn furthest_chunk(file: &mut File, data: u64, entries: u32, section_size: u64) -> Result<u64, String> {
+    let room = section_size.saturating_sub((SECTION_DESCRIPTOR_SIZE + TABLE_HEADER_SIZE) as u64) / 4;
+    let entries = (entries as u64).min(room) as usize;
+    if entries == 0 {
+        return Ok(0);
+    }
+    let mut header = [0u8; 16];
+    file.seek(SeekFrom::Start(data)).and_then(|_| file.read_exact(&mut header))
+        .map_err(|e| format!("Failed to read table header at offset {}: {}", data, e))?;
+    let base = u64::from_le_bytes(header[8..16].try_into().unwrap());
+    let mut raw = vec![0u8; entries * 4];
+    file.seek(SeekFrom::Start(data + TABLE_HEADER_SIZE as u64)).and_then(|_| file.read_exact(&mut raw))
+        .map_err(|e| format!("Failed to read table entries at offset {}: {}", data, e))?;
+    let furthest = raw.chunks_exact(4)
+        .map(|entry| (u32::from_le_bytes(entry.try_into().unwrap()) & 0x7FFF_FFFF) as u64)
+        .max()
+        .unwrap_or(0);
+    Ok(base + furthest + 1)
+}
+
+fn read_u32(file: &mut File, offset: u64) -> Result<u32, String> {
+    let mut bytes = [0u8; 4];
+    file.seek(SeekFrom::Start(offset)).and_then(|_| file.read_exact(&mut bytes))
+        .map_err(|e| format!("Failed to read section data at offset {}: {}", offset, e))?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn section(segment: &mut Vec<u8>, kind: &str, data: &[u8]) {
+        let start = segment.len() as u64;
+        let size = (SECTION_DESCRIPTOR_SIZE + data.len()) as u64;
+        let next = if kind == "done" || kind == "next" { start } else { start + size };
+        let mut descriptor = [0u8; SECTION_DESCRIPTOR_SIZE];
+        descriptor[..kind.len()].copy_from_slice(kind.as_bytes());
+        descriptor[16..24].copy_from_slice(&next.to_le_bytes());
+        descriptor[24..32].copy_from_slice(&size.to_le_bytes());
+        segment.extend_from_slice(&descriptor);
+        segment.extend_from_slice(data);
+    }
+
+    fn table(entries: u32) -> Vec<u8> {
+        let mut data = vec![0u8; 24 + entries as usize * 4];
+        data[..4].copy_from_slice(&entries.to_le_bytes());
+        data
+    }
+
+    /// Two-segment set of 3 chunks: volume and 2 table entries in E01,
+    /// 1 table entry in E02
+    fn write_set(dir: &Path) -> String {
+        for number in 1..=2u16 {
+            let mut segment = EWF_SIGNATURE.to_vec();
+            segment.push(1);
+            segment.extend_from_slice(&number.to_le_bytes());
+            segment.extend_from_slice(&[0, 0]);
+            if number == 1 {
+                let mut volume = vec![0u8; 1052];
+                volume[4..8].copy_from_slice(&3u32.to_le_bytes());
+                section(&mut segment, "volume", &volume);
+                section(&mut segment, "sectors", &[0u8; 64]);
+                section(&mut segment, "table", &table(2));
+                section(&mut segment, "table2", &table(2));
+                section(&mut segment, "next", &[]);
+            } else {
+                section(&mut segment, "sectors", &[0u8; 32]);
+                section(&mut segment, "table", &table(1));
+                section(&mut segment, "done", &[]);
+            }
+            std::fs::write(dir.join(format!("disk.E{number:02}")), segment).unwrap();
+        }
+        dir.join("disk.E01").to_string_lossy().to_string()
+    }
+
+    fn failed(checks: &[StructureCheck]) -> Vec<&str> {
+        checks.iter().filter(|check| !check.passed).map(|check| check.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_intact_set_passes_every_check() {
+        let dir = tempfile::tempdir().unwrap();
+        let checks = quick_check(&write_set(dir.path())).unwrap();
+        assert_eq!(checks.len(), 5);
+        assert!(failed(&checks).is_empty(), "{checks:?}");
+        assert_eq!(checks[2].detail, "8 section(s) across 2 segment(s)");
+        assert_eq!(checks[4].detail, "3 chunk(s), as the volume section declares");
+        assert_eq!(verify_preflight(&write_set(dir.path())), None);
+    }
+
+    #[test]
+    fn test_missing_and_truncated_segments_fail() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write_set(dir.path());
+
+        // A copy that stopped before the last segment
+        let second = dir.path().join("disk.E02");
+        let bytes = std::fs::read(&second).unwrap();
+        std::fs::remove_file(&second).unwrap();
+        let checks = quick_check(&path).unwrap();
+        assert_eq!(failed(&checks), ["Done section", "Chunk tables"]);
+        assert!(checks[3].detail.contains("disk.E01 hands over to a next segment"), "{}", checks[3].detail);
+        assert_eq!(checks[4].detail, "Tables list 2 chunk(s) but the volume section declares 3");
+        assert_eq!(verify_preflight(&path), Some(checks[3].detail.clone()));
+
+        // ... and one cut off in the middle of its last segment
+        std::fs::write(&second, &bytes[..bytes.len() - 40]).unwrap();
+        let checks = quick_check(&path).unwrap();
+        assert_eq!(failed(&checks), ["Segment sizes", "Section chain", "Done section"]);
+        assert!(checks[2].detail.starts_with("disk.E02: chain runs past the end of the file"), "{}", checks[2].detail);
+        assert!(checks[3].detail.starts_with("Not reached"), "{}", checks[3].detail);
+    }
+
+    #[test]
+    fn test_truncation_names_the_section_past_the_end() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write_set(dir.path());
+        let second = dir.path().join("disk.E02");
+        let bytes = std::fs::read(&second).unwrap();
+
+        // E02: sectors at 13-121, table at 121-225, done at 225-301
+        for (cut, expected) in [
+            (100, "Segment 2 (disk.E02) truncated: 'sectors' section at offset 13 claims to extend to offset 121 but the file ends at 100"),
+            (200, "Segment 2 (disk.E02) truncated: 'table' section at offset 121 claims to extend to offset 225 but the file ends at 200"),
+            (261, "Segment 2 (disk.E02) truncated: 'table' section at offset 121 claims to extend to offset 301 but the file ends at 261"),
+        ] {
+            std::fs::write(&second, &bytes[..cut]).unwrap();
+            let checks = quick_check(&path).unwrap();
+            assert_eq!(checks[0].name, "Segment sizes");
+            assert_eq!(checks[0].detail, expected);
+            assert_eq!(verify_preflight(&path).as_deref(), Some(expected));
+        }
+
+        // A table listing a chunk past the end of the segment
+        let mut listed = bytes.clone();
+        listed[221..225].copy_from_slice(&0x8000_0200u32.to_le_bytes());
+        std::fs::write(&second, &listed).unwrap();
+        assert_eq!(
+            verify_preflight(&path).as_deref(),
+            Some("Segment 2 (disk.E02) truncated: 'table' section at offset 121 claims to extend to offset 513 but the file ends at 301")
+        );
+
+        // Cut inside the first section descriptor of E01
+        std::fs::write(&second, &bytes).unwrap();
+        let first = dir.path().join("disk.E01");
+        let head = std::fs::read(&first).unwrap()[..50].to_vec();
+        std::fs::write(&first, head).unwrap();
+        assert_eq!(
+            verify_preflight(&path).as_deref(),
+            Some("Segment 1 (disk.E01) truncated: 'file header' section at offset 0 claims to extend to offset 89 but the file ends at 50")
+        );
+    }
+}
@@ -1,6 +1,11 @@
 //! Type definitions for EWF format parsing
 
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+
+use super::header::HeaderValues;
+use crate::common::content_map::ContentMap;
+use crate::common::parse_warning::ParseWarning;
+use crate::common::time::Timestamp;
 
 // Re-export StoredHash from containers for backward compatibility
 pub use crate::containers::StoredHash as StoredImageHash;
@@ -17,6 +22,53 @@ pub(crate) const EWF2_SIGNATURE: &[u8; 8] = b"EVF2\x0d\x0a\x81\x00";
 pub(crate) const LVF_SIGNATURE: &[u8; 8] = b"LVF\x09\x0d\x0a\xff\x00";
 /// LVF v2 signature (Lx01 - logical evidence file)
 pub(crate) const LVF2_SIGNATURE: &[u8; 8] = b"LVF2\x0d\x0a\x81\x00";
+/// LEF2 signature (Lx01 as written by EnCase 7 and later)
+pub(crate) const LEF2_SIGNATURE: &[u8; 8] = b"LEF2\x0d\x0a\x81\x00";
+/// SMART (EWF-S01) files share the EVF file signature; the volume section
+/// instead carries this marker in its last bytes (before the checksum)
+pub(crate) const SMART_VOLUME_SIGNATURE: &[u8; 5] = b"SMART";
+/// Size of the SMART volume section data (vs 1052 bytes for EnCase volumes)
+pub(crate) const SMART_VOLUME_DATA_SIZE: u64 = 94;
+/// Format version reported for SMART images
+pub const SMART_FORMAT_VERSION: &str = "SMART/EWF-S01";
+/// EWF1 file header: signature, fields start, segment number, end of fields
+pub(crate) const EWF_FILE_HEADER_SIZE: usize = 13;
+/// EWF1 section descriptor preceding each section's data
+pub(crate) const SECTION_DESCRIPTOR_SIZE: usize = 76;
+/// Table section header: entry count, padding, base offset, checksum
+pub(crate) const TABLE_HEADER_SIZE: usize = 24;
+/// Set identifier GUID within volume/disk/data section data
+pub(crate) const VOLUME_SET_IDENTIFIER_OFFSET: usize = 64;
+
+// =============================================================================
+// Segment Set Errors
+// =============================================================================
+
+/// A later segment of an EWF set whose first segment is missing
+///
+/// Its sections only continue the media stream of the earlier segments, so
+/// the set cannot be read from it.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct OrphanedSegment {
+    pub path: String,
+    /// Segment number from the file header
+    pub segment_number: u32,
+    /// Filename the set's first segment should have (e.g. "image.E01")
+    pub expected_first: String,
+}
+
+impl std::fmt::Display for OrphanedSegment {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = std::path::Path::new(&self.path).file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| self.path.clone());
+        write!(
+            f,
+            "{} is segment {} of an EWF set, but the first segment {} is missing - the set cannot be opened without it",
+            name, self.segment_number, self.expected_first
+        )
+    }
+}
 
 // =============================================================================
 // Core Constants - Sizes
@@ -25,6 +77,39 @@ pub(crate) const LVF2_SIGNATURE: &[u8; 8] = b"LVF2\x0d\x0a\x81\x00";
 #[allow(dead_code)]
 pub(crate) const SECTOR_SIZE: u64 = 512;
 pub(crate) const MAX_OPEN_FILES: usize = 16; // Like libewf's rlimit handling
+/// Upper bound on sections walked across all segments of an image
+pub(crate) const MAX_SECTIONS: u32 = 1_000_000;
+/// Header/header2 sections larger than this are treated as corrupt
+pub(crate) const MAX_HEADER_SECTION_SIZE: u64 = 16 * 1024 * 1024;
+/// Session section: count (4), unknown (28), checksum (4)
+pub(crate) const SESSION_HEADER_SIZE: usize = 36;
+/// Session entry: flags (4), first sector (4), unknown (24)
+pub(crate) const SESSION_ENTRY_SIZE: usize = 32;
+/// Session sections larger than this are treated as corrupt
+pub(crate) const MAX_SESSION_SECTION_SIZE: u64 = 1024 * 1024;
+/// Error2 section: count (4), unknown (512), checksum (4)
+pub(crate) const ERROR2_HEADER_SIZE: usize = 520;
+/// Error2 entry: first sector (4), sector count (4)
+pub(crate) const ERROR2_ENTRY_SIZE: usize = 8;
+/// Error2 sections larger than this are treated as corrupt
+pub(crate) const MAX_ERROR2_SECTION_SIZE: u64 = 16 * 1024 * 1024;
+
+/// Volume section media type byte for CD/DVD/Blu-ray acquisitions
+pub(crate) const MEDIA_TYPE_OPTICAL: u8 = 0x03;
+/// Media type of logical evidence (L01/Lx01)
+pub(crate) const MEDIA_TYPE_LOGICAL: u8 = 0x0e;
+
+/// Name of a volume section media type byte
+pub(crate) fn media_type_name(media_type: u8) -> Option<&'static str> {
+    match media_type {
+        0x00 => Some("removable"),
+        0x01 => Some("fixed"),
+        MEDIA_TYPE_OPTICAL => Some("optical"),
+        MEDIA_TYPE_LOGICAL => Some("logical"),
+        0x10 => Some("memory"),
+        _ => None,
+    }
+}
 
 // =============================================================================
 // Section Descriptors - EWF Format Structures
@@ -39,11 +124,15 @@ pub(crate) struct SectionDescriptor {
 
 #[derive(Clone, Debug)]
 pub struct VolumeSection {
+    /// Media type byte (see [`media_type_name`])
+    pub media_type: u8,
     pub chunk_count: u32,
     pub sectors_per_chunk: u32,
     pub bytes_per_sector: u32,
     pub sector_count: u64,
     pub compression_level: u8,
+    /// Volume uses the SMART (EWF-S01) layout
+    pub is_smart: bool,
 }
 
 // =============================================================================
@@ -83,8 +172,8 @@ pub(crate) struct SegmentSection {
 pub(crate) struct TableSection {
     #[allow(dead_code)]
     pub chunk_count: u32,
+    /// Offset in the segment file the chunk offsets count from
     pub base_offset: u64,
-    /// Offsets are relative to the most recent 'sectors' section
     pub offsets: Vec<u64>,
 }
 
@@ -96,12 +185,29 @@ pub struct HeaderInfo {
     pub description: Option<String>,
     pub examiner_name: Option<String>,
     pub notes: Option<String>,
-    pub acquiry_date: Option<String>,
-    pub system_date: Option<String>,
+    pub acquiry_date: Option<Timestamp>,
+    pub system_date: Option<Timestamp>,
     pub acquiry_os: Option<String>,
     pub acquiry_sw_version: Option<String>,
 }
 
+impl HeaderInfo {
+    /// Map decoded header/header2 field codes onto header metadata
+    pub(crate) fn from_values(values: &HeaderValues) -> Self {
+        Self {
+            case_number: values.value("c"),
+            evidence_number: values.value("n"),
+            description: values.value("a"),
+            examiner_name: values.value("e"),
+            notes: values.value("t"),
+            acquiry_date: values.date("m"),
+            system_date: values.date("u"),
+            acquiry_os: values.value("ov"),
+            acquiry_sw_version: values.value("av"),
+        }
+    }
+}
+
 // =============================================================================
 // Chunk Location - Maps chunks to their storage location
 // =============================================================================
@@ -114,17 +220,25 @@ pub(crate) struct ChunkLocation {
     #[allow(dead_code)]
     pub chunk_in_table: usize,
     pub offset: u64, // The offset value from the table (may be relative to base_offset or absolute)
-    pub base_offset: u64, // Table base offset for EnCase 6+ (0 for older versions)
+    pub base_offset: u64, // Table base offset for EnCase 6+, table data offset for SMART (0 for older versions)
     pub sectors_base: u64, // Global offset of the sectors section data area
     pub is_delta_chunk: bool, // True if this was scanned from inline delta format
 }
 
+/// Where a chunk's data lives, resolved from its [`ChunkLocation`]
+pub(crate) enum ChunkStorage {
+    /// Not stored: read as this many zero bytes
+    Zeros(usize),
+    /// Stored in segment `segment` at `offset` within the segment file
+    Stored { segment: usize, offset: u64, compressed: bool },
+}
+
 // =============================================================================
 // Public API Types
 // =============================================================================
 
 /// Container information for EWF format files (E01, L01, Ex01, Lx01)
-#[derive(Serialize)]
+#[derive(Serialize, Clone)]
 pub struct EwfInfo {
     pub format_version: String,
     pub segment_count: u32,
@@ -139,10 +253,26 @@ pub struct EwfInfo {
     pub examiner_name: Option<String>,
     pub evidence_number: Option<String>,
     pub notes: Option<String>,
-    pub acquiry_date: Option<String>,
-    pub system_date: Option<String>,
+    pub acquiry_date: Option<Timestamp>,
+    pub system_date: Option<Timestamp>,
     pub model: Option<String>,
     pub serial_number: Option<String>,
+    /// "removable", "fixed", "optical", "logical" or "memory"; "optical"
+    /// whenever the image has a session section
+    pub media_type: Option<String>,
+    /// Sessions of optical media (empty for disks). Verification still
+    /// hashes all `sector_count` sectors, inter-session gaps included, which
+    /// is the stream EnCase's stored MD5 covers.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub sessions: Vec<SessionInfo>,
+    /// Sectors the acquiring tool could not read from the source drive and
+    /// wrote as zeros (from the error2 section), in sector order. A hash
+    /// mismatch against the source drive is expected when there are any.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub acquisition_errors: Vec<SectorRange>,
+    /// File and folder counts of logical evidence (Lx01)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub logical_entries: Option<LogicalEntries>,
     pub stored_hashes: Vec<StoredImageHash>,
     pub segment_files: Option<Vec<String>>,
     /// Section offsets for hex navigation
@@ -154,12 +284,341 @@ pub struct EwfInfo {
     pub hash_section_offset: Option<u64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub digest_section_offset: Option<u64>,
+    /// Header segment numbers and set identifiers of the segment files
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub segment_consistency: Option<SegmentConsistency>,
+    /// Where a best-effort parse stopped; later sections were not read
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub parse_warnings: Vec<ParseWarning>,
+}
+
+/// Identity a segment file records about itself
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct SegmentHeader {
+    pub filename: String,
+    /// Segment number from the file header (None if unreadable)
+    pub segment_number: Option<u32>,
+    /// Set identifier GUID shared by all segments of one acquisition (None
+    /// if not recorded - older tools write zeros)
+    pub set_identifier: Option<String>,
+}
+
+/// Cross-check of segment filenames against their headers
+///
+/// A renamed or shuffled set (image.E05 saved as image.E03) hashes to a
+/// wrong but plausible value; this names the files that are out of place.
+#[derive(Serialize, Clone, Debug)]
+pub struct SegmentConsistency {
+    /// Segments in filename order
+    pub segments: Vec<SegmentHeader>,
+    /// Every header segment number matches the file's position in the set
+    pub order_matches: bool,
+    /// No two segments record different set identifiers
+    pub same_set: bool,
+    /// No file next to the set shares its stem under another naming scheme
+    /// (image.Ex01 beside image.E01) or follows a missing segment
+    pub single_naming: bool,
+    /// One line per out-of-place, foreign or conflicting segment
+    pub issues: Vec<String>,
+}
+
+impl SegmentConsistency {
+    pub fn is_consistent(&self) -> bool {
+        self.order_matches && self.same_set && self.single_naming
+    }
+}
+
+/// One session of an optical-media acquisition (from the "session" section)
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct SessionInfo {
+    pub first_sector: u64,
+    /// Sectors up to the next session (or the end of the media), so a gap
+    /// between sessions is counted in the session before it
+    pub sector_count: u64,
+}
+
+/// A run of sectors of the acquired media
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SectorRange {
+    pub first_sector: u64,
+    pub sector_count: u64,
+}
+
+impl SectorRange {
+    /// First sector after the range
+    pub fn end_sector(&self) -> u64 {
+        self.first_sector.saturating_add(self.sector_count)
+    }
+}
+
+/// Entries stored in logical evidence (from the single files data section)
+#[derive(Serialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct LogicalEntries {
+    pub file_count: u64,
+    pub folder_count: u64,
+    /// Sum of the logical sizes of the files
+    pub total_bytes: u64,
 }
 
-/// VerifyEntry for container verification results
-#[derive(Serialize)]
-pub struct VerifyResult {
+// =============================================================================
+// Chunk Error Reporting
+// =============================================================================
+
+/// Maximum number of failed chunks listed in a best-effort verification report
+pub const MAX_REPORTED_CHUNK_ERRORS: usize = 1000;
+
+/// Why a chunk could not be read
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ChunkErrorKind {
+    /// Chunk table points outside the segment files
+    Location,
+    /// Segment ended before the chunk data did (truncated/missing segment)
+    ShortRead,
+    /// Zlib stream is corrupt
+    Decompression,
+    /// Chunk inflates past the chunk size declared by the volume section
+    CorruptStructure,
+    /// Any other I/O failure (seek, permission, bad sector)
+    Io,
+    /// The device holding the segment went away; nothing further can be read
+    DeviceDisconnected,
+}
+
+impl ChunkErrorKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ChunkErrorKind::Location => "invalid chunk location",
+            ChunkErrorKind::ShortRead => "short read",
+            ChunkErrorKind::Decompression => "zlib error",
+            ChunkErrorKind::CorruptStructure => "corrupt structure",
+            ChunkErrorKind::Io => "I/O error",
+            ChunkErrorKind::DeviceDisconnected => "device disconnected",
+        }
+    }
+}
+
+/// A chunk read failure mapped back to the part of the disk it covers
+#[derive(Serialize, Clone, Debug)]
+pub struct ChunkReadError {
     pub chunk_index: usize,
-    pub status: String,
-    pub message: Option<String>,
+    /// Logical byte range in the acquired media (end exclusive)
+    pub byte_start: u64,
+    pub byte_end: u64,
+    /// Sector range in the acquired media (end inclusive)
+    pub sector_start: u64,
+    pub sector_end: u64,
+    /// Segment file holding the stored chunk data, if it could be resolved
+    pub segment_path: Option<String>,
+    /// Offset of the stored chunk data within that segment file
+    pub segment_offset: Option<u64>,
+    pub compressed: bool,
+    pub kind: ChunkErrorKind,
+    pub message: String,
+}
+
+impl std::fmt::Display for ChunkReadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Chunk {} ({}) covering sectors {}-{} (bytes {}-{})",
+            self.chunk_index, self.kind.label(),
+            self.sector_start, self.sector_end,
+            self.byte_start, self.byte_end.saturating_sub(1),
+        )?;
+        if let (Some(path), Some(offset)) = (&self.segment_path, self.segment_offset) {
+            write!(f, " stored in {} at offset {}", path, offset)?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+/// Result of a best-effort verification that continues past unreadable chunks
+///
+/// Failed chunks are hashed as zero-filled data so the run can finish; in that
+/// case `hash` is NOT the acquisition hash and `complete` is false.
+#[derive(Serialize, Clone, Debug)]
+pub struct EwfVerifyReport {
+    pub algorithm: String,
+    pub hash: String,
+    pub complete: bool,
+    pub chunk_count: usize,
+    /// Total number of chunks that failed (may exceed `failed_chunks.len()`)
+    pub failed_chunk_count: usize,
+    /// First `MAX_REPORTED_CHUNK_ERRORS` failures, in chunk order
+    pub failed_chunks: Vec<ChunkReadError>,
+    /// Segment order problems (forced verification) or a truncated set
+    /// (best-effort verification) hashed through anyway; `hash` is then not
+    /// expected to match the acquisition hash
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub segment_issues: Vec<String>,
+    /// Sectors the acquisition could not read and zero-filled (see
+    /// [`EwfInfo::acquisition_errors`]); they were hashed as stored
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub acquisition_errors: Vec<SectorRange>,
+    /// Total sectors in `acquisition_errors`
+    pub unreadable_sector_count: u64,
+    /// First `MAX_REPORTED_CHUNK_ERRORS` chunks holding such sectors, in
+    /// chunk order
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub acquisition_error_chunks: Vec<usize>,
+    /// How the chunks are stored, when requested
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chunk_stats: Option<ChunkStats>,
+    /// Zero/entropy map of the image, when requested
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content_map: Option<ContentMap>,
+}
+
+/// Physical hashes of one segment file from [`full_hash`](super::full_hash)
+#[derive(Serialize, Clone, Debug)]
+pub struct SegmentFileHashes {
+    pub segment_name: String,
+    pub segment_number: u32,
+    pub segment_path: String,
+    pub size: u64,
+    /// One hash per algorithm, in the order they were asked for
+    pub hashes: Vec<String>,
+    pub duration_secs: f64,
+}
+
+/// Logical image hashes and physical segment hashes from a single read of
+/// the segment files
+#[derive(Serialize, Clone)]
+pub struct EwfFullHashes {
+    /// Algorithm names, in the order they were asked for
+    pub algorithms: Vec<String>,
+    /// Hash of the decompressed media per algorithm
+    pub image_hashes: Vec<String>,
+    pub segments: Vec<SegmentFileHashes>,
+    pub chunk_count: usize,
+    /// Hashes recorded in the image at acquisition
+    pub stored_hashes: Vec<StoredImageHash>,
+}
+
+/// How many chunks [`sample_verify`](super::sample_verify) checks
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum SampleSize {
+    /// Share of all chunks (0.01 = 1%), at least one chunk
+    Fraction(f64),
+    /// Number of chunks, capped at the chunk count
+    Count(usize),
+}
+
+/// What every sampled chunk check report says about itself
+pub const SAMPLE_NOTICE: &str = "Sampled chunk check, not a hash verification: only the listed share of \
+    chunks was read and no image hash was computed. Full verification is required for evidentiary statements.";
+
+/// Result of a sampled chunk check - a spot check for gross corruption, NOT a
+/// hash verification (there is deliberately no hash in it)
+///
+/// Compressed chunks are checked by inflating them, which validates their
+/// zlib Adler-32; uncompressed chunks only by reading them in full.
+#[derive(Serialize, Clone, Debug)]
+pub struct EwfSampleReport {
+    /// Always [`SAMPLE_NOTICE`]
+    pub notice: &'static str,
+    /// Seed of the chunk selection; the same seed picks the same chunks
+    pub seed: u64,
+    pub chunk_count: usize,
+    pub chunks_checked: usize,
+    /// `chunks_checked` over `chunk_count`
+    pub coverage: f64,
+    /// Media bytes the checked chunks hold
+    pub bytes_checked: u64,
+    pub compressed_chunks: u64,
+    pub uncompressed_chunks: u64,
+    /// Chunks with no stored data (nothing to check)
+    pub sparse_chunks: u64,
+    /// Smallest share of damaged chunks this sample finds with 99% confidence
+    pub detectable_damage_fraction: f64,
+    pub failed_chunk_count: usize,
+    /// First `MAX_REPORTED_CHUNK_ERRORS` failures, in chunk order
+    pub failed_chunks: Vec<ChunkReadError>,
+}
+
+/// Upper bounds of the [`ChunkStats::ratio_histogram`] buckets; the last
+/// bucket holds every ratio above 0.75
+pub const CHUNK_RATIO_BUCKETS: [f64; 4] = [0.1, 0.25, 0.5, 0.75];
+
+/// How an image's chunks are stored, counted while verification reads them
+///
+/// Ratios are stored over logical size of compressed chunks, so 0.25 means a
+/// chunk shrank to a quarter. An image labelled as compressed with few
+/// compressed chunks, or ratios all near 1, was likely acquired without
+/// compression.
+#[derive(Serialize, Clone, Debug, Default, PartialEq)]
+pub struct ChunkStats {
+    pub compressed_chunks: u64,
+    pub uncompressed_chunks: u64,
+    /// Chunks with no stored data, read as zeros
+    pub sparse_chunks: u64,
+    /// Chunk data read from the segment files
+    pub stored_bytes: u64,
+    /// Media bytes the chunks hold
+    pub logical_bytes: u64,
+    pub min_ratio: Option<f64>,
+    pub max_ratio: Option<f64>,
+    /// Stored over logical bytes of all compressed chunks together
+    pub mean_ratio: Option<f64>,
+    /// Compressed chunks per ratio bucket (see [`CHUNK_RATIO_BUCKETS`])
+    pub ratio_histogram: [u64; 5],
+    #[serde(skip)]
+    compressed_stored: u64,
+    #[serde(skip)]
+    compressed_logical: u64,
+}
+
+impl ChunkStats {
+    pub(crate) fn record_sparse(&mut self, logical: usize) {
+        self.sparse_chunks += 1;
+        self.logical_bytes += logical as u64;
+    }
+
+    pub(crate) fn record_stored(&mut self, compressed: bool, stored: u64, logical: usize) {
+        self.stored_bytes += stored;
+        self.logical_bytes += logical as u64;
+        if !compressed {
+            self.uncompressed_chunks += 1;
+            return;
+        }
+        self.compressed_chunks += 1;
+        self.compressed_stored += stored;
+        self.compressed_logical += logical as u64;
+        let ratio = stored as f64 / logical.max(1) as f64;
+        self.min_ratio = Some(self.min_ratio.map_or(ratio, |min| min.min(ratio)));
+        self.max_ratio = Some(self.max_ratio.map_or(ratio, |max| max.max(ratio)));
+        let bucket = CHUNK_RATIO_BUCKETS.iter().position(|&bound| ratio <= bound).unwrap_or(CHUNK_RATIO_BUCKETS.len());
+        self.ratio_histogram[bucket] += 1;
+        self.update_mean();
+    }
+
+    /// Add the counts of another part of the same image
+    pub fn merge(&mut self, other: &ChunkStats) {
+        self.compressed_chunks += other.compressed_chunks;
+        self.uncompressed_chunks += other.uncompressed_chunks;
+        self.sparse_chunks += other.sparse_chunks;
+        self.stored_bytes += other.stored_bytes;
+        self.logical_bytes += other.logical_bytes;
+        self.compressed_stored += other.compressed_stored;
+        self.compressed_logical += other.compressed_logical;
+        self.min_ratio = match (self.min_ratio, other.min_ratio) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+        self.max_ratio = match (self.max_ratio, other.max_ratio) {
+            (Some(a), Some(b)) => Some(a.max(b)),
+            (a, b) => a.or(b),
+        };
+        for (total, count) in self.ratio_histogram.iter_mut().zip(other.ratio_histogram) {
+            *total += count;
+        }
+        self.update_mean();
+    }
+
+    fn update_mean(&mut self) {
+        self.mean_ratio = (self.compressed_logical > 0)
+            .then(|| self.compressed_stored as f64 / self.compressed_logical as f64);
+    }
 }
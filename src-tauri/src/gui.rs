@@ -0,0 +1,2588 @@
+//! Tauri command layer (enabled by the `gui` feature)
+//!
+//! Thin adapters that run the library operations on blocking threads and
+//! forward their progress callbacks as Tauri events.
+
+use std::sync::atomic::AtomicBool;
+
+use tauri::{Emitter, Manager};
+use tracing::{debug, info, instrument, warn};
+
+use crate::{
+    ad1, benchmark, checkpoint, common, compare, containers, copy, database, ewf, iso, jobs, processed, project,
+    raw, report, selftest, settings, triage, ufed, verification, viewer,
+};
+use crate::logging::{audit, perf};
+
+#[tauri::command]
+fn logical_info(
+    #[allow(non_snake_case)]
+    inputPath: String,
+    #[allow(non_snake_case)]
+    includeTree: bool,
+    /// Maximum AD1 tree entries (default `ad1::DEFAULT_TREE_LIMIT`)
+    #[allow(non_snake_case)]
+    treeLimit: Option<usize>,
+    refresh: Option<bool>,
+    /// Attach a partition/OS triage of E01/raw images
+    #[allow(non_snake_case)]
+    includeTriage: Option<bool>,
+    /// Return what is readable from a damaged AD1/E01/L01, with `parse_warnings`
+    #[allow(non_snake_case)]
+    bestEffort: Option<bool>,
+) -> Result<containers::ContainerInfo, String> {
+    let op = audit::Operation::new("logical_info", vec![inputPath.clone()])
+        .param("includeTree", includeTree)
+        .param("bestEffort", bestEffort);
+    let result = containers::info_cached(&inputPath, includeTree, treeLimit, bestEffort.unwrap_or(false), refresh.unwrap_or(false));
+    op.record(&result, |info| info.container.clone());
+    let mut info = result?;
+    if includeTriage.unwrap_or(false) && (info.e01.is_some() || info.raw.is_some()) {
+        // Triage is best effort - the container info stands on its own
+        info.triage = triage::identify(&inputPath)
+            .map_err(|e| debug!(path = %inputPath, error = %e, "Triage failed"))
+            .ok();
+    }
+    Ok(info)
+}
+
+/// AD1 tree entries per `tree-entries` event
+const TREE_BATCH_SIZE: usize = 1000;
+
+#[derive(Clone, serde::Serialize)]
+struct TreeEntriesBatch {
+    path: String,
+    entries: Vec<ad1::TreeEntry>,
+}
+
+#[derive(Clone, serde::Serialize)]
+struct TreeStreamSummary {
+    path: String,
+    #[serde(flatten)]
+    summary: ad1::TreeSummary,
+}
+
+/// Stream the AD1 item tree as batched `tree-entries` events followed by a
+/// `tree-summary` event, for trees too large to return from `logical_info`
+#[tauri::command]
+async fn logical_info_tree_stream(
+    app: tauri::AppHandle,
+    #[allow(non_snake_case)]
+    inputPath: String,
+) -> Result<ad1::TreeSummary, String> {
+    let op = audit::Operation::new("logical_info_tree_stream", vec![inputPath.clone()]);
+    tauri::async_runtime::spawn_blocking(move || {
+        let result = ad1::tree_stream(&inputPath, TREE_BATCH_SIZE, |entries| {
+            let _ = app.emit("tree-entries", TreeEntriesBatch { path: inputPath.clone(), entries });
+        });
+        op.record(&result, |summary| format!("{} items", summary.item_count));
+        let summary = result?;
+        let _ = app.emit("tree-summary", TreeStreamSummary { path: inputPath.clone(), summary: summary.clone() });
+        Ok(summary)
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))?
+}
+
+/// Fast info - only reads headers, doesn't parse full item trees
+/// Use this for quick container listing/display
+#[tauri::command]
+fn logical_info_fast(
+    #[allow(non_snake_case)]
+    inputPath: String,
+    refresh: Option<bool>,
+) -> Result<containers::ContainerInfo, String> {
+    let op = audit::Operation::new("logical_info_fast", vec![inputPath.clone()]);
+    let result = containers::info_fast_cached(&inputPath, refresh.unwrap_or(false));
+    op.record(&result, |info| info.container.clone());
+    result
+}
+
+/// Fast info for many files at once, emitting `info-ready` with each file's
+/// info or error as soon as it is read
+///
+/// Files are read a few at a time per storage device, each with a timeout
+/// (`options.timeoutSecs`, default 30) so one hung network file can't hold
+/// up the rest. Results are cached like `logical_info_fast`. Cancellable
+/// through `cancel_job`: files not started yet are skipped.
+#[tauri::command]
+async fn logical_info_batch(
+    paths: Vec<String>,
+    options: Option<containers::BatchInfoOptions>,
+    app: tauri::AppHandle,
+) -> Result<containers::BatchInfoSummary, String> {
+    let job_id = uuid::Uuid::new_v4().to_string();
+    let job = jobs::registry().start_cancellable(&job_id, jobs::JobKind::Info, paths.clone())?;
+    let options = options.unwrap_or_default();
+    let op = audit::Operation::new("logical_info_batch", paths.clone())
+        .param("timeoutSecs", options.timeout_secs)
+        .param("refresh", options.refresh);
+    tauri::async_runtime::spawn_blocking(move || {
+        let total = paths.len() as u64;
+        let done = std::sync::atomic::AtomicU64::new(0);
+        let summary = job.span().in_scope(|| containers::info_batch(&paths, &options, job.cancel_flag(), |item| {
+            job.progress(done.fetch_add(1, std::sync::atomic::Ordering::Relaxed) + 1, total);
+            let _ = app.emit("info-ready", JobEvent::new(&job, item));
+        }));
+        let outcome = Ok(format!(
+            "{} read, {} failed ({} timed out), {} skipped",
+            summary.succeeded, summary.failed, summary.timed_out, summary.skipped
+        ));
+        op.param("jobId", job.id()).finish(outcome.clone());
+        job.finish(outcome);
+        Ok(summary)
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))?
+}
+
+/// Why a file is detected as the container kind it is: every format check
+/// with its confidence, the leading bytes and the name's hint
+#[tauri::command]
+async fn identify_container(
+    #[allow(non_snake_case)]
+    inputPath: String,
+) -> Result<containers::DetectionReport, String> {
+    let op = audit::Operation::new("identify_container", vec![inputPath.clone()]);
+    tauri::async_runtime::spawn_blocking(move || {
+        let result = containers::identify(&inputPath);
+        op.record(&result, |report| match report.detected {
+            Some(kind) => format!("{:?}", kind),
+            None => "unrecognized".to_string(),
+        });
+        result
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))?
+}
+
+/// Header-only structure check that runs in seconds: segment headers,
+/// section chains and item/chunk table ends, without hashing any data
+#[tauri::command]
+async fn quick_check(
+    #[allow(non_snake_case)]
+    inputPath: String,
+) -> Result<containers::QuickCheckReport, String> {
+    let op = audit::Operation::new("quick_check", vec![inputPath.clone()]);
+    tauri::async_runtime::spawn_blocking(move || {
+        let result = containers::quick_check(&inputPath);
+        op.record(&result, |report| if report.passed { "passed".to_string() } else { "failed".to_string() });
+        result
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))?
+}
+
+#[tauri::command]
+async fn logical_verify(
+    #[allow(non_snake_case)]
+    inputPath: String,
+    algorithm: String,
+    #[allow(non_snake_case)]
+    failFast: Option<bool>,  // Stop at the first AD1 file that can't be read
+    #[allow(non_snake_case)]
+    bestEffort: Option<bool>,  // Hash unreadable E01/L01 chunks as zeros instead of failing
+    version: Option<u32>,  // Entry JSON shape; the version 1 shape unless 2 or later
+) -> Result<containers::VerifyEntries, String> {
+    let fail_fast = failFast.unwrap_or(false);
+    let best_effort = bestEffort.unwrap_or(false);
+    let op = audit::Operation::new("logical_verify", vec![inputPath.clone()])
+        .param("algorithm", &algorithm)
+        .param("fail_fast", fail_fast)
+        .param("best_effort", best_effort);
+    run_job(jobs::JobKind::Verify, op, |entries: &Vec<_>| summarize_verify_entries(entries), move |_job| {
+        let options = containers::VerifyOptions { fail_fast, best_effort, ..Default::default() };
+        containers::verify_with_options(&inputPath, &algorithm, &options)
+    })
+    .await
+    .map(|entries| containers::VerifyEntries::versioned(entries, version))
+}
+
+/// Verify every file of an AD1 container with ok/nok/corrupt/missing counts.
+/// Files whose data cannot be read are listed with the failing chunk and
+/// the error; with `failFast` the first one aborts instead.
+#[tauri::command]
+async fn ad1_verify_report(
+    #[allow(non_snake_case)]
+    inputPath: String,
+    algorithm: String,
+    #[allow(non_snake_case)]
+    failFast: Option<bool>,
+    version: Option<u32>,
+    app: tauri::AppHandle,
+) -> Result<ad1::VersionedAd1VerifyReport, String> {
+    let fail_fast = failFast.unwrap_or(false);
+    let op = audit::Operation::new("ad1_verify_report", vec![inputPath.clone()])
+        .param("algorithm", &algorithm)
+        .param("fail_fast", fail_fast);
+    run_job(jobs::JobKind::Verify, op, |report: &ad1::Ad1VerifyReport| {
+        let summary = &report.summary;
+        format!(
+            "{} ok, {} mismatched, {} computed, {} corrupt, {} missing",
+            summary.ok, summary.nok, summary.computed, summary.corrupt, summary.missing
+        )
+    }, move |job| {
+        ad1::verify_report(&inputPath, &algorithm, &containers::VerifyOptions { fail_fast, ..Default::default() }, |current, total| {
+            emit_verify_progress(&app, job, &inputPath, current as u64, total as u64);
+        })
+    })
+    .await
+    .map(|report| report.versioned(version))
+}
+
+/// Compare per-file hashes of a container against an exported file manifest
+/// (FTK Imager CSV file listing)
+#[tauri::command]
+async fn verify_against_manifest(
+    #[allow(non_snake_case)]
+    containerPath: String,
+    #[allow(non_snake_case)]
+    manifestPath: String,
+    version: Option<u32>,
+) -> Result<containers::VerifyEntries, String> {
+    let op = audit::Operation::new("verify_against_manifest", vec![containerPath.clone(), manifestPath.clone()]);
+    run_job(jobs::JobKind::Verify, op, |entries: &Vec<_>| summarize_verify_entries(entries), move |_job| {
+        containers::verify_against_manifest(&containerPath, &manifestPath)
+    })
+    .await
+    .map(|entries| containers::VerifyEntries::versioned(entries, version))
+}
+
+/// Hash all AD1 segment files to produce a single hash of the files on disk.
+/// This is different from logical_verify which verifies internal file hashes.
+#[tauri::command]
+async fn ad1_hash_segments(
+    #[allow(non_snake_case)]
+    inputPath: String,
+    algorithm: String,
+    app: tauri::AppHandle,
+) -> Result<String, String> {
+    // Run on blocking thread pool to prevent UI freeze
+    let op = audit::Operation::new("ad1_hash_segments", vec![inputPath.clone()]).param("algorithm", &algorithm);
+    run_job(jobs::JobKind::Verify, op, String::clone, move |job| {
+        ad1::hash_segments_with_progress(&inputPath, &algorithm, |current, total| {
+            emit_verify_progress(&app, job, &inputPath, current, total);
+        })
+    })
+    .await
+}
+
+/// Hash the AD1 logical image data stream - the image hash FTK records in
+/// the companion log (.ad1.txt), so the result is comparable to it.
+#[tauri::command]
+async fn ad1_hash_image_stream(
+    #[allow(non_snake_case)]
+    inputPath: String,
+    algorithm: String,
+    app: tauri::AppHandle,
+) -> Result<String, String> {
+    let op = audit::Operation::new("ad1_hash_image_stream", vec![inputPath.clone()]).param("algorithm", &algorithm);
+    run_job(jobs::JobKind::Verify, op, String::clone, move |job| {
+        ad1::hash_image_stream_with_progress(&inputPath, &algorithm, |current, total| {
+            emit_verify_progress(&app, job, &inputPath, current, total);
+        })
+    })
+    .await
+}
+
+#[tauri::command]
+async fn logical_extract(
+    #[allow(non_snake_case)]
+    inputPath: String,
+    #[allow(non_snake_case)]
+    outputDir: String,
+    options: Option<containers::ExtractOptions>,
+    #[allow(non_snake_case)]
+    dryRun: Option<bool>,  // Only report what would be written (overrides options.dryRun)
+    #[allow(non_snake_case)]
+    lowMemory: Option<bool>,  // Small buffers and read-ahead (always on in 32-bit builds)
+    app: tauri::AppHandle,
+) -> Result<containers::ExtractResult, String> {
+    let summarize = |result: &containers::ExtractResult| match (result.dry_run, &result.stats) {
+        (true, _) => format!("Dry run: {} files, {} bytes to {}", result.file_count, result.total_bytes, result.output_dir),
+        (false, Some(_)) => format!("{} files extracted to {}", result.file_count, result.output_dir),
+        (false, None) => format!("Extracted to {} ({:.1} MB/s)", result.output_dir, result.bytes_per_sec / 1_000_000.0),
+    };
+    let mut options = options.unwrap_or_default();
+    if let Some(dry_run) = dryRun {
+        options.dry_run = dry_run;
+    }
+    let resources = common::JobResources::resolve(None, None, lowMemory);
+    let op = audit::Operation::new("logical_extract", vec![inputPath.clone()])
+        .param("outputDir", &outputDir)
+        .param("options", &options);
+    run_limited_job(jobs::JobKind::Extract, op, resources, summarize, move |job| {
+        containers::extract_with_progress(&inputPath, &outputDir, &options, |progress| {
+            job.progress(progress.bytes_done, progress.bytes_total);
+            let _ = app.emit("extract-progress", JobEvent::new(job, progress));
+        })
+    })
+    .await
+}
+
+/// Mount point, filesystem, size and free space of the volume holding `path`
+/// (an extraction destination need not exist yet), and whether it is a
+/// removable drive or a network share
+#[tauri::command]
+async fn get_volume_info(path: String) -> Result<common::StorageVolume, String> {
+    tauri::async_runtime::spawn_blocking(move || common::volume_info(std::path::Path::new(&path)))
+        .await
+        .map_err(|e| format!("Task failed: {}", e))?
+}
+
+/// Remove `.partial` outputs and progress markers under `dir` left by runs
+/// that crashed, older than `maxAgeHours` (default 24)
+#[tauri::command]
+async fn clean_partial_outputs(
+    dir: String,
+    #[allow(non_snake_case)]
+    maxAgeHours: Option<u64>,
+) -> Result<common::PartialCleanup, String> {
+    let max_age = maxAgeHours
+        .map(|hours| std::time::Duration::from_secs(hours * 3600))
+        .unwrap_or(common::atomic_file::STALE_PARTIAL_AGE);
+    let op = audit::Operation::new("clean_partial_outputs", vec![dir.clone()])
+        .param("maxAgeHours", maxAgeHours);
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        Ok::<_, String>(common::atomic_file::clean_stale_partials(std::path::Path::new(&dir), max_age))
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))?;
+    op.record(&result, |cleanup| format!("{} files removed", cleanup.removed.len()));
+    result
+}
+
+/// Copy a container and all its segments to `destDir`, hashing both sides
+/// and writing a copy manifest; emits `evidence-copy-progress`
+///
+/// Cancellable through `cancel_job`. Running the same copy again resumes it.
+#[tauri::command]
+async fn evidence_copy(
+    #[allow(non_snake_case)]
+    sourcePath: String,
+    #[allow(non_snake_case)]
+    destDir: String,
+    options: Option<copy::CopyOptions>,
+    app: tauri::AppHandle,
+) -> Result<copy::CopyResult, String> {
+    let job_id = uuid::Uuid::new_v4().to_string();
+    let job = jobs::registry().start_cancellable(&job_id, jobs::JobKind::Copy, vec![sourcePath.clone()])?;
+    let options = options.unwrap_or_default();
+    let op = audit::Operation::new("evidence_copy", vec![sourcePath.clone()])
+        .param("destDir", &destDir)
+        .param("options", &options);
+    tauri::async_runtime::spawn_blocking(move || {
+        let result = job.span().in_scope(|| copy::copy_evidence(&sourcePath, &destDir, &options, job.cancel_flag(), |progress| {
+            job.progress(progress.bytes_done, progress.bytes_total);
+            let _ = app.emit("evidence-copy-progress", JobEvent::new(&job, progress));
+        }));
+        let outcome = result.as_ref().map(|r| {
+            let mismatched = r.manifest.files.iter().filter(|f| f.verified == Some(false)).count();
+            format!("{} files copied to {}, {} mismatched", r.manifest.files.len(), r.manifest.dest_dir, mismatched)
+        }).map_err(Clone::clone);
+        op.finish(outcome.clone());
+        job.finish(outcome);
+        result
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))?
+}
+
+/// Compare two images byte for byte through their decoded data (raw, split
+/// raw, E01), listing where they differ; emits `image-compare-progress`
+///
+/// Cancellable through `cancel_job`.
+#[tauri::command]
+async fn compare_images(
+    #[allow(non_snake_case)]
+    pathA: String,
+    #[allow(non_snake_case)]
+    pathB: String,
+    options: Option<compare::CompareOptions>,  // maxRanges / bufferSize
+    app: tauri::AppHandle,
+) -> Result<compare::ImageComparison, String> {
+    let job_id = uuid::Uuid::new_v4().to_string();
+    let job = jobs::registry().start_cancellable(&job_id, jobs::JobKind::Compare, vec![pathA.clone(), pathB.clone()])?;
+    let options = options.unwrap_or_default();
+    let op = audit::Operation::new("compare_images", vec![pathA.clone(), pathB.clone()])
+        .param("options", &options);
+    tauri::async_runtime::spawn_blocking(move || {
+        let result = job.span().in_scope(|| compare::compare_images(&pathA, &pathB, &options, job.cancel_flag(), |progress| {
+            job.progress(progress.bytes_done, progress.bytes_total);
+            let _ = app.emit("image-compare-progress", JobEvent::new(&job, progress));
+        }));
+        let outcome = result.as_ref().map(|r| if r.identical {
+            format!("Identical ({} bytes)", r.compared_bytes)
+        } else {
+            format!("{} bytes differ in {} ranges, sizes {} and {}", r.differing_bytes, r.range_count, r.size_a, r.size_b)
+        }).map_err(Clone::clone);
+        op.finish(outcome.clone());
+        job.finish(outcome);
+        result
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))?
+}
+
+#[tauri::command]
+fn scan_directory(
+    #[allow(non_snake_case)]
+    dirPath: String,
+    #[allow(non_snake_case)]
+    groupBundles: Option<bool>,
+) -> Result<ScanResults, String> {
+    containers::scan_directory(&dirPath).map(|files| ScanResults::new(files, groupBundles))
+}
+
+#[tauri::command]
+fn scan_directory_recursive(
+    #[allow(non_snake_case)]
+    dirPath: String,
+    options: Option<containers::ScanOptions>, // maxDepth / followSymlinks
+    #[allow(non_snake_case)]
+    groupBundles: Option<bool>,
+) -> Result<ScanResults, String> {
+    containers::scan_directory_with_options(&dirPath, true, &options.unwrap_or_default())
+        .map(|files| ScanResults::new(files, groupBundles))
+}
+
+/// Scan results, as evidence bundles when `groupBundles` is set
+#[derive(serde::Serialize)]
+#[serde(untagged)]
+enum ScanResults {
+    Files(Vec<containers::DiscoveredFile>),
+    Bundles(Vec<containers::EvidenceBundle>),
+}
+
+impl ScanResults {
+    fn new(files: Vec<containers::DiscoveredFile>, group_bundles: Option<bool>) -> Self {
+        if group_bundles.unwrap_or(false) {
+            ScanResults::Bundles(containers::group_evidence(&files))
+        } else {
+            ScanResults::Files(files)
+        }
+    }
+}
+
+#[tauri::command]
+#[instrument(skip(window), fields(path = %dirPath, recursive))]
+async fn scan_directory_streaming(
+    window: tauri::Window,
+    #[allow(non_snake_case)]
+    dirPath: String,
+    recursive: bool,
+    options: Option<containers::ScanOptions>,
+    #[allow(non_snake_case)]
+    groupBundles: Option<bool>,
+) -> Result<containers::ScanSummary, String> {
+    use tokio::sync::mpsc;
+    
+    info!("Starting directory scan");
+    let (tx, mut rx) = mpsc::unbounded_channel::<containers::DiscoveredFile>();
+    
+    // Spawn blocking directory scan in background thread
+    let dir_path_clone = dirPath.clone();
+    let scan_handle = tauri::async_runtime::spawn_blocking(move || {
+        containers::scan_directory_parallel(&dir_path_clone, recursive, &options.unwrap_or_default(), |file| {
+            let _ = tx.send(file.clone());
+        })
+    });
+    
+    // Stream results to frontend as they arrive, followed by the evidence
+    // bundle each one joined so the UI can group them live
+    let mut grouper = groupBundles.unwrap_or(false).then(containers::BundleGrouper::default);
+    let mut emitted = 0usize;
+    while let Some(file) = rx.recv().await {
+        debug!(file = %file.filename, "Found file");
+        let _ = window.emit("scan-file-found", &file);
+        if let Some(bundle) = grouper.as_mut().and_then(|grouper| grouper.add(file)) {
+            let _ = window.emit("bundle-updated", &bundle);
+        }
+        emitted += 1;
+    }
+    
+    // Wait for scan to complete and return count
+    let summary = scan_handle.await.map_err(|e| format!("Task failed: {e}"))??;
+    if summary.timed_out {
+        warn!(count = emitted, directories = summary.directories, "Scan stopped at timeout");
+    } else {
+        info!(count = emitted, directories = summary.directories, "Scan complete");
+    }
+    if summary.loops_skipped > 0 || summary.depth_skipped > 0 {
+        warn!(loops = summary.loops_skipped, depth = summary.depth_skipped, "Scan skipped directories");
+    }
+    Ok(summary)
+}
+
+#[derive(Clone, serde::Serialize)]
+struct WatchFileInfo {
+    path: String,
+    info: Option<containers::ContainerInfo>,
+    error: Option<String>,
+}
+
+/// Watch an intake folder and emit `scan-file-found` for each new container
+/// once it has finished copying. With `autoInfo`, also emits `watch-file-info`
+/// with the fast container info of each new file. Returns the watch id.
+#[tauri::command]
+fn watch_directory(
+    window: tauri::Window,
+    #[allow(non_snake_case)]
+    dirPath: String,
+    recursive: bool,
+    #[allow(non_snake_case)]
+    autoInfo: Option<bool>,
+) -> Result<String, String> {
+    let auto_info = autoInfo.unwrap_or(false);
+    containers::watch_directory(&dirPath, recursive, move |file| {
+        debug!(file = %file.filename, "Watched file ready");
+        let _ = window.emit("scan-file-found", &file);
+        if auto_info {
+            let result = containers::info_fast_cached(&file.path, false);
+            let (info, error) = match result {
+                Ok(info) => (Some(info), None),
+                Err(e) => (None, Some(e)),
+            };
+            let _ = window.emit("watch-file-info", WatchFileInfo { path: file.path, info, error });
+        }
+    })
+}
+
+/// Stop a watch started with `watch_directory`
+#[tauri::command]
+fn unwatch_directory(
+    #[allow(non_snake_case)]
+    watchId: String,
+) -> Result<bool, String> {
+    containers::unwatch_directory(&watchId)
+}
+
+// ============================================================================
+// Path and Discovery Utilities - for Project Setup Wizard
+// ============================================================================
+
+/// Formats the backend detects and which operations each supports
+#[tauri::command]
+fn get_supported_formats() -> Vec<containers::SupportedFormat> {
+    containers::supported_formats()
+}
+
+/// Check if a path exists (file or directory)
+#[tauri::command]
+fn path_exists(path: String) -> Result<bool, String> {
+    let path = std::path::PathBuf::from(&path);
+    Ok(path.exists())
+}
+
+/// Check if a path is a directory
+#[tauri::command]
+fn path_is_directory(path: String) -> Result<bool, String> {
+    let path = std::path::PathBuf::from(&path);
+    Ok(path.is_dir())
+}
+
+/// Discover evidence files (E01, AD1, L01, etc.) in a directory
+/// Returns just the file paths for quick discovery
+#[tauri::command]
+fn discover_evidence_files(
+    #[allow(non_snake_case)]
+    dirPath: String,
+    recursive: bool,
+) -> Result<Vec<String>, String> {
+    let path = std::path::PathBuf::from(&dirPath);
+    
+    if !path.exists() {
+        return Err(format!("Path does not exist: {}", path.display()));
+    }
+    
+    if !path.is_dir() {
+        return Err(format!("Path is not a directory: {}", path.display()));
+    }
+    
+    let files = if recursive {
+        containers::scan_directory_recursive(&dirPath)?
+    } else {
+        containers::scan_directory(&dirPath)?
+    };
+    
+    Ok(files.into_iter().map(|f| f.path).collect())
+}
+
+/// Scan for processed databases (AXIOM, Cellebrite, etc.) and return them
+/// Returns ProcessedDbInfo directly (can be converted to ProcessedDatabase in frontend)
+#[tauri::command]
+fn scan_for_processed_databases(
+    #[allow(non_snake_case)]
+    dirPath: String,
+) -> Result<Vec<processed::types::ProcessedDbInfo>, String> {
+    use std::path::PathBuf;
+    
+    let path = PathBuf::from(&dirPath);
+    
+    if !path.exists() {
+        return Err(format!("Path does not exist: {}", path.display()));
+    }
+    
+    // Use the processed database scanner
+    let dbs = processed::detection::scan_for_processed_dbs(&path, true);
+    
+    Ok(dbs)
+}
+
+// EWF Commands - Expert Witness Format implementation (E01/L01/Ex01/Lx01)
+#[tauri::command]
+async fn e01_v3_info(
+    #[allow(non_snake_case)]
+    inputPath: String,
+    #[allow(non_snake_case)]
+    segmentPaths: Option<Vec<String>>,  // Segments in order, when they are not all beside inputPath
+) -> Result<ewf::EwfInfo, String> {
+    // Run on blocking thread pool to prevent UI freeze during file parsing
+    let op = audit::Operation::new("e01_v3_info", vec![inputPath.clone()])
+        .param("segmentPaths", &segmentPaths);
+    tauri::async_runtime::spawn_blocking(move || {
+        let info = || ewf::info(&inputPath);
+        let result = match &segmentPaths {
+            Some(segments) => ewf::with_segment_list(&inputPath, segments, info),
+            None => info(),
+        };
+        op.record(&result, |info| info.format_version.clone());
+        result
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))?
+}
+
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct VerifyProgress {
+    job_id: String,
+    path: String,
+    current: usize,
+    total: usize,
+    percent: f64,
+}
+
+/// Record a job's progress and forward it as a `verify-progress` event
+fn emit_verify_progress(app: &tauri::AppHandle, job: &jobs::JobHandle, path: &str, current: u64, total: u64) {
+    job.progress(current, total);
+    let percent = if total > 0 { (current as f64 / total as f64) * 100.0 } else { 0.0 };
+    let _ = app.emit("verify-progress", VerifyProgress {
+        job_id: job.id().to_string(),
+        path: path.to_string(),
+        current: current as usize,
+        total: total as usize,
+        percent,
+    });
+}
+
+/// Verify an E01 image; refuses sets whose segment headers contradict the
+/// filename order unless `force` is set. With `collectStats` the report
+/// includes how the chunks are stored, with `collectMap` a zero/entropy map.
+#[tauri::command]
+async fn e01_v3_verify(
+    #[allow(non_snake_case)]
+    inputPath: String,
+    algorithm: String,
+    force: Option<bool>,
+    #[allow(non_snake_case)]
+    collectStats: Option<bool>,  // Per-chunk compression statistics
+    #[allow(non_snake_case)]
+    collectMap: Option<bool>,  // Zero/entropy map of the image
+    threads: Option<usize>,  // Worker thread cap (default from the job settings)
+    #[allow(non_snake_case)]
+    ioPriority: Option<common::IoPriority>,
+    #[allow(non_snake_case)]
+    lowMemory: Option<bool>,  // Small buffers, no mmap (always on in 32-bit builds)
+    #[allow(non_snake_case)]
+    segmentPaths: Option<Vec<String>>,  // Segments in order, when they are not all beside inputPath
+    app: tauri::AppHandle,
+) -> Result<ewf::EwfVerifyReport, String> {
+    // Run on blocking thread pool to prevent UI freeze
+    let resources = common::JobResources::resolve(threads, ioPriority, lowMemory);
+    let op = audit::Operation::new("e01_v3_verify", vec![inputPath.clone()])
+        .param("algorithm", &algorithm)
+        .param("force", force)
+        .param("segmentPaths", &segmentPaths);
+    run_limited_job(jobs::JobKind::Verify, op, resources, |report: &ewf::EwfVerifyReport| report.hash.clone(), move |job| {
+        let progress = |current: usize, total: usize| {
+            emit_verify_progress(&app, job, &inputPath, current as u64, total as u64);
+        };
+        let options = containers::VerifyOptions {
+            best_effort: false,
+            force: force.unwrap_or(false),
+            collect_stats: collectStats.unwrap_or(false),
+            collect_map: collectMap.unwrap_or(false),
+            ..Default::default()
+        };
+        let verify = || ewf::verify_report(&inputPath, &algorithm, &options, progress);
+        // Parks at batch boundaries while paused through `pause_job`
+        job.pausable(|| match &segmentPaths {
+            Some(segments) => ewf::with_segment_list(&inputPath, segments, verify),
+            None => verify(),
+        })
+    })
+    .await
+}
+
+/// Best-effort E01 verification: continues past unreadable chunks and returns
+/// the failed chunks (with the sector ranges they cover) alongside the hash.
+/// With `force`, a set whose segment headers contradict the filename order is
+/// hashed anyway and the problems are listed in `segment_issues`.
+#[tauri::command]
+async fn e01_v3_verify_best_effort(
+    #[allow(non_snake_case)]
+    inputPath: String,
+    algorithm: String,
+    force: Option<bool>,
+    #[allow(non_snake_case)]
+    collectStats: Option<bool>,  // Per-chunk compression statistics
+    #[allow(non_snake_case)]
+    collectMap: Option<bool>,  // Zero/entropy map of the image
+    threads: Option<usize>,  // Worker thread cap (default from the job settings)
+    #[allow(non_snake_case)]
+    ioPriority: Option<common::IoPriority>,
+    #[allow(non_snake_case)]
+    lowMemory: Option<bool>,  // Small buffers, no mmap (always on in 32-bit builds)
+    #[allow(non_snake_case)]
+    segmentPaths: Option<Vec<String>>,  // Segments in order, when they are not all beside inputPath
+    app: tauri::AppHandle,
+) -> Result<ewf::EwfVerifyReport, String> {
+    let resources = common::JobResources::resolve(threads, ioPriority, lowMemory);
+    let op = audit::Operation::new("e01_v3_verify_best_effort", vec![inputPath.clone()])
+        .param("algorithm", &algorithm)
+        .param("force", force)
+        .param("segmentPaths", &segmentPaths);
+    run_limited_job(jobs::JobKind::Verify, op, resources, |report: &ewf::EwfVerifyReport| {
+        format!("{} ({} of {} chunks unreadable)", report.hash, report.failed_chunk_count, report.chunk_count)
+    }, move |job| {
+        let progress = |current: usize, total: usize| {
+            emit_verify_progress(&app, job, &inputPath, current as u64, total as u64);
+        };
+        let options = containers::VerifyOptions {
+            best_effort: true,
+            force: force.unwrap_or(false),
+            collect_stats: collectStats.unwrap_or(false),
+            collect_map: collectMap.unwrap_or(false),
+            ..Default::default()
+        };
+        let verify = || ewf::verify_report(&inputPath, &algorithm, &options, progress);
+        // Parks at batch boundaries while paused through `pause_job`
+        job.pausable(|| match &segmentPaths {
+            Some(segments) => ewf::with_segment_list(&inputPath, segments, verify),
+            None => verify(),
+        })
+    })
+    .await
+}
+
+/// Sampled chunk check of an E01: reads a seeded random subset of the chunks
+/// and lists the ones that fail to decode. Not a hash verification - the
+/// report carries no hash and says so.
+#[tauri::command]
+async fn e01_sample_verify(
+    #[allow(non_snake_case)]
+    inputPath: String,
+    sample: ewf::SampleSize,
+    seed: Option<u64>,  // Default: derived from the clock; the report records it
+    app: tauri::AppHandle,
+) -> Result<ewf::EwfSampleReport, String> {
+    let seed = seed.unwrap_or_else(|| {
+        std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).map(|d| d.as_nanos() as u64).unwrap_or_default()
+    });
+    let op = audit::Operation::new("e01_sample_verify", vec![inputPath.clone()])
+        .param("sample", format!("{:?}", sample))
+        .param("seed", seed);
+    run_job(jobs::JobKind::Verify, op, |report: &ewf::EwfSampleReport| {
+        format!("{} of {} sampled chunks failed (not a hash verification)", report.failed_chunk_count, report.chunks_checked)
+    }, move |job| {
+        let progress = |current: usize, total: usize| {
+            emit_verify_progress(&app, job, &inputPath, current as u64, total as u64);
+        };
+        ewf::sample_verify(&inputPath, sample, seed, progress)
+    })
+    .await
+}
+
+/// Read bytes from E01 image at arbitrary offset (for filesystem browsing)
+#[tauri::command]
+async fn e01_read_at(
+    #[allow(non_snake_case)]
+    inputPath: String,
+    offset: u64,
+    length: usize,
+) -> Result<Vec<u8>, String> {
+    let op = audit::Operation::new("e01_read_at", vec![inputPath.clone()])
+        .param("offset", offset)
+        .param("length", length);
+    tauri::async_runtime::spawn_blocking(move || {
+        let result = ewf::EwfHandle::open(&inputPath).and_then(|mut handle| handle.read_at(offset, length));
+        op.record(&result, |data| format!("{} bytes", data.len()));
+        result
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))?
+}
+
+/// Get media info for E01 (size, sector size, chunk size)
+#[tauri::command]
+async fn e01_media_info(
+    #[allow(non_snake_case)]
+    inputPath: String,
+) -> Result<E01MediaInfo, String> {
+    let op = audit::Operation::new("e01_media_info", vec![inputPath.clone()]);
+    tauri::async_runtime::spawn_blocking(move || {
+        let result = ewf::EwfHandle::open(&inputPath).map(|handle| E01MediaInfo {
+            media_size: handle.get_media_size(),
+            chunk_size: handle.get_chunk_size(),
+            sector_size: handle.get_volume_info().bytes_per_sector,
+            sector_count: handle.get_volume_info().sector_count,
+            chunk_count: handle.get_chunk_count() as u64,
+        });
+        op.record(&result, |info| format!("{} bytes", info.media_size));
+        result
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))?
+}
+
+#[derive(Clone, serde::Serialize)]
+struct E01MediaInfo {
+    media_size: u64,
+    chunk_size: u32,
+    sector_size: u32,
+    sector_count: u64,
+    chunk_count: u64,
+}
+
+// RAW Commands - Raw disk image implementation (.dd, .raw, .img, .001)
+#[tauri::command]
+async fn raw_info(
+    #[allow(non_snake_case)]
+    inputPath: String,
+    #[allow(non_snake_case)]
+    fullScan: Option<bool>,  // Decompress gzip images for their exact size
+    #[allow(non_snake_case)]
+    segmentPaths: Option<Vec<String>>,  // Segments in order, when they are not all beside inputPath
+) -> Result<raw::RawInfo, String> {
+    let full_scan = fullScan.unwrap_or(false);
+    let op = audit::Operation::new("raw_info", vec![inputPath.clone()])
+        .param("fullScan", full_scan)
+        .param("segmentPaths", &segmentPaths);
+    tauri::async_runtime::spawn_blocking(move || {
+        let info = || raw::info_with_scan(&inputPath, full_scan);
+        let result = match &segmentPaths {
+            Some(segments) => raw::with_segment_list(&inputPath, segments, info),
+            None => info(),
+        };
+        op.record(&result, |info| format!("{} segments, {} bytes", info.segment_count, info.total_size));
+        result
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))?
+}
+
+#[tauri::command]
+async fn raw_verify(
+    #[allow(non_snake_case)]
+    inputPath: String,
+    algorithm: String,
+    threads: Option<usize>,  // Worker thread cap (default from the job settings)
+    #[allow(non_snake_case)]
+    ioPriority: Option<common::IoPriority>,
+    #[allow(non_snake_case)]
+    lowMemory: Option<bool>,  // Small buffers, no mmap (always on in 32-bit builds)
+    logical: Option<bool>,  // Hash the expanded image of an Android sparse image
+    #[allow(non_snake_case)]
+    segmentPaths: Option<Vec<String>>,  // Segments in order, when they are not all beside inputPath
+    app: tauri::AppHandle,
+) -> Result<String, String> {
+    let resources = common::JobResources::resolve(threads, ioPriority, lowMemory);
+    let logical = logical.unwrap_or(false);
+    let op = audit::Operation::new("raw_verify", vec![inputPath.clone()])
+        .param("algorithm", &algorithm)
+        .param("logical", logical)
+        .param("segmentPaths", &segmentPaths);
+    run_limited_job(jobs::JobKind::Verify, op, resources, String::clone, move |job| {
+        let progress = |current, total| emit_verify_progress(&app, job, &inputPath, current, total);
+        let verify = || if logical {
+            raw::verify_logical_with_progress(&inputPath, &algorithm, progress)
+        } else {
+            raw::verify_with_progress(&inputPath, &algorithm, progress)
+        };
+        // Parks at batch boundaries while paused through `pause_job`
+        job.pausable(|| match &segmentPaths {
+            Some(segments) => raw::with_segment_list(&inputPath, segments, verify),
+            None => verify(),
+        })
+    })
+    .await
+}
+
+/// Verify a raw image, returning the hash with its size and timing; with
+/// `collectMap` the result includes a zero/entropy map of the image
+#[tauri::command]
+async fn raw_verify_report(
+    #[allow(non_snake_case)]
+    inputPath: String,
+    algorithm: String,
+    #[allow(non_snake_case)]
+    collectMap: Option<bool>,  // Zero/entropy map of the image
+    threads: Option<usize>,  // Worker thread cap (default from the job settings)
+    #[allow(non_snake_case)]
+    ioPriority: Option<common::IoPriority>,
+    #[allow(non_snake_case)]
+    lowMemory: Option<bool>,  // Small buffers, no mmap (always on in 32-bit builds)
+    #[allow(non_snake_case)]
+    segmentPaths: Option<Vec<String>>,  // Segments in order, when they are not all beside inputPath
+    app: tauri::AppHandle,
+) -> Result<raw::VerifyResult, String> {
+    let resources = common::JobResources::resolve(threads, ioPriority, lowMemory);
+    let collect_map = collectMap.unwrap_or(false);
+    let op = audit::Operation::new("raw_verify_report", vec![inputPath.clone()])
+        .param("algorithm", &algorithm)
+        .param("collectMap", collect_map)
+        .param("segmentPaths", &segmentPaths);
+    run_limited_job(jobs::JobKind::Verify, op, resources, |result: &raw::VerifyResult| result.hash.clone(), move |job| {
+        let progress = |current, total| emit_verify_progress(&app, job, &inputPath, current, total);
+        let options = containers::VerifyOptions { collect_map, ..Default::default() };
+        let verify = || raw::verify_report(&inputPath, &algorithm, &options, progress);
+        // Parks at batch boundaries while paused through `pause_job`
+        job.pausable(|| match &segmentPaths {
+            Some(segments) => raw::with_segment_list(&inputPath, segments, verify),
+            None => verify(),
+        })
+    })
+    .await
+}
+
+/// Hash a drive in place (`/dev/sdb`, `\\.\PhysicalDrive2`), e.g. the
+/// original behind a write blocker; the system drive only with `force`
+#[tauri::command]
+async fn raw_verify_device(
+    #[allow(non_snake_case)]
+    inputPath: String,
+    algorithm: String,
+    force: Option<bool>,  // Hash the drive the running system is on
+    threads: Option<usize>,  // Worker thread cap (default from the job settings)
+    #[allow(non_snake_case)]
+    ioPriority: Option<common::IoPriority>,
+    #[allow(non_snake_case)]
+    lowMemory: Option<bool>,  // Small buffers (always on in 32-bit builds)
+    app: tauri::AppHandle,
+) -> Result<String, String> {
+    let resources = common::JobResources::resolve(threads, ioPriority, lowMemory);
+    let force = force.unwrap_or(false);
+    let op = audit::Operation::new("raw_verify_device", vec![inputPath.clone()])
+        .param("algorithm", &algorithm)
+        .param("force", force);
+    run_limited_job(jobs::JobKind::Verify, op, resources, String::clone, move |job| {
+        let progress = |current, total| emit_verify_progress(&app, job, &inputPath, current, total);
+        raw::block_device::verify_with_progress(&inputPath, &algorithm, force, progress)
+    })
+    .await
+}
+
+/// Partition/OS triage of a drive in place; the system drive only with `force`
+#[tauri::command]
+async fn triage_device(
+    #[allow(non_snake_case)]
+    inputPath: String,
+    force: Option<bool>,
+) -> Result<triage::TriageSummary, String> {
+    let force = force.unwrap_or(false);
+    let op = audit::Operation::new("triage_device", vec![inputPath.clone()]).param("force", force);
+    tauri::async_runtime::spawn_blocking(move || {
+        let result = triage::identify_device(&inputPath, force);
+        op.record(&result, |summary| format!("{} partitions", summary.partitions.len()));
+        result
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))?
+}
+
+/// Percent-zero and percent-high-entropy of a verification's content map;
+/// windows from `highEntropyBits` (default 7.5) up count as high-entropy
+#[tauri::command]
+fn summarize_content_map(
+    map: common::ContentMap,
+    #[allow(non_snake_case)]
+    highEntropyBits: Option<f32>,
+) -> common::MapSummary {
+    common::summarize_map(&map, highEntropyBits.unwrap_or(common::HIGH_ENTROPY_BITS))
+}
+
+/// Write a verification's content map as JSON
+#[tauri::command]
+fn export_content_map(map: common::ContentMap, path: String) -> Result<(), String> {
+    map.export_json(std::path::Path::new(&path))
+}
+
+/// Hash each MBR/GPT partition of an E01 or raw disk image, optionally
+/// with the whole-disk hash in the same pass; emits `verify-progress`
+#[tauri::command]
+async fn hash_partitions(
+    #[allow(non_snake_case)]
+    inputPath: String,
+    algorithm: String,
+    #[allow(non_snake_case)]
+    includeDisk: Option<bool>,  // Also hash the whole image (reads every byte)
+    threads: Option<usize>,  // Worker thread cap (default from the job settings)
+    #[allow(non_snake_case)]
+    ioPriority: Option<common::IoPriority>,
+    #[allow(non_snake_case)]
+    lowMemory: Option<bool>,  // Small buffers, no mmap (always on in 32-bit builds)
+    app: tauri::AppHandle,
+) -> Result<verification::PartitionHashReport, String> {
+    let resources = common::JobResources::resolve(threads, ioPriority, lowMemory);
+    let include_disk = includeDisk.unwrap_or(false);
+    let op = audit::Operation::new("hash_partitions", vec![inputPath.clone()])
+        .param("algorithm", &algorithm)
+        .param("includeDisk", include_disk);
+    run_limited_job(jobs::JobKind::Verify, op, resources, summarize_partitions, move |job| {
+        verification::hash_partitions(&inputPath, &algorithm, include_disk, |current, total| {
+            emit_verify_progress(&app, job, &inputPath, current, total)
+        })
+    })
+    .await
+}
+
+fn summarize_partitions(report: &verification::PartitionHashReport) -> String {
+    let hashed = report.partitions.iter().filter(|p| p.hash.is_some()).count();
+    let skipped = report.partitions.len() - hashed;
+    if skipped > 0 {
+        format!("{} partitions hashed, {} skipped", hashed, skipped)
+    } else {
+        format!("{} partitions hashed", hashed)
+    }
+}
+
+/// Hash the files inside a UFED ZIP against the per-file hashes in its UFD
+/// (or report.xml) without extracting it; emits `verify-progress`
+#[tauri::command]
+async fn ufed_verify_contents(
+    #[allow(non_snake_case)]
+    zipPath: String,
+    #[allow(non_snake_case)]
+    ufdPath: Option<String>,  // UFD or report.xml (default: the UFD beside the ZIP)
+    password: Option<String>,  // For ZipCrypto/AES-encrypted ZIPs; redacted in the audit log
+    app: tauri::AppHandle,
+) -> Result<ufed::ZipVerifyReport, String> {
+    let ufd_path = match ufdPath {
+        Some(path) => path,
+        None => ufed::find_sibling_ufd(&zipPath)
+            .filter(|path| path.exists())
+            .map(|path| path.to_string_lossy().into_owned())
+            .ok_or_else(|| format!("No UFD found beside {zipPath}"))?,
+    };
+    let summarize = |report: &ufed::ZipVerifyReport| {
+        format!("{} files verified, {} mismatched", report.verified, report.mismatched)
+    };
+    let op = audit::Operation::new("ufed_verify_contents", vec![zipPath.clone(), ufd_path.clone()])
+        .param("password", &password);
+    run_job(jobs::JobKind::Verify, op, summarize, move |job| {
+        ufed::verify_zip_contents(&zipPath, &ufd_path, password.as_deref(), |current, total| {
+            emit_verify_progress(&app, job, &zipPath, current, total);
+        })
+    })
+    .await
+}
+
+/// Stream a (possibly segmented) raw image into one contiguous image, or a
+/// re-split set, hashing source and destination in the same pass
+#[tauri::command]
+async fn raw_consolidate(
+    #[allow(non_snake_case)]
+    inputPath: String,
+    #[allow(non_snake_case)]
+    outputPath: String,
+    options: Option<raw::ConsolidateOptions>,
+    app: tauri::AppHandle,
+) -> Result<raw::ConsolidateResult, String> {
+    let options = options.unwrap_or_default();
+    let summarize = |result: &raw::ConsolidateResult| {
+        let state = if result.verified { "verified" } else { "HASH MISMATCH" };
+        format!("{} file(s), {} bytes, {}", result.output_files.len(), result.bytes_written, state)
+    };
+    let op = audit::Operation::new("raw_consolidate", vec![inputPath.clone()])
+        .param("outputPath", &outputPath)
+        .param("options", &options);
+    run_job(jobs::JobKind::Extract, op, summarize, move |job| {
+        raw::consolidate(&inputPath, &outputPath, &options, |current, total| {
+            emit_verify_progress(&app, job, &inputPath, current, total);
+        })
+    })
+    .await
+}
+
+/// Expand an Android sparse image into a raw image that hashes like a dd
+/// image of the partition
+#[tauri::command]
+async fn raw_sparse_to_raw(
+    #[allow(non_snake_case)]
+    inputPath: String,
+    #[allow(non_snake_case)]
+    outputPath: String,
+    overwrite: Option<bool>,
+    app: tauri::AppHandle,
+) -> Result<common::ExtractedFile, String> {
+    let overwrite = overwrite.unwrap_or(false);
+    let op = audit::Operation::new("raw_sparse_to_raw", vec![inputPath.clone()])
+        .param("outputPath", &outputPath)
+        .param("overwrite", overwrite);
+    run_job(jobs::JobKind::Extract, op, |file: &common::ExtractedFile| format!("{} bytes, MD5 {}", file.size, file.md5), move |job| {
+        raw::sparse::convert_to_raw(&inputPath, &outputPath, overwrite, |current, total| {
+            emit_verify_progress(&app, job, &inputPath, current, total);
+        })
+    })
+    .await
+}
+
+// ISO Commands - ISO 9660 / UDF optical disc images (.iso)
+/// List the root directory, or the whole tree when `recursive` (capped at
+/// `maxEntries`, default `iso::DEFAULT_MAX_LIST_ENTRIES`)
+#[tauri::command]
+async fn iso_list_entries(
+    #[allow(non_snake_case)]
+    inputPath: String,
+    recursive: Option<bool>,
+    #[allow(non_snake_case)]
+    maxEntries: Option<usize>,
+) -> Result<iso::IsoListing, String> {
+    let recursive = recursive.unwrap_or(false);
+    let max_entries = maxEntries.unwrap_or(iso::DEFAULT_MAX_LIST_ENTRIES);
+    let op = audit::Operation::new("iso_list_entries", vec![inputPath.clone()])
+        .param("recursive", recursive)
+        .param("max_entries", max_entries);
+    tauri::async_runtime::spawn_blocking(move || {
+        let result = iso::list_entries(&inputPath, recursive, max_entries);
+        op.record(&result, |listing| {
+            format!("{} entries{}", listing.entries.len(), if listing.truncated { " (truncated)" } else { "" })
+        });
+        result
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))?
+}
+
+// ============================================================================
+// Jobs - every long-running command registers in the global job registry
+// ============================================================================
+
+/// Progress event payload tagged with the job it belongs to
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct JobEvent<T> {
+    job_id: String,
+    #[serde(flatten)]
+    progress: T,
+}
+
+impl<T> JobEvent<T> {
+    fn new(job: &jobs::JobHandle, progress: T) -> Self {
+        Self { job_id: job.id().to_string(), progress }
+    }
+}
+
+/// Run `work` on a blocking thread as a registered job on the inputs of
+/// `op`, recording its outcome (described by `summarize`) in the job
+/// registry and the audit log when it finishes
+async fn run_job<T, S, W>(kind: jobs::JobKind, op: audit::Operation, summarize: S, work: W) -> Result<T, String>
+where
+    T: Send + 'static,
+    S: FnOnce(&T) -> String + Send + 'static,
+    W: FnOnce(&jobs::JobHandle) -> Result<T, String> + Send + 'static,
+{
+    let job = jobs::registry().start(kind, op.inputs().to_vec());
+    tauri::async_runtime::spawn_blocking(move || {
+        let result = job.span().in_scope(|| work(&job));
+        let outcome = result.as_ref().map(summarize).map_err(Clone::clone);
+        op.param("jobId", job.id()).finish(outcome.clone());
+        job.finish(outcome);
+        result
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))?
+}
+
+/// [`run_job`] on a pool with the job's thread cap and I/O priority
+async fn run_limited_job<T, S, W>(
+    kind: jobs::JobKind,
+    op: audit::Operation,
+    resources: common::JobResources,
+    summarize: S,
+    work: W,
+) -> Result<T, String>
+where
+    T: Send + 'static,
+    S: FnOnce(&T) -> String + Send + 'static,
+    W: FnOnce(&jobs::JobHandle) -> Result<T, String> + Send + 'static,
+{
+    let job = jobs::registry().start_with_resources(kind, op.inputs().to_vec(), resources);
+    tauri::async_runtime::spawn_blocking(move || {
+        let result = resources.run(|| job.span().in_scope(|| work(&job)));
+        let outcome = result.as_ref().map(summarize).map_err(Clone::clone);
+        op.param("jobId", job.id()).param("resources", resources).finish(outcome.clone());
+        job.finish(outcome);
+        result
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))?
+}
+
+fn summarize_verify_entries(entries: &[containers::VerifyEntry]) -> String {
+    use containers::VerifyStatus;
+    let count = |status: VerifyStatus| entries.iter().filter(|e| e.status == status).count();
+    let mismatched = count(VerifyStatus::Mismatch);
+    let unreadable = count(VerifyStatus::Corrupt) + count(VerifyStatus::Error);
+    if unreadable > 0 {
+        format!("{} entries, {} mismatched, {} unreadable", entries.len(), mismatched, unreadable)
+    } else {
+        format!("{} entries, {} mismatched", entries.len(), mismatched)
+    }
+}
+
+fn summarize_segments(results: &[verification::SegmentHashResult]) -> String {
+    let mismatched = results.iter().filter(|r| r.verified == Some(false)).count();
+    let missing = results.iter().filter(|r| r.missing).count();
+    if missing > 0 {
+        format!("{} segments hashed, {} mismatched, {} expected but not found", results.len() - missing, mismatched, missing)
+    } else {
+        format!("{} segments hashed, {} mismatched", results.len(), mismatched)
+    }
+}
+
+/// Running jobs followed by recently finished ones (newest first)
+#[tauri::command]
+fn list_jobs() -> Vec<jobs::JobInfo> {
+    jobs::registry().list()
+}
+
+/// App settings (the defaults for anything never changed)
+#[tauri::command]
+fn get_settings() -> settings::Settings {
+    (*settings::current()).clone()
+}
+
+/// Why the settings file could not be used at startup, so the app is
+/// running on the defaults; null when it loaded
+#[tauri::command]
+fn get_settings_load_error() -> Option<settings::SettingsLoadError> {
+    settings::store().and_then(|store| store.load_error().cloned())
+}
+
+/// Change some settings: `partial` holds only the fields to change (nested
+/// objects field by field). Invalid values are refused and nothing changes;
+/// otherwise the settings are saved, applied and sent as `settings-changed`.
+#[tauri::command]
+fn update_settings(partial: serde_json::Value, app: tauri::AppHandle) -> Result<settings::Settings, String> {
+    change_settings(&app, &partial).map(|settings| (*settings).clone())
+}
+
+fn change_settings(app: &tauri::AppHandle, partial: &serde_json::Value) -> Result<std::sync::Arc<settings::Settings>, String> {
+    let store = settings::store().ok_or("Settings are not loaded yet")?;
+    let updated = store.update(partial)?;
+    apply_settings(&updated);
+    let _ = app.emit("settings-changed", &*updated);
+    Ok(updated)
+}
+
+/// Hand the settings kept outside [`settings::current`] to their modules
+fn apply_settings(settings: &settings::Settings) {
+    common::resources::set_defaults(settings.job_resources);
+    common::retry::set_policy(settings.io_retry);
+    if settings.audit_log {
+        if let Err(e) = audit::enable(&audit::default_path()) {
+            warn!("Audit log not enabled: {}", e);
+        }
+    } else {
+        audit::disable();
+    }
+}
+
+/// Database keys of the settings kept before the settings file, moved
+/// into it on first start
+const JOB_RESOURCES_SETTING: &str = "job_resources";
+const IO_RETRY_SETTING: &str = "io_retry_policy";
+const AUDIT_LOG_SETTING: &str = "audit_log_enabled";
+
+/// Settings stored in the database by earlier versions, as a partial update
+fn legacy_settings(db: &database::Database) -> serde_json::Map<String, serde_json::Value> {
+    let mut partial = serde_json::Map::new();
+    let json = |key| db.get_setting(key).ok().flatten().and_then(|json| serde_json::from_str::<serde_json::Value>(&json).ok());
+    if let Some(resources) = json(JOB_RESOURCES_SETTING) {
+        partial.insert("jobResources".to_string(), resources);
+    }
+    if let Some(policy) = json(IO_RETRY_SETTING) {
+        partial.insert("ioRetry".to_string(), policy);
+    }
+    if let Some(enabled) = json(AUDIT_LOG_SETTING) {
+        partial.insert("auditLog".to_string(), enabled);
+    }
+    partial
+}
+
+/// Thread cap, I/O priority and memory mode used by jobs that don't choose
+/// their own
+#[tauri::command]
+fn get_job_resource_defaults() -> common::JobResources {
+    common::resources::defaults()
+}
+
+/// Change (and persist) the thread cap and I/O priority for jobs started
+/// from now on; running jobs keep their settings
+#[tauri::command]
+fn set_job_resource_defaults(defaults: common::JobResources, app: tauri::AppHandle) -> Result<(), String> {
+    // A thread cap of 0 has always meant "no cap" here
+    let defaults = common::JobResources { threads: defaults.threads.filter(|n| *n > 0), ..defaults };
+    change_settings(&app, &serde_json::json!({ "jobResources": defaults })).map(|_| ())
+}
+
+/// How reads failing with a transient network error are retried
+#[tauri::command]
+fn get_io_retry_policy() -> common::RetryPolicy {
+    common::retry::policy()
+}
+
+/// Change (and persist) the read retry policy for files opened from now on
+#[tauri::command]
+fn set_io_retry_policy(policy: common::RetryPolicy, app: tauri::AppHandle) -> Result<(), String> {
+    change_settings(&app, &serde_json::json!({ "ioRetry": policy })).map(|_| ())
+}
+
+/// Whether evidence operations are being written to the audit log
+#[tauri::command]
+fn get_audit_log_enabled() -> bool {
+    audit::is_enabled()
+}
+
+/// Turn the audit log (at its default location) on or off and persist the choice
+#[tauri::command]
+fn set_audit_log_enabled(enabled: bool, app: tauri::AppHandle) -> Result<(), String> {
+    // Report a log that can't be opened instead of only recording the switch
+    if enabled {
+        audit::enable(&audit::default_path())?;
+    }
+    change_settings(&app, &serde_json::json!({ "auditLog": enabled })).map(|_| ())
+}
+
+/// Check the hash chain of an audit log (default: the app's own log)
+#[tauri::command]
+fn audit_verify_chain(path: Option<String>) -> Result<audit::ChainReport, String> {
+    let path = path.map(std::path::PathBuf::from).unwrap_or_else(audit::default_path);
+    audit::verify_chain(&path)
+}
+
+/// Copy the app's audit log entries within `range` to `outputPath` as a
+/// self-contained, independently verifiable chain; returns the entry count
+#[tauri::command]
+fn audit_export(
+    range: audit::AuditRange,
+    #[allow(non_snake_case)]
+    outputPath: String,
+) -> Result<usize, String> {
+    let op = audit::Operation::new("audit_export", vec![audit::default_path().to_string_lossy().into_owned()])
+        .param("range", &range)
+        .param("outputPath", &outputPath);
+    let result = audit::export(&audit::default_path(), &range, std::path::Path::new(&outputPath));
+    op.record(&result, |count| format!("{} entries exported", count));
+    result
+}
+
+/// Check the integrity tag of an export (manifest, audit log export, hash
+/// list, report JSON) against this installation's key
+#[tauri::command]
+fn verify_export_integrity(path: String) -> Result<common::IntegrityCheck, String> {
+    let key = common::IntegrityKey::installation()?;
+    common::integrity::verify_file(std::path::Path::new(&path), &key)
+}
+
+/// Current state of one job, running or recently finished
+#[tauri::command]
+fn get_job(
+    #[allow(non_snake_case)]
+    jobId: String,
+) -> Option<jobs::JobInfo> {
+    jobs::registry().get(&jobId)
+}
+
+/// Phase-by-phase timing of a running or recent job, for a chart of where
+/// its time went
+#[tauri::command]
+fn get_job_perf(
+    #[allow(non_snake_case)]
+    jobId: String,
+) -> Option<perf::JobPerf> {
+    perf::job_perf(&jobId)
+}
+
+/// Pause a running verification at its next buffer boundary, freeing the
+/// disk; errors for jobs that can't be paused. Sends `job-status`.
+#[tauri::command]
+fn pause_job(
+    #[allow(non_snake_case)]
+    jobId: String,
+    app: tauri::AppHandle,
+) -> Result<bool, String> {
+    let paused = jobs::registry().pause(&jobId)?;
+    emit_job_status(&app, &jobId);
+    Ok(paused)
+}
+
+/// Let a paused job carry on from where it stopped. Sends `job-status`.
+#[tauri::command]
+fn resume_job(
+    #[allow(non_snake_case)]
+    jobId: String,
+    app: tauri::AppHandle,
+) -> Result<bool, String> {
+    let resumed = jobs::registry().resume(&jobId)?;
+    emit_job_status(&app, &jobId);
+    Ok(resumed)
+}
+
+/// Send a job's current state as a `job-status` event (progress events
+/// stop while a job is paused)
+fn emit_job_status(app: &tauri::AppHandle, job_id: &str) {
+    if let Some(info) = jobs::registry().get(job_id) {
+        let _ = app.emit("job-status", info);
+    }
+}
+
+/// Request cancellation of a running job; errors for jobs that can't stop early
+#[tauri::command]
+fn cancel_job(
+    #[allow(non_snake_case)]
+    jobId: String,
+) -> Result<bool, String> {
+    jobs::registry().cancel(&jobId)
+}
+
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ResumableVerifyProgress {
+    job_id: String,
+    path: String,
+    current: u64,
+    total: u64,
+    percent: f64,
+}
+
+/// Run a checkpointed verification as a cancellable job on a blocking
+/// thread, emitting `resumable-verify-progress`
+async fn run_checkpointed_job<F>(app: tauri::AppHandle, job_id: String, op: audit::Operation, run: F) -> Result<checkpoint::ResumableVerifyResult, String>
+where
+    F: FnOnce(&checkpoint::CheckpointStore, &AtomicBool, &mut dyn FnMut(u64, u64)) -> Result<checkpoint::ResumableVerifyResult, String>
+        + Send
+        + 'static,
+{
+    let path = op.inputs().first().cloned().unwrap_or_default();
+    let job = jobs::registry().start_cancellable(&job_id, jobs::JobKind::Verify, op.inputs().to_vec())?;
+    tauri::async_runtime::spawn_blocking(move || {
+        let store = settings::current().checkpoint_store();
+        let mut progress = |current: u64, total: u64| {
+            job.progress(current, total);
+            let percent = if total > 0 { (current as f64 / total as f64) * 100.0 } else { 100.0 };
+            let _ = app.emit("resumable-verify-progress", ResumableVerifyProgress {
+                job_id: job_id.clone(),
+                path: path.clone(),
+                current,
+                total,
+                percent,
+            });
+        };
+        let result = job.span().in_scope(|| run(&store, job.cancel_flag(), &mut progress));
+        let outcome = result.as_ref().map(|r| match &r.hash {
+            Some(hash) => hash.clone(),
+            None => format!("Checkpoint kept at {} of {} bytes", r.bytes_hashed, r.total_size),
+        }).map_err(Clone::clone);
+        op.param("jobId", &job_id).finish(outcome.clone());
+        job.finish(outcome);
+        result
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))?
+}
+
+/// Verify a raw image with periodic checkpoints so an interrupted run can be
+/// continued with `resume_verify`
+#[tauri::command]
+async fn raw_verify_resumable(
+    #[allow(non_snake_case)]
+    inputPath: String,
+    algorithm: String,
+    app: tauri::AppHandle,
+) -> Result<checkpoint::ResumableVerifyResult, String> {
+    let job_id = uuid::Uuid::new_v4().to_string();
+    let id = job_id.clone();
+    let op = audit::Operation::new("raw_verify_resumable", vec![inputPath.clone()]).param("algorithm", &algorithm);
+    run_checkpointed_job(app, job_id, op, move |store, cancel, progress| {
+        checkpoint::start_raw_verify(store, &id, &inputPath, &algorithm, checkpoint::DEFAULT_CHECKPOINT_INTERVAL, cancel, progress)
+    })
+    .await
+}
+
+/// Continue an interrupted verification after checking the evidence is unchanged
+#[tauri::command]
+async fn resume_verify(
+    #[allow(non_snake_case)]
+    jobId: String,
+    app: tauri::AppHandle,
+) -> Result<checkpoint::ResumableVerifyResult, String> {
+    let path = settings::current().checkpoint_store().load(&jobId)?.path;
+    let id = jobId.clone();
+    let op = audit::Operation::new("resume_verify", vec![path]);
+    run_checkpointed_job(app, jobId, op, move |store, cancel, progress| {
+        checkpoint::resume_raw_verify(store, &id, checkpoint::DEFAULT_CHECKPOINT_INTERVAL, cancel, progress)
+    })
+    .await
+}
+
+/// Stop a running checkpointed verification, keeping its checkpoint
+#[tauri::command]
+fn cancel_verify(
+    #[allow(non_snake_case)]
+    jobId: String,
+) -> Result<bool, String> {
+    jobs::registry().cancel(&jobId)
+}
+
+/// Interrupted verifications that can be resumed
+#[tauri::command]
+fn list_verify_checkpoints() -> Vec<checkpoint::VerifyCheckpoint> {
+    settings::current().checkpoint_store().list()
+}
+
+/// Delete a checkpoint without resuming it
+#[tauri::command]
+fn discard_verify_checkpoint(
+    #[allow(non_snake_case)]
+    jobId: String,
+) -> Result<bool, String> {
+    settings::current().checkpoint_store().remove(&jobId)
+}
+
+/// Retry a verification that stopped because its evidence device disconnected
+///
+/// The inputs are revalidated first (present, no missing segments). A job
+/// with a checkpoint resumes from it; any other verification restarts, which
+/// needs the algorithm it was started with. Returns the hash (or checkpoint
+/// summary) like the original command.
+#[tauri::command]
+async fn retry_job(
+    #[allow(non_snake_case)]
+    jobId: String,
+    algorithm: Option<String>,
+    app: tauri::AppHandle,
+) -> Result<String, String> {
+    let info = jobs::registry().disconnected(&jobId)?;
+    jobs::revalidate_inputs(&info.inputs)?;
+    if info.kind != jobs::JobKind::Verify {
+        return Err(format!("{:?} jobs cannot be retried - start the operation again", info.kind));
+    }
+
+    if settings::current().checkpoint_store().load(&jobId).is_ok() {
+        let result = resume_verify(jobId, app).await?;
+        return Ok(match result.hash {
+            Some(hash) => hash,
+            None => format!("Checkpoint kept at {} of {} bytes", result.bytes_hashed, result.total_size),
+        });
+    }
+
+    let [path] = info.inputs.as_slice() else {
+        return Err(format!("Job {jobId} has {} inputs; only single-image verifications can be restarted", info.inputs.len()));
+    };
+    let path = path.clone();
+    let algorithm = algorithm.ok_or("Restarting the verification needs its hash algorithm")?;
+    let container_type = containers::discover_file(std::path::Path::new(&path))
+        .map(|file| file.container_type)
+        .unwrap_or_default();
+    let op = audit::Operation::new("retry_job", vec![path.clone()])
+        .param("retriedJobId", &jobId)
+        .param("algorithm", &algorithm);
+    run_job(jobs::JobKind::Verify, op, String::clone, move |job| {
+        verification::verify_auto(&path, &container_type, &algorithm, |current, total| {
+            emit_verify_progress(&app, job, &path, current, total);
+        })
+    })
+    .await
+}
+
+/// Expected hashes passed inline, or the `algorithm` hashes of a hash list
+/// file (so big lists don't round-trip through the frontend)
+fn expected_segment_hashes(
+    inline: Option<Vec<containers::SegmentHash>>,
+    list_path: Option<String>,
+    algorithm: &str,
+) -> Result<Vec<containers::SegmentHash>, String> {
+    match list_path {
+        Some(path) => Ok(containers::import_expected_hashes(std::path::Path::new(&path), None, None)?.for_algorithm(algorithm)),
+        None => Ok(inline.unwrap_or_default()),
+    }
+}
+
+/// Read an expected-hash list (hash per line, CSV or filename<TAB>hash);
+/// the format is detected unless given
+#[tauri::command]
+fn import_expected_hashes(
+    path: String,
+    format: Option<containers::HashListFormat>,
+    columns: Option<containers::CsvColumns>,
+) -> Result<containers::ExpectedHashList, String> {
+    containers::import_expected_hashes(std::path::Path::new(&path), format, columns.as_ref())
+}
+
+/// Write the computed hashes of a segment verification as an expected-hash
+/// list for another agency; returns the number of hashes written
+#[tauri::command]
+fn export_expected_hashes(
+    results: Vec<verification::SegmentHashResult>,
+    path: String,
+    format: containers::HashListFormat,
+) -> Result<usize, String> {
+    let hashes: Vec<_> = results.iter().filter_map(verification::SegmentHashResult::to_segment_hash).collect();
+    containers::export_expected_hashes(&hashes, std::path::Path::new(&path), format)
+}
+
+/// Verify individual segments of a raw image, comparing against stored hashes
+#[tauri::command]
+async fn raw_verify_segments(
+    #[allow(non_snake_case)]
+    inputPath: String,
+    algorithm: String,
+    #[allow(non_snake_case)]
+    expectedHashes: Option<Vec<containers::SegmentHash>>,  // Optional: stored hashes from companion log
+    #[allow(non_snake_case)]
+    expectedHashesPath: Option<String>,  // Alternative: an imported hash list file
+    threads: Option<usize>,  // Worker thread cap (default from the job settings)
+    #[allow(non_snake_case)]
+    ioPriority: Option<common::IoPriority>,
+    #[allow(non_snake_case)]
+    lowMemory: Option<bool>,  // Small buffers, no mmap (always on in 32-bit builds)
+    app: tauri::AppHandle,
+) -> Result<Vec<verification::SegmentHashResult>, String> {
+    let resources = common::JobResources::resolve(threads, ioPriority, lowMemory);
+    let op = audit::Operation::new("raw_verify_segments", vec![inputPath.clone()])
+        .param("algorithm", &algorithm)
+        .param("expectedHashesPath", &expectedHashesPath);
+    run_limited_job(jobs::JobKind::SegmentVerify, op, resources, |results: &Vec<_>| summarize_segments(results), move |job| {
+        let expected = expected_segment_hashes(expectedHashes, expectedHashesPath, &algorithm)?;
+        verification::verify_raw_segments(&inputPath, &algorithm, &expected, |progress| {
+            job.progress(progress.bytes_done, progress.bytes_total);
+            let _ = app.emit("segment-verify-progress", JobEvent::new(job, progress));
+        })
+    })
+    .await
+}
+
+/// Verify individual E01 segment files by hashing each .E01, .E02, etc. file
+#[tauri::command]
+async fn e01_verify_segments(
+    #[allow(non_snake_case)]
+    inputPath: String,
+    algorithm: String,
+    #[allow(non_snake_case)]
+    expectedHashes: Option<Vec<containers::SegmentHash>>,
+    #[allow(non_snake_case)]
+    expectedHashesPath: Option<String>,
+    threads: Option<usize>,  // Worker thread cap (default from the job settings)
+    #[allow(non_snake_case)]
+    ioPriority: Option<common::IoPriority>,
+    #[allow(non_snake_case)]
+    lowMemory: Option<bool>,  // Small buffers, no mmap (always on in 32-bit builds)
+    app: tauri::AppHandle,
+) -> Result<Vec<verification::SegmentHashResult>, String> {
+    let resources = common::JobResources::resolve(threads, ioPriority, lowMemory);
+    let op = audit::Operation::new("e01_verify_segments", vec![inputPath.clone()])
+        .param("algorithm", &algorithm)
+        .param("expectedHashesPath", &expectedHashesPath);
+    run_limited_job(jobs::JobKind::SegmentVerify, op, resources, |results: &Vec<_>| summarize_segments(results), move |job| {
+        let expected = expected_segment_hashes(expectedHashes, expectedHashesPath, &algorithm)?;
+        verification::verify_e01_segments(&inputPath, &algorithm, &expected, |progress| {
+            job.progress(progress.bytes_done, progress.bytes_total);
+            let _ = app.emit("segment-verify-progress", JobEvent::new(job, progress));
+        })
+    })
+    .await
+}
+
+/// Hash an E01's media and each of its segment files in one read of the
+/// set, comparing them against the embedded and companion-log hashes.
+/// Progress comes as `verify-progress` events in bytes.
+#[tauri::command]
+async fn e01_full_verify(
+    #[allow(non_snake_case)]
+    inputPath: String,
+    algorithms: Vec<String>,
+    threads: Option<usize>,  // Worker thread cap (default from the job settings)
+    #[allow(non_snake_case)]
+    ioPriority: Option<common::IoPriority>,
+    #[allow(non_snake_case)]
+    lowMemory: Option<bool>,  // Small buffers, no mmap (always on in 32-bit builds)
+    app: tauri::AppHandle,
+) -> Result<verification::E01FullVerifyReport, String> {
+    let resources = common::JobResources::resolve(threads, ioPriority, lowMemory);
+    let op = audit::Operation::new("e01_full_verify", vec![inputPath.clone()])
+        .param("algorithms", &algorithms);
+    run_limited_job(jobs::JobKind::Verify, op, resources, |report: &verification::E01FullVerifyReport| {
+        let image: Vec<String> = report.image_hashes.iter().map(|check| format!("{} {}", check.algorithm, check.computed_hash)).collect();
+        let mismatched = report.segments.iter().filter(|segment| segment.verified == Some(false)).count();
+        format!("{} ({} segment hashes, {} mismatched)", image.join(", "), report.segments.len(), mismatched)
+    }, move |job| {
+        verification::verify_e01_full(&inputPath, &algorithms, |current, total| {
+            emit_verify_progress(&app, job, &inputPath, current, total);
+        })
+    })
+    .await
+}
+
+/// Hash multiple files in parallel, emitting `batch-progress` events
+#[tauri::command]
+#[instrument(skip(files, app), fields(num_files = files.len(), algorithm = %algorithm))]
+async fn batch_hash(
+    files: Vec<verification::BatchFileInput>,
+    algorithm: String,
+    threads: Option<usize>,  // Worker thread cap (default from the job settings)
+    #[allow(non_snake_case)]
+    ioPriority: Option<common::IoPriority>,
+    #[allow(non_snake_case)]
+    lowMemory: Option<bool>,  // Small buffers, no mmap (always on in 32-bit builds)
+    app: tauri::AppHandle,
+) -> Result<Vec<verification::BatchHashResult>, String> {
+    let inputs = files.iter().map(|f| f.path.clone()).collect();
+    let summarize = |results: &Vec<verification::BatchHashResult>| {
+        let failed = results.iter().filter(|r| r.error.is_some()).count();
+        format!("{} files hashed, {} failed", results.len() - failed, failed)
+    };
+    let resources = common::JobResources::resolve(threads, ioPriority, lowMemory);
+    let op = audit::Operation::new("batch_hash", inputs).param("algorithm", &algorithm);
+    run_limited_job(jobs::JobKind::BatchHash, op, resources, summarize, move |job| {
+        Ok(verification::batch_hash(&files, &algorithm, |progress| {
+            job.progress(progress.files_completed as u64, progress.files_total as u64);
+            let _ = app.emit("batch-progress", JobEvent::new(job, progress));
+        }))
+    })
+    .await
+}
+
+/// Hash files and directories (recursively) and write a hashdeep audit file
+/// with the requested md5/sha1/sha256 columns
+#[tauri::command]
+async fn export_hashdeep(
+    paths: Vec<String>,
+    algorithms: Vec<String>,
+    #[allow(non_snake_case)]
+    outputPath: String,
+    threads: Option<usize>,  // Worker thread cap (default from the job settings)
+    #[allow(non_snake_case)]
+    ioPriority: Option<common::IoPriority>,
+    #[allow(non_snake_case)]
+    lowMemory: Option<bool>,  // Small buffers, no mmap (always on in 32-bit builds)
+) -> Result<common::HashdeepExport, String> {
+    let resources = common::JobResources::resolve(threads, ioPriority, lowMemory);
+    let op = audit::Operation::new("export_hashdeep", paths.clone())
+        .param("algorithms", &algorithms)
+        .param("outputPath", &outputPath);
+    let summarize = |export: &common::HashdeepExport| {
+        format!("{} files written, {} skipped", export.files_written, export.skipped.len())
+    };
+    run_limited_job(jobs::JobKind::BatchHash, op, resources, summarize, move |job| {
+        common::hashdeep::export(&paths, &algorithms, std::path::Path::new(&outputPath), |done, total| {
+            job.progress(done as u64, total as u64);
+        })
+    })
+    .await
+}
+
+/// Audit a directory against a hashdeep file: matched, moved, changed, new
+/// and missing files
+#[tauri::command]
+async fn audit_hashdeep(
+    #[allow(non_snake_case)]
+    dirPath: String,
+    #[allow(non_snake_case)]
+    auditFile: String,
+) -> Result<common::HashdeepAudit, String> {
+    let op = audit::Operation::new("audit_hashdeep", vec![dirPath.clone()]).param("auditFile", &auditFile);
+    let summarize = |report: &common::HashdeepAudit| {
+        format!("{} matched, {} moved, {} changed, {} new, {} missing",
+            report.matched.len(), report.moved.len(), report.changed.len(), report.new.len(), report.missing.len())
+    };
+    run_job(jobs::JobKind::BatchHash, op, summarize, move |job| {
+        common::hashdeep::audit(std::path::Path::new(&dirPath), std::path::Path::new(&auditFile), |done, total| {
+            job.progress(done as u64, total as u64);
+        })
+    })
+    .await
+}
+
+/// Group copies of the same evidence set (same size and whole-set hash),
+/// emitting `batch-progress` events for the sets that need hashing
+#[tauri::command]
+#[instrument(skip(files, app), fields(num_files = files.len(), algorithm = %algorithm))]
+async fn find_duplicates(
+    files: Vec<verification::BatchFileInput>,
+    algorithm: String,
+    app: tauri::AppHandle,
+) -> Result<Vec<verification::DuplicateGroup>, String> {
+    let inputs = files.iter().map(|f| f.path.clone()).collect();
+    let summarize = |groups: &Vec<verification::DuplicateGroup>| format!("{} duplicate groups", groups.len());
+    let op = audit::Operation::new("find_duplicates", inputs).param("algorithm", &algorithm);
+    run_job(jobs::JobKind::BatchHash, op, summarize, move |job| {
+        Ok(verification::find_duplicates(&files, &algorithm, |progress| {
+            job.progress(progress.files_completed as u64, progress.files_total as u64);
+            let _ = app.emit("batch-progress", JobEvent::new(job, progress));
+        }))
+    })
+    .await
+}
+
+/// Queue low-priority verification of scanned files, one job per file and
+/// one running job per storage device, behind any user-started job
+///
+/// Each outcome is stored in the case database and emitted as a
+/// `file-verification-status` event. Returns the queued job ids.
+#[tauri::command]
+#[instrument(skip(files, app), fields(num_files = files.len(), algorithm = %algorithm))]
+fn background_verify(
+    files: Vec<verification::BatchFileInput>,
+    algorithm: String,
+    app: tauri::AppHandle,
+) -> Vec<String> {
+    files.into_iter().map(|file| {
+        let lane = benchmark::device_id(std::path::Path::new(&file.path)).unwrap_or_default();
+        let algorithm = algorithm.clone();
+        let app = app.clone();
+        let inputs = vec![file.path.clone()];
+        jobs::registry().queue_background(&lane, jobs::JobKind::Verify, inputs, Box::new(move |job| {
+            // Logged when the job runs, not when it is queued
+            let op = audit::Operation::new("background_verify", vec![file.path.clone()])
+                .param("algorithm", &algorithm)
+                .param("container_type", &file.container_type);
+            let record = verification::verify_status(&file.path, &file.container_type, &algorithm, |current, total| {
+                job.progress(current, total);
+                job.wait_for_user_jobs();
+            });
+            if let Err(e) = database::get_db().upsert_verification_status(&record) {
+                warn!(path = %file.path, error = %e, "Failed to store verification status");
+            }
+            let _ = app.emit("file-verification-status", &record);
+            let outcome = match &record.error {
+                Some(error) => Err(error.clone()),
+                None => Ok(record.status.clone()),
+            };
+            op.finish(outcome.clone());
+            outcome
+        }))
+    }).collect()
+}
+
+/// Stored background verification outcomes of `paths`; files changed
+/// since they were verified are left out
+#[tauri::command]
+fn get_verification_status(paths: Vec<String>) -> Result<Vec<database::VerificationStatusRecord>, String> {
+    let db = database::get_db();
+    let mut statuses = Vec::new();
+    for path in paths {
+        if let Some(record) = db.get_verification_status(&path).map_err(|e| e.to_string())? {
+            if verification::status_is_current(&record) {
+                statuses.push(record);
+            }
+        }
+    }
+    Ok(statuses)
+}
+
+// System Stats Command
+#[derive(Clone, serde::Serialize)]
+struct SystemStats {
+    cpu_usage: f32,
+    memory_used: u64,
+    memory_total: u64,
+    memory_percent: f32,
+    // App-specific stats
+    app_cpu_usage: f32,
+    app_memory: u64,
+    app_threads: usize,
+    cpu_cores: usize,
+}
+
+use std::sync::{OnceLock, Mutex as StdMutex};
+
+static SYSTEM: OnceLock<StdMutex<sysinfo::System>> = OnceLock::new();
+
+fn get_system() -> &'static StdMutex<sysinfo::System> {
+    SYSTEM.get_or_init(|| {
+        let mut sys = sysinfo::System::new_all();
+        sys.refresh_cpu_usage();
+        sys.refresh_memory();
+        sys.refresh_processes(sysinfo::ProcessesToUpdate::All, true);
+        StdMutex::new(sys)
+    })
+}
+
+fn collect_system_stats() -> SystemStats {
+    let Ok(mut sys) = get_system().lock() else {
+        // Return default stats if lock is poisoned
+        tracing::warn!("System stats lock poisoned, returning defaults");
+        return SystemStats {
+            cpu_usage: 0.0,
+            memory_used: 0,
+            memory_total: 0,
+            memory_percent: 0.0,
+            app_cpu_usage: 0.0,
+            app_memory: 0,
+            app_threads: 0,
+            cpu_cores: 0,
+        };
+    };
+    sys.refresh_cpu_usage();
+    sys.refresh_memory();
+    sys.refresh_processes(sysinfo::ProcessesToUpdate::All, true);
+    
+    let cpu_usage = sys.global_cpu_usage();
+    let memory_used = sys.used_memory();
+    let memory_total = sys.total_memory();
+    let memory_percent = if memory_total > 0 {
+        (memory_used as f32 / memory_total as f32) * 100.0
+    } else {
+        0.0
+    };
+    
+    // Get app-specific stats
+    let pid = sysinfo::Pid::from_u32(std::process::id());
+    let (app_cpu_usage, app_memory, app_threads) = if let Some(process) = sys.process(pid) {
+        // process.tasks() is not supported on macOS, use rayon thread count as worker threads
+        let threads = process.tasks()
+            .map(|t| t.len())
+            .unwrap_or_else(|| rayon::current_num_threads());
+        (process.cpu_usage(), process.memory(), threads)
+    } else {
+        (0.0, 0, rayon::current_num_threads())
+    };
+    
+    let cpu_cores = sys.cpus().len();
+    
+    SystemStats {
+        cpu_usage,
+        memory_used,
+        memory_total,
+        memory_percent,
+        app_cpu_usage,
+        app_memory,
+        app_threads,
+        cpu_cores,
+    }
+}
+
+#[tauri::command]
+fn get_system_stats() -> SystemStats {
+    collect_system_stats()
+}
+
+// =============================================================================
+// Data Viewing & Analysis Commands
+// =============================================================================
+
+/// Read raw bytes from a file at specified offset
+/// 
+/// Returns up to `length` bytes starting at `offset`.
+/// Useful for previewing file contents without full extraction.
+#[tauri::command]
+fn read_file_bytes(
+    path: String,
+    offset: u64,
+    length: usize,
+) -> Result<Vec<u8>, String> {
+    use std::fs::File;
+    use std::io::{Read, Seek, SeekFrom};
+    
+    let mut file = File::open(&path)
+        .map_err(|e| format!("Failed to open file: {}", e))?;
+    
+    let file_size = file.metadata()
+        .map_err(|e| format!("Failed to get file size: {}", e))?
+        .len();
+    
+    if offset >= file_size {
+        return Ok(Vec::new());
+    }
+    
+    file.seek(SeekFrom::Start(offset))
+        .map_err(|e| format!("Failed to seek: {}", e))?;
+    
+    let read_len = length.min((file_size - offset) as usize);
+    let mut buffer = vec![0u8; read_len];
+    
+    file.read_exact(&mut buffer)
+        .map_err(|e| format!("Failed to read: {}", e))?;
+    
+    Ok(buffer)
+}
+
+/// Get hex dump of file contents
+///
+/// Reads bytes from file and returns formatted hex dump string.
+#[tauri::command]
+fn hex_dump(
+    path: String,
+    offset: u64,
+    length: usize,
+    #[allow(non_snake_case)]
+    showAscii: Option<bool>,
+    #[allow(non_snake_case)]
+    bytesPerLine: Option<usize>,
+) -> Result<common::hex::HexDumpResult, String> {
+    let data = read_file_bytes(path, offset, length)?;
+    
+    let options = common::hex::HexDumpOptions {
+        show_ascii: showAscii.unwrap_or(true),
+        bytes_per_line: bytesPerLine.unwrap_or(16),
+        show_offset: true,
+        uppercase: true,
+        group_size: 1,
+        start_offset: offset,
+    };
+    
+    Ok(common::hex::create_hex_dump(&data, &options))
+}
+
+/// Detect file type from magic signature
+///
+/// Reads file header and identifies type based on magic bytes.
+/// Returns None if file type cannot be determined.
+#[tauri::command]
+fn detect_file_type(path: String) -> Result<Option<common::magic::FileType>, String> {
+    // Read first 64 bytes for magic detection
+    let header = read_file_bytes(path, 0, 64)?;
+    Ok(common::magic::detect_file_type(&header))
+}
+
+/// Analyze entropy of a file or portion of a file
+///
+/// Returns entropy statistics useful for detecting encryption.
+#[tauri::command]
+fn analyze_file_entropy(
+    path: String,
+    offset: Option<u64>,
+    length: Option<usize>,
+) -> Result<common::entropy::EntropyResult, String> {
+    let start = offset.unwrap_or(0);
+    // Default to 1MB sample for entropy analysis
+    let len = length.unwrap_or(1024 * 1024);
+    
+    let data = read_file_bytes(path, start, len)?;
+    
+    Ok(common::entropy::EntropyResult::new(&data)
+        .with_offset(start))
+}
+
+/// Analyze entropy across file blocks
+///
+/// Useful for finding encrypted regions in disk images.
+#[tauri::command]
+fn analyze_entropy_blocks(
+    path: String,
+    #[allow(non_snake_case)]
+    blockSize: Option<usize>,
+    #[allow(non_snake_case)]
+    maxBlocks: Option<usize>,
+) -> Result<common::entropy::BlockEntropyAnalysis, String> {
+    use std::fs::File;
+    use std::io::Read;
+    
+    let block_size = blockSize.unwrap_or(4096);
+    let max_blocks = maxBlocks.unwrap_or(1000);
+    
+    let mut file = File::open(&path)
+        .map_err(|e| format!("Failed to open file: {}", e))?;
+    
+    let file_size = file.metadata()
+        .map_err(|e| format!("Failed to get file size: {}", e))?
+        .len();
+    
+    // Limit data read to prevent memory issues
+    let max_bytes = block_size * max_blocks;
+    let read_len = (file_size as usize).min(max_bytes);
+    
+    let mut data = vec![0u8; read_len];
+    file.read_exact(&mut data)
+        .map_err(|e| format!("Failed to read: {}", e))?;
+    
+    Ok(common::entropy::analyze_blocks(&data, block_size))
+}
+
+/// Compare two hash values
+///
+/// Returns detailed comparison result including case-sensitivity.
+#[tauri::command]
+fn compare_hashes(
+    hash1: String,
+    hash2: String,
+) -> common::hash::HashMatchResult {
+    common::hash::compare_hashes(&hash1, &hash2)
+}
+
+/// Verify a file's hash against expected value
+#[tauri::command]
+fn verify_file_hash(
+    path: String,
+    expected: String,
+    algorithm: String,
+) -> Result<common::hash::HashVerificationResult, String> {
+    let algo = common::hash::HashAlgorithm::from_str(&algorithm)?;
+    common::hash::verify_file_hash(std::path::Path::new(&path), &expected, algo)
+}
+
+/// Run the known-answer self-test of every hash algorithm and E01 decoding
+///
+/// Check `passed` (or `failed_checks`) on the result - a failed check is a
+/// normal return value, not an error.
+#[tauri::command]
+async fn self_test() -> Result<selftest::SelfTestReport, String> {
+    tauri::async_runtime::spawn_blocking(selftest::self_test)
+        .await
+        .map_err(|e| format!("Task failed: {}", e))
+}
+
+/// Hash an arbitrary regular file (PDF, keyword list, ...) with one or more algorithms
+#[tauri::command]
+async fn hash_file(
+    path: String,
+    algorithms: Vec<String>,
+    expected: Option<String>,
+    app: tauri::AppHandle,
+) -> Result<common::FileHashResult, String> {
+    let summarize = |result: &common::FileHashResult| {
+        result.digests.iter().map(|d| format!("{}: {}", d.algorithm, d.hash)).collect::<Vec<_>>().join(", ")
+    };
+    let op = audit::Operation::new("hash_file", vec![path.clone()])
+        .param("algorithms", &algorithms)
+        .param("expected", &expected);
+    run_job(jobs::JobKind::Hash, op, summarize, move |job| {
+        common::hash_regular_file(std::path::Path::new(&path), &algorithms, expected.as_deref(), |current, total| {
+            emit_verify_progress(&app, job, &path, current, total);
+        })
+    })
+    .await
+}
+
+/// Measure hashing throughput for each algorithm and buffer size on a file
+/// (read-only) or a temporary sample, and save the result for the device so
+/// `verify_auto` picks the fastest buffer size there
+#[tauri::command]
+async fn benchmark_hash(
+    #[allow(non_snake_case)]
+    pathOrSize: benchmark::BenchmarkTarget,
+    algorithms: Vec<String>,
+    /// Defaults to `benchmark::DEFAULT_BUFFER_SIZES`
+    #[allow(non_snake_case)]
+    bufferSizes: Option<Vec<usize>>,
+) -> Result<benchmark::BenchmarkResult, String> {
+    let inputs = match &pathOrSize {
+        benchmark::BenchmarkTarget::File { path, .. } => vec![path.clone()],
+        benchmark::BenchmarkTarget::TempFile { .. } => Vec::new(),
+    };
+    let summarize = |result: &benchmark::BenchmarkResult| {
+        result.best.iter()
+            .map(|m| format!("{}: {:.0} MB/s ({} KB)", m.algorithm, m.mb_per_sec, m.buffer_size / 1024))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let op = audit::Operation::new("benchmark_hash", inputs).param("algorithms", &algorithms);
+    run_job(jobs::JobKind::Benchmark, op, summarize, move |job| {
+        let result = benchmark::benchmark_hash(&pathOrSize, &algorithms, &bufferSizes.unwrap_or_default(), |done, total| {
+            job.progress(done, total);
+        })?;
+        benchmark::BenchmarkStore::default_location().save(&result)?;
+        Ok(result)
+    })
+    .await
+}
+
+// ============================================================================
+// Database Commands
+// ============================================================================
+
+/// Get or create a session for a directory path
+#[tauri::command]
+fn db_get_or_create_session(root_path: String) -> Result<database::Session, String> {
+    let db = database::get_db();
+    db.get_or_create_session(&root_path).map_err(|e| e.to_string())
+}
+
+/// Get recent sessions
+#[tauri::command]
+fn db_get_recent_sessions(limit: i32) -> Result<Vec<database::Session>, String> {
+    let db = database::get_db();
+    db.get_recent_sessions(limit).map_err(|e| e.to_string())
+}
+
+/// Get the last opened session
+#[tauri::command]
+fn db_get_last_session() -> Result<Option<database::Session>, String> {
+    let db = database::get_db();
+    db.get_last_session().map_err(|e| e.to_string())
+}
+
+/// Save or update a file record
+#[tauri::command]
+fn db_upsert_file(file: database::FileRecord) -> Result<(), String> {
+    let db = database::get_db();
+    db.upsert_file(&file).map_err(|e| e.to_string())
+}
+
+/// Get all files for a session
+#[tauri::command]
+fn db_get_files_for_session(session_id: String) -> Result<Vec<database::FileRecord>, String> {
+    let db = database::get_db();
+    db.get_files_for_session(&session_id).map_err(|e| e.to_string())
+}
+
+/// Get a file by path
+#[tauri::command]
+fn db_get_file_by_path(session_id: String, path: String) -> Result<Option<database::FileRecord>, String> {
+    let db = database::get_db();
+    db.get_file_by_path(&session_id, &path).map_err(|e| e.to_string())
+}
+
+/// Insert a hash record
+#[tauri::command]
+fn db_insert_hash(hash: database::HashRecord) -> Result<(), String> {
+    let db = database::get_db();
+    db.insert_hash(&hash).map_err(|e| e.to_string())
+}
+
+/// Get all hashes for a file
+#[tauri::command]
+fn db_get_hashes_for_file(file_id: String) -> Result<Vec<database::HashRecord>, String> {
+    let db = database::get_db();
+    db.get_hashes_for_file(&file_id).map_err(|e| e.to_string())
+}
+
+/// Get the latest hash for a file/algorithm/segment combo
+#[tauri::command]
+fn db_get_latest_hash(
+    file_id: String,
+    algorithm: String,
+    segment_index: Option<i32>,
+) -> Result<Option<database::HashRecord>, String> {
+    let db = database::get_db();
+    db.get_latest_hash(&file_id, &algorithm, segment_index).map_err(|e| e.to_string())
+}
+
+/// Insert a verification record
+#[tauri::command]
+fn db_insert_verification(verification: database::VerificationRecord) -> Result<(), String> {
+    let db = database::get_db();
+    db.insert_verification(&verification).map_err(|e| e.to_string())
+}
+
+/// Get verifications for a file
+#[tauri::command]
+fn db_get_verifications_for_file(file_id: String) -> Result<Vec<database::VerificationRecord>, String> {
+    let db = database::get_db();
+    db.get_verifications_for_file(&file_id).map_err(|e| e.to_string())
+}
+
+/// Save open tabs for a session
+#[tauri::command]
+fn db_save_open_tabs(session_id: String, tabs: Vec<database::OpenTabRecord>) -> Result<(), String> {
+    let db = database::get_db();
+    db.save_open_tabs(&session_id, &tabs).map_err(|e| e.to_string())
+}
+
+/// Get open tabs for a session
+#[tauri::command]
+fn db_get_open_tabs(session_id: String) -> Result<Vec<database::OpenTabRecord>, String> {
+    let db = database::get_db();
+    db.get_open_tabs(&session_id).map_err(|e| e.to_string())
+}
+
+/// Set a setting value
+#[tauri::command]
+fn db_set_setting(key: String, value: String) -> Result<(), String> {
+    let db = database::get_db();
+    db.set_setting(&key, &value).map_err(|e| e.to_string())
+}
+
+/// Get a setting value
+#[tauri::command]
+fn db_get_setting(key: String) -> Result<Option<String>, String> {
+    let db = database::get_db();
+    db.get_setting(&key).map_err(|e| e.to_string())
+}
+
+// ============================================================================
+// Project File Commands
+// ============================================================================
+
+/// Get the default project file path for a root directory
+#[tauri::command]
+fn project_get_default_path(root_path: String) -> String {
+    project::get_default_project_path(&root_path)
+        .to_string_lossy()
+        .to_string()
+}
+
+/// Check if a project file exists for the given root directory
+#[tauri::command]
+fn project_check_exists(root_path: String) -> Option<String> {
+    project::check_project_exists(&root_path)
+}
+
+/// Save a project to the specified path (or default if not provided)
+#[tauri::command]
+fn project_save(project: project::FFXProject, path: Option<String>) -> project::ProjectSaveResult {
+    let mut proj = project;
+    proj.touch(); // Update saved_at timestamp
+    project::save_project(&proj, path.as_deref())
+}
+
+/// Load a project from the specified path
+#[tauri::command]
+fn project_load(path: String) -> project::ProjectLoadResult {
+    project::load_project(&path)
+}
+
+/// Create a new project for a root directory
+#[tauri::command]
+fn project_create(root_path: String) -> project::FFXProject {
+    project::FFXProject::new(&root_path)
+}
+
+// ============================================================================
+// File Viewer Commands
+// ============================================================================
+
+/// Read a chunk of a file for hex viewing
+#[tauri::command]
+fn viewer_read_chunk(
+    path: String, 
+    offset: u64, 
+    size: Option<usize>
+) -> Result<viewer::FileChunk, String> {
+    viewer::read_file_chunk(&path, offset, size)
+}
+
+/// Detect file type from magic bytes and extension
+#[tauri::command]
+fn viewer_detect_type(path: String) -> Result<viewer::FileTypeInfo, String> {
+    viewer::detect_file_type(&path)
+}
+
+/// Parse file header and extract metadata with regions for hex highlighting
+#[tauri::command]
+fn viewer_parse_header(path: String) -> Result<viewer::ParsedMetadata, String> {
+    viewer::parse_file_header(&path)
+}
+
+/// Read file as text for text viewer
+#[tauri::command]
+fn viewer_read_text(path: String, offset: u64, max_chars: usize) -> Result<String, String> {
+    viewer::read_file_text(&path, offset, max_chars)
+}
+
+/// Start background system stats monitoring - emits "system-stats" events every 2 seconds
+fn start_system_stats_monitor(app_handle: tauri::AppHandle) {
+    std::thread::spawn(move || {
+        loop {
+            std::thread::sleep(std::time::Duration::from_secs(2));
+            let stats = collect_system_stats();
+            let _ = app_handle.emit("system-stats", stats);
+        }
+    });
+}
+
+#[cfg_attr(mobile, tauri::mobile_entry_point)]
+pub fn run() {
+    tauri::Builder::default()
+        .plugin(tauri_plugin_opener::init())
+        .plugin(tauri_plugin_dialog::init())
+        .manage(report::commands::ReportState::default())
+        .setup(|app| {
+            // Settings first: commands read them from the start
+            let settings_path = app.path().app_config_dir()
+                .map(|dir| dir.join(settings::SETTINGS_FILE))
+                .unwrap_or_else(|_| settings::default_path());
+            let first_start = !settings_path.exists();
+            let store = settings::init(settings::SettingsStore::open(&settings_path).unwrap_or_else(|e| {
+                tracing::warn!("Using default settings: {}", e);
+                settings::SettingsStore::recovered(&settings_path, e)
+            }));
+
+            // Initialize database early (in background thread to not block startup)
+            std::thread::spawn(move || {
+                let db = database::get_db();  // This triggers lazy initialization
+                tracing::info!("Database initialized");
+                let legacy = legacy_settings(db);
+                if first_start && !legacy.is_empty() {
+                    if let Err(e) = store.update(&serde_json::Value::Object(legacy)) {
+                        tracing::warn!("Earlier settings not migrated: {}", e);
+                    }
+                }
+                apply_settings(&store.get());
+            });
+            
+            // Warn the frontend about reads retried on a flaky network share
+            let handle = app.handle().clone();
+            common::retry::add_observer(Box::new(move |event| {
+                let _ = handle.emit("io-retry", event);
+            }));
+
+            // Start background system stats monitoring
+            start_system_stats_monitor(app.handle().clone());
+            Ok(())
+        })
+        .invoke_handler(tauri::generate_handler![
+            logical_info,
+            logical_info_fast,
+            logical_info_batch,
+            logical_info_tree_stream,
+            quick_check,
+            identify_container,
+            logical_verify,
+            ad1_verify_report,
+            verify_against_manifest,
+            ad1_hash_segments,
+            ad1_hash_image_stream,
+            logical_extract,
+            get_volume_info,
+            clean_partial_outputs,
+            evidence_copy,
+            compare_images,
+            scan_directory,
+            scan_directory_recursive,
+            scan_directory_streaming,
+            watch_directory,
+            unwatch_directory,
+            get_supported_formats,
+            // Path and discovery utilities
+            path_exists,
+            path_is_directory,
+            discover_evidence_files,
+            scan_for_processed_databases,
+            e01_v3_info,
+            e01_v3_verify,
+            e01_v3_verify_best_effort,
+            e01_sample_verify,
+            e01_verify_segments,
+            e01_full_verify,
+            e01_read_at,
+            e01_media_info,
+            raw_info,
+            raw_verify,
+            raw_verify_report,
+            raw_verify_device,
+            triage_device,
+            summarize_content_map,
+            export_content_map,
+            raw_verify_resumable,
+            raw_consolidate,
+            raw_sparse_to_raw,
+            hash_partitions,
+            ufed_verify_contents,
+            iso_list_entries,
+            resume_verify,
+            cancel_verify,
+            list_jobs,
+            get_settings,
+            get_settings_load_error,
+            update_settings,
+            get_job_resource_defaults,
+            set_job_resource_defaults,
+            get_io_retry_policy,
+            set_io_retry_policy,
+            get_audit_log_enabled,
+            set_audit_log_enabled,
+            audit_verify_chain,
+            audit_export,
+            verify_export_integrity,
+            get_job,
+            get_job_perf,
+            cancel_job,
+            pause_job,
+            resume_job,
+            retry_job,
+            list_verify_checkpoints,
+            discard_verify_checkpoint,
+            raw_verify_segments,
+            import_expected_hashes,
+            export_expected_hashes,
+            batch_hash,
+            export_hashdeep,
+            audit_hashdeep,
+            find_duplicates,
+            background_verify,
+            get_verification_status,
+            get_system_stats,
+            // Data viewing & analysis
+            read_file_bytes,
+            hex_dump,
+            detect_file_type,
+            analyze_file_entropy,
+            analyze_entropy_blocks,
+            compare_hashes,
+            verify_file_hash,
+            hash_file,
+            benchmark_hash,
+            self_test,
+            // Database operations
+            db_get_or_create_session,
+            db_get_recent_sessions,
+            db_get_last_session,
+            db_upsert_file,
+            db_get_files_for_session,
+            db_get_file_by_path,
+            db_insert_hash,
+            db_get_hashes_for_file,
+            db_get_latest_hash,
+            db_insert_verification,
+            db_get_verifications_for_file,
+            db_save_open_tabs,
+            db_get_open_tabs,
+            db_set_setting,
+            db_get_setting,
+            // Project file operations
+            project_get_default_path,
+            project_check_exists,
+            project_save,
+            project_load,
+            project_create,
+            // File viewer commands
+            viewer_read_chunk,
+            viewer_detect_type,
+            viewer_parse_header,
+            viewer_read_text,
+            // Report generation commands
+            report::commands::generate_report,
+            report::commands::preview_report,
+            report::commands::get_output_formats,
+            report::commands::create_new_report,
+            report::commands::validate_report,
+            report::commands::add_evidence_to_report,
+            report::commands::add_finding_to_report,
+            report::commands::export_report_json,
+            report::commands::import_report_json,
+            report::commands::extract_evidence_from_containers,
+            report::commands::create_evidence_from_container,
+            report::commands::get_report_template,
+            // AI commands
+            report::commands::ai_commands::is_ai_available,
+            report::commands::ai_commands::get_ai_providers,
+            report::commands::ai_commands::generate_ai_narrative,
+            report::commands::ai_commands::check_ollama_connection,
+            // Processed database commands
+            processed::commands::scan_processed_databases,
+            processed::commands::get_processed_db_details,
+            processed::commands::is_processed_database,
+            processed::commands::get_processed_db_summary,
+            // AXIOM-specific commands
+            processed::commands::get_axiom_case_info,
+            processed::commands::get_axiom_artifact_categories,
+            processed::commands::query_axiom_artifacts_cmd,
+            processed::commands::list_axiom_db_tables
+        ])
+        .run(tauri::generate_context!())
+        .expect("error while running tauri application");
+}
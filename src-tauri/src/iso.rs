@@ -0,0 +1,832 @@
+//! ISO 9660 / UDF optical disc images (.iso)
+//!
+//! Reads the volume descriptors that follow the 32 KiB system area: the
+//! ISO 9660 primary descriptor (volume label, publisher, dates, volume
+//! size), a Joliet supplementary descriptor, El Torito boot records and the
+//! UDF volume recognition sequence. Pure UDF discs (no ISO 9660 bridge) are
+//! identified through the anchor at sector 256 and their primary volume
+//! descriptor.
+//!
+//! Directories are listed from the ISO 9660 tree - through Joliet when the
+//! disc has it (UCS-2 names), otherwise with Rock Ridge `NM` names when
+//! present. Files stored in several extents are reported once with their
+//! combined size. UDF directory trees and file extraction are not handled;
+//! hashing goes through the raw image path.
+//!
+//! ```rust,ignore
+//! use ffx_check_lib::iso;
+//!
+//! let info = iso::info("/evidence/disc.iso")?;
+//! let listing = iso::list_entries("/evidence/disc.iso", true, iso::DEFAULT_MAX_LIST_ENTRIES)?;
+//! println!("{:?}: {} entries", info.volume_label, listing.entries.len());
+//! ```
+
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+
+use chrono::{Duration, NaiveDate};
+use serde::Serialize;
+use tracing::debug;
+
+use crate::common::capabilities::FormatCapabilities;
+
+/// Logical sector size of optical media and of the descriptor area
+pub const ISO_SECTOR_SIZE: u64 = 2048;
+
+/// Entries returned by a recursive listing unless the caller asks for fewer
+pub const DEFAULT_MAX_LIST_ENTRIES: usize = 10_000;
+
+/// First volume descriptor sector (after the 16-sector system area)
+const DESCRIPTOR_START: u64 = 16;
+
+/// Descriptors read before giving up on finding the end of the set
+const MAX_DESCRIPTORS: u64 = 64;
+
+/// Sector of the UDF anchor volume descriptor pointer
+const UDF_ANCHOR_SECTOR: u64 = 256;
+
+/// UDF volume descriptor sequence sectors read
+const MAX_UDF_DESCRIPTORS: u64 = 32;
+
+/// Directory nesting followed by a recursive listing (Rock Ridge trees can be deep)
+const MAX_LIST_DEPTH: usize = 64;
+
+/// Directory extents larger than this are treated as corrupt
+const MAX_DIRECTORY_SIZE: u64 = 16 * 1024 * 1024;
+
+const ISO_IDENTIFIER: &[u8; 5] = b"CD001";
+
+/// Directory record flag bits
+const FLAG_DIRECTORY: u8 = 0x02;
+const FLAG_MULTI_EXTENT: u8 = 0x80;
+
+/// UDF descriptor tag identifiers
+const UDF_TAG_PRIMARY_VOLUME: u16 = 1;
+const UDF_TAG_ANCHOR: u16 = 2;
+const UDF_TAG_TERMINATOR: u16 = 8;
+
+/// Volume information of an optical disc image
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct IsoInfo {
+    /// Joliet label if present (mixed case), else the ISO 9660 or UDF label
+    pub volume_label: Option<String>,
+    pub system_id: Option<String>,
+    pub volume_set_id: Option<String>,
+    pub publisher: Option<String>,
+    pub data_preparer: Option<String>,
+    pub application: Option<String>,
+    /// Volume creation date, UTC ("YYYY-MM-DD HH:MM:SS")
+    pub creation_date: Option<String>,
+    pub modification_date: Option<String>,
+    pub block_size: u32,
+    /// Volume space size in blocks, as recorded in the descriptor
+    pub total_sectors: u64,
+    /// Size of the image file (may differ from the recorded volume size)
+    pub image_size: u64,
+    pub has_iso9660: bool,
+    pub has_joliet: bool,
+    pub has_rock_ridge: bool,
+    pub has_udf: bool,
+    /// UDF descriptor standard: "NSR02" (UDF 1.0x) or "NSR03" (UDF 2.x)
+    pub udf_revision: Option<String>,
+    /// El Torito boot record present
+    pub bootable: bool,
+}
+
+/// One file or directory of the disc
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct IsoEntry {
+    /// Path from the root, '/'-separated, without a leading '/'
+    pub path: String,
+    pub is_dir: bool,
+    /// Combined size of all extents
+    pub size: u64,
+    /// First block of the data
+    pub lba: u32,
+    /// Directory records the file is stored in (more than 1 for files over 4 GiB)
+    pub extents: u32,
+    /// Recording date, UTC
+    pub recorded: Option<String>,
+}
+
+/// Directory listing of a disc
+#[derive(Clone, Debug, Serialize)]
+pub struct IsoListing {
+    pub entries: Vec<IsoEntry>,
+    /// The listing hit the entry cap (or skipped a corrupt directory)
+    pub truncated: bool,
+    /// Where names come from: "Joliet", "Rock Ridge" or "ISO 9660"
+    pub name_source: String,
+}
+
+/// A directory record as stored on disc
+#[derive(Clone, Debug)]
+struct DirRecord {
+    lba: u32,
+    size: u64,
+    flags: u8,
+    name: Vec<u8>,
+    system_use: Vec<u8>,
+    recorded: Option<String>,
+}
+
+impl DirRecord {
+    fn is_dir(&self) -> bool {
+        self.flags & FLAG_DIRECTORY != 0
+    }
+
+    /// "." and ".." are stored as the single bytes 0 and 1
+    fn is_self_or_parent(&self) -> bool {
+        self.name == [0] || self.name == [1]
+    }
+}
+
+/// How directory record names are decoded
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum NameSource {
+    Joliet,
+    RockRidge,
+    Iso9660,
+}
+
+impl NameSource {
+    fn label(self) -> &'static str {
+        match self {
+            NameSource::Joliet => "Joliet",
+            NameSource::RockRidge => "Rock Ridge",
+            NameSource::Iso9660 => "ISO 9660",
+        }
+    }
+}
+
+/// Parsed volume descriptor set
+struct Volume {
+    info: IsoInfo,
+    /// Root of the tree listings walk (the Joliet root when present)
+    root: Option<DirRecord>,
+    names: NameSource,
+}
+
+/// Volume info, directory listing and whole-image hash; no file export yet
+pub const fn capabilities() -> FormatCapabilities {
+    FormatCapabilities {
+        can_info: true,
+        can_info_tree: true,
+        can_verify_whole: true,
+        can_verify_items: false,
+        can_verify_segments: false,
+        can_extract: false,
+        can_extract_item: false,
+        notes: &["UDF-only discs cannot be listed", "File extraction is not implemented - mount the image or use 7z/xorriso"],
+    }
+}
+
+/// Check for an ISO 9660 descriptor or UDF recognition sequence at sector 16
+pub fn is_iso(path: &str) -> Result<bool, String> {
+    let mut file = File::open(path)
+        .map_err(|e| format!("Failed to open file: {}", e))?;
+    let Ok(sector) = read_block(&mut file, DESCRIPTOR_START, ISO_SECTOR_SIZE) else {
+        return Ok(false);
+    };
+    Ok(&sector[1..6] == ISO_IDENTIFIER || is_vrs_descriptor(&sector))
+}
+
+/// Read the volume information of an ISO 9660 and/or UDF image
+pub fn info(path: &str) -> Result<IsoInfo, String> {
+    let mut file = File::open(path)
+        .map_err(|e| format!("Failed to open ISO image '{}': {}", path, e))?;
+    Ok(read_volume(&mut file)?.info)
+}
+
+/// List the root directory
+pub fn list_root(path: &str) -> Result<IsoListing, String> {
+    list_entries(path, false, DEFAULT_MAX_LIST_ENTRIES)
+}
+
+/// List the root directory, or the whole tree when `recursive`, up to `max_entries`
+///
+/// Directories are listed depth first, each in on-disc order. A directory
+/// whose extent cannot be read is skipped and marks the listing truncated.
+pub fn list_entries(path: &str, recursive: bool, max_entries: usize) -> Result<IsoListing, String> {
+    let mut file = File::open(path)
+        .map_err(|e| format!("Failed to open ISO image '{}': {}", path, e))?;
+    let volume = read_volume(&mut file)?;
+    let root = volume.root.ok_or("No ISO 9660 directory tree (UDF-only discs cannot be listed yet)")?;
+    let block_size = volume.info.block_size as u64;
+
+    let mut listing = IsoListing {
+        entries: Vec::new(),
+        truncated: false,
+        name_source: volume.names.label().to_string(),
+    };
+    let mut visited = HashSet::from([root.lba]);
+    // (directory, its path, depth) - pushed in reverse so entries come out in disc order
+    let mut pending = vec![(root, String::new(), 0usize)];
+
+    while let Some((dir, dir_path, depth)) = pending.pop() {
+        let records = match read_directory(&mut file, &dir, block_size) {
+            Ok(records) => records,
+            Err(e) => {
+                debug!(path = %dir_path, error = %e, "Skipping unreadable ISO directory");
+                listing.truncated = true;
+                continue;
+            }
+        };
+        let mut subdirs = Vec::new();
+        for (record, extents, size) in merge_extents(records) {
+            if listing.entries.len() >= max_entries {
+                listing.truncated = true;
+                return Ok(listing);
+            }
+            let name = decode_name(&record, volume.names);
+            let entry_path = if dir_path.is_empty() { name } else { format!("{}/{}", dir_path, name) };
+            if recursive && record.is_dir() && depth + 1 < MAX_LIST_DEPTH && visited.insert(record.lba) {
+                subdirs.push((record.clone(), entry_path.clone(), depth + 1));
+            }
+            listing.entries.push(IsoEntry {
+                path: entry_path,
+                is_dir: record.is_dir(),
+                size,
+                lba: record.lba,
+                extents,
+                recorded: record.recorded,
+            });
+        }
+        pending.extend(subdirs.into_iter().rev());
+    }
+
+    Ok(listing)
+}
+
+/// Walk the volume descriptor set and the UDF anchor
+fn read_volume(file: &mut File) -> Result<Volume, String> {
+    let image_size = file.seek(SeekFrom::End(0))
+        .map_err(|e| format!("Failed to seek ISO image: {}", e))?;
+    let mut info = IsoInfo { image_size, block_size: ISO_SECTOR_SIZE as u32, ..Default::default() };
+    let mut primary_root = None;
+    let mut joliet = None;
+    let mut terminated = false;
+
+    for index in 0..MAX_DESCRIPTORS {
+        let Ok(sector) = read_block(file, DESCRIPTOR_START + index, ISO_SECTOR_SIZE) else {
+            break;
+        };
+        if &sector[1..6] == ISO_IDENTIFIER && !terminated {
+            match sector[0] {
+                0 if sector[7..30].starts_with(b"EL TORITO SPECIFICATION") => info.bootable = true,
+                1 if !info.has_iso9660 => {
+                    info.has_iso9660 = true;
+                    read_primary_descriptor(&sector, &mut info);
+                    primary_root = parse_record(&sector[156..190]);
+                }
+                2 if is_joliet(&sector) && joliet.is_none() => {
+                    info.has_joliet = true;
+                    if let Some(label) = ucs2_field(&sector[40..72]) {
+                        info.volume_label = Some(label);
+                    }
+                    joliet = parse_record(&sector[156..190]);
+                }
+                255 => terminated = true,
+                _ => {}
+            }
+        } else if is_vrs_descriptor(&sector) {
+            // The UDF recognition sequence follows the ISO 9660 terminator on bridge discs
+            match &sector[1..6] {
+                b"NSR02" | b"NSR03" => {
+                    info.has_udf = true;
+                    info.udf_revision = Some(String::from_utf8_lossy(&sector[1..6]).to_string());
+                }
+                b"TEA01" => break,
+                _ => {}
+            }
+        } else {
+            break;
+        }
+    }
+
+    if let Ok(anchor) = read_block(file, UDF_ANCHOR_SECTOR, ISO_SECTOR_SIZE) {
+        if udf_tag(&anchor) == Some(UDF_TAG_ANCHOR) {
+            info.has_udf = true;
+            read_udf_primary(file, &anchor, &mut info);
+        }
+    }
+
+    if !info.has_iso9660 && !info.has_udf {
+        return Err("Not an ISO 9660 or UDF image: no volume descriptor at sector 16".to_string());
+    }
+
+    // Rock Ridge extends the primary tree; Joliet names still win for listings
+    info.has_rock_ridge = primary_root.as_ref()
+        .is_some_and(|root| has_rock_ridge(file, root, info.block_size as u64));
+    let names = if joliet.is_some() {
+        NameSource::Joliet
+    } else if info.has_rock_ridge {
+        NameSource::RockRidge
+    } else {
+        NameSource::Iso9660
+    };
+    let root = joliet.or(primary_root);
+
+    debug!(
+        label = ?info.volume_label, sectors = info.total_sectors, joliet = info.has_joliet,
+        rock_ridge = info.has_rock_ridge, udf = info.has_udf, "Read ISO volume descriptors"
+    );
+    Ok(Volume { info, root, names })
+}
+
+/// Fields of the primary volume descriptor
+fn read_primary_descriptor(sector: &[u8], info: &mut IsoInfo) {
+    info.system_id = a_string(&sector[8..40]);
+    info.volume_label = a_string(&sector[40..72]);
+    info.total_sectors = u32_le(sector, 80) as u64;
+    let block_size = u16_le(sector, 128) as u32;
+    if block_size.is_power_of_two() && (512..=ISO_SECTOR_SIZE as u32).contains(&block_size) {
+        info.block_size = block_size;
+    }
+    info.volume_set_id = a_string(&sector[190..318]);
+    info.publisher = a_string(&sector[318..446]);
+    info.data_preparer = a_string(&sector[446..574]);
+    info.application = a_string(&sector[574..702]);
+    info.creation_date = descriptor_date(&sector[813..830]);
+    info.modification_date = descriptor_date(&sector[830..847]);
+}
+
+/// Supplementary descriptor with a UCS-2 escape sequence (levels 1-3)
+fn is_joliet(sector: &[u8]) -> bool {
+    sector[88..120].windows(3).any(|w| matches!(w, b"%/@" | b"%/C" | b"%/E"))
+}
+
+/// UDF volume recognition sequence descriptor (ECMA-167 part 2)
+fn is_vrs_descriptor(sector: &[u8]) -> bool {
+    sector[0] == 0 && matches!(&sector[1..6], b"BEA01" | b"NSR02" | b"NSR03" | b"TEA01" | b"BOOT2" | b"CDW02")
+}
+
+/// Tag identifier of a UDF descriptor whose tag checksum is valid
+fn udf_tag(block: &[u8]) -> Option<u16> {
+    let checksum = block[..16].iter().enumerate()
+        .filter(|(i, _)| *i != 4)
+        .fold(0u8, |sum, (_, b)| sum.wrapping_add(*b));
+    (checksum == block[4]).then(|| u16_le(block, 0))
+}
+
+/// Label and recording date from the UDF primary volume descriptor
+fn read_udf_primary(file: &mut File, anchor: &[u8], info: &mut IsoInfo) {
+    let length = u32_le(anchor, 16) as u64;
+    let location = u32_le(anchor, 20) as u64;
+    for index in 0..(length / ISO_SECTOR_SIZE).min(MAX_UDF_DESCRIPTORS) {
+        let Ok(block) = read_block(file, location + index, ISO_SECTOR_SIZE) else {
+            return;
+        };
+        match udf_tag(&block) {
+            Some(UDF_TAG_PRIMARY_VOLUME) => {
+                if info.volume_label.is_none() {
+                    info.volume_label = dstring(&block[24..56]);
+                }
+                if info.creation_date.is_none() {
+                    info.creation_date = udf_timestamp(&block[376..388]);
+                }
+                return;
+            }
+            Some(UDF_TAG_TERMINATOR) | None => return,
+            _ => {}
+        }
+    }
+}
+
+/// Whether the root's "." record carries SUSP/Rock Ridge entries
+fn has_rock_ridge(file: &mut File, root: &DirRecord, block_size: u64) -> bool {
+    let Ok(sector) = read_block(file, root.lba as u64, block_size) else {
+        return false;
+    };
+    parse_record(&sector).is_some_and(|dot| {
+        susp_entries(&dot.system_use).any(|(signature, _)| matches!(signature, b"SP" | b"RR" | b"PX" | b"NM"))
+    })
+}
+
+/// Records of a directory extent, without "." and ".."
+///
+/// Records never span a sector; a zero length byte means the rest of the
+/// sector is padding.
+fn read_directory(file: &mut File, dir: &DirRecord, block_size: u64) -> Result<Vec<DirRecord>, String> {
+    if dir.size > MAX_DIRECTORY_SIZE {
+        return Err(format!("Directory extent of {} bytes exceeds the {} byte limit", dir.size, MAX_DIRECTORY_SIZE));
+    }
+    let data = read_block(file, dir.lba as u64, dir.size)?;
+    let mut records = Vec::new();
+    let mut offset = 0usize;
+    while offset < data.len() {
+        if data[offset] == 0 {
+            offset = (offset / block_size as usize + 1) * block_size as usize;
+            continue;
+        }
+        let record = parse_record(&data[offset..])
+            .ok_or_else(|| format!("Corrupt directory record at offset {} of block {}", offset, dir.lba))?;
+        offset += data[offset] as usize;
+        if !record.is_self_or_parent() {
+            records.push(record);
+        }
+    }
+    Ok(records)
+}
+
+/// Combine the records of multi-extent files: (first record, extents, total size)
+fn merge_extents(records: Vec<DirRecord>) -> Vec<(DirRecord, u32, u64)> {
+    let mut merged: Vec<(DirRecord, u32, u64)> = Vec::new();
+    let mut continues = false;
+    for record in records {
+        match merged.last_mut() {
+            Some((first, extents, size)) if continues && first.name == record.name => {
+                *extents += 1;
+                *size += record.size;
+            }
+            _ => {
+                let size = record.size;
+                merged.push((record.clone(), 1, size));
+            }
+        }
+        continues = record.flags & FLAG_MULTI_EXTENT != 0;
+    }
+    merged
+}
+
+/// Parse one directory record (ECMA-119 9.1)
+fn parse_record(data: &[u8]) -> Option<DirRecord> {
+    let length = *data.first()? as usize;
+    if length < 34 || length > data.len() {
+        return None;
+    }
+    let name_length = data[32] as usize;
+    let name_end = 33 + name_length;
+    if name_end > length {
+        return None;
+    }
+    // A padding byte keeps the system use area at an even offset
+    let system_use_start = (name_end + (name_length + 1) % 2).min(length);
+    Some(DirRecord {
+        lba: u32_le(data, 2).saturating_add(data[1] as u32),
+        size: u32_le(data, 10) as u64,
+        flags: data[25],
+        name: data[33..name_end].to_vec(),
+        system_use: data[system_use_start..length].to_vec(),
+        recorded: record_date(&data[18..25]),
+    })
+}
+
+/// Display name of a record
+fn decode_name(record: &DirRecord, names: NameSource) -> String {
+    let name = match names {
+        NameSource::Joliet => {
+            let units: Vec<u16> = record.name.chunks_exact(2).map(|c| u16::from_be_bytes([c[0], c[1]])).collect();
+            String::from_utf16_lossy(&units)
+        }
+        NameSource::RockRidge => match rock_ridge_name(&record.system_use) {
+            Some(name) => return name,
+            None => String::from_utf8_lossy(&record.name).to_string(),
+        },
+        NameSource::Iso9660 => String::from_utf8_lossy(&record.name).to_string(),
+    };
+    // Drop the ";1" version and the dot of names without an extension
+    let name = name.split_once(';').map_or(name.as_str(), |(base, _)| base);
+    let name = if record.is_dir() { name } else { name.strip_suffix('.').unwrap_or(name) };
+    name.to_string()
+}
+
+/// Alternate name from Rock Ridge NM entries (continued entries are joined)
+fn rock_ridge_name(system_use: &[u8]) -> Option<String> {
+    let mut name = Vec::new();
+    let mut found = false;
+    for (signature, entry) in susp_entries(system_use) {
+        if signature != b"NM" || entry.len() < 5 {
+            continue;
+        }
+        found = true;
+        name.extend_from_slice(&entry[5..]);
+        // Bit 0: the name continues in the next NM entry
+        if entry[4] & 0x01 == 0 {
+            break;
+        }
+    }
+    (found && !name.is_empty()).then(|| String::from_utf8_lossy(&name).to_string())
+}
+
+/// SUSP entries of a system use area: (signature, whole entry)
+///
+/// Continuation areas (CE) are not followed.
+fn susp_entries(system_use: &[u8]) -> impl Iterator<Item = (&[u8], &[u8])> {
+    let mut offset = 0usize;
+    std::iter::from_fn(move || {
+        let header = system_use.get(offset..offset + 4)?;
+        let length = header[2] as usize;
+        if length < 4 || offset + length > system_use.len() {
+            return None;
+        }
+        let entry = &system_use[offset..offset + length];
+        offset += length;
+        Some((&entry[..2], entry))
+    })
+}
+
+/// Read `length` bytes starting at block `lba`
+fn read_block(file: &mut File, lba: u64, length: u64) -> Result<Vec<u8>, String> {
+    let mut data = vec![0u8; length as usize];
+    file.seek(SeekFrom::Start(lba * ISO_SECTOR_SIZE))
+        .and_then(|_| file.read_exact(&mut data))
+        .map_err(|e| format!("Failed to read block {}: {}", lba, e))?;
+    Ok(data)
+}
+
+fn u16_le(data: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes([data[offset], data[offset + 1]])
+}
+
+fn u32_le(data: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap())
+}
+
+/// Space-padded a-/d-characters field
+fn a_string(field: &[u8]) -> Option<String> {
+    let value = String::from_utf8_lossy(field).trim_end_matches([' ', '\0']).to_string();
+    (!value.is_empty()).then_some(value)
+}
+
+/// UCS-2 big-endian field of a Joliet descriptor
+fn ucs2_field(field: &[u8]) -> Option<String> {
+    let units: Vec<u16> = field.chunks_exact(2).map(|c| u16::from_be_bytes([c[0], c[1]])).collect();
+    let value = String::from_utf16_lossy(&units).trim_end_matches([' ', '\0']).to_string();
+    (!value.is_empty()).then_some(value)
+}
+
+/// UDF d-string: compression id (8 or 16), characters, used length in the last byte
+fn dstring(field: &[u8]) -> Option<String> {
+    let used = (*field.last()? as usize).min(field.len() - 1);
+    if used < 2 {
+        return None;
+    }
+    let chars = &field[1..used];
+    let value = match field[0] {
+        8 => chars.iter().map(|&b| b as char).collect(),
+        16 => {
+            let units: Vec<u16> = chars.chunks_exact(2).map(|c| u16::from_be_bytes([c[0], c[1]])).collect();
+            String::from_utf16_lossy(&units)
+        }
+        _ => return None,
+    };
+    let value: String = value.trim_end_matches(['\0', ' ']).to_string();
+    (!value.is_empty()).then_some(value)
+}
+
+/// Shift a local date by its offset from UTC and format it
+fn format_utc(year: i32, month: u32, day: u32, hour: u32, minute: u32, second: u32, offset_minutes: i64) -> Option<String> {
+    let local = NaiveDate::from_ymd_opt(year, month, day)?.and_hms_opt(hour, minute, second)?;
+    Some((local - Duration::minutes(offset_minutes)).format("%Y-%m-%d %H:%M:%S").to_string())
+}
+
+/// 17-byte descriptor date: "YYYYMMDDHHMMSScc" and a signed offset in 15 minute units
+fn descriptor_date(field: &[u8]) -> Option<String> {
+    let digits = std::str::from_utf8(&field[..16]).ok()?;
+    if !digits.bytes().all(|b| b.is_ascii_digit()) || digits.bytes().all(|b| b == b'0') {
+        return None;
+    }
+    let number = |range: std::ops::Range<usize>| digits[range].parse::<u32>().ok();
+    format_utc(
+        number(0..4)? as i32, number(4..6)?, number(6..8)?,
+        number(8..10)?, number(10..12)?, number(12..14)?,
+        field[16] as i8 as i64 * 15,
+    )
+}
+
+/// 7-byte directory record date: years since 1900, month, day, time, offset
+fn record_date(field: &[u8]) -> Option<String> {
+    if field[..6].iter().all(|&b| b == 0) {
+        return None;
+    }
+    format_utc(
+        1900 + field[0] as i32, field[1] as u32, field[2] as u32,
+        field[3] as u32, field[4] as u32, field[5] as u32,
+        field[6] as i8 as i64 * 15,
+    )
+}
+
+/// UDF timestamp: type and timezone (minutes in the low 12 bits), year, month, day, time
+fn udf_timestamp(field: &[u8]) -> Option<String> {
+    let type_and_zone = u16_le(field, 0);
+    let year = i16::from_le_bytes([field[2], field[3]]) as i32;
+    if year == 0 {
+        return None;
+    }
+    // Sign-extend the 12-bit offset; -2047 means "no time zone"
+    let zone = ((type_and_zone << 4) as i16 >> 4) as i64;
+    let offset = if zone == -2047 { 0 } else { zone };
+    format_utc(year, field[4] as u32, field[5] as u32, field[6] as u32, field[7] as u32, field[8] as u32, offset)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECTOR: usize = ISO_SECTOR_SIZE as usize;
+
+    fn both_u32(value: u32) -> [u8; 8] {
+        let mut both = [0u8; 8];
+        both[..4].copy_from_slice(&value.to_le_bytes());
+        both[4..].copy_from_slice(&value.to_be_bytes());
+        both
+    }
+
+    fn ucs2(text: &str) -> Vec<u8> {
+        text.encode_utf16().flat_map(u16::to_be_bytes).collect()
+    }
+
+    fn record(name: &[u8], lba: u32, size: u32, flags: u8, system_use: &[u8]) -> Vec<u8> {
+        let mut data = vec![0u8; 33];
+        data[2..10].copy_from_slice(&both_u32(lba));
+        data[10..18].copy_from_slice(&both_u32(size));
+        // 2024-01-02 03:04:05 at UTC+1
+        data[18..25].copy_from_slice(&[124, 1, 2, 3, 4, 5, 4]);
+        data[25] = flags;
+        data[32] = name.len() as u8;
+        data.extend_from_slice(name);
+        if name.len().is_multiple_of(2) {
+            data.push(0);
+        }
+        data.extend_from_slice(system_use);
+        if data.len() % 2 == 1 {
+            data.push(0);
+        }
+        data[0] = data.len() as u8;
+        data
+    }
+
+    fn directory(lba: u32, parent: u32, dot_system_use: &[u8], records: &[Vec<u8>]) -> Vec<u8> {
+        let mut data = record(&[0], lba, SECTOR as u32, FLAG_DIRECTORY, dot_system_use);
+        data.extend(record(&[1], parent, SECTOR as u32, FLAG_DIRECTORY, &[]));
+        for record in records {
+            data.extend_from_slice(record);
+        }
+        data
+    }
+
+    fn descriptor(kind: u8, label: &[u8], root: u32, escape: &[u8], total_sectors: u32) -> Vec<u8> {
+        let mut data = vec![0u8; SECTOR];
+        data[0] = kind;
+        data[1..6].copy_from_slice(ISO_IDENTIFIER);
+        data[6] = 1;
+        data[8..40].fill(b' ');
+        data[40..72].fill(b' ');
+        data[40..40 + label.len()].copy_from_slice(label);
+        data[80..88].copy_from_slice(&both_u32(total_sectors));
+        data[88..88 + escape.len()].copy_from_slice(escape);
+        data[128..130].copy_from_slice(&2048u16.to_le_bytes());
+        data[156..190].copy_from_slice(&record(&[0], root, SECTOR as u32, FLAG_DIRECTORY, &[]));
+        data[318..327].copy_from_slice(b"ACME Labs");
+        data[813..829].copy_from_slice(b"2024010203040500");
+        data[829] = 4; // UTC+1
+        data
+    }
+
+    fn vrs(identifier: &[u8; 5]) -> Vec<u8> {
+        let mut data = vec![0u8; SECTOR];
+        data[1..6].copy_from_slice(identifier);
+        data[6] = 1;
+        data
+    }
+
+    fn image(sectors: &[(usize, Vec<u8>)]) -> tempfile::NamedTempFile {
+        let count = sectors.iter().map(|(lba, _)| lba + 1).max().unwrap();
+        let mut data = vec![0u8; count * SECTOR];
+        for (lba, sector) in sectors {
+            data[lba * SECTOR..lba * SECTOR + sector.len()].copy_from_slice(sector);
+        }
+        let file = tempfile::Builder::new().suffix(".iso").tempfile().unwrap();
+        std::fs::write(file.path(), data).unwrap();
+        file
+    }
+
+    #[test]
+    fn test_joliet_bridge_disc_with_multi_extent_file() {
+        let iso_root = directory(24, 24, &[], &[
+            record(b"BIG.BIN;1", 26, 2048, FLAG_MULTI_EXTENT, &[]),
+            record(b"BIG.BIN;1", 27, 100, 0, &[]),
+            record(b"DOCS", 25, 2048, FLAG_DIRECTORY, &[]),
+        ]);
+        let joliet_root = directory(28, 28, &[], &[
+            record(&ucs2("big file.bin;1"), 26, 2048, FLAG_MULTI_EXTENT, &[]),
+            record(&ucs2("big file.bin;1"), 27, 100, 0, &[]),
+            record(&ucs2("Documents"), 29, 2048, FLAG_DIRECTORY, &[]),
+        ]);
+        let disc = image(&[
+            (16, descriptor(1, b"EVIDENCE_DISC", 24, &[], 30)),
+            (17, descriptor(2, &ucs2(&format!("{:<16}", "Evidence Disc")), 28, b"%/E", 30)),
+            (18, descriptor(255, b"", 0, &[], 0)),
+            (19, vrs(b"BEA01")),
+            (20, vrs(b"NSR02")),
+            (21, vrs(b"TEA01")),
+            (24, iso_root),
+            (25, directory(25, 24, &[], &[record(b"README.TXT;1", 26, 10, 0, &[])])),
+            (28, joliet_root),
+            (29, directory(29, 28, &[], &[record(&ucs2("Read Me.txt;1"), 26, 10, 0, &[])])),
+        ]);
+        let path = disc.path().to_str().unwrap();
+
+        assert!(is_iso(path).unwrap());
+        let info = info(path).unwrap();
+        assert_eq!(info.volume_label.as_deref(), Some("Evidence Disc"));
+        assert_eq!(info.publisher.as_deref(), Some("ACME Labs"));
+        assert_eq!(info.creation_date.as_deref(), Some("2024-01-02 02:04:05"));
+        assert_eq!(info.total_sectors, 30);
+        assert!(info.has_iso9660 && info.has_joliet && info.has_udf && !info.has_rock_ridge);
+        assert_eq!(info.udf_revision.as_deref(), Some("NSR02"));
+
+        let root = list_root(path).unwrap();
+        assert_eq!(root.name_source, "Joliet");
+        let names: Vec<&str> = root.entries.iter().map(|e| e.path.as_str()).collect();
+        assert_eq!(names, ["big file.bin", "Documents"]);
+        assert_eq!((root.entries[0].size, root.entries[0].extents), (2148, 2));
+        assert_eq!(root.entries[0].recorded.as_deref(), Some("2024-01-02 02:04:05"));
+
+        let tree = list_entries(path, true, DEFAULT_MAX_LIST_ENTRIES).unwrap();
+        let names: Vec<&str> = tree.entries.iter().map(|e| e.path.as_str()).collect();
+        assert_eq!(names, ["big file.bin", "Documents", "Documents/Read Me.txt"]);
+        assert!(!tree.truncated);
+
+        let capped = list_entries(path, true, 2).unwrap();
+        assert_eq!(capped.entries.len(), 2);
+        assert!(capped.truncated);
+    }
+
+    #[test]
+    fn test_rock_ridge_names_without_joliet() {
+        let sp = [b'S', b'P', 7, 1, 0xbe, 0xef, 0];
+        let mut nm = vec![b'N', b'M', 0, 1, 0];
+        nm.extend_from_slice(b"Mixed Case Name.jpeg");
+        nm[2] = nm.len() as u8;
+        let disc = image(&[
+            (16, descriptor(1, b"RR_DISC", 20, &[], 21)),
+            (17, descriptor(255, b"", 0, &[], 0)),
+            (20, directory(20, 20, &sp, &[
+                record(b"MIXED_CA.JPE;1", 20, 5, 0, &nm),
+                record(b"PLAIN.;1", 20, 7, 0, &[]),
+            ])),
+        ]);
+        let path = disc.path().to_str().unwrap();
+
+        let info = info(path).unwrap();
+        assert!(info.has_rock_ridge && !info.has_joliet && !info.has_udf);
+        assert_eq!(info.volume_label.as_deref(), Some("RR_DISC"));
+
+        let listing = list_root(path).unwrap();
+        assert_eq!(listing.name_source, "Rock Ridge");
+        let names: Vec<&str> = listing.entries.iter().map(|e| e.path.as_str()).collect();
+        assert_eq!(names, ["Mixed Case Name.jpeg", "PLAIN"]);
+    }
+
+    #[test]
+    fn test_udf_only_disc_reports_label_but_cannot_be_listed() {
+        fn tagged(identifier: u16, location: u32, body: &[(usize, &[u8])]) -> Vec<u8> {
+            let mut data = vec![0u8; SECTOR];
+            data[0..2].copy_from_slice(&identifier.to_le_bytes());
+            data[2] = 3;
+            data[12..16].copy_from_slice(&location.to_le_bytes());
+            for (offset, bytes) in body {
+                data[*offset..offset + bytes.len()].copy_from_slice(bytes);
+            }
+            data[4] = data[..16].iter().enumerate().filter(|(i, _)| *i != 4).fold(0u8, |s, (_, b)| s.wrapping_add(*b));
+            data
+        }
+        let mut label = [0u8; 32];
+        label[0] = 8;
+        label[1..11].copy_from_slice(b"UDF Volume");
+        label[31] = 11;
+        // UTC-5 (-300 minutes in the low 12 bits), 2023-06-30 22:15:00
+        let zone = (1u16 << 12) | ((-300i16 as u16) & 0x0fff);
+        let mut stamp = zone.to_le_bytes().to_vec();
+        stamp.extend_from_slice(&2023i16.to_le_bytes());
+        stamp.extend_from_slice(&[6, 30, 22, 15, 0, 0, 0, 0]);
+        let mut extent = 4096u32.to_le_bytes().to_vec();
+        extent.extend_from_slice(&257u32.to_le_bytes());
+
+        let disc = image(&[
+            (16, vrs(b"BEA01")),
+            (17, vrs(b"NSR03")),
+            (18, vrs(b"TEA01")),
+            (256, tagged(UDF_TAG_ANCHOR, 256, &[(16, &extent)])),
+            (257, tagged(UDF_TAG_PRIMARY_VOLUME, 257, &[(24, &label), (376, &stamp)])),
+            (258, tagged(UDF_TAG_TERMINATOR, 258, &[])),
+        ]);
+        let path = disc.path().to_str().unwrap();
+
+        assert!(is_iso(path).unwrap());
+        let info = info(path).unwrap();
+        assert!(info.has_udf && !info.has_iso9660);
+        assert_eq!(info.udf_revision.as_deref(), Some("NSR03"));
+        assert_eq!(info.volume_label.as_deref(), Some("UDF Volume"));
+        assert_eq!(info.creation_date.as_deref(), Some("2023-07-01 03:15:00"));
+        assert!(list_root(path).unwrap_err().contains("UDF-only"));
+
+        let plain = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(plain.path(), vec![0u8; 40 * SECTOR]).unwrap();
+        assert!(!is_iso(plain.path().to_str().unwrap()).unwrap());
+        assert!(super::info(plain.path().to_str().unwrap()).unwrap_err().contains("Not an ISO 9660"));
+    }
+}
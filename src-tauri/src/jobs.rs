@@ -0,0 +1,730 @@
+//! Global registry of long-running jobs (verification, hashing, extraction)
+//!
+//! Every long-running operation registers itself when it starts and gets a
+//! [`JobHandle`] that it reports progress through and finishes with its
+//! outcome. The registry can be listed at any time, so a frontend that
+//! reloads can rediscover what is still running. Finished jobs are kept
+//! (newest first, at most [`MAX_FINISHED_JOBS`]) as recent history.
+//!
+//! A job that failed because its evidence device went away (see
+//! [`crate::common::device`]) is recorded as [`JobStatus::Disconnected`]
+//! rather than failed, so it can be retried once the media is reconnected
+//! and [`revalidate_inputs`] finds every segment again.
+//!
+//! Jobs have two priorities. User-initiated jobs start immediately.
+//! Background jobs ([`JobRegistry::queue_background`]) wait in per-lane
+//! queues. Each lane (e.g. a storage device) runs one job at a time, and
+//! only while no user job is running. Background work pauses through
+//! [`JobHandle::wait_for_user_jobs`] when a user job starts mid-way.
+//!
+//! Verification jobs can also be paused by the user
+//! ([`JobRegistry::pause`]) to free the disk for something urgent. Work
+//! wrapped in [`JobHandle::pausable`] parks at its next buffer boundary
+//! through [`crate::common::progress::pause_point`], keeping its hasher
+//! state in memory, and carries on where it stopped once resumed.
+//!
+//! Reads retried after a transient network error ([`crate::common::retry`])
+//! are counted against every running job whose inputs belong to the same
+//! segment set as the file that was retried.
+//!
+//! Progress goes through the same atomic counters as
+//! [`crate::common::progress`], so reporting once per hashed buffer never
+//! takes the registry lock.
+//!
+//! ```rust,ignore
+//! use ffx_check_lib::jobs::{self, JobKind};
+//!
+//! let job = jobs::registry().start(JobKind::Verify, vec![path.clone()]);
+//! let result = ewf::verify_with_progress(&path, "sha256", |current, total| {
+//!     job.progress(current as u64, total as u64);
+//! });
+//! job.finish(result.clone());
+//! ```
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock};
+use std::time::Duration;
+
+use chrono::{SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info};
+
+use crate::common::device;
+use crate::common::progress::{self, JobProgress, PauseGate};
+use crate::common::resources::JobResources;
+use crate::common::retry;
+
+/// Finished jobs retained for the history view
+pub const MAX_FINISHED_JOBS: usize = 100;
+
+/// How often waiting background work checks whether user jobs are still running
+const BACKGROUND_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Low-priority work, run on its lane's worker thread
+pub type BackgroundWork = Box<dyn FnOnce(&JobHandle) -> Result<String, String> + Send>;
+
+/// What a job does
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobKind {
+    /// Whole-image or per-item container verification
+    Verify,
+    /// Per-segment hashing of a segmented image
+    SegmentVerify,
+    /// Parallel hashing of a list of files
+    BatchHash,
+    /// Hashing of one regular file
+    Hash,
+    /// Extraction of container contents
+    Extract,
+    /// Hash throughput benchmark
+    Benchmark,
+    /// Hash-verified copy of a container to another location
+    Copy,
+    /// Fast container info of a list of files
+    Info,
+    /// Byte-for-byte comparison of two images
+    Compare,
+}
+
+/// Scheduling class of a job
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobPriority {
+    /// Started by the user; runs immediately
+    #[default]
+    User,
+    /// Queued work that never runs alongside user jobs
+    Background,
+}
+
+/// Lifecycle of a job
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    /// Background job waiting for its lane or for user jobs to finish
+    Queued,
+    Running,
+    /// Parked by the user at a buffer boundary until resumed
+    Paused,
+    /// Cancellation requested, the job has not stopped yet
+    Cancelling,
+    Completed,
+    Failed,
+    Cancelled,
+    /// Stopped because the device holding the evidence went away
+    Disconnected,
+}
+
+/// Progress of a job when it was last queried
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct JobProgressSnapshot {
+    pub current: u64,
+    /// 0 when the job does not report progress
+    pub total: u64,
+    pub percent: f64,
+    /// Still to be read (`total - current`), in the same unit
+    #[serde(default)]
+    pub remaining: u64,
+}
+
+/// Everything known about one job
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JobInfo {
+    pub job_id: String,
+    pub kind: JobKind,
+    #[serde(default)]
+    pub priority: JobPriority,
+    /// Evidence or files the job works on
+    pub inputs: Vec<String>,
+    /// Thread cap and I/O priority, for jobs that honour them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resources: Option<JobResources>,
+    pub status: JobStatus,
+    /// Whether the job stops when `cancel` is called
+    pub cancellable: bool,
+    /// Whether the job parks when `pause` is called
+    #[serde(default)]
+    pub pausable: bool,
+    pub progress: JobProgressSnapshot,
+    /// RFC 3339, UTC
+    pub started_at: String,
+    pub finished_at: Option<String>,
+    /// Short description of the result (hash, item count, ...)
+    pub result_summary: Option<String>,
+    pub error: Option<String>,
+    /// Reads retried after transient network errors
+    #[serde(default)]
+    pub retries: u64,
+}
+
+struct RunningJob {
+    info: JobInfo,
+    progress: Arc<JobProgress>,
+    cancel: Arc<AtomicBool>,
+    pause: Arc<PauseGate>,
+}
+
+impl RunningJob {
+    fn snapshot(&self) -> JobInfo {
+        let mut info = self.info.clone();
+        let progress = self.progress.snapshot();
+        info.progress = JobProgressSnapshot {
+            current: progress.done,
+            total: progress.total,
+            percent: progress.percent(),
+            remaining: progress.total.saturating_sub(progress.done),
+        };
+        info
+    }
+}
+
+/// A background job waiting on its lane
+struct QueuedJob {
+    handle: JobHandle,
+    work: BackgroundWork,
+}
+
+#[derive(Default)]
+struct Jobs {
+    /// Running and queued jobs
+    running: HashMap<String, RunningJob>,
+    /// Newest first
+    finished: VecDeque<JobInfo>,
+    /// Background jobs per lane, in queue order
+    lanes: HashMap<String, VecDeque<QueuedJob>>,
+    /// Lanes with a worker thread
+    active_lanes: HashSet<String>,
+}
+
+/// Registry of running and recently finished jobs
+///
+/// Cheap to clone; clones share the same jobs. The application uses the
+/// process-wide instance from [`registry`].
+#[derive(Clone, Default)]
+pub struct JobRegistry {
+    jobs: Arc<Mutex<Jobs>>,
+    /// Running user jobs, read by background work without taking the lock
+    user_jobs: Arc<AtomicUsize>,
+}
+
+/// The process-wide job registry
+pub fn registry() -> &'static JobRegistry {
+    static REGISTRY: OnceLock<JobRegistry> = OnceLock::new();
+    REGISTRY.get_or_init(|| {
+        retry::add_observer(Box::new(|event| registry().record_retry(Path::new(&event.path))));
+        JobRegistry::default()
+    })
+}
+
+/// Whether `path` is `input` or another segment of its set (same folder
+/// and file stem, e.g. disk.E02 for disk.E01)
+fn reads_evidence(input: &str, path: &Path) -> bool {
+    let input = Path::new(input);
+    input == path || (input.parent() == path.parent() && input.file_stem() == path.file_stem())
+}
+
+fn now() -> String {
+    Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+impl JobRegistry {
+    fn lock(&self) -> MutexGuard<'_, Jobs> {
+        // A panicking job must not take the registry down with it
+        self.jobs.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Register a job that runs to completion (cannot be cancelled)
+    pub fn start(&self, kind: JobKind, inputs: Vec<String>) -> JobHandle {
+        let job_id = uuid::Uuid::new_v4().to_string();
+        self.register(job_id, kind, inputs, false, JobPriority::User)
+            .expect("fresh job ids are unique")
+    }
+
+    /// Register a job that runs under `resources` (recorded for the job list)
+    pub fn start_with_resources(&self, kind: JobKind, inputs: Vec<String>, resources: JobResources) -> JobHandle {
+        let handle = self.start(kind, inputs);
+        if let Some(job) = self.lock().running.get_mut(handle.id()) {
+            job.info.resources = Some(resources);
+        }
+        handle
+    }
+
+    /// Register a job under a caller-chosen id whose work polls
+    /// [`JobHandle::cancel_flag`]
+    ///
+    /// Fails if a job with the same id is still running (e.g. resuming a
+    /// checkpoint that is already being resumed).
+    pub fn start_cancellable(&self, job_id: &str, kind: JobKind, inputs: Vec<String>) -> Result<JobHandle, String> {
+        self.register(job_id.to_string(), kind, inputs, true, JobPriority::User)
+    }
+
+    /// Queue low-priority work on `lane` and return its job id
+    ///
+    /// Jobs of one lane run one after another on a worker thread, each only
+    /// once no user job is running. Background jobs can be cancelled while
+    /// queued; a started one sees the request through its cancel flag.
+    pub fn queue_background(&self, lane: &str, kind: JobKind, inputs: Vec<String>, work: BackgroundWork) -> String {
+        let job_id = uuid::Uuid::new_v4().to_string();
+        let handle = self.register(job_id.clone(), kind, inputs, true, JobPriority::Background)
+            .expect("fresh job ids are unique");
+        let mut jobs = self.lock();
+        jobs.lanes.entry(lane.to_string()).or_default().push_back(QueuedJob { handle, work });
+        if jobs.active_lanes.insert(lane.to_string()) {
+            let registry = self.clone();
+            let lane = lane.to_string();
+            std::thread::spawn(move || registry.run_lane(&lane));
+        }
+        job_id
+    }
+
+    /// Worker loop of one background lane; exits when the lane is empty
+    fn run_lane(&self, lane: &str) {
+        loop {
+            let next = {
+                let mut jobs = self.lock();
+                let next = jobs.lanes.get_mut(lane).and_then(VecDeque::pop_front);
+                if next.is_none() {
+                    jobs.lanes.remove(lane);
+                    jobs.active_lanes.remove(lane);
+                }
+                next
+            };
+            let Some(QueuedJob { handle, work }) = next else {
+                return;
+            };
+            handle.wait_for_user_jobs();
+            if handle.cancel.load(Ordering::Relaxed) {
+                handle.finish(Ok("Cancelled before it started".to_string()));
+                continue;
+            }
+            self.mark_started(handle.id());
+            let outcome = handle.span().in_scope(|| work(&handle));
+            handle.finish(outcome);
+        }
+    }
+
+    /// Move a queued job to running
+    fn mark_started(&self, job_id: &str) {
+        let mut jobs = self.lock();
+        if let Some(job) = jobs.running.get_mut(job_id) {
+            if job.info.status == JobStatus::Queued {
+                job.info.status = JobStatus::Running;
+            }
+            job.info.started_at = now();
+            job.progress.start();
+            info!(job_id, inputs = ?job.info.inputs, "Background job started");
+        }
+    }
+
+    fn register(&self, job_id: String, kind: JobKind, inputs: Vec<String>, cancellable: bool, priority: JobPriority) -> Result<JobHandle, String> {
+        let mut jobs = self.lock();
+        if jobs.running.contains_key(&job_id) {
+            return Err(format!("Job {job_id} is already running"));
+        }
+        let progress = Arc::new(JobProgress::default());
+        let status = match priority {
+            JobPriority::User => {
+                progress.start();
+                self.user_jobs.fetch_add(1, Ordering::AcqRel);
+                JobStatus::Running
+            }
+            JobPriority::Background => JobStatus::Queued,
+        };
+        let cancel = Arc::new(AtomicBool::new(false));
+        let pause = Arc::new(PauseGate::default());
+        let info = JobInfo {
+            job_id: job_id.clone(),
+            kind,
+            priority,
+            inputs,
+            resources: None,
+            status,
+            cancellable,
+            pausable: false,
+            progress: JobProgressSnapshot::default(),
+            started_at: now(),
+            finished_at: None,
+            result_summary: None,
+            error: None,
+            retries: 0,
+        };
+        info!(job_id = %job_id, ?kind, ?priority, inputs = ?info.inputs, "Job started");
+        jobs.running.insert(job_id.clone(), RunningJob {
+            info,
+            progress: progress.clone(),
+            cancel: cancel.clone(),
+            pause: pause.clone(),
+        });
+        // Not a child of whatever span registered the job
+        let span = tracing::info_span!(parent: None, "job", job_id = %job_id, ?kind);
+        Ok(JobHandle {
+            job_id,
+            span,
+            registry: self.clone(),
+            progress,
+            cancel,
+            pause,
+            priority,
+            finished: false,
+        })
+    }
+
+    /// Running jobs (oldest first) followed by finished jobs (newest first)
+    pub fn list(&self) -> Vec<JobInfo> {
+        let jobs = self.lock();
+        let mut running: Vec<JobInfo> = jobs.running.values().map(RunningJob::snapshot).collect();
+        running.sort_by(|a, b| a.started_at.cmp(&b.started_at));
+        running.extend(jobs.finished.iter().cloned());
+        running
+    }
+
+    pub fn get(&self, job_id: &str) -> Option<JobInfo> {
+        let jobs = self.lock();
+        match jobs.running.get(job_id) {
+            Some(job) => Some(job.snapshot()),
+            None => jobs.finished.iter().find(|info| info.job_id == job_id).cloned(),
+        }
+    }
+
+    /// Count a retried read of `path` against the running jobs reading it
+    pub fn record_retry(&self, path: &Path) {
+        let mut jobs = self.lock();
+        for job in jobs.running.values_mut() {
+            if job.info.inputs.iter().any(|input| reads_evidence(input, path)) {
+                job.info.retries += 1;
+            }
+        }
+    }
+
+    /// A finished job that stopped because its evidence device disconnected
+    pub fn disconnected(&self, job_id: &str) -> Result<JobInfo, String> {
+        match self.get(job_id) {
+            Some(info) if info.status == JobStatus::Disconnected => Ok(info),
+            Some(info) => Err(format!("Job {job_id} is {:?}, not waiting for a reconnect", info.status)),
+            None => Err(format!("Unknown job {job_id}")),
+        }
+    }
+
+    /// Request cancellation of a running job
+    ///
+    /// Returns false if the job is not running. Jobs that do not poll their
+    /// cancel flag are refused rather than silently left running.
+    pub fn cancel(&self, job_id: &str) -> Result<bool, String> {
+        let mut jobs = self.lock();
+        let Some(job) = jobs.running.get_mut(job_id) else {
+            return Ok(false);
+        };
+        if !job.info.cancellable {
+            return Err(format!("Job {job_id} ({:?}) cannot be cancelled", job.info.kind));
+        }
+        job.cancel.store(true, Ordering::Relaxed);
+        // A paused job has to wake up to see the request
+        job.pause.resume();
+        job.info.status = JobStatus::Cancelling;
+        info!(job_id, "Job cancellation requested");
+        Ok(true)
+    }
+
+    /// Park a running job at its next buffer boundary
+    ///
+    /// Returns false if the job is not running (or already paused). Jobs
+    /// whose current work has no pause points are refused.
+    pub fn pause(&self, job_id: &str) -> Result<bool, String> {
+        let mut jobs = self.lock();
+        let Some(job) = jobs.running.get_mut(job_id) else {
+            return Ok(false);
+        };
+        if !job.info.pausable {
+            return Err(format!("Job {job_id} ({:?}) cannot be paused", job.info.kind));
+        }
+        if job.info.status != JobStatus::Running || !job.pause.pause() {
+            return Ok(false);
+        }
+        job.info.status = JobStatus::Paused;
+        info!(job_id, "Job paused");
+        Ok(true)
+    }
+
+    /// Let a paused job carry on; returns false if it was not paused
+    pub fn resume(&self, job_id: &str) -> Result<bool, String> {
+        let mut jobs = self.lock();
+        let Some(job) = jobs.running.get_mut(job_id) else {
+            return Ok(false);
+        };
+        if job.info.status != JobStatus::Paused || !job.pause.resume() {
+            return Ok(false);
+        }
+        job.info.status = JobStatus::Running;
+        info!(job_id, "Job resumed");
+        Ok(true)
+    }
+
+    /// Allow or stop pausing a running job, resuming it when pausing ends
+    fn set_pausable(&self, job_id: &str, pausable: bool) {
+        let mut jobs = self.lock();
+        if let Some(job) = jobs.running.get_mut(job_id) {
+            job.info.pausable = pausable;
+            if !pausable && job.pause.resume() && job.info.status == JobStatus::Paused {
+                job.info.status = JobStatus::Running;
+            }
+        }
+    }
+
+    fn finish(&self, job_id: &str, outcome: Result<String, String>) {
+        let mut jobs = self.lock();
+        let Some(job) = jobs.running.remove(job_id) else {
+            return;
+        };
+        if job.info.priority == JobPriority::User {
+            self.user_jobs.fetch_sub(1, Ordering::AcqRel);
+        }
+        let cancelled = job.cancel.load(Ordering::Relaxed);
+        if outcome.is_ok() && !cancelled {
+            job.progress.finish();
+        }
+        let mut info = job.snapshot();
+        info.finished_at = Some(now());
+        match outcome {
+            Ok(summary) => {
+                info.status = if cancelled { JobStatus::Cancelled } else { JobStatus::Completed };
+                info.result_summary = Some(summary);
+            }
+            Err(error) => {
+                info.status = if cancelled {
+                    JobStatus::Cancelled
+                } else if device::is_device_disconnected(&error) {
+                    JobStatus::Disconnected
+                } else {
+                    JobStatus::Failed
+                };
+                info.error = Some(error);
+            }
+        }
+        info!(job_id, status = ?info.status, "Job finished");
+
+        jobs.finished.push_front(info);
+        jobs.finished.truncate(MAX_FINISHED_JOBS);
+    }
+}
+
+/// Check that the evidence of a job is reachable again before retrying it
+///
+/// Every input must exist and its segment set must have no gaps - a
+/// reconnected drive that mounted elsewhere, or only partly, is refused.
+pub fn revalidate_inputs(inputs: &[String]) -> Result<(), String> {
+    for input in inputs {
+        if !std::path::Path::new(input).exists() {
+            return Err(format!("{input} is still not available - reconnect the device"));
+        }
+        if let Some(set) = crate::containers::segment_set_info(input, None) {
+            if !set.missing.is_empty() {
+                return Err(format!("{input} is missing segments after reconnecting: {}", set.missing.join(", ")));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// A running job's connection to the registry
+///
+/// Dropping the handle without calling [`JobHandle::finish`] (e.g. when the
+/// work panicked) records the job as failed.
+pub struct JobHandle {
+    job_id: String,
+    span: tracing::Span,
+    registry: JobRegistry,
+    progress: Arc<JobProgress>,
+    cancel: Arc<AtomicBool>,
+    pause: Arc<PauseGate>,
+    priority: JobPriority,
+    finished: bool,
+}
+
+impl JobHandle {
+    pub fn id(&self) -> &str {
+        &self.job_id
+    }
+
+    /// Span the job's work runs in; phase spans opened inside it are timed
+    /// per job by [`crate::logging::perf`]
+    pub fn span(&self) -> &tracing::Span {
+        &self.span
+    }
+
+    /// Record progress - cheap enough to call once per buffer
+    pub fn progress(&self, current: u64, total: u64) {
+        self.progress.update(current, total);
+    }
+
+    /// Set by [`JobRegistry::cancel`]; cancellable work polls this
+    pub fn cancel_flag(&self) -> &AtomicBool {
+        &self.cancel
+    }
+
+    /// Run `work` as pausable: [`JobRegistry::pause`] parks it at the next
+    /// [`progress::pause_point`] on this thread (or on threads it hands the
+    /// gate to via [`progress::current_pause_gate`])
+    pub fn pausable<T>(&self, work: impl FnOnce() -> T) -> T {
+        self.registry.set_pausable(&self.job_id, true);
+        let result = progress::with_pause_gate(self.pause.clone(), work);
+        self.registry.set_pausable(&self.job_id, false);
+        result
+    }
+
+    /// Block a background job while any user job runs (returns at once
+    /// for user jobs, or when cancellation is requested)
+    ///
+    /// Background work calls this between files and from its progress
+    /// callback, so a user job started mid-way gets the device to itself.
+    pub fn wait_for_user_jobs(&self) {
+        if self.priority == JobPriority::User {
+            return;
+        }
+        while self.registry.user_jobs.load(Ordering::Acquire) > 0 && !self.cancel.load(Ordering::Relaxed) {
+            std::thread::sleep(BACKGROUND_POLL_INTERVAL);
+        }
+    }
+
+    /// Move the job to the finished history
+    ///
+    /// `Ok` carries a short result summary. A job whose cancellation was
+    /// requested is recorded as cancelled either way.
+    pub fn finish(mut self, outcome: Result<String, String>) {
+        self.finished = true;
+        self.registry.finish(&self.job_id, outcome);
+    }
+}
+
+impl Drop for JobHandle {
+    fn drop(&mut self) {
+        if !self.finished {
+            debug!(job_id = %self.job_id, "Job handle dropped without a result");
+            self.registry.finish(&self.job_id, Err("Job ended without reporting a result".to_string()));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_job_lifecycle_and_history() {
+        let registry = JobRegistry::default();
+
+        let verify = registry.start(JobKind::Verify, vec!["/evidence/disk.E01".to_string()]);
+        verify.progress(25, 100);
+        let info = registry.get(verify.id()).unwrap();
+        assert_eq!(info.status, JobStatus::Running);
+        assert_eq!(info.progress.percent, 25.0);
+        assert!(registry.cancel(verify.id()).is_err());
+        assert_eq!(info.resources, None);
+
+        let limited = JobResources { threads: Some(2), ..Default::default() };
+        let other = JobRegistry::default();
+        let capped = other.start_with_resources(JobKind::BatchHash, vec![], limited);
+        assert_eq!(other.get(capped.id()).unwrap().resources, Some(limited));
+
+        let resumable = registry.start_cancellable("job-1", JobKind::Verify, vec![]).unwrap();
+        assert!(registry.start_cancellable("job-1", JobKind::Verify, vec![]).is_err());
+        assert!(registry.cancel("job-1").unwrap());
+        assert!(resumable.cancel_flag().load(Ordering::Relaxed));
+        assert_eq!(registry.get("job-1").unwrap().status, JobStatus::Cancelling);
+        resumable.finish(Ok("cancelled at 40%".to_string()));
+
+        let id = verify.id().to_string();
+        verify.finish(Ok("d41d8cd98f00b204e9800998ecf8427e".to_string()));
+        let info = registry.get(&id).unwrap();
+        assert_eq!(info.status, JobStatus::Completed);
+        assert_eq!(info.progress.percent, 100.0);
+        assert!(info.finished_at.is_some());
+
+        // Dropped without a result (panicked worker)
+        let extract = registry.start(JobKind::Extract, vec![]);
+        let extract_id = extract.id().to_string();
+        drop(extract);
+
+        let list = registry.list();
+        assert_eq!(list.iter().map(|j| j.status).collect::<Vec<_>>(),
+            vec![JobStatus::Failed, JobStatus::Completed, JobStatus::Cancelled]);
+        assert_eq!(list[0].job_id, extract_id);
+        assert!(!registry.cancel(&id).unwrap());
+    }
+
+    #[test]
+    fn test_device_loss_marks_job_disconnected() {
+        let registry = JobRegistry::default();
+        let dir = tempfile::tempdir().unwrap();
+        let first = dir.path().join("disk.001");
+        std::fs::write(&first, b"seg1").unwrap();
+        let input = first.to_string_lossy().to_string();
+
+        let job = registry.start(JobKind::Verify, vec![input.clone()]);
+        let id = job.id().to_string();
+        assert!(registry.disconnected(&id).is_err());
+        // Retries on other segments of the set count, other evidence doesn't
+        registry.record_retry(&dir.path().join("disk.002"));
+        registry.record_retry(Path::new("/other/disk.002"));
+        assert_eq!(registry.get(&id).unwrap().retries, 1);
+        job.finish(Err(device::device_disconnected(&first, "Input/output error")));
+
+        let info = registry.disconnected(&id).unwrap();
+        assert_eq!(info.status, JobStatus::Disconnected);
+        assert!(revalidate_inputs(&info.inputs).is_ok());
+
+        // A gap in the set (or a missing input) blocks the retry
+        std::fs::write(dir.path().join("disk.003"), b"seg3").unwrap();
+        assert!(revalidate_inputs(&info.inputs).unwrap_err().contains("disk.002"));
+        std::fs::remove_file(&first).unwrap();
+        assert!(revalidate_inputs(&info.inputs).is_err());
+    }
+
+    #[test]
+    fn test_background_jobs_wait_for_user_jobs() {
+        let registry = JobRegistry::default();
+        let order = Arc::new(Mutex::new(Vec::new()));
+        let queue = |name: &'static str| {
+            let order = order.clone();
+            registry.queue_background("device-1", JobKind::Verify, vec![name.to_string()], Box::new(move |job| {
+                job.wait_for_user_jobs();
+                order.lock().unwrap().push(name);
+                Ok(name.to_string())
+            }))
+        };
+
+        let user = registry.start(JobKind::Verify, vec!["/evidence/disk.E01".to_string()]);
+        let first = queue("first");
+        let second = queue("second");
+        let skipped = queue("skipped");
+        std::thread::sleep(BACKGROUND_POLL_INTERVAL * 2);
+        assert!(order.lock().unwrap().is_empty());
+        assert_eq!(registry.get(&first).unwrap().status, JobStatus::Queued);
+        assert_eq!(registry.get(&first).unwrap().priority, JobPriority::Background);
+        assert!(registry.cancel(&skipped).unwrap());
+
+        user.finish(Ok(String::new()));
+        let deadline = std::time::Instant::now() + Duration::from_secs(10);
+        while registry.get(&skipped).unwrap().finished_at.is_none() {
+            assert!(std::time::Instant::now() < deadline, "background lane did not drain");
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        assert_eq!(*order.lock().unwrap(), ["first", "second"]);
+        assert_eq!(registry.get(&second).unwrap().status, JobStatus::Completed);
+        assert_eq!(registry.get(&skipped).unwrap().status, JobStatus::Cancelled);
+    }
+
+    #[test]
+    fn test_finished_history_is_bounded() {
+        let registry = JobRegistry::default();
+        for _ in 0..MAX_FINISHED_JOBS + 5 {
+            registry.start(JobKind::Hash, vec![]).finish(Err("unreadable".to_string()));
+        }
+        let list = registry.list();
+        assert_eq!(list.len(), MAX_FINISHED_JOBS);
+        assert!(list.iter().all(|job| job.status == JobStatus::Failed));
+    }
+}
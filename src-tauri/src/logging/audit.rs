@@ -0,0 +1,548 @@
+//! Tamper-evident audit log of operations on evidence
+//!
+//! When enabled in the settings, every evidence operation started from the
+//! app appends one JSON line to `audit.jsonl` in the app data folder: when
+//! it started, which files it touched with which parameters, its outcome
+//! (summary, hash or error), how long it took, the app version and the OS
+//! user.
+//!
+//! Each line holds the SHA-256 of its own content and the hash of the line
+//! before it, so editing, removing or reordering lines breaks the chain
+//! ([`verify_chain`]). Exports end with an integrity tag line when those
+//! are on in the settings (see `common::integrity`). Lines are written by a background thread: finishing
+//! an [`Operation`] only queues its entry, so hashing threads never wait on
+//! audit I/O.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{OnceLock, RwLock};
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use sha2::{Digest, Sha256};
+use tracing::{debug, info, warn};
+
+use crate::common::atomic_file::AtomicFile;
+use crate::common::integrity;
+
+/// File name of the audit log in the app data folder
+pub const AUDIT_FILE_NAME: &str = "audit.jsonl";
+
+/// `prev_hash` of the first entry of a log
+pub const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// How long [`flush`] waits for the writer thread
+const FLUSH_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// One line of the audit log
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AuditEntry {
+    /// Position in the log, counting from 0
+    pub seq: u64,
+    /// When the operation started (RFC 3339, UTC)
+    pub timestamp: String,
+    /// Command that ran, e.g. "e01_v3_verify"
+    pub operation: String,
+    pub inputs: Vec<String>,
+    pub parameters: Map<String, Value>,
+    pub ok: bool,
+    /// Result summary (the hash, for hashing operations) or the error
+    pub result: String,
+    pub duration_ms: u64,
+    pub app_version: String,
+    /// OS account the app runs under
+    pub user: String,
+    /// `hash` of the previous line ([`GENESIS_HASH`] for the first)
+    pub prev_hash: String,
+    /// SHA-256 of this line serialized without `hash`
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub hash: String,
+}
+
+impl AuditEntry {
+    fn content_hash(&self) -> String {
+        let unsealed = AuditEntry { hash: String::new(), ..self.clone() };
+        let json = serde_json::to_vec(&unsealed).expect("audit entries always serialize");
+        hex::encode(Sha256::digest(&json))
+    }
+}
+
+/// Time range of entries to export; open ends are unbounded
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AuditRange {
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+}
+
+impl AuditRange {
+    fn contains(&self, entry: &AuditEntry) -> bool {
+        let Ok(started) = DateTime::parse_from_rfc3339(&entry.timestamp) else { return false };
+        self.from.is_none_or(|from| started >= from) && self.to.is_none_or(|to| started <= to)
+    }
+}
+
+/// Outcome of [`verify_chain`]
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ChainReport {
+    pub path: String,
+    /// Entries checked before the first problem
+    pub entries: u64,
+    pub valid: bool,
+    /// The first entry links to [`GENESIS_HASH`]. False for exports of a
+    /// later range - and for a log whose first lines were removed.
+    pub anchored: bool,
+    /// 1-based line of the first problem
+    pub broken_line: Option<u64>,
+    pub error: Option<String>,
+    /// Hash of the last valid entry
+    pub last_hash: Option<String>,
+}
+
+/// Stands in for secret parameter values
+pub const REDACTED: &str = "[redacted]";
+
+/// Parameter names whose values never reach the log (passwords of encrypted
+/// archives and the like)
+fn is_secret(key: &str) -> bool {
+    let key = key.to_ascii_lowercase();
+    ["password", "passphrase", "secret"].iter().any(|word| key.contains(word))
+}
+
+/// An operation being audited; [`Operation::finish`] records it
+pub struct Operation {
+    name: &'static str,
+    inputs: Vec<String>,
+    parameters: Map<String, Value>,
+    started_at: DateTime<Utc>,
+    started: Instant,
+}
+
+impl Operation {
+    /// Start timing `name` on `inputs`
+    pub fn new(name: &'static str, inputs: Vec<String>) -> Self {
+        Self { name, inputs, parameters: Map::new(), started_at: Utc::now(), started: Instant::now() }
+    }
+
+    /// Add a parameter to the entry (skipped if it doesn't serialize)
+    ///
+    /// Secrets (see [`is_secret`]) are recorded only as given or not given.
+    pub fn param(mut self, key: &str, value: impl Serialize) -> Self {
+        if let Ok(value) = serde_json::to_value(value) {
+            let value = match value {
+                Value::Null => Value::Null,
+                _ if is_secret(key) => Value::String(REDACTED.to_string()),
+                value => value,
+            };
+            self.parameters.insert(key.to_string(), value);
+        }
+        self
+    }
+
+    pub fn inputs(&self) -> &[String] {
+        &self.inputs
+    }
+
+    /// Queue the entry: result summary on success, otherwise the error
+    ///
+    /// Does nothing while the audit log is disabled.
+    pub fn finish(self, outcome: Result<String, String>) {
+        let Some(sender) = sender() else { return };
+        let (ok, result) = match outcome {
+            Ok(summary) => (true, summary),
+            Err(error) => (false, error),
+        };
+        let entry = AuditEntry {
+            seq: 0,
+            timestamp: self.started_at.to_rfc3339_opts(SecondsFormat::Millis, true),
+            operation: self.name.to_string(),
+            inputs: self.inputs,
+            parameters: self.parameters,
+            ok,
+            result,
+            duration_ms: self.started.elapsed().as_millis() as u64,
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+            user: os_user().to_string(),
+            prev_hash: String::new(),
+            hash: String::new(),
+        };
+        if sender.send(Message::Record(Box::new(entry))).is_err() {
+            warn!(operation = self.name, "Audit log writer stopped, entry dropped");
+        }
+    }
+
+    /// [`finish`](Self::finish) with `result` described by `summarize`
+    pub fn record<T>(self, result: &Result<T, String>, summarize: impl FnOnce(&T) -> String) {
+        self.finish(result.as_ref().map(summarize).map_err(Clone::clone));
+    }
+}
+
+enum Message {
+    Record(Box<AuditEntry>),
+    Flush(mpsc::SyncSender<()>),
+}
+
+struct AuditLog {
+    path: PathBuf,
+    sender: Sender<Message>,
+}
+
+static LOG: RwLock<Option<AuditLog>> = RwLock::new(None);
+
+fn sender() -> Option<Sender<Message>> {
+    LOG.read().unwrap_or_else(|poisoned| poisoned.into_inner()).as_ref().map(|log| log.sender.clone())
+}
+
+/// Audit log in the app data folder (next to the database)
+pub fn default_path() -> PathBuf {
+    dirs::data_local_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("com.ffxcheck.app")
+        .join(AUDIT_FILE_NAME)
+}
+
+/// Start appending entries to `path`, continuing its chain
+pub fn enable(path: &Path) -> Result<(), String> {
+    if current_path().as_deref() == Some(path) {
+        return Ok(());
+    }
+    disable();
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create audit log folder: {}", e))?;
+    }
+    let file = OpenOptions::new().create(true).append(true).open(path)
+        .map_err(|e| format!("Failed to open audit log {}: {}", path.display(), e))?;
+    let tail = ChainTail::read(path)?;
+    let (sender, receiver) = mpsc::channel();
+    std::thread::Builder::new()
+        .name("audit-log".to_string())
+        .spawn(move || write_entries(file, tail, receiver))
+        .map_err(|e| format!("Failed to start audit log writer: {}", e))?;
+    info!(path = %path.display(), "Audit log enabled");
+    *LOG.write().unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(AuditLog { path: path.to_path_buf(), sender });
+    Ok(())
+}
+
+/// Stop recording, after writing the entries already queued
+pub fn disable() {
+    let previous = LOG.write().unwrap_or_else(|poisoned| poisoned.into_inner()).take();
+    if let Some(log) = previous {
+        flush_sender(&log.sender);
+        info!(path = %log.path.display(), "Audit log disabled");
+    }
+}
+
+pub fn is_enabled() -> bool {
+    current_path().is_some()
+}
+
+/// File entries are written to while enabled
+pub fn current_path() -> Option<PathBuf> {
+    LOG.read().unwrap_or_else(|poisoned| poisoned.into_inner()).as_ref().map(|log| log.path.clone())
+}
+
+/// Wait until queued entries are on disk
+pub fn flush() {
+    if let Some(sender) = sender() {
+        flush_sender(&sender);
+    }
+}
+
+fn flush_sender(sender: &Sender<Message>) {
+    let (done, flushed) = mpsc::sync_channel(1);
+    if sender.send(Message::Flush(done)).is_ok() && flushed.recv_timeout(FLUSH_TIMEOUT).is_err() {
+        warn!("Audit log writer did not flush in time");
+    }
+}
+
+/// Sequence number and hash the next entry continues from
+struct ChainTail {
+    seq: u64,
+    prev_hash: String,
+}
+
+impl ChainTail {
+    fn genesis() -> Self {
+        Self { seq: 0, prev_hash: GENESIS_HASH.to_string() }
+    }
+
+    /// Continue after the last line of an existing log
+    ///
+    /// A damaged last line is chained by the hash of its raw text;
+    /// [`verify_chain`] still reports the damage.
+    fn read(path: &Path) -> Result<Self, String> {
+        let file = File::open(path).map_err(|e| format!("Failed to read audit log: {}", e))?;
+        let mut count = 0u64;
+        let mut last = None;
+        for line in BufReader::new(file).lines() {
+            let line = line.map_err(|e| format!("Failed to read audit log: {}", e))?;
+            count += 1;
+            last = Some(line);
+        }
+        Ok(match last {
+            None => Self::genesis(),
+            Some(line) => match serde_json::from_str::<AuditEntry>(&line) {
+                Ok(entry) => Self { seq: entry.seq + 1, prev_hash: entry.hash },
+                Err(_) => Self { seq: count, prev_hash: hex::encode(Sha256::digest(line.as_bytes())) },
+            },
+        })
+    }
+
+    /// Number `entry` and link it to the previous one
+    fn seal(&mut self, entry: &mut AuditEntry) {
+        entry.seq = self.seq;
+        entry.prev_hash = self.prev_hash.clone();
+        entry.hash = entry.content_hash();
+        self.seq += 1;
+        self.prev_hash = entry.hash.clone();
+    }
+}
+
+fn write_entries(file: File, mut tail: ChainTail, receiver: Receiver<Message>) {
+    let mut out = BufWriter::new(file);
+    for message in receiver {
+        match message {
+            Message::Record(mut entry) => {
+                tail.seal(&mut entry);
+                let written = serde_json::to_writer(&mut out, &*entry)
+                    .map_err(io::Error::from)
+                    .and_then(|_| out.write_all(b"\n"))
+                    .and_then(|_| out.flush());
+                if let Err(e) = written {
+                    warn!(operation = %entry.operation, "Failed to write audit entry: {}", e);
+                }
+            }
+            Message::Flush(done) => {
+                let _ = out.flush();
+                let _ = done.send(());
+            }
+        }
+    }
+    debug!("Audit log writer stopped");
+}
+
+/// Check the hash chain of a log (or an export of one)
+pub fn verify_chain(path: &Path) -> Result<ChainReport, String> {
+    if current_path().as_deref() == Some(path) {
+        flush();
+    }
+    let file = File::open(path).map_err(|e| format!("Failed to open audit log {}: {}", path.display(), e))?;
+    let mut report = ChainReport {
+        path: path.display().to_string(),
+        entries: 0,
+        valid: true,
+        anchored: true,
+        broken_line: None,
+        error: None,
+        last_hash: None,
+    };
+    let mut previous: Option<AuditEntry> = None;
+    for (index, line) in BufReader::new(file).lines().enumerate() {
+        let line = line.map_err(|e| format!("Failed to read audit log: {}", e))?;
+        // Checked on its own by `integrity::verify_file`
+        if integrity::is_tag_line(&line) {
+            continue;
+        }
+        let problem = match serde_json::from_str::<AuditEntry>(&line) {
+            Err(e) => Some(format!("not an audit entry: {}", e)),
+            Ok(entry) => {
+                let problem = match &previous {
+                    None => {
+                        report.anchored = entry.prev_hash == GENESIS_HASH;
+                        (report.anchored && entry.seq != 0).then(|| format!("first entry has sequence number {}", entry.seq))
+                    }
+                    Some(prev) if entry.seq != prev.seq + 1 => Some(format!("sequence jumps from {} to {}", prev.seq, entry.seq)),
+                    Some(prev) if entry.prev_hash != prev.hash => Some("does not link to the previous entry".to_string()),
+                    Some(_) => None,
+                };
+                let problem = problem.or_else(|| (entry.hash != entry.content_hash()).then(|| "content does not match its hash".to_string()));
+                if problem.is_none() {
+                    report.entries += 1;
+                    report.last_hash = Some(entry.hash.clone());
+                    previous = Some(entry);
+                }
+                problem
+            }
+        };
+        if let Some(problem) = problem {
+            report.valid = false;
+            report.broken_line = Some(index as u64 + 1);
+            report.error = Some(format!("Line {}: {}", index + 1, problem));
+            break;
+        }
+    }
+    Ok(report)
+}
+
+/// Copy the entries of `range` to `output` unchanged, so the export can be
+/// checked with [`verify_chain`] on its own; returns the number copied
+pub fn export(path: &Path, range: &AuditRange, output: &Path) -> Result<usize, String> {
+    if current_path().as_deref() == Some(path) {
+        flush();
+    }
+    let file = File::open(path).map_err(|e| format!("Failed to open audit log {}: {}", path.display(), e))?;
+    let mut tagger = integrity::enabled_key()?.map(|key| key.text_tagger());
+    let mut out = AtomicFile::create(output).map_err(|e| format!("Failed to create {}: {}", output.display(), e))?;
+    let write_error = |e: io::Error| format!("Failed to write {}: {}", output.display(), e);
+    let mut exported = 0;
+    for line in BufReader::new(file).lines() {
+        let line = line.map_err(|e| format!("Failed to read audit log: {}", e))?;
+        let Ok(entry) = serde_json::from_str::<AuditEntry>(&line) else { continue };
+        if range.contains(&entry) {
+            let line = format!("{}\n", line);
+            out.write_all(line.as_bytes()).map_err(write_error)?;
+            if let Some(tagger) = &mut tagger {
+                tagger.update(line.as_bytes());
+            }
+            exported += 1;
+        }
+    }
+    if let Some(tagger) = &tagger {
+        out.write_all(tagger.tag_line().as_bytes()).map_err(write_error)?;
+    }
+    out.commit().map_err(|e| format!("Failed to write {}: {}", output.display(), e))?;
+    info!(output = %output.display(), exported, "Audit log exported");
+    Ok(exported)
+}
+
+fn os_user() -> &'static str {
+    static USER: OnceLock<String> = OnceLock::new();
+    USER.get_or_init(|| {
+        std::env::var("USER")
+            .or_else(|_| std::env::var("USERNAME"))
+            .unwrap_or_else(|_| "unknown".to_string())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(operation: &str, timestamp: &str) -> AuditEntry {
+        AuditEntry {
+            seq: 0,
+            timestamp: timestamp.to_string(),
+            operation: operation.to_string(),
+            inputs: vec!["/evidence/disk.E01".to_string()],
+            parameters: Map::from_iter([("algorithm".to_string(), Value::from("sha256"))]),
+            ok: true,
+            result: "9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08".to_string(),
+            duration_ms: 1200,
+            app_version: "1.0.0".to_string(),
+            user: "examiner".to_string(),
+            prev_hash: String::new(),
+            hash: String::new(),
+        }
+    }
+
+    fn write_log(path: &Path, entries: &mut [AuditEntry]) -> Vec<String> {
+        let mut tail = ChainTail::genesis();
+        let lines: Vec<String> = entries.iter_mut()
+            .map(|entry| {
+                tail.seal(entry);
+                serde_json::to_string(entry).unwrap()
+            })
+            .collect();
+        fs::write(path, lines.join("\n") + "\n").unwrap();
+        lines
+    }
+
+    #[test]
+    fn test_secret_parameters_are_redacted() {
+        let op = Operation::new("ufed_verify_contents", vec!["/evidence/phone.zip".into()])
+            .param("password", Some("hunter2"))
+            .param("zipPassword", None::<String>)
+            .param("algorithm", "sha256");
+        assert_eq!(op.parameters["password"], Value::from(REDACTED));
+        assert_eq!(op.parameters["zipPassword"], Value::Null);
+        assert_eq!(op.parameters["algorithm"], Value::from("sha256"));
+        assert!(!serde_json::to_string(&op.parameters).unwrap().contains("hunter2"));
+    }
+
+    #[test]
+    fn test_recorded_operations_form_a_chain() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(AUDIT_FILE_NAME);
+
+        enable(&path).unwrap();
+        Operation::new("e01_v3_verify", vec!["/evidence/disk.E01".into()]).param("algorithm", "sha256").finish(Ok("abc123".into()));
+        Operation::new("logical_info", vec!["/evidence/disk.E01".into()]).finish(Err("Not an E01".into()));
+        disable();
+        Operation::new("ignored", Vec::new()).finish(Ok(String::new()));
+        // Reopening continues the existing chain
+        enable(&path).unwrap();
+        Operation::new("raw_info", vec!["/evidence/disk.dd".into()]).finish(Ok("RAW".into()));
+        disable();
+
+        let report = verify_chain(&path).unwrap();
+        assert!(report.valid && report.anchored, "{:?}", report.error);
+        assert_eq!(report.entries, 3);
+        let entries: Vec<AuditEntry> = fs::read_to_string(&path).unwrap().lines().map(|l| serde_json::from_str(l).unwrap()).collect();
+        assert_eq!(entries.iter().map(|e| e.seq).collect::<Vec<_>>(), [0, 1, 2]);
+        assert_eq!(entries[0].parameters["algorithm"], "sha256");
+        assert!(!entries[1].ok && entries[1].result == "Not an E01");
+    }
+
+    #[test]
+    fn test_edits_and_removed_lines_break_the_chain() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(AUDIT_FILE_NAME);
+        let mut entries = [
+            entry("e01_v3_verify", "2026-01-12T09:00:00.000Z"),
+            entry("logical_extract", "2026-01-12T10:00:00.000Z"),
+            entry("raw_verify", "2026-01-12T11:00:00.000Z"),
+        ];
+        let lines = write_log(&path, &mut entries);
+        assert!(verify_chain(&path).unwrap().valid);
+
+        fs::write(&path, [lines[0].clone(), lines[1].replace("9f86d0", "000000"), lines[2].clone()].join("\n")).unwrap();
+        let edited = verify_chain(&path).unwrap();
+        assert_eq!((edited.valid, edited.entries, edited.broken_line), (false, 1, Some(2)));
+
+        fs::write(&path, [lines[0].clone(), lines[2].clone()].join("\n")).unwrap();
+        assert_eq!(verify_chain(&path).unwrap().broken_line, Some(2));
+
+        fs::write(&path, lines[1..].join("\n")).unwrap();
+        let truncated = verify_chain(&path).unwrap();
+        assert!(truncated.valid && !truncated.anchored);
+    }
+
+    #[test]
+    fn test_tagged_export_still_verifies_as_a_chain() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("export.jsonl");
+        let mut entries = [entry("e01_v3_verify", "2026-01-12T09:00:00.000Z"), entry("raw_verify", "2026-01-12T11:00:00.000Z")];
+        let lines = write_log(&path, &mut entries);
+        let key = integrity::IntegrityKey::from_bytes([3; 32]);
+        fs::write(&path, integrity::tag_text(&key, &lines.join("\n"))).unwrap();
+
+        let report = verify_chain(&path).unwrap();
+        assert!(report.valid && report.anchored, "{:?}", report.error);
+        assert_eq!(report.entries, 2);
+        assert!(integrity::verify_file(&path, &key).unwrap().valid);
+    }
+
+    #[test]
+    fn test_export_keeps_range_verifiable() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(AUDIT_FILE_NAME);
+        let mut entries = [
+            entry("e01_v3_verify", "2026-01-12T09:00:00.000Z"),
+            entry("logical_extract", "2026-01-12T10:00:00.000Z"),
+            entry("raw_verify", "2026-01-12T11:00:00.000Z"),
+        ];
+        write_log(&path, &mut entries);
+
+        let range: AuditRange = serde_json::from_str(r#"{"from": "2026-01-12T09:30:00Z"}"#).unwrap();
+        let output = dir.path().join("export.jsonl");
+        assert_eq!(export(&path, &range, &output).unwrap(), 2);
+        let report = verify_chain(&output).unwrap();
+        assert!(report.valid && !report.anchored);
+        assert_eq!(report.last_hash.as_deref(), Some(entries[2].hash.as_str()));
+    }
+}
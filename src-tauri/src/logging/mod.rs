@@ -40,10 +40,19 @@
 //! RUST_LOG=warn ./ffx-check           # Only warnings and errors
 //! RUST_LOG=ewf=debug,ad1=info ./ffx-check  # Per-module control
 //! ```
+//!
+//! # Job Timing
+//!
+//! [`perf`] times the phases of every job from its info-level spans, so
+//! `get_job_perf` has no data for jobs run while the level is `warn` or
+//! `error`.
 
 use tracing::Level;
 use tracing_subscriber::{fmt, EnvFilter, prelude::*};
 
+pub mod audit;
+pub mod perf;
+
 /// Initialize the logging/tracing system
 /// 
 /// Call this once at application startup (in main.rs)
@@ -69,7 +78,8 @@ pub fn init() {
                 .with_file(false)       // Hide file:line in normal mode
                 .with_line_number(false)
                 .compact()              // Compact format
-        );
+        )
+        .with(perf::PerfLayer::new(perf::store().clone()));
     
     // Set as global default (ignore error if already set)
     let _ = tracing::subscriber::set_global_default(subscriber);
@@ -90,7 +100,8 @@ pub fn init_verbose() {
                 .with_file(true)
                 .with_line_number(true)
                 .pretty()  // Pretty multi-line format
-        );
+        )
+        .with(perf::PerfLayer::new(perf::store().clone()));
     
     let _ = tracing::subscriber::set_global_default(subscriber);
 }
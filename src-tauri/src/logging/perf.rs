@@ -0,0 +1,337 @@
+//! Per-job timing of container operations, for "where did the time go?"
+//!
+//! Long operations open a span per phase with [`phase`] - segment discovery,
+//! section/item parsing, decompression, hashing, writing - and record the
+//! bytes it handled in its `bytes` field when it ends. Each job runs inside
+//! its own `job` span (see [`crate::jobs::JobHandle::span`]); the
+//! [`PerfLayer`] installed by [`super::init`] adds every closed phase span to
+//! its job's totals in memory, and [`job_perf`] returns them phase by phase.
+//!
+//! Spans are opened per phase or per batch, never per chunk, and byte counts
+//! are recorded once at the end, so the layer costs nothing measurable. A
+//! phase span outside any job is ignored. Phases that run in parallel (I/O
+//! and hashing in a pipeline) overlap, so their times can add up to more
+//! than the job took.
+
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Span, Subscriber};
+use tracing_subscriber::layer::{Context, Layer};
+use tracing_subscriber::registry::LookupSpan;
+
+/// Jobs whose timings are kept (the oldest are dropped first)
+pub const MAX_PERF_JOBS: usize = 100;
+
+/// Phase names used across the container modules
+pub const SEGMENT_DISCOVERY: &str = "segment_discovery";
+pub const PARSING: &str = "parsing";
+pub const DECOMPRESSION: &str = "decompression";
+pub const HASHING: &str = "hashing";
+pub const WRITING: &str = "writing";
+
+/// Open a span for one phase of the current job; record the bytes it
+/// handled with `span.record("bytes", n)` before it ends
+///
+/// ```rust,ignore
+/// let span = perf::phase(perf::HASHING).entered();
+/// // ... hash ...
+/// span.record("bytes", total_size);
+/// ```
+pub fn phase(name: &'static str) -> Span {
+    tracing::info_span!("phase", phase = name, bytes = tracing::field::Empty)
+}
+
+/// Time spent in one phase of a job
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PhaseTiming {
+    pub phase: String,
+    /// Spans of this phase that ended (e.g. one per batch)
+    pub spans: u64,
+    /// Summed duration of those spans
+    pub elapsed_ms: f64,
+    pub bytes: u64,
+    /// `bytes` over `elapsed_ms`, when both are known
+    pub throughput_mbs: Option<f64>,
+    /// When the phase first started and last ended, from the job's start
+    pub first_start_ms: f64,
+    pub last_end_ms: f64,
+}
+
+/// Phase-by-phase timing of one job, phases in the order they started
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct JobPerf {
+    pub job_id: String,
+    /// Wall time of the whole job, once it has finished
+    pub total_ms: Option<f64>,
+    pub phases: Vec<PhaseTiming>,
+}
+
+struct PhaseTotals {
+    spans: u64,
+    elapsed: Duration,
+    bytes: u64,
+    first_start: Duration,
+    last_end: Duration,
+}
+
+struct JobTotals {
+    started: Instant,
+    total: Option<Duration>,
+    /// In order of first start
+    phases: Vec<(String, PhaseTotals)>,
+}
+
+impl JobTotals {
+    fn report(&self, job_id: &str) -> JobPerf {
+        let ms = |duration: Duration| duration.as_secs_f64() * 1000.0;
+        JobPerf {
+            job_id: job_id.to_string(),
+            total_ms: self.total.map(ms),
+            phases: self.phases.iter()
+                .map(|(phase, totals)| PhaseTiming {
+                    phase: phase.clone(),
+                    spans: totals.spans,
+                    elapsed_ms: ms(totals.elapsed),
+                    bytes: totals.bytes,
+                    throughput_mbs: (totals.bytes > 0 && !totals.elapsed.is_zero())
+                        .then(|| totals.bytes as f64 / totals.elapsed.as_secs_f64() / (1024.0 * 1024.0)),
+                    first_start_ms: ms(totals.first_start),
+                    last_end_ms: ms(totals.last_end),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Timings of the most recent jobs
+#[derive(Default)]
+pub struct PerfStore {
+    jobs: Mutex<(HashMap<String, JobTotals>, VecDeque<String>)>,
+}
+
+impl PerfStore {
+    fn lock(&self) -> MutexGuard<'_, (HashMap<String, JobTotals>, VecDeque<String>)> {
+        self.jobs.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn start_job(&self, job_id: &str, started: Instant) {
+        let mut guard = self.lock();
+        let (jobs, order) = &mut *guard;
+        if jobs.insert(job_id.to_string(), JobTotals { started, total: None, phases: Vec::new() }).is_none() {
+            order.push_back(job_id.to_string());
+        }
+        while order.len() > MAX_PERF_JOBS {
+            if let Some(oldest) = order.pop_front() {
+                jobs.remove(&oldest);
+            }
+        }
+    }
+
+    fn finish_job(&self, job_id: &str, total: Duration) {
+        if let Some(job) = self.lock().0.get_mut(job_id) {
+            job.total = Some(total);
+        }
+    }
+
+    fn add_phase(&self, job_id: &str, phase: &str, started: Instant, ended: Instant, bytes: u64) {
+        let mut guard = self.lock();
+        let Some(job) = guard.0.get_mut(job_id) else { return };
+        let first_start = started.saturating_duration_since(job.started);
+        let last_end = ended.saturating_duration_since(job.started);
+        let elapsed = ended.saturating_duration_since(started);
+        match job.phases.iter_mut().find(|(name, _)| name == phase) {
+            Some((_, totals)) => {
+                totals.spans += 1;
+                totals.elapsed += elapsed;
+                totals.bytes += bytes;
+                totals.first_start = totals.first_start.min(first_start);
+                totals.last_end = totals.last_end.max(last_end);
+            }
+            None => job.phases.push((phase.to_string(), PhaseTotals { spans: 1, elapsed, bytes, first_start, last_end })),
+        }
+        job.phases.sort_by_key(|(_, totals)| totals.first_start);
+    }
+
+    /// Phase timings of `job_id`, if any of its spans were seen
+    pub fn job(&self, job_id: &str) -> Option<JobPerf> {
+        self.lock().0.get(job_id).map(|job| job.report(job_id))
+    }
+}
+
+/// The store the installed [`PerfLayer`] fills
+pub fn store() -> &'static Arc<PerfStore> {
+    static STORE: OnceLock<Arc<PerfStore>> = OnceLock::new();
+    STORE.get_or_init(Arc::default)
+}
+
+/// Phase timings of a recent job
+pub fn job_perf(job_id: &str) -> Option<JobPerf> {
+    store().job(job_id)
+}
+
+/// The fields of a span the layer looks at
+#[derive(Default)]
+struct PerfFields {
+    job_id: Option<String>,
+    phase: Option<String>,
+    bytes: Option<u64>,
+}
+
+impl Visit for PerfFields {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        match field.name() {
+            "job_id" => self.job_id = Some(value.to_string()),
+            "phase" => self.phase = Some(value.to_string()),
+            _ => {}
+        }
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        if field.name() == "bytes" {
+            self.bytes = Some(value);
+        }
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.record_u64(field, value.max(0) as u64);
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        // `job_id = %id` arrives here
+        if matches!(field.name(), "job_id" | "phase") {
+            self.record_str(field, &format!("{:?}", value));
+        }
+    }
+}
+
+/// What the layer keeps with a span that belongs to a job
+struct Timed {
+    job_id: String,
+    /// The job's own span, rather than one of its phases
+    root: bool,
+    /// A job span has been entered, i.e. the job left the queue
+    entered: bool,
+    phase: Option<String>,
+    bytes: u64,
+    started: Instant,
+}
+
+/// Layer that sums phase span durations per job into a [`PerfStore`]
+pub struct PerfLayer {
+    store: Arc<PerfStore>,
+}
+
+impl PerfLayer {
+    pub fn new(store: Arc<PerfStore>) -> Self {
+        Self { store }
+    }
+}
+
+impl<S> Layer<S> for PerfLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else { return };
+        let mut fields = PerfFields::default();
+        attrs.record(&mut fields);
+        let root = fields.job_id.is_some();
+        let job_id = fields.job_id.or_else(|| {
+            let parent = span.parent()?;
+            let extensions = parent.extensions();
+            extensions.get::<Timed>().map(|timed| timed.job_id.clone())
+        });
+        // Spans outside any job cost nothing more
+        let Some(job_id) = job_id else { return };
+        span.extensions_mut().insert(Timed {
+            job_id,
+            root,
+            entered: false,
+            phase: fields.phase,
+            bytes: fields.bytes.unwrap_or(0),
+            started: Instant::now(),
+        });
+    }
+
+    fn on_enter(&self, id: &Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else { return };
+        let mut extensions = span.extensions_mut();
+        // A job is timed from when its work starts, not from when it was
+        // queued
+        if let Some(timed) = extensions.get_mut::<Timed>().filter(|timed| timed.root && !timed.entered) {
+            timed.entered = true;
+            timed.started = Instant::now();
+            self.store.start_job(&timed.job_id, timed.started);
+        }
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else { return };
+        let mut extensions = span.extensions_mut();
+        let Some(timed) = extensions.get_mut::<Timed>() else { return };
+        let mut fields = PerfFields::default();
+        values.record(&mut fields);
+        if let Some(bytes) = fields.bytes {
+            timed.bytes = bytes;
+        }
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(&id) else { return };
+        let Some(timed) = span.extensions_mut().remove::<Timed>() else { return };
+        let ended = Instant::now();
+        if timed.root {
+            // Finishing a job that never started (cancelled while queued)
+            // is a no-op
+            self.store.finish_job(&timed.job_id, ended.saturating_duration_since(timed.started));
+        } else if let Some(phase) = &timed.phase {
+            self.store.add_phase(&timed.job_id, phase, timed.started, ended, timed.bytes);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tracing_subscriber::prelude::*;
+
+    #[test]
+    fn test_phases_summed_per_job() {
+        let store = Arc::new(PerfStore::default());
+        let subscriber = tracing_subscriber::registry().with(PerfLayer::new(store.clone()));
+        tracing::subscriber::with_default(subscriber, || {
+            let job = tracing::info_span!("job", job_id = %"job-1");
+            job.in_scope(|| {
+                drop(phase(SEGMENT_DISCOVERY).entered());
+                // One hashing span per batch, each with its byte count
+                for bytes in [1000u64, 500] {
+                    let span = phase(HASHING).entered();
+                    std::thread::sleep(Duration::from_millis(2));
+                    span.record("bytes", bytes);
+                }
+            });
+            assert_eq!(store.job("job-1").unwrap().total_ms, None);
+            drop(job);
+            // Not part of any job
+            drop(phase(WRITING).entered());
+            // Cancelled while queued: never entered
+            drop(tracing::info_span!("job", job_id = %"job-2"));
+        });
+
+        let perf = store.job("job-1").unwrap();
+        assert!(perf.total_ms.is_some());
+        let phases: Vec<(&str, u64, u64)> = perf.phases.iter().map(|p| (p.phase.as_str(), p.spans, p.bytes)).collect();
+        assert_eq!(phases, [(SEGMENT_DISCOVERY, 1, 0), (HASHING, 2, 1500)]);
+        assert!(perf.phases[1].elapsed_ms >= 4.0, "{perf:?}");
+        assert!(perf.phases[1].throughput_mbs.is_some());
+        assert!(store.job("job-2").is_none());
+    }
+}
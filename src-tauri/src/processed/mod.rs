@@ -9,8 +9,10 @@
 pub mod types;
 pub mod detection;
 pub mod axiom;
+#[cfg(feature = "gui")]
 pub mod commands;
 
 pub use types::*;
 pub use detection::*;
+#[cfg(feature = "gui")]
 pub use commands::*;
@@ -12,6 +12,8 @@ use std::fs;
 use std::path::{Path, PathBuf};
 use tracing::{info, warn};
 
+use crate::common::atomic_file::write_atomic;
+
 /// Current project file format version
 pub const PROJECT_VERSION: u32 = 1;
 
@@ -200,7 +202,7 @@ pub fn save_project(project: &FFXProject, path: Option<&str>) -> ProjectSaveResu
     // Serialize to pretty JSON
     match serde_json::to_string_pretty(project) {
         Ok(json) => {
-            match fs::write(&save_path, &json) {
+            match write_atomic(&save_path, &json) {
                 Ok(_) => {
                     info!("Project saved successfully: {} bytes", json.len());
                     ProjectSaveResult {
@@ -0,0 +1,415 @@
+//! Hashing a drive in place through its block device
+//!
+//! Triage sometimes starts on the original drive behind a write blocker,
+//! before it is imaged: `/dev/sdb`, `/dev/rdisk3` or `\\.\PhysicalDrive2`
+//! (see [`is_block_device_path`]). A device has no segments and no
+//! companion log, and `fs::metadata` reports its size as 0, so it is sized
+//! by ioctl (BLKGETSIZE64, DKIOCGETBLOCKCOUNT, IOCTL_DISK_GET_LENGTH_INFO)
+//! and read in whole sectors - Windows refuses unaligned reads of a raw disk.
+//!
+//! The drive the running system lives on is refused unless forced: it is
+//! mounted and changing, so its hash can never be reproduced. Devices are
+//! only ever read; [`AtomicFile`](crate::common::atomic_file::AtomicFile)
+//! and the extraction location checks refuse them as outputs.
+
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::Path;
+use tracing::{debug, warn};
+
+use crate::common::{BUFFER_SIZE, content_map::ContentMapBuilder, device, hash::{HashAlgorithm, StreamingHasher}, progress as job_progress, resources};
+use crate::logging::perf;
+
+pub use crate::common::device::is_block_device_path;
+
+/// Reads start and end on multiples of this, which covers 512-byte and
+/// 4K-native sectors alike
+const SECTOR_ALIGN: u64 = 4096;
+
+/// A drive or volume opened read-only for hashing and triage
+pub struct BlockDevice {
+    path: String,
+    file: File,
+    size: u64,
+}
+
+impl BlockDevice {
+    /// Open the device at `path`; the drive holding the running system only
+    /// with `force`
+    pub fn open(path: &str, force: bool) -> Result<Self, String> {
+        Self::open_with(path, force, &os::system_devices(), os::device_size)
+    }
+
+    /// [`open`](Self::open) with the system drives and the size query given
+    fn open_with(
+        path: &str,
+        force: bool,
+        system_devices: &[String],
+        size_of: impl FnOnce(&File) -> io::Result<u64>,
+    ) -> Result<Self, String> {
+        // Resolve /dev/disk/by-id/... links to the device they name
+        let resolved = Path::new(path).canonicalize()
+            .map(|resolved| resolved.to_string_lossy().to_string())
+            .unwrap_or_else(|_| path.to_string());
+        if let Some(system) = system_devices.iter().find(|system| same_disk(&resolved, system)) {
+            if !force {
+                return Err(format!(
+                    "{} is the drive the running system is on ({}) - its hash cannot be reproduced; force the operation only if that is intended",
+                    path, system
+                ));
+            }
+            warn!(path, system = %system, "Opening the system drive (forced)");
+        }
+
+        let file = os::open_read_only(Path::new(path))
+            .map_err(|e| device::describe_io_error(Path::new(path), &format!("Failed to open device {}", path), &e))?;
+        let size = size_of(&file).map_err(|e| format!("Failed to get the size of {}: {}", path, e))?;
+        if size == 0 {
+            return Err(format!("{} reports a size of 0 - is there media in the drive?", path));
+        }
+        debug!(path, size, "Block device opened");
+        Ok(Self { path: path.to_string(), file, size })
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Size of the device in bytes
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// Read `length` bytes at `offset` (fewer at the end of the device),
+    /// widening the read to sector boundaries
+    pub fn read_at(&mut self, offset: u64, length: usize) -> Result<Vec<u8>, String> {
+        let offset = offset.min(self.size);
+        let end = offset.saturating_add(length as u64).min(self.size);
+        let aligned_start = offset / SECTOR_ALIGN * SECTOR_ALIGN;
+        let aligned_end = end.div_ceil(SECTOR_ALIGN).saturating_mul(SECTOR_ALIGN).min(self.size);
+        let mut buf = vec![0u8; (aligned_end - aligned_start) as usize];
+        self.read_exact_at(aligned_start, &mut buf)?;
+        Ok(buf[(offset - aligned_start) as usize..(end - aligned_start) as usize].to_vec())
+    }
+
+    /// Hash the whole device, counting it into `map` as well when given
+    pub fn hash<F>(&mut self, algorithm: HashAlgorithm, mut map: Option<&mut ContentMapBuilder>, mut progress: F) -> Result<String, String>
+    where
+        F: FnMut(u64, u64),
+    {
+        let buffer_size = (resources::buffer_size(BUFFER_SIZE) as u64 / SECTOR_ALIGN).max(1) * SECTOR_ALIGN;
+        let mut buf = vec![0u8; buffer_size as usize];
+        let mut hasher = StreamingHasher::new(algorithm);
+        // Reads and hashing alternate on this thread; both count as hashing
+        let phase = perf::phase(perf::HASHING).entered();
+        let mut position = 0;
+        while position < self.size {
+            job_progress::pause_point(|| {});
+            let len = (self.size - position).min(buffer_size) as usize;
+            self.read_exact_at(position, &mut buf[..len])?;
+            hasher.update(&buf[..len]);
+            if let Some(map) = map.as_deref_mut() {
+                map.update(&buf[..len]);
+            }
+            position += len as u64;
+            progress(position, self.size);
+        }
+        phase.record("bytes", position);
+        Ok(hasher.finalize())
+    }
+
+    fn read_exact_at(&mut self, offset: u64, buf: &mut [u8]) -> Result<(), String> {
+        self.file.seek(SeekFrom::Start(offset))
+            .and_then(|_| self.file.read_exact(buf))
+            .map_err(|e| device::describe_io_error(Path::new(&self.path), &format!("Read failed at offset {}", offset), &e))
+    }
+}
+
+/// Hash the device at `path`; the system drive only with `force`
+pub fn verify_with_progress<F>(path: &str, algorithm: &str, force: bool, progress: F) -> Result<String, String>
+where
+    F: FnMut(u64, u64),
+{
+    let algorithm = HashAlgorithm::from_str(algorithm)?;
+    BlockDevice::open(path, force)?.hash(algorithm, None, progress)
+}
+
+/// Whether two device paths name the same physical disk, counting a
+/// partition or volume as its disk
+fn same_disk(device: &str, other: &str) -> bool {
+    let disk = |path: &str| whole_disk(path.rsplit(['/', '\\']).next().unwrap_or(path));
+    disk(device) == disk(other)
+}
+
+/// Disk a device name belongs to: `sda2` -> `sda`, `nvme0n1p3` -> `nvme0n1`,
+/// `disk3s1s1` and `rdisk3` -> `disk3`, `Harddisk0Partition1` ->
+/// `physicaldrive0`; other names are returned lower-cased
+fn whole_disk(name: &str) -> String {
+    let name = name.to_ascii_lowercase();
+    if let Some((disk, _)) = name.strip_prefix("harddisk").and_then(|rest| rest.split_once("partition")) {
+        return format!("physicaldrive{}", disk);
+    }
+    let name = name.strip_prefix('r').filter(|rest| rest.starts_with("disk")).unwrap_or(&name);
+    if let Some(number) = name.strip_prefix("disk") {
+        let digits = number.bytes().take_while(u8::is_ascii_digit).count();
+        if digits > 0 {
+            return name[.."disk".len() + digits].to_string();
+        }
+    }
+    // Disks whose own names end in a number mark partitions with "p<n>"
+    if ["nvme", "mmcblk", "loop", "nbd", "md"].iter().any(|prefix| name.starts_with(prefix)) {
+        let trimmed = name.trim_end_matches(|c: char| c.is_ascii_digit());
+        return match trimmed.strip_suffix('p') {
+            Some(disk) if trimmed.len() < name.len() && disk.ends_with(|c: char| c.is_ascii_digit()) => disk.to_string(),
+            _ => name.to_string(),
+        };
+    }
+    if ["sd", "hd", "vd", "xvd"].iter().any(|prefix| name.starts_with(prefix)) {
+        return name.trim_end_matches(|c: char| c.is_ascii_digit()).to_string();
+    }
+    name.to_string()
+}
+
+#[cfg(target_os = "linux")]
+mod os {
+    use std::fs::File;
+    use std::io;
+    use std::os::unix::io::AsRawFd;
+    use std::path::Path;
+
+    use crate::common::volume::find_mount;
+
+    /// _IOR(0x12, 114, size_t) from linux/fs.h
+    const BLKGETSIZE64: libc::c_ulong = (2 << 30) | ((std::mem::size_of::<usize>() as libc::c_ulong) << 16) | (0x12 << 8) | 114;
+
+    /// Mount points whose devices count as the system drive
+    const SYSTEM_MOUNTS: &[&str] = &["/", "/boot", "/boot/efi", "/usr"];
+
+    pub fn open_read_only(path: &Path) -> io::Result<File> {
+        File::open(path)
+    }
+
+    pub fn device_size(file: &File) -> io::Result<u64> {
+        let metadata = file.metadata()?;
+        if metadata.is_file() {
+            return Ok(metadata.len());
+        }
+        let mut size = 0u64;
+        // SAFETY: BLKGETSIZE64 writes one u64 into `size`
+        if unsafe { libc::ioctl(file.as_raw_fd(), BLKGETSIZE64 as _, &mut size as *mut u64) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(size)
+    }
+
+    /// Devices behind the root, boot and /usr mounts; for device-mapper
+    /// volumes (LVM, LUKS) the disks underneath
+    pub fn system_devices() -> Vec<String> {
+        let Ok(mounts) = std::fs::read_to_string("/proc/mounts") else { return Vec::new() };
+        let mut devices = Vec::new();
+        for mount in SYSTEM_MOUNTS {
+            let Some((source, _)) = find_mount(&mounts, mount) else { continue };
+            let Ok(device) = Path::new(&source).canonicalize() else { continue };
+            let name = device.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+            let slaves: Vec<String> = std::fs::read_dir(Path::new("/sys/class/block").join(&name).join("slaves"))
+                .map(|entries| entries.flatten().map(|entry| format!("/dev/{}", entry.file_name().to_string_lossy())).collect())
+                .unwrap_or_default();
+            devices.push(device.to_string_lossy().to_string());
+            devices.extend(slaves);
+        }
+        devices
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod os {
+    use std::ffi::{CStr, CString};
+    use std::fs::File;
+    use std::io;
+    use std::os::unix::io::AsRawFd;
+    use std::path::Path;
+
+    /// _IOR('d', 24, u32) and _IOR('d', 25, u64) from sys/disk.h
+    const DKIOCGETBLOCKSIZE: libc::c_ulong = 0x4004_6418;
+    const DKIOCGETBLOCKCOUNT: libc::c_ulong = 0x4008_6419;
+
+    pub fn open_read_only(path: &Path) -> io::Result<File> {
+        File::open(path)
+    }
+
+    pub fn device_size(file: &File) -> io::Result<u64> {
+        let metadata = file.metadata()?;
+        if metadata.is_file() {
+            return Ok(metadata.len());
+        }
+        let (mut block_size, mut block_count) = (0u32, 0u64);
+        // SAFETY: each ioctl writes one value of the type passed
+        let failed = unsafe {
+            libc::ioctl(file.as_raw_fd(), DKIOCGETBLOCKSIZE, &mut block_size as *mut u32) != 0
+                || libc::ioctl(file.as_raw_fd(), DKIOCGETBLOCKCOUNT, &mut block_count as *mut u64) != 0
+        };
+        if failed {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(block_size as u64 * block_count)
+    }
+
+    /// The device the root volume is mounted from (the APFS container disk)
+    pub fn system_devices() -> Vec<String> {
+        let root = CString::new("/").unwrap();
+        // SAFETY: statfs only writes into the zeroed struct we pass it
+        let mut stat: libc::statfs = unsafe { std::mem::zeroed() };
+        if unsafe { libc::statfs(root.as_ptr(), &mut stat) } != 0 {
+            return Vec::new();
+        }
+        // SAFETY: the kernel NUL-terminates the name within its array
+        let source = unsafe { CStr::from_ptr(stat.f_mntfromname.as_ptr()) }.to_string_lossy().to_string();
+        vec![source]
+    }
+}
+
+#[cfg(windows)]
+mod os {
+    use std::fs::{File, OpenOptions};
+    use std::io;
+    use std::os::windows::fs::OpenOptionsExt;
+    use std::os::windows::io::AsRawHandle;
+    use std::path::Path;
+    use windows_sys::Win32::Storage::FileSystem::{FILE_SHARE_READ, FILE_SHARE_WRITE};
+    use windows_sys::Win32::System::Ioctl::{
+        GET_LENGTH_INFORMATION, IOCTL_DISK_GET_LENGTH_INFO, IOCTL_STORAGE_GET_DEVICE_NUMBER, STORAGE_DEVICE_NUMBER,
+    };
+    use windows_sys::Win32::System::IO::DeviceIoControl;
+
+    /// Raw disks can only be opened sharing them with the system's own handles
+    pub fn open_read_only(path: &Path) -> io::Result<File> {
+        OpenOptions::new().read(true).share_mode(FILE_SHARE_READ | FILE_SHARE_WRITE).open(path)
+    }
+
+    /// One fixed-size structure out of DeviceIoControl
+    fn query<T>(file: &File, code: u32, out: &mut T) -> io::Result<()> {
+        let mut returned = 0u32;
+        // SAFETY: the output buffer is `out`, with its size passed along
+        let ok = unsafe {
+            DeviceIoControl(
+                file.as_raw_handle() as _,
+                code,
+                std::ptr::null(),
+                0,
+                out as *mut T as *mut _,
+                std::mem::size_of::<T>() as u32,
+                &mut returned,
+                std::ptr::null_mut(),
+            )
+        };
+        if ok == 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+
+    pub fn device_size(file: &File) -> io::Result<u64> {
+        let metadata = file.metadata()?;
+        if metadata.is_file() {
+            return Ok(metadata.len());
+        }
+        let mut length = GET_LENGTH_INFORMATION { Length: 0 };
+        query(file, IOCTL_DISK_GET_LENGTH_INFO, &mut length)?;
+        Ok(length.Length as u64)
+    }
+
+    /// The system volume and the physical drive it is on
+    pub fn system_devices() -> Vec<String> {
+        let drive = std::env::var("SystemDrive").unwrap_or_else(|_| "C:".to_string());
+        let volume = format!(r"\\.\{}", drive.trim_end_matches('\\'));
+        let mut devices = vec![volume.clone()];
+        let mut number = STORAGE_DEVICE_NUMBER { DeviceType: 0, DeviceNumber: 0, PartitionNumber: 0 };
+        if let Ok(file) = open_read_only(Path::new(&volume)) {
+            if query(&file, IOCTL_STORAGE_GET_DEVICE_NUMBER, &mut number).is_ok() {
+                devices.push(format!(r"\\.\PhysicalDrive{}", number.DeviceNumber));
+            }
+        }
+        devices
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+mod os {
+    use std::fs::File;
+    use std::io::{self, Seek, SeekFrom};
+    use std::path::Path;
+
+    pub fn open_read_only(path: &Path) -> io::Result<File> {
+        File::open(path)
+    }
+
+    /// Block devices report their size as the end of the stream here
+    pub fn device_size(file: &File) -> io::Result<u64> {
+        let mut file = file;
+        file.seek(SeekFrom::End(0))
+    }
+
+    pub fn system_devices() -> Vec<String> {
+        Vec::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_whole_disk_of_partitions() {
+        for (name, disk) in [
+            ("sda", "sda"), ("sdb2", "sdb"), ("nvme0n1p3", "nvme0n1"), ("nvme0n1", "nvme0n1"),
+            ("mmcblk0p1", "mmcblk0"), ("disk3s1s1", "disk3"), ("rdisk3", "disk3"), ("disk10", "disk10"),
+            ("Harddisk1Partition2", "physicaldrive1"), ("PhysicalDrive1", "physicaldrive1"), ("C:", "c:"),
+        ] {
+            assert_eq!(whole_disk(name), disk, "{name}");
+        }
+        assert!(same_disk("/dev/sda", "/dev/sda2"));
+        assert!(same_disk(r"\\.\PhysicalDrive0", r"\\.\Harddisk0Partition1"));
+        assert!(!same_disk("/dev/sdb", "/dev/sda2"));
+        assert!(!same_disk(r"\\.\PhysicalDrive2", r"\\.\PhysicalDrive0"));
+    }
+
+    #[test]
+    fn test_system_drive_refused_unless_forced() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("sdb");
+        let data: Vec<u8> = (0..10_000u32).map(|i| (i % 251) as u8).collect();
+        std::fs::write(&path, &data).unwrap();
+        let path = path.canonicalize().unwrap().to_string_lossy().to_string();
+        let system = [format!("{}1", path)];
+
+        let error = BlockDevice::open_with(&path, false, &system, |_| Ok(10_000)).err().unwrap();
+        assert!(error.contains("the drive the running system is on"), "{error}");
+        assert!(BlockDevice::open_with(&path, true, &system, |_| Ok(10_000)).is_ok());
+
+        let error = BlockDevice::open_with(&path, false, &[], |_| Ok(0)).err().unwrap();
+        assert!(error.contains("size of 0"), "{error}");
+        let error = BlockDevice::open_with(&path, false, &[], |_| Err(io::Error::other("ioctl failed"))).err().unwrap();
+        assert!(error.contains("ioctl failed"), "{error}");
+    }
+
+    #[test]
+    fn test_aligned_reads_and_hash_of_mock_device() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("sdc");
+        let data: Vec<u8> = (0..10_000u32).map(|i| (i % 251) as u8).collect();
+        std::fs::write(&path, &data).unwrap();
+
+        // The size comes from the (mocked) ioctl, not the file
+        let mut device = BlockDevice::open_with(path.to_str().unwrap(), false, &[], |_| Ok(9_216)).unwrap();
+        assert_eq!(device.size(), 9_216);
+        assert_eq!(device.read_at(4_000, 200).unwrap(), data[4_000..4_200]);
+        assert_eq!(device.read_at(9_000, 1_000).unwrap(), data[9_000..9_216]);
+
+        let mut reported = Vec::new();
+        let hash = device.hash(HashAlgorithm::Sha256, None, |done, total| reported.push((done, total))).unwrap();
+        let mut expected = StreamingHasher::new(HashAlgorithm::Sha256);
+        expected.update(&data[..9_216]);
+        assert_eq!(hash, expected.finalize());
+        assert_eq!(reported.last(), Some(&(9_216, 9_216)));
+    }
+}
@@ -0,0 +1,326 @@
+//! Gzip-compressed raw images (.dd.gz, .raw.gz, .img.gz)
+//!
+//! Archived acquisitions are often stored as `gzip image.dd`. The hash that
+//! matters is the hash of the original dd image, so verification streams
+//! the file through a gzip decoder and hashes the decompressed bytes.
+//!
+//! ```text
+//! ┌──────────────────────────────┬──────────────────────────────┬─────
+//! │ member 1                     │ member 2 (pigz, concatenated)│ ...
+//! │ 1F 8B ... deflate ... CRC32  │ 1F 8B ... deflate ... CRC32  │
+//! │                      ISIZE   │                      ISIZE   │
+//! └──────────────────────────────┴──────────────────────────────┴─────
+//! ```
+//!
+//! ## Sizes
+//!
+//! The only size stored in the file is the ISIZE trailer: the uncompressed
+//! size of the *last member* modulo 4 GiB. For any single-member image
+//! under 4 GiB it is exact; otherwise the exact size is only known after
+//! decompressing the whole file (`info(path, true)`).
+//!
+//! Progress is reported in compressed bytes consumed, since the
+//! uncompressed total is usually unknown up front.
+
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+
+use flate2::bufread::GzDecoder;
+use serde::Serialize;
+use tracing::{debug, instrument, warn};
+
+use crate::common::atomic_file::AtomicFile;
+use crate::common::extract_manifest::{ExtractedFile, HashingWriter};
+use crate::common::hash::{HashAlgorithm, StreamingHasher};
+use crate::common::{progress as job_progress, resources, BUFFER_SIZE};
+
+const GZIP_MAGIC: [u8; 2] = [0x1F, 0x8B];
+
+/// Extensions that name a compressed disk image without looking inside
+const IMAGE_EXTENSIONS: &[&str] = &[".dd.gz", ".raw.gz", ".img.gz", ".bin.gz"];
+
+/// Decompressed bytes sniffed to decide whether a plain `.gz` holds a disk
+const SNIFF_SIZE: usize = 64 * 1024;
+
+/// Compressed and uncompressed sizes of a gzip image
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct GzipInfo {
+    pub compressed_size: u64,
+    /// ISIZE trailer: uncompressed size of the last member modulo 4 GiB
+    pub isize: u32,
+    /// Original file name stored in the first member header (FNAME)
+    pub original_name: Option<String>,
+    /// Exact uncompressed size, only known after a full scan
+    pub uncompressed_size: Option<u64>,
+    /// Member count, only known after a full scan
+    pub members: Option<u32>,
+}
+
+impl GzipInfo {
+    /// Best known uncompressed size: exact after a full scan, else ISIZE
+    pub fn logical_size(&self) -> u64 {
+        self.uncompressed_size.unwrap_or(self.isize as u64)
+    }
+}
+
+/// Whether `path` is a gzip-compressed disk image
+///
+/// `.dd.gz`, `.raw.gz`, `.img.gz` and `.bin.gz` qualify by name; any other
+/// `.gz` (not `.tar.gz`) only if its decompressed start looks like a disk.
+pub fn is_gzip_image(path: &Path) -> bool {
+    let lower = path.to_string_lossy().to_lowercase();
+    if !lower.ends_with(".gz") || lower.ends_with(".tar.gz") || !has_gzip_magic(path) {
+        return false;
+    }
+    if IMAGE_EXTENSIONS.iter().any(|ext| lower.ends_with(ext)) {
+        return true;
+    }
+    sniff(path).is_ok_and(|(head, _)| looks_like_disk(&head))
+}
+
+fn has_gzip_magic(path: &Path) -> bool {
+    let mut magic = [0u8; 2];
+    File::open(path)
+        .and_then(|mut file| file.read_exact(&mut magic))
+        .is_ok_and(|_| magic == GZIP_MAGIC)
+}
+
+/// First `SNIFF_SIZE` decompressed bytes and the stored file name
+fn sniff(path: &Path) -> Result<(Vec<u8>, Option<String>), String> {
+    let file = File::open(path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    let mut decoder = GzDecoder::new(BufReader::new(file));
+    let mut head = Vec::with_capacity(SNIFF_SIZE);
+    (&mut decoder).take(SNIFF_SIZE as u64).read_to_end(&mut head)
+        .map_err(|e| format!("Failed to decompress {}: {}", path.display(), e))?;
+    let name = decoder.header()
+        .and_then(|header| header.filename())
+        .map(|name| String::from_utf8_lossy(name).to_string());
+    Ok((head, name))
+}
+
+/// Partition table, boot sector or volume signatures near the start of a disk
+fn looks_like_disk(head: &[u8]) -> bool {
+    let at = |offset: usize, signature: &[u8]| head.get(offset..offset + signature.len()) == Some(signature);
+    if at(257, b"ustar") {
+        return false;
+    }
+    at(510, &[0x55, 0xAA])              // MBR / boot sector
+        || at(512, b"EFI PART")         // GPT
+        || at(512, b"LABELONE")         // LVM
+        || at(0, b"LUKS\xBA\xBE")       // LUKS
+        || at(32, b"NXSB")              // APFS container
+        || at(1024, b"H+") || at(1024, b"HX")  // HFS+ / HFSX
+        || at(1080, &[0x53, 0xEF])      // ext2/3/4
+        || at(32769, b"CD001")          // ISO 9660
+}
+
+/// Read the header name and ISIZE trailer; with `full_scan`, decompress the
+/// whole file for the exact size and member count
+#[instrument]
+pub fn info(path: &Path, full_scan: bool) -> Result<GzipInfo, String> {
+    let mut file = File::open(path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    let compressed_size = file.metadata().map_err(|e| format!("Failed to read file size: {}", e))?.len();
+    if compressed_size < 18 {
+        return Err(format!("Truncated gzip file: {} bytes", compressed_size));
+    }
+    let mut trailer = [0u8; 4];
+    file.seek(SeekFrom::End(-4))
+        .and_then(|_| file.read_exact(&mut trailer))
+        .map_err(|e| format!("Failed to read gzip trailer: {}", e))?;
+    let (_, original_name) = sniff(path)?;
+
+    let (uncompressed_size, members) = if full_scan {
+        let (size, members) = decompress(path, |_| Ok(()), |_, _| {})?;
+        (Some(size), Some(members))
+    } else {
+        (None, None)
+    };
+    Ok(GzipInfo {
+        compressed_size,
+        isize: u32::from_le_bytes(trailer),
+        original_name,
+        uncompressed_size,
+        members,
+    })
+}
+
+/// Hash the decompressed image; matches the hash of the original dd image
+#[instrument(skip(progress))]
+pub fn verify_with_progress<F>(path: &Path, algorithm: &str, progress: F) -> Result<String, String>
+where
+    F: FnMut(u64, u64),
+{
+    let mut hasher = StreamingHasher::new(HashAlgorithm::from_str(algorithm)?);
+    decompress(path, |data| {
+        job_progress::pause_point(|| {});
+        hasher.update(data);
+        Ok(())
+    }, progress)?;
+    Ok(hasher.finalize())
+}
+
+/// Write the decompressed image to `output`, hashing it on the way out
+pub(crate) fn decompress_to_file(path: &Path, output: &Path) -> Result<ExtractedFile, String> {
+    use std::io::Write;
+
+    let file = AtomicFile::create(output)
+        .and_then(|file| file.with_progress_markers(None))
+        .map_err(|e| format!("Failed to create output file: {}", e))?;
+    let mut writer = HashingWriter::new(file);
+    decompress(path, |data| writer.write_all(data).map_err(|e| format!("Write failed: {}", e)), |_, _| {})?;
+    let image_name = path.file_name().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+    writer.commit(&image_name)
+}
+
+/// Counts bytes read from the compressed file
+struct CountingReader<R> {
+    inner: R,
+    count: u64,
+}
+
+impl<R: Read> Read for CountingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.count += n as u64;
+        Ok(n)
+    }
+}
+
+/// Stream every member's decompressed bytes to `sink`, returning the
+/// uncompressed size and member count; `progress` gets compressed bytes
+///
+/// Each member's CRC32 and ISIZE are checked by the decoder. Bytes after the
+/// last member that do not start another member are ignored, like gzip does.
+pub(crate) fn decompress<S, F>(path: &Path, mut sink: S, mut progress: F) -> Result<(u64, u32), String>
+where
+    S: FnMut(&[u8]) -> Result<(), String>,
+    F: FnMut(u64, u64),
+{
+    let file = File::open(path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    let compressed_size = file.metadata().map_err(|e| format!("Failed to read file size: {}", e))?.len();
+    let mut reader = BufReader::with_capacity(1024 * 1024, CountingReader { inner: file, count: 0 });
+    let mut buf = vec![0u8; resources::buffer_size(BUFFER_SIZE)];
+    let report_interval = (compressed_size / 100).max(1);
+    let mut last_report = 0u64;
+    let mut members = 0u32;
+    let mut total = 0u64;
+
+    loop {
+        let next = reader.fill_buf().map_err(|e| format!("Read error: {}", e))?;
+        if next.is_empty() {
+            break;
+        }
+        if !next.starts_with(&GZIP_MAGIC) {
+            if members == 0 {
+                return Err(format!("Not a gzip file: {}", path.display()));
+            }
+            warn!(members, "Ignoring trailing bytes after the last gzip member");
+            break;
+        }
+
+        let mut decoder = GzDecoder::new(reader);
+        loop {
+            let n = decoder.read(&mut buf)
+                .map_err(|e| format!("Corrupt gzip member {} after {} bytes: {}", members + 1, total, e))?;
+            if n == 0 {
+                break;
+            }
+            sink(&buf[..n])?;
+            total += n as u64;
+            let inner = decoder.get_ref();
+            let consumed = inner.get_ref().count - inner.buffer().len() as u64;
+            if consumed - last_report >= report_interval {
+                progress(consumed, compressed_size);
+                last_report = consumed;
+            }
+        }
+        reader = decoder.into_inner();
+        members += 1;
+    }
+
+    debug!(members, total, "Gzip image decompressed");
+    progress(compressed_size, compressed_size);
+    Ok((total, members))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use std::io::Write;
+
+    /// 200 KiB "disk" with an MBR signature
+    fn disk() -> Vec<u8> {
+        let mut data: Vec<u8> = (0..200 * 1024u32).map(|i| (i % 251) as u8).collect();
+        data[510] = 0x55;
+        data[511] = 0xAA;
+        data
+    }
+
+    fn gzip(data: &[u8]) -> Vec<u8> {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::fast());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    #[test]
+    fn test_multi_member_hash_matches_original() {
+        let dir = tempfile::tempdir().unwrap();
+        let data = disk();
+        let (first, second) = data.split_at(70_000);
+        let path = dir.path().join("evidence.gz");
+        std::fs::write(&path, [gzip(first), gzip(second)].concat()).unwrap();
+        assert!(is_gzip_image(&path));
+
+        let original = dir.path().join("evidence.dd");
+        std::fs::write(&original, &data).unwrap();
+        let expected = crate::common::hash::hash_file(&original, "sha256").unwrap();
+        let mut last = (0, 0);
+        let hash = verify_with_progress(&path, "sha256", |done, total| last = (done, total)).unwrap();
+        assert_eq!(hash, expected);
+        let compressed_size = std::fs::metadata(&path).unwrap().len();
+        assert_eq!(last, (compressed_size, compressed_size));
+
+        // ISIZE only covers the last member; the full scan is exact
+        let quick = info(&path, false).unwrap();
+        assert_eq!(quick.isize as usize, second.len());
+        assert_eq!(quick.uncompressed_size, None);
+        let full = info(&path, true).unwrap();
+        assert_eq!(full.uncompressed_size, Some(data.len() as u64));
+        assert_eq!(full.members, Some(2));
+        assert_eq!(full.logical_size(), data.len() as u64);
+    }
+
+    #[test]
+    fn test_detection() {
+        let dir = tempfile::tempdir().unwrap();
+        let text = dir.path().join("notes.gz");
+        std::fs::write(&text, gzip(b"not a disk")).unwrap();
+        assert!(!is_gzip_image(&text));
+
+        let named = dir.path().join("usb.dd.gz");
+        std::fs::write(&named, gzip(b"short image")).unwrap();
+        assert!(is_gzip_image(&named));
+
+        let fake = dir.path().join("fake.dd.gz");
+        std::fs::write(&fake, b"plain bytes").unwrap();
+        assert!(!is_gzip_image(&fake));
+    }
+
+    #[test]
+    fn test_corrupt_member_is_an_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut compressed = gzip(&disk());
+        let crc_offset = compressed.len() - 8;
+        compressed[crc_offset] ^= 0xFF;
+        let path = dir.path().join("bad.dd.gz");
+        std::fs::write(&path, &compressed).unwrap();
+        assert!(verify_with_progress(&path, "md5", |_, _| {}).is_err());
+
+        let truncated = dir.path().join("truncated.dd.gz");
+        std::fs::write(&truncated, &compressed[..compressed.len() / 2]).unwrap();
+        assert!(verify_with_progress(&truncated, "md5", |_, _| {}).is_err());
+    }
+}
@@ -0,0 +1,1438 @@
+//! RAW Disk Image Parser
+//!
+//! This module provides parsing and verification for raw (dd-style) forensic
+//! disk images, supporting both single-file and multi-segment formats.
+//!
+//! ## Supported Formats
+//!
+//! | Extension     | Description                           |
+//! |---------------|---------------------------------------|
+//! | `.dd`         | Standard dd-style raw image           |
+//! | `.raw`        | Generic raw disk image                |
+//! | `.img`        | Disk image (verify magic to disambiguate) |
+//! | `.001`, `.0001`, `.01` | Split image, numbered segments |
+//! | `.dd.aa`, `.dd.ab` | Split image, `split -b` letter suffixes |
+//! | `.part1`, `.part2` | Split image, part-numbered segments |
+//!
+//! ## Multi-Segment Images
+//!
+//! Raw images can be split into numbered or lettered segments:
+//!
+//! ```text
+//! evidence.001  ─┐
+//! evidence.002   │  Combined: Single contiguous byte stream
+//! evidence.003   │  representing the original disk image
+//! evidence.004  ─┘
+//! ```
+//!
+//! Segment discovery:
+//! 1. Match the input's suffix against the split schemes (.001, .aa, .part1)
+//! 2. Scan directory for the same basename and scheme, ignoring case
+//! 3. Sort segments by number, verify no gaps
+//! 4. Concatenate virtually for seamless reading
+//!
+//! A set spread over several directories is given as a list instead
+//! ([`RawHandle::open_with_segments`], [`with_segment_list`]); the numeric
+//! suffixes must then run from the first segment without a gap.
+//!
+//! ## RawHandle
+//!
+//! The `RawHandle` provides a virtual file-like interface over segmented images:
+//!
+//! ```rust,ignore
+//! let mut handle = RawHandle::open("/evidence/disk.001")?;
+//!
+//! // Read seamlessly across segment boundaries
+//! let mut buf = vec![0u8; 1024];
+//! let bytes_read = handle.read(&mut buf)?;
+//!
+//! // Total size spans all segments
+//! let total_size = handle.total_size();
+//! ```
+//!
+//! ## Hash Verification
+//!
+//! High-performance hashing with algorithm-specific optimizations:
+//!
+//! | Algorithm | Implementation                              |
+//! |-----------|---------------------------------------------|
+//! | BLAKE3    | Memory-mapped I/O + rayon parallel hashing  |
+//! | XXH3      | Memory-mapped I/O, extremely fast           |
+//! | SHA-256   | Pipelined: async I/O → hasher thread        |
+//! | MD5       | Pipelined I/O (legacy, not recommended)     |
+//!
+//! ```rust,ignore
+//! // Verify with progress callback
+//! raw::verify_with_progress("/evidence/disk.001", "sha256", |current, total| {
+//!     let percent = (current as f64 / total as f64) * 100.0;
+//!     println!("Progress: {:.1}%", percent);
+//! })?;
+//! ```
+//!
+//! ## Consolidation
+//!
+//! `consolidate` streams a complete segment set into one contiguous image
+//! (or an evenly split copy), hashing what it reads and what it writes in
+//! the same pass. It refuses sets with gaps and existing output files
+//! unless `overwrite` is set.
+//!
+//! ## Android Sparse Images
+//!
+//! A `.img` that starts with the sparse magic (`3A FF 26 ED`) is an Android
+//! sparse image (see [`sparse`]). `info` reports its logical size and chunk
+//! layout, `verify_logical_with_progress` hashes the expanded partition
+//! (what a dd image of it would hash to), and `extract` writes the expanded
+//! image rather than the container bytes.
+//!
+//! ## Gzip-Compressed Images
+//!
+//! `.dd.gz`, `.raw.gz`, `.img.gz` (and plain `.gz` files that decompress to
+//! something disk-like) are handled by [`gzip`]: `info` reports compressed
+//! and uncompressed sizes, and `verify_with_progress` hashes the
+//! decompressed bytes, so the result matches the hash of the original dd.
+//!
+//! ## Drives
+//!
+//! A device path (`/dev/sdb`, `\\.\PhysicalDrive2`) is hashed in place
+//! through [`block_device`]: no segment discovery, sized by ioctl, read in
+//! whole sectors, and the system drive refused unless forced.
+//!
+//! ## Forensic Notes
+//!
+//! - Raw images preserve **physical** disk layout (sector-by-sector)
+//! - No compression or metadata - pure byte-for-byte copy
+//! - Hash of raw image = hash of original disk
+//! - Segment boundaries have NO forensic significance
+//!   (they're just split points, not disk boundaries)
+//! - For evidentiary purposes, always hash the **complete** image
+//!
+//! ## Performance
+//!
+//! Buffer sizes and threading are tuned for modern storage:
+//! - 16MB I/O buffers for sequential throughput
+//! - Memory-mapped I/O for >64MB files
+//! - Parallel hashing for BLAKE3
+//! - Pipelined I/O for other algorithms
+//! - Buffered reads retry transient network errors on a reopened handle
+//!   ([`crate::common::retry`]); memory-mapped segments are not retried
+
+// RAW disk image parser (.dd, .raw, .img, .001, .002, etc.)
+// Supports single and multi-segment raw forensic images
+
+pub mod block_device;
+pub mod gzip;
+pub mod sparse;
+
+pub use block_device::{BlockDevice, is_block_device_path};
+
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::thread;
+use tracing::{debug, trace, info, instrument};
+
+use crate::logging::perf;
+use crate::containers::VerifyOptions;
+
+use crate::common::{BUFFER_SIZE, atomic_file::AtomicFile, capabilities::FormatCapabilities, device, progress, quick_check::StructureCheck, resources, retry::RetryingReader, extract_manifest::{ExtractedFile, HashingWriter}, hash::{blake3_update, HashAlgorithm, HashTuning, StreamingHasher}, content_map::{ContentMap, ContentMapBuilder, DEFAULT_MAP_WINDOW}, path_security::check_output_location, segments::{discover_numbered_segments, with_listed_segments, SplitName}};
+
+// =============================================================================
+// Public Types
+// =============================================================================
+
+#[derive(Serialize, Clone)]
+pub struct RawInfo {
+    pub segment_count: u32,
+    pub total_size: u64,
+    pub segment_sizes: Vec<u64>,
+    pub segment_names: Vec<String>,
+    pub first_segment: String,
+    pub last_segment: String,
+    /// Size of the image the container represents (the expanded size for
+    /// sparse images, the uncompressed size for gzip images, otherwise
+    /// `total_size`)
+    pub logical_size: u64,
+    /// Set when the image is an Android sparse image
+    pub sparse: Option<sparse::SparseInfo>,
+    /// Set when the image is gzip-compressed
+    pub gzip: Option<gzip::GzipInfo>,
+}
+
+#[derive(Serialize)]
+pub struct VerifyResult {
+    pub algorithm: String,
+    pub hash: String,
+    pub total_size: u64,
+    pub duration_secs: f64,
+    pub throughput_mbs: f64,
+    /// Zero/entropy map of the hashed bytes, when requested
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content_map: Option<ContentMap>,
+}
+
+// =============================================================================
+// Raw Image Handle
+// =============================================================================
+
+pub struct RawHandle {
+    segments: Vec<PathBuf>,
+    segment_sizes: Vec<u64>,
+    total_size: u64,
+    current_segment: usize,
+    current_file: Option<File>,
+    position: u64,
+}
+
+impl RawHandle {
+    /// Open a raw image (single or multi-segment)
+    #[instrument(skip_all, fields(path))]
+    pub fn open(path: &str) -> Result<Self, String> {
+        debug!(path, "Opening raw image handle");
+        let path_obj = Path::new(path);
+        if !path_obj.exists() {
+            return Err(format!("File not found: {}", path));
+        }
+
+        let (segments, segment_sizes) = discover_segments(path)?;
+        let total_size: u64 = segment_sizes.iter().sum();
+        
+        debug!(
+            segment_count = segments.len(),
+            total_size,
+            "Raw handle opened"
+        );
+
+        Ok(RawHandle {
+            segments,
+            segment_sizes,
+            total_size,
+            current_segment: 0,
+            current_file: None,
+            position: 0,
+        })
+    }
+
+    /// Open a split image from its segment files in order, wherever they
+    /// are (see [`with_segment_list`])
+    pub fn open_with_segments(paths: Vec<String>) -> Result<Self, String> {
+        let first = paths.first().cloned().unwrap_or_default();
+        with_segment_list(&first, &paths, || Self::open(&first))
+    }
+
+    /// Get total size of all segments
+    pub fn total_size(&self) -> u64 {
+        self.total_size
+    }
+
+    /// Get segment count
+    pub fn segment_count(&self) -> usize {
+        self.segments.len()
+    }
+
+    /// Get current read position (bytes from the start of the image)
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// Move the read position (clamped to the image size)
+    pub fn seek(&mut self, position: u64) {
+        self.position = position.min(self.total_size);
+    }
+
+    /// Read bytes at current position
+    pub fn read(&mut self, buf: &mut [u8]) -> Result<usize, String> {
+        if self.position >= self.total_size {
+            return Ok(0);
+        }
+
+        let mut total_read = 0;
+        let mut remaining = buf.len();
+
+        while remaining > 0 && self.position < self.total_size {
+            // Find which segment we're in
+            let (seg_idx, seg_offset) = self.position_to_segment(self.position);
+            
+            // Open segment if needed
+            if self.current_segment != seg_idx || self.current_file.is_none() {
+                self.current_segment = seg_idx;
+                let file = File::open(&self.segments[seg_idx])
+                    .map_err(|e| device::describe_io_error(&self.segments[seg_idx], &format!("Failed to open segment {}", seg_idx), &e))?;
+                self.current_file = Some(file);
+            }
+
+            let file = self.current_file.as_mut().unwrap();
+            file.seek(SeekFrom::Start(seg_offset))
+                .map_err(|e| format!("Seek failed: {}", e))?;
+
+            // Calculate how much we can read from this segment
+            let seg_remaining = self.segment_sizes[seg_idx] - seg_offset;
+            let to_read = remaining.min(seg_remaining as usize);
+
+            let bytes_read = match file.read(&mut buf[total_read..total_read + to_read]) {
+                Ok(n) => n,
+                Err(e) => {
+                    // Don't hold a handle on a device that may be gone
+                    self.current_file = None;
+                    return Err(device::describe_io_error(&self.segments[seg_idx], "Read failed", &e));
+                }
+            };
+
+            if bytes_read == 0 {
+                break;
+            }
+
+            total_read += bytes_read;
+            remaining -= bytes_read;
+            self.position += bytes_read as u64;
+        }
+
+        Ok(total_read)
+    }
+
+    /// Convert absolute position to (segment_index, offset_within_segment)
+    fn position_to_segment(&self, pos: u64) -> (usize, u64) {
+        let mut offset = pos;
+        for (idx, &size) in self.segment_sizes.iter().enumerate() {
+            if offset < size {
+                return (idx, offset);
+            }
+            offset -= size;
+        }
+        // Past end - return last segment
+        let last = self.segments.len() - 1;
+        (last, self.segment_sizes[last])
+    }
+}
+
+// =============================================================================
+// Public API
+// =============================================================================
+
+/// Raw images (including sparse and gzip images): whole-image and
+/// per-segment hashes, and export as one contiguous image
+pub const fn capabilities() -> FormatCapabilities {
+    FormatCapabilities {
+        can_info: true,
+        can_info_tree: false,
+        can_verify_whole: true,
+        can_verify_items: false,
+        can_verify_segments: true,
+        can_extract: true,
+        can_extract_item: false,
+        notes: &["File systems inside the image are not parsed - extraction exports the whole image"],
+    }
+}
+
+/// Get information about a raw image
+pub fn info(path: &str) -> Result<RawInfo, String> {
+    info_with_scan(path, false)
+}
+
+/// Get information about a raw image; `full_scan` decompresses gzip images
+/// for their exact uncompressed size instead of trusting the ISIZE trailer
+#[instrument]
+pub fn info_with_scan(path: &str, full_scan: bool) -> Result<RawInfo, String> {
+    debug!("Getting raw image info");
+    let handle = RawHandle::open(path)?;
+    
+    // Extract just filenames for display
+    let segment_names: Vec<String> = handle.segments.iter()
+        .map(|p| p.file_name()
+            .map(|f| f.to_string_lossy().to_string())
+            .unwrap_or_default())
+        .collect();
+    
+    debug!(
+        segment_count = handle.segment_count(),
+        total_size = handle.total_size(),
+        "Raw image info loaded"
+    );
+
+    let sparse = match handle.segments.as_slice() {
+        [single] if sparse::is_sparse_image(single) => Some(sparse::SparseImage::open(single)?.info().clone()),
+        _ => None,
+    };
+    let gzip = match handle.segments.as_slice() {
+        [single] if gzip::is_gzip_image(single) => Some(gzip::info(single, full_scan)?),
+        _ => None,
+    };
+    let logical_size = match (&sparse, &gzip) {
+        (Some(sparse), _) => sparse.logical_size,
+        (_, Some(gzip)) => gzip.logical_size(),
+        _ => handle.total_size(),
+    };
+    
+    Ok(RawInfo {
+        segment_count: handle.segment_count() as u32,
+        total_size: handle.total_size(),
+        segment_sizes: handle.segment_sizes.clone(),
+        segment_names,
+        first_segment: handle.segments.first()
+            .map(|p| p.file_name()
+                .map(|f| f.to_string_lossy().to_string())
+                .unwrap_or_default())
+            .unwrap_or_default(),
+        last_segment: handle.segments.last()
+            .map(|p| p.file_name()
+                .map(|f| f.to_string_lossy().to_string())
+                .unwrap_or_default())
+            .unwrap_or_default(),
+        logical_size,
+        sparse,
+        gzip,
+    })
+}
+
+/// Whether `path` is a single-file Android sparse image
+pub fn is_sparse(path: &str) -> bool {
+    sparse::is_sparse_image(Path::new(path))
+        && discover_segments(path).is_ok_and(|(segments, _)| segments.len() == 1)
+}
+
+/// Check if a file is a raw image (by extension, or gzip content)
+pub fn is_raw(path: &str) -> Result<bool, String> {
+    let lower = path.to_lowercase();
+    
+    // Check common raw extensions
+    if lower.ends_with(".dd") || lower.ends_with(".raw") || lower.ends_with(".img") {
+        trace!(path, "Detected as raw by extension");
+        return Ok(true);
+    }
+
+    if lower.ends_with(".gz") {
+        return Ok(gzip::is_gzip_image(Path::new(path)));
+    }
+    
+    // Check split suffixes (.001, .0001, .dd.aa, .part1, etc.)
+    if SplitName::parse(&lower).is_some() {
+        return Ok(true);
+    }
+    
+    Ok(false)
+}
+
+/// Verify raw image with specified hash algorithm
+pub fn verify(path: &str, algorithm: &str) -> Result<String, String> {
+    verify_with_progress(path, algorithm, |_, _| {})
+}
+
+/// Verify with progress callback - OPTIMIZED with pipelined I/O and hashing
+pub fn verify_with_progress<F>(path: &str, algorithm: &str, progress_callback: F) -> Result<String, String>
+where
+    F: FnMut(u64, u64)
+{
+    verify_with_tuning(path, algorithm, &HashTuning::default(), progress_callback)
+}
+
+/// Hash the logical image: the expanded partition for Android sparse
+/// images, the plain image bytes for everything else
+pub fn verify_logical_with_progress<F>(path: &str, algorithm: &str, progress_callback: F) -> Result<String, String>
+where
+    F: FnMut(u64, u64)
+{
+    if is_sparse(path) {
+        sparse::hash_expanded(Path::new(path), algorithm, progress_callback)
+    } else {
+        verify_with_progress(path, algorithm, progress_callback)
+    }
+}
+
+/// Verify, with a zero/entropy map of the image when `options.collect_map`
+/// is set
+///
+/// The map needs every byte on one thread, so with it every algorithm goes
+/// through the pipelined reader. Gzip images are hashed and mapped as
+/// decompressed, like `verify_with_progress` hashes them.
+pub fn verify_report<F>(path: &str, algorithm: &str, options: &VerifyOptions, mut progress_callback: F) -> Result<VerifyResult, String>
+where
+    F: FnMut(u64, u64)
+{
+    let started = std::time::Instant::now();
+    let algo = HashAlgorithm::from_str(algorithm)?;
+    let mut map = options.collect_map.then(|| ContentMapBuilder::new(DEFAULT_MAP_WINDOW));
+    let (hash, total_size) = if is_block_device_path(path) {
+        let mut device = BlockDevice::open(path, false)?;
+        (device.hash(algo, map.as_mut(), &mut progress_callback)?, device.size())
+    } else if gzip::is_gzip_image(Path::new(path)) {
+        let mut hasher = StreamingHasher::new(algo);
+        let (total_size, _) = gzip::decompress(Path::new(path), |data| {
+            progress::pause_point(|| {});
+            hasher.update(data);
+            if let Some(map) = &mut map {
+                map.update(data);
+            }
+            Ok(())
+        }, &mut progress_callback)?;
+        (hasher.finalize(), total_size)
+    } else {
+        let total_size = RawHandle::open(path)?.total_size();
+        let hash = match map.take() {
+            Some(builder) => {
+                let (hash, builder) = verify_pipelined(path, algo, total_size, HashTuning::default().buffer_size(), Some(builder), &mut progress_callback)?;
+                map = builder;
+                hash
+            }
+            None => verify_with_progress(path, algorithm, &mut progress_callback)?,
+        };
+        (hash, total_size)
+    };
+
+    let duration_secs = started.elapsed().as_secs_f64();
+    Ok(VerifyResult {
+        algorithm: algo.name().to_string(),
+        hash,
+        total_size,
+        duration_secs,
+        throughput_mbs: if duration_secs > 0.0 { total_size as f64 / duration_secs / (1024.0 * 1024.0) } else { 0.0 },
+        content_map: map.map(ContentMapBuilder::finish),
+    })
+}
+
+/// Verify with progress callback, reading in `tuning.buffer_size()` chunks
+/// and hashing BLAKE3 on `tuning.blake3_threads` threads
+///
+/// A device path is hashed through [`block_device`], refusing the system
+/// drive (see [`block_device::verify_with_progress`] to force it).
+#[instrument(skip(progress_callback))]
+pub fn verify_with_tuning<F>(path: &str, algorithm: &str, tuning: &HashTuning, progress_callback: F) -> Result<String, String>
+where
+    F: FnMut(u64, u64)
+{
+    debug!("Starting raw image verification");
+    if is_block_device_path(path) {
+        return block_device::verify_with_progress(path, algorithm, false, progress_callback);
+    }
+    if gzip::is_gzip_image(Path::new(path)) {
+        // Hash what was compressed, not the compressed bytes
+        return gzip::verify_with_progress(Path::new(path), algorithm, progress_callback);
+    }
+    let handle = RawHandle::open(path)?;
+    let total_size = handle.total_size();
+    let algo = HashAlgorithm::from_str(algorithm)?;
+
+    debug!(algorithm = algo.name(), total_size, buffer_size = tuning.buffer_size(), "Verifying with algorithm");
+
+    match algo {
+        // For BLAKE3, use its built-in parallel hashing with memory-mapped I/O
+        HashAlgorithm::Blake3 => verify_blake3_optimized(path, total_size, tuning, progress_callback),
+        // For XXH3/XXH128, use memory-mapped I/O for maximum speed
+        HashAlgorithm::Xxh3 | HashAlgorithm::Xxh128 => verify_xxh3_optimized(path, total_size, tuning.buffer_size(), progress_callback),
+        // For other algorithms, use pipelined I/O -> hashing
+        _ => verify_pipelined(path, algo, total_size, tuning.buffer_size(), None, progress_callback).map(|(hash, _)| hash),
+    }
+}
+
+/// BLAKE3 optimized path - uses memory-mapped I/O + rayon parallel hashing
+fn verify_blake3_optimized<F>(path: &str, total_size: u64, tuning: &HashTuning, mut progress_callback: F) -> Result<String, String>
+where
+    F: FnMut(u64, u64)
+{
+    use memmap2::Mmap;
+    
+    let mut hasher = blake3::Hasher::new();
+    let buffer_size = tuning.buffer_size();
+    let pool = tuning.blake3_pool();
+    let segments = discover_segments(path)?.0;
+    let mut bytes_processed = 0u64;
+    let report_interval = (total_size / 50).max(buffer_size as u64); // Report ~50 times
+    let mut last_report = 0u64;
+    
+    for seg_path in &segments {
+        let file = File::open(seg_path)
+            .map_err(|e| device::describe_io_error(seg_path, "Failed to open segment", &e))?;
+        let seg_size = file.metadata()
+            .map_err(|e| format!("Failed to get segment size: {}", e))?
+            .len();
+        
+        // Use memory-mapped I/O for large segments (faster than buffered read)
+        if resources::use_mmap(seg_size) {
+            // SAFETY: File is opened read-only, mmap is safe for read access
+            let mmap = unsafe { Mmap::map(&file) }
+                .map_err(|e| format!("Failed to memory-map segment: {}", e))?;
+            
+            // Process in chunks for progress reporting
+            let chunk_size = buffer_size;
+            for chunk in mmap.chunks(chunk_size) {
+                progress::pause_point(|| {});
+                blake3_update(&mut hasher, chunk, pool.as_ref());
+                bytes_processed += chunk.len() as u64;
+                
+                if bytes_processed - last_report >= report_interval {
+                    progress_callback(bytes_processed, total_size);
+                    last_report = bytes_processed;
+                }
+            }
+        } else {
+            // Small files: use buffered read
+            use std::io::BufRead;
+            drop(file);
+            let file = RetryingReader::open(seg_path)
+                .map_err(|e| device::describe_io_error(seg_path, "Failed to open segment", &e))?;
+            let mut reader = std::io::BufReader::with_capacity(buffer_size, file);
+            
+            loop {
+                progress::pause_point(|| {});
+                let buf = reader.fill_buf()
+                    .map_err(|e| device::describe_io_error(seg_path, "Read error", &e))?;
+                let len = buf.len();
+                if len == 0 { break; }
+                
+                blake3_update(&mut hasher, buf, pool.as_ref());
+                reader.consume(len);
+                
+                bytes_processed += len as u64;
+                if bytes_processed - last_report >= report_interval {
+                    progress_callback(bytes_processed, total_size);
+                    last_report = bytes_processed;
+                }
+            }
+        }
+    }
+    
+    progress_callback(total_size, total_size);
+    Ok(hasher.finalize().to_hex().to_string())
+}
+
+/// XXH3 optimized path - uses memory-mapped I/O for maximum speed
+/// XXH3 is ~10x faster than SHA-256 for non-cryptographic checksums
+fn verify_xxh3_optimized<F>(path: &str, total_size: u64, buffer_size: usize, mut progress_callback: F) -> Result<String, String>
+where
+    F: FnMut(u64, u64)
+{
+    use memmap2::Mmap;
+    use xxhash_rust::xxh3::Xxh3;
+    
+    let mut hasher = Xxh3::new();
+    let segments = discover_segments(path)?.0;
+    let mut bytes_processed = 0u64;
+    let report_interval = (total_size / 50).max(buffer_size as u64);
+    let mut last_report = 0u64;
+    
+    for seg_path in &segments {
+        let file = File::open(seg_path)
+            .map_err(|e| device::describe_io_error(seg_path, "Failed to open segment", &e))?;
+        let seg_size = file.metadata()
+            .map_err(|e| format!("Failed to get segment size: {}", e))?
+            .len();
+        
+        // Use memory-mapped I/O for large segments
+        if resources::use_mmap(seg_size) {
+            let mmap = unsafe { Mmap::map(&file) }
+                .map_err(|e| format!("Failed to memory-map segment: {}", e))?;
+            
+            // Process in chunks for progress reporting
+            let chunk_size = buffer_size;
+            for chunk in mmap.chunks(chunk_size) {
+                progress::pause_point(|| {});
+                hasher.update(chunk);
+                bytes_processed += chunk.len() as u64;
+                
+                if bytes_processed - last_report >= report_interval {
+                    progress_callback(bytes_processed, total_size);
+                    last_report = bytes_processed;
+                }
+            }
+        } else {
+            // Small files: use buffered read
+            use std::io::BufRead;
+            drop(file);
+            let file = RetryingReader::open(seg_path)
+                .map_err(|e| device::describe_io_error(seg_path, "Failed to open segment", &e))?;
+            let mut reader = std::io::BufReader::with_capacity(buffer_size, file);
+            
+            loop {
+                progress::pause_point(|| {});
+                let buf = reader.fill_buf()
+                    .map_err(|e| device::describe_io_error(seg_path, "Read error", &e))?;
+                let len = buf.len();
+                if len == 0 { break; }
+                
+                hasher.update(buf);
+                reader.consume(len);
+                
+                bytes_processed += len as u64;
+                if bytes_processed - last_report >= report_interval {
+                    progress_callback(bytes_processed, total_size);
+                    last_report = bytes_processed;
+                }
+            }
+        }
+    }
+    
+    progress_callback(total_size, total_size);
+    Ok(format!("{:032x}", hasher.digest128()))
+}
+
+/// Pipelined verification: I/O thread feeds data to hashing thread, which
+/// also feeds `map` when given
+fn verify_pipelined<F>(
+    path: &str,
+    algo: HashAlgorithm,
+    total_size: u64,
+    buffer_size: usize,
+    mut map: Option<ContentMapBuilder>,
+    mut progress_callback: F,
+) -> Result<(String, Option<ContentMapBuilder>), String>
+where
+    F: FnMut(u64, u64)
+{
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::Arc;
+    
+    let segments = discover_segments(path)?.0;
+    
+    // Shared progress counter
+    let bytes_hashed = Arc::new(AtomicU64::new(0));
+    let bytes_hashed_clone = Arc::clone(&bytes_hashed);
+    
+    // Channel with 4 buffer slots for pipelining (allows I/O to stay ahead)
+    let (tx, rx) = mpsc::sync_channel::<Option<Vec<u8>>>(4);
+    
+    // I/O thread: reads segments and sends buffers, parking while the job
+    // is paused (the hashing thread then simply waits for the next one)
+    let pause_gate = progress::current_pause_gate();
+    let io_handle = thread::spawn(move || -> Result<(), String> {
+        progress::set_current_thread_pause_gate(pause_gate);
+        for seg_path in &segments {
+            let file = RetryingReader::open(seg_path)
+                .map_err(|e| device::describe_io_error(seg_path, &format!("Failed to open segment {:?}", seg_path), &e))?;
+            let mut reader = BufReader::with_capacity(buffer_size, file);
+            
+            loop {
+                progress::pause_point(|| {});
+                let mut buf = vec![0u8; buffer_size];
+                let bytes_read = reader.read(&mut buf)
+                    .map_err(|e| device::describe_io_error(seg_path, "Read error", &e))?;
+                
+                if bytes_read == 0 { break; }
+                
+                buf.truncate(bytes_read);
+                if tx.send(Some(buf)).is_err() {
+                    return Err("Hash thread terminated early".to_string());
+                }
+            }
+        }
+        // Signal completion
+        let _ = tx.send(None);
+        Ok(())
+    });
+    
+    // Hashing thread: receives buffers and updates hash using StreamingHasher
+    // (its span is opened here, inside the job's span)
+    let hashing = perf::phase(perf::HASHING);
+    let hash_handle = thread::spawn(move || -> Result<(String, Option<ContentMapBuilder>), String> {
+        let _entered = hashing.enter();
+        let mut hasher = StreamingHasher::new(algo);
+        let mut hashed = 0u64;
+        
+        // Process incoming buffers
+        while let Ok(Some(buf)) = rx.recv() {
+            let len = buf.len() as u64;
+            hasher.update(&buf);
+            if let Some(map) = &mut map {
+                map.update(&buf);
+            }
+            bytes_hashed_clone.fetch_add(len, Ordering::Relaxed);
+            hashed += len;
+        }
+        hashing.record("bytes", hashed);
+        
+        // Finalize and return hash
+        Ok((hasher.finalize(), map))
+    });
+    
+    // Progress reporting in main thread
+    let report_interval = (total_size / 100).max(1);
+    let mut last_reported = 0u64;
+    
+    loop {
+        let current = bytes_hashed.load(Ordering::Relaxed);
+        if current >= total_size { break; }
+        
+        if current - last_reported >= report_interval {
+            progress_callback(current, total_size);
+            last_reported = current;
+        }
+        
+        // Check if I/O thread finished
+        if io_handle.is_finished() {
+            break;
+        }
+        
+        thread::sleep(std::time::Duration::from_millis(50));
+    }
+    
+    // Wait for threads
+    io_handle.join()
+        .map_err(|_| "I/O thread panicked")?
+        .map_err(|e| format!("I/O error: {}", e))?;
+    
+    let hash = hash_handle.join()
+        .map_err(|_| "Hash thread panicked")?
+        .map_err(|e| format!("Hash error: {}", e))?;
+    
+    progress_callback(total_size, total_size);
+    Ok(hash)
+}
+
+/// Result of verifying a single segment
+#[derive(Serialize, Clone)]
+pub struct SegmentVerifyResult {
+    pub segment_name: String,
+    pub segment_number: u32,
+    pub algorithm: String,
+    pub computed_hash: String,
+    pub expected_hash: Option<String>,
+    pub verified: Option<bool>,  // None = no expected hash, Some(true) = match, Some(false) = mismatch
+    pub size: u64,
+    pub duration_secs: f64,
+}
+
+/// Verify a single segment file and return hash - OPTIMIZED with buffered I/O
+#[instrument(skip(progress_callback))]
+pub fn hash_single_segment<F>(segment_path: &str, algorithm: &str, mut progress_callback: F) -> Result<String, String>
+where
+    F: FnMut(u64, u64)
+{
+    use std::io::BufRead;
+    
+    let path = Path::new(segment_path);
+    if !path.exists() {
+        return Err(format!("Segment file not found: {}", segment_path));
+    }
+    
+    let metadata = std::fs::metadata(path)
+        .map_err(|e| format!("Failed to get file metadata: {}", e))?;
+    let total_size = metadata.len();
+    
+    debug!(segment_path, algorithm, total_size, "Hashing single segment");
+    
+    let file = RetryingReader::open(path)
+        .map_err(|e| format!("Failed to open segment: {}", e))?;
+    let mut reader = BufReader::with_capacity(resources::buffer_size(BUFFER_SIZE), file);
+    
+    let algo = HashAlgorithm::from_str(algorithm)?;
+    
+    // For BLAKE3, use parallel hashing for best performance
+    if algo == HashAlgorithm::Blake3 {
+        trace!("Using BLAKE3 parallel hashing");
+        let mut hasher = blake3::Hasher::new();
+        let mut bytes_read_total = 0u64;
+        let report_interval = (total_size / 20).max(BUFFER_SIZE as u64);
+        let mut last_report = 0u64;
+        
+        loop {
+            let buf = reader.fill_buf()
+                .map_err(|e| format!("Read error: {}", e))?;
+            let len = buf.len();
+            if len == 0 { break; }
+            
+            hasher.update_rayon(buf);
+            reader.consume(len);
+            
+            bytes_read_total += len as u64;
+            if bytes_read_total - last_report >= report_interval {
+                progress_callback(bytes_read_total, total_size);
+                last_report = bytes_read_total;
+            }
+        }
+        
+        progress_callback(total_size, total_size);
+        let hash = hasher.finalize().to_hex().to_string();
+        info!(segment_path, hash = hash.as_str(), "Segment hash complete");
+        return Ok(hash);
+    }
+    
+    // For other algorithms, use StreamingHasher
+    let mut hasher = StreamingHasher::new(algo);
+    
+    let mut bytes_read_total = 0u64;
+    let report_interval = (total_size / 20).max(BUFFER_SIZE as u64);
+    let mut last_report = 0u64;
+    
+    loop {
+        let buf = reader.fill_buf()
+            .map_err(|e| format!("Read error: {}", e))?;
+        let len = buf.len();
+        if len == 0 { break; }
+        
+        hasher.update(buf);
+        reader.consume(len);
+        bytes_read_total += len as u64;
+        
+        if bytes_read_total - last_report >= report_interval {
+            progress_callback(bytes_read_total, total_size);
+            last_report = bytes_read_total;
+        }
+    }
+    
+    progress_callback(total_size, total_size);
+    Ok(hasher.finalize())
+}
+
+/// Get all segment file paths for a raw image
+pub fn get_segment_paths(path: &str) -> Result<Vec<PathBuf>, String> {
+    let (segments, _) = discover_segments(path)?;
+    Ok(segments)
+}
+
+/// Check that `segments` are the segments of one split image in order, by
+/// their numeric (or letter) suffixes
+pub fn validate_segment_list(segments: &[String]) -> Result<Vec<PathBuf>, String> {
+    let paths: Vec<PathBuf> = segments.iter().map(PathBuf::from).collect();
+    let name = |path: &PathBuf| path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let Some(first_path) = paths.first() else {
+        return Err("No segment files listed".to_string());
+    };
+    let Some(first) = SplitName::parse(&name(first_path)) else {
+        if paths.len() == 1 {
+            return Ok(paths);
+        }
+        return Err(format!("{} is not named like a segment of a split image", name(first_path)));
+    };
+    if !first.is_first() {
+        return Err(format!("{} is not the first segment of its set", name(first_path)));
+    }
+    for (index, path) in (first.index..).zip(&paths).skip(1) {
+        let listed = SplitName::parse(&name(path)).filter(|split| split.same_set(&first));
+        if listed.as_ref().map(|split| split.index) != Some(index) {
+            let expected = first.sibling(index).unwrap_or_else(|| format!("#{}", index));
+            return Err(format!("{} is listed where {} belongs", name(path), expected));
+        }
+    }
+    Ok(paths)
+}
+
+/// Run `work` on the image at `path` with its segments taken from
+/// `segments` instead of the directory of `path`
+///
+/// The list is checked with [`validate_segment_list`] first; `path` must be
+/// one of its files.
+pub fn with_segment_list<T>(path: &str, segments: &[String], work: impl FnOnce() -> Result<T, String>) -> Result<T, String> {
+    let paths = validate_segment_list(segments)?;
+    if !paths.iter().any(|segment| segment == Path::new(path)) {
+        return Err(format!("{} is not one of the listed segment files", path));
+    }
+    debug!(path, segment_count = paths.len(), "Raw image given as a segment list");
+    with_listed_segments(paths, work)
+}
+
+/// Header-only structure check: segment numbering has no gaps and no
+/// segment is empty
+pub fn quick_check(path: &str) -> Result<Vec<StructureCheck>, String> {
+    let (segments, sizes) = match discover_segments(path) {
+        Ok(found) => found,
+        Err(error) => return Ok(vec![StructureCheck::fail("Segment continuity", error)]),
+    };
+    let total: u64 = sizes.iter().sum();
+    let empty: Vec<String> = segments.iter().zip(&sizes)
+        .filter(|(_, &size)| size == 0)
+        .map(|(segment, _)| segment.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default())
+        .collect();
+    Ok(vec![
+        StructureCheck::pass("Segment continuity", format!("{} segment(s) without gaps", segments.len())),
+        if empty.is_empty() {
+            StructureCheck::pass("Segment sizes", format!("{} bytes in total", total))
+        } else {
+            StructureCheck::fail("Segment sizes", format!("Empty segment(s): {}", empty.join(", ")))
+        },
+    ])
+}
+
+/// Extract raw image to a single file (useful for reassembling multi-segment)
+///
+/// Android sparse images are written expanded and gzip images decompressed.
+/// The written bytes are hashed on the way out for the extraction manifest.
+pub fn extract(path: &str, output_path: &str) -> Result<ExtractedFile, String> {
+    use std::io::Write;
+
+    if is_sparse(path) {
+        return sparse::expand_to_file(Path::new(path), Path::new(output_path), |_, _| {});
+    }
+    if gzip::is_gzip_image(Path::new(path)) {
+        return gzip::decompress_to_file(Path::new(path), Path::new(output_path));
+    }
+    
+    let mut handle = RawHandle::open(path)?;
+    let output = AtomicFile::create(Path::new(output_path))
+        .and_then(|output| output.with_progress_markers(Some(handle.total_size())))
+        .map_err(|e| format!("Failed to create output file: {}", e))?;
+    let mut output = HashingWriter::new(output);
+
+    let mut buf = vec![0u8; resources::buffer_size(BUFFER_SIZE)];
+    let writing = perf::phase(perf::WRITING).entered();
+    let mut written = 0u64;
+    
+    loop {
+        let bytes_read = handle.read(&mut buf)?;
+        if bytes_read == 0 {
+            break;
+        }
+        output.write_all(&buf[..bytes_read])
+            .map_err(|e| format!("Write failed: {}", e))?;
+        written += bytes_read as u64;
+    }
+    writing.record("bytes", written);
+    drop(writing);
+
+    let image_name = Path::new(path).file_name()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    output.commit(&image_name)
+}
+
+// =============================================================================
+// Consolidation
+// =============================================================================
+
+/// Options for [`consolidate`]
+#[derive(Deserialize, Serialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ConsolidateOptions {
+    /// Algorithm for the source and destination hashes (default SHA-256)
+    #[serde(default)]
+    pub algorithm: Option<String>,
+    /// Replace existing output files instead of failing
+    #[serde(default)]
+    pub overwrite: bool,
+    /// Write `<output>.001`, `<output>.002`, ... of at most this many bytes
+    /// instead of one file
+    #[serde(default)]
+    pub split_size: Option<u64>,
+}
+
+/// Result of [`consolidate`]
+#[derive(Serialize, Clone, Debug)]
+pub struct ConsolidateResult {
+    /// Written files in order (one unless `split_size` was set)
+    pub output_files: Vec<String>,
+    pub segment_count: usize,
+    pub bytes_written: u64,
+    pub algorithm: String,
+    /// Hash of the bytes read from the segment set
+    pub source_hash: String,
+    /// Hash of the bytes written to the output file(s)
+    pub destination_hash: String,
+    /// Hashes and byte counts of source and destination agree
+    pub verified: bool,
+}
+
+/// Output file(s) of a consolidation, rolling over at the split size
+struct ConsolidateWriter {
+    paths: Vec<PathBuf>,
+    part_size: u64,
+    overwrite: bool,
+    current: Option<AtomicFile>,
+    /// Index in `paths` of the file being written
+    index: usize,
+    /// Files created so far (the first `created` of `paths`)
+    created: usize,
+    written_in_part: u64,
+    hasher: StreamingHasher,
+}
+
+impl ConsolidateWriter {
+    fn open(path: &Path, overwrite: bool) -> Result<AtomicFile, String> {
+        if !overwrite && path.exists() {
+            return Err(format!("Output file {} already exists - pass overwrite to replace it", path.display()));
+        }
+        AtomicFile::create_with_capacity(path, resources::buffer_size(BUFFER_SIZE))
+            .and_then(|file| file.with_progress_markers(None))
+            .map_err(|e| format!("Failed to create output file {}: {}", path.display(), e))
+    }
+
+    /// Sync the current file to disk and move it into place
+    fn close_current(&mut self) -> Result<(), String> {
+        if let Some(file) = self.current.take() {
+            let path = &self.paths[self.index];
+            file.commit()
+                .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        }
+        Ok(())
+    }
+
+    fn write(&mut self, mut data: &[u8]) -> Result<(), String> {
+        while !data.is_empty() {
+            if self.written_in_part == self.part_size {
+                self.close_current()?;
+                self.index += 1;
+                self.written_in_part = 0;
+            }
+            if self.current.is_none() {
+                let path = self.paths.get(self.index)
+                    .ok_or_else(|| "Image is larger than the planned output files".to_string())?;
+                self.current = Some(Self::open(path, self.overwrite)?);
+                self.created += 1;
+            }
+            let room = (self.part_size - self.written_in_part).min(data.len() as u64) as usize;
+            let (chunk, rest) = data.split_at(room);
+            self.current.as_mut().expect("output file opened above")
+                .write_all(chunk)
+                .map_err(|e| format!("Write failed: {}", e))?;
+            self.hasher.update(chunk);
+            self.written_in_part += room as u64;
+            data = rest;
+        }
+        Ok(())
+    }
+}
+
+/// Stream all segments of a raw image, in order, into one contiguous image
+///
+/// With `split_size` the output is `<output>.001`, `<output>.002`, ...
+/// instead. Refuses a segment set with gaps, an output inside the evidence
+/// folder, and existing output files unless `options.overwrite` is set.
+/// Progress is reported in bytes across the whole set. On failure the
+/// partially written output is removed.
+#[instrument(skip(options, progress_callback))]
+pub fn consolidate<F>(path: &str, output_path: &str, options: &ConsolidateOptions, mut progress_callback: F) -> Result<ConsolidateResult, String>
+where
+    F: FnMut(u64, u64)
+{
+    let set = crate::containers::segment_set_info(path, None)
+        .ok_or_else(|| format!("Cannot determine the segment set of {}", path))?;
+    if !set.missing.is_empty() {
+        return Err(format!("Segment set has gaps - missing {}", set.missing.join(", ")));
+    }
+
+    let algorithm = HashAlgorithm::from_str(options.algorithm.as_deref().unwrap_or("sha256"))?;
+    if options.split_size == Some(0) {
+        return Err("Split size must be greater than zero".to_string());
+    }
+
+    let mut handle = RawHandle::open(path)?;
+    let total_size = handle.total_size();
+    let output = PathBuf::from(output_path);
+    let output_dir = output.parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    check_output_location(&handle.segments, output_dir)?;
+
+    // Plan every output name up front so a collision fails before any write
+    let (paths, part_size) = match options.split_size {
+        Some(size) => {
+            let parts = total_size.div_ceil(size).max(1);
+            let paths = (1..=parts).map(|n| PathBuf::from(format!("{}.{:03}", output_path, n))).collect();
+            (paths, size)
+        }
+        None => (vec![output], u64::MAX),
+    };
+    if !options.overwrite {
+        if let Some(existing) = paths.iter().find(|p| p.exists()) {
+            return Err(format!("Output file {} already exists - pass overwrite to replace it", existing.display()));
+        }
+    }
+
+    info!(segments = handle.segment_count(), total_size, outputs = paths.len(), "Consolidating raw image");
+    let mut writer = ConsolidateWriter {
+        paths,
+        part_size,
+        overwrite: options.overwrite,
+        current: None,
+        index: 0,
+        created: 0,
+        written_in_part: 0,
+        hasher: StreamingHasher::new(algorithm),
+    };
+    let mut source = StreamingHasher::new(algorithm);
+    let mut buf = vec![0u8; resources::buffer_size(BUFFER_SIZE)];
+    let mut bytes_read = 0u64;
+
+    let copied = (|| -> Result<(), String> {
+        loop {
+            let n = handle.read(&mut buf)?;
+            if n == 0 {
+                break;
+            }
+            source.update(&buf[..n]);
+            writer.write(&buf[..n])?;
+            bytes_read += n as u64;
+            progress_callback(bytes_read, total_size);
+        }
+        // An empty image still gets its (empty) output file
+        if writer.created == 0 {
+            writer.current = Some(ConsolidateWriter::open(&writer.paths[0], writer.overwrite)?);
+            writer.created = 1;
+        }
+        writer.close_current()
+    })();
+
+    let written = &writer.paths[..writer.created];
+    if let Err(e) = copied {
+        for partial in written {
+            let _ = std::fs::remove_file(partial);
+        }
+        return Err(e);
+    }
+
+    let source_hash = source.finalize();
+    let destination_hash = writer.hasher.finalize();
+    let verified = source_hash == destination_hash && bytes_read == total_size;
+    info!(bytes_read, verified, "Raw image consolidated");
+
+    Ok(ConsolidateResult {
+        output_files: written.iter().map(|p| p.to_string_lossy().to_string()).collect(),
+        segment_count: handle.segment_count(),
+        bytes_written: bytes_read,
+        algorithm: algorithm.name().to_string(),
+        source_hash,
+        destination_hash,
+        verified,
+    })
+}
+
+// =============================================================================
+// Helper Functions  
+// =============================================================================
+
+/// Discover all segments for a raw image - uses common segment discovery
+fn discover_segments(path: &str) -> Result<(Vec<std::path::PathBuf>, Vec<u64>), String> {
+    if is_block_device_path(path) {
+        return Err(format!("{} is a drive, not an image file - it can only be hashed or triaged in place", path));
+    }
+    trace!(path, "Discovering raw image segments");
+    discover_numbered_segments(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_raw() {
+        assert!(is_raw("/path/to/image.dd").unwrap());
+        assert!(is_raw("/path/to/image.raw").unwrap());
+        assert!(is_raw("/path/to/image.img").unwrap());
+        assert!(is_raw("/path/to/image.001").unwrap());
+        assert!(is_raw("/path/to/image.002").unwrap());
+        assert!(!is_raw("/path/to/image.e01").unwrap());
+        assert!(!is_raw("/path/to/image.ad1").unwrap());
+        assert!(is_raw("/path/to/image.dd.aa").unwrap());
+        assert!(is_raw("/path/to/image.part3").unwrap());
+    }
+
+    #[test]
+    fn test_quick_check_flags_gaps_and_empty_segments() {
+        let dir = tempfile::tempdir().unwrap();
+        for (name, size) in [("disk.001", 512), ("disk.002", 512), ("disk.003", 0)] {
+            std::fs::write(dir.path().join(name), vec![0u8; size]).unwrap();
+        }
+        let first = dir.path().join("disk.001");
+        let checks = quick_check(first.to_str().unwrap()).unwrap();
+        assert!(checks[0].passed, "{checks:?}");
+        assert_eq!(checks[1], StructureCheck::fail("Segment sizes", "Empty segment(s): disk.003"));
+
+        std::fs::remove_file(dir.path().join("disk.002")).unwrap();
+        let checks = quick_check(first.to_str().unwrap()).unwrap();
+        assert_eq!(checks.len(), 1);
+        assert!(!checks[0].passed && checks[0].detail.contains("disk.002"), "{checks:?}");
+    }
+
+    #[test]
+    fn test_low_memory_hash_of_multi_gigabyte_sparse_file() {
+        // Data only at both ends - the 4.5GB in between is a hole, so the
+        // fixture takes no real disk space
+        const SIZE: u64 = (4 << 30) + (512 << 20);
+        const HEAD: &[u8] = b"first sector of the image";
+        const TAIL: &[u8] = b"last bytes of the image";
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("large.dd");
+        let mut file = File::create(&path).unwrap();
+        file.write_all(HEAD).unwrap();
+        file.seek(SeekFrom::Start(SIZE - TAIL.len() as u64)).unwrap();
+        file.write_all(TAIL).unwrap();
+        drop(file);
+
+        let mut expected = xxhash_rust::xxh3::Xxh3::new();
+        expected.update(HEAD);
+        let zeros = vec![0u8; resources::LOW_MEMORY_BUFFER_SIZE];
+        let mut remaining = SIZE - (HEAD.len() + TAIL.len()) as u64;
+        while remaining > 0 {
+            let n = remaining.min(zeros.len() as u64) as usize;
+            expected.update(&zeros[..n]);
+            remaining -= n as u64;
+        }
+        expected.update(TAIL);
+
+        // XXH3 memory-maps large files unless low-memory mode is on
+        resources::set_current_thread_low_memory(true);
+        let mut last = (0, 0);
+        let hash = verify_with_progress(path.to_str().unwrap(), "xxh128", |done, total| last = (done, total));
+        resources::set_current_thread_low_memory(false);
+        assert_eq!(hash.unwrap(), format!("{:032x}", expected.digest128()));
+        assert_eq!(last, (SIZE, SIZE));
+    }
+
+    #[test]
+    fn test_letter_split_hash_matches_whole_image() {
+        let dir = tempfile::tempdir().unwrap();
+        let data: Vec<u8> = (0..3000u32).map(|i| (i % 251) as u8).collect();
+        let whole = dir.path().join("whole.dd");
+        std::fs::write(&whole, &data).unwrap();
+        for (name, chunk) in ["split.dd.aa", "split.dd.ab", "split.dd.ac"].iter().zip(data.chunks(1024)) {
+            std::fs::write(dir.path().join(name), chunk).unwrap();
+        }
+
+        let expected = verify(whole.to_str().unwrap(), "sha256").unwrap();
+        let split = dir.path().join("split.dd.ab");
+        assert_eq!(verify(split.to_str().unwrap(), "sha256").unwrap(), expected);
+    }
+
+    #[test]
+    fn test_paused_verification_resumes_with_same_hash() {
+        use crate::jobs::{JobKind, JobRegistry, JobStatus};
+
+        let dir = tempfile::tempdir().unwrap();
+        let data: Vec<u8> = (0..64 * 1024u32).map(|i| (i % 251) as u8).collect();
+        let image = dir.path().join("disk.dd");
+        std::fs::write(&image, &data).unwrap();
+        let path = image.to_string_lossy().to_string();
+        let total = data.len() as u64;
+        let expected = verify_xxh3_optimized(&path, total, 1024, |_, _| {}).unwrap();
+
+        let registry = JobRegistry::default();
+        let job = registry.start(JobKind::Verify, vec![path.clone()]);
+        let id = job.id().to_string();
+        let worker = {
+            let (registry, id) = (registry.clone(), id.clone());
+            thread::spawn(move || {
+                let mut paused = false;
+                let hash = job.pausable(|| verify_xxh3_optimized(&path, total, 1024, |done, total| {
+                    job.progress(done, total);
+                    // Pause once, part-way through
+                    if !paused && done < total {
+                        paused = registry.pause(&id).unwrap();
+                    }
+                }));
+                job.finish(hash.clone());
+                hash
+            })
+        };
+
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(10);
+        while registry.get(&id).unwrap().status != JobStatus::Paused {
+            assert!(std::time::Instant::now() < deadline, "verification was never paused");
+            thread::sleep(std::time::Duration::from_millis(5));
+        }
+        let parked = registry.get(&id).unwrap().progress;
+        thread::sleep(std::time::Duration::from_millis(200));
+        assert!(!worker.is_finished());
+        assert_eq!(registry.get(&id).unwrap().progress, parked);
+        assert_eq!(parked.remaining, total - parked.current);
+        assert!(parked.remaining > 0);
+
+        assert!(registry.resume(&id).unwrap());
+        assert_eq!(worker.join().unwrap().unwrap(), expected);
+        assert_eq!(registry.get(&id).unwrap().status, JobStatus::Completed);
+    }
+
+    fn with_map() -> VerifyOptions {
+        VerifyOptions { collect_map: true, ..Default::default() }
+    }
+
+    /// Three numbered segments (2048 + 2048 + 904 bytes) and their contents
+    fn segmented_fixture() -> (tempfile::TempDir, String, Vec<u8>) {
+        let evidence = tempfile::tempdir().unwrap();
+        let data: Vec<u8> = (0..5000u32).map(|i| (i % 251) as u8).collect();
+        for (i, chunk) in data.chunks(2048).enumerate() {
+            std::fs::write(evidence.path().join(format!("disk.{:03}", i + 1)), chunk).unwrap();
+        }
+        let first = evidence.path().join("disk.001").to_string_lossy().to_string();
+        (evidence, first, data)
+    }
+
+    #[test]
+    fn test_segments_listed_across_directories_match_one_directory() {
+        let (evidence, first, data) = segmented_fixture();
+        let (drive_a, drive_b) = (tempfile::tempdir().unwrap(), tempfile::tempdir().unwrap());
+        let listed: Vec<String> = [(&drive_a, "disk.001"), (&drive_b, "disk.002"), (&drive_b, "disk.003")].iter()
+            .map(|(dir, name)| {
+                std::fs::copy(evidence.path().join(name), dir.path().join(name)).unwrap();
+                dir.path().join(name).to_string_lossy().to_string()
+            })
+            .collect();
+
+        let expected = verify_report(&first, "sha1", &with_map(), |_, _| {}).unwrap();
+        let report = with_segment_list(&listed[0], &listed, || verify_report(&listed[0], "sha1", &with_map(), |_, _| {})).unwrap();
+        assert_eq!((&report.hash, report.total_size, &report.content_map), (&expected.hash, data.len() as u64, &expected.content_map));
+        // Without the list only the segment beside the first is found
+        assert_eq!(info(&listed[0]).unwrap().segment_count, 1);
+        let listed_info = with_segment_list(&listed[0], &listed, || info(&listed[0])).unwrap();
+        assert_eq!(serde_json::to_value(listed_info).unwrap(), serde_json::to_value(info(&first).unwrap()).unwrap());
+        assert_eq!(RawHandle::open_with_segments(listed.clone()).unwrap().total_size(), data.len() as u64);
+
+        let gap = [listed[0].clone(), listed[2].clone()];
+        assert_eq!(validate_segment_list(&gap).unwrap_err(), "disk.003 is listed where disk.002 belongs");
+        assert!(validate_segment_list(&listed[1..]).unwrap_err().contains("not the first segment"));
+    }
+
+    #[test]
+    fn test_verify_report_maps_segmented_and_gzip_images() {
+        let (evidence, first, data) = segmented_fixture();
+        let mut expected = ContentMapBuilder::new(DEFAULT_MAP_WINDOW);
+        expected.update(&data);
+        let expected = expected.finish();
+
+        let report = verify_report(&first, "blake3", &with_map(), |_, _| {}).unwrap();
+        assert_eq!(report.hash, verify(&first, "blake3").unwrap());
+        assert_eq!(report.total_size, data.len() as u64);
+        assert_eq!(report.content_map.as_ref(), Some(&expected));
+        assert!(verify_report(&first, "md5", &VerifyOptions::default(), |_, _| {}).unwrap().content_map.is_none());
+
+        // Compressed images are mapped as the disk they hold
+        let gz = evidence.path().join("disk.dd.gz");
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::fast());
+        encoder.write_all(&data).unwrap();
+        std::fs::write(&gz, encoder.finish().unwrap()).unwrap();
+        let report = verify_report(gz.to_str().unwrap(), "md5", &with_map(), |_, _| {}).unwrap();
+        assert_eq!(report.hash, verify(&first, "md5").unwrap());
+        assert_eq!(report.content_map, Some(expected));
+    }
+
+    #[test]
+    fn test_consolidate_output_hash_matches_whole_set() {
+        let (_evidence, first, data) = segmented_fixture();
+        let out_dir = tempfile::tempdir().unwrap();
+        let output = out_dir.path().join("disk.dd").to_string_lossy().to_string();
+
+        let mut last_progress = (0, 0);
+        let result = consolidate(&first, &output, &ConsolidateOptions::default(), |current, total| {
+            last_progress = (current, total);
+        }).unwrap();
+        assert!(result.verified);
+        assert_eq!(last_progress, (5000, 5000));
+        assert_eq!(result.segment_count, 3);
+        assert_eq!(result.output_files, vec![output.clone()]);
+        assert_eq!(std::fs::read(&output).unwrap(), data);
+        let whole_set = verify(&first, "sha256").unwrap();
+        assert_eq!(result.source_hash, whole_set);
+        assert_eq!(crate::common::hash::hash_file(Path::new(&output), "sha256").unwrap(), whole_set);
+
+        // An existing output is only replaced when asked to
+        let err = consolidate(&first, &output, &ConsolidateOptions::default(), |_, _| {}).unwrap_err();
+        assert!(err.contains("already exists"), "{err}");
+        let options = ConsolidateOptions { overwrite: true, split_size: Some(2000), ..Default::default() };
+        let split = consolidate(&first, &output, &options, |_, _| {}).unwrap();
+        assert_eq!(split.output_files.len(), 3);
+        assert_eq!(split.destination_hash, whole_set);
+        assert_eq!(std::fs::metadata(&split.output_files[2]).unwrap().len(), 1000);
+        assert_eq!(verify(&split.output_files[0], "sha256").unwrap(), whole_set);
+    }
+
+    #[test]
+    fn test_consolidate_refuses_gaps_and_evidence_folder() {
+        let (evidence, first, _) = segmented_fixture();
+        let inside = evidence.path().join("disk.dd").to_string_lossy().to_string();
+        assert!(consolidate(&first, &inside, &ConsolidateOptions::default(), |_, _| {}).is_err());
+        assert!(!Path::new(&inside).exists());
+
+        std::fs::remove_file(evidence.path().join("disk.002")).unwrap();
+        let out_dir = tempfile::tempdir().unwrap();
+        let output = out_dir.path().join("disk.dd").to_string_lossy().to_string();
+        let err = consolidate(&first, &output, &ConsolidateOptions::default(), |_, _| {}).unwrap_err();
+        assert!(err.contains("disk.002"), "{err}");
+        assert!(!Path::new(&output).exists());
+    }
+}
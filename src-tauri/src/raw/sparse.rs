@@ -0,0 +1,426 @@
+//! Android sparse images (simg)
+//!
+//! Android physical extractions often arrive as sparse images: a 28-byte
+//! header followed by chunks that each describe a run of blocks of the
+//! partition. Only `RAW` chunks carry data; `FILL` repeats a 4-byte
+//! pattern, `DONT_CARE` stands for blocks with undefined content (written
+//! as zeros, like `simg2img`) and `CRC32` holds a checksum of everything
+//! expanded so far.
+//!
+//! ```text
+//! ┌────────────────┬───────────────┬──────────┬───────────────┬─────
+//! │ header (28 B)  │ chunk hdr RAW │ data ... │ chunk hdr FILL│ ...
+//! │ magic ED26FF3A │ (12 B)        │          │ + pattern     │
+//! └────────────────┴───────────────┴──────────┴───────────────┴─────
+//! ```
+//!
+//! Hashing the container bytes never matches the hash of the dd image of
+//! the same partition another lab computed, so this module hashes and
+//! writes the *expanded* logical image.
+
+use std::fs::File;
+use std::io::{BufReader, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+use serde::Serialize;
+use tracing::{debug, instrument};
+
+use crate::common::atomic_file::AtomicFile;
+use crate::common::binary::{read_u16_le, read_u32_le};
+use crate::common::extract_manifest::{ExtractedFile, HashingWriter};
+use crate::common::hash::{HashAlgorithm, StreamingHasher};
+use crate::common::path_security::check_output_location;
+use crate::common::{progress as job_progress, resources};
+
+/// First four bytes of a sparse image (3A FF 26 ED on disk)
+pub const SPARSE_MAGIC: u32 = 0xED26_FF3A;
+
+const FILE_HEADER_SIZE: u16 = 28;
+const CHUNK_HEADER_SIZE: u16 = 12;
+
+const CHUNK_TYPE_RAW: u16 = 0xCAC1;
+const CHUNK_TYPE_FILL: u16 = 0xCAC2;
+const CHUNK_TYPE_DONT_CARE: u16 = 0xCAC3;
+const CHUNK_TYPE_CRC32: u16 = 0xCAC4;
+
+/// Sparse layout summary shown next to the raw image info
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SparseInfo {
+    /// Format version, e.g. "1.0"
+    pub version: String,
+    pub block_size: u32,
+    pub total_blocks: u32,
+    pub chunk_count: u32,
+    pub raw_chunks: u32,
+    pub fill_chunks: u32,
+    pub dont_care_chunks: u32,
+    pub crc32_chunks: u32,
+    /// Size of the expanded partition image
+    pub logical_size: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ChunkKind {
+    /// Data stored at this offset of the sparse file
+    Raw { data_offset: u64 },
+    Fill { pattern: [u8; 4] },
+    DontCare,
+    /// Checksum of all expanded data before this chunk
+    Crc32 { crc: u32 },
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Chunk {
+    kind: ChunkKind,
+    blocks: u32,
+}
+
+/// Parsed header and chunk table of a sparse image
+#[derive(Debug)]
+pub struct SparseImage {
+    file: File,
+    info: SparseInfo,
+    chunks: Vec<Chunk>,
+}
+
+/// Whether the file starts with the sparse image magic
+pub fn is_sparse_image(path: &Path) -> bool {
+    let mut magic = [0u8; 4];
+    File::open(path)
+        .and_then(|mut file| file.read_exact(&mut magic))
+        .is_ok_and(|_| u32::from_le_bytes(magic) == SPARSE_MAGIC)
+}
+
+impl SparseImage {
+    /// Read the header and walk the chunk table, validating sizes and block counts
+    #[instrument]
+    pub fn open(path: &Path) -> Result<Self, String> {
+        let file = File::open(path).map_err(|e| format!("Failed to open sparse image {}: {}", path.display(), e))?;
+        let container_size = file.metadata().map_err(|e| format!("Failed to read sparse image size: {e}"))?.len();
+        let mut reader = BufReader::new(file);
+
+        let magic = read_u32_le(&mut reader)?;
+        if magic != SPARSE_MAGIC {
+            return Err(format!("Not an Android sparse image (magic {magic:#010x})"));
+        }
+        let major = read_u16_le(&mut reader)?;
+        let minor = read_u16_le(&mut reader)?;
+        if major != 1 {
+            return Err(format!("Unsupported sparse image version {major}.{minor}"));
+        }
+        let file_header_size = read_u16_le(&mut reader)?;
+        let chunk_header_size = read_u16_le(&mut reader)?;
+        let block_size = read_u32_le(&mut reader)?;
+        let total_blocks = read_u32_le(&mut reader)?;
+        let chunk_count = read_u32_le(&mut reader)?;
+        let _image_checksum = read_u32_le(&mut reader)?;
+        if file_header_size < FILE_HEADER_SIZE || chunk_header_size < CHUNK_HEADER_SIZE {
+            return Err(format!("Corrupt sparse header: header sizes {file_header_size}/{chunk_header_size}"));
+        }
+        if block_size == 0 || block_size % 4 != 0 {
+            return Err(format!("Corrupt sparse header: block size {block_size}"));
+        }
+
+        let mut info = SparseInfo {
+            version: format!("{major}.{minor}"),
+            block_size,
+            total_blocks,
+            chunk_count,
+            raw_chunks: 0,
+            fill_chunks: 0,
+            dont_care_chunks: 0,
+            crc32_chunks: 0,
+            logical_size: total_blocks as u64 * block_size as u64,
+        };
+        let mut chunks = Vec::with_capacity(chunk_count.min(1 << 20) as usize);
+        let mut offset = file_header_size as u64;
+        let mut blocks_seen = 0u64;
+        for index in 0..chunk_count {
+            reader.seek(SeekFrom::Start(offset)).map_err(|e| format!("Failed to seek to sparse chunk {index}: {e}"))?;
+            let chunk_type = read_u16_le(&mut reader)?;
+            let _reserved = read_u16_le(&mut reader)?;
+            let blocks = read_u32_le(&mut reader)?;
+            let total_size = read_u32_le(&mut reader)? as u64;
+            let body_offset = offset + chunk_header_size as u64;
+            reader.seek(SeekFrom::Start(body_offset)).map_err(|e| format!("Failed to seek to sparse chunk {index}: {e}"))?;
+
+            let data_size = blocks as u64 * block_size as u64;
+            let (kind, body_size) = match chunk_type {
+                CHUNK_TYPE_RAW => {
+                    info.raw_chunks += 1;
+                    (ChunkKind::Raw { data_offset: body_offset }, data_size)
+                }
+                CHUNK_TYPE_FILL => {
+                    info.fill_chunks += 1;
+                    let mut pattern = [0u8; 4];
+                    reader.read_exact(&mut pattern).map_err(|e| format!("Failed to read fill chunk {index}: {e}"))?;
+                    (ChunkKind::Fill { pattern }, 4)
+                }
+                CHUNK_TYPE_DONT_CARE => {
+                    info.dont_care_chunks += 1;
+                    (ChunkKind::DontCare, 0)
+                }
+                CHUNK_TYPE_CRC32 => {
+                    info.crc32_chunks += 1;
+                    (ChunkKind::Crc32 { crc: read_u32_le(&mut reader)? }, 4)
+                }
+                other => return Err(format!("Corrupt sparse image: unknown chunk type {other:#06x} at offset {offset}")),
+            };
+            if total_size != chunk_header_size as u64 + body_size {
+                return Err(format!(
+                    "Corrupt sparse image: chunk {index} at offset {offset} is {total_size} bytes, expected {}",
+                    chunk_header_size as u64 + body_size
+                ));
+            }
+            if matches!(kind, ChunkKind::Crc32 { .. }) && blocks != 0 {
+                return Err(format!("Corrupt sparse image: CRC32 chunk {index} covers {blocks} blocks"));
+            }
+            offset += total_size;
+            if offset > container_size {
+                return Err(format!("Truncated sparse image: chunk {index} ends at {offset}, file is {container_size} bytes"));
+            }
+            blocks_seen += blocks as u64;
+            chunks.push(Chunk { kind, blocks });
+        }
+        if blocks_seen != total_blocks as u64 {
+            return Err(format!("Corrupt sparse image: chunks cover {blocks_seen} blocks, header says {total_blocks}"));
+        }
+
+        debug!(?info, "Sparse image parsed");
+        Ok(Self { file: reader.into_inner(), info, chunks })
+    }
+
+    pub fn info(&self) -> &SparseInfo {
+        &self.info
+    }
+
+    /// Stream the expanded image to `sink` in pieces of at most the I/O buffer size,
+    /// checking CRC32 chunks along the way; `progress` gets expanded bytes
+    pub fn expand<S, F>(&mut self, mut sink: S, mut progress: F) -> Result<(), String>
+    where
+        S: FnMut(&[u8]) -> Result<(), String>,
+        F: FnMut(u64, u64),
+    {
+        let total = self.info.logical_size;
+        let block_size = self.info.block_size as u64;
+        let mut buffer = vec![0u8; resources::io_buffer_size().min(total.max(1) as usize)];
+        let mut crc = crc32fast::Hasher::new();
+        let mut done = 0u64;
+        let mut emit = |data: &[u8], crc: &mut crc32fast::Hasher| -> Result<(), String> {
+            crc.update(data);
+            sink(data)?;
+            done += data.len() as u64;
+            progress(done, total);
+            Ok(())
+        };
+
+        for (index, chunk) in self.chunks.iter().enumerate() {
+            let mut remaining = chunk.blocks as u64 * block_size;
+            match chunk.kind {
+                ChunkKind::Raw { data_offset } => {
+                    self.file.seek(SeekFrom::Start(data_offset))
+                        .map_err(|e| format!("Failed to seek to sparse chunk {index}: {e}"))?;
+                    while remaining > 0 {
+                        let n = remaining.min(buffer.len() as u64) as usize;
+                        self.file.read_exact(&mut buffer[..n])
+                            .map_err(|e| format!("Failed to read sparse chunk {index}: {e}"))?;
+                        emit(&buffer[..n], &mut crc)?;
+                        remaining -= n as u64;
+                    }
+                }
+                ChunkKind::Fill { .. } | ChunkKind::DontCare => {
+                    let pattern = match chunk.kind {
+                        ChunkKind::Fill { pattern } => pattern,
+                        _ => [0u8; 4],
+                    };
+                    // Buffer length is a multiple of 4, so every slice starts on the pattern
+                    let fill_len = (remaining.min(buffer.len() as u64) as usize) & !3;
+                    for word in buffer[..fill_len].chunks_exact_mut(4) {
+                        word.copy_from_slice(&pattern);
+                    }
+                    while remaining > 0 {
+                        let n = remaining.min(fill_len as u64) as usize;
+                        emit(&buffer[..n], &mut crc)?;
+                        remaining -= n as u64;
+                    }
+                }
+                ChunkKind::Crc32 { crc: expected } => {
+                    let actual = crc.clone().finalize();
+                    if actual != expected {
+                        return Err(format!(
+                            "Sparse image CRC32 mismatch at chunk {index}: stored {expected:08x}, computed {actual:08x}"
+                        ));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Hash the expanded logical image, comparable to a hash of the dd image
+#[instrument(skip(progress))]
+pub fn hash_expanded<F>(path: &Path, algorithm: &str, progress: F) -> Result<String, String>
+where
+    F: FnMut(u64, u64),
+{
+    let mut hasher = StreamingHasher::new(HashAlgorithm::from_str(algorithm)?);
+    let mut image = SparseImage::open(path)?;
+    image.expand(|data| {
+        job_progress::pause_point(|| {});
+        hasher.update(data);
+        Ok(())
+    }, progress)?;
+    Ok(hasher.finalize())
+}
+
+/// Write the expanded image to `output`, hashing it on the way out
+pub(crate) fn expand_to_file<F>(path: &Path, output: &Path, progress: F) -> Result<ExtractedFile, String>
+where
+    F: FnMut(u64, u64),
+{
+    let mut image = SparseImage::open(path)?;
+    let file = AtomicFile::create(output)
+        .and_then(|file| file.with_progress_markers(None))
+        .map_err(|e| format!("Failed to create output file: {}", e))?;
+    let mut writer = HashingWriter::new(file);
+    image.expand(|data| writer.write_all(data).map_err(|e| format!("Write failed: {}", e)), progress)?;
+    let image_name = path.file_name().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+    writer.commit(&image_name)
+}
+
+/// Convert a sparse image into a true raw image at `output_path`
+///
+/// Refuses an output inside the evidence folder, and an existing output
+/// unless `overwrite`. The output is written under a partial name and only
+/// replaces an existing file once complete.
+#[instrument(skip(progress))]
+pub fn convert_to_raw<F>(path: &str, output_path: &str, overwrite: bool, progress: F) -> Result<ExtractedFile, String>
+where
+    F: FnMut(u64, u64),
+{
+    let output = Path::new(output_path);
+    let parent = output.parent().filter(|p| !p.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    check_output_location(&[Path::new(path).to_path_buf()], parent)?;
+    if output.exists() && !overwrite {
+        return Err(format!("Output file {output_path} already exists"));
+    }
+    let access = crate::containers::access_registry();
+    let _access = (access.read(path)?, access.write(output_path, "sparse image conversion")?);
+    expand_to_file(Path::new(path), output, progress)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BLOCK: u32 = 4096;
+
+    fn chunk(chunk_type: u16, blocks: u32, body: &[u8]) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.extend_from_slice(&chunk_type.to_le_bytes());
+        out.extend_from_slice(&0u16.to_le_bytes());
+        out.extend_from_slice(&blocks.to_le_bytes());
+        out.extend_from_slice(&(CHUNK_HEADER_SIZE as u32 + body.len() as u32).to_le_bytes());
+        out.extend_from_slice(body);
+        out
+    }
+
+    /// RAW(2) + FILL(1) + DONT_CARE(3) + CRC32 + RAW(1), and the expanded image
+    fn fixture(corrupt_crc: bool) -> (Vec<u8>, Vec<u8>) {
+        let raw1: Vec<u8> = (0..2 * BLOCK).map(|i| (i % 251) as u8).collect();
+        let raw2: Vec<u8> = (0..BLOCK).map(|i| (i % 7) as u8).collect();
+        let mut expanded = raw1.clone();
+        expanded.extend([0xde, 0xad, 0xbe, 0xef].repeat(BLOCK as usize / 4));
+        expanded.extend(vec![0u8; 3 * BLOCK as usize]);
+        let crc = crc32fast::hash(&expanded) ^ if corrupt_crc { 1 } else { 0 };
+        expanded.extend(&raw2);
+
+        let chunks = [
+            chunk(CHUNK_TYPE_RAW, 2, &raw1),
+            chunk(CHUNK_TYPE_FILL, 1, &[0xde, 0xad, 0xbe, 0xef]),
+            chunk(CHUNK_TYPE_DONT_CARE, 3, &[]),
+            chunk(CHUNK_TYPE_CRC32, 0, &crc.to_le_bytes()),
+            chunk(CHUNK_TYPE_RAW, 1, &raw2),
+        ];
+        let mut image = Vec::new();
+        image.extend_from_slice(&SPARSE_MAGIC.to_le_bytes());
+        for value in [1u16, 0, FILE_HEADER_SIZE, CHUNK_HEADER_SIZE] {
+            image.extend_from_slice(&value.to_le_bytes());
+        }
+        for value in [BLOCK, 7, chunks.len() as u32, 0] {
+            image.extend_from_slice(&value.to_le_bytes());
+        }
+        image.extend(chunks.concat());
+        (image, expanded)
+    }
+
+    #[test]
+    fn test_expanded_hash_matches_dd_image() {
+        let dir = tempfile::tempdir().unwrap();
+        let (image, expanded) = fixture(false);
+        let path = dir.path().join("system.img");
+        std::fs::write(&path, &image).unwrap();
+        assert_eq!(&image[..4], &[0x3a, 0xff, 0x26, 0xed]);
+        assert!(is_sparse_image(&path));
+
+        let sparse = SparseImage::open(&path).unwrap();
+        let info = sparse.info();
+        assert_eq!(info.logical_size, 7 * BLOCK as u64);
+        assert_eq!((info.raw_chunks, info.fill_chunks, info.dont_care_chunks, info.crc32_chunks), (2, 1, 1, 1));
+
+        let mut last = (0, 0);
+        let hash = hash_expanded(&path, "sha256", |done, total| last = (done, total)).unwrap();
+        let dd = dir.path().join("system.dd");
+        std::fs::write(&dd, &expanded).unwrap();
+        assert_eq!(hash, crate::common::hash::hash_file(&dd, "sha256").unwrap());
+        assert_eq!(last, (expanded.len() as u64, expanded.len() as u64));
+    }
+
+    #[test]
+    fn test_convert_to_raw() {
+        let evidence = tempfile::tempdir().unwrap();
+        let (image, expanded) = fixture(false);
+        let path = evidence.path().join("userdata.img");
+        std::fs::write(&path, &image).unwrap();
+
+        let out = tempfile::tempdir().unwrap();
+        let output = out.path().join("userdata.raw");
+        let file = convert_to_raw(path.to_str().unwrap(), output.to_str().unwrap(), false, |_, _| {}).unwrap();
+        assert_eq!(std::fs::read(&output).unwrap(), expanded);
+        assert_eq!(file.size, expanded.len() as u64);
+        assert_eq!(file.internal_path, "userdata.img");
+
+        let err = convert_to_raw(path.to_str().unwrap(), output.to_str().unwrap(), false, |_, _| {}).unwrap_err();
+        assert!(err.contains("already exists"), "{err}");
+        let inside = evidence.path().join("userdata.raw");
+        assert!(convert_to_raw(path.to_str().unwrap(), inside.to_str().unwrap(), false, |_, _| {}).is_err());
+
+        // Another operation writing the source blocks the conversion
+        let other = out.path().join("other.raw");
+        let writer = crate::containers::access_registry().write(path.to_str().unwrap(), "extraction").unwrap();
+        let err = convert_to_raw(path.to_str().unwrap(), other.to_str().unwrap(), false, |_, _| {}).unwrap_err();
+        assert!(err.contains("being written by extraction"), "{err}");
+        drop(writer);
+        convert_to_raw(path.to_str().unwrap(), other.to_str().unwrap(), false, |_, _| {}).unwrap();
+    }
+
+    #[test]
+    fn test_corrupt_images_are_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let (image, _) = fixture(true);
+        let path = dir.path().join("bad_crc.img");
+        std::fs::write(&path, &image).unwrap();
+        let err = hash_expanded(&path, "md5", |_, _| {}).unwrap_err();
+        assert!(err.contains("CRC32 mismatch"), "{err}");
+
+        let (image, _) = fixture(false);
+        let truncated = dir.path().join("truncated.img");
+        std::fs::write(&truncated, &image[..image.len() - 100]).unwrap();
+        assert!(SparseImage::open(&truncated).unwrap_err().contains("Truncated"));
+
+        let plain = dir.path().join("plain.img");
+        std::fs::write(&plain, vec![0u8; 64]).unwrap();
+        assert!(!is_sparse_image(&plain));
+    }
+}
@@ -1,7 +1,12 @@
 //! AI Assistant module for narrative generation
 //!
-//! This module is feature-gated behind `ai-assistant` and provides
-//! LLM-powered narrative generation for forensic reports.
+//! This module is feature-gated behind `ai-assistant` and generates report
+//! narratives through a [`NarrativeProvider`]:
+//!
+//! - [`OfflineProvider`] (the default) writes deterministic, template-based
+//!   text from the structured report data. Nothing leaves the machine and no
+//!   model is involved, so the feature can be enabled on air-gapped systems.
+//! - [`RemoteProvider`] sends a prompt to an LLM (Ollama or OpenAI).
 //!
 //! # Security
 //! 
@@ -19,20 +24,29 @@
 //!
 //! Then use the AI assistant:
 //! ```rust,ignore
-//! use report::ai::{AiAssistant, AiProvider};
+//! use report::ai::{AiAssistant, AiProvider, NarrativeContext};
+//!
+//! // Offline by default
+//! let ai = AiAssistant::default();
+//! let summary = ai.generate_narrative(
+//!     &NarrativeContext::from_report(&report),
+//!     NarrativeType::ExecutiveSummary
+//! ).await?;
 //!
+//! // Or pick a provider at runtime
 //! let ai = AiAssistant::new(AiProvider::Ollama {
 //!     model: "llama3.2".to_string(),
 //!     base_url: None,
 //! });
-//!
-//! let summary = ai.generate_narrative(
-//!     &evidence_context,
-//!     NarrativeType::ExecutiveSummary
-//! ).await?;
 //! ```
 
+use std::borrow::Cow;
+use std::collections::BTreeSet;
+
+use async_trait::async_trait;
+
 use super::error::{ReportError, ReportResult};
+use super::types::{EvidenceItem, FindingSeverity, ForensicReport, VerificationResult};
 use super::NarrativeType;
 
 /// Validate a URL for Ollama connections to prevent SSRF attacks.
@@ -119,9 +133,65 @@ fn log_ai_response(provider: &str, model: &str, success: bool, response_length:
     }
 }
 
-/// AI provider configuration
-#[derive(Debug, Clone)]
+/// Data a narrative is written from
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NarrativeContext<'a> {
+    /// Free-text context, as written into an LLM prompt
+    pub text: &'a str,
+    /// Structured report data (required by the offline provider)
+    pub report: Option<&'a ForensicReport>,
+}
+
+impl<'a> NarrativeContext<'a> {
+    /// Context from free text only
+    pub fn text(text: &'a str) -> Self {
+        Self { text, report: None }
+    }
+
+    /// The same context, with the structured report attached
+    pub fn with_report(self, report: &'a ForensicReport) -> Self {
+        Self { report: Some(report), ..self }
+    }
+
+    /// Context from the structured report
+    pub fn from_report(report: &'a ForensicReport) -> Self {
+        Self { text: "", report: Some(report) }
+    }
+
+    /// Text for a prompt - the free text, or the report's evidence and
+    /// findings described by the offline provider when there is none
+    pub fn prompt_text(&self) -> Cow<'a, str> {
+        match self.report {
+            Some(report) if self.text.trim().is_empty() => {
+                let evidence = OfflineProvider.narrative(report, NarrativeType::EvidenceDescription);
+                let findings = OfflineProvider.narrative(report, NarrativeType::FindingDescription);
+                Cow::Owned(format!("{}\n\n{}", evidence, findings))
+            }
+            _ => Cow::Borrowed(self.text),
+        }
+    }
+}
+
+/// Source of report narratives
+#[async_trait]
+pub trait NarrativeProvider: Send + Sync {
+    /// Provider identifier for the audit log ("offline", "ollama", ...)
+    fn name(&self) -> &str;
+
+    /// Generate a narrative of the given type
+    async fn generate_narrative(
+        &self,
+        context: &NarrativeContext<'_>,
+        narrative_type: NarrativeType,
+    ) -> ReportResult<String>;
+}
+
+/// AI provider configuration, for selecting a provider at runtime
+#[derive(Debug, Clone, Default)]
 pub enum AiProvider {
+    /// Template-based narratives; no model, no network
+    #[default]
+    Offline,
     /// Ollama (local LLM)
     Ollama {
         /// Model name (e.g., "llama3.2", "mistral")
@@ -147,15 +217,35 @@ pub enum AiProvider {
     },
 }
 
+impl AiProvider {
+    /// Build the provider this configuration selects
+    pub fn into_provider(self) -> Box<dyn NarrativeProvider> {
+        match self {
+            AiProvider::Offline => Box::new(OfflineProvider),
+            remote => Box::new(RemoteProvider::new(remote)),
+        }
+    }
+}
+
 /// AI Assistant for generating report narratives
 pub struct AiAssistant {
-    provider: AiProvider,
+    provider: Box<dyn NarrativeProvider>,
 }
 
 impl AiAssistant {
     /// Create a new AI Assistant with the specified provider
     pub fn new(provider: AiProvider) -> Self {
-        Self { provider }
+        Self { provider: provider.into_provider() }
+    }
+
+    /// Create an AI Assistant around any narrative provider
+    pub fn with_provider(provider: impl NarrativeProvider + 'static) -> Self {
+        Self { provider: Box::new(provider) }
+    }
+
+    /// Create an AI Assistant using the offline template provider
+    pub fn offline() -> Self {
+        Self::new(AiProvider::Offline)
     }
 
     /// Create an AI Assistant using Ollama with default settings
@@ -174,25 +264,79 @@ impl AiAssistant {
         })
     }
 
+    /// Identifier of the active provider
+    pub fn provider_name(&self) -> &str {
+        self.provider.name()
+    }
+
     /// Generate a narrative for the given context and type
     pub async fn generate_narrative(
         &self,
-        context: &str,
+        context: &NarrativeContext<'_>,
         narrative_type: NarrativeType,
     ) -> ReportResult<String> {
-        let prompt = self.build_prompt(context, narrative_type);
-        
-        match &self.provider {
-            AiProvider::Ollama { model, base_url } => {
-                self.generate_with_ollama(model, base_url.as_deref(), &prompt).await
-            }
-            AiProvider::OpenAi { model, api_key } => {
-                self.generate_with_openai(model, api_key.as_deref(), &prompt).await
-            }
-            AiProvider::AzureOpenAi { deployment, endpoint, api_key } => {
-                self.generate_with_azure(deployment, endpoint, api_key.as_deref(), &prompt).await
-            }
-        }
+        self.provider.generate_narrative(context, narrative_type).await
+    }
+
+    /// Generate multiple narratives for a report
+    ///
+    /// Remote providers prompt with the summaries in `context`; the offline
+    /// provider writes from `report` itself.
+    pub async fn enhance_report(
+        &self,
+        report: &ForensicReport,
+        context: &ReportContext,
+    ) -> ReportResult<EnhancedNarratives> {
+        let executive_summary = if context.generate_executive_summary {
+            Some(
+                self.generate_narrative(&NarrativeContext::text(&context.evidence_summary).with_report(report), NarrativeType::ExecutiveSummary)
+                    .await?
+            )
+        } else {
+            None
+        };
+
+        let methodology = if context.generate_methodology {
+            Some(
+                self.generate_narrative(&NarrativeContext::text(&context.tools_used).with_report(report), NarrativeType::Methodology)
+                    .await?
+            )
+        } else {
+            None
+        };
+
+        let conclusion = if context.generate_conclusion {
+            Some(
+                self.generate_narrative(&NarrativeContext::text(&context.findings_summary).with_report(report), NarrativeType::Conclusion)
+                    .await?
+            )
+        } else {
+            None
+        };
+
+        Ok(EnhancedNarratives {
+            executive_summary,
+            methodology,
+            conclusion,
+        })
+    }
+}
+
+impl Default for AiAssistant {
+    fn default() -> Self {
+        Self::offline()
+    }
+}
+
+/// LLM-backed provider (Ollama, OpenAI, Azure OpenAI)
+pub struct RemoteProvider {
+    config: AiProvider,
+}
+
+impl RemoteProvider {
+    /// Create a provider for a remote configuration
+    pub fn new(config: AiProvider) -> Self {
+        Self { config }
     }
 
     /// Build a prompt for the given narrative type
@@ -402,45 +546,361 @@ FORMATTING:
             "Azure OpenAI support is not yet implemented".to_string()
         ))
     }
+}
 
-    /// Generate multiple narratives for a report
-    pub async fn enhance_report(
+#[async_trait]
+impl NarrativeProvider for RemoteProvider {
+    fn name(&self) -> &str {
+        match self.config {
+            AiProvider::Offline => "offline",
+            AiProvider::Ollama { .. } => "ollama",
+            AiProvider::OpenAi { .. } => "openai",
+            AiProvider::AzureOpenAi { .. } => "azure",
+        }
+    }
+
+    async fn generate_narrative(
         &self,
-        context: &ReportContext,
-    ) -> ReportResult<EnhancedNarratives> {
-        let executive_summary = if context.generate_executive_summary {
-            Some(
-                self.generate_narrative(&context.evidence_summary, NarrativeType::ExecutiveSummary)
-                    .await?
-            )
-        } else {
-            None
-        };
+        context: &NarrativeContext<'_>,
+        narrative_type: NarrativeType,
+    ) -> ReportResult<String> {
+        let prompt = self.build_prompt(&context.prompt_text(), narrative_type);
+        
+        match &self.config {
+            AiProvider::Offline => Err(ReportError::AiError(
+                "Offline narratives are written by OfflineProvider".to_string()
+            )),
+            AiProvider::Ollama { model, base_url } => {
+                self.generate_with_ollama(model, base_url.as_deref(), &prompt).await
+            }
+            AiProvider::OpenAi { model, api_key } => {
+                self.generate_with_openai(model, api_key.as_deref(), &prompt).await
+            }
+            AiProvider::AzureOpenAi { deployment, endpoint, api_key } => {
+                self.generate_with_azure(deployment, endpoint, api_key.as_deref(), &prompt).await
+            }
+        }
+    }
+}
 
-        let methodology = if context.generate_methodology {
-            Some(
-                self.generate_narrative(&context.tools_used, NarrativeType::Methodology)
-                    .await?
-            )
-        } else {
-            None
-        };
+/// Template-based provider that works without a model or network access
+///
+/// The text is assembled from the report data only, so the same report
+/// always produces the same narrative.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OfflineProvider;
 
-        let conclusion = if context.generate_conclusion {
-            Some(
-                self.generate_narrative(&context.findings_summary, NarrativeType::Conclusion)
-                    .await?
-            )
+#[async_trait]
+impl NarrativeProvider for OfflineProvider {
+    fn name(&self) -> &str {
+        "offline"
+    }
+
+    async fn generate_narrative(
+        &self,
+        context: &NarrativeContext<'_>,
+        narrative_type: NarrativeType,
+    ) -> ReportResult<String> {
+        let report = context.report.ok_or_else(|| ReportError::AiError(
+            "The offline narrative provider needs the report data".to_string()
+        ))?;
+        log_ai_interaction("offline", "template", narrative_type.as_str(), 0);
+        Ok(self.narrative(report, narrative_type))
+    }
+}
+
+impl OfflineProvider {
+    /// Write the narrative for a report
+    pub fn narrative(&self, report: &ForensicReport, narrative_type: NarrativeType) -> String {
+        match narrative_type {
+            NarrativeType::ExecutiveSummary => executive_summary(report),
+            NarrativeType::FindingDescription => finding_descriptions(report),
+            NarrativeType::TimelineNarrative => timeline_narrative(report),
+            NarrativeType::EvidenceDescription => evidence_descriptions(report),
+            NarrativeType::Methodology => methodology(report),
+            NarrativeType::Conclusion => conclusion(report),
+        }
+    }
+}
+
+/// "1 evidence item", "2 evidence items"
+fn count(n: usize, singular: &str, plural: &str) -> String {
+    format!("{} {}", n, if n == 1 { singular } else { plural })
+}
+
+/// Hash comparison outcome of each evidence item
+struct IntegritySummary<'a> {
+    /// Items with at least one matching comparison and no mismatch
+    matched: Vec<&'a str>,
+    /// Items with a mismatch, with the algorithms that did not match
+    mismatched: Vec<(&'a str, Vec<&'a str>)>,
+    /// Items with nothing to compare against
+    unverified: Vec<&'a str>,
+}
+
+impl<'a> IntegritySummary<'a> {
+    fn new(items: &'a [EvidenceItem]) -> Self {
+        let mut summary = Self { matched: Vec::new(), mismatched: Vec::new(), unverified: Vec::new() };
+        for item in items {
+            let mismatches: Vec<&str> = item.verification.iter()
+                .filter(|record| record.result == VerificationResult::Mismatch)
+                .map(|record| record.algorithm.as_str())
+                .collect();
+            let verified = item.verification.iter().any(|record| record.result == VerificationResult::Verified);
+            if !mismatches.is_empty() {
+                summary.mismatched.push((&item.evidence_id, mismatches));
+            } else if verified {
+                summary.matched.push(&item.evidence_id);
+            } else {
+                summary.unverified.push(&item.evidence_id);
+            }
+        }
+        summary
+    }
+
+    fn compared(&self) -> usize {
+        self.matched.len() + self.mismatched.len()
+    }
+
+    /// "EV-03 (SHA1), EV-05 (MD5, SHA256)"
+    fn mismatch_list(&self) -> String {
+        self.mismatched.iter()
+            .map(|(id, algorithms)| format!("{} ({})", id, algorithms.join(", ")))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// "Between 2026-02-01 and 2026-02-04, the examiner" or "The examiner"
+fn examiner_period(report: &ForensicReport) -> String {
+    match (report.case_info.exam_start_date, report.case_info.exam_end_date) {
+        (Some(start), Some(end)) => format!(
+            "Between {} and {}, the examiner",
+            start.format("%Y-%m-%d"),
+            end.format("%Y-%m-%d")
+        ),
+        (Some(start), None) => format!("Beginning {}, the examiner", start.format("%Y-%m-%d")),
+        _ => "The examiner".to_string(),
+    }
+}
+
+fn executive_summary(report: &ForensicReport) -> String {
+    let items = &report.evidence_items;
+    let integrity = IntegritySummary::new(items);
+    
+    let mut case = report.case_info.case_number.clone();
+    if let Some(ref name) = report.case_info.case_name {
+        case.push_str(&format!(" ({})", name));
+    }
+    let mut text = format!(
+        "This report documents the forensic examination of {} for case {}. ",
+        count(items.len(), "evidence item", "evidence items"),
+        case
+    );
+    
+    let period = examiner_period(report);
+    if integrity.compared() == 0 {
+        text.push_str(&format!("{} found no stored hashes to verify the evidence against.", period));
+    } else {
+        text.push_str(&format!(
+            "{} verified {}; {} matched stored hashes",
+            period,
+            count(integrity.compared(), "evidence item", "evidence items"),
+            integrity.matched.len()
+        ));
+        if integrity.mismatched.is_empty() {
+            text.push('.');
         } else {
-            None
-        };
+            text.push_str(&format!(
+                " and {} did not: {}.",
+                integrity.mismatched.len(),
+                integrity.mismatch_list()
+            ));
+        }
+        if !integrity.unverified.is_empty() {
+            text.push_str(&format!(
+                " {} could not be verified because no stored hash was available.",
+                count(integrity.unverified.len(), "item", "items")
+            ));
+        }
+    }
+    
+    let significant = report.findings.iter()
+        .filter(|finding| matches!(finding.severity, FindingSeverity::High | FindingSeverity::Critical))
+        .count();
+    text.push_str(&format!(" {} recorded", count(report.findings.len(), "finding was", "findings were")));
+    if significant > 0 {
+        text.push_str(&format!(", {} of high or critical significance", significant));
+    }
+    text.push('.');
+    text
+}
 
-        Ok(EnhancedNarratives {
-            executive_summary,
-            methodology,
-            conclusion,
+fn evidence_descriptions(report: &ForensicReport) -> String {
+    if report.evidence_items.is_empty() {
+        return "No evidence items were examined.".to_string();
+    }
+    
+    report.evidence_items.iter()
+        .map(|item| {
+            let mut text = format!(
+                "Evidence {} ({}): {}.",
+                item.evidence_id,
+                item.evidence_type.as_str(),
+                item.description.trim_end_matches('.')
+            );
+            if let Some(ref serial) = item.serial_number {
+                text.push_str(&format!(" Serial number {}.", serial));
+            }
+            if let Some(ref image) = item.image_info {
+                text.push_str(&format!(
+                    " Imaged as {} in {} ({}).",
+                    image.format,
+                    count(image.file_names.len(), "segment file", "segment files"),
+                    super::template::format_bytes(image.total_size)
+                ));
+                if !image.missing_segments.is_empty() {
+                    text.push_str(&format!(
+                        " {} could not be located: {}.",
+                        count(image.missing_segments.len(), "expected segment file", "expected segment files"),
+                        image.missing_segments.join(", ")
+                    ));
+                }
+            }
+            for record in &item.verification {
+                let outcome = match record.result {
+                    VerificationResult::Verified => "matched the stored value",
+                    VerificationResult::Mismatch => "did not match the stored value",
+                    VerificationResult::Unverified => "was recorded but not verified",
+                    VerificationResult::Computed => "was computed with no stored value to compare against",
+                };
+                text.push_str(&format!(" The {} hash {}.", record.algorithm, outcome));
+            }
+            text
         })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+fn finding_descriptions(report: &ForensicReport) -> String {
+    if report.findings.is_empty() {
+        return "No findings were recorded.".to_string();
+    }
+    
+    report.findings.iter()
+        .map(|finding| {
+            let mut text = format!(
+                "{} - {} ({}, {}): {}",
+                finding.finding_id,
+                finding.title,
+                finding.severity.as_str(),
+                finding.category.as_str(),
+                finding.description
+            );
+            if !finding.supporting_evidence.is_empty() {
+                text.push_str(&format!(" Supported by evidence {}.", finding.supporting_evidence.join(", ")));
+            }
+            if !finding.related_files.is_empty() {
+                text.push_str(&format!(" {}.", count(finding.related_files.len(), "related file", "related files")));
+            }
+            text
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+fn timeline_narrative(report: &ForensicReport) -> String {
+    let mut events: Vec<_> = report.timeline.iter().collect();
+    events.sort_by_key(|event| event.timestamp);
+    let (Some(first), Some(last)) = (events.first(), events.last()) else {
+        return "No timeline events were recorded.".to_string();
+    };
+    
+    let mut lines = vec![format!(
+        "The timeline contains {} between {} and {} (UTC).",
+        count(events.len(), "event", "events"),
+        first.timestamp.format("%Y-%m-%d %H:%M:%S"),
+        last.timestamp.format("%Y-%m-%d %H:%M:%S")
+    )];
+    lines.extend(events.iter().map(|event| format!(
+        "On {}, {} ({}, {}).",
+        event.timestamp.format("%Y-%m-%d %H:%M:%S"),
+        event.description.trim_end_matches('.'),
+        event.timestamp_type,
+        event.source
+    )));
+    lines.join("\n")
+}
+
+fn methodology(report: &ForensicReport) -> String {
+    let mut sentences = Vec::new();
+    
+    if !report.tools.is_empty() {
+        let tools: Vec<String> = report.tools.iter()
+            .map(|tool| match tool.vendor {
+                Some(ref vendor) => format!("{} {} ({})", tool.name, tool.version, vendor),
+                None => format!("{} {}", tool.name, tool.version),
+            })
+            .collect();
+        sentences.push(format!("The evidence was examined using {}.", tools.join(", ")));
+    }
+    
+    let algorithms: BTreeSet<&str> = report.evidence_items.iter()
+        .flat_map(|item| item.verification.iter())
+        .filter(|record| record.computed.is_some())
+        .map(|record| record.algorithm.as_str())
+        .collect();
+    if algorithms.is_empty() {
+        sentences.push("No hash comparisons were recorded.".to_string());
+    } else {
+        sentences.push(format!(
+            "Evidence integrity was assessed by computing {} hashes and comparing them with the values recorded at acquisition.",
+            algorithms.into_iter().collect::<Vec<_>>().join(", ")
+        ));
+    }
+    sentences.join(" ")
+}
+
+fn conclusion(report: &ForensicReport) -> String {
+    let integrity = IntegritySummary::new(&report.evidence_items);
+    
+    let mut text = format!(
+        "Of the {} examined, {} matched the hashes recorded at acquisition",
+        count(report.evidence_items.len(), "evidence item", "evidence items"),
+        integrity.matched.len()
+    );
+    if integrity.mismatched.is_empty() {
+        text.push('.');
+    } else {
+        text.push_str(&format!("; integrity could not be confirmed for {}.", integrity.mismatch_list()));
+    }
+    if !integrity.unverified.is_empty() {
+        text.push_str(&format!(" No stored hashes were available for {}.", integrity.unverified.join(", ")));
+    }
+    
+    if report.findings.is_empty() {
+        text.push_str(" The examination produced no findings.");
+    } else {
+        let severities = [
+            FindingSeverity::Critical,
+            FindingSeverity::High,
+            FindingSeverity::Medium,
+            FindingSeverity::Low,
+            FindingSeverity::Info,
+        ];
+        let breakdown: Vec<String> = severities.iter()
+            .filter_map(|severity| {
+                let n = report.findings.iter().filter(|finding| finding.severity == *severity).count();
+                (n > 0).then(|| format!("{} {}", n, severity.as_str().to_lowercase()))
+            })
+            .collect();
+        text.push_str(&format!(
+            " The examination produced {}: {}.",
+            count(report.findings.len(), "finding", "findings"),
+            breakdown.join(", ")
+        ));
     }
+    text
 }
 
 /// Context for AI-enhanced report generation
@@ -474,10 +934,12 @@ pub struct EnhancedNarratives {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::report::types::*;
+    use chrono::{TimeZone, Utc};
 
     #[test]
     fn test_prompt_building() {
-        let ai = AiAssistant::ollama("llama3.2");
+        let ai = RemoteProvider::new(AiProvider::Ollama { model: "llama3.2".to_string(), base_url: None });
         let prompt = ai.build_prompt("Test context", NarrativeType::ExecutiveSummary);
         
         assert!(prompt.contains("executive summary"));
@@ -490,8 +952,160 @@ mod tests {
         let ollama = AiAssistant::ollama("mistral");
         let openai = AiAssistant::openai("gpt-4");
         
-        // Just verify they can be created without panicking
-        drop(ollama);
-        drop(openai);
+        assert_eq!(ollama.provider_name(), "ollama");
+        assert_eq!(openai.provider_name(), "openai");
+        assert_eq!(AiAssistant::default().provider_name(), "offline");
+    }
+
+    fn record(algorithm: &str, result: VerificationResult) -> VerificationRecord {
+        VerificationRecord {
+            algorithm: algorithm.to_string(),
+            stored: Some("00".to_string()),
+            computed: Some("00".to_string()),
+            result,
+            source: None,
+            timestamp: None,
+        }
+    }
+
+    fn item(id: &str, description: &str, verification: Vec<VerificationRecord>) -> EvidenceItem {
+        EvidenceItem {
+            evidence_id: id.to_string(),
+            description: description.to_string(),
+            evidence_type: EvidenceType::ForensicImage,
+            make: None,
+            model: None,
+            serial_number: None,
+            capacity: None,
+            condition: None,
+            received_date: None,
+            submitted_by: None,
+            acquisition_hashes: vec![],
+            image_info: None,
+            notes: None,
+            acquisition: None,
+            verification,
+            file_hashes: vec![],
+        }
+    }
+
+    fn finding(id: &str, severity: FindingSeverity) -> Finding {
+        Finding {
+            finding_id: id.to_string(),
+            title: "Deleted chat database".to_string(),
+            severity,
+            category: FindingCategory::Communication,
+            description: "A deleted chat database was recovered.".to_string(),
+            supporting_evidence: vec!["EV-01".to_string()],
+            related_files: vec![],
+            timestamps: vec![],
+            exhibits: vec![],
+            notes: None,
+        }
+    }
+
+    /// Three items: MD5 and SHA1 matched, SHA1 mismatch, nothing stored
+    fn fixed_report() -> ForensicReport {
+        let mut laptop = item("EV-01", "Laptop SSD", vec![
+            record("MD5", VerificationResult::Verified),
+            record("SHA1", VerificationResult::Verified),
+        ]);
+        laptop.serial_number = Some("S64ANS0T512345".to_string());
+        laptop.image_info = Some(ImageInfo {
+            format: "E01".to_string(),
+            file_names: vec!["laptop.E01".to_string(), "laptop.E02".to_string()],
+            total_size: 1_610_612_736,
+            segments: Some(3),
+            compression: None,
+            acquisition_tool: None,
+            acquisition_date: None,
+            missing_segments: vec!["laptop.E03".to_string()],
+        });
+        
+        let mut report = ForensicReport::builder()
+            .case_number("2026-017")
+            .examiner_name("J. Rivera")
+            .add_evidence(laptop)
+            .add_evidence(item("EV-02", "USB drive", vec![
+                record("MD5", VerificationResult::Verified),
+                record("SHA1", VerificationResult::Mismatch),
+            ]))
+            .add_evidence(item("EV-03", "SD card", vec![record("MD5", VerificationResult::Computed)]))
+            .add_finding(finding("F-01", FindingSeverity::High))
+            .add_finding(finding("F-02", FindingSeverity::Low))
+            .add_tool(ToolInfo {
+                name: "FFX".to_string(),
+                version: "0.1.0".to_string(),
+                vendor: None,
+                purpose: None,
+            })
+            .build()
+            .unwrap();
+        report.case_info.exam_start_date = Some(Utc.with_ymd_and_hms(2026, 2, 1, 9, 0, 0).unwrap());
+        report.case_info.exam_end_date = Some(Utc.with_ymd_and_hms(2026, 2, 4, 17, 0, 0).unwrap());
+        report
+    }
+
+    #[test]
+    fn test_offline_narratives_are_stable() {
+        let report = fixed_report();
+        let offline = OfflineProvider;
+        
+        assert_eq!(
+            offline.narrative(&report, NarrativeType::ExecutiveSummary),
+            "This report documents the forensic examination of 3 evidence items for case 2026-017. \
+             Between 2026-02-01 and 2026-02-04, the examiner verified 2 evidence items; 1 matched stored hashes \
+             and 1 did not: EV-02 (SHA1). 1 item could not be verified because no stored hash was available. \
+             2 findings were recorded, 1 of high or critical significance."
+        );
+        assert_eq!(
+            offline.narrative(&report, NarrativeType::EvidenceDescription).split("\n\n").next().unwrap(),
+            "Evidence EV-01 (Forensic Image): Laptop SSD. Serial number S64ANS0T512345. \
+             Imaged as E01 in 2 segment files (1.50 GB). 1 expected segment file could not be located: laptop.E03. \
+             The MD5 hash matched the stored value. The SHA1 hash matched the stored value."
+        );
+        assert_eq!(
+            offline.narrative(&report, NarrativeType::Methodology),
+            "The evidence was examined using FFX 0.1.0. Evidence integrity was assessed by computing MD5, SHA1 \
+             hashes and comparing them with the values recorded at acquisition."
+        );
+        assert_eq!(
+            offline.narrative(&report, NarrativeType::Conclusion),
+            "Of the 3 evidence items examined, 1 matched the hashes recorded at acquisition; integrity could not \
+             be confirmed for EV-02 (SHA1). No stored hashes were available for EV-03. \
+             The examination produced 2 findings: 1 high, 1 low."
+        );
+        
+        // Same report, same text
+        assert_eq!(
+            offline.narrative(&report, NarrativeType::FindingDescription),
+            offline.narrative(&fixed_report(), NarrativeType::FindingDescription)
+        );
+    }
+
+    #[test]
+    fn test_enhance_report_with_default_provider() {
+        let report = fixed_report();
+        let context = ReportContext {
+            evidence_summary: "3 evidence items".to_string(),
+            findings_summary: "2 findings".to_string(),
+            tools_used: "FFX".to_string(),
+            generate_executive_summary: true,
+            generate_methodology: true,
+            generate_conclusion: false,
+        };
+        // The offline provider never awaits anything, so one poll finishes it
+        let assistant = AiAssistant::default();
+        let future = std::pin::pin!(assistant.enhance_report(&report, &context));
+        let mut cx = std::task::Context::from_waker(std::task::Waker::noop());
+        let std::task::Poll::Ready(narratives) = std::future::Future::poll(future, &mut cx) else {
+            panic!("offline narratives should not wait");
+        };
+        let narratives = narratives.unwrap();
+
+        let offline = OfflineProvider;
+        assert_eq!(narratives.executive_summary, Some(offline.narrative(&report, NarrativeType::ExecutiveSummary)));
+        assert_eq!(narratives.methodology, Some(offline.narrative(&report, NarrativeType::Methodology)));
+        assert_eq!(narratives.conclusion, None);
     }
 }
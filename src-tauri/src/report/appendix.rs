@@ -0,0 +1,92 @@
+//! Source data appendix: the JSON a report was rendered from
+//!
+//! Reviewers sometimes need the exact values behind a rendered table. With
+//! [`GenerateOptions::embed_source_data`] the serialized `ForensicReport`
+//! (plus any container or verification data it was built from) is appended
+//! to HTML, DOCX and Markdown output. The report is serialized once: the
+//! templates render from the same value that is embedded.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::error::ReportResult;
+use super::types::ForensicReport;
+
+/// Options for [`ReportGenerator::generate`](super::ReportGenerator::generate)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct GenerateOptions {
+    /// Append the report data as JSON. Off by default - the data holds every
+    /// field, including ones the rendered sections leave out, and some
+    /// reports go to opposing counsel
+    pub embed_source_data: bool,
+    /// ContainerInfo or verification results the report was built from,
+    /// embedded after the report data
+    pub source_data: Option<Value>,
+}
+
+/// The serialized data of one generated report
+pub struct SourceAppendix {
+    /// The report as the templates see it
+    pub report: Value,
+    /// `report`, pretty-printed - the text that is embedded
+    pub report_json: String,
+    pub source_json: Option<String>,
+}
+
+impl SourceAppendix {
+    pub fn new(report: &ForensicReport, source_data: Option<&Value>) -> ReportResult<Self> {
+        let report = serde_json::to_value(report)?;
+        let report_json = serde_json::to_string_pretty(&report)?;
+        let source_json = source_data.map(serde_json::to_string_pretty).transpose()?;
+        Ok(Self { report, report_json, source_json })
+    }
+
+    /// Template context of the appendix
+    pub(crate) fn context(&self) -> Value {
+        let fence = markdown_fence(self.report_json.as_str(), self.source_json.as_deref());
+        serde_json::json!({
+            "report": self.report_json,
+            "report_href": data_url(&self.report_json),
+            "source": self.source_json,
+            "source_href": self.source_json.as_deref().map(data_url),
+            "fence": fence,
+        })
+    }
+}
+
+/// `data:` URL of a JSON document, for a download link
+fn data_url(json: &str) -> String {
+    let mut url = String::with_capacity(json.len() * 2 + 32);
+    url.push_str("data:application/json;charset=utf-8,");
+    for byte in json.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-_.~".contains(&byte) {
+            url.push(byte as char);
+        } else {
+            url.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    url
+}
+
+/// Backtick fence longer than any backtick run in the embedded texts
+fn markdown_fence(report_json: &str, source_json: Option<&str>) -> String {
+    let longest = [Some(report_json), source_json].into_iter().flatten()
+        .flat_map(|text| text.split(|c| c != '`'))
+        .map(str::len)
+        .max()
+        .unwrap_or(0);
+    "`".repeat((longest + 1).max(3))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_data_url_and_fence() {
+        assert_eq!(data_url("{\"a\": \"b c\"}"), "data:application/json;charset=utf-8,%7B%22a%22%3A%20%22b%20c%22%7D");
+        assert_eq!(markdown_fence("{}", None), "```");
+        assert_eq!(markdown_fence("\"````\"", Some("``")), "`````");
+    }
+}
@@ -6,7 +6,7 @@ use tauri::State;
 use std::sync::Mutex;
 
 use super::{
-    ForensicReport, OutputFormat, ReportGenerator,
+    ForensicReport, GenerateOptions, OutputFormat, ReportGenerator,
     types::*,
 };
 
@@ -38,19 +38,21 @@ impl Default for ReportState {
     }
 }
 
-/// Generate a report in the specified format
+/// Generate a report in the specified format; `options` can embed the
+/// report's source data as an appendix (off when not given)
 #[tauri::command]
 pub async fn generate_report(
     report: ForensicReport,
     format: OutputFormat,
     output_path: String,
+    options: Option<GenerateOptions>,
     state: State<'_, ReportState>,
 ) -> Result<String, String> {
     let generator = state.generator.lock()
         .map_err(|e| format!("Lock error: {}", e))?;
     
     generator
-        .generate(&report, format, &output_path)
+        .generate(&report, format, &output_path, &options.unwrap_or_default())
         .map_err(|e| e.to_string())?;
     
     Ok(output_path)
@@ -168,11 +170,13 @@ pub fn add_finding_to_report(
     report
 }
 
-/// Export report to JSON (for saving/loading)
+/// Export report to JSON (for saving/loading), with an integrity tag
+/// when those are on in the settings
 #[tauri::command]
 pub fn export_report_json(report: ForensicReport) -> Result<String, String> {
-    serde_json::to_string_pretty(&report)
-        .map_err(|e| e.to_string())
+    let json = serde_json::to_string_pretty(&report)
+        .map_err(|e| e.to_string())?;
+    crate::common::integrity::tag_json_if_enabled(json)
 }
 
 /// Import report from JSON
@@ -269,6 +273,7 @@ pub fn extract_evidence_from_containers(
             acquisition_date: container.acquiry_date.as_ref().and_then(|d| 
                 chrono::DateTime::parse_from_rfc3339(d).ok().map(|dt| dt.with_timezone(&chrono::Utc))
             ),
+            missing_segments: Vec::new(),
         });
         
         // Create evidence item
@@ -286,6 +291,9 @@ pub fn extract_evidence_from_containers(
             acquisition_hashes,
             image_info,
             notes: container.notes.clone(),
+            acquisition: None,
+            verification: Vec::new(),
+            file_hashes: Vec::new(),
         };
         
         evidence_items.push(evidence_item);
@@ -301,8 +309,10 @@ fn parse_hash_algorithm(s: &str) -> HashAlgorithm {
         "sha1" | "sha-1" => HashAlgorithm::SHA1,
         "sha256" | "sha-256" => HashAlgorithm::SHA256,
         "sha512" | "sha-512" => HashAlgorithm::SHA512,
+        "sha3-256" | "sha3_256" => HashAlgorithm::SHA3_256,
         "blake2" | "blake2b" => HashAlgorithm::Blake2b,
         "blake3" => HashAlgorithm::Blake3,
+        "xxh128" => HashAlgorithm::XXH128,
         _ => HashAlgorithm::SHA256, // Default to SHA256
     }
 }
@@ -422,8 +432,8 @@ The examination process included:
 
 #[cfg(feature = "ai-assistant")]
 pub mod ai_commands {
-    use crate::report::ai::{AiAssistant, AiProvider};
-    use crate::report::NarrativeType;
+    use crate::report::ai::{AiAssistant, AiProvider, NarrativeContext};
+    use crate::report::{ForensicReport, NarrativeType};
 
     /// AI provider info for frontend
     #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
@@ -440,6 +450,14 @@ pub mod ai_commands {
     #[tauri::command]
     pub fn get_ai_providers() -> Vec<AiProviderInfo> {
         vec![
+            AiProviderInfo {
+                id: "offline".to_string(),
+                name: "Offline (Template)".to_string(),
+                description: "Deterministic text from the report data - no model, nothing leaves this machine".to_string(),
+                requires_api_key: false,
+                default_model: "template".to_string(),
+                available_models: vec!["template".to_string()],
+            },
             AiProviderInfo {
                 id: "ollama".to_string(),
                 name: "Ollama (Local)".to_string(),
@@ -472,6 +490,9 @@ pub mod ai_commands {
     }
 
     /// Generate AI narrative for a report section
+    ///
+    /// `report` carries the structured data the offline provider writes
+    /// from; remote providers use `context`.
     #[tauri::command]
     pub async fn generate_ai_narrative(
         context: String,
@@ -479,6 +500,7 @@ pub mod ai_commands {
         provider: String,
         model: String,
         api_key: Option<String>,
+        report: Option<ForensicReport>,
     ) -> Result<String, String> {
         let narrative_type = match narrative_type.as_str() {
             "executive_summary" => NarrativeType::ExecutiveSummary,
@@ -491,6 +513,7 @@ pub mod ai_commands {
         };
 
         let provider_enum = match provider.as_str() {
+            "offline" => AiProvider::Offline,
             "ollama" => AiProvider::Ollama {
                 model: model.clone(),
                 base_url: None,
@@ -503,6 +526,7 @@ pub mod ai_commands {
         };
 
         let ai = AiAssistant::new(provider_enum);
+        let context = NarrativeContext { text: &context, report: report.as_ref() };
 
         ai.generate_narrative(&context, narrative_type)
             .await
@@ -565,6 +589,7 @@ pub mod ai_commands {
         _provider: String,
         _model: String,
         _api_key: Option<String>,
+        _report: Option<crate::report::ForensicReport>,
     ) -> Result<String, String> {
         Err("AI assistant is not enabled. Rebuild with 'ai-assistant' feature.".to_string())
     }
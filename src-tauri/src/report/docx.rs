@@ -2,14 +2,35 @@
 //!
 //! Generates Microsoft Word documents from forensic report data.
 
-use std::fs::File;
+use std::io::{Cursor, Write};
 use std::path::Path;
 
 use docx_rs::*;
+use zip::write::SimpleFileOptions;
 
+use super::appendix::SourceAppendix;
 use super::error::{ReportError, ReportResult};
+use crate::common::atomic_file::write_atomic;
 use super::types::*;
 
+/// Character style for hash values (monospace, never reflowed by Word)
+const HASH_STYLE: &str = "HashValue";
+/// Paragraph style of table header rows
+const TABLE_HEADER_STYLE: &str = "TableHeader";
+/// Package part holding title, author and dates
+const CORE_PROPS_PART: &str = "docProps/core.xml";
+
+// Column widths in twips. The text area is A4 with 1" margins (9026 twips);
+// hash columns are 5600 twips, enough for a 64-char hash in 7.5pt Consolas.
+const EVIDENCE_COLUMNS: [usize; 5] = [1200, 3400, 1600, 1600, 1200];
+const VERIFICATION_COLUMNS: [usize; 4] = [1000, 1000, 5600, 1400];
+const HASH_RECORD_COLUMNS: [usize; 4] = [1400, 1000, 5600, 1000];
+const SEGMENT_COLUMNS: [usize; 4] = [1400, 600, 5600, 1400];
+const FINDING_COLUMNS: [usize; 4] = [1200, 4800, 1400, 1600];
+const TIMELINE_COLUMNS: [usize; 4] = [2000, 1200, 4200, 1600];
+const TOOL_COLUMNS: [usize; 4] = [2200, 1200, 1800, 3800];
+const INFO_COLUMNS: [usize; 2] = [2000, 7000];
+
 /// DOCX generator for forensic reports
 pub struct DocxGenerator {
     // Configuration options can be added here
@@ -23,7 +44,19 @@ impl DocxGenerator {
 
     /// Generate a DOCX report
     pub fn generate(&self, report: &ForensicReport, output_path: impl AsRef<Path>) -> ReportResult<()> {
-        let mut docx = Docx::new();
+        self.generate_with_appendix(report, None, output_path)
+    }
+
+    /// Generate a DOCX report ending with `appendix`, when given
+    pub fn generate_with_appendix(
+        &self,
+        report: &ForensicReport,
+        appendix: Option<&SourceAppendix>,
+        output_path: impl AsRef<Path>,
+    ) -> ReportResult<()> {
+        let mut docx = Docx::new()
+            .page_margin(PageMargin::new().top(1440).bottom(1440).left(1440).right(1440));
+        docx = self.add_styles(docx);
 
         // Add classification header
         docx = self.add_classification_header(docx, report);
@@ -59,6 +92,11 @@ impl DocxGenerator {
             docx = self.add_findings_section(docx, report);
         }
         
+        // Add hash verification results
+        if !report.hash_records.is_empty() {
+            docx = self.add_hash_records_section(docx, report);
+        }
+        
         // Add timeline
         if !report.timeline.is_empty() {
             docx = self.add_timeline_section(docx, report);
@@ -74,19 +112,48 @@ impl DocxGenerator {
             docx = self.add_conclusions(docx, report);
         }
         
+        // Add per-segment appendix
+        if report.evidence_items.iter().any(|item| item.image_info.is_some()) {
+            docx = self.add_segment_appendix(docx, report);
+        }
+        
+        // Add source data appendix
+        if let Some(appendix) = appendix {
+            docx = self.add_source_appendix(docx, appendix);
+        }
+        
         // Add footer
         docx = self.add_footer(docx, report);
 
-        // Write to file - pack() takes a writer that implements Write + Seek
-        // File implements both traits
-        let file = File::create(output_path.as_ref())?;
+        // pack() needs Write + Seek; pack in memory so the core properties
+        // can be replaced before the file is written
+        let mut packed = Cursor::new(Vec::new());
         docx.build()
-            .pack(file)
+            .pack(&mut packed)
             .map_err(|e| ReportError::Docx(e.to_string()))?;
+        let bytes = set_core_properties(packed.get_ref(), &core_properties_xml(report))?;
+        write_atomic(output_path.as_ref(), bytes)?;
 
         Ok(())
     }
 
+    /// Register the hash and table header styles
+    fn add_styles(&self, docx: Docx) -> Docx {
+        docx.add_style(
+            Style::new(HASH_STYLE, StyleType::Character)
+                .name("Hash Value")
+                .fonts(RunFonts::new().ascii("Consolas").hi_ansi("Consolas").cs("Consolas"))
+                .size(15) // 7.5pt
+        )
+        .add_style(
+            Style::new(TABLE_HEADER_STYLE, StyleType::Paragraph)
+                .name("Table Header")
+                .bold()
+                .color("FFFFFF")
+                .size(18) // 9pt
+        )
+    }
+
     fn add_classification_header(&self, docx: Docx, report: &ForensicReport) -> Docx {
         let classification = report.metadata.classification.as_str();
         
@@ -226,35 +293,97 @@ impl DocxGenerator {
     fn add_evidence_section(&self, docx: Docx, report: &ForensicReport) -> Docx {
         let docx = self.add_section_header(docx, "Evidence Examined");
         
-        // Create evidence table
-        let mut table = Table::new(vec![
-            // Header row
-            TableRow::new(vec![
-                self.header_cell("ID"),
-                self.header_cell("Description"),
-                self.header_cell("Type"),
-                self.header_cell("Serial Number"),
-                self.header_cell("Capacity"),
-            ]),
-        ]);
-        
-        // Add data rows
-        for item in &report.evidence_items {
-            table = table.add_row(TableRow::new(vec![
+        let rows = report.evidence_items.iter()
+            .map(|item| vec![
                 self.data_cell(&item.evidence_id),
                 self.data_cell(&item.description),
                 self.data_cell(item.evidence_type.as_str()),
                 self.data_cell(item.serial_number.as_deref().unwrap_or("-")),
                 self.data_cell(item.capacity.as_deref().unwrap_or("-")),
-            ]));
+            ])
+            .collect();
+        let table = self.table(
+            &EVIDENCE_COLUMNS,
+            &["ID", "Description", "Type", "Serial Number", "Capacity"],
+            rows,
+        );
+        
+        let mut docx = docx.add_table(table)
+            .add_paragraph(Paragraph::new()); // Spacer
+        
+        for item in &report.evidence_items {
+            if item.acquisition.is_some() || !item.verification.is_empty() {
+                docx = self.add_verification_table(docx, item);
+            }
         }
+        docx
+    }
+
+    /// "Acquisition & Verification" details and hash table for one evidence item
+    ///
+    /// Each record gets a "Stored" and a "Computed" row so both hashes sit in
+    /// the same monospace column and can be compared character by character.
+    fn add_verification_table(&self, docx: Docx, item: &EvidenceItem) -> Docx {
+        let mut docx = docx.add_paragraph(
+            Paragraph::new()
+                .add_run(Run::new().add_text(format!("Evidence {} - Acquisition & Verification", item.evidence_id)).bold().size(22))
+                .keep_next(true)
+        );
         
-        docx.add_table(table)
-            .add_paragraph(Paragraph::new()) // Spacer
+        let mut rows: Vec<(&str, String)> = item.acquisition.as_ref()
+            .map(|acq| acquisition_rows(acq).into_iter().map(|(label, value)| (label, value.to_string())).collect())
+            .unwrap_or_default();
+        if let Some(ref image) = item.image_info {
+            if !image.missing_segments.is_empty() {
+                rows.push(("Missing Segments", image.missing_segments.join(", ")));
+            }
+        }
+        if !rows.is_empty() {
+            docx = self.add_info_table(docx, &rows);
+        }
+        
+        if !item.verification.is_empty() {
+            let mut table_rows = Vec::new();
+            for record in &item.verification {
+                let note = match (record.source.as_deref(), record.timestamp.as_deref()) {
+                    (Some(source), Some(ts)) => Some(format!("{} - {}", source, ts)),
+                    (source, ts) => source.or(ts).map(str::to_string),
+                };
+                let values = [("Stored", record.stored.as_deref()), ("Computed", record.computed.as_deref())];
+                for (idx, (kind, value)) in values.into_iter().enumerate() {
+                    let first = idx == 0;
+                    table_rows.push(vec![
+                        self.data_cell(if first { record.algorithm.as_str() } else { "" }),
+                        self.data_cell(kind),
+                        match value {
+                            Some(hash) => self.hash_cell(hash, if first { note.as_deref() } else { None }),
+                            None => self.data_cell("-"),
+                        },
+                        self.data_cell(if first { record.result.as_str() } else { "" }),
+                    ]);
+                }
+            }
+            let table = self.table(&VERIFICATION_COLUMNS, &["Algorithm", "Value", "Hash", "Result"], table_rows);
+            docx = docx.add_paragraph(Paragraph::new()).add_table(table);
+        }
+        
+        docx.add_paragraph(Paragraph::new()) // Spacer
     }
 
     fn add_findings_section(&self, docx: Docx, report: &ForensicReport) -> Docx {
-        let mut docx = self.add_section_header(docx, "Findings");
+        let docx = self.add_section_header(docx, "Findings");
+        
+        let rows = report.findings.iter()
+            .map(|finding| vec![
+                self.data_cell(&finding.finding_id),
+                self.data_cell(&finding.title),
+                self.data_cell(finding.severity.as_str()),
+                self.data_cell(finding.category.as_str()),
+            ])
+            .collect();
+        let table = self.table(&FINDING_COLUMNS, &["ID", "Title", "Severity", "Category"], rows);
+        let mut docx = docx.add_table(table)
+            .add_paragraph(Paragraph::new()); // Spacer
         
         for finding in &report.findings {
             // Finding title
@@ -311,27 +440,46 @@ impl DocxGenerator {
         docx
     }
 
+    fn add_hash_records_section(&self, docx: Docx, report: &ForensicReport) -> Docx {
+        let docx = self.add_section_header(docx, "Hash Verification");
+        
+        let rows = report.hash_records.iter()
+            .map(|record| {
+                let status = match record.verified {
+                    Some(true) => "Verified",
+                    Some(false) => "FAILED",
+                    None => "-",
+                };
+                let computed = record.computed_at.map(|ts| ts.format("%Y-%m-%d %H:%M:%S UTC").to_string());
+                vec![
+                    self.data_cell(&record.item),
+                    self.data_cell(record.algorithm.as_str()),
+                    self.hash_cell(&record.value, computed.as_deref()),
+                    self.data_cell(status),
+                ]
+            })
+            .collect();
+        let table = self.table(&HASH_RECORD_COLUMNS, &["Item", "Algorithm", "Hash", "Status"], rows);
+        
+        docx.add_table(table)
+            .add_paragraph(Paragraph::new())
+    }
+
     fn add_timeline_section(&self, docx: Docx, report: &ForensicReport) -> Docx {
         let docx = self.add_section_header(docx, "Timeline of Events");
         
-        let mut table = Table::new(vec![
-            TableRow::new(vec![
-                self.header_cell("Timestamp"),
-                self.header_cell("Type"),
-                self.header_cell("Description"),
-                self.header_cell("Source"),
-            ]),
-        ]);
-        
-        for event in &report.timeline {
-            let ts = event.timestamp.format("%Y-%m-%d %H:%M:%S").to_string();
-            table = table.add_row(TableRow::new(vec![
-                self.data_cell(&ts),
-                self.data_cell(&event.timestamp_type),
-                self.data_cell(&event.description),
-                self.data_cell(&event.source),
-            ]));
-        }
+        let rows = report.timeline.iter()
+            .map(|event| {
+                let ts = event.timestamp.format("%Y-%m-%d %H:%M:%S").to_string();
+                vec![
+                    self.data_cell(&ts),
+                    self.data_cell(&event.timestamp_type),
+                    self.data_cell(&event.description),
+                    self.data_cell(&event.source),
+                ]
+            })
+            .collect();
+        let table = self.table(&TIMELINE_COLUMNS, &["Timestamp", "Type", "Description", "Source"], rows);
         
         docx.add_table(table)
             .add_paragraph(Paragraph::new())
@@ -340,28 +488,60 @@ impl DocxGenerator {
     fn add_tools_section(&self, docx: Docx, report: &ForensicReport) -> Docx {
         let docx = self.add_section_header(docx, "Tools Used");
         
-        let mut table = Table::new(vec![
-            TableRow::new(vec![
-                self.header_cell("Tool"),
-                self.header_cell("Version"),
-                self.header_cell("Vendor"),
-                self.header_cell("Purpose"),
-            ]),
-        ]);
-        
-        for tool in &report.tools {
-            table = table.add_row(TableRow::new(vec![
+        let rows = report.tools.iter()
+            .map(|tool| vec![
                 self.data_cell(&tool.name),
                 self.data_cell(&tool.version),
                 self.data_cell(tool.vendor.as_deref().unwrap_or("-")),
                 self.data_cell(tool.purpose.as_deref().unwrap_or("-")),
-            ]));
+            ])
+            .collect();
+        let table = self.table(&TOOL_COLUMNS, &["Tool", "Version", "Vendor", "Purpose"], rows);
+        
+        docx.add_table(table)
+            .add_paragraph(Paragraph::new())
+    }
+
+    /// Appendix listing every image segment of every evidence item
+    fn add_segment_appendix(&self, docx: Docx, report: &ForensicReport) -> Docx {
+        let docx = self.add_section_header(docx, "Appendix: Image Segments");
+        
+        let mut rows = Vec::new();
+        for item in &report.evidence_items {
+            let Some(ref image) = item.image_info else { continue };
+            for (idx, (name, present)) in image.segment_files().into_iter().enumerate() {
+                rows.push(vec![
+                    self.data_cell(&item.evidence_id),
+                    self.data_cell(&(idx + 1).to_string()),
+                    self.data_cell(name),
+                    self.data_cell(if present { "Present" } else { "MISSING" }),
+                ]);
+            }
         }
+        let table = self.table(&SEGMENT_COLUMNS, &["Evidence", "#", "Segment File", "Status"], rows);
         
         docx.add_table(table)
             .add_paragraph(Paragraph::new())
     }
 
+    /// The embedded JSON, one monospaced paragraph per line
+    fn add_source_appendix(&self, docx: Docx, appendix: &SourceAppendix) -> Docx {
+        let mut docx = self.add_section_header(docx, "Appendix: Source Data");
+        let parts = [
+            ("Report data (JSON)", Some(&appendix.report_json)),
+            ("Container and verification data (JSON)", appendix.source_json.as_ref()),
+        ];
+        for (title, json) in parts {
+            let Some(json) = json else { continue };
+            docx = docx.add_paragraph(Paragraph::new().add_run(Run::new().add_text(title).bold().size(20)));
+            for line in json.lines() {
+                docx = docx.add_paragraph(Paragraph::new().add_run(Run::new().add_text(line).style(HASH_STYLE)));
+            }
+            docx = docx.add_paragraph(Paragraph::new());
+        }
+        docx
+    }
+
     fn add_conclusions(&self, docx: Docx, report: &ForensicReport) -> Docx {
         let docx = self.add_section_header(docx, "Conclusions");
         
@@ -406,11 +586,14 @@ impl DocxGenerator {
         docx.add_paragraph(
             Paragraph::new()
                 .add_run(Run::new().add_text(title).bold().size(28)) // 14pt
+                .keep_next(true)
         )
     }
 
     fn add_info_table(&self, docx: Docx, rows: &[(&str, String)]) -> Docx {
-        let mut table = Table::new(vec![]);
+        let mut table = Table::new(vec![])
+            .set_grid(INFO_COLUMNS.to_vec())
+            .layout(TableLayoutType::Fixed);
         
         for (label, value) in rows {
             table = table.add_row(TableRow::new(vec![
@@ -419,23 +602,49 @@ impl DocxGenerator {
                         Paragraph::new()
                             .add_run(Run::new().add_text(*label).bold().size(20))
                     )
-                    .width(2000, WidthType::Dxa),
+                    .width(INFO_COLUMNS[0], WidthType::Dxa),
                 TableCell::new()
                     .add_paragraph(
                         Paragraph::new()
                             .add_run(Run::new().add_text(value).size(20))
-                    ),
-            ]));
+                    )
+                    .width(INFO_COLUMNS[1], WidthType::Dxa),
+            ]).cant_split());
         }
         
         docx.add_table(table)
     }
 
+    /// Fixed-layout table with a styled header row
+    ///
+    /// Rows never break across pages, and the header paragraphs are kept with
+    /// the next row so a page never ends on a bare header.
+    fn table(&self, widths: &[usize], headers: &[&str], rows: Vec<Vec<TableCell>>) -> Table {
+        let with_widths = |cells: Vec<TableCell>| -> Vec<TableCell> {
+            cells.into_iter()
+                .zip(widths)
+                .map(|(cell, width)| cell.width(*width, WidthType::Dxa))
+                .collect()
+        };
+        
+        let header = headers.iter().map(|title| self.header_cell(title)).collect();
+        let mut table = Table::new(vec![TableRow::new(with_widths(header)).cant_split()])
+            .set_grid(widths.to_vec())
+            .layout(TableLayoutType::Fixed)
+            .width(widths.iter().sum(), WidthType::Dxa);
+        for cells in rows {
+            table = table.add_row(TableRow::new(with_widths(cells)).cant_split());
+        }
+        table
+    }
+
     fn header_cell(&self, text: &str) -> TableCell {
         TableCell::new()
             .add_paragraph(
                 Paragraph::new()
-                    .add_run(Run::new().add_text(text).bold().size(18))
+                    .style(TABLE_HEADER_STYLE)
+                    .keep_next(true)
+                    .add_run(Run::new().add_text(text))
             )
             .shading(Shading::new().fill("1a365d")) // Dark blue background
     }
@@ -447,6 +656,71 @@ impl DocxGenerator {
                     .add_run(Run::new().add_text(text).size(18))
             )
     }
+
+    /// Hash value in the monospace style, with an optional note line below
+    fn hash_cell(&self, hash: &str, note: Option<&str>) -> TableCell {
+        let cell = TableCell::new()
+            .add_paragraph(
+                Paragraph::new()
+                    .add_run(Run::new().add_text(hash).style(HASH_STYLE))
+            );
+        match note {
+            Some(note) => cell.add_paragraph(
+                Paragraph::new()
+                    .add_run(Run::new().add_text(note).italic().size(14))
+            ),
+            None => cell,
+        }
+    }
+}
+
+/// docProps/core.xml with the report title, the examiner as author and the
+/// generation time as created/modified date
+fn core_properties_xml(report: &ForensicReport) -> String {
+    use quick_xml::escape::escape;
+    
+    let created = report.metadata.generated_at.format("%Y-%m-%dT%H:%M:%SZ");
+    format!(
+        concat!(
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+            r#"<cp:coreProperties xmlns:cp="http://schemas.openxmlformats.org/package/2006/metadata/core-properties" "#,
+            r#"xmlns:dc="http://purl.org/dc/elements/1.1/" xmlns:dcterms="http://purl.org/dc/terms/" "#,
+            r#"xmlns:dcmitype="http://purl.org/dc/dcmitype/" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance">"#,
+            "<dc:title>{title}</dc:title>",
+            "<dc:subject>Case {case}</dc:subject>",
+            "<dc:creator>{author}</dc:creator>",
+            "<cp:lastModifiedBy>{author}</cp:lastModifiedBy>",
+            "<cp:revision>1</cp:revision>",
+            r#"<dcterms:created xsi:type="dcterms:W3CDTF">{created}</dcterms:created>"#,
+            r#"<dcterms:modified xsi:type="dcterms:W3CDTF">{created}</dcterms:modified>"#,
+            "</cp:coreProperties>",
+        ),
+        title = escape(report.metadata.title.as_str()),
+        case = escape(report.case_info.case_number.as_str()),
+        author = escape(report.examiner.name.as_str()),
+        created = created,
+    )
+}
+
+/// Replace the core properties part of a packed document
+///
+/// docx-rs only exposes the created/modified dates of the core properties, so
+/// title and author are set by rewriting the part after packing. All other
+/// entries are copied without recompressing.
+fn set_core_properties(packed: &[u8], core_xml: &str) -> ReportResult<Vec<u8>> {
+    let zip_err = |e: zip::result::ZipError| ReportError::Docx(e.to_string());
+    
+    let mut archive = zip::ZipArchive::new(Cursor::new(packed)).map_err(zip_err)?;
+    let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+    for idx in 0..archive.len() {
+        let entry = archive.by_index_raw(idx).map_err(zip_err)?;
+        if entry.name() != CORE_PROPS_PART {
+            writer.raw_copy_file(entry).map_err(zip_err)?;
+        }
+    }
+    writer.start_file(CORE_PROPS_PART, SimpleFileOptions::default()).map_err(zip_err)?;
+    writer.write_all(core_xml.as_bytes())?;
+    Ok(writer.finish().map_err(zip_err)?.into_inner())
 }
 
 impl Default for DocxGenerator {
@@ -454,3 +728,109 @@ impl Default for DocxGenerator {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+
+    const MD5: &str = "9e107d9d372bb6826bd81d3542a419d6";
+    const SHA256: &str = "d7a8fbb307d7809469ca9abcb0082e4f8d5651e46d3cdb762d02d0bf37c9e592";
+
+    fn read_part(path: &Path, part: &str) -> String {
+        let mut archive = zip::ZipArchive::new(std::fs::File::open(path).unwrap()).unwrap();
+        let mut xml = String::new();
+        archive.by_name(part).unwrap().read_to_string(&mut xml).unwrap();
+        xml
+    }
+
+    /// Table rows of document.xml, one string per `<w:tr>`
+    fn table_rows(xml: &str) -> Vec<&str> {
+        xml.split("<w:tr>").skip(1).map(|row| row.split("</w:tr>").next().unwrap()).collect()
+    }
+
+    #[test]
+    fn test_docx_tables_round_trip() {
+        let generated_at = chrono::DateTime::parse_from_rfc3339("2026-02-04T08:00:00Z").unwrap().with_timezone(&chrono::Utc);
+        let report = ForensicReport::builder()
+            .metadata(ReportMetadata {
+                title: "Forensic Examination Report - Case 2026-017".to_string(),
+                report_number: "RPT-2026-017".to_string(),
+                version: "1.0".to_string(),
+                classification: Classification::Confidential,
+                generated_at,
+                generated_by: "FFX Forensic File Xplorer".to_string(),
+            })
+            .case_number("2026-017")
+            .examiner_name("J. Rivera")
+            .add_evidence(EvidenceItem {
+                evidence_id: "EV-03".to_string(),
+                description: "Laptop SSD".to_string(),
+                evidence_type: EvidenceType::ForensicImage,
+                make: None,
+                model: None,
+                serial_number: Some("S64ANS0T512345".to_string()),
+                capacity: None,
+                condition: None,
+                received_date: None,
+                submitted_by: None,
+                acquisition_hashes: vec![],
+                image_info: Some(ImageInfo {
+                    format: "E01".to_string(),
+                    file_names: vec!["laptop.E02".to_string(), "laptop.E01".to_string()],
+                    total_size: 1_610_612_736,
+                    segments: Some(3),
+                    compression: None,
+                    acquisition_tool: None,
+                    acquisition_date: None,
+                    missing_segments: vec!["laptop.E03".to_string()],
+                }),
+                notes: None,
+                acquisition: None,
+                verification: vec![VerificationRecord {
+                    algorithm: "MD5".to_string(),
+                    stored: Some(MD5.to_string()),
+                    computed: Some(MD5.to_string()),
+                    result: VerificationResult::Verified,
+                    source: Some("container".to_string()),
+                    timestamp: None,
+                }],
+                file_hashes: vec![],
+            })
+            .add_hash_record(HashRecord {
+                item: "laptop.E01".to_string(),
+                algorithm: HashAlgorithm::SHA256,
+                value: SHA256.to_string(),
+                computed_at: None,
+                verified: Some(false),
+            })
+            .build()
+            .unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("report.docx");
+        DocxGenerator::new().generate(&report, &path).unwrap();
+
+        let document = read_part(&path, "word/document.xml");
+        let rows = table_rows(&document);
+        let row_with = |needles: &[&str]| rows.iter().any(|row| needles.iter().all(|n| row.contains(n)));
+        assert!(row_with(&["EV-03", "Laptop SSD", "S64ANS0T512345"]));
+        assert!(row_with(&["MD5", "Stored", MD5, "Verified"]));
+        assert!(row_with(&["Computed", MD5]));
+        assert!(row_with(&["laptop.E01", "SHA-256", SHA256, "FAILED"]));
+        // Segments are listed in name order with the gap flagged
+        assert!(row_with(&["EV-03", ">1<", "laptop.E01", "Present"]));
+        assert!(row_with(&["EV-03", ">3<", "laptop.E03", "MISSING"]));
+
+        // Hashes use the monospace style, header rows stay with their table
+        assert!(rows.iter().filter(|row| row.contains(SHA256)).all(|row| row.contains(HASH_STYLE)));
+        assert!(rows.iter().all(|row| row.contains("<w:cantSplit")));
+        assert!(document.contains(r#"<w:tblLayout w:type="fixed""#));
+        assert!(read_part(&path, "word/styles.xml").contains("Consolas"));
+
+        let core = read_part(&path, CORE_PROPS_PART);
+        assert!(core.contains("<dc:title>Forensic Examination Report - Case 2026-017</dc:title>"));
+        assert!(core.contains("<dc:creator>J. Rivera</dc:creator>"));
+        assert!(core.contains(">2026-02-04T08:00:00Z</dcterms:created>"));
+    }
+}
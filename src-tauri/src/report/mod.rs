@@ -46,14 +46,16 @@
 //!     .build()?;
 //!
 //! let generator = ReportGenerator::new();
-//! generator.generate(&report, OutputFormat::Pdf, "output/report.pdf")?;
+//! generator.generate(&report, OutputFormat::Pdf, "output/report.pdf", &GenerateOptions::default())?;
 //! ```
 
 pub mod types;
 pub mod template;
+pub mod appendix;
 pub mod pdf;
 pub mod docx;
 pub mod error;
+#[cfg(feature = "gui")]
 pub mod commands;
 
 #[cfg(feature = "ai-assistant")]
@@ -65,18 +67,21 @@ pub mod typst_gen;
 // Re-exports for convenience
 pub use types::*;
 pub use template::TemplateEngine;
+pub use appendix::{GenerateOptions, SourceAppendix};
 pub use pdf::PdfGenerator;
 pub use docx::DocxGenerator;
 pub use error::{ReportError, ReportResult};
 
 #[cfg(feature = "ai-assistant")]
-pub use ai::AiAssistant;
+pub use ai::{AiAssistant, AiProvider, NarrativeContext, NarrativeProvider, OfflineProvider, RemoteProvider};
 
 #[cfg(feature = "typst-reports")]
 pub use typst_gen::TypstGenerator;
 
 use std::path::Path;
 
+use crate::common::atomic_file::write_atomic;
+
 /// Supported output formats for report generation
 #[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
 pub enum OutputFormat {
@@ -88,7 +93,7 @@ pub enum OutputFormat {
     Html,
     /// Markdown document
     Markdown,
-    /// Typst document (future support)
+    /// Typst source document (requires the `typst-reports` feature)
     #[serde(rename = "typst")]
     Typst,
 }
@@ -126,7 +131,7 @@ pub struct ReportGenerator {
     #[cfg(feature = "typst-reports")]
     typst_generator: TypstGenerator,
     #[cfg(feature = "ai-assistant")]
-    ai_assistant: Option<AiAssistant>,
+    ai_assistant: AiAssistant,
 }
 
 impl ReportGenerator {
@@ -139,7 +144,7 @@ impl ReportGenerator {
             #[cfg(feature = "typst-reports")]
             typst_generator: TypstGenerator::new(),
             #[cfg(feature = "ai-assistant")]
-            ai_assistant: None,
+            ai_assistant: AiAssistant::default(),
         })
     }
 
@@ -152,29 +157,46 @@ impl ReportGenerator {
             #[cfg(feature = "typst-reports")]
             typst_generator: TypstGenerator::new(),
             #[cfg(feature = "ai-assistant")]
-            ai_assistant: None,
+            ai_assistant: AiAssistant::default(),
         })
     }
 
     #[cfg(feature = "ai-assistant")]
-    /// Enable AI assistance with the specified provider
-    pub fn with_ai(mut self, assistant: AiAssistant) -> Self {
-        self.ai_assistant = Some(assistant);
+    /// Use the given narrative provider instead of the offline default
+    pub fn with_ai(mut self, provider: impl NarrativeProvider + 'static) -> Self {
+        self.ai_assistant = AiAssistant::with_provider(provider);
         self
     }
 
     /// Generate a report in the specified format
+    ///
+    /// With `options.embed_source_data`, HTML, DOCX and Markdown reports end
+    /// with the report data (and `options.source_data`) as JSON; other
+    /// formats refuse the option.
     pub fn generate(
         &self,
         report: &ForensicReport,
         format: OutputFormat,
         output_path: impl AsRef<Path>,
+        options: &GenerateOptions,
     ) -> ReportResult<()> {
+        let appendix = if options.embed_source_data {
+            if !matches!(format, OutputFormat::Html | OutputFormat::Docx | OutputFormat::Markdown) {
+                return Err(ReportError::UnsupportedFormat(format!(
+                    "Source data can only be embedded in HTML, DOCX and Markdown reports, not {}",
+                    format.extension()
+                )));
+            }
+            Some(SourceAppendix::new(report, options.source_data.as_ref())?)
+        } else {
+            None
+        };
+        let appendix = appendix.as_ref();
         match format {
             OutputFormat::Pdf => self.pdf_generator.generate(report, output_path),
-            OutputFormat::Docx => self.docx_generator.generate(report, output_path),
-            OutputFormat::Html => self.generate_html(report, output_path),
-            OutputFormat::Markdown => self.generate_markdown(report, output_path),
+            OutputFormat::Docx => self.docx_generator.generate_with_appendix(report, appendix, output_path),
+            OutputFormat::Html => self.generate_html(report, appendix, output_path),
+            OutputFormat::Markdown => self.generate_markdown(report, appendix, output_path),
             #[cfg(feature = "typst-reports")]
             OutputFormat::Typst => self.typst_generator.generate(report, output_path),
             #[cfg(not(feature = "typst-reports"))]
@@ -185,16 +207,16 @@ impl ReportGenerator {
     }
 
     /// Generate HTML report using templates
-    fn generate_html(&self, report: &ForensicReport, output_path: impl AsRef<Path>) -> ReportResult<()> {
-        let html = self.template_engine.render_html(report)?;
-        std::fs::write(output_path, html)?;
+    fn generate_html(&self, report: &ForensicReport, appendix: Option<&SourceAppendix>, output_path: impl AsRef<Path>) -> ReportResult<()> {
+        let html = self.template_engine.render_with_appendix("report.html", report, appendix)?;
+        write_atomic(output_path.as_ref(), html)?;
         Ok(())
     }
 
     /// Generate Markdown report using templates
-    fn generate_markdown(&self, report: &ForensicReport, output_path: impl AsRef<Path>) -> ReportResult<()> {
-        let markdown = self.template_engine.render_markdown(report)?;
-        std::fs::write(output_path, markdown)?;
+    fn generate_markdown(&self, report: &ForensicReport, appendix: Option<&SourceAppendix>, output_path: impl AsRef<Path>) -> ReportResult<()> {
+        let markdown = self.template_engine.render_with_appendix("report.md", report, appendix)?;
+        write_atomic(output_path.as_ref(), markdown)?;
         Ok(())
     }
 
@@ -212,13 +234,10 @@ impl ReportGenerator {
     /// Generate AI-assisted narrative for a section
     pub async fn generate_narrative(
         &self,
-        context: &str,
+        context: &NarrativeContext<'_>,
         section_type: NarrativeType,
     ) -> ReportResult<String> {
-        match &self.ai_assistant {
-            Some(ai) => ai.generate_narrative(context, section_type).await,
-            None => Err(ReportError::AiNotConfigured),
-        }
+        self.ai_assistant.generate_narrative(context, section_type).await
     }
 }
 
@@ -245,6 +264,20 @@ pub enum NarrativeType {
     Conclusion,
 }
 
+impl NarrativeType {
+    /// Identifier used by the frontend ("executive_summary", "finding", ...)
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NarrativeType::ExecutiveSummary => "executive_summary",
+            NarrativeType::FindingDescription => "finding",
+            NarrativeType::TimelineNarrative => "timeline",
+            NarrativeType::EvidenceDescription => "evidence",
+            NarrativeType::Methodology => "methodology",
+            NarrativeType::Conclusion => "conclusion",
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
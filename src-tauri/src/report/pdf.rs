@@ -9,6 +9,7 @@ use genpdf::{
 };
 
 use super::error::{ReportError, ReportResult};
+use crate::common::atomic_file::AtomicFile;
 use super::types::*;
 
 /// PDF generator for forensic reports
@@ -111,9 +112,11 @@ impl PdfGenerator {
         
         self.add_footer(&mut doc, report)?;
 
-        // Render to file
-        doc.render_to_file(output_path)
+        // Render under a partial name, so a failure never leaves a truncated PDF
+        let mut file = AtomicFile::create(output_path.as_ref())?;
+        doc.render(&mut file)
             .map_err(|e| ReportError::Pdf(e.to_string()))?;
+        file.commit()?;
 
         Ok(())
     }
@@ -244,6 +247,89 @@ impl PdfGenerator {
         doc.push(table);
         doc.push(Break::new(1.0));
         
+        for item in &report.evidence_items {
+            if item.acquisition.is_some() || !item.verification.is_empty() || !item.file_hashes.is_empty() {
+                self.add_verification_table(doc, item)?;
+            }
+        }
+        
+        Ok(())
+    }
+
+    /// "Acquisition & Verification" details and hash table for one evidence item
+    fn add_verification_table(&self, doc: &mut Document, item: &EvidenceItem) -> ReportResult<()> {
+        doc.push(
+            Paragraph::new(format!("Evidence {} - Acquisition & Verification", item.evidence_id))
+                .styled(style::Style::new().bold().with_font_size(11))
+        );
+        doc.push(Break::new(0.25));
+        
+        if let Some(ref acq) = item.acquisition {
+            let mut details = LinearLayout::vertical();
+            for (label, value) in acquisition_rows(acq) {
+                details.push(self.info_row(&format!("{}:", label), value).styled(style::Style::new().with_font_size(9)));
+            }
+            doc.push(details);
+        }
+        if let Some(ref image) = item.image_info {
+            if !image.missing_segments.is_empty() {
+                doc.push(
+                    self.info_row("Missing segments:", &image.missing_segments.join(", "))
+                        .styled(style::Style::new().bold().with_font_size(9))
+                );
+            }
+        }
+        
+        if !item.verification.is_empty() {
+            doc.push(Break::new(0.25));
+            let mut table = TableLayout::new(vec![1, 3, 3, 1, 1, 2]);
+            table.set_cell_decorator(genpdf::elements::FrameCellDecorator::new(true, true, false));
+            
+            let mut header = table.row();
+            for title in ["Algorithm", "Stored", "Computed", "Result", "Source", "Timestamp"] {
+                header = header.element(Text::new(title).styled(style::Style::new().bold().with_font_size(8)));
+            }
+            header.push().map_err(|e| ReportError::Pdf(e.to_string()))?;
+            
+            let small = style::Style::new().with_font_size(8);
+            for record in &item.verification {
+                table.row()
+                    .element(Paragraph::new(&record.algorithm).styled(small))
+                    .element(Paragraph::new(record.stored.as_deref().unwrap_or("-")).styled(small))
+                    .element(Paragraph::new(record.computed.as_deref().unwrap_or("-")).styled(small))
+                    .element(Paragraph::new(record.result.as_str()).styled(small))
+                    .element(Paragraph::new(record.source.as_deref().unwrap_or("-")).styled(small))
+                    .element(Paragraph::new(record.timestamp.as_deref().unwrap_or("-")).styled(small))
+                    .push()
+                    .map_err(|e| ReportError::Pdf(e.to_string()))?;
+            }
+            doc.push(table);
+        }
+
+        if !item.file_hashes.is_empty() {
+            doc.push(Break::new(0.25));
+            let mut table = TableLayout::new(vec![3, 1, 4]);
+            table.set_cell_decorator(genpdf::elements::FrameCellDecorator::new(true, true, false));
+
+            let mut header = table.row();
+            for title in ["File", "Algorithm", "Stored Hash"] {
+                header = header.element(Text::new(title).styled(style::Style::new().bold().with_font_size(8)));
+            }
+            header.push().map_err(|e| ReportError::Pdf(e.to_string()))?;
+
+            let small = style::Style::new().with_font_size(8);
+            for file in &item.file_hashes {
+                table.row()
+                    .element(Paragraph::new(&file.file_name).styled(small))
+                    .element(Paragraph::new(&file.algorithm).styled(small))
+                    .element(Paragraph::new(&file.hash).styled(small))
+                    .push()
+                    .map_err(|e| ReportError::Pdf(e.to_string()))?;
+            }
+            doc.push(table);
+        }
+        
+        doc.push(Break::new(1.0));
         Ok(())
     }
 
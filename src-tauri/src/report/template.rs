@@ -5,6 +5,7 @@
 use std::path::Path;
 use tera::{Context, Tera};
 
+use super::appendix::SourceAppendix;
 use super::error::ReportResult;
 use super::types::ForensicReport;
 
@@ -116,18 +117,35 @@ impl TemplateEngine {
 
     /// Render a report using a specific template
     pub fn render(&self, template_name: &str, report: &ForensicReport) -> ReportResult<String> {
-        let context = self.build_context(report)?;
+        self.render_with_appendix(template_name, report, None)
+    }
+
+    /// Render a report, with its source data as `source_data` when given
+    ///
+    /// The templates then see the report value the appendix embeds.
+    pub fn render_with_appendix(
+        &self,
+        template_name: &str,
+        report: &ForensicReport,
+        appendix: Option<&SourceAppendix>,
+    ) -> ReportResult<String> {
+        let context = self.build_context(report, appendix)?;
         let rendered = self.tera.render(template_name, &context)?;
         Ok(rendered)
     }
 
     /// Build Tera context from report
-    fn build_context(&self, report: &ForensicReport) -> ReportResult<Context> {
+    fn build_context(&self, report: &ForensicReport, appendix: Option<&SourceAppendix>) -> ReportResult<Context> {
         let mut context = Context::new();
         
         // Serialize report to JSON and insert into context
-        let report_json = serde_json::to_value(report)?;
-        context.insert("report", &report_json);
+        match appendix {
+            Some(appendix) => {
+                context.insert("report", &appendix.report);
+                context.insert("source_data", &appendix.context());
+            }
+            None => context.insert("report", &serde_json::to_value(report)?),
+        }
         
         // Also add top-level shortcuts for convenience
         context.insert("metadata", &report.metadata);
@@ -153,7 +171,7 @@ impl TemplateEngine {
 }
 
 /// Format bytes as human-readable string
-fn format_bytes(bytes: u64) -> String {
+pub(crate) fn format_bytes(bytes: u64) -> String {
     const KB: u64 = 1024;
     const MB: u64 = KB * 1024;
     const GB: u64 = MB * 1024;
@@ -175,6 +193,10 @@ fn format_bytes(bytes: u64) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::common::time::Timestamp;
+    use crate::containers::{CompanionLogInfo, ContainerInfo, LogConfidence, SegmentFile, SegmentSetInfo, StoredHash};
+    use crate::ewf::EwfInfo;
+    use crate::report::types::{Classification, EvidenceItem, ReportMetadata};
 
     #[test]
     fn test_format_bytes() {
@@ -189,4 +211,212 @@ mod tests {
         let engine = TemplateEngine::new();
         assert!(engine.is_ok());
     }
+
+    fn stored_hash(algorithm: &str, hash: &str, source: &str, timestamp: Option<&str>) -> StoredHash {
+        StoredHash {
+            algorithm: algorithm.to_string(),
+            hash: hash.to_string(),
+            verified: None,
+            timestamp: timestamp.map(str::to_string),
+            source: Some(source.to_string()),
+            offset: None,
+            size: None,
+        }
+    }
+
+    /// Two-segment E01 (third segment missing) with an FTK Imager log, after
+    /// MD5 and SHA1 were computed - the SHA1 does not match the log
+    fn fixture_container() -> ContainerInfo {
+        let md5 = "9e107d9d372bb6826bd81d3542a419d6";
+        ContainerInfo {
+            container: "E01".to_string(),
+            ad1: None,
+            e01: Some(EwfInfo {
+                format_version: "EWF1".to_string(),
+                segment_count: 2,
+                chunk_count: 61_035,
+                sector_count: 3_906_250,
+                bytes_per_sector: 512,
+                sectors_per_chunk: 64,
+                total_size: 2_000_000_000,
+                compression: "Good".to_string(),
+                case_number: Some("2026-017".to_string()),
+                description: Some("Laptop SSD".to_string()),
+                examiner_name: Some("J. Rivera".to_string()),
+                evidence_number: Some("EV-03".to_string()),
+                notes: None,
+                acquiry_date: Some(Timestamp::parse("2026-01-12T09:14:02Z")),
+                system_date: None,
+                model: Some("Samsung PM9A1".to_string()),
+                serial_number: Some("S64ANS0T512345".to_string()),
+                media_type: Some("fixed".to_string()),
+                sessions: Vec::new(),
+                acquisition_errors: Vec::new(),
+                logical_entries: None,
+                stored_hashes: vec![
+                    stored_hash("MD5", md5, "container", None),
+                    stored_hash("MD5", md5, "computed", Some("2026-02-03T15:20:00Z")),
+                    stored_hash("SHA1", "2fd4e1c67a2d28fced849ee1bb76e7391b93eb12", "computed", Some("2026-02-03T15:20:00Z")),
+                ],
+                segment_files: None,
+                header_section_offset: None,
+                volume_section_offset: None,
+                hash_section_offset: None,
+                digest_section_offset: None,
+                segment_consistency: None,
+                parse_warnings: Vec::new(),
+            }),
+            l01: None,
+            raw: None,
+            archive: None,
+            ufed: None,
+            iso: None,
+            note: None,
+            companion_log: Some(CompanionLogInfo {
+                log_path: "/evidence/laptop.E01.txt".to_string(),
+                created_by: Some("AccessData FTK Imager 4.7.1.2".to_string()),
+                case_number: Some("2026-017".to_string()),
+                evidence_number: Some("EV-03".to_string()),
+                unique_description: Some("Dell Latitude 7420 internal SSD".to_string()),
+                examiner: Some("J. Rivera".to_string()),
+                acquisition_started: Some(Timestamp::parse("Mon Jan 12 09:14:02 2026")),
+                acquisition_finished: Some(Timestamp::parse("Mon Jan 12 10:02:47 2026")),
+                verification_started: Some(Timestamp::parse("Mon Jan 12 10:02:48 2026")),
+                verification_finished: Some(Timestamp::parse("Mon Jan 12 10:31:05 2026")),
+                stored_hashes: vec![
+                    stored_hash("SHA1", "de9f2c7fd25e1b3afad3e85a0bd17d9b100db4b3", "companion", Some("Mon Jan 12 10:31:05 2026")),
+                ],
+                confidence: LogConfidence::Exact,
+                ..Default::default()
+            }),
+            segments: Some(SegmentSetInfo {
+                count: 2,
+                total_size: 1_610_612_736,
+                files: vec![
+                    SegmentFile { name: "laptop.E01".to_string(), size: 1_073_741_824 },
+                    SegmentFile { name: "laptop.E02".to_string(), size: 536_870_912 },
+                ],
+                missing: vec!["laptop.E03".to_string()],
+            }),
+            manifest: None,
+            triage: None,
+            physical_size: None,
+            logical_size: None,
+            compression_ratio: None,
+        }
+    }
+
+    #[test]
+    fn test_markdown_evidence_verification_golden() {
+        let generated_at = chrono::DateTime::parse_from_rfc3339("2026-02-04T08:00:00Z").unwrap().with_timezone(&chrono::Utc);
+        let report = ForensicReport::builder()
+            .metadata(ReportMetadata {
+                title: "Forensic Examination Report - Case 2026-017".to_string(),
+                report_number: "RPT-2026-017".to_string(),
+                version: "1.0".to_string(),
+                classification: Classification::Confidential,
+                generated_at,
+                generated_by: "FFX Forensic File Xplorer".to_string(),
+            })
+            .case_number("2026-017")
+            .examiner_name("J. Rivera")
+            .add_evidence(EvidenceItem::from_container(&fixture_container()))
+            .build()
+            .unwrap();
+
+        let rendered = TemplateEngine::new().unwrap().render_markdown(&report).unwrap();
+        let golden_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("src/report/testdata/evidence_verification.md");
+        if std::env::var_os("UPDATE_GOLDEN").is_some() {
+            std::fs::write(&golden_path, &rendered).unwrap();
+        }
+        let golden = std::fs::read_to_string(&golden_path).unwrap();
+        assert_eq!(rendered, golden, "Markdown layout changed - rerun with UPDATE_GOLDEN=1 to accept");
+    }
+
+    #[test]
+    fn test_ufed_file_hashes_are_listed_by_file() {
+        let mut container = fixture_container();
+        let file_hash = |filename: &str, hash: &str| crate::ufed::StoredHash {
+            filename: filename.to_string(),
+            algorithm: "SHA256".to_string(),
+            hash: hash.to_string(),
+        };
+        container.ufed = Some(crate::ufed::UfedInfo {
+            format: "UFD".to_string(),
+            size: 2048,
+            parent_folder: None,
+            associated_files: Vec::new(),
+            is_extraction_set: true,
+            device_hint: None,
+            case_info: None,
+            device_info: None,
+            extraction_info: None,
+            stored_hashes: Some(vec![
+                file_hash("FileSystem.zip", "aa".repeat(32).as_str()),
+                file_hash("Apps.zip", "bb".repeat(32).as_str()),
+            ]),
+            evidence_number: None,
+            collection_info: None,
+            raw_sections: Default::default(),
+        });
+
+        // The image-level rows are those of the container without UFED data
+        let item = EvidenceItem::from_container(&container);
+        let without = EvidenceItem::from_container(&fixture_container());
+        assert_eq!(serde_json::to_value(&item.verification).unwrap(), serde_json::to_value(&without.verification).unwrap());
+        let files: Vec<(&str, &str)> = item.file_hashes.iter().map(|f| (f.file_name.as_str(), f.algorithm.as_str())).collect();
+        assert_eq!(files, vec![("FileSystem.zip", "SHA256"), ("Apps.zip", "SHA256")]);
+
+        let report = ForensicReport::builder()
+            .case_number("2026-017")
+            .examiner_name("J. Rivera")
+            .add_evidence(item)
+            .build()
+            .unwrap();
+        let rendered = TemplateEngine::new().unwrap().render_markdown(&report).unwrap();
+        assert!(rendered.contains(&format!("| `Apps.zip` | SHA256 | `{}` |", "bb".repeat(32))));
+    }
+
+    #[test]
+    fn test_html_source_data_round_trip() {
+        use crate::report::{GenerateOptions, OutputFormat, ReportGenerator};
+
+        let container = fixture_container();
+        let report = ForensicReport::builder()
+            .case_number("2026-017")
+            .examiner_name("J. Rivera <lab & co>")
+            .add_evidence(EvidenceItem::from_container(&container))
+            .build()
+            .unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("report.html");
+        let generator = ReportGenerator::new().unwrap();
+
+        generator.generate(&report, OutputFormat::Html, &path, &GenerateOptions::default()).unwrap();
+        assert!(!std::fs::read_to_string(&path).unwrap().contains("<pre id=\"source-data-report\">"));
+
+        let options = GenerateOptions {
+            embed_source_data: true,
+            source_data: Some(serde_json::to_value(&container).unwrap()),
+        };
+        generator.generate(&report, OutputFormat::Html, &path, &options).unwrap();
+        let html = std::fs::read_to_string(&path).unwrap();
+        let embedded = |id: &str| {
+            let open = format!("<pre id=\"{id}\">");
+            let start = html.find(&open).unwrap() + open.len();
+            let end = start + html[start..].find("</pre>").unwrap();
+            html[start..end]
+                .replace("&lt;", "<").replace("&gt;", ">").replace("&quot;", "\"")
+                .replace("&#x27;", "'").replace("&#x2F;", "/").replace("&amp;", "&")
+        };
+        let parsed: ForensicReport = serde_json::from_str(&embedded("source-data-report")).unwrap();
+        assert_eq!(parsed.examiner.name, "J. Rivera <lab & co>");
+        assert_eq!(serde_json::to_value(&parsed).unwrap(), serde_json::to_value(&report).unwrap());
+        let source: serde_json::Value = serde_json::from_str(&embedded("source-data-source")).unwrap();
+        assert_eq!(source, options.source_data.unwrap());
+
+        let pdf = dir.path().join("report.pdf");
+        let embed = GenerateOptions { embed_source_data: true, source_data: None };
+        assert!(generator.generate(&report, OutputFormat::Pdf, &pdf, &embed).is_err());
+    }
 }
@@ -0,0 +1,228 @@
+// FFX Forensic Report - Generated by {{ metadata.generated_by }}
+// Classification: {{ classification }}
+//
+// To compile this file to PDF:
+//   typst compile report.typ report.pdf
+//
+// Or use Typst's watch mode for live preview:
+//   typst watch report.typ
+
+#set document(
+  title: "{{ metadata.title | typst_str }}",
+  author: "{{ examiner.name | typst_str }}",
+)
+
+#set page(
+  paper: "us-letter",
+  margin: (x: 1in, y: 1in),
+  header: align(center, text(size: 9pt, weight: "bold", fill: {{ classification_color }})[{{ classification }}]),
+  footer: context align(center, text(size: 9pt, counter(page).display("1 of 1", both: true))),
+)
+
+#set text(font: "New Computer Modern", size: 11pt)
+#set heading(numbering: "1.1")
+#set par(justify: true, leading: 0.65em)
+#show raw: set text(font: "DejaVu Sans Mono", size: 7.5pt)
+
+// Shared styling
+#let header-fill = (x, y) => if y == 0 { luma(230) } else { none }
+#let alert(body) = text(fill: rgb("#dc2626"), weight: "bold", body)
+
+#let hash-pair(stored, computed) = grid(
+  columns: (auto, auto),
+  column-gutter: 6pt,
+  row-gutter: 4pt,
+  [Stored], if stored == none { [-] } else { raw(stored) },
+  [Computed], if computed == none { [-] } else { raw(computed) },
+)
+
+#let severity-badge(level) = {
+  let (color, label) = if level == "Critical" {
+    (rgb("#dc2626"), "CRITICAL")
+  } else if level == "High" {
+    (rgb("#ea580c"), "HIGH")
+  } else if level == "Medium" {
+    (rgb("#ca8a04"), "MEDIUM")
+  } else if level == "Low" {
+    (rgb("#16a34a"), "LOW")
+  } else {
+    (rgb("#6b7280"), "INFO")
+  }
+  box(fill: color, inset: (x: 6pt, y: 3pt), radius: 3pt)[#text(fill: white, weight: "bold", size: 8pt)[#label]]
+}
+
+// Title Page
+#align(center)[
+  #v(2in)
+
+  #text(size: 24pt, weight: "bold")[{{ metadata.title | typst }}]
+
+  #v(0.5em)
+
+  #text(size: 14pt)[Report \#{{ metadata.report_number | typst }} | Version {{ metadata.version | typst }}]
+
+  #v(2em)
+
+  #table(
+    columns: (auto, auto),
+    stroke: none,
+    align: (right, left),
+    [*Case Number*], [{{ case.case_number | typst }}],
+{% if case.case_name %}    [*Case Name*], [{{ case.case_name | typst }}],
+{% endif %}{% if case.agency %}    [*Agency*], [{{ case.agency | typst }}],
+{% endif %}    [*Evidence Items*], [{{ evidence | length }}],
+  )
+
+  #v(3em)
+
+  #text(size: 11pt)[
+    Prepared by: \
+    *{{ examiner.name | typst }}*{% if examiner.title %} \
+    {{ examiner.title | typst }}{% endif %}{% if examiner.organization %} \
+    {{ examiner.organization | typst }}{% endif %}
+  ]
+
+  #v(2em)
+
+  #text(size: 10pt)[Generated: {{ generated_date }}]
+
+  #v(1fr)
+
+  #text(size: 12pt, weight: "bold")[{{ classification }}]
+]
+
+#pagebreak()
+
+#outline(title: "Table of Contents", depth: 2)
+
+#pagebreak()
+
+= Case Information
+
+#table(
+  columns: (auto, 1fr),
+  stroke: none,
+  row-gutter: 0.5em,
+  [*Case Number*], [{{ case.case_number | typst }}],
+{% if case.case_name %}  [*Case Name*], [{{ case.case_name | typst }}],
+{% endif %}{% if case.agency %}  [*Agency*], [{{ case.agency | typst }}],
+{% endif %}{% if case.requestor %}  [*Requestor*], [{{ case.requestor | typst }}],
+{% endif %}{% if case.investigation_type %}  [*Investigation Type*], [{{ case.investigation_type | typst }}],
+{% endif %})
+
+== Examiner
+
+#table(
+  columns: (auto, 1fr),
+  stroke: none,
+  row-gutter: 0.5em,
+  [*Name*], [{{ examiner.name | typst }}],
+{% if examiner.title %}  [*Title*], [{{ examiner.title | typst }}],
+{% endif %}{% if examiner.organization %}  [*Organization*], [{{ examiner.organization | typst }}],
+{% endif %}{% if examiner.email %}  [*Email*], [{{ examiner.email | typst }}],
+{% endif %}{% if examiner.phone %}  [*Phone*], [{{ examiner.phone | typst }}],
+{% endif %}{% if examiner.certifications | length > 0 %}  [*Certifications*], [{% for cert in examiner.certifications %}{{ cert | typst }}{% if not loop.last %}, {% endif %}{% endfor %}],
+{% endif %})
+{% for section in sections %}
+= {{ section.title }}
+
+{{ section.body | typst }}
+{% endfor %}{% if evidence | length > 0 %}
+= Evidence Examined
+
+#table(
+  columns: (auto, 2fr, auto, auto, auto),
+  fill: header-fill,
+  table.header([*ID*], [*Description*], [*Type*], [*Serial*], [*Capacity*]),
+{% for item in evidence %}  [{{ item.id | typst }}], [{{ item.description | typst }}], [{{ item.evidence_type }}], [{{ item.serial | typst }}], [{{ item.capacity | typst }}],
+{% endfor %})
+{% for item in evidence %}
+== Evidence {{ item.id | typst }}
+{% if item.acquisition | length > 0 %}
+#table(
+  columns: (auto, 1fr),
+  stroke: none,
+  row-gutter: 0.3em,
+{% for row in item.acquisition %}  [*{{ row.label }}*], [{{ row.value | typst }}],
+{% endfor %})
+{% endif %}{% if item.image %}
+*Image:* {{ item.image | typst }}
+{% endif %}{% if item.missing_segments | length > 0 %}
+#alert[Missing segments:] {% for name in item.missing_segments %}{{ name | typst }}{% if not loop.last %}, {% endif %}{% endfor %}
+{% endif %}{% if item.hashes | length > 0 %}
+#table(
+  columns: (auto, 1fr, auto),
+  fill: header-fill,
+  table.header([*Algorithm*], [*Hash*], [*Result*]),
+{% for hash in item.hashes %}  [{{ hash.algorithm | typst }}], [#hash-pair({% if hash.stored %}"{{ hash.stored | typst_str }}"{% else %}none{% endif %}, {% if hash.computed %}"{{ hash.computed | typst_str }}"{% else %}none{% endif %})], [{% if hash.mismatch %}#alert[{{ hash.result }}]{% else %}{{ hash.result }}{% endif %}],
+{% endfor %})
+{% endif %}{% endfor %}{% endif %}{% if findings | length > 0 %}
+= Findings
+{% for finding in findings %}
+== {{ finding.id | typst }} -- {{ finding.title | typst }}
+
+#severity-badge("{{ finding.severity }}") #h(1em) *Category:* {{ finding.category }}
+
+{{ finding.description | typst }}
+{% if finding.related_files | length > 0 %}
+*Related Files:*
+{% for file in finding.related_files %}- #raw("{{ file | typst_str }}")
+{% endfor %}{% endif %}{% if finding.integrity | length > 0 %}
+*Evidence Integrity:*
+{% for check in finding.integrity %}- {{ check.evidence_id | typst }} {{ check.algorithm | typst }}: {% if check.mismatch %}#alert[{{ check.result }}]{% else %}{{ check.result }}{% endif %}
+{% endfor %}{% endif %}{% if finding.notes %}
+*Notes:* {{ finding.notes | typst }}
+{% endif %}{% endfor %}{% endif %}{% if hash_records | length > 0 %}
+= Hash Verification
+
+#table(
+  columns: (auto, auto, 1fr, auto),
+  fill: header-fill,
+  table.header([*Item*], [*Algorithm*], [*Hash*], [*Verified*]),
+{% for record in hash_records %}  [{{ record.item | typst }}], [{{ record.algorithm }}], [#raw("{{ record.value | typst_str }}")], [{% if record.verified == "No" %}#alert[No]{% else %}{{ record.verified }}{% endif %}],
+{% endfor %})
+{% endif %}{% if timeline | length > 0 %}
+= Timeline of Events
+
+#table(
+  columns: (auto, auto, 2fr, auto),
+  fill: header-fill,
+  table.header([*Timestamp*], [*Type*], [*Description*], [*Source*]),
+{% for event in timeline %}  [{{ event.timestamp | date(format="%Y-%m-%d %H:%M:%S") }}], [{{ event.timestamp_type | typst }}], [{{ event.description | typst }}], [{{ event.source | typst }}],
+{% endfor %})
+{% endif %}{% if tools | length > 0 %}
+= Tools Used
+
+#table(
+  columns: (1fr, auto, 1fr, 2fr),
+  fill: header-fill,
+  table.header([*Tool*], [*Version*], [*Vendor*], [*Purpose*]),
+{% for tool in tools %}  [{{ tool.name | typst }}], [{{ tool.version | typst }}], [{{ tool.vendor | typst }}], [{{ tool.purpose | typst }}],
+{% endfor %})
+{% endif %}{% if conclusions %}
+= Conclusions
+
+{{ conclusions | typst }}
+{% endif %}{% for appendix in appendices %}
+= Appendix {{ appendix.appendix_id | typst }}: {{ appendix.title | typst }}
+
+{{ appendix.content | typst }}
+{% endfor %}{% if segments | length > 0 %}
+= Appendix: Image Segments
+
+#table(
+  columns: (auto, auto, 1fr, auto),
+  fill: header-fill,
+  table.header([*Evidence*], [*\#*], [*Segment File*], [*Status*]),
+{% for segment in segments %}  [{{ segment.evidence_id | typst }}], [{{ segment.index }}], [#raw("{{ segment.name | typst_str }}"){% for hash in segment.hashes %} \ #text(size: 8pt)[{{ hash.algorithm }}] #raw("{{ hash.value | typst_str }}"){% endfor %}], [{% if segment.present %}Present{% else %}#alert[MISSING]{% endif %}],
+{% endfor %})
+{% endif %}
+// End of Report
+#v(2em)
+#align(center)[
+  #line(length: 50%)
+  #v(1em)
+  #text(size: 9pt)[This report was generated by *{{ metadata.generated_by | typst }}* on {{ generated_at }} UTC.]
+  #v(0.5em)
+  #text(size: 10pt, weight: "bold")[{{ classification }}]
+]
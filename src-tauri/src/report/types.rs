@@ -3,9 +3,16 @@
 //! This module defines all the types used to represent forensic report data.
 //! These structures are serializable and can be used with templates and output generators.
 
+use std::path::Path;
+
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
+use super::template::format_bytes;
+use crate::common::hash::{hashes_match, HashValue};
+use crate::common::time::Timestamp;
+use crate::containers::{ContainerInfo, LogConfidence, StoredHash};
+
 /// Main forensic report structure containing all report data
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ForensicReport {
@@ -358,6 +365,271 @@ pub struct EvidenceItem {
     pub image_info: Option<ImageInfo>,
     /// Notes about this item
     pub notes: Option<String>,
+    /// Acquisition and chain-of-custody details from the image and its log
+    #[serde(default)]
+    pub acquisition: Option<AcquisitionInfo>,
+    /// Stored vs computed hashes ("Acquisition & Verification" table)
+    #[serde(default)]
+    pub verification: Vec<VerificationRecord>,
+    /// Hashes the container stores for single files inside it (UFED),
+    /// which say nothing about the image as a whole
+    #[serde(default)]
+    pub file_hashes: Vec<FileHashRecord>,
+}
+
+impl EvidenceItem {
+    /// Build an evidence item from parsed container info
+    ///
+    /// Acquisition details come from the companion log, falling back to the
+    /// image headers (EWF) or extraction info (UFED). Every stored image
+    /// hash becomes a verification row, compared against a computed hash of
+    /// the same algorithm when the info carries one (source "computed").
+    /// UFED hashes cover single files of the extraction, so they are listed
+    /// by file in `file_hashes` instead. Logs flagged ambiguous are ignored -
+    /// they may belong to another image.
+    pub fn from_container(info: &ContainerInfo) -> Self {
+        let log = info.companion_log.as_ref().filter(|log| log.confidence != LogConfidence::Ambiguous);
+        let ewf = info.e01.as_ref().or(info.l01.as_ref());
+        let ufed = info.ufed.as_ref();
+        let file_names = container_file_names(info);
+        let primary_name = file_names.first().cloned().unwrap_or_else(|| info.container.clone());
+        let started = log.and_then(|l| l.acquisition_started.as_ref())
+            .or_else(|| ewf.and_then(|e| e.acquiry_date.as_ref()))
+            .or_else(|| ufed.and_then(|u| u.extraction_info.as_ref()?.start_time.as_ref()));
+        let as_written = |ts: Option<&Timestamp>| ts.map(|ts| ts.original.clone());
+
+        let acquisition = AcquisitionInfo {
+            case_number: log.and_then(|l| l.case_number.clone())
+                .or_else(|| ewf.and_then(|e| e.case_number.clone()))
+                .or_else(|| ufed.and_then(|u| u.case_info.as_ref()?.case_identifier.clone())),
+            evidence_number: log.and_then(|l| l.evidence_number.clone())
+                .or_else(|| ewf.and_then(|e| e.evidence_number.clone()))
+                .or_else(|| ufed.and_then(|u| u.evidence_number.clone())),
+            examiner: log.and_then(|l| l.examiner.clone())
+                .or_else(|| ewf.and_then(|e| e.examiner_name.clone()))
+                .or_else(|| ufed.and_then(|u| u.case_info.as_ref()?.examiner_name.clone())),
+            description: log.and_then(|l| l.unique_description.clone())
+                .or_else(|| ewf.and_then(|e| e.description.clone())),
+            acquisition_tool: log.and_then(|l| l.created_by.clone())
+                .or_else(|| ufed.and_then(|u| u.extraction_info.as_ref()?.acquisition_tool.clone())),
+            acquisition_started: as_written(started),
+            acquisition_finished: as_written(log.and_then(|l| l.acquisition_finished.as_ref())
+                .or_else(|| ufed.and_then(|u| u.extraction_info.as_ref()?.end_time.as_ref()))),
+            verification_started: as_written(log.and_then(|l| l.verification_started.as_ref())),
+            verification_finished: as_written(log.and_then(|l| l.verification_finished.as_ref())),
+            log_path: log.map(|l| l.log_path.clone()),
+        };
+
+        let mut hashes: Vec<&StoredHash> = Vec::new();
+        if let Some(ewf) = ewf {
+            hashes.extend(&ewf.stored_hashes);
+        }
+        if let Some(log) = log {
+            hashes.extend(&log.stored_hashes);
+        }
+
+        let segments = info.segments.as_ref();
+        let image_info = ImageInfo {
+            format: info.container.clone(),
+            file_names,
+            total_size: segments.map(|s| s.total_size)
+                .or_else(|| ewf.map(|e| e.total_size))
+                .or_else(|| info.raw.as_ref().map(|r| r.total_size))
+                .unwrap_or(0),
+            segments: segments.map(|s| s.count).filter(|count| *count > 1),
+            compression: ewf.map(|e| e.compression.clone()),
+            acquisition_tool: acquisition.acquisition_tool.clone(),
+            acquisition_date: started.and_then(Timestamp::to_utc),
+            missing_segments: segments.map(|s| s.missing.clone()).unwrap_or_default(),
+        };
+
+        EvidenceItem {
+            evidence_id: acquisition.evidence_number.clone().unwrap_or_else(|| primary_name.clone()),
+            description: acquisition.description.clone().unwrap_or(primary_name),
+            evidence_type: match info.container.as_str() {
+                "UFED" => EvidenceType::MobilePhone,
+                _ if info.archive.is_some() => EvidenceType::Other,
+                _ => EvidenceType::ForensicImage,
+            },
+            make: None,
+            model: ewf.and_then(|e| e.model.clone()),
+            serial_number: ewf.and_then(|e| e.serial_number.clone()),
+            capacity: ewf.map(|e| format_bytes(e.total_size)),
+            condition: None,
+            received_date: None,
+            submitted_by: None,
+            // Superseded by `verification` for items built from a container
+            acquisition_hashes: Vec::new(),
+            image_info: Some(image_info),
+            notes: log.and_then(|l| l.notes.clone()).or_else(|| ewf.and_then(|e| e.notes.clone())),
+            acquisition: Some(acquisition),
+            verification: verification_records(&hashes),
+            file_hashes: ufed.and_then(|u| u.stored_hashes.as_ref())
+                .into_iter()
+                .flatten()
+                .map(|stored| FileHashRecord {
+                    file_name: stored.filename.clone(),
+                    algorithm: stored.algorithm.to_uppercase(),
+                    hash: stored.hash.clone(),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// A hash the container stores for one file inside it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FileHashRecord {
+    pub file_name: String,
+    pub algorithm: String,
+    pub hash: String,
+}
+
+/// Segment (or single image) file names of a container, in order
+fn container_file_names(info: &ContainerInfo) -> Vec<String> {
+    if let Some(segments) = info.segments.as_ref().filter(|s| !s.files.is_empty()) {
+        return segments.files.iter().map(|f| f.name.clone()).collect();
+    }
+    let names = info.ad1.as_ref().and_then(|a| a.segment_files.clone())
+        .or_else(|| info.e01.as_ref().or(info.l01.as_ref()).and_then(|e| e.segment_files.clone()))
+        .or_else(|| info.raw.as_ref().map(|r| r.segment_names.clone()))
+        .unwrap_or_default();
+    names.iter()
+        .map(|name| Path::new(name).file_name().map_or_else(|| name.clone(), |n| n.to_string_lossy().to_string()))
+        .collect()
+}
+
+/// Pair each stored hash with the computed hash of the same algorithm
+fn verification_records(hashes: &[&StoredHash]) -> Vec<VerificationRecord> {
+    let is_computed = |hash: &&StoredHash| hash.source.as_deref() == Some("computed");
+    let algorithm_key = |algorithm: &str| algorithm.to_lowercase().replace(['-', '_'], "");
+
+    let mut seen: Vec<(String, Option<HashValue>)> = Vec::new();
+    let mut records = Vec::new();
+    for stored in hashes.iter().filter(|h| !is_computed(h)) {
+        let key = (algorithm_key(&stored.algorithm), stored.hash.parse::<HashValue>().ok());
+        if seen.contains(&key) {
+            continue;
+        }
+        let computed = hashes.iter().copied().filter(is_computed).find(|c| algorithm_key(&c.algorithm) == key.0);
+        seen.push(key);
+
+        let result = match (computed, stored.verified) {
+            (Some(computed), _) if hashes_match(&computed.hash, &stored.hash) => VerificationResult::Verified,
+            (Some(_), _) | (None, Some(false)) => VerificationResult::Mismatch,
+            (None, Some(true)) => VerificationResult::Verified,
+            (None, None) => VerificationResult::Unverified,
+        };
+        records.push(VerificationRecord {
+            algorithm: stored.algorithm.to_uppercase(),
+            stored: Some(stored.hash.clone()),
+            computed: computed.map(|c| c.hash.clone()),
+            result,
+            source: stored.source.clone(),
+            timestamp: computed.and_then(|c| c.timestamp.clone()).or_else(|| stored.timestamp.clone()),
+        });
+    }
+
+    // Computed hashes with nothing stored to compare against
+    for computed in hashes.iter().copied().filter(is_computed) {
+        let key = algorithm_key(&computed.algorithm);
+        if !seen.iter().any(|(algorithm, _)| *algorithm == key) {
+            seen.push((key, computed.hash.parse::<HashValue>().ok()));
+            records.push(VerificationRecord {
+                algorithm: computed.algorithm.to_uppercase(),
+                stored: None,
+                computed: Some(computed.hash.clone()),
+                result: VerificationResult::Computed,
+                source: computed.source.clone(),
+                timestamp: computed.timestamp.clone(),
+            });
+        }
+    }
+    records
+}
+
+/// Acquisition and chain-of-custody details of an evidence item
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AcquisitionInfo {
+    /// Case number recorded at acquisition
+    pub case_number: Option<String>,
+    /// Evidence number recorded at acquisition
+    pub evidence_number: Option<String>,
+    /// Examiner who acquired the image
+    pub examiner: Option<String>,
+    /// Description entered at acquisition
+    pub description: Option<String>,
+    /// Imaging tool and version
+    pub acquisition_tool: Option<String>,
+    /// Acquisition start (as written by the imaging tool)
+    pub acquisition_started: Option<String>,
+    /// Acquisition end
+    pub acquisition_finished: Option<String>,
+    /// Verification start
+    pub verification_started: Option<String>,
+    /// Verification end
+    pub verification_finished: Option<String>,
+    /// Companion log the details were read from
+    pub log_path: Option<String>,
+}
+
+/// Labelled acquisition fields that are set, in report order
+pub(crate) fn acquisition_rows(acq: &AcquisitionInfo) -> Vec<(&'static str, &str)> {
+    [
+        ("Case Number", &acq.case_number),
+        ("Evidence Number", &acq.evidence_number),
+        ("Examiner", &acq.examiner),
+        ("Acquisition Tool", &acq.acquisition_tool),
+        ("Acquisition Started", &acq.acquisition_started),
+        ("Acquisition Finished", &acq.acquisition_finished),
+        ("Verification Started", &acq.verification_started),
+        ("Verification Finished", &acq.verification_finished),
+        ("Acquisition Log", &acq.log_path),
+    ]
+    .into_iter()
+    .filter_map(|(label, value)| Some((label, value.as_deref()?)))
+    .collect()
+}
+
+/// One row of an evidence item's verification table
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VerificationRecord {
+    /// Hash algorithm, e.g. "MD5"
+    pub algorithm: String,
+    /// Hash recorded at acquisition
+    pub stored: Option<String>,
+    /// Hash computed during this examination
+    pub computed: Option<String>,
+    /// Outcome of the comparison
+    pub result: VerificationResult,
+    /// Where the stored hash came from ("container", "companion")
+    pub source: Option<String>,
+    /// When the hash was computed or recorded
+    pub timestamp: Option<String>,
+}
+
+/// Outcome of comparing a stored hash with a computed one
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum VerificationResult {
+    /// Computed hash matches (or the imaging tool verified it)
+    Verified,
+    /// Computed hash differs (or the imaging tool reported a mismatch)
+    Mismatch,
+    /// Stored hash not verified yet
+    Unverified,
+    /// Computed hash with no stored hash to compare against
+    Computed,
+}
+
+impl VerificationResult {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            VerificationResult::Verified => "Verified",
+            VerificationResult::Mismatch => "MISMATCH",
+            VerificationResult::Unverified => "Not verified",
+            VerificationResult::Computed => "Computed only",
+        }
+    }
 }
 
 /// Types of evidence
@@ -431,6 +703,20 @@ pub struct ImageInfo {
     pub acquisition_tool: Option<String>,
     /// Acquisition date
     pub acquisition_date: Option<DateTime<Utc>>,
+    /// Segment files expected but not found
+    #[serde(default)]
+    pub missing_segments: Vec<String>,
+}
+
+impl ImageInfo {
+    /// Present and missing segment file names in name order, with whether each exists
+    pub fn segment_files(&self) -> Vec<(&str, bool)> {
+        let mut segments: Vec<(&str, bool)> = self.file_names.iter().map(|name| (name.as_str(), true))
+            .chain(self.missing_segments.iter().map(|name| (name.as_str(), false)))
+            .collect();
+        segments.sort_by_key(|(name, _)| name.to_lowercase());
+        segments
+    }
 }
 
 /// Chain of custody record
@@ -636,8 +922,10 @@ pub enum HashAlgorithm {
     SHA1,
     SHA256,
     SHA512,
+    SHA3_256,
     Blake2b,
     Blake3,
+    XXH128,
 }
 
 impl HashAlgorithm {
@@ -647,8 +935,10 @@ impl HashAlgorithm {
             HashAlgorithm::SHA1 => "SHA-1",
             HashAlgorithm::SHA256 => "SHA-256",
             HashAlgorithm::SHA512 => "SHA-512",
+            HashAlgorithm::SHA3_256 => "SHA3-256",
             HashAlgorithm::Blake2b => "BLAKE2b",
             HashAlgorithm::Blake3 => "BLAKE3",
+            HashAlgorithm::XXH128 => "XXH128",
         }
     }
 }
@@ -717,6 +1007,9 @@ mod tests {
                 acquisition_hashes: vec![],
                 image_info: None,
                 notes: None,
+                acquisition: None,
+                verification: vec![],
+                file_hashes: vec![],
             })
             .executive_summary("This is a test report.")
             .build()
@@ -3,25 +3,40 @@
 //! Generates high-quality typeset reports using Typst markup.
 //! Typst is a modern alternative to LaTeX with cleaner syntax and faster compilation.
 //!
-//! This module generates `.typ` source files. To compile to PDF:
+//! The `.typ` source is rendered from `templates/report.typ` with Tera; the
+//! `typst` and `typst_str` filters escape values for markup and string
+//! literals. To compile the source to PDF:
 //! - Install Typst CLI: `cargo install typst-cli`
 //! - Run: `typst compile report.typ report.pdf`
 //!
+//! With the `typst-compiler` feature the embedded compiler renders PDF
+//! directly, using the fonts bundled with typst-assets so the output does not
+//! depend on the fonts installed on the workstation.
+//!
 //! This module is feature-gated under `typst-reports`.
 
 #![cfg(feature = "typst-reports")]
 
+use std::collections::{HashMap, HashSet};
 use std::path::Path;
 
-use super::error::{ReportError, ReportResult};
+use serde::Serialize;
+use tera::{Context, Tera};
+
+use super::error::ReportResult;
+use crate::common::atomic_file::write_atomic;
+use super::template::format_bytes;
 use super::types::*;
 
+/// Default Typst report template
+const DEFAULT_TYPST_TEMPLATE: &str = include_str!("templates/report.typ");
+
 /// Typst generator for forensic reports
 /// 
 /// Generates Typst markup that can be compiled to professional PDF documents
 /// using the Typst CLI or integrated compiler.
 pub struct TypstGenerator {
-    /// Custom template path (optional)
+    /// Custom template source (optional)
     custom_template: Option<String>,
 }
 
@@ -34,6 +49,9 @@ impl TypstGenerator {
     }
 
     /// Create a Typst generator with a custom template
+    ///
+    /// The template is Tera syntax and receives the same context as the
+    /// built-in `report.typ`.
     pub fn with_template(template: impl Into<String>) -> Self {
         Self {
             custom_template: Some(template.into()),
@@ -46,500 +64,421 @@ impl TypstGenerator {
     /// `typst compile output.typ output.pdf`
     pub fn generate(&self, report: &ForensicReport, output_path: impl AsRef<Path>) -> ReportResult<()> {
         let typst_source = self.render_typst(report)?;
-        std::fs::write(output_path, typst_source)?;
+        write_atomic(output_path.as_ref(), typst_source)?;
         Ok(())
     }
 
-    /// Render the report to Typst source code
-    fn render_typst(&self, report: &ForensicReport) -> ReportResult<String> {
-        if let Some(ref template) = self.custom_template {
-            // Use custom template
-            self.render_with_template(report, template)
-        } else {
-            // Use built-in template
-            Ok(self.render_default(report))
-        }
+    /// Render the report and compile it straight to a PDF file
+    #[cfg(feature = "typst-compiler")]
+    pub fn generate_pdf(&self, report: &ForensicReport, output_path: impl AsRef<Path>) -> ReportResult<()> {
+        let pdf = self.compile_pdf(report)?;
+        write_atomic(output_path.as_ref(), pdf)?;
+        Ok(())
     }
 
-    /// Render with custom template (template placeholders)
-    fn render_with_template(&self, _report: &ForensicReport, _template: &str) -> ReportResult<String> {
-        // TODO: Implement template variable substitution
-        Err(ReportError::Typst("Custom templates not yet implemented".to_string()))
+    /// Render the report and compile it to PDF bytes with the embedded compiler
+    #[cfg(feature = "typst-compiler")]
+    pub fn compile_pdf(&self, report: &ForensicReport) -> ReportResult<Vec<u8>> {
+        let source = self.render_typst(report)?;
+        compiler::compile_pdf(source, report.metadata.generated_at.date_naive())
+            .map_err(super::error::ReportError::Typst)
     }
 
-    /// Render using the built-in default template
-    fn render_default(&self, report: &ForensicReport) -> String {
-        let mut output = String::new();
-
-        // Document setup and styling
-        output.push_str(&self.render_preamble(report));
-        
-        // Title page
-        output.push_str(&self.render_title_page(report));
-        
-        // Table of contents
-        output.push_str("\n#outline(title: \"Table of Contents\", depth: 2)\n");
-        output.push_str("#pagebreak()\n\n");
-        
-        // Case information
-        output.push_str(&self.render_case_info(report));
-        
-        // Executive summary
-        if let Some(ref summary) = report.executive_summary {
-            output.push_str(&self.render_section("Executive Summary", summary));
-        }
-        
-        // Scope
-        if let Some(ref scope) = report.scope {
-            output.push_str(&self.render_section("Scope of Examination", scope));
-        }
-        
-        // Methodology
-        if let Some(ref methodology) = report.methodology {
-            output.push_str(&self.render_section("Methodology", methodology));
-        }
-        
-        // Evidence
-        if !report.evidence_items.is_empty() {
-            output.push_str(&self.render_evidence_section(report));
-        }
-        
-        // Findings
-        if !report.findings.is_empty() {
-            output.push_str(&self.render_findings_section(report));
-        }
-        
-        // Timeline
-        if !report.timeline.is_empty() {
-            output.push_str(&self.render_timeline_section(report));
-        }
-        
-        // Tools
-        if !report.tools.is_empty() {
-            output.push_str(&self.render_tools_section(report));
-        }
-        
-        // Conclusions
-        if let Some(ref conclusions) = report.conclusions {
-            output.push_str(&self.render_section("Conclusions", conclusions));
-        }
+    /// Render the report to Typst source code
+    pub fn render_typst(&self, report: &ForensicReport) -> ReportResult<String> {
+        let template = self.custom_template.as_deref().unwrap_or(DEFAULT_TYPST_TEMPLATE);
         
-        // Footer
-        output.push_str(&self.render_footer(report));
+        let mut tera = Tera::default();
+        tera.add_raw_template("report.typ", template)?;
+        Self::register_filters(&mut tera);
         
-        output
+        Ok(tera.render("report.typ", &Self::build_context(report))?)
     }
 
-    /// Render document preamble with styling
-    fn render_preamble(&self, report: &ForensicReport) -> String {
-        let classification = report.metadata.classification.as_str();
+    /// Register the escaping filters used by the templates
+    fn register_filters(tera: &mut Tera) {
+        // Markup content; missing values render as "-"
+        tera.register_filter("typst", |value: &tera::Value, _: &HashMap<String, tera::Value>| {
+            Ok(tera::Value::String(match value {
+                tera::Value::String(s) => Self::escape_typst(s),
+                tera::Value::Null => "-".to_string(),
+                other => Self::escape_typst(&other.to_string()),
+            }))
+        });
+
+        // Contents of a "..." string literal
+        tera.register_filter("typst_str", |value: &tera::Value, _: &HashMap<String, tera::Value>| {
+            let s = value.as_str().map(str::to_string).unwrap_or_else(|| value.to_string());
+            Ok(tera::Value::String(Self::escape_typst_str(&s)))
+        });
+    }
+
+    /// Build the template context
+    ///
+    /// Enums are passed as their display strings and the evidence, findings
+    /// and segment rows are flattened so the template only does layout.
+    fn build_context(report: &ForensicReport) -> Context {
+        let mut context = Context::new();
+        
         let classification_color = match report.metadata.classification {
             Classification::Public => "green",
-            Classification::Internal => "blue", 
+            Classification::Internal => "blue",
             Classification::Confidential => "orange",
             Classification::Restricted => "red",
             Classification::LawEnforcementSensitive => "purple",
         };
+        context.insert("metadata", &report.metadata);
+        context.insert("classification", report.metadata.classification.as_str());
+        context.insert("classification_color", classification_color);
+        context.insert("generated_date", &report.metadata.generated_at.format("%Y-%m-%d").to_string());
+        context.insert("generated_at", &report.metadata.generated_at.format("%Y-%m-%d %H:%M:%S").to_string());
+        context.insert("case", &report.case_info);
+        context.insert("examiner", &report.examiner);
+        
+        let sections: Vec<TextSection> = [
+            ("Executive Summary", &report.executive_summary),
+            ("Scope of Examination", &report.scope),
+            ("Methodology", &report.methodology),
+        ]
+        .into_iter()
+        .filter_map(|(title, body)| Some(TextSection { title, body: body.as_deref()? }))
+        .collect();
+        context.insert("sections", &sections);
+        
+        let evidence: Vec<EvidenceView> = report.evidence_items.iter().map(EvidenceView::new).collect();
+        context.insert("evidence", &evidence);
+        
+        let findings: Vec<FindingView> = report.findings.iter()
+            .map(|finding| FindingView::new(finding, &report.evidence_items))
+            .collect();
+        context.insert("findings", &findings);
+        
+        let hash_records: Vec<HashRecordView> = report.hash_records.iter().map(HashRecordView::new).collect();
+        context.insert("hash_records", &hash_records);
+        
+        context.insert("timeline", &report.timeline);
+        context.insert("tools", &report.tools);
+        context.insert("conclusions", &report.conclusions);
+        context.insert("appendices", &report.appendices);
+        context.insert("segments", &segment_rows(report));
+        
+        context
+    }
 
-        format!(r##"// FFX Forensic Report - Generated by FFX Forensic File Xplorer
-// Classification: {classification}
-// 
-// To compile this file to PDF:
-//   typst compile report.typ report.pdf
-//
-// Or use Typst's watch mode for live preview:
-//   typst watch report.typ
-
-#set document(
-  title: "{title}",
-  author: "{examiner}",
-)
-
-#set page(
-  paper: "us-letter",
-  margin: (x: 1in, y: 1in),
-  header: align(center)[
-    #set text(size: 9pt, weight: "bold", fill: {classification_color})
-    {classification}
-  ],
-  footer: context [
-    #set text(size: 9pt)
-    #align(center)[
-      #counter(page).display("1 of 1", both: true)
-    ]
-  ],
-)
-
-#set text(
-  font: "New Computer Modern",
-  size: 11pt,
-)
-
-#set heading(numbering: "1.1")
-
-#set par(
-  justify: true,
-  leading: 0.65em,
-)
-
-// Custom styling functions
-#let info-box(title, content) = {{
-  block(
-    fill: luma(245),
-    inset: 10pt,
-    radius: 4pt,
-    width: 100%,
-  )[
-    *#title*
-    #v(0.5em)
-    #content
-  ]
-}}
-
-#let severity-badge(level) = {{
-  let (color, label) = if level == "Critical" {{
-    (rgb("#dc2626"), "CRITICAL")
-  }} else if level == "High" {{
-    (rgb("#ea580c"), "HIGH")  
-  }} else if level == "Medium" {{
-    (rgb("#ca8a04"), "MEDIUM")
-  }} else if level == "Low" {{
-    (rgb("#16a34a"), "LOW")
-  }} else {{
-    (rgb("#6b7280"), "INFO")
-  }}
-  box(
-    fill: color,
-    inset: (x: 6pt, y: 3pt),
-    radius: 3pt,
-  )[#text(fill: white, weight: "bold", size: 8pt)[#label]]
-}}
-
-"##,
-            title = Self::escape_typst(&report.metadata.title),
-            examiner = Self::escape_typst(&report.examiner.name),
-            classification = classification,
-            classification_color = classification_color,
-        )
+    /// Escape special Typst characters in markup
+    ///
+    /// Characters that only have meaning at the start of a line (headings,
+    /// lists) are escaped there only.
+    fn escape_typst(s: &str) -> String {
+        let mut escaped = String::with_capacity(s.len());
+        let mut line_start = true;
+        for c in s.chars() {
+            let special = matches!(c, '\\' | '#' | '*' | '_' | '@' | '$' | '<' | '>' | '[' | ']' | '`' | '~' | '/')
+                || (line_start && matches!(c, '=' | '-' | '+'));
+            if special {
+                escaped.push('\\');
+            }
+            escaped.push(c);
+            line_start = c == '\n' || (line_start && c.is_whitespace());
+        }
+        escaped
     }
 
-    /// Render title page
-    fn render_title_page(&self, report: &ForensicReport) -> String {
-        let classification = report.metadata.classification.as_str();
-        
-        format!(r##"
-// Title Page
-#align(center)[
-  #v(2in)
-  
-  #text(size: 24pt, weight: "bold")[
-    {title}
-  ]
-  
-  #v(0.5em)
-  
-  #text(size: 14pt)[
-    Report \#{report_number} | Version {version}
-  ]
-  
-  #v(2em)
-  
-  #text(size: 12pt)[
-    Case Number: *{case_number}*
-  ]
-  
-  #v(0.5em)
-  
-  #text(size: 12pt)[
-    {case_name}
-  ]
-  
-  #v(3em)
-  
-  #text(size: 11pt)[
-    Prepared by: \
-    *{examiner_name}* \
-    {examiner_title} \
-    {examiner_org}
-  ]
-  
-  #v(2em)
-  
-  #text(size: 10pt)[
-    Generated: {generated_date}
-  ]
-  
-  #v(fill)
-  
-  #text(size: 12pt, weight: "bold")[
-    {classification}
-  ]
-]
-
-#pagebreak()
-
-"##,
-            title = Self::escape_typst(&report.metadata.title),
-            report_number = Self::escape_typst(&report.metadata.report_number),
-            version = Self::escape_typst(&report.metadata.version),
-            case_number = Self::escape_typst(&report.case_info.case_number),
-            case_name = report.case_info.case_name.as_deref().map(Self::escape_typst).unwrap_or_default(),
-            examiner_name = Self::escape_typst(&report.examiner.name),
-            examiner_title = report.examiner.title.as_deref().map(Self::escape_typst).unwrap_or_default(),
-            examiner_org = report.examiner.organization.as_deref().map(Self::escape_typst).unwrap_or_default(),
-            generated_date = report.metadata.generated_at.format("%Y-%m-%d"),
-            classification = classification,
-        )
+    /// Escape a value for use inside a Typst string literal
+    fn escape_typst_str(s: &str) -> String {
+        s.replace('\\', "\\\\")
+         .replace('"', "\\\"")
+         .replace('\n', "\\n")
+         .replace('\r', "")
     }
+}
 
-    /// Render case information section
-    fn render_case_info(&self, report: &ForensicReport) -> String {
-        let mut s = String::from("= Case Information\n\n");
-        
-        s.push_str("#table(\n");
-        s.push_str("  columns: (auto, 1fr),\n");
-        s.push_str("  stroke: none,\n");
-        s.push_str("  row-gutter: 0.5em,\n");
-        
-        s.push_str(&format!("  [*Case Number*], [{}],\n", Self::escape_typst(&report.case_info.case_number)));
-        
-        if let Some(ref name) = report.case_info.case_name {
-            s.push_str(&format!("  [*Case Name*], [{}],\n", Self::escape_typst(name)));
-        }
-        if let Some(ref agency) = report.case_info.agency {
-            s.push_str(&format!("  [*Agency*], [{}],\n", Self::escape_typst(agency)));
-        }
-        if let Some(ref requestor) = report.case_info.requestor {
-            s.push_str(&format!("  [*Requestor*], [{}],\n", Self::escape_typst(requestor)));
-        }
-        if let Some(ref inv_type) = report.case_info.investigation_type {
-            s.push_str(&format!("  [*Investigation Type*], [{}],\n", Self::escape_typst(inv_type)));
+impl Default for TypstGenerator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Free-text section (executive summary, scope, methodology)
+#[derive(Serialize)]
+struct TextSection<'a> {
+    title: &'static str,
+    body: &'a str,
+}
+
+#[derive(Serialize)]
+struct LabelledValue<'a> {
+    label: &'static str,
+    value: &'a str,
+}
+
+/// Evidence table row plus the item's acquisition details and hashes
+#[derive(Serialize)]
+struct EvidenceView<'a> {
+    id: &'a str,
+    description: &'a str,
+    evidence_type: &'static str,
+    serial: Option<&'a str>,
+    capacity: Option<&'a str>,
+    acquisition: Vec<LabelledValue<'a>>,
+    image: Option<String>,
+    missing_segments: &'a [String],
+    hashes: Vec<HashComparison<'a>>,
+}
+
+impl<'a> EvidenceView<'a> {
+    fn new(item: &'a EvidenceItem) -> Self {
+        let acquisition = item.acquisition.as_ref()
+            .map(|acq| acquisition_rows(acq).into_iter().map(|(label, value)| LabelledValue { label, value }).collect())
+            .unwrap_or_default();
+        
+        // Verification records compare stored and computed hashes; acquisition
+        // hashes entered by hand are shown as stored values
+        let mut hashes: Vec<HashComparison> = item.verification.iter().map(HashComparison::from_record).collect();
+        if hashes.is_empty() {
+            hashes = item.acquisition_hashes.iter().map(HashComparison::from_hash).collect();
         }
         
-        s.push_str(")\n\n");
-        
-        // Examiner info
-        s.push_str("== Examiner\n\n");
-        s.push_str("#table(\n");
-        s.push_str("  columns: (auto, 1fr),\n");
-        s.push_str("  stroke: none,\n");
-        s.push_str("  row-gutter: 0.5em,\n");
-        s.push_str(&format!("  [*Name*], [{}],\n", Self::escape_typst(&report.examiner.name)));
-        
-        if let Some(ref title) = report.examiner.title {
-            s.push_str(&format!("  [*Title*], [{}],\n", Self::escape_typst(title)));
-        }
-        if let Some(ref org) = report.examiner.organization {
-            s.push_str(&format!("  [*Organization*], [{}],\n", Self::escape_typst(org)));
+        Self {
+            id: &item.evidence_id,
+            description: &item.description,
+            evidence_type: item.evidence_type.as_str(),
+            serial: item.serial_number.as_deref(),
+            capacity: item.capacity.as_deref(),
+            acquisition,
+            image: item.image_info.as_ref().map(|image| {
+                format!("{}, {} file(s), {}", image.format, image.file_names.len(), format_bytes(image.total_size))
+            }),
+            missing_segments: item.image_info.as_ref().map(|image| image.missing_segments.as_slice()).unwrap_or_default(),
+            hashes,
         }
-        if let Some(ref email) = report.examiner.email {
-            s.push_str(&format!("  [*Email*], [{}],\n", Self::escape_typst(email)));
+    }
+}
+
+#[derive(Serialize)]
+struct HashComparison<'a> {
+    algorithm: &'a str,
+    stored: Option<&'a str>,
+    computed: Option<&'a str>,
+    result: &'static str,
+    mismatch: bool,
+}
+
+impl<'a> HashComparison<'a> {
+    fn from_record(record: &'a VerificationRecord) -> Self {
+        Self {
+            algorithm: &record.algorithm,
+            stored: record.stored.as_deref(),
+            computed: record.computed.as_deref(),
+            result: record.result.as_str(),
+            mismatch: record.result == VerificationResult::Mismatch,
         }
-        if let Some(ref phone) = report.examiner.phone {
-            s.push_str(&format!("  [*Phone*], [{}],\n", Self::escape_typst(phone)));
+    }
+
+    fn from_hash(hash: &'a HashRecord) -> Self {
+        Self {
+            algorithm: hash.algorithm.as_str(),
+            stored: Some(&hash.value),
+            computed: None,
+            result: if hash.verified == Some(true) { VerificationResult::Verified.as_str() } else { VerificationResult::Unverified.as_str() },
+            mismatch: hash.verified == Some(false),
         }
+    }
+}
+
+/// Verification result of one evidence item cited by a finding
+#[derive(Serialize)]
+struct IntegrityCheck<'a> {
+    evidence_id: &'a str,
+    algorithm: &'a str,
+    result: &'static str,
+    mismatch: bool,
+}
+
+#[derive(Serialize)]
+struct FindingView<'a> {
+    id: &'a str,
+    title: &'a str,
+    severity: &'static str,
+    category: &'static str,
+    description: &'a str,
+    related_files: &'a [String],
+    integrity: Vec<IntegrityCheck<'a>>,
+    notes: Option<&'a str>,
+}
+
+impl<'a> FindingView<'a> {
+    fn new(finding: &'a Finding, evidence_items: &'a [EvidenceItem]) -> Self {
+        let integrity = evidence_items.iter()
+            .filter(|item| finding.supporting_evidence.contains(&item.evidence_id))
+            .flat_map(|item| item.verification.iter().map(move |record| IntegrityCheck {
+                evidence_id: &item.evidence_id,
+                algorithm: &record.algorithm,
+                result: record.result.as_str(),
+                mismatch: record.result == VerificationResult::Mismatch,
+            }))
+            .collect();
         
-        s.push_str(")\n\n");
-        
-        if !report.examiner.certifications.is_empty() {
-            s.push_str("*Certifications:* ");
-            s.push_str(&report.examiner.certifications.iter()
-                .map(|c| Self::escape_typst(c))
-                .collect::<Vec<_>>()
-                .join(", "));
-            s.push_str("\n\n");
+        Self {
+            id: &finding.finding_id,
+            title: &finding.title,
+            severity: finding.severity.as_str(),
+            category: finding.category.as_str(),
+            description: &finding.description,
+            related_files: &finding.related_files,
+            integrity,
+            notes: finding.notes.as_deref(),
         }
-        
-        s.push_str("#pagebreak()\n\n");
-        s
     }
+}
 
-    /// Render a simple text section
-    fn render_section(&self, title: &str, content: &str) -> String {
-        format!(
-            "= {}\n\n{}\n\n#pagebreak()\n\n",
-            title,
-            Self::escape_typst(content)
-        )
-    }
+#[derive(Serialize)]
+struct HashRecordView<'a> {
+    item: &'a str,
+    algorithm: &'static str,
+    value: &'a str,
+    verified: &'static str,
+}
 
-    /// Render evidence section
-    fn render_evidence_section(&self, report: &ForensicReport) -> String {
-        let mut s = String::from("= Evidence Examined\n\n");
-        
-        s.push_str("#table(\n");
-        s.push_str("  columns: (auto, 2fr, auto, auto, auto),\n");
-        s.push_str("  align: (left, left, left, left, left),\n");
-        s.push_str("  fill: (x, y) => if y == 0 { luma(230) } else { none },\n");
-        s.push_str("  [*ID*], [*Description*], [*Type*], [*Serial*], [*Capacity*],\n");
-        
-        for item in &report.evidence_items {
-            s.push_str(&format!(
-                "  [{}], [{}], [{}], [{}], [{}],\n",
-                Self::escape_typst(&item.evidence_id),
-                Self::escape_typst(&item.description),
-                item.evidence_type.as_str(),
-                item.serial_number.as_deref().unwrap_or("-"),
-                item.capacity.as_deref().unwrap_or("-"),
-            ));
+impl<'a> HashRecordView<'a> {
+    fn new(record: &'a HashRecord) -> Self {
+        Self {
+            item: &record.item,
+            algorithm: record.algorithm.as_str(),
+            value: &record.value,
+            verified: match record.verified {
+                Some(true) => "Yes",
+                Some(false) => "No",
+                None => "-",
+            },
         }
-        
-        s.push_str(")\n\n");
-        
-        // Detailed evidence info
-        for item in &report.evidence_items {
-            s.push_str(&format!("== Evidence {} Details\n\n", Self::escape_typst(&item.evidence_id)));
-            
-            if !item.acquisition_hashes.is_empty() {
-                s.push_str("*Hash Values:*\n\n");
-                s.push_str("#table(\n");
-                s.push_str("  columns: (auto, 1fr),\n");
-                s.push_str("  [*Algorithm*], [*Value*],\n");
-                for hash in &item.acquisition_hashes {
-                    s.push_str(&format!(
-                        "  [{}], [```{}```],\n",
-                        hash.algorithm.as_str(),
-                        &hash.value,
-                    ));
-                }
-                s.push_str(")\n\n");
-            }
-            
-            if let Some(ref notes) = item.notes {
-                s.push_str(&format!("*Notes:* {}\n\n", Self::escape_typst(notes)));
+    }
+}
+
+#[derive(Serialize)]
+struct SegmentHash<'a> {
+    algorithm: &'static str,
+    value: &'a str,
+}
+
+/// One row of the segment appendix
+#[derive(Serialize)]
+struct SegmentRow<'a> {
+    evidence_id: &'a str,
+    index: usize,
+    name: &'a str,
+    present: bool,
+    hashes: Vec<SegmentHash<'a>>,
+}
+
+/// Segments of every imaged evidence item, with the hash records whose item
+/// names the segment file (by name or as the last path component)
+fn segment_rows(report: &ForensicReport) -> Vec<SegmentRow<'_>> {
+    let mut rows = Vec::new();
+    let mut seen = HashSet::new();
+    for item in &report.evidence_items {
+        let Some(ref image) = item.image_info else { continue };
+        for (idx, (name, present)) in image.segment_files().into_iter().enumerate() {
+            if !seen.insert((item.evidence_id.as_str(), name)) {
+                continue;
             }
+            let hashes = report.hash_records.iter()
+                .filter(|record| {
+                    let file_name = record.item.rsplit(['/', '\\']).next().unwrap_or(&record.item);
+                    file_name.eq_ignore_ascii_case(name)
+                })
+                .map(|record| SegmentHash { algorithm: record.algorithm.as_str(), value: &record.value })
+                .collect();
+            rows.push(SegmentRow { evidence_id: &item.evidence_id, index: idx + 1, name, present, hashes });
         }
-        
-        s.push_str("#pagebreak()\n\n");
-        s
     }
+    rows
+}
 
-    /// Render findings section
-    fn render_findings_section(&self, report: &ForensicReport) -> String {
-        let mut s = String::from("= Findings\n\n");
-        
-        for finding in &report.findings {
-            s.push_str(&format!(
-                "== {} - {}\n\n",
-                Self::escape_typst(&finding.finding_id),
-                Self::escape_typst(&finding.title)
-            ));
-            
-            s.push_str(&format!(
-                "#severity-badge(\"{}\") #h(1em) *Category:* {}\n\n",
-                finding.severity.as_str(),
-                finding.category.as_str()
-            ));
-            
-            s.push_str(&format!("{}\n\n", Self::escape_typst(&finding.description)));
-            
-            if !finding.related_files.is_empty() {
-                s.push_str("*Related Files:*\n");
-                for file in &finding.related_files {
-                    s.push_str(&format!("- `{}`\n", Self::escape_typst(file)));
-                }
-                s.push_str("\n");
-            }
-            
-            if !finding.supporting_evidence.is_empty() {
-                s.push_str(&format!(
-                    "*Supporting Evidence:* {}\n\n",
-                    finding.supporting_evidence.join(", ")
-                ));
+/// Embedded Typst compiler with the typst-assets fonts
+#[cfg(feature = "typst-compiler")]
+mod compiler {
+    use chrono::{Datelike, NaiveDate};
+    use typst::diag::{FileError, FileResult, SourceDiagnostic};
+    use typst::foundations::{Bytes, Datetime};
+    use typst::syntax::{FileId, Source, VirtualPath};
+    use typst::text::{Font, FontBook};
+    use typst::utils::LazyHash;
+    use typst::{Library, World};
+
+    /// A world holding only the report source; there are no other files,
+    /// packages or system fonts to resolve
+    struct ReportWorld {
+        library: LazyHash<Library>,
+        book: LazyHash<FontBook>,
+        fonts: Vec<Font>,
+        source: Source,
+        today: Option<Datetime>,
+    }
+
+    impl ReportWorld {
+        fn new(text: String, today: NaiveDate) -> Self {
+            let fonts: Vec<Font> = typst_assets::fonts()
+                .flat_map(|data| Font::iter(Bytes::from_static(data)))
+                .collect();
+            Self {
+                library: LazyHash::new(Library::builder().build()),
+                book: LazyHash::new(FontBook::from_fonts(&fonts)),
+                fonts,
+                source: Source::new(FileId::new(None, VirtualPath::new("/report.typ")), text),
+                today: Datetime::from_ymd(today.year(), today.month() as u8, today.day() as u8),
             }
         }
-        
-        s.push_str("#pagebreak()\n\n");
-        s
-    }
 
-    /// Render timeline section
-    fn render_timeline_section(&self, report: &ForensicReport) -> String {
-        let mut s = String::from("= Timeline of Events\n\n");
-        
-        s.push_str("#table(\n");
-        s.push_str("  columns: (auto, auto, 2fr, auto),\n");
-        s.push_str("  fill: (x, y) => if y == 0 { luma(230) } else { none },\n");
-        s.push_str("  [*Timestamp*], [*Type*], [*Description*], [*Source*],\n");
-        
-        for event in &report.timeline {
-            s.push_str(&format!(
-                "  [{}], [{}], [{}], [{}],\n",
-                event.timestamp.format("%Y-%m-%d %H:%M:%S"),
-                Self::escape_typst(&event.timestamp_type),
-                Self::escape_typst(&event.description),
-                Self::escape_typst(&event.source),
-            ));
+        fn not_found(id: FileId) -> FileError {
+            FileError::NotFound(id.vpath().as_rootless_path().to_path_buf())
         }
-        
-        s.push_str(")\n\n");
-        s.push_str("#pagebreak()\n\n");
-        s
     }
 
-    /// Render tools section
-    fn render_tools_section(&self, report: &ForensicReport) -> String {
-        let mut s = String::from("= Tools Used\n\n");
-        
-        s.push_str("#table(\n");
-        s.push_str("  columns: (1fr, auto, 1fr, 2fr),\n");
-        s.push_str("  fill: (x, y) => if y == 0 { luma(230) } else { none },\n");
-        s.push_str("  [*Tool*], [*Version*], [*Vendor*], [*Purpose*],\n");
-        
-        for tool in &report.tools {
-            s.push_str(&format!(
-                "  [{}], [{}], [{}], [{}],\n",
-                Self::escape_typst(&tool.name),
-                Self::escape_typst(&tool.version),
-                tool.vendor.as_deref().map(Self::escape_typst).unwrap_or_else(|| "-".to_string()),
-                tool.purpose.as_deref().map(Self::escape_typst).unwrap_or_else(|| "-".to_string()),
-            ));
+    impl World for ReportWorld {
+        fn library(&self) -> &LazyHash<Library> {
+            &self.library
+        }
+
+        fn book(&self) -> &LazyHash<FontBook> {
+            &self.book
+        }
+
+        fn main(&self) -> FileId {
+            self.source.id()
+        }
+
+        fn source(&self, id: FileId) -> FileResult<Source> {
+            if id == self.source.id() {
+                Ok(self.source.clone())
+            } else {
+                Err(Self::not_found(id))
+            }
+        }
+
+        fn file(&self, id: FileId) -> FileResult<Bytes> {
+            Err(Self::not_found(id))
+        }
+
+        fn font(&self, index: usize) -> Option<Font> {
+            self.fonts.get(index).cloned()
         }
-        
-        s.push_str(")\n\n");
-        s.push_str("#pagebreak()\n\n");
-        s
-    }
 
-    /// Render footer
-    fn render_footer(&self, report: &ForensicReport) -> String {
-        format!(r##"
-// End of Report
-#v(2em)
-#align(center)[
-  #line(length: 50%)
-  #v(1em)
-  #text(size: 9pt)[
-    This report was generated by *{}* on {} UTC.
-  ]
-  #v(0.5em)
-  #text(size: 10pt, weight: "bold")[
-    {}
-  ]
-]
-"##,
-            Self::escape_typst(&report.metadata.generated_by),
-            report.metadata.generated_at.format("%Y-%m-%d %H:%M:%S"),
-            report.metadata.classification.as_str(),
-        )
+        /// Report date rather than the clock, so output is reproducible
+        fn today(&self, _offset: Option<i64>) -> Option<Datetime> {
+            self.today
+        }
     }
 
-    /// Escape special Typst characters
-    fn escape_typst(s: &str) -> String {
-        s.replace('\\', "\\\\")
-         .replace('#', "\\#")
-         .replace('*', "\\*")
-         .replace('_', "\\_")
-         .replace('@', "\\@")
-         .replace('$', "\\$")
-         .replace('<', "\\<")
-         .replace('>', "\\>")
+    /// Compile Typst source to PDF bytes
+    pub(super) fn compile_pdf(source: String, today: NaiveDate) -> Result<Vec<u8>, String> {
+        let world = ReportWorld::new(source, today);
+        let document = typst::compile(&world).output.map_err(|errors| describe(&errors))?;
+        typst_pdf::pdf(&document, &typst_pdf::PdfOptions::default()).map_err(|errors| describe(&errors))
     }
-}
 
-impl Default for TypstGenerator {
-    fn default() -> Self {
-        Self::new()
+    fn describe(errors: &[SourceDiagnostic]) -> String {
+        errors.iter().map(|error| error.message.to_string()).collect::<Vec<_>>().join("; ")
     }
 }
 
@@ -548,10 +487,125 @@ mod tests {
     use super::*;
     use chrono::Utc;
 
+    const MD5: &str = "9e107d9d372bb6826bd81d3542a419d6";
+    const SHA1: &str = "2fd4e1c67a2d28fced849ee1bb76e7391b93eb12";
+
     #[test]
     fn test_typst_escape() {
         assert_eq!(TypstGenerator::escape_typst("Hello #world"), "Hello \\#world");
         assert_eq!(TypstGenerator::escape_typst("Test *bold*"), "Test \\*bold\\*");
+        assert_eq!(TypstGenerator::escape_typst("C:/Users [x]"), "C:\\/Users \\[x\\]");
+        assert_eq!(TypstGenerator::escape_typst("- a-b\n= c"), "\\- a-b\n\\= c");
+        assert_eq!(TypstGenerator::escape_typst_str("say \"hi\"\\"), "say \\\"hi\\\"\\\\");
+    }
+
+    /// One imaged evidence item (third segment missing) with an MD5 that
+    /// verified and a SHA1 that did not, cited by a finding
+    fn evidence_report() -> ForensicReport {
+        let generated_at = chrono::DateTime::parse_from_rfc3339("2026-02-04T08:00:00Z").unwrap().with_timezone(&Utc);
+        ForensicReport::builder()
+            .metadata(ReportMetadata {
+                title: "Forensic Examination Report - Case 2026-017".to_string(),
+                report_number: "RPT-2026-017".to_string(),
+                version: "1.0".to_string(),
+                classification: Classification::Confidential,
+                generated_at,
+                generated_by: "FFX Forensic File Xplorer".to_string(),
+            })
+            .case_number("2026-017")
+            .examiner_name("J. Rivera")
+            .add_evidence(EvidenceItem {
+                evidence_id: "EV-03".to_string(),
+                description: "Laptop SSD [internal]".to_string(),
+                evidence_type: EvidenceType::ForensicImage,
+                make: None,
+                model: None,
+                serial_number: Some("S64ANS0T512345".to_string()),
+                capacity: None,
+                condition: None,
+                received_date: None,
+                submitted_by: None,
+                acquisition_hashes: vec![],
+                image_info: Some(ImageInfo {
+                    format: "E01".to_string(),
+                    file_names: vec!["laptop.E01".to_string(), "laptop.E02".to_string()],
+                    total_size: 1_610_612_736,
+                    segments: Some(3),
+                    compression: None,
+                    acquisition_tool: None,
+                    acquisition_date: None,
+                    missing_segments: vec!["laptop.E03".to_string()],
+                }),
+                notes: None,
+                acquisition: None,
+                verification: vec![
+                    VerificationRecord {
+                        algorithm: "MD5".to_string(),
+                        stored: Some(MD5.to_string()),
+                        computed: Some(MD5.to_string()),
+                        result: VerificationResult::Verified,
+                        source: Some("container".to_string()),
+                        timestamp: None,
+                    },
+                    VerificationRecord {
+                        algorithm: "SHA1".to_string(),
+                        stored: Some("de9f2c7fd25e1b3afad3e85a0bd17d9b100db4b3".to_string()),
+                        computed: Some(SHA1.to_string()),
+                        result: VerificationResult::Mismatch,
+                        source: Some("companion".to_string()),
+                        timestamp: None,
+                    },
+                ],
+                file_hashes: vec![],
+            })
+            .add_finding(Finding {
+                finding_id: "F-01".to_string(),
+                title: "Image integrity".to_string(),
+                severity: FindingSeverity::High,
+                category: FindingCategory::Other,
+                description: "The SHA1 recorded at acquisition does not match.".to_string(),
+                supporting_evidence: vec!["EV-03".to_string()],
+                related_files: vec![],
+                timestamps: vec![],
+                exhibits: vec![],
+                notes: None,
+            })
+            .add_hash_record(HashRecord {
+                item: "/evidence/laptop.E01".to_string(),
+                algorithm: HashAlgorithm::MD5,
+                value: "0cc175b9c0f1b6a831c399e269772661".to_string(),
+                computed_at: None,
+                verified: Some(true),
+            })
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_typst_evidence_rows() {
+        let source = TypstGenerator::new().render_typst(&evidence_report()).unwrap();
+        
+        assert!(source.contains("[EV-03], [Laptop SSD \\[internal\\]], [Forensic Image], [S64ANS0T512345], [-],"));
+        assert!(source.contains(&format!(r#"[MD5], [#hash-pair("{MD5}", "{MD5}")], [Verified],"#)));
+        assert!(source.contains(&format!(r#"[#hash-pair("de9f2c7fd25e1b3afad3e85a0bd17d9b100db4b3", "{SHA1}")], [#alert[MISMATCH]],"#)));
+        // The finding cites EV-03, so its hash results are repeated there
+        assert!(source.contains("- EV-03 SHA1: #alert[MISMATCH]"));
+        // Segment appendix in name order, with the matching hash record
+        assert!(source.contains(r#"[EV-03], [1], [#raw("laptop.E01") \ #text(size: 8pt)[MD5] #raw("0cc175b9c0f1b6a831c399e269772661")], [Present],"#));
+        assert!(source.contains(r#"[EV-03], [3], [#raw("laptop.E03")], [#alert[MISSING]],"#));
+    }
+
+    #[test]
+    fn test_typst_custom_template() {
+        let generator = TypstGenerator::with_template("{% for item in evidence %}{{ item.id }};{% endfor %}");
+        assert_eq!(generator.render_typst(&evidence_report()).unwrap(), "EV-03;");
+    }
+
+    #[cfg(feature = "typst-compiler")]
+    #[test]
+    fn test_typst_compiles_to_pdf() {
+        let pdf = TypstGenerator::new().compile_pdf(&evidence_report()).unwrap();
+        assert!(pdf.starts_with(b"%PDF-"));
     }
 
     #[test]
@@ -0,0 +1,374 @@
+//! Integrity self-test - known-answer checks of FFX's own hashing
+//!
+//! Lab SOPs require showing that the tool computes hashes correctly on the
+//! machine an examination runs on. [`self_test`] runs every supported hash
+//! algorithm over embedded known-answer vectors (NIST FIPS 180/202 and RFC
+//! 1321/7693 values where published, reference-implementation output
+//! otherwise) and decodes a tiny embedded E01 whose logical hash is known,
+//! exercising the same chunk inflation path as a real verification.
+//!
+//! The returned [`SelfTestReport`] is serializable and can be attached to a
+//! report with [`SelfTestReport::to_appendix`]. The whole run takes well
+//! under a second.
+//!
+//! ```rust,ignore
+//! let report = ffx_check_lib::selftest::self_test();
+//! if !report.passed {
+//!     eprintln!("SELF-TEST FAILED: {}", report.failed_checks.join(", "));
+//! }
+//! ```
+
+use std::fs;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use chrono::{SecondsFormat, Utc};
+use serde::Serialize;
+use tracing::{info, warn};
+
+use crate::common::hash::{hashes_match, HashAlgorithm, StreamingHasher};
+use crate::ewf;
+use crate::report::types::{Appendix, AppendixType};
+
+// =============================================================================
+// Known-Answer Vectors
+// =============================================================================
+
+/// Size of the patterned buffer (byte `i` is `i % 251`)
+const PATTERN_SIZE: usize = 1024 * 1024;
+
+/// Hash inputs, in the order their digests appear in `HASH_VECTORS`
+const INPUT_NAMES: [&str; 3] = ["empty", "\"abc\"", "1 MiB pattern (i mod 251)"];
+
+/// Expected digests per algorithm for the empty input, "abc" and the pattern
+const HASH_VECTORS: &[(HashAlgorithm, [&str; 3])] = &[
+    (HashAlgorithm::Md5, [
+        "d41d8cd98f00b204e9800998ecf8427e",
+        "900150983cd24fb0d6963f7d28e17f72",
+        "8f293a2f6c19b345152f7a49bb4c643c",
+    ]),
+    (HashAlgorithm::Sha1, [
+        "da39a3ee5e6b4b0d3255bfef95601890afd80709",
+        "a9993e364706816aba3e25717850c26c9cd0d89d",
+        "c2fc4cb20f1301a6b0dd211c19e69a13925dbe40",
+    ]),
+    (HashAlgorithm::Sha256, [
+        "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+        "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+        "631b84027d6b9e52b539c4e8373622d23032dfadc64d60af87339c9037e4f769",
+    ]),
+    (HashAlgorithm::Sha512, [
+        "cf83e1357eefb8bdf1542850d66d8007d620e4050b5715dc83f4a921d36ce9ce47d0d13c5d85f2b0ff8318d2877eec2f63b931bd47417a81a538327af927da3e",
+        "ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f",
+        "67dad569eefc986a3b2424f5516d5a0284bb53d7b52d75f5ed881a6830a95765ccc82bc48752fb693422579f11dc9a400561ec1885af9eeef703dbbd312d4fd0",
+    ]),
+    (HashAlgorithm::Sha3_256, [
+        "a7ffc6f8bf1ed76651c14756a061d662f580ff4de43b49fa82d80a4b80f8434a",
+        "3a985da74fe225b2045c172d6bd390bd855f086e3e9d525b46bfe24511431532",
+        "eec77e4d80484c04a505e6203c3822c67e13ce186fec1ea01e56961dcd7261ca",
+    ]),
+    (HashAlgorithm::Blake3, [
+        "af1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262",
+        "6437b3ac38465133ffb63b75273a8db548c558465d79db03fd359c6cd5bd9d85",
+        "74cb441fd087764ca9c3694da742ebe30cbeb3060a17009ca81825c7a8d10343",
+    ]),
+    (HashAlgorithm::Blake2, [
+        "786a02f742015903c6c6fd852552d272912f4740e15847618a86e217f71f5419d25e1031afee585313896444934eb04b903a685b1448b755d56f701afe9be2ce",
+        "ba80a53f981c4d0d6a2797b69f12f6e94c212f14685ac4b74b12bb6fdbffa2d17d87c5392aab792dc252d5de4533cc9518d38aa8dbf1925ab92386edd4009923",
+        "797c6241704933d0c62cea0793db1dd5c65ffd258f8340d394d2cd26b7bf537046ebb5914fb1fae7635ce1f379fb819abc57ad509c015bb4dba4bc981bb1c446",
+    ]),
+    (HashAlgorithm::Xxh3, [
+        "99aa06d3014798d86001c324468d497f",
+        "06b05ab6733a618578af5f94892f3950",
+        "53738d98098cabba6e0d7ac36b8c10ff",
+    ]),
+    (HashAlgorithm::Xxh128, [
+        "99aa06d3014798d86001c324468d497f",
+        "06b05ab6733a618578af5f94892f3950",
+        "53738d98098cabba6e0d7ac36b8c10ff",
+    ]),
+    (HashAlgorithm::Xxh64, [
+        "ef46db3751d8e999",
+        "44bc2cf5ad770999",
+        "89ac0399c4464a31",
+    ]),
+    (HashAlgorithm::Crc32, [
+        "00000000",
+        "352441c2",
+        "ef0e6054",
+    ]),
+];
+
+// =============================================================================
+// E01 Fixture
+// =============================================================================
+
+/// Chunk 0: 512 bytes of `FIXTURE_TEXT` repeated, zlib-compressed
+const FIXTURE_CHUNK0_ZLIB: &[u8] = &[
+    0x78, 0xda, 0xed, 0xc8, 0x41, 0x0d, 0x00, 0x20, 0x0c, 0x03, 0x40, 0x2b, 0x35, 0x00, 0x32, 0xaa,
+    0x01, 0x03, 0x5d, 0x42, 0xc2, 0x8b, 0x95, 0x04, 0xf9, 0x08, 0x61, 0xdf, 0x23, 0x07, 0x52, 0x2b,
+    0x9a, 0x95, 0x46, 0xcc, 0xeb, 0xb3, 0xd5, 0xc1, 0xe2, 0x3f, 0xf8, 0x01, 0xc4, 0xcc, 0xb3, 0x3d,
+];
+/// Chunk 1: 512 zero bytes, zlib-compressed
+const FIXTURE_CHUNK1_ZLIB: &[u8] = &[
+    0x78, 0xda, 0xed, 0xc0, 0x01, 0x01, 0x00, 0x00, 0x00, 0x40, 0x20, 0xff, 0x57, 0x1b, 0x42, 0xb0,
+    0x32, 0x01, 0x02, 0x00, 0x00, 0x01,
+];
+/// Adler-32 trailer of chunk 2 (stored uncompressed, bytes 0..=255 twice)
+const FIXTURE_CHUNK2_ADLER32: u32 = 0xe36cff01;
+/// Text the first fixture chunk is made of
+#[cfg(test)]
+const FIXTURE_TEXT: &str = "FFX self-test fixture. ";
+/// Logical hashes of the 1536-byte fixture media
+const FIXTURE_MD5: &str = "ffc9856fd1585b22bcb2a143c30cde71";
+const FIXTURE_SHA256: &str = "083ef8aaf1e0b8df56a75ec5764baea414d4ead4991cde69cec71fb12006fe7e";
+
+const FIXTURE_SECTOR_SIZE: u32 = 512;
+const FIXTURE_CHUNKS: u32 = 3;
+const SECTION_DESCRIPTOR_SIZE: usize = 76;
+const VOLUME_DATA_SIZE: usize = 1052;
+
+/// Build a single-segment E01: volume, sectors (two compressed chunks and
+/// one stored chunk, one sector each), table and done sections
+fn build_fixture_e01() -> Vec<u8> {
+    fn section(image: &mut Vec<u8>, kind: &str, data: &[u8]) {
+        let start = image.len();
+        let size = (SECTION_DESCRIPTOR_SIZE + data.len()) as u64;
+        let next = if kind == "done" { start as u64 } else { start as u64 + size };
+        let mut descriptor = [0u8; SECTION_DESCRIPTOR_SIZE];
+        descriptor[..kind.len()].copy_from_slice(kind.as_bytes());
+        descriptor[16..24].copy_from_slice(&next.to_le_bytes());
+        descriptor[24..32].copy_from_slice(&size.to_le_bytes());
+        image.extend_from_slice(&descriptor);
+        image.extend_from_slice(data);
+    }
+
+    let mut image = Vec::with_capacity(2048);
+    image.extend_from_slice(b"EVF\x09\x0d\x0a\xff\x00\x01\x01\x00\x00\x00");
+
+    let mut volume = vec![0u8; VOLUME_DATA_SIZE];
+    volume[0] = 0x01; // fixed disk
+    volume[4..8].copy_from_slice(&FIXTURE_CHUNKS.to_le_bytes());
+    volume[8..12].copy_from_slice(&1u32.to_le_bytes()); // sectors per chunk
+    volume[12..16].copy_from_slice(&FIXTURE_SECTOR_SIZE.to_le_bytes());
+    volume[16..24].copy_from_slice(&(FIXTURE_CHUNKS as u64).to_le_bytes());
+    section(&mut image, "volume", &volume);
+
+    let mut sectors = Vec::new();
+    let mut offsets = Vec::new();
+    let sectors_data_start = (image.len() + SECTION_DESCRIPTOR_SIZE) as u32;
+    for compressed in [FIXTURE_CHUNK0_ZLIB, FIXTURE_CHUNK1_ZLIB] {
+        offsets.push((sectors_data_start + sectors.len() as u32) | 0x8000_0000);
+        sectors.extend_from_slice(compressed);
+    }
+    offsets.push(sectors_data_start + sectors.len() as u32);
+    sectors.extend((0..FIXTURE_SECTOR_SIZE).map(|i| i as u8));
+    sectors.extend_from_slice(&FIXTURE_CHUNK2_ADLER32.to_le_bytes());
+    section(&mut image, "sectors", &sectors);
+
+    let mut table = vec![0u8; 24];
+    table[0..4].copy_from_slice(&FIXTURE_CHUNKS.to_le_bytes());
+    for offset in offsets {
+        table.extend_from_slice(&offset.to_le_bytes());
+    }
+    table.extend_from_slice(&[0u8; 4]);
+    section(&mut image, "table", &table);
+
+    section(&mut image, "done", &[]);
+    image
+}
+
+// =============================================================================
+// Results
+// =============================================================================
+
+/// Outcome of one known-answer check
+#[derive(Debug, Clone, Serialize)]
+pub struct SelfTestCheck {
+    /// "hash" or "e01_decompression"
+    pub category: String,
+    pub algorithm: String,
+    /// Description of the input the check ran on
+    pub input: String,
+    pub expected: String,
+    pub computed: String,
+    pub passed: bool,
+    /// Why the check could not produce a value (fixture I/O, parse failure)
+    pub error: Option<String>,
+}
+
+/// Result of a full self-test run
+#[derive(Debug, Clone, Serialize)]
+pub struct SelfTestReport {
+    /// True only if every check passed
+    pub passed: bool,
+    /// One-line verdict, e.g. "PASSED (35/35 checks)" or "FAILED (2 of 35 checks)"
+    pub summary: String,
+    /// "<algorithm> (<input>)" for each failed check
+    pub failed_checks: Vec<String>,
+    pub tool: String,
+    pub tool_version: String,
+    /// When the run started (RFC 3339, UTC)
+    pub run_at: String,
+    pub duration_ms: u64,
+    pub checks: Vec<SelfTestCheck>,
+}
+
+impl SelfTestReport {
+    /// Render the run as a report appendix (a hash table in markdown)
+    pub fn to_appendix(&self, appendix_id: &str) -> Appendix {
+        let mut content = format!(
+            "Self-test of {} {} run at {}: **{}**\n\n",
+            self.tool, self.tool_version, self.run_at, self.summary
+        );
+        content.push_str("| Check | Algorithm | Input | Expected | Computed | Result |\n");
+        content.push_str("|---|---|---|---|---|---|\n");
+        for check in &self.checks {
+            let computed = match &check.error {
+                Some(error) => error.as_str(),
+                None => check.computed.as_str(),
+            };
+            content.push_str(&format!(
+                "| {} | {} | {} | `{}` | `{}` | {} |\n",
+                check.category,
+                check.algorithm,
+                check.input,
+                check.expected,
+                computed,
+                if check.passed { "Pass" } else { "**FAIL**" },
+            ));
+        }
+        Appendix {
+            appendix_id: appendix_id.to_string(),
+            title: "Tool Integrity Self-Test".to_string(),
+            content_type: AppendixType::HashTable,
+            content,
+        }
+    }
+}
+
+// =============================================================================
+// Self-Test
+// =============================================================================
+
+/// Run every known-answer check and report pass/fail per check
+pub fn self_test() -> SelfTestReport {
+    let started = Instant::now();
+    let run_at = Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true);
+
+    let pattern: Vec<u8> = (0..PATTERN_SIZE).map(|i| (i % 251) as u8).collect();
+    let inputs: [&[u8]; 3] = [b"", b"abc", &pattern];
+
+    let mut checks = Vec::new();
+    for (algorithm, expected) in HASH_VECTORS {
+        for ((input, name), expected) in inputs.iter().zip(INPUT_NAMES).zip(expected) {
+            // Feed the pattern in uneven pieces so block boundaries are crossed mid-update
+            let mut hasher = StreamingHasher::new(*algorithm);
+            for piece in input.chunks(65_521) {
+                hasher.update(piece);
+            }
+            checks.push(check("hash", algorithm.name(), name, expected, Ok(hasher.finalize())));
+        }
+    }
+    checks.extend(check_e01_fixture());
+
+    let failed_checks: Vec<String> = checks
+        .iter()
+        .filter(|c| !c.passed)
+        .map(|c| format!("{} ({})", c.algorithm, c.input))
+        .collect();
+    let passed = failed_checks.is_empty();
+    let summary = if passed {
+        format!("PASSED ({}/{} checks)", checks.len(), checks.len())
+    } else {
+        format!("FAILED ({} of {} checks)", failed_checks.len(), checks.len())
+    };
+    if passed {
+        info!(checks = checks.len(), "Self-test passed");
+    } else {
+        warn!(failed = ?failed_checks, "Self-test FAILED");
+    }
+
+    SelfTestReport {
+        passed,
+        summary,
+        failed_checks,
+        tool: env!("CARGO_PKG_NAME").to_string(),
+        tool_version: env!("CARGO_PKG_VERSION").to_string(),
+        run_at,
+        duration_ms: started.elapsed().as_millis() as u64,
+        checks,
+    }
+}
+
+fn check(category: &str, algorithm: &str, input: &str, expected: &str, computed: Result<String, String>) -> SelfTestCheck {
+    let (computed, error) = match computed {
+        Ok(value) => (value, None),
+        Err(e) => (String::new(), Some(e)),
+    };
+    SelfTestCheck {
+        category: category.to_string(),
+        algorithm: algorithm.to_string(),
+        input: input.to_string(),
+        expected: expected.to_string(),
+        passed: error.is_none() && hashes_match(&computed, expected),
+        computed,
+        error,
+    }
+}
+
+/// Verify the embedded E01 through the regular EWF verification path
+fn check_e01_fixture() -> Vec<SelfTestCheck> {
+    let input = "embedded E01 (3 chunks, 2 zlib-compressed)";
+    let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_nanos()).unwrap_or(0);
+    let dir = std::env::temp_dir().join(format!("ffx-selftest-{}-{}", std::process::id(), nanos));
+    let image_path = dir.join("selftest.E01");
+
+    let written = fs::create_dir_all(&dir)
+        .and_then(|_| fs::write(&image_path, build_fixture_e01()))
+        .map_err(|e| format!("Failed to write self-test fixture to {:?}: {}", dir, e));
+
+    let checks = [("md5", "MD5", FIXTURE_MD5), ("sha256", "SHA-256", FIXTURE_SHA256)]
+        .into_iter()
+        .map(|(algorithm, name, expected)| {
+            let computed = written.clone()
+                .and_then(|_| ewf::verify(&image_path.to_string_lossy(), algorithm));
+            check("e01_decompression", name, input, expected, computed)
+        })
+        .collect();
+
+    let _ = fs::remove_dir_all(&dir);
+    checks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_self_test_passes() {
+        let report = self_test();
+        assert!(report.passed, "failed checks: {:?}", report.failed_checks);
+        assert_eq!(report.checks.len(), HASH_VECTORS.len() * 3 + 2);
+        assert!(report.checks.iter().any(|c| c.category == "e01_decompression" && c.passed));
+
+        let appendix = report.to_appendix("A");
+        assert!(appendix.content.contains("PASSED"));
+        assert!(!appendix.content.contains("FAIL**"));
+    }
+
+    #[test]
+    fn test_mismatch_is_reported() {
+        let failed = check("hash", "MD5", "\"abc\"", "00000000000000000000000000000000", Ok("900150983cd24fb0d6963f7d28e17f72".to_string()));
+        assert!(!failed.passed);
+        let errored = check("e01_decompression", "MD5", "fixture", FIXTURE_MD5, Err("read failed".to_string()));
+        assert!(!errored.passed);
+        assert_eq!(errored.error.as_deref(), Some("read failed"));
+    }
+
+    #[test]
+    fn test_fixture_text_matches_chunk0() {
+        let chunk = crate::common::inflate::inflate_bounded(FIXTURE_CHUNK0_ZLIB, 512, 512).unwrap();
+        let expected: Vec<u8> = FIXTURE_TEXT.bytes().cycle().take(512).collect();
+        assert_eq!(chunk, expected);
+    }
+}
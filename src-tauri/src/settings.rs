@@ -0,0 +1,491 @@
+//! Application settings persisted as JSON in the app config directory
+//!
+//! One [`Settings`] value holds every app-wide knob: I/O buffer size,
+//! info batch concurrency, the default hash algorithm, job resources, the
+//! read retry policy, the audit log switch and where verification
+//! checkpoints go. Code reads it through [`current`], which falls back to
+//! the built-in defaults until a [`SettingsStore`] is installed with
+//! [`init`], so library users and tests never need a settings file.
+//!
+//! The file carries a `version`. Older files are migrated on load; fields
+//! this build doesn't know (written by a newer one) are kept and written
+//! back untouched, and a newer file's version is never lowered. A file that
+//! can't be loaded at all is moved aside (see [`SettingsStore::recovered`])
+//! before anything is saved over it.
+//!
+//! ```rust,ignore
+//! use ffx_check_lib::settings::{self, SettingsStore};
+//!
+//! settings::init(SettingsStore::open(settings::default_path())?);
+//! let updated = settings::store().unwrap().update(&serde_json::json!({ "bufferSize": 4194304 }))?;
+//! ```
+
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock, RwLock};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use tracing::{debug, info, warn};
+
+use crate::checkpoint::CheckpointStore;
+use crate::common::atomic_file::write_atomic;
+use crate::common::hash::{HashAlgorithm, MAX_TUNED_BUFFER_SIZE, MIN_TUNED_BUFFER_SIZE};
+use crate::common::{JobResources, RetryPolicy, BUFFER_SIZE};
+use crate::containers::{DEFAULT_BATCH_WORKERS, DEFAULT_WORKERS_PER_DEVICE, MAX_BATCH_WORKERS};
+
+/// Schema version written by this build
+pub const SETTINGS_VERSION: u32 = 1;
+
+/// File name of the settings in the app config directory
+pub const SETTINGS_FILE: &str = "settings.json";
+
+/// App-wide settings; every field has a default, so a partial or older
+/// file still loads
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Settings {
+    /// Schema version of the file the settings came from
+    pub version: u32,
+    /// I/O buffer for hashing, extraction and raw reads, in bytes (still
+    /// capped in low-memory mode)
+    pub buffer_size: usize,
+    /// Files an info batch reads at once
+    pub batch_workers: usize,
+    /// Files an info batch reads at once from one storage device
+    pub workers_per_device: usize,
+    /// Algorithm offered first for verification and hashing
+    pub default_algorithm: String,
+    /// Thread cap, I/O priority and memory mode of jobs that don't set their own
+    pub job_resources: JobResources,
+    /// How reads failing with a transient network error are retried
+    pub io_retry: RetryPolicy,
+    /// Record evidence operations in the audit log
+    pub audit_log: bool,
+    /// End exports (manifests, audit log exports, hash lists, report JSON)
+    /// with a keyed integrity tag; see `common::integrity`
+    pub integrity_tags: bool,
+    /// Folder of verification checkpoints (saved hash state); `None` uses
+    /// the app data folder
+    pub checkpoint_dir: Option<PathBuf>,
+    /// Fields this build doesn't know, kept so a newer build's settings
+    /// survive a round trip through this one
+    #[serde(flatten)]
+    pub unknown: Map<String, Value>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            version: SETTINGS_VERSION,
+            buffer_size: BUFFER_SIZE,
+            batch_workers: DEFAULT_BATCH_WORKERS,
+            workers_per_device: DEFAULT_WORKERS_PER_DEVICE,
+            default_algorithm: "md5".to_string(),
+            job_resources: JobResources::default(),
+            io_retry: RetryPolicy::default(),
+            audit_log: false,
+            integrity_tags: false,
+            checkpoint_dir: None,
+            unknown: Map::new(),
+        }
+    }
+}
+
+/// Why settings could not be loaded, changed or saved
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SettingsError {
+    /// A value is out of range
+    Invalid { field: &'static str, message: String },
+    /// An update names a field no build of this version knows
+    UnknownField { field: String },
+    /// The file or an update is not valid settings JSON
+    Malformed { error: String },
+    /// The settings file could not be read or written
+    Io { path: String, error: String },
+}
+
+impl fmt::Display for SettingsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SettingsError::Invalid { field, message } => write!(f, "Invalid setting {field}: {message}"),
+            SettingsError::UnknownField { field } => write!(f, "Unknown setting: {field}"),
+            SettingsError::Malformed { error } => write!(f, "Malformed settings: {error}"),
+            SettingsError::Io { path, error } => write!(f, "Settings file {path}: {error}"),
+        }
+    }
+}
+
+/// Why the settings file was not used at startup
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct SettingsLoadError {
+    pub error: SettingsError,
+    /// Where the unreadable file was moved; `None` when it couldn't be,
+    /// and updates are refused so it is not overwritten
+    pub backup_path: Option<PathBuf>,
+}
+
+impl From<SettingsError> for String {
+    fn from(error: SettingsError) -> String {
+        error.to_string()
+    }
+}
+
+impl Settings {
+    /// Check every value is in range
+    pub fn validate(&self) -> Result<(), SettingsError> {
+        let invalid = |field, message: String| Err(SettingsError::Invalid { field, message });
+        if !(MIN_TUNED_BUFFER_SIZE..=MAX_TUNED_BUFFER_SIZE).contains(&self.buffer_size) {
+            return invalid("bufferSize", format!(
+                "{} bytes is outside {}-{} bytes", self.buffer_size, MIN_TUNED_BUFFER_SIZE, MAX_TUNED_BUFFER_SIZE
+            ));
+        }
+        if !(1..=MAX_BATCH_WORKERS).contains(&self.batch_workers) {
+            return invalid("batchWorkers", format!("{} is outside 1-{}", self.batch_workers, MAX_BATCH_WORKERS));
+        }
+        if self.workers_per_device == 0 {
+            return invalid("workersPerDevice", "must be at least 1".to_string());
+        }
+        if self.job_resources.threads == Some(0) {
+            return invalid("jobResources.threads", "must be at least 1 (leave unset for every core)".to_string());
+        }
+        if let Err(e) = HashAlgorithm::from_str(&self.default_algorithm) {
+            return invalid("defaultAlgorithm", e);
+        }
+        if self.io_retry.initial_backoff_ms > self.io_retry.max_backoff_ms {
+            return invalid("ioRetry", "initial backoff exceeds the maximum backoff".to_string());
+        }
+        if self.checkpoint_dir.as_deref().is_some_and(|dir| !dir.is_absolute()) {
+            return invalid("checkpointDir", "must be an absolute path".to_string());
+        }
+        Ok(())
+    }
+
+    /// These settings with the fields of `partial` (a JSON object) replaced
+    ///
+    /// Nested objects are merged field by field. The version can't be
+    /// changed, and unknown fields (at any depth) are refused so a typo
+    /// doesn't get stored.
+    pub fn merged(&self, partial: &Value) -> Result<Settings, SettingsError> {
+        let Value::Object(changes) = partial else {
+            return Err(SettingsError::Malformed { error: "an update must be a JSON object".to_string() });
+        };
+        if changes.contains_key("version") {
+            return Err(SettingsError::UnknownField { field: "version".to_string() });
+        }
+        let known = serde_json::to_value(Settings::default()).map_err(malformed)?;
+        if let Some(field) = unknown_field(&known, changes) {
+            return Err(SettingsError::UnknownField { field });
+        }
+        let mut value = serde_json::to_value(self).map_err(malformed)?;
+        merge(&mut value, partial);
+        let settings: Settings = serde_json::from_value(value).map_err(malformed)?;
+        settings.validate()?;
+        Ok(settings)
+    }
+
+    /// Where verification checkpoints are kept
+    pub fn checkpoint_store(&self) -> CheckpointStore {
+        match &self.checkpoint_dir {
+            Some(dir) => CheckpointStore::new(dir),
+            None => CheckpointStore::default_location(),
+        }
+    }
+}
+
+fn malformed(error: serde_json::Error) -> SettingsError {
+    SettingsError::Malformed { error: error.to_string() }
+}
+
+/// First field of `changes` missing from `known`, as a dotted path,
+/// recursing into objects both have
+fn unknown_field(known: &Value, changes: &Map<String, Value>) -> Option<String> {
+    changes.iter().find_map(|(key, change)| match (known.get(key), change) {
+        (None, _) => Some(key.clone()),
+        (Some(Value::Object(_)), Value::Object(nested)) => {
+            unknown_field(&known[key], nested).map(|field| format!("{key}.{field}"))
+        }
+        _ => None,
+    })
+}
+
+/// Overwrite fields of `target` with those of `changes`, recursing into objects
+fn merge(target: &mut Value, changes: &Value) {
+    match (target, changes) {
+        (Value::Object(target), Value::Object(changes)) => {
+            for (key, change) in changes {
+                match target.get_mut(key) {
+                    Some(existing) if existing.is_object() && change.is_object() => merge(existing, change),
+                    _ => {
+                        target.insert(key.clone(), change.clone());
+                    }
+                }
+            }
+        }
+        (target, changes) => *target = changes.clone(),
+    }
+}
+
+/// Bring a settings file written by an older build up to [`SETTINGS_VERSION`]
+///
+/// Files from before versioning (no `version`) count as version 0. Newer
+/// files are left alone: their extra fields end up in [`Settings::unknown`].
+fn migrate(value: &mut Value) {
+    let Some(fields) = value.as_object_mut() else { return };
+    let version = fields.get("version").and_then(Value::as_u64).unwrap_or(0);
+    if version > u64::from(SETTINGS_VERSION) {
+        warn!(version, supported = SETTINGS_VERSION, "Settings were written by a newer version; keeping the fields it added");
+        return;
+    }
+    // Version 0 -> 1: the fields are unchanged, only the version is new
+    fields.insert("version".to_string(), Value::from(SETTINGS_VERSION));
+}
+
+/// Settings file in the app config folder
+pub fn default_path() -> PathBuf {
+    dirs::config_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("com.ffxcheck.app")
+        .join(SETTINGS_FILE)
+}
+
+/// Settings of one file, kept in memory between changes
+pub struct SettingsStore {
+    path: PathBuf,
+    current: RwLock<Arc<Settings>>,
+    load_error: Option<SettingsLoadError>,
+}
+
+impl SettingsStore {
+    /// Load `path`; a missing file gives the defaults (and is not created
+    /// until the first update)
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self, SettingsError> {
+        let path = path.into();
+        let settings = load(&path)?;
+        Ok(Self { path, current: RwLock::new(Arc::new(settings)), load_error: None })
+    }
+
+    /// Defaults for `path` after [`open`](Self::open) failed with `error`
+    ///
+    /// The unreadable file is moved to `<name>.bak` first, so the user's
+    /// settings (and a newer build's fields) survive the next update. If it
+    /// can't be moved, updates are refused until it is fixed or removed.
+    pub fn recovered(path: impl Into<PathBuf>, error: SettingsError) -> Self {
+        let path = path.into();
+        let backup = backup_path(&path);
+        let backup_path = match fs::rename(&path, &backup) {
+            Ok(()) => {
+                warn!(path = %path.display(), backup = %backup.display(), %error, "Unreadable settings moved aside");
+                Some(backup)
+            }
+            Err(e) => {
+                warn!(path = %path.display(), %error, "Unreadable settings could not be moved aside, not saving settings: {}", e);
+                None
+            }
+        };
+        Self {
+            path,
+            current: RwLock::new(Arc::new(Settings::default())),
+            load_error: Some(SettingsLoadError { error, backup_path }),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Why the file was not loaded, for a store from [`recovered`](Self::recovered)
+    pub fn load_error(&self) -> Option<&SettingsLoadError> {
+        self.load_error.as_ref()
+    }
+
+    pub fn get(&self) -> Arc<Settings> {
+        Arc::clone(&self.current.read().unwrap_or_else(|poisoned| poisoned.into_inner()))
+    }
+
+    /// Apply `partial` (see [`Settings::merged`]) and save the result
+    ///
+    /// Nothing changes, in memory or on disk, if it is invalid or can't be saved.
+    pub fn update(&self, partial: &Value) -> Result<Arc<Settings>, SettingsError> {
+        if let Some(SettingsLoadError { error, backup_path: None }) = &self.load_error {
+            return Err(SettingsError::Io {
+                path: self.path.display().to_string(),
+                error: format!("not saved over the unreadable settings file ({error}); fix or remove it and restart"),
+            });
+        }
+        let mut current = self.current.write().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let settings = Arc::new(current.merged(partial)?);
+        save(&self.path, &settings)?;
+        info!(path = %self.path.display(), changes = %partial, "Settings updated");
+        *current = Arc::clone(&settings);
+        Ok(settings)
+    }
+}
+
+/// Read, migrate and validate a settings file
+pub fn load(path: &Path) -> Result<Settings, SettingsError> {
+    let io_error = |e: std::io::Error| SettingsError::Io { path: path.display().to_string(), error: e.to_string() };
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            debug!(path = %path.display(), "No settings file, using defaults");
+            return Ok(Settings::default());
+        }
+        Err(e) => return Err(io_error(e)),
+    };
+    let mut value: Value = serde_json::from_str(&text).map_err(malformed)?;
+    migrate(&mut value);
+    let settings: Settings = serde_json::from_value(value).map_err(malformed)?;
+    settings.validate()?;
+    Ok(settings)
+}
+
+/// `settings.json.bak` for `settings.json`
+fn backup_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".bak");
+    path.with_file_name(name)
+}
+
+/// Write `settings` to `path` through a temporary file, so a crash never
+/// leaves half a file
+fn save(path: &Path, settings: &Settings) -> Result<(), SettingsError> {
+    let io_error = |e: std::io::Error| SettingsError::Io { path: path.display().to_string(), error: e.to_string() };
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(io_error)?;
+    }
+    let json = serde_json::to_string_pretty(settings).map_err(malformed)?;
+    write_atomic(path, json).map_err(io_error)
+}
+
+static STORE: OnceLock<SettingsStore> = OnceLock::new();
+
+/// Make `store` the app's settings; only the first call takes effect
+pub fn init(store: SettingsStore) -> &'static SettingsStore {
+    STORE.get_or_init(|| store)
+}
+
+/// The app's settings store, once [`init`] was called
+pub fn store() -> Option<&'static SettingsStore> {
+    STORE.get()
+}
+
+/// Settings in effect: the store's, or the defaults before [`init`]
+pub fn current() -> Arc<Settings> {
+    static DEFAULTS: OnceLock<Arc<Settings>> = OnceLock::new();
+    match STORE.get() {
+        Some(store) => store.get(),
+        None => Arc::clone(DEFAULTS.get_or_init(|| Arc::new(Settings::default()))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_missing_file_gives_defaults_and_update_round_trips() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config").join(SETTINGS_FILE);
+        let store = SettingsStore::open(&path).unwrap();
+        assert_eq!(*store.get(), Settings::default());
+        assert!(!path.exists());
+
+        let updated = store.update(&json!({ "bufferSize": 1 << 20, "jobResources": { "threads": 2 }, "auditLog": true })).unwrap();
+        assert_eq!((updated.buffer_size, updated.job_resources.threads, updated.audit_log), (1 << 20, Some(2), true));
+        // Fields of a nested object that the update leaves out are kept
+        assert_eq!(updated.io_retry, RetryPolicy::default());
+
+        let reloaded = SettingsStore::open(&path).unwrap();
+        assert_eq!(reloaded.get(), updated);
+    }
+
+    #[test]
+    fn test_invalid_values_are_refused() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(SETTINGS_FILE);
+        let store = SettingsStore::open(&path).unwrap();
+        for (update, field) in [
+            (json!({ "bufferSize": 4096 }), "bufferSize"),
+            (json!({ "jobResources": { "threads": 0 } }), "jobResources.threads"),
+            (json!({ "batchWorkers": 0 }), "batchWorkers"),
+            (json!({ "defaultAlgorithm": "md4" }), "defaultAlgorithm"),
+        ] {
+            match store.update(&update) {
+                Err(SettingsError::Invalid { field: found, .. }) => assert_eq!(found, field),
+                other => panic!("{update} was not refused: {other:?}"),
+            }
+        }
+        assert!(matches!(store.update(&json!({ "bufferSise": 1 << 20 })), Err(SettingsError::UnknownField { .. })));
+        match store.update(&json!({ "jobResources": { "thredas": 2 } })) {
+            Err(SettingsError::UnknownField { field }) => assert_eq!(field, "jobResources.thredas"),
+            other => panic!("nested typo was not refused: {other:?}"),
+        }
+        assert!(matches!(store.update(&json!({ "bufferSize": "large" })), Err(SettingsError::Malformed { .. })));
+        // Nothing was saved or changed
+        assert!(!path.exists());
+        assert_eq!(*store.get(), Settings::default());
+
+        fs::write(&path, r#"{ "version": 1, "workersPerDevice": 0 }"#).unwrap();
+        assert!(matches!(load(&path), Err(SettingsError::Invalid { field: "workersPerDevice", .. })));
+        fs::write(&path, "{ not json").unwrap();
+        assert!(matches!(load(&path), Err(SettingsError::Malformed { .. })));
+    }
+
+    #[test]
+    fn test_older_and_newer_files_migrate_and_keep_unknown_fields() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(SETTINGS_FILE);
+
+        // Before versioning
+        fs::write(&path, r#"{ "batchWorkers": 3 }"#).unwrap();
+        let settings = load(&path).unwrap();
+        assert_eq!((settings.version, settings.batch_workers), (SETTINGS_VERSION, 3));
+
+        // Written by a newer build with a field this one doesn't know
+        fs::write(&path, r#"{ "version": 7, "batchWorkers": 3, "hashCacheSize": 512 }"#).unwrap();
+        let store = SettingsStore::open(&path).unwrap();
+        assert_eq!(store.get().unknown.get("hashCacheSize"), Some(&json!(512)));
+        store.update(&json!({ "batchWorkers": 5 })).unwrap();
+        let saved: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!((&saved["version"], &saved["batchWorkers"], &saved["hashCacheSize"]), (&json!(7), &json!(5), &json!(512)));
+    }
+
+    #[test]
+    fn test_unreadable_file_is_moved_aside_before_saving() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(SETTINGS_FILE);
+        let newer = r#"{ "version": 7, "batchWorkers": 99, "hashCacheSize": 512 }"#;
+        fs::write(&path, newer).unwrap();
+
+        let error = SettingsStore::open(&path).err().unwrap();
+        assert!(matches!(error, SettingsError::Invalid { field: "batchWorkers", .. }));
+        let store = SettingsStore::recovered(&path, error.clone());
+        let backup = dir.path().join("settings.json.bak");
+        assert_eq!(store.load_error(), Some(&SettingsLoadError { error, backup_path: Some(backup.clone()) }));
+        assert_eq!(*store.get(), Settings::default());
+
+        store.update(&json!({ "auditLog": true })).unwrap();
+        assert_eq!(fs::read_to_string(&backup).unwrap(), newer);
+        assert!(load(&path).unwrap().audit_log);
+    }
+
+    #[test]
+    fn test_unreadable_file_that_cannot_be_moved_is_not_overwritten() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(SETTINGS_FILE);
+        fs::write(&path, "{ not json").unwrap();
+        // A non-empty folder in the way of the backup
+        fs::create_dir_all(dir.path().join("settings.json.bak").join("keep")).unwrap();
+
+        let error = SettingsStore::open(&path).err().unwrap();
+        let store = SettingsStore::recovered(&path, error);
+        assert_eq!(store.load_error().unwrap().backup_path, None);
+        assert!(matches!(store.update(&json!({ "auditLog": true })), Err(SettingsError::Io { .. })));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "{ not json");
+    }
+}
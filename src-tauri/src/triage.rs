@@ -0,0 +1,1007 @@
+//! Content-defined triage of disk images ("what is this image?")
+//!
+//! Reads the partition table (MBR with extended partitions, or GPT), probes
+//! each partition's boot sector or superblock and picks up cheap
+//! identifiers - NTFS label, serial and version, ext volume name, UUID and
+//! last mount point, APFS container UUID, FAT/exFAT label and serial. No
+//! filesystem is walked: every read goes against a budget of
+//! [`MAX_TRIAGE_READ`] bytes, every field is optional, and a partition that
+//! can't be probed only records its own `error`.
+//!
+//! Encrypted volumes are labelled, never decrypted: BitLocker (including
+//! BitLocker To Go), LUKS1/2, APFS containers holding FileVault volumes and
+//! Core Storage volumes are recognised by their headers. A partition with
+//! no known signature whose first bytes look random is reported as
+//! possibly encrypted (VeraCrypt/TrueCrypt volumes have no header to find).
+//!
+//! The Windows version lives in the SOFTWARE registry hive, which takes a
+//! full NTFS directory walk to reach, so it is not reported.
+//!
+//! ```rust,ignore
+//! use ffx_check_lib::triage;
+//!
+//! let summary = triage::identify("/evidence/laptop.E01")?;
+//! println!("{:?}: {} partitions", summary.guessed_platform, summary.partitions.len());
+//! ```
+
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+use crate::common::binary::bytes_to_string;
+use crate::{ewf, raw};
+
+/// Total bytes triage may read from one image
+pub const MAX_TRIAGE_READ: u64 = 4 * 1024 * 1024;
+
+/// Logical partitions followed through an extended partition's EBR chain
+const MAX_LOGICAL_PARTITIONS: usize = 64;
+
+/// GPT partition entries read (the usual array size)
+const MAX_GPT_ENTRIES: u32 = 128;
+
+/// Bytes probed at the start of each partition - covers the boot sector,
+/// the ext and HFS+ superblocks at 1024, and the swap signature at 4086
+const PROBE_SIZE: usize = 4096;
+
+const SECTOR_SIZE: u64 = 512;
+
+/// Bits per byte from which a partition's first [`PROBE_SIZE`] bytes count
+/// as random - 4096 random bytes measure about 7.95, text and code stay
+/// well below 7
+const RANDOM_ENTROPY: f64 = 7.9;
+
+/// BitLocker volume identifier {4967D63B-2E29-4AD8-8399-F6A339E3D001}, as
+/// stored on disk (at 424 in the FAT-compatible BitLocker To Go header)
+const BITLOCKER_GUID: [u8; 16] = [0x3B, 0xD6, 0x67, 0x49, 0x29, 0x2E, 0xD8, 0x4A, 0x83, 0x99, 0xF6, 0xA3, 0x39, 0xE3, 0xD0, 0x01];
+
+/// `nx_flags` bit of an APFS container whose volumes use software encryption
+const NX_CRYPTO_SW: u64 = 0x4;
+
+/// Triage result for a disk image
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct TriageSummary {
+    /// "MBR", "GPT", or "none" for an unpartitioned volume image
+    pub partition_scheme: String,
+    pub disk_size: u64,
+    pub partitions: Vec<PartitionTriage>,
+    /// "Windows", "Linux" or "macOS" when the partitions point to one
+    pub guessed_platform: Option<String>,
+    /// Bytes read from the image to produce this summary
+    pub bytes_read: u64,
+    /// Some partition is encrypted or possibly encrypted (see
+    /// [`PartitionTriage::encryption`])
+    pub encryption_detected: bool,
+}
+
+/// One partition and the identifiers found in its first sectors
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct PartitionTriage {
+    /// 1-based table index; logical partitions are numbered from 5
+    pub index: u32,
+    /// Byte offset of the partition in the image
+    pub offset: u64,
+    pub size: u64,
+    /// MBR type byte ("0x07") or GPT type GUID
+    pub partition_type: String,
+    pub type_description: Option<String>,
+    /// GPT partition name
+    pub name: Option<String>,
+    /// MBR active flag; set for the EFI system partition on GPT disks
+    pub bootable: bool,
+    /// "NTFS", "ext4", "APFS", "FAT32", "BitLocker", ...
+    pub filesystem: Option<String>,
+    pub volume_label: Option<String>,
+    /// NTFS/FAT/exFAT volume serial number
+    pub volume_serial: Option<String>,
+    /// ext filesystem UUID or APFS container UUID
+    pub volume_uuid: Option<String>,
+    /// ext: directory the filesystem was last mounted on
+    pub last_mounted: Option<String>,
+    /// NTFS on-disk version (e.g. "3.1")
+    pub ntfs_version: Option<String>,
+    /// Full-disk encryption found on the partition; `filesystem` then names
+    /// the encryption container, not the filesystem inside it
+    pub encryption: Option<EncryptionFinding>,
+    /// Why the partition could not be probed
+    pub error: Option<String>,
+}
+
+/// Encryption of a partition, as far as its first sectors tell
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct EncryptionFinding {
+    /// "BitLocker", "LUKS1", "LUKS2", "FileVault", "Core Storage" or "unknown"
+    pub scheme: String,
+    /// What to show instead of a filesystem, e.g. "BitLocker protected volume"
+    pub description: String,
+    /// No encryption header was found - the data only looks encrypted
+    /// (random bytes, or a Core Storage volume that may be a Fusion Drive)
+    pub heuristic: bool,
+}
+
+/// Random access to the decoded bytes of a disk image
+pub trait ImageReader {
+    /// Size of the decoded image in bytes
+    fn size(&self) -> u64;
+
+    /// Read up to `length` bytes at `offset` (short only at the end of the image)
+    fn read_at(&mut self, offset: u64, length: usize) -> Result<Vec<u8>, String>;
+}
+
+impl ImageReader for ewf::EwfHandle {
+    fn size(&self) -> u64 {
+        self.get_media_size()
+    }
+
+    fn read_at(&mut self, offset: u64, length: usize) -> Result<Vec<u8>, String> {
+        ewf::EwfHandle::read_at(self, offset, length)
+    }
+}
+
+impl ImageReader for raw::RawHandle {
+    fn size(&self) -> u64 {
+        self.total_size()
+    }
+
+    fn read_at(&mut self, offset: u64, length: usize) -> Result<Vec<u8>, String> {
+        self.seek(offset);
+        let mut buf = vec![0u8; length];
+        let mut filled = 0;
+        while filled < length {
+            let n = self.read(&mut buf[filled..])?;
+            if n == 0 {
+                break;
+            }
+            filled += n;
+        }
+        buf.truncate(filled);
+        Ok(buf)
+    }
+}
+
+impl ImageReader for raw::BlockDevice {
+    fn size(&self) -> u64 {
+        raw::BlockDevice::size(self)
+    }
+
+    fn read_at(&mut self, offset: u64, length: usize) -> Result<Vec<u8>, String> {
+        raw::BlockDevice::read_at(self, offset, length)
+    }
+}
+
+/// Open an E01 (any EWF variant), raw image or drive (refusing the system
+/// drive) for random access
+pub fn open_image(path: &str) -> Result<Box<dyn ImageReader>, String> {
+    if raw::is_block_device_path(path) {
+        Ok(Box::new(raw::BlockDevice::open(path, false)?))
+    } else if ewf::is_ewf(path)? {
+        Ok(Box::new(ewf::EwfHandle::open(path)?))
+    } else {
+        Ok(Box::new(raw::RawHandle::open(path)?))
+    }
+}
+
+/// Triage an E01 (any EWF variant) or raw image
+pub fn identify(path: &str) -> Result<TriageSummary, String> {
+    identify_reader(open_image(path)?.as_mut())
+}
+
+/// Triage a drive in place; the system drive only with `force`
+pub fn identify_device(path: &str, force: bool) -> Result<TriageSummary, String> {
+    identify_reader(&mut raw::BlockDevice::open(path, force)?)
+}
+
+/// Partitions of a disk image as its table lists them
+pub struct PartitionTable {
+    /// "MBR", "GPT", or "none" for an unpartitioned volume image
+    pub scheme: &'static str,
+    /// Sector size the table's LBAs are in (4096 for 4Kn GPT disks)
+    pub sector_size: u64,
+    /// Entries are not checked against each other or the image size
+    pub partitions: Vec<PartitionTriage>,
+}
+
+/// Read the partition table of an already opened image without probing
+/// the partitions
+pub fn read_partition_table(reader: &mut dyn ImageReader) -> Result<PartitionTable, String> {
+    partition_table(&mut BudgetReader { reader, remaining: MAX_TRIAGE_READ })
+}
+
+fn partition_table(image: &mut BudgetReader) -> Result<PartitionTable, String> {
+    let disk_size = image.reader.size();
+    let sector0 = image.read(0, PROBE_SIZE)?;
+    if sector0.len() < SECTOR_SIZE as usize {
+        return Err(format!("Image too small to triage ({} bytes)", disk_size));
+    }
+
+    // A volume image starts with its boot sector - FAT and NTFS boot sectors
+    // also end in 55 AA, so probe for a filesystem before reading a table.
+    // A whole-disk LUKS or VeraCrypt volume has no table either
+    if probe_filesystem(&sector0).is_some() || looks_random(&sector0) {
+        let volume = PartitionTriage {
+            index: 1,
+            size: disk_size,
+            partition_type: "none".to_string(),
+            ..Default::default()
+        };
+        Ok(PartitionTable { scheme: "none", sector_size: SECTOR_SIZE, partitions: vec![volume] })
+    } else if sector0[510..512] != [0x55, 0xAA] {
+        Err("No partition table or filesystem found in the first sector".to_string())
+    } else if mbr_entries(&sector0).any(|entry| entry.partition_type == 0xEE) {
+        let (partitions, sector_size) = read_gpt(image)?;
+        Ok(PartitionTable { scheme: "GPT", sector_size, partitions })
+    } else {
+        Ok(PartitionTable { scheme: "MBR", sector_size: SECTOR_SIZE, partitions: read_mbr(image, &sector0) })
+    }
+}
+
+/// Triage an already opened image
+///
+/// Only failing to read the first sector is an error; partitions that can't
+/// be probed are returned with their `error` set.
+pub fn identify_reader(reader: &mut dyn ImageReader) -> Result<TriageSummary, String> {
+    let mut image = BudgetReader { reader, remaining: MAX_TRIAGE_READ };
+    let disk_size = image.reader.size();
+    let PartitionTable { scheme: partition_scheme, mut partitions, .. } = partition_table(&mut image)?;
+
+    for partition in &mut partitions {
+        if let Err(e) = probe_partition(&mut image, partition) {
+            debug!(index = partition.index, error = %e, "Partition probe failed");
+            partition.error = Some(e);
+        }
+    }
+
+    Ok(TriageSummary {
+        partition_scheme: partition_scheme.to_string(),
+        disk_size,
+        guessed_platform: guess_platform(&partitions),
+        encryption_detected: partitions.iter().any(|p| p.encryption.is_some()),
+        partitions,
+        bytes_read: MAX_TRIAGE_READ - image.remaining,
+    })
+}
+
+/// Reads charged against the triage budget
+struct BudgetReader<'a> {
+    reader: &'a mut dyn ImageReader,
+    remaining: u64,
+}
+
+impl BudgetReader<'_> {
+    fn read(&mut self, offset: u64, length: usize) -> Result<Vec<u8>, String> {
+        let size = self.reader.size();
+        if offset >= size {
+            return Err(format!("Offset {} is beyond the end of the image ({} bytes)", offset, size));
+        }
+        let length = (length as u64).min(size - offset);
+        if length > self.remaining {
+            return Err(format!("Triage read budget of {} bytes exhausted", MAX_TRIAGE_READ));
+        }
+        self.remaining -= length;
+        self.reader.read_at(offset, length as usize)
+    }
+}
+
+// =============================================================================
+// Partition Tables
+// =============================================================================
+
+struct MbrEntry {
+    bootable: bool,
+    partition_type: u8,
+    start_lba: u64,
+    sectors: u64,
+}
+
+fn mbr_entries(sector: &[u8]) -> impl Iterator<Item = MbrEntry> + '_ {
+    (0..4)
+        .map(|i| &sector[446 + i * 16..462 + i * 16])
+        .map(|entry| MbrEntry {
+            bootable: entry[0] == 0x80,
+            partition_type: entry[4],
+            start_lba: u32_at(entry, 8) as u64,
+            sectors: u32_at(entry, 12) as u64,
+        })
+}
+
+fn is_extended(partition_type: u8) -> bool {
+    matches!(partition_type, 0x05 | 0x0F | 0x85)
+}
+
+fn mbr_partition(index: u32, entry: &MbrEntry, base_lba: u64) -> PartitionTriage {
+    PartitionTriage {
+        index,
+        offset: (base_lba + entry.start_lba) * SECTOR_SIZE,
+        size: entry.sectors * SECTOR_SIZE,
+        partition_type: format!("0x{:02X}", entry.partition_type),
+        type_description: mbr_type_description(entry.partition_type).map(str::to_string),
+        bootable: entry.bootable,
+        ..Default::default()
+    }
+}
+
+/// Primary partitions, then the logical partitions of the extended partition
+fn read_mbr(image: &mut BudgetReader, sector0: &[u8]) -> Vec<PartitionTriage> {
+    let mut partitions = Vec::new();
+    let mut extended_start = None;
+    for (i, entry) in mbr_entries(sector0).enumerate() {
+        if entry.partition_type == 0 || entry.sectors == 0 {
+            continue;
+        }
+        if is_extended(entry.partition_type) {
+            extended_start.get_or_insert(entry.start_lba);
+        } else {
+            partitions.push(mbr_partition(i as u32 + 1, &entry, 0));
+        }
+    }
+
+    // EBR chain: entry 0 is the logical partition (relative to its EBR),
+    // entry 1 links the next EBR (relative to the extended partition)
+    if let Some(extended_start) = extended_start {
+        let mut ebr_lba = extended_start;
+        for index in 5..5 + MAX_LOGICAL_PARTITIONS as u32 {
+            let ebr = match image.read(ebr_lba * SECTOR_SIZE, SECTOR_SIZE as usize) {
+                Ok(ebr) if ebr.len() == SECTOR_SIZE as usize && ebr[510..512] == [0x55, 0xAA] => ebr,
+                Ok(_) => break,
+                Err(e) => {
+                    debug!(ebr_lba, error = %e, "Stopping at unreadable EBR");
+                    break;
+                }
+            };
+            let mut entries = mbr_entries(&ebr);
+            if let Some(logical) = entries.next().filter(|entry| entry.sectors > 0) {
+                partitions.push(mbr_partition(index, &logical, ebr_lba));
+            }
+            match entries.next().filter(|next| is_extended(next.partition_type) && next.start_lba > 0) {
+                Some(next) => ebr_lba = extended_start + next.start_lba,
+                None => break,
+            }
+        }
+    }
+    partitions
+}
+
+/// GPT partitions, with the sector size the header was found at
+fn read_gpt(image: &mut BudgetReader) -> Result<(Vec<PartitionTriage>, u64), String> {
+    // The header is at LBA 1; try 4Kn disks as well as 512-byte sectors
+    let (header, sector_size) = [SECTOR_SIZE, 4096].into_iter()
+        .find_map(|sector_size| {
+            let header = image.read(sector_size, 92).ok()?;
+            (header.len() == 92 && &header[..8] == b"EFI PART").then_some((header, sector_size))
+        })
+        .ok_or("Protective MBR without a GPT header")?;
+
+    let entries_lba = u64_at(&header, 72);
+    let entry_count = u32_at(&header, 80).min(MAX_GPT_ENTRIES);
+    let entry_size = u32_at(&header, 84) as usize;
+    if !(128..=4096).contains(&entry_size) {
+        return Err(format!("Corrupt GPT header: partition entry size {}", entry_size));
+    }
+    let table = image.read(entries_lba * sector_size, entry_count as usize * entry_size)?;
+
+    let mut partitions = Vec::new();
+    for (i, entry) in table.chunks_exact(entry_size).enumerate() {
+        let type_guid = &entry[..16];
+        if type_guid.iter().all(|b| *b == 0) {
+            continue;
+        }
+        let first_lba = u64_at(entry, 32);
+        let last_lba = u64_at(entry, 40);
+        let partition_type = format_guid(type_guid);
+        let type_description = gpt_type_description(&partition_type);
+        let name = utf16_string(&entry[56..128]);
+        partitions.push(PartitionTriage {
+            index: i as u32 + 1,
+            offset: first_lba * sector_size,
+            size: (last_lba + 1).saturating_sub(first_lba) * sector_size,
+            bootable: type_description == Some("EFI system partition"),
+            type_description: type_description.map(str::to_string),
+            partition_type,
+            name: (!name.is_empty()).then_some(name),
+            ..Default::default()
+        });
+    }
+    Ok((partitions, sector_size))
+}
+
+fn mbr_type_description(partition_type: u8) -> Option<&'static str> {
+    Some(match partition_type {
+        0x01 => "FAT12",
+        0x04 | 0x06 | 0x0E => "FAT16",
+        0x07 => "NTFS/exFAT",
+        0x0B | 0x0C => "FAT32",
+        0x27 => "Windows recovery",
+        0x82 => "Linux swap",
+        0x83 => "Linux",
+        0x8E => "Linux LVM",
+        0xA5 => "FreeBSD",
+        0xAF => "HFS+",
+        0xEE => "GPT protective",
+        0xEF => "EFI system partition",
+        _ => return None,
+    })
+}
+
+fn gpt_type_description(guid: &str) -> Option<&'static str> {
+    Some(match guid {
+        "C12A7328-F81F-11D2-BA4B-00A0C93EC93B" => "EFI system partition",
+        "E3C9E316-0B5C-4DB8-817D-F92DF00215AE" => "Microsoft reserved",
+        "EBD0A0A2-B9E5-4433-87C0-68B6B72699C7" => "Microsoft basic data",
+        "DE94BBA4-06D1-4D40-A16A-BFD50179D6AC" => "Windows recovery",
+        "0FC63DAF-8483-4772-8E79-3D69D8477DE4" => "Linux filesystem",
+        "4F68BCE3-E8CD-4DB1-96E7-FBCAF984B709" => "Linux root (x86-64)",
+        "0657FD6D-A4AB-43C4-84E5-0933C84B4F4F" => "Linux swap",
+        "E6D6D379-F507-44C2-A23C-238F2A3DF928" => "Linux LVM",
+        "CA7D7CCB-63ED-4C53-861C-1742536059CC" => "Linux LUKS",
+        "7C3457EF-0000-11AA-AA11-00306543ECAC" => "Apple APFS",
+        "48465300-0000-11AA-AA11-00306543ECAC" => "Apple HFS+",
+        "53746F72-6167-11AA-AA11-00306543ECAC" => "Apple Core Storage",
+        _ => return None,
+    })
+}
+
+// =============================================================================
+// Filesystem Probes
+// =============================================================================
+
+/// Identifiers from a partition's first [`PROBE_SIZE`] bytes
+#[derive(Default)]
+struct FilesystemProbe {
+    filesystem: &'static str,
+    volume_label: Option<String>,
+    volume_serial: Option<String>,
+    volume_uuid: Option<String>,
+    last_mounted: Option<String>,
+}
+
+impl FilesystemProbe {
+    fn named(filesystem: &'static str) -> Self {
+        Self { filesystem, ..Default::default() }
+    }
+}
+
+fn probe_filesystem(buf: &[u8]) -> Option<FilesystemProbe> {
+    let sig = |offset: usize, expected: &[u8]| buf.get(offset..offset + expected.len()) == Some(expected);
+
+    if sig(3, b"NTFS    ") {
+        return Some(FilesystemProbe {
+            volume_serial: Some(format_serial(u64_at(buf, 72) as u32)),
+            ..FilesystemProbe::named("NTFS")
+        });
+    }
+    // BitLocker To Go keeps a FAT boot sector for older readers, so check
+    // for it before FAT
+    if sig(3, b"-FVE-FS-") || sig(424, &BITLOCKER_GUID) {
+        return Some(FilesystemProbe::named("BitLocker"));
+    }
+    if sig(0, b"LUKS\xBA\xBE") {
+        // Version is big-endian; LUKS2 adds a label, both keep the UUID as text
+        let version = buf.get(6..8).map_or(0, |b| u16::from_be_bytes([b[0], b[1]]));
+        return Some(FilesystemProbe {
+            volume_label: buf.get(24..72).filter(|_| version == 2).and_then(|label| non_empty(bytes_to_string(label))),
+            volume_uuid: buf.get(168..208).and_then(|uuid| non_empty(bytes_to_string(uuid))),
+            ..FilesystemProbe::named(if version == 2 { "LUKS2" } else { "LUKS1" })
+        });
+    }
+    if sig(3, b"EXFAT   ") {
+        return Some(FilesystemProbe {
+            volume_serial: Some(format_serial(u32_at(buf, 100))),
+            ..FilesystemProbe::named("exFAT")
+        });
+    }
+    if sig(82, b"FAT32   ") {
+        return Some(FilesystemProbe {
+            volume_label: fat_label(&buf[71..82]),
+            volume_serial: Some(format_serial(u32_at(buf, 67))),
+            ..FilesystemProbe::named("FAT32")
+        });
+    }
+    if sig(54, b"FAT12   ") || sig(54, b"FAT16   ") || sig(54, b"FAT     ") {
+        return Some(FilesystemProbe {
+            volume_label: fat_label(&buf[43..54]),
+            volume_serial: Some(format_serial(u32_at(buf, 39))),
+            ..FilesystemProbe::named(if sig(54, b"FAT12") { "FAT12" } else { "FAT16" })
+        });
+    }
+    if sig(32, b"NXSB") {
+        return Some(FilesystemProbe {
+            volume_uuid: buf.get(72..88).map(format_uuid),
+            ..FilesystemProbe::named("APFS")
+        });
+    }
+    if let Some(sb) = buf.get(1024..2048).filter(|sb| u16_at(sb, 56) == 0xEF53) {
+        let (compat, incompat) = (u32_at(sb, 92), u32_at(sb, 96));
+        // extents / 64bit / flex_bg are ext4-only; a journal makes ext3
+        let filesystem = if incompat & (0x40 | 0x80 | 0x200) != 0 {
+            "ext4"
+        } else if compat & 0x4 != 0 {
+            "ext3"
+        } else {
+            "ext2"
+        };
+        return Some(FilesystemProbe {
+            volume_label: non_empty(bytes_to_string(&sb[120..136])),
+            volume_uuid: Some(format_uuid(&sb[104..120])),
+            last_mounted: non_empty(bytes_to_string(&sb[136..200])),
+            ..FilesystemProbe::named(filesystem)
+        });
+    }
+    if sig(1024, b"H+") || sig(1024, b"HX") {
+        return Some(FilesystemProbe::named("HFS+"));
+    }
+    // Core Storage physical volume header (FileVault 2 before APFS)
+    if sig(88, b"CS") && u16_at(buf, 8) == 1 {
+        return Some(FilesystemProbe::named("Core Storage"));
+    }
+    if sig(512, b"LABELONE") {
+        return Some(FilesystemProbe::named("LVM2"));
+    }
+    if sig(PROBE_SIZE - 10, b"SWAPSPACE2") || sig(PROBE_SIZE - 10, b"SWAP-SPACE") {
+        return Some(FilesystemProbe::named("Linux swap"));
+    }
+    None
+}
+
+fn probe_partition(image: &mut BudgetReader, partition: &mut PartitionTriage) -> Result<(), String> {
+    let buf = image.read(partition.offset, PROBE_SIZE)?;
+    let probe = probe_filesystem(&buf);
+    partition.encryption = detect_encryption(&buf, probe.as_ref());
+    let Some(probe) = probe else {
+        return Ok(());
+    };
+    partition.filesystem = Some(probe.filesystem.to_string());
+    partition.volume_label = probe.volume_label;
+    partition.volume_serial = probe.volume_serial;
+    partition.volume_uuid = probe.volume_uuid;
+    partition.last_mounted = probe.last_mounted;
+
+    if probe.filesystem == "NTFS" {
+        let (label, version) = read_ntfs_volume(image, partition.offset, &buf)?;
+        partition.volume_label = label;
+        partition.ntfs_version = version;
+    }
+    Ok(())
+}
+
+/// Encryption implied by the probe, or by random-looking data where no
+/// filesystem was found
+fn detect_encryption(buf: &[u8], probe: Option<&FilesystemProbe>) -> Option<EncryptionFinding> {
+    let finding = |scheme: &str, description: &str, heuristic: bool| Some(EncryptionFinding {
+        scheme: scheme.to_string(),
+        description: description.to_string(),
+        heuristic,
+    });
+    match probe.map(|probe| probe.filesystem) {
+        Some("BitLocker") => finding("BitLocker", "BitLocker protected volume", false),
+        Some("LUKS1") => finding("LUKS1", "LUKS1 encrypted volume", false),
+        Some("LUKS2") => finding("LUKS2", "LUKS2 encrypted volume", false),
+        // Software-encrypted container, or a keybag (nx_keylocker) holding
+        // the keys of encrypted volumes
+        Some("APFS") if u64_at(buf, 1264) & NX_CRYPTO_SW != 0 || u64_at(buf, 1304) > 0 => {
+            finding("FileVault", "APFS container with encrypted volumes (FileVault)", false)
+        }
+        Some("Core Storage") => finding("Core Storage", "Core Storage volume (FileVault 2 unless it is a Fusion Drive)", true),
+        Some(_) => None,
+        None if looks_random(buf) => finding(
+            "unknown",
+            "Possibly encrypted/random: no filesystem signature and random-looking data (VeraCrypt, TrueCrypt or wiped)",
+            true,
+        ),
+        None => None,
+    }
+}
+
+/// Volume label and version from the $Volume MFT record (record 3)
+fn read_ntfs_volume(image: &mut BudgetReader, offset: u64, boot: &[u8]) -> Result<(Option<String>, Option<String>), String> {
+    let bytes_per_sector = u16_at(boot, 11) as u64;
+    if boot.len() < 512 || !matches!(bytes_per_sector, 512 | 1024 | 2048 | 4096) {
+        return Err(format!("Corrupt NTFS boot sector: {} bytes per sector", bytes_per_sector));
+    }
+    let sectors_per_cluster = match boot[13] {
+        n if n > 0x80 => 1u64 << (256 - n as u32).min(31),
+        n => n as u64,
+    };
+    let cluster_size = bytes_per_sector * sectors_per_cluster;
+    let record_size = match boot[64] as i8 {
+        n if n < 0 => 1u64 << (-(n as i32)).min(16),
+        n => n as u64 * cluster_size,
+    };
+    if cluster_size == 0 || !(512..=65536).contains(&record_size) {
+        return Err(format!("Corrupt NTFS boot sector: cluster size {}, MFT record size {}", cluster_size, record_size));
+    }
+    let mft_offset = u64_at(boot, 48)
+        .checked_mul(cluster_size)
+        .and_then(|mft| offset.checked_add(mft))
+        .ok_or("Corrupt NTFS boot sector: MFT location overflows")?;
+
+    let mut record = image.read(mft_offset + 3 * record_size, record_size as usize)?;
+    if record.len() != record_size as usize || &record[..4] != b"FILE" {
+        return Err("$Volume MFT record not found".to_string());
+    }
+    apply_fixups(&mut record, bytes_per_sector as usize)?;
+
+    let mut label = None;
+    let mut version = None;
+    let mut pos = u16_at(&record, 20) as usize;
+    while pos + 24 <= record.len() {
+        let attribute_type = u32_at(&record, pos);
+        let length = u32_at(&record, pos + 4) as usize;
+        if attribute_type == 0xFFFF_FFFF || length < 24 || pos + length > record.len() {
+            break;
+        }
+        // $VOLUME_NAME and $VOLUME_INFORMATION are always resident
+        if record[pos + 8] == 0 {
+            let content_length = u32_at(&record, pos + 16) as usize;
+            let content_offset = pos + u16_at(&record, pos + 20) as usize;
+            if let Some(content) = record.get(content_offset..content_offset + content_length) {
+                match attribute_type {
+                    0x60 => label = non_empty(utf16_string(content)),
+                    0x70 if content.len() >= 10 => version = Some(format!("{}.{}", content[8], content[9])),
+                    _ => {}
+                }
+            }
+        }
+        pos += length;
+    }
+    Ok((label, version))
+}
+
+/// Restore the sector-end bytes replaced by the update sequence array
+fn apply_fixups(record: &mut [u8], sector_size: usize) -> Result<(), String> {
+    let usa_offset = u16_at(record, 4) as usize;
+    let usa_count = u16_at(record, 6) as usize;
+    if usa_count == 0 || usa_offset + usa_count * 2 > record.len() || (usa_count - 1) * sector_size > record.len() {
+        return Err("Corrupt MFT record: bad update sequence array".to_string());
+    }
+    let check = [record[usa_offset], record[usa_offset + 1]];
+    for i in 1..usa_count {
+        let end = i * sector_size - 2;
+        if record[end..end + 2] != check {
+            return Err("Corrupt MFT record: update sequence mismatch".to_string());
+        }
+        record[end] = record[usa_offset + i * 2];
+        record[end + 1] = record[usa_offset + i * 2 + 1];
+    }
+    Ok(())
+}
+
+/// Platform implied by the partitions, preferring boot evidence
+///
+/// Windows boot partitions (MSR, recovery, an active NTFS/BitLocker volume)
+/// win over a Linux root, which wins over Apple filesystems; after that any
+/// NTFS volume means Windows and any ext/swap/LVM means Linux. FAT/exFAT
+/// alone (removable media) gives no guess.
+fn guess_platform(partitions: &[PartitionTriage]) -> Option<String> {
+    let has_filesystem = |names: &[&str]| partitions.iter()
+        .any(|p| p.filesystem.as_deref().is_some_and(|fs| names.contains(&fs)));
+    let has_type = |names: &[&str]| partitions.iter()
+        .any(|p| p.type_description.as_deref().is_some_and(|t| names.contains(&t)));
+
+    let windows = has_filesystem(&["NTFS", "BitLocker"]);
+    let windows_boot = has_type(&["Microsoft reserved", "Windows recovery"])
+        || partitions.iter().any(|p| p.bootable && matches!(p.filesystem.as_deref(), Some("NTFS" | "BitLocker")));
+    let linux_root = has_type(&["Linux root (x86-64)"])
+        || partitions.iter().any(|p| p.last_mounted.as_deref() == Some("/"));
+    let linux = has_filesystem(&["ext2", "ext3", "ext4", "Linux swap", "LVM2", "LUKS1", "LUKS2"]);
+    let apple = has_filesystem(&["APFS", "HFS+", "Core Storage"]);
+
+    let platform = if windows && windows_boot {
+        "Windows"
+    } else if linux_root {
+        "Linux"
+    } else if apple {
+        "macOS"
+    } else if windows {
+        "Windows"
+    } else if linux {
+        "Linux"
+    } else {
+        return None;
+    };
+    Some(platform.to_string())
+}
+
+// =============================================================================
+// Helpers
+// =============================================================================
+
+fn u16_at(buf: &[u8], offset: usize) -> u16 {
+    buf.get(offset..offset + 2).map_or(0, |b| u16::from_le_bytes([b[0], b[1]]))
+}
+
+fn u32_at(buf: &[u8], offset: usize) -> u32 {
+    buf.get(offset..offset + 4).map_or(0, |b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+fn u64_at(buf: &[u8], offset: usize) -> u64 {
+    buf.get(offset..offset + 8).map_or(0, |b| u64::from_le_bytes(b.try_into().unwrap_or_default()))
+}
+
+/// Shannon entropy in bits per byte (8.0 for uniformly random data)
+fn entropy(buf: &[u8]) -> f64 {
+    let mut counts = [0u32; 256];
+    for byte in buf {
+        counts[*byte as usize] += 1;
+    }
+    let len = buf.len() as f64;
+    counts.iter()
+        .filter(|count| **count > 0)
+        .map(|count| {
+            let p = *count as f64 / len;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+fn looks_random(buf: &[u8]) -> bool {
+    buf.len() >= PROBE_SIZE && entropy(buf) >= RANDOM_ENTROPY
+}
+
+fn non_empty(value: String) -> Option<String> {
+    let value = value.trim().to_string();
+    (!value.is_empty()).then_some(value)
+}
+
+/// UTF-16LE up to the first NUL
+fn utf16_string(bytes: &[u8]) -> String {
+    let units: Vec<u16> = bytes.chunks_exact(2)
+        .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+        .take_while(|unit| *unit != 0)
+        .collect();
+    String::from_utf16_lossy(&units)
+}
+
+fn fat_label(bytes: &[u8]) -> Option<String> {
+    non_empty(String::from_utf8_lossy(bytes).to_string()).filter(|label| label != "NO NAME")
+}
+
+/// Serial in the XXXX-XXXX form Windows shows
+fn format_serial(serial: u32) -> String {
+    format!("{:04X}-{:04X}", serial >> 16, serial & 0xFFFF)
+}
+
+/// GUID with the first three fields little-endian (GPT, Microsoft)
+fn format_guid(b: &[u8]) -> String {
+    format!(
+        "{:02X}{:02X}{:02X}{:02X}-{:02X}{:02X}-{:02X}{:02X}-{:02X}{:02X}-{:02X}{:02X}{:02X}{:02X}{:02X}{:02X}",
+        b[3], b[2], b[1], b[0], b[5], b[4], b[7], b[6], b[8], b[9], b[10], b[11], b[12], b[13], b[14], b[15]
+    )
+}
+
+/// UUID stored in byte order (ext, APFS)
+fn format_uuid(b: &[u8]) -> String {
+    let hex = hex::encode(b);
+    format!("{}-{}-{}-{}-{}", &hex[..8], &hex[8..12], &hex[12..16], &hex[16..20], &hex[20..32])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MIB: usize = 1024 * 1024;
+
+    fn put(buf: &mut [u8], offset: usize, bytes: &[u8]) {
+        buf[offset..offset + bytes.len()].copy_from_slice(bytes);
+    }
+
+    fn mbr_entry(buf: &mut [u8], slot: usize, bootable: bool, partition_type: u8, start_lba: u32, sectors: u32) {
+        let entry = 446 + slot * 16;
+        buf[entry] = if bootable { 0x80 } else { 0 };
+        buf[entry + 4] = partition_type;
+        put(buf, entry + 8, &start_lba.to_le_bytes());
+        put(buf, entry + 12, &sectors.to_le_bytes());
+        put(buf, 510, &[0x55, 0xAA]);
+    }
+
+    /// NTFS boot sector plus a $Volume record carrying `label`
+    fn write_ntfs(disk: &mut [u8], offset: usize, label: &str) {
+        let boot = &mut disk[offset..];
+        put(boot, 3, b"NTFS    ");
+        put(boot, 11, &512u16.to_le_bytes());
+        boot[13] = 8; // 4 KiB clusters
+        put(boot, 48, &4u64.to_le_bytes()); // MFT at cluster 4
+        boot[64] = 0xF6; // 1 KiB records
+        put(boot, 72, &0x1234_ABCD_u64.to_le_bytes());
+
+        let record = &mut boot[4 * 4096 + 3 * 1024..][..1024];
+        put(record, 0, b"FILE");
+        put(record, 4, &48u16.to_le_bytes()); // update sequence array
+        put(record, 6, &3u16.to_le_bytes());
+        put(record, 48, &[0x07, 0x00, 0xAA, 0xBB, 0xCC, 0xDD]);
+        put(record, 510, &[0x07, 0x00]);
+        put(record, 1022, &[0x07, 0x00]);
+        put(record, 20, &56u16.to_le_bytes());
+
+        let name: Vec<u8> = label.encode_utf16().flat_map(u16::to_le_bytes).collect();
+        let name_attr_len = (24 + name.len() + 7) & !7;
+        let attr = &mut record[56..];
+        put(attr, 0, &0x60u32.to_le_bytes());
+        put(attr, 4, &(name_attr_len as u32).to_le_bytes());
+        put(attr, 16, &(name.len() as u32).to_le_bytes());
+        put(attr, 20, &24u16.to_le_bytes());
+        put(attr, 24, &name);
+        let attr = &mut attr[name_attr_len..];
+        put(attr, 0, &0x70u32.to_le_bytes());
+        put(attr, 4, &40u32.to_le_bytes());
+        put(attr, 16, &12u32.to_le_bytes());
+        put(attr, 20, &24u16.to_le_bytes());
+        put(attr, 24 + 8, &[3, 1]);
+        put(attr, 40, &0xFFFF_FFFFu32.to_le_bytes());
+    }
+
+    fn write_ext4(disk: &mut [u8], offset: usize, name: &str, mounted: &str) {
+        let sb = &mut disk[offset + 1024..];
+        put(sb, 56, &0xEF53u16.to_le_bytes());
+        put(sb, 92, &0x4u32.to_le_bytes());
+        put(sb, 96, &0x2C2u32.to_le_bytes());
+        put(sb, 104, &[0x5a; 16]);
+        put(sb, 120, name.as_bytes());
+        put(sb, 136, mounted.as_bytes());
+    }
+
+    fn identify_bytes(disk: &[u8]) -> TriageSummary {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("disk.dd");
+        std::fs::write(&path, disk).unwrap();
+        identify(path.to_str().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_mbr_with_ntfs_and_logical_ext4() {
+        let mut disk = vec![0u8; 8 * MIB];
+        mbr_entry(&mut disk, 0, true, 0x07, 2048, 4096);
+        mbr_entry(&mut disk, 1, false, 0x05, 8192, 8192);
+        // EBR at the start of the extended partition, logical partition 2048 sectors in
+        let ebr = &mut disk[8192 * 512..];
+        mbr_entry(ebr, 0, false, 0x83, 2048, 4096);
+        write_ntfs(&mut disk, 2048 * 512, "Windows");
+        write_ext4(&mut disk, (8192 + 2048) * 512, "rootfs", "/");
+
+        let summary = identify_bytes(&disk);
+        assert_eq!(summary.partition_scheme, "MBR");
+        assert_eq!(summary.partitions.len(), 2);
+
+        let ntfs = &summary.partitions[0];
+        assert_eq!((ntfs.index, ntfs.offset, ntfs.bootable), (1, 2048 * 512, true));
+        assert_eq!(ntfs.filesystem.as_deref(), Some("NTFS"));
+        assert_eq!(ntfs.volume_label.as_deref(), Some("Windows"));
+        assert_eq!(ntfs.volume_serial.as_deref(), Some("1234-ABCD"));
+        assert_eq!(ntfs.ntfs_version.as_deref(), Some("3.1"));
+
+        let ext = &summary.partitions[1];
+        assert_eq!((ext.index, ext.offset), (5, (8192 + 2048) * 512));
+        assert_eq!(ext.filesystem.as_deref(), Some("ext4"));
+        assert_eq!(ext.volume_label.as_deref(), Some("rootfs"));
+        assert_eq!(ext.last_mounted.as_deref(), Some("/"));
+        assert_eq!(ext.volume_uuid.as_deref(), Some("5a5a5a5a-5a5a-5a5a-5a5a-5a5a5a5a5a5a"));
+
+        // Active NTFS volume beats the Linux root on a dual-boot disk
+        assert_eq!(summary.guessed_platform.as_deref(), Some("Windows"));
+        assert!(summary.bytes_read <= MAX_TRIAGE_READ);
+        assert!(!summary.encryption_detected);
+    }
+
+    #[test]
+    fn test_gpt_with_apfs_and_unreadable_partition() {
+        let mut disk = vec![0u8; 4 * MIB];
+        mbr_entry(&mut disk, 0, false, 0xEE, 1, 8191);
+        let header = &mut disk[512..];
+        put(header, 0, b"EFI PART");
+        put(header, 72, &2u64.to_le_bytes());
+        put(header, 80, &128u32.to_le_bytes());
+        put(header, 84, &128u32.to_le_bytes());
+
+        let apfs_guid = [0xEF, 0x57, 0x34, 0x7C, 0x00, 0x00, 0xAA, 0x11, 0xAA, 0x11, 0x00, 0x30, 0x65, 0x43, 0xEC, 0xAC];
+        let entries = &mut disk[1024..];
+        put(entries, 0, &apfs_guid);
+        put(entries, 32, &2048u64.to_le_bytes());
+        put(entries, 40, &4095u64.to_le_bytes());
+        put(entries, 56, &"Macintosh HD".encode_utf16().flat_map(u16::to_le_bytes).collect::<Vec<_>>());
+        // Second entry points past the end of the image
+        put(entries, 128, &apfs_guid);
+        put(entries, 128 + 32, &100_000u64.to_le_bytes());
+        put(entries, 128 + 40, &200_000u64.to_le_bytes());
+
+        let container = &mut disk[2048 * 512..];
+        put(container, 32, b"NXSB");
+        put(container, 72, &[0x11; 16]);
+
+        let summary = identify_bytes(&disk);
+        assert_eq!(summary.partition_scheme, "GPT");
+        assert_eq!(summary.partitions.len(), 2);
+        let apfs = &summary.partitions[0];
+        assert_eq!(apfs.partition_type, "7C3457EF-0000-11AA-AA11-00306543ECAC");
+        assert_eq!(apfs.type_description.as_deref(), Some("Apple APFS"));
+        assert_eq!(apfs.name.as_deref(), Some("Macintosh HD"));
+        assert_eq!(apfs.size, 2048 * 512);
+        assert_eq!(apfs.filesystem.as_deref(), Some("APFS"));
+        assert_eq!(apfs.volume_uuid.as_deref(), Some("11111111-1111-1111-1111-111111111111"));
+        assert!(summary.partitions[1].error.is_some());
+        assert_eq!(summary.guessed_platform.as_deref(), Some("macOS"));
+    }
+
+    #[test]
+    fn test_unpartitioned_fat32_volume() {
+        let mut disk = vec![0u8; MIB];
+        put(&mut disk, 82, b"FAT32   ");
+        put(&mut disk, 71, b"EVIDENCE   ");
+        put(&mut disk, 67, &0xDEAD_BEEFu32.to_le_bytes());
+        put(&mut disk, 510, &[0x55, 0xAA]);
+
+        let summary = identify_bytes(&disk);
+        assert_eq!(summary.partition_scheme, "none");
+        let volume = &summary.partitions[0];
+        assert_eq!(volume.filesystem.as_deref(), Some("FAT32"));
+        assert_eq!(volume.volume_label.as_deref(), Some("EVIDENCE"));
+        assert_eq!(volume.volume_serial.as_deref(), Some("DEAD-BEEF"));
+        assert_eq!(summary.guessed_platform, None);
+
+        assert!(identify_bytes_err(&vec![0u8; MIB]).contains("No partition table"));
+    }
+
+    /// Deterministic random-looking bytes (xorshift)
+    fn noise(len: usize) -> Vec<u8> {
+        let mut state = 0x2545_F491_4F6C_DD1Du64;
+        (0..len).map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            (state >> 24) as u8
+        }).collect()
+    }
+
+    #[test]
+    fn test_encrypted_partitions_are_labelled() {
+        let mut disk = vec![0u8; 8 * MIB];
+        mbr_entry(&mut disk, 0, true, 0x07, 2048, 2048);
+        mbr_entry(&mut disk, 1, false, 0x0C, 4096, 2048);
+        mbr_entry(&mut disk, 2, false, 0x83, 6144, 2048);
+        mbr_entry(&mut disk, 3, false, 0x83, 8192, 2048);
+        put(&mut disk, 2048 * 512 + 3, b"-FVE-FS-");
+        // BitLocker To Go: FAT32 boot sector carrying the BitLocker GUID
+        let to_go = 4096 * 512;
+        put(&mut disk, to_go + 3, b"MSWIN4.1");
+        put(&mut disk, to_go + 82, b"FAT32   ");
+        put(&mut disk, to_go + 424, &BITLOCKER_GUID);
+        let luks = 6144 * 512;
+        put(&mut disk, luks, b"LUKS\xBA\xBE\x00\x02");
+        put(&mut disk, luks + 24, b"cryptroot");
+        put(&mut disk, luks + 168, b"0d6f3c2e-8a51-4f0e-9b7c-2e5d1a4b6c8f");
+        put(&mut disk, 8192 * 512, &noise(PROBE_SIZE));
+
+        let summary = identify_bytes(&disk);
+        assert!(summary.encryption_detected);
+        let findings: Vec<_> = summary.partitions.iter()
+            .map(|p| p.encryption.as_ref().map(|e| (e.scheme.as_str(), e.heuristic)))
+            .collect();
+        assert_eq!(findings, [Some(("BitLocker", false)), Some(("BitLocker", false)), Some(("LUKS2", false)), Some(("unknown", true))]);
+        assert_eq!(summary.partitions[0].encryption.as_ref().unwrap().description, "BitLocker protected volume");
+        assert_eq!(summary.partitions[1].filesystem.as_deref(), Some("BitLocker"));
+        let luks = &summary.partitions[2];
+        assert_eq!(luks.volume_label.as_deref(), Some("cryptroot"));
+        assert_eq!(luks.volume_uuid.as_deref(), Some("0d6f3c2e-8a51-4f0e-9b7c-2e5d1a4b6c8f"));
+        assert_eq!(summary.partitions[3].filesystem, None);
+    }
+
+    #[test]
+    fn test_filevault_container_and_whole_disk_random_image() {
+        let mut buf = vec![0u8; PROBE_SIZE];
+        put(&mut buf, 32, b"NXSB");
+        assert_eq!(detect_encryption(&buf, probe_filesystem(&buf).as_ref()), None);
+        put(&mut buf, 1304, &1u64.to_le_bytes()); // one keybag block
+        let finding = detect_encryption(&buf, probe_filesystem(&buf).as_ref()).unwrap();
+        assert_eq!((finding.scheme.as_str(), finding.heuristic), ("FileVault", false));
+
+        // No table and no filesystem, but random from the first byte
+        let summary = identify_bytes(&noise(MIB));
+        assert_eq!(summary.partition_scheme, "none");
+        assert!(summary.encryption_detected);
+        assert!(summary.partitions[0].encryption.as_ref().unwrap().description.starts_with("Possibly encrypted/random"));
+    }
+
+    fn identify_bytes_err(disk: &[u8]) -> String {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("blank.dd");
+        std::fs::write(&path, disk).unwrap();
+        identify(path.to_str().unwrap()).unwrap_err()
+    }
+}
@@ -8,6 +8,7 @@ use std::io::Read;
 use tracing::debug;
 
 use super::types::UFED_EXTENSIONS;
+use crate::archive::zip::{open_entry, read_error};
 
 /// Check if a filename has a UFED extension
 pub fn is_ufed_file(filename: &str) -> bool {
@@ -18,9 +19,11 @@ pub fn is_ufed_file(filename: &str) -> bool {
 /// Detect UFED files (UFDR/UFDX/UFD) inside a ZIP archive
 /// 
 /// Also checks nested ZIPs (one level deep) that might contain UFED files.
+/// Entry names are listed without decrypting anything; `password` is only
+/// needed to look inside encrypted nested ZIPs, which are skipped without it.
 /// 
 /// Returns: (detected, list of UFED file paths found)
-pub fn detect_in_zip(path: &str) -> Result<(bool, Vec<String>), String> {
+pub fn detect_in_zip(path: &str, password: Option<&str>) -> Result<(bool, Vec<String>), String> {
     let file = File::open(path)
         .map_err(|e| format!("Failed to open ZIP: {e}"))?;
     
@@ -32,7 +35,7 @@ pub fn detect_in_zip(path: &str) -> Result<(bool, Vec<String>), String> {
     
     // First pass: scan all entries in the archive
     for i in 0..archive.len() {
-        if let Ok(entry) = archive.by_index(i) {
+        if let Ok(entry) = archive.by_index_raw(i) {
             let name = entry.name().to_string();
             let lower_name = name.to_lowercase();
             
@@ -51,7 +54,7 @@ pub fn detect_in_zip(path: &str) -> Result<(bool, Vec<String>), String> {
     
     // Second pass: check inside nested ZIPs (one level deep)
     for nested_zip_name in &nested_zips {
-        if let Ok(nested_files) = scan_nested_zip(&mut archive, nested_zip_name) {
+        if let Ok(nested_files) = scan_nested_zip(&mut archive, nested_zip_name, password) {
             for nested_file in nested_files {
                 let full_path = format!("{}/{}", nested_zip_name, nested_file);
                 debug!(path = %path, entry = %full_path, "Found UFED file in nested ZIP");
@@ -78,6 +81,7 @@ pub fn detect_in_zip(path: &str) -> Result<(bool, Vec<String>), String> {
 fn scan_nested_zip(
     parent_archive: &mut zip::ZipArchive<File>,
     nested_zip_name: &str,
+    password: Option<&str>,
 ) -> Result<Vec<String>, String> {
     use std::io::Cursor;
     
@@ -85,8 +89,9 @@ fn scan_nested_zip(
     
     // Extract the nested ZIP to memory
     let nested_data = {
-        let mut entry = parent_archive.by_name(nested_zip_name)
-            .map_err(|e| format!("Failed to read nested ZIP {}: {e}", nested_zip_name))?;
+        let index = parent_archive.index_for_name(nested_zip_name)
+            .ok_or_else(|| format!("Nested ZIP {} not found", nested_zip_name))?;
+        let mut entry = open_entry(parent_archive, index, password)?;
         
         // Limit nested ZIP size to prevent memory issues (100MB max)
         let size = entry.size();
@@ -97,7 +102,7 @@ fn scan_nested_zip(
         
         let mut data = Vec::with_capacity(size as usize);
         entry.read_to_end(&mut data)
-            .map_err(|e| format!("Failed to extract nested ZIP: {e}"))?;
+            .map_err(|e| read_error(nested_zip_name, e))?;
         data
     };
     
@@ -113,7 +118,7 @@ fn scan_nested_zip(
     
     // Scan nested archive entries
     for i in 0..nested_archive.len() {
-        if let Ok(entry) = nested_archive.by_index(i) {
+        if let Ok(entry) = nested_archive.by_index_raw(i) {
             let name = entry.name().to_string();
             if is_ufed_file(&name) {
                 ufed_files.push(name);
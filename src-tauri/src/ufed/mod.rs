@@ -35,7 +35,8 @@
 //! ├── detection.rs   - Format detection, is_ufed(), device hints
 //! ├── parsing.rs     - UFD (INI) and UFDX (XML) parsers
 //! ├── collection.rs  - Extraction sets, associated files
-//! └── archive_scan.rs - UFED detection inside ZIP archives
+//! ├── archive_scan.rs - UFED detection inside ZIP archives
+//! └── zip_verify.rs  - Per-file hash verification of UFED ZIPs in place
 //! ```
 
 pub mod types;
@@ -43,18 +44,33 @@ pub mod detection;
 pub mod parsing;
 pub mod collection;
 pub mod archive_scan;
+pub mod zip_verify;
 
 // Re-exports for convenience
 pub use types::{
     UfedFormat, UfedInfo, CaseInfo, DeviceInfo, ExtractionInfo,
-    StoredHash, AssociatedFile, CollectionInfo, UFED_EXTENSIONS,
+    StoredHash, AssociatedFile, CollectionInfo, UfdContents, UfdSections, UFED_EXTENSIONS,
+    ZipEntryHashResult, ZipVerifyReport,
 };
 pub use detection::{is_ufed, detect_format, is_ufed_file, find_sibling_ufd};
 pub use archive_scan::detect_in_zip;
+pub use zip_verify::verify_zip_contents;
 
 use std::path::Path;
 use tracing::{debug, instrument};
 
+use crate::common::capabilities::FormatCapabilities;
+
+/// UFD/UFDR metadata, and the files in an extraction ZIP checked against
+/// the hashes its UFD or report.xml stores
+pub const fn capabilities() -> FormatCapabilities {
+    FormatCapabilities {
+        can_verify_items: true,
+        notes: &["Extractions are usually already unpacked; UFED containers are not extracted"],
+        ..FormatCapabilities::INFO_ONLY
+    }
+}
+
 /// Get UFED container information
 #[instrument]
 pub fn info(path: &str) -> Result<UfedInfo, String> {
@@ -89,26 +105,16 @@ pub fn info(path: &str) -> Result<UfedInfo, String> {
     // Parse UFD file contents:
     // - If it's a .ufd file, parse it directly
     // - If it's a UFED ZIP, find and parse the sibling .ufd file
-    let (case_info, device_info, extraction_info, stored_hashes) = match format {
+    let ufd = match format {
         UfedFormat::Ufd => parsing::parse_ufd_file(path)?,
-        UfedFormat::UfedZip => {
-            // Find sibling UFD and parse it
-            if let Some(ufd_path) = detection::find_sibling_ufd(path) {
-                if ufd_path.exists() {
-                    if let Some(ufd_str) = ufd_path.to_str() {
-                        parsing::parse_ufd_file(ufd_str).unwrap_or((None, None, None, None))
-                    } else {
-                        (None, None, None, None)
-                    }
-                } else {
-                    (None, None, None, None)
-                }
-            } else {
-                (None, None, None, None)
-            }
-        }
-        _ => (None, None, None, None),
+        // Find sibling UFD and parse it
+        UfedFormat::UfedZip => detection::find_sibling_ufd(path)
+            .filter(|ufd_path| ufd_path.exists())
+            .and_then(|ufd_path| parsing::parse_ufd_file(ufd_path.to_str()?).ok())
+            .unwrap_or_default(),
+        _ => UfdContents::default(),
     };
+    let UfdContents { case_info, device_info, extraction_info, stored_hashes, raw_sections } = ufd;
     
     // Find associated files in the same directory (with hash info)
     let associated_files = collection::find_associated_files(path_obj, stored_hashes.as_ref());
@@ -144,5 +150,6 @@ pub fn info(path: &str) -> Result<UfedInfo, String> {
         stored_hashes,
         evidence_number,
         collection_info,
+        raw_sections,
     })
 }
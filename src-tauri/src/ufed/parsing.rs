@@ -2,12 +2,36 @@
 //!
 //! Parsers for UFD (INI format) and UFDX (XML format) metadata files.
 
-use std::collections::HashMap;
-use std::fs::File;
-use std::io::{BufRead, BufReader};
+use std::collections::BTreeMap;
 use std::path::Path;
 
-use super::types::{CaseInfo, CollectionInfo, DeviceInfo, ExtractionInfo, StoredHash};
+use super::types::{CaseInfo, CollectionInfo, DeviceInfo, ExtractionInfo, StoredHash, UfdContents, UfdSections};
+use crate::common::hash::HashValue;
+use crate::common::time::Timestamp;
+
+/// Section names used by different UFED versions for the modelled sections
+const CASE_SECTIONS: &[&str] = &["Crime Case", "Case Information", "Case"];
+const DEVICE_SECTIONS: &[&str] = &["DeviceInfo", "Device"];
+const GENERAL_SECTIONS: &[&str] = &["General"];
+
+/// Key names used by different UFED versions for the same field
+///
+/// Lookups ignore case, spaces, underscores and punctuation, so only real
+/// synonyms need listing here.
+const CASE_IDENTIFIER: &[&str] = &["Case Identifier", "Case Number", "Case ID"];
+const CRIME_TYPE: &[&str] = &["Crime Type", "Offense"];
+const DEPARTMENT: &[&str] = &["Department", "Agency", "Organization"];
+const EVIDENCE_NUMBER: &[&str] = &["Device Name / Evidence Number", "Evidence Number", "Device Name"];
+const EXAMINER: &[&str] = &["Examiner Name", "Examiner"];
+const LOCATION: &[&str] = &["Location"];
+const VENDOR: &[&str] = &["Vendor", "Manufacturer"];
+const FULL_NAME: &[&str] = &["FullName", "Device Full Name"];
+const IMEI: &[&str] = &["IMEI1", "IMEI"];
+const OS_VERSION: &[&str] = &["OS", "OS Version", "Operating System"];
+const SERIAL_NUMBER: &[&str] = &["SerialNumber", "Serial"];
+const TOOL_VERSION: &[&str] = &["Version", "Tool Version", "UFED Version"];
+const START_TIME: &[&str] = &["Date", "Start Time", "Extraction Start"];
+const END_TIME: &[&str] = &["EndTime", "Extraction End"];
 
 /// Parse UFD file (INI-style format) and extract metadata
 /// 
@@ -16,77 +40,69 @@ use super::types::{CaseInfo, CollectionInfo, DeviceInfo, ExtractionInfo, StoredH
 /// - `[DeviceInfo]`: IMEI, model, OS version, vendor
 /// - `[General]`: Acquisition tool, extraction type, timestamps
 /// - `[SHA256]`, `[SHA1]`, `[MD5]`: Hash values for extraction files
-pub fn parse_ufd_file(path: &str) -> Result<(Option<CaseInfo>, Option<DeviceInfo>, Option<ExtractionInfo>, Option<Vec<StoredHash>>), String> {
-    let file = File::open(path)
+///
+/// Every section, including ones not modelled here (`[Dongle]`, `[Client]`,
+/// ...), is kept in `raw_sections`. UTF-8 and UTF-16 (with BOM) files are read.
+pub fn parse_ufd_file(path: &str) -> Result<UfdContents, String> {
+    let bytes = std::fs::read(path)
         .map_err(|e| format!("Failed to open UFD file: {e}"))?;
+    Ok(parse_ufd(&decode_text(&bytes)))
+}
+
+/// Parse the text of a UFD file
+pub fn parse_ufd(text: &str) -> UfdContents {
+    let mut sections = UfdSections::new();
+    let mut current_section: Option<String> = None;
     
-    let reader = BufReader::new(file);
-    let mut sections: HashMap<String, HashMap<String, String>> = HashMap::new();
-    let mut current_section = String::new();
-    
-    for line in reader.lines() {
-        let line = line.map_err(|e| format!("Failed to read UFD file: {e}"))?;
+    for line in text.lines() {
         let line = line.trim();
         
-        // Skip empty lines
-        if line.is_empty() {
+        // Skip empty lines and comments
+        if line.is_empty() || line.starts_with(';') {
             continue;
         }
         
         // Check for section header [SectionName]
         if line.starts_with('[') && line.ends_with(']') {
-            current_section = line[1..line.len()-1].to_string();
-            sections.entry(current_section.clone()).or_default();
+            let name = line[1..line.len()-1].trim().to_string();
+            sections.entry(name.clone()).or_default();
+            current_section = Some(name);
             continue;
         }
         
         // Parse key=value pairs
-        if let Some(eq_pos) = line.find('=') {
-            let key = line[..eq_pos].trim().to_string();
-            let value = line[eq_pos+1..].trim().to_string();
-            
-            if !current_section.is_empty() {
-                sections.get_mut(&current_section)
-                    .map(|s| s.insert(key, value));
+        if let (Some(section), Some((key, value))) = (&current_section, line.split_once('=')) {
+            if let Some(entries) = sections.get_mut(section) {
+                entries.insert(key.trim().to_string(), value.trim().to_string());
             }
         }
     }
     
     // Extract Case Info from [Crime Case] section
-    let case_info = sections.get("Crime Case").map(|s| CaseInfo {
-        case_identifier: s.get("Case Identifier").cloned().filter(|v| !v.is_empty()),
-        crime_type: s.get("Crime Type").cloned().filter(|v| !v.is_empty()),
-        department: s.get("Department").cloned().filter(|v| !v.is_empty()),
-        device_name: s.get("Device Name / Evidence Number").cloned().filter(|v| !v.is_empty()),
-        examiner_name: s.get("Examiner Name").cloned().filter(|v| !v.is_empty()),
-        location: s.get("Location").cloned().filter(|v| !v.is_empty()),
+    let case_section = find_section(&sections, CASE_SECTIONS);
+    let case_info = case_section.map(|s| CaseInfo {
+        case_identifier: field(Some(s), CASE_IDENTIFIER),
+        crime_type: field(Some(s), CRIME_TYPE),
+        department: field(Some(s), DEPARTMENT),
+        device_name: field(Some(s), EVIDENCE_NUMBER),
+        examiner_name: field(Some(s), EXAMINER),
+        location: field(Some(s), LOCATION),
     });
     
     // Extract Device Info from [DeviceInfo] and [General] sections
-    let device_section = sections.get("DeviceInfo");
-    let general_section = sections.get("General");
+    let device_section = find_section(&sections, DEVICE_SECTIONS);
+    let general_section = find_section(&sections, GENERAL_SECTIONS);
     
     let device_info = if device_section.is_some() || general_section.is_some() {
         Some(DeviceInfo {
-            vendor: general_section.and_then(|s| s.get("Vendor").cloned())
-                .or_else(|| device_section.and_then(|s| s.get("Vendor").cloned()))
-                .filter(|v| !v.is_empty()),
-            model: device_section.and_then(|s| s.get("Model").cloned())
-                .filter(|v| !v.is_empty()),
-            full_name: general_section.and_then(|s| s.get("FullName").cloned())
-                .or_else(|| general_section.and_then(|s| s.get("Model").cloned()))
-                .filter(|v| !v.is_empty()),
-            imei: device_section.and_then(|s| s.get("IMEI1").cloned())
-                .or_else(|| device_section.and_then(|s| s.get("IMEI").cloned()))
-                .filter(|v| !v.is_empty()),
-            imei2: device_section.and_then(|s| s.get("IMEI2").cloned())
-                .filter(|v| !v.is_empty()),
-            iccid: device_section.and_then(|s| s.get("ICCID").cloned())
-                .filter(|v| !v.is_empty()),
-            os_version: device_section.and_then(|s| s.get("OS").cloned())
-                .filter(|v| !v.is_empty()),
-            serial_number: device_section.and_then(|s| s.get("SerialNumber").cloned())
-                .filter(|v| !v.is_empty()),
+            vendor: field(general_section, VENDOR).or_else(|| field(device_section, VENDOR)),
+            model: field(device_section, &["Model"]),
+            full_name: field(general_section, FULL_NAME).or_else(|| field(general_section, &["Model"])),
+            imei: field(device_section, IMEI),
+            imei2: field(device_section, &["IMEI2"]),
+            iccid: field(device_section, &["ICCID"]),
+            os_version: field(device_section, OS_VERSION),
+            serial_number: field(device_section, SERIAL_NUMBER),
         })
     } else {
         None
@@ -94,27 +110,27 @@ pub fn parse_ufd_file(path: &str) -> Result<(Option<CaseInfo>, Option<DeviceInfo
     
     // Extract Extraction Info from [General] section
     let extraction_info = general_section.map(|s| ExtractionInfo {
-        acquisition_tool: s.get("AcquisitionTool").cloned().filter(|v| !v.is_empty()),
-        tool_version: s.get("Version").cloned().filter(|v| !v.is_empty()),
-        unit_id: s.get("UnitId").cloned().filter(|v| !v.is_empty()),
-        extraction_type: s.get("ExtractionType").cloned().filter(|v| !v.is_empty()),
-        connection_type: s.get("ConnectionType").cloned().filter(|v| !v.is_empty()),
-        start_time: s.get("Date").cloned().filter(|v| !v.is_empty()),
-        end_time: s.get("EndTime").cloned().filter(|v| !v.is_empty()),
-        guid: s.get("GUID").cloned().filter(|v| !v.is_empty()),
-        machine_name: s.get("MachineName").cloned().filter(|v| !v.is_empty()),
+        acquisition_tool: field(Some(s), &["AcquisitionTool"]),
+        tool_version: field(Some(s), TOOL_VERSION),
+        unit_id: field(Some(s), &["UnitId"]),
+        extraction_type: field(Some(s), &["ExtractionType"]),
+        connection_type: field(Some(s), &["ConnectionType"]),
+        start_time: field(Some(s), START_TIME).map(|t| Timestamp::parse(&t)),
+        end_time: field(Some(s), END_TIME).map(|t| Timestamp::parse(&t)),
+        guid: field(Some(s), &["GUID"]),
+        machine_name: field(Some(s), &["MachineName"]),
     });
     
     // Extract stored hashes from [SHA256], [SHA1], [MD5] sections
     let mut stored_hashes = Vec::new();
     
-    for (algo, section_name) in [("SHA256", "SHA256"), ("SHA1", "SHA1"), ("MD5", "MD5")] {
-        if let Some(section) = sections.get(section_name) {
+    for algo in ["SHA256", "SHA1", "MD5"] {
+        if let Some(section) = find_section(&sections, &[algo]) {
             for (filename, hash) in section.iter() {
                 stored_hashes.push(StoredHash {
                     filename: filename.clone(),
                     algorithm: algo.to_string(),
-                    hash: hash.clone(),
+                    hash: HashValue::parse_named(hash, algo).map_or_else(|_| hash.clone(), String::from),
                 });
             }
         }
@@ -122,7 +138,53 @@ pub fn parse_ufd_file(path: &str) -> Result<(Option<CaseInfo>, Option<DeviceInfo
     
     let stored_hashes = if stored_hashes.is_empty() { None } else { Some(stored_hashes) };
     
-    Ok((case_info, device_info, extraction_info, stored_hashes))
+    UfdContents {
+        case_info,
+        device_info,
+        extraction_info,
+        stored_hashes,
+        raw_sections: sections,
+    }
+}
+
+/// Decode UFD bytes: UTF-16 with a BOM (or NUL-interleaved ASCII without
+/// one), otherwise UTF-8 with an optional BOM
+fn decode_text(bytes: &[u8]) -> String {
+    let utf16 = |data: &[u8], from: fn([u8; 2]) -> u16| {
+        let units: Vec<u16> = data.chunks_exact(2).map(|pair| from([pair[0], pair[1]])).collect();
+        String::from_utf16_lossy(&units)
+    };
+    match bytes {
+        [0xFF, 0xFE, rest @ ..] => utf16(rest, u16::from_le_bytes),
+        [0xFE, 0xFF, rest @ ..] => utf16(rest, u16::from_be_bytes),
+        [0xEF, 0xBB, 0xBF, rest @ ..] => String::from_utf8_lossy(rest).into_owned(),
+        [first, 0, ..] if *first != 0 => utf16(bytes, u16::from_le_bytes),
+        _ => String::from_utf8_lossy(bytes).into_owned(),
+    }
+}
+
+/// Lowercase alphanumerics only, so "Serial Number" matches "SerialNumber"
+fn normalize_key(key: &str) -> String {
+    key.chars().filter(|c| c.is_alphanumeric()).flat_map(char::to_lowercase).collect()
+}
+
+/// First section matching one of `names`
+fn find_section<'a>(sections: &'a UfdSections, names: &[&str]) -> Option<&'a BTreeMap<String, String>> {
+    names.iter().find_map(|name| {
+        let wanted = normalize_key(name);
+        sections.iter().find(|(section, _)| normalize_key(section) == wanted).map(|(_, entries)| entries)
+    })
+}
+
+/// First non-empty value of a key matching one of `names`
+fn field(section: Option<&BTreeMap<String, String>>, names: &[&str]) -> Option<String> {
+    let section = section?;
+    names.iter().find_map(|name| {
+        let wanted = normalize_key(name);
+        section.iter()
+            .find(|(key, value)| !value.is_empty() && normalize_key(key) == wanted)
+            .map(|(_, value)| value.clone())
+    })
 }
 
 /// Parse EvidenceCollection.ufdx (XML format)
@@ -165,3 +227,53 @@ pub fn extract_xml_attr(content: &str, attr_name: &str) -> Option<String> {
     let value_end = content[value_start..].find('"')? + value_start;
     Some(content[value_start..value_end].to_string())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_utf16_ufd_is_decoded() {
+        let text = "[Crime Case]\r\nCase Identifier=2024-117\r\n[DeviceInfo]\r\nIMEI1=356938035643809\r\n";
+        let mut bytes = vec![0xFF, 0xFE];
+        bytes.extend(text.encode_utf16().flat_map(u16::to_le_bytes));
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("phone.ufd");
+        std::fs::write(&path, bytes).unwrap();
+
+        let contents = parse_ufd_file(path.to_str().unwrap()).unwrap();
+        assert_eq!(contents.case_info.unwrap().case_identifier.as_deref(), Some("2024-117"));
+        assert_eq!(contents.device_info.unwrap().imei.as_deref(), Some("356938035643809"));
+    }
+
+    #[test]
+    fn test_unknown_sections_and_key_variants_are_kept() {
+        let contents = parse_ufd(concat!(
+            "[crime case]\n",
+            "case number=CN-9\n",
+            "EXAMINER NAME=J. Doe\n",
+            "[Dongle]\n",
+            "DongleId=AB-1234\n",
+            "[General]\n",
+            "Acquisition Tool=UFED 4PC\n",
+            "Extraction Type=AdvancedLogical\n",
+            "Date=2024-03-05 09:41:17\n",
+            "[sha256]\n",
+            "phone.zip=9F86D081884C7D659A2FEAA0C55AD015A3BF4F1B2B0B822CD15D6C15B0F00A08\n",
+        ));
+
+        let case = contents.case_info.unwrap();
+        assert_eq!(case.case_identifier.as_deref(), Some("CN-9"));
+        assert_eq!(case.examiner_name.as_deref(), Some("J. Doe"));
+        let extraction = contents.extraction_info.unwrap();
+        assert_eq!(extraction.acquisition_tool.as_deref(), Some("UFED 4PC"));
+        assert_eq!(extraction.extraction_type.as_deref(), Some("AdvancedLogical"));
+        let started = extraction.start_time.unwrap();
+        assert_eq!((started.iso_utc.as_str(), started.original.as_str()), ("2024-03-05T09:41:17Z", "2024-03-05 09:41:17"));
+        assert!(!started.timezone_known);
+        let hashes = contents.stored_hashes.unwrap();
+        assert_eq!((hashes[0].filename.as_str(), hashes[0].algorithm.as_str()), ("phone.zip", "SHA256"));
+        assert_eq!(contents.raw_sections["Dongle"]["DongleId"], "AB-1234");
+        assert_eq!(contents.raw_sections.len(), 4);
+    }
+}
@@ -3,8 +3,12 @@
 //! Types for Universal Forensic Extraction Data (UFED) container formats
 //! commonly used in mobile device forensics.
 
+use std::collections::BTreeMap;
+
 use serde::Serialize;
 
+use crate::common::time::Timestamp;
+
 /// UFED file extensions for detection
 pub const UFED_EXTENSIONS: &[&str] = &[".ufdr", ".ufdx", ".ufd"];
 
@@ -65,6 +69,22 @@ pub struct UfedInfo {
     /// Collection-level info from EvidenceCollection.ufdx
     #[serde(skip_serializing_if = "Option::is_none")]
     pub collection_info: Option<CollectionInfo>,
+    /// Every section and key of the UFD file, including unmodelled ones
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub raw_sections: UfdSections,
+}
+
+/// Parsed UFD file: section name -> key -> value
+pub type UfdSections = BTreeMap<String, BTreeMap<String, String>>;
+
+/// Everything read from a UFD file
+#[derive(Debug, Clone, Default)]
+pub struct UfdContents {
+    pub case_info: Option<CaseInfo>,
+    pub device_info: Option<DeviceInfo>,
+    pub extraction_info: Option<ExtractionInfo>,
+    pub stored_hashes: Option<Vec<StoredHash>>,
+    pub raw_sections: UfdSections,
 }
 
 /// Collection-level information from EvidenceCollection.ufdx
@@ -140,9 +160,9 @@ pub struct ExtractionInfo {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub connection_type: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub start_time: Option<String>,
+    pub start_time: Option<Timestamp>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub end_time: Option<String>,
+    pub end_time: Option<Timestamp>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub guid: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -167,3 +187,31 @@ pub struct AssociatedFile {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub stored_hash: Option<String>,
 }
+
+/// Hash check of one ZIP entry against its stored hash
+#[derive(Debug, Clone, Serialize)]
+pub struct ZipEntryHashResult {
+    /// Entry name inside the ZIP
+    pub path: String,
+    pub size: u64,
+    pub algorithm: String,
+    pub expected: String,
+    pub computed: String,
+    pub verified: bool,
+}
+
+/// Result of verifying a UFED ZIP's contents in place
+#[derive(Debug, Clone, Serialize)]
+pub struct ZipVerifyReport {
+    pub zip_path: String,
+    /// UFD or report.xml the stored hashes came from
+    pub hash_source: String,
+    /// One result per entry with a stored hash, in ZIP order
+    pub results: Vec<ZipEntryHashResult>,
+    pub verified: usize,
+    pub mismatched: usize,
+    /// Files in the ZIP with no stored hash
+    pub zip_entries_without_hash: usize,
+    /// Stored hashes with no matching file in the ZIP
+    pub stored_hashes_without_entry: usize,
+}
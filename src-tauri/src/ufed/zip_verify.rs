@@ -0,0 +1,431 @@
+//! In-place verification of UFED ZIP extractions
+//!
+//! Advanced logical extractions ship as a ZIP with per-file hashes recorded
+//! in the UFD or in the extraction's `report.xml`. Each ZIP entry with a
+//! stored hash is decompressed straight into a hasher - nothing is written
+//! to disk and memory stays at one read buffer regardless of entry size.
+
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read};
+use std::path::Path;
+
+use quick_xml::events::Event;
+use quick_xml::reader::Reader;
+use tracing::{debug, instrument, warn};
+
+use super::parsing;
+use super::types::{ZipEntryHashResult, ZipVerifyReport};
+use crate::archive::zip::{open_entry, read_error};
+use crate::common::hash::{hashes_match, HashValue, StreamingHasher};
+
+/// Read buffer per entry; the only allocation that scales with data read
+const CHUNK_SIZE: usize = 1024 * 1024;
+
+/// Stored hash algorithms, weakest first; the strongest one stored per file is checked
+const ALGORITHMS: &[&str] = &["MD5", "SHA1", "SHA256"];
+
+/// Hash of one file as recorded by the extraction
+#[derive(Debug, Clone)]
+struct ExpectedHash {
+    algorithm: &'static str,
+    hash: String,
+}
+
+/// Expected hashes keyed by normalized path
+type ExpectedHashes = HashMap<String, ExpectedHash>;
+
+/// Verify every ZIP entry that has a stored hash without unpacking the ZIP
+///
+/// `ufd_path` is the extraction's UFD, or a `report.xml` directly. When the
+/// UFD only lists hashes of the extraction files themselves, the per-file
+/// hashes are read from the `report.xml` inside the ZIP.
+///
+/// Entries are matched to stored hashes by path, ignoring case, separator
+/// style and a leading `/`; an entry also matches with its top-level folder
+/// removed, since UFED often wraps the file system in one. `progress`
+/// receives bytes hashed and the total size of the matched entries.
+///
+/// Encrypted ZIPs (ZipCrypto or WinZip AES) need `password`; a wrong one is
+/// reported as [`WrongPassword`](crate::archive::ZipEntryError::WrongPassword)
+/// before anything is hashed.
+#[instrument(skip(password, progress))]
+pub fn verify_zip_contents<F>(zip_path: &str, ufd_path: &str, password: Option<&str>, mut progress: F) -> Result<ZipVerifyReport, String>
+where
+    F: FnMut(u64, u64),
+{
+    let file = File::open(zip_path).map_err(|e| format!("Failed to open ZIP: {e}"))?;
+    let mut archive = zip::ZipArchive::new(file).map_err(|e| format!("Failed to read ZIP archive: {e}"))?;
+    check_password(&mut archive, password)?;
+
+    let zip_name = Path::new(zip_path).file_name().and_then(|n| n.to_str()).unwrap_or_default();
+    let (hash_source, expected) = load_expected_hashes(ufd_path, &mut archive, zip_name, password)?;
+    debug!(zip = %zip_path, source = %hash_source, stored = expected.len(), "Loaded stored file hashes");
+
+    // Match entries first so progress knows the total
+    let mut matched = Vec::new();
+    let mut zip_entries_without_hash = 0;
+    for index in 0..archive.len() {
+        let entry = archive.by_index_raw(index).map_err(|e| format!("Failed to read ZIP entry {index}: {e}"))?;
+        if entry.is_dir() {
+            continue;
+        }
+        match lookup(&expected, entry.name()) {
+            Some(key) => matched.push((index, key, entry.size())),
+            None => zip_entries_without_hash += 1,
+        }
+    }
+
+    let total: u64 = matched.iter().map(|(_, _, size)| size).sum();
+    let mut done = 0u64;
+    let mut buffer = vec![0u8; CHUNK_SIZE];
+    let mut results = Vec::with_capacity(matched.len());
+    let mut hashed_keys = HashSet::new();
+    for (index, key, _) in matched {
+        let stored = &expected[&key];
+        let mut entry = open_entry(&mut archive, index, password)?;
+        let path = entry.name().to_string();
+        let mut hasher = StreamingHasher::from_str(stored.algorithm)?;
+        let mut size = 0u64;
+        loop {
+            let n = entry.read(&mut buffer).map_err(|e| read_error(&path, e))?;
+            if n == 0 {
+                break;
+            }
+            hasher.update(&buffer[..n]);
+            size += n as u64;
+            done += n as u64;
+            progress(done, total);
+        }
+        let computed = hasher.finalize();
+        results.push(ZipEntryHashResult {
+            path,
+            size,
+            algorithm: stored.algorithm.to_string(),
+            verified: hashes_match(&computed, &stored.hash),
+            expected: stored.hash.clone(),
+            computed,
+        });
+        hashed_keys.insert(key);
+    }
+
+    let verified = results.iter().filter(|r| r.verified).count();
+    Ok(ZipVerifyReport {
+        zip_path: zip_path.to_string(),
+        hash_source,
+        mismatched: results.len() - verified,
+        verified,
+        zip_entries_without_hash,
+        stored_hashes_without_entry: expected.len() - hashed_keys.len(),
+        results,
+    })
+}
+
+/// Fail before hashing anything if an entry needs a password that was not
+/// given, or the given one does not open the first encrypted entry
+fn check_password<R: Read + std::io::Seek>(archive: &mut zip::ZipArchive<R>, password: Option<&str>) -> Result<(), String> {
+    for index in 0..archive.len() {
+        let entry = archive.by_index_raw(index).map_err(|e| format!("Failed to read ZIP entry {index}: {e}"))?;
+        if entry.encrypted() {
+            drop(entry);
+            return open_entry(archive, index, password).map(|_| ()).map_err(String::from);
+        }
+    }
+    Ok(())
+}
+
+/// Per-file hashes from a report.xml, the UFD, or the report.xml in the ZIP;
+/// returns a description of the source with the hashes
+fn load_expected_hashes<R: Read + std::io::Seek>(
+    ufd_path: &str,
+    archive: &mut zip::ZipArchive<R>,
+    zip_name: &str,
+    password: Option<&str>,
+) -> Result<(String, ExpectedHashes), String> {
+    if ufd_path.to_lowercase().ends_with(".xml") {
+        let file = File::open(ufd_path).map_err(|e| format!("Failed to open {ufd_path}: {e}"))?;
+        return Ok((ufd_path.to_string(), parse_report_xml(BufReader::new(file))?));
+    }
+
+    let ufd_dir = Path::new(ufd_path).parent().unwrap_or(Path::new(""));
+    let mut expected = ExpectedHashes::new();
+    for stored in parsing::parse_ufd_file(ufd_path)?.stored_hashes.unwrap_or_default() {
+        // The UFD's hashes of the ZIP and the other extraction files beside it
+        // are not hashes of files inside the ZIP
+        if stored.filename.eq_ignore_ascii_case(zip_name) || ufd_dir.join(&stored.filename).is_file() {
+            continue;
+        }
+        insert_stronger(&mut expected, &stored.filename, &stored.algorithm, &stored.hash);
+    }
+    if !expected.is_empty() {
+        return Ok((ufd_path.to_string(), expected));
+    }
+
+    let report_index = (0..archive.len()).find(|&i| {
+        archive.name_for_index(i).is_some_and(|name| {
+            name.rsplit(['/', '\\']).next().is_some_and(|file| file.eq_ignore_ascii_case("report.xml"))
+        })
+    });
+    let Some(index) = report_index else {
+        return Err(format!("No per-file hashes in {ufd_path} and no report.xml in the ZIP"));
+    };
+    let entry = open_entry(archive, index, password)?;
+    let source = format!("{} (in ZIP)", entry.name());
+    let expected = parse_report_xml(BufReader::new(entry))?;
+    if expected.is_empty() {
+        return Err(format!("No per-file hashes in {ufd_path} or {source}"));
+    }
+    Ok((source, expected))
+}
+
+/// Parse `<file path="...">` elements of a Cellebrite report.xml
+///
+/// ```xml
+/// <file path="/private/var/mobile/Media/DCIM/IMG_0001.JPG" size="1024" ...>
+///   <metadata section="File">
+///     <item name="Local Path"><![CDATA[files\Image\IMG_0001.JPG]]></item>
+///     <item name="SHA256"><![CDATA[...]]></item>
+///     <item name="MD5"><![CDATA[...]]></item>
+///   </metadata>
+/// </file>
+/// ```
+///
+/// The file's `Local Path` (its location in the ZIP) is preferred over the
+/// device path. Read as a stream, so large reports are not held in memory.
+fn parse_report_xml<R: BufRead>(reader: R) -> Result<ExpectedHashes, String> {
+    let mut xml_reader = Reader::from_reader(reader);
+    xml_reader.config_mut().trim_text(true);
+
+    let mut expected = ExpectedHashes::new();
+    let mut buf = Vec::new();
+    let mut in_file = false;
+    let mut device_path: Option<String> = None;
+    let mut local_path: Option<String> = None;
+    let mut hashes: Vec<(String, String)> = Vec::new();
+    let mut item_name: Option<String> = None;
+
+    loop {
+        match xml_reader.read_event_into(&mut buf) {
+            Ok(Event::Start(ref e)) => {
+                let name = String::from_utf8_lossy(e.name().as_ref()).to_lowercase();
+                match name.as_str() {
+                    "file" => {
+                        in_file = true;
+                        device_path = e.attributes().flatten()
+                            .find(|a| a.key.as_ref().eq_ignore_ascii_case(b"path"))
+                            .map(|a| String::from_utf8_lossy(&a.value).to_string());
+                        local_path = None;
+                        hashes.clear();
+                    }
+                    "item" if in_file => {
+                        item_name = e.attributes().flatten()
+                            .find(|a| a.key.as_ref() == b"name")
+                            .map(|a| String::from_utf8_lossy(&a.value).to_string());
+                    }
+                    _ => {}
+                }
+            }
+            Ok(Event::Text(ref e)) => {
+                if let Some(item) = item_name.as_deref() {
+                    let text = e.unescape().unwrap_or_default().trim().to_string();
+                    record_item(item, text, &mut local_path, &mut hashes);
+                }
+            }
+            Ok(Event::CData(ref e)) => {
+                if let Some(item) = item_name.as_deref() {
+                    let text = String::from_utf8_lossy(e.as_ref()).trim().to_string();
+                    record_item(item, text, &mut local_path, &mut hashes);
+                }
+            }
+            Ok(Event::End(ref e)) => {
+                let name = String::from_utf8_lossy(e.name().as_ref()).to_lowercase();
+                match name.as_str() {
+                    "item" => item_name = None,
+                    "file" if in_file => {
+                        in_file = false;
+                        if let Some(path) = local_path.take().or_else(|| device_path.take()) {
+                            for (algorithm, hash) in hashes.drain(..) {
+                                insert_stronger(&mut expected, &path, &algorithm, &hash);
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            Ok(Event::Eof) => break,
+            Err(e) => {
+                if expected.is_empty() {
+                    return Err(format!("Failed to parse report.xml: {e}"));
+                }
+                warn!("report.xml parsing stopped early: {}", e);
+                break;
+            }
+            _ => {}
+        }
+        buf.clear();
+    }
+    Ok(expected)
+}
+
+fn record_item(item: &str, text: String, local_path: &mut Option<String>, hashes: &mut Vec<(String, String)>) {
+    if text.is_empty() {
+        return;
+    }
+    if item.eq_ignore_ascii_case("Local Path") {
+        *local_path = Some(text);
+    } else if ALGORITHMS.iter().any(|a| a.eq_ignore_ascii_case(item)) {
+        hashes.push((item.to_string(), text));
+    }
+}
+
+/// Keep the strongest stored algorithm for `path`
+fn insert_stronger(expected: &mut ExpectedHashes, path: &str, algorithm: &str, hash: &str) {
+    let Some(rank) = ALGORITHMS.iter().position(|a| a.eq_ignore_ascii_case(algorithm)) else { return };
+    let Ok(hash) = HashValue::parse_named(hash, algorithm) else { return };
+    let key = normalize_path(path);
+    if expected.get(&key).is_some_and(|e| ALGORITHMS.iter().position(|a| *a == e.algorithm) >= Some(rank)) {
+        return;
+    }
+    expected.insert(key, ExpectedHash { algorithm: ALGORITHMS[rank], hash: String::from(hash) });
+}
+
+/// Stored-hash key matching a ZIP entry name
+fn lookup(expected: &ExpectedHashes, entry_name: &str) -> Option<String> {
+    let key = normalize_path(entry_name);
+    if expected.contains_key(&key) {
+        return Some(key);
+    }
+    strip_top_level(&key).filter(|k| expected.contains_key(*k)).map(str::to_string)
+}
+
+/// Lowercase, `/`-separated, without leading `/` or `./`
+fn normalize_path(path: &str) -> String {
+    let path = path.replace('\\', "/").to_lowercase();
+    path.trim_start_matches("./").trim_start_matches('/').to_string()
+}
+
+fn strip_top_level(key: &str) -> Option<&str> {
+    key.split_once('/').map(|(_, rest)| rest).filter(|rest| !rest.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Cursor, Write};
+    use zip::write::SimpleFileOptions;
+
+    const HELLO_SHA256: &str = "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824";
+    const HELLO_MD5: &str = "5d41402abc4b2a76b9719d911017c592";
+
+    fn write_zip(path: &Path, entries: &[(&str, &[u8])]) {
+        let mut writer = zip::ZipWriter::new(File::create(path).unwrap());
+        for (name, data) in entries {
+            writer.start_file(*name, SimpleFileOptions::default()).unwrap();
+            writer.write_all(data).unwrap();
+        }
+        writer.finish().unwrap();
+    }
+
+    #[test]
+    fn test_verify_against_ufd_hashes() {
+        let dir = tempfile::tempdir().unwrap();
+        let zip_path = dir.path().join("Apple_iPhone.zip");
+        write_zip(&zip_path, &[
+            ("files/Image/a.jpg", b"hello"),
+            ("files/Image/b.jpg", b"tampered"),
+            ("files/Other/c.txt", b"no hash stored"),
+        ]);
+        let ufd_path = dir.path().join("Apple_iPhone.ufd");
+        std::fs::write(&ufd_path, format!(
+            "[SHA256]\nApple_iPhone.zip={0}\nfiles\\Image\\a.jpg={0}\nfiles/Image/b.jpg={0}\nfiles/Image/gone.jpg={0}\n",
+            HELLO_SHA256.to_uppercase()
+        )).unwrap();
+
+        let mut last = (0, 0);
+        let report = verify_zip_contents(zip_path.to_str().unwrap(), ufd_path.to_str().unwrap(), None, |d, t| last = (d, t)).unwrap();
+        assert_eq!(report.results.len(), 2);
+        assert_eq!((report.verified, report.mismatched), (1, 1));
+        // c.txt has no stored hash; gone.jpg isn't in the ZIP
+        assert_eq!((report.zip_entries_without_hash, report.stored_hashes_without_entry), (1, 1));
+        let a = report.results.iter().find(|r| r.path == "files/Image/a.jpg").unwrap();
+        assert!(a.verified);
+        assert_eq!(a.algorithm, "SHA256");
+        assert_eq!(last, (13, 13));
+    }
+
+    #[test]
+    fn test_falls_back_to_report_xml_in_zip() {
+        let report_xml = format!(
+            r#"<?xml version="1.0"?><project><taggedFiles>
+            <file path="/private/var/a.jpg" size="5"><metadata section="File">
+              <item name="Local Path"><![CDATA[files\Image\a.jpg]]></item>
+              <item name="MD5"><![CDATA[{HELLO_MD5}]]></item>
+              <item name="SHA256"><![CDATA[{HELLO_SHA256}]]></item>
+            </metadata></file>
+            <file path="/private/var/b.jpg" size="5"><metadata section="File">
+              <item name="MD5">{HELLO_MD5}</item>
+            </metadata></file>
+            <file path="/private/var/missing.db" size="5"><metadata section="File">
+              <item name="MD5">{HELLO_MD5}</item>
+            </metadata></file>
+            </taggedFiles></project>"#
+        );
+        let dir = tempfile::tempdir().unwrap();
+        let zip_path = dir.path().join("extraction.zip");
+        write_zip(&zip_path, &[
+            ("Apple_iPhone/files/Image/a.jpg", b"hello"),
+            ("Apple_iPhone/private/var/b.jpg", b"hello"),
+            ("Apple_iPhone/report.xml", report_xml.as_bytes()),
+        ]);
+        let ufd_path = dir.path().join("extraction.ufd");
+        std::fs::write(&ufd_path, format!("[SHA256]\nextraction.zip={HELLO_SHA256}\n")).unwrap();
+
+        let report = verify_zip_contents(zip_path.to_str().unwrap(), ufd_path.to_str().unwrap(), None, |_, _| {}).unwrap();
+        assert_eq!(report.hash_source, "Apple_iPhone/report.xml (in ZIP)");
+        assert_eq!((report.verified, report.mismatched), (2, 0));
+        assert_eq!(report.results.iter().map(|r| r.algorithm.as_str()).collect::<Vec<_>>(), ["SHA256", "MD5"]);
+        assert_eq!((report.zip_entries_without_hash, report.stored_hashes_without_entry), (1, 1));
+    }
+
+    #[test]
+    fn test_encrypted_zip_is_rejected() {
+        let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        writer.start_file("files/a.jpg", SimpleFileOptions::default()).unwrap();
+        writer.write_all(b"hello").unwrap();
+        let mut bytes = writer.finish().unwrap().into_inner();
+        // Set the "encrypted" general purpose flag in the central directory header
+        let central = bytes.windows(4).position(|w| w == b"PK\x01\x02").unwrap();
+        bytes[central + 8] |= 1;
+
+        let dir = tempfile::tempdir().unwrap();
+        let zip_path = dir.path().join("locked.zip");
+        std::fs::write(&zip_path, bytes).unwrap();
+        let ufd_path = dir.path().join("locked.ufd");
+        std::fs::write(&ufd_path, format!("[SHA256]\nfiles/a.jpg={HELLO_SHA256}\n")).unwrap();
+
+        let err = verify_zip_contents(zip_path.to_str().unwrap(), ufd_path.to_str().unwrap(), None, |_, _| {}).unwrap_err();
+        assert!(err.contains("encrypted"), "{err}");
+    }
+
+    #[test]
+    fn test_aes_encrypted_zip_verifies_with_its_password() {
+        let dir = tempfile::tempdir().unwrap();
+        let zip_path = dir.path().join("locked.zip");
+        let mut writer = zip::ZipWriter::new(File::create(&zip_path).unwrap());
+        let options = SimpleFileOptions::default().with_aes_encryption(zip::AesMode::Aes256, "1234");
+        writer.start_file("files/a.jpg", options).unwrap();
+        writer.write_all(b"hello").unwrap();
+        writer.finish().unwrap();
+        let ufd_path = dir.path().join("locked.ufd");
+        std::fs::write(&ufd_path, format!("[SHA256]\nfiles/a.jpg={HELLO_SHA256}\n")).unwrap();
+        let (zip, ufd) = (zip_path.to_str().unwrap(), ufd_path.to_str().unwrap());
+
+        let report = verify_zip_contents(zip, ufd, Some("1234"), |_, _| {}).unwrap();
+        assert_eq!((report.verified, report.mismatched), (1, 0));
+
+        let err = verify_zip_contents(zip, ufd, Some("4321"), |_, _| {}).unwrap_err();
+        assert_eq!(err, "Wrong password for ZIP entry files/a.jpg");
+        let err = verify_zip_contents(zip, ufd, None, |_, _| {}).unwrap_err();
+        assert!(err.contains("a password is required"), "{err}");
+    }
+}
@@ -0,0 +1,1510 @@
+//! Container-level verification and hashing operations
+//!
+//! These are the long-running operations behind the GUI's verify/hash
+//! commands, kept free of any Tauri types so they can be driven from CLI
+//! tools and automated pipelines. Progress is reported through plain
+//! closures and all results are serde-serializable.
+//!
+//! ```rust,ignore
+//! use ffx_check_lib::verification;
+//!
+//! let hash = verification::verify_auto("/evidence/disk.E01", "E01", "sha256", |current, total| {
+//!     eprintln!("{current}/{total}");
+//! })?;
+//! ```
+
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info, warn};
+use unicode_normalization::UnicodeNormalization;
+
+use crate::common::hash::{HashAlgorithm, HashValue, StreamingHasher};
+use crate::common::progress::{JobSnapshot, JobState, ProgressTracker, SampleClock, PROGRESS_INTERVAL};
+use crate::common::resources;
+use crate::containers::{self, CompanionLogInfo, ContainerInfo, LogConfidence, SegmentHash, SegmentSetInfo, StoredHash, VerifyStatus};
+use crate::benchmark::BenchmarkStore;
+use crate::database::VerificationStatusRecord;
+use crate::triage::{self, ImageReader, PartitionTriage};
+use crate::{ad1, ewf, raw};
+
+// =============================================================================
+// Segment Verification
+// =============================================================================
+
+/// Progress of one segment within a per-segment verification
+#[derive(Clone, Debug, Serialize)]
+pub struct SegmentPercent {
+    pub segment_name: String,
+    pub segment_number: u32,
+    pub percent: f64,
+    pub completed: bool,
+}
+
+/// Consolidated progress of a per-segment verification, sampled at a fixed
+/// cadence rather than sent per hashed buffer
+#[derive(Clone, Debug, Serialize)]
+pub struct SegmentVerifyProgress {
+    /// Segments currently being hashed or already done, in segment order
+    pub segments: Vec<SegmentPercent>,
+    pub segments_completed: usize,
+    pub segments_total: usize,
+    pub bytes_done: u64,
+    pub bytes_total: u64,
+    /// Overall percent across all segments (by bytes)
+    pub percent: f64,
+}
+
+/// Hash of a single segment file, compared against its stored hash if known
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SegmentHashResult {
+    pub segment_name: String,
+    pub segment_number: u32,
+    pub segment_path: String,
+    pub algorithm: String,
+    pub computed_hash: String,
+    pub expected_hash: Option<String>,
+    pub verified: Option<bool>,  // None = no expected, true = match, false = mismatch
+    pub size: u64,
+    pub duration_secs: f64,
+    /// An expected hash no present segment matched: nothing was hashed and
+    /// `segment_path`/`computed_hash` are empty
+    #[serde(default)]
+    pub missing: bool,
+}
+
+impl SegmentHashResult {
+    /// The computed hash as an expected hash for others; None if hashing failed
+    pub fn to_segment_hash(&self) -> Option<SegmentHash> {
+        let hash = HashValue::parse_named(&self.computed_hash, &self.algorithm).ok()?;
+        Some(SegmentHash {
+            segment_name: self.segment_name.clone(),
+            segment_number: self.segment_number,
+            algorithm: HashAlgorithm::from_str(&self.algorithm).map(|a| a.name().to_string()).unwrap_or_else(|_| self.algorithm.clone()),
+            hash: hash.into(),
+            offset_from: None,
+            offset_to: None,
+            size: Some(self.size),
+            verified: None,
+        })
+    }
+}
+
+/// Verify individual segments of a raw image, comparing against stored hashes
+///
+/// Segments are hashed in parallel; `on_progress` receives one consolidated
+/// update per `PROGRESS_INTERVAL` from a single sampler thread.
+pub fn verify_raw_segments<F>(
+    path: &str,
+    algorithm: &str,
+    expected_hashes: &[SegmentHash],
+    on_progress: F,
+) -> Result<Vec<SegmentHashResult>, String>
+where
+    F: FnMut(SegmentVerifyProgress) + Send,
+{
+    let segment_paths = raw::get_segment_paths(path)?;
+    if segment_paths.is_empty() {
+        return Err("No segments found".to_string());
+    }
+    verify_segment_files(segment_paths, algorithm, expected_hashes, PROGRESS_INTERVAL, on_progress, |seg_path, algo, progress| {
+        raw::hash_single_segment(seg_path, algo, progress)
+    })
+}
+
+/// Verify individual E01 segment files by hashing each .E01, .E02, etc. file
+///
+/// Progress is reported the same way as for `verify_raw_segments`.
+pub fn verify_e01_segments<F>(
+    path: &str,
+    algorithm: &str,
+    expected_hashes: &[SegmentHash],
+    on_progress: F,
+) -> Result<Vec<SegmentHashResult>, String>
+where
+    F: FnMut(SegmentVerifyProgress) + Send,
+{
+    let segment_paths = ewf::get_segment_paths(path)?;
+    if segment_paths.is_empty() {
+        return Err("No E01 segments found".to_string());
+    }
+    verify_segment_files(segment_paths, algorithm, expected_hashes, PROGRESS_INTERVAL, on_progress, |seg_path, algo, progress| {
+        ewf::hash_single_segment(seg_path, algo, progress)
+    })
+}
+
+/// Image hash from [`verify_e01_full`], with the hash it was compared to
+#[derive(Clone, Debug, Serialize)]
+pub struct ImageHashCheck {
+    pub algorithm: String,
+    pub computed_hash: String,
+    pub expected_hash: Option<String>,
+    /// "embedded" (stored in the image) or "companion" (acquisition log)
+    pub expected_source: Option<String>,
+    pub verified: Option<bool>,  // None = no expected, true = match, false = mismatch
+}
+
+/// Logical and physical hashes of an E01 from one read of its segment files
+#[derive(Clone, Debug, Serialize)]
+pub struct E01FullVerifyReport {
+    pub path: String,
+    /// One entry per algorithm
+    pub image_hashes: Vec<ImageHashCheck>,
+    /// One entry per segment file and algorithm, in segment order, compared
+    /// against the companion log's segment hashes
+    pub segments: Vec<SegmentHashResult>,
+    pub chunk_count: usize,
+    pub duration_secs: f64,
+}
+
+/// Hash the media of an E01 and each of its segment files in one pass
+/// ([`ewf::full_hash`]) and compare them against what the image stores and
+/// its companion log records
+///
+/// Image hashes are compared against the hash embedded in the image first,
+/// the companion log second. An ambiguous companion log is not used, as in
+/// [`find_expected_hash`]. `progress` receives (bytes read, bytes total).
+pub fn verify_e01_full<F>(path: &str, algorithms: &[String], progress: F) -> Result<E01FullVerifyReport, String>
+where
+    F: FnMut(u64, u64),
+{
+    let started = Instant::now();
+    let hashes = ewf::full_hash(path, algorithms, progress)?;
+    let log = containers::find_companion_log(path).filter(|log| log.confidence != LogConfidence::Ambiguous);
+    let normalize = |name: &str| name.replace('-', "").to_lowercase();
+
+    let image_hashes = hashes.algorithms.iter().zip(&hashes.image_hashes)
+        .map(|(algorithm, computed)| {
+            let wanted = normalize(algorithm);
+            let embedded = hashes.stored_hashes.iter()
+                .find(|stored| normalize(&stored.algorithm) == wanted)
+                .map(|stored| (stored.hash.clone(), "embedded"));
+            let companion = || log.as_ref()
+                .and_then(|log| companion_expected_hash(log, algorithm, false).ok().flatten())
+                .map(|stored| (stored.hash.clone(), "companion"));
+            let expected = embedded.or_else(companion)
+                .and_then(|(hash, source)| Some((HashValue::parse_named(&hash, algorithm).ok()?, source)));
+            ImageHashCheck {
+                algorithm: algorithm.clone(),
+                computed_hash: computed.clone(),
+                verified: expected.as_ref().map(|(expected, _)| expected.matches(computed)),
+                expected_hash: expected.as_ref().map(|(expected, _)| expected.to_string()),
+                expected_source: expected.map(|(_, source)| source.to_string()),
+            }
+        })
+        .collect();
+
+    let log_segments = log.map(|log| log.segment_hashes).unwrap_or_default();
+    let mut segments = Vec::with_capacity(hashes.segments.len() * hashes.algorithms.len());
+    for (index, algorithm) in hashes.algorithms.iter().enumerate() {
+        let wanted = normalize(algorithm);
+        let stored: Vec<SegmentHash> = log_segments.iter()
+            .filter(|stored| normalize(&stored.algorithm) == wanted)
+            .cloned()
+            .collect();
+        let expected = ExpectedSegments::new(&stored);
+        for segment in &hashes.segments {
+            let computed_hash = segment.hashes[index].clone();
+            let expected = expected.find(&segment.segment_name, None).map(|index| &expected.entries[index].1);
+            segments.push(SegmentHashResult {
+                segment_name: segment.segment_name.clone(),
+                segment_number: segment.segment_number,
+                segment_path: segment.segment_path.clone(),
+                algorithm: algorithm.to_uppercase(),
+                verified: expected.map(|expected| expected.matches(&computed_hash)),
+                expected_hash: expected.map(HashValue::to_string),
+                computed_hash,
+                size: segment.size,
+                duration_secs: segment.duration_secs,
+                missing: false,
+            });
+        }
+    }
+    segments.sort_by_key(|result| result.segment_number);
+
+    Ok(E01FullVerifyReport {
+        path: path.to_string(),
+        image_hashes,
+        segments,
+        chunk_count: hashes.chunk_count,
+        duration_secs: started.elapsed().as_secs_f64(),
+    })
+}
+
+/// Shared parallel driver for the per-segment verifications
+fn verify_segment_files<F, H, C>(
+    segment_paths: Vec<PathBuf>,
+    algorithm: &str,
+    expected_hashes: &[SegmentHash],
+    progress_clock: C,
+    mut on_progress: F,
+    hash_segment: H,
+) -> Result<Vec<SegmentHashResult>, String>
+where
+    C: SampleClock,
+    F: FnMut(SegmentVerifyProgress) + Send,
+    H: Fn(&str, &str, &mut dyn FnMut(u64, u64)) -> Result<String, String> + Sync,
+{
+    let num_segments = segment_paths.len();
+
+    let expected = ExpectedSegments::new(expected_hashes);
+
+    let segment_names: Vec<String> = segment_paths.iter().enumerate()
+        .map(|(idx, seg_path)| {
+            seg_path.file_name()
+                .map(|f| f.to_string_lossy().to_string())
+                .unwrap_or_else(|| format!("segment_{}", idx + 1))
+        })
+        .collect();
+
+    // Workers only touch their own atomic counters; sizes seed the totals
+    let tracker = ProgressTracker::new(num_segments);
+    let mut offset = 0u64;
+    let mut expected_index = Vec::with_capacity(num_segments);
+    for (idx, seg_path) in segment_paths.iter().enumerate() {
+        let size = std::fs::metadata(seg_path).map(|m| m.len()).unwrap_or(0);
+        tracker.job(idx).set_total(size);
+        // Image bytes this segment holds, for hashes logged by range only
+        let range = (size > 0).then(|| (offset, offset + size - 1));
+        expected_index.push(expected.find(&segment_names[idx], range));
+        offset += size;
+    }
+
+    // The current pool is the job's own when it runs with a thread cap
+    let parallelism = rayon::current_num_threads().min(num_segments);
+
+    let results: Mutex<Vec<SegmentHashResult>> = Mutex::new(Vec::with_capacity(num_segments));
+
+    let pool = resources::inherited_thread_pool(parallelism)
+        .map_err(|e| format!("Failed to create thread pool: {}", e))?;
+
+    let report = |_: &[JobSnapshot], jobs: &[JobSnapshot]| {
+        on_progress(segment_progress(&segment_names, jobs));
+    };
+
+    tracker.sample_while(progress_clock, report, || pool.scope(|s| {
+        for (idx, seg_path) in segment_paths.into_iter().enumerate() {
+            let results = &results;
+            let expected = &expected;
+            let hash_segment = &hash_segment;
+            let job = tracker.job(idx);
+            let segment_name = segment_names[idx].clone();
+            let segment_number = (idx + 1) as u32;
+            let expected_index = expected_index[idx];
+
+            s.spawn(move |_| {
+                let seg_path_str = seg_path.to_string_lossy().to_string();
+                let start_time = Instant::now();
+
+                job.start();
+                let hash_result = hash_segment(&seg_path_str, algorithm, &mut |current, total| {
+                    job.update(current, total);
+                });
+                job.finish();
+
+                let duration = start_time.elapsed().as_secs_f64();
+                let size = std::fs::metadata(&seg_path)
+                    .map(|m| m.len())
+                    .unwrap_or(0);
+
+                let result = match hash_result {
+                    Ok(computed_hash) => {
+                        let expected = expected_index.map(|index| &expected.entries[index].1);
+                        let verified = expected.map(|expected| expected.matches(&computed_hash));
+
+                        SegmentHashResult {
+                            segment_name,
+                            segment_number,
+                            segment_path: seg_path_str,
+                            algorithm: algorithm.to_uppercase(),
+                            computed_hash,
+                            expected_hash: expected.map(HashValue::to_string),
+                            verified,
+                            size,
+                            duration_secs: duration,
+                            missing: false,
+                        }
+                    }
+                    Err(e) => SegmentHashResult {
+                        segment_name,
+                        segment_number,
+                        segment_path: seg_path_str,
+                        algorithm: algorithm.to_uppercase(),
+                        computed_hash: format!("ERROR: {}", e),
+                        expected_hash: None,
+                        verified: None,
+                        size,
+                        duration_secs: duration,
+                        missing: false,
+                    },
+                };
+
+                // Use if-let to handle potential mutex poisoning gracefully
+                if let Ok(mut guard) = results.lock() {
+                    guard.push(result);
+                } else {
+                    tracing::error!("Mutex poisoned while storing hash result for segment {}", segment_number);
+                }
+            });
+        }
+    }));
+
+    let mut final_results = results
+        .into_inner()
+        .map_err(|e| format!("Lock error: {}", e))?;
+
+    // Sort by segment number
+    final_results.sort_by_key(|r| r.segment_number);
+
+    // Expected hashes nothing matched are listed, not dropped
+    let matched: HashSet<usize> = expected_index.into_iter().flatten().collect();
+    for (index, (stored, hash)) in expected.entries.iter().enumerate() {
+        if matched.contains(&index) {
+            continue;
+        }
+        debug!(segment = %stored.segment_name, "Expected segment hash matched no segment file");
+        // Hashes logged by range only are named by their range
+        let segment_name = match (stored.segment_name.is_empty(), stored.offset_from, stored.offset_to) {
+            (true, Some(from), Some(to)) => format!("bytes {} - {}", from, to),
+            _ => stored.segment_name.clone(),
+        };
+        final_results.push(SegmentHashResult {
+            segment_name,
+            segment_number: stored.segment_number,
+            segment_path: String::new(),
+            algorithm: algorithm.to_uppercase(),
+            computed_hash: String::new(),
+            expected_hash: Some(hash.to_string()),
+            verified: None,
+            size: stored.size.unwrap_or(0),
+            duration_secs: 0.0,
+            missing: true,
+        });
+    }
+
+    Ok(final_results)
+}
+
+/// Expected segment hashes looked up by segment file name
+///
+/// Names are compared after Unicode NFC normalization and lowercasing, so
+/// "SCHA\u{308}RDT.001" from a macOS (NFD) log matches "schärdt.001" on an
+/// NTFS copy. A log name carrying a path (`D:\Case\SCHARDT.001`) that
+/// doesn't match as a whole is matched on its file name. Hashes a log
+/// records per byte range without naming the file (dc3dd) are matched on
+/// the range.
+struct ExpectedSegments<'a> {
+    entries: Vec<(&'a SegmentHash, HashValue)>,
+    by_name: HashMap<String, usize>,
+    by_file_name: HashMap<String, usize>,
+    by_range: HashMap<(u64, u64), usize>,
+}
+
+impl<'a> ExpectedSegments<'a> {
+    fn new(hashes: &'a [SegmentHash]) -> Self {
+        let mut expected = Self { entries: Vec::new(), by_name: HashMap::new(), by_file_name: HashMap::new(), by_range: HashMap::new() };
+        for stored in hashes {
+            let hash = match HashValue::parse_named(&stored.hash, &stored.algorithm) {
+                Ok(hash) => hash,
+                Err(e) => {
+                    warn!(segment = %stored.segment_name, error = %e, "Ignoring unusable expected segment hash");
+                    continue;
+                }
+            };
+            let index = expected.entries.len();
+            expected.entries.push((stored, hash));
+            // The first entry for a name wins, as with a plain map insert-once
+            expected.by_name.entry(segment_key(&stored.segment_name)).or_insert(index);
+            expected.by_file_name.entry(segment_key(file_name_part(&stored.segment_name))).or_insert(index);
+            if let (Some(from), Some(to)) = (stored.offset_from, stored.offset_to) {
+                expected.by_range.entry((from, to)).or_insert(index);
+            }
+        }
+        expected
+    }
+
+    /// Index into `entries` of the hash expected for `segment_name`, else
+    /// for the segment holding image bytes `range` (inclusive)
+    fn find(&self, segment_name: &str, range: Option<(u64, u64)>) -> Option<usize> {
+        let key = segment_key(segment_name);
+        self.by_name.get(&key)
+            .or_else(|| self.by_file_name.get(&key))
+            .or_else(|| self.by_range.get(&range?))
+            .copied()
+    }
+}
+
+/// Comparison form of a segment name: trimmed, NFC, lowercase
+fn segment_key(name: &str) -> String {
+    name.trim().nfc().collect::<String>().to_lowercase()
+}
+
+/// Last component of a Windows or POSIX path
+fn file_name_part(name: &str) -> &str {
+    name.trim().rsplit(['/', '\\']).next().unwrap_or(name)
+}
+
+/// Build the consolidated progress event from a tracker sample
+fn segment_progress(segment_names: &[String], jobs: &[JobSnapshot]) -> SegmentVerifyProgress {
+    let segments = jobs.iter().enumerate()
+        .filter(|(_, job)| job.state != JobState::Pending)
+        .map(|(idx, job)| SegmentPercent {
+            segment_name: segment_names[idx].clone(),
+            segment_number: (idx + 1) as u32,
+            percent: job.percent(),
+            completed: job.state == JobState::Finished,
+        })
+        .collect();
+
+    let bytes_total: u64 = jobs.iter().map(|job| job.total).sum();
+    let bytes_done: u64 = jobs.iter().map(|job| job.done.min(job.total)).sum();
+    let segments_completed = jobs.iter().filter(|job| job.state == JobState::Finished).count();
+    let percent = if segments_completed == jobs.len() {
+        100.0
+    } else if bytes_total > 0 {
+        bytes_done as f64 / bytes_total as f64 * 100.0
+    } else {
+        0.0
+    };
+
+    SegmentVerifyProgress {
+        segments,
+        segments_completed,
+        segments_total: jobs.len(),
+        bytes_done,
+        bytes_total,
+        percent,
+    }
+}
+
+// =============================================================================
+// Auto-dispatch Verification
+// =============================================================================
+
+/// Compute the image-level hash of a container, picking the verification
+/// path from its scan `container_type` (e.g. "EnCase (E01)", "AD1", "Raw")
+///
+/// Progress is reported as (current, total) in format-specific units
+/// (chunks for EWF, bytes otherwise). Unknown types are hashed as raw files,
+/// tuned by the saved hash benchmark of their device (see [`crate::benchmark`]).
+/// A truncated EWF segment fails before hashing with the section that runs
+/// past its end ([`ewf::verify_preflight`]). For EWF images, a drive model or serial that differs from the companion
+/// log's source drive is logged as a warning ([`source_device_discrepancies`]).
+pub fn verify_auto<F>(path: &str, container_type: &str, algorithm: &str, mut progress: F) -> Result<String, String>
+where
+    F: FnMut(u64, u64),
+{
+    let container_type = container_type.to_lowercase();
+
+    if container_type.contains("e01") || container_type.contains("encase") || container_type.contains("ex01") || container_type.contains("smart") {
+        let hash = ewf::verify_with_progress(path, algorithm, |current: usize, total: usize| {
+            progress(current as u64, total as u64);
+        })?;
+        for issue in source_device_discrepancies(path) {
+            warn!(path, issue = %issue, "Image header and companion log describe different drives");
+        }
+        Ok(hash)
+    } else if container_type.contains("ad1") {
+        // AD1 containers - hash the logical data stream, which is what the
+        // FTK companion log's image hash covers
+        ad1::hash_image_stream_with_progress(path, algorithm, progress)
+    } else if container_type.contains("l01") {
+        // L01 containers - the first entry carries the image hash
+        let entries = containers::verify(path, algorithm)?;
+        let entry = entries.first().ok_or("L01 verification returned no result")?;
+        match (&entry.computed, entry.status) {
+            (Some(hash), VerifyStatus::Ok) => Ok(hash.to_string()),
+            _ => Err(entry.message.clone().unwrap_or_else(|| "L01 verification failed".to_string())),
+        }
+    } else {
+        // Raw images, UFED containers, archives (ZIP, TAR, 7z) and anything
+        // unrecognized - hash the file (set) directly, with the buffer size
+        // last benchmarked on the device holding it
+        let tuning = BenchmarkStore::default_location().tuning_for(path, algorithm);
+        raw::verify_with_tuning(path, algorithm, &tuning, progress)
+    }
+}
+
+/// Differences between the drive model/serial in an EWF image's header and
+/// the source drive its companion log describes
+///
+/// Empty when either side doesn't record them. A difference suggests the
+/// log belongs to another acquisition, whatever the hashes say.
+pub fn source_device_discrepancies(path: &str) -> Vec<String> {
+    let Some(device) = containers::find_companion_log(path).and_then(|log| log.source_device) else {
+        return Vec::new();
+    };
+    match ewf::info(path) {
+        Ok(info) => containers::source_device_discrepancies(&device, info.model.as_deref(), info.serial_number.as_deref()),
+        Err(e) => {
+            debug!(path, error = %e, "No image header to compare the source drive against");
+            Vec::new()
+        }
+    }
+}
+
+/// Stored hash from a companion log to compare a `verify_auto` result against
+///
+/// An ambiguous log (several logs in the folder and nothing tying one to
+/// the image) is refused unless the user confirmed it, so a hash from
+/// another image's log is never reported as a match or mismatch.
+pub fn companion_expected_hash<'a>(log: &'a CompanionLogInfo, algorithm: &str, confirmed: bool) -> Result<Option<&'a StoredHash>, String> {
+    if log.confidence == LogConfidence::Ambiguous && !confirmed {
+        return Err(format!(
+            "Companion log {} may belong to another image ({} other candidates) - confirm it before comparing",
+            log.log_path,
+            log.other_candidates.len()
+        ));
+    }
+    let normalize = |name: &str| name.replace('-', "").to_lowercase();
+    let algorithm = normalize(algorithm);
+    Ok(log.stored_hashes.iter().find(|stored| normalize(&stored.algorithm) == algorithm))
+}
+
+// =============================================================================
+// Background Verification
+// =============================================================================
+
+/// Background verification outcomes, as stored in `VerificationStatusRecord::status`
+pub const STATUS_VERIFIED: &str = "verified";
+pub const STATUS_MISMATCH: &str = "mismatch";
+pub const STATUS_NO_EXPECTED: &str = "no-expected";
+pub const STATUS_ERROR: &str = "error";
+
+/// Hash a container should have, found without asking the user
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExpectedHash {
+    pub value: HashValue,
+    /// "embedded" (container metadata) or "companion" (acquisition log)
+    pub source: &'static str,
+    /// File the hash covers when it is not the scanned path (UFED ZIP)
+    pub target: Option<String>,
+}
+
+/// Expected `algorithm` hash of a scanned container
+///
+/// Hashes embedded in the container come first (EWF stored hashes, UFED
+/// per-file hashes), then the companion log - unless the log is ambiguous,
+/// since nobody is there to confirm it.
+pub fn find_expected_hash(info: &ContainerInfo, path: &str, algorithm: &str) -> Option<ExpectedHash> {
+    let normalize = |name: &str| name.replace('-', "").to_lowercase();
+    let wanted = normalize(algorithm);
+    let parse = |hash: &str| HashValue::parse_named(hash, algorithm).ok();
+
+    let embedded = info.e01.iter().chain(info.l01.iter())
+        .flat_map(|ewf| ewf.stored_hashes.iter())
+        .find(|stored| normalize(&stored.algorithm) == wanted)
+        .and_then(|stored| parse(&stored.hash));
+    if let Some(value) = embedded {
+        return Some(ExpectedHash { value, source: "embedded", target: None });
+    }
+
+    // UFED hashes name the extraction file they cover (usually the ZIP next
+    // to the scanned .ufd), which is then hashed instead
+    let folder = Path::new(path).parent().unwrap_or(Path::new(""));
+    let file_name = Path::new(path).file_name().map(|name| name.to_string_lossy().to_string());
+    if let Some(ufed) = &info.ufed {
+        let mut candidates = ufed.stored_hashes.iter().flatten()
+            .filter(|stored| normalize(&stored.algorithm) == wanted)
+            .filter(|stored| folder.join(&stored.filename).is_file());
+        if let Some(stored) = candidates.next() {
+            let target = (file_name.as_deref() != Some(stored.filename.as_str()))
+                .then(|| folder.join(&stored.filename).to_string_lossy().to_string());
+            if let Some(value) = parse(&stored.hash) {
+                return Some(ExpectedHash { value, source: "embedded", target });
+            }
+        }
+    }
+
+    let stored = companion_expected_hash(info.companion_log.as_ref()?, algorithm, false).ok()??;
+    Some(ExpectedHash { value: parse(&stored.hash)?, source: "companion", target: None })
+}
+
+/// Size and modification time (Unix seconds) of a file, as recorded with its status
+fn file_stamp(path: &str) -> Option<(i64, Option<i64>)> {
+    let meta = std::fs::metadata(path).ok()?;
+    let modified = meta.modified().ok()
+        .and_then(|time| time.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|since| since.as_secs() as i64);
+    Some((meta.len() as i64, modified))
+}
+
+/// Verify a scanned container and compare it against [`find_expected_hash`]
+///
+/// Never fails - problems are recorded with [`STATUS_ERROR`], so a queue of
+/// background verifications gets an outcome for every file. Files without
+/// an expected hash are still hashed; the hash is kept for later comparison.
+pub fn verify_status<F>(path: &str, container_type: &str, algorithm: &str, progress: F) -> VerificationStatusRecord
+where
+    F: FnMut(u64, u64),
+{
+    let (file_size, file_modified) = file_stamp(path).unwrap_or((0, None));
+    let mut record = VerificationStatusRecord {
+        path: path.to_string(),
+        algorithm: algorithm.to_lowercase(),
+        status: STATUS_ERROR.to_string(),
+        computed_hash: None,
+        expected_hash: None,
+        expected_source: None,
+        error: None,
+        file_size,
+        file_modified,
+        verified_at: chrono::Utc::now().to_rfc3339(),
+    };
+
+    // Refreshed: a companion log written since the last scan must be seen
+    let expected = containers::info_fast_cached(path, true)
+        .map(|info| find_expected_hash(&info, path, algorithm));
+    let expected = match expected {
+        Ok(expected) => expected,
+        Err(e) => {
+            record.error = Some(e);
+            return record;
+        }
+    };
+    let target = expected.as_ref().and_then(|e| e.target.clone());
+    let computed = match &target {
+        Some(target) => raw::verify_with_progress(target, algorithm, progress),
+        None => verify_auto(path, container_type, algorithm, progress),
+    };
+    match computed {
+        Ok(computed) => {
+            record.status = match &expected {
+                Some(expected) if expected.value.matches(&computed) => STATUS_VERIFIED,
+                Some(_) => STATUS_MISMATCH,
+                None => STATUS_NO_EXPECTED,
+            }
+            .to_string();
+            record.computed_hash = Some(computed);
+        }
+        Err(e) => record.error = Some(e),
+    }
+    if let Some(expected) = expected {
+        record.expected_hash = Some(expected.value.into());
+        record.expected_source = Some(expected.source.to_string());
+    }
+    debug!(path, status = %record.status, "Background verification finished");
+    record
+}
+
+/// Whether a stored status still describes the file on disk (same size and
+/// modification time); a changed file has to be verified again
+pub fn status_is_current(record: &VerificationStatusRecord) -> bool {
+    file_stamp(&record.path) == Some((record.file_size, record.file_modified))
+}
+
+// =============================================================================
+// Batch Hashing
+// =============================================================================
+
+/// A file to hash in a batch, as listed by a directory scan
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BatchFileInput {
+    pub path: String,
+    pub container_type: String,
+}
+
+/// Batch hashing result for a single file
+#[derive(Clone, Debug, Serialize)]
+pub struct BatchHashResult {
+    pub path: String,
+    pub algorithm: String,
+    pub hash: Option<String>,
+    pub error: Option<String>,
+    pub duration_ms: Option<u64>,
+    pub throughput_mbs: Option<f64>,
+    /// Other paths in the batch with the same hash (duplicate evidence)
+    pub duplicates: Vec<String>,
+}
+
+/// Progress update for batch hashing - includes hash result when completed
+#[derive(Clone, Debug, Serialize)]
+pub struct BatchProgress {
+    pub path: String,
+    pub status: String,  // "queued", "started", "progress", "completed", "error"
+    pub percent: f64,
+    pub files_completed: usize,
+    pub files_total: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hash: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub algorithm: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    // Decompression progress (for E01/compressed containers)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chunks_processed: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chunks_total: Option<usize>,
+}
+
+impl BatchProgress {
+    fn status(path: &str, status: &str, percent: f64, files_completed: usize, files_total: usize) -> Self {
+        Self {
+            path: path.to_string(),
+            status: status.to_string(),
+            percent,
+            files_completed,
+            files_total,
+            hash: None,
+            algorithm: None,
+            error: None,
+            chunks_processed: None,
+            chunks_total: None,
+        }
+    }
+}
+
+/// Hash multiple containers in parallel (one file per CPU core at most)
+///
+/// Results are returned in input order, each listing the other files that
+/// hashed the same in `duplicates`. Status changes ("queued",
+/// "started", "completed", "error") are sent as they happen from the worker
+/// threads; "progress" updates come from one shared sampler every
+/// `PROGRESS_INTERVAL`, and only for files whose progress changed.
+pub fn batch_hash<F>(files: &[BatchFileInput], algorithm: &str, on_progress: F) -> Vec<BatchHashResult>
+where
+    F: Fn(BatchProgress) + Sync,
+{
+    batch_hash_sampled(files, algorithm, PROGRESS_INTERVAL, on_progress)
+}
+
+fn batch_hash_sampled<F>(files: &[BatchFileInput], algorithm: &str, progress_interval: Duration, on_progress: F) -> Vec<BatchHashResult>
+where
+    F: Fn(BatchProgress) + Sync,
+{
+    let num_files = files.len();
+    info!(num_files, algorithm, "Starting parallel batch hash");
+    if num_files == 0 {
+        return Vec::new();
+    }
+
+    // One file per worker of the current pool (capped for limited jobs),
+    // or fewer for small batches
+    let num_workers = rayon::current_num_threads();
+    let max_concurrent = num_workers.min(num_files);
+    debug!(max_concurrent, num_workers, "Parallel file limit set based on worker threads");
+
+    for file in files {
+        on_progress(BatchProgress::status(&file.path, "queued", 0.0, 0, num_files));
+    }
+
+    let batch = BatchReporter {
+        tracker: ProgressTracker::new(num_files),
+        emit_lock: Mutex::new(()),
+        on_progress,
+    };
+
+    let hash_all = || {
+        files.par_iter().enumerate().map(|(idx, file)| {
+            hash_batch_file(idx, file, num_files, algorithm, &batch)
+        }).collect::<Vec<_>>()
+    };
+
+    let report = |previous: &[JobSnapshot], current: &[JobSnapshot]| batch.report_progress(files, previous, current);
+    let mut results = batch.tracker.sample_while(progress_interval, report, || {
+        match resources::inherited_thread_pool(max_concurrent) {
+            Ok(pool) => pool.install(hash_all),
+            Err(e) => {
+                debug!(error = %e, "Falling back to global thread pool");
+                hash_all()
+            }
+        }
+    });
+
+    mark_duplicates(&mut results);
+    info!(num_files, results = results.len(), "Batch hash complete");
+    results
+}
+
+/// Progress state shared by the batch workers and the sampler
+struct BatchReporter<F> {
+    tracker: ProgressTracker,
+    /// Orders status events against sampled progress so a stale "progress"
+    /// update can never follow a file's "completed" event
+    emit_lock: Mutex<()>,
+    on_progress: F,
+}
+
+impl<F: Fn(BatchProgress)> BatchReporter<F> {
+    fn files_completed(&self) -> usize {
+        self.tracker.snapshot().iter().filter(|job| job.state == JobState::Finished).count()
+    }
+
+    /// Send a status event, marking the file finished first if it's final
+    fn status(&self, idx: usize, finished: bool, progress: impl FnOnce(usize) -> BatchProgress) {
+        let _guard = self.emit_lock.lock();
+        let job = self.tracker.job(idx);
+        if finished {
+            job.finish();
+        } else {
+            job.start();
+        }
+        (self.on_progress)(progress(self.files_completed()));
+    }
+
+    /// Sampler callback: one "progress" event per running file that moved
+    fn report_progress(&self, files: &[BatchFileInput], previous: &[JobSnapshot], current: &[JobSnapshot]) {
+        let _guard = self.emit_lock.lock();
+        let files_completed = self.files_completed();
+        for (idx, (before, now)) in previous.iter().zip(current).enumerate() {
+            // Re-check under the lock - the file may have completed since the sample
+            if now == before || now.total <= 1 || self.tracker.job(idx).snapshot().state != JobState::Running {
+                continue;
+            }
+            (self.on_progress)(BatchProgress {
+                chunks_processed: Some(now.done as usize),
+                chunks_total: Some(now.total as usize),
+                ..BatchProgress::status(&files[idx].path, "progress", now.percent().floor(), files_completed, files.len())
+            });
+        }
+    }
+}
+
+fn hash_batch_file<F>(idx: usize, file: &BatchFileInput, num_files: usize, algorithm: &str, batch: &BatchReporter<F>) -> BatchHashResult
+where
+    F: Fn(BatchProgress),
+{
+    debug!(idx = idx + 1, total = num_files, path = %file.path, "File started");
+    batch.status(idx, false, |files_completed| {
+        BatchProgress::status(&file.path, "started", 0.0, files_completed, num_files)
+    });
+
+    let start_time = Instant::now();
+    let file_size = std::fs::metadata(&file.path).map(|m| m.len()).unwrap_or(0);
+
+    let job = batch.tracker.job(idx);
+    let result = verify_auto(&file.path, &file.container_type, algorithm, |current, total| {
+        job.update(current, total);
+    });
+
+    let duration_ms = start_time.elapsed().as_millis() as u64;
+    let throughput_mbs = if duration_ms > 0 && file_size > 0 {
+        Some((file_size as f64 / (1024.0 * 1024.0)) / (duration_ms as f64 / 1000.0))
+    } else {
+        None
+    };
+
+    match result {
+        Ok(hash) => {
+            debug!(idx = idx + 1, hash_prefix = %&hash[..8.min(hash.len())], "File completed");
+            batch.status(idx, true, |files_completed| BatchProgress {
+                hash: Some(hash.clone()),
+                algorithm: Some(algorithm.to_uppercase()),
+                ..BatchProgress::status(&file.path, "completed", 100.0, files_completed, num_files)
+            });
+            BatchHashResult {
+                path: file.path.clone(),
+                algorithm: algorithm.to_uppercase(),
+                hash: Some(hash),
+                error: None,
+                duration_ms: Some(duration_ms),
+                throughput_mbs,
+                duplicates: Vec::new(),
+            }
+        }
+        Err(e) => {
+            debug!(idx = idx + 1, error = %e, "File error");
+            batch.status(idx, true, |files_completed| BatchProgress {
+                error: Some(e.clone()),
+                ..BatchProgress::status(&file.path, "error", 0.0, files_completed, num_files)
+            });
+            BatchHashResult {
+                path: file.path.clone(),
+                algorithm: algorithm.to_uppercase(),
+                hash: None,
+                error: Some(e),
+                duration_ms: Some(duration_ms),
+                throughput_mbs: None,
+                duplicates: Vec::new(),
+            }
+        }
+    }
+}
+
+// =============================================================================
+// Duplicate Detection
+// =============================================================================
+
+/// One copy of a duplicated evidence set
+#[derive(Clone, Debug, Serialize)]
+pub struct DuplicateCopy {
+    pub path: String,
+    /// Companion log found next to this copy (ambiguous logs are left out)
+    pub companion_log: Option<String>,
+}
+
+/// Evidence sets at different paths with the same whole-set hash
+#[derive(Clone, Debug, Serialize)]
+pub struct DuplicateGroup {
+    pub algorithm: String,
+    pub hash: String,
+    /// Combined size of all segments of one copy
+    pub total_size: u64,
+    pub copies: Vec<DuplicateCopy>,
+}
+
+/// Find evidence sets that are copies of each other, e.g. the same image
+/// copied into two folders of an intake drive
+///
+/// Sets are compared by the image-level hash `verify_auto` computes, so a
+/// segmented set matches only if the whole set does. A set is only hashed
+/// if another set has the same combined segment size, and `on_progress`
+/// follows the batch hash of those candidates. Inputs naming different
+/// segments of one set count as one set.
+pub fn find_duplicates<F>(files: &[BatchFileInput], algorithm: &str, on_progress: F) -> Vec<DuplicateGroup>
+where
+    F: Fn(BatchProgress) + Sync,
+{
+    let mut seen_sets = HashSet::new();
+    let mut sets: Vec<(&BatchFileInput, u64)> = Vec::new();
+    for file in files {
+        let Some(set) = containers::segment_set_info(&file.path, None) else {
+            debug!(path = %file.path, "Skipping unreadable evidence set");
+            continue;
+        };
+        if seen_sets.insert(set_identity(&file.path, &set)) {
+            sets.push((file, set.total_size));
+        }
+    }
+
+    let mut size_counts: HashMap<u64, usize> = HashMap::new();
+    for (_, total_size) in &sets {
+        *size_counts.entry(*total_size).or_default() += 1;
+    }
+    let candidates: Vec<BatchFileInput> = sets.iter()
+        .filter(|(_, total_size)| size_counts[total_size] > 1)
+        .map(|(file, _)| (*file).clone())
+        .collect();
+    info!(files = files.len(), sets = sets.len(), candidates = candidates.len(), "Hashing duplicate candidates");
+
+    let sizes: HashMap<&str, u64> = sets.iter().map(|(file, total_size)| (file.path.as_str(), *total_size)).collect();
+    let results = batch_hash(&candidates, algorithm, on_progress);
+    duplicate_groups(&results).into_iter()
+        .map(|(hash, group)| DuplicateGroup {
+            algorithm: group[0].algorithm.clone(),
+            hash: hash.into(),
+            total_size: sizes[group[0].path.as_str()],
+            copies: group.iter()
+                .map(|result| DuplicateCopy {
+                    path: result.path.clone(),
+                    companion_log: companion_log_path(&result.path),
+                })
+                .collect(),
+        })
+        .collect()
+}
+
+/// Identify a segment set by its folder and first segment, whichever
+/// segment the input names
+fn set_identity(path: &str, set: &SegmentSetInfo) -> PathBuf {
+    let path = Path::new(path);
+    let dir = path.parent().unwrap_or(Path::new("."));
+    let dir = dir.canonicalize().unwrap_or_else(|_| dir.to_path_buf());
+    match set.files.first() {
+        Some(first) => dir.join(&first.name),
+        None => dir.join(path.file_name().unwrap_or_default()),
+    }
+}
+
+/// A log that may belong to another image in the folder does not count
+fn companion_log_path(path: &str) -> Option<String> {
+    containers::find_companion_log(path)
+        .filter(|log| log.confidence != LogConfidence::Ambiguous)
+        .map(|log| log.log_path)
+}
+
+/// Results sharing a hash, groups in order of first appearance
+///
+/// Only well-formed hashes are grouped - L01 verification reports a status
+/// message instead, which must not make every L01 a duplicate.
+fn duplicate_groups(results: &[BatchHashResult]) -> Vec<(HashValue, Vec<&BatchHashResult>)> {
+    let mut groups: Vec<(HashValue, Vec<&BatchHashResult>)> = Vec::new();
+    let mut by_hash: HashMap<HashValue, usize> = HashMap::new();
+    for result in results {
+        let Some(hash) = result.hash.as_deref() else { continue };
+        let Ok(algorithm) = HashAlgorithm::from_str(&result.algorithm) else { continue };
+        let Ok(hash) = HashValue::parse(hash, algorithm) else { continue };
+        match by_hash.entry(hash) {
+            Entry::Occupied(entry) => groups[*entry.get()].1.push(result),
+            Entry::Vacant(entry) => {
+                groups.push((entry.key().clone(), vec![result]));
+                entry.insert(groups.len() - 1);
+            }
+        }
+    }
+    groups.retain(|(_, group)| group.len() > 1);
+    groups
+}
+
+fn mark_duplicates(results: &mut [BatchHashResult]) {
+    let groups: Vec<Vec<String>> = duplicate_groups(results).iter()
+        .map(|(_, group)| group.iter().map(|result| result.path.clone()).collect())
+        .collect();
+    for paths in groups {
+        for result in results.iter_mut().filter(|result| paths.contains(&result.path)) {
+            result.duplicates = paths.iter().filter(|path| **path != result.path).cloned().collect();
+        }
+    }
+}
+
+// =============================================================================
+// Partition Hashing
+// =============================================================================
+
+/// Hash of one partition of a disk image
+#[derive(Clone, Debug, Serialize)]
+pub struct PartitionHashResult {
+    /// 1-based table index; logical MBR partitions are numbered from 5
+    pub index: u32,
+    /// MBR type byte ("0x07") or GPT type GUID
+    pub partition_type: String,
+    pub type_description: Option<String>,
+    /// GPT partition name
+    pub name: Option<String>,
+    /// First sector, in the table's sector size
+    pub start_lba: u64,
+    /// Byte offset of the partition in the image
+    pub offset: u64,
+    /// Length in bytes
+    pub length: u64,
+    /// None when the entry was skipped
+    pub hash: Option<String>,
+    pub duration_secs: f64,
+    /// Why the entry was not hashed (corrupt table: overlapping entries,
+    /// entries past the end of the image)
+    pub skipped: Option<String>,
+}
+
+/// Per-partition hashes of a disk image
+#[derive(Clone, Debug, Serialize)]
+pub struct PartitionHashReport {
+    pub algorithm: String,
+    /// "MBR", "GPT", or "none" for an unpartitioned volume image
+    pub partition_scheme: String,
+    pub sector_size: u64,
+    pub disk_size: u64,
+    pub partitions: Vec<PartitionHashResult>,
+    /// Hash of the whole image, when requested
+    pub disk_hash: Option<String>,
+    pub duration_secs: f64,
+}
+
+/// Hash every partition of an E01 or raw disk image on its own
+///
+/// Partitions come from the MBR (with logical partitions) or GPT, as for
+/// [`triage`]. Each partition's byte range is hashed through the image's
+/// decoded data, so an E01 partition hash matches a dd of that partition.
+/// Entries that overlap another entry or run past the end of the image are
+/// listed with `skipped` set instead of being hashed. With `include_disk`
+/// the whole image is hashed in the same pass; otherwise only the
+/// partitions are read. `progress` gets bytes read and bytes to read.
+pub fn hash_partitions<F>(path: &str, algorithm: &str, include_disk: bool, progress: F) -> Result<PartitionHashReport, String>
+where
+    F: FnMut(u64, u64),
+{
+    let mut image = triage::open_image(path)?;
+    hash_image_partitions(image.as_mut(), algorithm, include_disk, progress)
+}
+
+/// Running hash of one partition
+struct PartitionHasher {
+    hasher: StreamingHasher,
+    started: Option<Instant>,
+    elapsed: Duration,
+}
+
+fn hash_image_partitions<F>(image: &mut dyn ImageReader, algorithm: &str, include_disk: bool, mut progress: F) -> Result<PartitionHashReport, String>
+where
+    F: FnMut(u64, u64),
+{
+    let algo = HashAlgorithm::from_str(algorithm)?;
+    let started = Instant::now();
+    let disk_size = image.size();
+    let table = triage::read_partition_table(image)?;
+
+    let mut results: Vec<PartitionHashResult> = table.partitions.iter()
+        .map(|partition| PartitionHashResult {
+            index: partition.index,
+            partition_type: partition.partition_type.clone(),
+            type_description: partition.type_description.clone(),
+            name: partition.name.clone(),
+            start_lba: partition.offset / table.sector_size,
+            offset: partition.offset,
+            length: partition.size,
+            hash: None,
+            duration_secs: 0.0,
+            skipped: partition_problem(partition, &table.partitions, disk_size),
+        })
+        .collect();
+    for result in results.iter().filter(|result| result.skipped.is_some()) {
+        warn!(index = result.index, reason = result.skipped.as_deref(), "Skipping partition entry");
+    }
+    let mut hashers: Vec<Option<PartitionHasher>> = results.iter()
+        .map(|result| result.skipped.is_none().then(|| PartitionHasher {
+            hasher: StreamingHasher::new(algo),
+            started: None,
+            elapsed: Duration::ZERO,
+        }))
+        .collect();
+
+    // Valid entries don't overlap, so without the disk hash each byte is
+    // read at most once
+    let mut disk_hasher = include_disk.then(|| StreamingHasher::new(algo));
+    let mut spans: Vec<(u64, u64)> = if include_disk {
+        vec![(0, disk_size)]
+    } else {
+        results.iter()
+            .filter(|result| result.skipped.is_none())
+            .map(|result| (result.offset, result.offset + result.length))
+            .collect()
+    };
+    spans.sort_unstable();
+    let total: u64 = spans.iter().map(|(start, end)| end - start).sum();
+    debug!(scheme = table.scheme, partitions = results.len(), total, include_disk, "Hashing partitions");
+
+    let buffer_size = resources::io_buffer_size() as u64;
+    let mut done = 0u64;
+    progress(0, total);
+    for (start, end) in spans {
+        let mut offset = start;
+        while offset < end {
+            let data = image.read_at(offset, (end - offset).min(buffer_size) as usize)?;
+            if data.is_empty() {
+                return Err(format!("Image ended at byte {} of {}", offset, disk_size));
+            }
+            let data_end = offset + data.len() as u64;
+            if let Some(hasher) = &mut disk_hasher {
+                hasher.update(&data);
+            }
+            for (result, state) in results.iter().zip(&mut hashers) {
+                let Some(state) = state else { continue };
+                let from = result.offset.max(offset);
+                let to = (result.offset + result.length).min(data_end);
+                if from < to {
+                    let partition_started = *state.started.get_or_insert_with(Instant::now);
+                    state.hasher.update(&data[(from - offset) as usize..(to - offset) as usize]);
+                    state.elapsed = partition_started.elapsed();
+                }
+            }
+            done += data.len() as u64;
+            offset = data_end;
+            progress(done, total);
+        }
+    }
+
+    for (result, state) in results.iter_mut().zip(hashers) {
+        if let Some(state) = state {
+            result.hash = Some(state.hasher.finalize());
+            result.duration_secs = state.elapsed.as_secs_f64();
+        }
+    }
+    Ok(PartitionHashReport {
+        algorithm: algo.name().to_string(),
+        partition_scheme: table.scheme.to_string(),
+        sector_size: table.sector_size,
+        disk_size,
+        partitions: results,
+        disk_hash: disk_hasher.map(StreamingHasher::finalize),
+        duration_secs: started.elapsed().as_secs_f64(),
+    })
+}
+
+/// Why a table entry can't be hashed, if it can't
+fn partition_problem(partition: &PartitionTriage, all: &[PartitionTriage], disk_size: u64) -> Option<String> {
+    if partition.size == 0 {
+        return Some("Empty partition entry".to_string());
+    }
+    let end = match partition.offset.checked_add(partition.size) {
+        Some(end) if end <= disk_size => end,
+        _ => return Some(format!(
+            "Runs past the end of the image ({} bytes at offset {}, image is {} bytes)",
+            partition.size, partition.offset, disk_size
+        )),
+    };
+    let overlapping: Vec<String> = all.iter()
+        .filter(|other| other.index != partition.index && other.size > 0)
+        .filter(|other| other.offset < end && partition.offset < other.offset.saturating_add(other.size))
+        .map(|other| other.index.to_string())
+        .collect();
+    (!overlapping.is_empty()).then(|| format!("Overlaps partition {}", overlapping.join(", ")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_segment_progress_emissions_are_sampled() {
+        let dir = tempfile::tempdir().unwrap();
+        let segment_paths: Vec<PathBuf> = (1..=4)
+            .map(|n| {
+                let path = dir.path().join(format!("disk.{:03}", n));
+                std::fs::write(&path, vec![0u8; 1000]).unwrap();
+                path
+            })
+            .collect();
+
+        // Simulated fast hash: 210k callbacks per segment, with a clock
+        // tick (handed straight to the sampler) every 7k of them
+        let (tick, ticks) = std::sync::mpsc::sync_channel(0);
+        let callbacks = std::sync::atomic::AtomicUsize::new(0);
+        let hash_segment = |_: &str, _: &str, progress: &mut dyn FnMut(u64, u64)| {
+            for step in 0..30u64 {
+                for i in 0..7_000u64 {
+                    progress(step * 7_000 + i, 210_000);
+                    callbacks.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                }
+                tick.send(()).unwrap();
+            }
+            Ok("00".to_string())
+        };
+
+        let clock = crate::common::progress::ManualClock(ticks);
+        let mut events = Vec::new();
+        let results = verify_segment_files(segment_paths, "md5", &[], clock, |p| events.push(p), hash_segment).unwrap();
+
+        assert_eq!(results.len(), 4);
+        assert_eq!(callbacks.into_inner(), 4 * 210_000);
+        // At most one event per tick, plus the final sample
+        assert!(!events.is_empty() && events.len() <= 4 * 30 + 1, "{} events", events.len());
+
+        let last = events.last().unwrap();
+        assert_eq!(last.segments_completed, 4);
+        assert_eq!(last.percent, 100.0);
+        assert!(last.segments.iter().all(|s| s.completed));
+    }
+
+    fn segment_hash(name: &str, number: u32, hash: &str) -> SegmentHash {
+        SegmentHash {
+            segment_name: name.to_string(),
+            segment_number: number,
+            algorithm: "MD5".to_string(),
+            hash: hash.to_string(),
+            offset_from: None,
+            offset_to: None,
+            size: None,
+            verified: None,
+        }
+    }
+
+    #[test]
+    fn test_expected_segment_names_match_across_unicode_forms_and_paths() {
+        // NFD (macOS log) and NFC (NTFS copy) spellings, in different case
+        assert_eq!(segment_key("SCHA\u{308}RDT.001"), segment_key("sch\u{e4}rdt.001"));
+        assert_eq!(file_name_part("D:\\Case 12\\SCHARDT.002"), "SCHARDT.002");
+
+        let dir = tempfile::tempdir().unwrap();
+        let segment_paths: Vec<PathBuf> = ["Sch\u{e4}rdt.001", "sch\u{e4}rdt.002"].iter()
+            .map(|name| {
+                let path = dir.path().join(name);
+                std::fs::write(&path, b"x").unwrap();
+                path
+            })
+            .collect();
+        let md5 = "d41d8cd98f00b204e9800998ecf8427e";
+        let expected = [
+            segment_hash("SCHA\u{308}RDT.001", 1, md5),
+            segment_hash("D:\\Case 12\\SCHA\u{308}RDT.002", 2, md5),
+            segment_hash("SCHA\u{308}RDT.003", 3, md5),
+        ];
+        let hash_segment = |_: &str, _: &str, _: &mut dyn FnMut(u64, u64)| Ok(md5.to_string());
+        let results = verify_segment_files(segment_paths, "md5", &expected, PROGRESS_INTERVAL, |_| {}, hash_segment).unwrap();
+
+        let rows: Vec<_> = results.iter().map(|r| (r.segment_number, r.verified, r.missing)).collect();
+        assert_eq!(rows, [(1, Some(true), false), (2, Some(true), false), (3, None, true)]);
+        assert_eq!(results[2].segment_name, "SCHA\u{308}RDT.003");
+        assert_eq!(results[2].expected_hash.as_deref(), Some(md5));
+        assert!(results[2].to_segment_hash().is_none());
+    }
+
+    #[test]
+    fn test_dc3dd_split_hashes_match_splits_by_sector_range() {
+        // 102 splits of 2 sectors, split i filled with byte i (see the log)
+        let dir = tempfile::tempdir().unwrap();
+        for i in 0..102u8 {
+            std::fs::write(dir.path().join(format!("usb.{:04}", i)), vec![i; 1024]).unwrap();
+        }
+        std::fs::write(dir.path().join("usb.log"), include_str!("containers/testdata/dc3dd_split.hashlog")).unwrap();
+        let first = dir.path().join("usb.0000");
+        let first = first.to_str().unwrap();
+
+        let log = containers::find_companion_log(first).unwrap();
+        assert_eq!(log.segment_hashes.len(), 102);
+        let piece = &log.segment_hashes[100];
+        assert_eq!((piece.offset_from, piece.offset_to, piece.size), (Some(100 * 1024), Some(101 * 1024 - 1), Some(1024)));
+
+        let results = verify_raw_segments(first, "md5", &log.segment_hashes, |_| {}).unwrap();
+        assert_eq!(results.len(), 102);
+        assert!(results.iter().all(|r| r.verified == Some(true) && !r.missing));
+        assert_eq!(results[100].segment_name, "usb.0100");
+        assert_eq!(results[100].expected_hash.as_deref(), Some(log.segment_hashes[100].hash.as_str()));
+    }
+
+    #[test]
+    fn test_ambiguous_companion_log_needs_confirmation() {
+        let mut log = CompanionLogInfo {
+            log_path: "/evidence/acquisition.txt".to_string(),
+            stored_hashes: vec![StoredHash {
+                algorithm: "SHA-1".to_string(),
+                hash: "93d522376d89b8dfe6bb61e4abef2bbb7102765a".to_string(),
+                verified: None,
+                timestamp: None,
+                source: Some("companion".to_string()),
+                offset: None,
+                size: None,
+            }],
+            confidence: LogConfidence::Ambiguous,
+            other_candidates: vec!["/evidence/notes.txt".to_string()],
+            ..Default::default()
+        };
+        assert!(companion_expected_hash(&log, "sha1", false).is_err());
+        assert!(companion_expected_hash(&log, "sha1", true).unwrap().is_some());
+
+        log.confidence = LogConfidence::Exact;
+        assert!(companion_expected_hash(&log, "sha1", false).unwrap().is_some());
+        assert!(companion_expected_hash(&log, "md5", false).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_verify_status_against_companion_log() {
+        let dir = tempfile::tempdir().unwrap();
+        let image = dir.path().join("disk.dd");
+        let data: Vec<u8> = (0..65536u32).map(|i| (i % 253) as u8).collect();
+        std::fs::write(&image, &data).unwrap();
+        let path = image.to_string_lossy().to_string();
+        let md5 = raw::verify(&path, "md5").unwrap();
+
+        let record = verify_status(&path, "Raw", "md5", |_, _| {});
+        assert_eq!(record.status, STATUS_NO_EXPECTED);
+        assert_eq!(record.computed_hash.as_deref(), Some(md5.as_str()));
+        assert!(status_is_current(&record));
+
+        std::fs::write(dir.path().join("disk.txt"), format!("Image: disk.dd\nMD5 checksum: {}\n", md5.to_uppercase())).unwrap();
+        let record = verify_status(&path, "Raw", "md5", |_, _| {});
+        assert_eq!((record.status.as_str(), record.expected_source.as_deref()), (STATUS_VERIFIED, Some("companion")));
+
+        std::fs::write(dir.path().join("disk.txt"), "Image: disk.dd\nMD5 checksum: 0123456789abcdef0123456789abcdef\n").unwrap();
+        let record = verify_status(&path, "Raw", "md5", |_, _| {});
+        assert_eq!(record.status, STATUS_MISMATCH);
+
+        // Changing the file invalidates the stored status
+        std::fs::write(&image, &data[1..]).unwrap();
+        assert!(!status_is_current(&record));
+        let record = verify_status(&dir.path().join("gone.dd").to_string_lossy(), "Raw", "md5", |_, _| {});
+        assert_eq!(record.status, STATUS_ERROR);
+        assert!(record.error.is_some());
+    }
+
+    #[test]
+    fn test_find_duplicates_across_folders() {
+        let image: Vec<u8> = (0..3000u32).map(|i| (i % 251) as u8).collect();
+        let write_set = |dir: &Path, data: &[u8]| {
+            for (n, segment) in data.chunks(1024).enumerate() {
+                std::fs::write(dir.join(format!("disk.{:03}", n + 1)), segment).unwrap();
+            }
+            dir.join("disk.001").to_string_lossy().to_string()
+        };
+        let intake = tempfile::tempdir().unwrap();
+        let copy = tempfile::tempdir().unwrap();
+        let other = tempfile::tempdir().unwrap();
+        let small = tempfile::tempdir().unwrap();
+        let original = write_set(intake.path(), &image);
+        let copied = write_set(copy.path(), &image);
+        // Same size, different content in the last segment
+        let mut altered = image.clone();
+        altered[2999] ^= 0xff;
+        let lookalike = write_set(other.path(), &altered);
+        let truncated = write_set(small.path(), &image[..2000]);
+        std::fs::write(copy.path().join("disk.txt"), "Image: disk.001\nMD5 checksum: 0123456789abcdef0123456789abcdef\n").unwrap();
+
+        let input = |path: &str| BatchFileInput { path: path.to_string(), container_type: "Raw".to_string() };
+        let second_segment = intake.path().join("disk.002").to_string_lossy().to_string();
+        let files = [input(&original), input(&second_segment), input(&copied), input(&lookalike), input(&truncated)];
+
+        let started = Mutex::new(Vec::new());
+        let groups = find_duplicates(&files, "sha256", |progress| {
+            if progress.status == "started" {
+                started.lock().unwrap().push(progress.path);
+            }
+        });
+
+        // The smaller set never gets hashed, the second segment is the same set
+        let mut started = started.into_inner().unwrap();
+        started.sort();
+        let mut expected = vec![original.clone(), copied.clone(), lookalike];
+        expected.sort();
+        assert_eq!(started, expected);
+
+        assert_eq!(groups.len(), 1);
+        let group = &groups[0];
+        assert_eq!(group.total_size, 3000);
+        assert_eq!(group.hash, raw::verify(&original, "sha256").unwrap());
+        assert_eq!(group.copies.iter().map(|c| c.path.as_str()).collect::<Vec<_>>(), vec![original.as_str(), copied.as_str()]);
+        assert!(group.copies[0].companion_log.is_none());
+        assert!(group.copies[1].companion_log.as_deref().is_some_and(|log| log.ends_with("disk.txt")));
+
+        // The same grouping is reported per file by batch_hash
+        let results = batch_hash(&files[2..], "md5", |_| {});
+        assert_eq!(results[0].duplicates, Vec::<String>::new());
+        let results = batch_hash(&[input(&original), input(&copied), input(&truncated)], "md5", |_| {});
+        assert_eq!(results[0].duplicates, vec![copied.clone()]);
+        assert_eq!(results[1].duplicates, vec![original]);
+        assert!(results[2].duplicates.is_empty());
+    }
+
+    #[test]
+    fn test_gpt_partitions_hash_like_their_dd_slices() {
+        const MIB: usize = 1024 * 1024;
+        let mut disk: Vec<u8> = (0..4 * MIB).map(|i| (i % 251) as u8 ^ (i / 4096) as u8).collect();
+        // Protective MBR, GPT header at LBA 1, entries at LBA 2
+        disk[446 + 4] = 0xEE;
+        disk[510..512].copy_from_slice(&[0x55, 0xAA]);
+        disk[512..520].copy_from_slice(b"EFI PART");
+        disk[512 + 72..512 + 80].copy_from_slice(&2u64.to_le_bytes());
+        disk[512 + 80..512 + 84].copy_from_slice(&128u32.to_le_bytes());
+        disk[512 + 84..512 + 88].copy_from_slice(&128u32.to_le_bytes());
+        disk[1024..1024 + 128 * 128].fill(0);
+        let basic_data = [0xA2, 0xA0, 0xD0, 0xEB, 0xE5, 0xB9, 0x33, 0x44, 0x87, 0xC0, 0x68, 0xB6, 0xB7, 0x26, 0x99, 0xC7];
+        // Two good partitions, two that overlap each other, one past the end
+        for (slot, (first, last)) in [(2048u64, 4095u64), (4096, 6143), (6144, 7167), (7000, 8000), (8001, 20_000)].iter().enumerate() {
+            let entry = &mut disk[1024 + slot * 128..][..128];
+            entry[..16].copy_from_slice(&basic_data);
+            entry[32..40].copy_from_slice(&first.to_le_bytes());
+            entry[40..48].copy_from_slice(&last.to_le_bytes());
+        }
+        let dir = tempfile::tempdir().unwrap();
+        let image = dir.path().join("disk.dd");
+        std::fs::write(&image, &disk).unwrap();
+        let slice_hash = |name: &str, from: usize, to: usize| {
+            let path = dir.path().join(name);
+            std::fs::write(&path, &disk[from..to]).unwrap();
+            raw::verify(path.to_str().unwrap(), "sha256").unwrap()
+        };
+
+        let mut last = (0, 0);
+        let report = hash_partitions(image.to_str().unwrap(), "sha256", true, |done, total| last = (done, total)).unwrap();
+        assert_eq!(report.partition_scheme, "GPT");
+        assert_eq!(last, (4 * MIB as u64, 4 * MIB as u64));
+        assert_eq!(report.disk_hash, Some(raw::verify(image.to_str().unwrap(), "sha256").unwrap()));
+        let hashes: Vec<Option<&str>> = report.partitions.iter().map(|p| p.hash.as_deref()).collect();
+        let first = slice_hash("p1.dd", 2048 * 512, 4096 * 512);
+        let second = slice_hash("p2.dd", 4096 * 512, 6144 * 512);
+        assert_eq!(hashes, [Some(first.as_str()), Some(second.as_str()), None, None, None]);
+        assert_eq!((report.partitions[1].start_lba, report.partitions[1].length), (4096, 2048 * 512));
+        assert_eq!(report.partitions[2].skipped.as_deref(), Some("Overlaps partition 4"));
+        assert_eq!(report.partitions[3].skipped.as_deref(), Some("Overlaps partition 3"));
+        assert!(report.partitions[4].skipped.as_deref().unwrap().starts_with("Runs past the end"));
+
+        // Without the disk hash only the partitions are read
+        let report = hash_partitions(image.to_str().unwrap(), "sha256", false, |done, total| last = (done, total)).unwrap();
+        assert_eq!(last, (4096 * 512, 4096 * 512));
+        assert_eq!(report.disk_hash, None);
+        assert_eq!(report.partitions[0].hash.as_deref(), Some(first.as_str()));
+    }
+}
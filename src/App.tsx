@@ -5,6 +5,7 @@ import type { ProjectLocations } from "./components";
 import ProcessedDatabasePanel from "./components/ProcessedDatabasePanel";
 import ProcessedDetailPanel from "./components/ProcessedDetailPanel";
 import type { ParsedMetadata, TabViewMode } from "./components";
+import { formatTimestamp } from "./utils";
 import ffxLogo from "./assets/branding/ffx-logo-48.png";
 import "./App.css";
 
@@ -142,6 +143,7 @@ function App() {
 
   // Store cleanup function reference
   let cleanupSystemStats: (() => void) | undefined;
+  let cleanupVerificationStatus: (() => void) | undefined;
   
   // Handle window resize for responsive toolbar
   const handleResize = () => setWindowWidth(window.innerWidth);
@@ -149,6 +151,7 @@ function App() {
   onMount(async () => {
     const unlisten = await fileManager.setupSystemStatsListener();
     cleanupSystemStats = unlisten;
+    cleanupVerificationStatus = await fileManager.setupVerificationStatusListener();
     window.addEventListener('mousemove', handleMouseMove);
     window.addEventListener('mouseup', handleMouseUp);
     window.addEventListener('resize', handleResize);
@@ -190,6 +193,7 @@ function App() {
 
   onCleanup(() => {
     cleanupSystemStats?.();
+    cleanupVerificationStatus?.();
     if (sessionInitTimer) clearTimeout(sessionInitTimer);
     if (fileSaveTimer) clearTimeout(fileSaveTimer);
     window.removeEventListener('mousemove', handleMouseMove);
@@ -293,6 +297,7 @@ function App() {
         onScan={() => fileManager.scanForFiles()}
         onHashSelected={() => hashManager.hashSelectedFiles()}
         onLoadAll={() => fileManager.loadAllInfo()}
+        onQuickCheckSelected={() => fileManager.quickCheckSelected()}
         // Project management
         projectPath={projectManager.projectPath()}
         projectModified={projectManager.modified()}
@@ -451,6 +456,8 @@ function App() {
               storedHashesGetter={hashManager.getAllStoredHashesSorted}
               busy={fileManager.busy()}
               onVerifySegments={(file) => hashManager.verifySegments(file)}
+              confirmedCompanionLogs={hashManager.confirmedCompanionLogs}
+              onConfirmCompanionLog={hashManager.confirmCompanionLog}
               onLoadInfo={(file) => fileManager.loadFileInfo(file, true)}
               formatHashDate={hashManager.formatHashDate}
               onTabSelect={(file) => fileManager.setActiveFile(file)}
@@ -497,8 +504,8 @@ function App() {
                   path: fileManager.activeFile()!.path,
                   filename: fileManager.activeFile()!.filename,
                   size: fileManager.activeFile()!.size,
-                  created: fileManager.activeFile()!.created,
-                  modified: fileManager.activeFile()!.modified,
+                  created: formatTimestamp(fileManager.activeFile()!.created),
+                  modified: formatTimestamp(fileManager.activeFile()!.modified),
                   container_type: fileManager.activeFile()!.container_type,
                   segment_count: fileManager.activeFile()!.segment_count
                 } : null}
@@ -4,6 +4,7 @@ import { listen } from "@tauri-apps/api/event";
 import { open } from "@tauri-apps/plugin-dialog";
 import "./E01V3Test.css";
 import { formatBytes } from "./utils";
+import type { EwfVerifyReport } from "./types";
 
 type E01V3Info = {
   segment_count: number;
@@ -21,6 +22,7 @@ type StatusState = {
 };
 
 type VerifyProgress = {
+  jobId: string;
   path: string;
   current: number;
   total: number;
@@ -110,7 +112,7 @@ export function E01V3Test() {
     });
 
     try {
-      const result = await invoke<string>("e01_v3_verify", {
+      const { hash: result } = await invoke<EwfVerifyReport>("e01_v3_verify", {
         inputPath: filePath(),
         algorithm: algorithm,
       });
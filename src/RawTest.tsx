@@ -20,6 +20,7 @@ type StatusState = {
 };
 
 type VerifyProgress = {
+  jobId: string;
   path: string;
   current: number;
   total: number;
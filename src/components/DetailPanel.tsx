@@ -31,6 +31,8 @@ interface DetailPanelProps {
   storedHashesGetter: (info: ContainerInfo | undefined) => StoredHash[];
   busy: boolean;
   onVerifySegments: (file: DiscoveredFile) => void;
+  confirmedCompanionLogs: () => Set<string>;
+  onConfirmCompanionLog: (logPath: string) => void;
   onLoadInfo: (file: DiscoveredFile) => void;
   formatHashDate: (timestamp: string) => string;
   // Tab switching callback (to update file manager's active file, null = all tabs closed)
@@ -283,6 +285,8 @@ export function DetailPanel(props: DetailPanelProps) {
             storedHashes={props.storedHashesGetter(activeFileInfo())}
             busy={props.busy}
             onVerifySegments={() => activeTabFile() && props.onVerifySegments(activeTabFile()!)}
+            companionLogConfirmed={props.confirmedCompanionLogs().has(activeFileInfo()?.companion_log?.log_path ?? "")}
+            onConfirmCompanionLog={props.onConfirmCompanionLog}
             onLoadInfo={() => activeTabFile() && props.onLoadInfo(activeTabFile()!)}
             formatHashDate={props.formatHashDate}
           />
@@ -1,7 +1,8 @@
 import { For, Show, createSignal, createEffect } from "solid-js";
-import type { DiscoveredFile, ContainerInfo, TreeEntry, SegmentHashResult, HashHistoryEntry, HashAlgorithm, StoredHash } from "../types";
+import type { Ad1SegmentIssue, ChunkStats, DiscoveredFile, ContainerInfo, TreeEntry, SegmentHashResult, HashHistoryEntry, HashAlgorithm, StoredHash } from "../types";
 import type { FileStatus, FileHashInfo } from "../hooks";
-import { formatBytes, typeIcon, typeClass, debounce, formatOffsetLabel } from "../utils";
+import { useFormatCapabilities } from "../hooks";
+import { formatBytes, typeIcon, typeClass, debounce, formatOffsetLabel, formatTimestamp } from "../utils";
 
 interface DetailPanelContentProps {
   activeFile: DiscoveredFile | null;
@@ -19,6 +20,8 @@ interface DetailPanelContentProps {
   storedHashes: StoredHash[];
   busy: boolean;
   onVerifySegments: () => void;
+  companionLogConfirmed: boolean;
+  onConfirmCompanionLog: (logPath: string) => void;
   onLoadInfo: () => void;
   formatHashDate: (timestamp: string) => string;
 }
@@ -45,8 +48,13 @@ export function DetailPanelContent(props: DetailPanelContentProps) {
   // Reactive helpers that properly track props changes
   const isHashing = () => props.fileStatus?.status === "hashing";
   const isVerifyingSegments = () => props.fileStatus?.status === "verifying-segments";
-  const isIncomplete = () => (props.fileInfo?.ad1?.missing_segments?.length ?? 0) > 0;
+  const isIncomplete = () => (props.fileInfo?.segments?.missing.length || props.fileInfo?.ad1?.missing_segments?.length || 0) > 0
+    || props.fileInfo?.archive?.set_report?.complete === false;
   const currentProgress = () => props.fileStatus?.progress ?? 0;
+  // Actions follow the backend's capability matrix; allowed while it loads
+  const { capabilitiesFor } = useFormatCapabilities();
+  const capabilities = () => props.activeFile ? capabilitiesFor(props.activeFile.container_type) : undefined;
+  const canVerifySegments = () => capabilities()?.can_verify_segments ?? true;
   
   return (
     <main class="detail-panel">
@@ -76,12 +84,17 @@ export function DetailPanelContent(props: DetailPanelContentProps) {
               <div class="stat-row">
                 <div class="stat-item">
                   <span class="stat-label">Size</span>
-                  <span class="stat-value" title={`${file.size.toLocaleString()} bytes`}>{formatBytes(file.size)}</span>
+                  <Show
+                    when={(props.fileInfo?.segments?.count ?? 1) > 1}
+                    fallback={<span class="stat-value" title={`${file.size.toLocaleString()} bytes`}>{formatBytes(file.size)}</span>}
+                  >
+                    <span class="stat-value" title={`${props.fileInfo!.segments!.total_size.toLocaleString()} bytes across all segments (first segment: ${formatBytes(file.size)})`}>{formatBytes(props.fileInfo!.segments!.total_size)}</span>
+                  </Show>
                 </div>
-                <Show when={file.segment_count}>
+                <Show when={props.fileInfo?.segments?.count ?? file.segment_count}>
                   <div class="stat-item">
                     <span class="stat-label">Segments</span>
-                    <span class="stat-value" title={`${file.segment_count} segments`}>{file.segment_count}</span>
+                    <span class="stat-value" title={`${props.fileInfo?.segments?.count ?? file.segment_count} segments`}>{props.fileInfo?.segments?.count ?? file.segment_count}</span>
                   </div>
                 </Show>
                 
@@ -89,7 +102,7 @@ export function DetailPanelContent(props: DetailPanelContentProps) {
                 <Show when={props.fileInfo?.e01?.acquiry_date}>
                   <div class="stat-item">
                     <span class="stat-label">Acquired</span>
-                    <span class="stat-value" title={`Acquisition date from E01 header: ${props.fileInfo!.e01!.acquiry_date}`}>{props.fileInfo!.e01!.acquiry_date}</span>
+                    <span class="stat-value" title={`Acquisition date from E01 header: ${props.fileInfo!.e01!.acquiry_date!.original}`}>{formatTimestamp(props.fileInfo!.e01!.acquiry_date)}</span>
                   </div>
                 </Show>
                 
@@ -97,7 +110,7 @@ export function DetailPanelContent(props: DetailPanelContentProps) {
                 <Show when={props.fileInfo?.ad1?.companion_log?.acquisition_date}>
                   <div class="stat-item">
                     <span class="stat-label">Acquired</span>
-                    <span class="stat-value" title={`Acquisition date from AD1 companion log: ${props.fileInfo!.ad1!.companion_log!.acquisition_date}`}>{props.fileInfo!.ad1!.companion_log!.acquisition_date}</span>
+                    <span class="stat-value" title={`Acquisition date from AD1 companion log: ${props.fileInfo!.ad1!.companion_log!.acquisition_date!.original}`}>{formatTimestamp(props.fileInfo!.ad1!.companion_log!.acquisition_date)}</span>
                   </div>
                 </Show>
                 
@@ -105,7 +118,7 @@ export function DetailPanelContent(props: DetailPanelContentProps) {
                 <Show when={props.fileInfo?.ufed?.extraction_info?.start_time}>
                   <div class="stat-item">
                     <span class="stat-label">Extracted</span>
-                    <span class="stat-value" title={`Extraction date from UFED metadata: ${props.fileInfo!.ufed!.extraction_info!.start_time}`}>{props.fileInfo!.ufed!.extraction_info!.start_time}</span>
+                    <span class="stat-value" title={`Extraction date from UFED metadata: ${props.fileInfo!.ufed!.extraction_info!.start_time!.original}`}>{formatTimestamp(props.fileInfo!.ufed!.extraction_info!.start_time)}</span>
                   </div>
                 </Show>
                 
@@ -114,13 +127,13 @@ export function DetailPanelContent(props: DetailPanelContentProps) {
                   <Show when={file.created}>
                     <div class="stat-item">
                       <span class="stat-label">File Created</span>
-                      <span class="stat-value" title={`Filesystem date (when file was created on disk): ${file.created}`}>{file.created}</span>
+                      <span class="stat-value" title={`Filesystem date (when file was created on disk): ${file.created!.original}`}>{formatTimestamp(file.created)}</span>
                     </div>
                   </Show>
                   <Show when={file.modified}>
                     <div class="stat-item">
                       <span class="stat-label">File Modified</span>
-                      <span class="stat-value" title={`Filesystem date (when file was last modified): ${file.modified}`}>{file.modified}</span>
+                      <span class="stat-value" title={`Filesystem date (when file was last modified): ${file.modified!.original}`}>{formatTimestamp(file.modified)}</span>
                     </div>
                   </Show>
                 </Show>
@@ -161,6 +174,13 @@ export function DetailPanelContent(props: DetailPanelContentProps) {
                 <div class={`hash-card computed ${props.fileHash!.verified === true ? 'verified' : props.fileHash!.verified === false ? 'failed' : 'no-stored'}`}>
                   <div class="hash-header">
                     <span class="hash-algo-label">🔐 {props.fileHash!.algorithm}</span>
+                    <Show when={props.fileHash!.scope}>
+                      <span class="hash-scope" title={props.fileHash!.scope === 'logical'
+                        ? 'Hash of the expanded partition - comparable to a dd image'
+                        : 'Hash of the sparse container bytes - will not match a dd image'}>
+                        {props.fileHash!.scope === 'logical' ? 'Logical image hash' : 'Container hash'}
+                      </span>
+                    </Show>
                     <Show when={props.fileHash!.verified === true}>
                       <span class="verify-status verified">
                         <span class="verify-icon-stacked"><span>✓</span><span>✓</span></span>
@@ -188,6 +208,13 @@ export function DetailPanelContent(props: DetailPanelContentProps) {
                   <Show when={props.fileHash!.verified === null && props.hashHistory.length === 0}>
                     <div class="verification-detail neutral">No stored hash or history to verify against</div>
                   </Show>
+                  <Show when={props.fileHash!.chunkStats}>
+                    {(stats) => (
+                      <div class="verification-detail neutral" title={describeRatioHistogram(stats())}>
+                        📦 {describeChunkStats(stats())}
+                      </div>
+                    )}
+                  </Show>
                 </div>
               </Show>
               
@@ -223,8 +250,8 @@ export function DetailPanelContent(props: DetailPanelContentProps) {
                     <button 
                       class="verify-segments-btn" 
                       onClick={props.onVerifySegments} 
-                      disabled={props.busy || isVerifyingSegments() || isIncomplete()}
-                      title={isIncomplete() ? "Cannot verify: missing segments" : "Verify each segment against stored hash"}
+                      disabled={props.busy || isVerifyingSegments() || isIncomplete() || !canVerifySegments()}
+                      title={!canVerifySegments() ? `Segment verification is not supported for ${file.container_type}` : isIncomplete() ? "Cannot verify: missing segments" : "Verify each segment against stored hash"}
                     >
                       {isIncomplete() ? '⚠️ Incomplete' : '🔍 Verify Segments'}
                     </button>
@@ -232,7 +259,7 @@ export function DetailPanelContent(props: DetailPanelContentProps) {
                   <div class="segment-hash-list">
                     <For each={props.fileInfo!.companion_log!.segment_hashes}>
                       {(sh) => {
-                        const computed = () => props.segmentResults.find(r => r.segment_name.toLowerCase() === sh.segment_name.toLowerCase());
+                        const computed = () => props.segmentResults.find(r => segmentKey(r.segment_name) === segmentKey(sh.segment_name));
                         return (
                           <div class={`segment-hash-row ${computed()?.verified === true ? 'verified' : computed()?.verified === false ? 'failed' : ''}`}>
                             <span class="seg-name" title={sh.segment_name}>{sh.segment_name}</span>
@@ -241,6 +268,7 @@ export function DetailPanelContent(props: DetailPanelContentProps) {
                             <Show when={sh.size}><span class="seg-size">{formatBytes(sh.size!)}</span></Show>
                             <Show when={computed()?.verified === true}><span class="seg-badge ok">✓</span></Show>
                             <Show when={computed()?.verified === false}><span class="seg-badge fail">✗</span></Show>
+                            <Show when={computed()?.missing}><span class="seg-badge fail" title="No segment file with this name was found">not found</span></Show>
                             <button class="seg-copy" onClick={() => navigator.clipboard.writeText(sh.hash)} title="Copy">📋</button>
                           </div>
                         );
@@ -265,6 +293,7 @@ export function DetailPanelContent(props: DetailPanelContentProps) {
                           <span class="seg-time">{sr.duration_secs.toFixed(1)}s</span>
                           <Show when={sr.verified === true}><span class="seg-badge ok">✓</span></Show>
                           <Show when={sr.verified === false}><span class="seg-badge fail">✗</span></Show>
+                          <Show when={sr.missing}><span class="seg-badge fail" title="Expected hash with no matching segment file">not found</span></Show>
                           <button class="seg-copy" onClick={() => navigator.clipboard.writeText(sr.computed_hash)} title="Copy">📋</button>
                         </div>
                       )}
@@ -286,6 +315,24 @@ export function DetailPanelContent(props: DetailPanelContentProps) {
                 </div>
               </Show>
               
+              {/* Companion log that could belong to another image in the folder */}
+              <Show when={props.fileInfo?.companion_log?.confidence === "ambiguous" && !props.companionLogConfirmed}>
+                <div class="compact-section">
+                  <div class="section-header-compact">
+                    <span class="section-title" title={[props.fileInfo!.companion_log!.log_path, ...props.fileInfo!.companion_log!.other_candidates].join("\n")}>
+                      ⚠️ Companion log match is ambiguous ({props.fileInfo!.companion_log!.other_candidates.length + 1} candidates)
+                    </span>
+                    <button
+                      class="btn-warning"
+                      onClick={() => props.onConfirmCompanionLog(props.fileInfo!.companion_log!.log_path)}
+                      title="Compare computed hashes against this log's stored hashes"
+                    >
+                      Use {props.fileInfo!.companion_log!.log_path.split(/[\\/]/).pop()}
+                    </button>
+                  </div>
+                </div>
+              </Show>
+              
               {/* Container details - includes stored hashes */}
               <Show when={props.fileInfo}>
                 <ContainerDetails info={props.fileInfo!} storedHashes={props.storedHashes} />
@@ -323,7 +370,7 @@ export function DetailPanelContent(props: DetailPanelContentProps) {
               
               {/* Action buttons */}
               <div class="detail-actions-compact">
-                <Show when={props.fileInfo?.raw && (props.fileInfo!.raw!.segment_count > 1 || (props.fileInfo?.companion_log?.segment_hashes?.length ?? 0) > 0)}>
+                <Show when={props.fileInfo?.raw && canVerifySegments() && (props.fileInfo!.raw!.segment_count > 1 || (props.fileInfo?.companion_log?.segment_hashes?.length ?? 0) > 0)}>
                   <button 
                     class="action-btn-secondary" 
                     onClick={props.onVerifySegments} 
@@ -343,6 +390,13 @@ export function DetailPanelContent(props: DetailPanelContentProps) {
                   </button>
                 </Show>
               </div>
+              <Show when={capabilities()?.notes.length}>
+                <div class="capability-notes">
+                  <For each={capabilities()!.notes}>
+                    {(note) => <div class="capability-note">ℹ️ {note}</div>}
+                  </For>
+                </div>
+              </Show>
             </div>
           );
         }}
@@ -425,6 +479,40 @@ function InfoRows(props: { fields: InfoField[] }) {
 // Normalize container info to common field structure
 // ============================================================================
 
+/** Segment name as compared with verification results: file name only, NFC, lowercase (mirrors the backend lookup) */
+function segmentKey(name: string): string {
+  return (name.trim().split(/[\\/]/).pop() ?? name).normalize("NFC").toLowerCase();
+}
+
+function describeSegmentIssue(issue: Ad1SegmentIssue): string {
+  switch (issue.kind) {
+    case "missing": return `segment ${issue.index} of ${issue.total} is missing (${issue.file})`;
+    case "unreadable": return `${issue.file}: unreadable header (${issue.error})`;
+    case "index_mismatch": return `${issue.file} is segment ${issue.found} by its header, expected ${issue.expected}`;
+    case "count_mismatch": return `${issue.file} declares ${issue.found} segments, expected ${issue.expected}`;
+  }
+}
+
+function describeChunkStats(stats: ChunkStats): string {
+  const parts = [
+    `${stats.compressed_chunks.toLocaleString()} compressed`,
+    `${stats.uncompressed_chunks.toLocaleString()} uncompressed`,
+  ];
+  if (stats.sparse_chunks > 0) parts.push(`${stats.sparse_chunks.toLocaleString()} sparse`);
+  let text = `Chunks: ${parts.join(', ')} • ${formatBytes(stats.stored_bytes)} stored for ${formatBytes(stats.logical_bytes)}`;
+  if (stats.mean_ratio != null) {
+    const pct = (ratio?: number | null) => `${((ratio ?? 0) * 100).toFixed(0)}%`;
+    text += ` • ratio ${pct(stats.mean_ratio)} (${pct(stats.min_ratio)}–${pct(stats.max_ratio)})`;
+  }
+  return text;
+}
+
+function describeRatioHistogram(stats: ChunkStats): string {
+  const buckets = ['≤10%', '≤25%', '≤50%', '≤75%', '>75%'];
+  return 'Compressed chunks by stored/logical size: '
+    + buckets.map((label, i) => `${label}: ${stats.ratio_histogram[i].toLocaleString()}`).join(', ');
+}
+
 function normalizeContainerFields(info: ContainerInfo, storedHashes: StoredHash[]): InfoField[] {
   const fields: InfoField[] = [];
   
@@ -443,18 +531,30 @@ function normalizeContainerFields(info: ContainerInfo, storedHashes: StoredHash[
         format: 'warning'
       });
     }
+    // Headers that contradict the set (renamed or foreign segments)
+    const contradictions = (ad1.segment_issues ?? []).filter(issue => issue.kind !== 'missing');
+    if (contradictions.length > 0) {
+      fields.push({
+        label: '⚠️ Segment headers',
+        value: contradictions.map(describeSegmentIssue).join('; '),
+        type: 'full-width',
+        format: 'warning'
+      });
+    }
     
+    const segmentsFound = ad1.segment_files?.length ?? ad1.segments?.length ?? 0;
     fields.push(
       { label: 'Format', value: `AD1 (${ad1.logical.signature})` },
       { label: 'Version', value: ad1.logical.image_version },
-      { label: 'Segments', value: `${ad1.segment_files?.length ?? 0} / ${ad1.segment.segment_number}${ad1.missing_segments?.length ? ' (incomplete)' : ''}` },
+      { label: 'Created By', value: ad1.creator_tool },
+      { label: 'Segments', value: `${segmentsFound} found / ${ad1.segment.segment_number} declared${ad1.segment_issues?.length || ad1.missing_segments?.length ? ' (incomplete)' : ''}` },
       { label: 'Total Size', value: ad1.total_size, format: 'bytes' },
       { label: 'Items', value: ad1.item_count },
       // Case metadata from companion log
       { label: 'Case #', value: log?.case_number, type: 'highlight' },
       { label: 'Evidence #', value: log?.evidence_number, type: 'highlight' },
       { label: 'Examiner', value: log?.examiner },
-      { label: 'Acquired', value: log?.acquisition_date },
+      { label: 'Acquired', value: formatTimestamp(log?.acquisition_date) },
       // Volume/system info from header
       { label: 'Volume', value: vol?.volume_label },
       { label: 'Filesystem', value: vol?.filesystem },
@@ -476,13 +576,17 @@ function normalizeContainerFields(info: ContainerInfo, storedHashes: StoredHash[
       { label: 'Segments', value: e01.segment_count },
       { label: 'Total Size', value: e01.total_size, format: 'bytes' },
       { label: 'Compression', value: e01.compression },
+      { label: 'Media', value: e01.media_type },
+      { label: 'Sessions', value: e01.sessions?.length
+        ? e01.sessions.map(s => `${s.first_sector}+${s.sector_count}`).join(', ')
+        : undefined },
       { label: 'Bytes/Sector', value: e01.bytes_per_sector },
       { label: 'Sectors/Chunk', value: e01.sectors_per_chunk },
       { label: 'Case #', value: e01.case_number, type: 'highlight' },
       { label: 'Evidence #', value: e01.evidence_number, type: 'highlight' },
       { label: 'Examiner', value: e01.examiner_name },
-      { label: 'Acquired', value: e01.acquiry_date },
-      { label: 'System Date', value: e01.system_date },
+      { label: 'Acquired', value: formatTimestamp(e01.acquiry_date) },
+      { label: 'System Date', value: formatTimestamp(e01.system_date) },
       { label: 'Model', value: e01.model, type: 'device' },
       { label: 'Serial #', value: e01.serial_number, type: 'device' },
       { label: 'Description', value: e01.description, type: 'full-width' },
@@ -491,6 +595,23 @@ function normalizeContainerFields(info: ContainerInfo, storedHashes: StoredHash[
     );
   }
   
+  // Best-effort parse of a damaged container - the details below are partial
+  const parseWarnings = info.ad1?.parse_warnings ?? info.e01?.parse_warnings ?? info.l01?.parse_warnings;
+  if (parseWarnings && parseWarnings.length > 0) {
+    fields.unshift({
+      label: '⚠️ Partial',
+      value: parseWarnings.map(w => `0x${w.address.toString(16)}: ${w.error}`).join('; '),
+      type: 'full-width',
+      format: 'warning'
+    });
+  }
+  
+  // Renamed, shuffled, foreign or mixed-naming segment files - the image hash would be wrong
+  const segmentCheck = info.e01?.segment_consistency;
+  if (segmentCheck && segmentCheck.issues.length > 0) {
+    fields.push({ label: '⚠ Segment Set', value: segmentCheck.issues.join('; '), type: 'full-width', format: 'notes' });
+  }
+  
   // L01 (Logical Evidence - uses same EwfInfo type as E01)
   if (info.l01) {
     const l01 = info.l01;
@@ -498,14 +619,16 @@ function normalizeContainerFields(info: ContainerInfo, storedHashes: StoredHash[
       { label: 'Format', value: l01.format_version },
       { label: 'Segments', value: l01.segment_count },
       { label: 'Total Size', value: l01.total_size, format: 'bytes' },
+      { label: 'Files', value: l01.logical_entries?.file_count },
+      { label: 'Folders', value: l01.logical_entries?.folder_count },
       { label: 'Compression', value: l01.compression },
       { label: 'Bytes/Sector', value: l01.bytes_per_sector },
       { label: 'Sectors/Chunk', value: l01.sectors_per_chunk },
       { label: 'Case #', value: l01.case_number, type: 'highlight' },
       { label: 'Evidence #', value: l01.evidence_number, type: 'highlight' },
       { label: 'Examiner', value: l01.examiner_name },
-      { label: 'Acquired', value: l01.acquiry_date },
-      { label: 'System Date', value: l01.system_date },
+      { label: 'Acquired', value: formatTimestamp(l01.acquiry_date) },
+      { label: 'System Date', value: formatTimestamp(l01.system_date) },
       { label: 'Model', value: l01.model, type: 'device' },
       { label: 'Serial #', value: l01.serial_number, type: 'device' },
       { label: 'Description', value: l01.description, type: 'full-width' },
@@ -516,11 +639,35 @@ function normalizeContainerFields(info: ContainerInfo, storedHashes: StoredHash[
   // Raw
   if (info.raw) {
     const raw = info.raw;
-    fields.push(
-      { label: 'Format', value: 'Raw Image' },
-      { label: 'Segments', value: raw.segment_count },
-      { label: 'Total Size', value: raw.total_size, format: 'bytes' },
-    );
+    if (raw.sparse) {
+      const sparse = raw.sparse;
+      fields.push(
+        { label: 'Format', value: `Android Sparse Image (v${sparse.version})`, type: 'highlight' },
+        { label: 'Container Size', value: raw.total_size, format: 'bytes' },
+        { label: 'Logical Image Size', value: raw.logical_size, format: 'bytes' },
+        { label: 'Blocks', value: `${sparse.total_blocks.toLocaleString()} × ${sparse.block_size}` },
+        { label: 'Chunks', value: `${sparse.chunk_count} (${sparse.raw_chunks} raw, ${sparse.fill_chunks} fill, ${sparse.dont_care_chunks} don't care, ${sparse.crc32_chunks} CRC32)` },
+        { label: 'Hashes', value: 'Container hash ≠ logical image hash. Compare the logical image hash with other tools\' dd hashes.', type: 'full-width' },
+      );
+    } else if (raw.gzip) {
+      const gzip = raw.gzip;
+      fields.push(
+        { label: 'Format', value: 'Raw Image (gzip)', type: 'highlight' },
+        { label: 'Compressed Size', value: gzip.compressed_size, format: 'bytes' },
+        gzip.uncompressed_size != null
+          ? { label: 'Uncompressed Size', value: gzip.uncompressed_size, format: 'bytes' }
+          : { label: 'Uncompressed Size', value: `${formatBytes(gzip.isize)} (gzip ISIZE - modulo 4 GiB, last member only; run a full scan for the exact size)` },
+        { label: 'Members', value: gzip.members ?? undefined },
+        { label: 'Original Name', value: gzip.original_name ?? undefined },
+        { label: 'Hashes', value: 'Computed on the decompressed image - comparable to a hash of the original dd.', type: 'full-width' },
+      );
+    } else {
+      fields.push(
+        { label: 'Format', value: 'Raw Image' },
+        { label: 'Segments', value: raw.segment_count },
+        { label: 'Total Size', value: raw.total_size, format: 'bytes' },
+      );
+    }
     if (raw.segment_count > 1) {
       const segList = raw.segment_names.slice(0, 5).join(', ') + 
         (raw.segment_count > 5 ? ` (+${raw.segment_count - 5} more)` : '');
@@ -528,9 +675,59 @@ function normalizeContainerFields(info: ContainerInfo, storedHashes: StoredHash[
     }
   }
   
+  // Optical disc (ISO 9660 / UDF)
+  if (info.iso) {
+    const iso = info.iso;
+    const filesystems = [
+      iso.has_iso9660 && 'ISO 9660',
+      iso.has_joliet && 'Joliet',
+      iso.has_rock_ridge && 'Rock Ridge',
+      iso.has_udf && `UDF${iso.udf_revision ? ` (${iso.udf_revision})` : ''}`,
+    ].filter(Boolean).join(', ');
+    fields.push(
+      { label: 'Format', value: info.container },
+      { label: 'Volume Label', value: iso.volume_label, type: 'highlight' },
+      { label: 'Filesystems', value: filesystems },
+      { label: 'Sectors', value: `${iso.total_sectors.toLocaleString()} × ${iso.block_size}` },
+      { label: 'Image Size', value: iso.image_size, format: 'bytes' },
+      { label: 'Created', value: iso.creation_date },
+      { label: 'Modified', value: iso.modification_date },
+      { label: 'Publisher', value: iso.publisher },
+      { label: 'Preparer', value: iso.data_preparer },
+      { label: 'Application', value: iso.application, type: 'full-width' },
+      { label: 'Bootable', value: iso.bootable ? 'Yes (El Torito)' : undefined },
+    );
+  }
+  
+  // Partition triage (E01/raw) - encrypted volumes are named as such, not by a filesystem guess
+  if (info.triage) {
+    const triage = info.triage;
+    fields.push(
+      { label: 'Partitions', value: `${triage.partition_scheme}: ${triage.partitions.map(p =>
+          `#${p.index} ${p.encryption?.description ?? p.filesystem ?? p.type_description ?? p.partition_type}${p.volume_label ? ` "${p.volume_label}"` : ''}`
+        ).join(', ')}`, type: 'full-width' },
+      { label: 'Platform', value: triage.guessed_platform },
+    );
+    if (triage.encryption_detected) {
+      fields.unshift({ label: '🔒 Encrypted', value: 'Contents are encrypted - filesystems inside cannot be parsed without the key', type: 'full-width', format: 'warning' });
+    }
+  }
+  
   // Archive (ZIP/7z)
   if (info.archive) {
     const archive = info.archive;
+    const set = archive.set_report;
+    
+    // Show warning if the multi-part set has gaps or stray parts
+    if (set && !set.complete) {
+      fields.push({
+        label: '⚠️ Incomplete',
+        value: set.issues.join('; '),
+        type: 'full-width',
+        format: 'warning'
+      });
+    }
+    
     fields.push(
       { label: 'Format', value: `${archive.format}${archive.version ? ` v${archive.version}` : ''}` },
       { label: 'Segments', value: archive.segment_count },
@@ -538,7 +735,31 @@ function normalizeContainerFields(info: ContainerInfo, storedHashes: StoredHash[
       { label: 'Entries', value: archive.entry_count },
       { label: 'AES Encrypted', value: archive.aes_encrypted ? 'Yes' : undefined, type: 'highlight' },
       { label: 'Encrypted Headers', value: archive.encrypted_headers ? 'Filenames Hidden' : undefined, type: 'highlight' },
+      { label: 'Filenames', value: archive.filenames_visible == null ? undefined : archive.filenames_visible ? 'Visible without password' : 'Hidden (encrypted header)' },
     );
+    const enc = archive.zip_encryption;
+    if (enc) {
+      const counts = [
+        [enc.unencrypted, 'unencrypted'],
+        [enc.zip_crypto, 'ZipCrypto'],
+        [enc.aes128, 'AES-128'],
+        [enc.aes192, 'AES-192'],
+        [enc.aes256, 'AES-256'],
+        [enc.other, 'other'],
+      ] as const;
+      fields.push({
+        label: 'Encryption',
+        value: counts.filter(([n]) => n > 0).map(([n, label]) => `${n} ${label}`).join(', ') + (enc.complete ? '' : ' (partial scan)'),
+        type: 'full-width',
+      });
+      if (enc.encrypted_entries.length > 0) {
+        fields.push({
+          label: 'Encrypted Entries',
+          value: enc.encrypted_entries.map(e => `${e.name} (${e.encryption})`).join(', '),
+          type: 'full-width',
+        });
+      }
+    }
     if (archive.start_header_crc_valid !== undefined && archive.start_header_crc_valid !== null) {
       fields.push({ 
         label: 'Header CRC', 
@@ -547,6 +768,17 @@ function normalizeContainerFields(info: ContainerInfo, storedHashes: StoredHash[
         condition: true 
       });
     }
+    if (archive.next_header_crc_valid !== undefined && archive.next_header_crc_valid !== null) {
+      fields.push({
+        label: 'Next Header CRC',
+        value: archive.next_header_crc_valid ? '✓ Valid' : '✗ Invalid - archive is damaged',
+        type: archive.next_header_crc_valid ? 'normal' : 'highlight',
+        condition: true
+      });
+    }
+    if (archive.truncated) {
+      fields.push({ label: '⚠ Truncated', value: 'Archive ends before its header - incomplete copy, extraction will fail', type: 'highlight' });
+    }
     fields.push(
       { label: 'Central Dir', value: archive.central_dir_offset ? `@ ${archive.central_dir_offset.toLocaleString()}` : undefined },
       { label: 'Next Header', value: archive.next_header_offset ? formatOffsetLabel(archive.next_header_offset) : undefined },
@@ -576,8 +808,8 @@ function normalizeContainerFields(info: ContainerInfo, storedHashes: StoredHash[
       { label: 'Case #', value: ufed.case_info?.case_identifier, type: 'highlight' },
       { label: 'Evidence #', value: ufed.case_info?.device_name || ufed.evidence_number, type: 'highlight' },
       { label: 'Examiner', value: ufed.case_info?.examiner_name },
-      { label: 'Acquired', value: ufed.extraction_info?.start_time },
-      { label: 'Completed', value: ufed.extraction_info?.end_time },
+      { label: 'Acquired', value: formatTimestamp(ufed.extraction_info?.start_time) },
+      { label: 'Completed', value: formatTimestamp(ufed.extraction_info?.end_time) },
       { label: 'Device', value: ufed.device_info?.full_name || ufed.device_hint, type: 'device' },
       { label: 'Model', value: ufed.device_info?.model, type: 'device' },
       { label: 'Serial #', value: ufed.device_info?.serial_number, type: 'device' },
@@ -592,6 +824,14 @@ function normalizeContainerFields(info: ContainerInfo, storedHashes: StoredHash[
     );
   }
   
+  // UFD sections the fields above don't cover ([Dongle], [Client], ...)
+  const modelledUfdSections = ['crime case', 'deviceinfo', 'general', 'sha256', 'sha1', 'md5'];
+  for (const [section, entries] of Object.entries(info.ufed?.raw_sections ?? {})) {
+    if (modelledUfdSections.includes(section.toLowerCase())) continue;
+    const pairs = Object.entries(entries).map(([key, value]) => `${key}=${value}`);
+    fields.push({ label: `[${section}]`, value: pairs.length > 0 ? pairs.join(', ') : undefined, type: 'full-width', format: 'mono' });
+  }
+  
   // Companion log
   if (info.companion_log) {
     const log = info.companion_log;
@@ -600,12 +840,18 @@ function normalizeContainerFields(info: ContainerInfo, storedHashes: StoredHash[
       { label: 'Case #', value: log.case_number, type: 'highlight' },
       { label: 'Evidence #', value: log.evidence_number, type: 'highlight' },
       { label: 'Examiner', value: log.examiner },
-      { label: 'Acquired', value: log.acquisition_started },
+      { label: 'Acquired', value: formatTimestamp(log.acquisition_started) },
       { label: 'Source', value: log.unique_description, type: 'full-width' },
       { label: 'Notes', value: log.notes, type: 'full-width', format: 'notes' },
     );
   }
   
+  // File manifest exported alongside the container
+  if (info.manifest) {
+    const hashes = [info.manifest.has_md5 && 'MD5', info.manifest.has_sha1 && 'SHA1'].filter(Boolean).join(', ');
+    fields.push({ label: 'Manifest', value: `${info.manifest.manifest_path.split(/[\\/]/).pop()} (${hashes})`, type: 'full-width' });
+  }
+  
   // Add all stored hashes (unified display for all container types)
   if (storedHashes && storedHashes.length > 0) {
     for (const sh of storedHashes) {
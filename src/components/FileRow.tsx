@@ -24,11 +24,18 @@ interface FileRowProps {
 
 export function FileRow(props: FileRowProps) {
   // Check if container is incomplete (missing segments)
-  const isIncomplete = () => (props.fileInfo?.ad1?.missing_segments?.length ?? 0) > 0;
+  const isIncomplete = () => (props.fileInfo?.segments?.missing.length || props.fileInfo?.ad1?.missing_segments?.length || 0) > 0
+    || props.fileInfo?.archive?.set_report?.complete === false;
+  const missingCount = () => props.fileInfo?.segments?.missing.length
+    || props.fileInfo?.ad1?.missing_segments?.length
+    || props.fileInfo?.archive?.set_report?.missing.length
+    || 0;
   
   // Get total container size (all segments combined) when available
   const totalContainerSize = () => {
     const info = props.fileInfo;
+    // Combined size of the segment files, from ContainerInfo.segments
+    if (info?.segments && info.segments.count > 1) return info.segments.total_size;
     if (info?.ad1?.total_size) return info.ad1.total_size;
     if (info?.e01?.total_size) return info.e01.total_size;
     if (info?.l01?.total_size) return info.l01.total_size;
@@ -39,11 +46,12 @@ export function FileRow(props: FileRowProps) {
   
   // Display size: use total container size if available, otherwise first segment size
   const displaySize = () => totalContainerSize() ?? props.file.size;
-  const hasMultipleSegments = () => (props.file.segment_count ?? 1) > 1;
+  const segmentCount = () => props.fileInfo?.segments?.count ?? props.file.segment_count ?? 1;
+  const hasMultipleSegments = () => segmentCount() > 1;
   const sizeLabel = () => {
     const total = totalContainerSize();
     if (total && hasMultipleSegments()) {
-      return `Total: ${formatBytes(total)} (${props.file.segment_count} segments, first segment: ${formatBytes(props.file.size)})`;
+      return `Total: ${formatBytes(total)} (${segmentCount()} segments, first segment: ${formatBytes(props.file.size)})`;
     }
     return `${formatBytes(displaySize())}`;
   };
@@ -138,6 +146,13 @@ export function FileRow(props: FileRowProps) {
           <Show when={props.file.segment_count && props.file.segment_count > 1}>
             <span class="seg-count">• {props.file.segment_count} segs</span>
           </Show>
+          {/* Header-only quick check result */}
+          <Show when={props.fileStatus?.status === "structure-ok"}>
+            <span class="seg-count" title="Quick check: headers and section chains are intact (not a hash)">• structure ok</span>
+          </Show>
+          <Show when={props.fileStatus?.status === "structure-damaged"}>
+            <span class="seg-count text-warning" title={props.fileStatus?.error}>• ⚠ structure damaged</span>
+          </Show>
         </span>
       </div>
       
@@ -226,7 +241,7 @@ export function FileRow(props: FileRowProps) {
         <Show when={!isHashing() && !isCompleting() && hashState() === "incomplete"}>
           <span 
             class="hash-indicator incomplete" 
-            title={`⚠️ Incomplete: Missing ${props.fileInfo?.ad1?.missing_segments?.length ?? 0} segment(s)\nCannot hash - segments are missing`}
+            title={`⚠️ Incomplete: Missing ${missingCount()} segment(s)\nCannot hash - segments are missing`}
           >
             <span class="hash-status">⚠</span>
             <span class="hash-icon-wrap">
@@ -1,7 +1,7 @@
 import { For, Show, createSignal, createMemo } from "solid-js";
 import type { ParsedMetadata, MetadataField } from "./HexViewer";
 import type { ContainerInfo } from "../types";
-import { formatBytes, formatOffsetLabel } from "../utils";
+import { formatBytes, formatOffsetLabel, formatTimestamp } from "../utils";
 
 // File info passed from parent
 interface FileInfo {
@@ -292,7 +292,7 @@ export function MetadataPanel(props: MetadataPanelProps) {
                     title="Click to view compressed header data (zlib stream)"
                   >
                     <span class="meta-key">ACQUIRED</span>
-                    <span class="meta-value">{ewf().acquiry_date}</span>
+                    <span class="meta-value">{formatTimestamp(ewf().acquiry_date)}</span>
                     <span class="meta-offset">{formatOffsetLabel(headerDataStart)} 📦</span>
                   </div>
                 </Show>
@@ -303,7 +303,7 @@ export function MetadataPanel(props: MetadataPanelProps) {
                     title="Click to view compressed header data (zlib stream)"
                   >
                     <span class="meta-key">SYSTEM DATE</span>
-                    <span class="meta-value">{ewf().system_date}</span>
+                    <span class="meta-value">{formatTimestamp(ewf().system_date)}</span>
                     <span class="meta-offset">{formatOffsetLabel(headerDataStart)} 📦</span>
                   </div>
                 </Show>
@@ -16,6 +16,7 @@ interface ToolbarProps {
   onScan: () => void;
   onHashSelected: () => void;
   onLoadAll: () => void;
+  onQuickCheckSelected?: () => void;
   // Project management
   projectPath?: string | null;
   projectModified?: boolean;
@@ -135,6 +136,17 @@ export function Toolbar(props: ToolbarProps) {
         ℹ️<Show when={!compact()}> Load All</Show>
       </button>
       
+      <Show when={props.onQuickCheckSelected}>
+        <button 
+          class="btn btn-default" 
+          onClick={() => props.onQuickCheckSelected?.()} 
+          disabled={props.busy || props.selectedCount === 0} 
+          title={`Check headers and section chains of ${props.selectedCount} selected files (seconds, no hashing)`}
+        >
+          🩺<Show when={!compact()}> Quick Check</Show>
+        </button>
+      </Show>
+      
       <div class="w-px h-6 bg-border mx-1" />
       
       {/* Project Management */}
@@ -14,6 +14,7 @@ import { invoke } from "@tauri-apps/api/core";
 import { save } from "@tauri-apps/plugin-dialog";
 import DOMPurify from "dompurify";
 import type { DiscoveredFile, ContainerInfo } from "../../types";
+import { formatTimestamp } from "../../utils";
 import { 
   isAiAvailable, 
   getAiProviders, 
@@ -67,7 +68,7 @@ export interface ExaminerInfo {
 }
 
 // Hash algorithm enum matching Rust
-export type HashAlgorithmType = "MD5" | "SHA1" | "SHA256" | "SHA512" | "Blake2b" | "Blake3" | "XXH3" | "XXH64";
+export type HashAlgorithmType = "MD5" | "SHA1" | "SHA256" | "SHA512" | "SHA3_256" | "Blake2b" | "Blake3" | "XXH3" | "XXH128" | "XXH64";
 
 export interface HashValue {
   item: string;
@@ -269,6 +270,8 @@ export function ReportWizard(props: ReportWizardProps) {
   const [selectedFormat, setSelectedFormat] = createSignal<string>("Pdf");
   const [exporting, setExporting] = createSignal(false);
   const [exportError, setExportError] = createSignal<string | null>(null);
+  const [embedSourceData, setEmbedSourceData] = createSignal(false);
+  const canEmbedSourceData = () => ["Html", "Docx", "Markdown"].includes(selectedFormat());
   
   // New certification input
   const [newCert, setNewCert] = createSignal("");
@@ -276,8 +279,8 @@ export function ReportWizard(props: ReportWizardProps) {
   // AI Assistant state
   const [aiAvailable, setAiAvailable] = createSignal(false);
   const [aiProviders, setAiProviders] = createSignal<AiProviderInfo[]>([]);
-  const [selectedProvider, setSelectedProvider] = createSignal<string>("ollama");
-  const [selectedModel, setSelectedModel] = createSignal<string>("llama3.2");
+  const [selectedProvider, setSelectedProvider] = createSignal<string>("offline");
+  const [selectedModel, setSelectedModel] = createSignal<string>("template");
   const [apiKey, setApiKey] = createSignal<string>("");
   const [ollamaConnected, setOllamaConnected] = createSignal(false);
   const [aiGenerating, setAiGenerating] = createSignal<string | null>(null); // Which section is generating
@@ -386,9 +389,11 @@ export function ReportWizard(props: ReportWizardProps) {
           "sha1": "SHA1", 
           "sha256": "SHA256",
           "sha512": "SHA512",
+          "sha3-256": "SHA3_256",
           "blake2b": "Blake2b",
           "blake3": "Blake3",
           "xxh3": "XXH3",
+          "xxh128": "XXH128",
           "xxh64": "XXH64",
         };
         const algo = algoMap[hashInfo.algorithm.toLowerCase()] || "SHA256";
@@ -410,7 +415,7 @@ export function ReportWizard(props: ReportWizardProps) {
       const totalSize = ewfInfo?.total_size ?? ad1Info?.total_size ?? file.size;
       
       // Get acquisition date from various sources
-      const acquisitionDate = ewfInfo?.acquiry_date ?? ad1Info?.companion_log?.acquisition_date ?? ufedInfo?.extraction_info?.start_time;
+      const acquisitionDate = formatTimestamp(ewfInfo?.acquiry_date ?? ad1Info?.companion_log?.acquisition_date ?? ufedInfo?.extraction_info?.start_time);
       
       // Get serial number from various sources
       const serialNumber = ewfInfo?.serial_number ?? ufedInfo?.device_info?.serial_number;
@@ -511,6 +516,7 @@ export function ReportWizard(props: ReportWizardProps) {
         report,
         format: selectedFormat(),
         outputPath: path,
+        options: { embedSourceData: embedSourceData() && canEmbedSourceData() },
       });
       
       props.onGenerated?.(outputPath, selectedFormat());
@@ -679,7 +685,8 @@ export function ReportWizard(props: ReportWizardProps) {
         type,
         selectedProvider(),
         selectedModel(),
-        selectedProvider() === "openai" ? apiKey() : undefined
+        selectedProvider() === "openai" ? apiKey() : undefined,
+        selectedProvider() === "offline" ? buildReport() : undefined
       );
       setter(result);
     } catch (e) {
@@ -991,7 +998,7 @@ export function ReportWizard(props: ReportWizardProps) {
                       const ewfInfo = i.e01 || i.l01;
                       const ad1Info = i.ad1;
                       const totalSize = ewfInfo?.total_size ?? ad1Info?.total_size;
-                      const acqDate = ewfInfo?.acquiry_date ?? ad1Info?.companion_log?.acquisition_date;
+                      const acqDate = formatTimestamp(ewfInfo?.acquiry_date ?? ad1Info?.companion_log?.acquisition_date);
                       return { totalSize, acqDate };
                     };
                     
@@ -1335,6 +1342,18 @@ export function ReportWizard(props: ReportWizardProps) {
                 </For>
               </div>
               
+              <Show when={canEmbedSourceData()}>
+                <label class="flex items-center gap-2 text-sm">
+                  <input
+                    type="checkbox"
+                    class="accent-accent"
+                    checked={embedSourceData()}
+                    onChange={(e) => setEmbedSourceData(e.currentTarget.checked)}
+                  />
+                  Append the report data as JSON
+                </label>
+              </Show>
+              
               <Show when={exportError()}>
                 <div class="p-3 bg-error/10 border border-error/30 rounded text-error text-sm">
                   Export failed: {exportError()}
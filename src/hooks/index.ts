@@ -10,5 +10,7 @@ export * from "./useDatabase";
 
 export { useProject } from "./useProject";
 
+export { useFormatCapabilities, capabilitiesFor, loadSupportedFormats } from "./useFormatCapabilities";
+
 export { useProcessedDatabases } from "./useProcessedDatabases";
 export type { ProcessedDatabasesManager } from "./useProcessedDatabases";
@@ -2,7 +2,7 @@ import { createSignal, createMemo } from "solid-js";
 import { invoke } from "@tauri-apps/api/core";
 import { open } from "@tauri-apps/plugin-dialog";
 import { listen } from "@tauri-apps/api/event";
-import type { DiscoveredFile, TreeEntry, ContainerInfo } from "../types";
+import type { DiscoveredFile, TreeEntry, TreeSummary, ContainerInfo, FileVerificationStatus, ScanSummary, QuickCheckReport, BatchInfoItem, BatchInfoSummary } from "../types";
 import { normalizeError, formatBytes } from "../utils";
 
 // System stats interface
@@ -40,6 +40,7 @@ export function useFileManager() {
   // File info and status maps
   const [fileInfoMap, setFileInfoMap] = createSignal<Map<string, ContainerInfo>>(new Map());
   const [fileStatusMap, setFileStatusMap] = createSignal<Map<string, FileStatus>>(new Map());
+  const [quickCheckMap, setQuickCheckMap] = createSignal<Map<string, QuickCheckReport>>(new Map());
   
   // Tree state for AD1 files
   const [tree, setTree] = createSignal<TreeEntry[]>([]);
@@ -235,6 +236,36 @@ export function useFileManager() {
     }
   };
 
+  // Attach background verification outcomes to the scanned files
+  const applyVerificationStatuses = (statuses: FileVerificationStatus[]) => {
+    if (statuses.length === 0) return;
+    const byPath = new Map(statuses.map(s => [s.path, s]));
+    setDiscoveredFiles(prev => prev.map(f => byPath.has(f.path) ? { ...f, verification: byPath.get(f.path) } : f));
+  };
+  
+  const setupVerificationStatusListener = async () => {
+    return listen<FileVerificationStatus>("file-verification-status", (e) => {
+      applyVerificationStatuses([e.payload]);
+    });
+  };
+  
+  // Statuses stored by earlier background verifications (still current files only)
+  const loadVerificationStatuses = async () => {
+    try {
+      const paths = discoveredFiles().map(f => f.path);
+      applyVerificationStatuses(await invoke<FileVerificationStatus[]>("get_verification_status", { paths }));
+    } catch (err) {
+      console.warn("Failed to load verification statuses:", normalizeError(err));
+    }
+  };
+  
+  // Queue low-priority verification of the scanned files (runs behind user jobs)
+  const startBackgroundVerify = async (algorithm = "md5") => {
+    const files = discoveredFiles().map(f => ({ path: f.path, container_type: f.container_type }));
+    if (files.length === 0) return [];
+    return invoke<string[]>("background_verify", { files, algorithm });
+  };
+
   // Scan for files
   const scanForFiles = async (dir?: string) => {
     const targetDir = dir || scanDir();
@@ -248,6 +279,7 @@ export function useFileManager() {
     setSelectedFiles(new Set<string>());
     setFileInfoMap(new Map());
     setFileStatusMap(new Map());
+    setQuickCheckMap(new Map());
     setActiveFile(null);
     setTree([]);
     setWorking("Scanning for evidence files...");
@@ -259,8 +291,11 @@ export function useFileManager() {
     });
     
     try {
-      const count = await invoke<number>("scan_directory_streaming", { dirPath: targetDir, recursive: recursiveScan() });
-      setOk(`Found ${count} evidence file(s) • ${formatBytes(discoveredFiles().reduce((s, f) => s + f.size, 0))}`);
+      const summary = await invoke<ScanSummary>("scan_directory_streaming", { dirPath: targetDir, recursive: recursiveScan() });
+      const skipped = summary.loopsSkipped + summary.depthSkipped;
+      setOk(`Found ${summary.count} evidence file(s) • ${formatBytes(discoveredFiles().reduce((s, f) => s + f.size, 0))}`
+        + (skipped > 0 ? ` • ${skipped} folder(s) skipped (link loops or depth limit)` : ""));
+      loadVerificationStatuses();
       // Auto-load only stored hashes (fast info) after scan
       loadStoredHashesInBackground();
     } catch (err) {
@@ -272,46 +307,88 @@ export function useFileManager() {
   
   // Load only stored hashes in background (fast - no heavy parsing)
   const loadStoredHashesInBackground = async () => {
-    const files = discoveredFiles();
-    if (files.length === 0) return;
+    const paths = discoveredFiles().map(f => f.path).filter(path => !fileInfoMap().has(path));
+    const total = discoveredFiles().length;
+    if (total === 0) return;
     
-    let loaded = 0;
-    const total = files.length;
+    let loaded = total - paths.length;
+    let cancelRequested = false;
+    setLoadProgress({ show: true, title: "Loading Stored Hashes", message: "Reading container headers...", current: loaded, total, cancelled: false });
     
-    setLoadProgress({ show: true, title: "Loading Stored Hashes", message: "Reading container headers...", current: 0, total, cancelled: false });
+    // One backend batch - files are read in parallel and reported as they finish
+    const unlisten = await listen<BatchInfoItem>("info-ready", (e) => {
+      const item = e.payload;
+      if (item.info) {
+        setFileInfoMap(prev => {
+          const m = new Map(prev);
+          m.set(item.path, item.info!);
+          return m;
+        });
+      } else {
+        console.warn(`Failed to load info for ${item.path}:`, item.error);
+      }
+      loaded++;
+      setLoadProgress(prev => ({ ...prev, current: loaded, message: item.path.split(/[\\/]/).pop() ?? item.path }));
+      if (loadProgress().cancelled && !cancelRequested) {
+        cancelRequested = true;
+        invoke("cancel_job", { jobId: item.jobId }).catch(() => {});
+      }
+    });
     
-    for (const file of files) {
-      // Check for cancellation
-      if (loadProgress().cancelled) {
-        setLoadProgress(prev => ({ ...prev, show: false }));
+    try {
+      const summary = paths.length > 0
+        ? await invoke<BatchInfoSummary>("logical_info_batch", { paths })
+        : null;
+      setLoadProgress(prev => ({ ...prev, show: false }));
+      if (summary && summary.skipped > 0) {
         setOk(`Cancelled • Loaded ${loaded}/${total} files`);
-        return;
+      } else {
+        const timedOut = summary?.timedOut ? ` • ${summary.timedOut} file(s) timed out` : "";
+        setOk(`Found ${total} file(s) • Stored hashes loaded${timedOut}`);
       }
-      
-      if (fileInfoMap().has(file.path)) {
-        loaded++;
-        setLoadProgress(prev => ({ ...prev, current: loaded, message: `${file.filename}` }));
-        continue;
-      }
-      
+    } catch (err) {
+      setLoadProgress(prev => ({ ...prev, show: false }));
+      setError(normalizeError(err));
+    } finally {
+      unlisten();
+    }
+  };
+  
+  // Header-only structure check of the selected files (seconds per file, no hashing)
+  const quickCheckSelected = async () => {
+    const files = discoveredFiles().filter(f => selectedFiles().has(f.path));
+    if (files.length === 0) return;
+    
+    let checked = 0;
+    let damaged = 0;
+    const total = files.length;
+    setLoadProgress({ show: true, title: "Quick Check", message: "Checking container structure...", current: 0, total, cancelled: false });
+    
+    for (const file of files) {
+      if (loadProgress().cancelled) break;
+      setLoadProgress(prev => ({ ...prev, current: checked, message: `${file.filename}` }));
       try {
-        const result = await invoke<ContainerInfo>("logical_info_fast", { inputPath: file.path });
-        setFileInfoMap(prev => {
+        const report = await invoke<QuickCheckReport>("quick_check", { inputPath: file.path });
+        setQuickCheckMap(prev => {
           const m = new Map(prev);
-          m.set(file.path, result);
+          m.set(file.path, report);
           return m;
         });
-        loaded++;
-        setLoadProgress(prev => ({ ...prev, current: loaded, message: `${file.filename}` }));
+        const failed = report.checks.filter(c => !c.passed).map(c => `${c.name}: ${c.detail}`);
+        if (failed.length > 0) damaged++;
+        updateFileStatus(file.path, failed.length > 0 ? "structure-damaged" : "structure-ok", 100, failed.join("\n") || undefined);
       } catch (err) {
-        console.warn(`Failed to load info for ${file.filename}:`, err);
-        loaded++;
-        setLoadProgress(prev => ({ ...prev, current: loaded }));
+        console.warn(`Quick check failed for ${file.filename}:`, normalizeError(err));
       }
+      checked++;
     }
     
     setLoadProgress(prev => ({ ...prev, show: false }));
-    setOk(`Found ${total} file(s) • Stored hashes loaded`);
+    if (damaged > 0) {
+      setError(`Quick check: ${damaged} of ${checked} file(s) structurally damaged`);
+    } else {
+      setOk(`Quick check: ${checked} file(s) structurally intact`);
+    }
   };
   
   // Cancel loading
@@ -323,7 +400,9 @@ export function useFileManager() {
   const loadFileInfo = async (file: DiscoveredFile, includeTree = false) => {
     updateFileStatus(file.path, "loading", 0);
     try {
-      const result = await invoke<ContainerInfo>("logical_info", { inputPath: file.path, includeTree });
+      // A damaged container still shows what could be read, with parse warnings
+      const result = await invoke<ContainerInfo>("logical_info", { inputPath: file.path, includeTree })
+        .catch(() => invoke<ContainerInfo>("logical_info", { inputPath: file.path, includeTree, bestEffort: true }));
       const m = new Map(fileInfoMap());
       m.set(file.path, result);
       setFileInfoMap(m);
@@ -331,6 +410,7 @@ export function useFileManager() {
       if (includeTree && result.ad1?.tree) {
         setTree(result.ad1.tree);
         setActiveFile(file);
+        if (result.ad1.tree_truncated) loadTreeInBackground(file);
       }
       return result;
     } catch (err) {
@@ -339,6 +419,35 @@ export function useFileManager() {
     }
   };
 
+  // Stream the full AD1 tree in batches (logical_info caps the tree it returns)
+  const streamTree = async (file: DiscoveredFile) => {
+    const entries: TreeEntry[] = [];
+    let lastRender = 0;
+    const unlisten = await listen<{ path: string; entries: TreeEntry[] }>("tree-entries", (e) => {
+      if (e.payload.path !== file.path) return;
+      for (const entry of e.payload.entries) entries.push(entry);
+      // Re-render a few times per second while batches arrive, not per batch
+      const now = Date.now();
+      if (now - lastRender > 500 && activeFile()?.path === file.path) {
+        lastRender = now;
+        setTree(entries.slice());
+      }
+    });
+    try {
+      const summary = await invoke<TreeSummary>("logical_info_tree_stream", { inputPath: file.path });
+      if (activeFile()?.path === file.path) setTree(entries);
+      return summary;
+    } finally {
+      unlisten();
+    }
+  };
+
+  const loadTreeInBackground = (file: DiscoveredFile) => {
+    streamTree(file).catch((err) => {
+      console.warn(`Failed to stream tree for ${file.filename}:`, normalizeError(err));
+    });
+  };
+
   // Load all file info (full details with progress modal)
   const loadAllInfo = async () => {
     const files = discoveredFiles();
@@ -382,6 +491,13 @@ export function useFileManager() {
   const selectAndViewFile = async (file: DiscoveredFile) => {
     setActiveFile(file);
     if (!fileInfoMap().has(file.path)) {
+      // Headers first (two small reads) so the detail pane fills in at once
+      try {
+        const fast = await invoke<ContainerInfo>("logical_info_fast", { inputPath: file.path });
+        setFileInfoMap(prev => prev.has(file.path) ? prev : new Map(prev).set(file.path, fast));
+      } catch {
+        // The full parse below reports the error
+      }
       try {
         await loadFileInfo(file, true);
       } catch (err) {
@@ -391,6 +507,7 @@ export function useFileManager() {
     } else {
       const info = fileInfoMap().get(file.path);
       if (info?.ad1?.tree) setTree(info.ad1.tree);
+      if (info?.ad1?.tree_truncated) loadTreeInBackground(file);
     }
   };
 
@@ -410,6 +527,7 @@ export function useFileManager() {
     fileInfoMap,
     setFileInfoMap,
     fileStatusMap,
+    quickCheckMap,
     tree,
     treeFilter,
     setTreeFilter,
@@ -444,8 +562,11 @@ export function useFileManager() {
     scanForFiles,
     loadFileInfo,
     loadAllInfo,
+    quickCheckSelected,
     selectAndViewFile,
     setupSystemStatsListener,
+    setupVerificationStatusListener,
+    startBackgroundVerify,
     cancelLoading,
   };
 }
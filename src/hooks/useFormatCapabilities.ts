@@ -0,0 +1,33 @@
+import { createSignal } from "solid-js";
+import { invoke } from "@tauri-apps/api/core";
+import type { FormatCapabilities, SupportedFormat } from "../types";
+import { formatId } from "../utils";
+
+// Capability matrix from the backend, loaded once and shared by all components
+const [supportedFormats, setSupportedFormats] = createSignal<SupportedFormat[]>([]);
+let loading: Promise<void> | undefined;
+
+/** Load `get_supported_formats` (once); later calls reuse the first request */
+export function loadSupportedFormats(): Promise<void> {
+  loading ??= invoke<SupportedFormat[]>("get_supported_formats")
+    .then(setSupportedFormats)
+    .catch((err) => {
+      console.warn("Failed to load supported formats:", err);
+      loading = undefined;
+    });
+  return loading;
+}
+
+/**
+ * Capabilities of a scanned container type, or undefined while the matrix
+ * is loading or the type is not a container the backend opens
+ */
+export function capabilitiesFor(containerType: string): FormatCapabilities | undefined {
+  const id = formatId(containerType);
+  return supportedFormats().find(f => f.id === id)?.capabilities;
+}
+
+export function useFormatCapabilities() {
+  void loadSupportedFormats();
+  return { supportedFormats, capabilitiesFor };
+}
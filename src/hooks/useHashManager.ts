@@ -1,14 +1,18 @@
 import { createSignal } from "solid-js";
 import { invoke } from "@tauri-apps/api/core";
 import { listen } from "@tauri-apps/api/event";
-import type { DiscoveredFile, ContainerInfo, SegmentHashResult, HashHistoryEntry, HashAlgorithm, StoredHash } from "../types";
-import { normalizeError } from "../utils";
+import type { BatchHashResult, DiscoveredFile, ContainerInfo, SegmentHashResult, SegmentVerifyProgress, HashHistoryEntry, HashAlgorithm, StoredHash, ChunkStats, EwfVerifyReport } from "../types";
+import { normalizeError, timestampValue } from "../utils";
 import type { FileManager } from "./useFileManager";
 
 export interface FileHashInfo {
   algorithm: string;
   hash: string;
   verified?: boolean | null;
+  /** What was hashed when the two differ (Android sparse images) */
+  scope?: "container" | "logical";
+  /** Chunk storage statistics gathered while verifying an E01 */
+  chunkStats?: ChunkStats | null;
 }
 
 export function useHashManager(fileManager: FileManager) {
@@ -34,6 +38,31 @@ export function useHashManager(fileManager: FileManager) {
   
   // Hash history state (per file)
   const [hashHistory, setHashHistory] = createSignal<Map<string, HashHistoryEntry[]>>(new Map());
+  
+  // Ambiguous companion logs the user confirmed for comparison (by log path)
+  const [confirmedCompanionLogs, setConfirmedCompanionLogs] = createSignal<Set<string>>(new Set());
+
+  // Companion log to compare hashes against - an ambiguous log may belong to
+  // another image in the folder, so it is only used once confirmed
+  const comparableCompanionLog = (info: ContainerInfo | undefined) => {
+    const log = info?.companion_log;
+    if (!log) return undefined;
+    if (log.confidence === "ambiguous" && !confirmedCompanionLogs().has(log.log_path)) return undefined;
+    return log;
+  };
+
+  // Confirm an ambiguous companion log and compare hashes already computed
+  const confirmCompanionLog = (logPath: string) => {
+    setConfirmedCompanionLogs(new Set([...confirmedCompanionLogs(), logPath]));
+    const m = new Map(fileHashMap());
+    for (const [path, entry] of m) {
+      const log = fileInfoMap().get(path)?.companion_log;
+      if (log?.log_path !== logPath || entry.verified != null) continue;
+      const stored = log.stored_hashes.find(sh => sh.algorithm.toLowerCase() === entry.algorithm.toLowerCase());
+      if (stored) m.set(path, { ...entry, verified: stored.hash.toLowerCase() === entry.hash.toLowerCase() });
+    }
+    setFileHashMap(m);
+  };
 
   // Add hash to history when computed
   const recordHashToHistory = (file: DiscoveredFile, algorithm: string, hash: string, verified?: boolean, verifiedAgainst?: string) => {
@@ -52,25 +81,38 @@ export function useHashManager(fileManager: FileManager) {
     setHashHistory(history);
   };
 
+  // File actually hashed for an entry: a grouped UFED extraction is hashed
+  // through its ZIP, which is what the UFD's stored hashes cover
+  const hashTarget = (file: DiscoveredFile) => {
+    if (file.container_type !== "UFED Extraction") return file.path;
+    const zip = file.segment_files?.find(name => name.toLowerCase().endsWith(".zip") && !name.startsWith(".."));
+    return zip ? file.path.replace(/[^/\\]+$/, () => zip) : file.path;
+  };
+
   // Hash a single file
   const hashSingleFile = async (file: DiscoveredFile) => {
     const algorithm = selectedHashAlgorithm();
+    const targetPath = hashTarget(file);
     updateFileStatus(file.path, "hashing", 0);
     // Listen for progress events, filtering by the file path to avoid mixing progress from multiple concurrent hashes
     const unlisten = await listen<{ path: string; percent: number }>("verify-progress", (e) => {
-      if (e.payload.path === file.path) {
+      if (e.payload.path === targetPath) {
         updateFileStatus(file.path, "hashing", e.payload.percent);
       }
     });
     try {
       let hash: string;
+      let scope: FileHashInfo["scope"];
+      let chunkStats: ChunkStats | null | undefined;
       const ctype = file.container_type.toLowerCase();
       if (ctype.includes("e01") || ctype.includes("encase") || ctype.includes("ex01")) {
-        hash = await invoke<string>("e01_v3_verify", { inputPath: file.path, algorithm });
+        const report = await invoke<EwfVerifyReport>("e01_v3_verify", { inputPath: file.path, algorithm, collectStats: true });
+        hash = report.hash;
+        chunkStats = report.chunk_stats;
       } else if (ctype.includes("ad1")) {
-        // AD1 containers - hash the segment files directly (not internal files)
+        // AD1 containers - hash the logical data stream, comparable to the FTK companion log
         try {
-          hash = await invoke<string>("ad1_hash_segments", { inputPath: file.path, algorithm });
+          hash = await invoke<string>("ad1_hash_image_stream", { inputPath: file.path, algorithm });
         } catch (ad1Err) {
           // AD1 may fail if segments are missing - report gracefully
           const errMsg = normalizeError(ad1Err);
@@ -80,10 +122,15 @@ export function useHashManager(fileManager: FileManager) {
           return;
         }
       } else if (ctype.includes("raw") || ctype.includes("dd")) {
-        hash = await invoke<string>("raw_verify", { inputPath: file.path, algorithm });
+        // Sparse images hash their expanded partition and gzip images their
+        // decompressed bytes, comparable to a dd image
+        const raw = fileInfoMap().get(file.path)?.raw;
+        const logical = !!raw?.sparse;
+        if (logical || raw?.gzip) scope = "logical";
+        hash = await invoke<string>("raw_verify", { inputPath: file.path, algorithm, logical });
       } else if (ctype.includes("ufed") || ctype.includes("zip") || ctype.includes("archive") || ctype.includes("tar") || ctype.includes("7z")) {
         // UFED and archive containers - hash the file directly
-        hash = await invoke<string>("raw_verify", { inputPath: file.path, algorithm });
+        hash = await invoke<string>("raw_verify", { inputPath: targetPath, algorithm });
       } else {
         // Unknown container type - try raw verification
         try {
@@ -99,9 +146,9 @@ export function useHashManager(fileManager: FileManager) {
       
       // Check if there's a stored hash to compare against
       const info = fileInfoMap().get(file.path);
-      const storedHashes = [...(info?.e01?.stored_hashes ?? []), ...(info?.companion_log?.stored_hashes ?? [])];
+      const storedHashes = [...(info?.e01?.stored_hashes ?? []), ...(comparableCompanionLog(info)?.stored_hashes ?? [])];
       // Also check UFED stored hashes (from .ufd file) - match by algorithm and filename
-      const fileName = file.path.split('/').pop() ?? '';
+      const fileName = targetPath.split(/[/\\]/).pop() ?? '';
       const ufedStoredHashes = info?.ufed?.stored_hashes ?? [];
       const matchingUfedStored = ufedStoredHashes.find(sh => 
         sh.algorithm.toLowerCase() === algorithm.toLowerCase() && 
@@ -124,7 +171,7 @@ export function useHashManager(fileManager: FileManager) {
       const verifiedAgainst = matchingStored?.hash ?? matchingHistory?.hash;
       
       const m = new Map(fileHashMap());
-      m.set(file.path, { algorithm: algorithm.toUpperCase(), hash, verified });
+      m.set(file.path, { algorithm: algorithm.toUpperCase(), hash, verified, scope, chunkStats });
       setFileHashMap(m);
       updateFileStatus(file.path, "hashed", 100);
       
@@ -191,7 +238,7 @@ export function useHashManager(fileManager: FileManager) {
           // Immediately update hash map and verify when a file completes
           const file = files.find(f => f.path === path);
           const info = fileInfoMap().get(path);
-          const storedHashes = [...(info?.e01?.stored_hashes ?? []), ...(info?.companion_log?.stored_hashes ?? [])];
+          const storedHashes = [...(info?.e01?.stored_hashes ?? []), ...(comparableCompanionLog(info)?.stored_hashes ?? [])];
           // Also check UFED stored hashes (from .ufd file) - match by algorithm and filename
           const fileName = path.split('/').pop() ?? '';
           const ufedStoredHashes = info?.ufed?.stored_hashes ?? [];
@@ -221,7 +268,8 @@ export function useHashManager(fileManager: FileManager) {
           
           // Update hash map immediately
           const hashMap = new Map(fileHashMap());
-          hashMap.set(path, { algorithm, hash, verified });
+          const scope = info?.raw?.sparse ? "container" : info?.raw?.gzip ? "logical" : undefined;
+          hashMap.set(path, { algorithm, hash, verified, scope });
           setFileHashMap(hashMap);
           
           updateFileStatus(path, "hashed", 100);
@@ -245,7 +293,7 @@ export function useHashManager(fileManager: FileManager) {
     
     try {
       // Wait for batch_hash to complete (results already processed via events)
-      await invoke<{ path: string; algorithm: string; hash?: string; error?: string }[]>(
+      const results = await invoke<BatchHashResult[]>(
         "batch_hash",
         { files: files.map(f => ({ path: f.path, container_type: f.container_type })), algorithm: selectedHashAlgorithm() }
       );
@@ -275,6 +323,10 @@ export function useHashManager(fileManager: FileManager) {
         if (noStoredCount > 0) parts.push(`${noStoredCount} no stored hash`);
         statusMsg += ` • ${parts.join(", ")}`;
       }
+      const duplicateCount = results.filter(r => r.duplicates.length > 0).length;
+      if (duplicateCount > 0) {
+        statusMsg += ` • ${duplicateCount} duplicate copies`;
+      }
       
       if (failedCountFinal > 0) {
         setError(statusMsg);
@@ -305,23 +357,26 @@ export function useHashManager(fileManager: FileManager) {
     const info = fileInfoMap().get(file.path);
     const isE01 = file.container_type.toLowerCase().includes("e01") || file.container_type.toLowerCase().includes("encase");
     
-    const expectedHashes = info?.companion_log?.segment_hashes ?? [];
+    const expectedHashes = comparableCompanionLog(info)?.segment_hashes ?? [];
     const algorithm = expectedHashes.length > 0 ? expectedHashes[0].algorithm.toLowerCase() : selectedHashAlgorithm();
     
     setWorking(`Verifying segments with ${algorithm.toUpperCase()}...`);
     updateFileStatus(file.path, "verifying-segments", 0);
     setSegmentVerifyProgress({ segment: "", percent: 0, completed: 0, total: 0 });
     
-    const unlisten = await listen<{ segment_name: string; segment_number: number; percent: number; segments_completed: number; segments_total: number }>(
+    const unlisten = await listen<SegmentVerifyProgress>(
       "segment-verify-progress",
       (e) => {
+        // One consolidated event per sample: show the segments still hashing
+        const active = e.payload.segments.filter(s => !s.completed).map(s => s.segment_name);
+        const segment = active.length > 0 ? active.join(", ") : (e.payload.segments[e.payload.segments.length - 1]?.segment_name ?? "");
         setSegmentVerifyProgress({
-          segment: e.payload.segment_name,
+          segment,
           percent: e.payload.percent,
           completed: e.payload.segments_completed,
           total: e.payload.segments_total
         });
-        setWorking(`Verifying segment ${e.payload.segment_name} (${e.payload.segments_completed}/${e.payload.segments_total})...`);
+        setWorking(`Verifying segment ${segment} (${e.payload.segments_completed}/${e.payload.segments_total})...`);
       }
     );
     
@@ -394,7 +449,7 @@ export function useHashManager(fileManager: FileManager) {
     
     // E01 container hashes
     if (plainInfo.e01?.stored_hashes) {
-      const containerDate = plainInfo.e01.acquiry_date;
+      const containerDate = timestampValue(plainInfo.e01.acquiry_date);
       for (const h of plainInfo.e01.stored_hashes) {
         allHashes.push({
           algorithm: h.algorithm || '',
@@ -408,7 +463,7 @@ export function useHashManager(fileManager: FileManager) {
     
     // UFED container hashes (include filename since UFED has per-file hashes)
     if (plainInfo.ufed?.stored_hashes) {
-      const containerDate = plainInfo.ufed.extraction_info?.start_time;
+      const containerDate = timestampValue(plainInfo.ufed.extraction_info?.start_time);
       for (const h of plainInfo.ufed.stored_hashes) {
         allHashes.push({
           algorithm: h.algorithm || '',
@@ -423,7 +478,7 @@ export function useHashManager(fileManager: FileManager) {
     
     // Companion log hashes
     if (plainInfo.companion_log?.stored_hashes) {
-      const logDate = plainInfo.companion_log.verification_finished || plainInfo.companion_log.acquisition_finished;
+      const logDate = timestampValue(plainInfo.companion_log.verification_finished || plainInfo.companion_log.acquisition_finished);
       for (const h of plainInfo.companion_log.stored_hashes) {
         allHashes.push({
           algorithm: h.algorithm || '',
@@ -504,12 +559,14 @@ export function useHashManager(fileManager: FileManager) {
     segmentResults,
     segmentVerifyProgress,
     hashHistory,
+    confirmedCompanionLogs,
     
     // Actions
     hashSingleFile,
     hashSelectedFiles,
     hashAllFiles,
     verifySegments,
+    confirmCompanionLog,
     
     // Helpers
     getAllStoredHashesSorted,
@@ -6,6 +6,7 @@
 
 import { invoke } from "@tauri-apps/api/core";
 import type { ContainerInfo, DiscoveredFile } from "../types";
+import { timestampValue } from "../utils";
 
 // =============================================================================
 // Input Types (sent to backend)
@@ -125,7 +126,7 @@ export function containerToInput(
     examiner_name: ewfInfo?.examiner_name ?? ad1Info?.companion_log?.examiner ?? ufedInfo?.case_info?.examiner_name ?? undefined,
     description: ewfInfo?.description ?? undefined,
     notes: ewfInfo?.notes ?? ad1Info?.companion_log?.notes ?? undefined,
-    acquiry_date: ewfInfo?.acquiry_date ?? ad1Info?.companion_log?.acquisition_date ?? ufedInfo?.extraction_info?.start_time ?? undefined,
+    acquiry_date: timestampValue(ewfInfo?.acquiry_date ?? ad1Info?.companion_log?.acquisition_date ?? ufedInfo?.extraction_info?.start_time),
     model: ewfInfo?.model ?? ufedInfo?.device_info?.model ?? undefined,
     serial_number: ewfInfo?.serial_number ?? ufedInfo?.device_info?.serial_number ?? undefined,
     total_size: ewfInfo?.total_size ?? ad1Info?.total_size ?? ufedInfo?.size ?? undefined,
@@ -254,7 +255,8 @@ export async function generateAiNarrative(
   narrativeType: NarrativeType,
   provider: string,
   model: string,
-  apiKey?: string
+  apiKey?: string,
+  report?: unknown
 ): Promise<string> {
   return invoke<string>("generate_ai_narrative", {
     context,
@@ -262,6 +264,7 @@ export async function generateAiNarrative(
     provider,
     model,
     apiKey: apiKey ?? null,
+    report: report ?? null,
   });
 }
 
@@ -16,6 +16,7 @@ import type {
   ContainerInfo,
   StoredHash,
 } from "../types";
+import { formatTimestamp, timestampValue } from "../utils";
 
 const APP_VERSION = "1.0.0"; // TODO: Import from package.json or config
 const APP_NAME = "FFX - Forensic File Explorer";
@@ -167,8 +168,8 @@ function generateEvidenceItem(
     containerType: file.container_type,
     size: file.size,
     segmentCount: file.segment_count,
-    created: file.created,
-    modified: file.modified,
+    created: formatTimestamp(file.created),
+    modified: formatTimestamp(file.modified),
     metadata: generateMetadata(file, info),
   };
 
@@ -312,7 +313,7 @@ function extractExtractionInfo(info?: ContainerInfo): ExtractionInfo | undefined
   // E01
   if (info.e01) {
     return {
-      startTime: info.e01.acquiry_date,
+      startTime: formatTimestamp(info.e01.acquiry_date),
     };
   }
 
@@ -324,8 +325,8 @@ function extractExtractionInfo(info?: ContainerInfo): ExtractionInfo | undefined
       toolVersion: e.tool_version ?? undefined,
       extractionType: e.extraction_type ?? undefined,
       connectionType: e.connection_type ?? undefined,
-      startTime: e.start_time ?? undefined,
-      endTime: e.end_time ?? undefined,
+      startTime: formatTimestamp(e.start_time),
+      endTime: formatTimestamp(e.end_time),
       machineName: e.machine_name ?? undefined,
       guid: e.guid ?? undefined,
       unitId: e.unit_id ?? undefined,
@@ -336,8 +337,8 @@ function extractExtractionInfo(info?: ContainerInfo): ExtractionInfo | undefined
   if (info.companion_log) {
     return {
       tool: info.companion_log.created_by,
-      startTime: info.companion_log.acquisition_started,
-      endTime: info.companion_log.acquisition_finished,
+      startTime: formatTimestamp(info.companion_log.acquisition_started),
+      endTime: formatTimestamp(info.companion_log.acquisition_finished),
     };
   }
 
@@ -427,11 +428,11 @@ function findStoredHash(
   // UFED stored hashes
   if (info.ufed?.stored_hashes) {
     const match = info.ufed.stored_hashes.find(h => h.algorithm.toLowerCase() === algo);
-    if (match) return { ...match, source: "container", timestamp: info.ufed.extraction_info?.start_time ?? null };
+    if (match) return { ...match, source: "container", timestamp: timestampValue(info.ufed.extraction_info?.start_time) ?? null };
   }
 
-  // Companion log stored hashes
-  if (info.companion_log?.stored_hashes) {
+  // Companion log stored hashes (an ambiguous log may belong to another image)
+  if (info.companion_log?.stored_hashes && info.companion_log.confidence !== "ambiguous") {
     const match = info.companion_log.stored_hashes.find(h => h.algorithm.toLowerCase() === algo);
     if (match) return match;
   }
@@ -458,7 +459,7 @@ function getAllStoredHashes(info: ContainerInfo | undefined): StoredHash[] {
       algorithm: h.algorithm,
       hash: h.hash,
       source: "container" as const,
-      timestamp: info.ufed!.extraction_info?.start_time ?? null,
+      timestamp: timestampValue(info.ufed!.extraction_info?.start_time) ?? null,
       verified: null,
     })));
   }
@@ -24,19 +24,81 @@ export type LogicalHeader = {
   data_source_name: string;
 };
 
+/** What an AD1 item holds; streams and attachments are children of a file */
+export type ItemKind = "file" | "folder" | "alternate_stream" | "attachment";
+
 export type TreeEntry = {
+  /** Container path; an alternate stream reads `file:stream` */
   path: string;
   is_dir: boolean;
   size: number;
   item_type: number;
+  item_kind: ItemKind;
+  /** Item offset in the logical image (unique per container) */
+  address: number;
+  /** Address of the parent folder item, 0 for root items */
+  parent_address: number;
+};
+
+/** Totals sent with the `tree-summary` event after a streamed tree */
+export type TreeSummary = {
+  item_count: number;
+  file_count: number;
+  folder_count: number;
+  total_size: number;
 };
 
+/** Verification entry in the version 1 shape (logical_verify without `version`) */
 export type VerifyEntry = {
   path: string;
   status: string;
   message?: string;
 };
 
+/** What a version 2 verification entry is about */
+export type VerifyTarget =
+  | { kind: "path"; path: string; chunk?: number | null }
+  | { kind: "chunk"; index: number }
+  | { kind: "segment"; name: string }
+  | { kind: "whole_image"; label?: string | null };
+
+export type VerifyStatus = "ok" | "mismatch" | "corrupt" | "skipped" | "error";
+
+export type VerifySeverity = "info" | "warning" | "failure";
+
+export type VerifyReason =
+  | "no_expected_hash"
+  | "missing_data"
+  | "partial_image"
+  | "missing_from_container"
+  | "missing_from_manifest"
+  /** Chunk holds sectors the acquisition could not read (zero-filled) */
+  | "acquisition_read_error";
+
+/** Verification entry of every format, returned when `version: 2` is passed */
+export type VerifyEntryV2 = {
+  target: VerifyTarget;
+  status: VerifyStatus;
+  severity: VerifySeverity;
+  reason?: VerifyReason;
+  /** Algorithm of `expected` and `computed`, e.g. "MD5" */
+  algorithm?: string;
+  expected?: string;
+  computed?: string;
+  size?: number;
+  message?: string;
+  duration_secs?: number;
+};
+
+/** A metadata date normalized by the backend, with the source text kept */
+export type Timestamp = {
+  /** "YYYY-MM-DDTHH:MM:SSZ"; empty when `original` wasn't recognized */
+  iso_utc: string;
+  original: string;
+  /** False when the source gave no zone and its wall-clock time was taken as UTC */
+  timezone_known: boolean;
+};
+
 // --- File Discovery Types ---
 
 export type DiscoveredFile = {
@@ -45,8 +107,109 @@ export type DiscoveredFile = {
   container_type: string;
   size: number;
   segment_count?: number;
-  created?: string;
-  modified?: string;
+  /** Member files, for segment sets and grouped UFED extraction folders */
+  segment_files?: string[];
+  segment_sizes?: number[];
+  total_segment_size?: number;
+  created?: Timestamp;
+  modified?: Timestamp;
+  /** Last background verification (get_verification_status / file-verification-status) */
+  verification?: FileVerificationStatus;
+};
+
+/** Role of a file within an evidence bundle */
+export type BundleRole = "primary" | "segment" | "log" | "manifest" | "derived_export";
+
+export type BundleMember = {
+  path: string;
+  filename: string;
+  role: BundleRole;
+  size: number;
+};
+
+/** A container with its segments, companion files and derived exports
+ * (scans with groupBundles; bundle-updated events) */
+export type EvidenceBundle = {
+  /** Stays the same while members arrive during a streaming scan */
+  id: string;
+  /** Basename shared by the members ("image" for image.E01) */
+  stem: string;
+  primary: DiscoveredFile;
+  /** Every file of the bundle, the primary first */
+  members: BundleMember[];
+};
+
+/** Outcome of a background verification against the hash found in the container or its log */
+export type FileVerificationStatus = {
+  path: string;
+  algorithm: string;
+  status: "verified" | "mismatch" | "no-expected" | "error";
+  computed_hash?: string | null;
+  expected_hash?: string | null;
+  /** "embedded" (container metadata) or "companion" (acquisition log) */
+  expected_source?: string | null;
+  error?: string | null;
+  file_size: number;
+  file_modified?: number | null;
+  verified_at: string;
+};
+
+/** Options for scan_directory_streaming */
+export type ScanOptions = {
+  /** Directory workers (default 4, at most 8) */
+  workers?: number;
+  /** Stop walking after this many milliseconds */
+  timeoutMs?: number;
+  /** Levels below the folder to descend into (unlimited when unset) */
+  maxDepth?: number;
+  /** Descend into symlinked folders and junctions (loops are still cut) */
+  followSymlinks?: boolean;
+};
+
+/** Result of scan_directory_streaming */
+export type ScanSummary = {
+  /** Evidence files reported */
+  count: number;
+  /** Folders listed */
+  directories: number;
+  timedOut: boolean;
+  /** Folders skipped because the scan had already been there (link loops) */
+  loopsSkipped: number;
+  /** Folders skipped for lying deeper than maxDepth */
+  depthSkipped: number;
+};
+
+/** Options of logical_info_batch */
+export type BatchInfoOptions = {
+  /** Seconds one file may take (default 30) */
+  timeoutSecs?: number;
+  /** Files read at once (default 8) */
+  workers?: number;
+  /** Files read at once from one storage device (default 4) */
+  workersPerDevice?: number;
+  refresh?: boolean;
+};
+
+/** `info-ready` event of logical_info_batch, one per file */
+export type BatchInfoItem = {
+  jobId: string;
+  path: string;
+  info?: ContainerInfo | null;
+  error?: string | null;
+  /** No answer within the timeout (hung network share) */
+  timedOut: boolean;
+  elapsedMs: number;
+};
+
+export type BatchInfoSummary = {
+  total: number;
+  succeeded: number;
+  /** Includes the files that timed out */
+  failed: number;
+  timedOut: number;
+  /** Not started because the batch was cancelled */
+  skipped: number;
+  elapsedMs: number;
 };
 
 // --- Container Info Types ---
@@ -66,7 +229,7 @@ export type Ad1CompanionLogInfo = {
   notes?: string | null;
   md5_hash?: string | null;
   sha1_hash?: string | null;
-  acquisition_date?: string | null;
+  acquisition_date?: Timestamp | null;
 };
 
 export type Ad1Info = {
@@ -74,15 +237,53 @@ export type Ad1Info = {
   logical: LogicalHeader;
   item_count: number;
   tree?: TreeEntry[];
+  /** `tree` stops at the entry limit - stream it with logical_info_tree_stream */
+  tree_truncated?: boolean;
   segment_files?: string[];
   /** Size of each segment file in bytes */
   segment_sizes?: number[];
   /** Total size of all segment files combined */
   total_size?: number;
+  /** Sum of the uncompressed file sizes (full info of an undamaged container only) */
+  logical_size?: number | null;
   /** Missing segment files (incomplete container) */
   missing_segments?: string[];
+  /** Header of each segment file present, in segment order (fast info: the first only) */
+  segments?: SegmentHeader[];
+  /** Missing segments (fast info only - full info refuses such sets) */
+  segment_issues?: Ad1SegmentIssue[];
   volume?: Ad1VolumeInfo | null;
+  /** Imaging tool named in the logical header area, when present */
+  creator_tool?: string;
   companion_log?: Ad1CompanionLogInfo | null;
+  /** Damage skipped by a best-effort parse - the tree is partial */
+  parse_warnings?: ParseWarning[];
+};
+
+/** Why an AD1 segment set does not add up */
+export type Ad1SegmentIssue =
+  | { kind: "missing"; index: number; total: number; file: string }
+  | { kind: "unreadable"; index: number; file: string; error: string }
+  | { kind: "index_mismatch"; file: string; expected: number; found: number }
+  | { kind: "count_mismatch"; file: string; expected: number; found: number };
+
+/** A structure a best-effort parse could not read, by file offset */
+export type ParseWarning = {
+  address: number;
+  error: string;
+};
+
+/** Optical-media session from an E01 "session" section */
+export type SessionInfo = {
+  first_sector: number;
+  /** Up to the next session, so gaps count towards the session before */
+  sector_count: number;
+};
+
+/** Run of sectors of the acquired media */
+export type SectorRange = {
+  first_sector: number;
+  sector_count: number;
 };
 
 /** EWF container info (E01/L01/Ex01/Lx01 formats) */
@@ -100,16 +301,62 @@ export type EwfInfo = {
   examiner_name?: string;
   evidence_number?: string;
   notes?: string;
-  acquiry_date?: string;
-  system_date?: string;
+  acquiry_date?: Timestamp;
+  system_date?: Timestamp;
   model?: string;
   serial_number?: string;
+  /** "removable", "fixed", "optical", "logical" or "memory" */
+  media_type?: string | null;
+  /** Optical-media sessions (absent for disks) */
+  sessions?: SessionInfo[];
+  /** Sectors the source drive could not deliver, zero-filled (error2 section) */
+  acquisition_errors?: SectorRange[];
+  /** File and folder counts of logical evidence (Lx01) */
+  logical_entries?: LogicalEntries;
   stored_hashes?: StoredHash[];
   // Section offsets for hex navigation
   header_section_offset?: number;
   volume_section_offset?: number;
   hash_section_offset?: number;
   digest_section_offset?: number;
+  /** Header segment numbers / set identifiers checked against filename order */
+  segment_consistency?: SegmentConsistency;
+  /** Damage skipped by a best-effort parse - later sections are missing */
+  parse_warnings?: ParseWarning[];
+};
+
+/** Identity one E01 segment file records in its headers */
+export type SegmentHeader = {
+  filename: string;
+  segment_number?: number | null;
+  set_identifier?: string | null;
+};
+
+/** Renamed/shuffled, foreign or mixed-naming segment files (verification refuses these unless forced) */
+export type SegmentConsistency = {
+  segments: SegmentHeader[];
+  order_matches: boolean;
+  same_set: boolean;
+  /** No same-stem file in another naming scheme (.Ex01 next to .E01) or past a gap */
+  single_naming: boolean;
+  issues: string[];
+};
+
+/** One structural property checked by `quick_check` */
+export type StructureCheck = {
+  name: string;
+  passed: boolean;
+  detail: string;
+};
+
+/** Header-only integrity check of a container (seconds, not a hash) */
+export type QuickCheckReport = {
+  path: string;
+  /** Format id as in get_supported_formats, e.g. "e01" */
+  format: string;
+  passed: boolean;
+  checks: StructureCheck[];
+  elapsedMs: number;
 };
 
 /** @deprecated Use EwfInfo instead - L01 uses the same EWF format */
@@ -125,6 +372,249 @@ export type RawInfo = {
   segment_names: string[];
   first_segment: string;
   last_segment: string;
+  /** Expanded size for Android sparse images, otherwise total_size */
+  logical_size: number;
+  /** Set when the image is an Android sparse image */
+  sparse?: SparseInfo | null;
+  /** Set when the image is gzip-compressed */
+  gzip?: GzipInfo | null;
+};
+
+/** Gzip-compressed raw image (.dd.gz) */
+export type GzipInfo = {
+  compressed_size: number;
+  /** Uncompressed size of the last member modulo 4 GiB */
+  isize: number;
+  original_name?: string | null;
+  /** Exact size and member count, only set after a full scan */
+  uncompressed_size?: number | null;
+  members?: number | null;
+};
+
+/** Operations a container format supports (from `get_supported_formats`) */
+export type FormatCapabilities = {
+  can_info: boolean;
+  can_info_tree: boolean;
+  can_verify_whole: boolean;
+  can_verify_items: boolean;
+  can_verify_segments: boolean;
+  can_extract: boolean;
+  can_extract_item: boolean;
+  /** Known limitations, shown next to disabled actions */
+  notes: string[];
+};
+
+export type SupportedFormat = {
+  /** Stable identifier, e.g. "e01" */
+  id: string;
+  label: string;
+  capabilities: FormatCapabilities;
+};
+
+/** Android sparse image (simg) layout */
+export type SparseInfo = {
+  version: string;
+  block_size: number;
+  total_blocks: number;
+  chunk_count: number;
+  raw_chunks: number;
+  fill_chunks: number;
+  dont_care_chunks: number;
+  crc32_chunks: number;
+  logical_size: number;
+};
+
+/** ISO 9660 / UDF optical disc image */
+export type IsoInfo = {
+  /** Joliet label if present, else the ISO 9660 or UDF label */
+  volume_label?: string | null;
+  system_id?: string | null;
+  volume_set_id?: string | null;
+  publisher?: string | null;
+  data_preparer?: string | null;
+  application?: string | null;
+  /** UTC, "YYYY-MM-DD HH:MM:SS" */
+  creation_date?: string | null;
+  modification_date?: string | null;
+  block_size: number;
+  total_sectors: number;
+  image_size: number;
+  has_iso9660: boolean;
+  has_joliet: boolean;
+  has_rock_ridge: boolean;
+  has_udf: boolean;
+  /** "NSR02" (UDF 1.0x) or "NSR03" (UDF 2.x) */
+  udf_revision?: string | null;
+  bootable: boolean;
+};
+
+export type IsoEntry = {
+  /** '/'-separated path from the disc root */
+  path: string;
+  is_dir: boolean;
+  size: number;
+  lba: number;
+  /** More than 1 for files stored in several extents */
+  extents: number;
+  recorded?: string | null;
+};
+
+/** Result of iso_list_entries */
+export type IsoListing = {
+  entries: IsoEntry[];
+  truncated: boolean;
+  /** "Joliet", "Rock Ridge" or "ISO 9660" */
+  name_source: string;
+};
+
+/** Options for raw_consolidate */
+export type ConsolidateOptions = {
+  /** Hash algorithm for source and destination (default sha256) */
+  algorithm?: string;
+  /** Replace existing output files instead of failing */
+  overwrite?: boolean;
+  /** Split the output into parts of this many bytes */
+  splitSize?: number;
+};
+
+export type ConsolidateResult = {
+  output_files: string[];
+  segment_count: number;
+  bytes_written: number;
+  algorithm: string;
+  source_hash: string;
+  destination_hash: string;
+  verified: boolean;
+};
+
+/** A path component changed to make an output path safe */
+export type PathChange = {
+  original: string;
+  sanitized: string;
+  reason: string;
+};
+
+/** Result of logical_extract; with dry_run nothing was written */
+export type ExtractResult = {
+  output_dir: string;
+  dry_run: boolean;
+  warnings: string[];
+  stats?: {
+    matched: number;
+    skipped: number;
+    extracted: number;
+    pruned_folders: number;
+    total_bytes: number;
+    /** Files a best-effort extraction (options.bestEffort) could not read */
+    unreadable: { path: string; status: "corrupt" | "missing"; chunk?: number | null; message: string }[];
+    /** Files whose content was already in the output (options.dedupe) */
+    deduplicated: number;
+    /** Bytes those files did not take up again */
+    deduplicated_bytes: number;
+  } | null;
+  /** Empty for a dry run */
+  manifest_path: string;
+  file_count: number;
+  total_bytes: number;
+  longest_output_path?: string | null;
+  sanitized_entries: { internal_path: string; output_path: string; changes: PathChange[] }[];
+  /** Output files that already exist (dry run only) */
+  collisions: string[];
+  /** Destination free space, when known (dry run only) */
+  free_bytes?: number | null;
+  insufficient_space: boolean;
+  /** Bytes the destination is short of, when insufficient_space */
+  shortfall_bytes?: number | null;
+  /** Partial files of an earlier, interrupted run removed before extracting */
+  cleaned_partials: string[];
+  /** Wall time of the extraction, zero for a dry run */
+  duration_secs: number;
+  /** Throughput over `total_bytes`, zero for a dry run */
+  bytes_per_sec: number;
+};
+
+/** Consecutive differing bytes of two compared images */
+export type DiffRange = {
+  offset: number;
+  length: number;
+};
+
+/** Result of compare_images */
+export type ImageComparison = {
+  path_a: string;
+  path_b: string;
+  size_a: number;
+  size_b: number;
+  /** Same size and the same bytes */
+  identical: boolean;
+  /** size_b - size_a; only the first compared_bytes are compared */
+  length_difference: number;
+  compared_bytes: number;
+  /** First differing byte, or the end of the shorter image when only the lengths differ */
+  first_difference?: number | null;
+  differing_bytes: number;
+  range_count: number;
+  /** The first options.maxRanges differing ranges */
+  ranges: DiffRange[];
+  ranges_truncated: boolean;
+  duration_secs: number;
+};
+
+/** Payload of the image-compare-progress event emitted by compare_images */
+export type CompareProgress = {
+  jobId: string;
+  bytesDone: number;
+  bytesTotal: number;
+  differingBytes: number;
+};
+
+/** Result of clean_partial_outputs */
+export type PartialCleanup = {
+  /** Removed .partial files and progress markers */
+  removed: string[];
+  bytesFreed: number;
+  /** Stale partial files that could not be removed */
+  failed: string[];
+};
+
+/** Volume holding a path, from get_volume_info (unknown fields are null) */
+export type StorageVolume = {
+  path: string;
+  /** Mount point, or volume root such as "D:\\" on Windows */
+  mount_point?: string | null;
+  filesystem?: string | null;
+  total_bytes?: number | null;
+  free_bytes?: number | null;
+  /** Free bytes this process may write */
+  available_bytes?: number | null;
+  removable?: boolean | null;
+  network?: boolean | null;
+};
+
+/** Payload of the extract-progress event emitted by logical_extract */
+export type ExtractProgress = {
+  jobId: string;
+  filesDone: number;
+  filesTotal: number;
+  bytesDone: number;
+  bytesTotal: number;
+};
+
+export type ZipEncryption = "none" | "zip_crypto" | "aes128" | "aes192" | "aes256" | "other";
+
+/** ZIP entries counted by encryption method; ZipCrypto is weak, AES is not */
+export type ZipEncryptionSummary = {
+  unencrypted: number;
+  zip_crypto: number;
+  aes128: number;
+  aes192: number;
+  aes256: number;
+  /** PKWARE strong encryption or unknown AES strength */
+  other: number;
+  /** The first encrypted entries (at most 50) */
+  encrypted_entries: { name: string; encryption: ZipEncryption }[];
+  /** false if the central directory could not be read to its end */
+  complete: boolean;
 };
 
 export type ArchiveInfo = {
@@ -137,9 +627,14 @@ export type ArchiveInfo = {
   last_segment: string;
   is_multipart: boolean;
   entry_count?: number | null;
+  /** Sum of the entries' uncompressed sizes (ZIP with a complete central directory only) */
+  uncompressed_size?: number | null;
   encrypted_headers: boolean;
   aes_encrypted: boolean;
+  /** Entry names listable without the password (always for ZIP; null for other formats) */
+  filenames_visible?: boolean | null;
   // ZIP-specific
+  zip_encryption?: ZipEncryptionSummary | null;
   central_dir_offset?: number | null;
   central_dir_size?: number | null;
   // 7z-specific
@@ -151,10 +646,27 @@ export type ArchiveInfo = {
   start_header_crc_valid?: boolean | null;
   /** Next Header CRC value */
   next_header_crc?: number | null;
+  /** Whether the Next Header matches its CRC (null if not checked) */
+  next_header_crc_valid?: boolean | null;
+  /** Next Header lies beyond the end of the data - incomplete copy or download */
+  truncated?: boolean;
   /** Whether Cellebrite UFED files were detected inside the archive */
   cellebrite_detected?: boolean;
   /** List of Cellebrite files found (UFD, UFDR, UFDX) */
   cellebrite_files?: string[];
+  /** Consistency of the multi-part set (null if it could not be checked) */
+  set_report?: ArchiveSetReport | null;
+};
+
+/** Consistency check of a multi-part archive set (part file names) */
+export type ArchiveSetReport = {
+  complete: boolean;
+  /** Expected parts not on disk */
+  missing: string[];
+  /** Parts on disk that do not belong at their position in the set */
+  unexpected: string[];
+  /** Reason for each missing or unexpected part */
+  issues: string[];
 };
 
 // --- UFED (Cellebrite) Types ---
@@ -192,8 +704,8 @@ export type UfedExtractionInfo = {
   unit_id?: string | null;
   extraction_type?: string | null;
   connection_type?: string | null;
-  start_time?: string | null;
-  end_time?: string | null;
+  start_time?: Timestamp | null;
+  end_time?: Timestamp | null;
   guid?: string | null;
   machine_name?: string | null;
 };
@@ -226,6 +738,30 @@ export type UfedInfo = {
   stored_hashes?: UfedStoredHash[] | null;
   evidence_number?: string | null;
   collection_info?: UfedCollectionInfo | null;
+  /** Every UFD section (section -> key -> value), including unmodelled ones */
+  raw_sections?: Record<string, Record<string, string>>;
+};
+
+/** One ZIP entry checked by ufed_verify_contents */
+export type UfedZipEntryHashResult = {
+  path: string;
+  size: number;
+  algorithm: string;
+  expected: string;
+  computed: string;
+  verified: boolean;
+};
+
+/** Result of ufed_verify_contents (hashes files inside a UFED ZIP in place) */
+export type UfedZipVerifyReport = {
+  zip_path: string;
+  /** UFD or report.xml the stored hashes came from */
+  hash_source: string;
+  results: UfedZipEntryHashResult[];
+  verified: number;
+  mismatched: number;
+  zip_entries_without_hash: number;
+  stored_hashes_without_entry: number;
 };
 
 // --- Hash Types ---
@@ -255,6 +791,29 @@ export type SegmentHash = {
   verified?: boolean | null;
 };
 
+/** Layout of an expected-hash list: "hash [filename]" lines, CSV, or filename<TAB>hash */
+export type HashListFormat = "lines" | "csv" | "tsv";
+
+/** Explicit CSV columns (0-based) for import_expected_hashes when the header isn't recognized */
+export type CsvColumns = {
+  filename?: number | null;
+  hash: number;
+  /** Guessed from the hash length when unset */
+  algorithm?: string | null;
+  size?: number | null;
+  hasHeader?: boolean;
+};
+
+/** Result of import_expected_hashes */
+export type ExpectedHashList = {
+  format: HashListFormat;
+  segment_hashes: SegmentHash[];
+  /** Hashes without a file name */
+  hashes: StoredHash[];
+  /** 1-based line numbers without a usable hash */
+  skipped_lines: number[];
+};
+
 export type SegmentHashResult = {
   segment_name: string;
   segment_number: number;
@@ -265,6 +824,504 @@ export type SegmentHashResult = {
   verified?: boolean | null;
   size: number;
   duration_secs: number;
+  /** Expected hash no present segment matched (nothing was hashed) */
+  missing?: boolean;
+};
+
+/** Image hash from e01_full_verify, with the hash it was compared to */
+export type ImageHashCheck = {
+  algorithm: string;
+  computed_hash: string;
+  expected_hash?: string | null;
+  /** "embedded" (stored in the image) or "companion" (acquisition log) */
+  expected_source?: string | null;
+  verified?: boolean | null;
+};
+
+/** Logical and per-segment hashes of an E01 from one read of its segments */
+export type E01FullVerifyReport = {
+  path: string;
+  image_hashes: ImageHashCheck[];
+  /** One entry per segment file and algorithm, in segment order */
+  segments: SegmentHashResult[];
+  chunk_count: number;
+  duration_secs: number;
+};
+
+/** Consolidated segment-verify-progress event (sampled, not per buffer) */
+export type SegmentVerifyProgress = {
+  jobId: string;
+  segments: { segment_name: string; segment_number: number; percent: number; completed: boolean }[];
+  segments_completed: number;
+  segments_total: number;
+  bytes_done: number;
+  bytes_total: number;
+  percent: number;
+};
+
+/** A chunk that could not be read, mapped to the disk range it covers */
+export type ChunkReadError = {
+  chunk_index: number;
+  byte_start: number;
+  byte_end: number;
+  sector_start: number;
+  sector_end: number;
+  segment_path?: string | null;
+  segment_offset?: number | null;
+  compressed: boolean;
+  kind: "location" | "short_read" | "decompression" | "corrupt_structure" | "io" | "device_disconnected";
+  message: string;
+};
+
+/** How an E01 image's chunks are stored (verification with collectStats) */
+export type ChunkStats = {
+  compressed_chunks: number;
+  uncompressed_chunks: number;
+  /** Chunks with no stored data, read as zeros */
+  sparse_chunks: number;
+  stored_bytes: number;
+  logical_bytes: number;
+  /** Stored / logical size of compressed chunks */
+  min_ratio?: number | null;
+  max_ratio?: number | null;
+  mean_ratio?: number | null;
+  /** Compressed chunks with ratio ≤0.1, ≤0.25, ≤0.5, ≤0.75 and above */
+  ratio_histogram: [number, number, number, number, number];
+};
+
+/** Zero fraction and entropy of one window of a content map */
+export type MapWindow = {
+  zero_fraction: number;
+  /** Bits per byte, 0-8 */
+  entropy: number;
+};
+
+/** Per-window zero/entropy map collected during verification (collectMap) */
+export type ContentMap = {
+  window_size: number;
+  total_bytes: number;
+  /** In image order; only the last may be shorter than window_size */
+  windows: MapWindow[];
+};
+
+/** Result of summarize_content_map */
+export type MapSummary = {
+  total_bytes: number;
+  percent_zero: number;
+  percent_high_entropy: number;
+  /** Windows holding nothing but zeros */
+  zero_windows: number;
+  window_count: number;
+};
+
+/** Result of raw_verify_report */
+export type RawVerifyResult = {
+  algorithm: string;
+  hash: string;
+  total_size: number;
+  duration_secs: number;
+  throughput_mbs: number;
+  content_map?: ContentMap;
+};
+
+/** Result of e01_v3_verify and e01_v3_verify_best_effort (hash is partial when complete is false) */
+export type EwfVerifyReport = {
+  algorithm: string;
+  hash: string;
+  complete: boolean;
+  chunk_count: number;
+  failed_chunk_count: number;
+  failed_chunks: ChunkReadError[];
+  /** Segment order problems hashed through with force (hash won't match the acquisition) */
+  segment_issues?: string[];
+  /** Sectors zero-filled at acquisition; a mismatch against the source is expected there */
+  acquisition_errors?: SectorRange[];
+  unreadable_sector_count: number;
+  /** Chunks holding those sectors (first 1000) */
+  acquisition_error_chunks?: number[];
+  chunk_stats?: ChunkStats | null;
+  content_map?: ContentMap;
+};
+
+/** Chunks checked by e01_sample_verify: a share of all chunks or a count */
+export type SampleSize = { fraction: number } | { count: number };
+
+/** Result of e01_sample_verify - a spot check, NOT a hash verification */
+export type EwfSampleReport = {
+  /** States that full verification is required for evidentiary statements */
+  notice: string;
+  /** The same seed selects the same chunks again */
+  seed: number;
+  chunk_count: number;
+  chunks_checked: number;
+  /** chunks_checked / chunk_count */
+  coverage: number;
+  bytes_checked: number;
+  compressed_chunks: number;
+  /** Checked by size only: they carry no checksum */
+  uncompressed_chunks: number;
+  sparse_chunks: number;
+  /** Smallest share of damaged chunks the sample finds with 99% confidence */
+  detectable_damage_fraction: number;
+  failed_chunk_count: number;
+  failed_chunks: ChunkReadError[];
+};
+
+/** One AD1 file checked by ad1_verify_report */
+export type Ad1VerifyEntry = {
+  path: string;
+  /** "corrupt"/"missing": the file's data could not be read */
+  status: "ok" | "nok" | "computed" | "corrupt" | "missing";
+  algorithm?: string;
+  computed?: string;
+  stored?: string;
+  size?: number;
+  /** Chunk within the file that could not be read */
+  chunk?: number;
+  message?: string;
+};
+
+/** Result of ad1_verify_report */
+export type Ad1VerifyReport = {
+  /** Entry shape: 1 = Ad1VerifyEntry, 2 = VerifyEntryV2 */
+  version?: number;
+  algorithm: string;
+  entries: Ad1VerifyEntry[] | VerifyEntryV2[];
+  summary: { ok: number; nok: number; computed: number; corrupt: number; missing: number };
+};
+
+/** Saved state of an interrupted checkpointed verification (list_verify_checkpoints) */
+export type VerifyCheckpoint = {
+  jobId: string;
+  path: string;
+  algorithm: string;
+  inputs: { path: string; size: number; modified_ms?: number | null }[];
+  totalSize: number;
+  bytesHashed: number;
+  hasher?: unknown;
+  /** false = algorithm state can't be saved, resume restarts from byte 0 */
+  exactResume: boolean;
+  updatedAt: string;
+};
+
+// --- Job Registry Types ---
+
+export type JobKind = "verify" | "segment_verify" | "batch_hash" | "hash" | "extract" | "benchmark" | "copy" | "info" | "compare";
+
+/** "user" jobs run at once; "background" jobs wait until no user job runs */
+export type JobPriority = "user" | "background";
+
+/** "disconnected": the evidence device went away; retry_job resumes or restarts it */
+export type JobStatus = "queued" | "running" | "paused" | "cancelling" | "completed" | "failed" | "cancelled" | "disconnected";
+
+/** "low" = idle-class I/O (Linux) / background mode (Windows); ignored elsewhere */
+export type IoPriority = "normal" | "low";
+
+/** Per-job limits; verify/batch commands take these as optional threads/ioPriority/lowMemory args */
+export type JobResources = {
+  /** Worker thread cap; null = all cores */
+  threads?: number | null;
+  ioPriority: IoPriority;
+  /** 4MB buffers, no mmap, small caches (always on in 32-bit builds) */
+  lowMemory?: boolean;
+};
+
+/** A running or recently finished job (list_jobs / get_job) */
+export type JobInfo = {
+  jobId: string;
+  kind: JobKind;
+  priority: JobPriority;
+  inputs: string[];
+  /** Set for jobs that honour a thread cap / I/O priority */
+  resources?: JobResources | null;
+  status: JobStatus;
+  /** false = cancel_job is refused for this job */
+  cancellable: boolean;
+  /** false = pause_job is refused for this job; `job-status` reports pauses */
+  pausable: boolean;
+  /** `remaining` = total - current, still to be read */
+  progress: { current: number; total: number; percent: number; remaining: number };
+  startedAt: string;
+  finishedAt?: string | null;
+  resultSummary?: string | null;
+  error?: string | null;
+  /** Reads retried after transient network errors */
+  retries?: number;
+};
+
+/** Time a job spent in one phase (segment_discovery, parsing, decompression, hashing, writing) */
+export type PhaseTiming = {
+  phase: string;
+  /** Spans of the phase, e.g. one per batch */
+  spans: number;
+  elapsedMs: number;
+  bytes: number;
+  throughputMbs?: number | null;
+  /** From the job's start; parallel phases overlap */
+  firstStartMs: number;
+  lastEndMs: number;
+};
+
+/** Phase-by-phase timing of a job (get_job_perf) */
+export type JobPerf = {
+  jobId: string;
+  /** Set once the job has finished */
+  totalMs?: number | null;
+  phases: PhaseTiming[];
+};
+
+/** Retry of reads failing with a transient network error (get/set_io_retry_policy) */
+export type IoRetryPolicy = {
+  /** 0 disables retrying */
+  maxRetries: number;
+  initialBackoffMs: number;
+  maxBackoffMs: number;
+};
+
+/** App settings (get_settings; also the payload of the "settings-changed" event) */
+export type Settings = {
+  /** Schema version of the settings file */
+  version: number;
+  /** I/O buffer in bytes, 64KB-256MB */
+  bufferSize: number;
+  /** Files an info batch reads at once, 1-32 */
+  batchWorkers: number;
+  workersPerDevice: number;
+  defaultAlgorithm: string;
+  jobResources: JobResources;
+  ioRetry: IoRetryPolicy;
+  auditLog: boolean;
+  /** End exports with a keyed integrity tag (not an evidentiary hash) */
+  integrityTags: boolean;
+  /** Verification checkpoint folder; null = app data folder */
+  checkpointDir?: string | null;
+};
+
+/** Why settings could not be loaded, changed or saved */
+export type SettingsError =
+  | { kind: "invalid"; field: string; message: string }
+  | { kind: "unknown_field"; field: string }
+  | { kind: "malformed"; error: string }
+  | { kind: "io"; path: string; error: string };
+
+/** Result of get_settings_load_error: the settings file was not used and the app runs on defaults */
+export type SettingsLoadError = {
+  error: SettingsError;
+  /** Where the unreadable file was moved; null = it couldn't be, and update_settings is refused */
+  backupPath: string | null;
+};
+
+/** Result of verify_export_integrity */
+export type IntegrityCheck = {
+  path: string;
+  /** The file ends with an integrity tag line or field */
+  tagged: boolean;
+  /** The tag matches this installation's key */
+  valid: boolean;
+  error?: string | null;
+};
+
+/** Argument of update_settings: only the fields to change */
+export type SettingsUpdate = Partial<Omit<Settings, "version" | "jobResources" | "ioRetry">> & {
+  jobResources?: Partial<JobResources>;
+  ioRetry?: Partial<IoRetryPolicy>;
+};
+
+/** Payload of the "io-retry" warning event */
+export type IoRetryEvent = {
+  path: string;
+  /** 1 for the first retry */
+  attempt: number;
+  maxRetries: number;
+  delayMs: number;
+  error: string;
+};
+
+/** Result of raw_verify_resumable / resume_verify (hash is null when cancelled) */
+export type ResumableVerifyResult = {
+  jobId: string;
+  hash?: string | null;
+  bytesHashed: number;
+  totalSize: number;
+  resumedFrom: number;
+};
+
+// --- Audit Log Types ---
+
+/** Date range for audit_export (ISO 8601; omitted bounds are open) */
+export type AuditRange = {
+  from?: string | null;
+  to?: string | null;
+};
+
+/** Result of audit_verify_chain */
+export type AuditChainReport = {
+  path: string;
+  /** Entries checked before the first problem */
+  entries: number;
+  valid: boolean;
+  /** false for exports of a later range, or a log whose first lines were removed */
+  anchored: boolean;
+  /** 1-based line of the first problem */
+  broken_line?: number | null;
+  error?: string | null;
+  last_hash?: string | null;
+};
+
+// --- Evidence Copy Types ---
+
+/** Options for evidence_copy (all optional) */
+export type CopyOptions = {
+  /** Default "sha256" */
+  algorithm?: string;
+  /** Read back and hash each copied file (default true) */
+  verifyDestination?: boolean;
+  /** Replace destination files that differ from their source */
+  overwrite?: boolean;
+};
+
+export type CopyPhase = "check" | "copy" | "verify";
+
+/** Payload of the evidence-copy-progress event */
+export type CopyProgress = {
+  jobId: string;
+  file: string;
+  fileIndex: number;
+  fileCount: number;
+  phase: CopyPhase;
+  bytesDone: number;
+  bytesTotal: number;
+  bytesPerSecond: number;
+};
+
+export type CopyAction = "copied" | "resumed" | "already_present" | "overwritten";
+
+/** One file in a copy manifest */
+export type CopiedFile = {
+  source: string;
+  destination: string;
+  size: number;
+  sourceModified?: string | null;
+  sourceHash: string;
+  destinationHash?: string | null;
+  /** null when the destination was not read back */
+  verified?: boolean | null;
+  action: CopyAction;
+  resumedFrom: number;
+  startedAt: string;
+  finishedAt: string;
+};
+
+/** Result of evidence_copy - the manifest written next to the copy, plus its path */
+export type CopyResult = {
+  manifestPath: string;
+  tool: string;
+  toolVersion: string;
+  algorithm: string;
+  sourcePath: string;
+  destDir: string;
+  startedAt: string;
+  finishedAt: string;
+  files: CopiedFile[];
+  verified: boolean;
+};
+
+/** One known-answer check of the integrity self-test */
+export type SelfTestCheck = {
+  category: "hash" | "e01_decompression";
+  algorithm: string;
+  input: string;
+  expected: string;
+  computed: string;
+  passed: boolean;
+  error?: string | null;
+};
+
+/** Result of self_test - `passed` is false if any check failed */
+export type SelfTestReport = {
+  passed: boolean;
+  summary: string;
+  failed_checks: string[];
+  tool: string;
+  tool_version: string;
+  run_at: string;
+  duration_ms: number;
+  checks: SelfTestCheck[];
+};
+
+// --- Hash Benchmark Types ---
+
+/** What benchmark_hash reads: the start of a file (read-only) or a temp sample */
+export type BenchmarkTarget =
+  | { path: string; limitBytes?: number | null }
+  | { sizeBytes: number };
+
+/** Throughput of one algorithm with one buffer size */
+export type BenchmarkMeasurement = {
+  algorithm: string;
+  bufferSize: number;
+  bytes: number;
+  durationMs: number;
+  mbPerSec: number;
+};
+
+/** Result of benchmark_hash, saved per device for verify_auto */
+export type BenchmarkResult = {
+  /** Mount point / volume the sample was read from */
+  device: string;
+  sourcePath?: string | null;
+  sampleBytes: number;
+  /** true = combinations shared one cached region, figures don't reflect the device */
+  cachedReads: boolean;
+  measurements: BenchmarkMeasurement[];
+  /** Fastest buffer size per algorithm */
+  best: BenchmarkMeasurement[];
+  measuredAt: string;
+};
+
+// --- Batch Hash / Duplicate Types ---
+
+/** Per-file result of batch_hash */
+export type BatchHashResult = {
+  path: string;
+  algorithm: string;
+  hash?: string | null;
+  error?: string | null;
+  duration_ms?: number | null;
+  throughput_mbs?: number | null;
+  /** Other files in the batch with the same hash */
+  duplicates: string[];
+};
+
+/** Copies of one evidence set found by find_duplicates */
+export type DuplicateGroup = {
+  algorithm: string;
+  hash: string;
+  total_size: number;
+  copies: { path: string; companion_log?: string | null }[];
+};
+
+/** A file left out of a hashdeep export or audit */
+export type HashdeepSkippedFile = { path: string; reason: string };
+
+/** Result of export_hashdeep */
+export type HashdeepExport = {
+  outputPath: string;
+  /** Hash columns in hashdeep order (md5, sha1, sha256) */
+  columns: string[];
+  filesWritten: number;
+  skipped: HashdeepSkippedFile[];
+};
+
+/** Result of audit_hashdeep, in hashdeep's categories */
+export type HashdeepAudit = {
+  matched: string[];
+  moved: { path: string; knownPath: string }[];
+  changed: string[];
+  new: string[];
+  missing: string[];
+  unreadable: HashdeepSkippedFile[];
+  passed: boolean;
 };
 
 export type HashHistoryEntry = {
@@ -286,17 +1343,59 @@ export type CompanionLogInfo = {
   unique_description?: string;
   examiner?: string;
   notes?: string;
-  acquisition_started?: string;
-  acquisition_finished?: string;
-  verification_started?: string;
-  verification_finished?: string;
+  acquisition_started?: Timestamp;
+  acquisition_finished?: Timestamp;
+  verification_started?: Timestamp;
+  verification_finished?: Timestamp;
   stored_hashes: StoredHash[];
   segment_list: string[];
   segment_hashes: SegmentHash[];
+  /** How sure the backend is that this log belongs to the image */
+  confidence: CompanionLogConfidence;
+  /** Other logs in the folder that could also belong to the image */
+  other_candidates: string[];
+  /** The drive that was imaged, from the log's drive information */
+  source_device?: SourceDeviceInfo | null;
 };
 
+/** Acquisition source drive described by an imaging log */
+export type SourceDeviceInfo = {
+  model?: string | null;
+  serial?: string | null;
+  /** e.g. "IDE", "USB", "SATA 3.0" */
+  interface?: string | null;
+  capacity_bytes?: number | null;
+  sector_size?: number | null;
+  geometry?: { cylinders: number; heads: number; sectors_per_track: number } | null;
+};
+
+/** "ambiguous" logs are not compared against until the user confirms them */
+export type CompanionLogConfidence = "exact" | "probable" | "ambiguous";
+
 // --- Combined Container Info ---
 
+export type ContainerKind = "ad1" | "e01" | "l01" | "lx01" | "raw" | "iso" | "archive" | "ufed";
+
+/** One format check run by identify_container */
+export type DetectionCandidate = {
+  kind: ContainerKind;
+  /** "none": the check ruled the kind out; "low": name only */
+  confidence: "none" | "low" | "medium" | "high";
+  reason: string;
+};
+
+/** Result of identify_container - why a file is detected as what it is */
+export type DetectionReport = {
+  path: string;
+  file_size: number;
+  /** First matching candidate; null for empty or unrecognized files */
+  detected?: ContainerKind | null;
+  /** Every check, in detection order */
+  candidates: DetectionCandidate[];
+  signature_bytes_hex: string;
+  extension_hint?: string | null;
+};
+
 export type ContainerInfo = {
   container: string;
   ad1?: Ad1Info | null;
@@ -307,13 +1406,113 @@ export type ContainerInfo = {
   raw?: RawInfo | null;
   archive?: ArchiveInfo | null;
   ufed?: UfedInfo | null;
+  /** ISO 9660 / UDF optical disc image */
+  iso?: IsoInfo | null;
   note?: string | null;
   companion_log?: CompanionLogInfo | null;
+  /** All files of a segmented container and their combined size */
+  segments?: SegmentSetInfo | null;
+  /** File listing with per-file hashes exported next to the container (AD1/L01) */
+  manifest?: ManifestInfo | null;
+  /** Partition/OS triage (E01/raw), only with `includeTriage: true` */
+  triage?: TriageSummary | null;
+  /** Bytes on disk across all segments */
+  physical_size?: number | null;
+  /** Size of the acquired image or extracted files, when known */
+  logical_size?: number | null;
+  /** logical_size / physical_size */
+  compression_ratio?: number | null;
+};
+
+/** Partition table and filesystem identifiers of a disk image */
+export type TriageSummary = {
+  /** "MBR", "GPT", or "none" for an unpartitioned volume image */
+  partition_scheme: string;
+  disk_size: number;
+  partitions: PartitionTriage[];
+  /** "Windows", "Linux" or "macOS" */
+  guessed_platform?: string | null;
+  bytes_read: number;
+  /** Some partition is encrypted or possibly encrypted */
+  encryption_detected: boolean;
+};
+
+/** Full-disk encryption recognised at the start of a partition (never decrypted) */
+export type EncryptionFinding = {
+  /** "BitLocker", "LUKS1", "LUKS2", "FileVault", "Core Storage" or "unknown" */
+  scheme: string;
+  /** Shown instead of a filesystem, e.g. "BitLocker protected volume" */
+  description: string;
+  /** No encryption header found - the data only looks encrypted */
+  heuristic: boolean;
+};
+
+export type PartitionTriage = {
+  index: number;
+  offset: number;
+  size: number;
+  /** MBR type byte ("0x07") or GPT type GUID */
+  partition_type: string;
+  type_description?: string | null;
+  name?: string | null;
+  bootable: boolean;
+  filesystem?: string | null;
+  volume_label?: string | null;
+  volume_serial?: string | null;
+  volume_uuid?: string | null;
+  last_mounted?: string | null;
+  ntfs_version?: string | null;
+  /** Set when the partition is encrypted; `filesystem` then names the container */
+  encryption?: EncryptionFinding | null;
+  error?: string | null;
+};
+
+/** Hash of one partition (hash_partitions); `hash` is null for skipped entries */
+export type PartitionHashResult = {
+  index: number;
+  partition_type: string;
+  type_description?: string | null;
+  name?: string | null;
+  /** First sector, in the table's sector size */
+  start_lba: number;
+  offset: number;
+  length: number;
+  hash?: string | null;
+  duration_secs: number;
+  /** Why the entry was not hashed (overlapping or out-of-range entry) */
+  skipped?: string | null;
+};
+
+export type PartitionHashReport = {
+  algorithm: string;
+  partition_scheme: string;
+  sector_size: number;
+  disk_size: number;
+  partitions: PartitionHashResult[];
+  /** Whole-image hash, only with `includeDisk: true` */
+  disk_hash?: string | null;
+  duration_secs: number;
+};
+
+/** FTK Imager style CSV/TSV file listing; verify with `verify_against_manifest` */
+export type ManifestInfo = {
+  manifest_path: string;
+  has_md5: boolean;
+  has_sha1: boolean;
+};
+
+export type SegmentSetInfo = {
+  count: number;
+  /** Combined size of all present segments */
+  total_size: number;
+  files: { name: string; size: number }[];
+  /** Expected segment names that were not found (gaps or short set) */
+  missing: string[];
 };
 
 // --- Hash Algorithm Types ---
 
-export type HashAlgorithm = "md5" | "sha1" | "sha256" | "sha512" | "blake3" | "blake2" | "xxh3" | "xxh64" | "crc32";
+export type HashAlgorithm = "md5" | "sha1" | "sha256" | "sha512" | "sha3-256" | "blake3" | "blake2" | "xxh3" | "xxh128" | "xxh64" | "crc32";
 
 export type HashAlgorithmInfo = { 
   value: HashAlgorithm; 
@@ -329,8 +1528,10 @@ export const HASH_ALGORITHMS: HashAlgorithmInfo[] = [
   { value: "md5", label: "MD5", speed: "medium", forensic: true, cryptographic: false },
   { value: "blake3", label: "BLAKE3 ⚡", speed: "fast", forensic: false, cryptographic: true },
   { value: "sha512", label: "SHA-512", speed: "slow", forensic: true, cryptographic: true },
+  { value: "sha3-256", label: "SHA3-256", speed: "slow", forensic: true, cryptographic: true },
   { value: "blake2", label: "BLAKE2b", speed: "fast", forensic: false, cryptographic: true },
   { value: "xxh3", label: "XXH3 ⚡⚡", speed: "fast", forensic: false, cryptographic: false },
+  { value: "xxh128", label: "XXH128 ⚡⚡", speed: "fast", forensic: false, cryptographic: false },
   { value: "xxh64", label: "XXH64 ⚡⚡", speed: "fast", forensic: false, cryptographic: false },
   { value: "crc32", label: "CRC32", speed: "fast", forensic: false, cryptographic: false },
 ];
@@ -1,5 +1,7 @@
 // Shared utility functions for forensic container analysis
 
+import type { Timestamp } from "./types";
+
 /**
  * Format byte count to human-readable string (B, KB, MB, GB, TB)
  */
@@ -11,6 +13,26 @@ export function formatBytes(value: number): string {
   return `${scaled.toFixed(scaled < 10 ? 2 : 1)} ${units[i]}`;
 }
 
+/**
+ * ISO 8601 UTC form of a timestamp, or the source text when unrecognized
+ * (matches the backend's string form, e.g. stored hash timestamps)
+ */
+export function timestampValue(ts?: Timestamp | null): string | undefined {
+  if (!ts) return undefined;
+  return ts.iso_utc || ts.original;
+}
+
+/**
+ * Format a normalized timestamp as "YYYY-MM-DD HH:MM:SS" (plus " UTC" when
+ * the source stated its zone); unrecognized values are shown as written
+ */
+export function formatTimestamp(ts?: Timestamp | null): string | undefined {
+  if (!ts) return undefined;
+  if (!ts.iso_utc) return ts.original;
+  const text = ts.iso_utc.replace("T", " ").replace("Z", "");
+  return ts.timezone_known ? `${text} UTC` : text;
+}
+
 /**
  * Normalize various error types to a string message
  */
@@ -41,6 +63,23 @@ export function typeIcon(type: string): string {
   return "📄";
 }
 
+/**
+ * Backend format id (`get_supported_formats`) of a scanned container type
+ */
+export function formatId(type: string): string | undefined {
+  const t = type.toLowerCase();
+  if (t.includes("ufed") || t.includes("ufd")) return "ufed";
+  if (t.includes("ad1")) return "ad1";
+  if (t.includes("e01") || t.includes("encase") || t.includes("smart")) return "e01";
+  if (t.includes("lx01")) return "lx01";
+  if (t.includes("l01")) return "l01";
+  if (t.includes("iso")) return "iso";
+  if (t.includes("raw") || t.includes("dd")) return "raw";
+  if (t.includes("tar") || t.includes("7z") || t.includes("7-zip") || t.includes("zip") || t.includes("rar")
+    || t.includes("gz") || t.includes("xz") || t.includes("bz") || t.includes("zst") || t.includes("lz4")) return "archive";
+  return undefined;
+}
+
 /**
  * Get CSS class for container type styling
  */